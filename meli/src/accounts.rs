@@ -26,7 +26,6 @@ use std::{
     convert::TryFrom,
     future::Future,
     ops::{Index, IndexMut},
-    os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
     pin::Pin,
     sync::{Arc, Mutex},
@@ -41,27 +40,56 @@ use melib::{
     log,
     thread::Threads,
     utils::{fnmatch::Fnmatch, futures::sleep, random, shellexpand::ShellExpandTrait},
-    Contacts, SortField, SortOrder,
+    Contacts, SortChain,
 };
 
 #[cfg(feature = "sqlite3")]
 use crate::command::actions::AccountAction;
 use crate::{
     conf::{data_types::SearchBackend, AccountConf, FileMailboxConf},
-    jobs::{IsAsync, JobId, JoinHandle},
-    types::{ForkedProcess, NotificationType, UIEvent},
+    jobs::{IsAsync, JobId, JoinHandle, TimerId},
+    types::{CallbackFn, CommandPolicyExt, ForkedProcess, NotificationType, UIEvent},
+    version_migrations::artifacts::{from_unversioned, StateFile},
     MainLoopHandler, StatusEvent, ThreadEvent,
 };
 
+mod attachment_index;
+mod await_reply;
 mod backend_ops;
+mod deletions;
+mod flag_batch;
 mod jobs;
 mod mailbox;
 mod mailbox_ops;
+mod maintenance;
+mod previews;
+mod reminders;
+mod retention;
+mod scoring;
+mod search_results;
+mod sending;
+mod sieve;
+mod special_mailboxes;
+mod tag_mailboxes;
 #[cfg(test)]
 mod tests;
 
+pub use attachment_index::*;
+pub use await_reply::*;
+pub use backend_ops::*;
+pub use deletions::*;
+pub use flag_batch::*;
 pub use jobs::*;
 pub use mailbox::*;
+pub use maintenance::*;
+pub use previews::*;
+pub use reminders::*;
+pub use retention::*;
+pub use search_results::*;
+pub use sending::*;
+pub use sieve::*;
+pub use special_mailboxes::*;
+pub use tag_mailboxes::*;
 
 #[macro_export]
 macro_rules! try_recv_timeout {
@@ -135,59 +163,32 @@ pub struct Account {
     pub active_job_instants: BTreeMap<std::time::Instant, JobId>,
     pub event_queue: IndexMap<MailboxHash, VecDeque<RefreshEventKind>>,
     pub backend_capabilities: MailBackendCapabilities,
+    pub flag_batch: FlagBatch,
+    pub await_replies: AwaitReplies,
+    pub retention: Retention,
+    pub maintenance: Maintenance,
+    pub pending_deletions: PendingDeletions,
+    pub previews: Previews,
+    pub search_results: SearchResults,
+    pub sieve: SieveFilter,
+    pub attachment_index: AttachmentIndex,
 }
 
 impl Drop for Account {
     fn drop(&mut self) {
-        if let Ok(data_dir) = xdg::BaseDirectories::with_profile("meli", self.name.as_ref()) {
-            if let Ok(data) = data_dir.place_data_file("contacts") {
-                /* place result in cache directory */
-                let f = match std::fs::File::create(data) {
-                    Ok(f) => f,
-                    Err(e) => {
-                        eprintln!("{e}");
-                        return;
-                    }
-                };
-                let metadata = f.metadata().unwrap();
-                let mut permissions = metadata.permissions();
-
-                permissions.set_mode(0o600); // Read/write for owner only.
-                f.set_permissions(permissions).unwrap();
-                let writer = std::io::BufWriter::new(f);
-                if let Err(err) = serde_json::to_writer(writer, &self.contacts) {
-                    eprintln!("{err}");
-                };
-            };
-            /*
-            if let Ok(data) = data_dir.place_data_file("mailbox") {
-                /* place result in cache directory */
-                let f = match fs::File::create(data) {
-                    Ok(f) => f,
-                    Err(e) => {
-                        eprintln!("{}", e);
-                        return;
-                    }
-                };
-                let metadata = f.metadata().unwrap();
-                let mut permissions = metadata.permissions();
-
-                permissions.set_mode(0o600); // Read/write for owner only.
-                f.set_permissions(permissions).unwrap();
-                let writer = io::BufWriter::new(f);
-                if let Err(err) = bincode::Options::serialize_into(
-                    bincode::config::DefaultOptions::new(),
-                    writer,
-                    &self.collection,
-                ) {
-                    eprintln!("{}", err);
-                };
-            };
-                */
+        if let Err(err) = CONTACTS_FILE.save(&self.name, &self.contacts) {
+            eprintln!("{err}");
         }
     }
 }
 
+/// The `contacts` file of an account.
+pub const CONTACTS_FILE: StateFile = StateFile {
+    description: "contacts",
+    name: "contacts",
+    steps: &[from_unversioned],
+};
+
 impl Account {
     pub fn new(
         hash: AccountHash,
@@ -198,6 +199,20 @@ impl Account {
         event_consumer: BackendEventConsumer,
     ) -> Result<Self> {
         let name: Arc<str> = name.into();
+        // Credential commands are run by melib, which does not know about
+        // `allowed_commands`, so they are checked once before the backend is
+        // set up.
+        let command_policy = settings.conf.command_policy();
+        for cmd in settings.credential_commands() {
+            command_policy
+                .check(std::process::Command::new("sh").args(["-c", cmd]))
+                .map_err(|err| {
+                    Error::new(format!(
+                        "Account `{name}`: credential command `{cmd}` was refused: {err}"
+                    ))
+                    .set_kind(ErrorKind::Configuration)
+                })?;
+        }
         let s = settings.clone();
         let mut backend = map.get(&settings.account().format)(
             settings.account(),
@@ -216,22 +231,21 @@ impl Account {
             event_consumer,
         )?;
 
-        let data_dir = xdg::BaseDirectories::with_profile("meli", name.as_ref())?;
         let mut contacts = Contacts::with_account(settings.account());
 
-        if let Ok(data) = data_dir.place_data_file("contacts") {
-            if data.exists() {
-                let reader = std::io::BufReader::new(std::fs::File::open(data).unwrap());
-                let result: std::result::Result<Contacts, _> = serde_json::from_reader(reader);
-                if let Ok(data_t) = result {
-                    for (id, c) in data_t.cards {
-                        if !contacts.card_exists(id) && !c.external_resource() {
-                            contacts.add_card(c);
-                        }
+        match CONTACTS_FILE.load::<Contacts>(&name) {
+            Ok(Some(data_t)) => {
+                for (id, c) in data_t.cards {
+                    if !contacts.card_exists(id) && !c.external_resource() {
+                        contacts.add_card(c);
                     }
                 }
             }
-        };
+            Ok(None) => {}
+            Err(err) => {
+                log::warn!("Could not read contacts of account {}: {}", name, err);
+            }
+        }
 
         if settings.conf.search_backend == SearchBackend::Auto {
             if backend.capabilities().supports_search {
@@ -327,6 +341,15 @@ impl Account {
             event_queue: IndexMap::default(),
             backend_capabilities: backend.capabilities(),
             backend: Arc::new(Mutex::new(backend)),
+            flag_batch: FlagBatch::default(),
+            await_replies: AwaitReplies::default(),
+            retention: Retention::default(),
+            maintenance: Maintenance::default(),
+            pending_deletions: PendingDeletions::default(),
+            previews: Previews::default(),
+            search_results: SearchResults::default(),
+            sieve: SieveFilter::default(),
+            attachment_index: AttachmentIndex::default(),
         })
     }
 
@@ -354,10 +377,16 @@ impl Account {
             .clone()
             .into_iter()
             .collect::<HashSet<String>>();
+        /* Mailboxes whose special usage was reported by the backend, as opposed
+         * to detected from their name. */
+        let mut special_use = HashSet::new();
         for f in ref_mailboxes.values_mut() {
             if default_mailbox.remove(f.path()) {
                 self.settings.default_mailbox = Some(f.hash());
             }
+            if f.special_usage() != SpecialUsageMailbox::Normal {
+                special_use.insert(f.hash());
+            }
             if let Some(conf) = self.settings.mailbox_confs.get_mut(f.path()) {
                 mailbox_conf_hash_set.remove(f.path());
                 conf.mailbox_conf.usage = if f.special_usage() != SpecialUsageMailbox::Normal {
@@ -409,6 +438,17 @@ impl Account {
             }
         }
 
+        let ambiguous = self.resolve_special_mailboxes(&mut mailbox_entries, &special_use);
+        if !ambiguous.is_empty() {
+            let account_hash = self.hash;
+            self.main_loop_handler
+                .send(ThreadEvent::UIEvent(UIEvent::Callback(CallbackFn(
+                    Box::new(move |context| {
+                        special_mailboxes_dialog(context, account_hash, &ambiguous)
+                    }),
+                ))));
+        }
+
         if !mailbox_conf_hash_set.is_empty() {
             let mut mailbox_comma_sep_list_string = mailbox_entries
                 .values()
@@ -476,7 +516,12 @@ impl Account {
                     if let Ok(mailbox_job) = self.backend.lock().unwrap().fetch(*h) {
                         let handle = self.main_loop_handler.job_executor.spawn(
                             "fetch-mailbox".into(),
-                            mailbox_job.into_future(),
+                            fetch_batch(
+                                mailbox_job,
+                                self.collection.clone(),
+                                *h,
+                                self.settings.sent_mailbox,
+                            ),
                             self.is_async(),
                         );
                         let job_id = handle.job_id;
@@ -503,6 +548,12 @@ impl Account {
         self.mailboxes_order = mailboxes_order;
         self.mailbox_entries = mailbox_entries;
         self.tree = tree;
+        self.update_tag_mailboxes(None);
+        self.init_await_replies();
+        self.announce_reminders();
+        self.init_sieve();
+        self.init_retention();
+        self.init_maintenance();
         Ok(())
     }
 
@@ -522,6 +573,23 @@ impl Account {
         }
 
         let mut ui_events = vec![];
+        let mut env_hashes: Vec<EnvelopeHash> = vec![];
+        if self.tag_mailboxes_enabled() || !self.await_replies.entries.is_empty() {
+            for event in &events {
+                match event {
+                    RefreshEventKind::Update(old_hash, envelope) => {
+                        env_hashes.extend([*old_hash, envelope.hash()]);
+                    }
+                    RefreshEventKind::Rename(old_hash, new_hash) => {
+                        env_hashes.extend([*old_hash, *new_hash]);
+                    }
+                    RefreshEventKind::Create(envelope) => env_hashes.push(envelope.hash()),
+                    RefreshEventKind::Remove(env_hash)
+                    | RefreshEventKind::NewFlags(env_hash, _) => env_hashes.push(*env_hash),
+                    _ => {}
+                }
+            }
+        }
         while let Some(event) = events.pop() {
             match event {
                 RefreshEventKind::Update(old_hash, envelope) => {
@@ -529,6 +597,7 @@ impl Account {
                         events.push(RefreshEventKind::Create(envelope));
                         continue;
                     }
+                    self.previews.remove(old_hash);
                     #[cfg(feature = "sqlite3")]
                     self.update_cached_env(*envelope.clone(), Some(old_hash));
                     self.collection.update(old_hash, *envelope, mailbox_hash);
@@ -615,6 +684,8 @@ impl Account {
                                 (*envelope).clone(),
                                 self.backend.clone(),
                                 self.name.clone(),
+                                self.settings.conf.attachment_extractors.clone(),
+                                self.settings.conf.command_policy(),
                             ),
                             crate::sqlite3::AccountCache::is_async(),
                         );
@@ -637,6 +708,9 @@ impl Account {
                         /* is a duplicate */
                         continue;
                     }
+                    if !is_seen && !is_draft {
+                        self.sieve_filter(env_hash, mailbox_hash);
+                    }
 
                     let mbox_update_event = UIEvent::MailboxUpdate((self.hash, mailbox_hash));
 
@@ -705,6 +779,8 @@ impl Account {
                     if !self.collection.contains_key(&env_hash) {
                         continue;
                     }
+                    self.attachment_index.remove(env_hash);
+                    self.previews.remove(env_hash);
                     #[cfg(feature = "sqlite3")]
                     if self.settings.conf.search_backend == SearchBackend::Sqlite3 {
                         let handle = self.main_loop_handler.job_executor.spawn(
@@ -765,8 +841,13 @@ impl Account {
                 } => {}
                 RefreshEventKind::MailboxSubscribe(_mailbox_hash) => {}
                 RefreshEventKind::MailboxUnsubscribe(_mailbox_hash) => {}
+                RefreshEventKind::MailboxCountsUpdate => {
+                    ui_events.push(UIEvent::MailboxUpdate((self.hash, mailbox_hash)));
+                }
             }
         }
+        self.update_tag_mailboxes(Some(&env_hashes));
+        self.check_await_replies(&env_hashes);
         Some(ui_events)
     }
 
@@ -777,7 +858,7 @@ impl Account {
                 .stdin(std::process::Stdio::null())
                 .stdout(std::process::Stdio::null())
                 .stderr(std::process::Stdio::piped())
-                .spawn()?;
+                .spawn_checked(&self.settings.conf.command_policy())?;
             self.main_loop_handler
                 .send(ThreadEvent::UIEvent(UIEvent::Fork(
                     ForkedProcess::Generic {
@@ -787,6 +868,11 @@ impl Account {
                     },
                 )));
         }
+        if self.is_virtual_mailbox(mailbox_hash) {
+            // Virtual mailboxes are updated along with the mailboxes their
+            // envelopes are stored in.
+            return Ok(());
+        }
         let refresh_job = self.backend.lock().unwrap().refresh(mailbox_hash);
         if let Ok(refresh_job) = refresh_job {
             let handle = self.main_loop_handler.job_executor.spawn(
@@ -917,7 +1003,12 @@ impl Account {
                         Ok(mailbox_job) => {
                             let handle = self.main_loop_handler.job_executor.spawn(
                                 "fetch-mailbox".into(),
-                                mailbox_job.into_future(),
+                                fetch_batch(
+                                    mailbox_job,
+                                    self.collection.clone(),
+                                    mailbox_hash,
+                                    self.settings.sent_mailbox,
+                                ),
                                 self.is_async(),
                             );
                             self.insert_job(
@@ -993,9 +1084,7 @@ impl Account {
         mailbox_hash: MailboxHash,
         flags: Option<Flag>,
     ) -> Result<()> {
-        if self.settings.account.read_only {
-            return Err(Error::new(format!("Account {} is read-only.", self.name)));
-        }
+        self.check_writable(Some(mailbox_hash))?;
         let job = self
             .backend
             .lock()
@@ -1023,48 +1112,10 @@ impl Account {
         send_mail: crate::conf::composing::SendMail,
         #[allow(unused_variables)] complete_in_background: bool,
     ) -> Result<Option<JoinHandle<Result<()>>>> {
-        use std::{
-            io::Write,
-            process::{Command, Stdio},
-        };
-
         use crate::conf::composing::SendMail;
         match send_mail {
             SendMail::ShellCommand(ref command) => {
-                if command.is_empty() {
-                    return Err(Error::new(
-                        "send_mail shell command configuration value is empty",
-                    ));
-                }
-                let mut msmtp = Command::new("sh")
-                    .args(["-c", command])
-                    .stdin(Stdio::piped())
-                    .stdout(Stdio::piped())
-                    .spawn()
-                    .expect("Failed to start mailer command");
-                {
-                    let stdin = msmtp.stdin.as_mut().expect("failed to open stdin");
-                    stdin
-                        .write_all(message.as_bytes())
-                        .expect("Failed to write to stdin");
-                }
-                let output = msmtp.wait().expect("Failed to wait on mailer");
-                if output.success() {
-                    log::trace!("Message sent.");
-                } else {
-                    let error_message = if let Some(exit_code) = output.code() {
-                        format!(
-                            "Could not send e-mail using `{command}`: Process exited with \
-                             {exit_code}"
-                        )
-                    } else {
-                        format!(
-                            "Could not send e-mail using `{command}`: Process was killed by signal"
-                        )
-                    };
-                    log::error!("{}", error_message);
-                    return Err(Error::new(error_message).set_summary("Message not sent."));
-                }
+                send_with_shell_command(command, &message, &self.settings.conf.command_policy())?;
                 Ok(None)
             }
             #[cfg(feature = "smtp")]
@@ -1114,73 +1165,62 @@ impl Account {
     ) -> impl FnOnce(Arc<String>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> + Send {
         let capabilities = self.backend_capabilities.clone();
         let backend = self.backend.clone();
+        let command_policy = self.settings.conf.command_policy();
+        let (retries, mut delay) = self.settings.conf.send_mail_retry_policy();
+        let account_name = self.name.clone();
         move |message: Arc<String>| -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
             Box::pin(async move {
-                use std::{
-                    io::Write,
-                    process::{Command, Stdio},
-                };
-
                 use crate::conf::composing::SendMail;
-                match send_mail {
-                    SendMail::ShellCommand(ref command) => {
-                        if command.is_empty() {
-                            return Err(Error::new(
-                                "send_mail shell command configuration value is empty",
-                            ));
-                        }
-                        let mut msmtp = Command::new("sh")
-                            .args(["-c", command])
-                            .stdin(Stdio::piped())
-                            .stdout(Stdio::piped())
-                            .spawn()
-                            .expect("Failed to start mailer command");
-                        {
-                            let stdin = msmtp.stdin.as_mut().expect("failed to open stdin");
-                            stdin
-                                .write_all(message.as_bytes())
-                                .expect("Failed to write to stdin");
+
+                let mut attempt = 0;
+                loop {
+                    let ret = match send_mail {
+                        SendMail::ShellCommand(ref command) => {
+                            send_with_shell_command(command, message.as_str(), &command_policy)
                         }
-                        let output = msmtp.wait().expect("Failed to wait on mailer");
-                        if output.success() {
-                            log::trace!("Message sent.");
-                        } else {
-                            let error_message = if let Some(exit_code) = output.code() {
-                                format!(
-                                    "Could not send e-mail using `{command}`: Process exited with \
-                                     {exit_code}"
-                                )
-                            } else {
-                                format!(
-                                    "Could not send e-mail using `{command}`: Process was killed \
-                                     by signal"
-                                )
-                            };
-                            log::error!("{}", error_message);
-                            return Err(Error::new(error_message).set_summary("Message not sent."));
+                        #[cfg(feature = "smtp")]
+                        SendMail::Smtp(ref conf) => {
+                            match melib::smtp::SmtpConnection::new_connection(conf.clone()).await {
+                                Ok(mut smtp_connection) => {
+                                    smtp_connection
+                                        .mail_transaction(message.as_str(), None)
+                                        .await
+                                }
+                                Err(err) => Err(err),
+                            }
                         }
-                        Ok(())
-                    }
-                    #[cfg(feature = "smtp")]
-                    SendMail::Smtp(conf) => {
-                        let mut smtp_connection =
-                            melib::smtp::SmtpConnection::new_connection(conf).await?;
-                        smtp_connection
-                            .mail_transaction(message.as_str(), None)
-                            .await
-                    }
-                    SendMail::ServerSubmission => {
-                        if capabilities.supports_submission {
+                        SendMail::ServerSubmission => {
+                            if !capabilities.supports_submission {
+                                return Err(Error::new("Server does not support submission.")
+                                    .set_summary("Message not sent."));
+                            }
                             let fut = backend.lock().unwrap().submit(
                                 message.as_bytes().to_vec(),
                                 None,
                                 None,
-                            )?;
-                            fut.await?;
-                            return Ok(());
+                            );
+                            match fut {
+                                Ok(fut) => fut.await,
+                                Err(err) => Err(err),
+                            }
                         }
-                        Err(Error::new("Server does not support submission.")
-                            .set_summary("Message not sent."))
+                    };
+                    match ret {
+                        Err(err) if attempt < retries => {
+                            attempt += 1;
+                            log::warn!(
+                                "Account `{}`: sending e-mail failed, retrying in {} seconds \
+                                 (attempt {}/{}): {}",
+                                account_name,
+                                delay.as_secs(),
+                                attempt,
+                                retries,
+                                err
+                            );
+                            sleep(delay).await;
+                            delay = delay.saturating_mul(2);
+                        }
+                        ret => return ret,
                     }
                 }
             })
@@ -1265,7 +1305,7 @@ impl Account {
     pub fn search(
         &self,
         search_term: &str,
-        _sort: (SortField, SortOrder),
+        _sort: &SortChain,
         mailbox_hash: MailboxHash,
     ) -> ResultFuture<Vec<EnvelopeHash>> {
         let query = melib::search::Query::try_from(search_term)?;
@@ -1274,7 +1314,7 @@ impl Account {
             SearchBackend::Sqlite3 => Ok(Box::pin(crate::sqlite3::AccountCache::search(
                 self.name.clone(),
                 query,
-                _sort,
+                _sort.clone(),
             ))),
             SearchBackend::Auto | SearchBackend::None => {
                 if self.backend_capabilities.supports_search {
@@ -1376,6 +1416,13 @@ impl Account {
                 StatusEvent::JobFinished(*job_id),
             )));
         let job_id = *job_id;
+        if self.process_preview_event(job_id)
+            || self.process_search_results_event(job_id)
+            || self.process_attachment_index_event(job_id)
+            || self.process_sieve_event(job_id)
+        {
+            return true;
+        }
         macro_rules! is_canceled {
             ($handle:expr) => {{
                 if $handle.is_canceled() {
@@ -1464,10 +1511,21 @@ impl Account {
                             ));
                             return true;
                         }
-                        Ok(Some((Some(Ok(payload)), rest))) => {
+                        Ok(Some((
+                            Some(Ok(FetchedBatch {
+                                env_hashes,
+                                updated_mailboxes,
+                            })),
+                            rest,
+                        ))) => {
                             let handle = self.main_loop_handler.job_executor.spawn(
                                 "fetch-mailbox-continued".into(),
-                                rest.into_future(),
+                                fetch_batch(
+                                    rest,
+                                    self.collection.clone(),
+                                    mailbox_hash,
+                                    self.settings.sent_mailbox,
+                                ),
                                 self.is_async(),
                             );
                             self.insert_job(
@@ -1477,16 +1535,8 @@ impl Account {
                                     handle,
                                 },
                             );
-                            let envelopes = payload
-                                .into_iter()
-                                .map(|e| (e.hash(), e))
-                                .collect::<HashMap<EnvelopeHash, Envelope>>();
-                            let len = envelopes.len();
-                            if let Some(updated_mailboxes) = self.collection.merge(
-                                envelopes,
-                                mailbox_hash,
-                                self.settings.sent_mailbox,
-                            ) {
+                            let len = env_hashes.len();
+                            if let Some(updated_mailboxes) = updated_mailboxes {
                                 for f in updated_mailboxes {
                                     self.main_loop_handler.send(ThreadEvent::UIEvent(
                                         UIEvent::MailboxUpdate((self.hash, f)),
@@ -1504,6 +1554,8 @@ impl Account {
                                         };
                                     entry.status = MailboxStatus::Parsing(prev_len + len, 0);
                                 });
+                            self.update_tag_mailboxes(Some(&env_hashes));
+                            self.check_await_replies(&env_hashes);
                             self.main_loop_handler.send(ThreadEvent::UIEvent(
                                 UIEvent::MailboxUpdate((self.hash, mailbox_hash)),
                             ));
@@ -1641,6 +1693,10 @@ impl Account {
                                 self.main_loop_handler
                                     .send(ThreadEvent::UIEvent(UIEvent::EnvelopeUpdate(env_hash)));
                             }
+                            self.update_tag_mailboxes(Some(
+                                &env_hashes.iter().collect::<Vec<EnvelopeHash>>(),
+                            ));
+                            self.check_await_replies(&[]);
                         }
                         Err(_) | Ok(None) => {}
                     }
@@ -1857,6 +1913,60 @@ impl Account {
         }
     }
 
+    /// Handle the expiry of the account's timer `id`.
+    ///
+    /// Returns `false` if `id` is not one of the account's timers.
+    pub fn process_timer(&mut self, id: TimerId) -> bool {
+        if self.flag_batch.timer_id() == Some(id) {
+            if let Err(err) = self.flush_flag_batch() {
+                self.notify_error("could not set flags", err);
+            }
+        } else if self.pending_deletions.has_timer(id) {
+            if let Err(err) = self.process_deletion_timer(id) {
+                self.notify_error("could not delete e-mail", err);
+            }
+        } else if self.await_replies.timer_id() == Some(id) {
+            self.check_await_replies(&[]);
+        } else if self.retention.timer_id() == Some(id) {
+            self.apply_retention();
+        } else if self.maintenance.timer_id() == Some(id) {
+            if let Err(err) = self.start_maintenance() {
+                log::warn!("{}: {}", self.name, err);
+            }
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Returns `false` if quitting now would lose changes to e-mail.
+    ///
+    /// Queued flag changes and deletions that are still in their grace period
+    /// are submitted to the backend, and quitting has to wait until the jobs
+    /// that apply them have finished.
+    pub fn can_quit_cleanly(&mut self) -> bool {
+        if let Err(err) = self.flush_flag_batch() {
+            self.notify_error("could not set flags", err);
+        }
+        if let Err(err) = self.process_pending_deletions() {
+            self.notify_error("could not delete e-mail", err);
+        }
+        !self
+            .active_jobs
+            .values()
+            .any(|j| j.is_set_flags() || j.is_delete_messages())
+    }
+
+    fn notify_error(&self, title: &str, err: Error) {
+        self.main_loop_handler
+            .send(ThreadEvent::UIEvent(UIEvent::Notification {
+                title: Some(format!("{}: {}", self.name, title).into()),
+                body: err.to_string().into(),
+                kind: Some(NotificationType::Error(err.kind)),
+                source: Some(err),
+            }));
+    }
+
     #[inline]
     pub fn is_async(&self) -> IsAsync {
         if self.backend_capabilities.is_async {
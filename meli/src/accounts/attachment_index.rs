@@ -0,0 +1,188 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Index of the attachments of each envelope, listed by the attachments view
+//! of a mailbox.
+//!
+//! [`Account::index_attachments`] spawns a job that fetches the envelopes of a
+//! mailbox that have attachments and are not indexed yet, and parses their
+//! MIME structure with [`MimeStructure`] without copying any part bodies.
+//! When the job finishes the attachments are cached and an
+//! [`UIEvent::MailboxUpdate`] is sent so that the view lists them.
+
+use melib::{email::attachment_types::ContentDispositionKind, EnvelopeHash, MimeStructure};
+
+use super::*;
+
+/// An attachment of an envelope.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttachmentInfo {
+    /// Path of the attachment's part in the message's MIME structure, see
+    /// [`MimeStructure::get`].
+    pub path: Vec<usize>,
+    pub filename: Option<String>,
+    pub mime_type: String,
+    /// Size of the encoded body in bytes.
+    pub size: usize,
+}
+
+impl AttachmentInfo {
+    /// The attachments of the message in `bytes`: every part that is not
+    /// multipart and either has a file name or is marked as an attachment.
+    pub fn from_bytes(bytes: &[u8]) -> Vec<Self> {
+        MimeStructure::new(bytes)
+            .parts()
+            .into_iter()
+            .filter(|(_, part)| !part.is_multipart())
+            .filter_map(|(path, part)| {
+                let filename = part.filename();
+                (filename.is_some()
+                    || part.content_disposition.kind == ContentDispositionKind::Attachment)
+                    .then(|| Self {
+                        path,
+                        filename,
+                        mime_type: part.mime_type(),
+                        size: part.size(),
+                    })
+            })
+            .collect()
+    }
+}
+
+type IndexJob = JoinHandle<Result<Vec<(EnvelopeHash, Vec<AttachmentInfo>)>>>;
+
+#[derive(Debug, Default)]
+pub struct AttachmentIndex {
+    cache: HashMap<EnvelopeHash, Arc<[AttachmentInfo]>>,
+    job: Option<(JobId, MailboxHash, IndexJob)>,
+}
+
+impl AttachmentIndex {
+    /// The cached attachments of `env_hash`, if it has been indexed.
+    pub fn get(&self, env_hash: EnvelopeHash) -> Option<Arc<[AttachmentInfo]>> {
+        self.cache.get(&env_hash).cloned()
+    }
+
+    /// Whether `mailbox_hash` is being indexed.
+    pub fn is_pending(&self, mailbox_hash: MailboxHash) -> bool {
+        matches!(self.job, Some((_, h, _)) if h == mailbox_hash)
+    }
+
+    pub fn remove(&mut self, env_hash: EnvelopeHash) {
+        self.cache.remove(&env_hash);
+    }
+}
+
+impl Account {
+    /// Spawn a job to index the attachments of the envelopes of
+    /// `mailbox_hash` that are not in the index yet.
+    ///
+    /// Returns `false` if there is nothing to index.
+    pub fn index_attachments(&mut self, mailbox_hash: MailboxHash) -> Result<bool> {
+        if self.attachment_index.is_pending(mailbox_hash) {
+            return Ok(true);
+        }
+        let env_hashes = self
+            .collection
+            .get_mailbox(mailbox_hash)
+            .iter()
+            .copied()
+            .filter(|h| !self.attachment_index.cache.contains_key(h))
+            .filter(|h| self.collection.get_env(*h).has_attachments())
+            .collect::<Vec<EnvelopeHash>>();
+        if env_hashes.is_empty() {
+            return Ok(false);
+        }
+        let mut futures = Vec::with_capacity(env_hashes.len());
+        for env_hash in env_hashes {
+            match self.envelope_bytes_by_hash(env_hash) {
+                Ok(fut) => futures.push((env_hash, fut)),
+                Err(err) => {
+                    log::debug!("Could not index attachments of {}: {err}", env_hash);
+                }
+            }
+        }
+        let handle = self.main_loop_handler.job_executor.spawn(
+            "index-attachments".into(),
+            async move {
+                let mut ret = Vec::with_capacity(futures.len());
+                for (env_hash, fut) in futures {
+                    match fut.await {
+                        Ok(bytes) => ret.push((env_hash, AttachmentInfo::from_bytes(&bytes))),
+                        Err(err) => {
+                            log::debug!("Could not index attachments of {}: {err}", env_hash);
+                        }
+                    }
+                }
+                Ok(ret)
+            },
+            self.is_async(),
+        );
+        self.main_loop_handler
+            .send(ThreadEvent::UIEvent(UIEvent::StatusEvent(
+                StatusEvent::NewJob(handle.job_id),
+            )));
+        if let Some((_, _, previous)) =
+            self.attachment_index
+                .job
+                .replace((handle.job_id, mailbox_hash, handle))
+        {
+            if let Some(canceled) = previous.cancel() {
+                self.main_loop_handler
+                    .send(ThreadEvent::UIEvent(UIEvent::StatusEvent(canceled)));
+            }
+        }
+        Ok(true)
+    }
+
+    /// Handle the completion of a job spawned by
+    /// [`Account::index_attachments`].
+    ///
+    /// Returns `false` if `job_id` is not an attachment indexing job.
+    fn process_attachment_index_event(&mut self, job_id: JobId) -> bool {
+        if !matches!(self.attachment_index.job, Some((id, _, _)) if id == job_id) {
+            return false;
+        }
+        let Some((_, mailbox_hash, mut handle)) = self.attachment_index.job.take() else {
+            return false;
+        };
+        match handle.chan.try_recv() {
+            Ok(Some(Ok(entries))) => {
+                for (env_hash, attachments) in entries {
+                    self.attachment_index
+                        .cache
+                        .insert(env_hash, attachments.into());
+                }
+            }
+            Ok(Some(Err(err))) => {
+                log::debug!("Could not index attachments: {err}");
+            }
+            Err(_) | Ok(None) => return true,
+        }
+        self.main_loop_handler
+            .send(ThreadEvent::UIEvent(UIEvent::MailboxUpdate((
+                self.hash,
+                mailbox_hash,
+            ))));
+        true
+    }
+}
@@ -0,0 +1,585 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Follow-up reminders for sent e-mail.
+//!
+//! The `await-reply` command tracks e-mail by their `Message-ID`. If no
+//! e-mail referring to a tracked e-mail arrives by its deadline, a
+//! notification is raised and the e-mail is listed in the
+//! [`AWAIT_REPLY_MAILBOX`] virtual mailbox. Reminders are persisted in the
+//! account's state database when meli is built with `sqlite3` support.
+//!
+//! Only new and changed envelopes are looked at for replies, so the cost of
+//! a check does not grow with the size of the account.
+
+use melib::{
+    backends::SpecialUsageMailbox, utils::datetime, Address, Envelope, EnvelopeHash, UnixTimestamp,
+};
+
+#[cfg(feature = "sqlite3")]
+pub use self::store::state_db;
+use self::store::*;
+use super::*;
+use crate::jobs::{Timer, TimerId};
+
+/// Path of the mailbox overdue e-mail are listed under.
+pub const AWAIT_REPLY_MAILBOX: &str = "Awaiting reply";
+
+/// How often deadlines are checked.
+pub const AWAIT_REPLY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AwaitReply {
+    /// `Message-ID` of the tracked e-mail, without angle brackets.
+    pub message_id: String,
+    pub subject: String,
+    pub deadline: UnixTimestamp,
+    /// Whether the user has been notified that the deadline has passed.
+    pub notified: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct AwaitReplies {
+    pub entries: IndexMap<String, AwaitReply>,
+    /// Loaded envelopes of tracked e-mail, by `Message-ID`.
+    envelopes: HashMap<String, EnvelopeHash>,
+    timer: Option<Timer>,
+}
+
+impl AwaitReplies {
+    pub fn timer_id(&self) -> Option<TimerId> {
+        self.timer.as_ref().map(Timer::id)
+    }
+
+    /// Take note of the new or changed envelope `env`.
+    ///
+    /// If `env` is a tracked e-mail, it is indexed. Unless it is sent from one
+    /// of `identities`, the `Message-ID`s of the tracked e-mail it refers to
+    /// with its `In-Reply-To` or `References` headers are returned.
+    pub fn index_envelope(&mut self, env: &Envelope, identities: &[Address]) -> Vec<String> {
+        if self.entries.contains_key(env.message_id().as_str()) {
+            self.envelopes
+                .insert(env.message_id().as_str().to_string(), env.hash());
+        }
+        if env.from().iter().any(|a| identities.contains(a)) {
+            return vec![];
+        }
+        let in_reply_to = env.in_reply_to();
+        env.references()
+            .iter()
+            .chain(in_reply_to.iter().flat_map(|r| r.refs().iter()))
+            .map(|r| r.as_str())
+            .filter(|r| self.entries.contains_key(*r))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Envelopes of tracked e-mail, and whether their deadline has passed at
+    /// `now`.
+    ///
+    /// Envelopes that `is_loaded` returns `false` for are forgotten.
+    pub fn overdue(
+        &mut self,
+        now: UnixTimestamp,
+        is_loaded: impl Fn(EnvelopeHash) -> bool,
+    ) -> Vec<(EnvelopeHash, bool)> {
+        self.envelopes.retain(|_, env_hash| is_loaded(*env_hash));
+        self.envelopes
+            .iter()
+            .map(|(message_id, env_hash)| (*env_hash, self.entries[message_id].deadline <= now))
+            .collect()
+    }
+
+    /// Stop tracking `message_id`.
+    pub fn remove(&mut self, message_id: &str) -> Option<AwaitReply> {
+        self.envelopes.remove(message_id);
+        self.entries.shift_remove(message_id)
+    }
+}
+
+#[cfg(feature = "sqlite3")]
+mod store {
+    use melib::utils::sqlite3::{params, DatabaseDescription};
+
+    use super::AwaitReply;
+    use melib::Result;
+
+    /// The account's state database.
+    const DB: DatabaseDescription = DatabaseDescription {
+        name: "state.db",
+        identifier: None,
+        application_prefix: "meli",
+        directory: None,
+        init_script: Some(
+            "CREATE TABLE IF NOT EXISTS await_reply (
+                    message_id       TEXT PRIMARY KEY,
+                    subject          TEXT NOT NULL,
+                    deadline         INTEGER NOT NULL,
+                    notified         BOOLEAN NOT NULL
+                  );",
+        ),
+        version: 1,
+        migrations: &[],
+    };
+
+    /// Description of the state database of account `account_name`.
+    pub fn state_db(account_name: &str) -> DatabaseDescription {
+        DatabaseDescription {
+            identifier: Some(account_name.to_string().into()),
+            ..DB
+        }
+    }
+
+    pub fn load_await_replies(account_name: &str) -> Result<Vec<AwaitReply>> {
+        let db = state_db(account_name);
+        if !db.exists()? {
+            return Ok(vec![]);
+        }
+        let conn = db.open_or_create_db()?;
+        let mut stmt =
+            conn.prepare("SELECT message_id, subject, deadline, notified FROM await_reply")?;
+        let ret = stmt
+            .query_map([], |row| {
+                Ok(AwaitReply {
+                    message_id: row.get(0)?,
+                    subject: row.get(1)?,
+                    deadline: row.get::<_, i64>(2)? as u64,
+                    notified: row.get(3)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<AwaitReply>, _>>()?;
+        Ok(ret)
+    }
+
+    pub fn store_await_reply(account_name: &str, entry: &AwaitReply) -> Result<()> {
+        let conn = state_db(account_name).open_or_create_db()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO await_reply (message_id, subject, deadline, notified) VALUES \
+             (?1, ?2, ?3, ?4)",
+            params![
+                entry.message_id,
+                entry.subject,
+                entry.deadline as i64,
+                entry.notified
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_await_reply(account_name: &str, message_id: &str) -> Result<()> {
+        let db = state_db(account_name);
+        if !db.exists()? {
+            return Ok(());
+        }
+        db.open_or_create_db()?.execute(
+            "DELETE FROM await_reply WHERE message_id = ?1",
+            params![message_id],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "sqlite3"))]
+mod store {
+    use super::AwaitReply;
+    use melib::Result;
+
+    pub fn load_await_replies(_: &str) -> Result<Vec<AwaitReply>> {
+        Ok(vec![])
+    }
+
+    pub fn store_await_reply(_: &str, _: &AwaitReply) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn remove_await_reply(_: &str, _: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AwaitReplyMailbox {
+    hash: MailboxHash,
+    unseen: Arc<Mutex<usize>>,
+    total: Arc<Mutex<usize>>,
+}
+
+impl AwaitReplyMailbox {
+    pub fn new() -> Self {
+        Self {
+            hash: Self::mailbox_hash(),
+            unseen: Default::default(),
+            total: Default::default(),
+        }
+    }
+
+    pub fn mailbox_hash() -> MailboxHash {
+        MailboxHash::from_bytes(
+            format!("meli-await-reply-mailbox:{AWAIT_REPLY_MAILBOX}").as_bytes(),
+        )
+    }
+}
+
+impl Default for AwaitReplyMailbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BackendMailbox for AwaitReplyMailbox {
+    fn hash(&self) -> MailboxHash {
+        self.hash
+    }
+
+    fn name(&self) -> &str {
+        AWAIT_REPLY_MAILBOX
+    }
+
+    fn path(&self) -> &str {
+        AWAIT_REPLY_MAILBOX
+    }
+
+    fn children(&self) -> &[MailboxHash] {
+        &[]
+    }
+
+    fn clone(&self) -> Mailbox {
+        Box::new(std::clone::Clone::clone(self))
+    }
+
+    fn special_usage(&self) -> SpecialUsageMailbox {
+        SpecialUsageMailbox::Normal
+    }
+
+    fn parent(&self) -> Option<MailboxHash> {
+        None
+    }
+
+    fn permissions(&self) -> MailboxPermissions {
+        MailboxPermissions {
+            remove_messages: true,
+            set_flags: true,
+            delete_messages: true,
+            delete_mailbox: false,
+            ..MailboxPermissions::default()
+        }
+    }
+
+    fn is_subscribed(&self) -> bool {
+        true
+    }
+
+    fn set_is_subscribed(&mut self, _: bool) -> Result<()> {
+        Err(
+            Error::new("The awaiting reply mailbox is always subscribed.")
+                .set_kind(ErrorKind::NotSupported),
+        )
+    }
+
+    fn set_special_usage(&mut self, _: SpecialUsageMailbox) -> Result<()> {
+        Err(
+            Error::new("The awaiting reply mailbox cannot have a special usage.")
+                .set_kind(ErrorKind::NotSupported),
+        )
+    }
+
+    fn count(&self) -> Result<(usize, usize)> {
+        Ok((*self.unseen.lock()?, *self.total.lock()?))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+impl Account {
+    pub fn is_await_reply_mailbox(&self, mailbox_hash: MailboxHash) -> bool {
+        self.mailbox_entries.get(&mailbox_hash).is_some_and(|e| {
+            e.ref_mailbox
+                .as_any()
+                .downcast_ref::<AwaitReplyMailbox>()
+                .is_some()
+        })
+    }
+
+    /// Load persisted reminders.
+    pub(super) fn init_await_replies(&mut self) {
+        match load_await_replies(&self.name) {
+            Ok(entries) => {
+                self.await_replies.entries = entries
+                    .into_iter()
+                    .map(|e| (e.message_id.clone(), e))
+                    .collect();
+            }
+            Err(err) => {
+                log::error!(
+                    "{}: could not load await-reply reminders: {}",
+                    self.name,
+                    err
+                );
+            }
+        }
+        self.arm_await_reply_timer();
+    }
+
+    fn arm_await_reply_timer(&mut self) {
+        if self.await_replies.entries.is_empty() {
+            self.await_replies.timer = None;
+        } else if self.await_replies.timer.is_none() {
+            self.await_replies.timer = Some(
+                self.main_loop_handler
+                    .job_executor
+                    .clone()
+                    .create_timer(AWAIT_REPLY_CHECK_INTERVAL, AWAIT_REPLY_CHECK_INTERVAL),
+            );
+        }
+    }
+
+    /// Track `env_hashes` and raise a notification if they are not replied to
+    /// by `deadline`.
+    pub fn await_reply(
+        &mut self,
+        env_hashes: &[EnvelopeHash],
+        deadline: UnixTimestamp,
+    ) -> Result<()> {
+        let entries = {
+            let envelopes = self.collection.envelopes.read().unwrap();
+            env_hashes
+                .iter()
+                .filter_map(|h| envelopes.get(h))
+                .map(|env| AwaitReply {
+                    message_id: env.message_id().as_str().to_string(),
+                    subject: env.subject().to_string(),
+                    deadline,
+                    notified: false,
+                })
+                .collect::<Vec<AwaitReply>>()
+        };
+        let mut ret = Ok(());
+        for entry in entries {
+            if let Err(err) = store_await_reply(&self.name, &entry) {
+                log::error!(
+                    "{}: could not store await-reply reminder: {}",
+                    self.name,
+                    err
+                );
+                ret = Err(err);
+            }
+            self.await_replies
+                .entries
+                .insert(entry.message_id.clone(), entry);
+        }
+        self.arm_await_reply_timer();
+        // Replies might have arrived before the e-mail were tracked.
+        let loaded = self
+            .collection
+            .envelopes
+            .read()
+            .unwrap()
+            .keys()
+            .copied()
+            .collect::<Vec<EnvelopeHash>>();
+        self.check_await_replies(&loaded);
+        ret
+    }
+
+    /// Stop tracking `env_hashes`.
+    pub fn cancel_await_reply(&mut self, env_hashes: &[EnvelopeHash]) -> Result<()> {
+        let message_ids = {
+            let envelopes = self.collection.envelopes.read().unwrap();
+            env_hashes
+                .iter()
+                .filter_map(|h| envelopes.get(h))
+                .map(|env| env.message_id().as_str().to_string())
+                .collect::<Vec<String>>()
+        };
+        let mut ret = Ok(());
+        for message_id in message_ids {
+            if self.await_replies.remove(&message_id).is_none() {
+                continue;
+            }
+            if let Err(err) = remove_await_reply(&self.name, &message_id) {
+                log::error!(
+                    "{}: could not remove await-reply reminder: {}",
+                    self.name,
+                    err
+                );
+                ret = Err(err);
+            }
+        }
+        self.arm_await_reply_timer();
+        self.check_await_replies(&[]);
+        ret
+    }
+
+    /// Stop tracking e-mail that have been replied to by the new or changed
+    /// envelopes `env_hashes`, notify about e-mail whose deadline has passed
+    /// and update the [`AWAIT_REPLY_MAILBOX`] mailbox.
+    ///
+    /// Replies are e-mail referring to the tracked e-mail with their
+    /// `In-Reply-To` or `References` headers, sent from an address that is
+    /// not one of the account's identities.
+    pub fn check_await_replies(&mut self, env_hashes: &[EnvelopeHash]) {
+        if self.await_replies.entries.is_empty()
+            && !self.is_await_reply_mailbox(AwaitReplyMailbox::mailbox_hash())
+        {
+            return;
+        }
+        let now = datetime::now();
+        if !self.await_replies.entries.is_empty() && !env_hashes.is_empty() {
+            let mut identities = self.settings.account.extra_identity_addresses();
+            identities.push(self.settings.account.main_identity_address());
+            let mut replied: HashSet<String> = HashSet::default();
+            {
+                let envelopes = self.collection.envelopes.read().unwrap();
+                for env in env_hashes.iter().filter_map(|h| envelopes.get(h)) {
+                    replied.extend(self.await_replies.index_envelope(env, &identities));
+                }
+            }
+            for message_id in replied {
+                self.await_replies.remove(&message_id);
+                if let Err(err) = remove_await_reply(&self.name, &message_id) {
+                    log::error!(
+                        "{}: could not remove await-reply reminder: {}",
+                        self.name,
+                        err
+                    );
+                }
+            }
+        }
+        self.arm_await_reply_timer();
+        if self.mailbox_entries.values().any(|e| e.status.is_parsing()) {
+            // Replies might be in mailboxes that have not been loaded yet.
+            return;
+        }
+        for entry in self.await_replies.entries.values_mut() {
+            if entry.notified || entry.deadline > now {
+                continue;
+            }
+            entry.notified = true;
+            if let Err(err) = store_await_reply(&self.name, entry) {
+                log::error!(
+                    "{}: could not store await-reply reminder: {}",
+                    self.name,
+                    err
+                );
+            }
+            self.main_loop_handler
+                .send(ThreadEvent::UIEvent(UIEvent::Notification {
+                    title: Some(format!("{}: no reply received", self.name).into()),
+                    source: None,
+                    body: format!(
+                        "\"{}\" has not been replied to by {}.",
+                        entry.subject,
+                        datetime::timestamp_to_string(
+                            entry.deadline,
+                            Some(datetime::formats::RFC3339_DATE),
+                            false
+                        )
+                    )
+                    .into(),
+                    kind: Some(NotificationType::Info),
+                }));
+        }
+        let membership = {
+            let envelopes = self.collection.envelopes.read().unwrap();
+            self.await_replies
+                .overdue(now, |env_hash| envelopes.contains_key(&env_hash))
+        };
+        self.update_await_reply_mailbox(membership);
+    }
+
+    fn update_await_reply_mailbox(&mut self, mut membership: Vec<(EnvelopeHash, bool)>) {
+        let mailbox_hash = AwaitReplyMailbox::mailbox_hash();
+        let mut created = false;
+        if !self.is_await_reply_mailbox(mailbox_hash) {
+            if !membership.iter().any(|(_, is_member)| *is_member) {
+                return;
+            }
+            self.mailbox_entries.insert(
+                mailbox_hash,
+                MailboxEntry::new(
+                    MailboxStatus::Available,
+                    AWAIT_REPLY_MAILBOX.to_string(),
+                    Box::new(AwaitReplyMailbox::new()),
+                    FileMailboxConf::default(),
+                ),
+            );
+            self.collection.new_mailbox(mailbox_hash);
+            build_mailboxes_order(
+                &mut self.tree,
+                &self.mailbox_entries,
+                &mut self.mailboxes_order,
+            );
+            created = true;
+        }
+        // Envelopes that are no longer tracked are removed as well.
+        let untracked = self
+            .collection
+            .get_mailbox(mailbox_hash)
+            .iter()
+            .filter(|h| !membership.iter().any(|(m, _)| m == *h))
+            .map(|h| (*h, false))
+            .collect::<Vec<(EnvelopeHash, bool)>>();
+        membership.extend(untracked);
+        let mut changed = self
+            .collection
+            .set_mailbox_membership(mailbox_hash, membership)
+            || created;
+        let (unseen, total) = {
+            let envelopes = self.collection.envelopes.read().unwrap();
+            let mailbox = self.collection.get_mailbox(mailbox_hash);
+            (
+                mailbox
+                    .iter()
+                    .filter(|h| envelopes.get(*h).is_some_and(|env| !env.is_seen()))
+                    .count(),
+                mailbox.len(),
+            )
+        };
+        if let Some(mailbox) = self
+            .mailbox_entries
+            .get(&mailbox_hash)
+            .and_then(|e| e.ref_mailbox.as_any().downcast_ref::<AwaitReplyMailbox>())
+        {
+            changed |= std::mem::replace(&mut *mailbox.unseen.lock().unwrap(), unseen) != unseen;
+            changed |= std::mem::replace(&mut *mailbox.total.lock().unwrap(), total) != total;
+        }
+        if created {
+            self.main_loop_handler
+                .send(ThreadEvent::UIEvent(UIEvent::MailboxCreate((
+                    self.hash,
+                    mailbox_hash,
+                ))));
+        } else if changed {
+            self.main_loop_handler
+                .send(ThreadEvent::UIEvent(UIEvent::MailboxUpdate((
+                    self.hash,
+                    mailbox_hash,
+                ))));
+        }
+    }
+}
@@ -21,14 +21,114 @@
 
 //! Account mail backend operations.
 
+use melib::thread::{ThreadHash, Threads};
+
 use super::*;
 
+/// The e-mail of the threads `env_hashes` belong to, `env_hashes` included.
+pub fn thread_envelopes(threads: &Threads, env_hashes: &[EnvelopeHash]) -> Vec<EnvelopeHash> {
+    let mut groups: IndexSet<ThreadHash> = IndexSet::default();
+    let mut ret: IndexSet<EnvelopeHash> = env_hashes.iter().copied().collect();
+    for env_hash in env_hashes {
+        if let Some(thread_hash) = threads.envelope_to_thread.get(env_hash) {
+            groups.insert(threads.find_group(*thread_hash));
+        }
+    }
+    for group in groups {
+        ret.extend(
+            threads
+                .thread_iter(group)
+                .filter_map(|(_, h)| threads.thread_nodes()[&h].message()),
+        );
+    }
+    ret.into_iter().collect()
+}
+
 impl Account {
+    /// Return an error if the account, or `mailbox_hash` if given, is
+    /// configured as `read_only` and must not be changed.
+    pub fn check_writable(&self, mailbox_hash: Option<MailboxHash>) -> Result<()> {
+        if self.settings.account.read_only {
+            return Err(Error::new(format!("Account {} is read-only.", self.name))
+                .set_kind(ErrorKind::NotSupported));
+        }
+        if let Some(entry) = mailbox_hash.and_then(|h| self.mailbox_entries.get(&h)) {
+            if entry.conf.mailbox_conf.read_only {
+                return Err(Error::new(format!(
+                    "Mailbox {} of account {} is read-only.",
+                    entry.name(),
+                    self.name
+                ))
+                .set_kind(ErrorKind::NotSupported));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether [`check_writable`](Self::check_writable) would fail for
+    /// `mailbox_hash`.
+    pub fn is_read_only(&self, mailbox_hash: MailboxHash) -> bool {
+        self.check_writable(Some(mailbox_hash)).is_err()
+    }
+
+    /// Change flags of `env_hashes`.
+    ///
+    /// Unless the account's `flag_batch_window` is zero, the operation is
+    /// queued and submitted together with any other flag changes that arrive
+    /// within the window, see [`FlagBatch`].
     pub fn set_flags(
         &mut self,
         env_hashes: EnvelopeHashBatch,
         mailbox_hash: MailboxHash,
         flags: Vec<FlagOp>,
+    ) -> Result<()> {
+        if self.is_virtual_mailbox(mailbox_hash) {
+            let env_hashes = env_hashes.iter().collect::<Vec<EnvelopeHash>>();
+            for (mailbox_hash, env_hashes) in self.backing_mailboxes(mailbox_hash, &env_hashes) {
+                if let Ok(env_hashes) = EnvelopeHashBatch::try_from(env_hashes.as_slice()) {
+                    self.set_flags(env_hashes, mailbox_hash, flags.clone())?;
+                }
+            }
+            return Ok(());
+        }
+        self.check_writable(Some(mailbox_hash))?;
+        let window = self.settings.conf.flag_batch_window();
+        if window.is_zero() {
+            self.set_flags_now(env_hashes, mailbox_hash, flags)?;
+            return Ok(());
+        }
+        if self.flag_batch.conflicts(&env_hashes, mailbox_hash, &flags) {
+            self.flush_flag_batch()?;
+        }
+        self.flag_batch.push(env_hashes, mailbox_hash, flags);
+        if !self.flag_batch.is_armed() {
+            let timer = self
+                .main_loop_handler
+                .job_executor
+                .clone()
+                .create_timer(Duration::ZERO, window);
+            self.flag_batch.set_timer(timer);
+        }
+        Ok(())
+    }
+
+    /// Submit all queued flag changes to the backend.
+    pub fn flush_flag_batch(&mut self) -> Result<()> {
+        let mut ret = Ok(());
+        for (env_hashes, mailbox_hash, flags) in self.flag_batch.drain() {
+            if let Err(err) = self.set_flags_now(env_hashes, mailbox_hash, flags) {
+                log::error!("{}: could not set flags: {}", self.name, err);
+                ret = Err(err);
+            }
+        }
+        ret
+    }
+
+    fn set_flags_now(
+        &mut self,
+        env_hashes: EnvelopeHashBatch,
+        mailbox_hash: MailboxHash,
+        flags: Vec<FlagOp>,
     ) -> Result<JobId> {
         let fut = self.backend.lock().unwrap().set_flags(
             env_hashes.clone(),
@@ -53,12 +153,194 @@ impl Account {
         Ok(job_id)
     }
 
+    /// Move `env_hashes` from `mailbox_hash` to `destination_mailbox_hash`.
+    ///
+    /// If `mailbox_hash` is a virtual mailbox, the e-mail are moved from the
+    /// mailboxes they are stored in and the last job is returned.
+    pub fn move_messages(
+        &mut self,
+        env_hashes: EnvelopeHashBatch,
+        mailbox_hash: MailboxHash,
+        destination_mailbox_hash: MailboxHash,
+    ) -> Result<JobId> {
+        self.check_move_destination(destination_mailbox_hash)?;
+        if self.is_virtual_mailbox(mailbox_hash) {
+            let env_hashes = env_hashes.iter().collect::<Vec<EnvelopeHash>>();
+            let mut ret = None;
+            for (mailbox_hash, env_hashes) in self.backing_mailboxes(mailbox_hash, &env_hashes) {
+                if mailbox_hash == destination_mailbox_hash {
+                    continue;
+                }
+                if let Ok(env_hashes) = EnvelopeHashBatch::try_from(env_hashes.as_slice()) {
+                    ret = Some(self.move_messages(
+                        env_hashes,
+                        mailbox_hash,
+                        destination_mailbox_hash,
+                    )?);
+                }
+            }
+            return ret
+                .ok_or_else(|| Error::new("No e-mail to move.").set_kind(ErrorKind::NotFound));
+        }
+        self.check_writable(Some(mailbox_hash))?;
+        self.check_writable(Some(destination_mailbox_hash))?;
+        let fut = self.backend.lock().unwrap().copy_messages(
+            env_hashes,
+            mailbox_hash,
+            destination_mailbox_hash,
+            /* move? */ true,
+        )?;
+        let handle = self.main_loop_handler.job_executor.spawn(
+            "move-to-mailbox".into(),
+            fut,
+            self.is_async(),
+        );
+        let job_id = handle.job_id;
+        self.insert_job(
+            job_id,
+            JobRequest::Generic {
+                name: "message moving".into(),
+                handle,
+                on_finish: None,
+                log_level: LogLevel::INFO,
+            },
+        );
+        Ok(job_id)
+    }
+
+    /// Copy or move the entire threads of `env_hashes` from `mailbox_hash` to
+    /// `destination_mailbox_hash` with a single backend operation.
+    ///
+    /// When moving, the e-mail are removed from the mailbox and its threads
+    /// once the backend operation succeeds, so that the thread structure does
+    /// not depend on when the backend reports the removals.
+    pub fn copy_threads(
+        &mut self,
+        env_hashes: &[EnvelopeHash],
+        mailbox_hash: MailboxHash,
+        destination_mailbox_hash: MailboxHash,
+        move_: bool,
+    ) -> Result<JobId> {
+        self.check_move_destination(destination_mailbox_hash)?;
+        if self.is_virtual_mailbox(mailbox_hash) {
+            let mut ret = None;
+            for (mailbox_hash, env_hashes) in self.backing_mailboxes(mailbox_hash, env_hashes) {
+                if move_ && mailbox_hash == destination_mailbox_hash {
+                    continue;
+                }
+                ret = Some(self.copy_threads(
+                    &env_hashes,
+                    mailbox_hash,
+                    destination_mailbox_hash,
+                    move_,
+                )?);
+            }
+            return ret
+                .ok_or_else(|| Error::new("No e-mail to copy.").set_kind(ErrorKind::NotFound));
+        }
+        if move_ {
+            self.check_writable(Some(mailbox_hash))?;
+        }
+        self.check_writable(Some(destination_mailbox_hash))?;
+        let env_hashes = thread_envelopes(&self.collection.get_threads(mailbox_hash), env_hashes);
+        let batch = EnvelopeHashBatch::try_from(env_hashes.as_slice())
+            .map_err(|_| Error::new("No e-mail to copy.").set_kind(ErrorKind::NotFound))?;
+        let fut = self.backend.lock().unwrap().copy_messages(
+            batch,
+            mailbox_hash,
+            destination_mailbox_hash,
+            move_,
+        )?;
+        let succeeded = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let fut = {
+            let succeeded = succeeded.clone();
+            async move {
+                let ret = fut.await;
+                succeeded.store(ret.is_ok(), std::sync::atomic::Ordering::SeqCst);
+                ret
+            }
+        };
+        let handle = self.main_loop_handler.job_executor.spawn(
+            if move_ {
+                "move-threads".into()
+            } else {
+                "copy-threads".into()
+            },
+            fut,
+            self.is_async(),
+        );
+        let job_id = handle.job_id;
+        let account_hash = self.hash;
+        let on_finish = crate::types::CallbackFn(Box::new(move |context: &mut crate::Context| {
+            if !succeeded.load(std::sync::atomic::Ordering::SeqCst) {
+                return;
+            }
+            let Some(account) = context.accounts.get_mut(&account_hash) else {
+                return;
+            };
+            if move_ {
+                for env_hash in env_hashes.iter() {
+                    let thread_hash = account
+                        .collection
+                        .get_threads(mailbox_hash)
+                        .envelope_to_thread
+                        .get(env_hash)
+                        .copied();
+                    account.collection.remove(*env_hash, mailbox_hash);
+                    if let Some(thread_hash) = thread_hash {
+                        context
+                            .replies
+                            .push_back(UIEvent::EnvelopeRemove(*env_hash, thread_hash));
+                    }
+                }
+                context
+                    .replies
+                    .push_back(UIEvent::MailboxUpdate((account_hash, mailbox_hash)));
+            }
+            context
+                .replies
+                .push_back(UIEvent::StatusEvent(StatusEvent::DisplayMessage(format!(
+                    "{} {} e-mail.",
+                    if move_ { "Moved" } else { "Copied" },
+                    env_hashes.len()
+                ))));
+        }));
+        self.insert_job(
+            job_id,
+            JobRequest::Generic {
+                name: if move_ {
+                    "thread moving".into()
+                } else {
+                    "thread copying".into()
+                },
+                handle,
+                on_finish: Some(on_finish),
+                log_level: LogLevel::INFO,
+            },
+        );
+        Ok(job_id)
+    }
+
+    /// Virtual mailboxes only show e-mail stored elsewhere, so nothing can be
+    /// copied or moved into them.
+    fn check_move_destination(&self, destination_mailbox_hash: MailboxHash) -> Result<()> {
+        if self.is_virtual_mailbox(destination_mailbox_hash) {
+            return Err(
+                Error::new("E-mail cannot be copied or moved into a virtual mailbox.")
+                    .set_kind(ErrorKind::NotSupported),
+            );
+        }
+        Ok(())
+    }
+
     #[cfg(feature = "sqlite3")]
     pub(super) fn update_cached_env(&mut self, env: Envelope, old_hash: Option<EnvelopeHash>) {
         if self.settings.conf.search_backend == SearchBackend::Sqlite3 {
             let msg_id = env.message_id().to_string();
             let name = self.name.clone();
             let backend = self.backend.clone();
+            let attachment_extractors = self.settings.conf.attachment_extractors.clone();
+            let command_policy = self.settings.conf.command_policy();
             let fut = async move {
                 crate::sqlite3::AccountCache::remove(
                     name.clone(),
@@ -66,7 +348,14 @@ impl Account {
                 )
                 .await?;
 
-                crate::sqlite3::AccountCache::insert(env, backend, name).await?;
+                crate::sqlite3::AccountCache::insert(
+                    env,
+                    backend,
+                    name,
+                    attachment_extractors,
+                    command_policy,
+                )
+                .await?;
                 Ok(())
             };
             let handle = self.main_loop_handler.job_executor.spawn(
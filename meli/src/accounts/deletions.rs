@@ -0,0 +1,187 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Bulk deletions with a grace period.
+//!
+//! The `empty-trash`, `empty-folder` and `expunge` commands and retention
+//! rules do not delete e-mail right away: deletions are held for the
+//! account's `delete_grace_period`, during which the `undo-delete` command
+//! cancels them.
+
+use melib::{EnvelopeHash, Flag};
+
+use super::*;
+use crate::jobs::{Timer, TimerId};
+
+/// How long bulk deletions are held before they are performed, if the
+/// account's `delete_grace_period` is not set.
+pub const DEFAULT_DELETE_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+struct PendingDeletion {
+    mailbox_hash: MailboxHash,
+    env_hashes: EnvelopeHashBatch,
+    timer: Timer,
+}
+
+#[derive(Debug, Default)]
+pub struct PendingDeletions {
+    entries: Vec<PendingDeletion>,
+}
+
+impl PendingDeletions {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn has_timer(&self, timer_id: TimerId) -> bool {
+        self.entries.iter().any(|e| e.timer.id() == timer_id)
+    }
+
+    /// Whether `env_hash` of `mailbox_hash` is held for deletion.
+    pub fn contains(&self, mailbox_hash: MailboxHash, env_hash: EnvelopeHash) -> bool {
+        self.entries
+            .iter()
+            .any(|e| e.mailbox_hash == mailbox_hash && e.env_hashes.iter().any(|h| h == env_hash))
+    }
+}
+
+impl Account {
+    /// The loaded e-mail of `mailbox_hash`, or only those flagged as trashed
+    /// if `only_trashed` is set.
+    pub fn mailbox_envelopes(
+        &self,
+        mailbox_hash: MailboxHash,
+        only_trashed: bool,
+    ) -> Vec<EnvelopeHash> {
+        let envelopes = self.collection.envelopes.read().unwrap();
+        self.collection
+            .get_mailbox(mailbox_hash)
+            .iter()
+            .filter(|h| {
+                !only_trashed
+                    || envelopes
+                        .get(*h)
+                        .is_some_and(|env| env.flags().contains(Flag::TRASHED))
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Delete `env_hashes` of `mailbox_hash` once the account's
+    /// `delete_grace_period` has passed, unless
+    /// [`cancel_pending_deletions`](Self::cancel_pending_deletions) is called
+    /// before.
+    ///
+    /// Returns the number of e-mail that will be deleted.
+    pub fn schedule_deletion(
+        &mut self,
+        mailbox_hash: MailboxHash,
+        env_hashes: &[EnvelopeHash],
+    ) -> Result<usize> {
+        let backing_mailboxes = self.backing_mailboxes(mailbox_hash, env_hashes);
+        self.check_writable(None)?;
+        for (mailbox_hash, _) in &backing_mailboxes {
+            self.check_writable(Some(*mailbox_hash))?;
+        }
+        let mut ret = 0;
+        for (mailbox_hash, env_hashes) in backing_mailboxes {
+            let Ok(env_hashes) = EnvelopeHashBatch::try_from(env_hashes.as_slice()) else {
+                continue;
+            };
+            ret += env_hashes.len();
+            let grace_period = self.settings.conf.delete_grace_period();
+            if grace_period.is_zero() {
+                self.delete_now(mailbox_hash, env_hashes)?;
+                continue;
+            }
+            let timer = self
+                .main_loop_handler
+                .job_executor
+                .clone()
+                .create_timer(Duration::ZERO, grace_period);
+            self.pending_deletions.entries.push(PendingDeletion {
+                mailbox_hash,
+                env_hashes,
+                timer,
+            });
+        }
+        Ok(ret)
+    }
+
+    /// Cancel deletions that are still in their grace period and return the
+    /// number of e-mail that were spared.
+    pub fn cancel_pending_deletions(&mut self) -> usize {
+        self.pending_deletions
+            .entries
+            .drain(..)
+            .map(|e| e.env_hashes.len())
+            .sum()
+    }
+
+    /// Perform the deletions whose grace period timer is `timer_id`.
+    pub fn process_deletion_timer(&mut self, timer_id: TimerId) -> Result<()> {
+        let mut ret = Ok(());
+        let (due, pending) = std::mem::take(&mut self.pending_deletions.entries)
+            .into_iter()
+            .partition::<Vec<PendingDeletion>, _>(|e| e.timer.id() == timer_id);
+        self.pending_deletions.entries = pending;
+        for entry in due {
+            if let Err(err) = self.delete_now(entry.mailbox_hash, entry.env_hashes) {
+                log::error!("{}: could not delete e-mail: {}", self.name, err);
+                ret = Err(err);
+            }
+        }
+        ret
+    }
+
+    /// Perform all deletions that are still in their grace period.
+    pub fn process_pending_deletions(&mut self) -> Result<()> {
+        let mut ret = Ok(());
+        for entry in std::mem::take(&mut self.pending_deletions.entries) {
+            if let Err(err) = self.delete_now(entry.mailbox_hash, entry.env_hashes) {
+                log::error!("{}: could not delete e-mail: {}", self.name, err);
+                ret = Err(err);
+            }
+        }
+        ret
+    }
+
+    pub(super) fn delete_now(
+        &mut self,
+        mailbox_hash: MailboxHash,
+        env_hashes: EnvelopeHashBatch,
+    ) -> Result<JobId> {
+        let fut = self
+            .backend
+            .lock()
+            .unwrap()
+            .delete_messages(env_hashes.clone(), mailbox_hash)?;
+        let handle =
+            self.main_loop_handler
+                .job_executor
+                .spawn("delete".into(), fut, self.is_async());
+        let job_id = handle.job_id;
+        self.insert_job(job_id, JobRequest::DeleteMessages { env_hashes, handle });
+        Ok(job_id)
+    }
+}
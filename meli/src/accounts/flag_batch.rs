@@ -0,0 +1,144 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Coalescing of envelope flag changes.
+//!
+//! Flag operations that arrive within a short window (e.g. marking every
+//! message of a thread as read, or rapidly toggling a flag) are collected in a
+//! [`FlagBatch`] and submitted to the backend as one
+//! [`MailBackend::set_flags`] call per mailbox and set of operations. For IMAP
+//! this results in a single `UID STORE` command over the combined UID set,
+//! and for maildir in a single rename pass.
+
+use std::time::Duration;
+
+use melib::{backends::prelude::*, EnvelopeHash, MailboxHash};
+
+use crate::jobs::{Timer, TimerId};
+
+/// Default debounce window, used when the account configuration does not
+/// specify one.
+pub const DEFAULT_FLAG_BATCH_WINDOW: Duration = Duration::from_millis(150);
+
+#[derive(Debug, Default)]
+pub struct FlagBatch {
+    pending: IndexMap<(MailboxHash, Vec<FlagOp>), IndexSet<EnvelopeHash>>,
+    timer: Option<Timer>,
+}
+
+/// Returns `true` if both operations change the same flag or tag, regardless
+/// of whether they set or unset it.
+fn same_target(a: &FlagOp, b: &FlagOp) -> bool {
+    match (a, b) {
+        (FlagOp::Set(a) | FlagOp::UnSet(a), FlagOp::Set(b) | FlagOp::UnSet(b)) => a.intersects(*b),
+        (FlagOp::SetTag(a) | FlagOp::UnSetTag(a), FlagOp::SetTag(b) | FlagOp::UnSetTag(b)) => {
+            a == b
+        }
+        _ => false,
+    }
+}
+
+/// Returns `true` if every operation in `old` is overridden by an operation in
+/// `new`, which means `new` can replace `old` for an envelope.
+fn supersedes(new: &[FlagOp], old: &[FlagOp]) -> bool {
+    old.iter().all(|o| new.iter().any(|n| same_target(n, o)))
+}
+
+impl FlagBatch {
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Number of pending backend operations.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn timer_id(&self) -> Option<TimerId> {
+        self.timer.as_ref().map(Timer::id)
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.timer.is_some()
+    }
+
+    pub fn set_timer(&mut self, timer: Timer) {
+        self.timer = Some(timer);
+    }
+
+    /// Returns `true` if queueing `flags` for `env_hashes` would reorder
+    /// operations on the same flag or tag that cannot be merged. In that case
+    /// the pending batch must be flushed first.
+    pub fn conflicts(
+        &self,
+        env_hashes: &EnvelopeHashBatch,
+        mailbox_hash: MailboxHash,
+        flags: &[FlagOp],
+    ) -> bool {
+        self.pending.iter().any(|((mbox, ops), envs)| {
+            *mbox == mailbox_hash
+                && ops.as_slice() != flags
+                && !supersedes(flags, ops)
+                && ops.iter().any(|o| flags.iter().any(|n| same_target(n, o)))
+                && env_hashes.iter().any(|h| envs.contains(&h))
+        })
+    }
+
+    /// Queue `flags` for `env_hashes`. Pending operations on the same
+    /// envelopes that are fully overridden by `flags` are dropped, so that
+    /// e.g. setting and then unsetting a flag within the window results in
+    /// only the latter being sent to the backend.
+    pub fn push(
+        &mut self,
+        env_hashes: EnvelopeHashBatch,
+        mailbox_hash: MailboxHash,
+        flags: Vec<FlagOp>,
+    ) {
+        for ((mbox, ops), envs) in self.pending.iter_mut() {
+            if *mbox != mailbox_hash || *ops == flags || !supersedes(&flags, ops) {
+                continue;
+            }
+            for h in env_hashes.iter() {
+                envs.shift_remove(&h);
+            }
+        }
+        self.pending.retain(|_, envs| !envs.is_empty());
+        self.pending
+            .entry((mailbox_hash, flags))
+            .or_default()
+            .extend(env_hashes.iter());
+    }
+
+    /// Take all pending operations, in the order they were first queued, and
+    /// disarm the timer.
+    pub fn drain(&mut self) -> Vec<(EnvelopeHashBatch, MailboxHash, Vec<FlagOp>)> {
+        self.timer = None;
+        self.pending
+            .drain(..)
+            .filter_map(|((mailbox_hash, flags), envs)| {
+                let envs = envs.into_iter().collect::<Vec<EnvelopeHash>>();
+                let batch = EnvelopeHashBatch::try_from(envs.as_slice()).ok()?;
+                Some((batch, mailbox_hash, flags))
+            })
+            .collect()
+    }
+}
@@ -22,7 +22,9 @@
 
 use std::{borrow::Cow, collections::HashMap};
 
-use melib::{backends::prelude::*, error::Result, LogLevel};
+use futures::stream::StreamExt;
+use melib::{backends::prelude::*, error::Result, Collection, LogLevel};
+use smallvec::SmallVec;
 
 use crate::{is_variant, jobs::JoinHandle, StatusEvent};
 
@@ -105,14 +107,50 @@ impl MailboxJobRequest {
     }
 }
 
+/// The remaining batches of envelopes of a mailbox fetch.
+pub type FetchStream = BoxStream<'static, Result<Vec<Envelope>>>;
+
+/// A batch of envelopes of a mailbox fetch, already merged into the account's
+/// collection by [`fetch_batch`].
+#[derive(Debug)]
+pub struct FetchedBatch {
+    pub env_hashes: Vec<EnvelopeHash>,
+    /// Other mailboxes whose threads were updated, see
+    /// [`Collection::merge`].
+    pub updated_mailboxes: Option<SmallVec<[MailboxHash; 8]>>,
+}
+
+/// Get the next batch of envelopes of `stream` and merge it into `collection`
+/// in the job's thread, so that threading large batches does not block the
+/// UI.
+pub async fn fetch_batch(
+    stream: FetchStream,
+    collection: Collection,
+    mailbox_hash: MailboxHash,
+    sent_mailbox: Option<MailboxHash>,
+) -> (Option<Result<FetchedBatch>>, FetchStream) {
+    let (batch, rest) = stream.into_future().await;
+    let batch = batch.map(|payload| {
+        payload.map(|payload| {
+            let envelopes = payload
+                .into_iter()
+                .map(|e| (e.hash(), e))
+                .collect::<HashMap<EnvelopeHash, Envelope>>();
+            let env_hashes = envelopes.keys().copied().collect();
+            let updated_mailboxes = collection.merge(envelopes, mailbox_hash, sent_mailbox);
+            FetchedBatch {
+                env_hashes,
+                updated_mailboxes,
+            }
+        })
+    });
+    (batch, rest)
+}
+
 pub enum JobRequest {
     Fetch {
         mailbox_hash: MailboxHash,
-        #[allow(clippy::type_complexity)]
-        handle: JoinHandle<(
-            Option<Result<Vec<Envelope>>>,
-            BoxStream<'static, Result<Vec<Envelope>>>,
-        )>,
+        handle: JoinHandle<(Option<Result<FetchedBatch>>, FetchStream)>,
     },
     Generic {
         name: Cow<'static, str>,
@@ -224,6 +262,8 @@ impl JobRequest {
     is_variant! { is_watch, Watch { .. } }
     is_variant! { is_online, IsOnline { .. } }
     is_variant! { is_any_fetch, Fetch { .. } }
+    is_variant! { is_set_flags, SetFlags { .. } }
+    is_variant! { is_delete_messages, DeleteMessages { .. } }
 
     pub fn is_fetch(&self, mailbox_hash: MailboxHash) -> bool {
         matches!(self, Self::Fetch {
@@ -27,9 +27,7 @@ use crate::command::actions::MailboxOperation;
 
 impl Account {
     pub fn mailbox_operation(&mut self, op: MailboxOperation) -> Result<JobId> {
-        if self.settings.account.read_only {
-            return Err(Error::new("Account is read-only."));
-        }
+        self.check_writable(None)?;
         match op {
             MailboxOperation::Create(path) => {
                 let job = self
@@ -55,6 +53,7 @@ impl Account {
                 }
 
                 let mailbox_hash = self.mailbox_by_path(&path)?;
+                self.check_writable(Some(mailbox_hash))?;
                 let job = self.backend.lock().unwrap().delete_mailbox(mailbox_hash)?;
                 let handle = self.main_loop_handler.job_executor.spawn(
                     "delete-mailbox".into(),
@@ -119,6 +118,7 @@ impl Account {
             }
             MailboxOperation::Rename(path, new_path) => {
                 let mailbox_hash = self.mailbox_by_path(&path)?;
+                self.check_writable(Some(mailbox_hash))?;
                 let job = self
                     .backend
                     .lock()
@@ -273,16 +273,7 @@ impl Account {
                             new,
                         ),
                     );
-                    self.collection
-                        .threads
-                        .write()
-                        .unwrap()
-                        .insert(mailbox_hash, Threads::default());
-                    self.collection
-                        .mailboxes
-                        .write()
-                        .unwrap()
-                        .insert(mailbox_hash, Default::default());
+                    self.collection.new_mailbox(mailbox_hash);
                     build_mailboxes_order(
                         &mut self.tree,
                         &self.mailbox_entries,
@@ -312,11 +303,7 @@ impl Account {
                     if self.settings.sent_mailbox == Some(mailbox_hash) {
                         self.settings.sent_mailbox = None;
                     }
-                    self.collection
-                        .threads
-                        .write()
-                        .unwrap()
-                        .remove(&mailbox_hash);
+                    self.collection.remove_mailbox(mailbox_hash);
                     let deleted_mailbox =
                         self.mailbox_entries.shift_remove(&mailbox_hash).unwrap();
                     // if deleted mailbox had parent, we need to update its children field
@@ -327,11 +314,6 @@ impl Account {
                                 parent.ref_mailbox = mailboxes.remove(&parent_hash).unwrap();
                             });
                     }
-                    self.collection
-                        .mailboxes
-                        .write()
-                        .unwrap()
-                        .remove(&mailbox_hash);
                     build_mailboxes_order(
                         &mut self.tree,
                         &self.mailbox_entries,
@@ -368,18 +350,7 @@ impl Account {
                     if let Some((_, key, _)) = self.event_queue.get_full_mut2(&mailbox_hash) {
                         *key = new_hash;
                     }
-                    {
-                        let mut threads = self.collection.threads.write().unwrap();
-                        if let Some(entry) = threads.remove(&mailbox_hash) {
-                            threads.insert(new_hash, entry);
-                        }
-                    }
-                    {
-                        let mut mailboxes = self.collection.mailboxes.write().unwrap();
-                        if let Some(entry) = mailboxes.remove(&mailbox_hash) {
-                            mailboxes.insert(new_hash, entry);
-                        }
-                    }
+                    self.collection.rename_mailbox(mailbox_hash, new_hash);
                     build_mailboxes_order(
                         &mut self.tree,
                         &self.mailbox_entries,
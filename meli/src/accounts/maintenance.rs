@@ -0,0 +1,239 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Cache maintenance: integrity checks, compaction and garbage collection of
+//! the sqlite3 caches of an account.
+//!
+//! Starting maintenance also drops the interned envelope subjects that are
+//! not used anymore, see [`melib::utils::intern`].
+//!
+//! Maintenance rebuilds the message index and resets the offline cache of the
+//! backend if they are corrupted, removes cached message bodies beyond the
+//! account's `body_cache_budget` and vacuums the databases. It runs in the
+//! background on startup or periodically, as configured, and with the
+//! `maintenance` command.
+
+use super::*;
+use crate::jobs::{Timer, TimerId};
+
+/// Delay of maintenance on startup, so that it doesn't compete with the
+/// loading of mailboxes.
+const MAINTENANCE_STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default)]
+pub struct Maintenance {
+    timer: Option<Timer>,
+    job_id: Option<JobId>,
+}
+
+impl Maintenance {
+    pub fn timer_id(&self) -> Option<TimerId> {
+        self.timer.as_ref().map(Timer::id)
+    }
+}
+
+impl Account {
+    pub(super) fn init_maintenance(&mut self) {
+        if self.maintenance.timer.is_some() {
+            return;
+        }
+        let interval = self.settings.conf.maintenance_interval();
+        let first = if self.settings.conf.maintenance_on_startup {
+            MAINTENANCE_STARTUP_DELAY
+        } else if interval.is_zero() {
+            return;
+        } else {
+            interval
+        };
+        // A zero interval makes this a one-shot timer.
+        self.maintenance.timer = Some(
+            self.main_loop_handler
+                .job_executor
+                .clone()
+                .create_timer(interval, first),
+        );
+    }
+
+    /// Whether a maintenance job of this account is running.
+    pub fn is_maintenance_running(&self) -> bool {
+        self.maintenance
+            .job_id
+            .is_some_and(|job_id| self.active_jobs.contains_key(&job_id))
+    }
+
+    /// Start cache maintenance in the background. Progress is reported with
+    /// status messages.
+    pub fn start_maintenance(&mut self) -> Result<()> {
+        if self.is_maintenance_running() {
+            return Err(Error::new(format!(
+                "Cache maintenance of account {} is already running.",
+                self.name
+            )));
+        }
+        let dropped = melib::utils::intern::collect_garbage();
+        log::trace!("{}: dropped {} unused interned strings", self.name, dropped);
+        #[cfg(feature = "sqlite3")]
+        {
+            let index = *self.settings.conf.search_backend() == SearchBackend::Sqlite3;
+            let job = maintenance_job(
+                Arc::clone(&self.name),
+                index.then(|| {
+                    (
+                        self.collection.clone(),
+                        self.backend.clone(),
+                        self.settings.conf.attachment_extractors.clone(),
+                        self.settings.conf.command_policy(),
+                    )
+                }),
+                self.settings.conf.body_cache_budget(),
+                self.main_loop_handler.clone(),
+            );
+            let handle = self.main_loop_handler.job_executor.spawn(
+                "cache-maintenance".into(),
+                job,
+                IsAsync::Blocking,
+            );
+            self.maintenance.job_id = Some(handle.job_id);
+            self.insert_job(
+                handle.job_id,
+                JobRequest::Generic {
+                    name: "Cache maintenance".into(),
+                    handle,
+                    on_finish: None,
+                    log_level: LogLevel::INFO,
+                },
+            );
+            Ok(())
+        }
+        #[cfg(not(feature = "sqlite3"))]
+        {
+            Err(
+                Error::new("Cache maintenance failed: meli is not built with sqlite3 support.")
+                    .set_kind(ErrorKind::NotSupported),
+            )
+        }
+    }
+}
+
+#[cfg(feature = "sqlite3")]
+async fn maintenance_job(
+    acc_name: Arc<str>,
+    index: Option<(
+        Collection,
+        Arc<Mutex<Box<dyn MailBackend>>>,
+        IndexMap<String, String>,
+        crate::CommandPolicy,
+    )>,
+    body_cache_budget: Option<u64>,
+    main_loop_handler: MainLoopHandler,
+) -> Result<()> {
+    use melib::imap::sync::sqlite3_cache::Sqlite3Cache;
+
+    use crate::sqlite3::AccountCache;
+
+    let index_db = AccountCache::db_description(&acc_name);
+    let index = index.filter(|_| index_db.exists().unwrap_or(false));
+    let offline_cache_db = Sqlite3Cache::db_description(&acc_name, None);
+    let offline_cache = offline_cache_db.exists()?;
+    let total = if index.is_some() { 2 } else { 0 }
+        + match (offline_cache, body_cache_budget) {
+            (false, _) => 0,
+            (true, None) => 2,
+            (true, Some(_)) => 3,
+        };
+    if total == 0 {
+        main_loop_handler.send(ThreadEvent::UIEvent(UIEvent::StatusEvent(
+            StatusEvent::DisplayMessage(format!("{acc_name}: no caches to maintain.")),
+        )));
+        return Ok(());
+    }
+    let mut step = 0;
+    let mut progress = |msg: &str| {
+        step += 1;
+        main_loop_handler.send(ThreadEvent::UIEvent(UIEvent::StatusEvent(
+            StatusEvent::DisplayMessage(format!(
+                "{acc_name}: cache maintenance {step}/{total}: {msg}"
+            )),
+        )));
+    };
+    let mut summary = vec![];
+    let mut reclaimed = 0;
+    if let Some((collection, backend, attachment_extractors, command_policy)) = index {
+        progress("checking message index");
+        let problems = index_db.check_integrity()?;
+        if !problems.is_empty() {
+            log::warn!(
+                "{acc_name}: message index is corrupted, rebuilding it: {}",
+                problems.join("; ")
+            );
+            index_db.reset_db()?;
+            AccountCache::index(
+                Arc::clone(&acc_name),
+                collection,
+                backend,
+                attachment_extractors,
+                command_policy,
+            )
+            .await?;
+            summary.push("rebuilt corrupted message index".to_string());
+        }
+        progress("compacting message index");
+        let (before, after) = index_db.vacuum()?;
+        reclaimed += before.saturating_sub(after);
+    }
+    if offline_cache {
+        progress("checking offline cache");
+        let problems = offline_cache_db.check_integrity()?;
+        if !problems.is_empty() {
+            // The account's backend keeps the cache open, so it can only be
+            // reset when the backend opens it again on the next start.
+            log::warn!(
+                "{acc_name}: offline cache is corrupted, resetting it on the next start: {}",
+                problems.join("; ")
+            );
+            offline_cache_db.mark_for_reset()?;
+            summary.push("corrupted offline cache will be reset on the next start".to_string());
+        } else {
+            if let Some(budget) = body_cache_budget {
+                progress("expiring cached message bodies");
+                let expired = Sqlite3Cache::expire_bodies(&offline_cache_db, budget)?;
+                if expired > 0 {
+                    summary.push(format!("expired {expired} cached message bodies"));
+                }
+            }
+            progress("compacting offline cache");
+            let (before, after) = offline_cache_db.vacuum()?;
+            reclaimed += before.saturating_sub(after);
+        }
+    }
+    summary.push(format!(
+        "reclaimed {}",
+        melib::BytesDisplay(reclaimed as usize)
+    ));
+    main_loop_handler.send(ThreadEvent::UIEvent(UIEvent::StatusEvent(
+        StatusEvent::DisplayMessage(format!(
+            "{acc_name}: cache maintenance finished, {}.",
+            summary.join(", ")
+        )),
+    )));
+    Ok(())
+}
@@ -0,0 +1,186 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Body previews shown in mail listings.
+//!
+//! Previews are requested by listings when they draw an entry, see
+//! [`Account::preview`]. The first request for an envelope spawns a job that
+//! fetches and decodes its text body. When the job finishes the preview is
+//! cached and an [`UIEvent::EnvelopeUpdate`] is sent so that listings redraw
+//! the entry.
+//!
+//! At most [`PREVIEW_CACHE_CAPACITY`] previews are kept per account, the least
+//! recently drawn ones are dropped first. Previews of removed envelopes are
+//! dropped when the removal is processed.
+
+use melib::{
+    email::attachment_types::Text,
+    text::{TextProcessing, Truncate},
+    EnvelopeHash,
+};
+
+use super::*;
+
+/// Maximum number of previews cached per account.
+pub const PREVIEW_CACHE_CAPACITY: usize = 4096;
+
+#[derive(Debug, Default)]
+pub struct Previews {
+    /// Cached previews, from least to most recently used.
+    cache: Mutex<IndexMap<EnvelopeHash, String>>,
+    jobs: Mutex<HashMap<JobId, (EnvelopeHash, JoinHandle<Result<String>>)>>,
+}
+
+impl Previews {
+    /// Return the cached preview of `env_hash` and mark it as the most
+    /// recently used.
+    pub fn get(&self, env_hash: EnvelopeHash) -> Option<String> {
+        let mut cache = self.cache.lock().unwrap();
+        let index = cache.get_index_of(&env_hash)?;
+        let last = cache.len() - 1;
+        cache.move_index(index, last);
+        Some(cache[last].clone())
+    }
+
+    /// Cache `preview`, dropping the least recently used preview if the cache
+    /// is full.
+    pub fn insert(&self, env_hash: EnvelopeHash, preview: String) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.shift_remove(&env_hash);
+        if cache.len() >= PREVIEW_CACHE_CAPACITY {
+            cache.shift_remove_index(0);
+        }
+        cache.insert(env_hash, preview);
+    }
+
+    /// Drop the cached preview of `env_hash`.
+    pub fn remove(&self, env_hash: EnvelopeHash) {
+        self.cache.lock().unwrap().shift_remove(&env_hash);
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn is_pending(&self, env_hash: EnvelopeHash) -> bool {
+        self.jobs
+            .lock()
+            .unwrap()
+            .values()
+            .any(|(h, _)| *h == env_hash)
+    }
+}
+
+/// Make a preview of at most `length` graphemes out of a text body.
+///
+/// Blank lines and quoted lines are skipped, whitespace is collapsed and
+/// nothing after a signature delimiter is included.
+pub fn preview_text(text: &str, length: usize) -> String {
+    let mut ret = String::new();
+    for line in text.lines() {
+        if line == "-- " {
+            break;
+        }
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('>') {
+            continue;
+        }
+        for word in line.split_whitespace() {
+            if !ret.is_empty() {
+                ret.push(' ');
+            }
+            ret.push_str(word);
+        }
+        if ret.grapheme_len() > length {
+            break;
+        }
+    }
+    if ret.grapheme_len() > length {
+        ret.truncate_at_boundary(length);
+        ret.truncate(ret.trim_end().len());
+        ret.push('…');
+    }
+    ret
+}
+
+impl Account {
+    /// Return the cached body preview of `env_hash`, or spawn a job to make
+    /// one and return `None`.
+    pub fn preview(&self, env_hash: EnvelopeHash, length: usize) -> Option<String> {
+        if let Some(preview) = self.previews.get(env_hash) {
+            return Some(preview);
+        }
+        if !self.contains_key(env_hash) || self.previews.is_pending(env_hash) {
+            return None;
+        }
+        let envelope = self.collection.get_env(env_hash).clone();
+        let bytes_fut = match self.envelope_bytes_by_hash(env_hash) {
+            Ok(fut) => fut,
+            Err(err) => {
+                log::debug!("Could not fetch preview of {}: {err}", env_hash);
+                self.previews.insert(env_hash, String::new());
+                return None;
+            }
+        };
+        let handle = self.main_loop_handler.job_executor.spawn(
+            "preview".into(),
+            async move {
+                let bytes = bytes_fut.await?;
+                let text = envelope.body_bytes(&bytes).text(Text::Plain);
+                Ok(preview_text(&text, length))
+            },
+            self.is_async(),
+        );
+        self.previews
+            .jobs
+            .lock()
+            .unwrap()
+            .insert(handle.job_id, (env_hash, handle));
+        None
+    }
+
+    /// Handle the completion of a job spawned by [`Account::preview`].
+    ///
+    /// Returns `false` if `job_id` is not a preview job.
+    fn process_preview_event(&mut self, job_id: JobId) -> bool {
+        let Some((env_hash, mut handle)) = self.previews.jobs.get_mut().unwrap().remove(&job_id)
+        else {
+            return false;
+        };
+        let preview = match handle.chan.try_recv() {
+            Ok(Some(Ok(preview))) => preview,
+            Ok(Some(Err(err))) => {
+                log::debug!("Could not fetch preview of {}: {err}", env_hash);
+                String::new()
+            }
+            Err(_) | Ok(None) => return true,
+        };
+        self.previews.insert(env_hash, preview);
+        self.main_loop_handler
+            .send(ThreadEvent::UIEvent(UIEvent::EnvelopeUpdate(env_hash)));
+        true
+    }
+}
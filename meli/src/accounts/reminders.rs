@@ -0,0 +1,91 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Reminders shown on launch: birthdays of contacts and e-mail tracked with
+//! `await-reply` whose deadline has passed.
+//!
+//! They are summarized in the status bar once an account is loaded, and
+//! listed in the startup dashboard.
+
+use melib::{utils::datetime, Card, UnixTimestamp};
+
+use super::*;
+
+/// Reminders of an account for a given day.
+#[derive(Debug, Default)]
+pub struct Reminders<'a> {
+    /// Contacts whose birthday is on that day.
+    pub birthdays: Vec<&'a Card>,
+    /// Tracked e-mail that have not been replied to by their deadline.
+    pub overdue: Vec<&'a AwaitReply>,
+}
+
+impl Reminders<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.birthdays.is_empty() && self.overdue.is_empty()
+    }
+}
+
+impl std::fmt::Display for Reminders<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut parts = vec![];
+        match self.birthdays.len() {
+            0 => {}
+            1 => parts.push("1 birthday today".to_string()),
+            n => parts.push(format!("{n} birthdays today")),
+        }
+        if !self.overdue.is_empty() {
+            parts.push(format!(
+                "{} e-mail awaiting reply overdue",
+                self.overdue.len()
+            ));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+impl Account {
+    /// Reminders of the day of `now`.
+    pub fn reminders(&self, now: UnixTimestamp) -> Reminders<'_> {
+        Reminders {
+            birthdays: self.contacts.birthdays_on(now).collect(),
+            overdue: self
+                .await_replies
+                .entries
+                .values()
+                .filter(|e| e.deadline <= now)
+                .collect(),
+        }
+    }
+
+    /// Show a summary of today's reminders in the status bar.
+    pub(super) fn announce_reminders(&self) {
+        let reminders = self.reminders(datetime::now());
+        if reminders.is_empty() {
+            return;
+        }
+        self.main_loop_handler
+            .send(ThreadEvent::UIEvent(UIEvent::StatusEvent(
+                StatusEvent::DisplayMessage(format!("{}: {reminders}", self.name)),
+            )));
+    }
+}
@@ -0,0 +1,305 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Retention rules: archiving and deletion of old e-mail.
+//!
+//! Mailboxes can have [`RetentionRule`]s in their configuration. They are
+//! applied periodically to the loaded e-mail of each mailbox, and the
+//! `retention-preview` command shows what they would do without applying
+//! them.
+
+use std::fmt::Write as _;
+
+use melib::{utils::datetime, EnvelopeHash, UnixTimestamp};
+
+use super::*;
+use crate::{
+    conf::{RetentionAction, RetentionRule},
+    jobs::{Timer, TimerId},
+};
+
+/// How often retention rules are applied.
+pub const RETENTION_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Delay of the first application of retention rules, so that mailboxes have
+/// time to load.
+const RETENTION_FIRST_CHECK: Duration = Duration::from_secs(2 * 60);
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Default)]
+pub struct Retention {
+    timer: Option<Timer>,
+}
+
+impl Retention {
+    pub fn timer_id(&self) -> Option<TimerId> {
+        self.timer.as_ref().map(Timer::id)
+    }
+}
+
+/// What a retention rule does to e-mail.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum RetentionTarget {
+    /// Move to the mailbox with this path.
+    Move(String),
+    Delete,
+}
+
+/// E-mail of a mailbox that retention rules apply to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetentionBatch {
+    pub mailbox_hash: MailboxHash,
+    pub target: RetentionTarget,
+    pub env_hashes: Vec<EnvelopeHash>,
+}
+
+/// Group `envelopes`, given by their hash and date, by what `rules` do to
+/// them at `now`.
+///
+/// If several rules apply to an e-mail, the one with the longest age wins, so
+/// that e.g. e-mail can be archived after 90 days and deleted after a year.
+/// E-mail without a date are left alone.
+pub fn plan_retention(
+    rules: &[RetentionRule],
+    envelopes: impl IntoIterator<Item = (EnvelopeHash, UnixTimestamp)>,
+    now: UnixTimestamp,
+) -> IndexMap<RetentionTarget, Vec<EnvelopeHash>> {
+    let mut ret: IndexMap<RetentionTarget, Vec<EnvelopeHash>> = IndexMap::default();
+    if rules.is_empty() {
+        return ret;
+    }
+    for (env_hash, date) in envelopes {
+        if date == 0 {
+            continue;
+        }
+        let age = now.saturating_sub(date);
+        let Some(rule) = rules
+            .iter()
+            .filter(|r| age >= r.older_than_days.saturating_mul(SECONDS_PER_DAY))
+            .max_by_key(|r| r.older_than_days)
+        else {
+            continue;
+        };
+        let target = match rule.action {
+            RetentionAction::Move {
+                ref destination, ..
+            } => RetentionTarget::Move(datetime::timestamp_to_string(
+                date,
+                Some(destination),
+                false,
+            )),
+            RetentionAction::Delete => RetentionTarget::Delete,
+        };
+        ret.entry(target).or_default().push(env_hash);
+    }
+    ret
+}
+
+impl Account {
+    pub(super) fn init_retention(&mut self) {
+        if self
+            .mailbox_entries
+            .values()
+            .all(|e| e.conf.retention.is_empty())
+        {
+            self.retention.timer = None;
+        } else if self.retention.timer.is_none() {
+            self.retention.timer = Some(
+                self.main_loop_handler
+                    .job_executor
+                    .clone()
+                    .create_timer(RETENTION_CHECK_INTERVAL, RETENTION_FIRST_CHECK),
+            );
+        }
+    }
+
+    /// What retention rules would do to the loaded e-mail of the account's
+    /// mailboxes.
+    pub fn retention_plan(&self) -> Vec<RetentionBatch> {
+        let now = datetime::now();
+        let envelopes = self.collection.envelopes.read().unwrap();
+        let mut ret = vec![];
+        for (mailbox_hash, entry) in self.mailbox_entries.iter() {
+            if entry.conf.retention.is_empty() || !entry.status.is_available() {
+                continue;
+            }
+            let mailbox = self.collection.get_mailbox(*mailbox_hash);
+            let plan = plan_retention(
+                &entry.conf.retention,
+                mailbox
+                    .iter()
+                    .filter_map(|h| envelopes.get(h))
+                    .map(|env| (env.hash(), env.date())),
+                now,
+            );
+            ret.extend(
+                plan.into_iter()
+                    .filter(|(target, _)| {
+                        // Archiving to the mailbox itself would be a no-op.
+                        !matches!(target, RetentionTarget::Move(path) if *path == entry.path)
+                    })
+                    .map(|(target, env_hashes)| RetentionBatch {
+                        mailbox_hash: *mailbox_hash,
+                        target,
+                        env_hashes,
+                    }),
+            );
+        }
+        ret
+    }
+
+    /// A report of what retention rules would do, for the `retention-preview`
+    /// command.
+    pub fn retention_report(&self) -> String {
+        let mut ret = format!("Retention rules of account {} (dry run)\n\n", self.name);
+        let plan = self.retention_plan();
+        if plan.is_empty() {
+            if self
+                .mailbox_entries
+                .values()
+                .all(|e| e.conf.retention.is_empty())
+            {
+                ret.push_str("No mailbox has retention rules.\n");
+            } else {
+                ret.push_str("No e-mail of loaded mailboxes is affected.\n");
+            }
+            return ret;
+        }
+        let envelopes = self.collection.envelopes.read().unwrap();
+        for batch in plan {
+            let path = &self.mailbox_entries[&batch.mailbox_hash].path;
+            let n = batch.env_hashes.len();
+            let plural = if n == 1 { "" } else { "s" };
+            match batch.target {
+                RetentionTarget::Move(ref destination) => {
+                    let exists = self.mailbox_by_path(destination).is_ok();
+                    _ = writeln!(
+                        ret,
+                        "{path}: move {n} e-mail{plural} to {destination}{}",
+                        if exists { "" } else { " (will be created)" }
+                    );
+                }
+                RetentionTarget::Delete => {
+                    _ = writeln!(ret, "{path}: delete {n} e-mail{plural}");
+                }
+            }
+            for env in batch.env_hashes.iter().filter_map(|h| envelopes.get(h)) {
+                _ = writeln!(
+                    ret,
+                    "    {}  {}",
+                    datetime::timestamp_to_string(
+                        env.date(),
+                        Some(datetime::formats::RFC3339_DATE),
+                        false
+                    ),
+                    env.subject()
+                );
+            }
+            ret.push('\n');
+        }
+        ret
+    }
+
+    /// Apply retention rules to the loaded e-mail of the account's mailboxes.
+    ///
+    /// Destination mailboxes that do not exist are created, and their e-mail
+    /// are moved the next time rules are applied. Deletions are held for the
+    /// account's `delete_grace_period` like other bulk deletions.
+    pub fn apply_retention(&mut self) {
+        if self.settings.account.read_only {
+            return;
+        }
+        let mut moved = 0;
+        let mut deleted = 0;
+        let mut created: HashSet<String> = HashSet::default();
+        for batch in self.retention_plan() {
+            let Ok(env_hashes) = EnvelopeHashBatch::try_from(batch.env_hashes.as_slice()) else {
+                continue;
+            };
+            if self.is_read_only(batch.mailbox_hash) {
+                continue;
+            }
+            match batch.target {
+                RetentionTarget::Move(destination) => {
+                    let destination_mailbox_hash = match self.mailbox_by_path(&destination) {
+                        Ok(h) => h,
+                        Err(_) => {
+                            if created.insert(destination.clone()) {
+                                if let Err(err) = self.mailbox_operation(
+                                    crate::command::actions::MailboxOperation::Create(
+                                        destination.clone(),
+                                    ),
+                                ) {
+                                    log::error!(
+                                        "{}: could not create retention mailbox {}: {}",
+                                        self.name,
+                                        destination,
+                                        err
+                                    );
+                                }
+                            }
+                            continue;
+                        }
+                    };
+                    match self.move_messages(
+                        env_hashes,
+                        batch.mailbox_hash,
+                        destination_mailbox_hash,
+                    ) {
+                        Ok(_) => moved += batch.env_hashes.len(),
+                        Err(err) => {
+                            log::error!("{}: could not apply retention rules: {}", self.name, err);
+                        }
+                    }
+                }
+                RetentionTarget::Delete => {
+                    // Deletions of the previous application may still be held.
+                    let env_hashes = batch
+                        .env_hashes
+                        .iter()
+                        .copied()
+                        .filter(|h| !self.pending_deletions.contains(batch.mailbox_hash, *h))
+                        .collect::<Vec<EnvelopeHash>>();
+                    if env_hashes.is_empty() {
+                        continue;
+                    }
+                    match self.schedule_deletion(batch.mailbox_hash, &env_hashes) {
+                        Ok(n) => deleted += n,
+                        Err(err) => {
+                            log::error!("{}: could not apply retention rules: {}", self.name, err);
+                        }
+                    }
+                }
+            }
+        }
+        if moved > 0 || deleted > 0 {
+            self.main_loop_handler
+                .send(ThreadEvent::UIEvent(UIEvent::StatusEvent(
+                    StatusEvent::DisplayMessage(format!(
+                        "{}: retention rules moved {} and deleted {} e-mail.",
+                        self.name, moved, deleted
+                    )),
+                )));
+        }
+    }
+}
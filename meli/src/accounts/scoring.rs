@@ -0,0 +1,74 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Scoring of envelopes with the `listing.score_rules` of their mailbox.
+
+use melib::thread::ThreadNodeHash;
+
+use super::*;
+use crate::conf::data_types::ScoreRule;
+
+impl Account {
+    /// Score the envelopes of `mailbox_hash` with `rules`. Envelopes that
+    /// already have a score are skipped, unless `rescore` is true.
+    ///
+    /// Returns whether any score changed.
+    pub fn apply_score_rules(
+        &self,
+        mailbox_hash: MailboxHash,
+        rules: &[ScoreRule],
+        rescore: bool,
+    ) -> bool {
+        let mut changed: Vec<ThreadNodeHash> = vec![];
+        {
+            let mailboxes = self.collection.mailboxes.read().unwrap();
+            let Some(env_hashes) = mailboxes.get(&mailbox_hash) else {
+                return false;
+            };
+            let mut envelopes = self.collection.envelopes.write().unwrap();
+            for env_hash in env_hashes {
+                let Some(envelope) = envelopes.get_mut(env_hash) else {
+                    continue;
+                };
+                if envelope.is_scored() && !rescore {
+                    continue;
+                }
+                let score = Some(ScoreRule::score(rules, envelope));
+                if envelope.score != score {
+                    envelope.set_score(score);
+                    changed.push(envelope.thread());
+                }
+            }
+        }
+        if changed.is_empty() {
+            return false;
+        }
+        // Scores are sortable, so the affected threads have to be re-sorted.
+        if self.collection.has_threads(mailbox_hash) {
+            let threads = self.collection.get_threads(mailbox_hash);
+            for h in changed {
+                threads.mark_node_dirty(h);
+            }
+        }
+        true
+    }
+}
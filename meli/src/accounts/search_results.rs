@@ -0,0 +1,479 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Search results across all mailboxes of an account.
+//!
+//! The `search-tab` command searches every mailbox of the account and lists
+//! the matches in the [`SEARCH_RESULTS_MAILBOX`] virtual mailbox, which is
+//! opened in a new tab. The query can be narrowed down with `refine-search`
+//! and run again with `rerun-search`; the mailbox is updated in place.
+//!
+//! The `correspondence` command is a search for all e-mail exchanged with an
+//! address, see [`Account::search_correspondence`].
+
+use melib::{
+    backends::SpecialUsageMailbox, search::Query, utils::datetime, Address, EnvelopeHash,
+    UnixTimestamp,
+};
+
+use super::*;
+
+/// Path of the mailbox search results are listed under.
+pub const SEARCH_RESULTS_MAILBOX: &str = "Search results";
+
+#[derive(Debug, Default)]
+pub struct SearchResults {
+    /// The query of the last search.
+    pub query: Option<String>,
+    /// The address of the last `correspondence` search, if the last search
+    /// was one.
+    pub correspondent: Option<Address>,
+    job: Option<(JobId, JoinHandle<Result<Vec<EnvelopeHash>>>)>,
+}
+
+impl SearchResults {
+    /// Whether a search is still running.
+    pub fn is_pending(&self) -> bool {
+        self.job.is_some()
+    }
+}
+
+/// Summary of the e-mail exchanged with an address, shown in the status bar of
+/// `correspondence` search results.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CorrespondenceSummary {
+    pub address: Address,
+    pub total: usize,
+    /// E-mail sent by `address`.
+    pub received: usize,
+    /// E-mail sent to `address`.
+    pub sent: usize,
+    pub first: Option<UnixTimestamp>,
+    pub last: Option<UnixTimestamp>,
+}
+
+impl std::fmt::Display for CorrespondenceSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let date = |d: Option<UnixTimestamp>| {
+            d.map(|d| {
+                datetime::timestamp_to_string(d, Some(datetime::formats::RFC3339_DATE), false)
+            })
+            .unwrap_or_else(|| "-".to_string())
+        };
+        write!(
+            f,
+            "{}: {} e-mail ({} received, {} sent), first: {}, last: {}",
+            self.address.get_email(),
+            self.total,
+            self.received,
+            self.sent,
+            date(self.first),
+            date(self.last)
+        )
+    }
+}
+
+/// Combine `query` with `refinement` so that only envelopes matching both
+/// are returned.
+pub fn refine_query(query: &str, refinement: &str) -> String {
+    format!("({}) and ({})", query.trim(), refinement.trim())
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResultsMailbox {
+    hash: MailboxHash,
+    unseen: Arc<Mutex<usize>>,
+    total: Arc<Mutex<usize>>,
+}
+
+impl SearchResultsMailbox {
+    pub fn new() -> Self {
+        Self {
+            hash: Self::mailbox_hash(),
+            unseen: Default::default(),
+            total: Default::default(),
+        }
+    }
+
+    pub fn mailbox_hash() -> MailboxHash {
+        MailboxHash::from_bytes(
+            format!("meli-search-results-mailbox:{SEARCH_RESULTS_MAILBOX}").as_bytes(),
+        )
+    }
+}
+
+impl Default for SearchResultsMailbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BackendMailbox for SearchResultsMailbox {
+    fn hash(&self) -> MailboxHash {
+        self.hash
+    }
+
+    fn name(&self) -> &str {
+        SEARCH_RESULTS_MAILBOX
+    }
+
+    fn path(&self) -> &str {
+        SEARCH_RESULTS_MAILBOX
+    }
+
+    fn children(&self) -> &[MailboxHash] {
+        &[]
+    }
+
+    fn clone(&self) -> Mailbox {
+        Box::new(std::clone::Clone::clone(self))
+    }
+
+    fn special_usage(&self) -> SpecialUsageMailbox {
+        SpecialUsageMailbox::Normal
+    }
+
+    fn parent(&self) -> Option<MailboxHash> {
+        None
+    }
+
+    fn permissions(&self) -> MailboxPermissions {
+        MailboxPermissions {
+            remove_messages: true,
+            set_flags: true,
+            delete_messages: true,
+            delete_mailbox: false,
+            ..MailboxPermissions::default()
+        }
+    }
+
+    fn is_subscribed(&self) -> bool {
+        true
+    }
+
+    fn set_is_subscribed(&mut self, _: bool) -> Result<()> {
+        Err(
+            Error::new("The search results mailbox is always subscribed.")
+                .set_kind(ErrorKind::NotSupported),
+        )
+    }
+
+    fn set_special_usage(&mut self, _: SpecialUsageMailbox) -> Result<()> {
+        Err(
+            Error::new("The search results mailbox cannot have a special usage.")
+                .set_kind(ErrorKind::NotSupported),
+        )
+    }
+
+    fn count(&self) -> Result<(usize, usize)> {
+        Ok((*self.unseen.lock()?, *self.total.lock()?))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+impl Account {
+    pub fn is_search_results_mailbox(&self, mailbox_hash: MailboxHash) -> bool {
+        self.mailbox_entries.get(&mailbox_hash).is_some_and(|e| {
+            e.ref_mailbox
+                .as_any()
+                .downcast_ref::<SearchResultsMailbox>()
+                .is_some()
+        })
+    }
+
+    /// Search all mailboxes of the account for `search_term`.
+    ///
+    /// Unlike [`Account::search`], the result is not restricted to a single
+    /// mailbox. Virtual mailboxes are not searched since their envelopes are
+    /// stored in other mailboxes.
+    pub fn search_all(&self, search_term: &str) -> ResultFuture<Vec<EnvelopeHash>> {
+        let query = Query::try_from(search_term)?;
+        match self.settings.conf.search_backend {
+            #[cfg(feature = "sqlite3")]
+            SearchBackend::Sqlite3 => Ok(Box::pin(crate::sqlite3::AccountCache::search(
+                self.name.clone(),
+                query,
+                melib::SortChain::default(),
+            ))),
+            SearchBackend::Auto | SearchBackend::None => {
+                let mailboxes = self
+                    .mailboxes_order
+                    .iter()
+                    .copied()
+                    .filter(|h| !self.is_virtual_mailbox(*h))
+                    .collect::<Vec<MailboxHash>>();
+                if self.backend_capabilities.supports_search {
+                    let mut backend = self.backend.lock().unwrap();
+                    let futures = mailboxes
+                        .into_iter()
+                        .map(|h| backend.search(query.clone(), Some(h)))
+                        .collect::<Result<Vec<_>>>()?;
+                    Ok(Box::pin(async move {
+                        let mut ret = vec![];
+                        for results in futures::future::try_join_all(futures).await? {
+                            ret.extend(results);
+                        }
+                        Ok(ret)
+                    }))
+                } else {
+                    use melib::search::QueryTrait;
+                    let mailboxes_lck = self.collection.mailboxes.read().unwrap();
+                    let envelopes = self.collection.envelopes.read().unwrap();
+                    let ret = mailboxes
+                        .iter()
+                        .filter_map(|h| mailboxes_lck.get(h))
+                        .flatten()
+                        .copied()
+                        .filter(|h| envelopes.get(h).is_some_and(|env| env.is_match(&query)))
+                        .collect::<Vec<EnvelopeHash>>();
+                    Ok(Box::pin(async { Ok(ret) }))
+                }
+            }
+        }
+    }
+
+    /// Search all mailboxes for `search_term` and list the results in the
+    /// [`SEARCH_RESULTS_MAILBOX`] mailbox, replacing any previous results.
+    ///
+    /// The mailbox is created immediately, so it can be opened before the
+    /// search has finished. Returns its hash.
+    pub fn search_in_results_mailbox(&mut self, search_term: &str) -> Result<MailboxHash> {
+        self.start_search(search_term)?;
+        self.search_results.correspondent = None;
+        Ok(SearchResultsMailbox::mailbox_hash())
+    }
+
+    /// Search all mailboxes for e-mail from or to `address`, like
+    /// [`Account::search_in_results_mailbox`].
+    ///
+    /// A summary of the results is available with
+    /// [`Account::correspondence_summary`].
+    pub fn search_correspondence(&mut self, address: &Address) -> Result<MailboxHash> {
+        self.start_search(&format!(
+            "all-addresses:\"{}\"",
+            address.get_email().replace('"', "")
+        ))?;
+        self.search_results.correspondent = Some(address.clone());
+        Ok(SearchResultsMailbox::mailbox_hash())
+    }
+
+    fn start_search(&mut self, search_term: &str) -> Result<()> {
+        let job = self.search_all(search_term)?;
+        let handle =
+            self.main_loop_handler
+                .job_executor
+                .spawn("search-all".into(), job, self.is_async());
+        if let Some((_, previous)) = self.search_results.job.replace((handle.job_id, handle)) {
+            if let Some(canceled) = previous.cancel() {
+                self.main_loop_handler
+                    .send(ThreadEvent::UIEvent(UIEvent::StatusEvent(canceled)));
+            }
+        }
+        self.search_results.query = Some(search_term.to_string());
+        self.update_search_results_mailbox(None);
+        Ok(())
+    }
+
+    /// Narrow down the last search by also requiring `refinement` to match.
+    pub fn refine_search(&mut self, refinement: &str) -> Result<MailboxHash> {
+        let Some(query) = self.search_results.query.as_deref() else {
+            return Err(Error::new("There is no search to refine.").set_kind(ErrorKind::NotFound));
+        };
+        let query = refine_query(query, refinement);
+        self.start_search(&query)?;
+        Ok(SearchResultsMailbox::mailbox_hash())
+    }
+
+    /// Run the last search again, e.g. to include newly arrived e-mail.
+    pub fn rerun_search(&mut self) -> Result<MailboxHash> {
+        let Some(query) = self.search_results.query.clone() else {
+            return Err(
+                Error::new("There is no search to run again.").set_kind(ErrorKind::NotFound)
+            );
+        };
+        self.start_search(&query)?;
+        Ok(SearchResultsMailbox::mailbox_hash())
+    }
+
+    /// Summarize the results of the last `correspondence` search, if the
+    /// last search was one.
+    pub fn correspondence_summary(&self) -> Option<CorrespondenceSummary> {
+        let address = self.search_results.correspondent.clone()?;
+        let mailbox_hash = SearchResultsMailbox::mailbox_hash();
+        if !self.is_search_results_mailbox(mailbox_hash) {
+            return None;
+        }
+        let mut ret = CorrespondenceSummary {
+            address,
+            total: 0,
+            received: 0,
+            sent: 0,
+            first: None,
+            last: None,
+        };
+        let email = ret.address.get_email();
+        let envelopes = self.collection.envelopes.read().unwrap();
+        for env in self
+            .collection
+            .get_mailbox(mailbox_hash)
+            .iter()
+            .filter_map(|h| envelopes.get(h))
+        {
+            ret.total += 1;
+            if env
+                .from()
+                .iter()
+                .any(|a| a.get_email().eq_ignore_ascii_case(&email))
+            {
+                ret.received += 1;
+            } else {
+                ret.sent += 1;
+            }
+            let date = env.date();
+            ret.first = Some(ret.first.map_or(date, |d| d.min(date)));
+            ret.last = Some(ret.last.map_or(date, |d| d.max(date)));
+        }
+        Some(ret)
+    }
+
+    /// Handle the completion of a job spawned by
+    /// [`Account::search_in_results_mailbox`].
+    ///
+    /// Returns `false` if `job_id` is not a search job.
+    pub(super) fn process_search_results_event(&mut self, job_id: JobId) -> bool {
+        if !self
+            .search_results
+            .job
+            .as_ref()
+            .is_some_and(|(id, _)| *id == job_id)
+        {
+            return false;
+        }
+        let (_, mut handle) = self.search_results.job.take().unwrap();
+        match handle.chan.try_recv() {
+            Ok(Some(Ok(results))) => {
+                self.update_search_results_mailbox(Some(results));
+            }
+            Ok(Some(Err(err))) => {
+                self.main_loop_handler
+                    .job_executor
+                    .set_job_success(job_id, false);
+                self.main_loop_handler
+                    .send(ThreadEvent::UIEvent(UIEvent::Notification {
+                        title: Some(format!("{}: could not perform search", self.name).into()),
+                        source: None,
+                        body: err.to_string().into(),
+                        kind: Some(NotificationType::Error(err.kind)),
+                    }));
+            }
+            Err(_) | Ok(None) => {}
+        }
+        true
+    }
+
+    /// Create the [`SEARCH_RESULTS_MAILBOX`] mailbox if it doesn't exist and,
+    /// if `results` is `Some`, replace its contents.
+    fn update_search_results_mailbox(&mut self, results: Option<Vec<EnvelopeHash>>) {
+        let mailbox_hash = SearchResultsMailbox::mailbox_hash();
+        let mut created = false;
+        if !self.is_search_results_mailbox(mailbox_hash) {
+            self.mailbox_entries.insert(
+                mailbox_hash,
+                MailboxEntry::new(
+                    MailboxStatus::Available,
+                    SEARCH_RESULTS_MAILBOX.to_string(),
+                    Box::new(SearchResultsMailbox::new()),
+                    FileMailboxConf::default(),
+                ),
+            );
+            self.collection.new_mailbox(mailbox_hash);
+            build_mailboxes_order(
+                &mut self.tree,
+                &self.mailbox_entries,
+                &mut self.mailboxes_order,
+            );
+            created = true;
+        }
+        let mut changed = created;
+        if let Some(results) = results {
+            let results = {
+                // Search backends may return envelopes that have not been
+                // loaded.
+                let envelopes = self.collection.envelopes.read().unwrap();
+                results
+                    .into_iter()
+                    .filter(|h| envelopes.contains_key(h))
+                    .collect::<HashSet<EnvelopeHash>>()
+            };
+            let mut membership = self
+                .collection
+                .get_mailbox(mailbox_hash)
+                .iter()
+                .filter(|h| !results.contains(*h))
+                .map(|h| (*h, false))
+                .collect::<Vec<(EnvelopeHash, bool)>>();
+            membership.extend(results.into_iter().map(|h| (h, true)));
+            changed |= self
+                .collection
+                .set_mailbox_membership(mailbox_hash, membership);
+        }
+        let (unseen, total) = {
+            let envelopes = self.collection.envelopes.read().unwrap();
+            let mailbox = self.collection.get_mailbox(mailbox_hash);
+            (
+                mailbox
+                    .iter()
+                    .filter(|h| envelopes.get(*h).is_some_and(|env| !env.is_seen()))
+                    .count(),
+                mailbox.len(),
+            )
+        };
+        if let Some(mailbox) = self.mailbox_entries.get(&mailbox_hash).and_then(|e| {
+            e.ref_mailbox
+                .as_any()
+                .downcast_ref::<SearchResultsMailbox>()
+        }) {
+            changed |= std::mem::replace(&mut *mailbox.unseen.lock().unwrap(), unseen) != unseen;
+            changed |= std::mem::replace(&mut *mailbox.total.lock().unwrap(), total) != total;
+        }
+        if created {
+            self.main_loop_handler
+                .send(ThreadEvent::UIEvent(UIEvent::MailboxCreate((
+                    self.hash,
+                    mailbox_hash,
+                ))));
+        } else if changed {
+            self.main_loop_handler
+                .send(ThreadEvent::UIEvent(UIEvent::MailboxUpdate((
+                    self.hash,
+                    mailbox_hash,
+                ))));
+        }
+    }
+}
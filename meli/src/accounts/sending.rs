@@ -0,0 +1,89 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Sending e-mail with a `send_mail` shell command.
+//!
+//! The command gets the message on its standard input, like `sendmail(8)`
+//! and compatible programs such as `msmtp`. Its standard error is included in
+//! the error when it fails, so that the reason shows up in the notification
+//! log.
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use super::*;
+use crate::CommandPolicy;
+
+/// How long to wait before retrying to send an e-mail, if the account's
+/// `send_mail_retry_delay` is not set.
+pub const DEFAULT_SEND_MAIL_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Pipe `message` to `sh -c command` and wait for it to exit.
+pub fn send_with_shell_command(
+    command: &str,
+    message: &str,
+    command_policy: &CommandPolicy,
+) -> Result<()> {
+    if command.is_empty() {
+        return Err(Error::new(
+            "send_mail shell command configuration value is empty",
+        ));
+    }
+    let mut mailer = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_checked(command_policy)
+        .map_err(|err| Error::from(err).set_summary("Failed to start mailer command"))?;
+    if let Some(mut stdin) = mailer.stdin.take() {
+        stdin.write_all(message.as_bytes()).map_err(|err| {
+            Error::new(format!("Could not write e-mail to `{command}`"))
+                .set_source(Some(Arc::new(err)))
+                .set_summary("Message not sent.")
+        })?;
+    }
+    let output = mailer.wait_with_output().map_err(|err| {
+        Error::new(format!("Could not wait on `{command}`"))
+            .set_source(Some(Arc::new(err)))
+            .set_summary("Message not sent.")
+    })?;
+    if output.status.success() {
+        log::trace!("Message sent.");
+        return Ok(());
+    }
+    let mut error_message = if let Some(exit_code) = output.status.code() {
+        format!("Could not send e-mail using `{command}`: Process exited with {exit_code}")
+    } else {
+        format!("Could not send e-mail using `{command}`: Process was killed by signal")
+    };
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr.trim();
+    if !stderr.is_empty() {
+        error_message.push_str(": ");
+        error_message.push_str(stderr);
+    }
+    log::error!("{}", error_message);
+    Err(Error::new(error_message).set_summary("Message not sent."))
+}
@@ -0,0 +1,277 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Local filtering of new e-mail with a Sieve script.
+//!
+//! Accounts with a `sieve_script` evaluate it on new unseen e-mail of their
+//! inbox, see [`Account::sieve_filter`]. The e-mail is fetched and evaluated
+//! in a job, and when it finishes the resulting actions are applied:
+//! `fileinto` saves a copy to the named mailbox, `redirect` sends a copy with
+//! the account's `send_mail`, and unless the e-mail is also kept it is then
+//! deleted from the inbox, once all copies have been saved and sent.
+
+use melib::{
+    email::{parser, HeaderName},
+    sieve::{ActionCommand, Script},
+    utils::datetime,
+    EnvelopeHash,
+};
+
+use super::*;
+
+#[derive(Debug, Default)]
+pub struct SieveFilter {
+    script: Option<Arc<Script>>,
+    #[allow(clippy::type_complexity)]
+    jobs: HashMap<
+        JobId,
+        (
+            EnvelopeHash,
+            MailboxHash,
+            JoinHandle<Result<(Vec<ActionCommand>, Vec<u8>)>>,
+        ),
+    >,
+}
+
+/// Make the message that redirects `message` to `address`.
+///
+/// `Resent-*` fields are prepended as described in RFC 5322 section 3.6.6.
+/// Since `send_mail` takes the recipients from the header, the original `To`
+/// and `Cc` fields are renamed to `X-Original-To` and `X-Original-Cc`, any
+/// `Bcc` field is removed, and `address` becomes the only recipient.
+pub fn redirect_message(message: &[u8], from: &str, address: &str, date: &str) -> Result<String> {
+    let (headers, body) = parser::mail(message)?;
+    let mut ret = format!("Resent-From: {from}\r\nResent-Date: {date}\r\nResent-To: {address}\r\n");
+    for (name, value) in headers {
+        let name = if name == HeaderName::TO {
+            "X-Original-To"
+        } else if name == HeaderName::CC {
+            "X-Original-Cc"
+        } else if name == HeaderName::BCC {
+            continue;
+        } else {
+            name.as_str()
+        };
+        ret.push_str(name);
+        ret.push_str(": ");
+        ret.push_str(&String::from_utf8_lossy(value));
+        ret.push_str("\r\n");
+    }
+    ret.push_str("To: ");
+    ret.push_str(address);
+    ret.push_str("\r\n\r\n");
+    ret.push_str(&String::from_utf8_lossy(body));
+    Ok(ret)
+}
+
+impl Account {
+    pub(super) fn init_sieve(&mut self) {
+        self.sieve.script = None;
+        let Some(path) = self.settings.conf.sieve_script.as_ref() else {
+            return;
+        };
+        match crate::conf::load_sieve_script(path) {
+            Ok(script) => self.sieve.script = Some(Arc::new(script)),
+            Err(err) => {
+                log::error!("{}: could not load sieve script: {}", self.name, err);
+                self.main_loop_handler
+                    .send(ThreadEvent::UIEvent(UIEvent::StatusEvent(
+                        StatusEvent::DisplayMessage(format!(
+                            "{}: could not load sieve script: {}",
+                            self.name, err.summary
+                        )),
+                    )));
+            }
+        }
+    }
+
+    /// Evaluate the account's sieve script on the new e-mail `env_hash` of
+    /// `mailbox_hash`, if it is the account's inbox.
+    ///
+    /// If the account has no inbox, e-mail of its default mailbox are
+    /// filtered instead.
+    pub fn sieve_filter(&mut self, env_hash: EnvelopeHash, mailbox_hash: MailboxHash) {
+        let Some(script) = self.sieve.script.clone() else {
+            return;
+        };
+        if self
+            .special_use_mailbox(SpecialUsageMailbox::Inbox)
+            .or(self.settings.default_mailbox)
+            != Some(mailbox_hash)
+        {
+            return;
+        }
+        let bytes_fut = match self.envelope_bytes_by_hash(env_hash) {
+            Ok(fut) => fut,
+            Err(err) => {
+                log::error!("{}: could not filter e-mail: {}", self.name, err);
+                return;
+            }
+        };
+        let handle = self.main_loop_handler.job_executor.spawn(
+            "sieve".into(),
+            async move {
+                let bytes = bytes_fut.await?;
+                let actions = script.evaluate(&bytes)?;
+                Ok((actions, bytes))
+            },
+            self.is_async(),
+        );
+        self.sieve
+            .jobs
+            .insert(handle.job_id, (env_hash, mailbox_hash, handle));
+    }
+
+    /// Handle the completion of a job spawned by [`Account::sieve_filter`].
+    ///
+    /// Returns `false` if `job_id` is not a sieve job.
+    pub(super) fn process_sieve_event(&mut self, job_id: JobId) -> bool {
+        let Some((env_hash, mailbox_hash, mut handle)) = self.sieve.jobs.remove(&job_id) else {
+            return false;
+        };
+        match handle.chan.try_recv() {
+            Ok(Some(Ok((actions, bytes)))) => {
+                if let Err(err) = self.apply_sieve_actions(env_hash, mailbox_hash, actions, bytes) {
+                    self.sieve_error(err);
+                }
+            }
+            Ok(Some(Err(err))) => self.sieve_error(err),
+            Err(_) | Ok(None) => {}
+        }
+        true
+    }
+
+    fn sieve_error(&self, err: Error) {
+        log::error!("{}: sieve script failed: {}", self.name, err);
+        self.main_loop_handler
+            .send(ThreadEvent::UIEvent(UIEvent::Notification {
+                title: Some(format!("{}: sieve script failed", self.name).into()),
+                body: err.to_string().into(),
+                kind: Some(NotificationType::Error(err.kind)),
+                source: Some(err),
+            }));
+    }
+
+    /// Apply `actions` to the e-mail `env_hash` of `mailbox_hash`.
+    ///
+    /// Copies are saved and redirects sent in a single job. Unless the e-mail
+    /// is also kept, it is deleted from the inbox with
+    /// [`Account::schedule_deletion`] only once every copy has been saved
+    /// and every redirect has been sent, so that a failure never loses it.
+    fn apply_sieve_actions(
+        &mut self,
+        env_hash: EnvelopeHash,
+        mailbox_hash: MailboxHash,
+        actions: Vec<ActionCommand>,
+        bytes: Vec<u8>,
+    ) -> Result<()> {
+        if !self.contains_key(env_hash) {
+            // It was removed while the script was evaluated.
+            return Ok(());
+        }
+        let flags = self.collection.get_env(env_hash).flags();
+        let mut keep = false;
+        let mut jobs: Vec<Pin<Box<dyn Future<Output = Result<()>> + Send>>> = vec![];
+        for action in actions {
+            match action {
+                ActionCommand::Keep => keep = true,
+                ActionCommand::Fileinto { mailbox } => {
+                    let destination = self.mailbox_by_path(&mailbox)?;
+                    if destination == mailbox_hash {
+                        keep = true;
+                    } else {
+                        self.check_writable(Some(destination))?;
+                        jobs.push(self.backend.lock().unwrap().save(
+                            bytes.clone(),
+                            destination,
+                            Some(flags),
+                        )?);
+                    }
+                }
+                ActionCommand::Redirect { address } => {
+                    let message = redirect_message(
+                        &bytes,
+                        &self.settings.account.main_identity_address().to_string(),
+                        &address,
+                        &datetime::timestamp_to_string(
+                            datetime::now(),
+                            Some(datetime::formats::RFC822_DATE),
+                            true,
+                        ),
+                    )?;
+                    let send_cb = self.send_async(self.settings.send_mail.clone());
+                    jobs.push(send_cb(Arc::new(message)));
+                }
+                ActionCommand::Discard => {}
+            }
+        }
+        if !keep {
+            self.check_writable(Some(mailbox_hash))?;
+        }
+        if jobs.is_empty() {
+            if !keep {
+                self.schedule_deletion(mailbox_hash, &[env_hash])?;
+            }
+            return Ok(());
+        }
+        let succeeded = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let fut = {
+            let succeeded = succeeded.clone();
+            async move {
+                for fut in jobs {
+                    fut.await?;
+                }
+                succeeded.store(true, std::sync::atomic::Ordering::SeqCst);
+                Ok(())
+            }
+        };
+        let handle =
+            self.main_loop_handler
+                .job_executor
+                .spawn("sieve-actions".into(), fut, IsAsync::Async);
+        let account_hash = self.hash;
+        let on_finish = crate::types::CallbackFn(Box::new(move |context: &mut crate::Context| {
+            if keep || !succeeded.load(std::sync::atomic::Ordering::SeqCst) {
+                return;
+            }
+            let Some(account) = context.accounts.get_mut(&account_hash) else {
+                return;
+            };
+            if !account.contains_key(env_hash) {
+                return;
+            }
+            if let Err(err) = account.schedule_deletion(mailbox_hash, &[env_hash]) {
+                account.sieve_error(err);
+            }
+        }));
+        self.insert_job(
+            handle.job_id,
+            JobRequest::Generic {
+                name: "sieve script actions".into(),
+                handle,
+                on_finish: Some(on_finish),
+                log_level: LogLevel::INFO,
+            },
+        );
+        Ok(())
+    }
+}
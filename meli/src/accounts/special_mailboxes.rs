@@ -0,0 +1,238 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Mapping of the sent, drafts, trash, junk and archive mailboxes of an
+//! account.
+//!
+//! The mailbox of each special usage is taken, in order of precedence, from
+//! the account's `special_mailboxes` setting, from the choices made in the
+//! `special-mailboxes` dialog, from the `SPECIAL-USE` attributes the backend
+//! reports and from the mailbox names. If more than one mailbox is detected
+//! for a usage and none of them has a `SPECIAL-USE` attribute, the user is
+//! asked to choose one. Choices are kept in a `special_mailboxes` file in the
+//! account's data directory.
+
+use crate::{
+    command::actions::{AccountAction, Action},
+    components::ComponentId,
+    conf::SpecialMailboxes,
+    utilities::UIDialog,
+    Context,
+};
+
+use super::*;
+
+/// The `special_mailboxes` file of an account.
+pub const SPECIAL_MAILBOXES_FILE: StateFile = StateFile {
+    description: "special mailboxes",
+    name: "special_mailboxes",
+    steps: &[from_unversioned],
+};
+
+impl Account {
+    /// The special mailboxes chosen in the `special-mailboxes` dialog.
+    fn saved_special_mailboxes(&self) -> SpecialMailboxes {
+        match SPECIAL_MAILBOXES_FILE.load(&self.name) {
+            Ok(mailboxes) => mailboxes.unwrap_or_default(),
+            Err(err) => {
+                log::warn!(
+                    "Could not read special mailboxes of account {}: {}",
+                    self.name,
+                    err
+                );
+                SpecialMailboxes::default()
+            }
+        }
+    }
+
+    /// Settle which of `entries` is used for each special usage.
+    ///
+    /// `special_use` are the mailboxes whose usage was reported by the
+    /// backend instead of detected from their name. Returns the usages that
+    /// are ambiguous.
+    pub(super) fn resolve_special_mailboxes(
+        &mut self,
+        entries: &mut IndexMap<MailboxHash, MailboxEntry>,
+        special_use: &HashSet<MailboxHash>,
+    ) -> Vec<SpecialUsageMailbox> {
+        let saved = self.saved_special_mailboxes();
+        let mut ambiguous = vec![];
+        for usage in SpecialMailboxes::USAGES {
+            if let Some(path) = self.settings.conf.special_mailboxes.get(usage) {
+                if let Some(mailbox_hash) = find_mailbox(entries, path) {
+                    assign_special_usage(entries, usage, mailbox_hash);
+                    continue;
+                }
+                let message = format!(
+                    "Account `{}` has its {} mailbox set as `{path}` in special_mailboxes but it \
+                     doesn't exist.",
+                    self.name,
+                    usage.to_string().to_lowercase()
+                );
+                log::warn!("{message}");
+                self.main_loop_handler
+                    .send(ThreadEvent::UIEvent(UIEvent::Notification {
+                        title: None,
+                        source: None,
+                        body: message.into(),
+                        kind: None,
+                    }));
+            }
+            if let Some(mailbox_hash) = saved.get(usage).and_then(|p| find_mailbox(entries, p)) {
+                assign_special_usage(entries, usage, mailbox_hash);
+                continue;
+            }
+            let candidates = entries
+                .iter()
+                .filter(|(_, e)| e.conf.mailbox_conf.usage == Some(usage))
+                .map(|(h, _)| *h)
+                .collect::<Vec<MailboxHash>>();
+            if candidates.len() < 2 {
+                continue;
+            }
+            let reported = candidates
+                .into_iter()
+                .filter(|h| special_use.contains(h))
+                .collect::<Vec<MailboxHash>>();
+            if let [mailbox_hash] = reported.as_slice() {
+                assign_special_usage(entries, usage, *mailbox_hash);
+            } else {
+                ambiguous.push(usage);
+            }
+        }
+        if let Some((mailbox_hash, _)) = entries
+            .iter()
+            .find(|(_, e)| e.conf.mailbox_conf.usage == Some(SpecialUsageMailbox::Sent))
+        {
+            self.settings.sent_mailbox = Some(*mailbox_hash);
+        }
+        ambiguous
+    }
+
+    /// Use mailbox `path` for `usage` and remember the choice for the next
+    /// sessions.
+    pub fn set_special_mailbox(&mut self, usage: SpecialUsageMailbox, path: &str) -> Result<()> {
+        let Some(mailbox_hash) = find_mailbox(&self.mailbox_entries, path) else {
+            return Err(Error::new(format!(
+                "Account `{}` has no mailbox `{path}`.",
+                self.name
+            )));
+        };
+        assign_special_usage(&mut self.mailbox_entries, usage, mailbox_hash);
+        if usage == SpecialUsageMailbox::Sent {
+            self.settings.sent_mailbox = Some(mailbox_hash);
+        }
+        let mut saved = self.saved_special_mailboxes();
+        saved.set(usage, path.to_string());
+        SPECIAL_MAILBOXES_FILE.save(&self.name, &saved)
+    }
+}
+
+fn find_mailbox(entries: &IndexMap<MailboxHash, MailboxEntry>, path: &str) -> Option<MailboxHash> {
+    entries
+        .iter()
+        .find(|(_, e)| e.path == path)
+        .map(|(h, _)| *h)
+}
+
+/// Mark `mailbox_hash` as the only mailbox of `entries` with `usage`.
+fn assign_special_usage(
+    entries: &mut IndexMap<MailboxHash, MailboxEntry>,
+    usage: SpecialUsageMailbox,
+    mailbox_hash: MailboxHash,
+) {
+    for (h, entry) in entries.iter_mut() {
+        let new_usage = if *h == mailbox_hash {
+            usage
+        } else if entry.conf.mailbox_conf.usage == Some(usage) {
+            SpecialUsageMailbox::Normal
+        } else {
+            continue;
+        };
+        entry.conf.mailbox_conf.usage = Some(new_usage);
+        let _ = entry.ref_mailbox.set_special_usage(new_usage);
+    }
+}
+
+/// Open a dialog for each of `usages` to choose the mailbox of account
+/// `account_hash` to use for it. Mailboxes currently marked with the usage
+/// are listed first.
+pub fn special_mailboxes_dialog(
+    context: &mut Context,
+    account_hash: AccountHash,
+    usages: &[SpecialUsageMailbox],
+) {
+    let Some(account) = context.accounts.get(&account_hash) else {
+        return;
+    };
+    let account_name = account.name().to_string();
+    let mut dialogs = Vec::with_capacity(usages.len());
+    for &usage in usages {
+        let mut entries = account
+            .mailboxes_order
+            .iter()
+            .filter(|h| !account.is_virtual_mailbox(**h))
+            .filter_map(|h| account.mailbox_entries.get(h))
+            .map(|e| (e.conf.mailbox_conf.usage != Some(usage), e.path.clone()))
+            .collect::<Vec<(bool, String)>>();
+        entries.sort_by_key(|(other, _)| *other);
+        let entries = entries
+            .into_iter()
+            .map(|(other, path)| {
+                let title = if other {
+                    path.clone()
+                } else {
+                    format!("{path} (detected)")
+                };
+                (path, title)
+            })
+            .collect::<Vec<(String, String)>>();
+        if entries.is_empty() {
+            continue;
+        }
+        let account_name = account_name.clone();
+        dialogs.push(UIDialog::new(
+            &format!(
+                "{account_name}: select {} mailbox",
+                usage.to_string().to_lowercase()
+            ),
+            entries,
+            true,
+            Some(Box::new(move |id: ComponentId, results: &[String]| {
+                Some(UIEvent::FinishedUIDialog(
+                    id,
+                    Box::new(Some(Action::AccountAction(
+                        account_name,
+                        AccountAction::SetSpecialMailbox(usage, results.first().cloned()?),
+                    ))),
+                ))
+            })),
+            context,
+        ));
+    }
+    for dialog in dialogs {
+        context.replies.push_back(UIEvent::GlobalUIDialog {
+            value: Box::new(dialog),
+            parent: None,
+        });
+    }
+}
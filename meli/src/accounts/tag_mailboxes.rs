@@ -0,0 +1,352 @@
+//
+// meli - accounts module.
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Virtual mailboxes generated from e-mail tags.
+//!
+//! For backends that support tags, and if the account's `tag_mailboxes`
+//! setting is enabled, every tag gets a [`TagMailbox`] listed under a `Tags`
+//! mailbox. Tag mailboxes are not known to the backend: their contents are
+//! kept up to date from the envelopes of the account's other mailboxes, and
+//! operations on their envelopes are submitted for the mailboxes the
+//! envelopes are actually stored in, see [`Account::backing_mailboxes`].
+
+use melib::{backends::SpecialUsageMailbox, EnvelopeHash};
+
+use super::*;
+
+/// Path of the mailbox tag mailboxes are listed under.
+pub const TAG_MAILBOXES_ROOT: &str = "Tags";
+
+#[derive(Clone, Debug)]
+pub struct TagMailbox {
+    hash: MailboxHash,
+    /// The tag this mailbox is made of, `None` for the
+    /// [`TAG_MAILBOXES_ROOT`] mailbox.
+    tag: Option<TagHash>,
+    name: String,
+    path: String,
+    parent: Option<MailboxHash>,
+    children: Vec<MailboxHash>,
+    unseen: Arc<Mutex<usize>>,
+    total: Arc<Mutex<usize>>,
+}
+
+impl TagMailbox {
+    pub fn root() -> Self {
+        Self {
+            hash: Self::root_hash(),
+            tag: None,
+            name: TAG_MAILBOXES_ROOT.to_string(),
+            path: TAG_MAILBOXES_ROOT.to_string(),
+            parent: None,
+            children: vec![],
+            unseen: Default::default(),
+            total: Default::default(),
+        }
+    }
+
+    pub fn new(tag: &str) -> Self {
+        let path = format!("{TAG_MAILBOXES_ROOT}/{tag}");
+        Self {
+            hash: MailboxHash::from_bytes(format!("meli-tag-mailbox:{path}").as_bytes()),
+            tag: Some(TagHash::from_bytes(tag.as_bytes())),
+            name: tag.to_string(),
+            path,
+            parent: Some(Self::root_hash()),
+            children: vec![],
+            unseen: Default::default(),
+            total: Default::default(),
+        }
+    }
+
+    pub fn root_hash() -> MailboxHash {
+        MailboxHash::from_bytes(format!("meli-tag-mailbox:{TAG_MAILBOXES_ROOT}").as_bytes())
+    }
+
+    pub fn tag(&self) -> Option<TagHash> {
+        self.tag
+    }
+}
+
+impl BackendMailbox for TagMailbox {
+    fn hash(&self) -> MailboxHash {
+        self.hash
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn children(&self) -> &[MailboxHash] {
+        &self.children
+    }
+
+    fn clone(&self) -> Mailbox {
+        Box::new(std::clone::Clone::clone(self))
+    }
+
+    fn special_usage(&self) -> SpecialUsageMailbox {
+        SpecialUsageMailbox::Normal
+    }
+
+    fn parent(&self) -> Option<MailboxHash> {
+        self.parent
+    }
+
+    fn permissions(&self) -> MailboxPermissions {
+        MailboxPermissions {
+            remove_messages: self.tag.is_some(),
+            set_flags: self.tag.is_some(),
+            delete_messages: self.tag.is_some(),
+            delete_mailbox: false,
+            ..MailboxPermissions::default()
+        }
+    }
+
+    fn is_subscribed(&self) -> bool {
+        true
+    }
+
+    fn set_is_subscribed(&mut self, _: bool) -> Result<()> {
+        Err(Error::new("Tag mailboxes are always subscribed.").set_kind(ErrorKind::NotSupported))
+    }
+
+    fn set_special_usage(&mut self, _: SpecialUsageMailbox) -> Result<()> {
+        Err(Error::new("Tag mailboxes cannot have a special usage.")
+            .set_kind(ErrorKind::NotSupported))
+    }
+
+    fn count(&self) -> Result<(usize, usize)> {
+        Ok((*self.unseen.lock()?, *self.total.lock()?))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+impl Account {
+    /// Whether tag mailboxes are enabled for this account and its backend
+    /// supports tags.
+    pub fn tag_mailboxes_enabled(&self) -> bool {
+        self.settings.conf.tag_mailboxes && self.backend_capabilities.supports_tags
+    }
+
+    fn tag_mailbox(&self, mailbox_hash: MailboxHash) -> Option<&TagMailbox> {
+        self.mailbox_entries
+            .get(&mailbox_hash)?
+            .ref_mailbox
+            .as_any()
+            .downcast_ref::<TagMailbox>()
+    }
+
+    pub fn is_tag_mailbox(&self, mailbox_hash: MailboxHash) -> bool {
+        self.tag_mailbox(mailbox_hash).is_some()
+    }
+
+    /// Whether `mailbox_hash` is a mailbox that is not known to the backend:
+    /// a tag mailbox, the [`AWAIT_REPLY_MAILBOX`] mailbox or the
+    /// [`SEARCH_RESULTS_MAILBOX`] mailbox.
+    pub fn is_virtual_mailbox(&self, mailbox_hash: MailboxHash) -> bool {
+        self.is_tag_mailbox(mailbox_hash)
+            || self.is_await_reply_mailbox(mailbox_hash)
+            || self.is_search_results_mailbox(mailbox_hash)
+    }
+
+    /// The tag `mailbox_hash` is made of, if it is a tag mailbox.
+    pub fn tag_mailbox_tag(&self, mailbox_hash: MailboxHash) -> Option<TagHash> {
+        self.tag_mailbox(mailbox_hash)?.tag()
+    }
+
+    /// Group `env_hashes` by the backend mailbox each envelope is stored in.
+    ///
+    /// If `mailbox_hash` is not a virtual mailbox, `env_hashes` are returned as
+    /// they are.
+    pub fn backing_mailboxes(
+        &self,
+        mailbox_hash: MailboxHash,
+        env_hashes: &[EnvelopeHash],
+    ) -> Vec<(MailboxHash, SmallVec<[EnvelopeHash; 8]>)> {
+        if !self.is_virtual_mailbox(mailbox_hash) {
+            return vec![(mailbox_hash, env_hashes.iter().copied().collect())];
+        }
+        let mailboxes_lck = self.collection.mailboxes.read().unwrap();
+        let mut ret: IndexMap<MailboxHash, SmallVec<[EnvelopeHash; 8]>> = IndexMap::default();
+        for &env_hash in env_hashes {
+            if let Some(&backing_hash) = self.mailboxes_order.iter().find(|h| {
+                !self.is_virtual_mailbox(**h)
+                    && mailboxes_lck
+                        .get(*h)
+                        .is_some_and(|envs| envs.contains(&env_hash))
+            }) {
+                ret.entry(backing_hash).or_default().push(env_hash);
+            }
+        }
+        ret.into_iter().collect()
+    }
+
+    fn insert_tag_mailbox(&mut self, mailbox: TagMailbox) {
+        let mailbox_hash = mailbox.hash;
+        if let Some(parent) = mailbox
+            .parent
+            .and_then(|h| self.mailbox_entries.get_mut(&h))
+            .and_then(|e| e.ref_mailbox.as_any_mut().downcast_mut::<TagMailbox>())
+        {
+            parent.children.push(mailbox_hash);
+        }
+        self.mailbox_entries.insert(
+            mailbox_hash,
+            MailboxEntry::new(
+                MailboxStatus::Available,
+                mailbox.path.clone(),
+                Box::new(mailbox),
+                FileMailboxConf::default(),
+            ),
+        );
+        self.collection.new_mailbox(mailbox_hash);
+    }
+
+    /// Create tag mailboxes for tags that do not have one yet, and update the
+    /// contents of tag mailboxes.
+    ///
+    /// If `env_hashes` is `None`, or a new tag mailbox was created, every
+    /// envelope of the collection is checked. Otherwise only `env_hashes` are.
+    pub fn update_tag_mailboxes(&mut self, env_hashes: Option<&[EnvelopeHash]>) {
+        if !self.tag_mailboxes_enabled() {
+            return;
+        }
+        let mut created: Vec<MailboxHash> = vec![];
+        if !self.mailbox_entries.contains_key(&TagMailbox::root_hash()) {
+            self.insert_tag_mailbox(TagMailbox::root());
+            created.push(TagMailbox::root_hash());
+        }
+        let tags = self
+            .collection
+            .tag_index
+            .read()
+            .unwrap()
+            .values()
+            .filter(|tag| self.settings.conf.tag_mailbox_allowed(tag))
+            .cloned()
+            .collect::<Vec<String>>();
+        for tag in tags {
+            let mailbox = TagMailbox::new(&tag);
+            if !self.mailbox_entries.contains_key(&mailbox.hash) {
+                created.push(mailbox.hash);
+                self.insert_tag_mailbox(mailbox);
+            }
+        }
+        if !created.is_empty() {
+            build_mailboxes_order(
+                &mut self.tree,
+                &self.mailbox_entries,
+                &mut self.mailboxes_order,
+            );
+        }
+
+        let all_env_hashes: Vec<EnvelopeHash>;
+        let env_hashes = match env_hashes {
+            Some(env_hashes) if created.is_empty() => env_hashes,
+            _ => {
+                all_env_hashes = self
+                    .collection
+                    .envelopes
+                    .read()
+                    .unwrap()
+                    .keys()
+                    .copied()
+                    .collect();
+                &all_env_hashes
+            }
+        };
+        if env_hashes.is_empty() && created.is_empty() {
+            return;
+        }
+        let tag_mailboxes = self
+            .mailbox_entries
+            .values()
+            .filter_map(|e| {
+                let mailbox = e.ref_mailbox.as_any().downcast_ref::<TagMailbox>()?;
+                Some((mailbox.hash, mailbox.tag?))
+            })
+            .collect::<Vec<(MailboxHash, TagHash)>>();
+        for (mailbox_hash, tag_hash) in tag_mailboxes {
+            let membership = {
+                let envelopes = self.collection.envelopes.read().unwrap();
+                env_hashes
+                    .iter()
+                    .map(|h| {
+                        (
+                            *h,
+                            envelopes
+                                .get(h)
+                                .is_some_and(|env| env.tags().contains(&tag_hash)),
+                        )
+                    })
+                    .collect::<Vec<(EnvelopeHash, bool)>>()
+            };
+            let mut changed = self
+                .collection
+                .set_mailbox_membership(mailbox_hash, membership)
+                || created.contains(&mailbox_hash);
+            let (unseen, total) = {
+                let envelopes = self.collection.envelopes.read().unwrap();
+                let mailbox = self.collection.get_mailbox(mailbox_hash);
+                (
+                    mailbox
+                        .iter()
+                        .filter(|h| envelopes.get(*h).is_some_and(|env| !env.is_seen()))
+                        .count(),
+                    mailbox.len(),
+                )
+            };
+            if let Some(mailbox) = self.tag_mailbox(mailbox_hash) {
+                changed |=
+                    std::mem::replace(&mut *mailbox.unseen.lock().unwrap(), unseen) != unseen;
+                changed |= std::mem::replace(&mut *mailbox.total.lock().unwrap(), total) != total;
+            }
+            if !changed {
+                continue;
+            }
+            self.main_loop_handler
+                .send(ThreadEvent::UIEvent(UIEvent::MailboxUpdate((
+                    self.hash,
+                    mailbox_hash,
+                ))));
+        }
+        for mailbox_hash in created {
+            self.main_loop_handler
+                .send(ThreadEvent::UIEvent(UIEvent::MailboxCreate((
+                    self.hash,
+                    mailbox_hash,
+                ))));
+        }
+    }
+}
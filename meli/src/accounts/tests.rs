@@ -31,7 +31,7 @@ use melib::{
 use tempfile::TempDir;
 
 use crate::{
-    accounts::{AccountConf, FileMailboxConf, MailboxEntry, MailboxStatus},
+    accounts::{AccountConf, FileMailboxConf, FlagBatch, MailboxEntry, MailboxStatus},
     command::actions::MailboxOperation,
     utilities::tests::{eprint_step_fn, eprintln_ok_fn},
 };
@@ -174,6 +174,7 @@ fn new_maildir_backend(
         subscribed_mailboxes,
         mailboxes,
         manual_refresh: true,
+        retained_headers: vec![],
         extra,
     };
 
@@ -236,6 +237,11 @@ fn test_accounts_mailbox_by_path_error_msg() {
             event_queue: IndexMap::default(),
             backend_capabilities: backend.capabilities(),
             backend: Arc::new(std::sync::Mutex::new(backend)),
+            flag_batch: Default::default(),
+            await_replies: Default::default(),
+            retention: Default::default(),
+            pending_deletions: Default::default(),
+            previews: Default::default(),
         };
         account.init(ref_mailboxes).unwrap();
         while let Ok(thread_event) = ctx.receiver.try_recv() {
@@ -271,6 +277,35 @@ fn test_accounts_mailbox_by_path_error_msg() {
             .to_string()
         );
         eprintln_ok();
+        eprint_step!("Assert that read-only accounts and mailboxes reject changes...");
+        let inbox_hash = account.mailbox_by_path("inbox").unwrap();
+        account.check_writable(Some(inbox_hash)).unwrap();
+        account.mailbox_entries[&inbox_hash]
+            .conf
+            .mailbox_conf
+            .read_only = true;
+        assert!(account.is_read_only(inbox_hash));
+        let err = account
+            .save(b"Subject: test\n\ntest\n", inbox_hash, None)
+            .unwrap_err();
+        assert_eq!(err.kind, ErrorKind::NotSupported);
+        assert!(err.summary.ends_with(" of account test is read-only."));
+        account.check_writable(None).unwrap();
+        account.mailbox_entries[&inbox_hash]
+            .conf
+            .mailbox_conf
+            .read_only = false;
+        account.settings.account.read_only = true;
+        assert_eq!(
+            account
+                .check_writable(Some(inbox_hash))
+                .unwrap_err()
+                .summary,
+            "Account test is read-only."
+        );
+        account.settings.account.read_only = false;
+        assert!(!account.is_read_only(inbox_hash));
+        eprintln_ok();
 
         macro_rules! wait_for_job {
             ($job_id:expr) => {{
@@ -445,6 +480,11 @@ fn test_accounts_mailbox_by_path_error_msg() {
             event_queue: IndexMap::default(),
             backend_capabilities: backend.capabilities(),
             backend: Arc::new(std::sync::Mutex::new(backend)),
+            flag_batch: Default::default(),
+            await_replies: Default::default(),
+            retention: Default::default(),
+            pending_deletions: Default::default(),
+            previews: Default::default(),
         };
         account.init(ref_mailboxes).unwrap();
         while let Ok(thread_event) = ctx.receiver.try_recv() {
@@ -559,3 +599,481 @@ fn test_accounts_mailbox_by_path_error_msg() {
         eprintln_ok();
     }
 }
+
+#[test]
+fn test_accounts_flag_batch() {
+    use melib::{EnvelopeHash, Flag};
+
+    let mailbox_hash = MailboxHash(1);
+    let other_mailbox_hash = MailboxHash(2);
+    let mut batch = FlagBatch::default();
+    assert!(batch.is_empty());
+
+    // Same operation on the same mailbox is coalesced into one backend call.
+    for i in 1..=5 {
+        batch.push(
+            EnvelopeHash(i).into(),
+            mailbox_hash,
+            vec![FlagOp::Set(Flag::SEEN)],
+        );
+    }
+    batch.push(
+        EnvelopeHash(6).into(),
+        other_mailbox_hash,
+        vec![FlagOp::Set(Flag::SEEN)],
+    );
+    assert_eq!(batch.len(), 2);
+    assert!(!batch.conflicts(
+        &EnvelopeHash(7).into(),
+        mailbox_hash,
+        &[FlagOp::UnSet(Flag::SEEN)]
+    ));
+
+    // A later operation on the same flag supersedes the pending one.
+    batch.push(
+        EnvelopeHash(1).into(),
+        mailbox_hash,
+        vec![FlagOp::UnSet(Flag::SEEN)],
+    );
+    batch.push(
+        EnvelopeHash(6).into(),
+        other_mailbox_hash,
+        vec![FlagOp::UnSet(Flag::SEEN)],
+    );
+    assert_eq!(batch.len(), 3);
+
+    // Unrelated flags and tags do not conflict.
+    assert!(!batch.conflicts(
+        &EnvelopeHash(2).into(),
+        mailbox_hash,
+        &[FlagOp::Set(Flag::FLAGGED)]
+    ));
+    assert!(!batch.conflicts(
+        &EnvelopeHash(2).into(),
+        mailbox_hash,
+        &[FlagOp::SetTag("todo".into())]
+    ));
+
+    // An operation that only partially overrides a pending one cannot be merged.
+    batch.push(
+        EnvelopeHash(3).into(),
+        mailbox_hash,
+        vec![FlagOp::Set(Flag::FLAGGED), FlagOp::SetTag("todo".into())],
+    );
+    assert!(batch.conflicts(
+        &EnvelopeHash(3).into(),
+        mailbox_hash,
+        &[FlagOp::UnSet(Flag::FLAGGED)]
+    ));
+
+    let drained = batch.drain();
+    assert!(batch.is_empty());
+    assert!(!batch.is_armed());
+    assert_eq!(
+        drained
+            .iter()
+            .map(|(envs, mbox, flags)| (envs.iter().collect::<Vec<_>>(), *mbox, flags.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                vec![
+                    EnvelopeHash(2),
+                    EnvelopeHash(3),
+                    EnvelopeHash(4),
+                    EnvelopeHash(5)
+                ],
+                mailbox_hash,
+                vec![FlagOp::Set(Flag::SEEN)]
+            ),
+            (
+                vec![EnvelopeHash(1)],
+                mailbox_hash,
+                vec![FlagOp::UnSet(Flag::SEEN)]
+            ),
+            (
+                vec![EnvelopeHash(6)],
+                other_mailbox_hash,
+                vec![FlagOp::UnSet(Flag::SEEN)]
+            ),
+            (
+                vec![EnvelopeHash(3)],
+                mailbox_hash,
+                vec![FlagOp::Set(Flag::FLAGGED), FlagOp::SetTag("todo".into())]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_accounts_tag_mailboxes() {
+    use melib::{Collection, Envelope, TagHash};
+
+    use crate::{accounts::TagMailbox, conf::FileAccount};
+
+    let conf = FileAccount {
+        tag_mailboxes: true,
+        tag_mailboxes_allowlist: vec!["work*".into(), "urgent".into()],
+        ..Default::default()
+    };
+    assert!(conf.tag_mailbox_allowed("work"));
+    assert!(conf.tag_mailbox_allowed("work/reports"));
+    assert!(conf.tag_mailbox_allowed("urgent"));
+    assert!(!conf.tag_mailbox_allowed("personal"));
+    assert!(!FileAccount::default().tag_mailbox_allowed("work"));
+
+    let mailbox = TagMailbox::new("work");
+    assert_eq!(mailbox.path(), "Tags/work");
+    assert_eq!(mailbox.name(), "work");
+    assert_eq!(mailbox.parent(), Some(TagMailbox::root_hash()));
+    assert_eq!(mailbox.tag(), Some(TagHash::from_bytes(b"work")));
+    assert_eq!(mailbox.hash(), TagMailbox::new("work").hash());
+    assert_ne!(mailbox.hash(), TagMailbox::new("urgent").hash());
+    assert_eq!(TagMailbox::root().tag(), None);
+
+    let collection = Collection::default();
+    let inbox_hash = MailboxHash(1);
+    let mut env_hashes = vec![];
+    for (i, tags) in [&["work"][..], &["work", "urgent"], &[]]
+        .into_iter()
+        .enumerate()
+    {
+        let mut env = Envelope::from_bytes(
+            format!(
+                "From: <user@example.com>\nSubject: message {i}\nMessage-ID: \
+                 <{i}@example.com>\n\nbody\n"
+            )
+            .as_bytes(),
+            None,
+        )
+        .unwrap();
+        env.tags_mut()
+            .extend(tags.iter().map(|t| TagHash::from_bytes(t.as_bytes())));
+        env_hashes.push(env.hash());
+        collection.insert(env, inbox_hash);
+    }
+    let work_tag = TagHash::from_bytes(b"work");
+    let membership = |collection: &Collection| {
+        env_hashes
+            .iter()
+            .map(|h| (*h, collection.get_env(*h).tags().contains(&work_tag)))
+            .collect::<Vec<_>>()
+    };
+    assert!(collection.set_mailbox_membership(mailbox.hash(), membership(&collection)));
+    assert_eq!(collection.get_mailbox(mailbox.hash()).len(), 2);
+    assert_eq!(collection.get_threads(mailbox.hash()).len(), 2);
+    // Nothing changed.
+    assert!(!collection.set_mailbox_membership(mailbox.hash(), membership(&collection)));
+
+    collection
+        .get_env_mut(env_hashes[0])
+        .tags_mut()
+        .shift_remove(&work_tag);
+    assert!(collection.set_mailbox_membership(mailbox.hash(), membership(&collection)));
+    assert_eq!(
+        collection
+            .get_mailbox(mailbox.hash())
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![&env_hashes[1]]
+    );
+    // The backend mailbox is left alone.
+    assert_eq!(collection.get_mailbox(inbox_hash).len(), 3);
+}
+
+#[test]
+fn test_accounts_preview_text() {
+    use crate::accounts::preview_text;
+
+    assert_eq!(
+        preview_text(
+            "Hi all,\n\n> quoted text\n>> more quoted text\nThe  build is\tbroken.\n-- \nA \
+             signature",
+            100
+        ),
+        "Hi all, The build is broken."
+    );
+    assert_eq!(preview_text("abcdefghij klm", 5), "abcd…");
+    assert_eq!(preview_text("abc defgh", 5), "abc…");
+    assert_eq!(preview_text("abc de", 6), "abc de");
+    assert_eq!(preview_text("\n\n> only a quote\n", 100), "");
+}
+
+#[test]
+fn test_accounts_preview_cache() {
+    use melib::EnvelopeHash;
+
+    use crate::accounts::{Previews, PREVIEW_CACHE_CAPACITY};
+
+    let previews = Previews::default();
+    for i in 0..PREVIEW_CACHE_CAPACITY as u64 {
+        previews.insert(EnvelopeHash(i), format!("preview {i}"));
+    }
+    assert_eq!(previews.len(), PREVIEW_CACHE_CAPACITY);
+    // Using the oldest preview makes the second oldest the first to go.
+    assert_eq!(previews.get(EnvelopeHash(0)).as_deref(), Some("preview 0"));
+    previews.insert(EnvelopeHash(u64::MAX), "new".to_string());
+    assert_eq!(previews.len(), PREVIEW_CACHE_CAPACITY);
+    assert_eq!(previews.get(EnvelopeHash(0)).as_deref(), Some("preview 0"));
+    assert_eq!(previews.get(EnvelopeHash(1)), None);
+    assert_eq!(previews.get(EnvelopeHash(u64::MAX)).as_deref(), Some("new"));
+
+    previews.remove(EnvelopeHash(0));
+    assert_eq!(previews.get(EnvelopeHash(0)), None);
+    assert_eq!(previews.len(), PREVIEW_CACHE_CAPACITY - 1);
+}
+
+#[test]
+fn test_accounts_reminders_summary() {
+    use melib::Card;
+
+    use crate::accounts::{AwaitReply, Reminders};
+
+    let (alice, bob) = (Card::new(), Card::new());
+    let overdue = AwaitReply {
+        message_id: "1@example.com".to_string(),
+        subject: "Report".to_string(),
+        deadline: 1_700_000_000,
+        notified: true,
+    };
+    assert!(Reminders::default().is_empty());
+    assert_eq!(Reminders::default().to_string(), "");
+    let reminders = Reminders {
+        birthdays: vec![&alice],
+        overdue: vec![],
+    };
+    assert!(!reminders.is_empty());
+    assert_eq!(reminders.to_string(), "1 birthday today");
+    let reminders = Reminders {
+        birthdays: vec![&alice, &bob],
+        overdue: vec![&overdue],
+    };
+    assert_eq!(
+        reminders.to_string(),
+        "2 birthdays today, 1 e-mail awaiting reply overdue"
+    );
+}
+
+#[test]
+fn test_accounts_await_replies_index() {
+    use melib::{Address, Envelope};
+
+    use crate::accounts::{AwaitReplies, AwaitReply};
+
+    let envelope = |headers: &str| {
+        Envelope::from_bytes(format!("{headers}\n\nbody\n").as_bytes(), None).unwrap()
+    };
+    let me = Address::new(None, "me@example.com".to_string());
+    let mut replies = AwaitReplies::default();
+    for (message_id, deadline) in [("1@example.com", 100), ("2@example.com", 200)] {
+        replies.entries.insert(
+            message_id.to_string(),
+            AwaitReply {
+                message_id: message_id.to_string(),
+                subject: "Report".to_string(),
+                deadline,
+                notified: false,
+            },
+        );
+    }
+
+    // Tracked e-mail are indexed when they are loaded.
+    let sent = envelope("From: <me@example.com>\nMessage-ID: <1@example.com>");
+    assert!(replies.index_envelope(&sent, &[me.clone()]).is_empty());
+    assert_eq!(replies.overdue(150, |_| true), vec![(sent.hash(), true)]);
+    assert_eq!(replies.overdue(50, |_| true), vec![(sent.hash(), false)]);
+
+    // Follow-ups sent from one of the account's identities are not replies.
+    let follow_up = envelope(
+        "From: <me@example.com>\nMessage-ID: <3@example.com>\nIn-Reply-To: <1@example.com>",
+    );
+    assert!(replies.index_envelope(&follow_up, &[me.clone()]).is_empty());
+
+    let reply = envelope(
+        "From: <you@example.com>\nMessage-ID: <4@example.com>\nIn-Reply-To: \
+         <1@example.com>\nReferences: <0@example.com> <2@example.com>",
+    );
+    let mut replied = replies.index_envelope(&reply, &[me]);
+    replied.sort();
+    replied.dedup();
+    assert_eq!(replied, vec!["1@example.com", "2@example.com"]);
+    assert!(replies.remove("1@example.com").is_some());
+    assert!(replies.overdue(150, |_| true).is_empty());
+
+    // Envelopes that are no longer loaded are forgotten.
+    let sent = envelope("From: <me@example.com>\nMessage-ID: <2@example.com>");
+    replies.index_envelope(&sent, &[]);
+    assert!(replies.overdue(250, |_| false).is_empty());
+    assert!(replies.overdue(250, |_| true).is_empty());
+}
+
+#[test]
+fn test_accounts_collection_newest_date() {
+    use melib::{Collection, Envelope};
+
+    let collection = Collection::default();
+    let inbox_hash = MailboxHash(1);
+    let empty_hash = MailboxHash(2);
+    collection.new_mailbox(empty_hash);
+    for (i, date) in [1_700_000_000, 1_700_500_000, 1_600_000_000]
+        .into_iter()
+        .enumerate()
+    {
+        let mut env = Envelope::from_bytes(
+            format!(
+                "From: <user@example.com>\nSubject: message {i}\nMessage-ID: \
+                 <{i}@example.com>\n\nbody\n"
+            )
+            .as_bytes(),
+            None,
+        )
+        .unwrap();
+        env.set_datetime(date);
+        collection.insert(env, inbox_hash);
+    }
+    assert_eq!(collection.newest_date(inbox_hash), Some(1_700_500_000));
+    assert_eq!(collection.newest_date(empty_hash), None);
+    assert_eq!(collection.newest_date(MailboxHash(3)), None);
+}
+
+#[test]
+fn test_accounts_plan_retention() {
+    use melib::EnvelopeHash;
+
+    use crate::{
+        accounts::{plan_retention, RetentionTarget},
+        conf::{RetentionAction, RetentionRule},
+    };
+
+    let conf: FileMailboxConf = toml::from_str(
+        r#"retention = [ { older_than_days = 90, action = "move", destination = "Archive/%Y" },
+                         { older-than-days = 600, action = "delete" } ]"#,
+    )
+    .unwrap();
+    assert_eq!(
+        conf.retention,
+        vec![
+            RetentionRule {
+                older_than_days: 90,
+                action: RetentionAction::Move {
+                    destination: "Archive/%Y".to_string()
+                },
+            },
+            RetentionRule {
+                older_than_days: 600,
+                action: RetentionAction::Delete,
+            },
+        ]
+    );
+
+    // 2024-06-15
+    let now = 1_718_409_600;
+    let envelopes = [
+        // 2024-06-01
+        (EnvelopeHash(1), 1_717_200_000),
+        // 2023-06-15
+        (EnvelopeHash(2), 1_686_787_200),
+        // 2023-07-15
+        (EnvelopeHash(3), 1_689_379_200),
+        // 2022-06-15
+        (EnvelopeHash(4), 1_655_251_200),
+        // No date.
+        (EnvelopeHash(5), 0),
+    ];
+    let plan = plan_retention(&conf.retention, envelopes, now);
+    assert_eq!(
+        plan.into_iter().collect::<Vec<_>>(),
+        vec![
+            (
+                RetentionTarget::Move("Archive/2023".to_string()),
+                vec![EnvelopeHash(2), EnvelopeHash(3)]
+            ),
+            (RetentionTarget::Delete, vec![EnvelopeHash(4)]),
+        ]
+    );
+    assert!(plan_retention(&[], envelopes, now).is_empty());
+}
+
+#[test]
+fn test_accounts_thread_envelopes() {
+    use melib::{Collection, Envelope};
+
+    use crate::accounts::thread_envelopes;
+
+    let collection = Collection::default();
+    let inbox_hash = MailboxHash(1);
+    let mut env_hashes = vec![];
+    for (i, in_reply_to) in [None, Some(0), Some(1), None].into_iter().enumerate() {
+        let env = Envelope::from_bytes(
+            format!(
+                "From: <user@example.com>\nSubject: message {i}\nMessage-ID: \
+                 <{i}@example.com>\n{}\nbody\n",
+                in_reply_to.map_or_else(String::new, |r| format!(
+                    "In-Reply-To: <{r}@example.com>\nReferences: <{r}@example.com>\n"
+                ))
+            )
+            .as_bytes(),
+            None,
+        )
+        .unwrap();
+        env_hashes.push(env.hash());
+        collection.insert(env, inbox_hash);
+    }
+    let threads = collection.get_threads(inbox_hash);
+    let mut thread = thread_envelopes(&threads, &[env_hashes[1]]);
+    thread.sort();
+    let mut expected = env_hashes[..3].to_vec();
+    expected.sort();
+    assert_eq!(thread, expected);
+    assert_eq!(
+        thread_envelopes(&threads, &[env_hashes[3]]),
+        vec![env_hashes[3]]
+    );
+}
+
+#[test]
+fn test_accounts_attachment_info() {
+    use crate::accounts::AttachmentInfo;
+
+    let message = b"From: <user@example.com>\r\nSubject: report\r\nMIME-Version: 1.0\r\n\
+Content-Type: multipart/mixed; boundary=\"b1\"\r\n\r\n\
+--b1\r\nContent-Type: text/plain\r\n\r\nSee attached.\r\n\
+--b1\r\nContent-Type: application/pdf; name=\"report.pdf\"\r\n\
+Content-Disposition: attachment; filename=\"report.pdf\"\r\n\
+Content-Transfer-Encoding: base64\r\n\r\nJVBERi0xLjQK\r\n\
+--b1\r\nContent-Type: application/octet-stream\r\nContent-Disposition: attachment\r\n\r\n\
+data\r\n\
+--b1--\r\n";
+    let attachments = AttachmentInfo::from_bytes(message);
+    assert_eq!(attachments.len(), 2);
+    assert_eq!(attachments[0].path, vec![1]);
+    assert_eq!(attachments[0].filename.as_deref(), Some("report.pdf"));
+    assert_eq!(attachments[0].mime_type, "application/pdf");
+    assert_eq!(attachments[1].path, vec![2]);
+    assert_eq!(attachments[1].filename, None);
+
+    assert!(AttachmentInfo::from_bytes(
+        b"From: <user@example.com>\r\nSubject: hi\r\n\r\nhello\r\n"
+    )
+    .is_empty());
+}
+
+#[test]
+fn test_accounts_sieve_redirect_message() {
+    use crate::accounts::redirect_message;
+
+    let message = redirect_message(
+        b"From: <coyote@example.com>\r\nTo: <user@example.com>\r\nCc: <other@example.com>\r\n\
+Bcc: <hidden@example.com>\r\nSubject: hi\r\n\r\nhello\r\n",
+        "User <user@example.com>",
+        "work@example.com",
+        "Thu, 15 Oct 2026 10:00:00 +0000",
+    )
+    .unwrap();
+    assert_eq!(
+        message,
+        "Resent-From: User <user@example.com>\r\nResent-Date: Thu, 15 Oct 2026 10:00:00 \
+         +0000\r\nResent-To: work@example.com\r\nFrom: <coyote@example.com>\r\nX-Original-To: \
+         <user@example.com>\r\nX-Original-Cc: <other@example.com>\r\nSubject: hi\r\nTo: \
+         work@example.com\r\n\r\nhello\r\n"
+    );
+}
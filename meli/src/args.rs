@@ -51,6 +51,19 @@ pub struct Opt {
     #[structopt(short, long, parse(from_os_str))]
     pub config: Option<PathBuf>,
 
+    /// print memory usage estimate of loaded accounts to stderr on exit.
+    #[structopt(long)]
+    pub memory_report: bool,
+
+    /// print how long each phase of startup took to stderr on exit.
+    #[structopt(long)]
+    pub profile_startup: bool,
+
+    /// start even if another running instance holds the lock of the
+    /// configuration or data directory.
+    #[structopt(long)]
+    pub take_over_lock: bool,
+
     #[structopt(subcommand)]
     pub subcommand: Option<SubCommand>,
 }
@@ -86,6 +99,27 @@ pub enum SubCommand {
         #[structopt(value_name = "CONFIG_PATH", parse(from_os_str = try_path_or_stdio))]
         path: Option<PathOrStdio>,
     },
+    /// convert a mutt or neomutt configuration file to a meli configuration
+    /// and print it to standard output. If `PATH` is not specified, meli will
+    /// look for `~/.muttrc` and the other usual locations. Path `-` will read
+    /// input from standard input instead.
+    #[structopt(display_order = 3)]
+    ImportMutt {
+        /// name of the account in the new configuration.
+        #[structopt(long, default_value = "mutt")]
+        account_name: String,
+        #[structopt(value_name = "MUTTRC_PATH", parse(from_os_str = try_path_or_stdio))]
+        path: Option<PathOrStdio>,
+    },
+    /// bring the caches, indexes and state files of all accounts up to date,
+    /// after performing any pending configuration migrations. meli also
+    /// migrates each file when it opens it.
+    #[structopt(display_order = 3)]
+    Migrate {
+        /// only print what would be migrated.
+        #[structopt(long)]
+        dry_run: bool,
+    },
     #[structopt(display_order = 3)]
     /// Testing tools such as IMAP, SMTP shells for debugging.
     Tools(ToolOpt),
@@ -100,6 +134,13 @@ pub enum SubCommand {
         #[structopt(value_name = "DESTINATION_PATH", parse(from_os_str))]
         destination_path: Option<PathBuf>,
     },
+    #[structopt(display_order = 5)]
+    /// print the completion script of `SHELL` for meli's arguments and
+    /// subcommands to standard output.
+    Completions {
+        #[structopt(value_name = "SHELL", possible_values = &structopt::clap::Shell::variants(), case_insensitive = true)]
+        shell: structopt::clap::Shell,
+    },
     #[structopt(display_order = 6)]
     /// Print compile time feature flags of this binary
     CompiledWith,
@@ -204,6 +245,9 @@ impl Opt {
             SubCommand::TestConfig { path } => {
                 subcommands::test_config(path)
             }
+            SubCommand::Migrate { dry_run } => {
+                subcommands::migrate(dry_run, self.take_over_lock)
+            }
             SubCommand::Tools(toolopt) => {
                  subcommands::tool(self.config, toolopt)
             }
@@ -213,6 +257,9 @@ impl Opt {
             SubCommand::EditConfig => {
                 subcommands::edit_config()
             }
+            SubCommand::ImportMutt { account_name, path } => {
+                subcommands::import_mutt(path, &account_name)
+            }
             SubCommand::PrintConfigPath => {
                 let config_path = ret_err!(crate::conf::get_config_file());
                 print_path(&config_path);
@@ -230,11 +277,12 @@ impl Opt {
             }) => {
                 use std::io::Write;
 
-                ret_err!(std::io::stdout().write_all(if no_raw {
+                let page = ret_err!(if no_raw {
                     page.text_gz()
                 } else {
                     page.mdoc_gz()
-                }));
+                });
+                ret_err!(std::io::stdout().write_all(&page));
                 Ok(())
             }
             #[cfg(feature = "cli-docs")]
@@ -245,6 +293,10 @@ impl Opt {
             }) => {
                 subcommands::man(page, false).and_then(|s| subcommands::pager(s, no_raw))
             }
+            SubCommand::Completions { shell } => {
+                Opt::clap().gen_completions_to("meli", shell, &mut std::io::stdout());
+                Ok(())
+            }
             SubCommand::CompiledWith => {
                 subcommands::compiled_with()
             }
@@ -25,6 +25,7 @@
 use std::{borrow::Cow, collections::HashSet, str::FromStr};
 
 use melib::{
+    contacts::ImportFormat,
     nom::{
         self,
         branch::alt,
@@ -37,7 +38,7 @@ use melib::{
         IResult,
     },
     parser::BytesExt,
-    SortField, SortOrder,
+    SortChain, SortField, SortOrder,
 };
 
 #[cfg(test)]
@@ -57,12 +58,13 @@ pub use parser::parse_command;
 pub use crate::actions::{
     AccountAction::{self, *},
     Action::{self, *},
+    AwaitReplyAction,
     ComposeAction::{self, *},
-    ComposerTabAction, FlagAction,
+    ComposerTabAction, FlagAction, ForgeAction,
     ListingAction::{self, *},
     MailingListAction::{self, *},
     TabAction::{self, *},
-    TagAction,
+    TagAction, ThreadExportFormat,
     ViewAction::{self, *},
 };
 
@@ -285,6 +287,59 @@ pub enum Token {
     IndexValue,
 }
 
+impl std::fmt::Display for TokenStream {
+    /// Writes the synopsis of the command, e.g. `empty-folder [MAILBOX_PATH]`.
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, t) in self.tokens.iter().enumerate() {
+            if i > 0 {
+                write!(fmt, " ")?;
+            }
+            write!(fmt, "{t}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for TokenAdicity {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ZeroOrOne(t) => write!(fmt, "[{t}]"),
+            ZeroOrMore(t) => write!(fmt, "[{t}...]"),
+            One(t) => write!(fmt, "{t}"),
+            OneOrMore(t) => write!(fmt, "{t}..."),
+        }
+    }
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Literal(s) => write!(fmt, "{s}"),
+            Filepath => write!(fmt, "PATH"),
+            Alternatives(streams) => {
+                write!(fmt, "{{")?;
+                for (i, s) in streams.iter().enumerate() {
+                    if i > 0 {
+                        write!(fmt, "|")?;
+                    }
+                    write!(fmt, "{s}")?;
+                }
+                write!(fmt, "}}")
+            }
+            AlternativeStrings(strings) => write!(fmt, "{{{}}}", strings.join("|")),
+            Seq(tokens) => write!(fmt, "{}", TokenStream { tokens }),
+            AccountName => write!(fmt, "ACCOUNT"),
+            MailboxPath => write!(fmt, "MAILBOX_PATH"),
+            QuotedStringValue => write!(fmt, "\"STRING\""),
+            RestOfStringValue => write!(fmt, "STRING"),
+            AlphanumericStringValue => write!(fmt, "VALUE"),
+            AttachmentIndexValue => write!(fmt, "ATTACHMENT_INDEX"),
+            MailboxIndexValue => write!(fmt, "MAILBOX_INDEX"),
+            IndexValue => write!(fmt, "INDEX"),
+        }
+    }
+}
+
 fn eof(input: &[u8]) -> IResult<&[u8], ()> {
     if input.is_empty() {
         Ok((input, ()))
@@ -313,16 +368,46 @@ define_commands!([
                    ],
                    parser: parser::set
                  },
+                 { tags: ["set local"],
+                   desc: "set local KEY=VALUE, overrides a setting for the current mailbox, e.g. set local listing.sort=subject asc",
+                   tokens: &[One(Literal("set")), One(Literal("local")), One(RestOfStringValue)],
+                   parser: parser::set
+                 },
                  { tags: ["delete"],
                    desc: "delete message",
                    tokens: &[One(Literal("delete"))],
                    parser: parser::delete_message
                  },
+                 { tags: ["empty-trash"],
+                   desc: "delete all messages of the account's trash mailbox",
+                   tokens: &[One(Literal("empty-trash"))],
+                   parser: parser::empty_trash
+                 },
+                 { tags: ["empty-folder"],
+                   desc: "empty-folder [MAILBOX_PATH], delete all messages of a mailbox, or of the current one",
+                   tokens: &[One(Literal("empty-folder")), ZeroOrOne(MailboxPath)],
+                   parser: parser::empty_folder
+                 },
+                 { tags: ["expunge"],
+                   desc: "delete the messages of the current mailbox that are flagged as trashed",
+                   tokens: &[One(Literal("expunge"))],
+                   parser: parser::expunge
+                 },
+                 { tags: ["undo-delete"],
+                   desc: "cancel deletions of empty-trash, empty-folder and expunge that are still in their grace period",
+                   tokens: &[One(Literal("undo-delete"))],
+                   parser: parser::undo_delete
+                 },
                  { tags: ["copyto", "moveto"],
                    desc: "copy/move message",
                    tokens: &[One(Alternatives(&[to_stream!(One(Literal("copyto"))), to_stream!(One(Literal("moveto")))])), ZeroOrOne(AccountName), One(MailboxPath)],
                    parser: parser::copymove
                  },
+                 { tags: ["copyto-thread", "moveto-thread"],
+                   desc: "copy/move the entire threads of the selected messages",
+                   tokens: &[One(Alternatives(&[to_stream!(One(Literal("copyto-thread"))), to_stream!(One(Literal("moveto-thread")))])), One(MailboxPath)],
+                   parser: parser::copymove
+                 },
                 { tags: ["import "],
                   desc: "import FILESYSTEM_PATH MAILBOX_PATH",
                   tokens: &[One(Literal("import")), One(Filepath), One(MailboxPath)],
@@ -339,15 +424,20 @@ define_commands!([
                    parser: parser::goto
                  },
                  { tags: ["subsort"],
-                   desc: "subsort [date/subject] [asc/desc], sorts first level replies in threads.",
-                   tokens: &[One(Literal("subsort")), One(Alternatives(&[to_stream!(One(Literal("date"))), to_stream!(One(Literal("subject")))])), One(Alternatives(&[to_stream!(One(Literal("asc"))), to_stream!(One(Literal("desc")))])) ],
+                   desc: "subsort [date/subject/size/attachments/from/domain/recipients/thread-length/unseen/score] [asc/desc], breaks ties of the primary sort key.",
+                   tokens: &[One(Literal("subsort")), One(Alternatives(&[to_stream!(One(Literal("date"))), to_stream!(One(Literal("subject"))), to_stream!(One(Literal("size"))), to_stream!(One(Literal("attachments"))), to_stream!(One(Literal("from"))), to_stream!(One(Literal("domain"))), to_stream!(One(Literal("recipients"))), to_stream!(One(Literal("thread-length"))), to_stream!(One(Literal("unseen"))), to_stream!(One(Literal("score")))])), One(Alternatives(&[to_stream!(One(Literal("asc"))), to_stream!(One(Literal("desc")))])) ],
                    parser: parser::subsort
                  },
                 { tags: ["sort"],
-                  desc: "sort [date/subject] [asc/desc], sorts threads.",
-                   tokens: &[One(Literal("sort")), One(Alternatives(&[to_stream!(One(Literal("date"))), to_stream!(One(Literal("subject")))])), One(Alternatives(&[to_stream!(One(Literal("asc"))), to_stream!(One(Literal("desc")))])) ],
+                  desc: "sort <field> [asc/desc][, <field> [asc/desc]..], sorts threads by each field in turn. Fields: date/subject/size/attachments/from/domain/recipients/thread-length/unseen/score.",
+                   tokens: &[One(Literal("sort")), One(Alternatives(&[to_stream!(One(Literal("date"))), to_stream!(One(Literal("subject"))), to_stream!(One(Literal("size"))), to_stream!(One(Literal("attachments"))), to_stream!(One(Literal("from"))), to_stream!(One(Literal("domain"))), to_stream!(One(Literal("recipients"))), to_stream!(One(Literal("thread-length"))), to_stream!(One(Literal("unseen"))), to_stream!(One(Literal("score")))])), One(Alternatives(&[to_stream!(One(Literal("asc"))), to_stream!(One(Literal("desc")))])) ],
                   parser: parser::sort
                 },
+                { tags: ["sort reverse"],
+                  desc: "sort reverse, reverses the order of every sort key.",
+                   tokens: &[One(Literal("sort")), One(Literal("reverse"))],
+                  parser: parser::sort_reverse
+                },
                 { tags: ["sort"],
                   desc: "sort <column index> [asc/desc], sorts table columns.",
                    tokens: &[One(Literal("sort")), One(IndexValue), ZeroOrOne(Alternatives(&[to_stream!(One(Literal("asc"))), to_stream!(One(Literal("desc")))])) ],
@@ -363,6 +453,36 @@ define_commands!([
                   tokens: &[One(Literal("search")), One(RestOfStringValue)],
                   parser: parser::search
                 },
+                { tags: ["search-tab "],
+                  desc: "search-tab <QUERY>, searches all mailboxes of the account and lists the results in a new tab",
+                  tokens: &[One(Literal("search-tab")), One(RestOfStringValue)],
+                  parser: parser::search_tab
+                },
+                { tags: ["refine-search "],
+                  desc: "refine-search <QUERY>, shows only the results of the last search-tab search that also match QUERY",
+                  tokens: &[One(Literal("refine-search")), One(RestOfStringValue)],
+                  parser: parser::refine_search
+                },
+                { tags: ["rerun-search"],
+                  desc: "rerun-search, runs the last search-tab search again",
+                  tokens: &[One(Literal("rerun-search"))],
+                  parser: parser::rerun_search
+                },
+                { tags: ["correspondence"],
+                  desc: "correspondence [ADDRESS], lists all e-mail from or to ADDRESS, or the sender of the entry under the cursor, in a new tab",
+                  tokens: &[One(Literal("correspondence")), ZeroOrOne(RestOfStringValue)],
+                  parser: parser::correspondence
+                },
+                { tags: ["limit"],
+                  desc: "limit <QUERY>, shows only entries that match the query. `limit all` shows every entry again.",
+                  tokens: &[One(Literal("limit")), One(RestOfStringValue)],
+                  parser: parser::limit
+                },
+                { tags: ["jump-to-date "],
+                  desc: "jump-to-date YYYY-MM-DD, moves the cursor to the first entry on or after the given date",
+                  tokens: &[One(Literal("jump-to-date")), One(RestOfStringValue)],
+                  parser: parser::jump_to_date
+                },
                 { tags: ["clear-selection"],
                   desc: "clear-selection",
                   tokens: &[One(Literal("clear-selection"))],
@@ -378,11 +498,21 @@ define_commands!([
                   tokens: &[One(Literal("export-mbox")), One(Filepath)],
                   parser: parser::export_mbox
                 },
+                { tags: ["export mbox ", "export eml ", "export html ", "export markdown "],
+                  desc: "export mbox FILEPATH | export eml DIRECTORY | export html|markdown PATH, export selected threads",
+                  tokens: &[One(Literal("export")), One(Alternatives(&[to_stream!(One(Literal("mbox"))), to_stream!(One(Literal("eml"))), to_stream!(One(Literal("html"))), to_stream!(One(Literal("markdown")))])), One(Filepath)],
+                  parser: parser::export
+                },
                 { tags: ["list-archive", "list-post", "list-unsubscribe", "list-"],
                   desc: "list-[unsubscribe/post/archive]",
                   tokens: &[One(Alternatives(&[to_stream!(One(Literal("list-archive"))), to_stream!(One(Literal("list-post"))), to_stream!(One(Literal("list-unsubscribe")))]))],
                   parser: parser::mailinglist
                 },
+                { tags: ["forge-open", "forge-mute", "forge-"],
+                  desc: "forge-[open/mute]",
+                  tokens: &[One(Alternatives(&[to_stream!(One(Literal("forge-open"))), to_stream!(One(Literal("forge-mute")))]))],
+                  parser: parser::forge
+                },
                 { tags: ["setenv "],
                   desc: "setenv VAR=VALUE",
                   tokens: &[One(Literal("setenv")), OneOrMore(Seq(&[One(AlphanumericStringValue), One(Literal("=")), One(QuotedStringValue)]))],
@@ -431,6 +561,11 @@ Alternatives(&[to_stream!(One(Literal("add-attachment")), One(Filepath)), to_str
                   tokens: &[One(Literal("discard-draft"))],
                   parser: parser::discard_draft
                 },
+                { tags: ["reformat"],
+                  desc: "reformat the paragraphs of the draft body at the wrap column, keeping quote prefixes",
+                  tokens: &[One(Literal("reformat"))],
+                  parser: parser::reformat
+                },
                 { tags: ["toggle sign "],
                   desc: "switch between sign/unsign for this draft",
                   tokens: &[One(Literal("toggle")), One(Literal("sign"))],
@@ -471,6 +606,31 @@ Alternatives(&[to_stream!(One(Literal("add-attachment")), One(Filepath)), to_str
                   tokens: &[One(Literal("reindex")), One(AccountName)],
                   parser: parser::reindex
                 },
+                { tags: ["contact-duplicates "],
+                  desc: "contact-duplicates ACCOUNT, show a report of likely duplicate contacts in the account's address book",
+                  tokens: &[One(Literal("contact-duplicates")), One(AccountName)],
+                  parser: parser::contact_duplicates
+                },
+                { tags: ["import-contacts "],
+                  desc: "import-contacts ACCOUNT mutt|abook FILESYSTEM_PATH, import contacts from a mutt alias file or an abook database into the account's address book",
+                  tokens: &[One(Literal("import-contacts")), One(AccountName), One(AlternativeStrings(&["mutt", "abook"])), One(Filepath)],
+                  parser: parser::import_contacts
+                },
+                { tags: ["retention-preview "],
+                  desc: "retention-preview ACCOUNT, show what the retention rules of the account's mailboxes would archive or delete, without applying them",
+                  tokens: &[One(Literal("retention-preview")), One(AccountName)],
+                  parser: parser::retention_preview
+                },
+                { tags: ["maintenance "],
+                  desc: "maintenance ACCOUNT, check, compact and garbage collect the account's caches in the background",
+                  tokens: &[One(Literal("maintenance")), One(AccountName)],
+                  parser: parser::maintenance
+                },
+                { tags: ["special-mailboxes "],
+                  desc: "special-mailboxes ACCOUNT, choose the sent, drafts, trash, junk and archive mailboxes of the account",
+                  tokens: &[One(Literal("special-mailboxes")), One(AccountName)],
+                  parser: parser::special_mailboxes
+                },
                 { tags: ["open-in-tab"],
                   desc: "opens envelope view in new tab",
                   tokens: &[One(Literal("open-in-tab"))],
@@ -487,6 +647,16 @@ Alternatives(&[to_stream!(One(Literal("save-attachment")), One(AttachmentIndexVa
                   tokens: &[One(Literal("export-mail")), One(Filepath)],
                   parser: parser::export_mail
                 },
+                { tags: ["await-reply "],
+                  desc: "await-reply DATE|DURATION|cancel, remind if selected e-mail is not replied to by DATE (YYYY-MM-DD) or within DURATION (e.g. 3d, 12h, 2w)",
+                  tokens: &[One(Literal("await-reply")), One(AlternativeStrings(&["cancel", "1d", "3d", "1w"]))],
+                  parser: parser::await_reply
+                },
+                { tags: ["print-mail "],
+                  desc: "print-mail [COMMAND], pipe formatted message to COMMAND or pager.print_command",
+                  tokens: &[One(Literal("print-mail")), ZeroOrOne(RestOfStringValue)],
+                  parser: parser::print_mail
+                },
                 { tags: ["add-addresses-to-contacts "],
                   desc: "add-addresses-to-contacts",
                   tokens: &[One(Literal("add-addresses-to-contacts"))],
@@ -534,6 +704,31 @@ Alternatives(&[to_stream!(One(Literal("save-attachment")), One(AttachmentIndexVa
                   tokens: &[One(Literal("manage-jobs"))],
                   parser: parser::manage_jobs
                 },
+                { tags: ["mailbox-attachments"],
+                  desc: "view and save the attachments of the e-mail of the current mailbox",
+                  tokens: &[One(Literal("mailbox-attachments"))],
+                  parser: parser::mailbox_attachments
+                },
+                { tags: ["triage"],
+                  desc: "go through the unread e-mail of the current mailbox newest first, archiving, deleting, keeping or replying to each with a single key",
+                  tokens: &[One(Literal("triage"))],
+                  parser: parser::triage
+                },
+                { tags: ["startup-profile"],
+                  desc: "view how long each phase of startup took",
+                  tokens: &[One(Literal("startup-profile"))],
+                  parser: parser::startup_profile
+                },
+                { tags: ["usage-stats"],
+                  desc: "usage-stats [purge], view the local usage statistics, or delete them",
+                  tokens: &[One(Literal("usage-stats")), ZeroOrOne(Literal("purge"))],
+                  parser: parser::usage_stats
+                },
+                { tags: ["manage-settings"],
+                  desc: "view and edit settings",
+                  tokens: &[One(Literal("manage-settings"))],
+                  parser: parser::manage_settings
+                },
                 { tags: ["quit"],
                   desc: "quit meli",
                   tokens: &[One(Literal("quit"))],
@@ -23,7 +23,10 @@
 
 use std::{path::PathBuf, sync::Arc};
 
-use melib::{email::mailto::Mailto, Flag, SortField, SortOrder};
+use melib::{
+    contacts::ImportFormat, email::mailto::Mailto, CardId, Flag, SortChain, SortField, SortOrder,
+    SpecialUsageMailbox,
+};
 
 use crate::components::{Component, ComponentId};
 
@@ -39,6 +42,23 @@ pub enum TagAction {
     Remove(String),
 }
 
+/// Format of documents of exported threads.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ThreadExportFormat {
+    #[default]
+    Html,
+    Markdown,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum AwaitReplyAction {
+    /// Remind if no reply has arrived by this date.
+    By(melib::UnixTimestamp),
+    /// Remind if no reply has arrived within this many seconds.
+    Within(u64),
+    Cancel,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum ListingAction {
     SetPlain,
@@ -46,20 +66,56 @@ pub enum ListingAction {
     SetCompact,
     SetConversations,
     Search(String),
+    /// Search all mailboxes of the account and list the results in a new
+    /// tab.
+    SearchInTab(String),
+    /// Narrow down the last `SearchInTab` search with another query.
+    RefineSearch(String),
+    /// Run the last `SearchInTab` search again.
+    RerunSearch,
+    /// Search all mailboxes for e-mail from or to an address, or the sender
+    /// of the entry under the cursor if `None`.
+    Correspondence(Option<String>),
     Select(String),
+    /// Show only entries matching a query, or all entries if `None`.
+    Limit(Option<String>),
+    /// Move the cursor to the oldest entry on or after this date.
+    JumpToDate(melib::UnixTimestamp),
     SetSeen,
     SetUnseen,
+    /// Override a setting for the current mailbox, e.g. `listing.sort` to
+    /// `date asc`.
+    SetLocal(String, String),
     SendToTrash,
     CopyTo(MailboxPath),
     CopyToOtherAccount(AccountName, MailboxPath),
     MoveTo(MailboxPath),
     MoveToOtherAccount(AccountName, MailboxPath),
+    /// Copy the entire threads of the selected e-mail.
+    CopyThreadTo(MailboxPath),
+    /// Move the entire threads of the selected e-mail.
+    MoveThreadTo(MailboxPath),
     Import(PathBuf, MailboxPath),
     ExportMbox(Option<melib::mbox::MboxFormat>, PathBuf),
+    /// Export each envelope to a separate `.eml` file in a directory.
+    ExportEml(PathBuf),
+    /// Export the entire threads of the selected e-mail as a single
+    /// document.
+    ExportThread(ThreadExportFormat, PathBuf),
     Delete,
+    /// Delete all e-mail of the account's trash mailbox.
+    EmptyTrash,
+    /// Delete all e-mail of a mailbox, or of the current one if `None`.
+    EmptyMailbox(Option<MailboxPath>),
+    /// Delete the e-mail of the current mailbox that are flagged as trashed.
+    Expunge,
+    /// Cancel deletions that are still in their grace period.
+    UndoDelete,
     OpenInNewTab,
     Tag(TagAction),
     Flag(FlagAction),
+    /// Track envelopes and raise a notification if they are not replied to.
+    AwaitReply(AwaitReplyAction),
     ClearSelection,
     ToggleThreadSnooze,
 }
@@ -81,6 +137,7 @@ pub enum ComposerTabAction {
     AddAttachment(FileAction),
     AddAttachmentPipe(String),
     RemoveAttachment(usize),
+    Reformat,
 }
 
 #[derive(Debug, PartialEq)]
@@ -91,6 +148,11 @@ pub enum TabAction {
     New(Option<Box<dyn Component>>),
     ManageMailboxes,
     ManageJobs,
+    ManageSettings,
+    MailboxAttachments,
+    Triage,
+    ViewStartupProfile,
+    ViewUsageStats,
     #[cfg(feature = "cli-docs")]
     Man(crate::manpages::ManPages),
 }
@@ -102,6 +164,15 @@ pub enum MailingListAction {
     ListUnsubscribe,
 }
 
+/// Actions on the viewed GitHub or GitLab notification.
+#[derive(Debug, Eq, PartialEq)]
+pub enum ForgeAction {
+    /// Open the web page of the issue or pull request.
+    Open,
+    /// Unsubscribe from the thread of the issue or pull request.
+    Mute,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum ViewAction {
     Pipe(String, Vec<String>),
@@ -109,6 +180,7 @@ pub enum ViewAction {
     SaveAttachment(usize, FileAction),
     PipeAttachment(usize, String, Vec<String>),
     ExportMail(String),
+    PrintMail(Option<String>),
     AddAddressesToContacts,
 }
 
@@ -121,6 +193,13 @@ pub enum ComposeAction {
 pub enum AccountAction {
     ReIndex,
     PrintAccountSetting(String),
+    ContactDuplicates,
+    MergeContacts { into: CardId, from: Vec<CardId> },
+    ImportContacts(ImportFormat, PathBuf),
+    RetentionPreview,
+    Maintenance,
+    SpecialMailboxes,
+    SetSpecialMailbox(SpecialUsageMailbox, MailboxPath),
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -138,11 +217,15 @@ pub enum MailboxOperation {
 pub enum Action {
     Listing(ListingAction),
     ViewMailbox(usize),
-    Sort(SortField, SortOrder),
+    Sort(SortChain),
+    /// Reverse the order of every key of the current sort chain.
+    ReverseSort,
     SortColumn(usize, SortOrder),
+    /// Keep the primary sort key and break ties with this key.
     SubSort(SortField, SortOrder),
     Tab(TabAction),
     MailingListAction(MailingListAction),
+    Forge(ForgeAction),
     View(ViewAction),
     SetEnv(String, String),
     PrintEnv(String),
@@ -154,6 +237,8 @@ pub enum Action {
     PrintSetting(String),
     ReloadConfiguration,
     ToggleMouse,
+    /// Delete the collected usage statistics.
+    PurgeUsageStats,
     Quit,
 }
 
@@ -162,8 +247,13 @@ impl Action {
         matches!(
             self,
             Self::Listing(ListingAction::Delete)
+                | Self::Listing(ListingAction::EmptyTrash)
+                | Self::Listing(ListingAction::EmptyMailbox(_))
+                | Self::Listing(ListingAction::Expunge)
                 | Self::MailingListAction(_)
+                | Self::Forge(ForgeAction::Mute)
                 | Self::Mailbox(_, _)
+                | Self::PurgeUsageStats
                 | Self::Quit
         )
     }
@@ -200,6 +290,7 @@ impl_into_action!(
     { ListingAction => Listing },
     { TabAction => Tab },
     { MailingListAction => MailingListAction },
+    { ForgeAction => Forge },
     { ViewAction => View },
     { ComposeAction => Compose }
 );
@@ -85,6 +85,15 @@ pub fn sortfield(input: &[u8]) -> IResult<&[u8], SortField> {
     )(input.trim())
 }
 
+/// A comma separated list of `field [order]` sort keys, e.g. `unseen desc, date
+/// desc`.
+pub fn sortchain(input: &[u8]) -> IResult<&[u8], SortChain> {
+    map_res(
+        map_res(not_line_ending, std::str::from_utf8),
+        std::str::FromStr::from_str,
+    )(input.trim())
+}
+
 pub fn sortorder(input: &[u8]) -> IResult<&[u8], SortOrder> {
     map_res(
         map_res(not_line_ending, std::str::from_utf8),
@@ -114,12 +123,21 @@ pub fn listing_action(input: &[u8]) -> IResult<&[u8], Result<Action, CommandErro
         delete_message,
         copymove,
         import,
+        search_tab,
+        refine_search,
+        rerun_search,
+        correspondence,
         search,
         select,
+        limit,
+        jump_to_date,
         open_in_new_tab,
         export_mbox,
+        export,
         _tag,
         flag,
+        await_reply,
+        bulk_delete,
     ))(input)
 }
 
@@ -130,11 +148,20 @@ pub fn compose_action(input: &[u8]) -> IResult<&[u8], Result<Action, CommandErro
         remove_attachment,
         save_draft,
         discard_draft,
+        reformat,
     ))(input)
 }
 
 pub fn account_action(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
-    alt((reindex, print_account_setting))(input)
+    alt((
+        reindex,
+        contact_duplicates,
+        import_contacts,
+        retention_preview,
+        maintenance,
+        special_mailboxes,
+        print_account_setting,
+    ))(input)
 }
 
 pub fn view(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
@@ -144,23 +171,33 @@ pub fn view(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
         save_attachment,
         pipe_attachment,
         export_mail,
+        print_mail,
         add_addresses_to_contacts,
     ))(input)
 }
 
 pub fn new_tab(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
-    alt((manage_mailboxes, manage_jobs, compose_action, view_manpage))(input)
+    alt((
+        manage_mailboxes,
+        manage_jobs,
+        manage_settings,
+        mailbox_attachments,
+        triage,
+        startup_profile,
+        usage_stats,
+        compose_action,
+        view_manpage,
+    ))(input)
 }
 
 pub fn parse_command(input: &[u8]) -> Result<Action, CommandError> {
     alt((
         goto,
         listing_action,
-        sort,
-        sort_column,
-        subsort,
+        alt((sort_reverse, sort, sort_column, subsort)),
         close,
         mailinglist,
+        forge,
         setenv,
         alt((printenv, currentdir, change_currentdir)),
         view,
@@ -329,9 +366,42 @@ pub fn set(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
         let (input, _) = eof(input)?;
         Ok((input, Ok(ret)))
     }
+    fn local(input: &'_ [u8]) -> IResult<&'_ [u8], Result<Action, CommandError>> {
+        let mut check = arg_init! { min_arg:2, max_arg:{ u8::MAX}, set_local};
+        let (input, _) = tag("set")(input.trim())?;
+        arg_chk!(start check, input);
+        let (input, _) = is_a(" ")(input)?;
+        let (input, _) = tag("local")(input)?;
+        arg_chk!(inc check, input);
+        let (input, _) = is_a(" ")(input)?;
+        arg_chk!(inc check, input);
+        let (input, setting) = map_res(not_line_ending, std::str::from_utf8)(input)?;
+        arg_chk!(finish check, input);
+        let (input, _) = eof(input)?;
+        let Some((key, value)) = setting
+            .split_once('=')
+            .map(|(k, v)| (k.trim(), v.trim()))
+            .filter(|(k, _)| !k.is_empty())
+        else {
+            return Ok((
+                input,
+                Err(CommandError::BadValue {
+                    inner: format!("{setting} is not of the form KEY=VALUE").into(),
+                    suggestions: Some(&["listing.sort=date desc", "pager.filter=\"less\""]),
+                }),
+            ));
+        };
+        Ok((
+            input,
+            Ok(Listing(SetLocal(key.to_string(), value.to_string()))),
+        ))
+    }
     if let val @ Ok((_, Ok(_))) = toggle(input) {
         return val;
     }
+    if let val @ Ok(_) = local(input) {
+        return val;
+    }
     seen_flag(input)
 }
 
@@ -344,8 +414,70 @@ pub fn delete_message(input: &'_ [u8]) -> IResult<&'_ [u8], Result<Action, Comma
     Ok((input, Ok(ret)))
 }
 
+pub fn bulk_delete(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    alt((empty_trash, empty_folder, expunge, undo_delete))(input)
+}
+pub fn empty_trash(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 0, empty_trash};
+    let (input, _) = tag("empty-trash")(input.trim())?;
+    arg_chk!(start check, input);
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(Listing(EmptyTrash))))
+}
+pub fn empty_folder(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 1, empty_folder};
+    let (input, _) = tag("empty-folder")(input.trim())?;
+    arg_chk!(start check, input);
+    if let Ok((input, _)) = eof(input) {
+        arg_chk!(finish check, input);
+        return Ok((input, Ok(Listing(EmptyMailbox(None)))));
+    }
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, path) = quoted_argument(input)?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(Listing(EmptyMailbox(Some(path.to_string()))))))
+}
+pub fn expunge(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 0, expunge};
+    let (input, _) = tag("expunge")(input.trim())?;
+    arg_chk!(start check, input);
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(Listing(Expunge))))
+}
+pub fn undo_delete(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 0, undo_delete};
+    let (input, _) = tag("undo-delete")(input.trim())?;
+    arg_chk!(start check, input);
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(Listing(UndoDelete))))
+}
+
 pub fn copymove<'a>(input: &'a [u8]) -> IResult<&'a [u8], Result<Action, CommandError>> {
     alt((
+        |input: &'a [u8]| -> IResult<&'a [u8], Result<Action, CommandError>> {
+            let mut check = arg_init! { min_arg:1, max_arg: 1, copymove};
+            let (input, action) = alt((tag("copyto-thread"), tag("moveto-thread")))(input.trim())?;
+            arg_chk!(start check, input);
+            let (input, _) = is_a(" ")(input)?;
+            arg_chk!(inc check, input);
+            let (input, path) = quoted_argument(input)?;
+            arg_chk!(finish check, input);
+            let (input, _) = eof(input)?;
+            let path = path.to_string();
+            Ok((
+                input,
+                Ok(Listing(if action == b"copyto-thread" {
+                    CopyThreadTo(path)
+                } else {
+                    MoveThreadTo(path)
+                })),
+            ))
+        },
         |input: &'a [u8]| -> IResult<&'a [u8], Result<Action, CommandError>> {
             let mut check = arg_init! { min_arg:1, max_arg: 1, copymove};
             let (input, _) = tag("copyto")(input.trim())?;
@@ -447,15 +579,22 @@ pub fn subsort(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
     Ok((input, Ok(SubSort(p.0, p.1))))
 }
 pub fn sort(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
-    let mut check = arg_init! { min_arg:1, max_arg: 2, sort};
+    let mut check = arg_init! { min_arg:1, max_arg: {u8::MAX}, sort};
     let (input, _) = tag("sort")(input.trim())?;
     arg_chk!(start check, input);
     let (input, _) = is_a(" ")(input)?;
     arg_chk!(inc check, input);
-    let (input, p) = separated_pair(sortfield, tag(" "), sortorder)(input)?;
+    let (input, chain) = sortchain(input)?;
     arg_chk!(finish check, input);
     let (input, _) = eof(input)?;
-    Ok((input, Ok(Sort(p.0, p.1))))
+    Ok((input, Ok(Sort(chain))))
+}
+pub fn sort_reverse(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let (input, _) = tag("sort")(input.trim())?;
+    let (input, _) = is_a(" ")(input)?;
+    let (input, _) = tag("reverse")(input.trim())?;
+    let (input, _) = eof(input.trim())?;
+    Ok((input, Ok(ReverseSort)))
 }
 pub fn sort_column(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
     let mut check = arg_init! { min_arg:1, max_arg: 1, sort_column};
@@ -485,6 +624,195 @@ pub fn search(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
     let (input, _) = eof(input)?;
     Ok((input, Ok(Listing(Search(String::from(string))))))
 }
+/// Search all mailboxes of the account and list the results in a new tab.
+///
+/// # Example
+///
+/// ```
+/// # use meli::command::{Action, ListingAction, parser::search_tab};
+///
+/// let (rest, parsed) = search_tab(b"search-tab from:alice").unwrap();
+/// assert_eq!(rest, b"");
+/// assert_eq!(parsed, Ok(Action::Listing(ListingAction::SearchInTab("from:alice".to_string()))));
+/// ```
+pub fn search_tab(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:1, max_arg:{ u8::MAX}, search_tab};
+    let (input, _) = tag("search-tab")(input.trim())?;
+    arg_chk!(start check, input);
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, string) = map_res(not_line_ending, std::str::from_utf8)(input)?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(Listing(SearchInTab(string.trim().to_string())))))
+}
+pub fn refine_search(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:1, max_arg:{ u8::MAX}, refine_search};
+    let (input, _) = tag("refine-search")(input.trim())?;
+    arg_chk!(start check, input);
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, string) = map_res(not_line_ending, std::str::from_utf8)(input)?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(Listing(RefineSearch(string.trim().to_string())))))
+}
+pub fn rerun_search(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 0, rerun_search};
+    let (input, _) = tag("rerun-search")(input.trim())?;
+    arg_chk!(start check, input);
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(Listing(RerunSearch))))
+}
+/// List all e-mail exchanged with an address, or with the sender of the
+/// entry under the cursor.
+///
+/// # Example
+///
+/// ```
+/// # use meli::command::{Action, ListingAction, parser::correspondence};
+///
+/// let (rest, parsed) = correspondence(b"correspondence alice@example.com").unwrap();
+/// assert_eq!(rest, b"");
+/// assert_eq!(
+///     parsed,
+///     Ok(Action::Listing(ListingAction::Correspondence(Some(
+///         "alice@example.com".to_string()
+///     ))))
+/// );
+/// ```
+pub fn correspondence(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg:{ u8::MAX}, correspondence};
+    let (input, _) = tag("correspondence")(input.trim())?;
+    arg_chk!(start check, input);
+    if input.trim().is_empty() {
+        arg_chk!(finish check, input);
+        let (input, _) = eof(input.trim())?;
+        return Ok((input, Ok(Listing(Correspondence(None)))));
+    }
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, address) = map_res(not_line_ending, std::str::from_utf8)(input)?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((
+        input,
+        Ok(Listing(Correspondence(Some(address.trim().to_string())))),
+    ))
+}
+pub fn limit(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:1, max_arg:{ u8::MAX}, limit};
+    let (input, _) = tag("limit")(input.trim())?;
+    arg_chk!(start check, input);
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, string) = map_res(not_line_ending, std::str::from_utf8)(input)?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    let term = string.trim();
+    Ok((
+        input,
+        Ok(Listing(Limit((term != "all").then(|| term.to_string())))),
+    ))
+}
+/// Move the cursor to the first entry on or after a date in `YYYY-MM-DD`
+/// format.
+///
+/// # Example
+///
+/// ```
+/// # use meli::command::{Action, ListingAction, parser::jump_to_date};
+///
+/// let (rest, parsed) = jump_to_date(b"jump-to-date 2024-03-01").unwrap();
+/// assert_eq!(rest, b"");
+/// assert_eq!(parsed, Ok(Action::Listing(ListingAction::JumpToDate(1709251200))));
+/// ```
+pub fn jump_to_date(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:1, max_arg: 1, jump_to_date};
+    let (input, _) = tag("jump-to-date")(input.trim())?;
+    arg_chk!(start check, input);
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, date) = quoted_argument(input.trim())?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    match melib::utils::datetime::parse_timestamp_from_string(
+        date,
+        melib::utils::datetime::formats::RFC3339_DATE,
+    ) {
+        Ok((_, timestamp)) => Ok((input, Ok(Listing(JumpToDate(timestamp))))),
+        Err(_) => Ok((
+            input,
+            Err(CommandError::BadValue {
+                inner: format!("{date} is not a date in YYYY-MM-DD format").into(),
+                suggestions: None,
+            }),
+        )),
+    }
+}
+/// Set up or cancel a follow-up reminder.
+///
+/// The deadline is either a date in `YYYY-MM-DD` format or a number of days,
+/// hours or weeks from now, e.g. `3d`, `12h`, `2w`.
+///
+/// # Example
+///
+/// ```
+/// # use meli::command::{Action, ListingAction, AwaitReplyAction, parser::await_reply};
+///
+/// let (rest, parsed) = await_reply(b"await-reply 3d").unwrap();
+/// assert_eq!(rest, b"");
+/// assert!(matches!(parsed, Ok(Action::Listing(ListingAction::AwaitReply(AwaitReplyAction::Within(259200))))), "{:?}", parsed);
+/// ```
+pub fn await_reply(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:1, max_arg: 1, await_reply};
+    let (input, _) = tag("await-reply")(input.trim())?;
+    arg_chk!(start check, input);
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, deadline) = quoted_argument(input.trim())?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    if deadline == "cancel" {
+        return Ok((input, Ok(Listing(AwaitReply(AwaitReplyAction::Cancel)))));
+    }
+    let within = deadline.char_indices().last().and_then(|(idx, unit)| {
+        let secs = match unit {
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+        Some(deadline[..idx].parse::<u64>().ok()? * secs)
+    });
+    if let Some(within) = within {
+        return Ok((
+            input,
+            Ok(Listing(AwaitReply(AwaitReplyAction::Within(within)))),
+        ));
+    }
+    match melib::utils::datetime::parse_timestamp_from_string(
+        deadline,
+        melib::utils::datetime::formats::RFC3339_DATE,
+    ) {
+        Ok((_, timestamp)) => Ok((
+            input,
+            Ok(Listing(AwaitReply(AwaitReplyAction::By(timestamp)))),
+        )),
+        Err(_) => Ok((
+            input,
+            Err(CommandError::BadValue {
+                inner: format!(
+                    "{deadline} is not a date in YYYY-MM-DD format or a duration such as 3d, 12h \
+                     or 2w"
+                )
+                .into(),
+                suggestions: Some(&["cancel", "1d", "3d", "1w"]),
+            }),
+        )),
+    }
+}
 pub fn select(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
     #[inline]
     fn clear_selection(input: &[u8]) -> Option<IResult<&[u8], Result<Action, CommandError>>> {
@@ -533,6 +861,35 @@ pub fn export_mbox(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>>
         ))),
     ))
 }
+pub fn export(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:2, max_arg: 2, export};
+    let (input, _) = tag("export")(input.trim())?;
+    arg_chk!(start check, input);
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, format) = alt((
+        tag("mbox"),
+        tag("eml"),
+        tag("html"),
+        tag("markdown"),
+        tag("md"),
+    ))(input)?;
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, path) = quoted_argument(input.trim())?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    let path = std::path::PathBuf::from(path.to_string());
+    Ok((
+        input,
+        Ok(Listing(match format {
+            b"eml" => ExportEml(path),
+            b"html" => ExportThread(ThreadExportFormat::Html, path),
+            b"markdown" | b"md" => ExportThread(ThreadExportFormat::Markdown, path),
+            _ => ExportMbox(Some(melib::mbox::MboxFormat::MboxCl2), path),
+        })),
+    ))
+}
 pub fn mailinglist(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
     let mut check = arg_init! { min_arg:0, max_arg: 0, mailinglist};
     arg_chk!(start check, input);
@@ -547,6 +904,17 @@ pub fn mailinglist(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>>
     let (input, _) = eof(input)?;
     Ok((input, Ok(ret)))
 }
+pub fn forge(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 0, forge};
+    arg_chk!(start check, input);
+    let (input, ret) = alt((
+        map(tag("forge-open"), |_| Forge(ForgeAction::Open)),
+        map(tag("forge-mute"), |_| Forge(ForgeAction::Mute)),
+    ))(input.trim())?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(ret)))
+}
 pub fn setenv(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
     let mut check = arg_init! { min_arg:1, max_arg: 1, setenv};
     let (input, _) = tag("setenv")(input.trim())?;
@@ -763,6 +1131,14 @@ pub fn discard_draft(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError
         Ok(Tab(ComposerAction(ComposerTabAction::DiscardDraft))),
     ))
 }
+pub fn reformat(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 0, reformat };
+    let (input, _) = tag("reformat")(input.trim())?;
+    arg_chk!(start check, input);
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(Tab(ComposerAction(ComposerTabAction::Reformat)))))
+}
 pub fn create_mailbox(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
     let mut check = arg_init! { min_arg:1, max_arg: 1, create_malbox};
     let (input, _) = tag("create-mailbox")(input.trim())?;
@@ -877,6 +1253,89 @@ pub fn reindex(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
     let (input, _) = eof(input)?;
     Ok((input, Ok(AccountAction(account.to_string(), ReIndex))))
 }
+pub fn contact_duplicates(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:1, max_arg: 1, contact_duplicates};
+    let (input, _) = tag("contact-duplicates")(input.trim())?;
+    arg_chk!(start check, input);
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, account) = quoted_argument(input)?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((
+        input,
+        Ok(AccountAction(account.to_string(), ContactDuplicates)),
+    ))
+}
+pub fn retention_preview(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:1, max_arg: 1, retention_preview};
+    let (input, _) = tag("retention-preview")(input.trim())?;
+    arg_chk!(start check, input);
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, account) = quoted_argument(input)?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((
+        input,
+        Ok(AccountAction(account.to_string(), RetentionPreview)),
+    ))
+}
+pub fn maintenance(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:1, max_arg: 1, maintenance};
+    let (input, _) = tag("maintenance")(input.trim())?;
+    arg_chk!(start check, input);
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, account) = quoted_argument(input)?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(AccountAction(account.to_string(), Maintenance))))
+}
+pub fn special_mailboxes(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:1, max_arg: 1, special_mailboxes};
+    let (input, _) = tag("special-mailboxes")(input.trim())?;
+    arg_chk!(start check, input);
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, account) = quoted_argument(input)?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((
+        input,
+        Ok(AccountAction(account.to_string(), SpecialMailboxes)),
+    ))
+}
+pub fn import_contacts(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:3, max_arg: 3, import_contacts};
+    let (input, _) = tag("import-contacts")(input.trim())?;
+    arg_chk!(start check, input);
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, account) = quoted_argument(input)?;
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, format) = command_err!(nom
+                                       alt((
+                                               map(tag("mutt"), |_| ImportFormat::MuttAlias),
+                                               map(tag("abook"), |_| ImportFormat::Abook),
+                                       ))(input),
+                                       input,
+                                       String::from_utf8_lossy(input.trim()).to_string(),
+                                       Some(&["mutt", "abook"]));
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, path) = quoted_argument(input)?;
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((
+        input,
+        Ok(AccountAction(
+            account.to_string(),
+            ImportContacts(format, path.to_string().into()),
+        )),
+    ))
+}
 pub fn open_in_new_tab(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
     let mut check = arg_init! { min_arg:0, max_arg: 0, open_in_tab};
     let (input, _) = tag("open-in-tab")(input.trim())?;
@@ -985,6 +1444,20 @@ pub fn export_mail(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>>
     let (input, _) = eof(input)?;
     Ok((input, Ok(View(ExportMail(path.to_string())))))
 }
+pub fn print_mail(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 255, print_mail};
+    let (input, _) = tag("print-mail")(input.trim())?;
+    arg_chk!(start check, input);
+    if let Ok((input, _)) = eof(input) {
+        arg_chk!(finish check, input);
+        return Ok((input, Ok(View(PrintMail(None)))));
+    }
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, cmd) = map_res(not_line_ending, std::str::from_utf8)(input)?;
+    arg_chk!(finish check, input);
+    Ok((input, Ok(View(PrintMail(Some(cmd.to_string()))))))
+}
 pub fn add_addresses_to_contacts(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
     let mut check = arg_init! { min_arg:0, max_arg: 0, add_addresses_to_contacts};
     let (input, _) = tag("add-addresses-to-contacts")(input.trim())?;
@@ -1128,6 +1601,57 @@ pub fn manage_jobs(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>>
     let (input, _) = eof(input)?;
     Ok((input, Ok(Tab(ManageJobs))))
 }
+pub fn mailbox_attachments(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 0, mailbox_attachments};
+    let (input, _) = tag("mailbox-attachments")(input.trim())?;
+    arg_chk!(start check, input);
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(Tab(MailboxAttachments))))
+}
+pub fn triage(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 0, triage};
+    let (input, _) = tag("triage")(input.trim())?;
+    arg_chk!(start check, input);
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(Tab(Triage))))
+}
+pub fn startup_profile(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 0, startup_profile};
+    let (input, _) = tag("startup-profile")(input.trim())?;
+    arg_chk!(start check, input);
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(Tab(ViewStartupProfile))))
+}
+pub fn usage_stats(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 1, usage_stats};
+    let (input, _) = tag("usage-stats")(input.trim())?;
+    arg_chk!(start check, input);
+    if let Ok((input, _)) = eof(input) {
+        arg_chk!(finish check, input);
+        return Ok((input, Ok(Tab(ViewUsageStats))));
+    }
+    let (input, _) = is_a(" ")(input)?;
+    arg_chk!(inc check, input);
+    let (input, _) = command_err!(nom
+                                  tag("purge")(input.trim()),
+                                  input,
+                                  String::from_utf8_lossy(input.trim()).to_string(),
+                                  Some(&["purge"]));
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(PurgeUsageStats)))
+}
+pub fn manage_settings(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
+    let mut check = arg_init! { min_arg:0, max_arg: 0, manage_settings};
+    let (input, _) = tag("manage-settings")(input.trim())?;
+    arg_chk!(start check, input);
+    arg_chk!(finish check, input);
+    let (input, _) = eof(input)?;
+    Ok((input, Ok(Tab(ManageSettings))))
+}
 
 pub fn view_manpage(input: &[u8]) -> IResult<&[u8], Result<Action, CommandError>> {
     let mut check = arg_init! { min_arg:1, max_arg: 1, view_manpage };
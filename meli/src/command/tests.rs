@@ -45,7 +45,19 @@ fn test_command_parser() {
     }
     assert_eq!(
         &match_input!(input),
-        &IntoIterator::into_iter(["sort date".to_string(), "sort subject".to_string()]).collect(),
+        &IntoIterator::into_iter([
+            "sort date".to_string(),
+            "sort subject".to_string(),
+            "sort size".to_string(),
+            "sort attachments".to_string(),
+            "sort from".to_string(),
+            "sort domain".to_string(),
+            "sort recipients".to_string(),
+            "sort thread-length".to_string(),
+            "sort unseen".to_string(),
+            "sort reverse".to_string(),
+        ])
+        .collect(),
     );
     input = "so".to_string();
     assert_eq!(
@@ -128,16 +140,201 @@ fn test_command_parser_all() {
     for cmd in [
         "set unseen",
         "set seen",
+        "set local listing.sort=subject asc",
+        "set local pager.filter = \"less\"",
         "delete",
         "copyto somewhere",
         "moveto somewhere",
+        "copyto-thread somewhere",
+        "empty-trash",
+        "empty-folder",
+        "empty-folder \"INBOX/old stuff\"",
+        "expunge",
+        "undo-delete",
+        "moveto-thread somewhere",
         "import fpath mpath",
         "close  ",
         "go 5",
+        "sort size desc",
+        "sort domain asc",
+        "subsort thread-length desc",
+        "sort unseen desc, date desc",
+        "sort unseen, subject asc",
+        "sort reverse",
+        "limit from:alice and not flags:seen",
+        "limit all",
+        "contact-duplicates personal",
+        "import-contacts personal mutt ~/.mutt/aliases",
+        "import-contacts personal abook ~/.abook/addressbook",
+        "retention-preview personal",
+        "maintenance personal",
+        "special-mailboxes personal",
+        "reformat",
     ] {
         parse_command(cmd.as_bytes()).unwrap_or_else(|err| panic!("{cmd} failed {err}"));
     }
 
+    assert_eq!(
+        parse_command(b"sort unseen desc, date asc").unwrap(),
+        Action::Sort("unseen desc, date asc".parse::<melib::SortChain>().unwrap()),
+    );
+    assert_eq!(
+        parse_command(b"limit subject:meli").unwrap(),
+        Action::Listing(ListingAction::Limit(Some("subject:meli".to_string()))),
+    );
+    assert_eq!(
+        parse_command(b"limit all").unwrap(),
+        Action::Listing(ListingAction::Limit(None)),
+    );
+    assert_eq!(
+        parse_command(b"empty-folder Spam").unwrap(),
+        Action::Listing(ListingAction::EmptyMailbox(Some("Spam".to_string()))),
+    );
+    assert_eq!(
+        parse_command(b"empty-folder").unwrap(),
+        Action::Listing(ListingAction::EmptyMailbox(None)),
+    );
+    assert!(parse_command(b"empty-trash").unwrap().needs_confirmation());
+    assert!(!parse_command(b"undo-delete").unwrap().needs_confirmation());
+    assert_eq!(
+        parse_command(b"forge-open").unwrap(),
+        Action::Forge(ForgeAction::Open),
+    );
+    assert!(!parse_command(b"forge-open").unwrap().needs_confirmation());
+    assert!(parse_command(b"forge-mute").unwrap().needs_confirmation());
+    assert_eq!(
+        parse_command(b"moveto-thread Archive").unwrap(),
+        Action::Listing(ListingAction::MoveThreadTo("Archive".to_string())),
+    );
+    assert_eq!(
+        parse_command(b"copyto-thread \"Lists/meli dev\"").unwrap(),
+        Action::Listing(ListingAction::CopyThreadTo("Lists/meli dev".to_string())),
+    );
+    assert_eq!(
+        parse_command(b"moveto Archive").unwrap(),
+        Action::Listing(ListingAction::MoveTo("Archive".to_string())),
+    );
+    assert_eq!(
+        parse_command(b"export eml ~/backup").unwrap(),
+        Action::Listing(ListingAction::ExportEml("~/backup".into())),
+    );
+    assert_eq!(
+        parse_command(b"export html ~/thread.html").unwrap(),
+        Action::Listing(ListingAction::ExportThread(
+            ThreadExportFormat::Html,
+            "~/thread.html".into()
+        )),
+    );
+    assert_eq!(
+        parse_command(b"export md thread.md").unwrap(),
+        Action::Listing(ListingAction::ExportThread(
+            ThreadExportFormat::Markdown,
+            "thread.md".into()
+        )),
+    );
+    assert_eq!(
+        parse_command(b"export mbox thread.mbox").unwrap(),
+        Action::Listing(ListingAction::ExportMbox(
+            Some(melib::mbox::MboxFormat::MboxCl2),
+            "thread.mbox".into()
+        )),
+    );
+    assert_eq!(
+        parse_command(b"export-mbox thread.mbox").unwrap(),
+        Action::Listing(ListingAction::ExportMbox(
+            Some(melib::mbox::MboxFormat::MboxCl2),
+            "thread.mbox".into()
+        )),
+    );
+    assert_eq!(
+        parse_command(b"await-reply 2w").unwrap(),
+        Action::Listing(ListingAction::AwaitReply(AwaitReplyAction::Within(
+            14 * 24 * 60 * 60
+        ))),
+    );
+    assert_eq!(
+        parse_command(b"await-reply 2024-03-01").unwrap(),
+        Action::Listing(ListingAction::AwaitReply(AwaitReplyAction::By(1709251200))),
+    );
+    assert_eq!(
+        parse_command(b"await-reply cancel").unwrap(),
+        Action::Listing(ListingAction::AwaitReply(AwaitReplyAction::Cancel)),
+    );
+    assert!(parse_command(b"await-reply tomorrow").is_err());
+    assert_eq!(
+        parse_command(b"jump-to-date 2024-03-01").unwrap(),
+        Action::Listing(ListingAction::JumpToDate(1709251200)),
+    );
+    assert!(parse_command(b"jump-to-date yesterday").is_err());
+    assert_eq!(
+        parse_command(b"search-tab subject:invoice and not is:seen").unwrap(),
+        Action::Listing(ListingAction::SearchInTab(
+            "subject:invoice and not is:seen".to_string()
+        )),
+    );
+    assert_eq!(
+        parse_command(b"refine-search from:bob").unwrap(),
+        Action::Listing(ListingAction::RefineSearch("from:bob".to_string())),
+    );
+    assert_eq!(
+        parse_command(b"rerun-search").unwrap(),
+        Action::Listing(ListingAction::RerunSearch),
+    );
+    assert!(parse_command(b"search-tab").is_err());
+    assert_eq!(
+        parse_command(b"mailbox-attachments").unwrap(),
+        Action::Tab(TabAction::MailboxAttachments),
+    );
+    assert!(parse_command(b"mailbox-attachments all").is_err());
+    assert_eq!(
+        parse_command(b"triage").unwrap(),
+        Action::Tab(TabAction::Triage),
+    );
+    assert!(parse_command(b"triage INBOX").is_err());
+    assert_eq!(
+        parse_command(b"startup-profile").unwrap(),
+        Action::Tab(TabAction::ViewStartupProfile),
+    );
+    assert_eq!(
+        parse_command(b"usage-stats").unwrap(),
+        Action::Tab(TabAction::ViewUsageStats),
+    );
+    assert_eq!(
+        parse_command(b"usage-stats purge").unwrap(),
+        Action::PurgeUsageStats,
+    );
+    assert!(parse_command(b"usage-stats send").is_err());
+    assert_eq!(
+        parse_command(b"correspondence").unwrap(),
+        Action::Listing(ListingAction::Correspondence(None)),
+    );
+    assert_eq!(
+        parse_command(b"correspondence Alice <alice@example.com>").unwrap(),
+        Action::Listing(ListingAction::Correspondence(Some(
+            "Alice <alice@example.com>".to_string()
+        ))),
+    );
+    assert_eq!(
+        parse_command(b"search subject:invoice").unwrap(),
+        Action::Listing(ListingAction::Search("subject:invoice".to_string())),
+    );
+    assert_eq!(
+        parse_command(b"print-mail").unwrap(),
+        Action::View(ViewAction::PrintMail(None)),
+    );
+    assert_eq!(
+        parse_command(b"print-mail lpr -P office").unwrap(),
+        Action::View(ViewAction::PrintMail(Some("lpr -P office".into()))),
+    );
+    assert_eq!(
+        parse_command(b"print pager.print_command").unwrap(),
+        Action::PrintSetting("pager.print_command".into()),
+    );
+    assert_eq!(
+        parse_command(b"sort 2 asc").unwrap(),
+        Action::SortColumn(2, melib::SortOrder::Asc)
+    );
+
     assert_eq!(
         parse_command(b"setfafsfoo").unwrap_err().to_string(),
         Parsing {
@@ -204,3 +401,24 @@ fn test_command_error_display() {
          conversations"
     );
 }
+
+#[test]
+fn test_command_synopsis() {
+    let synopsis = |tag: &str, desc_prefix: &str| {
+        COMMAND_COMPLETION
+            .iter()
+            .find(|(t, d, _, _)| *t == tag && d.starts_with(desc_prefix))
+            .map(|(_, _, tokens, _)| tokens.to_string())
+            .unwrap()
+    };
+    assert_eq!(
+        synopsis("empty-folder", "empty-folder"),
+        "empty-folder [MAILBOX_PATH]"
+    );
+    assert_eq!(synopsis("toggle thread_snooze", ""), "toggle thread_snooze");
+    assert_eq!(
+        synopsis("sort", "sort <column index>"),
+        "sort INDEX [{asc|desc}]"
+    );
+    assert_eq!(synopsis("set local", ""), "set local STRING");
+}
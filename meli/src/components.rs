@@ -162,6 +162,12 @@ pub trait Component: std::fmt::Display + std::fmt::Debug + Send + Sync {
         String::new()
     }
 
+    /// The kind of view the component currently shows, if it is a view of its
+    /// own, for the usage statistics. It must not reveal what is shown.
+    fn view_name(&self) -> Option<&'static str> {
+        None
+    }
+
     fn attributes(&self) -> &'static ComponentAttr {
         &ComponentAttr::DEFAULT
     }
@@ -229,6 +235,10 @@ impl Component for Box<dyn Component> {
         (**self).status(context)
     }
 
+    fn view_name(&self) -> Option<&'static str> {
+        (**self).view_name()
+    }
+
     fn attributes(&self) -> &'static ComponentAttr {
         (**self).attributes()
     }
@@ -36,11 +36,12 @@ use std::{
 
 use indexmap::IndexMap;
 use melib::{
-    backends::{MailboxHash, TagHash},
+    backends::{MailboxHash, SpecialUsageMailbox, TagHash},
     conf::{ActionFlag, MailboxConf, ToggleFlag},
     error::*,
     search::Query,
-    ShellExpandTrait, SortField, SortOrder, StderrLogger,
+    utils::fnmatch::Fnmatch,
+    ShellExpandTrait, SortChain, SortField, SortOrder, StderrLogger,
 };
 use serde::{Deserialize, Serialize};
 
@@ -51,6 +52,7 @@ pub mod preprocessing;
 use preprocessing as pp;
 
 pub mod data_types;
+pub mod edit;
 #[cfg(test)]
 pub mod tests;
 #[rustfmt::skip]
@@ -64,6 +66,7 @@ pub mod tags;
 #[macro_use]
 pub mod shortcuts;
 mod listing;
+pub mod muttrc;
 pub mod terminal;
 mod themes;
 use default_values::*;
@@ -145,6 +148,15 @@ pub struct MailUIConf {
     pub pgp: PGPSettingsOverride,
 }
 
+impl MailUIConf {
+    /// Set the setting at the dotted path `key`, e.g. `listing.sort`, to
+    /// `value`. `value` is parsed as a TOML value, and used as a string if it
+    /// is not one.
+    pub fn set_key(&mut self, key: &str, value: &str) -> Result<()> {
+        edit::set_key(self, &edit::split_key(key)?, edit::parse_value(value))
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(default)]
 pub struct FileMailboxConf {
@@ -152,6 +164,9 @@ pub struct FileMailboxConf {
     pub conf_override: MailUIConf,
     #[serde(default = "false_val")]
     pub collapsed: bool,
+    /// Rules for archiving or deleting old e-mail of this mailbox.
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub retention: Vec<RetentionRule>,
     #[serde(flatten)]
     pub mailbox_conf: MailboxConf,
 }
@@ -166,6 +181,94 @@ impl FileMailboxConf {
     }
 }
 
+/// A rule for archiving or deleting old e-mail of a mailbox, e.g.
+///
+/// ```toml
+/// [accounts.personal.mailboxes."INBOX"]
+/// retention = [ { older_than_days = 90, action = "move", destination = "Archive/%Y" } ]
+/// ```
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RetentionRule {
+    /// Age in days after which e-mail are affected.
+    #[serde(alias = "older-than-days")]
+    pub older_than_days: u64,
+    #[serde(flatten)]
+    pub action: RetentionAction,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum RetentionAction {
+    /// Move e-mail to `destination`, a mailbox path which is expanded with
+    /// `strftime` for each e-mail's date, e.g. `Archive/%Y`.
+    Move { destination: String },
+    /// Delete e-mail.
+    Delete,
+}
+
+/// Mailbox paths to use for special usages, overriding the mailboxes detected
+/// from the backend's `SPECIAL-USE` attributes and from mailbox names.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpecialMailboxes {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drafts: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub junk: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<String>,
+}
+
+impl SpecialMailboxes {
+    /// The special usages that can be mapped to a mailbox.
+    pub const USAGES: [SpecialUsageMailbox; 5] = [
+        SpecialUsageMailbox::Sent,
+        SpecialUsageMailbox::Drafts,
+        SpecialUsageMailbox::Trash,
+        SpecialUsageMailbox::Junk,
+        SpecialUsageMailbox::Archive,
+    ];
+
+    fn field_mut(&mut self, usage: SpecialUsageMailbox) -> Option<&mut Option<String>> {
+        match usage {
+            SpecialUsageMailbox::Sent => Some(&mut self.sent),
+            SpecialUsageMailbox::Drafts => Some(&mut self.drafts),
+            SpecialUsageMailbox::Trash => Some(&mut self.trash),
+            SpecialUsageMailbox::Junk => Some(&mut self.junk),
+            SpecialUsageMailbox::Archive => Some(&mut self.archive),
+            SpecialUsageMailbox::Normal
+            | SpecialUsageMailbox::Inbox
+            | SpecialUsageMailbox::Flagged => None,
+        }
+    }
+
+    /// The mailbox path mapped to `usage`, if any.
+    pub fn get(&self, usage: SpecialUsageMailbox) -> Option<&str> {
+        match usage {
+            SpecialUsageMailbox::Sent => self.sent.as_deref(),
+            SpecialUsageMailbox::Drafts => self.drafts.as_deref(),
+            SpecialUsageMailbox::Trash => self.trash.as_deref(),
+            SpecialUsageMailbox::Junk => self.junk.as_deref(),
+            SpecialUsageMailbox::Archive => self.archive.as_deref(),
+            SpecialUsageMailbox::Normal
+            | SpecialUsageMailbox::Inbox
+            | SpecialUsageMailbox::Flagged => None,
+        }
+    }
+
+    /// Map `usage` to mailbox `path`. Usages not in [`Self::USAGES`] are
+    /// ignored.
+    pub fn set(&mut self, usage: SpecialUsageMailbox, path: String) {
+        if let Some(field) = self.field_mut(usage) {
+            *field = Some(path);
+        }
+    }
+}
+
 use crate::conf::deserializers::extra_settings;
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct FileAccount {
@@ -178,6 +281,23 @@ pub struct FileAccount {
     pub default_mailbox: Option<String>,
     pub format: String,
     pub send_mail: SendMail,
+    /// Rules that choose a different `send_mail` value by sender identity or
+    /// recipient domain when sending. The first matching rule is used,
+    /// otherwise [`Self::send_mail`].
+    #[serde(default, alias = "send-mail-rules")]
+    pub send_mail_rules: Vec<SendMailRule>,
+    /// How many more times to try sending an e-mail in the background if
+    /// sending fails.
+    ///
+    /// If not specified, the default is 0.
+    #[serde(default = "none", skip_serializing_if = "Option::is_none")]
+    pub send_mail_retries: Option<u8>,
+    /// Seconds to wait before retrying to send an e-mail. The wait doubles
+    /// after every attempt.
+    ///
+    /// If not specified, the default is 30 seconds.
+    #[serde(default = "none", skip_serializing_if = "Option::is_none")]
+    pub send_mail_retry_delay: Option<u64>,
     pub identity: String,
     #[serde(default)]
     pub extra_identities: Vec<String>,
@@ -195,6 +315,86 @@ pub struct FileAccount {
     pub manual_refresh: bool,
     #[serde(default = "none", skip_serializing_if = "Option::is_none")]
     pub refresh_command: Option<String>,
+    /// Milliseconds to wait for further envelope flag changes before
+    /// submitting them to the backend as a single operation. `0` disables
+    /// batching.
+    ///
+    /// If not specified, the default is 150 milliseconds.
+    #[serde(default = "none", skip_serializing_if = "Option::is_none")]
+    pub flag_batch_window: Option<u64>,
+    /// Seconds to hold deletions of the `empty-trash`, `empty-folder` and
+    /// `expunge` commands before performing them, during which `undo-delete`
+    /// cancels them. `0` deletes right away.
+    ///
+    /// If not specified, the default is 10 seconds.
+    #[serde(default = "none", skip_serializing_if = "Option::is_none")]
+    pub delete_grace_period: Option<u64>,
+    /// Show a virtual mailbox for each e-mail tag under a `Tags` mailbox in
+    /// the sidebar, if the account's backend supports tags.
+    #[serde(default = "false_val")]
+    pub tag_mailboxes: bool,
+    /// Only show virtual mailboxes for tags matching one of these glob
+    /// patterns. An empty list allows every tag.
+    #[serde(default)]
+    pub tag_mailboxes_allowlist: Vec<String>,
+    /// Run cache maintenance shortly after startup.
+    #[serde(default = "false_val")]
+    pub maintenance_on_startup: bool,
+    /// Hours between runs of cache maintenance. `0` disables periodic
+    /// maintenance.
+    #[serde(default = "none", skip_serializing_if = "Option::is_none")]
+    pub maintenance_interval: Option<u64>,
+    /// Megabytes of message bodies to keep in the offline cache of the
+    /// account's backend. Cache maintenance removes the least recently cached
+    /// bodies beyond it. If not specified, bodies are never removed.
+    #[serde(default = "none", skip_serializing_if = "Option::is_none")]
+    pub body_cache_budget: Option<u64>,
+    /// Megabytes of storage available to the account, e.g. the quota of its
+    /// server. The dashboard warns when the e-mail of the account uses 90% of
+    /// it or more.
+    #[serde(default = "none", skip_serializing_if = "Option::is_none")]
+    pub disk_quota: Option<u64>,
+    /// Commands that extract the text of attachments for the `sqlite3`
+    /// search index, keyed by MIME type glob. `%s` is replaced by the path of
+    /// a temporary file with the attachment, otherwise it is piped to the
+    /// command's standard input. An empty command disables extraction.
+    #[serde(
+        default = "default_attachment_extractors",
+        alias = "attachment-extractors"
+    )]
+    pub attachment_extractors: IndexMap<String, String>,
+    /// Absolute paths of the only executables that may be run on behalf of
+    /// this account, such as editors, viewers, filters and `send_mail`
+    /// commands. If not specified, every command may run.
+    #[serde(
+        default = "none",
+        skip_serializing_if = "Option::is_none",
+        alias = "allowed-commands"
+    )]
+    pub allowed_commands: Option<Vec<PathBuf>>,
+    /// Log every external command run on behalf of this account along with its
+    /// arguments.
+    #[serde(default = "false_val", alias = "log-commands")]
+    pub log_commands: bool,
+    /// Mailbox paths of the account's sent, drafts, trash, junk and archive
+    /// mailboxes. They take precedence over the detected mailboxes and over
+    /// the choices made in the `special-mailboxes` dialog.
+    #[serde(default, alias = "special-mailboxes")]
+    pub special_mailboxes: SpecialMailboxes,
+    /// Names of additional headers, such as `X-Spam-Status` or
+    /// `Delivered-To`, to keep in envelopes so that search queries and score
+    /// rules can match them.
+    #[serde(default, alias = "retained-headers")]
+    pub retained_headers: Vec<String>,
+    /// Path of a Sieve (RFC 5228) script that files, redirects or discards
+    /// new e-mail of the account as it arrives. Only `maildir` and `mbox`
+    /// accounts support it.
+    #[serde(
+        default = "none",
+        skip_serializing_if = "Option::is_none",
+        alias = "sieve-script"
+    )]
+    pub sieve_script: Option<PathBuf>,
     #[serde(flatten)]
     pub conf_override: MailUIConf,
     #[serde(flatten)]
@@ -207,6 +407,13 @@ pub struct FileAccount {
     pub extra: IndexMap<String, String>,
 }
 
+fn default_attachment_extractors() -> IndexMap<String, String> {
+    [("application/pdf", "pdftotext -q %s -")]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
 impl FileAccount {
     pub fn mailboxes(&self) -> &IndexMap<String, FileMailboxConf> {
         &self.mailboxes
@@ -215,6 +422,67 @@ impl FileAccount {
     pub fn search_backend(&self) -> &data_types::SearchBackend {
         &self.search_backend
     }
+
+    pub fn flag_batch_window(&self) -> std::time::Duration {
+        self.flag_batch_window
+            .map(std::time::Duration::from_millis)
+            .unwrap_or(crate::accounts::DEFAULT_FLAG_BATCH_WINDOW)
+    }
+
+    /// The number of retries of failed sends and the delay before the first
+    /// one.
+    pub fn send_mail_retry_policy(&self) -> (u8, std::time::Duration) {
+        (
+            self.send_mail_retries.unwrap_or(0),
+            self.send_mail_retry_delay
+                .map(std::time::Duration::from_secs)
+                .unwrap_or(crate::accounts::DEFAULT_SEND_MAIL_RETRY_DELAY),
+        )
+    }
+
+    pub fn delete_grace_period(&self) -> std::time::Duration {
+        self.delete_grace_period
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(crate::accounts::DEFAULT_DELETE_GRACE_PERIOD)
+    }
+
+    pub fn maintenance_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(
+            self.maintenance_interval
+                .unwrap_or(0)
+                .saturating_mul(60 * 60),
+        )
+    }
+
+    /// The `body_cache_budget` in bytes.
+    pub fn body_cache_budget(&self) -> Option<u64> {
+        self.body_cache_budget
+            .map(|mb| mb.saturating_mul(1024 * 1024))
+    }
+
+    /// The `disk_quota` in bytes.
+    pub fn disk_quota(&self) -> Option<u64> {
+        self.disk_quota.map(|mb| mb.saturating_mul(1024 * 1024))
+    }
+
+    /// The [`CommandPolicy`](crate::CommandPolicy) of external commands run
+    /// on behalf of this account.
+    pub fn command_policy(&self) -> crate::CommandPolicy {
+        crate::CommandPolicy {
+            allowed: self.allowed_commands.clone(),
+            log: self.log_commands,
+        }
+    }
+
+    /// Whether a virtual mailbox should be shown for `tag`.
+    pub fn tag_mailbox_allowed(&self, tag: &str) -> bool {
+        self.tag_mailboxes
+            && (self.tag_mailboxes_allowlist.is_empty()
+                || self
+                    .tag_mailboxes_allowlist
+                    .iter()
+                    .any(|pattern| tag.fnmatches(pattern)))
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -239,6 +507,12 @@ pub struct FileSettings {
     pub terminal: terminal::TerminalSettings,
     #[serde(default)]
     pub log: LogSettings,
+    #[serde(default)]
+    pub jobs: JobsSettings,
+    #[serde(default)]
+    pub usage_stats: UsageStatsSettings,
+    #[serde(default)]
+    pub plugins: IndexMap<String, PluginConf>,
 }
 
 #[derive(Clone, Debug, Default, Serialize)]
@@ -255,6 +529,38 @@ pub struct AccountConf {
 }
 
 impl AccountConf {
+    /// Shell commands that melib runs by itself to get the credentials of
+    /// this account: `server_password_command`, and the password and token
+    /// commands of its SMTP `send_mail` values.
+    pub fn credential_commands(&self) -> Vec<&str> {
+        let mut ret = vec![];
+        if let Some(cmd) = self.account.extra.get("server_password_command") {
+            ret.push(cmd.as_str());
+        }
+        #[cfg(feature = "smtp")]
+        for send_mail in std::iter::once(&self.send_mail)
+            .chain(self.conf.send_mail_rules.iter().map(|r| &r.send_mail))
+            .chain(self.conf_override.send_mail.as_ref())
+        {
+            use melib::smtp::{Password, SmtpAuth};
+
+            if let SendMail::Smtp(ref conf) = send_mail {
+                match conf.auth {
+                    SmtpAuth::Auto {
+                        password: Password::CommandEval(ref cmd),
+                        ..
+                    }
+                    | SmtpAuth::XOAuth2 {
+                        token_command: ref cmd,
+                        ..
+                    } => ret.push(cmd.as_str()),
+                    _ => {}
+                }
+            }
+        }
+        ret
+    }
+
     pub fn account(&self) -> &melib::AccountSettings {
         &self.account
     }
@@ -264,6 +570,40 @@ impl AccountConf {
     pub fn conf(&self) -> &FileAccount {
         &self.conf
     }
+
+    /// The `send_mail` value to send `draft` with: that of the first of
+    /// [`FileAccount::send_mail_rules`] that matches its sender and
+    /// recipients, otherwise [`Self::send_mail`].
+    pub fn send_mail_for(&self, draft: &melib::Draft) -> &SendMail {
+        if self.conf.send_mail_rules.is_empty() {
+            return &self.send_mail;
+        }
+        let from = draft
+            .headers()
+            .get(melib::HeaderName::FROM)
+            .and_then(|value| melib::Address::list_try_from(value).ok())
+            .and_then(|list| list.into_iter().next());
+        let mut recipients: Vec<melib::Address> = vec![];
+        for header in [
+            melib::HeaderName::TO,
+            melib::HeaderName::CC,
+            melib::HeaderName::BCC,
+        ] {
+            let Some(list) = draft
+                .headers()
+                .get(header)
+                .and_then(|value| melib::Address::list_try_from(value).ok())
+            else {
+                continue;
+            };
+            recipients.extend(list.iter().flat_map(melib::Address::mailboxes).cloned());
+        }
+        self.conf
+            .send_mail_rules
+            .iter()
+            .find(|rule| rule.matches(from.as_ref(), &recipients))
+            .map_or(&self.send_mail, |rule| &rule.send_mail)
+    }
     pub fn conf_mut(&mut self) -> &mut FileAccount {
         &mut self.conf
     }
@@ -300,6 +640,7 @@ impl From<FileAccount> for AccountConf {
             subscribed_mailboxes: x.subscribed_mailboxes.clone(),
             mailboxes,
             manual_refresh: x.manual_refresh,
+            retained_headers: x.retained_headers.clone(),
             extra: x.extra.clone().into_iter().collect(),
         };
 
@@ -316,6 +657,22 @@ impl From<FileAccount> for AccountConf {
     }
 }
 
+/// Read and parse the Sieve script at `path`, see
+/// [`FileAccount::sieve_script`].
+pub fn load_sieve_script(path: &Path) -> Result<melib::sieve::Script> {
+    let path = path.expand();
+    let source = std::fs::read_to_string(&path).map_err(|err| {
+        Error::new(format!(
+            "Could not read sieve script `{}`: {err}",
+            path.display()
+        ))
+        .set_source(Some(Arc::new(err)))
+    })?;
+    melib::sieve::Script::new(&source).map_err(|err| {
+        Error::new(format!("`{}`: {}", path.display(), err.summary)).set_kind(err.kind)
+    })
+}
+
 pub fn get_config_file() -> Result<PathBuf> {
     if let Ok(path) = env::var("MELI_CONFIG") {
         return Ok(PathBuf::from(path).expand());
@@ -430,6 +787,9 @@ impl FileSettings {
                 root_mailbox,
                 format,
                 send_mail: _,
+                send_mail_rules: _,
+                send_mail_retries: _,
+                send_mail_retry_delay: _,
                 identity,
                 extra_identities,
                 read_only,
@@ -440,11 +800,50 @@ impl FileSettings {
                 manual_refresh,
                 default_mailbox: _,
                 refresh_command: _,
+                flag_batch_window: _,
+                delete_grace_period: _,
+                tag_mailboxes: _,
+                tag_mailboxes_allowlist: _,
+                maintenance_on_startup: _,
+                maintenance_interval: _,
+                body_cache_budget: _,
+                disk_quota: _,
+                attachment_extractors: _,
+                allowed_commands,
+                log_commands: _,
+                special_mailboxes: _,
+                retained_headers,
+                sieve_script,
                 search_backend: _,
                 conf_override: _,
             } = acc.clone();
+            if let Some(path) = allowed_commands
+                .iter()
+                .flatten()
+                .find(|path| !path.is_absolute())
+            {
+                return Err(Error::new(format!(
+                    "Account `{name}`: allowed_commands must contain absolute paths, got `{}`",
+                    path.display()
+                ))
+                .set_kind(ErrorKind::Configuration));
+            }
 
             let lowercase_format = format.to_lowercase();
+            if let Some(path) = sieve_script.as_ref() {
+                if !matches!(lowercase_format.as_str(), "maildir" | "mbox") {
+                    return Err(Error::new(format!(
+                        "Account `{name}`: sieve_script is only supported by maildir and mbox \
+                         accounts"
+                    ))
+                    .set_kind(ErrorKind::Configuration));
+                }
+                load_sieve_script(path).map_err(|err| {
+                    Error::new(format!("Account `{name}`: {}", err.summary))
+                        .set_kind(ErrorKind::Configuration)
+                })?;
+            }
+
             let mut s = melib::AccountSettings {
                 name: name.to_string(),
                 root_mailbox,
@@ -455,6 +854,7 @@ impl FileSettings {
                 display_name,
                 subscribed_mailboxes,
                 manual_refresh,
+                retained_headers,
                 mailboxes: mailboxes
                     .into_iter()
                     .map(|(k, v)| (k, v.mailbox_conf))
@@ -532,6 +932,9 @@ impl FileSettings {
                 root_mailbox,
                 format,
                 send_mail: _,
+                send_mail_rules: _,
+                send_mail_retries: _,
+                send_mail_retry_delay: _,
                 identity,
                 extra_identities,
                 read_only,
@@ -542,11 +945,50 @@ impl FileSettings {
                 manual_refresh,
                 default_mailbox: _,
                 refresh_command: _,
+                flag_batch_window: _,
+                delete_grace_period: _,
+                tag_mailboxes: _,
+                tag_mailboxes_allowlist: _,
+                maintenance_on_startup: _,
+                maintenance_interval: _,
+                body_cache_budget: _,
+                disk_quota: _,
+                attachment_extractors: _,
+                allowed_commands,
+                log_commands: _,
+                special_mailboxes: _,
+                retained_headers,
+                sieve_script,
                 search_backend: _,
                 conf_override: _,
             } = acc.clone();
+            if let Some(path) = allowed_commands
+                .iter()
+                .flatten()
+                .find(|path| !path.is_absolute())
+            {
+                return Err(Error::new(format!(
+                    "Account `{name}`: allowed_commands must contain absolute paths, got `{}`",
+                    path.display()
+                ))
+                .set_kind(ErrorKind::Configuration));
+            }
 
             let lowercase_format = format.to_lowercase();
+            if let Some(path) = sieve_script.as_ref() {
+                if !matches!(lowercase_format.as_str(), "maildir" | "mbox") {
+                    return Err(Error::new(format!(
+                        "Account `{name}`: sieve_script is only supported by maildir and mbox \
+                         accounts"
+                    ))
+                    .set_kind(ErrorKind::Configuration));
+                }
+                load_sieve_script(path).map_err(|err| {
+                    Error::new(format!("Account `{name}`: {}", err.summary))
+                        .set_kind(ErrorKind::Configuration)
+                })?;
+            }
+
             let mut s = melib::AccountSettings {
                 name: name.to_string(),
                 root_mailbox,
@@ -557,6 +999,7 @@ impl FileSettings {
                 display_name,
                 subscribed_mailboxes,
                 manual_refresh,
+                retained_headers,
                 mailboxes: mailboxes
                     .into_iter()
                     .map(|(k, v)| (k, v.mailbox_conf))
@@ -593,6 +1036,9 @@ pub struct Settings {
     pub pgp: pgp::PGPSettings,
     pub terminal: terminal::TerminalSettings,
     pub log: LogSettings,
+    pub jobs: JobsSettings,
+    pub usage_stats: UsageStatsSettings,
+    pub plugins: IndexMap<String, PluginConf>,
     #[serde(skip)]
     pub _logger: StderrLogger,
 }
@@ -626,6 +1072,9 @@ impl Settings {
             pgp: fs.pgp,
             terminal: fs.terminal,
             log: fs.log,
+            jobs: fs.jobs,
+            usage_stats: fs.usage_stats,
+            plugins: fs.plugins,
             _logger,
         })
     }
@@ -649,9 +1098,67 @@ impl Settings {
             pgp: fs.pgp,
             terminal: fs.terminal,
             log: fs.log,
+            jobs: fs.jobs,
+            usage_stats: fs.usage_stats,
+            plugins: fs.plugins,
             _logger,
         })
     }
+
+    /// The settings of `section`, one of [`edit::SECTIONS`], as a TOML value.
+    pub fn section_value(&self, section: &str) -> Result<toml::Value> {
+        let ret = match section {
+            "pager" => toml::Value::try_from(&self.pager),
+            "listing" => toml::Value::try_from(&self.listing),
+            "notifications" => toml::Value::try_from(&self.notifications),
+            "shortcuts" => toml::Value::try_from(&self.shortcuts),
+            "tags" => toml::Value::try_from(&self.tags),
+            "composing" => toml::Value::try_from(&self.composing),
+            "pgp" => toml::Value::try_from(&self.pgp),
+            "terminal" => toml::Value::try_from(&self.terminal),
+            "log" => toml::Value::try_from(&self.log),
+            "jobs" => toml::Value::try_from(&self.jobs),
+            "usage_stats" => toml::Value::try_from(&self.usage_stats),
+            "plugins" => toml::Value::try_from(&self.plugins),
+            other => {
+                return Err(Error::new(format!("{other} is not a settings section"))
+                    .set_kind(ErrorKind::ValueError))
+            }
+        };
+        ret.map_err(|err| {
+            Error::new(format!("Could not serialize {section} settings"))
+                .set_details(err.to_string())
+        })
+    }
+
+    /// Set the setting at the key path `path`, e.g. `["pager", "filter"]`, to
+    /// `value`. The first part of `path` must be one of [`edit::SECTIONS`].
+    pub fn set_key<S: AsRef<str>>(&mut self, path: &[S], value: toml::Value) -> Result<()> {
+        let Some((section, rest)) = path.split_first().filter(|(_, rest)| !rest.is_empty()) else {
+            return Err(
+                Error::new(format!("{} is not a valid setting", edit::join_key(path)))
+                    .set_kind(ErrorKind::ValueError),
+            );
+        };
+        match section.as_ref() {
+            "pager" => edit::set_key(&mut self.pager, rest, value),
+            "listing" => edit::set_key(&mut self.listing, rest, value),
+            "notifications" => edit::set_key(&mut self.notifications, rest, value),
+            "shortcuts" => edit::set_key(&mut self.shortcuts, rest, value),
+            "tags" => edit::set_key(&mut self.tags, rest, value),
+            "composing" => edit::set_key(&mut self.composing, rest, value),
+            "pgp" => edit::set_key(&mut self.pgp, rest, value),
+            "terminal" => edit::set_key(&mut self.terminal, rest, value),
+            "log" => edit::set_key(&mut self.log, rest, value),
+            "jobs" => edit::set_key(&mut self.jobs, rest, value),
+            "usage_stats" => edit::set_key(&mut self.usage_stats, rest, value),
+            "plugins" => edit::set_key(&mut self.plugins, rest, value),
+            _ => Err(
+                Error::new(format!("{} is not a valid setting", edit::join_key(path)))
+                    .set_kind(ErrorKind::ValueError),
+            ),
+        }
+    }
 }
 
 mod deserializers {
@@ -759,4 +1266,70 @@ pub struct LogSettings {
     pub maximum_level: melib::LogLevel,
 }
 
+/// Settings of the thread pool that runs background jobs.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct JobsSettings {
+    /// Number of threads that run background jobs. If unset, the available
+    /// parallelism of the system is used.
+    #[serde(default, alias = "executor-threads")]
+    pub executor_threads: Option<std::num::NonZeroUsize>,
+    /// CPUs to pin the job threads to, in a round-robin fashion. Only
+    /// supported on Linux.
+    #[serde(default, alias = "cpu-affinity")]
+    pub cpu_affinity: Vec<usize>,
+}
+
+/// Settings of the local usage statistics, see [`crate::usage_stats`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct UsageStatsSettings {
+    /// Count the commands that are used and the time spent in each view. The
+    /// statistics are only stored in the data directory.
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Hook points a plugin can be called for, see [`crate::plugins`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginHook {
+    /// A new e-mail arrived in a mailbox.
+    MessageReceived,
+    /// A draft is about to be sent; the plugin can change or reject it.
+    PreSend,
+    /// The plugin provides a column of the compact listing.
+    ListingColumn,
+}
+
+/// An external process `meli` exchanges JSON-RPC messages with, see
+/// [`crate::plugins`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginConf {
+    /// Command line of the plugin, run with `sh -c`.
+    pub command: String,
+    /// Hook points the plugin is called for.
+    #[serde(default)]
+    pub hooks: Vec<PluginHook>,
+    /// Names of the commands the plugin handles in command mode.
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Maximum width of the plugin's listing column.
+    #[serde(default = "default_plugin_column_width", alias = "column-width")]
+    pub column_width: usize,
+    /// Seconds to wait for the reply to a `pre_send` request before sending
+    /// the draft unchanged.
+    #[serde(default = "default_plugin_timeout")]
+    pub timeout: u64,
+}
+
+const fn default_plugin_column_width() -> usize {
+    12
+}
+
+const fn default_plugin_timeout() -> u64 {
+    5
+}
+
 pub use data_types::dotaddressable::*;
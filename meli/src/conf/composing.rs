@@ -28,10 +28,13 @@ use melib::{
     conf::ActionFlag,
     email::HeaderName,
     error::{Error, Result},
+    utils::fnmatch::Fnmatch,
+    Address,
 };
 use serde::{de, Deserialize, Deserializer};
 
 use crate::conf::{
+    data_types::PaneSplit,
     default_values::{ask, false_val, none, true_val},
     deserializers::non_empty_string,
     DotAddressable,
@@ -95,6 +98,41 @@ pub struct ComposingSettings {
     /// Default: true
     #[serde(default = "true_val")]
     pub attribution_use_posix_locale: bool,
+    /// The prefix of each quoted line in replies.
+    /// Default: ">"
+    #[serde(default = "quote_prefix_val", alias = "quote-prefix")]
+    pub quote_prefix: String,
+    /// Whether to quote the text of attachments that are displayed inline
+    /// along with the body in replies.
+    /// Default: true
+    #[serde(default = "true_val", alias = "quote-attachments")]
+    pub quote_attachments: bool,
+    /// Whether to leave out the signature of the replied e-mail, that is
+    /// everything after a `-- ` line, from the quoted text.
+    /// Default: false
+    #[serde(default = "false_val", alias = "quote-strip-signature")]
+    pub quote_strip_signature: bool,
+    /// Leave out lines of the replied e-mail that are already quoted this many
+    /// times or more. For example, `1` quotes only the replied text itself.
+    /// Default: None
+    #[serde(default = "none", alias = "quote-depth-limit")]
+    pub quote_depth_limit: Option<usize>,
+    /// Query parameters to remove from URLs in the quoted text of replies, as
+    /// glob patterns of parameter names, e.g. `utm_*`. A pattern preceded by
+    /// a domain and `:`, e.g. `example.com:ref`, only applies to URLs of that
+    /// domain and its subdomains.
+    /// Default: common tracking parameters, such as `utm_*` and `fbclid`.
+    #[serde(
+        default = "quote_strip_url_parameters_val",
+        alias = "quote-strip-url-parameters"
+    )]
+    pub quote_strip_url_parameters: Vec<String>,
+    /// Show the replied e-mail next to the composer when replying, either
+    /// `"vertical"` (side by side) or `"horizontal"` (below the composer).
+    /// The `toggle_original` shortcut shows or hides it.
+    /// Default: None
+    #[serde(default = "none", alias = "reply-split")]
+    pub reply_split: Option<PaneSplit>,
     /// Forward emails as attachment? (Alternative is inline)
     /// Default: ask
     #[serde(default = "ask", alias = "forward-as-attachment")]
@@ -155,6 +193,33 @@ pub struct ComposingSettings {
     /// Default: `false`
     #[serde(default = "false_val", alias = "allow-reply-to-self")]
     pub allow_reply_to_self: bool,
+    /// Named distribution lists, that are expanded to their members' addresses
+    /// when they appear in the `To`, `Cc` or `Bcc` headers of a sent e-mail.
+    /// Contacts of the address book whose e-mail field holds more than one
+    /// address are distribution lists as well.
+    ///
+    /// Default: `{}`
+    #[serde(default, alias = "distribution-lists")]
+    pub distribution_lists: IndexMap<String, Vec<String>>,
+    /// Keep the names of expanded distribution lists in the `To` and `Cc`
+    /// headers as empty groups, e.g. `team:;`, and send to the members with
+    /// `Bcc` instead.
+    ///
+    /// Default: `false`
+    #[serde(default = "false_val", alias = "distribution-lists-keep-name")]
+    pub distribution_lists_keep_name: bool,
+    /// Column at which the draft body is wrapped when shown in the composer,
+    /// and at which the `reformat` command wraps its paragraphs.
+    ///
+    /// Default: `77`
+    #[serde(default = "wrap_column_val", alias = "wrap-column")]
+    pub wrap_column: usize,
+    /// Reformat the paragraphs of the draft body at `wrap_column` before
+    /// sending it, as the `reformat` command does.
+    ///
+    /// Default: `false`
+    #[serde(default = "false_val", alias = "hard-wrap")]
+    pub hard_wrap: bool,
 }
 
 impl Default for ComposingSettings {
@@ -169,6 +234,12 @@ impl Default for ComposingSettings {
             wrap_header_preamble: None,
             attribution_format_string: None,
             attribution_use_posix_locale: true,
+            quote_prefix: quote_prefix_val(),
+            quote_attachments: true,
+            quote_strip_signature: false,
+            quote_depth_limit: None,
+            quote_strip_url_parameters: quote_strip_url_parameters_val(),
+            reply_split: None,
             forward_as_attachment: ActionFlag::Ask,
             reply_prefix_list_to_strip: None,
             reply_prefix: res(),
@@ -178,6 +249,10 @@ impl Default for ComposingSettings {
             use_signature: false,
             signature_delimiter: None,
             allow_reply_to_self: false,
+            distribution_lists: IndexMap::default(),
+            distribution_lists_keep_name: false,
+            wrap_column: wrap_column_val(),
+            hard_wrap: false,
         }
     }
 }
@@ -201,6 +276,14 @@ impl DotAddressable for ComposingSettings {
                     "attribution_use_posix_locale" => {
                         self.attribution_use_posix_locale.lookup(field, tail)
                     }
+                    "quote_prefix" => self.quote_prefix.lookup(field, tail),
+                    "quote_attachments" => self.quote_attachments.lookup(field, tail),
+                    "quote_strip_signature" => self.quote_strip_signature.lookup(field, tail),
+                    "quote_depth_limit" => self.quote_depth_limit.lookup(field, tail),
+                    "quote_strip_url_parameters" => {
+                        self.quote_strip_url_parameters.lookup(field, tail)
+                    }
+                    "reply_split" => self.reply_split.lookup(field, tail),
                     "forward_as_attachment" => self.forward_as_attachment.lookup(field, tail),
                     "reply_prefix_list_to_strip" => {
                         self.reply_prefix_list_to_strip.lookup(field, tail)
@@ -212,6 +295,12 @@ impl DotAddressable for ComposingSettings {
                     "use_signature" => self.use_signature.lookup(field, tail),
                     "signature_delimiter" => self.signature_delimiter.lookup(field, tail),
                     "allow_reply_to_self" => self.allow_reply_to_self.lookup(field, tail),
+                    "distribution_lists" => self.distribution_lists.lookup(field, tail),
+                    "distribution_lists_keep_name" => {
+                        self.distribution_lists_keep_name.lookup(field, tail)
+                    }
+                    "wrap_column" => self.wrap_column.lookup(field, tail),
+                    "hard_wrap" => self.hard_wrap.lookup(field, tail),
                     other => Err(Error::new(format!(
                         "{parent_field} has no field named {other}"
                     ))),
@@ -224,6 +313,37 @@ impl DotAddressable for ComposingSettings {
     }
 }
 
+const fn wrap_column_val() -> usize {
+    77
+}
+
+fn quote_prefix_val() -> String {
+    ">".to_string()
+}
+
+fn quote_strip_url_parameters_val() -> Vec<String> {
+    [
+        "utm_*",
+        "fbclid",
+        "gclid",
+        "dclid",
+        "msclkid",
+        "yclid",
+        "mc_cid",
+        "mc_eid",
+        "_hsenc",
+        "_hsmi",
+        "mkt_tok",
+        "igshid",
+        "vero_id",
+        "oly_anon_id",
+        "oly_enc_id",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
 fn res() -> String {
     "Re:".to_string()
 }
@@ -302,6 +422,67 @@ impl From<ComposeHook> for crate::mail::hooks::Hook {
         Self::new_shell_command(c.name.into(), c.command)
     }
 }
+
+/// A `send_mail` value to use instead of the account's one for e-mail sent
+/// from a matching identity or only to recipients of a matching domain, see
+/// [`FileAccount::send_mail_rules`](crate::conf::FileAccount::send_mail_rules).
+///
+/// A rule with neither `identity` nor `recipient_domain` matches every
+/// e-mail.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SendMailRule {
+    /// Glob pattern matched against the address of the `From` header, e.g.
+    /// `"work@example.com"` or `"*@example.com"`.
+    #[serde(default = "none", skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    /// Domain all recipients must belong to, directly or as a subdomain.
+    #[serde(
+        default = "none",
+        skip_serializing_if = "Option::is_none",
+        alias = "recipient-domain"
+    )]
+    pub recipient_domain: Option<String>,
+    #[serde(alias = "send-mail")]
+    pub send_mail: SendMail,
+}
+
+impl DotAddressable for SendMailRule {}
+
+impl SendMailRule {
+    /// Whether the rule applies to e-mail sent from `from` to `recipients`.
+    pub fn matches(&self, from: Option<&Address>, recipients: &[Address]) -> bool {
+        if let Some(ref pattern) = self.identity {
+            let Some(from) = from else {
+                return false;
+            };
+            if !from
+                .get_email()
+                .to_ascii_lowercase()
+                .fnmatches(&pattern.to_ascii_lowercase())
+            {
+                return false;
+            }
+        }
+        if let Some(ref domain) = self.recipient_domain {
+            let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+            let in_domain = |address: &Address| {
+                address.get_fqdn().is_some_and(|fqdn| {
+                    let fqdn = fqdn.to_ascii_lowercase();
+                    fqdn == domain
+                        || fqdn
+                            .strip_suffix(domain.as_str())
+                            .is_some_and(|sub| sub.ends_with('.'))
+                })
+            };
+            if recipients.is_empty() || !recipients.iter().all(in_domain) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
 const SENDMAIL_ERR_HELP: &str = r#"Invalid `send_mail` value.
 
 Here are some valid examples:
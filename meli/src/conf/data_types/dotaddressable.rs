@@ -46,14 +46,21 @@ impl DotAddressable for char {}
 impl DotAddressable for data_types::IndexStyle {}
 impl DotAddressable for data_types::SearchBackend {}
 impl DotAddressable for data_types::ThreadLayout {}
+impl DotAddressable for data_types::PaneLayout {}
+impl DotAddressable for data_types::PaneSplit {}
 impl DotAddressable for data_types::NotificationEnable {}
+impl DotAddressable for data_types::ScoreRule {}
+impl DotAddressable for data_types::ThreadTreeGlyphs {}
+impl DotAddressable for SpecialMailboxes {}
 impl DotAddressable for u8 {}
 impl DotAddressable for u64 {}
+impl DotAddressable for i64 {}
 impl DotAddressable for TagHash {}
 impl DotAddressable for crate::terminal::Color {}
 impl DotAddressable for crate::terminal::Attr {}
 impl DotAddressable for crate::terminal::Key {}
 impl DotAddressable for usize {}
+impl DotAddressable for std::num::NonZeroUsize {}
 impl DotAddressable for Query {}
 impl DotAddressable for melib::LogLevel {}
 impl DotAddressable for PathBuf {}
@@ -75,6 +82,73 @@ impl<K: DotAddressable + std::cmp::Eq + std::hash::Hash, V: DotAddressable> DotA
 }
 impl<K: DotAddressable + std::cmp::Eq + std::hash::Hash> DotAddressable for indexmap::IndexSet<K> {}
 impl DotAddressable for (SortField, SortOrder) {}
+impl DotAddressable for SortChain {}
+impl DotAddressable for PluginHook {}
+
+impl DotAddressable for JobsSettings {
+    fn lookup(&self, parent_field: &str, path: &[&str]) -> Result<String> {
+        match path.first() {
+            Some(field) => {
+                let tail = &path[1..];
+                match *field {
+                    "executor_threads" => self.executor_threads.lookup(field, tail),
+                    "cpu_affinity" => self.cpu_affinity.lookup(field, tail),
+
+                    other => Err(Error::new(format!(
+                        "{parent_field} has no field named {other}"
+                    ))),
+                }
+            }
+            None => Ok(toml::Value::try_from(self)
+                .map_err(|err| err.to_string())?
+                .to_string()),
+        }
+    }
+}
+
+impl DotAddressable for UsageStatsSettings {
+    fn lookup(&self, parent_field: &str, path: &[&str]) -> Result<String> {
+        match path.first() {
+            Some(field) => {
+                let tail = &path[1..];
+                match *field {
+                    "enabled" => self.enabled.lookup(field, tail),
+
+                    other => Err(Error::new(format!(
+                        "{parent_field} has no field named {other}"
+                    ))),
+                }
+            }
+            None => Ok(toml::Value::try_from(self)
+                .map_err(|err| err.to_string())?
+                .to_string()),
+        }
+    }
+}
+
+impl DotAddressable for PluginConf {
+    fn lookup(&self, parent_field: &str, path: &[&str]) -> Result<String> {
+        match path.first() {
+            Some(field) => {
+                let tail = &path[1..];
+                match *field {
+                    "command" => self.command.lookup(field, tail),
+                    "hooks" => self.hooks.lookup(field, tail),
+                    "commands" => self.commands.lookup(field, tail),
+                    "column_width" => self.column_width.lookup(field, tail),
+                    "timeout" => self.timeout.lookup(field, tail),
+
+                    other => Err(Error::new(format!(
+                        "{parent_field} has no field named {other}"
+                    ))),
+                }
+            }
+            None => Ok(toml::Value::try_from(self)
+                .map_err(|err| err.to_string())?
+                .to_string()),
+        }
+    }
+}
 
 impl DotAddressable for LogSettings {
     fn lookup(&self, parent_field: &str, path: &[&str]) -> Result<String> {
@@ -113,6 +187,9 @@ impl DotAddressable for Settings {
                     "pgp" => Err(Error::new("unimplemented")),
                     "terminal" => self.terminal.lookup(field, tail),
                     "log" => self.log.lookup(field, tail),
+                    "jobs" => self.jobs.lookup(field, tail),
+                    "usage_stats" => self.usage_stats.lookup(field, tail),
+                    "plugins" => self.plugins.lookup(field, tail),
 
                     other => Err(Error::new(format!(
                         "{parent_field} has no field named {other}"
@@ -214,6 +291,9 @@ impl DotAddressable for FileAccount {
                 match *field {
                     "root_mailbox" => self.root_mailbox.lookup(field, tail),
                     "format" => self.format.lookup(field, tail),
+                    "send_mail_rules" => self.send_mail_rules.lookup(field, tail),
+                    "send_mail_retries" => self.send_mail_retries.lookup(field, tail),
+                    "send_mail_retry_delay" => self.send_mail_retry_delay.lookup(field, tail),
                     "identity" => self.identity.lookup(field, tail),
                     "display_name" => self.display_name.lookup(field, tail),
                     "read_only" => self.read_only.lookup(field, tail),
@@ -222,6 +302,20 @@ impl DotAddressable for FileAccount {
                     "search_backend" => self.search_backend.lookup(field, tail),
                     "manual_refresh" => self.manual_refresh.lookup(field, tail),
                     "refresh_command" => self.refresh_command.lookup(field, tail),
+                    "flag_batch_window" => self.flag_batch_window.lookup(field, tail),
+                    "delete_grace_period" => self.delete_grace_period.lookup(field, tail),
+                    "tag_mailboxes" => self.tag_mailboxes.lookup(field, tail),
+                    "tag_mailboxes_allowlist" => self.tag_mailboxes_allowlist.lookup(field, tail),
+                    "maintenance_on_startup" => self.maintenance_on_startup.lookup(field, tail),
+                    "maintenance_interval" => self.maintenance_interval.lookup(field, tail),
+                    "body_cache_budget" => self.body_cache_budget.lookup(field, tail),
+                    "disk_quota" => self.disk_quota.lookup(field, tail),
+                    "attachment_extractors" => self.attachment_extractors.lookup(field, tail),
+                    "allowed_commands" => self.allowed_commands.lookup(field, tail),
+                    "log_commands" => self.log_commands.lookup(field, tail),
+                    "special_mailboxes" => self.special_mailboxes.lookup(field, tail),
+                    "retained_headers" => self.retained_headers.lookup(field, tail),
+                    "sieve_script" => self.sieve_script.lookup(field, tail),
                     "conf_override" => self.conf_override.lookup(field, tail),
                     "extra" => self.extra.lookup(field, tail),
                     other => Err(Error::new(format!(
@@ -251,6 +345,7 @@ impl DotAddressable for melib::AccountSettings {
                     "subscribed_mailboxes" => self.subscribed_mailboxes.lookup(field, tail),
                     "mailboxes" => self.mailboxes.lookup(field, tail),
                     "manual_refresh" => self.manual_refresh.lookup(field, tail),
+                    "retained_headers" => self.retained_headers.lookup(field, tail),
                     "extra" => self.extra.lookup(field, tail),
                     other => Err(Error::new(format!(
                         "{parent_field} has no field named {other}"
@@ -23,6 +23,10 @@
 pub mod dotaddressable;
 pub mod regex_pattern;
 
+use melib::{
+    search::{Query, QueryTrait},
+    Envelope,
+};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
@@ -162,6 +166,183 @@ impl Serialize for ThreadLayout {
     }
 }
 
+/// Arrangement of the panes of a mail listing tab.
+///
+/// Layouts are written as pane names joined with `|` (side by side) and `/`
+/// (stacked, binds tighter than `|`), for example `sidebar | listing / pager`.
+/// The panes are `sidebar`, which can only be the leftmost pane, `listing`
+/// and `pager`, which shows the opened entry. The listing can be followed by
+/// the percentage of the space it shares with the pager, e.g.
+/// `listing:30 | pager`. Without a `pager` pane, opened entries replace the
+/// listing.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PaneLayout {
+    /// Show the sidebar menu.
+    pub sidebar: bool,
+    /// Where the pager is placed relative to the listing, if it is shown
+    /// alongside it.
+    pub split: Option<PaneSplit>,
+    /// Percentage of the space shared with the pager that the listing takes.
+    pub ratio: usize,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PaneSplit {
+    /// `listing | pager`
+    Vertical,
+    /// `listing / pager`
+    Horizontal,
+}
+
+impl<'de> Deserialize<'de> for PaneSplit {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = <String>::deserialize(deserializer)?;
+        match s.as_str() {
+            vertical if vertical.eq_ignore_ascii_case("vertical") => Ok(Self::Vertical),
+            horizontal if horizontal.eq_ignore_ascii_case("horizontal") => Ok(Self::Horizontal),
+            _ => Err(de::Error::custom(
+                "invalid split value, expected one of: \"vertical\" or \"horizontal\".",
+            )),
+        }
+    }
+}
+
+impl Serialize for PaneSplit {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Vertical => serializer.serialize_str("vertical"),
+            Self::Horizontal => serializer.serialize_str("horizontal"),
+        }
+    }
+}
+
+impl PaneLayout {
+    pub const DEFAULT_RATIO: usize = 50;
+
+    /// `sidebar | listing`, where opened entries replace the listing.
+    pub const fn single() -> Self {
+        Self {
+            sidebar: true,
+            split: None,
+            ratio: Self::DEFAULT_RATIO,
+        }
+    }
+}
+
+impl std::str::FromStr for PaneLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut ratio = Self::DEFAULT_RATIO;
+        let mut columns = vec![];
+        for column in s.split('|') {
+            let mut panes = vec![];
+            for pane in column.split('/') {
+                let (name, pane_ratio) = match pane.trim().split_once(':') {
+                    Some((name, r)) => {
+                        let Some(r) = r
+                            .trim()
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|r| (1..100).contains(r))
+                        else {
+                            return Err(format!(
+                                "invalid pane ratio `{r}` in layout `{s}`, expected a percentage \
+                                 between 1 and 99."
+                            ));
+                        };
+                        (name.trim(), Some(r))
+                    }
+                    None => (pane.trim(), None),
+                };
+                if !["sidebar", "listing", "pager"].contains(&name) {
+                    return Err(format!(
+                        "unknown pane `{name}` in layout `{s}`, expected one of: \"sidebar\", \
+                         \"listing\" or \"pager\"."
+                    ));
+                }
+                if let Some(r) = pane_ratio {
+                    if name != "listing" {
+                        return Err(format!(
+                            "only the listing pane can have a ratio, in layout `{s}`"
+                        ));
+                    }
+                    ratio = r;
+                }
+                panes.push(name);
+            }
+            columns.push(panes);
+        }
+        let sidebar = columns.first().is_some_and(|c| c == &["sidebar"]);
+        if sidebar {
+            columns.remove(0);
+        }
+        let split = match columns
+            .iter()
+            .map(|c| c.as_slice())
+            .collect::<Vec<&[&str]>>()
+            .as_slice()
+        {
+            [["listing"]] => None,
+            [["listing"], ["pager"]] => Some(PaneSplit::Vertical),
+            [["listing", "pager"]] => Some(PaneSplit::Horizontal),
+            _ => {
+                return Err(format!(
+                    "unsupported layout `{s}`, expected an optional leftmost `sidebar` followed \
+                     by `listing`, `listing | pager` or `listing / pager`."
+                ))
+            }
+        };
+        Ok(Self {
+            sidebar,
+            split,
+            ratio,
+        })
+    }
+}
+
+impl std::fmt::Display for PaneLayout {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.sidebar {
+            write!(fmt, "sidebar | ")?;
+        }
+        write!(fmt, "listing")?;
+        if self.ratio != Self::DEFAULT_RATIO {
+            write!(fmt, ":{}", self.ratio)?;
+        }
+        match self.split {
+            None => Ok(()),
+            Some(PaneSplit::Vertical) => write!(fmt, " | pager"),
+            Some(PaneSplit::Horizontal) => write!(fmt, " / pager"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PaneLayout {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = <String>::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for PaneLayout {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 /// How to handle UI notifications.
 #[derive(Clone, Copy, Debug, Default)]
 pub enum NotificationEnable {
@@ -225,3 +406,71 @@ impl Serialize for NotificationEnable {
         }
     }
 }
+
+/// A scoring rule: envelopes that match `query` get `score` added to their
+/// score.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScoreRule {
+    pub query: Query,
+    pub score: i64,
+}
+
+impl ScoreRule {
+    /// The sum of the scores of the `rules` that match `envelope`.
+    pub fn score(rules: &[Self], envelope: &Envelope) -> i64 {
+        rules
+            .iter()
+            .filter(|r| envelope.is_match(&r.query))
+            .map(|r| r.score)
+            .sum()
+    }
+}
+
+/// Characters thread trees are drawn with in threaded listings.
+///
+/// Missing fields default to the box drawing characters of
+/// [`ThreadTreeGlyphs::UNICODE`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThreadTreeGlyphs {
+    /// Continues the branch of an ancestor that has more replies below.
+    pub vertical: char,
+    /// Connects an entry that has more siblings below.
+    pub branch: char,
+    /// Connects the first entry of a thread whose parent is missing, if it has
+    /// siblings.
+    pub first_branch: char,
+    /// Connects the last entry of its siblings.
+    pub last_branch: char,
+    /// Connects the branch to the subject.
+    pub horizontal: char,
+    /// Points to the subject.
+    pub arrow: char,
+}
+
+impl ThreadTreeGlyphs {
+    pub const UNICODE: Self = Self {
+        vertical: '│',
+        branch: '├',
+        first_branch: '┬',
+        last_branch: '└',
+        horizontal: '─',
+        arrow: '>',
+    };
+
+    pub const ASCII: Self = Self {
+        vertical: '|',
+        branch: '|',
+        first_branch: '+',
+        last_branch: '`',
+        horizontal: '-',
+        arrow: '>',
+    };
+}
+
+impl Default for ThreadTreeGlyphs {
+    fn default() -> Self {
+        Self::UNICODE
+    }
+}
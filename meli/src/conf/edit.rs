@@ -0,0 +1,437 @@
+/*
+ * meli - configuration module.
+ *
+ * Copyright 2024 Manos Pitsidianakis
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Editing of settings by key, used by the `set local` command and the
+//! settings editor.
+//!
+//! Settings are changed by serializing them to a [`toml::Value`], replacing
+//! the value at a key path and deserializing the result, so that values are
+//! validated exactly like they are when read from the configuration file.
+//! Key paths are kept as lists of parts, since a part may itself contain dots,
+//! e.g. a shortcut named `"a.b"`; see [`split_key`] and [`join_key`].
+//! [`update_toml_text`] writes a changed value back to the text of a
+//! configuration file, leaving the rest of it, comments included, untouched.
+
+use std::sync::OnceLock;
+
+use indexmap::IndexMap;
+use melib::error::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Sections of [`Settings`](super::Settings) that can be edited, in the order
+/// they are documented in `meli.conf(5)`.
+pub const SECTIONS: &[&str] = &[
+    "composing",
+    "shortcuts",
+    "notifications",
+    "pager",
+    "listing",
+    "tags",
+    "pgp",
+    "terminal",
+    "log",
+    "jobs",
+    "usage_stats",
+    "plugins",
+];
+
+/// Parse `value` as a TOML value, or use it as a string if it is not one.
+pub fn parse_value(value: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut t| t.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
+/// Split a dotted TOML key, e.g. `shortcuts."a.b".c`, into its parts.
+pub fn split_key(key: &str) -> Result<Vec<String>> {
+    // Parse `key` the same way it would be parsed in a configuration file.
+    toml::from_str::<toml::Table>(&format!("{key} = 0"))
+        .ok()
+        .filter(|_| !key.contains(['\n', '\r']))
+        .and_then(table_path)
+        .ok_or_else(|| {
+            Error::new(format!("{key} is not a valid setting")).set_kind(ErrorKind::ValueError)
+        })
+}
+
+/// The path of the only key of `table`, descending into tables that have a
+/// single key, e.g. `[a, b]` for `{ a = { b = 0 } }` or `{ a = { b = {} } }`.
+fn table_path(table: toml::Table) -> Option<Vec<String>> {
+    let mut ret = vec![];
+    let mut table = table;
+    loop {
+        let mut iter = table.into_iter();
+        let (Some((part, value)), None) = (iter.next(), iter.next()) else {
+            return None;
+        };
+        ret.push(part);
+        match value {
+            toml::Value::Table(inner) if !inner.is_empty() => table = inner,
+            _ => return Some(ret),
+        }
+    }
+}
+
+/// Join the parts of a key path into a dotted TOML key, quoting the parts that
+/// are not bare keys.
+pub fn join_key<S: AsRef<str>>(path: &[S]) -> String {
+    path.iter()
+        .map(|part| format_key(part.as_ref()))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Set the setting of `target` at the key path `path` to `value`.
+///
+/// `target` is left unchanged if the result is not a valid value of `T`.
+pub fn set_key<T: Serialize + DeserializeOwned, S: AsRef<str>>(
+    target: &mut T,
+    path: &[S],
+    value: toml::Value,
+) -> Result<()> {
+    let key = join_key(path);
+    let invalid_key =
+        || Error::new(format!("{key} is not a valid setting")).set_kind(ErrorKind::ValueError);
+    let mut conf = toml::Value::try_from(&*target).map_err(|err| {
+        Error::new("Could not serialize configuration").set_details(err.to_string())
+    })?;
+    let (last, path) = path.split_last().ok_or_else(invalid_key)?;
+    let mut table = conf.as_table_mut().ok_or_else(invalid_key)?;
+    for part in path {
+        table = table
+            .entry(part.as_ref())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(invalid_key)?;
+    }
+    table.insert(last.as_ref().to_string(), value);
+    *target = conf.try_into().map_err(|err: toml::de::Error| {
+        Error::new(format!("Could not set {key}"))
+            .set_details(err.message().to_string())
+            .set_kind(ErrorKind::ValueError)
+    })?;
+    Ok(())
+}
+
+/// Flatten the tables of `value` into `(key path, value)` pairs.
+pub fn flatten(value: &toml::Value) -> Vec<(Vec<String>, toml::Value)> {
+    fn inner(
+        path: &mut Vec<String>,
+        value: &toml::Value,
+        ret: &mut Vec<(Vec<String>, toml::Value)>,
+    ) {
+        match value {
+            toml::Value::Table(table) if !table.is_empty() || path.is_empty() => {
+                for (k, v) in table {
+                    path.push(k.to_string());
+                    inner(path, v, ret);
+                    path.pop();
+                }
+            }
+            other => ret.push((path.clone(), other.clone())),
+        }
+    }
+    let mut ret = vec![];
+    inner(&mut vec![], value, &mut ret);
+    ret
+}
+
+/// Documentation of a setting.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SettingDoc {
+    /// The type of the value, as documented, e.g. `boolean` or `[String]`.
+    pub kind: Option<String>,
+    pub text: String,
+}
+
+/// Documentation of the settings of [`SECTIONS`], by dotted key, as found in
+/// `meli.conf(5)`.
+pub fn settings_docs() -> &'static IndexMap<String, SettingDoc> {
+    static DOCS: OnceLock<IndexMap<String, SettingDoc>> = OnceLock::new();
+    DOCS.get_or_init(|| parse_docs(include_str!("../../docs/meli.conf.5")))
+}
+
+/// Extract the documentation of settings from the `mdoc(7)` source of
+/// `meli.conf(5)`.
+///
+/// Settings are the `.It Ic key Ar type` items of the top level lists of each
+/// section. Shortcuts are further grouped by the `.Em context` line that
+/// precedes their list.
+pub fn parse_docs(mdoc: &str) -> IndexMap<String, SettingDoc> {
+    let mut ret = IndexMap::new();
+    let mut section: Option<String> = None;
+    let mut prefix = String::new();
+    let mut depth = 0_usize;
+    let mut literal = false;
+    let mut current: Option<(String, SettingDoc)> = None;
+    let mut flush = |current: &mut Option<(String, SettingDoc)>| {
+        if let Some((key, mut doc)) = current.take() {
+            doc.text = doc.text.trim().to_string();
+            ret.insert(key, doc);
+        }
+    };
+    for line in mdoc.lines() {
+        let Some(line) = line.strip_prefix('.') else {
+            if let Some((_, doc)) = current.as_mut() {
+                if literal {
+                    doc.text.push_str(&unescape(line));
+                    doc.text.push('\n');
+                } else {
+                    push_text(&mut doc.text, &unescape(line));
+                }
+            }
+            continue;
+        };
+        let (line, comment) = line.split_once("\\\"").unwrap_or((line, ""));
+        let mut tokens = line.split_whitespace();
+        let Some(macro_name) = tokens.next() else {
+            continue;
+        };
+        match macro_name {
+            "Sh" => {
+                flush(&mut current);
+                let name = tokens.collect::<Vec<_>>().join(" ").to_ascii_lowercase();
+                section = SECTIONS.contains(&name.as_str()).then_some(name);
+                prefix.clear();
+                depth = 0;
+            }
+            "Bl" => depth += 1,
+            "El" => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    flush(&mut current);
+                }
+            }
+            "Em" if depth == 0 && section.as_deref() == Some("shortcuts") => {
+                prefix = tokens.next().unwrap_or_default().replace('-', "_");
+            }
+            "It" if depth == 1 && section.is_some() => {
+                flush(&mut current);
+                let (Some("Ic"), Some(name)) = (tokens.next(), tokens.next()) else {
+                    continue;
+                };
+                let section = section.as_deref().unwrap_or_default();
+                let key = if prefix.is_empty() {
+                    format!("{section}.{name}")
+                } else {
+                    format!("{section}.{prefix}.{name}")
+                };
+                let kind = match tokens.next() {
+                    Some("Ar") => Some(unescape(&tokens.collect::<Vec<_>>().join(" "))),
+                    _ => None,
+                };
+                current = Some((
+                    key,
+                    SettingDoc {
+                        kind,
+                        text: String::new(),
+                    },
+                ));
+            }
+            "Pq" if current.is_some() => {
+                let words = tokens
+                    .filter(|t| !is_macro_name(t))
+                    .map(unescape)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let Some((_, doc)) = current.as_mut() else {
+                    continue;
+                };
+                if comment.trim() == "default value" {
+                    if !doc.text.ends_with('\n') {
+                        doc.text.push('\n');
+                    }
+                    doc.text.push_str(&format!("Default: {words}\n"));
+                } else if words != "optional" {
+                    push_text(&mut doc.text, &format!("({words})"));
+                }
+            }
+            "Pp" | "sp" | "Bd" | "Ed" => {
+                literal = macro_name == "Bd" && line.contains("-literal");
+                if let Some((_, doc)) = current.as_mut() {
+                    if !doc.text.ends_with('\n') {
+                        doc.text.push('\n');
+                    }
+                }
+            }
+            _ => {
+                if let Some((_, doc)) = current.as_mut() {
+                    // Drop macro names, keeping their arguments.
+                    let mut words = String::new();
+                    for word in tokens.filter(|t| !is_macro_name(t)) {
+                        push_text(&mut words, &unescape(word));
+                    }
+                    push_text(&mut doc.text, &words);
+                }
+            }
+        }
+    }
+    flush(&mut current);
+    ret
+}
+
+fn is_macro_name(token: &str) -> bool {
+    matches!(
+        token,
+        "Ic" | "Ar"
+            | "Em"
+            | "Ql"
+            | "Pq"
+            | "Cm"
+            | "Ns"
+            | "Sy"
+            | "Xr"
+            | "Op"
+            | "Li"
+            | "Dq"
+            | "Sq"
+            | "Va"
+            | "Fl"
+            | "No"
+    )
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("\\-", "-")
+        .replace("\\&", "")
+        .replace("\\(aq", "'")
+        .replace("\\e", "\\")
+}
+
+fn push_text(text: &mut String, words: &str) {
+    let words = words.trim();
+    if words.is_empty() {
+        return;
+    }
+    if !text.is_empty() && !text.ends_with('\n') {
+        if words.starts_with([',', '.', ';', ':', ')']) {
+            // Punctuation closes the preceding macro, e.g. `.Ql value ,`.
+        } else {
+            text.push(' ');
+        }
+    }
+    text.push_str(words);
+}
+
+fn format_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        key.to_string()
+    } else {
+        toml::Value::String(key.to_string()).to_string()
+    }
+}
+
+/// Set the key at `path` to `value` in the text of a TOML document, keeping
+/// the rest of the text, including comments, as it is.
+///
+/// An existing `key = value` line is replaced. Otherwise the key is added
+/// after the header of its table, which is appended if it does not exist.
+/// Fails if the existing value spans multiple lines, or if the result is not
+/// a valid TOML document.
+pub fn update_toml_text<S: AsRef<str>>(
+    text: &str,
+    path: &[S],
+    value: &toml::Value,
+) -> Result<String> {
+    let path = path
+        .iter()
+        .map(|part| part.as_ref().to_string())
+        .collect::<Vec<String>>();
+    let Some((key, table_path)) = path.split_last() else {
+        return Err(Error::new("Empty setting key").set_kind(ErrorKind::ValueError));
+    };
+    let full_key = join_key(&path);
+    let new_value = value.to_string();
+    let mut lines = text.lines().map(str::to_string).collect::<Vec<_>>();
+    // The path of the current table, or `None` inside an array of tables.
+    let mut header = Some(vec![]);
+    let mut header_line = None;
+    let mut existing = None;
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("[[") {
+            header = None;
+        } else if trimmed.starts_with('[') {
+            header = toml::from_str::<toml::Table>(trimmed)
+                .ok()
+                .and_then(table_path);
+            if header.as_deref() == Some(table_path) {
+                header_line = Some(i);
+            }
+        } else if let Some((lhs, _)) = trimmed
+            .split_once('=')
+            .filter(|_| !trimmed.starts_with('#'))
+        {
+            let (Some(header), Ok(lhs)) = (header.as_ref(), split_key(lhs)) else {
+                continue;
+            };
+            if header.len() + lhs.len() == path.len()
+                && path.starts_with(header)
+                && path.ends_with(&lhs)
+            {
+                existing = Some(i);
+            }
+        }
+    }
+    if let Some(i) = existing {
+        let line = &lines[i];
+        let (lhs, rhs) = line.split_once('=').unwrap_or_default();
+        if toml::from_str::<toml::Table>(&format!("value = {rhs}")).is_err() {
+            return Err(Error::new(format!(
+                "The value of {full_key} spans multiple lines and cannot be replaced"
+            ))
+            .set_kind(ErrorKind::ValueError));
+        }
+        // Keep a comment that follows the value.
+        let comment = rhs
+            .match_indices('#')
+            .map(|(pos, _)| pos)
+            .find(|pos| toml::from_str::<toml::Table>(&format!("value = {}", &rhs[..*pos])).is_ok())
+            .map(|pos| format!(" {}", &rhs[pos..]))
+            .unwrap_or_default();
+        lines[i] = format!("{} = {new_value}{comment}", lhs.trim_end());
+    } else if let Some(i) = header_line {
+        lines.insert(i + 1, format!("{} = {new_value}", format_key(key)));
+    } else if table_path.is_empty() {
+        lines.insert(0, format!("{} = {new_value}", format_key(key)));
+    } else {
+        if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.push(format!("[{}]", join_key(table_path)));
+        lines.push(format!("{} = {new_value}", format_key(key)));
+    }
+    let mut ret = lines.join("\n");
+    ret.push('\n');
+    toml::from_str::<toml::Table>(&ret).map_err(|err| {
+        Error::new(format!(
+            "Could not write {full_key} to the configuration file"
+        ))
+        .set_details(err.message().to_string())
+        .set_kind(ErrorKind::ValueError)
+    })?;
+    Ok(ret)
+}
@@ -19,10 +19,10 @@
  * along with meli. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use melib::{search::Query, Error, Result, SortField, SortOrder, ToggleFlag};
+use melib::{search::Query, Error, Result, SortChain, ToggleFlag};
 
 use crate::conf::{
-    data_types::{IndexStyle, ThreadLayout},
+    data_types::{IndexStyle, PaneLayout, PaneSplit, ScoreRule, ThreadLayout, ThreadTreeGlyphs},
     default_values::*,
     DotAddressable,
 };
@@ -158,6 +158,25 @@ pub struct ListingSettings {
     #[serde(default = "false_val")]
     pub threaded_repeat_identical_from_values: bool,
 
+    /// In threaded listing style, draw thread trees with ASCII characters
+    /// instead of box drawing characters.
+    /// Default: false
+    #[serde(default = "false_val", alias = "thread-tree-ascii")]
+    pub thread_tree_ascii: bool,
+
+    /// In threaded listing style, the characters to draw thread trees with.
+    /// Overrides `thread_tree_ascii`.
+    /// Default: None
+    #[serde(default = "none", alias = "thread-tree-glyphs")]
+    pub thread_tree_glyphs: Option<ThreadTreeGlyphs>,
+
+    /// In threaded listing style, when `filter` or a limit hides some entries
+    /// of a thread, show how many after the subject of the thread. The
+    /// `toggle_thread_context` shortcut shows them as context.
+    /// Default: false
+    #[serde(default = "false_val", alias = "filter-context")]
+    pub filter_context: bool,
+
     /// Show relative indices in menu mailboxes to quickly help with jumping to
     /// them. Default: "true"
     #[serde(default = "true_val", alias = "relative-menu-indices")]
@@ -180,9 +199,117 @@ pub struct ListingSettings {
     #[serde(default)]
     pub thread_layout: ThreadLayout,
 
+    /// Sort chain: a list of sort keys, applied in order.
     /// Default: "date, desc"
     #[serde(default, alias = "order")]
-    pub sort: (SortField, SortOrder),
+    pub sort: SortChain,
+
+    /// Show a preview of the first lines of each entry's text body. In
+    /// conversations style it is shown on the entry's second row, otherwise
+    /// after the subject.
+    /// Default: false
+    #[serde(default = "false_val", alias = "show-preview")]
+    pub show_preview: bool,
+
+    /// Maximum length of body previews, in characters.
+    /// Default: 100
+    #[serde(default = "default_preview_length", alias = "preview-length")]
+    pub preview_length: usize,
+
+    /// Show a summary of GitHub and GitLab notifications (repository, issue or
+    /// pull request number and reason) after the subject in compact listing
+    /// style.
+    /// Default: true
+    #[serde(default = "true_val", alias = "show-forge-summary")]
+    pub show_forge_summary: bool,
+
+    /// Number of lines each entry spans in compact listing style, either
+    /// `1` or `2`. Two-line entries show the sender and subject on the first
+    /// line and the preview and tags on the second.
+    /// Default: 1
+    #[serde(default = "default_row_height", alias = "row-height")]
+    pub row_height: usize,
+
+    /// Show the initials of the sender before the From column, on a
+    /// background color derived from the sender's address.
+    /// Default: false
+    #[serde(default = "false_val", alias = "show-initials")]
+    pub show_initials: bool,
+
+    /// Show the size of each entry after its date. In threaded listings
+    /// the size of a thread is the total size of its e-mails.
+    /// Default: false
+    #[serde(default = "false_val", alias = "show-size")]
+    pub show_size: bool,
+
+    /// In listings sorted by date, show a label such as `Yesterday` or `Last
+    /// week` in place of the date of the first entry of each group of
+    /// entries.
+    /// Default: false
+    #[serde(default = "false_val", alias = "date-separators")]
+    pub date_separators: bool,
+
+    /// Show the age of the newest message of each mailbox in the sidebar,
+    /// next to its unseen count.
+    /// Default: false
+    #[serde(default = "false_val", alias = "sidebar-show-recency")]
+    pub sidebar_show_recency: bool,
+
+    /// Make `copyto` and `moveto` copy or move the entire threads of the
+    /// selected e-mail, like `copyto-thread` and `moveto-thread`.
+    /// Default: false
+    #[serde(default = "false_val", alias = "copy-move-whole-thread")]
+    pub copy_move_whole_thread: bool,
+
+    /// Remember the open mailbox, the selected e-mail of each mailbox, the
+    /// collapsed sidebar entries and the collapsed threads of the account, and
+    /// restore them on launch.
+    /// Default: true
+    #[serde(default = "true_val", alias = "restore-state")]
+    pub restore_state: bool,
+
+    /// Rules that assign a score to each envelope. The score of an envelope
+    /// is the sum of the scores of the rules whose query matches it.
+    /// Default: empty
+    #[serde(default, alias = "score-rules")]
+    pub score_rules: Vec<ScoreRule>,
+
+    /// Show the score of each entry next to its flags.
+    /// Default: false
+    #[serde(default = "false_val", alias = "show-score")]
+    pub show_score: bool,
+
+    /// Entries with a score greater than or equal to this value are drawn
+    /// with the `mail.listing.score_high` theme attribute.
+    /// Default: None
+    #[serde(default = "none", alias = "score-high")]
+    pub score_high: Option<i64>,
+
+    /// Entries with a score less than or equal to this value are drawn with
+    /// the `mail.listing.score_low` theme attribute.
+    /// Default: None
+    #[serde(default = "none", alias = "score-low")]
+    pub score_low: Option<i64>,
+
+    /// Draw entries with the `mail.listing.age_today`,
+    /// `mail.listing.age_month` and `mail.listing.age_older` theme attributes
+    /// depending on the age of their newest e-mail, so that older entries
+    /// are progressively dimmed.
+    /// Default: false
+    #[serde(default = "false_val", alias = "age-colors")]
+    pub age_colors: bool,
+
+    /// Show a dashboard summarizing all accounts on launch, instead of the
+    /// first mailbox.
+    /// Default: false
+    #[serde(default = "false_val", alias = "startup-dashboard")]
+    pub startup_dashboard: bool,
+
+    /// Layouts that `next_layout` cycles through, the first one is used on
+    /// launch. See [`PaneLayout`] for their syntax.
+    /// Default: ["sidebar | listing", "sidebar | listing / pager", "listing"]
+    #[serde(default = "default_layouts")]
+    pub layouts: Vec<PaneLayout>,
 }
 
 const fn default_divider() -> char {
@@ -193,6 +320,28 @@ const fn default_ratio() -> usize {
     90
 }
 
+const fn default_preview_length() -> usize {
+    100
+}
+
+const fn default_row_height() -> usize {
+    1
+}
+
+fn default_layouts() -> Vec<PaneLayout> {
+    vec![
+        PaneLayout::single(),
+        PaneLayout {
+            split: Some(PaneSplit::Horizontal),
+            ..PaneLayout::single()
+        },
+        PaneLayout {
+            sidebar: false,
+            ..PaneLayout::single()
+        },
+    ]
+}
+
 impl Default for ListingSettings {
     fn default() -> Self {
         Self {
@@ -216,12 +365,32 @@ impl Default for ListingSettings {
             highlight_self: ToggleFlag::Unset,
             thread_subject_pack: true,
             threaded_repeat_identical_from_values: false,
+            thread_tree_ascii: false,
+            thread_tree_glyphs: None,
+            filter_context: false,
             relative_menu_indices: true,
             relative_list_indices: true,
             hide_sidebar_on_launch: false,
             mail_view_divider: default_divider(),
             thread_layout: ThreadLayout::default(),
             sort: Default::default(),
+            show_preview: false,
+            preview_length: default_preview_length(),
+            show_forge_summary: true,
+            row_height: default_row_height(),
+            show_initials: false,
+            show_size: false,
+            date_separators: false,
+            sidebar_show_recency: false,
+            copy_move_whole_thread: false,
+            restore_state: true,
+            score_rules: vec![],
+            show_score: false,
+            score_high: None,
+            score_low: None,
+            age_colors: false,
+            startup_dashboard: false,
+            layouts: default_layouts(),
         }
     }
 }
@@ -262,12 +431,32 @@ impl DotAddressable for ListingSettings {
                     "threaded_repeat_identical_from_values" => self
                         .threaded_repeat_identical_from_values
                         .lookup(field, tail),
+                    "thread_tree_ascii" => self.thread_tree_ascii.lookup(field, tail),
+                    "thread_tree_glyphs" => self.thread_tree_glyphs.lookup(field, tail),
+                    "filter_context" => self.filter_context.lookup(field, tail),
                     "relative_menu_indices" => self.relative_menu_indices.lookup(field, tail),
                     "relative_list_indices" => self.relative_list_indices.lookup(field, tail),
                     "hide_sidebar_on_launch" => self.hide_sidebar_on_launch.lookup(field, tail),
                     "mail_view_divider" => self.mail_view_divider.lookup(field, tail),
                     "thread_layout" => self.thread_layout.lookup(field, tail),
                     "sort" | "order" => self.sort.lookup(field, tail),
+                    "show_preview" => self.show_preview.lookup(field, tail),
+                    "preview_length" => self.preview_length.lookup(field, tail),
+                    "show_forge_summary" => self.show_forge_summary.lookup(field, tail),
+                    "row_height" => self.row_height.lookup(field, tail),
+                    "show_initials" => self.show_initials.lookup(field, tail),
+                    "show_size" => self.show_size.lookup(field, tail),
+                    "date_separators" => self.date_separators.lookup(field, tail),
+                    "sidebar_show_recency" => self.sidebar_show_recency.lookup(field, tail),
+                    "copy_move_whole_thread" => self.copy_move_whole_thread.lookup(field, tail),
+                    "restore_state" => self.restore_state.lookup(field, tail),
+                    "score_rules" => self.score_rules.lookup(field, tail),
+                    "show_score" => self.show_score.lookup(field, tail),
+                    "score_high" => self.score_high.lookup(field, tail),
+                    "score_low" => self.score_low.lookup(field, tail),
+                    "age_colors" => self.age_colors.lookup(field, tail),
+                    "startup_dashboard" => self.startup_dashboard.lookup(field, tail),
+                    "layouts" => self.layouts.lookup(field, tail),
                     other => Err(Error::new(format!(
                         "{parent_field} has no field named {other}"
                     ))),
@@ -0,0 +1,1009 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Conversion of `mutt` and `neomutt` configuration files to a meli
+//! configuration, for the `import-mutt` subcommand.
+//!
+//! Common settings are translated: the mailbox location and `mailboxes`,
+//! identity and sending settings, `folder-hook`s that set the sort order or
+//! sender of a mailbox, `color` rules that have a matching theme key and
+//! `bind`ings of functions that meli has. Everything else is listed in
+//! comments at the end of the output, to be translated by hand.
+
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+use melib::{error::*, ShellExpandTrait};
+
+/// `source`d files nested deeper than this are not followed.
+const MAX_SOURCE_DEPTH: usize = 8;
+
+/// Bindable mutt functions that have a meli shortcut, as `(menu, function,
+/// shortcut section, shortcut)`. Functions of the `generic` menu are looked up
+/// for every menu.
+const FUNCTIONS: &[(&str, &str, &str, &str)] = &[
+    ("index", "next-entry", "listing", "scroll_down"),
+    ("index", "next-undeleted", "listing", "scroll_down"),
+    ("index", "previous-entry", "listing", "scroll_up"),
+    ("index", "previous-undeleted", "listing", "scroll_up"),
+    ("index", "next-page", "listing", "next_page"),
+    ("index", "previous-page", "listing", "prev_page"),
+    ("index", "display-message", "listing", "open_entry"),
+    ("index", "search", "listing", "search"),
+    ("index", "mail", "listing", "new_mail"),
+    ("index", "delete-message", "listing", "send_to_trash"),
+    ("index", "tag-entry", "listing", "select_entry"),
+    ("index", "imap-fetch-mail", "listing", "refresh"),
+    ("index", "sync-mailbox", "listing", "refresh"),
+    ("index", "sidebar-next", "listing", "next_mailbox"),
+    ("index", "sidebar-prev", "listing", "prev_mailbox"),
+    ("index", "sidebar-open", "listing", "open_mailbox"),
+    (
+        "index",
+        "sidebar-toggle-visible",
+        "listing",
+        "toggle_menu_visibility",
+    ),
+    ("index", "reply", "envelope-view", "reply"),
+    ("index", "group-reply", "envelope-view", "reply_to_all"),
+    ("index", "list-reply", "envelope-view", "reply_to_all"),
+    ("index", "forward-message", "envelope-view", "forward"),
+    ("pager", "next-line", "pager", "scroll_down"),
+    ("pager", "previous-line", "pager", "scroll_up"),
+    ("pager", "next-page", "pager", "page_down"),
+    ("pager", "previous-page", "pager", "page_up"),
+    ("pager", "exit", "listing", "exit_entry"),
+    ("pager", "reply", "envelope-view", "reply"),
+    ("pager", "group-reply", "envelope-view", "reply_to_all"),
+    ("pager", "list-reply", "envelope-view", "reply_to_all"),
+    ("pager", "forward-message", "envelope-view", "forward"),
+    (
+        "pager",
+        "display-toggle-weed",
+        "envelope-view",
+        "toggle_expand_headers",
+    ),
+    ("pager", "sidebar-next", "listing", "next_mailbox"),
+    ("pager", "sidebar-prev", "listing", "prev_mailbox"),
+    ("compose", "send-message", "composing", "send_mail"),
+    ("compose", "edit-message", "composing", "edit"),
+    ("generic", "quit", "general", "quit"),
+    ("generic", "help", "general", "toggle_help"),
+    ("generic", "enter-command", "general", "enter_command_mode"),
+    ("generic", "next-entry", "general", "scroll_down"),
+    ("generic", "previous-entry", "general", "scroll_up"),
+    ("generic", "next-line", "general", "scroll_down"),
+    ("generic", "previous-line", "general", "scroll_up"),
+    ("generic", "next-page", "general", "next_page"),
+    ("generic", "previous-page", "general", "prev_page"),
+    ("generic", "first-entry", "general", "home_page"),
+    ("generic", "last-entry", "general", "end_page"),
+    ("generic", "select-entry", "general", "open_entry"),
+    ("generic", "search-next", "general", "next_search_result"),
+    (
+        "generic",
+        "search-opposite",
+        "general",
+        "previous_search_result",
+    ),
+];
+
+/// `set` variables that are translated, including the `neomutt` spellings.
+const TRANSLATED_VARIABLES: &[&str] = &[
+    "copy",
+    "date_format",
+    "editor",
+    "folder",
+    "from",
+    "header_cache",
+    "imap_pass",
+    "imap_user",
+    "mbox_type",
+    "pager_context",
+    "pager_stop",
+    "postponed",
+    "realname",
+    "record",
+    "sendmail",
+    "sidebar_visible",
+    "signature",
+    "smtp_pass",
+    "smtp_url",
+    "sort",
+    "sort_aux",
+    "spoolfile",
+    "ssl_starttls",
+    "trash",
+];
+
+/// The value of a `set` variable and where it was set.
+#[derive(Debug)]
+struct Variable {
+    value: String,
+    origin: String,
+}
+
+#[derive(Debug, Default)]
+struct Muttrc {
+    vars: IndexMap<String, Variable>,
+    mailboxes: Vec<(String, Option<String>)>,
+    /// `folder-hook` settings, as TOML values keyed by mailbox and setting.
+    folder_hooks: IndexMap<String, IndexMap<String, String>>,
+    /// Theme attributes, as TOML inline tables keyed by theme key.
+    colors: IndexMap<&'static str, String>,
+    /// Keys, by shortcut section and shortcut.
+    bindings: IndexMap<(&'static str, &'static str), String>,
+    headers: IndexMap<String, String>,
+    /// Lines that were not translated, with where they came from.
+    untranslated: Vec<String>,
+}
+
+/// Convert the `mutt` configuration file `path` and the files it `source`s to
+/// a meli configuration with an account named `account_name`.
+pub fn import_muttrc(path: &Path, account_name: &str) -> Result<String> {
+    let mut muttrc = Muttrc::default();
+    muttrc.read_file(path, 0)?;
+    Ok(muttrc.to_toml(account_name, Some(path)))
+}
+
+/// Convert the `mutt` configuration `input`. `source` commands are not
+/// followed.
+pub fn convert(input: &str, account_name: &str) -> String {
+    let mut muttrc = Muttrc::default();
+    muttrc.read_str(input, "muttrc", None, 0);
+    muttrc.to_toml(account_name, None)
+}
+
+impl Muttrc {
+    fn read_file(&mut self, path: &Path, depth: usize) -> Result<()> {
+        let path = path.expand();
+        let input = std::fs::read_to_string(&path)
+            .chain_err_summary(|| format!("Could not read `{}`", path.display()))
+            .chain_err_related_path(&path)?;
+        self.read_str(
+            &input,
+            &path.display().to_string(),
+            path.parent().map(Path::to_path_buf),
+            depth,
+        );
+        Ok(())
+    }
+
+    fn read_str(&mut self, input: &str, name: &str, dir: Option<PathBuf>, depth: usize) {
+        let mut line = String::new();
+        let mut line_no = 0;
+        for (i, l) in input.lines().enumerate() {
+            if line.is_empty() {
+                line_no = i + 1;
+            }
+            // A trailing backslash continues the line.
+            if let Some(l) = l.strip_suffix('\\') {
+                line.push_str(l);
+                continue;
+            }
+            line.push_str(l);
+            let origin = format!("{name}:{line_no}");
+            self.command(
+                std::mem::take(&mut line).trim(),
+                &origin,
+                dir.as_deref(),
+                depth,
+            );
+        }
+        if !line.is_empty() {
+            let origin = format!("{name}:{line_no}");
+            self.command(line.trim(), &origin, dir.as_deref(), depth);
+        }
+    }
+
+    fn untranslated(&mut self, origin: &str, line: &str) {
+        self.untranslated.push(format!("{origin}: {line}"));
+    }
+
+    fn command(&mut self, line: &str, origin: &str, dir: Option<&Path>, depth: usize) {
+        let name = line.split_whitespace().next().unwrap_or_default();
+        let args = tokenize(line, matches!(name, "set" | "unset" | "reset"));
+        let Some((command, args)) = args.split_first() else {
+            return;
+        };
+        match command.as_str() {
+            "set" | "unset" | "reset" => {
+                if !self.set(command, args, origin) {
+                    self.untranslated(origin, line);
+                }
+            }
+            "source" if args.len() == 1 && !args[0].ends_with('|') => {
+                let mut path = PathBuf::from(&args[0]).expand();
+                if path.is_relative() {
+                    if let Some(dir) = dir {
+                        path = dir.join(path);
+                    }
+                }
+                if depth >= MAX_SOURCE_DEPTH {
+                    self.untranslated(origin, line);
+                } else if let Err(err) = self.read_file(&path, depth + 1) {
+                    self.untranslated(origin, &format!("{line} ({err})"));
+                }
+            }
+            "mailboxes" => {
+                self.mailboxes
+                    .extend(args.iter().map(|m| (m.to_string(), None)));
+            }
+            "named-mailboxes" if args.len() % 2 == 0 => {
+                self.mailboxes.extend(
+                    args.chunks(2)
+                        .map(|pair| (pair[1].to_string(), Some(pair[0].to_string()))),
+                );
+            }
+            "my_hdr" if !args.is_empty() => {
+                let header = args.join(" ");
+                match header.split_once(':') {
+                    Some((name, value)) if !name.trim().is_empty() => {
+                        self.headers
+                            .insert(name.trim().to_string(), value.trim().to_string());
+                    }
+                    _ => self.untranslated(origin, line),
+                }
+            }
+            "folder-hook" if !args.is_empty() => {
+                let args = if args[0] == "-noregex" {
+                    &args[1..]
+                } else {
+                    args
+                };
+                if args.len() != 2 || !self.folder_hook(&args[0], &args[1]) {
+                    self.untranslated(origin, line);
+                }
+            }
+            "color" => {
+                if !self.color(args) {
+                    self.untranslated(origin, line);
+                }
+            }
+            "bind" => {
+                if !self.bind(args) {
+                    self.untranslated(origin, line);
+                }
+            }
+            _ => self.untranslated(origin, line),
+        }
+    }
+
+    /// Apply a `set`, `unset` or `reset` command. Returns `false` if any of the
+    /// variables is not translated.
+    fn set(&mut self, command: &str, args: &[String], origin: &str) -> bool {
+        let mut translated = true;
+        let mut i = 0;
+        while i < args.len() {
+            let (name, value) = if args.get(i + 1).is_some_and(|a| a == "=") {
+                i += 3;
+                (
+                    variable_name(&args[i - 3]),
+                    args.get(i - 1).cloned().unwrap_or_default(),
+                )
+            } else {
+                i += 1;
+                let name = args[i - 1].as_str();
+                match (command, name.strip_prefix("no")) {
+                    ("set", Some(name)) if is_known_variable(name) => {
+                        (variable_name(name), "no".to_string())
+                    }
+                    ("set", _) => (variable_name(name), "yes".to_string()),
+                    _ => (variable_name(name), "no".to_string()),
+                }
+            };
+            if !TRANSLATED_VARIABLES.contains(&name.as_str()) {
+                translated = false;
+            } else if command == "reset" {
+                self.vars.shift_remove(&name);
+            } else {
+                self.vars.insert(
+                    name,
+                    Variable {
+                        value,
+                        origin: origin.to_string(),
+                    },
+                );
+            }
+        }
+        translated
+    }
+
+    /// Translate a `folder-hook` that sets the sort order or sender of a
+    /// single mailbox.
+    fn folder_hook(&mut self, pattern: &str, commands: &str) -> bool {
+        let mailbox = pattern
+            .trim_start_matches('^')
+            .trim_end_matches('$')
+            .trim_start_matches(['=', '+']);
+        if mailbox.is_empty() || mailbox.contains(['.', '*', '?', '[', '(', '|', '\\', '~']) {
+            return false;
+        }
+        let mut settings = IndexMap::new();
+        for command in commands.split(';') {
+            let args = tokenize(command, true);
+            match args.as_slice() {
+                [set, name, eq, value] if set == "set" && eq == "=" => {
+                    match variable_name(name).as_str() {
+                        "sort" if value == "threads" => {
+                            settings
+                                .insert("listing.index_style".to_string(), toml_str("Threaded"));
+                        }
+                        "sort" | "sort_aux" => {
+                            let Some(chain) = sort_chain(value) else {
+                                return false;
+                            };
+                            settings.insert("listing.sort".to_string(), toml_str(&chain));
+                        }
+                        "from" => {
+                            settings.insert("identity".to_string(), toml_str(value));
+                        }
+                        _ => return false,
+                    }
+                }
+                [] => {}
+                _ => return false,
+            }
+        }
+        self.folder_hooks
+            .entry(mailbox.to_string())
+            .or_default()
+            .extend(settings);
+        true
+    }
+
+    fn color(&mut self, args: &[String]) -> bool {
+        let Some((object, args)) = args.split_first() else {
+            return false;
+        };
+        let mut attrs = vec![];
+        let mut args = args;
+        while let Some((attr, rest)) = args.split_first() {
+            let attr = match attr.as_str() {
+                "bold" => "Bold",
+                "underline" => "Underline",
+                "reverse" | "standout" => "Reverse",
+                "blink" => "Blink",
+                "italic" => "Italics",
+                "none" | "normal" => "Default",
+                _ => break,
+            };
+            attrs.push(attr);
+            args = rest;
+        }
+        let (fg, bg, pattern) = match args {
+            [fg, bg] => (fg, bg, None),
+            [fg, bg, pattern] => (fg, bg, Some(pattern.as_str())),
+            _ => return false,
+        };
+        let (Some(fg), Some(bg)) = (color(fg), color(bg)) else {
+            return false;
+        };
+        let keys: &[&'static str] = match (object.as_str(), pattern) {
+            ("normal", None) => &["theme_default"],
+            ("status", None) => &["status.bar"],
+            ("hdrdefault", None) => &["mail.view.headers"],
+            ("indicator", None) => &[
+                "mail.listing.plain.even_highlighted",
+                "mail.listing.plain.odd_highlighted",
+                "mail.listing.compact.even_highlighted",
+                "mail.listing.compact.odd_highlighted",
+                "mail.listing.conversations.highlighted",
+            ],
+            ("index", None | Some("~A" | ".*" | "all")) => &[
+                "mail.listing.plain.even",
+                "mail.listing.plain.odd",
+                "mail.listing.compact.even",
+                "mail.listing.compact.odd",
+                "mail.listing.conversations",
+            ],
+            ("index", Some("~N" | "~U")) => &[
+                "mail.listing.plain.even_unseen",
+                "mail.listing.plain.odd_unseen",
+                "mail.listing.compact.even_unseen",
+                "mail.listing.compact.odd_unseen",
+                "mail.listing.conversations.unseen",
+            ],
+            ("index", Some("~T")) => &[
+                "mail.listing.plain.even_selected",
+                "mail.listing.plain.odd_selected",
+                "mail.listing.compact.even_selected",
+                "mail.listing.compact.odd_selected",
+                "mail.listing.conversations.selected",
+            ],
+            ("index_subject", None) => &["mail.listing.conversations.subject"],
+            ("index_author", None) => &["mail.listing.conversations.from"],
+            ("index_date", None) => &["mail.listing.conversations.date"],
+            ("sidebar_ordinary", None) => &["mail.sidebar"],
+            ("sidebar_highlight" | "sidebar_indicator", None) => &["mail.sidebar_highlighted"],
+            ("sidebar_new" | "sidebar_unread", None) => &["mail.sidebar_unread_count"],
+            ("sidebar_divider", None) => &["mail.sidebar_divider"],
+            _ => return false,
+        };
+        let mut value = format!("{{ fg = {}, bg = {}", toml_str(&fg), toml_str(&bg));
+        if !attrs.is_empty() {
+            _ = write!(value, ", attrs = {}", toml_str(&attrs.join("|")));
+        }
+        value.push_str(" }");
+        for key in keys {
+            self.colors.insert(*key, value.clone());
+        }
+        true
+    }
+
+    fn bind(&mut self, args: &[String]) -> bool {
+        let [menus, key, function] = args else {
+            return false;
+        };
+        let Some(key) = key_to_string(key) else {
+            return false;
+        };
+        let mut shortcuts = vec![];
+        for menu in menus.split(',') {
+            let Some((_, _, section, shortcut)) = FUNCTIONS
+                .iter()
+                .find(|(m, f, _, _)| *m == menu && *f == function.as_str())
+                .or_else(|| {
+                    FUNCTIONS
+                        .iter()
+                        .find(|(m, f, _, _)| *m == "generic" && *f == function.as_str())
+                })
+            else {
+                return false;
+            };
+            shortcuts.push((*section, *shortcut));
+        }
+        for shortcut in shortcuts {
+            self.bindings.insert(shortcut, key.clone());
+        }
+        true
+    }
+
+    fn var(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(|v| v.value.as_str())
+    }
+
+    fn to_toml(&self, account_name: &str, path: Option<&Path>) -> String {
+        let mut ret = String::new();
+        let mut untranslated = self.untranslated.clone();
+        if let Some(path) = path {
+            _ = writeln!(
+                ret,
+                "# Converted from {} by `meli import-mutt`.",
+                path.display()
+            );
+        } else {
+            ret.push_str("# Converted by `meli import-mutt`.\n");
+        }
+        ret.push_str(
+            "# Review the result and consult meli.conf(5) before using it. Settings that could \
+             not be\n# translated are listed at the end.\n\n",
+        );
+
+        // Account
+        _ = writeln!(ret, "[accounts.{}]", toml_str(account_name));
+        let folder = self
+            .var("folder")
+            .or_else(|| self.var("spoolfile"))
+            .unwrap_or("~/Mail")
+            .trim_end_matches('/');
+        let url = Url::parse(folder);
+        if let Some(ref url) = url {
+            ret.push_str("root_mailbox = \"INBOX\"\nformat = \"imap\"\n");
+            _ = writeln!(ret, "server_hostname = {}", toml_str(&url.host));
+            _ = writeln!(ret, "server_port = {}", toml_str(&url.port.to_string()));
+            if let Some(user) = url.user.as_deref().or_else(|| self.var("imap_user")) {
+                _ = writeln!(ret, "server_username = {}", toml_str(user));
+            }
+            if let Some(pass) = self.var("imap_pass") {
+                _ = writeln!(ret, "server_password = {}", toml_str(pass));
+            } else {
+                ret.push_str(
+                    "# mutt did not store the password; set a command that prints it:\n# \
+                     server_password_command = \"pass show mail\"\n",
+                );
+            }
+            if !url.tls {
+                let starttls = self.var("ssl_starttls").map(is_yes).unwrap_or(true);
+                _ = writeln!(ret, "use_tls = {starttls}");
+                _ = writeln!(ret, "use_starttls = {starttls}");
+            }
+            if self.var("header_cache").is_some() {
+                ret.push_str("offline_cache = true\n");
+            }
+        } else {
+            _ = writeln!(ret, "root_mailbox = {}", toml_str(folder));
+            let format = match self.var("mbox_type") {
+                Some(t) if t.eq_ignore_ascii_case("maildir") => "Maildir",
+                Some(t) if t.eq_ignore_ascii_case("mbox") => "mbox",
+                Some(t) => {
+                    untranslated.push(format!(
+                        "{}: set mbox_type={t} (meli supports Maildir and mbox)",
+                        self.vars["mbox_type"].origin
+                    ));
+                    "mbox"
+                }
+                // mutt's default
+                None => "mbox",
+            };
+            _ = writeln!(ret, "format = {}", toml_str(format));
+        }
+        let mailbox_path = |mailbox: &str| -> String {
+            let mailbox = mailbox.trim_end_matches('/');
+            let relative = if let Some(m) = mailbox.strip_prefix(['=', '+']) {
+                m
+            } else if let Some(m) = mailbox.strip_prefix(folder) {
+                m
+            } else if let Some(m) = Url::parse(mailbox) {
+                return m.path;
+            } else {
+                mailbox
+            }
+            .trim_start_matches('/');
+            if url.is_some() {
+                return relative.to_string();
+            }
+            // Local mailbox paths start with the name of the root mailbox.
+            let root = Path::new(folder)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            match (root.is_empty(), relative.is_empty()) {
+                (true, _) => relative.to_string(),
+                (false, true) => root,
+                (false, false) => format!("{root}/{relative}"),
+            }
+        };
+        let (identity, display_name) = match self.var("from") {
+            Some(from) => match from.rsplit_once('<') {
+                Some((name, address)) => (
+                    address.trim_end_matches('>').trim().to_string(),
+                    Some(name.trim().trim_matches('"').to_string()).filter(|n| !n.is_empty()),
+                ),
+                None => (from.to_string(), None),
+            },
+            None => (
+                self.var("imap_user")
+                    .or(url.as_ref().and_then(|u| u.user.as_deref()))
+                    .unwrap_or("user@example.com")
+                    .to_string(),
+                None,
+            ),
+        };
+        _ = writeln!(ret, "identity = {}", toml_str(&identity));
+        if let Some(name) = self.var("realname").map(str::to_string).or(display_name) {
+            _ = writeln!(ret, "display_name = {}", toml_str(&name));
+        }
+        if !self.mailboxes.is_empty() {
+            let mailboxes = self
+                .mailboxes
+                .iter()
+                .map(|(m, _)| toml_str(&mailbox_path(m)))
+                .collect::<Vec<_>>();
+            _ = writeln!(ret, "subscribed_mailboxes = [{}]", mailboxes.join(", "));
+        }
+        if let Some(smtp_url) = self.var("smtp_url") {
+            match Url::parse(smtp_url) {
+                Some(url) => {
+                    let password = match self.var("smtp_pass") {
+                        Some(pass) => format!("{{ type = \"raw\", value = {} }}", toml_str(pass)),
+                        None => {
+                            "{ type = \"command_eval\", value = \"pass show smtp\" }".to_string()
+                        }
+                    };
+                    _ = writeln!(
+                        ret,
+                        "send_mail = {{ hostname = {}, port = {}, auth = {{ type = \"auto\", \
+                         username = {}, password = {password} }}, security = {{ type = {} }} }}",
+                        toml_str(&url.host),
+                        url.port,
+                        toml_str(url.user.as_deref().unwrap_or(&identity)),
+                        toml_str(if url.tls { "TLS" } else { "STARTTLS" }),
+                    );
+                    if self.var("smtp_pass").is_none() {
+                        ret.push_str(
+                            "# mutt did not store the SMTP password; replace the password \
+                             command above.\n",
+                        );
+                    }
+                }
+                None => {
+                    untranslated.push(format!(
+                        "{}: set smtp_url={smtp_url}",
+                        self.vars["smtp_url"].origin
+                    ));
+                    ret.push_str("send_mail = \"/usr/sbin/sendmail -oem -oi\"\n");
+                }
+            }
+        } else {
+            let sendmail = self
+                .var("sendmail")
+                .unwrap_or("/usr/sbin/sendmail -oem -oi");
+            _ = writeln!(ret, "send_mail = {}", toml_str(sendmail));
+        }
+
+        // Mailbox settings
+        let mut mailbox_settings: IndexMap<String, IndexMap<String, String>> = IndexMap::new();
+        for (var, usage) in [
+            ("spoolfile", "Inbox"),
+            ("record", "Sent"),
+            ("postponed", "Drafts"),
+            ("trash", "Trash"),
+        ] {
+            if let Some(mailbox) = self.var(var).filter(|m| !m.is_empty()) {
+                if url.is_none() && var == "spoolfile" && mailbox.trim_end_matches('/') == folder {
+                    continue;
+                }
+                mailbox_settings
+                    .entry(mailbox_path(mailbox))
+                    .or_default()
+                    .insert("usage".to_string(), toml_str(usage));
+            }
+        }
+        for (mailbox, alias) in &self.mailboxes {
+            if let Some(alias) = alias {
+                mailbox_settings
+                    .entry(mailbox_path(mailbox))
+                    .or_default()
+                    .insert("alias".to_string(), toml_str(alias));
+            }
+        }
+        for (mailbox, settings) in &self.folder_hooks {
+            mailbox_settings
+                .entry(mailbox_path(mailbox))
+                .or_default()
+                .extend(settings.clone());
+        }
+        for (mailbox, settings) in &mailbox_settings {
+            _ = writeln!(
+                ret,
+                "\n[accounts.{}.mailboxes.{}]",
+                toml_str(account_name),
+                toml_str(mailbox)
+            );
+            for (key, value) in settings {
+                _ = writeln!(ret, "{key} = {value}");
+            }
+        }
+
+        // Global settings
+        let mut composing = vec![];
+        if let Some(editor) = self.var("editor") {
+            composing.push(format!("editor_command = {}", toml_str(editor)));
+        }
+        if let Some(signature) = self.var("signature") {
+            if signature.ends_with('|') {
+                untranslated.push(format!(
+                    "{}: set signature=\"{signature}\" (meli does not run signature commands)",
+                    self.vars["signature"].origin
+                ));
+            } else {
+                composing.push(format!("signature_file = {}", toml_str(signature)));
+                composing.push("use_signature = true".to_string());
+            }
+        }
+        if self.var("copy").is_some_and(|v| !is_yes(v)) {
+            composing.push("store_sent_mail = false".to_string());
+        }
+        if !self.headers.is_empty() {
+            let headers = self
+                .headers
+                .iter()
+                .map(|(k, v)| format!("{} = {}", toml_str(k), toml_str(v)))
+                .collect::<Vec<_>>();
+            composing.push(format!(
+                "default_header_values = {{ {} }}",
+                headers.join(", ")
+            ));
+        }
+        section(&mut ret, "composing", &composing);
+
+        let mut listing = vec![];
+        match (self.var("sort"), self.var("sort_aux")) {
+            (Some("threads"), aux) => {
+                listing.push("index_style = \"Threaded\"".to_string());
+                if let Some(chain) = aux.and_then(sort_chain) {
+                    listing.push(format!("sort = {}", toml_str(&chain)));
+                }
+            }
+            (Some(sort), _) => match sort_chain(sort) {
+                Some(chain) => listing.push(format!("sort = {}", toml_str(&chain))),
+                None => untranslated.push(format!("{}: set sort={sort}", self.vars["sort"].origin)),
+            },
+            (None, _) => {}
+        }
+        if let Some(fmt) = self.var("date_format") {
+            listing.push(format!(
+                "datetime_fmt = {}",
+                toml_str(fmt.trim_start_matches('!'))
+            ));
+        }
+        if self.var("sidebar_visible").is_some_and(|v| !is_yes(v)) {
+            listing.push("hide_sidebar_on_launch = true".to_string());
+        }
+        section(&mut ret, "listing", &listing);
+
+        let mut pager = vec![];
+        if let Some(context) = self
+            .var("pager_context")
+            .and_then(|c| c.parse::<usize>().ok())
+        {
+            pager.push(format!("pager_context = {context}"));
+        }
+        if let Some(stop) = self.var("pager_stop") {
+            pager.push(format!("pager_stop = {}", is_yes(stop)));
+        }
+        section(&mut ret, "pager", &pager);
+
+        let mut sections: IndexMap<&str, Vec<String>> = IndexMap::new();
+        for ((section, shortcut), key) in &self.bindings {
+            sections
+                .entry(*section)
+                .or_default()
+                .push(format!("{shortcut} = {}", toml_str(key)));
+        }
+        for (name, shortcuts) in sections {
+            section(&mut ret, &format!("shortcuts.{name}"), &shortcuts);
+        }
+
+        if !self.colors.is_empty() {
+            ret.push_str("\n[terminal]\ntheme = \"mutt\"\n");
+            ret.push_str(
+                "\n# Keys that are not set are inherited from the \"dark\" theme.\n\
+                 [terminal.themes.mutt]\n",
+            );
+            for (key, value) in &self.colors {
+                _ = writeln!(ret, "{} = {value}", toml_str(key));
+            }
+        }
+
+        if !untranslated.is_empty() {
+            ret.push_str("\n# The following settings could not be translated:\n");
+            for line in untranslated {
+                _ = writeln!(ret, "# {line}");
+            }
+        }
+        ret
+    }
+}
+
+fn section(ret: &mut String, name: &str, lines: &[String]) {
+    if lines.is_empty() {
+        return;
+    }
+    _ = writeln!(ret, "\n[{name}]");
+    for line in lines {
+        _ = writeln!(ret, "{line}");
+    }
+}
+
+/// A mail server URL such as `imaps://user@imap.example.com:993/INBOX`.
+#[derive(Debug)]
+struct Url {
+    user: Option<String>,
+    host: String,
+    port: u16,
+    tls: bool,
+    path: String,
+}
+
+impl Url {
+    fn parse(s: &str) -> Option<Self> {
+        let (scheme, rest) = s.split_once("://")?;
+        let (tls, default_port) = match scheme.to_ascii_lowercase().as_str() {
+            "imap" => (false, 143),
+            "imaps" => (true, 993),
+            "smtp" => (false, 587),
+            "smtps" => (true, 465),
+            _ => return None,
+        };
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let (user, host) = match authority.rsplit_once('@') {
+            Some((user, host)) => (
+                // Drop any password from the URL.
+                Some(user.split(':').next().unwrap_or(user).to_string()),
+                host,
+            ),
+            None => (None, authority),
+        };
+        let (host, port) = match host.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (host, default_port),
+        };
+        if host.is_empty() {
+            return None;
+        }
+        Some(Self {
+            user,
+            host: host.to_string(),
+            port,
+            tls,
+            path: path.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+/// Split a muttrc line into words, removing quotes, escapes and comments. If
+/// `split_assignments` is true, `=` outside quotes is a separate word.
+fn tokenize(line: &str, split_assignments: bool) -> Vec<String> {
+    let mut ret = vec![];
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                let c = match chars.next() {
+                    // Control characters, like mutt does.
+                    Some('c' | 'C') => match chars.next() {
+                        Some(c) if c.is_ascii_alphabetic() => {
+                            char::from(c.to_ascii_lowercase() as u8 & 0x1f)
+                        }
+                        Some(c) => c,
+                        None => break,
+                    },
+                    Some('e' | 'E') => '\x1b',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some(c) => c,
+                    None => break,
+                };
+                word.get_or_insert_with(String::new).push(c);
+            }
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'' | '`') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, '#') => break,
+            (None, '=') if split_assignments => {
+                ret.extend(word.take());
+                ret.push("=".to_string());
+            }
+            (None, c) if c.is_whitespace() => ret.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    ret.extend(word);
+    ret
+}
+
+/// The canonical name of a variable, translating `neomutt` renames.
+fn variable_name(name: &str) -> String {
+    match name {
+        "real_name" => "realname",
+        "spool_file" => "spoolfile",
+        "header_cache_backend" | "message_cachedir" => "header_cache",
+        other => other,
+    }
+    .to_string()
+}
+
+fn is_known_variable(name: &str) -> bool {
+    TRANSLATED_VARIABLES.contains(&variable_name(name).as_str())
+}
+
+fn is_yes(value: &str) -> bool {
+    matches!(value.to_ascii_lowercase().as_str(), "yes" | "ask-yes")
+}
+
+fn toml_str(s: &str) -> String {
+    toml::Value::String(s.to_string()).to_string()
+}
+
+/// Translate a `sort` or `sort_aux` value to a meli sort chain.
+fn sort_chain(value: &str) -> Option<String> {
+    let (field, order) = match value.strip_prefix("reverse-") {
+        Some(field) => (field, "desc"),
+        None => (value, "asc"),
+    };
+    let field = match field.trim_start_matches("last-") {
+        "date" | "date-sent" | "date-received" => "date",
+        "subject" => "subject",
+        "from" | "author" => "from",
+        "to" => "recipients",
+        "size" => "size",
+        "score" => "score",
+        _ => return None,
+    };
+    Some(format!("{field} {order}"))
+}
+
+/// Translate a mutt color to a meli color value.
+fn color(value: &str) -> Option<String> {
+    let value = value.to_ascii_lowercase();
+    if value == "default" {
+        return Some("Default".to_string());
+    }
+    if value.starts_with('#') && value.len() == 7 {
+        return Some(value);
+    }
+    if let Some(n) = value.strip_prefix("color") {
+        return n.parse::<u8>().ok().map(|n| n.to_string());
+    }
+    let (name, offset) = match value.strip_prefix("bright") {
+        Some(name) => (name, 8),
+        None => (value.as_str(), 0),
+    };
+    let base = [
+        "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    ]
+    .iter()
+    .position(|c| *c == name)?;
+    Some((base + offset).to_string())
+}
+
+/// Translate a mutt key to a meli key, if it is a single key.
+fn key_to_string(key: &str) -> Option<String> {
+    if let Some(name) = key
+        .strip_prefix('<')
+        .and_then(|k| k.strip_suffix('>'))
+        .map(str::to_ascii_lowercase)
+    {
+        let name = match name.as_str() {
+            "up" => "Up",
+            "down" => "Down",
+            "left" => "Left",
+            "right" => "Right",
+            "pageup" => "PageUp",
+            "pagedown" => "PageDown",
+            "home" => "Home",
+            "end" => "End",
+            "enter" | "return" => "Enter",
+            "tab" => "Tab",
+            "esc" => "Esc",
+            "space" => " ",
+            "backspace" => "Backspace",
+            "delete" => "Delete",
+            "insert" => "Insert",
+            f => {
+                let n = f.strip_prefix('f')?.parse::<u8>().ok()?;
+                return (1..=12).contains(&n).then(|| format!("F{n}"));
+            }
+        };
+        return Some(name.to_string());
+    }
+    let key = key.replace("<Esc>", "\x1b").replace("<esc>", "\x1b");
+    let mut chars = key.chars();
+    let ret = match (chars.next()?, chars.next(), chars.next()) {
+        ('\t', None, _) => "Tab".to_string(),
+        ('\n' | '\r', None, _) => "Enter".to_string(),
+        ('\x1b', None, _) => "Esc".to_string(),
+        ('\x1b', Some(c), None) if c.is_ascii_lowercase() || c.is_ascii_digit() => {
+            format!("M-{c}")
+        }
+        ('^', Some(c), None) if c.is_ascii_alphabetic() => {
+            format!("C-{}", c.to_ascii_lowercase())
+        }
+        (c, None, _) if ('\x01'..='\x1a').contains(&c) => {
+            format!("C-{}", char::from(c as u8 + b'a' - 1))
+        }
+        (c, None, _) if !c.is_control() => c.to_string(),
+        _ => return None,
+    };
+    Some(ret)
+}
@@ -30,17 +30,17 @@ use indexmap::IndexSet;
 
 use crate::conf::{*, data_types::*};
 
-# [derive (Debug , Serialize , Deserialize , Clone)] # [serde (deny_unknown_fields)] pub struct PagerSettingsOverride { # [doc = " Number of context lines when going to next page."] # [doc = " Default: 0"] # [serde (alias = "pager-context")] # [serde (default)] pub pager_context : Option < usize > , # [doc = " Stop at the end instead of displaying next mail."] # [doc = " Default: false"] # [serde (alias = "pager-stop")] # [serde (default)] pub pager_stop : Option < bool > , # [doc = " Always show headers when scrolling."] # [doc = " Default: true"] # [serde (alias = "sticky-headers" , alias = "headers-sticky" , alias = "headers_sticky")] # [serde (default)] pub sticky_headers : Option < bool > , # [doc = " The height of the pager in mail view, in percent."] # [doc = " Default: 80"] # [serde (alias = "pager-ratio")] # [serde (default)] pub pager_ratio : Option < usize > , # [doc = " A command to pipe mail output through for viewing in pager."] # [doc = " Default: None"] # [serde (deserialize_with = "non_empty_opt_string")] # [serde (default)] pub filter : Option < Option < String > > , # [doc = " Named filter commands to use at will."] # [doc = ""] # [doc = " Default: empty"] # [serde (default)] pub named_filters : Option < IndexMap < String , String > > , # [doc = " A command to pipe html output before displaying it in a pager"] # [doc = " Default: None"] # [serde (deserialize_with = "non_empty_opt_string" , alias = "html-filter")] # [serde (default)] pub html_filter : Option < Option < String > > , # [doc = " Respect \"format=flowed\""] # [doc = " Default: true"] # [serde (alias = "format-flowed")] # [serde (default)] pub format_flowed : Option < bool > , # [doc = " Split long lines that would overflow on the x axis."] # [doc = " Default: true"] # [serde (alias = "split-long-lines")] # [serde (default)] pub split_long_lines : Option < bool > , # [doc = " Minimum text width in columns."] # [doc = " Default: 80"] # [serde (alias = "minimum-width")] # [serde (default)] pub minimum_width : Option < usize > , # [doc = " Choose `text/html` alternative if `text/plain` is empty in"] # [doc = " `multipart/alternative` attachments."] # [doc = " Default: true"] # [serde (alias = "auto-choose-multipart-alternative")] # [serde (default)] pub auto_choose_multipart_alternative : Option < ToggleFlag > , # [doc = " Show Date: in my timezone"] # [doc = " Default: true"] # [serde (alias = "show-date-in-my-timezone")] # [serde (default)] pub show_date_in_my_timezone : Option < ToggleFlag > , # [doc = " A command to launch URLs with. The URL will be given as the first"] # [doc = " argument of the command. Default: None"] # [serde (deserialize_with = "non_empty_opt_string")] # [serde (default)] pub url_launcher : Option < Option < String > > , # [doc = " A command to open html files."] # [doc = " Default: None"] # [serde (deserialize_with = "non_empty_opt_string" , alias = "html-open")] # [serde (default)] pub html_open : Option < Option < String > > , # [doc = " Extra headers to display, if present, in the default header preamble."] # [doc = " Default: []"] # [serde (alias = "show-extra-headers")] # [serde (default)] pub show_extra_headers : Option < Vec < HeaderName > > } impl Default for PagerSettingsOverride { fn default () -> Self { Self { pager_context : None , pager_stop : None , sticky_headers : None , pager_ratio : None , filter : None , named_filters : None , html_filter : None , format_flowed : None , split_long_lines : None , minimum_width : None , auto_choose_multipart_alternative : None , show_date_in_my_timezone : None , url_launcher : None , html_open : None , show_extra_headers : None } } }
+# [derive (Debug , Serialize , Deserialize , Clone)] # [serde (deny_unknown_fields)] pub struct PagerSettingsOverride { # [doc = " Number of context lines when going to next page."] # [doc = " Default: 0"] # [serde (alias = "pager-context")] # [serde (default)] pub pager_context : Option < usize > , # [doc = " Stop at the end instead of displaying next mail."] # [doc = " Default: false"] # [serde (alias = "pager-stop")] # [serde (default)] pub pager_stop : Option < bool > , # [doc = " Mark e-mail as seen when it is opened."] # [doc = " Default: true"] # [serde (alias = "auto-mark-read")] # [serde (default)] pub auto_mark_read : Option < bool > , # [doc = " Always show headers when scrolling."] # [doc = " Default: true"] # [serde (alias = "sticky-headers" , alias = "headers-sticky" , alias = "headers_sticky")] # [serde (default)] pub sticky_headers : Option < bool > , # [doc = " The height of the pager in mail view, in percent."] # [doc = " Default: 80"] # [serde (alias = "pager-ratio")] # [serde (default)] pub pager_ratio : Option < usize > , # [doc = " A command to pipe mail output through for viewing in pager."] # [doc = " Default: None"] # [serde (deserialize_with = "non_empty_opt_string")] # [serde (default)] pub filter : Option < Option < String > > , # [doc = " Named filter commands to use at will."] # [doc = ""] # [doc = " Default: empty"] # [serde (default)] pub named_filters : Option < IndexMap < String , String > > , # [doc = " A command to pipe html output before displaying it in a pager"] # [doc = " Default: None"] # [serde (deserialize_with = "non_empty_opt_string" , alias = "html-filter")] # [serde (default)] pub html_filter : Option < Option < String > > , # [doc = " Respect \"format=flowed\""] # [doc = " Default: true"] # [serde (alias = "format-flowed")] # [serde (default)] pub format_flowed : Option < bool > , # [doc = " Split long lines that would overflow on the x axis."] # [doc = " Default: true"] # [serde (alias = "split-long-lines")] # [serde (default)] pub split_long_lines : Option < bool > , # [doc = " Minimum text width in columns."] # [doc = " Default: 80"] # [serde (alias = "minimum-width")] # [serde (default)] pub minimum_width : Option < usize > , # [doc = " Choose `text/html` alternative if `text/plain` is empty in"] # [doc = " `multipart/alternative` attachments."] # [doc = " Default: true"] # [serde (alias = "auto-choose-multipart-alternative")] # [serde (default)] pub auto_choose_multipart_alternative : Option < ToggleFlag > , # [doc = " Show Date: in my timezone"] # [doc = " Default: true"] # [serde (alias = "show-date-in-my-timezone")] # [serde (default)] pub show_date_in_my_timezone : Option < ToggleFlag > , # [doc = " A command to launch URLs with. The URL will be given as the first"] # [doc = " argument of the command. Default: None"] # [serde (deserialize_with = "non_empty_opt_string")] # [serde (default)] pub url_launcher : Option < Option < String > > , # [doc = " A command to open html files."] # [doc = " Default: None"] # [serde (deserialize_with = "non_empty_opt_string" , alias = "html-open")] # [serde (default)] pub html_open : Option < Option < String > > , # [doc = " Senders whose HTML e-mail may load remote content such as images,"] # [doc = " either as e-mail addresses or as domains prefixed with `@`, e.g."] # [doc = " `@example.com`. Remote content of other senders is blocked."] # [doc = " Default: []"] # [serde (alias = "html-remote-content-allowlist")] # [serde (default)] pub html_remote_content_allowlist : Option < Vec < String > > , # [doc = " A command to pipe html output of senders in"] # [doc = " `html_remote_content_allowlist` to instead of `html_filter`, so that it"] # [doc = " can fetch remote images."] # [doc = " Default: None"] # [serde (alias = "html-remote-content-filter")] # [serde (default)] pub html_remote_content_filter : Option < Option < String > > , # [doc = " Write out the targets of links in HTML e-mail whose anchor text"] # [doc = " differs from them. Targets of suspicious links are always written out."] # [doc = " Default: false"] # [serde (alias = "html-reveal-link-targets")] # [serde (default)] pub html_reveal_link_targets : Option < bool > , # [doc = " Extra headers to display, if present, in the default header preamble."] # [doc = " Default: []"] # [serde (alias = "show-extra-headers")] # [serde (default)] pub show_extra_headers : Option < Vec < HeaderName > > , # [doc = " A command to pipe formatted mail to with the `print-mail` command, for"] # [doc = " example `lpr` or `enscript -o - | ps2pdf - out.pdf`."] # [doc = " Default: None"] # [serde (deserialize_with = "non_empty_opt_string" , alias = "print-command")] # [serde (default)] pub print_command : Option < Option < String > > , # [doc = " Lines per page of formatted mail output, pages are separated with form"] # [doc = " feeds. Set to 0 to disable pagination."] # [doc = " Default: 66"] # [serde (alias = "print-page-lines")] # [serde (default)] pub print_page_lines : Option < usize > , # [doc = " A command to pipe text copied in the pager's visual mode to, for"] # [doc = " example `xclip -selection clipboard` or `wl-copy`. If unset, the text"] # [doc = " is sent to the terminal's clipboard with the OSC 52 escape sequence."] # [doc = " Default: None"] # [serde (deserialize_with = "non_empty_opt_string" , alias = "clipboard-command")] # [serde (default)] pub clipboard_command : Option < Option < String > > , # [doc = " Commands that convert attachments to text for previewing, keyed by"] # [doc = " MIME type glob. `%s` is replaced by the path of a temporary file with"] # [doc = " the attachment, otherwise it is piped to the command's standard input."] # [doc = " Default: converters for PDF, office documents and images."] # [serde (alias = "attachment-previewers")] # [serde (default)] pub attachment_previewers : Option < IndexMap < String , String > > , # [doc = " Commands to pipe attachments to with the `attachment_action` shortcut,"] # [doc = " keyed by MIME type glob, for example `khal import --batch %s` for"] # [doc = " `text/calendar`. `%s` is replaced by the path of a temporary file with"] # [doc = " the attachment, otherwise it is piped to the command's standard input."] # [doc = " Default: empty"] # [serde (alias = "attachment-actions")] # [serde (default)] pub attachment_actions : Option < IndexMap < String , String > > , # [doc = " A command to scan attachments with before opening or saving them, for"] # [doc = " example `clamdscan --no-summary -`. It must exit with status 0 for"] # [doc = " clean attachments and 1 for flagged ones. `%s` is replaced by the path"] # [doc = " of a temporary file with the attachment, otherwise it is piped to the"] # [doc = " command's standard input."] # [doc = " Default: None"] # [serde (deserialize_with = "non_empty_opt_string" , alias = "attachment-scan-command")] # [serde (default)] pub attachment_scan_command : Option < Option < String > > } impl Default for PagerSettingsOverride { fn default () -> Self { Self { pager_context : None , pager_stop : None , auto_mark_read : None , sticky_headers : None , pager_ratio : None , filter : None , named_filters : None , html_filter : None , format_flowed : None , split_long_lines : None , minimum_width : None , auto_choose_multipart_alternative : None , show_date_in_my_timezone : None , url_launcher : None , html_open : None , html_remote_content_allowlist : None , html_remote_content_filter : None , html_reveal_link_targets : None , show_extra_headers : None , print_command : None , print_page_lines : None , clipboard_command : None , attachment_previewers : None , attachment_actions : None , attachment_scan_command : None } } }
 
-# [derive (Debug , Serialize , Deserialize , Clone)] # [serde (deny_unknown_fields)] pub struct ListingSettingsOverride { # [doc = " Number of context lines when going to next page."] # [doc = " Default: 0"] # [serde (alias = "context-lines")] # [serde (default)] pub context_lines : Option < usize > , # [doc = " Show auto-hiding scrollbar in accounts sidebar menu."] # [doc = " Default: True"] # [serde (default)] pub show_menu_scrollbar : Option < bool > , # [doc = " Datetime formatting passed verbatim to strftime(3)."] # [doc = " Default: %Y-%m-%d %T"] # [serde (alias = "datetime-fmt")] # [serde (default)] pub datetime_fmt : Option < Option < String > > , # [doc = " Show recent dates as `X {minutes,hours,days} ago`, up to 7 days."] # [doc = " Default: true"] # [serde (alias = "recent-dates")] # [serde (default)] pub recent_dates : Option < bool > , # [doc = " Show only envelopes that match this query"] # [doc = " Default: None"] # [serde (default)] pub filter : Option < Option < Query > > , # [serde (alias = "index-style")] # [serde (default)] pub index_style : Option < IndexStyle > , # [doc = " Default: \" \""] # [serde (default)] pub sidebar_mailbox_tree_has_sibling : Option < Option < String > > , # [doc = " Default: \" \""] # [serde (default)] pub sidebar_mailbox_tree_no_sibling : Option < Option < String > > , # [doc = " Default: \" \""] # [serde (default)] pub sidebar_mailbox_tree_has_sibling_leaf : Option < Option < String > > , # [doc = " Default: \" \""] # [serde (default)] pub sidebar_mailbox_tree_no_sibling_leaf : Option < Option < String > > , # [doc = " Default: ' '"] # [serde (default)] pub sidebar_divider : Option < char > , # [doc = " Default: 90"] # [serde (default)] pub sidebar_ratio : Option < usize > , # [doc = " Flag to show if thread entry contains unseen mail."] # [doc = " Default: \"●\""] # [serde (default)] pub unseen_flag : Option < Option < String > > , # [doc = " Flag to show if thread has been snoozed."] # [doc = " Default: \"💤\""] # [serde (default)] pub thread_snoozed_flag : Option < Option < String > > , # [doc = " Flag to show if thread entry has been selected."] # [doc = " Default: \"☑\u{fe0f}\""] # [serde (default)] pub selected_flag : Option < Option < String > > , # [doc = " Flag to show if thread entry contains attachments."] # [doc = " Default: \"📎\""] # [serde (default)] pub attachment_flag : Option < Option < String > > , # [doc = " Flag to show if any thread entry contains your address as a receiver."] # [doc = " Useful to make mailing list threads that CC you stand out."] # [doc = " Default: \"✸\""] # [serde (default)] pub highlight_self_flag : Option < Option < String > > , # [doc = " Show `highlight_self_flag` or not."] # [doc = " Default: false"] # [serde (default)] pub highlight_self : Option < ToggleFlag > , # [doc = " Should threads with different Subjects show a list of those"] # [doc = " subjects on the entry title?"] # [doc = " Default: \"true\""] # [serde (default)] pub thread_subject_pack : Option < bool > , # [doc = " In threaded listing style, repeat identical From column values within a"] # [doc = " thread. Not repeating adds empty space in the From column which"] # [doc = " might result in less visual clutter."] # [doc = " Default: \"false\""] # [serde (default)] pub threaded_repeat_identical_from_values : Option < bool > , # [doc = " Show relative indices in menu mailboxes to quickly help with jumping to"] # [doc = " them. Default: \"true\""] # [serde (alias = "relative-menu-indices")] # [serde (default)] pub relative_menu_indices : Option < bool > , # [doc = " Show relative indices in listings to quickly help with jumping to"] # [doc = " them. Default: \"true\""] # [serde (alias = "relative-list-indices")] # [serde (default)] pub relative_list_indices : Option < bool > , # [doc = " Hide sidebar on launch. Default: \"false\""] # [serde (alias = "hide-sidebar-on-launch")] # [serde (default)] pub hide_sidebar_on_launch : Option < bool > , # [doc = " Default: ' '"] # [serde (default)] pub mail_view_divider : Option < char > , # [doc = " Default: \"auto\""] # [serde (default)] pub thread_layout : Option < ThreadLayout > , # [doc = " Default: \"date, desc\""] # [serde (alias = "order")] # [serde (default)] pub sort : Option < (SortField , SortOrder) > } impl Default for ListingSettingsOverride { fn default () -> Self { Self { context_lines : None , show_menu_scrollbar : None , datetime_fmt : None , recent_dates : None , filter : None , index_style : None , sidebar_mailbox_tree_has_sibling : None , sidebar_mailbox_tree_no_sibling : None , sidebar_mailbox_tree_has_sibling_leaf : None , sidebar_mailbox_tree_no_sibling_leaf : None , sidebar_divider : None , sidebar_ratio : None , unseen_flag : None , thread_snoozed_flag : None , selected_flag : None , attachment_flag : None , highlight_self_flag : None , highlight_self : None , thread_subject_pack : None , threaded_repeat_identical_from_values : None , relative_menu_indices : None , relative_list_indices : None , hide_sidebar_on_launch : None , mail_view_divider : None , thread_layout : None , sort : None } } }
+# [derive (Debug , Serialize , Deserialize , Clone)] # [serde (deny_unknown_fields)] pub struct ListingSettingsOverride { # [doc = " Number of context lines when going to next page."] # [doc = " Default: 0"] # [serde (alias = "context-lines")] # [serde (default)] pub context_lines : Option < usize > , # [doc = " Show auto-hiding scrollbar in accounts sidebar menu."] # [doc = " Default: True"] # [serde (default)] pub show_menu_scrollbar : Option < bool > , # [doc = " Datetime formatting passed verbatim to strftime(3)."] # [doc = " Default: %Y-%m-%d %T"] # [serde (alias = "datetime-fmt")] # [serde (default)] pub datetime_fmt : Option < Option < String > > , # [doc = " Show recent dates as `X {minutes,hours,days} ago`, up to 7 days."] # [doc = " Default: true"] # [serde (alias = "recent-dates")] # [serde (default)] pub recent_dates : Option < bool > , # [doc = " Show only envelopes that match this query"] # [doc = " Default: None"] # [serde (default)] pub filter : Option < Option < Query > > , # [serde (alias = "index-style")] # [serde (default)] pub index_style : Option < IndexStyle > , # [doc = " Default: \" \""] # [serde (default)] pub sidebar_mailbox_tree_has_sibling : Option < Option < String > > , # [doc = " Default: \" \""] # [serde (default)] pub sidebar_mailbox_tree_no_sibling : Option < Option < String > > , # [doc = " Default: \" \""] # [serde (default)] pub sidebar_mailbox_tree_has_sibling_leaf : Option < Option < String > > , # [doc = " Default: \" \""] # [serde (default)] pub sidebar_mailbox_tree_no_sibling_leaf : Option < Option < String > > , # [doc = " Default: ' '"] # [serde (default)] pub sidebar_divider : Option < char > , # [doc = " Default: 90"] # [serde (default)] pub sidebar_ratio : Option < usize > , # [doc = " Flag to show if thread entry contains unseen mail."] # [doc = " Default: \"●\""] # [serde (default)] pub unseen_flag : Option < Option < String > > , # [doc = " Flag to show if thread has been snoozed."] # [doc = " Default: \"💤\""] # [serde (default)] pub thread_snoozed_flag : Option < Option < String > > , # [doc = " Flag to show if thread entry has been selected."] # [doc = " Default: \"☑\u{fe0f}\""] # [serde (default)] pub selected_flag : Option < Option < String > > , # [doc = " Flag to show if thread entry contains attachments."] # [doc = " Default: \"📎\""] # [serde (default)] pub attachment_flag : Option < Option < String > > , # [doc = " Flag to show if any thread entry contains your address as a receiver."] # [doc = " Useful to make mailing list threads that CC you stand out."] # [doc = " Default: \"✸\""] # [serde (default)] pub highlight_self_flag : Option < Option < String > > , # [doc = " Show `highlight_self_flag` or not."] # [doc = " Default: false"] # [serde (default)] pub highlight_self : Option < ToggleFlag > , # [doc = " Should threads with different Subjects show a list of those"] # [doc = " subjects on the entry title?"] # [doc = " Default: \"true\""] # [serde (default)] pub thread_subject_pack : Option < bool > , # [doc = " In threaded listing style, repeat identical From column values within a"] # [doc = " thread. Not repeating adds empty space in the From column which"] # [doc = " might result in less visual clutter."] # [doc = " Default: \"false\""] # [serde (default)] pub threaded_repeat_identical_from_values : Option < bool > , # [doc = " In threaded listing style, draw thread trees with ASCII characters"] # [doc = " instead of box drawing characters."] # [doc = " Default: false"] # [serde (alias = "thread-tree-ascii")] # [serde (default)] pub thread_tree_ascii : Option < bool > , # [doc = " In threaded listing style, the characters to draw thread trees with."] # [doc = " Overrides `thread_tree_ascii`."] # [doc = " Default: None"] # [serde (alias = "thread-tree-glyphs")] # [serde (default)] pub thread_tree_glyphs : Option < Option < ThreadTreeGlyphs > > , # [doc = " In threaded listing style, when `filter` or a limit hides some entries"] # [doc = " of a thread, show how many after the subject of the thread. The"] # [doc = " `toggle_thread_context` shortcut shows them as context."] # [doc = " Default: false"] # [serde (alias = "filter-context")] # [serde (default)] pub filter_context : Option < bool > , # [doc = " Show relative indices in menu mailboxes to quickly help with jumping to"] # [doc = " them. Default: \"true\""] # [serde (alias = "relative-menu-indices")] # [serde (default)] pub relative_menu_indices : Option < bool > , # [doc = " Show relative indices in listings to quickly help with jumping to"] # [doc = " them. Default: \"true\""] # [serde (alias = "relative-list-indices")] # [serde (default)] pub relative_list_indices : Option < bool > , # [doc = " Hide sidebar on launch. Default: \"false\""] # [serde (alias = "hide-sidebar-on-launch")] # [serde (default)] pub hide_sidebar_on_launch : Option < bool > , # [doc = " Default: ' '"] # [serde (default)] pub mail_view_divider : Option < char > , # [doc = " Default: \"auto\""] # [serde (default)] pub thread_layout : Option < ThreadLayout > , # [doc = " Sort chain: a list of sort keys, applied in order."] # [doc = " Default: \"date, desc\""] # [serde (alias = "order")] # [serde (default)] pub sort : Option < SortChain > , # [doc = " Show a preview of the first lines of each entry's text body. In"] # [doc = " conversations style it is shown on the entry's second row, otherwise"] # [doc = " after the subject."] # [doc = " Default: false"] # [serde (alias = "show-preview")] # [serde (default)] pub show_preview : Option < bool > , # [doc = " Maximum length of body previews, in characters."] # [doc = " Default: 100"] # [serde (alias = "preview-length")] # [serde (default)] pub preview_length : Option < usize > , # [doc = " Show a summary of GitHub and GitLab notifications (repository, issue or"] # [doc = " pull request number and reason) after the subject in compact listing"] # [doc = " style."] # [doc = " Default: true"] # [serde (alias = "show-forge-summary")] # [serde (default)] pub show_forge_summary : Option < bool > , # [doc = " Number of lines each entry spans in compact listing style, either"] # [doc = " `1` or `2`. Two-line entries show the sender and subject on the first"] # [doc = " line and the preview and tags on the second."] # [doc = " Default: 1"] # [serde (alias = "row-height")] # [serde (default)] pub row_height : Option < usize > , # [doc = " Show the initials of the sender before the From column, on a"] # [doc = " background color derived from the sender's address."] # [doc = " Default: false"] # [serde (alias = "show-initials")] # [serde (default)] pub show_initials : Option < bool > , # [doc = " Show the size of each entry after its date. In threaded listings"] # [doc = " the size of a thread is the total size of its e-mails."] # [doc = " Default: false"] # [serde (alias = "show-size")] # [serde (default)] pub show_size : Option < bool > , # [doc = " In listings sorted by date, show a label such as `Yesterday` or `Last week`"] # [doc = " in place of the date of the first entry of each group of entries."] # [doc = " Default: false"] # [serde (alias = "date-separators")] # [serde (default)] pub date_separators : Option < bool > , # [doc = " Show the age of the newest message of each mailbox in the sidebar,"] # [doc = " next to its unseen count."] # [doc = " Default: false"] # [serde (alias = "sidebar-show-recency")] # [serde (default)] pub sidebar_show_recency : Option < bool > , # [doc = " Make `copyto` and `moveto` copy or move the entire threads of the"] # [doc = " selected e-mail, like `copyto-thread` and `moveto-thread`."] # [doc = " Default: false"] # [serde (alias = "copy-move-whole-thread")] # [serde (default)] pub copy_move_whole_thread : Option < bool > , # [doc = " Remember the open mailbox, the cursor position of each mailbox and the"] # [doc = " collapsed sidebar entries of the account, and restore them on launch."] # [doc = " Default: true"] # [serde (alias = "restore-state")] # [serde (default)] pub restore_state : Option < bool > , # [doc = " Rules that assign a score to each envelope. The score of an envelope"] # [doc = " is the sum of the scores of the rules whose query matches it."] # [doc = " Default: empty"] # [serde (alias = "score-rules")] # [serde (default)] pub score_rules : Option < Vec < ScoreRule > > , # [doc = " Show the score of each entry next to its flags."] # [doc = " Default: false"] # [serde (alias = "show-score")] # [serde (default)] pub show_score : Option < bool > , # [doc = " Entries with a score greater than or equal to this value are drawn"] # [doc = " with the `mail.listing.score_high` theme attribute."] # [doc = " Default: None"] # [serde (alias = "score-high")] # [serde (default)] pub score_high : Option < Option < i64 > > , # [doc = " Entries with a score less than or equal to this value are drawn with"] # [doc = " the `mail.listing.score_low` theme attribute."] # [doc = " Default: None"] # [serde (alias = "score-low")] # [serde (default)] pub score_low : Option < Option < i64 > > , # [doc = " Draw entries with the `mail.listing.age_today`,"] # [doc = " `mail.listing.age_month` and `mail.listing.age_older` theme attributes"] # [doc = " depending on the age of their newest e-mail, so that older entries"] # [doc = " are progressively dimmed."] # [doc = " Default: false"] # [serde (alias = "age-colors")] # [serde (default)] pub age_colors : Option < bool > , # [doc = " Show a dashboard summarizing all accounts on launch, instead of the"] # [doc = " first mailbox."] # [doc = " Default: false"] # [serde (alias = "startup-dashboard")] # [serde (default)] pub startup_dashboard : Option < bool > , # [doc = " Layouts that `next_layout` cycles through, the first one is used on"] # [doc = " launch. See [`PaneLayout`] for their syntax."] # [doc = " Default: [\"sidebar | listing\", \"sidebar | listing / pager\", \"listing\"]"] # [serde (default)] pub layouts : Option < Vec < PaneLayout > > } impl Default for ListingSettingsOverride { fn default () -> Self { Self { context_lines : None , show_menu_scrollbar : None , datetime_fmt : None , recent_dates : None , filter : None , index_style : None , sidebar_mailbox_tree_has_sibling : None , sidebar_mailbox_tree_no_sibling : None , sidebar_mailbox_tree_has_sibling_leaf : None , sidebar_mailbox_tree_no_sibling_leaf : None , sidebar_divider : None , sidebar_ratio : None , unseen_flag : None , thread_snoozed_flag : None , selected_flag : None , attachment_flag : None , highlight_self_flag : None , highlight_self : None , thread_subject_pack : None , threaded_repeat_identical_from_values : None , thread_tree_ascii : None , thread_tree_glyphs : None , filter_context : None , relative_menu_indices : None , relative_list_indices : None , hide_sidebar_on_launch : None , mail_view_divider : None , thread_layout : None , sort : None , show_preview : None , preview_length : None , show_forge_summary : None , row_height : None , show_initials : None , show_size : None , date_separators : None , sidebar_show_recency : None , copy_move_whole_thread : None , restore_state : None , score_rules : None , show_score : None , score_high : None , score_low : None , age_colors : None , startup_dashboard : None , layouts : None } } }
 
 # [derive (Debug , Serialize , Deserialize , Clone)] # [serde (deny_unknown_fields)] pub struct NotificationsSettingsOverride { # [doc = " Enable notifications."] # [doc = " Default: [`NotificationEnable::SystemAndUI`]"] # [serde (default)] pub enable : Option < NotificationEnable > , # [doc = " A command to pipe notifications through."] # [doc = " Default: None"] # [serde (default)] pub script : Option < Option < String > > , # [doc = " A command to pipe new mail notifications through (preferred over"] # [doc = " `script`). Default: None"] # [serde (default)] pub new_mail_script : Option < Option < String > > , # [doc = " A file location which has its size changed when new mail arrives (max"] # [doc = " 128 bytes). Can be used to trigger new mail notifications eg with"] # [doc = " `xbiff(1)`. Default: None"] # [serde (alias = "xbiff-file-path")] # [serde (default)] pub xbiff_file_path : Option < Option < String > > , # [serde (alias = "play-sound")] # [serde (default)] pub play_sound : Option < ToggleFlag > , # [serde (alias = "sound-file")] # [serde (default)] pub sound_file : Option < Option < String > > } impl Default for NotificationsSettingsOverride { fn default () -> Self { Self { enable : None , script : None , new_mail_script : None , xbiff_file_path : None , play_sound : None , sound_file : None } } }
 
-# [derive (Debug , Serialize , Deserialize , Clone)] # [serde (deny_unknown_fields)] pub struct ShortcutsOverride { # [serde (default)] pub general : Option < GeneralShortcuts > , # [serde (default)] pub listing : Option < ListingShortcuts > , # [serde (default)] pub composing : Option < ComposingShortcuts > , # [serde (alias = "contact-list")] # [serde (default)] pub contact_list : Option < ContactListShortcuts > , # [serde (alias = "envelope-view")] # [serde (default)] pub envelope_view : Option < EnvelopeViewShortcuts > , # [serde (alias = "thread-view")] # [serde (default)] pub thread_view : Option < ThreadViewShortcuts > , # [serde (default)] pub pager : Option < PagerShortcuts > } impl Default for ShortcutsOverride { fn default () -> Self { Self { general : None , listing : None , composing : None , contact_list : None , envelope_view : None , thread_view : None , pager : None } } }
+# [derive (Debug , Serialize , Deserialize , Clone)] # [serde (deny_unknown_fields)] pub struct ShortcutsOverride { # [serde (default)] pub general : Option < GeneralShortcuts > , # [serde (default)] pub listing : Option < ListingShortcuts > , # [serde (default)] pub composing : Option < ComposingShortcuts > , # [serde (alias = "contact-list")] # [serde (default)] pub contact_list : Option < ContactListShortcuts > , # [serde (alias = "envelope-view")] # [serde (default)] pub envelope_view : Option < EnvelopeViewShortcuts > , # [serde (alias = "thread-view")] # [serde (default)] pub thread_view : Option < ThreadViewShortcuts > , # [serde (default)] pub pager : Option < PagerShortcuts > , # [serde (default)] pub triage : Option < TriageShortcuts > } impl Default for ShortcutsOverride { fn default () -> Self { Self { general : None , listing : None , composing : None , contact_list : None , envelope_view : None , thread_view : None , pager : None , triage : None } } }
 
-# [derive (Debug , Serialize , Deserialize , Clone)] # [serde (deny_unknown_fields)] pub struct ComposingSettingsOverride { # [doc = " Command to launch editor. Can have arguments. Draft filename is given as"] # [doc = " the last argument. If it's missing, the environment variable $EDITOR is"] # [doc = " looked up."] # [serde (alias = "editor-command" , alias = "editor-cmd" , alias = "editor_cmd")] # [serde (default)] pub editor_command : Option < Option < String > > , # [doc = " Embedded editor (for terminal interfaces) instead of forking and"] # [doc = " waiting."] # [serde (alias = "embed")] # [serde (default)] pub embedded_pty : Option < bool > , # [doc = " Set \"format=flowed\" in plain text attachments."] # [doc = " Default: true"] # [serde (alias = "format-flowed")] # [serde (default)] pub format_flowed : Option < bool > , # [doc = " Set User-Agent"] # [doc = " Default: empty"] # [serde (alias = "insert_user_agent")] # [serde (default)] pub insert_user_agent : Option < bool > , # [doc = " Set default header values for new drafts"] # [doc = " Default: empty"] # [serde (alias = "default-header-values")] # [serde (default)] pub default_header_values : Option < IndexMap < HeaderName , String > > , # [doc = " Wrap header preamble when editing a draft in an editor. This allows you"] # [doc = " to write non-plain text email without the preamble creating syntax"] # [doc = " errors. They are stripped when you return from the editor. The"] # [doc = " values should be a two element array of strings, a prefix and suffix."] # [doc = " Default: None"] # [serde (alias = "wrap-header-preamble")] # [serde (default)] pub wrap_header_preamble : Option < Option < (String , String) > > , # [doc = " Store sent mail after successful submission. This setting is meant to be"] # [doc = " disabled for non-standard behaviour in gmail, which auto-saves sent"] # [doc = " mail on its own. Default: true"] # [serde (default)] pub store_sent_mail : Option < bool > , # [doc = " The attribution line that appears above the quoted reply text."] # [doc = ""] # [doc = " The format specifiers for the replied address are:"] # [doc = " - `%+f` — the sender's name and email address."] # [doc = " - `%+n` — the sender's name (or email address, if no name is included)."] # [doc = " - `%+a` — the sender's email address."] # [doc = ""] # [doc = " The format string is passed to strftime(3) with the replied envelope's"] # [doc = " date. Default: \"On %a, %0e %b %Y %H:%M, %+f wrote:%n\""] # [serde (default)] pub attribution_format_string : Option < Option < String > > , # [doc = " Whether the strftime call for the attribution string uses the POSIX"] # [doc = " locale instead of the user's active locale"] # [doc = " Default: true"] # [serde (default)] pub attribution_use_posix_locale : Option < bool > , # [doc = " Forward emails as attachment? (Alternative is inline)"] # [doc = " Default: ask"] # [serde (alias = "forward-as-attachment")] # [serde (default)] pub forward_as_attachment : Option < ActionFlag > , # [doc = " Alternative lists of reply prefixes (etc. [\"Re:\", \"RE:\", ...]) to strip"] # [doc = " Default: `[\"Re:\", \"RE:\", \"Fwd:\", \"Fw:\", \"回复:\", \"回覆:\", \"SV:\", \"Sv:\","] # [doc = " \"VS:\", \"Antw:\", \"Doorst:\", \"VS:\", \"VL:\", \"REF:\", \"TR:\", \"TR:\", \"AW:\","] # [doc = " \"WG:\", \"ΑΠ:\", \"Απ:\", \"απ:\", \"ΠΡΘ:\", \"Πρθ:\", \"πρθ:\", \"ΣΧΕΤ:\", \"Σχετ:\","] # [doc = " \"σχετ:\", \"ΠΡΘ:\", \"Πρθ:\", \"πρθ:\", \"Vá:\", \"Továbbítás:\", \"R:\", \"I:\","] # [doc = " \"RIF:\", \"FS:\", \"BLS:\", \"TRS:\", \"VS:\", \"VB:\", \"RV:\", \"RES:\", \"Res\","] # [doc = " \"ENC:\", \"Odp:\", \"PD:\", \"YNT:\", \"İLT:\", \"ATB:\", \"YML:\"]`"] # [serde (alias = "reply-prefix-list-to-strip")] # [serde (default)] pub reply_prefix_list_to_strip : Option < Option < Vec < String > > > , # [doc = " The prefix to use in reply subjects. The de facto prefix is \"Re:\"."] # [serde (alias = "reply-prefix")] # [serde (default)] pub reply_prefix : Option < String > , # [doc = " Custom `compose-hooks`."] # [serde (alias = "custom-compose-hooks")] # [serde (default)] pub custom_compose_hooks : Option < Vec < ComposeHook > > , # [doc = " Disabled `compose-hooks`."] # [serde (alias = "disabled-compose-hooks")] # [serde (default)] pub disabled_compose_hooks : Option < Vec < String > > , # [doc = " Plain text file with signature that will pre-populate an email draft."] # [doc = ""] # [doc = " Signatures must be explicitly enabled to be used, otherwise this setting"] # [doc = " will be ignored."] # [doc = ""] # [doc = " Default: `None`"] # [serde (alias = "signature-file")] # [serde (default)] pub signature_file : Option < Option < PathBuf > > , # [doc = " Pre-populate email drafts with signature, if any."] # [doc = ""] # [doc = " `meli` will lookup the signature value in this order:"] # [doc = ""] # [doc = " 1. The `signature_file` setting."] # [doc = " 2. `${XDG_CONFIG_DIR}/meli/<account>/signature`"] # [doc = " 3. `${XDG_CONFIG_DIR}/meli/signature`"] # [doc = " 4. `${XDG_CONFIG_DIR}/signature`"] # [doc = " 5. `${HOME}/.signature`"] # [doc = " 6. No signature otherwise."] # [doc = ""] # [doc = " Default: `false`"] # [serde (alias = "use-signature")] # [serde (default)] pub use_signature : Option < bool > , # [doc = " Signature delimiter, that is, text that will be prefixed to your"] # [doc = " signature to separate it from the email body."] # [doc = ""] # [doc = " Default: `\"\\n\\n-- \\n\"`"] # [serde (alias = "signature-delimiter")] # [serde (default)] pub signature_delimiter : Option < Option < String > > , # [doc = " When replying to an e-mail authored by our main identity or one of our"] # [doc = " extra identities, reply to those addresses instead of reusing the"] # [doc = " receivers of the original e-mail we are replying to."] # [doc = ""] # [doc = " The default is `false`, because the intuitive behavior when replying to"] # [doc = " ourselves is to follow-up on an e-mail we sent."] # [doc = ""] # [doc = " Default: `false`"] # [serde (alias = "allow-reply-to-self")] # [serde (default)] pub allow_reply_to_self : Option < bool > } impl Default for ComposingSettingsOverride { fn default () -> Self { Self { editor_command : None , embedded_pty : None , format_flowed : None , insert_user_agent : None , default_header_values : None , wrap_header_preamble : None , store_sent_mail : None , attribution_format_string : None , attribution_use_posix_locale : None , forward_as_attachment : None , reply_prefix_list_to_strip : None , reply_prefix : None , custom_compose_hooks : None , disabled_compose_hooks : None , signature_file : None , use_signature : None , signature_delimiter : None , allow_reply_to_self : None } } }
+# [derive (Debug , Serialize , Deserialize , Clone)] # [serde (deny_unknown_fields)] pub struct ComposingSettingsOverride { # [doc = " Command to launch editor. Can have arguments. Draft filename is given as"] # [doc = " the last argument. If it's missing, the environment variable $EDITOR is"] # [doc = " looked up."] # [serde (alias = "editor-command" , alias = "editor-cmd" , alias = "editor_cmd")] # [serde (default)] pub editor_command : Option < Option < String > > , # [doc = " Embedded editor (for terminal interfaces) instead of forking and"] # [doc = " waiting."] # [serde (alias = "embed")] # [serde (default)] pub embedded_pty : Option < bool > , # [doc = " Set \"format=flowed\" in plain text attachments."] # [doc = " Default: true"] # [serde (alias = "format-flowed")] # [serde (default)] pub format_flowed : Option < bool > , # [doc = " Set User-Agent"] # [doc = " Default: empty"] # [serde (alias = "insert_user_agent")] # [serde (default)] pub insert_user_agent : Option < bool > , # [doc = " Set default header values for new drafts"] # [doc = " Default: empty"] # [serde (alias = "default-header-values")] # [serde (default)] pub default_header_values : Option < IndexMap < HeaderName , String > > , # [doc = " Wrap header preamble when editing a draft in an editor. This allows you"] # [doc = " to write non-plain text email without the preamble creating syntax"] # [doc = " errors. They are stripped when you return from the editor. The"] # [doc = " values should be a two element array of strings, a prefix and suffix."] # [doc = " Default: None"] # [serde (alias = "wrap-header-preamble")] # [serde (default)] pub wrap_header_preamble : Option < Option < (String , String) > > , # [doc = " Store sent mail after successful submission. This setting is meant to be"] # [doc = " disabled for non-standard behaviour in gmail, which auto-saves sent"] # [doc = " mail on its own. Default: true"] # [serde (default)] pub store_sent_mail : Option < bool > , # [doc = " The attribution line that appears above the quoted reply text."] # [doc = ""] # [doc = " The format specifiers for the replied address are:"] # [doc = " - `%+f` — the sender's name and email address."] # [doc = " - `%+n` — the sender's name (or email address, if no name is included)."] # [doc = " - `%+a` — the sender's email address."] # [doc = ""] # [doc = " The format string is passed to strftime(3) with the replied envelope's"] # [doc = " date. Default: \"On %a, %0e %b %Y %H:%M, %+f wrote:%n\""] # [serde (default)] pub attribution_format_string : Option < Option < String > > , # [doc = " Whether the strftime call for the attribution string uses the POSIX"] # [doc = " locale instead of the user's active locale"] # [doc = " Default: true"] # [serde (default)] pub attribution_use_posix_locale : Option < bool > , # [doc = " The prefix of each quoted line in replies."] # [doc = " Default: \">\""] # [serde (alias = "quote-prefix")] # [serde (default)] pub quote_prefix : Option < String > , # [doc = " Whether to quote the text of attachments that are displayed inline"] # [doc = " along with the body in replies."] # [doc = " Default: true"] # [serde (alias = "quote-attachments")] # [serde (default)] pub quote_attachments : Option < bool > , # [doc = " Whether to leave out the signature of the replied e-mail, that is"] # [doc = " everything after a `-- ` line, from the quoted text."] # [doc = " Default: false"] # [serde (alias = "quote-strip-signature")] # [serde (default)] pub quote_strip_signature : Option < bool > , # [doc = " Leave out lines of the replied e-mail that are already quoted this many"] # [doc = " times or more. For example, `1` quotes only the replied text itself."] # [doc = " Default: None"] # [serde (alias = "quote-depth-limit")] # [serde (default)] pub quote_depth_limit : Option < Option < usize > > , # [doc = " Query parameters to remove from URLs in the quoted text of replies, as"] # [doc = " glob patterns of parameter names, e.g. `utm_*`. A pattern preceded by"] # [doc = " a domain and `:`, e.g. `example.com:ref`, only applies to URLs of that"] # [doc = " domain and its subdomains."] # [doc = " Default: common tracking parameters, such as `utm_*` and `fbclid`."] # [serde (alias = "quote-strip-url-parameters")] # [serde (default)] pub quote_strip_url_parameters : Option < Vec < String > > , # [doc = " Show the replied e-mail next to the composer when replying, either"] # [doc = " `\"vertical\"` (side by side) or `\"horizontal\"` (below the composer)."] # [doc = " The `toggle_original` shortcut shows or hides it."] # [doc = " Default: None"] # [serde (alias = "reply-split")] # [serde (default)] pub reply_split : Option < Option < PaneSplit > > , # [doc = " Forward emails as attachment? (Alternative is inline)"] # [doc = " Default: ask"] # [serde (alias = "forward-as-attachment")] # [serde (default)] pub forward_as_attachment : Option < ActionFlag > , # [doc = " Alternative lists of reply prefixes (etc. [\"Re:\", \"RE:\", ...]) to strip"] # [doc = " Default: `[\"Re:\", \"RE:\", \"Fwd:\", \"Fw:\", \"回复:\", \"回覆:\", \"SV:\", \"Sv:\","] # [doc = " \"VS:\", \"Antw:\", \"Doorst:\", \"VS:\", \"VL:\", \"REF:\", \"TR:\", \"TR:\", \"AW:\","] # [doc = " \"WG:\", \"ΑΠ:\", \"Απ:\", \"απ:\", \"ΠΡΘ:\", \"Πρθ:\", \"πρθ:\", \"ΣΧΕΤ:\", \"Σχετ:\","] # [doc = " \"σχετ:\", \"ΠΡΘ:\", \"Πρθ:\", \"πρθ:\", \"Vá:\", \"Továbbítás:\", \"R:\", \"I:\","] # [doc = " \"RIF:\", \"FS:\", \"BLS:\", \"TRS:\", \"VS:\", \"VB:\", \"RV:\", \"RES:\", \"Res\","] # [doc = " \"ENC:\", \"Odp:\", \"PD:\", \"YNT:\", \"İLT:\", \"ATB:\", \"YML:\"]`"] # [serde (alias = "reply-prefix-list-to-strip")] # [serde (default)] pub reply_prefix_list_to_strip : Option < Option < Vec < String > > > , # [doc = " The prefix to use in reply subjects. The de facto prefix is \"Re:\"."] # [serde (alias = "reply-prefix")] # [serde (default)] pub reply_prefix : Option < String > , # [doc = " Custom `compose-hooks`."] # [serde (alias = "custom-compose-hooks")] # [serde (default)] pub custom_compose_hooks : Option < Vec < ComposeHook > > , # [doc = " Disabled `compose-hooks`."] # [serde (alias = "disabled-compose-hooks")] # [serde (default)] pub disabled_compose_hooks : Option < Vec < String > > , # [doc = " Plain text file with signature that will pre-populate an email draft."] # [doc = ""] # [doc = " Signatures must be explicitly enabled to be used, otherwise this setting"] # [doc = " will be ignored."] # [doc = ""] # [doc = " Default: `None`"] # [serde (alias = "signature-file")] # [serde (default)] pub signature_file : Option < Option < PathBuf > > , # [doc = " Pre-populate email drafts with signature, if any."] # [doc = ""] # [doc = " `meli` will lookup the signature value in this order:"] # [doc = ""] # [doc = " 1. The `signature_file` setting."] # [doc = " 2. `${XDG_CONFIG_DIR}/meli/<account>/signature`"] # [doc = " 3. `${XDG_CONFIG_DIR}/meli/signature`"] # [doc = " 4. `${XDG_CONFIG_DIR}/signature`"] # [doc = " 5. `${HOME}/.signature`"] # [doc = " 6. No signature otherwise."] # [doc = ""] # [doc = " Default: `false`"] # [serde (alias = "use-signature")] # [serde (default)] pub use_signature : Option < bool > , # [doc = " Signature delimiter, that is, text that will be prefixed to your"] # [doc = " signature to separate it from the email body."] # [doc = ""] # [doc = " Default: `\"\\n\\n-- \\n\"`"] # [serde (alias = "signature-delimiter")] # [serde (default)] pub signature_delimiter : Option < Option < String > > , # [doc = " When replying to an e-mail authored by our main identity or one of our"] # [doc = " extra identities, reply to those addresses instead of reusing the"] # [doc = " receivers of the original e-mail we are replying to."] # [doc = ""] # [doc = " The default is `false`, because the intuitive behavior when replying to"] # [doc = " ourselves is to follow-up on an e-mail we sent."] # [doc = ""] # [doc = " Default: `false`"] # [serde (alias = "allow-reply-to-self")] # [serde (default)] pub allow_reply_to_self : Option < bool > , # [doc = " Named distribution lists, that are expanded to their members' addresses"] # [doc = " when they appear in the `To`, `Cc` or `Bcc` headers of a sent e-mail."] # [doc = " Contacts of the address book whose e-mail field holds more than one"] # [doc = " address are distribution lists as well."] # [doc = ""] # [doc = " Default: `{}`"] # [serde (alias = "distribution-lists")] # [serde (default)] pub distribution_lists : Option < IndexMap < String , Vec < String > > > , # [doc = " Keep the names of expanded distribution lists in the `To` and `Cc`"] # [doc = " headers as empty groups, e.g. `team:;`, and send to the members with"] # [doc = " `Bcc` instead."] # [doc = ""] # [doc = " Default: `false`"] # [serde (alias = "distribution-lists-keep-name")] # [serde (default)] pub distribution_lists_keep_name : Option < bool > , # [doc = " Column at which the draft body is wrapped when shown in the composer,"] # [doc = " and at which the `reformat` command wraps its paragraphs."] # [doc = ""] # [doc = " Default: `77`"] # [serde (alias = "wrap-column")] # [serde (default)] pub wrap_column : Option < usize > , # [doc = " Reformat the paragraphs of the draft body at `wrap_column` before"] # [doc = " sending it, as the `reformat` command does."] # [doc = ""] # [doc = " Default: `false`"] # [serde (alias = "hard-wrap")] # [serde (default)] pub hard_wrap : Option < bool > } impl Default for ComposingSettingsOverride { fn default () -> Self { Self { editor_command : None , embedded_pty : None , format_flowed : None , insert_user_agent : None , default_header_values : None , wrap_header_preamble : None , store_sent_mail : None , attribution_format_string : None , attribution_use_posix_locale : None , quote_prefix : None , quote_attachments : None , quote_strip_signature : None , quote_depth_limit : None , quote_strip_url_parameters : None , reply_split : None , forward_as_attachment : None , reply_prefix_list_to_strip : None , reply_prefix : None , custom_compose_hooks : None , disabled_compose_hooks : None , signature_file : None , use_signature : None , signature_delimiter : None , allow_reply_to_self : None , distribution_lists : None , distribution_lists_keep_name : None , wrap_column : None , hard_wrap : None } } }
 
 # [derive (Debug , Serialize , Deserialize , Clone)] # [serde (deny_unknown_fields)] pub struct TagsSettingsOverride { # [serde (deserialize_with = "tag_color_de")] # [serde (default)] pub colors : Option < IndexMap < TagHash , Color > > , # [serde (deserialize_with = "tag_set_de" , alias = "ignore-tags")] # [serde (default)] pub ignore_tags : Option < IndexSet < TagHash > > } impl Default for TagsSettingsOverride { fn default () -> Self { Self { colors : None , ignore_tags : None } } }
 
-# [derive (Debug , Serialize , Deserialize , Clone)] # [serde (deny_unknown_fields)] pub struct PGPSettingsOverride { # [doc = " auto verify signed e-mail according to RFC3156"] # [doc = " Default: true"] # [serde (alias = "auto-verify-signatures")] # [serde (default)] pub auto_verify_signatures : Option < ActionFlag > , # [doc = " auto decrypt encrypted e-mail"] # [doc = " Default: true"] # [serde (alias = "auto-decrypt")] # [serde (default)] pub auto_decrypt : Option < ActionFlag > , # [doc = " always sign sent e-mail"] # [doc = " Default: false"] # [serde (alias = "auto-sign")] # [serde (default)] pub auto_sign : Option < ActionFlag > , # [doc = " Auto encrypt sent e-mail"] # [doc = " Default: false"] # [serde (alias = "auto-encrypt")] # [serde (default)] pub auto_encrypt : Option < ActionFlag > , # [doc = " Default: None"] # [serde (alias = "sign-key")] # [serde (default)] pub sign_key : Option < Option < String > > , # [doc = " Default: None"] # [serde (alias = "decrypt-key")] # [serde (default)] pub decrypt_key : Option < Option < String > > , # [doc = " Default: None"] # [serde (alias = "encrypt-key")] # [serde (default)] pub encrypt_key : Option < Option < String > > , # [doc = " Default: true"] # [serde (alias = "encrypt-for-self")] # [serde (default)] pub encrypt_for_self : Option < bool > , # [doc = " Allow remote lookups"] # [doc = " Default: False"] # [serde (alias = "allow-remote-lookups")] # [serde (default)] pub allow_remote_lookup : Option < ActionFlag > , # [doc = " Remote lookup mechanisms."] # [doc = " Default: \"local,wkd\""] # [cfg_attr (feature = "gpgme" , serde (alias = "remote-lookup-mechanisms"))] # [cfg (feature = "gpgme")] # [serde (default)] pub remote_lookup_mechanisms : Option < melib :: gpgme :: LocateKey > , # [cfg (not (feature = "gpgme"))] # [cfg_attr (not (feature = "gpgme") , serde (alias = "remote-lookup-mechanisms"))] # [serde (default)] pub remote_lookup_mechanisms : Option < String > } impl Default for PGPSettingsOverride { fn default () -> Self { Self { auto_verify_signatures : None , auto_decrypt : None , auto_sign : None , auto_encrypt : None , sign_key : None , decrypt_key : None , encrypt_key : None , encrypt_for_self : None , allow_remote_lookup : None , remote_lookup_mechanisms : None } } }
+# [derive (Debug , Serialize , Deserialize , Clone)] # [serde (deny_unknown_fields)] pub struct PGPSettingsOverride { # [doc = " auto verify signed e-mail according to RFC3156"] # [doc = " Default: true"] # [serde (alias = "auto-verify-signatures")] # [serde (default)] pub auto_verify_signatures : Option < ActionFlag > , # [doc = " auto decrypt encrypted e-mail"] # [doc = " Default: true"] # [serde (alias = "auto-decrypt")] # [serde (default)] pub auto_decrypt : Option < ActionFlag > , # [doc = " always sign sent e-mail"] # [doc = " Default: false"] # [serde (alias = "auto-sign")] # [serde (default)] pub auto_sign : Option < ActionFlag > , # [doc = " Auto encrypt sent e-mail"] # [doc = " Default: false"] # [serde (alias = "auto-encrypt")] # [serde (default)] pub auto_encrypt : Option < ActionFlag > , # [doc = " Default: None"] # [serde (alias = "sign-key")] # [serde (default)] pub sign_key : Option < Option < String > > , # [doc = " Default: None"] # [serde (alias = "decrypt-key")] # [serde (default)] pub decrypt_key : Option < Option < String > > , # [doc = " Default: None"] # [serde (alias = "encrypt-key")] # [serde (default)] pub encrypt_key : Option < Option < String > > , # [doc = " Default: true"] # [serde (alias = "encrypt-for-self")] # [serde (default)] pub encrypt_for_self : Option < bool > , # [doc = " Move the subject of encrypted e-mail into the encrypted part and"] # [doc = " replace it with a placeholder in the unencrypted headers."] # [doc = " Default: true"] # [serde (alias = "protected-headers")] # [serde (default)] pub protected_headers : Option < bool > , # [doc = " Rules that decide when sending whether e-mail to each recipient is"] # [doc = " encrypted, signed or sent in plain text, keyed by e-mail address, by"] # [doc = " domain or by `*` for all other recipients. If set, a summary of the"] # [doc = " decisions is shown before sending."] # [doc = " Default: empty"] # [serde (alias = "recipient-policies")] # [serde (default)] pub recipient_policies : Option < IndexMap < String , RecipientPolicy > > , # [doc = " Allow remote lookups"] # [doc = " Default: False"] # [serde (alias = "allow-remote-lookups")] # [serde (default)] pub allow_remote_lookup : Option < ActionFlag > , # [doc = " Remote lookup mechanisms."] # [doc = " Default: \"local,wkd\""] # [cfg_attr (feature = "gpgme" , serde (alias = "remote-lookup-mechanisms"))] # [cfg (feature = "gpgme")] # [serde (default)] pub remote_lookup_mechanisms : Option < melib :: gpgme :: LocateKey > , # [cfg (not (feature = "gpgme"))] # [cfg_attr (not (feature = "gpgme") , serde (alias = "remote-lookup-mechanisms"))] # [serde (default)] pub remote_lookup_mechanisms : Option < String > } impl Default for PGPSettingsOverride { fn default () -> Self { Self { auto_verify_signatures : None , auto_decrypt : None , auto_sign : None , auto_encrypt : None , sign_key : None , decrypt_key : None , encrypt_key : None , encrypt_for_self : None , protected_headers : None , recipient_policies : None , allow_remote_lookup : None , remote_lookup_mechanisms : None } } }
 
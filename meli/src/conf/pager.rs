@@ -40,6 +40,11 @@ pub struct PagerSettings {
     #[serde(default = "false_val", alias = "pager-stop")]
     pub pager_stop: bool,
 
+    /// Mark e-mail as seen when it is opened.
+    /// Default: true
+    #[serde(default = "true_val", alias = "auto-mark-read")]
+    pub auto_mark_read: bool,
+
     /// Always show headers when scrolling.
     /// Default: true
     #[serde(
@@ -119,10 +124,112 @@ pub struct PagerSettings {
     )]
     pub html_open: Option<String>,
 
+    /// Senders whose HTML e-mail may load remote content such as images,
+    /// either as e-mail addresses or as domains prefixed with `@`, e.g.
+    /// `@example.com`. Remote content of other senders is blocked.
+    /// Default: []
+    #[serde(default = "Vec::new", alias = "html-remote-content-allowlist")]
+    pub html_remote_content_allowlist: Vec<String>,
+
+    /// A command to pipe html output of senders in
+    /// `html_remote_content_allowlist` to instead of `html_filter`, so that it
+    /// can fetch remote images.
+    /// Default: None
+    #[serde(
+        default = "none",
+        deserialize_with = "non_empty_opt_string",
+        alias = "html-remote-content-filter"
+    )]
+    pub html_remote_content_filter: Option<String>,
+
+    /// Write out the targets of links in HTML e-mail whose anchor text
+    /// differs from them. Targets of suspicious links are always written out.
+    /// Default: false
+    #[serde(default = "false_val", alias = "html-reveal-link-targets")]
+    pub html_reveal_link_targets: bool,
+
     /// Extra headers to display, if present, in the default header preamble.
     /// Default: []
     #[serde(default = "Vec::new", alias = "show-extra-headers")]
     pub show_extra_headers: Vec<HeaderName>,
+
+    /// A command to pipe formatted mail to with the `print-mail` command, for
+    /// example `lpr` or `enscript -o - | ps2pdf - out.pdf`.
+    /// Default: None
+    #[serde(
+        default = "none",
+        deserialize_with = "non_empty_opt_string",
+        alias = "print-command"
+    )]
+    pub print_command: Option<String>,
+
+    /// Lines per page of formatted mail output, pages are separated with form
+    /// feeds. Set to 0 to disable pagination.
+    /// Default: 66
+    #[serde(default = "sixty_six_val", alias = "print-page-lines")]
+    pub print_page_lines: usize,
+
+    /// A command to pipe text copied in the pager's visual mode to, for
+    /// example `xclip -selection clipboard` or `wl-copy`. If unset, the text
+    /// is sent to the terminal's clipboard with the OSC 52 escape sequence.
+    /// Default: None
+    #[serde(
+        default = "none",
+        deserialize_with = "non_empty_opt_string",
+        alias = "clipboard-command"
+    )]
+    pub clipboard_command: Option<String>,
+
+    /// Commands that convert attachments to text for previewing, keyed by
+    /// MIME type glob. `%s` is replaced by the path of a temporary file with
+    /// the attachment, otherwise it is piped to the command's standard input.
+    /// Default: converters for PDF, office documents and images.
+    #[serde(
+        default = "default_attachment_previewers",
+        alias = "attachment-previewers"
+    )]
+    pub attachment_previewers: IndexMap<String, String>,
+
+    /// Commands to pipe attachments to with the `attachment_action` shortcut,
+    /// keyed by MIME type glob, for example `khal import --batch %s` for
+    /// `text/calendar`. `%s` is replaced by the path of a temporary file with
+    /// the attachment, otherwise it is piped to the command's standard input.
+    /// Default: empty
+    #[serde(default, alias = "attachment-actions")]
+    pub attachment_actions: IndexMap<String, String>,
+
+    /// A command to scan attachments with before opening or saving them, for
+    /// example `clamdscan --no-summary -`. It must exit with status 0 for
+    /// clean attachments and 1 for flagged ones. `%s` is replaced by the path
+    /// of a temporary file with the attachment, otherwise it is piped to the
+    /// command's standard input.
+    /// Default: None
+    #[serde(
+        default = "none",
+        deserialize_with = "non_empty_opt_string",
+        alias = "attachment-scan-command"
+    )]
+    pub attachment_scan_command: Option<String>,
+}
+
+fn default_attachment_previewers() -> IndexMap<String, String> {
+    [
+        ("application/pdf", "pdftotext -layout %s -"),
+        ("application/msword", "libreoffice --headless --cat %s"),
+        (
+            "application/vnd.openxmlformats-officedocument.*",
+            "libreoffice --headless --cat %s",
+        ),
+        (
+            "application/vnd.oasis.opendocument.*",
+            "libreoffice --headless --cat %s",
+        ),
+        ("application/vnd.ms-*", "libreoffice --headless --cat %s"),
+        ("image/*", "exiftool %s"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect()
 }
 
 impl Default for PagerSettings {
@@ -130,12 +237,16 @@ impl Default for PagerSettings {
         Self {
             pager_context: 0,
             pager_stop: false,
+            auto_mark_read: true,
             sticky_headers: false,
             pager_ratio: 80,
             filter: None,
             named_filters: IndexMap::default(),
             html_filter: None,
             html_open: None,
+            html_remote_content_allowlist: vec![],
+            html_remote_content_filter: None,
+            html_reveal_link_targets: false,
             format_flowed: true,
             split_long_lines: true,
             minimum_width: 80,
@@ -143,6 +254,12 @@ impl Default for PagerSettings {
             show_date_in_my_timezone: ToggleFlag::InternalVal(true),
             url_launcher: None,
             show_extra_headers: vec![],
+            print_command: None,
+            print_page_lines: 66,
+            clipboard_command: None,
+            attachment_previewers: default_attachment_previewers(),
+            attachment_actions: IndexMap::default(),
+            attachment_scan_command: None,
         }
     }
 }
@@ -155,12 +272,20 @@ impl DotAddressable for PagerSettings {
                 match *field {
                     "pager_context" => self.pager_context.lookup(field, tail),
                     "pager_stop" => self.pager_stop.lookup(field, tail),
+                    "auto_mark_read" => self.auto_mark_read.lookup(field, tail),
                     "sticky_headers" => self.sticky_headers.lookup(field, tail),
                     "pager_ratio" => self.pager_ratio.lookup(field, tail),
                     "filter" => self.filter.lookup(field, tail),
                     "named_filters" => self.named_filters.lookup(field, tail),
                     "html_filter" => self.html_filter.lookup(field, tail),
                     "html_open" => self.html_open.lookup(field, tail),
+                    "html_remote_content_allowlist" => {
+                        self.html_remote_content_allowlist.lookup(field, tail)
+                    }
+                    "html_remote_content_filter" => {
+                        self.html_remote_content_filter.lookup(field, tail)
+                    }
+                    "html_reveal_link_targets" => self.html_reveal_link_targets.lookup(field, tail),
                     "format_flowed" => self.format_flowed.lookup(field, tail),
                     "split_long_lines" => self.split_long_lines.lookup(field, tail),
                     "minimum_width" => self.minimum_width.lookup(field, tail),
@@ -170,6 +295,12 @@ impl DotAddressable for PagerSettings {
                     "show_date_in_my_timezone" => self.show_date_in_my_timezone.lookup(field, tail),
                     "url_launcher" => self.html_filter.lookup(field, tail),
                     "show_extra_headers" => self.show_extra_headers.lookup(field, tail),
+                    "print_command" => self.print_command.lookup(field, tail),
+                    "print_page_lines" => self.print_page_lines.lookup(field, tail),
+                    "clipboard_command" => self.clipboard_command.lookup(field, tail),
+                    "attachment_previewers" => self.attachment_previewers.lookup(field, tail),
+                    "attachment_actions" => self.attachment_actions.lookup(field, tail),
+                    "attachment_scan_command" => self.attachment_scan_command.lookup(field, tail),
                     other => Err(Error::new(format!(
                         "{parent_field} has no field named {other}"
                     ))),
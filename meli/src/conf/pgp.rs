@@ -19,6 +19,7 @@
  * along with meli. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use indexmap::IndexMap;
 use melib::conf::ActionFlag;
 
 use crate::conf::default_values::*;
@@ -64,6 +65,20 @@ pub struct PGPSettings {
     #[serde(default = "true_val", alias = "encrypt-for-self")]
     pub encrypt_for_self: bool,
 
+    /// Move the subject of encrypted e-mail into the encrypted part and
+    /// replace it with a placeholder in the unencrypted headers.
+    /// Default: true
+    #[serde(default = "true_val", alias = "protected-headers")]
+    pub protected_headers: bool,
+
+    /// Rules that decide when sending whether e-mail to each recipient is
+    /// encrypted, signed or sent in plain text, keyed by e-mail address, by
+    /// domain or by `*` for all other recipients. If set, a summary of the
+    /// decisions is shown before sending.
+    /// Default: empty
+    #[serde(default, alias = "recipient-policies")]
+    pub recipient_policies: IndexMap<String, RecipientPolicy>,
+
     /// Allow remote lookups
     /// Default: False
     #[serde(
@@ -104,6 +119,8 @@ impl Default for PGPSettings {
             auto_sign: false.into(),
             auto_encrypt: false.into(),
             encrypt_for_self: true,
+            protected_headers: true,
+            recipient_policies: IndexMap::default(),
             sign_key: None,
             decrypt_key: None,
             encrypt_key: None,
@@ -115,3 +132,18 @@ impl Default for PGPSettings {
         }
     }
 }
+
+/// How e-mail to a recipient is protected, see
+/// [`PGPSettings::recipient_policies`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecipientPolicy {
+    /// Encrypt, and refuse to send if the recipient has no usable key.
+    Encrypt,
+    /// Encrypt if the recipient has a usable key.
+    Opportunistic,
+    /// Sign without encrypting.
+    Sign,
+    /// Neither sign nor encrypt.
+    Plain,
+}
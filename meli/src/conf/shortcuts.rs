@@ -52,6 +52,8 @@ pub struct Shortcuts {
     pub thread_view: ThreadViewShortcuts,
     #[serde(default)]
     pub pager: PagerShortcuts,
+    #[serde(default)]
+    pub triage: TriageShortcuts,
 }
 
 impl Shortcuts {
@@ -62,6 +64,7 @@ impl Shortcuts {
     pub const ENVELOPE_VIEW: &'static str = "envelope_view";
     pub const THREAD_VIEW: &'static str = "thread_view";
     pub const PAGER: &'static str = "pager";
+    pub const TRIAGE: &'static str = "triage";
 }
 
 impl DotAddressable for Shortcuts {
@@ -77,6 +80,7 @@ impl DotAddressable for Shortcuts {
                     "envelope_view" | "envelope-view" => self.envelope_view.lookup(field, tail),
                     "thread_view" | "thread-view" => self.thread_view.lookup(field, tail),
                     "pager" => self.pager.lookup(field, tail),
+                    "triage" => self.triage.lookup(field, tail),
                     other => Err(Error::new(format!(
                         "{parent_field} has no field named {other}"
                     ))),
@@ -184,6 +188,8 @@ shortcut_key_values! { "listing",
         next_entry |> "Focus on next entry." |> Key::Ctrl('n'),
         previous_entry |> "Focus on previous entry." |> Key::Ctrl('p'),
         toggle_menu_visibility |> "Toggle visibility of side menu in mail list." |> Key::Char('`'),
+        next_layout |> "Switch to the next layout of listing.layouts." |> Key::Char('W'),
+        toggle_thread_context |> "Show or hide the entries of the thread under the cursor that listing.filter hides." |> Key::Char('X'),
         focus_left |> "Switch focus on the left." |> Key::Left,
         focus_right |> "Switch focus on the right." |> Key::Right,
         exit_entry |> "Exit e-mail entry." |> Key::Char('i'),
@@ -201,6 +207,8 @@ shortcut_key_values! { "contact-list",
         export_contact |> "Export contact under cursor to .vcf." |> Key::Char('E'),
         delete_contact |> "Delete contact under cursor." |> Key::Char('d'),
         mail_contact |> "Mail contact under cursor." |> Key::Char('m'),
+        merge_contact |> "Merge duplicates of contact under cursor." |> Key::Char('M'),
+        show_correspondence |> "List all e-mail exchanged with contact under cursor." |> Key::Char('C'),
         next_account |> "Go to next account." |> Key::Char('H'),
         prev_account |> "Go to previous account." |> Key::Char('L'),
         toggle_menu_visibility |> "Toggle visibility of side menu in mail list." |> Key::Char('`')
@@ -214,7 +222,9 @@ shortcut_key_values! { "pager",
         page_up |> "Go to previous pager page." |>  Key::PageUp,
         scroll_down |> "Scroll down pager." |> Key::Char('j'),
         scroll_up |> "Scroll up pager." |> Key::Char('k'),
-        select_filter |> "Select content filter." |> Key::Char('f')
+        select_filter |> "Select content filter." |> Key::Char('f'),
+        visual_mode |> "Toggle visual mode to select lines." |> Key::Char('V'),
+        copy_selection |> "Copy selected lines to the clipboard." |> Key::Char('y')
     }
 }
 
@@ -225,6 +235,7 @@ shortcut_key_values! { "general",
         quit |> "Quit meli." |> Key::Char('q'),
         go_to_tab |> "Go to the nth tab." |> Key::Alt('n'),
         next_tab |> "Go to the next tab." |> Key::Char('T'),
+        toggle_zoom |> "Expand the focused pane to the whole window, or restore the layout." |> Key::Char('Z'),
         scroll_right |> "Generic scroll right (catch-all setting)" |> Key::Char('l'),
         scroll_left |> "Generic scroll left (catch-all setting)" |>Key::Char('h'),
         scroll_up |> "Generic scroll up (catch-all setting)" |> Key::Char('k'),
@@ -247,7 +258,12 @@ shortcut_key_values! { "composing",
         edit |> "Edit." |> Key::Char('e'),
         send_mail |> "Deliver draft to mailer." |> Key::Char('s'),
         scroll_up |> "Change field focus." |> Key::Char('k'),
-        scroll_down |> "Change field focus." |> Key::Char('j')
+        scroll_down |> "Change field focus." |> Key::Char('j'),
+        toggle_headers |> "Show or hide empty Cc, Bcc, Reply-To and Followup-To fields." |> Key::Char('H'),
+        thread_participants |> "Choose which participants of the replied thread are in Cc." |> Key::Char('P'),
+        toggle_original |> "Show or hide the replied e-mail next to the composer." |> Key::Char('O'),
+        scroll_original_up |> "Scroll the replied e-mail up." |> Key::Char('K'),
+        scroll_original_down |> "Scroll the replied e-mail down." |> Key::Char('J')
     }
 }
 
@@ -258,6 +274,9 @@ shortcut_key_values! { "envelope-view",
         go_to_url |> "Go to url of given index." |> Key::Char('g'),
         open_attachment |> "Opens selected attachment with xdg-open." |> Key::Char('a'),
         open_mailcap |> "Opens selected attachment according to its mailcap entry." |> Key::Char('m'),
+        preview_attachment |> "Preview selected attachment as text converted by its attachment previewer." |> Key::Char('P'),
+        allow_flagged_attachment |> "Allow opening selected attachment despite the result of the attachment scanner." |> Key::Char('!'),
+        attachment_action |> "Pipe selected attachment, or the first one with an action, to its command in pager.attachment_actions." |> Key::Char('I'),
         open_html |> "Opens html attachment in the default browser." |> Key::Char('v'),
         reply |> "Reply to envelope." |> Key::Char('R'),
         reply_to_author |> "Reply to author." |> Key::Ctrl('r'),
@@ -267,6 +286,9 @@ shortcut_key_values! { "envelope-view",
         toggle_expand_headers |> "Expand extra headers (References and others)." |> Key::Char('h'),
         toggle_url_mode |> "Toggles url open mode." |> Key::Char('u'),
         view_raw_source |> "View envelope source in a pager. (toggles between raw and decoded source)" |> Key::Alt('r'),
+        view_delivery_path |> "View delivery path and per-hop latency from Received headers." |> Key::Alt('p'),
+        forge_open |> "Open the web page of a GitHub or GitLab notification's issue or pull request." |> Key::Alt('o'),
+        forge_mute |> "Unsubscribe from the thread of a GitHub or GitLab notification." |> Key::Alt('m'),
         change_charset |> "Force attachment charset for decoding." |> Key::Char('d')
     }
 }
@@ -284,3 +306,13 @@ shortcut_key_values! { "thread-view",
         toggle_layout |> "Toggle between horizontal and vertical layout." |> Key::Char(' ')
     }
 }
+
+shortcut_key_values! { "triage",
+    /// Shortcut listing for the unread e-mail triage view.
+    pub struct TriageShortcuts {
+        archive |> "Move e-mail to the Archive mailbox and go to the next one." |> Key::Char('a'),
+        delete |> "Send e-mail to the Trash mailbox and go to the next one." |> Key::Char('d'),
+        keep |> "Mark e-mail as seen, leave it in the mailbox and go to the next one." |> Key::Char('n'),
+        reply |> "Reply to e-mail and go to the next one." |> Key::Char('r')
+    }
+}
@@ -38,9 +38,15 @@ pub struct TerminalSettings {
     /// Default: 4.
     pub tab_width: u8,
     pub ascii_drawing: bool,
+    /// Make the interface easier to use with screen readers and braille
+    /// displays: draw with ASCII characters, show the terminal cursor on the
+    /// focused line, describe flags with words and announce changes in the
+    /// status line.
+    /// Default: False
+    pub screen_reader: bool,
     pub use_color: ToggleFlag,
     /// Draw `OSC8` hyperlinks.
-    /// Default: True
+    /// Default: True if the terminal is known to support them.
     pub draw_hyperlinks: ToggleFlag,
     /// Try forcing text presentations of symbols and emoji as much as possible.
     /// Might not work on all non-text symbols and is experimental.
@@ -75,6 +81,7 @@ impl Default for TerminalSettings {
             themes: Themes::default(),
             tab_width: tab_width(),
             ascii_drawing: false,
+            screen_reader: false,
             force_text_presentation: ToggleFlag::InternalVal(false),
             draw_hyperlinks: ToggleFlag::InternalVal(true),
             use_color: ToggleFlag::InternalVal(true),
@@ -101,11 +108,6 @@ impl TerminalSettings {
     pub fn use_text_presentation(&self) -> bool {
         self.force_text_presentation.is_true() || !self.use_color()
     }
-
-    #[inline]
-    pub fn draw_hyperlinks(&self) -> bool {
-        self.draw_hyperlinks.is_true()
-    }
 }
 
 impl DotAddressable for TerminalSettings {
@@ -118,6 +120,7 @@ impl DotAddressable for TerminalSettings {
                     "themes" => Err(Error::new("unimplemented")),
                     "tab_width" => self.tab_width.lookup(field, tail),
                     "ascii_drawing" => self.ascii_drawing.lookup(field, tail),
+                    "screen_reader" => self.screen_reader.lookup(field, tail),
                     "draw_hyperlinks" => self.draw_hyperlinks.lookup(field, tail),
                     "force_text_presentation" => self.force_text_presentation.lookup(field, tail),
                     "use_color" => self.use_color.lookup(field, tail),
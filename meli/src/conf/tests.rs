@@ -277,6 +277,43 @@ color_aliases= { "Jebediah" = "$JebediahJr", "JebediahJr" = "mail.listing.tag_de
     parsed.validate().unwrap_err();
 }
 
+#[test]
+fn test_conf_theme_text_format_groups() {
+    /* MUST SUCCEED: groups apply to valid keys */
+    const TEST_STR: &str = r#"[dark.text_format_regexps."pager.envelope.body"]
+'^-- $' = { attrs = "Dim" }
+
+[dark.text_format_groups.diff]
+keys = ["pager.envelope.body"]
+regexps.'^\+.*' = { fg = "Green" }
+regexps.'^-.*' = { fg = "Red", priority = 1 }
+
+[dark.text_format_groups.team]
+keys = ["mail.view.headers", "listing.from"]
+regexps.'@example\.com\b' = { fg = "mail.listing.tag_default.bg", case_insensitive = true }
+"#;
+    let parsed: Themes = toml::from_str(TEST_STR).unwrap();
+    parsed.validate().unwrap();
+    /* MUST FAIL: group applies to an invalid key */
+    const TEST_INVALID_KEY_STR: &str = r#"[dark.text_format_groups.diff]
+keys = ["pager.body"]
+regexps.'^\+.*' = { fg = "Green" }
+"#;
+    let parsed: Themes = toml::from_str(TEST_INVALID_KEY_STR).unwrap();
+    parsed.validate().unwrap_err();
+    /* MUST FAIL: group does not apply to any key */
+    const TEST_NO_KEYS_STR: &str = r#"[dark.text_format_groups.diff]
+regexps.'^\+.*' = { fg = "Green" }
+"#;
+    toml::from_str::<Themes>(TEST_NO_KEYS_STR).unwrap_err();
+    /* MUST FAIL: invalid regular expression */
+    const TEST_INVALID_REGEXP_STR: &str = r#"[dark.text_format_groups.diff]
+keys = ["pager.envelope.body"]
+regexps.'^(\+.*' = { fg = "Green" }
+"#;
+    toml::from_str::<Themes>(TEST_INVALID_REGEXP_STR).unwrap_err();
+}
+
 #[test]
 fn test_conf_theme_key_values() {
     use std::{collections::VecDeque, fs::File, io::Read, path::PathBuf};
@@ -378,3 +415,653 @@ interval_ms = 51"#
         },
     );
 }
+
+#[test]
+fn test_conf_score_rules() {
+    use melib::Envelope;
+
+    use crate::conf::data_types::ScoreRule;
+
+    let listing: crate::conf::ListingSettingsOverride = toml::from_str(
+        r#"score_rules = [
+  { query = "from:boss@example.com", score = 100 },
+  { query = "header:List-Id,announce", score = -20 },
+  { query = "subject:urgent", score = 10 },
+]"#,
+    )
+    .unwrap();
+    let rules = listing.score_rules.unwrap();
+    assert_eq!(rules.len(), 3);
+
+    let envelope = Envelope::from_bytes(
+        b"From: Boss <boss@example.com>\r\nSubject: urgent\r\nList-Id: \
+          <announce.example.com>\r\n\r\nbody",
+        None,
+    )
+    .unwrap();
+    assert_eq!(ScoreRule::score(&rules, &envelope), 90);
+    let envelope = Envelope::from_bytes(
+        b"From: someone@example.com\r\nSubject: hi\r\n\r\nbody",
+        None,
+    )
+    .unwrap();
+    assert_eq!(ScoreRule::score(&rules, &envelope), 0);
+    assert_eq!(ScoreRule::score(&[], &envelope), 0);
+
+    assert!(toml::from_str::<crate::conf::ListingSettingsOverride>(
+        r#"score_rules = [ { query = "from:a", score = 1, colour = "red" } ]"#
+    )
+    .is_err());
+}
+
+#[test]
+fn test_conf_thread_tree_glyphs() {
+    use crate::conf::data_types::ThreadTreeGlyphs;
+
+    let listing: crate::conf::ListingSettingsOverride = toml::from_str(
+        r#"thread_tree_ascii = true
+thread_tree_glyphs = { vertical = "┃", branch = "┣", last_branch = "┗" }"#,
+    )
+    .unwrap();
+    assert_eq!(listing.thread_tree_ascii, Some(true));
+    assert_eq!(
+        listing.thread_tree_glyphs.unwrap(),
+        Some(ThreadTreeGlyphs {
+            vertical: '┃',
+            branch: '┣',
+            last_branch: '┗',
+            ..ThreadTreeGlyphs::UNICODE
+        })
+    );
+    assert!(toml::from_str::<crate::conf::ListingSettingsOverride>(
+        r#"thread_tree_glyphs = { vertical = "||" }"#
+    )
+    .is_err());
+    assert!(toml::from_str::<crate::conf::ListingSettingsOverride>(
+        r#"thread_tree_glyphs = { stem = "|" }"#
+    )
+    .is_err());
+}
+
+#[test]
+fn test_conf_pane_layouts() {
+    use crate::conf::data_types::{PaneLayout, PaneSplit};
+
+    let listing: crate::conf::ListingSettingsOverride = toml::from_str(
+        r#"layouts = [ "sidebar|listing", "sidebar | listing:30 | pager", "listing / pager" ]"#,
+    )
+    .unwrap();
+    assert_eq!(
+        listing.layouts.unwrap(),
+        vec![
+            PaneLayout::single(),
+            PaneLayout {
+                sidebar: true,
+                split: Some(PaneSplit::Vertical),
+                ratio: 30,
+            },
+            PaneLayout {
+                sidebar: false,
+                split: Some(PaneSplit::Horizontal),
+                ratio: PaneLayout::DEFAULT_RATIO,
+            },
+        ]
+    );
+    for layout in [
+        "sidebar | listing",
+        "listing:30 | pager",
+        "sidebar | listing / pager",
+    ] {
+        assert_eq!(layout.parse::<PaneLayout>().unwrap().to_string(), layout);
+    }
+    for invalid in [
+        "",
+        "pager",
+        "listing | sidebar",
+        "sidebar / listing",
+        "listing | pager | pager",
+        "listing:0 | pager",
+        "sidebar:20 | listing",
+        "listing | preview",
+    ] {
+        assert!(invalid.parse::<PaneLayout>().is_err(), "{invalid:?}");
+    }
+}
+
+#[test]
+fn test_conf_reply_split() {
+    use crate::conf::data_types::PaneSplit;
+
+    let composing: crate::conf::ComposingSettingsOverride =
+        toml::from_str(r#"reply-split = "Horizontal""#).unwrap();
+    assert_eq!(composing.reply_split, Some(Some(PaneSplit::Horizontal)));
+    let composing: crate::conf::ComposingSettings =
+        toml::from_str(r#"reply_split = "vertical""#).unwrap();
+    assert_eq!(composing.reply_split, Some(PaneSplit::Vertical));
+    assert_eq!(crate::conf::ComposingSettings::default().reply_split, None);
+    toml::from_str::<crate::conf::ComposingSettings>(r#"reply_split = "diagonal""#).unwrap_err();
+}
+
+#[test]
+fn test_conf_allowed_commands() {
+    let config = FileSettings::validate_string(
+        format!("{IMAP_CONFIG}\nallowed_commands = [\"/usr/bin/vim\"]\nlog_commands = true\n"),
+        true,
+    )
+    .unwrap();
+    let policy = config.accounts["imap"].command_policy();
+    assert_eq!(
+        policy.allowed,
+        Some(vec![std::path::PathBuf::from("/usr/bin/vim")])
+    );
+    assert!(policy.log);
+    let account = crate::conf::AccountConf::from(config.accounts["imap"].clone());
+    assert_eq!(account.credential_commands(), vec!["false"]);
+    let mut sh = std::process::Command::new("sh");
+    sh.args(["-c", "false"]);
+    assert_eq!(
+        policy.check(&sh).unwrap_err().kind(),
+        std::io::ErrorKind::PermissionDenied
+    );
+
+    let err = FileSettings::validate_string(
+        format!("{IMAP_CONFIG}\nallowed_commands = [\"vim\"]\n"),
+        true,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.summary.as_ref(),
+        "Account `imap`: allowed_commands must contain absolute paths, got `vim`"
+    );
+}
+
+#[test]
+fn test_conf_retained_headers() {
+    let config = FileSettings::validate_string(
+        format!("{IMAP_CONFIG}\nretained_headers = [\"X-Spam-Status\", \"Delivered-To\"]\n"),
+        true,
+    )
+    .unwrap();
+    assert_eq!(
+        config.accounts["imap"].retained_headers,
+        vec!["X-Spam-Status".to_string(), "Delivered-To".to_string()]
+    );
+
+    let err = FileSettings::validate_string(
+        format!("{IMAP_CONFIG}\nretained_headers = [\"X-Spam Status\"]\n"),
+        true,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.summary.as_ref(),
+        "`retained_headers` value \"X-Spam Status\" is not a valid header name"
+    );
+}
+
+#[test]
+fn test_conf_sieve_script() {
+    const MBOX_CONFIG: &str = r#"
+[accounts.mbox]
+root_mailbox = "/"
+format = "mbox"
+send_mail = 'false'
+identity="username@hostname.local"
+    "#;
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let script = ConfigFile::new(
+        "require \"fileinto\";\nif header :contains \"subject\" \"[rust]\" {\n    fileinto \
+         \"lists/rust\";\n}\n",
+        &tempdir,
+    )
+    .unwrap();
+    let config = FileSettings::validate_string(
+        format!(
+            "{MBOX_CONFIG}\nsieve_script = \"{}\"\n",
+            script.path.display()
+        ),
+        true,
+    )
+    .unwrap();
+    assert_eq!(
+        config.accounts["mbox"].sieve_script.as_ref(),
+        Some(&script.path)
+    );
+
+    let err = FileSettings::validate_string(
+        format!(
+            "{IMAP_CONFIG}\nsieve_script = \"{}\"\n",
+            script.path.display()
+        ),
+        true,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.summary.as_ref(),
+        "Account `imap`: sieve_script is only supported by maildir and mbox accounts"
+    );
+
+    let invalid = ConfigFile::new("if true {\n    discard;\n", &tempdir).unwrap();
+    let err = FileSettings::validate_string(
+        format!(
+            "{MBOX_CONFIG}\nsieve_script = \"{}\"\n",
+            invalid.path.display()
+        ),
+        true,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.summary.as_ref(),
+        format!(
+            "Account `mbox`: `{}`: Could not parse sieve script at line 1.",
+            invalid.path.display()
+        )
+    );
+}
+
+#[test]
+fn test_conf_send_mail_rules() {
+    use melib::{Draft, HeaderName};
+
+    use crate::conf::{composing::SendMail, AccountConf};
+
+    let config = FileSettings::validate_string(
+        format!(
+            "{IMAP_CONFIG}\nsend_mail_retries = 2\n\n[[accounts.imap.send_mail_rules]]\nidentity \
+             = \"*@work.example.com\"\nsend_mail = \"msmtp -a \
+             work\"\n\n[[accounts.imap.send_mail_rules]]\nrecipient_domain = \
+             \"example.org\"\nsend_mail = \"msmtp -a lists\"\n"
+        ),
+        true,
+    )
+    .unwrap();
+    let account = &config.accounts["imap"];
+    assert_eq!(account.send_mail_rules.len(), 2);
+    assert_eq!(
+        account.send_mail_retry_policy(),
+        (2, crate::accounts::DEFAULT_SEND_MAIL_RETRY_DELAY)
+    );
+    let account = AccountConf::from(account.clone());
+    let command = |from: &str, to: &str| {
+        let mut draft = Draft::default();
+        draft
+            .set_header(HeaderName::FROM, from.to_string())
+            .set_header(HeaderName::TO, to.to_string());
+        match account.send_mail_for(&draft) {
+            SendMail::ShellCommand(command) => command.clone(),
+            other => panic!("unexpected send_mail {other:?}"),
+        }
+    };
+    assert_eq!(
+        command("Me <me@work.example.com>", "someone@example.com"),
+        "msmtp -a work"
+    );
+    assert_eq!(
+        command(
+            "username@example.com",
+            "list@example.org, other@lists.example.org"
+        ),
+        "msmtp -a lists"
+    );
+    assert_eq!(
+        command(
+            "username@example.com",
+            "list@example.org, someone@example.com"
+        ),
+        "false"
+    );
+    assert_eq!(
+        command("username@example.com", "someone@notexample.org"),
+        "false"
+    );
+
+    let err = FileSettings::validate_string(
+        format!("{IMAP_CONFIG}\n[[accounts.imap.send_mail_rules]]\nidentity = \"*@example.com\"\n"),
+        true,
+    );
+    assert!(err.is_err(), "a rule without send_mail should be rejected");
+}
+
+#[test]
+fn test_conf_special_mailboxes() {
+    use melib::SpecialUsageMailbox;
+
+    use crate::conf::SpecialMailboxes;
+
+    let config = FileSettings::validate_string(
+        format!(
+            "{IMAP_CONFIG}\n[accounts.imap.special_mailboxes]\nsent = \"[Gmail]/Sent \
+             Mail\"\ntrash = \"[Gmail]/Trash\"\n"
+        ),
+        true,
+    )
+    .unwrap();
+    let special_mailboxes = &config.accounts["imap"].special_mailboxes;
+    assert_eq!(
+        special_mailboxes.get(SpecialUsageMailbox::Sent),
+        Some("[Gmail]/Sent Mail")
+    );
+    assert_eq!(
+        special_mailboxes.get(SpecialUsageMailbox::Trash),
+        Some("[Gmail]/Trash")
+    );
+    assert_eq!(special_mailboxes.get(SpecialUsageMailbox::Drafts), None);
+    assert_eq!(special_mailboxes.get(SpecialUsageMailbox::Inbox), None);
+
+    let mut special_mailboxes = SpecialMailboxes::default();
+    for usage in SpecialMailboxes::USAGES {
+        special_mailboxes.set(usage, usage.to_string());
+        assert_eq!(
+            special_mailboxes.get(usage),
+            Some(usage.to_string().as_str())
+        );
+    }
+    special_mailboxes.set(SpecialUsageMailbox::Inbox, "INBOX".to_string());
+    assert_eq!(special_mailboxes.get(SpecialUsageMailbox::Inbox), None);
+    assert_eq!(
+        serde_json::from_str::<SpecialMailboxes>(
+            &serde_json::to_string(&special_mailboxes).unwrap()
+        )
+        .unwrap(),
+        special_mailboxes
+    );
+
+    FileSettings::validate_string(
+        format!("{IMAP_CONFIG}\n[accounts.imap.special_mailboxes]\noutbox = \"Outbox\"\n"),
+        true,
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn test_conf_jobs() {
+    let config = FileSettings::validate_string(
+        format!("{IMAP_CONFIG}\n[jobs]\nexecutor_threads = 3\ncpu_affinity = [0, 2]\n"),
+        true,
+    )
+    .unwrap();
+    assert_eq!(config.jobs.executor_threads.map(usize::from), Some(3));
+    assert_eq!(config.jobs.cpu_affinity, vec![0, 2]);
+    FileSettings::validate_string(
+        format!("{IMAP_CONFIG}\n[jobs]\nexecutor_threads = 0\n"),
+        true,
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn test_conf_usage_stats() {
+    let config = FileSettings::validate_string(IMAP_CONFIG.to_string(), true).unwrap();
+    assert!(!config.usage_stats.enabled);
+    let config = FileSettings::validate_string(
+        format!("{IMAP_CONFIG}\n[usage_stats]\nenabled = true\n"),
+        true,
+    )
+    .unwrap();
+    assert!(config.usage_stats.enabled);
+    FileSettings::validate_string(
+        format!("{IMAP_CONFIG}\n[usage_stats]\nupload = true\n"),
+        true,
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn test_conf_pgp_recipient_policies() {
+    use crate::conf::RecipientPolicy;
+
+    let config = FileSettings::validate_string(
+        format!(
+            "{IMAP_CONFIG}\n[pgp.recipient_policies]\n\"example.com\" = \"encrypt\"\n\"*\" = \
+             \"opportunistic\"\n"
+        ),
+        true,
+    )
+    .unwrap();
+    assert_eq!(
+        config
+            .pgp
+            .recipient_policies
+            .iter()
+            .map(|(k, v)| (k.as_str(), *v))
+            .collect::<Vec<_>>(),
+        vec![
+            ("example.com", RecipientPolicy::Encrypt),
+            ("*", RecipientPolicy::Opportunistic),
+        ]
+    );
+    FileSettings::validate_string(
+        format!("{IMAP_CONFIG}\n[pgp.recipient_policies]\n\"example.com\" = \"always\"\n"),
+        true,
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn test_conf_plugins() {
+    use crate::conf::PluginHook;
+
+    let config = FileSettings::validate_string(IMAP_CONFIG.to_string(), true).unwrap();
+    assert!(config.plugins.is_empty());
+    let config = FileSettings::validate_string(
+        format!(
+            "{IMAP_CONFIG}\n[plugins.score]\ncommand = \"score-plugin\"\nhooks = [\"pre_send\", \
+             \"listing_column\"]\ncommands = [\"report\"]\n"
+        ),
+        true,
+    )
+    .unwrap();
+    let plugin = &config.plugins["score"];
+    assert_eq!(plugin.command, "score-plugin");
+    assert_eq!(
+        plugin.hooks,
+        vec![PluginHook::PreSend, PluginHook::ListingColumn]
+    );
+    assert_eq!(plugin.commands, vec!["report".to_string()]);
+    assert_eq!(plugin.column_width, 12);
+    assert_eq!(plugin.timeout, 5);
+    FileSettings::validate_string(
+        format!("{IMAP_CONFIG}\n[plugins.score]\ncommand = \"x\"\nhooks = [\"on_quit\"]\n"),
+        true,
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn test_conf_edit_settings_docs() {
+    use crate::conf::edit::settings_docs;
+
+    let docs = settings_docs();
+    let doc = &docs["pager.sticky_headers"];
+    assert_eq!(doc.kind.as_deref(), Some("boolean"));
+    assert!(doc.text.starts_with("Always show headers when scrolling."));
+    assert!(doc.text.ends_with("Default: false"));
+    assert!(docs["listing.sort"].kind.is_some());
+    assert!(docs["shortcuts.general.quit"].text.contains("Default: q"));
+    assert!(docs.contains_key("shortcuts.contact_list.create_contact"));
+    // Account settings are not part of any editable section.
+    assert!(!docs.keys().any(|k| k.contains("root_mailbox")));
+    assert!(!docs.values().any(|d| d.text.contains("\\-")));
+}
+
+#[test]
+fn test_conf_edit_update_toml_text() {
+    use crate::conf::edit::update_toml_text;
+
+    const TEXT: &str = r#"# My configuration
+[pager]
+# Show headers
+sticky_headers = false # comment
+  filter = "less"
+
+[listing.sidebar]
+"#;
+    let text = update_toml_text(
+        TEXT,
+        &["pager", "sticky_headers"],
+        &toml::Value::Boolean(true),
+    )
+    .unwrap();
+    assert_eq!(
+        text,
+        TEXT.replace("sticky_headers = false", "sticky_headers = true")
+    );
+    let text = update_toml_text(
+        TEXT,
+        &["pager", "filter"],
+        &toml::Value::String("less -R".to_string()),
+    )
+    .unwrap();
+    assert_eq!(
+        text,
+        TEXT.replace(r#"  filter = "less""#, r#"  filter = "less -R""#)
+    );
+    let text =
+        update_toml_text(TEXT, &["pager", "pager_context"], &toml::Value::Integer(2)).unwrap();
+    assert_eq!(
+        text,
+        TEXT.replace("[pager]\n", "[pager]\npager_context = 2\n")
+    );
+    let text = update_toml_text(
+        TEXT,
+        &["terminal", "theme"],
+        &toml::Value::String("light".into()),
+    )
+    .unwrap();
+    assert_eq!(text, format!("{TEXT}\n[terminal]\ntheme = \"light\"\n"));
+
+    // Dotted keys and top level tables.
+    let text = update_toml_text(
+        "pager.filter = \"less\"\n",
+        &["pager", "filter"],
+        &toml::Value::String("more".into()),
+    )
+    .unwrap();
+    assert_eq!(text, "pager.filter = \"more\"\n");
+
+    // Values that span multiple lines are not replaced.
+    assert!(update_toml_text(
+        "[pager]\nfilter = \"\"\"\nless\n\"\"\"\n",
+        &["pager", "filter"],
+        &toml::Value::String("more".into()),
+    )
+    .is_err());
+}
+
+#[test]
+fn test_conf_edit_key_paths() {
+    use crate::conf::edit::{flatten, join_key, split_key, update_toml_text};
+
+    assert_eq!(split_key("pager.filter").unwrap(), vec!["pager", "filter"]);
+    assert_eq!(
+        split_key(r#"shortcuts . "a.b".c"#).unwrap(),
+        vec!["shortcuts", "a.b", "c"]
+    );
+    assert_eq!(join_key(&["shortcuts", "a.b", "c"]), r#"shortcuts."a.b".c"#);
+    for key in ["", "a.", "a = 1\nb", "a b"] {
+        assert!(split_key(key).is_err(), "{key:?} should be rejected");
+    }
+
+    let value: toml::Value = toml::from_str("[\"a.b\"]\nc = 1\n").unwrap();
+    assert_eq!(
+        flatten(&value),
+        vec![(
+            vec!["a.b".to_string(), "c".to_string()],
+            toml::Value::Integer(1)
+        )]
+    );
+
+    // Parts that contain dots are neither split nor confused with tables.
+    const TEXT: &str = "[a]\nb = { c = 0 }\n\n[\"a.b\"]\nc = 1\n";
+    let text = update_toml_text(TEXT, &["a.b", "c"], &toml::Value::Integer(2)).unwrap();
+    assert_eq!(text, TEXT.replace("c = 1", "c = 2"));
+    let text = update_toml_text(TEXT, &["a", "b.c"], &toml::Value::Integer(2)).unwrap();
+    assert_eq!(text, TEXT.replace("[a]\n", "[a]\n\"b.c\" = 2\n"));
+}
+
+#[test]
+fn test_conf_mail_ui_conf_set_key() {
+    use crate::conf::{ListingSettingsOverride, MailUIConf};
+
+    let mut conf = MailUIConf::default();
+    conf.set_key("listing.sort", "subject asc").unwrap();
+    conf.set_key("pager.filter", "\"less -R\"").unwrap();
+    conf.set_key("pager.auto_mark_read", "false").unwrap();
+    conf.set_key("pager.pager_context", " 3").unwrap();
+    let expected: ListingSettingsOverride = toml::from_str(r#"sort = "subject asc""#).unwrap();
+    assert_eq!(conf.listing.sort, expected.sort);
+    assert_eq!(conf.pager.filter, Some(Some("less -R".to_string())));
+    assert_eq!(conf.pager.auto_mark_read, Some(false));
+    assert_eq!(conf.pager.pager_context, Some(3));
+    assert_eq!(conf.listing.show_preview, None);
+
+    for (key, value) in [
+        ("listing.no_such_setting", "1"),
+        ("pager.auto_mark_read", "maybe"),
+        ("", "1"),
+        ("listing.sort.field", "date"),
+    ] {
+        assert!(
+            conf.set_key(key, value).is_err(),
+            "{key} = {value} should be rejected"
+        );
+    }
+    // Failed attempts leave the configuration unchanged.
+    assert_eq!(conf.pager.auto_mark_read, Some(false));
+}
+
+#[test]
+fn test_conf_import_muttrc() {
+    let muttrc = r#"
+# IMAP account
+set folder = "imaps://user@imap.example.com/"
+set imap_pass = "hunter2"
+set spoolfile = +INBOX
+set record = "+Sent Items"
+set postponed = +Drafts
+set from = "User Name <user@example.com>"
+set sendmail = "msmtp -a example"
+set sort = threads
+set sort_aux = reverse-last-date-received
+set editor = "vim"
+set index_format = "%4C %Z %{%b %d} %-15.15L (%?l?%4l&%4c?) %s"
+mailboxes +INBOX "+Sent Items" +Drafts +lists/rust
+folder-hook =lists/rust 'set sort=subject; set from=lists@example.com'
+my_hdr X-Clacks-Overhead: GNU Terry Pratchett
+bind index,pager \Cn sidebar-next
+bind pager <up> previous-line
+bind index gg first-entry
+macro index S "<save-message>=Spam<enter>"
+color status brightwhite blue
+color index bold yellow default ~N
+color body red default "[0-9]+"
+"#;
+    let config = crate::conf::muttrc::convert(muttrc, "example");
+    for expected in [
+        "[accounts.\"example\"]\nroot_mailbox = \"INBOX\"\nformat = \"imap\"\n",
+        "server_hostname = \"imap.example.com\"\nserver_port = \"993\"\n",
+        "server_username = \"user\"\nserver_password = \"hunter2\"\n",
+        "identity = \"user@example.com\"\ndisplay_name = \"User Name\"\n",
+        "subscribed_mailboxes = [\"INBOX\", \"Sent Items\", \"Drafts\", \"lists/rust\"]\n",
+        "send_mail = \"msmtp -a example\"\n",
+        "[accounts.\"example\".mailboxes.\"Sent Items\"]\nusage = \"Sent\"\n",
+        "[accounts.\"example\".mailboxes.\"lists/rust\"]\nlisting.sort = \"subject asc\"\nidentity \
+         = \"lists@example.com\"\n",
+        "default_header_values = { \"X-Clacks-Overhead\" = \"GNU Terry Pratchett\" }\n",
+        "[listing]\nindex_style = \"Threaded\"\nsort = \"date desc\"\n",
+        "[shortcuts.listing]\nnext_mailbox = \"C-n\"\n",
+        "[shortcuts.pager]\nscroll_up = \"Up\"\n",
+        "\"status.bar\" = { fg = \"15\", bg = \"4\" }\n",
+        "\"mail.listing.plain.even_unseen\" = { fg = \"3\", bg = \"Default\", attrs = \"Bold\" }\n",
+        "# muttrc:13: set index_format",
+        "# muttrc:19: bind index gg first-entry\n",
+        "# muttrc:20: macro index S",
+        "# muttrc:23: color body red default",
+    ] {
+        assert!(
+            config.contains(expected),
+            "{expected:?} is missing from:\n{config}"
+        );
+    }
+    FileSettings::validate_string(config, true).expect("could not parse imported config");
+}
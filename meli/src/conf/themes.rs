@@ -332,8 +332,28 @@ pub const DEFAULT_KEYS: &[&str] = &[
     "mail.listing.thread_snooze_flag",
     "mail.listing.tag_default",
     "mail.listing.highlight_self",
+    "mail.listing.preview",
+    "mail.listing.initials",
+    "mail.listing.score_high",
+    "mail.listing.score_low",
+    "mail.listing.age_today",
+    "mail.listing.age_month",
+    "mail.listing.age_older",
+    "mail.listing.skeleton",
+    "mail.listing.context",
     "pager.highlight_search",
     "pager.highlight_search_current",
+    "pager.highlight_selection",
+    "pager.quote.a",
+    "pager.quote.b",
+    "pager.quote.c",
+    "pager.quote.d",
+    "pager.diff.header",
+    "pager.diff.hunk",
+    "pager.diff.added",
+    "pager.diff.removed",
+    "pager.signature",
+    "pager.suspicious_link",
 ];
 
 /// `ThemeAttributeInner` but with the links resolved.
@@ -557,8 +577,12 @@ mod regexp {
     use super::*;
     use crate::{conf::data_types::regex_pattern::RegexValue, terminal::FormatTag};
 
-    pub(super) const DEFAULT_TEXT_FORMATTER_KEYS: &[&str] =
-        &["pager.envelope.body", "listing.from", "listing.subject"];
+    pub(super) const DEFAULT_TEXT_FORMATTER_KEYS: &[&str] = &[
+        "pager.envelope.body",
+        "mail.view.headers",
+        "listing.from",
+        "listing.subject",
+    ];
 
     #[derive(Clone, Debug)]
     pub(super) struct TextFormatterSetting {
@@ -713,9 +737,20 @@ impl<'de> Deserialize<'de> for Themes {
             attr_aliases: IndexMap<Cow<'static, str>, ThemeValue<Attr>>,
             #[serde(default)]
             text_format_regexps: IndexMap<Cow<'static, str>, IndexMap<String, RegexpOptions>>,
+            #[serde(default)]
+            text_format_groups: IndexMap<String, TextFormatGroupOptions>,
             #[serde(flatten, default)]
             keys: IndexMap<Cow<'static, str>, ThemeAttributeInnerOptions>,
         }
+        /// A named set of regular expressions applied to each key in `keys`.
+        #[derive(Default, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct TextFormatGroupOptions {
+            #[serde(default)]
+            keys: Vec<Cow<'static, str>>,
+            #[serde(default)]
+            regexps: IndexMap<String, RegexpOptions>,
+        }
         #[derive(Default, Deserialize)]
         struct RegexpOptions {
             #[serde(flatten)]
@@ -789,9 +824,12 @@ impl<'de> Deserialize<'de> for Themes {
                         .join(", ")
                 )));
             }
-            theme.color_aliases = s.color_aliases;
-            theme.attr_aliases = s.attr_aliases;
-            for (k, v) in s.text_format_regexps {
+            fn compile_regexps<'de, D>(
+                v: IndexMap<String, RegexpOptions>,
+            ) -> std::result::Result<SmallVec<[TextFormatterSetting; 32]>, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
                 let mut acc = SmallVec::new();
                 for (rs, v) in v {
                     match RegexValue::new_with_options(&rs, v.o) {
@@ -809,7 +847,30 @@ impl<'de> Deserialize<'de> for Themes {
                         }
                     }
                 }
-                theme.text_format_regexps.insert(k, acc);
+                Ok(acc)
+            }
+
+            theme.color_aliases = s.color_aliases;
+            theme.attr_aliases = s.attr_aliases;
+            for (k, v) in s.text_format_regexps {
+                theme
+                    .text_format_regexps
+                    .insert(k, compile_regexps::<D>(v)?);
+            }
+            for (group, v) in s.text_format_groups {
+                if v.keys.is_empty() {
+                    return Err(de::Error::custom(format!(
+                        "{name} theme: text_format_groups.{group} does not apply to any keys"
+                    )));
+                }
+                let acc = compile_regexps::<D>(v.regexps)?;
+                for k in v.keys {
+                    theme
+                        .text_format_regexps
+                        .entry(k)
+                        .or_default()
+                        .extend(acc.iter().cloned());
+                }
             }
             Ok(())
         }
@@ -1568,9 +1629,107 @@ impl Default for Themes {
                 fg: Color::BLUE,
             }
         );
+        add!(
+            "mail.listing.preview",
+            light = {
+                fg: Color::Byte(243),
+            },
+            dark = {
+                fg: Color::Byte(243),
+            }
+        );
+        add!(
+            "mail.listing.score_high",
+            light = {
+                fg: Color::Byte(28),
+                attrs: Attr::BOLD
+            },
+            dark = {
+                fg: Color::Byte(114),
+                attrs: Attr::BOLD
+            }
+        );
+        add!(
+            "mail.listing.score_low",
+            light = {
+                fg: Color::Byte(247),
+            },
+            dark = {
+                fg: Color::Byte(243),
+            }
+        );
+        add!(
+            "mail.listing.age_today",
+            light = {
+                attrs: Attr::BOLD
+            },
+            dark = {
+                attrs: Attr::BOLD
+            }
+        );
+        add!(
+            "mail.listing.age_month",
+            light = {
+                fg: Color::Byte(244),
+            },
+            dark = {
+                fg: Color::Byte(248),
+            }
+        );
+        add!(
+            "mail.listing.age_older",
+            light = {
+                fg: Color::Byte(248),
+            },
+            dark = {
+                fg: Color::Byte(242),
+            }
+        );
+        add!(
+            "mail.listing.skeleton",
+            light = {
+                fg: Color::Byte(250),
+                attrs: Attr::ITALICS
+            },
+            dark = {
+                fg: Color::Byte(240),
+                attrs: Attr::ITALICS
+            }
+        );
+        add!(
+            "mail.listing.context",
+            light = {
+                fg: Color::Byte(248),
+            },
+            dark = {
+                fg: Color::Byte(242),
+            }
+        );
+        add!(
+            "mail.listing.initials",
+            light = {
+                fg: Color::White,
+                attrs: Attr::BOLD
+            },
+            dark = {
+                fg: Color::White,
+                attrs: Attr::BOLD
+            }
+        );
 
         add!("pager.highlight_search", light = { fg: Color::White, bg: Color::Byte(6) /* Teal */, attrs: Attr::BOLD }, dark = { fg: Color::White, bg: Color::Byte(6) /* Teal */, attrs: Attr::BOLD });
         add!("pager.highlight_search_current", light = { fg: Color::White, bg: Color::Byte(17) /* NavyBlue */, attrs: Attr::BOLD }, dark = { fg: Color::White, bg: Color::Byte(17) /* NavyBlue */, attrs: Attr::BOLD });
+        add!("pager.highlight_selection", light = { fg: "theme_default", bg: "theme_default", attrs: Attr::REVERSE }, dark = { fg: "theme_default", bg: "theme_default", attrs: Attr::REVERSE });
+        add!("pager.quote.a" from "mail.view.body", light = { fg: Color::Byte(25) }, dark = { fg: Color::Byte(74) }); // DeepSkyBlue4, SkyBlue3
+        add!("pager.quote.b" from "mail.view.body", light = { fg: Color::Byte(29) }, dark = { fg: Color::Byte(72) }); // SpringGreen4, CadetBlue
+        add!("pager.quote.c" from "mail.view.body", light = { fg: Color::Byte(96) }, dark = { fg: Color::Byte(139) }); // Plum4, Grey63
+        add!("pager.quote.d" from "mail.view.body", light = { fg: Color::Byte(130) }, dark = { fg: Color::Byte(137) }); // DarkOrange3, LightSalmon3
+        add!("pager.diff.header" from "mail.view.body", light = { attrs: Attr::BOLD }, dark = { attrs: Attr::BOLD });
+        add!("pager.diff.hunk" from "mail.view.body", light = { fg: Color::Byte(30) }, dark = { fg: Color::Byte(37) }); // Turquoise4, LightSeaGreen
+        add!("pager.diff.added" from "mail.view.body", light = { fg: Color::Byte(28) }, dark = { fg: Color::Byte(34) }); // Green4, Green3
+        add!("pager.diff.removed" from "mail.view.body", light = { fg: Color::Byte(124) }, dark = { fg: Color::Byte(160) }); // Red3
+        add!("pager.signature" from "mail.view.body", light = { fg: Color::Byte(243) }, dark = { fg: Color::Byte(243) }); // Grey46
+        add!("pager.suspicious_link" from "mail.view.body", light = { fg: Color::Byte(160), attrs: Attr::BOLD | Attr::UNDERLINE }, dark = { fg: Color::Byte(196), attrs: Attr::BOLD | Attr::UNDERLINE }); // Red3, Red1
         Self {
             light: Theme {
                 keys: light,
@@ -146,6 +146,8 @@ impl ContactManager {
             .push(("NAME PREFIX".into(), self.card.name_prefix().to_string()));
         self.form
             .push(("NAME SUFFIX".into(), self.card.name_suffix().to_string()));
+        self.form
+            .push(("NICKNAME".into(), self.card.nickname().to_string()));
         self.form
             .push(("E-MAIL".into(), self.card.email().to_string()));
         self.form.push(("URL".into(), self.card.url().to_string()));
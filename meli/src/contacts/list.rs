@@ -24,9 +24,16 @@ use std::cmp;
 use melib::{backends::AccountHash, text::TextProcessing, Card, CardId, Draft};
 
 use crate::{
-    conf, contacts::editor::ContactManager, shortcut, terminal::*, Action::Tab, Component,
-    ComponentId, Composer, Context, DataColumns, PageMovement, ScrollContext, ScrollUpdate,
-    ShortcutMaps, Shortcuts, StatusEvent, TabAction, ThemeAttribute, UIEvent, UIMode,
+    command::actions::AccountAction,
+    conf,
+    contacts::editor::ContactManager,
+    shortcut,
+    terminal::*,
+    types::NotificationType,
+    Action::{self, Tab},
+    Component, ComponentId, Composer, Context, DataColumns, Listing, PageMovement, ScrollContext,
+    ScrollUpdate, ShortcutMaps, Shortcuts, StatusEvent, TabAction, ThemeAttribute, UIDialog,
+    UIEvent, UIMode,
 };
 
 #[derive(Debug)]
@@ -539,6 +546,17 @@ impl Component for ContactList {
                 self.sidebar_divider_theme = conf::value(context, "mail.sidebar_divider");
                 self.set_dirty(true);
             }
+            UIEvent::AccountAdded(account_hash) => {
+                if let Some(index) = context.accounts.get_index_of(account_hash) {
+                    self.accounts.push(AccountMenuEntry {
+                        name: context.accounts[index].name().to_string(),
+                        _hash: *account_hash,
+                        index,
+                    });
+                }
+                self.initialized = false;
+                self.set_dirty(true);
+            }
             UIEvent::AccountStatusChange(_, _) => {
                 self.initialized = false;
                 self.set_dirty(true);
@@ -549,6 +567,42 @@ impl Component for ContactList {
             UIEvent::Resize => {
                 self.set_dirty(true);
             }
+            UIEvent::Action(Action::AccountAction(
+                account_name,
+                AccountAction::MergeContacts { into, from },
+            )) if context.accounts[self.account_pos].name() == account_name.as_str() => {
+                let into = *into;
+                let contacts = &mut context.accounts[self.account_pos].contacts;
+                match contacts.merge_cards(into, from) {
+                    Ok(()) => {
+                        self.new_cursor_pos = contacts.get_index_of(&into).unwrap_or(0);
+                        self.cursor_pos = self.new_cursor_pos;
+                        context.replies.push_back(UIEvent::Notification {
+                            title: None,
+                            source: None,
+                            body: format!(
+                                "Merged {} contact{} into {}.",
+                                from.len(),
+                                if from.len() == 1 { "" } else { "s" },
+                                contacts[&into]
+                            )
+                            .into(),
+                            kind: Some(NotificationType::Info),
+                        });
+                    }
+                    Err(err) => {
+                        context.replies.push_back(UIEvent::Notification {
+                            title: Some("Could not merge contacts.".into()),
+                            body: err.to_string().into(),
+                            kind: Some(NotificationType::Error(err.kind)),
+                            source: Some(err),
+                        });
+                    }
+                }
+                self.initialized = false;
+                self.set_dirty(true);
+                return true;
+            }
             _ => {}
         }
 
@@ -643,6 +697,94 @@ impl Component for ContactList {
 
                     return true;
                 }
+                UIEvent::Input(ref key)
+                    if shortcut!(
+                        key == shortcuts[Shortcuts::CONTACT_LIST]["show_correspondence"]
+                    ) =>
+                {
+                    if self.length == 0 {
+                        return true;
+                    }
+                    let account = &mut context.accounts[self.account_pos];
+                    let account_hash = account.hash();
+                    let card = &account.contacts[&self.id_positions[self.cursor_pos]];
+                    let result = melib::Address::try_from(card.email())
+                        .and_then(|address| account.search_correspondence(&address));
+                    match result {
+                        Ok(mailbox_hash) => {
+                            let listing = Listing::new_tab(context, account_hash, mailbox_hash);
+                            context
+                                .replies
+                                .push_back(UIEvent::Action(Tab(TabAction::New(Some(Box::new(
+                                    listing,
+                                ))))));
+                        }
+                        Err(err) => {
+                            context.replies.push_back(UIEvent::Notification {
+                                title: Some("Could not perform search".into()),
+                                source: None,
+                                body: err.to_string().into(),
+                                kind: Some(NotificationType::Error(err.kind)),
+                            });
+                        }
+                    }
+                    return true;
+                }
+                UIEvent::Input(ref key)
+                    if shortcut!(key == shortcuts[Shortcuts::CONTACT_LIST]["merge_contact"]) =>
+                {
+                    if self.length == 0 {
+                        return true;
+                    }
+                    let account = &context.accounts[self.account_pos];
+                    let account_name = account.name().to_string();
+                    let contacts = &account.contacts;
+                    let into = self.id_positions[self.cursor_pos];
+                    let duplicates = contacts
+                        .duplicates()
+                        .into_iter()
+                        .find(|group| group.contains(&into))
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|id| *id != into && !contacts[id].external_resource())
+                        .map(|id| {
+                            let card = &contacts[&id];
+                            (id, format!("{} <{}>", card.name(), card.email()))
+                        })
+                        .collect::<Vec<(CardId, String)>>();
+                    if duplicates.is_empty() {
+                        context.replies.push_back(UIEvent::Notification {
+                            title: None,
+                            source: None,
+                            body: format!("No duplicates of {} found.", contacts[&into]).into(),
+                            kind: Some(NotificationType::Info),
+                        });
+                        return true;
+                    }
+                    let title = format!("merge into {}", contacts[&into]);
+                    context.replies.push_back(UIEvent::GlobalUIDialog {
+                        value: Box::new(UIDialog::new(
+                            &title,
+                            duplicates,
+                            false,
+                            Some(Box::new(move |_id: ComponentId, results: &[CardId]| {
+                                if results.is_empty() {
+                                    return None;
+                                }
+                                Some(UIEvent::Action(Action::AccountAction(
+                                    account_name,
+                                    AccountAction::MergeContacts {
+                                        into,
+                                        from: results.to_vec(),
+                                    },
+                                )))
+                            })),
+                            context,
+                        )),
+                        parent: Some(self.id()),
+                    });
+                    return true;
+                }
                 UIEvent::Input(ref key)
                     if shortcut!(key == shortcuts[Shortcuts::CONTACT_LIST]["delete_contact"]) =>
                 {
@@ -928,6 +1070,10 @@ impl Component for ContactList {
         self.id
     }
 
+    fn view_name(&self) -> Option<&'static str> {
+        Some("contacts")
+    }
+
     fn can_quit_cleanly(&mut self, context: &Context) -> bool {
         if let ViewMode::View(ref mut mgr) = self.mode {
             return mgr.can_quit_cleanly(context);
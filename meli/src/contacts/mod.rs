@@ -19,7 +19,9 @@
  * along with meli. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use melib::{AccountHash, Card};
+use std::fmt::Write;
+
+use melib::{AccountHash, Card, Contacts};
 
 use crate::{
     types::{sanitize_filename, File, NotificationType, UIEvent},
@@ -29,6 +31,35 @@ use crate::{
 pub mod editor;
 pub mod list;
 
+/// Describe the groups of likely duplicate contacts of an address book.
+pub fn duplicates_report(account_name: &str, contacts: &Contacts) -> String {
+    let groups = contacts.duplicates();
+    if groups.is_empty() {
+        return format!("No duplicate contacts found in {account_name}.\n");
+    }
+    let mut ret = format!(
+        "{} groups of likely duplicate contacts found in {account_name}:\n",
+        groups.len(),
+    );
+    for group in groups {
+        ret.push('\n');
+        for card in group.iter().map(|id| &contacts[id]) {
+            let _ = writeln!(
+                ret,
+                "  {} <{}>{}",
+                card.name(),
+                card.email(),
+                if card.external_resource() {
+                    " (read-only)"
+                } else {
+                    ""
+                }
+            );
+        }
+    }
+    ret
+}
+
 pub fn export_to_vcard(card: &Card, account_hash: AccountHash, context: &mut Context) {
     let mut output_dir = context.accounts[&account_hash]
         .settings
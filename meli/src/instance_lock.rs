@@ -0,0 +1,206 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Lock that prevents concurrent instances.
+//!
+//! Two instances using the same configuration would race on the sqlite3
+//! caches, the drafts and the saved listing state of its accounts. Each
+//! instance takes an exclusive `flock(2)` lock on a lock file in the data
+//! directory that is named after its configuration file, and holds it while
+//! it runs, so that instances with different configuration files do not
+//! block each other. The kernel releases the locks when the process exits,
+//! so there are no stale locks to clean up. The lock files contain the
+//! process ID of their holder, which is only used in error messages.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Seek, Write},
+    os::unix::io::AsRawFd,
+    path::{Path, PathBuf},
+};
+
+use melib::src_err_arc_wrap;
+
+use crate::*;
+
+const FILE_NAME: &str = "meli.lock";
+
+/// The locks of this instance, released when dropped.
+#[derive(Debug)]
+pub struct InstanceLock {
+    files: Vec<(PathBuf, File)>,
+}
+
+impl InstanceLock {
+    /// Lock the configuration file in use.
+    ///
+    /// If `take_over` is set and another instance holds the lock, start
+    /// anyway without it.
+    pub fn acquire(take_over: bool) -> Result<Self> {
+        let config_path = conf::get_config_file()?;
+        // Different paths to the same file, such as symbolic links, take the
+        // same lock.
+        let config_path = config_path.canonicalize().unwrap_or(config_path);
+        let data_dir = xdg::BaseDirectories::with_prefix("meli")?;
+        let path = data_dir.place_data_file(lock_file_name(&config_path))?;
+        Self::acquire_at(vec![path], take_over)
+    }
+
+    pub fn acquire_at(paths: Vec<PathBuf>, take_over: bool) -> Result<Self> {
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            if files.iter().any(|(p, _)| *p == path) {
+                continue;
+            }
+            match lock_file(&path)? {
+                Some(file) => files.push((path, file)),
+                None if take_over => {
+                    log::warn!(
+                        "Starting without lock {}, which is held by process {:?}.",
+                        path.display(),
+                        lock_owner(&path)
+                    );
+                }
+                None => {
+                    let owner = lock_owner(&path)
+                        .map(|pid| format!(" (process {pid})"))
+                        .unwrap_or_default();
+                    return Err(Error::new(format!(
+                        "Another meli instance{owner} is already running with lock {}. Running \
+                         two instances at once can corrupt caches and drafts. Start with \
+                         `--take-over-lock` to run anyway.",
+                        path.display()
+                    ))
+                    .set_kind(ErrorKind::External));
+                }
+            }
+        }
+        Ok(Self { files })
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // The file is not removed: another instance might have opened it and
+        // be waiting for the lock. Closing the file releases the lock.
+        for (_, file) in self.files.iter_mut() {
+            _ = file.set_len(0);
+        }
+    }
+}
+
+/// The name of the lock file of configuration file `config_path`: its path
+/// with `%` and `/` percent-encoded, so that it stays readable in error
+/// messages.
+fn lock_file_name(config_path: &Path) -> String {
+    let mut ret = String::new();
+    for c in config_path.display().to_string().chars() {
+        match c {
+            '%' => ret.push_str("%25"),
+            '/' => ret.push_str("%2F"),
+            c => ret.push(c),
+        }
+    }
+    ret.push('.');
+    ret.push_str(FILE_NAME);
+    ret
+}
+
+/// Open and lock `path`. Returns `None` if another process holds the lock.
+fn lock_file(path: &Path) -> Result<Option<File>> {
+    let open_err = |err: std::io::Error| {
+        Error::new(format!("Could not open lock file {}", path.display()))
+            .set_source(Some(src_err_arc_wrap! {err}))
+    };
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(open_err)?;
+    // SAFETY: the file descriptor is valid for the lifetime of `file`.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+        let err = std::io::Error::last_os_error();
+        if err.raw_os_error() == Some(libc::EWOULDBLOCK) {
+            return Ok(None);
+        }
+        return Err(open_err(err));
+    }
+    file.set_len(0)
+        .and_then(|()| file.rewind())
+        .and_then(|()| file.write_all(format!("{}\n", std::process::id()).as_bytes()))
+        .map_err(open_err)?;
+    Ok(Some(file))
+}
+
+/// The process ID written in lock file `path`.
+fn lock_owner(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance_lock() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let config = tmp_dir.path().join("config.lock");
+        let data = tmp_dir.path().join(FILE_NAME);
+        let this = std::process::id();
+
+        let lock = InstanceLock::acquire_at(vec![config.clone(), data.clone()], false).unwrap();
+        assert_eq!(lock_owner(&config), Some(this));
+        assert_eq!(lock_owner(&data), Some(this));
+
+        // flock(2) locks belong to the open file description, so a second
+        // open of the same file conflicts even within one process.
+        let err = InstanceLock::acquire_at(vec![data.clone()], false).unwrap_err();
+        assert!(err.summary.contains("already running"), "{err}");
+        let err = InstanceLock::acquire_at(vec![config.clone(), data.clone()], false).unwrap_err();
+        assert!(err.summary.contains(&config.display().to_string()), "{err}");
+        let unlocked = InstanceLock::acquire_at(vec![data.clone()], true).unwrap();
+        assert!(unlocked.files.is_empty());
+        drop(unlocked);
+
+        // Left-over lock files of instances that exited are reused.
+        drop(lock);
+        assert!(data.exists());
+        assert_eq!(lock_owner(&data), None);
+        let lock = InstanceLock::acquire_at(vec![data.clone(), data.clone()], false).unwrap();
+        assert_eq!(lock.files.len(), 1);
+        assert_eq!(lock_owner(&data), Some(this));
+    }
+
+    #[test]
+    fn test_instance_lock_file_name() {
+        assert_eq!(
+            lock_file_name(Path::new("/home/user/.config/meli/config.toml")),
+            "%2Fhome%2Fuser%2F.config%2Fmeli%2Fconfig.toml.meli.lock"
+        );
+        assert_ne!(
+            lock_file_name(Path::new("/a/b%2Fc.toml")),
+            lock_file_name(Path::new("/a/b/c.toml"))
+        );
+    }
+}
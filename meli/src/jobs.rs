@@ -27,7 +27,7 @@ use std::{
     iter,
     panic::catch_unwind,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc, Mutex,
     },
     thread,
@@ -43,7 +43,10 @@ pub use futures::channel::oneshot;
 use indexmap::IndexMap;
 use melib::{log, smol, utils::datetime, uuid::Uuid, UnixTimestamp};
 
-use crate::types::{StatusEvent, ThreadEvent, UIEvent};
+use crate::{
+    conf::JobsSettings,
+    types::{StatusEvent, ThreadEvent, UIEvent},
+};
 
 #[derive(Clone, Copy, Eq, Hash, PartialEq)]
 pub enum IsAsync {
@@ -105,6 +108,16 @@ fn find_task(
     })
 }
 
+/// Pin the calling thread to `cpu`.
+#[cfg(target_os = "linux")]
+fn set_cpu_affinity(cpu: usize) -> nix::Result<()> {
+    use nix::{sched, unistd::Pid};
+
+    let mut cpu_set = sched::CpuSet::new();
+    cpu_set.set(cpu)?;
+    sched::sched_setaffinity(Pid::from_raw(0), &cpu_set)
+}
+
 macro_rules! uuid_hash_type {
     ($n:ident) => {
         #[derive(PartialEq, Hash, Eq, Copy, Clone, Ord, PartialOrd, Serialize, Deserialize)]
@@ -192,6 +205,8 @@ pub struct JobExecutor {
     workers: Vec<Stealer<MeliTask>>,
     sender: Sender<ThreadEvent>,
     parkers: Vec<Unparker>,
+    /// Number of executor threads that are running a task.
+    busy: Arc<AtomicUsize>,
     timers: Arc<Mutex<IndexMap<TimerId, TimerPrivate>>>,
     pub jobs: Arc<Mutex<IndexMap<JobId, JobMetadata>>>,
 }
@@ -240,20 +255,33 @@ impl Drop for Timer {
 impl JobExecutor {
     /// A queue that holds scheduled tasks.
     pub fn new(sender: Sender<ThreadEvent>) -> Self {
+        Self::with_settings(sender, &JobsSettings::default())
+    }
+
+    /// A queue that holds scheduled tasks, run by as many threads as
+    /// `settings` ask for.
+    pub fn with_settings(sender: Sender<ThreadEvent>, settings: &JobsSettings) -> Self {
         // Create a queue.
         let mut ret = Self {
             global_queue: Arc::new(Injector::new()),
             workers: vec![],
             parkers: vec![],
             sender,
+            busy: Arc::new(AtomicUsize::new(0)),
             timers: Arc::new(Mutex::new(IndexMap::default())),
             jobs: Arc::new(Mutex::new(IndexMap::default())),
         };
-        let mut workers = vec![];
-        for _ in 0..std::thread::available_parallelism()
+        let threads = settings
+            .executor_threads
+            .or_else(|| std::thread::available_parallelism().ok())
             .map(Into::into)
-            .unwrap_or(1)
-        {
+            .unwrap_or(1);
+        #[cfg(not(target_os = "linux"))]
+        if !settings.cpu_affinity.is_empty() {
+            log::warn!("jobs.cpu_affinity is only supported on Linux and will be ignored.");
+        }
+        let mut workers = vec![];
+        for _ in 0..threads {
             let new_worker = Worker::new_fifo();
             ret.workers.push(new_worker.stealer());
             let p = Parker::new();
@@ -275,24 +303,38 @@ impl JobExecutor {
         for (i, (local, parker)) in workers.into_iter().enumerate() {
             let global = ret.global_queue.clone();
             let stealers = ret.workers.clone();
+            let busy = ret.busy.clone();
+            #[cfg(target_os = "linux")]
+            let cpu = (!settings.cpu_affinity.is_empty())
+                .then(|| settings.cpu_affinity[i % settings.cpu_affinity.len()]);
             thread::Builder::new()
                 .name(format!("meli-executor-{i}"))
-                .spawn(move || loop {
-                    parker.park_timeout(Duration::from_millis(100));
-                    let task = find_task(&local, &global, stealers.as_slice());
-                    if let Some(meli_task) = task {
-                        let MeliTask {
-                            task,
-                            id,
-                            timer,
-                            desc,
-                        } = meli_task;
-                        if !timer {
-                            log::trace!("Worker {} got task {:?} {:?}", i, desc, id);
+                .spawn(move || {
+                    #[cfg(target_os = "linux")]
+                    if let Some(cpu) = cpu {
+                        if let Err(err) = set_cpu_affinity(cpu) {
+                            log::warn!("Could not pin worker {} to CPU {}: {}", i, cpu, err);
                         }
-                        let _ = catch_unwind(|| task.run());
-                        if !timer {
-                            log::trace!("Worker {} returned after {:?} {:?}", i, desc, id);
+                    }
+                    loop {
+                        parker.park_timeout(Duration::from_millis(100));
+                        let task = find_task(&local, &global, stealers.as_slice());
+                        if let Some(meli_task) = task {
+                            let MeliTask {
+                                task,
+                                id,
+                                timer,
+                                desc,
+                            } = meli_task;
+                            if !timer {
+                                log::trace!("Worker {} got task {:?} {:?}", i, desc, id);
+                            }
+                            busy.fetch_add(1, Ordering::Relaxed);
+                            let _ = catch_unwind(|| task.run());
+                            busy.fetch_sub(1, Ordering::Relaxed);
+                            if !timer {
+                                log::trace!("Worker {} returned after {:?} {:?}", i, desc, id);
+                            }
                         }
                     }
                 })
@@ -301,6 +343,12 @@ impl JobExecutor {
         ret
     }
 
+    /// The number of executor threads that are running a task, and the
+    /// total number of executor threads.
+    pub fn utilization(&self) -> (usize, usize) {
+        (self.busy.load(Ordering::Relaxed), self.workers.len())
+    }
+
     /// Spawns a future with a generic return value `R`
     #[inline(always)]
     pub fn spawn<F, R>(
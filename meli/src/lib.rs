@@ -110,10 +110,14 @@ pub use melib::{
 };
 
 pub mod args;
+pub mod instance_lock;
 #[cfg(feature = "cli-docs")]
 pub mod manpages;
+pub mod plugins;
 pub mod signal_handlers;
+pub mod startup_profile;
 pub mod subcommands;
+pub mod usage_stats;
 
 #[macro_use]
 pub mod types;
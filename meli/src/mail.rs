@@ -49,3 +49,14 @@ pub mod pgp;
 
 pub mod status;
 pub use self::status::*;
+
+pub mod dashboard;
+pub use self::dashboard::*;
+
+pub mod export;
+
+pub mod attachments;
+pub use self::attachments::*;
+
+pub mod triage;
+pub use self::triage::*;
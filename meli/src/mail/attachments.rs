@@ -0,0 +1,798 @@
+/*
+ * meli
+ *
+ * Copyright 2024 Manos Pitsidianakis
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Attachments view of a mailbox.
+//!
+//! Lists every attachment of the e-mail of a mailbox in a sortable table, as
+//! found by the account's attachment index (see
+//! [`Account::index_attachments`](crate::accounts::Account::index_attachments)).
+//! Attachments can be opened with the default application of their type or
+//! saved in the current directory without opening their e-mail.
+
+use std::process::{Command, Stdio};
+
+use melib::{
+    utils::{datetime, xdg::query_default_app},
+    BytesDisplay, MimeStructure, SortOrder, UnixTimestamp,
+};
+
+use super::*;
+use crate::{accounts::AttachmentInfo, jobs::JoinHandle};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+enum Column {
+    _0 = 0,
+    _1,
+    _2,
+    _3,
+    _4,
+}
+
+const fn _assert_len() {
+    if AttachmentBrowser::HEADERS.len() != Column::_4 as usize + 1 {
+        panic!("AttachmentBrowser::HEADERS length changed, please update Column enum accordingly.");
+    }
+}
+
+const _: () = _assert_len();
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PendingAction {
+    Open,
+    Save,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    env_hash: EnvelopeHash,
+    info: AttachmentInfo,
+    from: String,
+    date: UnixTimestamp,
+}
+
+impl Entry {
+    fn filename(&self) -> &str {
+        self.info.filename.as_deref().unwrap_or("-")
+    }
+}
+
+#[derive(Debug)]
+pub struct AttachmentBrowser {
+    account_hash: AccountHash,
+    mailbox_hash: MailboxHash,
+    mailbox_name: String,
+    cursor_pos: usize,
+    new_cursor_pos: usize,
+    length: usize,
+    data_columns: DataColumns<5>,
+    min_width: [usize; 5],
+    sort_col: Column,
+    sort_order: SortOrder,
+    entries: Vec<Entry>,
+    /// Fetching of the e-mail of an attachment that is to be opened or
+    /// saved.
+    pending: Option<(JoinHandle<Result<Vec<u8>>>, Entry, PendingAction)>,
+
+    initialized: bool,
+    theme_default: ThemeAttribute,
+    highlight_theme: ThemeAttribute,
+
+    dirty: bool,
+
+    movement: Option<PageMovement>,
+    id: ComponentId,
+}
+
+impl std::fmt::Display for AttachmentBrowser {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "attachments of {}", self.mailbox_name)
+    }
+}
+
+impl AttachmentBrowser {
+    const HEADERS: [&'static str; 5] = ["filename", "type", "size", "from", "date"];
+
+    pub fn new(
+        context: &mut Context,
+        account_hash: AccountHash,
+        mailbox_hash: MailboxHash,
+    ) -> Result<Self> {
+        let theme_default = crate::conf::value(context, "theme_default");
+        let highlight_theme = if context.settings.terminal.use_color() {
+            crate::conf::value(context, "highlight")
+        } else {
+            ThemeAttribute {
+                attrs: Attr::REVERSE,
+                ..ThemeAttribute::default()
+            }
+        };
+        let account = &mut context.accounts[&account_hash];
+        let Some(mailbox_name) = account
+            .mailbox_entries
+            .get(&mailbox_hash)
+            .map(|entry| entry.name().to_string())
+        else {
+            return Err(Error::new("No mailbox is open.").set_kind(ErrorKind::NotFound));
+        };
+        account.index_attachments(mailbox_hash)?;
+        let mut data_columns = DataColumns::new(theme_default);
+        data_columns.theme_config.set_single_theme(theme_default);
+        Ok(Self {
+            account_hash,
+            mailbox_hash,
+            mailbox_name,
+            cursor_pos: 0,
+            new_cursor_pos: 0,
+            length: 0,
+            data_columns,
+            min_width: [0; 5],
+            sort_col: Column::_4,
+            sort_order: SortOrder::Desc,
+            entries: vec![],
+            pending: None,
+            theme_default,
+            highlight_theme,
+            initialized: false,
+            dirty: true,
+            movement: None,
+            id: ComponentId::default(),
+        })
+    }
+
+    fn initialize(&mut self, context: &Context) {
+        self.set_dirty(true);
+        self.initialized = true;
+
+        let account = &context.accounts[&self.account_hash];
+        self.entries.clear();
+        if account.mailbox_entries.contains_key(&self.mailbox_hash) {
+            for &env_hash in account.collection.get_mailbox(self.mailbox_hash).iter() {
+                let Some(attachments) = account.attachment_index.get(env_hash) else {
+                    continue;
+                };
+                let envelope = account.collection.get_env(env_hash);
+                for info in attachments.iter() {
+                    self.entries.push(Entry {
+                        env_hash,
+                        info: info.clone(),
+                        from: envelope.field_from_to_string(),
+                        date: envelope.date(),
+                    });
+                }
+            }
+        }
+
+        self.length = self.entries.len();
+        self.entries
+            .sort_by(|a, b| match (self.sort_col, self.sort_order) {
+                (Column::_0, SortOrder::Asc) => a.filename().cmp(b.filename()),
+                (Column::_0, SortOrder::Desc) => b.filename().cmp(a.filename()),
+                (Column::_1, SortOrder::Asc) => a.info.mime_type.cmp(&b.info.mime_type),
+                (Column::_1, SortOrder::Desc) => b.info.mime_type.cmp(&a.info.mime_type),
+                (Column::_2, SortOrder::Asc) => a.info.size.cmp(&b.info.size),
+                (Column::_2, SortOrder::Desc) => b.info.size.cmp(&a.info.size),
+                (Column::_3, SortOrder::Asc) => a.from.cmp(&b.from),
+                (Column::_3, SortOrder::Desc) => b.from.cmp(&a.from),
+                (Column::_4, SortOrder::Asc) => a.date.cmp(&b.date),
+                (Column::_4, SortOrder::Desc) => b.date.cmp(&a.date),
+            });
+        if self.new_cursor_pos >= self.length {
+            self.new_cursor_pos = self.length.saturating_sub(1);
+        }
+        self.cursor_pos = self.new_cursor_pos;
+
+        macro_rules! hdr {
+            ($idx:literal) => {{
+                Self::HEADERS[$idx].len() + if self.sort_col as u8 == $idx { 1 } else { 0 }
+            }};
+        }
+        self.min_width = [hdr!(0), hdr!(1), hdr!(2), hdr!(3), hdr!(4)];
+
+        let rows = self
+            .entries
+            .iter()
+            .map(|e| {
+                [
+                    e.filename().to_string(),
+                    e.info.mime_type.clone(),
+                    BytesDisplay(e.info.size).to_string(),
+                    e.from.clone(),
+                    datetime::timestamp_to_string(
+                        e.date,
+                        Some(datetime::formats::RFC3339_DATETIME_AND_SPACE),
+                        false,
+                    ),
+                ]
+            })
+            .collect::<Vec<[String; 5]>>();
+        for row in &rows {
+            for (w, cell) in self.min_width.iter_mut().zip(row) {
+                *w = (*w).max(cell.grapheme_width());
+            }
+        }
+
+        for (i, column) in self.data_columns.columns.iter_mut().enumerate() {
+            _ = column.resize_with_context(self.min_width[i], self.length, context);
+            column.grid_mut().clear(None);
+        }
+
+        for (idx, row) in rows.iter().enumerate() {
+            for (column, cell) in self.data_columns.columns.iter_mut().zip(row) {
+                let area = column.area().nth_row(idx);
+                column.grid_mut().write_string(
+                    cell,
+                    self.theme_default.fg,
+                    self.theme_default.bg,
+                    self.theme_default.attrs,
+                    area,
+                    None,
+                    None,
+                );
+            }
+        }
+
+        if self.length == 0 {
+            let message = if account.attachment_index.is_pending(self.mailbox_hash) {
+                "Indexing attachments…"
+            } else {
+                "No attachments."
+            };
+            if self.data_columns.columns[0].resize_with_context(
+                message.grapheme_width(),
+                self.length,
+                context,
+            ) {
+                let area = self.data_columns.columns[0].area();
+                self.data_columns.columns[0].grid_mut().write_string(
+                    message,
+                    self.theme_default.fg,
+                    self.theme_default.bg,
+                    self.theme_default.attrs,
+                    area,
+                    None,
+                    None,
+                );
+            }
+        }
+    }
+
+    fn draw_list(&mut self, grid: &mut CellBuffer, area: Area, context: &mut Context) {
+        let rows = area.height();
+        if rows == 0 {
+            return;
+        }
+        if self.length == 0 {
+            grid.clear_area(area, self.theme_default);
+
+            grid.copy_area(
+                self.data_columns.columns[0].grid(),
+                area,
+                self.data_columns.columns[0].area(),
+            );
+            context.dirty_areas.push_back(area);
+            return;
+        }
+
+        if let Some(mvm) = self.movement.take() {
+            match mvm {
+                PageMovement::Up(amount) => {
+                    self.new_cursor_pos = self.new_cursor_pos.saturating_sub(amount);
+                }
+                PageMovement::PageUp(multiplier) => {
+                    self.new_cursor_pos = self.new_cursor_pos.saturating_sub(rows * multiplier);
+                }
+                PageMovement::Down(amount) => {
+                    if self.new_cursor_pos + amount < self.length {
+                        self.new_cursor_pos += amount;
+                    } else {
+                        self.new_cursor_pos = self.length - 1;
+                    }
+                }
+                PageMovement::PageDown(multiplier) => {
+                    if self.new_cursor_pos + rows * multiplier < self.length {
+                        self.new_cursor_pos += rows * multiplier;
+                    } else {
+                        self.new_cursor_pos = self.length - 1;
+                    }
+                }
+                PageMovement::Right(amount) => {
+                    self.data_columns.x_offset += amount;
+                    self.data_columns.x_offset = self.data_columns.x_offset.min(
+                        self.data_columns
+                            .widths
+                            .iter()
+                            .map(|w| w + 2)
+                            .sum::<usize>()
+                            .saturating_sub(2),
+                    );
+                }
+                PageMovement::Left(amount) => {
+                    self.data_columns.x_offset = self.data_columns.x_offset.saturating_sub(amount);
+                }
+                PageMovement::Home => {
+                    self.new_cursor_pos = 0;
+                }
+                PageMovement::End => {
+                    self.new_cursor_pos = self.length - 1;
+                }
+            }
+        }
+
+        let prev_page_no = (self.cursor_pos).wrapping_div(rows);
+        let page_no = (self.new_cursor_pos).wrapping_div(rows);
+
+        let top_idx = page_no * rows;
+
+        if self.length >= rows {
+            context
+                .replies
+                .push_back(UIEvent::StatusEvent(StatusEvent::ScrollUpdate(
+                    ScrollUpdate::Update {
+                        id: self.id,
+                        context: ScrollContext {
+                            shown_lines: top_idx + rows,
+                            total_lines: self.length,
+                            has_more_lines: false,
+                        },
+                    },
+                )));
+        } else {
+            context
+                .replies
+                .push_back(UIEvent::StatusEvent(StatusEvent::ScrollUpdate(
+                    ScrollUpdate::End(self.id),
+                )));
+        }
+
+        // If cursor position has changed, remove the highlight from the previous
+        // position and apply it in the new one.
+        if self.cursor_pos != self.new_cursor_pos && prev_page_no == page_no {
+            let old_cursor_pos = self.cursor_pos;
+            self.cursor_pos = self.new_cursor_pos;
+            for &(idx, highlight) in &[(old_cursor_pos, false), (self.new_cursor_pos, true)] {
+                if idx >= self.length {
+                    continue; //bounds check
+                }
+                let new_area = area.nth_row(idx % rows);
+                self.data_columns
+                    .draw(grid, idx, self.cursor_pos, grid.bounds_iter(new_area));
+                let row_attr = if highlight {
+                    self.highlight_theme
+                } else {
+                    self.theme_default
+                };
+                grid.change_theme(new_area, row_attr);
+                context.dirty_areas.push_back(new_area);
+            }
+            return;
+        } else if self.cursor_pos != self.new_cursor_pos {
+            self.cursor_pos = self.new_cursor_pos;
+        }
+        // Page_no has changed, so draw new page
+        _ = self
+            .data_columns
+            .recalc_widths((area.width(), area.height()), top_idx);
+        grid.clear_area(area, self.theme_default);
+        // copy table columns
+        self.data_columns
+            .draw(grid, top_idx, self.cursor_pos, grid.bounds_iter(area));
+
+        // highlight cursor
+        grid.change_theme(area.nth_row(self.cursor_pos % rows), self.highlight_theme);
+
+        // clear gap if available height is more than count of entries
+        if top_idx + rows > self.length {
+            grid.change_theme(area.skip_rows(self.length - top_idx), self.theme_default);
+        }
+        context.dirty_areas.push_back(area);
+    }
+
+    /// Fetch the e-mail of the attachment under the cursor and perform
+    /// `action` on the attachment once it is fetched.
+    fn fetch(&mut self, action: PendingAction, context: &mut Context) {
+        let Some(entry) = self.entries.get(self.cursor_pos).cloned() else {
+            return;
+        };
+        let account = &context.accounts[&self.account_hash];
+        match account.envelope_bytes_by_hash(entry.env_hash) {
+            Ok(fut) => {
+                let handle = account.main_loop_handler.job_executor.spawn(
+                    "fetch-envelope".into(),
+                    fut,
+                    account.is_async(),
+                );
+                context
+                    .replies
+                    .push_back(UIEvent::StatusEvent(StatusEvent::NewJob(handle.job_id)));
+                if let Some((previous, _, _)) = self.pending.replace((handle, entry, action)) {
+                    if let Some(canceled) = previous.cancel() {
+                        context.replies.push_back(UIEvent::StatusEvent(canceled));
+                    }
+                }
+            }
+            Err(err) => {
+                context.replies.push_back(UIEvent::Notification {
+                    title: Some("Could not fetch e-mail".into()),
+                    source: None,
+                    body: err.to_string().into(),
+                    kind: Some(NotificationType::Error(err.kind)),
+                });
+            }
+        }
+    }
+
+    fn perform(
+        &self,
+        bytes: &[u8],
+        entry: &Entry,
+        action: PendingAction,
+        context: &mut Context,
+    ) -> Result<()> {
+        let structure = MimeStructure::new(bytes);
+        let Some(part) = structure.get(&entry.info.path) else {
+            return Err(Error::new(format!(
+                "Attachment `{}` was not found in its e-mail.",
+                entry.filename()
+            ))
+            .set_kind(ErrorKind::NotFound));
+        };
+        let attachment = structure.attachment(part);
+        let bytes = attachment.decode(Default::default());
+        match action {
+            PendingAction::Save => {
+                let path =
+                    context
+                        .current_dir()
+                        .join(entry.info.filename.clone().unwrap_or_else(|| {
+                            format!("meli_attachment_{}", Uuid::new_v4().as_simple())
+                        }));
+                save_attachment(&path, &bytes)?;
+                context.replies.push_back(UIEvent::Notification {
+                    title: None,
+                    source: None,
+                    body: format!("Saved at {}", path.display()).into(),
+                    kind: Some(NotificationType::Info),
+                });
+            }
+            PendingAction::Open => {
+                let mime_type = attachment.mime_type();
+                let Ok(command) = query_default_app(&mime_type) else {
+                    return Err(Error::new(format!(
+                        "Couldn't find a default application for type {mime_type}"
+                    )));
+                };
+                let p = File::create_temp_file(
+                    &bytes,
+                    entry.info.filename.as_deref(),
+                    None,
+                    None,
+                    true,
+                )?;
+                let exec_cmd =
+                    desktop_exec_to_command(&command, p.path().display().to_string(), false);
+                let child = Command::new("sh")
+                    .args(["-c", &exec_cmd])
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .spawn_checked(&context.command_policy(Some(self.account_hash)))?;
+                context.temp_files.push(p);
+                context
+                    .children
+                    .entry(command.clone().into())
+                    .or_default()
+                    .push(ForkedProcess::Generic {
+                        id: command.into(),
+                        command: Some(exec_cmd.into()),
+                        child,
+                    });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Component for AttachmentBrowser {
+    fn draw(&mut self, grid: &mut CellBuffer, area: Area, context: &mut Context) {
+        if !self.is_dirty() {
+            return;
+        }
+        if !self.initialized {
+            self.initialize(context);
+        }
+        {
+            let area = area.nth_row(0);
+            // Draw column headers.
+            grid.clear_area(area, self.theme_default);
+            let mut x_offset = 0;
+            for (i, (h, w)) in Self::HEADERS.iter().zip(self.min_width).enumerate() {
+                grid.write_string(
+                    h,
+                    self.theme_default.fg,
+                    self.theme_default.bg,
+                    self.theme_default.attrs | Attr::BOLD,
+                    area.skip_cols(x_offset),
+                    None,
+                    None,
+                );
+                if self.sort_col as usize == i {
+                    use SortOrder::*;
+                    let arrow = match (grid.ascii_drawing, self.sort_order) {
+                        (true, Asc) => DataColumns::<5>::ARROW_UP_ASCII,
+                        (true, Desc) => DataColumns::<5>::ARROW_DOWN_ASCII,
+                        (false, Asc) => DataColumns::<5>::ARROW_UP,
+                        (false, Desc) => DataColumns::<5>::ARROW_DOWN,
+                    };
+                    grid.write_string(
+                        arrow,
+                        self.theme_default.fg,
+                        self.theme_default.bg,
+                        self.theme_default.attrs,
+                        area.skip_cols(x_offset + h.len()),
+                        None,
+                        None,
+                    );
+                }
+                x_offset += w + 2;
+            }
+            context.dirty_areas.push_back(area);
+        }
+
+        self.draw_list(grid, area.skip_rows(1), context);
+        self.dirty = false;
+    }
+
+    fn process_event(&mut self, event: &mut UIEvent, context: &mut Context) -> bool {
+        if let UIEvent::ConfigReload { old_settings: _ } = event {
+            self.theme_default = crate::conf::value(context, "theme_default");
+            self.initialized = false;
+            self.set_dirty(true);
+        }
+
+        let shortcuts = self.shortcuts(context);
+        match event {
+            UIEvent::StatusEvent(StatusEvent::JobFinished(ref job_id))
+                if self
+                    .pending
+                    .as_ref()
+                    .map(|(handle, _, _)| handle.job_id == *job_id)
+                    .unwrap_or(false) =>
+            {
+                let Some((mut handle, entry, action)) = self.pending.take() else {
+                    return false;
+                };
+                let result = match handle.chan.try_recv() {
+                    Err(_) | Ok(None) => return false,
+                    Ok(Some(Err(err))) => Err(err),
+                    Ok(Some(Ok(bytes))) => self.perform(&bytes, &entry, action, context),
+                };
+                if let Err(err) = result {
+                    context.replies.push_back(UIEvent::Notification {
+                        title: Some(
+                            match action {
+                                PendingAction::Open => "Could not open attachment",
+                                PendingAction::Save => "Could not save attachment",
+                            }
+                            .into(),
+                        ),
+                        source: None,
+                        body: err.to_string().into(),
+                        kind: Some(NotificationType::Error(err.kind)),
+                    });
+                }
+                return false;
+            }
+            UIEvent::MailboxUpdate((account_hash, mailbox_hash))
+                if (*account_hash, *mailbox_hash) == (self.account_hash, self.mailbox_hash) =>
+            {
+                self.initialized = false;
+                self.set_dirty(true);
+                return false;
+            }
+            UIEvent::Action(Action::SortColumn(column, order)) => {
+                let column = match *column {
+                    0 => Column::_0,
+                    1 => Column::_1,
+                    2 => Column::_2,
+                    3 => Column::_3,
+                    4 => Column::_4,
+                    other => {
+                        context.replies.push_back(UIEvent::Notification {
+                            title: Some(format!("Invalid column index `{other}`").into()),
+                            source: None,
+                            body: format!("There are {} columns.", Self::HEADERS.len()).into(),
+                            kind: None,
+                        });
+
+                        return true;
+                    }
+                };
+                if (self.sort_col, self.sort_order) != (column, *order) {
+                    self.sort_col = column;
+                    self.sort_order = *order;
+                    self.initialized = false;
+                    self.set_dirty(true);
+                }
+                return true;
+            }
+            UIEvent::Input(Key::Char(ref c)) if c.is_ascii_digit() => {
+                let n = *c as u8 - b'0'; // safe cast because of is_ascii_digit() check;
+                let column = match n {
+                    1 => Column::_0,
+                    2 => Column::_1,
+                    3 => Column::_2,
+                    4 => Column::_3,
+                    5 => Column::_4,
+                    _ => {
+                        return false;
+                    }
+                };
+                if self.sort_col == column {
+                    self.sort_order = !self.sort_order;
+                } else {
+                    self.sort_col = column;
+                    self.sort_order = SortOrder::default();
+                }
+                self.initialized = false;
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["open_entry"]) =>
+            {
+                self.fetch(PendingAction::Open, context);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["save attachment"]) =>
+            {
+                self.fetch(PendingAction::Save, context);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["scroll_up"]) =>
+            {
+                self.movement = Some(PageMovement::Up(1));
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["scroll_down"])
+                    && self.cursor_pos < self.length.saturating_sub(1) =>
+            {
+                self.movement = Some(PageMovement::Down(1));
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["prev_page"]) =>
+            {
+                self.movement = Some(PageMovement::PageUp(1));
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["next_page"]) =>
+            {
+                self.movement = Some(PageMovement::PageDown(1));
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["home_page"]) =>
+            {
+                self.movement = Some(PageMovement::Home);
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["end_page"]) =>
+            {
+                self.movement = Some(PageMovement::End);
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["scroll_right"]) =>
+            {
+                self.movement = Some(PageMovement::Right(1));
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["scroll_left"]) =>
+            {
+                self.movement = Some(PageMovement::Left(1));
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Resize => {
+                self.set_dirty(true);
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, value: bool) {
+        self.dirty = value;
+    }
+
+    fn kill(&mut self, uuid: ComponentId, context: &mut Context) {
+        debug_assert!(uuid == self.id);
+        context
+            .replies
+            .push_back(UIEvent::Action(crate::command::Action::Tab(
+                crate::command::TabAction::Kill(uuid),
+            )));
+    }
+
+    fn shortcuts(&self, context: &Context) -> ShortcutMaps {
+        let mut map = ShortcutMaps::default();
+
+        map.insert(
+            Shortcuts::GENERAL,
+            context.settings.shortcuts.general.key_values(),
+        );
+        map[Shortcuts::GENERAL].insert("save attachment", Key::Char('s'));
+        map[Shortcuts::GENERAL].insert("sort by 1st column", Key::Char('1'));
+        map[Shortcuts::GENERAL].insert("sort by 2nd column", Key::Char('2'));
+        map[Shortcuts::GENERAL].insert("sort by 3rd column", Key::Char('3'));
+        map[Shortcuts::GENERAL].insert("sort by 4th column", Key::Char('4'));
+        map[Shortcuts::GENERAL].insert("sort by 5th column", Key::Char('5'));
+
+        map
+    }
+
+    fn id(&self) -> ComponentId {
+        self.id
+    }
+
+    fn view_name(&self) -> Option<&'static str> {
+        Some("attachments")
+    }
+
+    fn can_quit_cleanly(&mut self, _context: &Context) -> bool {
+        true
+    }
+
+    fn status(&self, context: &Context) -> String {
+        format!(
+            "{} attachments{}. Press {} to open the attachment under the cursor, s to save it in \
+             the current directory. Use `sort <n> [asc/desc]` command or press column index \
+             number key (twice to toggle asc/desc) to sort",
+            self.entries.len(),
+            if context.accounts[&self.account_hash]
+                .attachment_index
+                .is_pending(self.mailbox_hash)
+            {
+                " (indexing)"
+            } else {
+                ""
+            },
+            context.settings.shortcuts.general.open_entry,
+        )
+    }
+}
@@ -29,7 +29,7 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use melib::{
     email::attachment_types::{ContentType, MultipartType},
     list_management,
@@ -42,6 +42,7 @@ use super::*;
 use crate::{
     accounts::JobRequest,
     command::actions::{ComposerTabAction, FileAction},
+    conf::data_types::{PaneLayout, PaneSplit},
     jobs::{IsAsync, JoinHandle},
     terminal::embedded::Terminal,
     types::{sanitize_filename, File},
@@ -49,12 +50,17 @@ use crate::{
 
 #[cfg(feature = "gpgme")]
 pub mod gpg;
+#[cfg(feature = "gpgme")]
+pub mod policy;
 
 pub mod edit_attachments;
 use edit_attachments::*;
 
 pub mod hooks;
 
+pub mod external_changes;
+use external_changes::*;
+
 const TOGGLE_CHECKED_UNICODE: &str = "☑";
 const TOGGLE_UNCHECKED_UNICODE: &str = "☐";
 const TOGGLE_CHECKED_ASCII: &str = "[x]";
@@ -111,6 +117,9 @@ impl std::ops::DerefMut for EmbeddedPty {
 pub struct Composer {
     reply_context: Option<(MailboxHash, EnvelopeHash)>,
     account_hash: AccountHash,
+    /// The stored message being edited, if the composer was opened with
+    /// [`Composer::edit`].
+    edited: Option<EditedEnvelope>,
 
     /// Which part the cursor is focused in, e.g. `{Headers, Body, Sign,
     /// Encrypt, Attachments}`.
@@ -126,10 +135,33 @@ pub struct Composer {
     embedded_dimensions: (usize, usize),
     #[cfg(feature = "gpgme")]
     gpg_state: gpg::GpgComposeState,
+    /// Recipients that asked for encryption with Autocrypt in the e-mail being
+    /// replied to.
+    #[cfg(feature = "gpgme")]
+    autocrypt_mutual: Vec<Address>,
+    /// The encryption policy decisions the user was asked to confirm, and the
+    /// recipients' keys.
+    #[cfg(feature = "gpgme")]
+    send_plan: Option<(policy::SendPlan, Vec<melib::gpgme::Key>)>,
+    /// The senders and recipients of the replied e-mail and of the e-mail it
+    /// references, offered for `Cc` with the `thread_participants` shortcut.
+    thread_participants: Vec<Address>,
+    /// The replied e-mail, shown next to the composer if `show_original` is
+    /// set.
+    original: Option<Pager>,
+    show_original: bool,
+    /// Whether to show header fields that are usually empty, e.g. `Cc` and
+    /// `Reply-To`, even when they are empty.
+    show_all_headers: bool,
     dirty: bool,
     has_changes: bool,
     initialized: bool,
+    /// The last message sent to the status line.
+    status: String,
     hooks: Vec<hooks::Hook>,
+    /// Plugins that check the draft before it is sent, see
+    /// [`crate::plugins`].
+    pre_send_plugins: Vec<String>,
     id: ComponentId,
 }
 
@@ -142,10 +174,19 @@ enum ViewMode {
     Edit,
     EmbeddedPty,
     SelectRecipients(UIDialog<Address>),
+    /// Choose which participants of the replied thread are in `Cc`.
+    SelectCc(UIDialog<Address>),
     #[cfg(feature = "gpgme")]
     SelectKey(bool, gpg::KeySelection),
     Send(UIConfirmationDialog),
+    /// Problems were found in the MIME structure of the message.
+    Lint(UIDialog<char>),
+    /// The edited message was changed by another program.
+    ExternalChange(UIDialog<char>),
     WaitingForSendResult(UIDialog<char>, JoinHandle<Result<()>>),
+    /// Plugins are checking the draft before it is sent; the result is the
+    /// new text of the draft, if a plugin changed it.
+    WaitingForPlugins(JoinHandle<Result<Option<String>>>),
 }
 
 impl ViewMode {
@@ -183,6 +224,7 @@ impl Composer {
         form.set_cursor(2);
         Self {
             reply_context: None,
+            edited: None,
             account_hash: AccountHash::default(),
             focus: Focus::Headers,
             pager,
@@ -193,15 +235,25 @@ impl Composer {
                 hooks::MISSINGATTACHMENTWARN,
                 hooks::EMPTYDRAFTWARN,
             ],
+            pre_send_plugins: vec![],
             form,
             mode: ViewMode::Edit,
             #[cfg(feature = "gpgme")]
             gpg_state: gpg::GpgComposeState::default(),
+            #[cfg(feature = "gpgme")]
+            autocrypt_mutual: vec![],
+            #[cfg(feature = "gpgme")]
+            send_plan: None,
+            thread_participants: vec![],
+            original: None,
+            show_original: false,
+            show_all_headers: false,
             dirty: true,
             has_changes: false,
             embedded_pty: None,
             embedded_dimensions: (80, 20),
             initialized: false,
+            status: String::new(),
             id: ComponentId::default(),
         }
     }
@@ -221,11 +273,19 @@ impl Composer {
             ret.hooks.push(hook);
         }
 
-        ret.hooks.retain(|h| {
-            !account_settings!(context[account_hash].composing.disabled_compose_hooks)
-                .iter()
-                .any(|hn| hn.as_str() == h.name())
-        });
+        // Add plugins that check drafts before they are sent.
+        ret.pre_send_plugins = context
+            .plugins
+            .with_hook(crate::conf::PluginHook::PreSend)
+            .map(str::to_string)
+            .collect();
+
+        let disabled_hooks =
+            account_settings!(context[account_hash].composing.disabled_compose_hooks);
+        ret.hooks
+            .retain(|h| !disabled_hooks.iter().any(|hn| hn.as_str() == h.name()));
+        ret.pre_send_plugins
+            .retain(|name| !disabled_hooks.iter().any(|hn| hn == name));
 
         for h in context.accounts[&account_hash]
             .backend_capabilities
@@ -294,6 +354,7 @@ impl Composer {
         let mut ret = Self::with_account(account_hash, context);
         let envelope: EnvelopeRef = context.accounts[&account_hash].collection.get_env(env_hash);
         ret.draft = Draft::edit(&envelope, bytes, Text::Plain)?;
+        ret.edited = Some(EditedEnvelope::new(&envelope));
         Ok(ret)
     }
 
@@ -304,8 +365,13 @@ impl Composer {
         mut reply_to_all: bool,
     ) -> Self {
         let mut ret = Self::with_account(account_hash, context);
+        ret.thread_participants = thread_participants(context, coordinates);
         let account = &context.accounts[&account_hash];
         let envelope = account.collection.get_env(coordinates.2);
+        #[cfg(feature = "gpgme")]
+        {
+            ret.autocrypt_mutual = policy::autocrypt_mutual(envelope.other_headers());
+        }
         let subject = {
             let subject = envelope.subject();
             let prefix_list = account_settings!(
@@ -464,15 +530,35 @@ impl Composer {
                         .attribution_use_posix_locale
                 ),
             );
-            for l in reply_body.lines() {
-                quoted.push('>');
-                quoted.push_str(l);
-                quoted.push('\n');
-            }
+            quoted.push_str(&quote_reply_body(
+                &strip_url_parameters(
+                    &reply_body,
+                    account_settings!(
+                        context[ret.account_hash]
+                            .composing
+                            .quote_strip_url_parameters
+                    ),
+                ),
+                account_settings!(context[ret.account_hash].composing.quote_prefix),
+                *account_settings!(context[ret.account_hash].composing.quote_strip_signature),
+                *account_settings!(context[ret.account_hash].composing.quote_depth_limit),
+            ));
             _ = write!(&mut quoted, "{}", ret.draft.body);
             quoted
         };
 
+        let mut original = Pager::from_string(
+            reply_body,
+            context,
+            None,
+            None,
+            crate::conf::value(context, "theme_default"),
+        );
+        original.set_show_scrollbar(true);
+        ret.original = Some(original);
+        ret.show_original =
+            account_settings!(context[account_hash].composing.reply_split).is_some();
+
         ret.account_hash = coordinates.0;
         ret.reply_context = Some((coordinates.1, coordinates.2));
         ret
@@ -596,6 +682,272 @@ To: {}
         self.update_form(context);
     }
 
+    /// Send the draft and wait for the result.
+    fn submit(&mut self, context: &mut Context) {
+        #[cfg(feature = "gpgme")]
+        let gpg_state = match self.send_plan.take() {
+            Some((plan, keys)) => {
+                let mut gpg_state = self.gpg_state.clone();
+                gpg_state.encrypt_mail = Some(ActionFlag::from(plan.encrypt()));
+                gpg_state.sign_mail = Some(ActionFlag::from(plan.sign()));
+                if plan.encrypt() {
+                    for key in keys {
+                        if !gpg_state.encrypt_keys.contains(&key) {
+                            gpg_state.encrypt_keys.push(key);
+                        }
+                    }
+                }
+                gpg_state
+            }
+            None => self.gpg_state.clone(),
+        };
+        match send_draft_async(
+            #[cfg(feature = "gpgme")]
+            gpg_state,
+            context,
+            self.account_hash,
+            self.draft.clone(),
+            SpecialUsageMailbox::Sent,
+            Flag::SEEN,
+        ) {
+            Ok(job) => {
+                let handle = context.main_loop_handler.job_executor.spawn(
+                    "compose::submit".into(),
+                    job,
+                    IsAsync::Blocking,
+                );
+                context
+                    .replies
+                    .push_back(UIEvent::StatusEvent(StatusEvent::NewJob(handle.job_id)));
+                self.mode = ViewMode::WaitingForSendResult(
+                    UIDialog::new(
+                        "Waiting for confirmation. The tab will close automatically \
+                         on successful submission.",
+                        vec![
+                            ('c', "force close tab".to_string()),
+                            (
+                                'n',
+                                "close this message and return to edit mode".to_string(),
+                            ),
+                        ],
+                        true,
+                        Some(Box::new(move |id: ComponentId, results: &[char]| {
+                            Some(UIEvent::FinishedUIDialog(
+                                id,
+                                Box::new(results.first().cloned().unwrap_or('c')),
+                            ))
+                        })),
+                        context,
+                    ),
+                    handle,
+                );
+            }
+            Err(err) => {
+                context.replies.push_back(UIEvent::Notification {
+                    title: None,
+                    source: None,
+                    body: err.to_string().into(),
+                    kind: Some(NotificationType::Error(err.kind)),
+                });
+                save_draft(
+                    self.draft.clone().finalise().unwrap().as_bytes(),
+                    context,
+                    SpecialUsageMailbox::Drafts,
+                    Flag::SEEN | Flag::DRAFT,
+                    self.account_hash,
+                );
+                self.mode = ViewMode::Edit;
+            }
+        }
+    }
+
+    /// Send the draft, unless the encryption policy has to be confirmed
+    /// first.
+    fn submit_checked(&mut self, context: &mut Context) {
+        #[cfg(feature = "gpgme")]
+        if !account_settings!(context[self.account_hash].pgp.recipient_policies).is_empty() {
+            self.confirm_send(context);
+            return;
+        }
+        self.submit(context);
+    }
+
+    /// Check the MIME structure of the draft and ask to send it, or to fix
+    /// the problems found first.
+    fn lint_and_confirm(&mut self, context: &mut Context) {
+        match self.draft.lint() {
+            Ok(lints) if !lints.is_empty() => {
+                let fixable = lints.iter().filter(|l| l.kind.is_fixable()).count();
+                context
+                    .replies
+                    .push_back(UIEvent::StatusEvent(StatusEvent::DisplayMessage(
+                        lints
+                            .iter()
+                            .map(|l| l.to_string())
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    )));
+                let mut entries = vec![];
+                if fixable > 0 {
+                    entries.push(('f', "fix and send".to_string()));
+                }
+                entries.push(('y', "send anyway".to_string()));
+                entries.push(('n', "return to edit mode".to_string()));
+                self.mode = ViewMode::Lint(UIDialog::new(
+                    &format!(
+                        "{} problem{} found in the message ({} fixable)",
+                        lints.len(),
+                        if lints.len() == 1 { "" } else { "s" },
+                        fixable
+                    ),
+                    entries,
+                    true,
+                    Some(Box::new(move |id: ComponentId, results: &[char]| {
+                        Some(UIEvent::FinishedUIDialog(
+                            id,
+                            Box::new(results.first().cloned().unwrap_or('n')),
+                        ))
+                    })),
+                    context,
+                ));
+            }
+            Ok(_) => self.confirm_send(context),
+            Err(err) => {
+                context.replies.push_back(UIEvent::Notification {
+                    title: Some("Could not build message".into()),
+                    source: None,
+                    body: err.to_string().into(),
+                    kind: Some(NotificationType::Error(err.kind)),
+                });
+            }
+        }
+    }
+
+    /// Ask for confirmation before sending the draft. If
+    /// `pgp.recipient_policies` is set, the decisions of the encryption policy
+    /// are shown first, and the draft isn't sent if a recipient that must be
+    /// encrypted to has no key.
+    fn confirm_send(&mut self, context: &mut Context) {
+        #[allow(unused_mut)]
+        let mut title = "send mail?".to_string();
+        #[cfg(feature = "gpgme")]
+        match self.recipient_plan(context) {
+            Ok(None) => {}
+            Ok(Some((plan, keys))) => {
+                if plan.blocked().next().is_some() {
+                    context.replies.push_back(UIEvent::Notification {
+                        title: Some("Could not send message".into()),
+                        source: None,
+                        body: plan.summary().into(),
+                        kind: Some(NotificationType::Error(ErrorKind::None)),
+                    });
+                    return;
+                }
+                context
+                    .replies
+                    .push_back(UIEvent::StatusEvent(StatusEvent::DisplayMessage(
+                        plan.summary(),
+                    )));
+                title = format!("send mail? ({} to everyone)", plan.description());
+                self.send_plan = Some((plan, keys));
+            }
+            Err(err) => {
+                context.replies.push_back(UIEvent::Notification {
+                    title: Some("Could not look up recipient keys".into()),
+                    source: None,
+                    body: err.to_string().into(),
+                    kind: Some(NotificationType::Error(err.kind)),
+                });
+                return;
+            }
+        }
+        self.mode = ViewMode::Send(UIConfirmationDialog::new(
+            &title,
+            vec![(true, "yes".to_string()), (false, "no".to_string())],
+            /* only one choice */
+            true,
+            Some(Box::new(move |id: ComponentId, result: bool| {
+                Some(UIEvent::FinishedUIDialog(id, Box::new(result)))
+            })),
+            context,
+        ));
+    }
+
+    /// Decide how to protect the draft for each recipient with the
+    /// `pgp.recipient_policies` setting, or `None` if it is empty.
+    #[cfg(feature = "gpgme")]
+    fn recipient_plan(
+        &mut self,
+        context: &Context,
+    ) -> Result<Option<(policy::SendPlan, Vec<melib::gpgme::Key>)>> {
+        use melib::gpgme::{self, LocateKey};
+
+        let rules = account_settings!(context[self.account_hash].pgp.recipient_policies);
+        if rules.is_empty() {
+            return Ok(None);
+        }
+        self.update_draft();
+        let mut draft = self.draft.clone();
+        expand_distribution_lists(
+            &mut draft,
+            &distribution_lists(context, self.account_hash),
+            *account_settings!(
+                context[self.account_hash]
+                    .composing
+                    .distribution_lists_keep_name
+            ),
+        );
+        let mut recipients: Vec<Address> = vec![];
+        for header in [HeaderName::TO, HeaderName::CC, HeaderName::BCC] {
+            let Some(value) = draft.headers().get(header) else {
+                continue;
+            };
+            for address in Address::list_try_from(value)?
+                .iter()
+                .flat_map(Address::mailboxes)
+            {
+                if !recipients.contains(address) {
+                    recipients.push(address.clone());
+                }
+            }
+        }
+        let is_on = |flag: Option<ActionFlag>| flag.unwrap_or(ActionFlag::False).is_true();
+        let default = if is_on(self.gpg_state.encrypt_mail) {
+            policy::Protection::Encrypt
+        } else if is_on(self.gpg_state.sign_mail) {
+            policy::Protection::Sign
+        } else {
+            policy::Protection::Plain
+        };
+        let mut ctx = gpgme::Context::new()?;
+        ctx.set_auto_key_locate(LocateKey::LOCAL)?;
+        let mut decisions = vec![];
+        let mut keys = vec![];
+        for address in recipients {
+            // A pattern in angle brackets matches the e-mail address exactly.
+            let key = futures::executor::block_on(
+                ctx.keylist(false, Some(format!("<{}>", address.get_email())))?,
+            )?
+            .into_iter()
+            .find(|k| k.can_encrypt() && !k.revoked() && !k.expired() && !k.invalid());
+            decisions.push(policy::decide(
+                rules,
+                &address,
+                key.is_some(),
+                self.autocrypt_mutual.contains(&address),
+                default,
+            ));
+            keys.extend(key);
+        }
+        Ok(Some((
+            policy::SendPlan {
+                decisions,
+                sign: is_on(self.gpg_state.sign_mail),
+            },
+            keys,
+        )))
+    }
+
     fn update_draft(&mut self) {
         let header_values = self.form.values_mut();
         let draft_header_map = self.draft.headers_mut();
@@ -604,6 +956,155 @@ To: {}
                 *v = vn.as_str().to_string();
             }
         }
+        // Fields that were revealed with `toggle_headers` are not in the draft
+        // until they are filled in.
+        for (k, vn) in header_values.iter() {
+            if vn.is_empty() || draft_header_map.contains_key(k.as_ref()) {
+                continue;
+            }
+            if let Ok(k) = HeaderName::try_from(k.as_ref()) {
+                draft_header_map.insert(k, vn.as_str().to_string());
+            }
+        }
+    }
+
+    /// Value of header field `header` in the header form, or the empty string
+    /// if it is not shown.
+    fn header_value(&self, header: &HeaderName) -> &str {
+        self.form
+            .values()
+            .get(header.as_str())
+            .map(Field::as_str)
+            .unwrap_or_default()
+    }
+
+    /// Header fields that are shown only if they are not empty, unless
+    /// `show_all_headers` is set.
+    fn optional_headers(&self, context: &Context) -> [HeaderName; 4] {
+        let is_nntp = context.accounts[&self.account_hash]
+            .backend_capabilities
+            .extra_submission_headers
+            .contains(&HeaderName::NEWSGROUPS);
+        [
+            HeaderName::CC,
+            HeaderName::BCC,
+            HeaderName::REPLY_TO,
+            if is_nntp {
+                HeaderName::FOLLOWUP_TO
+            } else {
+                HeaderName::MAIL_FOLLOWUP_TO
+            },
+        ]
+    }
+
+    /// Check address header fields and show an error next to those that
+    /// cannot be parsed.
+    fn validate_headers(&mut self) {
+        for header in [
+            HeaderName::FROM,
+            HeaderName::TO,
+            HeaderName::CC,
+            HeaderName::BCC,
+            HeaderName::REPLY_TO,
+            HeaderName::MAIL_FOLLOWUP_TO,
+        ] {
+            let error = validate_address_list(self.header_value(&header));
+            self.form.set_error(header.as_str(), error);
+        }
+    }
+
+    /// Show or hide empty optional header fields.
+    fn toggle_headers(&mut self, context: &Context) {
+        self.update_draft();
+        self.show_all_headers = !self.show_all_headers;
+        self.update_form(context);
+        self.set_dirty(true);
+    }
+
+    /// Show the participants of the replied thread, marking those that are
+    /// in `Cc`, so that they can be added to or removed from it.
+    fn select_cc(&mut self, context: &mut Context) {
+        if self.thread_participants.is_empty() {
+            context
+                .replies
+                .push_back(UIEvent::StatusEvent(StatusEvent::DisplayMessage(
+                    "There are no other participants in this thread.".to_string(),
+                )));
+            return;
+        }
+        self.update_draft();
+        let cc = self
+            .draft
+            .headers()
+            .get(HeaderName::CC)
+            .and_then(|v| Address::list_try_from(v).ok())
+            .unwrap_or_default();
+        let mut dialog = UIDialog::new(
+            "Cc participants of this thread",
+            self.thread_participants
+                .iter()
+                .map(|a| (a.clone(), a.to_string()))
+                .collect(),
+            false,
+            Some(Box::new(move |id: ComponentId, results: &[Address]| {
+                Some(UIEvent::FinishedUIDialog(id, Box::new(results.to_vec())))
+            })),
+            context,
+        );
+        dialog.select(|a| {
+            cc.iter()
+                .flat_map(Address::mailboxes)
+                .any(|c| same_mailbox(a, c))
+        });
+        self.mode = ViewMode::SelectCc(dialog);
+        self.set_dirty(true);
+    }
+
+    /// Draw the replied e-mail in its pane of `area`, as set by the
+    /// `reply_split` setting, and return the area left for the composer.
+    fn draw_original(&mut self, grid: &mut CellBuffer, area: Area, context: &mut Context) -> Area {
+        let Some(ref mut original) = self.original else {
+            return area;
+        };
+        let split = account_settings!(context[self.account_hash].composing.reply_split)
+            .unwrap_or(PaneSplit::Vertical);
+        let total = match split {
+            PaneSplit::Vertical => area.width(),
+            PaneSplit::Horizontal => area.height(),
+        };
+        let composer_len = total * PaneLayout::DEFAULT_RATIO / 100;
+        // The composer needs room for its title and at least one header field.
+        if composer_len < 4 || composer_len + 1 >= total {
+            return area;
+        }
+        let (composer_area, divider_area, original_area) = match split {
+            PaneSplit::Vertical => (
+                area.take_cols(composer_len),
+                area.nth_col(composer_len),
+                area.skip_cols(composer_len + 1),
+            ),
+            PaneSplit::Horizontal => (
+                area.take_rows(composer_len),
+                area.nth_row(composer_len),
+                area.skip_rows(composer_len + 1),
+            ),
+        };
+        if self.dirty {
+            let divider = *account_settings!(context[self.account_hash].listing.mail_view_divider);
+            let divider_theme = crate::conf::value(context, "mail.view.divider");
+            for row in grid.bounds_iter(divider_area) {
+                for c in row {
+                    grid[c]
+                        .set_ch(divider)
+                        .set_fg(divider_theme.fg)
+                        .set_bg(divider_theme.bg)
+                        .set_attrs(divider_theme.attrs);
+                }
+            }
+            context.dirty_areas.push_back(divider_area);
+        }
+        original.draw(grid, original_area, context);
+        composer_area
     }
 
     fn update_form(&mut self, context: &Context) {
@@ -633,7 +1134,7 @@ To: {}
                 .unwrap_or_else(|| context.settings.shortcuts.general.scroll_left.clone()),
         );
         self.form.hide_buttons();
-        self.form.set_cursor(old_cursor);
+        let optional_headers = self.optional_headers(context);
         let headers = self.draft.headers();
         let account_hash = self.account_hash;
         for k in context.accounts[&account_hash]
@@ -643,7 +1144,7 @@ To: {}
             if matches!(*k, HeaderName::NEWSGROUPS) {
                 self.form.push_cl((
                     k.into(),
-                    headers[k].to_string(),
+                    headers.get(k).unwrap_or_default().to_string(),
                     Box::new(move |c, term| {
                         c.accounts[&account_hash]
                             .mailbox_entries
@@ -660,21 +1161,30 @@ To: {}
                     }),
                 ));
             } else {
-                self.form.push((k.into(), headers[k].to_string()));
+                self.form
+                    .push((k.into(), headers.get(k).unwrap_or_default().to_string()));
             }
         }
-        for k in &[
-            HeaderName::DATE,
-            HeaderName::FROM,
-            HeaderName::TO,
-            HeaderName::CC,
-            HeaderName::BCC,
-            HeaderName::SUBJECT,
-        ] {
-            if matches!(*k, HeaderName::TO | HeaderName::CC | HeaderName::BCC) {
+        for k in [HeaderName::DATE, HeaderName::FROM, HeaderName::TO]
+            .iter()
+            .chain(optional_headers.iter())
+            .chain(std::iter::once(&HeaderName::SUBJECT))
+        {
+            let value = headers.get(k).unwrap_or_default();
+            if optional_headers.contains(k) && !self.show_all_headers && value.is_empty() {
+                continue;
+            }
+            if matches!(
+                *k,
+                HeaderName::TO
+                    | HeaderName::CC
+                    | HeaderName::BCC
+                    | HeaderName::REPLY_TO
+                    | HeaderName::MAIL_FOLLOWUP_TO
+            ) {
                 self.form.push_cl((
                     k.into(),
-                    headers[k].to_string(),
+                    value.to_string(),
                     Box::new(move |c, term| {
                         let book: &Contacts = &c.accounts[&account_hash].contacts;
                         let results: Vec<String> = book.search(term);
@@ -687,7 +1197,7 @@ To: {}
             } else if k == HeaderName::FROM {
                 self.form.push_cl((
                     k.into(),
-                    headers[k].to_string(),
+                    value.to_string(),
                     Box::new(move |c, _term| {
                         c.accounts
                             .values()
@@ -717,9 +1227,12 @@ To: {}
                     }),
                 ));
             } else {
-                self.form.push((k.into(), headers[k].to_string()));
+                self.form.push((k.into(), value.to_string()));
             }
         }
+        self.form
+            .set_cursor(old_cursor.min(self.form.len().saturating_sub(1)));
+        self.validate_headers();
     }
 
     fn draw_attachments(&self, grid: &mut CellBuffer, mut area: Area, context: &Context) {
@@ -946,7 +1459,7 @@ To: {}
         context: &Context,
     ) -> Result<gpg::KeySelectionLoading> {
         let (_, mut list) = melib::email::parser::address::rfc2822address_list(
-            self.form.values()[header.as_str()].as_str().as_bytes(),
+            self.header_value(header).as_bytes(),
         )
         .map_err(|_err| -> Error { format!("No valid address in `{header}:`").into() })?;
         if list.is_empty() {
@@ -998,14 +1511,33 @@ impl Component for Composer {
                         .to_string(),
                 );
             }
-            self.pager.update_from_str(self.draft.body(), Some(77));
+            self.pager.update_from_str(
+                self.draft.body(),
+                Some(*account_settings!(
+                    context[self.account_hash].composing.wrap_column
+                )),
+            );
             self.update_form(context);
             self.initialized = true;
         }
 
         let theme_default = crate::conf::value(context, "theme_default");
         let highlight_attr = crate::conf::value(context, "highlight");
+        let area = if self.show_original {
+            self.draw_original(grid, area, context)
+        } else {
+            area
+        };
         if self.dirty {
+            let status = self.status(context);
+            if status != self.status {
+                context
+                    .replies
+                    .push_back(UIEvent::StatusEvent(StatusEvent::UpdateStatus(
+                        status.clone(),
+                    )));
+                self.status = status;
+            }
             grid.clear_area(area.skip_rows(1), theme_default);
             grid.clear_area(area.nth_row(0), highlight_attr);
             let our_map: ShortcutMap =
@@ -1151,7 +1683,7 @@ impl Component for Composer {
         }
 
         match self.mode {
-            ViewMode::Edit | ViewMode::EmbeddedPty => {}
+            ViewMode::Edit | ViewMode::EmbeddedPty | ViewMode::WaitingForPlugins(_) => {}
             ViewMode::EditAttachments { ref mut widget } => {
                 (EditAttachmentsRefMut {
                     inner: widget,
@@ -1166,6 +1698,13 @@ impl Component for Composer {
                 ));
                 s.draw(grid, inner_area, context);
             }
+            ViewMode::Lint(ref mut s) | ViewMode::ExternalChange(ref mut s) => {
+                let inner_area = area.center_inside((
+                    area.width().saturating_sub(2),
+                    area.height().saturating_sub(2),
+                ));
+                s.draw(grid, inner_area, context);
+            }
             #[cfg(feature = "gpgme")]
             ViewMode::SelectKey(
                 _,
@@ -1182,7 +1721,7 @@ impl Component for Composer {
             }
             #[cfg(feature = "gpgme")]
             ViewMode::SelectKey(_, _) => {}
-            ViewMode::SelectRecipients(ref mut s) => {
+            ViewMode::SelectRecipients(ref mut s) | ViewMode::SelectCc(ref mut s) => {
                 let inner_area = area.center_inside((
                     area.width().saturating_sub(2),
                     area.height().saturating_sub(2),
@@ -1288,11 +1827,15 @@ impl Component for Composer {
         {
             if matches!(event, UIEvent::InsertInput(_)) {
                 self.update_draft();
+                self.validate_headers();
                 self.has_changes = true;
             }
             self.set_dirty(true);
             return true;
         }
+        if self.process_external_change(event, context) {
+            return true;
+        }
         match (&mut self.mode, &mut event) {
             (ViewMode::Edit, _) => {
                 if self.pager.process_event(event, context) {
@@ -1321,67 +1864,11 @@ impl Component for Composer {
             {
                 if matches!(result.downcast_ref::<bool>(), Some(true)) {
                     self.update_draft();
-                    match send_draft_async(
-                        #[cfg(feature = "gpgme")]
-                        self.gpg_state.clone(),
-                        context,
-                        self.account_hash,
-                        self.draft.clone(),
-                        SpecialUsageMailbox::Sent,
-                        Flag::SEEN,
-                    ) {
-                        Ok(job) => {
-                            let handle = context.main_loop_handler.job_executor.spawn(
-                                "compose::submit".into(),
-                                job,
-                                IsAsync::Blocking,
-                            );
-                            context
-                                .replies
-                                .push_back(UIEvent::StatusEvent(StatusEvent::NewJob(
-                                    handle.job_id,
-                                )));
-                            self.mode = ViewMode::WaitingForSendResult(
-                                UIDialog::new(
-                                    "Waiting for confirmation. The tab will close automatically \
-                                     on successful submission.",
-                                    vec![
-                                        ('c', "force close tab".to_string()),
-                                        (
-                                            'n',
-                                            "close this message and return to edit mode"
-                                                .to_string(),
-                                        ),
-                                    ],
-                                    true,
-                                    Some(Box::new(move |id: ComponentId, results: &[char]| {
-                                        Some(UIEvent::FinishedUIDialog(
-                                            id,
-                                            Box::new(results.first().cloned().unwrap_or('c')),
-                                        ))
-                                    })),
-                                    context,
-                                ),
-                                handle,
-                            );
-                        }
-                        Err(err) => {
-                            context.replies.push_back(UIEvent::Notification {
-                                title: None,
-                                source: None,
-                                body: err.to_string().into(),
-                                kind: Some(NotificationType::Error(err.kind)),
-                            });
-                            save_draft(
-                                self.draft.clone().finalise().unwrap().as_bytes(),
-                                context,
-                                SpecialUsageMailbox::Drafts,
-                                Flag::SEEN | Flag::DRAFT,
-                                self.account_hash,
-                            );
-                            self.mode = ViewMode::Edit;
-                        }
-                    }
+                    self.submit(context);
+                }
+                #[cfg(feature = "gpgme")]
+                {
+                    self.send_plan = None;
                 }
                 self.set_dirty(true);
                 return true;
@@ -1392,12 +1879,63 @@ impl Component for Composer {
                 self.mode = ViewMode::Edit;
                 self.set_dirty(true);
             }
-            (ViewMode::SelectRecipients(ref dialog), UIEvent::ComponentUnrealize(ref id))
+            (ViewMode::Lint(ref dialog), UIEvent::ComponentUnrealize(ref id))
                 if *id == dialog.id() =>
             {
                 self.mode = ViewMode::Edit;
                 self.set_dirty(true);
             }
+            (ViewMode::Lint(ref selector), UIEvent::FinishedUIDialog(id, result))
+                if selector.id() == *id =>
+            {
+                self.mode = ViewMode::Edit;
+                match result.downcast_ref::<char>() {
+                    Some('f') => {
+                        self.draft.fix_lints();
+                        self.update_form(context);
+                        self.submit_checked(context);
+                    }
+                    Some('y') => self.submit_checked(context),
+                    _ => {}
+                }
+                self.set_dirty(true);
+                return true;
+            }
+            (ViewMode::Lint(ref mut selector), _) => {
+                if selector.process_event(event, context) {
+                    self.set_dirty(true);
+                    return true;
+                }
+            }
+            (ViewMode::ExternalChange(ref dialog), UIEvent::ComponentUnrealize(ref id))
+                if *id == dialog.id() =>
+            {
+                self.mode = ViewMode::Edit;
+                self.set_dirty(true);
+            }
+            (ViewMode::ExternalChange(ref selector), UIEvent::FinishedUIDialog(id, result))
+                if selector.id() == *id =>
+            {
+                self.mode = ViewMode::Edit;
+                if let Some(choice) = result.downcast_ref::<char>() {
+                    self.resolve_external_change(*choice, context);
+                }
+                self.set_dirty(true);
+                return true;
+            }
+            (ViewMode::ExternalChange(ref mut selector), _) => {
+                if selector.process_event(event, context) {
+                    self.set_dirty(true);
+                    return true;
+                }
+            }
+            (
+                ViewMode::SelectRecipients(ref dialog) | ViewMode::SelectCc(ref dialog),
+                UIEvent::ComponentUnrealize(ref id),
+            ) if *id == dialog.id() => {
+                self.mode = ViewMode::Edit;
+                self.set_dirty(true);
+            }
             (ViewMode::Discard(_, ref dialog), UIEvent::ComponentUnrealize(ref id))
                 if *id == dialog.id() =>
             {
@@ -1431,7 +1969,53 @@ impl Component for Composer {
                 self.set_dirty(true);
                 return true;
             }
-            (ViewMode::SelectRecipients(ref mut selector), _) => {
+            (ViewMode::SelectCc(ref selector), UIEvent::FinishedUIDialog(id, ref mut result))
+                if selector.id() == *id =>
+            {
+                if let Some(selected) = result.downcast_mut::<Vec<Address>>() {
+                    let cc = self
+                        .draft
+                        .headers()
+                        .get(HeaderName::CC)
+                        .and_then(|v| Address::list_try_from(v).ok())
+                        .unwrap_or_default();
+                    let participants = &self.thread_participants;
+                    let mut new_cc = cc
+                        .into_iter()
+                        .filter(|c| {
+                            c.mailboxes().iter().all(|m| {
+                                !participants.iter().any(|p| same_mailbox(p, m))
+                                    || selected.iter().any(|s| same_mailbox(s, m))
+                            })
+                        })
+                        .collect::<Vec<Address>>();
+                    for address in selected.drain(..) {
+                        if !new_cc
+                            .iter()
+                            .flat_map(Address::mailboxes)
+                            .any(|c| same_mailbox(&address, c))
+                        {
+                            new_cc.push(address);
+                        }
+                    }
+                    self.draft.set_header(
+                        HeaderName::CC,
+                        new_cc
+                            .iter()
+                            .map(|a| a.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", "),
+                    );
+                    self.update_form(context);
+                }
+                self.mode = ViewMode::Edit;
+                self.set_dirty(true);
+                return true;
+            }
+            (
+                ViewMode::SelectRecipients(ref mut selector) | ViewMode::SelectCc(ref mut selector),
+                _,
+            ) => {
                 if selector.process_event(event, context) {
                     self.set_dirty(true);
                     return true;
@@ -1500,6 +2084,44 @@ impl Component for Composer {
                 }
                 return true;
             }
+            (
+                ViewMode::WaitingForPlugins(ref mut handle),
+                UIEvent::StatusEvent(StatusEvent::JobFinished(ref job_id)),
+            ) if handle.job_id == *job_id => {
+                let result =
+                    handle
+                        .chan
+                        .try_recv()
+                        .map_err(|_: futures::channel::oneshot::Canceled| {
+                            Error::new("Job was canceled")
+                        });
+                self.mode = ViewMode::Edit;
+                match result {
+                    Ok(Some(Ok(Some(new_draft)))) => {
+                        if let Err(err) = self.draft.update(&new_draft) {
+                            context.replies.push_back(UIEvent::Notification {
+                                title: None,
+                                source: None,
+                                body: err.to_string().into(),
+                                kind: None,
+                            });
+                        }
+                        self.update_form(context);
+                    }
+                    Ok(None) | Ok(Some(Ok(None))) => {}
+                    Err(err) | Ok(Some(Err(err))) => {
+                        context.replies.push_back(UIEvent::Notification {
+                            title: None,
+                            source: None,
+                            body: err.to_string().into(),
+                            kind: None,
+                        });
+                    }
+                }
+                self.lint_and_confirm(context);
+                self.set_dirty(true);
+                return true;
+            }
             (
                 ViewMode::WaitingForSendResult(_, ref mut handle),
                 UIEvent::StatusEvent(StatusEvent::JobFinished(ref job_id)),
@@ -1595,6 +2217,51 @@ impl Component for Composer {
                 };
                 self.set_dirty(true);
             }
+            UIEvent::Input(ref key)
+                if self.mode.is_edit()
+                    && shortcut!(key == shortcuts[Shortcuts::COMPOSING]["toggle_headers"]) =>
+            {
+                self.toggle_headers(context);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if self.mode.is_edit()
+                    && shortcut!(key == shortcuts[Shortcuts::COMPOSING]["thread_participants"]) =>
+            {
+                self.select_cc(context);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if self.mode.is_edit()
+                    && self.original.is_some()
+                    && shortcut!(key == shortcuts[Shortcuts::COMPOSING]["toggle_original"]) =>
+            {
+                self.show_original = !self.show_original;
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if self.mode.is_edit()
+                    && self.show_original
+                    && shortcut!(key == shortcuts[Shortcuts::COMPOSING]["scroll_original_up"]) =>
+            {
+                if let Some(ref mut original) = self.original {
+                    original.scroll(PageMovement::Up(1));
+                }
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if self.mode.is_edit()
+                    && self.show_original
+                    && shortcut!(
+                        key == shortcuts[Shortcuts::COMPOSING]["scroll_original_down"]
+                    ) =>
+            {
+                if let Some(ref mut original) = self.original {
+                    original.scroll(PageMovement::Down(1));
+                }
+                return true;
+            }
             UIEvent::Input(ref key)
                 if shortcut!(key == shortcuts[Shortcuts::COMPOSING]["send_mail"])
                     && self.mode.is_edit() =>
@@ -1622,16 +2289,24 @@ impl Component for Composer {
                         });
                     }
                 }
-                self.mode = ViewMode::Send(UIConfirmationDialog::new(
-                    "send mail?",
-                    vec![(true, "yes".to_string()), (false, "no".to_string())],
-                    /* only one choice */
-                    true,
-                    Some(Box::new(move |id: ComponentId, result: bool| {
-                        Some(UIEvent::FinishedUIDialog(id, Box::new(result)))
-                    })),
-                    context,
-                ));
+                if self.pre_send_plugins.is_empty() {
+                    self.lint_and_confirm(context);
+                } else {
+                    let job = context.plugins.pre_send(
+                        &self.pre_send_plugins,
+                        context.accounts[&self.account_hash].name(),
+                        self.draft.to_edit_string(),
+                    );
+                    let handle = context.main_loop_handler.job_executor.spawn(
+                        "compose::pre_send_plugins".into(),
+                        job,
+                        IsAsync::Async,
+                    );
+                    context
+                        .replies
+                        .push_back(UIEvent::StatusEvent(StatusEvent::NewJob(handle.job_id)));
+                    self.mode = ViewMode::WaitingForPlugins(handle);
+                }
                 return true;
             }
             UIEvent::EmbeddedInput((Key::Ctrl('z'), _)) => {
@@ -1831,10 +2506,7 @@ impl Component for Composer {
                     && shortcut!(key == shortcuts[Shortcuts::COMPOSING]["edit"]) =>
             {
                 let mut result = self.create_key_selection_widget(false, &HeaderName::TO, context);
-                if !self.form.values()[HeaderName::CC.as_str()]
-                    .as_str()
-                    .is_empty()
-                {
+                if !self.header_value(&HeaderName::CC).is_empty() {
                     result = result.and_then(|mut to_result| {
                         let cc_result =
                             self.create_key_selection_widget(false, &HeaderName::CC, context)?;
@@ -1842,10 +2514,7 @@ impl Component for Composer {
                         Ok(to_result)
                     });
                 }
-                if !self.form.values()[HeaderName::BCC.as_str()]
-                    .as_str()
-                    .is_empty()
-                {
+                if !self.header_value(&HeaderName::BCC).is_empty() {
                     result = result.and_then(|mut to_result| {
                         let bcc_result =
                             self.create_key_selection_widget(false, &HeaderName::BCC, context)?;
@@ -1853,10 +2522,7 @@ impl Component for Composer {
                         Ok(to_result)
                     });
                 }
-                if !self.form.values()[HeaderName::FROM.as_str()]
-                    .as_str()
-                    .is_empty()
-                {
+                if !self.header_value(&HeaderName::FROM).is_empty() {
                     result = result.and_then(|mut to_result| {
                         let from_result =
                             self.create_key_selection_widget(false, &HeaderName::FROM, context)?;
@@ -2017,11 +2683,17 @@ impl Component for Composer {
 
                 if *account_settings!(context[self.account_hash].composing.embedded_pty) {
                     let command = [editor, f.path().display().to_string()].join(" ");
-                    match crate::terminal::embedded::create_pty(
-                        self.embedded_dimensions.0,
-                        self.embedded_dimensions.1,
-                        &command,
-                    ) {
+                    match context
+                        .command_policy(Some(self.account_hash))
+                        .check(Command::new("sh").args(["-c", &command]))
+                        .map_err(Error::from)
+                        .and_then(|()| {
+                            crate::terminal::embedded::create_pty(
+                                self.embedded_dimensions.0,
+                                self.embedded_dimensions.1,
+                                &command,
+                            )
+                        }) {
                         Ok(terminal) => {
                             self.embedded_pty = Some(EmbeddedPty {
                                 running: true,
@@ -2077,7 +2749,7 @@ impl Component for Composer {
                     .args(["-c", &editor_command])
                     .stdin(Stdio::inherit())
                     .stdout(Stdio::inherit())
-                    .spawn()
+                    .spawn_checked(&context.command_policy(Some(self.account_hash)))
                 {
                     Ok(mut child) => {
                         let _ = child.wait();
@@ -2141,7 +2813,9 @@ impl Component for Composer {
                                     .args(["-c", command])
                                     .stdin(Stdio::null())
                                     .stdout(Stdio::from(std_file))
-                                    .spawn()?,
+                                    .spawn_checked(
+                                        &context.command_policy(Some(self.account_hash)),
+                                    )?,
                             ))
                         })
                         .and_then(|(f, child)| Ok((f, child.wait_with_output()?.stderr)));
@@ -2240,7 +2914,7 @@ impl Component for Composer {
                         .stdin(Stdio::inherit())
                         .stdout(Stdio::piped())
                         .stderr(Stdio::piped())
-                        .spawn()
+                        .spawn_checked(&context.command_policy(Some(self.account_hash)))
                         .and_then(|child| Ok(child.wait_with_output()?.stdout))
                     {
                         Ok(stdout) => {
@@ -2316,6 +2990,10 @@ impl Component for Composer {
                     return true;
                 }
                 ComposerTabAction::SaveDraft => {
+                    if self.confirm_external_change(context) {
+                        self.set_dirty(true);
+                        return true;
+                    }
                     save_draft(
                         self.draft.clone().finalise().unwrap().as_bytes(),
                         context,
@@ -2326,6 +3004,20 @@ impl Component for Composer {
                     self.set_dirty(true);
                     return true;
                 }
+                ComposerTabAction::Reformat => {
+                    let body = reformat_paragraphs(
+                        self.draft.body(),
+                        *account_settings!(context[self.account_hash].composing.wrap_column),
+                        *account_settings!(context[self.account_hash].composing.format_flowed),
+                    );
+                    if body != self.draft.body() {
+                        self.draft.set_body(body);
+                        self.has_changes = true;
+                        self.initialized = false;
+                    }
+                    self.set_dirty(true);
+                    return true;
+                }
                 ComposerTabAction::DiscardDraft => {
                     context
                         .replies
@@ -2397,11 +3089,16 @@ impl Component for Composer {
                     || self.pager.is_dirty()
                     || self.form.is_dirty()
             }
-            ViewMode::Edit => self.dirty || self.pager.is_dirty() || self.form.is_dirty(),
+            ViewMode::Edit => {
+                self.dirty
+                    || self.pager.is_dirty()
+                    || self.form.is_dirty()
+                    || (self.show_original && self.original.as_ref().is_some_and(Pager::is_dirty))
+            }
             ViewMode::Discard(_, ref widget) => {
                 widget.is_dirty() || self.pager.is_dirty() || self.form.is_dirty()
             }
-            ViewMode::SelectRecipients(ref widget) => {
+            ViewMode::SelectRecipients(ref widget) | ViewMode::SelectCc(ref widget) => {
                 widget.is_dirty() || self.pager.is_dirty() || self.form.is_dirty()
             }
             #[cfg(feature = "gpgme")]
@@ -2411,21 +3108,30 @@ impl Component for Composer {
             ViewMode::Send(ref widget) => {
                 widget.is_dirty() || self.pager.is_dirty() || self.form.is_dirty()
             }
+            ViewMode::Lint(ref widget) | ViewMode::ExternalChange(ref widget) => {
+                widget.is_dirty() || self.pager.is_dirty() || self.form.is_dirty()
+            }
             ViewMode::WaitingForSendResult(ref widget, _) => {
                 widget.is_dirty() || self.pager.is_dirty() || self.form.is_dirty()
             }
+            ViewMode::WaitingForPlugins(_) => {
+                self.dirty || self.pager.is_dirty() || self.form.is_dirty()
+            }
         }
     }
 
     fn set_dirty(&mut self, value: bool) {
         self.dirty = value;
         self.pager.set_dirty(value);
+        if let Some(ref mut original) = self.original {
+            original.set_dirty(value);
+        }
         self.form.set_dirty(value);
         match self.mode {
             ViewMode::Discard(_, ref mut widget) => {
                 widget.set_dirty(value);
             }
-            ViewMode::SelectRecipients(ref mut widget) => {
+            ViewMode::SelectRecipients(ref mut widget) | ViewMode::SelectCc(ref mut widget) => {
                 widget.set_dirty(value);
             }
             #[cfg(feature = "gpgme")]
@@ -2435,10 +3141,13 @@ impl Component for Composer {
             ViewMode::Send(ref mut widget) => {
                 widget.set_dirty(value);
             }
+            ViewMode::Lint(ref mut widget) | ViewMode::ExternalChange(ref mut widget) => {
+                widget.set_dirty(value);
+            }
             ViewMode::WaitingForSendResult(ref mut widget, _) => {
                 widget.set_dirty(value);
             }
-            ViewMode::Edit => {}
+            ViewMode::Edit | ViewMode::WaitingForPlugins(_) => {}
             ViewMode::EmbeddedPty => {
                 if let Some(pty) = self.embedded_pty.as_ref() {
                     if let Ok(mut guard) = pty.try_lock() {
@@ -2507,6 +3216,37 @@ impl Component for Composer {
         self.id
     }
 
+    fn view_name(&self) -> Option<&'static str> {
+        Some("composer")
+    }
+
+    /// The encoding and estimated size of the message, e.g. `7bit us-ascii, 120
+    /// characters, ~2.00 KiB`.
+    fn status(&self, _context: &Context) -> String {
+        let encoding = self.draft.body_encoding();
+        let mut ret = format!(
+            "{} {}, {} characters, ~{}",
+            encoding.content_transfer_encoding,
+            encoding.charset,
+            self.draft.body().chars().count(),
+            melib::BytesDisplay(self.draft.estimated_size())
+        );
+        if encoding.long_lines > 0 {
+            _ = write!(
+                ret,
+                " | WARNING: {} line{} longer than {} bytes",
+                encoding.long_lines,
+                if encoding.long_lines == 1 {
+                    " is"
+                } else {
+                    "s are"
+                },
+                melib::email::compose::MAX_LINE_LENGTH
+            );
+        }
+        ret
+    }
+
     fn can_quit_cleanly(&mut self, context: &Context) -> bool {
         if !self.has_changes {
             return true;
@@ -2548,6 +3288,18 @@ pub fn send_draft(
     complete_in_background: bool,
 ) -> Result<Option<JoinHandle<Result<()>>>> {
     let format_flowed = *account_settings!(context[account_hash].composing.format_flowed);
+    expand_distribution_lists(
+        &mut draft,
+        &distribution_lists(context, account_hash),
+        *account_settings!(context[account_hash].composing.distribution_lists_keep_name),
+    );
+    if *account_settings!(context[account_hash].composing.hard_wrap) {
+        draft.body = reformat_paragraphs(
+            &draft.body,
+            *account_settings!(context[account_hash].composing.wrap_column),
+            format_flowed,
+        );
+    }
     /*    if sign_mail.is_true() {
         let mut content_type = ContentType::default();
         if format_flowed {
@@ -2631,8 +3383,11 @@ pub fn send_draft(
             draft.attachments.insert(0, body);
         }
     }
+    let send_mail = context.accounts[&account_hash]
+        .settings
+        .send_mail_for(&draft)
+        .clone();
     let bytes = draft.finalise().unwrap();
-    let send_mail = account_settings!(context[account_hash].send_mail).clone();
     let ret =
         context.accounts[&account_hash].send(bytes.clone(), send_mail, complete_in_background);
     save_draft(bytes.as_bytes(), context, mailbox_type, flags, account_hash);
@@ -2686,6 +3441,18 @@ pub fn send_draft_async(
     let store_sent_mail = *account_settings!(context[account_hash].composing.store_sent_mail);
     let format_flowed = *account_settings!(context[account_hash].composing.format_flowed);
     let event_sender = context.main_loop_handler.sender.clone();
+    expand_distribution_lists(
+        &mut draft,
+        &distribution_lists(context, account_hash),
+        *account_settings!(context[account_hash].composing.distribution_lists_keep_name),
+    );
+    if *account_settings!(context[account_hash].composing.hard_wrap) {
+        draft.body = reformat_paragraphs(
+            &draft.body,
+            *account_settings!(context[account_hash].composing.wrap_column),
+            format_flowed,
+        );
+    }
     #[cfg(feature = "gpgme")]
     let mut filters_stack: Vec<AttachmentFilterBox> = vec![];
     #[cfg(feature = "gpgme")]
@@ -2707,6 +3474,18 @@ pub fn send_draft_async(
         .unwrap_or(ActionFlag::False)
         .is_true()
     {
+        let protected_headers = account_settings!(context[account_hash].pgp.protected_headers)
+            .then(|| {
+                let mut headers = melib::HeaderMap::empty();
+                for name in melib::email::pgp::PROTECTED_HEADERS {
+                    if let Some(value) = draft.headers().get(name.clone()) {
+                        headers.insert(name.clone(), value.to_string());
+                    }
+                }
+                headers
+            })
+            .filter(|headers| headers.contains_key(HeaderName::SUBJECT));
+        let protect_subject = protected_headers.is_some();
         filters_stack.push(Box::new(crate::mail::pgp::encrypt_filter(
             gpg_state.encrypt_for_self.then_some(()).map_or_else(
                 || Ok(None),
@@ -2732,9 +3511,19 @@ pub fn send_draft_async(
                 .then(|| account_settings!(context[account_hash].pgp.encrypt_key).clone())
                 .flatten(),
             gpg_state.encrypt_keys,
+            protected_headers,
         )?));
+        if protect_subject {
+            draft.set_header(
+                HeaderName::SUBJECT,
+                melib::email::pgp::PROTECTED_SUBJECT_PLACEHOLDER.to_string(),
+            );
+        }
     }
-    let send_mail = account_settings!(context[account_hash].send_mail).clone();
+    let send_mail = context.accounts[&account_hash]
+        .settings
+        .send_mail_for(&draft)
+        .clone();
     let send_cb = context.accounts[&account_hash].send_async(send_mail);
     let mut content_type = ContentType::default();
     if let (
@@ -2811,6 +3600,401 @@ pub fn send_draft_async(
     }))
 }
 
+/// Check that `value` is a list of addresses, and return a message describing
+/// the problem if it is not.
+///
+/// Empty values and trailing separators are accepted.
+fn validate_address_list(value: &str) -> Option<String> {
+    if value.trim().is_empty() {
+        return None;
+    }
+    match melib::email::parser::address::rfc2822address_list(value.as_bytes()) {
+        Ok((rest, list)) if rest.iter().all(|b| b.is_ascii_whitespace() || *b == b',') => list
+            .iter()
+            .map(Address::get_email)
+            .find(|a| !a.is_empty() && !a.contains('@'))
+            .map(|a| format!("address has no domain: {a}")),
+        Ok((rest, _)) => Some(format!(
+            "invalid address: {}",
+            String::from_utf8_lossy(rest).trim_matches(|c: char| c.is_whitespace() || c == ',')
+        )),
+        Err(_) => Some("invalid address".to_string()),
+    }
+}
+
+/// Split an address list header value on its top level commas, that is the ones
+/// not inside quoted strings, angle brackets or groups.
+fn split_address_list(value: &str) -> Vec<&str> {
+    let mut ret = vec![];
+    let (mut in_quotes, mut escaped, mut in_angle, mut in_group) = (false, false, false, false);
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            _ if in_quotes => {}
+            '<' => in_angle = true,
+            '>' => in_angle = false,
+            ':' if !in_angle => in_group = true,
+            ';' if !in_angle => in_group = false,
+            ',' if !in_angle && !in_group => {
+                ret.push(value[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    ret.push(value[start..].trim());
+    ret.retain(|s| !s.is_empty());
+    ret
+}
+
+/// Whether mailboxes `a` and `b` have the same e-mail address.
+fn same_mailbox(a: &Address, b: &Address) -> bool {
+    a.get_email().eq_ignore_ascii_case(&b.get_email())
+}
+
+/// The senders and recipients of e-mail `env_hash` and of the e-mail of its
+/// thread that it references, oldest first, without the account's own
+/// addresses.
+fn thread_participants(
+    context: &Context,
+    (account_hash, mailbox_hash, env_hash): (AccountHash, MailboxHash, EnvelopeHash),
+) -> Vec<Address> {
+    let account = &context.accounts[&account_hash];
+    let collection = &account.collection;
+    let references = {
+        let envelope = collection.get_env(env_hash);
+        let mut references = envelope.references().to_vec();
+        if let Some(in_reply_to) = envelope.in_reply_to() {
+            for id in in_reply_to.refs() {
+                if !references.contains(id) {
+                    references.push(id.clone());
+                }
+            }
+        }
+        references
+    };
+    let thread_envelopes: Vec<EnvelopeHash> = if collection.has_threads(mailbox_hash) {
+        let threads = collection.get_threads(mailbox_hash);
+        threads
+            .envelope_to_thread
+            .get(&env_hash)
+            .map(|thread| {
+                threads
+                    .thread_iter(*thread)
+                    .filter_map(|(_, h)| threads.thread_nodes()[&h].message())
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        vec![]
+    };
+    let mut chain = thread_envelopes
+        .into_iter()
+        .filter_map(|h| {
+            let envelope = collection.get_env(h);
+            references
+                .iter()
+                .position(|id| id == envelope.message_id())
+                .map(|pos| (pos, h))
+        })
+        .collect::<Vec<_>>();
+    chain.sort();
+    chain.push((references.len(), env_hash));
+    let ours = std::iter::once(account.settings.account().main_identity_address())
+        .chain(account.settings.account().extra_identity_addresses())
+        .collect::<Vec<Address>>();
+    let mut ret: Vec<Address> = vec![];
+    for (_, h) in chain {
+        let envelope = collection.get_env(h);
+        for mailbox in envelope
+            .from()
+            .iter()
+            .chain(envelope.to())
+            .chain(envelope.cc())
+            .flat_map(Address::mailboxes)
+        {
+            if !ours.iter().any(|o| same_mailbox(o, mailbox))
+                && !ret.iter().any(|a| same_mailbox(a, mailbox))
+            {
+                ret.push(mailbox.clone());
+            }
+        }
+    }
+    ret
+}
+
+/// Collect the distribution lists of an account: the contacts of its address
+/// book that have more than one e-mail address, and the lists of the
+/// `composing.distribution_lists` setting, which take precedence.
+fn distribution_lists(
+    context: &Context,
+    account_hash: AccountHash,
+) -> IndexMap<String, Vec<String>> {
+    let mut ret: IndexMap<String, Vec<String>> = context.accounts[&account_hash]
+        .contacts
+        .values()
+        .filter(|c| !c.name.is_empty())
+        .filter_map(|c| {
+            let addresses = Address::list_try_from(c.email.as_str()).ok()?;
+            if addresses.iter().flat_map(Address::mailboxes).count() < 2 {
+                return None;
+            }
+            Some((
+                c.name.clone(),
+                addresses.iter().map(|a| a.to_string()).collect(),
+            ))
+        })
+        .collect();
+    for (name, members) in account_settings!(context[account_hash].composing.distribution_lists) {
+        ret.insert(name.clone(), members.clone());
+    }
+    ret
+}
+
+/// Expand the names of distribution `lists` in the recipient headers of
+/// `draft` to the lists' members.
+///
+/// A list name written as an empty group (e.g. `team:;`), or any list name if
+/// `keep_name` is true, is kept in the `To` and `Cc` headers as an empty group
+/// and its members are added to `Bcc` instead.
+fn expand_distribution_lists(
+    draft: &mut Draft,
+    lists: &IndexMap<String, Vec<String>>,
+    keep_name: bool,
+) {
+    if lists.is_empty() {
+        return;
+    }
+    let mut extra_bcc = vec![];
+    for header in [HeaderName::TO, HeaderName::CC, HeaderName::BCC] {
+        let Some(value) = draft.headers().get(header.clone()) else {
+            continue;
+        };
+        let mut changed = false;
+        let mut new_value = vec![];
+        for item in split_address_list(value) {
+            let (name, empty_group) = match item.strip_suffix(":;") {
+                Some(name) => (name.trim(), true),
+                None => (item, false),
+            };
+            let Some((name, members)) = lists
+                .iter()
+                .find(|(list, _)| list.eq_ignore_ascii_case(name))
+            else {
+                new_value.push(item.to_string());
+                continue;
+            };
+            changed = true;
+            if (empty_group || keep_name) && header != HeaderName::BCC {
+                new_value.push(format!("{name}:;"));
+                extra_bcc.extend(members.iter().cloned());
+            } else {
+                new_value.extend(members.iter().cloned());
+            }
+        }
+        if changed {
+            draft.set_header(header, new_value.join(", "));
+        }
+    }
+    if !extra_bcc.is_empty() {
+        let mut bcc = draft
+            .headers()
+            .get(HeaderName::BCC)
+            .map(|v| {
+                split_address_list(v)
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        for member in extra_bcc {
+            if !bcc.contains(&member) {
+                bcc.push(member);
+            }
+        }
+        draft.set_header(HeaderName::BCC, bcc.join(", "));
+    }
+}
+
+/// Quote `text` for a reply, prefixing each line with `prefix`.
+///
+/// If `strip_signature` is true, the text after a `-- ` signature delimiter is
+/// left out. Lines that are already quoted `depth_limit` times or more are left
+/// out as well.
+fn quote_reply_body(
+    text: &str,
+    prefix: &str,
+    strip_signature: bool,
+    depth_limit: Option<usize>,
+) -> String {
+    let mut ret = String::with_capacity(text.len() + text.len() / 8);
+    for l in text.lines() {
+        if strip_signature && l == "-- " {
+            break;
+        }
+        if depth_limit.is_some_and(|limit| BodyLineKind::quote_depth(l) >= limit) {
+            continue;
+        }
+        if l.is_empty() {
+            ret.push_str(prefix.trim_end());
+        } else {
+            ret.push_str(prefix);
+            ret.push_str(l);
+        }
+        ret.push('\n');
+    }
+    ret
+}
+
+/// Split `line` into its quote prefix, the `>` characters at its start along
+/// with the spaces that follow them, and the rest of the line.
+fn split_quote_prefix(line: &str) -> (&str, &str) {
+    if !line.starts_with('>') {
+        return ("", line);
+    }
+    let end = line
+        .find(|c: char| c != '>' && c != ' ')
+        .unwrap_or(line.len());
+    line.split_at(end)
+}
+
+/// Whether `text` starts a list item, e.g. `- item`, `* item` or `1. item`.
+fn is_list_item(text: &str) -> bool {
+    let digits = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(0);
+    text.starts_with("- ")
+        || text.starts_with("* ")
+        || (digits > 0 && (text[digits..].starts_with(". ") || text[digits..].starts_with(") ")))
+}
+
+/// Reformat the paragraphs of `text` so that their lines are at most `width`
+/// columns wide, like `gq` in vim.
+///
+/// A paragraph is a run of non-empty lines with the same quote depth; its
+/// lines are joined and wrapped again with the quote prefix of its first line.
+/// List items start a new paragraph. Indented lines that are not the
+/// continuation of a list item, patches and the text after a `-- ` signature
+/// delimiter are left as they are. If `flowed` is true, wrapped lines end with a space
+/// as soft line breaks of `format=flowed`.
+fn reformat_paragraphs(text: &str, width: usize, flowed: bool) -> String {
+    fn flush(
+        ret: &mut String,
+        paragraph: &mut Option<(String, String)>,
+        width: usize,
+        flowed: bool,
+    ) {
+        let Some((prefix, words)) = paragraph.take() else {
+            return;
+        };
+        let mut line_width = 0;
+        for word in words.split_whitespace() {
+            let word_width = word.grapheme_width();
+            if line_width == 0 {
+                ret.push_str(&prefix);
+                line_width = prefix.grapheme_width();
+            } else if line_width + 1 + word_width > width {
+                if flowed {
+                    ret.push(' ');
+                }
+                ret.push('\n');
+                ret.push_str(&prefix);
+                line_width = prefix.grapheme_width();
+            } else {
+                ret.push(' ');
+                line_width += 1;
+            }
+            ret.push_str(word);
+            line_width += word_width;
+        }
+        ret.push('\n');
+    }
+
+    let mut ret = String::with_capacity(text.len());
+    /* The quote prefix and the words of the current paragraph. */
+    let mut paragraph: Option<(String, String)> = None;
+    let mut in_list_item = false;
+    let kinds = BodyLineKind::classify(text);
+    let mut kind_idx = 0;
+    let mut offset = 0;
+    let mut lines = text.split_inclusive('\n');
+    for line in lines.by_ref() {
+        while kinds
+            .get(kind_idx + 1)
+            .is_some_and(|(start, _)| *start <= offset)
+        {
+            kind_idx += 1;
+        }
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+        if matches!(
+            kinds.get(kind_idx),
+            Some((
+                _,
+                BodyLineKind::DiffHeader
+                    | BodyLineKind::DiffHunk
+                    | BodyLineKind::DiffAdded
+                    | BodyLineKind::DiffRemoved
+            ))
+        ) {
+            flush(&mut ret, &mut paragraph, width, flowed);
+            in_list_item = false;
+            ret.push_str(line);
+            ret.push('\n');
+            continue;
+        }
+        if line == "-- " {
+            flush(&mut ret, &mut paragraph, width, flowed);
+            ret.push_str(line);
+            ret.push('\n');
+            break;
+        }
+        let (prefix, rest) = split_quote_prefix(line);
+        let depth = BodyLineKind::quote_depth(prefix);
+        let rest = rest.trim_end();
+        let continues = paragraph
+            .as_ref()
+            .is_some_and(|(p, _)| BodyLineKind::quote_depth(p) == depth);
+        if rest.is_empty() {
+            flush(&mut ret, &mut paragraph, width, flowed);
+            in_list_item = false;
+            ret.push_str(line.trim_end());
+            ret.push('\n');
+        } else if rest.starts_with(char::is_whitespace) && !(continues && in_list_item) {
+            flush(&mut ret, &mut paragraph, width, flowed);
+            in_list_item = false;
+            ret.push_str(line.trim_end());
+            ret.push('\n');
+        } else if continues && !is_list_item(rest) {
+            if let Some((_, ref mut words)) = paragraph {
+                words.push(' ');
+                words.push_str(rest.trim_start());
+            }
+        } else {
+            flush(&mut ret, &mut paragraph, width, flowed);
+            in_list_item = is_list_item(rest);
+            let mut prefix = prefix.to_string();
+            if !prefix.is_empty() && !prefix.ends_with(' ') {
+                prefix.push(' ');
+            }
+            paragraph = Some((prefix, rest.to_string()));
+        }
+    }
+    flush(&mut ret, &mut paragraph, width, flowed);
+    for line in lines {
+        ret.push_str(line.trim_end_matches(['\n', '\r']));
+        ret.push('\n');
+    }
+    if !text.ends_with('\n') {
+        ret.pop();
+    }
+    ret
+}
+
 /* Sender details
  * %+f — the sender's name and email address.
  * %+n — the sender's name (or email address, if no name is included).
@@ -2848,6 +4032,52 @@ fn attribution_string(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_compose_thread_participants() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let context = Context::new_mock(&tempdir);
+        let account_hash = context.accounts[0].hash();
+        let mailbox_hash = MailboxHash::default();
+        let mut hashes = vec![];
+        for raw_mail in [
+            "From: Alice <alice@example.com>\nTo: username@example.com\nCc: Bob \
+             <bob@example.com>\nSubject: plans\nMessage-ID: <a@example.com>\n\nhello\n",
+            "From: Dave <dave@example.com>\nTo: alice@example.com\nSubject: Re: \
+             plans\nMessage-ID: <c@example.com>\nIn-Reply-To: <a@example.com>\nReferences: \
+             <a@example.com>\n\nhello\n",
+            "From: Carol <carol@example.com>\nTo: Alice <alice@example.com>, \
+             username@example.com\nSubject: Re: plans\nMessage-ID: \
+             <b@example.com>\nIn-Reply-To: <a@example.com>\nReferences: \
+             <a@example.com>\n\nhello\n",
+        ] {
+            let envelope =
+                Envelope::from_bytes(raw_mail.as_bytes(), None).expect("Could not parse mail");
+            hashes.push(envelope.hash());
+            context.accounts[0]
+                .collection
+                .insert(envelope, mailbox_hash);
+        }
+        // Dave replied to the same e-mail, but isn't referenced by Carol's reply.
+        let composer = Composer::reply_to(
+            (account_hash, mailbox_hash, hashes[2]),
+            String::new(),
+            &context,
+            false,
+        );
+        assert_eq!(
+            composer
+                .thread_participants
+                .iter()
+                .map(|a| a.get_email())
+                .collect::<Vec<_>>(),
+            vec!["alice@example.com", "bob@example.com", "carol@example.com"]
+        );
+        assert!(same_mailbox(
+            &Address::try_from("ALICE@example.com").unwrap(),
+            &composer.thread_participants[0]
+        ));
+    }
+
     #[test]
     fn test_compose_reply_subject_prefix() {
         let raw_mail = r#"From: "some name" <some@example.com>
@@ -2914,4 +4144,99 @@ hello world.
             r#"some name <some@example.com>"#
         );
     }
+
+    #[test]
+    fn test_compose_quote_reply_body() {
+        const BODY: &str = "Hello,\n\n> earlier\n> > much earlier\n\nBye\n-- \nsignature\n";
+
+        assert_eq!(
+            quote_reply_body(BODY, ">", false, None),
+            ">Hello,\n>\n>> earlier\n>> > much earlier\n>\n>Bye\n>-- \n>signature\n"
+        );
+        assert_eq!(
+            quote_reply_body(BODY, "> ", true, None),
+            "> Hello,\n>\n> > earlier\n> > > much earlier\n>\n> Bye\n"
+        );
+        assert_eq!(
+            quote_reply_body(BODY, "> ", true, Some(2)),
+            "> Hello,\n>\n> > earlier\n>\n> Bye\n"
+        );
+        assert_eq!(
+            quote_reply_body(BODY, "> ", false, Some(1)),
+            "> Hello,\n>\n>\n> Bye\n> -- \n> signature\n"
+        );
+    }
+
+    #[test]
+    fn test_compose_reformat_paragraphs() {
+        const BODY: &str = "Hello there, this is a long paragraph\nthat continues here.\n\n> \
+                            quoted text that is quite long and\n> continues\n>> deeper quote \
+                            line\n- item one is long enough to wrap\n  continued\n- item \
+                            two\n\n    indented code\n--- a/x\n+++ b/x\n@@ -1 +1 @@\n-old line \
+                            that is very very long\n+new\n-- \nsignature that is long long long";
+
+        let reformatted = reformat_paragraphs(BODY, 20, false);
+        assert_eq!(
+            reformatted,
+            "Hello there, this is\na long paragraph\nthat continues here.\n\n> quoted text \
+             that\n> is quite long and\n> continues\n>> deeper quote line\n- item one is \
+             long\nenough to wrap\ncontinued\n- item two\n\n    indented code\n--- a/x\n+++ \
+             b/x\n@@ -1 +1 @@\n-old line that is very very long\n+new\n-- \nsignature that is \
+             long long long"
+        );
+        assert_eq!(reformat_paragraphs(&reformatted, 20, false), reformatted);
+        assert_eq!(
+            reformat_paragraphs("a few words to wrap\n", 10, true),
+            "a few \nwords to \nwrap\n"
+        );
+    }
+
+    #[test]
+    fn test_compose_expand_distribution_lists() {
+        assert_eq!(
+            split_address_list(
+                r#""Doe, John" <john@example.com>, team:;, friends: a@example.com, b@example.com;,"#
+            ),
+            vec![
+                r#""Doe, John" <john@example.com>"#,
+                "team:;",
+                "friends: a@example.com, b@example.com;",
+            ]
+        );
+
+        let lists: IndexMap<String, Vec<String>> = [(
+            "team".to_string(),
+            vec!["a@example.com".to_string(), "B <b@example.com>".to_string()],
+        )]
+        .into_iter()
+        .collect();
+
+        let mut draft = Draft::default();
+        draft.set_header(HeaderName::TO, "Team, c@example.com".into());
+        expand_distribution_lists(&mut draft, &lists, false);
+        assert_eq!(
+            &draft.headers()[HeaderName::TO],
+            "a@example.com, B <b@example.com>, c@example.com"
+        );
+        assert_eq!(&draft.headers()[HeaderName::BCC], "");
+
+        let mut draft = Draft::default();
+        draft.set_header(HeaderName::CC, "team:;".into());
+        draft.set_header(HeaderName::BCC, "a@example.com".into());
+        expand_distribution_lists(&mut draft, &lists, false);
+        assert_eq!(&draft.headers()[HeaderName::CC], "team:;");
+        assert_eq!(
+            &draft.headers()[HeaderName::BCC],
+            "a@example.com, B <b@example.com>"
+        );
+
+        let mut draft = Draft::default();
+        draft.set_header(HeaderName::TO, "team".into());
+        expand_distribution_lists(&mut draft, &lists, true);
+        assert_eq!(&draft.headers()[HeaderName::TO], "team:;");
+        assert_eq!(
+            &draft.headers()[HeaderName::BCC],
+            "a@example.com, B <b@example.com>"
+        );
+    }
 }
@@ -0,0 +1,246 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Detection of changes made by other programs, e.g. `mbsync` or another
+//! client, to a stored message that is being edited in a composer.
+//!
+//! The mailbox watchers report such changes as envelope updates and removals.
+//! Saving the draft after a change asks whether to reload the changed
+//! message or to save the composer's contents as a new draft.
+
+use melib::MessageID;
+
+use super::*;
+
+/// How the edited message was changed by another program.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExternalChange {
+    Modified,
+    Removed,
+}
+
+/// A stored message, e.g. a draft, that a [`Composer`] was opened to edit.
+#[derive(Debug)]
+pub struct EditedEnvelope {
+    pub env_hash: EnvelopeHash,
+    /// Headers of the message when it was opened, to tell content changes
+    /// apart from flag changes.
+    headers: (MessageID, String, UnixTimestamp),
+    pub change: Option<ExternalChange>,
+    reload: Option<JoinHandle<Result<Vec<u8>>>>,
+}
+
+impl EditedEnvelope {
+    pub fn new(envelope: &Envelope) -> Self {
+        Self {
+            env_hash: envelope.hash(),
+            headers: Self::headers_of(envelope),
+            change: None,
+            reload: None,
+        }
+    }
+
+    fn headers_of(envelope: &Envelope) -> (MessageID, String, UnixTimestamp) {
+        (
+            envelope.message_id().clone(),
+            envelope.subject().to_string(),
+            envelope.date(),
+        )
+    }
+}
+
+impl Composer {
+    /// Track envelope events concerning the edited message.
+    ///
+    /// Returns `true` if the event was consumed.
+    pub(super) fn process_external_change(
+        &mut self,
+        event: &UIEvent,
+        context: &mut Context,
+    ) -> bool {
+        let Some(edited) = self.edited.as_mut() else {
+            return false;
+        };
+        let change = match event {
+            UIEvent::EnvelopeRename(old_hash, new_hash) if *old_hash == edited.env_hash => {
+                edited.env_hash = *new_hash;
+                return false;
+            }
+            UIEvent::EnvelopeUpdate(env_hash) if *env_hash == edited.env_hash => {
+                let account = &context.accounts[&self.account_hash];
+                if !account.collection.contains_key(env_hash)
+                    || EditedEnvelope::headers_of(&account.collection.get_env(*env_hash))
+                        == edited.headers
+                {
+                    return false;
+                }
+                ExternalChange::Modified
+            }
+            UIEvent::EnvelopeRemove(env_hash, _) if *env_hash == edited.env_hash => {
+                ExternalChange::Removed
+            }
+            UIEvent::StatusEvent(StatusEvent::JobFinished(job_id))
+                if edited.reload.as_ref().is_some_and(|h| h.job_id == *job_id) =>
+            {
+                let mut handle = edited.reload.take().unwrap();
+                match handle.chan.try_recv() {
+                    Err(_) | Ok(None) => {}
+                    Ok(Some(result)) => {
+                        if let Err(err) = self.reload_edited(result, context) {
+                            context.replies.push_back(UIEvent::Notification {
+                                title: Some("Could not reload draft".into()),
+                                source: None,
+                                body: err.to_string().into(),
+                                kind: Some(NotificationType::Error(err.kind)),
+                            });
+                        }
+                    }
+                }
+                self.set_dirty(true);
+                return true;
+            }
+            _ => return false,
+        };
+        if edited.change == Some(ExternalChange::Removed) {
+            return false;
+        }
+        edited.change = Some(change);
+        context.replies.push_back(UIEvent::Notification {
+            title: Some("Draft changed by another program".into()),
+            source: None,
+            body: match change {
+                ExternalChange::Modified => {
+                    "The message being edited was modified by another program. You will be asked \
+                     before saving over it."
+                }
+                ExternalChange::Removed => {
+                    "The message being edited was removed or replaced by another program. You \
+                     will be asked before saving it again."
+                }
+            }
+            .into(),
+            kind: Some(NotificationType::Info),
+        });
+        self.set_dirty(true);
+        false
+    }
+
+    /// If the edited message was changed by another program, ask how to save
+    /// the draft instead of saving it.
+    ///
+    /// Returns `true` if the user was asked.
+    pub(super) fn confirm_external_change(&mut self, context: &mut Context) -> bool {
+        let Some(change) = self.edited.as_ref().and_then(|e| e.change) else {
+            return false;
+        };
+        let mut entries = vec![];
+        let title = match change {
+            ExternalChange::Modified => {
+                entries.push((
+                    'r',
+                    "reload the changed message, discarding the changes made here".to_string(),
+                ));
+                "this message was modified by another program"
+            }
+            ExternalChange::Removed => "this message was removed by another program",
+        };
+        entries.push(('n', "save as a new draft".to_string()));
+        entries.push(('c', "cancel".to_string()));
+        self.mode = ViewMode::ExternalChange(UIDialog::new(
+            title,
+            entries,
+            true,
+            Some(Box::new(move |id: ComponentId, results: &[char]| {
+                Some(UIEvent::FinishedUIDialog(
+                    id,
+                    Box::new(results.first().copied().unwrap_or('c')),
+                ))
+            })),
+            context,
+        ));
+        true
+    }
+
+    /// Handle the choice made in the dialog opened by
+    /// [`Composer::confirm_external_change`].
+    pub(super) fn resolve_external_change(&mut self, choice: char, context: &mut Context) {
+        match choice {
+            'r' => {
+                let Some(edited) = self.edited.as_mut() else {
+                    return;
+                };
+                let account = &context.accounts[&self.account_hash];
+                match account.envelope_bytes_by_hash(edited.env_hash) {
+                    Ok(fut) => {
+                        let handle = context.main_loop_handler.job_executor.spawn(
+                            "compose::reload-draft".into(),
+                            fut,
+                            account.is_async(),
+                        );
+                        context
+                            .replies
+                            .push_back(UIEvent::StatusEvent(StatusEvent::NewJob(handle.job_id)));
+                        edited.reload = Some(handle);
+                    }
+                    Err(err) => {
+                        context.replies.push_back(UIEvent::Notification {
+                            title: Some("Could not reload draft".into()),
+                            source: None,
+                            body: err.to_string().into(),
+                            kind: Some(NotificationType::Error(err.kind)),
+                        });
+                    }
+                }
+            }
+            'n' => {
+                self.edited = None;
+                save_draft(
+                    self.draft.clone().finalise().unwrap().as_bytes(),
+                    context,
+                    SpecialUsageMailbox::Drafts,
+                    Flag::SEEN | Flag::DRAFT,
+                    self.account_hash,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn reload_edited(&mut self, bytes: Result<Vec<u8>>, context: &mut Context) -> Result<()> {
+        let bytes = bytes?;
+        let Some(edited) = self.edited.as_mut() else {
+            return Ok(());
+        };
+        let account = &context.accounts[&self.account_hash];
+        if !account.collection.contains_key(&edited.env_hash) {
+            return Err(Error::new("The message is no longer available."));
+        }
+        let envelope = account.collection.get_env(edited.env_hash);
+        let draft = Draft::edit(&envelope, &bytes, Text::Plain)?;
+        *edited = EditedEnvelope::new(&envelope);
+        drop(envelope);
+        self.set_draft(draft, context);
+        self.has_changes = false;
+        self.initialized = false;
+        Ok(())
+    }
+}
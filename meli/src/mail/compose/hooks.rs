@@ -91,7 +91,7 @@ impl Hook {
         let name_ = name.clone();
         Self {
             name,
-            hook_fn: HookFn::Closure(Box::new(move |_, draft| -> Result<()> {
+            hook_fn: HookFn::Closure(Box::new(move |context, draft| -> Result<()> {
                 use std::thread;
 
                 let mut child = Command::new("sh")
@@ -100,7 +100,7 @@ impl Hook {
                     .stdin(Stdio::piped())
                     .stdout(Stdio::piped())
                     .stderr(Stdio::piped())
-                    .spawn()
+                    .spawn_checked(&context.command_policy(None))
                     .map_err(|err| -> Error {
                         Error::new(format!(
                             "could not execute `{command}`. Check if its binary is in PATH or if \
@@ -0,0 +1,383 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Per-recipient encryption policy, consulted when sending.
+//!
+//! Each recipient gets a [`Protection`] from the first of these that applies:
+//!
+//! 1. Their rule in `pgp.recipient_policies`, looked up by address, then by
+//!    domain and its parent domains, then by `*`.
+//! 2. Their Autocrypt state: recipients who asked for encryption with
+//!    `prefer-encrypt=mutual` in the e-mail being replied to are encrypted to
+//!    if they have a usable key.
+//! 3. The sign and encrypt toggles of the composer.
+//!
+//! A message is a single copy for all recipients, so it is only encrypted if
+//! every recipient can and should get an encrypted copy. Otherwise everyone
+//! gets the same unencrypted copy. It is signed if the composer's sign toggle
+//! is on or if any recipient calls for a signature.
+
+use std::borrow::Cow;
+
+use indexmap::IndexMap;
+use melib::{Address, HeaderMap};
+
+use crate::conf::RecipientPolicy;
+
+/// How a recipient wants their copy of a message to be protected.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Protection {
+    Encrypt,
+    Sign,
+    Plain,
+}
+
+/// What a recipient calls for, and why.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecipientDecision {
+    pub address: Address,
+    /// `None` if the recipient must be encrypted to but has no usable key.
+    pub protection: Option<Protection>,
+    pub reason: Cow<'static, str>,
+}
+
+/// The rule of `rules` that applies to `address`, and its key.
+pub fn rule_for<'r>(
+    rules: &'r IndexMap<String, RecipientPolicy>,
+    address: &Address,
+) -> Option<(&'r str, RecipientPolicy)> {
+    let email = address.get_email().to_ascii_lowercase();
+    let mut domain = address.get_fqdn().map(|d| d.to_ascii_lowercase());
+    let lookup = |key: &str| {
+        rules
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(k, v)| (k.as_str(), *v))
+    };
+    if let Some(rule) = lookup(&email) {
+        return Some(rule);
+    }
+    while let Some(d) = domain {
+        if let Some(rule) = lookup(&d) {
+            return Some(rule);
+        }
+        domain = d.split_once('.').map(|(_, parent)| parent.to_string());
+    }
+    lookup("*")
+}
+
+/// The addresses that sent an Autocrypt header with `prefer-encrypt=mutual`
+/// in `headers`, which must be those of a received e-mail.
+pub fn autocrypt_mutual(headers: &HeaderMap) -> Vec<Address> {
+    let Some(from) = headers
+        .get(melib::HeaderName::FROM)
+        .and_then(|f| Address::list_try_from(f).ok())
+    else {
+        return vec![];
+    };
+    let Some(autocrypt) = headers.get("Autocrypt") else {
+        return vec![];
+    };
+    let mut addr = None;
+    let mut mutual = false;
+    for attribute in autocrypt.split(';') {
+        match attribute.trim().split_once('=') {
+            Some((name, value)) if name.trim().eq_ignore_ascii_case("addr") => {
+                addr = Some(value.trim().to_ascii_lowercase());
+            }
+            Some((name, value)) if name.trim().eq_ignore_ascii_case("prefer-encrypt") => {
+                mutual = value.trim().eq_ignore_ascii_case("mutual");
+            }
+            _ => {}
+        }
+    }
+    // The header only counts for the sender it was sent by.
+    from.into_iter()
+        .filter(|f| mutual && addr.as_deref() == Some(f.get_email().to_ascii_lowercase().as_str()))
+        .collect()
+}
+
+/// Decide how to protect the copy of `address`. `has_key` is whether a usable
+/// key of the recipient was found, `autocrypt` whether they asked for
+/// encryption with Autocrypt and `default` what the composer's toggles call
+/// for.
+pub fn decide(
+    rules: &IndexMap<String, RecipientPolicy>,
+    address: &Address,
+    has_key: bool,
+    autocrypt: bool,
+    default: Protection,
+) -> RecipientDecision {
+    let (protection, reason): (Option<Protection>, Cow<'static, str>) =
+        match rule_for(rules, address) {
+            Some((rule, RecipientPolicy::Encrypt)) if has_key => {
+                (Some(Protection::Encrypt), format!("rule `{rule}`").into())
+            }
+            Some((rule, RecipientPolicy::Encrypt)) => {
+                (None, format!("rule `{rule}`, but no usable key").into())
+            }
+            Some((rule, RecipientPolicy::Opportunistic)) if has_key => (
+                Some(Protection::Encrypt),
+                format!("rule `{rule}`, key available").into(),
+            ),
+            Some((rule, RecipientPolicy::Opportunistic)) => (
+                Some(Protection::Plain),
+                format!("rule `{rule}`, no usable key").into(),
+            ),
+            Some((rule, RecipientPolicy::Sign)) => {
+                (Some(Protection::Sign), format!("rule `{rule}`").into())
+            }
+            Some((rule, RecipientPolicy::Plain)) => {
+                (Some(Protection::Plain), format!("rule `{rule}`").into())
+            }
+            None if autocrypt && has_key => (
+                Some(Protection::Encrypt),
+                "Autocrypt: prefers encryption".into(),
+            ),
+            None if default == Protection::Encrypt && !has_key => {
+                (None, "encryption is on, but no usable key".into())
+            }
+            None => (Some(default), "composer setting".into()),
+        };
+    RecipientDecision {
+        address: address.clone(),
+        protection,
+        reason,
+    }
+}
+
+/// The protection of a message with recipients `decisions`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SendPlan {
+    pub decisions: Vec<RecipientDecision>,
+    /// Whether the composer's sign toggle is on.
+    pub sign: bool,
+}
+
+impl SendPlan {
+    /// Recipients who must be encrypted to but can't be.
+    pub fn blocked(&self) -> impl Iterator<Item = &RecipientDecision> {
+        self.decisions.iter().filter(|d| d.protection.is_none())
+    }
+
+    pub fn encrypt(&self) -> bool {
+        !self.decisions.is_empty()
+            && self
+                .decisions
+                .iter()
+                .all(|d| d.protection == Some(Protection::Encrypt))
+    }
+
+    pub fn sign(&self) -> bool {
+        self.sign
+            || self
+                .decisions
+                .iter()
+                .any(|d| d.protection == Some(Protection::Sign))
+    }
+
+    /// What every recipient's copy will be.
+    pub fn description(&self) -> &'static str {
+        match (self.encrypt(), self.sign()) {
+            (true, true) => "encrypted and signed",
+            (true, false) => "encrypted",
+            (false, true) => "signed",
+            (false, false) => "plain text",
+        }
+    }
+
+    /// A line per recipient with what they will receive and why, and a
+    /// description of the whole message.
+    pub fn summary(&self) -> String {
+        let description = self.description();
+        let mut lines = self
+            .decisions
+            .iter()
+            .map(|d| {
+                let receives = match d.protection {
+                    None => "not sent",
+                    Some(_) => description,
+                };
+                format!("{}: {receives} ({})", d.address, d.reason)
+            })
+            .collect::<Vec<_>>();
+        let blocked = self.blocked().count();
+        if blocked > 0 {
+            lines.push(format!(
+                "The message can't be sent: {blocked} recipient{} must be encrypted to but \
+                 {} no usable key.",
+                if blocked == 1 { "" } else { "s" },
+                if blocked == 1 { "has" } else { "have" },
+            ));
+        } else if !self.encrypt()
+            && self
+                .decisions
+                .iter()
+                .any(|d| d.protection == Some(Protection::Encrypt))
+        {
+            lines.push(
+                "Not every recipient can receive an encrypted copy, so the message will be \
+                 sent unencrypted to everyone."
+                    .to_string(),
+            );
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(list: &[(&str, RecipientPolicy)]) -> IndexMap<String, RecipientPolicy> {
+        list.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn test_compose_policy_rule_for() {
+        let rules = rules(&[
+            ("boss@example.com", RecipientPolicy::Plain),
+            ("example.com", RecipientPolicy::Encrypt),
+            ("*", RecipientPolicy::Opportunistic),
+        ]);
+        let rule = |a: &str| rule_for(&rules, &Address::try_from(a).unwrap());
+        assert_eq!(
+            rule("Boss <BOSS@example.com>"),
+            Some(("boss@example.com", RecipientPolicy::Plain))
+        );
+        assert_eq!(
+            rule("alice@mail.example.com"),
+            Some(("example.com", RecipientPolicy::Encrypt))
+        );
+        assert_eq!(
+            rule("bob@example.org"),
+            Some(("*", RecipientPolicy::Opportunistic))
+        );
+        assert_eq!(
+            rule_for(
+                &IndexMap::default(),
+                &Address::try_from("bob@example.org").unwrap()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_compose_policy_plan() {
+        let rules = rules(&[
+            ("example.com", RecipientPolicy::Encrypt),
+            ("example.org", RecipientPolicy::Opportunistic),
+        ]);
+        let alice = Address::try_from("alice@example.com").unwrap();
+        let bob = Address::try_from("bob@example.org").unwrap();
+        let carol = Address::try_from("carol@example.net").unwrap();
+
+        let plan = SendPlan {
+            decisions: vec![
+                decide(&rules, &alice, true, false, Protection::Plain),
+                decide(&rules, &bob, true, false, Protection::Plain),
+                decide(&rules, &carol, true, true, Protection::Plain),
+            ],
+            sign: false,
+        };
+        assert!(plan.encrypt());
+        assert!(!plan.sign());
+        assert_eq!(plan.blocked().count(), 0);
+        assert_eq!(
+            plan.summary(),
+            "alice@example.com: encrypted (rule `example.com`)\nbob@example.org: encrypted \
+             (rule `example.org`, key available)\ncarol@example.net: encrypted (Autocrypt: \
+             prefers encryption)"
+        );
+
+        let plan = SendPlan {
+            decisions: vec![
+                decide(&rules, &alice, true, false, Protection::Plain),
+                decide(&rules, &bob, false, false, Protection::Plain),
+            ],
+            sign: false,
+        };
+        assert!(!plan.encrypt());
+        assert_eq!(
+            plan.summary(),
+            "alice@example.com: plain text (rule `example.com`)\nbob@example.org: plain text \
+             (rule `example.org`, no usable key)\nNot every recipient can receive an encrypted \
+             copy, so the message will be sent unencrypted to everyone."
+        );
+        let plan = SendPlan { sign: true, ..plan };
+        assert_eq!(plan.description(), "signed");
+        let plan = SendPlan {
+            decisions: vec![
+                decide(&rules, &alice, true, false, Protection::Plain),
+                decide(&rules, &carol, false, false, Protection::Sign),
+            ],
+            sign: false,
+        };
+        assert!(!plan.encrypt());
+        assert!(plan.sign());
+
+        let plan = SendPlan {
+            decisions: vec![
+                decide(&rules, &alice, false, false, Protection::Plain),
+                decide(&rules, &carol, false, false, Protection::Plain),
+            ],
+            sign: false,
+        };
+        assert_eq!(plan.blocked().count(), 1);
+        assert!(plan
+            .summary()
+            .starts_with("alice@example.com: not sent (rule `example.com`, but no usable key)\n"));
+
+        let plan = SendPlan {
+            decisions: vec![decide(&rules, &carol, false, false, Protection::Encrypt)],
+            sign: true,
+        };
+        assert_eq!(plan.blocked().count(), 1);
+    }
+
+    #[test]
+    fn test_compose_policy_autocrypt() {
+        let mut headers = HeaderMap::empty();
+        headers.insert(
+            melib::HeaderName::FROM,
+            "Alice <alice@example.com>".to_string(),
+        );
+        headers.insert(
+            "Autocrypt".try_into().unwrap(),
+            "addr=alice@example.com; prefer-encrypt=mutual; keydata=mQENBFtH".to_string(),
+        );
+        assert_eq!(
+            autocrypt_mutual(&headers),
+            vec![Address::try_from("Alice <alice@example.com>").unwrap()]
+        );
+        headers.insert(
+            "Autocrypt".try_into().unwrap(),
+            "addr=alice@example.com; keydata=mQENBFtH".to_string(),
+        );
+        assert!(autocrypt_mutual(&headers).is_empty());
+        headers.insert(
+            "Autocrypt".try_into().unwrap(),
+            "addr=mallory@example.com; prefer-encrypt=mutual; keydata=mQENBFtH".to_string(),
+        );
+        assert!(autocrypt_mutual(&headers).is_empty());
+    }
+}
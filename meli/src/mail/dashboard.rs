@@ -0,0 +1,618 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Startup dashboard, shown in place of the mail listing when
+//! `listing.startup_dashboard` is enabled.
+//!
+//! It summarizes every account: unread, flagged and today's e-mail per
+//! mailbox, the size of the account's e-mail, messages that are still being
+//! sent and errors that were reported since launch. Opening an entry opens its mailbox in the listing.
+//!
+//! It also lists the reminders of the day: contacts whose birthday is today,
+//! which can be sent a greeting, and e-mail tracked with `await-reply` that
+//! are overdue, whose thread can be opened in a new tab.
+
+use std::collections::{HashMap, VecDeque};
+
+use melib::{
+    utils::datetime, BytesDisplay, Draft, Flag, HeaderName, SpecialUsageMailbox, UnixTimestamp,
+};
+
+use super::*;
+use crate::accounts::{AwaitReplyMailbox, JobRequest};
+
+/// Subject of birthday greetings.
+const GREETING_SUBJECT: &str = "Happy birthday!";
+
+/// How many errors are kept for the "Recent errors" section.
+const MAX_ERRORS: usize = 5;
+
+/// Percentage of `disk_quota` above which a warning is shown.
+const QUOTA_WARNING_PERCENT: u64 = 90;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum DashboardTarget {
+    Mailbox(AccountHash, MailboxHash),
+    /// Show the text in a message box.
+    Message(String),
+    /// Compose a birthday greeting to the address.
+    Greeting(AccountHash, String),
+    /// Open the thread of the e-mail in a new tab.
+    Thread(AccountHash, MailboxHash, EnvelopeHash),
+}
+
+#[derive(Clone, Debug)]
+struct DashboardRow {
+    text: String,
+    heading: bool,
+    target: Option<DashboardTarget>,
+}
+
+impl DashboardRow {
+    fn heading(text: String, target: Option<DashboardTarget>) -> Self {
+        Self {
+            text,
+            heading: true,
+            target,
+        }
+    }
+
+    fn entry(text: String, target: Option<DashboardTarget>) -> Self {
+        Self {
+            text,
+            heading: false,
+            target,
+        }
+    }
+}
+
+/// E-mail counts of a mailbox, or of all mailboxes of an account.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct Counts {
+    unseen: usize,
+    flagged: usize,
+    today: usize,
+}
+
+impl Counts {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn add(&mut self, other: Self) {
+        self.unseen += other.unseen;
+        self.flagged += other.flagged;
+        self.today += other.today;
+    }
+}
+
+impl std::fmt::Display for Counts {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} unread, {} flagged, {} today",
+            self.unseen, self.flagged, self.today
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Dashboard {
+    /// The listing the dashboard is shown in, which opens the selected
+    /// mailboxes.
+    parent: ComponentId,
+    rows: Vec<DashboardRow>,
+    errors: VecDeque<(UnixTimestamp, String)>,
+    cursor: usize,
+    theme_default: ThemeAttribute,
+    heading_theme: ThemeAttribute,
+    highlight_theme: ThemeAttribute,
+    initialized: bool,
+    dirty: bool,
+    id: ComponentId,
+}
+
+impl std::fmt::Display for Dashboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "dashboard")
+    }
+}
+
+impl Dashboard {
+    pub fn new(parent: ComponentId, context: &Context) -> Self {
+        let mut ret = Self {
+            parent,
+            rows: vec![],
+            errors: VecDeque::new(),
+            cursor: 0,
+            theme_default: ThemeAttribute::default(),
+            heading_theme: ThemeAttribute::default(),
+            highlight_theme: ThemeAttribute::default(),
+            initialized: false,
+            dirty: true,
+            id: ComponentId::default(),
+        };
+        ret.set_theme(context);
+        ret
+    }
+
+    fn set_theme(&mut self, context: &Context) {
+        self.theme_default = crate::conf::value(context, "theme_default");
+        self.heading_theme = crate::conf::value(context, "widgets.list.header");
+        self.highlight_theme = if context.settings.terminal.use_color() {
+            crate::conf::value(context, "highlight")
+        } else {
+            ThemeAttribute {
+                attrs: Attr::REVERSE,
+                ..ThemeAttribute::default()
+            }
+        };
+    }
+
+    fn update_rows(&mut self, context: &Context) {
+        let now = datetime::now();
+        let today = start_of_today(now);
+        self.rows.clear();
+        for (&account_hash, account) in context.accounts.iter() {
+            let mut total = Counts::default();
+            let mut total_size = 0;
+            let mut mailbox_rows = vec![];
+            {
+                let mailboxes = account.collection.mailboxes.read().unwrap();
+                let envelopes = account.collection.envelopes.read().unwrap();
+                for mailbox_hash in account.mailboxes_order() {
+                    let Some(entry) = account.mailbox_entries.get(mailbox_hash) else {
+                        continue;
+                    };
+                    if !entry.ref_mailbox.is_subscribed() {
+                        continue;
+                    }
+                    let mut counts = Counts {
+                        unseen: entry.ref_mailbox.count().map(|(u, _)| u).unwrap_or(0),
+                        ..Counts::default()
+                    };
+                    let mut size = 0;
+                    for envelope in mailboxes
+                        .get(mailbox_hash)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|h| envelopes.get(h))
+                    {
+                        if envelope.flags().contains(Flag::FLAGGED) {
+                            counts.flagged += 1;
+                        }
+                        if envelope.date() >= today {
+                            counts.today += 1;
+                        }
+                        size += envelope.size();
+                    }
+                    total_size += size;
+                    if counts.is_empty() {
+                        continue;
+                    }
+                    total.add(counts);
+                    mailbox_rows.push(DashboardRow::entry(
+                        format!("  {}: {counts}, {}", entry.path, BytesDisplay(size)),
+                        Some(DashboardTarget::Mailbox(account_hash, *mailbox_hash)),
+                    ));
+                }
+            }
+            self.rows.push(DashboardRow::heading(
+                format!("{}: {total}, {}", account.name(), BytesDisplay(total_size)),
+                account
+                    .default_mailbox()
+                    .map(|h| DashboardTarget::Mailbox(account_hash, h)),
+            ));
+            if let Some(warning) = account
+                .settings
+                .conf
+                .disk_quota()
+                .and_then(|quota| quota_warning(total_size, quota))
+            {
+                self.rows.push(DashboardRow::entry(
+                    format!("  {warning}"),
+                    Some(DashboardTarget::Message(format!(
+                        "{}: {warning}. Sizes of mailboxes that are not loaded are not counted.",
+                        account.name()
+                    ))),
+                ));
+            }
+            self.rows.extend(mailbox_rows);
+            let sending = account
+                .active_jobs
+                .values()
+                .filter(|j| {
+                    matches!(
+                        j,
+                        JobRequest::SendMessage | JobRequest::SendMessageBackground { .. }
+                    )
+                })
+                .count();
+            if sending > 0 {
+                self.rows.push(DashboardRow::entry(
+                    format!(
+                        "  {sending} message{} in the outbox",
+                        if sending == 1 { "" } else { "s" }
+                    ),
+                    account
+                        .special_use_mailbox(SpecialUsageMailbox::Drafts)
+                        .map(|h| DashboardTarget::Mailbox(account_hash, h)),
+                ));
+            }
+            let reminders = account.reminders(now);
+            for card in reminders.birthdays {
+                self.rows.push(DashboardRow::entry(
+                    format!("  Birthday today: {card}"),
+                    (!card.email().is_empty()).then(|| {
+                        DashboardTarget::Greeting(
+                            account_hash,
+                            melib::Address::new(
+                                (!card.name().is_empty()).then(|| card.name().to_string()),
+                                card.email().to_string(),
+                            )
+                            .to_string(),
+                        )
+                    }),
+                ));
+            }
+            if !reminders.overdue.is_empty() {
+                let await_reply_mailbox = AwaitReplyMailbox::mailbox_hash();
+                let tracked = if account.is_await_reply_mailbox(await_reply_mailbox) {
+                    let envelopes = account.collection.envelopes.read().unwrap();
+                    account
+                        .collection
+                        .get_mailbox(await_reply_mailbox)
+                        .iter()
+                        .filter_map(|h| envelopes.get(h))
+                        .map(|env| (env.message_id().as_str().to_string(), env.hash()))
+                        .collect::<HashMap<String, EnvelopeHash>>()
+                } else {
+                    HashMap::default()
+                };
+                self.rows.push(DashboardRow::entry(
+                    format!(
+                        "  {} e-mail awaiting reply overdue",
+                        reminders.overdue.len()
+                    ),
+                    account
+                        .is_await_reply_mailbox(await_reply_mailbox)
+                        .then_some(DashboardTarget::Mailbox(account_hash, await_reply_mailbox)),
+                ));
+                for entry in reminders.overdue {
+                    self.rows.push(DashboardRow::entry(
+                        format!(
+                            "    {} (due {})",
+                            entry.subject,
+                            datetime::timestamp_to_string(
+                                entry.deadline,
+                                Some(datetime::formats::RFC3339_DATE),
+                                false
+                            )
+                        ),
+                        tracked.get(&entry.message_id).map(|env_hash| {
+                            DashboardTarget::Thread(account_hash, await_reply_mailbox, *env_hash)
+                        }),
+                    ));
+                }
+            }
+        }
+        if !self.errors.is_empty() {
+            self.rows
+                .push(DashboardRow::heading("Recent errors".to_string(), None));
+            for (timestamp, error) in self.errors.iter().rev() {
+                self.rows.push(DashboardRow::entry(
+                    format!(
+                        "  {} {}",
+                        datetime::timestamp_to_string(*timestamp, Some("%H:%M"), false),
+                        error.lines().next().unwrap_or_default()
+                    ),
+                    Some(DashboardTarget::Message(error.clone())),
+                ));
+            }
+        }
+        self.cursor = self.cursor.min(self.rows.len().saturating_sub(1));
+        self.initialized = true;
+    }
+
+    fn open(&self, context: &mut Context) {
+        match self.rows.get(self.cursor).and_then(|r| r.target.clone()) {
+            Some(DashboardTarget::Mailbox(account_hash, mailbox_hash)) => {
+                context.replies.push_back(UIEvent::IntraComm {
+                    from: self.id,
+                    to: self.parent,
+                    content: Box::new(ListingMessage::OpenMailbox {
+                        account_hash,
+                        mailbox_hash,
+                    }),
+                });
+            }
+            Some(DashboardTarget::Message(msg)) => {
+                context
+                    .replies
+                    .push_back(UIEvent::StatusEvent(StatusEvent::DisplayMessage(msg)));
+            }
+            Some(DashboardTarget::Greeting(account_hash, to)) => {
+                let mut draft = Draft::default();
+                draft
+                    .set_header(HeaderName::TO, to)
+                    .set_header(HeaderName::SUBJECT, GREETING_SUBJECT.to_string());
+                let mut composer = Composer::with_account(account_hash, context);
+                composer.set_draft(draft, context);
+                context
+                    .replies
+                    .push_back(UIEvent::Action(Tab(New(Some(Box::new(composer))))));
+            }
+            Some(DashboardTarget::Thread(account_hash, mailbox_hash, env_hash)) => {
+                let thread_group = {
+                    let threads = context.accounts[&account_hash]
+                        .collection
+                        .get_threads(mailbox_hash);
+                    threads
+                        .envelope_to_thread
+                        .get(&env_hash)
+                        .map(|h| threads.find_group(*h))
+                };
+                let tab: Box<dyn Component> = match thread_group {
+                    Some(thread_group) => Box::new(ThreadView::new(
+                        (account_hash, mailbox_hash, env_hash),
+                        thread_group,
+                        Some(env_hash),
+                        false,
+                        None,
+                        context,
+                    )),
+                    None => Box::new(MailView::new(
+                        Some((account_hash, mailbox_hash, env_hash)),
+                        true,
+                        context,
+                    )),
+                };
+                context
+                    .replies
+                    .push_back(UIEvent::Action(Tab(New(Some(tab)))));
+            }
+            None => {}
+        }
+    }
+}
+
+impl Component for Dashboard {
+    fn draw(&mut self, grid: &mut CellBuffer, area: Area, context: &mut Context) {
+        if !self.is_dirty() {
+            return;
+        }
+        if !self.initialized {
+            self.update_rows(context);
+        }
+        self.dirty = false;
+        grid.clear_area(area, self.theme_default);
+        let rows = area.height();
+        if rows == 0 {
+            return;
+        }
+        let top_idx = (self.cursor / rows) * rows;
+        for (i, row) in self.rows.iter().enumerate().skip(top_idx).take(rows) {
+            let row_area = area.nth_row(i - top_idx);
+            let mut theme = if i == self.cursor {
+                self.highlight_theme
+            } else if row.heading {
+                self.heading_theme
+            } else {
+                self.theme_default
+            };
+            if row.heading {
+                theme.attrs |= Attr::BOLD;
+            }
+            grid.clear_area(row_area, theme);
+            grid.write_string(
+                &row.text,
+                theme.fg,
+                theme.bg,
+                theme.attrs,
+                row_area.skip_cols(1),
+                None,
+                None,
+            );
+        }
+        if self.rows.len() > rows {
+            ScrollBar::default().set_show_arrows(true).draw(
+                grid,
+                area.nth_col(area.width().saturating_sub(1)),
+                context,
+                self.cursor,
+                rows,
+                self.rows.len(),
+            );
+        }
+        context.dirty_areas.push_back(area);
+    }
+
+    fn process_event(&mut self, event: &mut UIEvent, context: &mut Context) -> bool {
+        let shortcuts = self.shortcuts(context);
+        match *event {
+            UIEvent::ConfigReload { old_settings: _ } => {
+                self.set_theme(context);
+                self.initialized = false;
+                self.set_dirty(true);
+            }
+            UIEvent::Resize => {
+                self.dirty = true;
+            }
+            UIEvent::Notification {
+                ref title,
+                ref body,
+                kind: Some(NotificationType::Error(_)),
+                ..
+            } => {
+                if self.errors.len() == MAX_ERRORS {
+                    self.errors.pop_front();
+                }
+                self.errors.push_back((
+                    datetime::now(),
+                    match title {
+                        Some(title) => format!("{title}: {body}"),
+                        None => body.to_string(),
+                    },
+                ));
+                self.initialized = false;
+                self.set_dirty(true);
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["scroll_up"]) =>
+            {
+                self.cursor = self.cursor.saturating_sub(1);
+                self.dirty = true;
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["scroll_down"]) =>
+            {
+                if self.cursor + 1 < self.rows.len() {
+                    self.cursor += 1;
+                }
+                self.dirty = true;
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["open_entry"]) =>
+            {
+                self.open(context);
+                return true;
+            }
+            UIEvent::MailboxUpdate(_)
+            | UIEvent::EnvelopeUpdate(_)
+            | UIEvent::EnvelopeRemove(_, _)
+            | UIEvent::AccountStatusChange(_, _)
+            | UIEvent::AccountAdded(_)
+            | UIEvent::StatusEvent(StatusEvent::NewJob(_))
+            | UIEvent::StatusEvent(StatusEvent::JobFinished(_))
+            | UIEvent::StatusEvent(StatusEvent::JobCanceled(_)) => {
+                self.initialized = false;
+                self.set_dirty(true);
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn shortcuts(&self, context: &Context) -> ShortcutMaps {
+        let mut ret: ShortcutMaps = ShortcutMaps::default();
+        ret.insert(
+            Shortcuts::GENERAL,
+            context.settings.shortcuts.general.key_values(),
+        );
+        ret
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, value: bool) {
+        self.dirty = value;
+    }
+
+    fn id(&self) -> ComponentId {
+        self.id
+    }
+
+    fn view_name(&self) -> Option<&'static str> {
+        Some("dashboard")
+    }
+
+    fn status(&self, _context: &Context) -> String {
+        "Dashboard".to_string()
+    }
+}
+
+/// A warning if `used` bytes are at least [`QUOTA_WARNING_PERCENT`] of
+/// `quota` bytes.
+fn quota_warning(used: usize, quota: u64) -> Option<String> {
+    let used_percent = (used as u64).saturating_mul(100) / quota.max(1);
+    (used_percent >= QUOTA_WARNING_PERCENT).then(|| {
+        format!(
+            "Using {} of the {} disk quota ({used_percent}%)",
+            BytesDisplay(used),
+            BytesDisplay(quota.try_into().unwrap_or(usize::MAX))
+        )
+    })
+}
+
+/// Start of the day of `now` in local time.
+fn start_of_today(now: UnixTimestamp) -> UnixTimestamp {
+    let elapsed = datetime::timestamp_to_string(now, Some("%H:%M:%S"), false)
+        .split(':')
+        .filter_map(|n| n.parse::<u64>().ok())
+        .fold(0, |acc, n| acc * 60 + n);
+    now.saturating_sub(elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dashboard_counts() {
+        let mut total = Counts::default();
+        assert!(total.is_empty());
+        total.add(Counts {
+            unseen: 3,
+            flagged: 1,
+            today: 0,
+        });
+        total.add(Counts {
+            unseen: 1,
+            flagged: 0,
+            today: 2,
+        });
+        assert!(!total.is_empty());
+        assert_eq!(total.to_string(), "4 unread, 1 flagged, 2 today");
+    }
+
+    #[test]
+    fn test_dashboard_quota_warning() {
+        const MB: u64 = 1024 * 1024;
+        assert_eq!(quota_warning(0, 100 * MB), None);
+        assert_eq!(quota_warning(89 * MB as usize, 100 * MB), None);
+        assert_eq!(
+            quota_warning(95 * MB as usize, 100 * MB).as_deref(),
+            Some("Using 95.00 MiB of the 100.00 MiB disk quota (95%)")
+        );
+        assert_eq!(
+            quota_warning(150 * MB as usize, 100 * MB).as_deref(),
+            Some("Using 150.00 MiB of the 100.00 MiB disk quota (150%)")
+        );
+        assert!(quota_warning(1, 0).is_some());
+    }
+
+    #[test]
+    fn test_dashboard_start_of_today() {
+        let now = datetime::now();
+        let today = start_of_today(now);
+        assert!(today <= now);
+        assert!(now - today < 24 * 60 * 60);
+        assert_eq!(
+            datetime::timestamp_to_string(today, Some("%H:%M:%S"), false),
+            "00:00:00"
+        );
+    }
+}
@@ -0,0 +1,511 @@
+/*
+ * meli
+ *
+ * Copyright 2024 Manos Pitsidianakis
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Export of entire threads as a single readable HTML or Markdown document.
+//!
+//! Messages are laid out chronologically with their headers and plain text
+//! bodies. Images are embedded as `data:` URIs, both those attached to a
+//! message and those an HTML body references with `cid:` URLs.
+
+use std::fmt::Write;
+
+use data_encoding::BASE64;
+use melib::{
+    email::{
+        attachment_types::{ContentType, Text},
+        parser::headers::headers,
+        Attachment, Envelope, HeaderName,
+    },
+    BytesDisplay,
+};
+
+use crate::{command::actions::ThreadExportFormat, mail::view::remote_content};
+
+/// An image of a message.
+struct Image {
+    name: String,
+    content_id: Option<String>,
+    data_uri: String,
+}
+
+/// The content of a message, as it appears in the exported document.
+struct Message {
+    headers: Vec<(&'static str, String)>,
+    body: String,
+    body_is_html: bool,
+    images: Vec<Image>,
+    attachments: Vec<String>,
+}
+
+impl Message {
+    fn new(envelope: &Envelope, bytes: &[u8]) -> Self {
+        let mut headers = vec![
+            ("From", envelope.field_from_to_string()),
+            ("To", envelope.field_to_to_string()),
+        ];
+        let cc = envelope.field_cc_to_string();
+        if !cc.is_empty() {
+            headers.push(("Cc", cc));
+        }
+        headers.push(("Date", envelope.date_as_str().to_string()));
+        headers.push(("Subject", envelope.subject().to_string()));
+
+        let body = envelope.body_bytes(bytes);
+        let (body_text, body_is_html) = body_text(&body);
+        let mut images = vec![];
+        let mut attachments = vec![];
+        for part in body.attachments() {
+            if matches!(part.content_type(), ContentType::Multipart { .. }) {
+                continue;
+            }
+            let mime_type = part.mime_type();
+            if mime_type.starts_with("image/") {
+                let content_id = headers_of(&part)
+                    .into_iter()
+                    .find(|(name, _)| *name == HeaderName::CONTENT_ID)
+                    .map(|(_, value)| {
+                        value
+                            .trim()
+                            .trim_start_matches('<')
+                            .trim_end_matches('>')
+                            .to_string()
+                    });
+                images.push(Image {
+                    name: part
+                        .filename()
+                        .or_else(|| content_id.clone())
+                        .unwrap_or_else(|| "image".to_string()),
+                    content_id,
+                    data_uri: format!(
+                        "data:{mime_type};base64,{}",
+                        BASE64.encode(&part.decode(Default::default()))
+                    ),
+                });
+            } else if let Some(filename) = part.filename() {
+                attachments.push(format!(
+                    "{filename} ({mime_type}, {})",
+                    BytesDisplay(part.raw().len())
+                ));
+            }
+        }
+        Self {
+            headers,
+            body: body_text,
+            body_is_html,
+            images,
+            attachments,
+        }
+    }
+}
+
+fn headers_of(part: &Attachment) -> Vec<(HeaderName, String)> {
+    headers(part.raw())
+        .map(|(_, headers)| {
+            headers
+                .into_iter()
+                .map(|(name, value)| (name, String::from_utf8_lossy(value).into_owned()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The plain text body of a message, or its HTML body if it has no plain
+/// text.
+fn body_text(body: &Attachment) -> (String, bool) {
+    if !body.is_html() {
+        let text = body.text(Text::Plain).replace("\r\n", "\n");
+        if !text.trim().is_empty() {
+            return (text, false);
+        }
+    }
+    let html = body.text(Text::Html);
+    let is_html = !html.trim().is_empty();
+    (html, is_html)
+}
+
+/// Render the messages of a thread as a single document. `messages` are laid
+/// out in chronological order.
+pub fn thread_document(format: ThreadExportFormat, messages: &[(Envelope, Vec<u8>)]) -> String {
+    let mut messages: Vec<&(Envelope, Vec<u8>)> = messages.iter().collect();
+    messages.sort_by_key(|(envelope, _)| envelope.date());
+    let title = messages
+        .first()
+        .map(|(envelope, _)| envelope.subject().to_string())
+        .unwrap_or_default();
+    let messages = messages
+        .into_iter()
+        .map(|(envelope, bytes)| Message::new(envelope, bytes));
+    match format {
+        ThreadExportFormat::Html => html_document(&title, messages),
+        ThreadExportFormat::Markdown => markdown_document(&title, messages),
+    }
+}
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 50em; margin: auto; padding: 1em; }
+article { border-top: 1px solid #ccc; padding: 1em 0; }
+dl { display: grid; grid-template-columns: max-content auto; gap: 0 1em; color: #555; }
+dt { font-weight: bold; }
+dd { margin: 0; }
+pre { white-space: pre-wrap; font-family: inherit; }
+img { max-width: 100%; }
+iframe { width: 100%; height: 40em; border: none; }";
+
+fn html_document(title: &str, messages: impl Iterator<Item = Message>) -> String {
+    let mut ret = String::new();
+    _ = write!(
+        ret,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>\n{STYLE}\n</style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+        title = escape_html(title),
+    );
+    for (i, message) in messages.enumerate() {
+        _ = writeln!(ret, "<article id=\"message-{}\">\n<dl>", i + 1);
+        for (name, value) in &message.headers {
+            _ = writeln!(ret, "<dt>{name}</dt><dd>{}</dd>", escape_html(value));
+        }
+        ret.push_str("</dl>\n");
+        let mut images = message.images.iter().collect::<Vec<_>>();
+        if message.body_is_html {
+            // HTML bodies are shown in a sandbox that does not run scripts, with their
+            // remote content blocked.
+            let (mut html, _) = remote_content::block_remote_content(&message.body);
+            images.retain(|image| {
+                let Some(cid) = image.content_id.as_ref() else {
+                    return true;
+                };
+                let reference = format!("cid:{cid}");
+                let referenced = html.contains(&reference);
+                html = html.replace(&reference, &image.data_uri);
+                !referenced
+            });
+            _ = writeln!(
+                ret,
+                "<iframe sandbox srcdoc=\"{}\"></iframe>",
+                escape_html(&html)
+            );
+        } else {
+            _ = writeln!(ret, "<pre>{}</pre>", escape_html(message.body.trim_end()));
+        }
+        for image in images {
+            _ = writeln!(
+                ret,
+                "<p><img src=\"{}\" alt=\"{}\"></p>",
+                image.data_uri,
+                escape_html(&image.name)
+            );
+        }
+        if !message.attachments.is_empty() {
+            ret.push_str("<p>Attachments:</p>\n<ul>\n");
+            for attachment in &message.attachments {
+                _ = writeln!(ret, "<li>{}</li>", escape_html(attachment));
+            }
+            ret.push_str("</ul>\n");
+        }
+        ret.push_str("</article>\n");
+    }
+    ret.push_str("</body>\n</html>\n");
+    ret
+}
+
+fn markdown_document(title: &str, messages: impl Iterator<Item = Message>) -> String {
+    let mut ret = format!("# {}\n", escape_markdown(title));
+    for message in messages {
+        ret.push('\n');
+        let from = message
+            .headers
+            .iter()
+            .find(|(name, _)| *name == "From")
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default();
+        _ = writeln!(ret, "## {}\n", escape_markdown(from));
+        for (name, value) in &message.headers {
+            // A trailing backslash is a hard line break.
+            _ = writeln!(ret, "**{name}:** {}\\", escape_markdown(value));
+        }
+        // The last header line must not end with a line break.
+        ret.truncate(ret.len() - "\\\n".len());
+        ret.push_str("\n\n");
+        let body = if message.body_is_html {
+            html_to_text(&message.body)
+        } else {
+            message.body.trim_end().to_string()
+        };
+        // The fence must be longer than any run of backticks in the body.
+        let mut fence = "```".to_string();
+        while body.contains(&fence) {
+            fence.push('`');
+        }
+        _ = writeln!(ret, "{fence}text\n{body}\n{fence}");
+        for image in &message.images {
+            _ = writeln!(
+                ret,
+                "\n![{}]({})",
+                escape_markdown(&image.name),
+                image.data_uri
+            );
+        }
+        if !message.attachments.is_empty() {
+            ret.push_str("\nAttachments:\n\n");
+            for attachment in &message.attachments {
+                _ = writeln!(ret, "- {}", escape_markdown(attachment));
+            }
+        }
+        ret.push_str("\n---\n");
+    }
+    ret
+}
+
+fn escape_html(text: &str) -> String {
+    let mut ret = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => ret.push_str("&amp;"),
+            '<' => ret.push_str("&lt;"),
+            '>' => ret.push_str("&gt;"),
+            '"' => ret.push_str("&quot;"),
+            '\'' => ret.push_str("&#39;"),
+            c => ret.push(c),
+        }
+    }
+    ret
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut ret = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]<>#|".contains(c) {
+            ret.push('\\');
+        }
+        ret.push(c);
+    }
+    ret
+}
+
+/// Plain text of an HTML document: tags are removed, block elements start new
+/// lines or paragraphs and the content of scripts and styles is dropped.
+pub fn html_to_text(html: &str) -> String {
+    let mut ret = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        ret.push_str(&unescape_html(&rest[..start]));
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment
+                .find("-->")
+                .map(|end| &comment[end + 3..])
+                .unwrap_or("");
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            rest = "";
+            break;
+        };
+        let is_closing = rest[1..end].starts_with('/');
+        let tag = rest[1..end]
+            .trim_start_matches('/')
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_lowercase();
+        rest = &rest[end + 1..];
+        match tag.as_str() {
+            "script" | "style" | "head" | "title" if !is_closing => {
+                let closing = format!("</{tag}");
+                let lowercase = rest.to_ascii_lowercase();
+                rest = lowercase
+                    .find(&closing)
+                    .and_then(|pos| rest[pos..].find('>').map(|end| &rest[pos + end + 1..]))
+                    .unwrap_or("");
+            }
+            "br" | "li" | "tr" => {
+                if !ret.is_empty() && !ret.ends_with('\n') {
+                    ret.push('\n');
+                }
+            }
+            "p" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "blockquote" | "pre"
+            | "table" | "ul" | "ol" | "hr" => {
+                if !ret.is_empty() && !ret.ends_with("\n\n") {
+                    ret.push_str(if ret.ends_with('\n') { "\n" } else { "\n\n" });
+                }
+            }
+            _ => {}
+        }
+    }
+    ret.push_str(&unescape_html(rest));
+    // Trim lines and collapse runs of blank lines.
+    let mut text = String::with_capacity(ret.len());
+    let mut blank = true;
+    for line in ret.lines().map(|l| l.trim()) {
+        if line.is_empty() {
+            if !blank {
+                text.push('\n');
+            }
+            blank = true;
+        } else {
+            text.push_str(line);
+            text.push('\n');
+            blank = false;
+        }
+    }
+    text.trim_end().to_string()
+}
+
+fn unescape_html(text: &str) -> String {
+    let text = text.replace(['\n', '\r'], " ");
+    let mut ret = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find('&') {
+        ret.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity_end = rest.find(';').filter(|&end| end <= 10);
+        let c = entity_end.and_then(|end| match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            entity => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .map(|n| u32::from_str_radix(n, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse::<u32>))
+                .and_then(|n| n.ok())
+                .and_then(char::from_u32),
+        });
+        match (c, entity_end) {
+            (Some(c), Some(end)) => {
+                ret.push(c);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                ret.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    ret.push_str(rest);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use melib::Envelope;
+
+    use super::*;
+
+    const FIRST: &str = "From: Jane Doe <jane@example.com>
+To: team@example.com
+Subject: Lunch?
+Message-ID: <1@example.com>
+Date: Mon, 1 Jan 2024 12:00:00 +0000
+Content-Type: multipart/mixed; boundary=\"b\"
+
+--b
+Content-Type: text/plain
+
+Pizza at <noon> & *after*?
+--b
+Content-Type: image/png
+Content-Disposition: attachment; filename=\"menu.png\"
+Content-Transfer-Encoding: base64
+
+iVBORw0K
+--b--
+";
+
+    const SECOND: &str = "From: John Doe <john@example.com>
+To: team@example.com
+Subject: Re: Lunch?
+Message-ID: <2@example.com>
+In-Reply-To: <1@example.com>
+Date: Mon, 1 Jan 2024 13:00:00 +0000
+Content-Type: multipart/related; boundary=\"r\"
+
+--r
+Content-Type: text/html
+
+<html><head><style>p { color: red; }</style></head><body><p>Sure&nbsp;thing</p><img src=\"cid:map@example.com\"><p>See you</p></body></html>
+--r
+Content-Type: image/gif
+Content-ID: <map@example.com>
+Content-Transfer-Encoding: base64
+
+R0lGODlh
+--r--
+";
+
+    fn messages() -> Vec<(Envelope, Vec<u8>)> {
+        // Out of order, to check that messages are sorted by date.
+        [SECOND, FIRST]
+            .into_iter()
+            .map(|raw| {
+                let raw = raw.replace('\n', "\r\n").into_bytes();
+                (Envelope::from_bytes(&raw, None).unwrap(), raw)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_export_thread_html() {
+        let html = thread_document(ThreadExportFormat::Html, &messages());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Lunch?</title>"));
+        let first = html.find("id=\"message-1\"").unwrap();
+        let second = html.find("id=\"message-2\"").unwrap();
+        assert!(first < html.find("jane@example.com").unwrap());
+        assert!(html.find("jane@example.com").unwrap() < second);
+        assert!(html.contains("<pre>Pizza at &lt;noon&gt; &amp; *after*?</pre>"));
+        assert!(html.contains("<img src=\"data:image/png;base64,iVBORw0K\" alt=\"menu.png\">"));
+        // HTML bodies are sandboxed, with images they reference embedded.
+        assert!(html.contains("<iframe sandbox srcdoc=\""));
+        assert!(html.contains("&lt;img src=&quot;data:image/gif;base64,R0lGODlh&quot;&gt;"));
+        assert!(!html.contains("cid:"));
+        assert!(!html.contains("alt=\"map@example.com\""));
+    }
+
+    #[test]
+    fn test_export_thread_markdown() {
+        let markdown = thread_document(ThreadExportFormat::Markdown, &messages());
+        assert!(markdown.starts_with("# Lunch?\n\n## Jane Doe \\<jane@example.com\\>\n\n"));
+        assert!(markdown.contains("**To:** team@example.com\\\n"));
+        assert!(markdown.contains("```text\nPizza at <noon> & *after*?\n```\n"));
+        assert!(markdown.contains("![menu.png](data:image/png;base64,iVBORw0K)"));
+        assert!(markdown.contains("```text\nSure thing\n\nSee you\n```\n"));
+        assert!(markdown.contains("![map@example.com](data:image/gif;base64,R0lGODlh)"));
+        assert!(
+            markdown.find("Jane Doe").unwrap() < markdown.find("## John Doe").unwrap(),
+            "{markdown}"
+        );
+    }
+
+    #[test]
+    fn test_export_html_to_text() {
+        assert_eq!(
+            html_to_text(
+                "<html><head><title>t</title></head><body><!-- x --><script>var a = \
+                 '<p>';</script><h1>Title</h1>\n<p>One <b>two</b>\nthree</p><ul><li>a &amp; \
+                 b</li><li>&#x263A;</li></ul><br/>End</body></html>"
+            ),
+            "Title\n\nOne two three\n\na & b\n☺\n\nEnd"
+        );
+    }
+}
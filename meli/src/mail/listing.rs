@@ -27,13 +27,14 @@ use std::{
     future::Future,
     io::{BufWriter, Write},
     ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
     pin::Pin,
 };
 
 use futures::future::try_join_all;
 use melib::{
     backends::EnvelopeHashBatch, mbox::MboxMetadata, utils::datetime, Flag, FlagOp,
-    ShellExpandTrait, UnixTimestamp,
+    ShellExpandTrait, SortChain, Threads, UnixTimestamp,
 };
 use smallvec::SmallVec;
 
@@ -41,6 +42,7 @@ use super::*;
 use crate::{
     accounts::{JobRequest, MailboxStatus},
     components::ExtendShortcutsMaps,
+    conf::data_types::{PaneLayout, PaneSplit},
     jobs::IsAsync,
 };
 
@@ -238,6 +240,51 @@ impl<T> RowsState<T> {
     }
 }
 
+/// Return the sort chain that results from applying `action` to `sort`, or
+/// `None` if `action` doesn't change how listings are sorted.
+pub fn apply_sort_action(sort: &SortChain, action: &Action) -> Option<SortChain> {
+    let new_sort = match action {
+        Action::Sort(new_sort) => new_sort.clone(),
+        Action::SubSort(field, order) => sort.with_secondary((*field, *order)),
+        Action::ReverseSort => sort.reversed(),
+        _ => return None,
+    };
+    (new_sort != *sort).then_some(new_sort)
+}
+
+/// A query that restricts the entries a listing shows, set with the `limit`
+/// command.
+#[derive(Clone, Debug)]
+pub struct ListingLimit {
+    pub term: String,
+    pub query: melib::search::Query,
+}
+
+impl ListingLimit {
+    pub fn new(term: &str) -> Result<Self> {
+        Ok(Self {
+            term: term.to_string(),
+            query: melib::search::Query::try_from(term)?,
+        })
+    }
+
+    pub fn is_match(&self, envelope: &Envelope) -> bool {
+        use melib::search::QueryTrait;
+
+        envelope.is_match(&self.query)
+    }
+
+    /// Returns `true` if any envelope of `thread` matches.
+    pub fn thread_matches(&self, account: &Account, threads: &Threads, thread: ThreadHash) -> bool {
+        threads.thread_iter(thread).any(|(_, h)| {
+            threads.thread_nodes()[&h]
+                .message()
+                .filter(|env_hash| account.contains_key(*env_hash))
+                .is_some_and(|env_hash| self.is_match(&account.collection.get_env(env_hash)))
+        })
+    }
+}
+
 mod conversations;
 pub use self::conversations::*;
 
@@ -253,6 +300,9 @@ pub use self::plain::*;
 mod offline;
 pub use self::offline::*;
 
+mod saved_state;
+pub use self::saved_state::*;
+
 #[derive(Clone, Copy, Debug)]
 pub enum Focus {
     None,
@@ -301,6 +351,15 @@ pub struct ColorCache {
     pub odd_highlighted_selected: ThemeAttribute,
     pub tag_default: ThemeAttribute,
     pub highlight_self: ThemeAttribute,
+    pub preview: ThemeAttribute,
+    pub initials: ThemeAttribute,
+    pub score_high: ThemeAttribute,
+    pub score_low: ThemeAttribute,
+    pub age_today: ThemeAttribute,
+    pub age_month: ThemeAttribute,
+    pub age_older: ThemeAttribute,
+    pub skeleton: ThemeAttribute,
+    pub context: ThemeAttribute,
 
     // Conversations
     pub subject: ThemeAttribute,
@@ -314,6 +373,15 @@ impl ColorCache {
             theme_default: crate::conf::value(context, "theme_default"),
             tag_default: crate::conf::value(context, "mail.listing.tag_default"),
             highlight_self: crate::conf::value(context, "mail.listing.highlight_self"),
+            preview: crate::conf::value(context, "mail.listing.preview"),
+            initials: crate::conf::value(context, "mail.listing.initials"),
+            score_high: crate::conf::value(context, "mail.listing.score_high"),
+            score_low: crate::conf::value(context, "mail.listing.score_low"),
+            age_today: crate::conf::value(context, "mail.listing.age_today"),
+            age_month: crate::conf::value(context, "mail.listing.age_month"),
+            age_older: crate::conf::value(context, "mail.listing.age_older"),
+            skeleton: crate::conf::value(context, "mail.listing.skeleton"),
+            context: crate::conf::value(context, "mail.listing.context"),
             ..Self::default()
         };
         let mut ret = match style {
@@ -408,9 +476,80 @@ impl ColorCache {
             ret.odd_highlighted.attrs |= Attr::REVERSE;
             ret.even_highlighted_selected.attrs |= Attr::REVERSE | Attr::DIM;
             ret.odd_highlighted_selected.attrs |= Attr::REVERSE | Attr::DIM;
+            ret.score_low.attrs |= Attr::DIM;
+            ret.age_month.attrs |= Attr::DIM;
+            ret.age_older.attrs |= Attr::DIM;
         }
         ret
     }
+
+    /// Draw `row_attr` with the `score_high` or `score_low` attribute if
+    /// `score` is in the band set by `listing.score_high` or
+    /// `listing.score_low`.
+    pub fn score_attr(
+        &self,
+        row_attr: ThemeAttribute,
+        score: i64,
+        context: &Context,
+        coordinates: (AccountHash, MailboxHash),
+    ) -> ThemeAttribute {
+        let band = if mailbox_settings!(context[coordinates.0][&coordinates.1].listing.score_high)
+            .is_some_and(|high| score >= high)
+        {
+            &self.score_high
+        } else if mailbox_settings!(context[coordinates.0][&coordinates.1].listing.score_low)
+            .is_some_and(|low| score <= low)
+        {
+            &self.score_low
+        } else {
+            return row_attr;
+        };
+        Self::overlay(row_attr, band)
+    }
+
+    /// Draw `row_attr` with the `age_today`, `age_month` or `age_older`
+    /// attribute depending on how old `date` is, if `listing.age_colors` is
+    /// enabled. Entries of the last week are drawn unchanged.
+    pub fn age_attr(
+        &self,
+        row_attr: ThemeAttribute,
+        date: UnixTimestamp,
+        context: &Context,
+        coordinates: (AccountHash, MailboxHash),
+    ) -> ThemeAttribute {
+        const DAY: UnixTimestamp = 24 * 60 * 60;
+
+        if !*mailbox_settings!(context[coordinates.0][&coordinates.1].listing.age_colors) {
+            return row_attr;
+        }
+        let band = match datetime::now().saturating_sub(date) {
+            age if age < DAY => &self.age_today,
+            age if age < 7 * DAY => return row_attr,
+            age if age < 30 * DAY => &self.age_month,
+            _ => &self.age_older,
+        };
+        Self::overlay(row_attr, band)
+    }
+
+    /// Draw `row_attr` with the `context` attribute, for entries that are
+    /// only shown as the context of the entries that match the filter.
+    pub fn context_attr(&self, row_attr: ThemeAttribute) -> ThemeAttribute {
+        Self::overlay(row_attr, &self.context)
+    }
+
+    /// Add the foreground color and attributes of `band` to `row_attr`,
+    /// keeping its background.
+    fn overlay(row_attr: ThemeAttribute, band: &ThemeAttribute) -> ThemeAttribute {
+        ThemeAttribute {
+            fg: if band.fg == Color::Default {
+                row_attr.fg
+            } else {
+                band.fg
+            },
+            bg: row_attr.bg,
+            attrs: row_attr.attrs | band.attrs,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -419,11 +558,79 @@ pub struct EntryStrings {
     pub subject: SubjectString,
     pub flag: FlagString,
     pub from: FromString,
+    pub initials: InitialsString,
     pub tags: TagString,
     pub unseen: bool,
     pub highlight_self: bool,
 }
 
+impl EntryStrings {
+    /// Width of the From column, including the sender's initials.
+    pub fn from_width(&self) -> usize {
+        self.initials.width() + self.from.grapheme_width()
+    }
+}
+
+/// Return the body preview of `env_hash` if `listing.show_preview` is enabled
+/// for the mailbox at `coordinates`.
+///
+/// The preview is fetched in the background the first time it is requested;
+/// an [`UIEvent::EnvelopeUpdate`] is sent when it becomes available.
+pub fn entry_preview(
+    context: &Context,
+    coordinates: (AccountHash, MailboxHash),
+    env_hash: EnvelopeHash,
+) -> Option<String> {
+    if !*mailbox_settings!(context[coordinates.0][&coordinates.1].listing.show_preview) {
+        return None;
+    }
+    let length = *mailbox_settings!(
+        context[coordinates.0][&coordinates.1]
+            .listing
+            .preview_length
+    );
+    context.accounts[&coordinates.0]
+        .preview(env_hash, length)
+        .filter(|p| !p.is_empty())
+}
+
+/// Return the summary of `envelope` if it is a GitHub or GitLab notification
+/// and `listing.show_forge_summary` is enabled for the mailbox at
+/// `coordinates`.
+pub fn entry_forge_summary(
+    context: &Context,
+    coordinates: (AccountHash, MailboxHash),
+    envelope: &Envelope,
+) -> Option<String> {
+    if !*mailbox_settings!(
+        context[coordinates.0][&coordinates.1]
+            .listing
+            .show_forge_summary
+    ) {
+        return None;
+    }
+    melib::email::forge::ForgeNotification::detect(envelope).map(|n| n.to_string())
+}
+
+/// Return the path of the mailbox `env_hash` is stored in if the mailbox at
+/// `coordinates` is the [`SEARCH_RESULTS_MAILBOX`](crate::accounts::SEARCH_RESULTS_MAILBOX)
+/// mailbox, whose entries come from different mailboxes.
+pub fn entry_mailbox(
+    context: &Context,
+    coordinates: (AccountHash, MailboxHash),
+    env_hash: EnvelopeHash,
+) -> Option<String> {
+    let account = &context.accounts[&coordinates.0];
+    if !account.is_search_results_mailbox(coordinates.1) {
+        return None;
+    }
+    let (mailbox_hash, _) = account
+        .backing_mailboxes(coordinates.1, &[env_hash])
+        .into_iter()
+        .next()?;
+    Some(account[&mailbox_hash].ref_mailbox.path().to_string())
+}
+
 #[macro_export]
 macro_rules! digits_of_num {
     ($num:expr) => {{
@@ -489,6 +696,320 @@ column_str!(struct FromString(String));
 column_str!(struct SubjectString(String));
 column_str!(struct FlagString(String));
 column_str!(struct TagString(String, SmallVec<[Option<Color>; 8]>));
+column_str!(struct InitialsString(String, Color));
+
+/// Format the age of `date` relative to `now` compactly, e.g. `5m`, `3h` or
+/// `2w`.
+pub fn format_age(now: UnixTimestamp, date: UnixTimestamp) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    const WEEK: u64 = 7 * DAY;
+    const YEAR: u64 = 365 * DAY;
+    match now.saturating_sub(date) {
+        n if n < HOUR => format!("{}m", n / MINUTE),
+        n if n < DAY => format!("{}h", n / HOUR),
+        n if n < WEEK => format!("{}d", n / DAY),
+        n if n < YEAR => format!("{}w", n / WEEK),
+        n => format!("{}y", n / YEAR),
+    }
+}
+
+/// Label of the group of entries dated `date` when `listing.date_separators`
+/// is enabled, e.g. `Yesterday` or `Last week`. Groups are made of calendar
+/// days relative to `now`.
+pub fn date_group(now: UnixTimestamp, date: UnixTimestamp) -> &'static str {
+    const DAY: u64 = 24 * 60 * 60;
+    match (now / DAY).saturating_sub(date / DAY) {
+        0 => "Today",
+        1 => "Yesterday",
+        n if n < 7 => "This week",
+        n if n < 14 => "Last week",
+        n if n < 31 => "This month",
+        _ => "Older",
+    }
+}
+
+/// The date separator to show on an entry dated `date` that follows an entry
+/// dated `prev`, i.e. its [`date_group`] if it's different from the previous
+/// entry's.
+///
+/// Returns `None` if `listing.date_separators` is disabled or the listing is
+/// not sorted by date.
+pub fn date_separator(
+    context: &Context,
+    coordinates: (AccountHash, MailboxHash),
+    sort: &SortChain,
+    prev: Option<UnixTimestamp>,
+    date: UnixTimestamp,
+) -> Option<&'static str> {
+    if !*mailbox_settings!(
+        context[coordinates.0][&coordinates.1]
+            .listing
+            .date_separators
+    ) || sort.first().0 != melib::SortField::Date
+    {
+        return None;
+    }
+    let now = datetime::now();
+    let group = date_group(now, date);
+    (prev.map(|prev| date_group(now, prev)) != Some(group)).then_some(group)
+}
+
+/// Format a size in bytes compactly in at most five columns, e.g. `812B`,
+/// `4.2K` or `18M`.
+pub fn format_size(bytes: usize) -> String {
+    const UNITS: [char; 6] = ['K', 'M', 'G', 'T', 'P', 'E'];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1023.5 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if size < 9.95 {
+        format!("{size:.1}{}", UNITS[unit])
+    } else {
+        format!("{size:.0}{}", UNITS[unit])
+    }
+}
+
+impl DateString {
+    /// The formatted `date` of an entry, followed by its size if
+    /// `listing.show_size` is enabled. `size` is only called if it is.
+    pub(self) fn new(
+        date: String,
+        size: impl FnOnce() -> usize,
+        context: &Context,
+        coordinates: (AccountHash, MailboxHash),
+    ) -> Self {
+        if *mailbox_settings!(context[coordinates.0][&coordinates.1].listing.show_size) {
+            Self(format!("{date} {:>5}", format_size(size())))
+        } else {
+            Self(date)
+        }
+    }
+}
+
+/// Total size of the e-mails of thread `hash`.
+pub fn thread_size(account: &Account, threads: &Threads, hash: ThreadHash) -> usize {
+    threads
+        .thread_iter(hash)
+        .filter_map(|(_, h)| threads.thread_nodes()[&h].message())
+        .map(|env_hash| account.collection.get_env(env_hash).size())
+        .sum()
+}
+
+/// Background colors of sender initials, see [`InitialsString`].
+const INITIALS_COLORS: [u8; 12] = [124, 130, 28, 25, 90, 30, 166, 61, 94, 22, 54, 88];
+
+impl InitialsString {
+    /// Initials of the first address of `from`, or an empty string if
+    /// `listing.show_initials` is disabled.
+    pub(self) fn new(
+        from: &[Address],
+        context: &Context,
+        coordinates: (AccountHash, MailboxHash),
+    ) -> Self {
+        if !*mailbox_settings!(context[coordinates.0][&coordinates.1].listing.show_initials) {
+            return Self(String::new(), Color::Default);
+        }
+        let Some(address) = from.first() else {
+            return Self(String::new(), Color::Default);
+        };
+        let (initials, color) = sender_initials(address);
+        Self(format!(" {initials:<2} "), color)
+    }
+
+    /// Width of the initials, including a separating space.
+    pub fn width(&self) -> usize {
+        if self.is_empty() {
+            0
+        } else {
+            self.grapheme_width() + 1
+        }
+    }
+}
+
+/// Return up to two initials of `address` and a color derived from its
+/// address spec.
+///
+/// The initials are taken from the first and last word of the display name,
+/// or from the local part of the address if it has no display name.
+pub fn sender_initials(address: &Address) -> (String, Color) {
+    let name = address
+        .get_display_name()
+        .filter(|n| n.chars().any(char::is_alphanumeric))
+        .unwrap_or_else(|| {
+            let email = address.get_email();
+            email.split('@').next().unwrap_or_default().to_string()
+        });
+    let words = name
+        .split(|c: char| c.is_whitespace() || matches!(c, '.' | '_' | '-' | ','))
+        .filter_map(|w| w.chars().find(|c| c.is_alphanumeric()))
+        .collect::<SmallVec<[char; 8]>>();
+    let initials = match words.as_slice() {
+        [] => String::new(),
+        [first] => first.to_uppercase().collect(),
+        [first, .., last] => first.to_uppercase().chain(last.to_uppercase()).collect(),
+    };
+    // FNV-1a, so that colors are stable across sessions.
+    let hash = address
+        .address_spec_raw()
+        .iter()
+        .fold(0xcbf29ce484222325_u64, |hash, b| {
+            (hash ^ u64::from(b.to_ascii_lowercase())).wrapping_mul(0x100000001b3)
+        });
+    (
+        initials,
+        Color::Byte(INITIALS_COLORS[(hash % INITIALS_COLORS.len() as u64) as usize]),
+    )
+}
+
+/// Fetch the bytes of `env_hashes` and export them with `write` in a
+/// blocking job, notifying the user of the outcome.
+///
+/// `write` gets `path`, made absolute and expanded, the account's collection,
+/// `env_hashes` and the bytes of each e-mail; `success_message` turns its
+/// result into the notification text. `kind` names the job and `what` is
+/// what notifications call the exported e-mail.
+fn export_job<T: Send + 'static>(
+    context: &mut Context,
+    account_hash: AccountHash,
+    env_hashes: &[EnvelopeHash],
+    path: &Path,
+    (kind, what): (&'static str, &'static str),
+    write: impl FnOnce(PathBuf, &melib::Collection, &[EnvelopeHash], Vec<Vec<u8>>) -> Result<T>
+        + Send
+        + 'static,
+    success_message: impl FnOnce(T) -> String + Send + 'static,
+) {
+    let account = &context.accounts[&account_hash];
+    let futures: Result<Vec<_>> = env_hashes
+        .iter()
+        .map(|&env_hash| account.envelope_bytes_by_hash(env_hash))
+        .collect::<Result<Vec<_>>>();
+    let mut path = path.to_path_buf();
+    if path.is_relative() {
+        path = context.current_dir().join(&path);
+    }
+    path = path.expand();
+    let account = &mut context.accounts[&account_hash];
+    let collection = account.collection.clone();
+    let env_hashes = env_hashes.to_vec();
+    let (sender, mut receiver) = crate::jobs::oneshot::channel();
+    let fut: Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> = Box::pin(async move {
+        let cl = async move {
+            let bytes: Vec<Vec<u8>> = try_join_all(futures?).await?;
+            write(path, &collection, &env_hashes, bytes)
+        };
+        let r: Result<T> = cl.await;
+        let _ = sender.send(r);
+        Ok(())
+    });
+    let handle = account.main_loop_handler.job_executor.spawn(
+        format!("exporting-{kind}").into(),
+        fut,
+        IsAsync::Blocking,
+    );
+    account.insert_job(
+        handle.job_id,
+        JobRequest::Generic {
+            name: format!("exporting {kind}").into(),
+            handle,
+            on_finish: Some(CallbackFn(Box::new(move |context: &mut Context| {
+                context.replies.push_back(match receiver.try_recv() {
+                    Err(_) | Ok(None) => UIEvent::Notification {
+                        title: Some(format!("Could not export {what}").into()),
+                        source: None,
+                        body: "Job was canceled.".into(),
+                        kind: Some(NotificationType::Info),
+                    },
+                    Ok(Some(Err(err))) => UIEvent::Notification {
+                        title: Some(format!("Could not export {what}").into()),
+                        source: None,
+                        body: err.to_string().into(),
+                        kind: Some(NotificationType::Error(err.kind)),
+                    },
+                    Ok(Some(Ok(ret))) => UIEvent::Notification {
+                        title: Some(format!("Successfully exported {what}").into()),
+                        source: None,
+                        body: success_message(ret).into(),
+                        kind: Some(NotificationType::Info),
+                    },
+                });
+            }))),
+            log_level: LogLevel::INFO,
+        },
+    );
+}
+
+/// Number of envelopes of `mailbox_hash` that are not fetched from the backend
+/// yet, while the mailbox is loading.
+pub fn pending_envelopes(account: &Account, mailbox_hash: MailboxHash) -> usize {
+    let Some(entry) = account.mailbox_entries.get(&mailbox_hash) else {
+        return 0;
+    };
+    if !entry.status.is_parsing() {
+        return 0;
+    }
+    let total = entry
+        .ref_mailbox
+        .count()
+        .map(|(_, total)| total)
+        .unwrap_or(0);
+    total.saturating_sub(account.collection.get_mailbox(mailbox_hash).len())
+}
+
+/// Draw a placeholder row for each of `count` envelopes that are still
+/// loading, from the top of `area`. Each row is `row_height` lines tall.
+pub fn draw_skeleton_rows(
+    grid: &mut CellBuffer,
+    area: Area,
+    count: usize,
+    row_height: usize,
+    color_cache: &ColorCache,
+) {
+    let row_height = row_height.max(1);
+    for i in 0..count.min(area.height() / row_height) {
+        let row = area.skip_rows(i * row_height).take_rows(row_height);
+        grid.clear_area(row, color_cache.skeleton);
+        grid.write_string(
+            "loading…",
+            color_cache.skeleton.fg,
+            color_cache.skeleton.bg,
+            color_cache.skeleton.attrs,
+            row,
+            None,
+            None,
+        );
+    }
+}
+
+/// Draw `initials` at the start of `area` and return the number of columns
+/// used, including a separating space.
+pub fn draw_initials(
+    grid: &mut CellBuffer,
+    area: Area,
+    initials: &InitialsString,
+    color_cache: &ColorCache,
+) -> usize {
+    if initials.is_empty() {
+        return 0;
+    }
+    let (x, _) = grid.write_string(
+        initials.as_str(),
+        color_cache.initials.fg,
+        initials.1,
+        color_cache.initials.attrs,
+        area,
+        None,
+        None,
+    );
+    x + 1
+}
 
 impl FlagString {
     pub(self) fn new(
@@ -497,12 +1018,15 @@ impl FlagString {
         is_snoozed: bool,
         is_unseen: bool,
         has_attachments: bool,
+        score: i64,
         context: &Context,
         coordinates: (AccountHash, MailboxHash),
     ) -> Self {
+        // Screen readers spell out symbols, so describe flags with words.
+        let screen_reader = context.screen_reader();
         Self(format!(
             "{flag_passed}{flag_replied}{flag_seen}{flag_trashed}{flag_draft}{flag_flagged} \
-             {selected}{snoozed}{unseen}{attachments}{whitespace}",
+             {selected}{snoozed}{unseen}{attachments}{whitespace}{score}",
             flag_passed = Some("P")
                 .filter(|_| flags.contains(Flag::PASSED))
                 .unwrap_or_default(),
@@ -525,7 +1049,11 @@ impl FlagString {
                 mailbox_settings!(context[coordinates.0][&coordinates.1].listing.selected_flag)
                     .as_ref()
                     .map(|s| s.as_str())
-                    .unwrap_or(DEFAULT_SELECTED_FLAG)
+                    .unwrap_or(if screen_reader {
+                        "[selected]"
+                    } else {
+                        DEFAULT_SELECTED_FLAG
+                    })
             } else {
                 ""
             },
@@ -537,7 +1065,11 @@ impl FlagString {
                 )
                 .as_ref()
                 .map(|s| s.as_str())
-                .unwrap_or(DEFAULT_SNOOZED_FLAG)
+                .unwrap_or(if screen_reader {
+                    "[snoozed]"
+                } else {
+                    DEFAULT_SNOOZED_FLAG
+                })
             } else {
                 ""
             },
@@ -545,7 +1077,11 @@ impl FlagString {
                 mailbox_settings!(context[coordinates.0][&coordinates.1].listing.unseen_flag)
                     .as_ref()
                     .map(|s| s.as_str())
-                    .unwrap_or(DEFAULT_UNSEEN_FLAG)
+                    .unwrap_or(if screen_reader {
+                        "[unread]"
+                    } else {
+                        DEFAULT_UNSEEN_FLAG
+                    })
             } else {
                 ""
             },
@@ -557,7 +1093,11 @@ impl FlagString {
                 )
                 .as_ref()
                 .map(|s| s.as_str())
-                .unwrap_or(DEFAULT_ATTACHMENT_FLAG)
+                .unwrap_or(if screen_reader {
+                    "[attachment]"
+                } else {
+                    DEFAULT_ATTACHMENT_FLAG
+                })
             } else {
                 ""
             },
@@ -566,6 +1106,14 @@ impl FlagString {
             } else {
                 ""
             },
+            score =
+                if !*mailbox_settings!(context[coordinates.0][&coordinates.1].listing.show_score) {
+                    String::new()
+                } else if screen_reader {
+                    format!("[score {score}] ")
+                } else {
+                    format!("{score:+} ")
+                },
         ))
     }
 }
@@ -780,12 +1328,17 @@ pub trait MailListingTrait: ListingTrait {
                         });
                         return;
                     };
-                    let job = account.backend.lock().unwrap().copy_messages(
-                        env_hashes,
-                        mailbox_hash,
-                        trash_mbox_hash,
-                        /* move? */ true,
-                    );
+                    let job = account
+                        .check_writable(Some(mailbox_hash))
+                        .and_then(|()| account.check_writable(Some(trash_mbox_hash)))
+                        .and_then(|()| {
+                            account.backend.lock().unwrap().copy_messages(
+                                env_hashes,
+                                mailbox_hash,
+                                trash_mbox_hash,
+                                /* move? */ true,
+                            )
+                        });
                     match job {
                         Err(err) => {
                             context.replies.push_back(UIEvent::Notification {
@@ -814,11 +1367,13 @@ pub trait MailListingTrait: ListingTrait {
                     }
                 }
                 ListingAction::Delete => {
-                    let job = account
-                        .backend
-                        .lock()
-                        .unwrap()
-                        .delete_messages(env_hashes.clone(), mailbox_hash);
+                    let job = account.check_writable(Some(mailbox_hash)).and_then(|()| {
+                        account
+                            .backend
+                            .lock()
+                            .unwrap()
+                            .delete_messages(env_hashes.clone(), mailbox_hash)
+                    });
                     match job {
                         Err(err) => {
                             context.replies.push_back(UIEvent::Notification {
@@ -844,6 +1399,7 @@ pub trait MailListingTrait: ListingTrait {
                 ListingAction::CopyTo(ref mailbox_path) => {
                     match account.mailbox_by_path(mailbox_path).and_then(
                         |destination_mailbox_hash| {
+                            account.check_writable(Some(destination_mailbox_hash))?;
                             account.backend.lock().unwrap().copy_messages(
                                 env_hashes,
                                 mailbox_hash,
@@ -889,6 +1445,8 @@ pub trait MailListingTrait: ListingTrait {
                 ListingAction::MoveTo(ref mailbox_path) => {
                     match account.mailbox_by_path(mailbox_path).and_then(
                         |destination_mailbox_hash| {
+                            account.check_writable(Some(mailbox_hash))?;
+                            account.check_writable(Some(destination_mailbox_hash))?;
                             account.backend.lock().unwrap().copy_messages(
                                 env_hashes,
                                 mailbox_hash,
@@ -924,132 +1482,237 @@ pub trait MailListingTrait: ListingTrait {
                     }
                 }
                 ListingAction::ExportMbox(format, ref path) => {
-                    let futures: Result<Vec<_>> = envs_to_set
-                        .iter()
-                        .map(|&env_hash| account.envelope_bytes_by_hash(env_hash))
-                        .collect::<Result<Vec<_>>>();
-                    let mut path = path.to_path_buf();
-                    if path.is_relative() {
-                        path = context.current_dir().join(&path);
-                    }
-                    path = path.expand();
-                    let account = &mut context.accounts[&account_hash];
                     let format = (*format).unwrap_or_default();
-                    let collection = account.collection.clone();
-                    let (sender, mut receiver) = crate::jobs::oneshot::channel();
-                    let fut: Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> =
-                        Box::pin(async move {
-                            let cl = async move {
-                                // fully capture variables.
-                                let _ = (&envs_to_set, &collection);
-                                let bytes: Vec<Vec<u8>> = try_join_all(futures?).await?;
-                                let envs: Vec<_> = envs_to_set
+                    export_job(
+                        context,
+                        account_hash,
+                        &envs_to_set,
+                        path,
+                        ("mbox", "mbox"),
+                        move |mut path, collection, env_hashes, bytes| {
+                            let envs: Vec<_> = env_hashes
+                                .iter()
+                                .map(|&env_hash| collection.get_env(env_hash))
+                                .collect();
+                            if path.is_dir() {
+                                if envs.len() == 1 {
+                                    path.push(format!("{}.mbox", envs[0].message_id()));
+                                } else {
+                                    let now = datetime::timestamp_to_string(
+                                        datetime::now(),
+                                        Some(datetime::formats::RFC3339_DATETIME),
+                                        false,
+                                    );
+                                    path.push(format!(
+                                        "{}-{}-{}_envelopes.mbox",
+                                        now,
+                                        envs[0].message_id(),
+                                        envs.len(),
+                                    ));
+                                }
+                            }
+                            let mut file = BufWriter::new(
+                                File::options()
+                                    .read(true)
+                                    .write(true)
+                                    .create_new(true)
+                                    .open(&path)?,
+                            );
+                            let tags_lck = collection.tag_index.read().unwrap();
+                            for (i, (env, bytes)) in envs.iter().zip(bytes).enumerate() {
+                                let tags: Vec<&str> = env
+                                    .tags()
                                     .iter()
-                                    .map(|&env_hash| collection.get_env(env_hash))
+                                    .filter_map(|h| tags_lck.get(h).map(|s| s.as_str()))
                                     .collect();
-                                if path.is_dir() {
-                                    if envs.len() == 1 {
-                                        path.push(format!("{}.mbox", envs[0].message_id()));
-                                    } else {
-                                        let now = datetime::timestamp_to_string(
-                                            datetime::now(),
-                                            Some(datetime::formats::RFC3339_DATETIME),
-                                            false,
-                                        );
-                                        path.push(format!(
-                                            "{}-{}-{}_envelopes.mbox",
-                                            now,
-                                            envs[0].message_id(),
-                                            envs.len(),
-                                        ));
-                                    }
+                                format.append(
+                                    &mut file,
+                                    bytes.as_slice(),
+                                    env.from().first(),
+                                    Some(env.date()),
+                                    (env.flags(), tags),
+                                    MboxMetadata::CClient,
+                                    i == 0,
+                                    false,
+                                )?;
+                            }
+                            file.flush()?;
+                            Ok(path)
+                        },
+                        |path| format!("Wrote to file {}", path.display()),
+                    );
+                }
+                ListingAction::ExportEml(ref path) => {
+                    export_job(
+                        context,
+                        account_hash,
+                        &envs_to_set,
+                        path,
+                        ("eml", "e-mail"),
+                        |path, collection, env_hashes, bytes| {
+                            std::fs::create_dir_all(&path)?;
+                            let tags_lck = collection.tag_index.read().unwrap();
+                            for (&env_hash, bytes) in env_hashes.iter().zip(bytes) {
+                                let env = collection.get_env(env_hash);
+                                let tags: Vec<&str> = env
+                                    .tags()
+                                    .iter()
+                                    .filter_map(|h| tags_lck.get(h).map(|s| s.as_str()))
+                                    .collect();
+                                let file_name: String = env
+                                    .message_id()
+                                    .to_string()
+                                    .chars()
+                                    .map(|c| {
+                                        if c.is_ascii_alphanumeric() || "@.-_+".contains(c) {
+                                            c
+                                        } else {
+                                            '_'
+                                        }
+                                    })
+                                    .collect();
+                                let mut file_path = path.join(format!("{file_name}.eml"));
+                                let mut ctr = 1;
+                                while file_path.try_exists()? {
+                                    file_path = path.join(format!("{file_name}-{ctr}.eml"));
+                                    ctr += 1;
                                 }
                                 let mut file = BufWriter::new(
                                     File::options()
-                                        .read(true)
                                         .write(true)
                                         .create_new(true)
-                                        .open(&path)?,
+                                        .open(&file_path)?,
                                 );
-                                let mut iter = envs.iter().zip(bytes);
-                                let tags_lck = collection.tag_index.read().unwrap();
-                                if let Some((env, ref bytes)) = iter.next() {
-                                    let tags: Vec<&str> = env
-                                        .tags()
-                                        .iter()
-                                        .filter_map(|h| tags_lck.get(h).map(|s| s.as_str()))
-                                        .collect();
-                                    format.append(
-                                        &mut file,
-                                        bytes.as_slice(),
-                                        env.from().first(),
-                                        Some(env.date()),
-                                        (env.flags(), tags),
-                                        MboxMetadata::CClient,
-                                        true,
-                                        false,
-                                    )?;
-                                }
-                                for (env, bytes) in iter {
-                                    let tags: Vec<&str> = env
-                                        .tags()
-                                        .iter()
-                                        .filter_map(|h| tags_lck.get(h).map(|s| s.as_str()))
-                                        .collect();
-                                    format.append(
-                                        &mut file,
-                                        bytes.as_slice(),
-                                        env.from().first(),
-                                        Some(env.date()),
-                                        (env.flags(), tags),
-                                        MboxMetadata::CClient,
-                                        false,
-                                        false,
-                                    )?;
-                                }
+                                MboxMetadata::CClient.write_message(
+                                    &mut file,
+                                    bytes.as_slice(),
+                                    (env.flags(), tags),
+                                )?;
                                 file.flush()?;
-                                Ok(path)
-                            };
-                            let r: Result<PathBuf> = cl.await;
-                            let _ = sender.send(r);
-                            Ok(())
-                        });
-                    let handle = account.main_loop_handler.job_executor.spawn(
-                        "exporting-mbox".into(),
-                        fut,
-                        IsAsync::Blocking,
+                            }
+                            Ok((path, env_hashes.len()))
+                        },
+                        |(path, len)| {
+                            format!(
+                                "Wrote {len} message{} to directory {}",
+                                if len == 1 { "" } else { "s" },
+                                path.display()
+                            )
+                        },
                     );
-                    account.insert_job(
-                        handle.job_id,
-                        JobRequest::Generic {
-                            name: "exporting mbox".into(),
-                            handle,
-                            on_finish: Some(CallbackFn(Box::new(move |context: &mut Context| {
-                                context.replies.push_back(match receiver.try_recv() {
-                                    Err(_) | Ok(None) => UIEvent::Notification {
-                                        title: Some("Could not export mbox".into()),
-                                        source: None,
-                                        body: "Job was canceled.".into(),
-                                        kind: Some(NotificationType::Info),
-                                    },
-                                    Ok(Some(Err(err))) => UIEvent::Notification {
-                                        title: Some("Could not export mbox".into()),
-                                        source: None,
-                                        body: err.to_string().into(),
-                                        kind: Some(NotificationType::Error(err.kind)),
-                                    },
-                                    Ok(Some(Ok(path))) => UIEvent::Notification {
-                                        title: Some("Successfully exported mbox".into()),
-                                        source: None,
-                                        body: format!("Wrote to file {}", path.display()).into(),
-                                        kind: Some(NotificationType::Info),
-                                    },
-                                });
-                            }))),
-                            log_level: LogLevel::INFO,
+                }
+                ListingAction::ExportThread(format, ref path) => {
+                    let env_hashes = crate::accounts::thread_envelopes(
+                        &account.collection.get_threads(mailbox_hash),
+                        &envs_to_set,
+                    );
+                    let format = *format;
+                    export_job(
+                        context,
+                        account_hash,
+                        &env_hashes,
+                        path,
+                        ("thread", "thread"),
+                        move |mut path, collection, env_hashes, bytes| {
+                            let messages: Vec<(Envelope, Vec<u8>)> = env_hashes
+                                .iter()
+                                .map(|&env_hash| (*collection.get_env(env_hash)).clone())
+                                .zip(bytes)
+                                .collect();
+                            if path.is_dir() {
+                                let subject = messages
+                                    .iter()
+                                    .min_by_key(|(env, _)| env.date())
+                                    .map(|(env, _)| env.subject().to_string())
+                                    .unwrap_or_default();
+                                let mut file_name: String = subject
+                                    .chars()
+                                    .map(|c| {
+                                        if c.is_alphanumeric() || "-_+".contains(c) {
+                                            c
+                                        } else {
+                                            '_'
+                                        }
+                                    })
+                                    .take(64)
+                                    .collect();
+                                if file_name.is_empty() {
+                                    file_name.push_str("thread");
+                                }
+                                path.push(format!(
+                                    "{file_name}.{}",
+                                    match format {
+                                        ThreadExportFormat::Html => "html",
+                                        ThreadExportFormat::Markdown => "md",
+                                    }
+                                ));
+                            }
+                            let document = crate::mail::export::thread_document(format, &messages);
+                            let mut file = BufWriter::new(
+                                File::options().write(true).create_new(true).open(&path)?,
+                            );
+                            file.write_all(document.as_bytes())?;
+                            file.flush()?;
+                            Ok((path, messages.len()))
+                        },
+                        |(path, len)| {
+                            format!(
+                                "Wrote {len} message{} to {}",
+                                if len == 1 { "" } else { "s" },
+                                path.display()
+                            )
                         },
                     );
                 }
+                ListingAction::AwaitReply(ref action) => {
+                    let result = match action {
+                        AwaitReplyAction::By(deadline) => {
+                            account.await_reply(&envs_to_set, *deadline)
+                        }
+                        AwaitReplyAction::Within(secs) => {
+                            account.await_reply(&envs_to_set, datetime::now().saturating_add(*secs))
+                        }
+                        AwaitReplyAction::Cancel => account.cancel_await_reply(&envs_to_set),
+                    };
+                    if let Err(err) = result {
+                        context.replies.push_back(UIEvent::Notification {
+                            title: Some("Could not save follow-up reminder".into()),
+                            source: None,
+                            body: err.to_string().into(),
+                            kind: Some(NotificationType::Error(err.kind)),
+                        });
+                    }
+                }
+                ListingAction::CopyThreadTo(ref mailbox_path)
+                | ListingAction::MoveThreadTo(ref mailbox_path) => {
+                    let move_ = matches!(a, ListingAction::MoveThreadTo(_));
+                    if let Err(err) =
+                        account
+                            .mailbox_by_path(mailbox_path)
+                            .and_then(|destination_mailbox_hash| {
+                                account.copy_threads(
+                                    &envs_to_set,
+                                    mailbox_hash,
+                                    destination_mailbox_hash,
+                                    move_,
+                                )
+                            })
+                    {
+                        context.replies.push_back(UIEvent::Notification {
+                            title: Some(
+                                if move_ {
+                                    "Could not move thread"
+                                } else {
+                                    "Could not copy thread"
+                                }
+                                .into(),
+                            ),
+                            source: None,
+                            body: err.to_string().into(),
+                            kind: Some(NotificationType::Error(err.kind)),
+                        });
+                    }
+                }
                 ListingAction::MoveToOtherAccount(ref _account_name, ref _mailbox_path) => {
                     context.replies.push_back(UIEvent::Notification {
                         title: Some("Could not move mail".into()),
@@ -1063,6 +1726,24 @@ pub trait MailListingTrait: ListingTrait {
         }
         let account_hash = self.coordinates().0;
         let mailbox_hash = self.coordinates().1;
+        let thread_action;
+        let a = match a {
+            ListingAction::CopyTo(ref mailbox_path) | ListingAction::MoveTo(ref mailbox_path)
+                if *mailbox_settings!(
+                    context[account_hash][&mailbox_hash]
+                        .listing
+                        .copy_move_whole_thread
+                ) =>
+            {
+                thread_action = if matches!(a, ListingAction::CopyTo(_)) {
+                    ListingAction::CopyThreadTo(mailbox_path.clone())
+                } else {
+                    ListingAction::MoveThreadTo(mailbox_path.clone())
+                };
+                &thread_action
+            }
+            a => a,
+        };
         /*{
             let threads_lck = account.collection.get_threads(mailbox_hash);
             for thread_hash in thread_hashes {
@@ -1073,7 +1754,23 @@ pub trait MailListingTrait: ListingTrait {
             }
         }
         */
-        inner(context, envs_to_set, account_hash, mailbox_hash, a);
+        if matches!(
+            a,
+            ListingAction::ExportMbox(_, _)
+                | ListingAction::ExportEml(_)
+                | ListingAction::ExportThread(_, _)
+                | ListingAction::AwaitReply(_)
+        ) {
+            inner(context, envs_to_set, account_hash, mailbox_hash, a);
+        } else {
+            // Envelopes of tag mailboxes have to be operated on in the mailboxes
+            // they are stored in.
+            for (mailbox_hash, envs_to_set) in
+                context.accounts[&account_hash].backing_mailboxes(mailbox_hash, &envs_to_set)
+            {
+                inner(context, envs_to_set, account_hash, mailbox_hash, a);
+            }
+        }
         self.set_dirty(true);
     }
 
@@ -1107,6 +1804,13 @@ pub trait ListingTrait: Component {
     fn draw_list(&mut self, grid: &mut CellBuffer, area: Area, context: &mut Context);
     fn highlight_line(&mut self, grid: &mut CellBuffer, area: Area, idx: usize, context: &Context);
     fn filter(&mut self, _filter_term: String, _results: Vec<EnvelopeHash>, _context: &Context) {}
+
+    /// The active `limit` query, if any.
+    fn limit(&self) -> Option<&ListingLimit> {
+        None
+    }
+
+    fn set_limit(&mut self, _limit: Option<ListingLimit>) {}
     fn unfocused(&self) -> bool;
     fn view_area(&self) -> Option<Area>;
     fn set_modifier_active(&mut self, _new_val: bool);
@@ -1114,6 +1818,28 @@ pub trait ListingTrait: Component {
     fn set_modifier_command(&mut self, _new_val: Option<Modifier>);
     fn modifier_command(&self) -> Option<Modifier>;
     fn set_movement(&mut self, mvm: PageMovement);
+
+    /// Row of the cursor in the list.
+    fn cursor_row(&self) -> usize {
+        0
+    }
+
+    /// E-mail in the row of the cursor, the first one of the thread if rows
+    /// are threads.
+    fn cursor_envelope(&self) -> Option<EnvelopeHash> {
+        None
+    }
+
+    /// Row of e-mail `env_hash` in the list, if it is listed.
+    fn envelope_row(&self, _env_hash: EnvelopeHash) -> Option<usize> {
+        None
+    }
+
+    /// Date of each row of the list, in order.
+    fn entry_dates(&self, _context: &Context) -> Vec<UnixTimestamp> {
+        vec![]
+    }
+
     fn focus(&self) -> Focus;
     fn set_focus(&mut self, new_value: Focus, context: &mut Context);
 
@@ -1132,32 +1858,32 @@ pub trait ListingTrait: Component {
     }
 
     fn format_date(&self, context: &Context, epoch: UnixTimestamp) -> String {
+        let (account_hash, mailbox_hash) = self.coordinates();
+        let recent_dates =
+            *mailbox_settings!(context[account_hash][&mailbox_hash].listing.recent_dates);
         let d = std::time::UNIX_EPOCH + std::time::Duration::from_secs(epoch);
         let now: std::time::Duration = std::time::SystemTime::now()
             .duration_since(d)
             .unwrap_or_else(|_| std::time::Duration::new(u64::MAX, 0));
         match now.as_secs() {
-            n if context.settings.listing.recent_dates && n < 60 * 60 => format!(
+            n if recent_dates && n < 60 * 60 => format!(
                 "{} minute{} ago",
                 n / (60),
                 if n / 60 == 1 { "" } else { "s" }
             ),
-            n if context.settings.listing.recent_dates && n < 24 * 60 * 60 => format!(
+            n if recent_dates && n < 24 * 60 * 60 => format!(
                 "{} hour{} ago",
                 n / (60 * 60),
                 if n / (60 * 60) == 1 { "" } else { "s" }
             ),
-            n if context.settings.listing.recent_dates && n < 7 * 24 * 60 * 60 => format!(
+            n if recent_dates && n < 7 * 24 * 60 * 60 => format!(
                 "{} day{} ago",
                 n / (24 * 60 * 60),
                 if n / (24 * 60 * 60) == 1 { "" } else { "s" }
             ),
             _ => melib::utils::datetime::timestamp_to_string(
                 epoch,
-                context
-                    .settings
-                    .listing
-                    .datetime_fmt
+                mailbox_settings!(context[account_hash][&mailbox_hash].listing.datetime_fmt)
                     .as_deref()
                     .or(Some("%Y-%m-%d %T")),
                 false,
@@ -1256,6 +1982,8 @@ pub struct Listing {
     component: ListingComponent,
     accounts: Vec<AccountMenuEntry>,
     status: Option<AccountStatus>,
+    /// Startup dashboard, shown until a mailbox is opened.
+    dashboard: Option<Box<Dashboard>>,
     dirty: bool,
     cursor_pos: CursorPos,
     menu_cursor_pos: CursorPos,
@@ -1276,8 +2004,40 @@ pub struct Listing {
     ratio: usize, // right/(container width) * 100
     prev_ratio: usize,
     menu_width: WidgetWidth,
+    /// Layouts that `next_layout` cycles through, see [`PaneLayout`].
+    layouts: Vec<PaneLayout>,
+    layout_idx: usize,
     focus: ListingFocus,
     view: Option<Box<ThreadView>>,
+    /// Cached [`Collection::newest_date`] values of mailboxes shown in the
+    /// sidebar, by account and mailbox, cleared whenever mailbox contents
+    /// change.
+    ///
+    /// [`Collection::newest_date`]: melib::Collection::newest_date
+    sidebar_recency: HashMap<(AccountHash, MailboxHash), Option<UnixTimestamp>>,
+    /// Saved state of each account, if `listing.restore_state` is enabled
+    /// for it.
+    saved_state: Vec<Option<ListingState>>,
+    /// Message-ID of the saved selection of the open mailbox, until it is
+    /// listed and the cursor is moved to it.
+    pending_selection: Option<(AccountHash, MailboxHash, String)>,
+}
+
+impl Drop for Listing {
+    fn drop(&mut self) {
+        for (entry, state) in self.accounts.iter().zip(self.saved_state.iter()) {
+            let Some(state) = state else {
+                continue;
+            };
+            if let Err(err) = state.save(&entry.name) {
+                log::error!(
+                    "Could not save listing state of account {}: {}",
+                    entry.name,
+                    err
+                );
+            }
+        }
+    }
 }
 
 impl std::fmt::Display for Listing {
@@ -1297,9 +2057,14 @@ impl Component for Listing {
         if !self.is_dirty() {
             return;
         }
+        self.save_selection(context);
+        self.select_pending(context);
         let total_cols = area.width();
 
-        let right_component_width = if self.is_menu_visible() {
+        // The sidebar is hidden while zoomed, unless it is focused.
+        let menu_visible =
+            self.is_menu_visible() && (!context.zoomed || self.focus == ListingFocus::Menu);
+        let right_component_width = if menu_visible {
             if self.focus == ListingFocus::Menu {
                 (self.ratio * total_cols) / 100
             } else {
@@ -1350,31 +2115,12 @@ impl Component for Listing {
                 self.component.realize(self.id().into(), context);
             }
 
-            if let Some(s) = self.status.as_mut() {
+            if let Some(d) = self.dashboard.as_mut() {
+                d.draw(grid, area, context);
+            } else if let Some(s) = self.status.as_mut() {
                 s.draw(grid, area, context);
             } else {
-                self.component.draw(grid, area, context);
-                if self.component.unfocused() {
-                    if let Some(ref mut view) = self.view {
-                        view.draw(grid, self.component.view_area().unwrap_or(area), context);
-                        if let Some(view_area) = self.component.view_area() {
-                            if view_area != area {
-                                let divider_area =
-                                    area.nth_col(area.width() - view_area.width() - 1);
-                                for row in grid.bounds_iter(divider_area) {
-                                    for c in row {
-                                        grid[c]
-                                            .set_ch(self.mail_view_divider)
-                                            .set_fg(self.mail_view_divider_theme.fg)
-                                            .set_bg(self.mail_view_divider_theme.bg)
-                                            .set_attrs(self.mail_view_divider_theme.attrs);
-                                    }
-                                }
-                                context.dirty_areas.push_back(divider_area);
-                            }
-                        }
-                    }
-                }
+                self.draw_component(grid, area, context);
             }
         } else if right_component_width == 0 {
             self.draw_menu(grid, area, context);
@@ -1390,38 +2136,22 @@ impl Component for Listing {
                     .process_event(&mut UIEvent::VisibilityChange(true), context);
                 self.component.realize(self.id().into(), context);
             }
-            if let Some(s) = self.status.as_mut() {
+            if let Some(d) = self.dashboard.as_mut() {
+                d.draw(grid, area.skip_cols(mid + 1), context);
+            } else if let Some(s) = self.status.as_mut() {
                 s.draw(grid, area.skip_cols(mid + 1), context);
             } else {
-                let area = area.skip_cols(mid + 1);
-                self.component.draw(grid, area, context);
-                if self.component.unfocused() {
-                    if let Some(ref mut view) = self.view {
-                        view.draw(grid, self.component.view_area().unwrap_or(area), context);
-                        if let Some(view_area) = self.component.view_area() {
-                            if view_area != area {
-                                let divider_area =
-                                    area.nth_col(area.width() - view_area.width() - 1);
-                                for row in grid.bounds_iter(divider_area) {
-                                    for c in row {
-                                        grid[c]
-                                            .set_ch(self.mail_view_divider)
-                                            .set_fg(self.mail_view_divider_theme.fg)
-                                            .set_bg(self.mail_view_divider_theme.bg)
-                                            .set_attrs(self.mail_view_divider_theme.attrs);
-                                    }
-                                }
-                                context.dirty_areas.push_back(divider_area);
-                            }
-                        }
-                    }
-                }
+                self.draw_component(grid, area.skip_cols(mid + 1), context);
             }
         }
         self.dirty = false;
     }
 
     fn process_event(&mut self, event: &mut UIEvent, context: &mut Context) -> bool {
+        if matches!(event, UIEvent::Input(_)) {
+            // The user has moved on, the saved selection is not restored anymore.
+            self.pending_selection = None;
+        }
         match event {
             UIEvent::ConfigReload { old_settings: _ } => {
                 self.theme_default = crate::conf::value(context, "theme_default");
@@ -1432,6 +2162,10 @@ impl Component for Listing {
                 self.mail_view_divider =
                     *account_settings!(context[account_hash].listing.mail_view_divider);
                 self.mail_view_divider_theme = conf::value(context, "mail.view.divider");
+                self.layouts = context.settings.listing.layouts.clone();
+                if self.layout_idx >= self.layouts.len() {
+                    self.layout_idx = 0;
+                }
                 self.menu.grid_mut().empty();
                 self.set_dirty(true);
             }
@@ -1457,13 +2191,31 @@ impl Component for Listing {
                     );
                 }
             }
+            UIEvent::AccountAdded(account_hash) => {
+                if self.accounts.iter().all(|a| a.hash != *account_hash) {
+                    let index = self.accounts.len();
+                    let state = Self::load_saved_state(*account_hash, context);
+                    self.accounts.push(Self::account_menu_entry(
+                        *account_hash,
+                        index,
+                        state.as_ref(),
+                        context,
+                    ));
+                    self.saved_state.push(state);
+                    self.set_dirty(true);
+                }
+                return false;
+            }
             UIEvent::AccountStatusChange(account_hash, msg) => {
                 let account_index: usize = context
                     .accounts
                     .get_index_of(account_hash)
                     .expect("Invalid account_hash in UIEventMailbox{Delete,Create}");
                 if self.cursor_pos.account == account_index {
+                    // The account coming online is not a reason to close the dashboard.
+                    let dashboard = self.dashboard.take();
                     self.change_account(context);
+                    self.dashboard = dashboard;
                 } else {
                     let previous_collapsed_mailboxes: BTreeSet<MailboxHash> = self.accounts
                         [account_index]
@@ -1498,7 +2250,11 @@ impl Component for Listing {
                             mailbox_hash: f.hash,
                             visible: true,
                             collapsed: if previous_collapsed_mailboxes.is_empty() {
-                                context.accounts[&*account_hash][&f.hash].conf.collapsed
+                                let entry = &context.accounts[&*account_hash][&f.hash];
+                                self.saved_state[account_index]
+                                    .as_ref()
+                                    .and_then(|s| s.is_collapsed(&entry.path))
+                                    .unwrap_or(entry.conf.collapsed)
                             } else {
                                 previous_collapsed_mailboxes.contains(&f.hash)
                             },
@@ -1599,12 +2355,21 @@ impl Component for Listing {
                     self.accounts[self.cursor_pos.account].entries.get(*idx)
                 {
                     let account_hash = self.accounts[self.cursor_pos.account].hash;
+                    let mailbox_hash = *mailbox_hash;
+                    let reopened = self.component.coordinates() == (account_hash, mailbox_hash);
                     self.cursor_pos.menu = MenuEntryCursor::Mailbox(*idx);
                     self.status = None;
+                    self.close_dashboard(context);
+                    if !reopened {
+                        self.save_selection(context);
+                    }
                     self.component
                         .process_event(&mut UIEvent::VisibilityChange(false), context);
-                    self.component
-                        .set_coordinates((account_hash, *mailbox_hash));
+                    self.component.set_coordinates((account_hash, mailbox_hash));
+                    if !reopened {
+                        self.restore_selection(context);
+                        self.announce_mailbox(context);
+                    }
                     self.component
                         .process_event(&mut UIEvent::VisibilityChange(true), context);
                     self.menu.grid_mut().empty();
@@ -1643,10 +2408,11 @@ impl Component for Listing {
                         go_to_first_unread,
                     }) => {
                         let (a, m) = self.component.coordinates();
+                        self.save_collapsed_threads(context);
                         if let Some(view) = self.view.take() {
                             view.unrealize(context);
                         }
-                        self.view = Some(Box::new(ThreadView::new(
+                        let mut view = Box::new(ThreadView::new(
                             (a, m, env_hash),
                             thread_hash,
                             Some(env_hash),
@@ -1657,11 +2423,34 @@ impl Component for Listing {
                                 Some(ThreadViewFocus::MailView)
                             },
                             context,
-                        )));
+                        ));
+                        if let Some(state) = context
+                            .accounts
+                            .get_index_of(&a)
+                            .and_then(|idx| self.saved_state.get(idx))
+                            .and_then(Option::as_ref)
+                        {
+                            view.collapse_entries(&state.collapsed_threads, context);
+                        }
+                        self.view = Some(view);
                     }
                 }
                 return true;
             }
+            UIEvent::IntraComm {
+                from,
+                to,
+                ref content,
+            } if *to == self.id() && self.dashboard.as_ref().is_some_and(|d| d.id() == *from) => {
+                if let Some(ListingMessage::OpenMailbox {
+                    account_hash,
+                    mailbox_hash,
+                }) = content.downcast_ref::<ListingMessage>().copied()
+                {
+                    self.open_mailbox(account_hash, mailbox_hash, context);
+                }
+                return true;
+            }
             #[cfg(feature = "debug-tracing")]
             UIEvent::IntraComm {
                 from,
@@ -1689,9 +2478,20 @@ impl Component for Listing {
                 .map(|v| v.process_event(event, context))
                 .unwrap_or(false)
         {
+            if matches!(event, UIEvent::Input(_)) {
+                self.save_collapsed_threads(context);
+            }
             return true;
         }
 
+        if let Some(d) = self.dashboard.as_mut() {
+            if (self.focus == ListingFocus::Mailbox || !matches!(event, UIEvent::Input(_)))
+                && d.process_event(event, context)
+            {
+                return true;
+            }
+        }
+
         if self.focus == ListingFocus::Mailbox && self.status.is_some() {
             if let Some(s) = self.status.as_mut() {
                 if s.process_event(event, context) {
@@ -1703,7 +2503,9 @@ impl Component for Listing {
         let mut have_forwarded_to_component = false;
         // Forward events to self.component if it's focused, otherwise forward any
         // unhandled events to self.component at the end of this function.
-        if (self.focus == ListingFocus::Mailbox && self.status.is_none())
+        if (self.focus == ListingFocus::Mailbox
+            && self.status.is_none()
+            && self.dashboard.is_none())
             && ((self.component.unfocused()
                 && self
                     .view
@@ -1873,6 +2675,20 @@ impl Component for Listing {
                     self.menu_visibility = !self.menu_visibility;
                     self.set_dirty(true);
                 }
+                UIEvent::Input(ref k)
+                    if shortcut!(k == shortcuts[Shortcuts::LISTING]["next_layout"]) =>
+                {
+                    if self.layouts.len() > 1 {
+                        self.layout_idx = (self.layout_idx + 1) % self.layouts.len();
+                        let layout = self.layout();
+                        self.menu_visibility = layout.sidebar;
+                        context.replies.push_back(UIEvent::StatusEvent(
+                            StatusEvent::DisplayMessage(format!("Layout: {layout}")),
+                        ));
+                        self.set_dirty(true);
+                    }
+                    return true;
+                }
                 UIEvent::Input(ref k)
                     if shortcut!(k == shortcuts[Shortcuts::LISTING]["increase_sidebar"]) =>
                 {
@@ -1913,6 +2729,48 @@ impl Component for Listing {
                             self.set_index_style(IndexStyle::Conversations, context);
                             return true;
                         }
+                        Action::Listing(ListingAction::SetLocal(key, value)) => {
+                            let (account_hash, mailbox_hash) = self.component.coordinates();
+                            let result = match context.accounts[&account_hash]
+                                .mailbox_entries
+                                .get_mut(&mailbox_hash)
+                            {
+                                Some(entry) => entry.conf.conf_override.set_key(key, value),
+                                None => Err(Error::new("No mailbox is open.")),
+                            };
+                            match result {
+                                Ok(()) => {
+                                    if key == "listing.index_style" {
+                                        // Forget styles set with `set plain` etc.
+                                        if let MenuEntryCursor::Mailbox(idx) = self.cursor_pos.menu
+                                        {
+                                            if let Some(entry) = self.accounts
+                                                [self.cursor_pos.account]
+                                                .entries
+                                                .get_mut(idx)
+                                            {
+                                                entry.index_style = None;
+                                            }
+                                        }
+                                    }
+                                    self.reload_component(context);
+                                    context.replies.push_back(UIEvent::StatusEvent(
+                                        StatusEvent::DisplayMessage(format!(
+                                            "{key} set to {value} for this mailbox."
+                                        )),
+                                    ));
+                                }
+                                Err(err) => {
+                                    context.replies.push_back(UIEvent::Notification {
+                                        title: Some(format!("Could not set {key}").into()),
+                                        source: None,
+                                        body: err.to_string().into(),
+                                        kind: Some(NotificationType::Error(err.kind)),
+                                    });
+                                }
+                            }
+                            return true;
+                        }
                         Action::Listing(ListingAction::Import(file_path, mailbox_path)) => {
                             let file_path = file_path.expand();
                             let account = &mut context.accounts[self.cursor_pos.account];
@@ -1944,11 +2802,16 @@ impl Component for Listing {
                         | Action::Listing(a @ ListingAction::Delete)
                         | Action::Listing(a @ ListingAction::CopyTo(_))
                         | Action::Listing(a @ ListingAction::MoveTo(_))
+                        | Action::Listing(a @ ListingAction::CopyThreadTo(_))
+                        | Action::Listing(a @ ListingAction::MoveThreadTo(_))
                         | Action::Listing(a @ ListingAction::CopyToOtherAccount(_, _))
                         | Action::Listing(a @ ListingAction::MoveToOtherAccount(_, _))
                         | Action::Listing(a @ ListingAction::ExportMbox(_, _))
+                        | Action::Listing(a @ ListingAction::ExportEml(_))
+                        | Action::Listing(a @ ListingAction::ExportThread(_, _))
                         | Action::Listing(a @ ListingAction::Flag(_))
                         | Action::Listing(a @ ListingAction::Tag(_))
+                        | Action::Listing(a @ ListingAction::AwaitReply(_))
                         | Action::Listing(a @ ListingAction::SendToTrash) => {
                             let focused = self.component.get_focused_items(context);
                             self.component.perform_action(context, focused, a);
@@ -1956,6 +2819,7 @@ impl Component for Listing {
                                 a,
                                 ListingAction::Delete
                                     | ListingAction::MoveTo(_)
+                                    | ListingAction::MoveThreadTo(_)
                                     | ListingAction::MoveToOtherAccount(_, _)
                                     | ListingAction::SendToTrash
                             );
@@ -1966,7 +2830,207 @@ impl Component for Listing {
                                     row_updates.push(*k);
                                 }
                             }
-                            self.component.row_updates().extend(row_updates);
+                            self.component.row_updates().extend(row_updates);
+                            return true;
+                        }
+                        Action::Listing(
+                            a @ (ListingAction::EmptyTrash
+                            | ListingAction::EmptyMailbox(_)
+                            | ListingAction::Expunge),
+                        ) => {
+                            use melib::backends::SpecialUsageMailbox;
+
+                            let (account_hash, mailbox_hash) = self.component.coordinates();
+                            let account = &mut context.accounts[&account_hash];
+                            let mailbox_hash = match a {
+                                ListingAction::EmptyTrash => account
+                                    .special_use_mailbox(SpecialUsageMailbox::Trash)
+                                    .ok_or_else(|| {
+                                        Error::new("No Trash mailbox is configured.")
+                                            .set_kind(ErrorKind::NotFound)
+                                    }),
+                                ListingAction::EmptyMailbox(Some(path)) => {
+                                    account.mailbox_by_path(path)
+                                }
+                                _ => Ok(mailbox_hash),
+                            };
+                            let result = mailbox_hash.and_then(|mailbox_hash| {
+                                let env_hashes = account.mailbox_envelopes(
+                                    mailbox_hash,
+                                    matches!(a, ListingAction::Expunge),
+                                );
+                                let n = account.schedule_deletion(mailbox_hash, &env_hashes)?;
+                                Ok((n, account[&mailbox_hash].name().to_string()))
+                            });
+                            match result {
+                                Ok((0, name)) => {
+                                    context.replies.push_back(UIEvent::StatusEvent(
+                                        StatusEvent::DisplayMessage(format!(
+                                            "Nothing to delete in {name}."
+                                        )),
+                                    ));
+                                }
+                                Ok((n, name)) => {
+                                    let grace_period = account.settings.conf.delete_grace_period();
+                                    context.replies.push_back(UIEvent::StatusEvent(
+                                        StatusEvent::DisplayMessage(if grace_period.is_zero() {
+                                            format!("Deleting {n} e-mail from {name}.")
+                                        } else {
+                                            format!(
+                                                "Deleting {n} e-mail from {name} in {} seconds. \
+                                                 Run `undo-delete` to cancel.",
+                                                grace_period.as_secs()
+                                            )
+                                        }),
+                                    ));
+                                }
+                                Err(err) => {
+                                    context.replies.push_back(UIEvent::Notification {
+                                        title: Some("Could not delete mail".into()),
+                                        source: None,
+                                        body: err.to_string().into(),
+                                        kind: Some(NotificationType::Error(err.kind)),
+                                    });
+                                }
+                            }
+                            return true;
+                        }
+                        Action::Listing(ListingAction::UndoDelete) => {
+                            let account_hash = self.component.coordinates().0;
+                            let n = context.accounts[&account_hash].cancel_pending_deletions();
+                            context.replies.push_back(UIEvent::StatusEvent(
+                                StatusEvent::DisplayMessage(if n == 0 {
+                                    "No deletions to cancel.".to_string()
+                                } else {
+                                    format!("Cancelled the deletion of {n} e-mail.")
+                                }),
+                            ));
+                            return true;
+                        }
+                        Action::Listing(
+                            a @ (ListingAction::SearchInTab(_)
+                            | ListingAction::RefineSearch(_)
+                            | ListingAction::RerunSearch
+                            | ListingAction::Correspondence(_)),
+                        ) => {
+                            let account_hash = self.component.coordinates().0;
+                            // Without an address, show the correspondence with the sender
+                            // of the entry under the cursor.
+                            let sender = match a {
+                                ListingAction::Correspondence(None) => {
+                                    self.component.get_focused_items(context).first().and_then(
+                                        |h| {
+                                            context.accounts[&account_hash]
+                                                .collection
+                                                .get_env(*h)
+                                                .from()
+                                                .first()
+                                                .cloned()
+                                        },
+                                    )
+                                }
+                                _ => None,
+                            };
+                            let account = &mut context.accounts[&account_hash];
+                            let result = match a {
+                                ListingAction::SearchInTab(query) => {
+                                    account.search_in_results_mailbox(query)
+                                }
+                                ListingAction::RefineSearch(refinement) => {
+                                    account.refine_search(refinement)
+                                }
+                                ListingAction::Correspondence(Some(address)) => {
+                                    melib::Address::try_from(address.as_str())
+                                        .and_then(|address| account.search_correspondence(&address))
+                                }
+                                ListingAction::Correspondence(None) => match sender {
+                                    Some(address) => account.search_correspondence(&address),
+                                    None => Err(Error::new("No entry is selected.")
+                                        .set_kind(ErrorKind::NotFound)),
+                                },
+                                _ => account.rerun_search(),
+                            };
+                            match result {
+                                Ok(mailbox_hash) => {
+                                    context.replies.push_back(UIEvent::StatusEvent(
+                                        StatusEvent::DisplayMessage(format!(
+                                            "Searching for: {}",
+                                            account.search_results.query.as_deref().unwrap_or("")
+                                        )),
+                                    ));
+                                    // The results are updated in place if they are already
+                                    // shown in this tab.
+                                    if self.component.coordinates() != (account_hash, mailbox_hash)
+                                    {
+                                        let listing =
+                                            Self::new_tab(context, account_hash, mailbox_hash);
+                                        context.replies.push_back(UIEvent::Action(Tab(New(Some(
+                                            Box::new(listing),
+                                        )))));
+                                    }
+                                }
+                                Err(err) => {
+                                    context.replies.push_back(UIEvent::Notification {
+                                        title: Some("Could not perform search".into()),
+                                        source: None,
+                                        body: err.to_string().into(),
+                                        kind: Some(NotificationType::Error(err.kind)),
+                                    });
+                                }
+                            }
+                            return true;
+                        }
+                        Action::Listing(ListingAction::JumpToDate(date)) => {
+                            // Jump to the oldest entry on or after `date`, which works
+                            // regardless of the sort order.
+                            let target = self
+                                .component
+                                .entry_dates(context)
+                                .into_iter()
+                                .enumerate()
+                                .filter(|(_, d)| d >= date)
+                                .min_by_key(|&(i, d)| (d, i))
+                                .map(|(i, _)| i);
+                            let Some(target) = target else {
+                                context.replies.push_back(UIEvent::StatusEvent(
+                                    StatusEvent::DisplayMessage(format!(
+                                        "No e-mail on or after {}.",
+                                        datetime::timestamp_to_string(
+                                            *date,
+                                            Some(datetime::formats::RFC3339_DATE),
+                                            false
+                                        )
+                                    )),
+                                ));
+                                return true;
+                            };
+                            let cursor = self.component.cursor_row();
+                            self.component.set_movement(if target >= cursor {
+                                PageMovement::Down(target - cursor)
+                            } else {
+                                PageMovement::Up(cursor - target)
+                            });
+                            return true;
+                        }
+                        Action::Listing(ListingAction::Limit(term)) => {
+                            let limit = match term.as_deref().map(ListingLimit::new).transpose() {
+                                Ok(limit) => limit,
+                                Err(err) => {
+                                    context.replies.push_back(UIEvent::Notification {
+                                        title: Some("Could not parse limit query".into()),
+                                        source: None,
+                                        body: err.to_string().into(),
+                                        kind: Some(NotificationType::Error(err.kind)),
+                                    });
+                                    return true;
+                                }
+                            };
+                            self.component.set_limit(limit);
+                            self.component.refresh_mailbox(context, true);
+                            self.component.set_dirty(true);
+                            context.replies.push_back(UIEvent::StatusEvent(
+                                StatusEvent::UpdateStatus(self.status(context)),
+                            ));
                             return true;
                         }
                         Action::Listing(ListingAction::ClearSelection) => {
@@ -2252,6 +3316,7 @@ impl Component for Listing {
                         .get_mut(target_mailbox_idx)
                     {
                         target.collapsed = !(target.collapsed);
+                        self.save_collapsed(self.menu_cursor_pos.account, context);
                         self.dirty = true;
                         self.menu.grid_mut().empty();
                         context
@@ -2630,6 +3695,51 @@ impl Component for Listing {
                     .push_back(UIEvent::Action(Tab(New(Some(Box::new(mgr))))));
                 return true;
             }
+            UIEvent::Action(Action::Tab(MailboxAttachments)) => {
+                let (account_hash, mailbox_hash) = self.component.coordinates();
+                match AttachmentBrowser::new(context, account_hash, mailbox_hash) {
+                    Ok(browser) => {
+                        context
+                            .replies
+                            .push_back(UIEvent::Action(Tab(New(Some(Box::new(browser))))));
+                    }
+                    Err(err) => {
+                        context.replies.push_back(UIEvent::Notification {
+                            title: Some("Could not list attachments".into()),
+                            source: None,
+                            body: err.to_string().into(),
+                            kind: Some(NotificationType::Error(err.kind)),
+                        });
+                    }
+                }
+                return true;
+            }
+            UIEvent::Action(Action::Tab(Triage)) => {
+                let (account_hash, mailbox_hash) = self.component.coordinates();
+                match TriageView::new(context, account_hash, mailbox_hash) {
+                    Ok(triage) => {
+                        context
+                            .replies
+                            .push_back(UIEvent::Action(Tab(New(Some(Box::new(triage))))));
+                    }
+                    Err(err) => {
+                        context.replies.push_back(UIEvent::Notification {
+                            title: Some("Could not start triage".into()),
+                            source: None,
+                            body: err.to_string().into(),
+                            kind: Some(NotificationType::Error(err.kind)),
+                        });
+                    }
+                }
+                return true;
+            }
+            UIEvent::Action(Action::Tab(ManageSettings)) => {
+                let editor = SettingsEditor::new(context);
+                context
+                    .replies
+                    .push_back(UIEvent::Action(Tab(New(Some(Box::new(editor))))));
+                return true;
+            }
             UIEvent::Action(Action::Compose(ComposeAction::Mailto(ref mailto))) => {
                 let account_hash = context.accounts[self.cursor_pos.account].hash();
                 let mut composer = Composer::with_account(account_hash, context);
@@ -2645,6 +3755,7 @@ impl Component for Listing {
             | UIEvent::EnvelopeRename(_, _)
             | UIEvent::EnvelopeRemove(_, _) => {
                 self.dirty = true;
+                self.sidebar_recency.clear();
                 // clear menu to force redraw
                 self.menu.grid_mut().empty();
                 context
@@ -2703,6 +3814,7 @@ impl Component for Listing {
 
     fn is_dirty(&self) -> bool {
         self.dirty
+            || self.dashboard.as_ref().is_some_and(|d| d.is_dirty())
             || self
                 .status
                 .as_ref()
@@ -2717,6 +3829,9 @@ impl Component for Listing {
 
     fn set_dirty(&mut self, value: bool) {
         self.dirty = value;
+        if let Some(d) = self.dashboard.as_mut() {
+            d.set_dirty(value);
+        }
         if let Some(s) = self.status.as_mut() {
             s.set_dirty(value);
         } else {
@@ -2736,7 +3851,9 @@ impl Component for Listing {
                 map.extend_shortcuts(view.shortcuts(context));
             }
         }
-        map.extend_shortcuts(if let Some(s) = self.status.as_ref() {
+        map.extend_shortcuts(if let Some(d) = self.dashboard.as_ref() {
+            d.shortcuts(context)
+        } else if let Some(s) = self.status.as_ref() {
             s.shortcuts(context)
         } else {
             self.component.shortcuts(context)
@@ -2754,6 +3871,16 @@ impl Component for Listing {
         self.id
     }
 
+    fn view_name(&self) -> Option<&'static str> {
+        if self.status.is_some() {
+            return Some("account status");
+        }
+        Some(match self.component.focus() {
+            Focus::None => "listing",
+            Focus::Entry | Focus::EntryFullscreen => "mail view",
+        })
+    }
+
     fn status(&self, context: &Context) -> String {
         let mailbox_hash = match self.cursor_pos.menu {
             MenuEntryCursor::Mailbox(idx) => {
@@ -2778,8 +3905,20 @@ impl Component for Listing {
                     .count()
                     .ok()
                     .unwrap_or((0, 0));
+                let search = if account.is_search_results_mailbox(mailbox_hash) {
+                    match (
+                        account.correspondence_summary(),
+                        account.search_results.query.as_ref(),
+                    ) {
+                        (Some(summary), _) => format!(", {summary}"),
+                        (None, Some(query)) => format!(", Search: {query}"),
+                        (None, None) => String::new(),
+                    }
+                } else {
+                    String::new()
+                };
                 format!(
-                    "Mailbox: {}, Messages: {}, New: {}{}",
+                    "Mailbox: {}, Messages: {}, New: {}{}{}{}",
                     account[&mailbox_hash].name(),
                     total,
                     unseen,
@@ -2787,7 +3926,12 @@ impl Component for Listing {
                         "(Loading...)"
                     } else {
                         ""
-                    }
+                    },
+                    self.component
+                        .limit()
+                        .map(|l| format!(", Limit: {}", l.term))
+                        .unwrap_or_default(),
+                    search
                 )
             }
             MailboxStatus::Failed(_) | MailboxStatus::None => account[&mailbox_hash].status(),
@@ -2829,35 +3973,24 @@ impl Component for Listing {
 
 impl Listing {
     pub fn new(context: &mut Context) -> Self {
+        let saved_state: Vec<Option<ListingState>> = context
+            .accounts
+            .keys()
+            .map(|h| Self::load_saved_state(*h, context))
+            .collect();
         let account_entries: Vec<AccountMenuEntry> = context
             .accounts
-            .iter()
+            .keys()
             .enumerate()
-            .map(|(i, (h, a))| {
-                let entries: SmallVec<[MailboxMenuEntry; 16]> = a
-                    .list_mailboxes()
-                    .into_iter()
-                    .filter(|mailbox_node| a[&mailbox_node.hash].ref_mailbox.is_subscribed())
-                    .map(|f| MailboxMenuEntry {
-                        depth: f.depth,
-                        indentation: f.indentation,
-                        has_sibling: f.has_sibling,
-                        mailbox_hash: f.hash,
-                        visible: true,
-                        collapsed: a[&f.hash].conf.collapsed,
-                        index_style: a[&f.hash].conf.conf_override().listing.index_style,
-                    })
-                    .collect::<_>();
-
-                AccountMenuEntry {
-                    name: a.name().to_string(),
-                    hash: *h,
-                    index: i,
-                    entries,
-                }
-            })
+            .map(|(i, h)| Self::account_menu_entry(*h, i, saved_state[i].as_ref(), context))
             .collect();
         let first_account_hash = account_entries[0].hash;
+        // The sidebar is hidden in screen reader mode so that the listing can be
+        // read linearly.
+        let layouts = context.settings.listing.layouts.clone();
+        let hide_sidebar = context.screen_reader()
+            || *account_settings!(context[first_account_hash].listing.hide_sidebar_on_launch)
+            || layouts.first().is_some_and(|l| !l.sidebar);
         let mut ret = Self {
             component: Offline(OfflineListing::new((
                 first_account_hash,
@@ -2866,6 +3999,7 @@ impl Listing {
             view: None,
             accounts: account_entries,
             status: None,
+            dashboard: None,
             dirty: true,
             cursor_pos: CursorPos {
                 account: 0,
@@ -2896,19 +4030,22 @@ impl Listing {
                 context[first_account_hash].listing.mail_view_divider
             ),
             mail_view_divider_theme: conf::value(context, "mail.view.divider"),
-            menu_visibility: !*account_settings!(
-                context[first_account_hash].listing.hide_sidebar_on_launch
-            ),
+            menu_visibility: !hide_sidebar,
             ratio: *account_settings!(context[first_account_hash].listing.sidebar_ratio),
             prev_ratio: *account_settings!(context[first_account_hash].listing.sidebar_ratio),
             menu_width: WidgetWidth::Unset,
+            layouts,
+            layout_idx: 0,
             focus: ListingFocus::Mailbox,
+            sidebar_recency: HashMap::default(),
+            saved_state,
+            pending_selection: None,
         };
         ret.component.realize(ret.id().into(), context);
         {
             let _new_val = ret.cursor_pos.account;
-            if let Some(idx) = context.accounts[_new_val]
-                .default_mailbox()
+            if let Some(idx) = ret
+                .initial_mailbox(_new_val, context)
                 .and_then(|h| ret.accounts[_new_val].entry_by_hash(h))
             {
                 ret.cursor_pos.menu = MenuEntryCursor::Mailbox(idx);
@@ -2916,9 +4053,51 @@ impl Listing {
             }
         }
         ret.change_account(context);
+        if context.settings.listing.startup_dashboard {
+            let dashboard = Box::new(Dashboard::new(ret.id(), context));
+            dashboard.realize(ret.id().into(), context);
+            ret.dashboard = Some(dashboard);
+        }
         ret
     }
 
+    fn load_saved_state(account_hash: AccountHash, context: &Context) -> Option<ListingState> {
+        account_settings!(context[account_hash].listing.restore_state)
+            .then(|| ListingState::load(context.accounts[&account_hash].name()))
+    }
+
+    fn account_menu_entry(
+        account_hash: AccountHash,
+        index: usize,
+        saved_state: Option<&ListingState>,
+        context: &Context,
+    ) -> AccountMenuEntry {
+        let a = &context.accounts[&account_hash];
+        let entries: SmallVec<[MailboxMenuEntry; 16]> = a
+            .list_mailboxes()
+            .into_iter()
+            .filter(|mailbox_node| a[&mailbox_node.hash].ref_mailbox.is_subscribed())
+            .map(|f| MailboxMenuEntry {
+                depth: f.depth,
+                indentation: f.indentation,
+                has_sibling: f.has_sibling,
+                mailbox_hash: f.hash,
+                visible: true,
+                collapsed: saved_state
+                    .and_then(|s| s.is_collapsed(&a[&f.hash].path))
+                    .unwrap_or(a[&f.hash].conf.collapsed),
+                index_style: a[&f.hash].conf.conf_override().listing.index_style,
+            })
+            .collect::<_>();
+
+        AccountMenuEntry {
+            name: a.name().to_string(),
+            hash: account_hash,
+            index,
+            entries,
+        }
+    }
+
     fn draw_menu(&mut self, grid: &mut CellBuffer, area: Area, context: &mut Context) {
         grid.clear_area(area, self.theme_default);
         let total_height: usize = 3 * (self.accounts.len())
@@ -3102,6 +4281,12 @@ impl Listing {
                 .listing
                 .relative_menu_indices
         );
+        let show_recency = *account_settings!(
+            context[self.accounts[aidx].hash]
+                .listing
+                .sidebar_show_recency
+        );
+        let now = datetime::now();
         for (
             i,
             &MailboxMenuEntry {
@@ -3329,13 +4514,22 @@ impl Listing {
                 .0
                 + x;
             area = self.menu.area().skip_rows(account_y);
+            // Tag mailboxes are drawn with their tag's color, if it has one.
+            let name_fg = context.accounts[self.accounts[aidx].index]
+                .tag_mailbox_tag(l.mailbox_idx)
+                .and_then(|tag| {
+                    account_settings!(context[self.accounts[aidx].hash].tags.colors)
+                        .get(&tag)
+                        .cloned()
+                })
+                .unwrap_or(att.fg);
             let x = self
                 .menu
                 .grid_mut()
                 .write_string(
                     context.accounts[self.accounts[aidx].index].mailbox_entries[&l.mailbox_idx]
                         .name(),
-                    att.fg,
+                    name_fg,
                     att.bg,
                     att.attrs,
                     area.nth_row(y + 1).skip_cols(x),
@@ -3360,8 +4554,22 @@ impl Listing {
                 (None, Some(coll)) => format!("({coll}) v").into(),
             };
 
+            // Age of the newest message
+            let recency_string: String = if show_recency {
+                let collection = &context.accounts[self.accounts[aidx].index].collection;
+                self.sidebar_recency
+                    .entry((self.accounts[aidx].hash, l.mailbox_idx))
+                    .or_insert_with(|| collection.newest_date(l.mailbox_idx))
+                    .map(|date| format!("{} ", format_age(now, date)))
+                    .unwrap_or_default()
+            } else {
+                String::new()
+            };
+
             let skip_cols = {
-                let val = area.width().saturating_sub(count_string.len());
+                let val = area
+                    .width()
+                    .saturating_sub(recency_string.len() + count_string.len());
                 let skip_cols = x.min(val);
                 if skip_cols == val && matches!(self.show_menu_scrollbar, ShowMenuScrollbar::True) {
                     skip_cols.saturating_sub(1)
@@ -3369,6 +4577,16 @@ impl Listing {
                     skip_cols
                 }
             };
+            let (recency_x, _) = self.menu.grid_mut().write_string(
+                &recency_string,
+                index_att.fg,
+                index_att.bg,
+                index_att.attrs,
+                area.nth_row(y + 1).skip_cols(skip_cols),
+                None,
+                None,
+            );
+            let skip_cols = skip_cols + recency_x;
             let (x, _) = self.menu.grid_mut().write_string(
                 count_string.as_ref(),
                 unread_count_att.fg,
@@ -3404,6 +4622,7 @@ impl Listing {
     }
 
     fn change_account(&mut self, context: &mut Context) {
+        self.close_dashboard(context);
         let account_hash = context.accounts[self.cursor_pos.account].hash();
         let previous_collapsed_mailboxes: BTreeSet<MailboxHash> = self.accounts
             [self.cursor_pos.account]
@@ -3438,9 +4657,11 @@ impl Listing {
                 mailbox_hash: f.hash,
                 visible: true,
                 collapsed: if previous_collapsed_mailboxes.is_empty() {
-                    context.accounts[self.cursor_pos.account][&f.hash]
-                        .conf
-                        .collapsed
+                    let entry = &context.accounts[self.cursor_pos.account][&f.hash];
+                    self.saved_state[self.cursor_pos.account]
+                        .as_ref()
+                        .and_then(|s| s.is_collapsed(&entry.path))
+                        .unwrap_or(entry.conf.collapsed)
                 } else {
                     previous_collapsed_mailboxes.contains(&f.hash)
                 },
@@ -3454,8 +4675,7 @@ impl Listing {
         ) = (
             &self.component,
             &mut self.cursor_pos.menu,
-            context.accounts[self.cursor_pos.account]
-                .default_mailbox()
+            self.initial_mailbox(self.cursor_pos.account, context)
                 .and_then(|h| self.accounts[self.cursor_pos.account].entry_by_hash(h)),
         ) {
             *idx = default;
@@ -3463,6 +4683,7 @@ impl Listing {
         }
         match self.cursor_pos.menu {
             MenuEntryCursor::Mailbox(idx) => {
+                let mut announce = false;
                 // Account might have no mailboxes yet if it's offline
                 if let Some(MailboxMenuEntry {
                     mailbox_hash,
@@ -3470,16 +4691,25 @@ impl Listing {
                     ..
                 }) = self.accounts[self.cursor_pos.account].entries.get(idx)
                 {
+                    let (mailbox_hash, index_style) = (*mailbox_hash, *index_style);
+                    let reopened = self.component.coordinates() == (account_hash, mailbox_hash);
+                    if !reopened {
+                        self.save_selection(context);
+                    }
                     self.component
                         .process_event(&mut UIEvent::VisibilityChange(false), context);
-                    self.component
-                        .set_coordinates((account_hash, *mailbox_hash));
+                    self.component.set_coordinates((account_hash, mailbox_hash));
                     self.component.refresh_mailbox(context, true);
 
                     // Check if per-mailbox configuration overrides general configuration
-                    let index_style_override =
-                        *mailbox_settings!(context[account_hash][mailbox_hash].listing.index_style);
+                    let index_style_override = *mailbox_settings!(
+                        context[account_hash][&mailbox_hash].listing.index_style
+                    );
                     self.set_index_style(index_style.unwrap_or(index_style_override), context);
+                    if !reopened {
+                        self.restore_selection(context);
+                        announce = true;
+                    }
                 } else if !matches!(self.component, ListingComponent::Offline(_)) {
                     self.component.unrealize(context);
                     self.component =
@@ -3494,6 +4724,9 @@ impl Listing {
                     .push_back(UIEvent::StatusEvent(StatusEvent::UpdateStatus(
                         self.status(context),
                     )));
+                if announce {
+                    self.announce_mailbox(context);
+                }
             }
             MenuEntryCursor::Status if context.is_online(account_hash).is_ok() => {
                 self.open_status(self.cursor_pos.account, context);
@@ -3527,6 +4760,52 @@ impl Listing {
         }
     }
 
+    /// Make a listing that shows `mailbox_hash` of `account_hash`, to be
+    /// opened in a new tab.
+    ///
+    /// The listing does not save its state on exit, so that it doesn't
+    /// overwrite the state of the main listing.
+    pub fn new_tab(
+        context: &mut Context,
+        account_hash: AccountHash,
+        mailbox_hash: MailboxHash,
+    ) -> Self {
+        let mut ret = Self::new(context);
+        ret.saved_state.iter_mut().for_each(|s| *s = None);
+        ret.close_dashboard(context);
+        ret.open_mailbox(account_hash, mailbox_hash, context);
+        ret
+    }
+
+    /// Open `mailbox_hash` of `account_hash`, e.g. when it is selected in the
+    /// dashboard.
+    fn open_mailbox(
+        &mut self,
+        account_hash: AccountHash,
+        mailbox_hash: MailboxHash,
+        context: &mut Context,
+    ) {
+        let Some(account_idx) = context.accounts.get_index_of(&account_hash) else {
+            return;
+        };
+        let Some(idx) = self.accounts[account_idx].entry_by_hash(mailbox_hash) else {
+            return;
+        };
+        self.cursor_pos = CursorPos {
+            account: account_idx,
+            menu: MenuEntryCursor::Mailbox(idx),
+        };
+        self.change_account(context);
+        self.focus = ListingFocus::Mailbox;
+    }
+
+    fn close_dashboard(&mut self, context: &mut Context) {
+        if let Some(d) = self.dashboard.take() {
+            d.unrealize(context);
+            self.set_dirty(true);
+        }
+    }
+
     fn open_status(&mut self, account_idx: usize, context: &mut Context) {
         self.status = Some(AccountStatus::new(account_idx, self.theme_default));
         self.menu.grid_mut().empty();
@@ -3537,10 +4816,307 @@ impl Listing {
             )));
     }
 
+    /// The mailbox to open when account `account_idx` is first shown: the one
+    /// that was open last, if `listing.restore_state` is enabled, or the
+    /// account's default mailbox.
+    fn initial_mailbox(&self, account_idx: usize, context: &Context) -> Option<MailboxHash> {
+        self.saved_state[account_idx]
+            .as_ref()
+            .filter(|s| !s.restored)
+            .and_then(|s| s.mailbox.as_ref())
+            .and_then(|path| context.accounts[account_idx].mailbox_by_path(path).ok())
+            .or_else(|| context.accounts[account_idx].default_mailbox())
+    }
+
+    /// Remember the e-mail under the cursor of the open mailbox.
+    fn save_selection(&mut self, context: &Context) {
+        if matches!(self.component, Offline(_)) || self.pending_selection.is_some() {
+            return;
+        }
+        let (account_hash, mailbox_hash) = self.component.coordinates();
+        let Some(account_idx) = context.accounts.get_index_of(&account_hash) else {
+            return;
+        };
+        let Some(state) = self.saved_state[account_idx].as_mut() else {
+            return;
+        };
+        let account = &context.accounts[account_idx];
+        let (Some(entry), Some(env_hash)) = (
+            account.mailbox_entries.get(&mailbox_hash),
+            self.component.cursor_envelope(),
+        ) else {
+            return;
+        };
+        if account.contains_key(env_hash) {
+            let message_id = account
+                .collection
+                .get_env(env_hash)
+                .message_id()
+                .to_string();
+            state.selected.insert(entry.path.clone(), message_id);
+        }
+    }
+
+    /// Select the saved e-mail of the mailbox that was just opened, once it
+    /// is listed.
+    fn restore_selection(&mut self, context: &Context) {
+        self.pending_selection = None;
+        let (account_hash, mailbox_hash) = self.component.coordinates();
+        let Some(account_idx) = context.accounts.get_index_of(&account_hash) else {
+            return;
+        };
+        let Some(entry) = context.accounts[account_idx]
+            .mailbox_entries
+            .get(&mailbox_hash)
+        else {
+            return;
+        };
+        let Some(state) = self.saved_state[account_idx].as_mut() else {
+            return;
+        };
+        state.restored = true;
+        state.mailbox = Some(entry.path.clone());
+        self.pending_selection = state
+            .selected
+            .get(&entry.path)
+            .map(|message_id| (account_hash, mailbox_hash, message_id.clone()));
+        self.select_pending(context);
+    }
+
+    /// Move the cursor to the pending saved selection, if it is listed.
+    ///
+    /// The selection is dropped if another mailbox was opened meanwhile, or
+    /// if the mailbox has loaded and the e-mail is not in it anymore.
+    fn select_pending(&mut self, context: &Context) {
+        let Some((account_hash, mailbox_hash, ref message_id)) = self.pending_selection else {
+            return;
+        };
+        if self.component.coordinates() != (account_hash, mailbox_hash) {
+            self.pending_selection = None;
+            return;
+        }
+        let account = &context.accounts[&account_hash];
+        let env_hash = {
+            let envelopes = account.collection.envelopes.read().unwrap();
+            account
+                .collection
+                .get_mailbox(mailbox_hash)
+                .iter()
+                .copied()
+                .find(|h| {
+                    envelopes
+                        .get(h)
+                        .is_some_and(|e| e.message_id() == message_id.as_str())
+                })
+        };
+        let Some(env_hash) = env_hash else {
+            if account
+                .mailbox_entries
+                .get(&mailbox_hash)
+                .is_none_or(|e| e.status.is_available())
+            {
+                self.pending_selection = None;
+            }
+            return;
+        };
+        let Some(target) = self.component.envelope_row(env_hash) else {
+            return;
+        };
+        self.pending_selection = None;
+        let cursor = self.component.cursor_row();
+        if target != cursor {
+            self.component.set_movement(if target > cursor {
+                PageMovement::Down(target - cursor)
+            } else {
+                PageMovement::Up(cursor - target)
+            });
+        }
+    }
+
+    /// Remember which threads are collapsed in the open thread view.
+    fn save_collapsed_threads(&mut self, context: &Context) {
+        let Some(view) = self.view.as_ref() else {
+            return;
+        };
+        let Some(state) = context
+            .accounts
+            .get_index_of(&view.account_hash())
+            .and_then(|idx| self.saved_state.get_mut(idx))
+            .and_then(Option::as_mut)
+        else {
+            return;
+        };
+        for (message_id, collapsed) in view.collapsed_entries(context) {
+            if collapsed {
+                state.collapsed_threads.insert(message_id);
+            } else {
+                state.collapsed_threads.remove(&message_id);
+            }
+        }
+    }
+
+    /// Announce the mailbox that was just opened, in screen reader mode.
+    fn announce_mailbox(&self, context: &mut Context) {
+        if !context.screen_reader() {
+            return;
+        }
+        let (account_hash, mailbox_hash) = self.component.coordinates();
+        let Some(account) = context.accounts.get(&account_hash) else {
+            return;
+        };
+        let Some(entry) = account.mailbox_entries.get(&mailbox_hash) else {
+            return;
+        };
+        let description = match entry.ref_mailbox.count() {
+            Ok((unseen, total)) => format!(
+                "{} in {}, {} messages, {} unread",
+                entry.path,
+                account.name(),
+                total,
+                unseen
+            ),
+            Err(_) => format!("{} in {}", entry.path, account.name()),
+        };
+        context.announce("Mailbox", description);
+    }
+
+    /// Remember which mailboxes of account `account_idx` are collapsed in the
+    /// sidebar.
+    fn save_collapsed(&mut self, account_idx: usize, context: &Context) {
+        let Some(state) = self.saved_state[account_idx].as_mut() else {
+            return;
+        };
+        state.collapsed_mailboxes = Some(
+            self.accounts[account_idx]
+                .entries
+                .iter()
+                .filter(|e| e.collapsed)
+                .filter_map(|e| {
+                    Some(
+                        context.accounts[account_idx]
+                            .mailbox_entries
+                            .get(&e.mailbox_hash)?
+                            .path
+                            .clone(),
+                    )
+                })
+                .collect(),
+        );
+    }
+
     fn is_menu_visible(&self) -> bool {
         !matches!(self.component.focus(), Focus::EntryFullscreen) && self.menu_visibility
     }
 
+    /// The active pane layout.
+    fn layout(&self) -> PaneLayout {
+        self.layouts
+            .get(self.layout_idx)
+            .copied()
+            .unwrap_or_else(PaneLayout::single)
+    }
+
+    /// Draw the listing and, if an entry is open, its view in `area`. If the
+    /// active layout has a pager pane the listing stays visible next to the
+    /// view, otherwise the view replaces it.
+    fn draw_component(&mut self, grid: &mut CellBuffer, area: Area, context: &mut Context) {
+        let layout = self.layout();
+        let split = layout
+            .split
+            .filter(|_| {
+                !context.zoomed
+                    && self.view.is_some()
+                    && matches!(self.component.focus(), Focus::Entry)
+            })
+            .and_then(|split| {
+                let total = match split {
+                    PaneSplit::Vertical => area.width(),
+                    PaneSplit::Horizontal => area.height(),
+                };
+                let list_len = total * layout.ratio / 100;
+                // Both panes need at least one column or row.
+                (list_len > 0 && list_len + 1 < total).then_some((split, list_len))
+            });
+        if let Some((split, list_len)) = split {
+            let (list_area, divider_area, view_area) = match split {
+                PaneSplit::Vertical => (
+                    area.take_cols(list_len),
+                    area.nth_col(list_len),
+                    area.skip_cols(list_len + 1),
+                ),
+                PaneSplit::Horizontal => (
+                    area.take_rows(list_len),
+                    area.nth_row(list_len),
+                    area.skip_rows(list_len + 1),
+                ),
+            };
+            self.component.draw_list(grid, list_area, context);
+            context.dirty_areas.push_back(list_area);
+            for row in grid.bounds_iter(divider_area) {
+                for c in row {
+                    grid[c]
+                        .set_ch(self.mail_view_divider)
+                        .set_fg(self.mail_view_divider_theme.fg)
+                        .set_bg(self.mail_view_divider_theme.bg)
+                        .set_attrs(self.mail_view_divider_theme.attrs);
+                }
+            }
+            context.dirty_areas.push_back(divider_area);
+            if let Some(ref mut view) = self.view {
+                view.draw(grid, view_area, context);
+            }
+            return;
+        }
+        self.component.draw(grid, area, context);
+        if self.component.unfocused() {
+            if let Some(ref mut view) = self.view {
+                view.draw(grid, self.component.view_area().unwrap_or(area), context);
+                if let Some(view_area) = self.component.view_area() {
+                    if view_area != area {
+                        let divider_area = area.nth_col(area.width() - view_area.width() - 1);
+                        for row in grid.bounds_iter(divider_area) {
+                            for c in row {
+                                grid[c]
+                                    .set_ch(self.mail_view_divider)
+                                    .set_fg(self.mail_view_divider_theme.fg)
+                                    .set_bg(self.mail_view_divider_theme.bg)
+                                    .set_attrs(self.mail_view_divider_theme.attrs);
+                            }
+                        }
+                        context.dirty_areas.push_back(divider_area);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replace the listing of the current mailbox with a new one, so that it
+    /// uses the current values of the mailbox's settings.
+    fn reload_component(&mut self, context: &mut Context) {
+        let (account_hash, mailbox_hash) = self.component.coordinates();
+        let limit = self.component.limit().cloned();
+        let old = std::mem::replace(
+            &mut self.component,
+            Offline(OfflineListing::new((account_hash, mailbox_hash))),
+        );
+        old.unrealize(context);
+        let menu_entry = match self.cursor_pos.menu {
+            MenuEntryCursor::Mailbox(idx) => {
+                self.accounts[self.cursor_pos.account].entries.get(idx)
+            }
+            _ => None,
+        };
+        let index_style = menu_entry
+            .and_then(|e| e.index_style)
+            .unwrap_or(*mailbox_settings!(
+                context[account_hash][&mailbox_hash].listing.index_style
+            ));
+        self.set_index_style(index_style, context);
+        self.component.set_limit(limit);
+        self.component.refresh_mailbox(context, true);
+        self.set_dirty(true);
+    }
+
     fn set_index_style(&mut self, new_style: IndexStyle, context: &mut Context) {
         let old = match new_style {
             IndexStyle::Plain => {
@@ -3589,6 +5165,7 @@ impl Listing {
                 mbox_entry.index_style = Some(new_style);
             }
         }
+        self.component.set_limit(old.limit().cloned());
         self.component
             .process_event(&mut UIEvent::VisibilityChange(true), context);
         old.unrealize(context);
@@ -3608,4 +5185,127 @@ pub enum ListingMessage {
         go_to_first_unread: bool,
     },
     UpdateView,
+    /// Open a mailbox, sent by the dashboard.
+    OpenMailbox {
+        account_hash: AccountHash,
+        mailbox_hash: MailboxHash,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listing_sender_initials() {
+        let initials = |display_name: Option<&str>, address: &str| {
+            sender_initials(&Address::new(
+                display_name.map(str::to_string),
+                address.to_string(),
+            ))
+        };
+        assert_eq!(initials(Some("John Doe"), "jdoe@example.com").0, "JD");
+        assert_eq!(initials(Some("mary ann smith"), "mas@example.com").0, "MS");
+        assert_eq!(initials(Some("GitHub"), "noreply@github.com").0, "G");
+        assert_eq!(initials(None, "first.last@example.com").0, "FL");
+        assert_eq!(initials(Some("\"\""), "alice@example.com").0, "A");
+        // Colors only depend on the address spec.
+        assert_eq!(
+            initials(Some("John Doe"), "jdoe@example.com").1,
+            initials(None, "JDoe@Example.com").1
+        );
+    }
+
+    #[test]
+    fn test_listing_visible_thread_rows() {
+        // A
+        // ├─>B
+        // │ └─>C
+        // └─>D
+        // E
+        // └─>F
+        let rows = vec![
+            (0, 'A', false),
+            (1, 'B', true),
+            (2, 'C', false),
+            (1, 'D', false),
+            (0, 'E', false),
+            (1, 'F', false),
+        ];
+        assert_eq!(visible_thread_rows(rows.clone(), |_| true), rows);
+        assert_eq!(
+            visible_thread_rows(rows.clone(), |c| *c != 'B'),
+            vec![
+                (0, 'A', false),
+                (1, 'C', true),
+                (1, 'D', false),
+                (0, 'E', false),
+                (1, 'F', false),
+            ]
+        );
+        assert_eq!(
+            visible_thread_rows(rows.clone(), |c| *c != 'A'),
+            vec![
+                (0, 'B', false),
+                (1, 'C', false),
+                (0, 'D', false),
+                (0, 'E', false),
+                (1, 'F', false),
+            ]
+        );
+        assert_eq!(
+            visible_thread_rows(rows, |c| *c != 'D' && *c != 'E'),
+            vec![
+                (0, 'A', false),
+                (1, 'B', false),
+                (2, 'C', false),
+                (0, 'F', false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_listing_format_size() {
+        assert_eq!(format_size(0), "0B");
+        assert_eq!(format_size(812), "812B");
+        assert_eq!(format_size(1024), "1.0K");
+        assert_eq!(format_size(4300), "4.2K");
+        assert_eq!(format_size(10_189), "10K");
+        assert_eq!(format_size(1_048_000), "1023K");
+        assert_eq!(format_size(1_048_500), "1.0M");
+        assert_eq!(format_size(18 * 1024 * 1024), "18M");
+        assert_eq!(format_size(3 << 40), "3.0T");
+        for bytes in [1023, 9_999, 1_048_575, 999 << 30, usize::MAX] {
+            assert!(format_size(bytes).len() <= 5, "{}", format_size(bytes));
+        }
+    }
+
+    #[test]
+    fn test_listing_date_group() {
+        const DAY: u64 = 24 * 60 * 60;
+        // 2023-11-14 22:13:20 UTC
+        let now = 1_700_000_000;
+        assert_eq!(date_group(now, now), "Today");
+        assert_eq!(date_group(now, now - 22 * 60 * 60), "Today");
+        assert_eq!(date_group(now, now - 23 * 60 * 60), "Yesterday");
+        assert_eq!(date_group(now, now - DAY), "Yesterday");
+        assert_eq!(date_group(now, now - 3 * DAY), "This week");
+        assert_eq!(date_group(now, now - 10 * DAY), "Last week");
+        assert_eq!(date_group(now, now - 20 * DAY), "This month");
+        assert_eq!(date_group(now, now - 400 * DAY), "Older");
+        assert_eq!(date_group(now, now + DAY), "Today");
+    }
+
+    #[test]
+    fn test_listing_format_age() {
+        let now = 1_700_000_000;
+        assert_eq!(format_age(now, now), "0m");
+        assert_eq!(format_age(now, now - 5 * 60 - 10), "5m");
+        assert_eq!(format_age(now, now - 3 * 60 * 60), "3h");
+        assert_eq!(format_age(now, now - 2 * 24 * 60 * 60), "2d");
+        assert_eq!(format_age(now, now - 15 * 24 * 60 * 60), "2w");
+        assert_eq!(format_age(now, now - 800 * 24 * 60 * 60), "2y");
+        // Dates in the future are shown as new.
+        assert_eq!(format_age(now, now + 100), "0m");
+    }
 }
@@ -22,7 +22,7 @@
 use std::{collections::BTreeMap, convert::TryInto, iter::FromIterator};
 
 use indexmap::IndexSet;
-use melib::{Address, SortField, SortOrder, TagHash, Threads};
+use melib::{Address, SortChain, TagHash, Threads};
 
 use super::*;
 use crate::{components::PageMovement, jobs::JoinHandle, segment_tree::SegmentTree};
@@ -134,12 +134,13 @@ pub struct CompactListing {
     cursor_pos: (AccountHash, MailboxHash, usize),
     new_cursor_pos: (AccountHash, MailboxHash, usize),
     length: usize,
-    sort: (SortField, SortOrder),
-    subsort: (SortField, SortOrder),
+    sort: SortChain,
     /// Cache current view.
     data_columns: DataColumns<5>,
     rows_drawn: SegmentTree,
     rows: RowsState<(ThreadHash, EnvelopeHash)>,
+    /// Active `limit` query.
+    limit: Option<ListingLimit>,
 
     #[allow(clippy::type_complexity)]
     search_job: Option<(String, JoinHandle<Result<Vec<EnvelopeHash>>>)>,
@@ -252,10 +253,8 @@ impl MailListingTrait for CompactListing {
         let threads = context.accounts[&self.cursor_pos.0]
             .collection
             .get_threads(self.cursor_pos.1);
-        let mut roots = threads.roots();
-        threads.group_inner_sort_by(
-            &mut roots,
-            self.sort,
+        let roots = threads.sorted_roots(
+            &self.sort,
             &context.accounts[&self.cursor_pos.0].collection.envelopes,
         );
         drop(threads);
@@ -339,7 +338,31 @@ impl MailListingTrait for CompactListing {
         .map(|s| s.as_str())
         .unwrap_or(super::DEFAULT_HIGHLIGHT_SELF_FLAG)
         .grapheme_width();
+        // Previews are fetched when rows are drawn, so reserve their width in
+        // advance.
+        let preview_colwidth: usize = if *mailbox_settings!(
+            context[self.cursor_pos.0][&self.cursor_pos.1]
+                .listing
+                .show_preview
+        ) {
+            1 + *mailbox_settings!(
+                context[self.cursor_pos.0][&self.cursor_pos.1]
+                    .listing
+                    .preview_length
+            )
+        } else {
+            0
+        };
+        let plugins_colwidth = context.plugins.listing_columns_width();
+        let row_height = (*mailbox_settings!(
+            context[self.cursor_pos.0][&self.cursor_pos.1]
+                .listing
+                .row_height
+        ))
+        .clamp(1, 2);
+        self.data_columns.row_height = row_height;
         let mut itoa_buffer = itoa::Buffer::new();
+        let mut prev_date = None;
         'items_for_loop: for thread in items {
             let thread_node = &threads.thread_nodes()[&threads.thread_ref(thread).root()];
             let root_env_hash = if let Some(h) = thread_node.message().or_else(|| {
@@ -385,6 +408,11 @@ impl MailListingTrait for CompactListing {
                     continue;
                 }
             }
+            if let Some(ref limit) = self.limit {
+                if !limit.thread_matches(account, &threads, thread) {
+                    continue;
+                }
+            }
             other_subjects.clear();
             tags.clear();
             from_address_list.clear();
@@ -434,9 +462,21 @@ impl MailListingTrait for CompactListing {
                 highlighted: false,
                 selected: false
             );
+            let row_attr = self.color_cache.age_attr(
+                row_attr,
+                threads.thread_ref(thread).date(),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            );
+            let row_attr = self.color_cache.score_attr(
+                row_attr,
+                root_envelope.score(),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            );
             self.rows.row_attr_cache.insert(self.length, row_attr);
 
-            let entry_strings = self.make_entry_string(
+            let mut entry_strings = self.make_entry_string(
                 &root_envelope,
                 context,
                 &tags_lck,
@@ -447,6 +487,22 @@ impl MailListingTrait for CompactListing {
                 highlight_self,
                 thread,
             );
+            if let Some(label) = date_separator(
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+                &self.sort,
+                prev_date,
+                threads.thread_ref(thread).date(),
+            ) {
+                entry_strings.date = DateString(label.to_string());
+            }
+            prev_date = Some(threads.thread_ref(thread).date());
+            let forge_colwidth = super::entry_forge_summary(
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+                &root_envelope,
+            )
+            .map_or(0, |s| 1 + s.grapheme_width());
             row_widths.0.push(
                 itoa_buffer
                     .format(self.length)
@@ -463,33 +519,41 @@ impl MailListingTrait for CompactListing {
                     .unwrap_or(255),
             );
             /* from */
-            row_widths.2.push(
-                entry_strings
-                    .from
-                    .grapheme_width()
-                    .try_into()
-                    .unwrap_or(255),
-            );
+            row_widths
+                .2
+                .push(entry_strings.from_width().try_into().unwrap_or(255));
             row_widths.3.push(
                 (entry_strings.flag.grapheme_width()
                     + usize::from(entry_strings.highlight_self) * highlight_self_colwidth)
                     .try_into()
                     .unwrap_or(255),
             );
-            row_widths.4.push(
-                (entry_strings.subject.grapheme_width() + 1 + entry_strings.tags.grapheme_width())
-                    .try_into()
-                    .unwrap_or(255),
-            );
+            /* subject, and in two-line entries tags and preview on the second line */
+            let subject_colwidth = if row_height > 1 {
+                entry_strings.subject.grapheme_width().max(
+                    entry_strings.tags.grapheme_width()
+                        + plugins_colwidth
+                        + forge_colwidth
+                        + preview_colwidth,
+                )
+            } else {
+                entry_strings.subject.grapheme_width()
+                    + 1
+                    + entry_strings.tags.grapheme_width()
+                    + plugins_colwidth
+                    + forge_colwidth
+                    + preview_colwidth
+            };
+            row_widths
+                .4
+                .push(subject_colwidth.try_into().unwrap_or(255));
             min_width.1 = min_width.1.max(entry_strings.date.grapheme_width()); /* date */
-            min_width.2 = min_width.2.max(entry_strings.from.grapheme_width()); /* from */
+            min_width.2 = min_width.2.max(entry_strings.from_width()); /* from */
             min_width.3 = min_width.3.max(
                 entry_strings.flag.grapheme_width()
                     + usize::from(entry_strings.highlight_self) * highlight_self_colwidth,
             );
-            min_width.4 = min_width.4.max(
-                entry_strings.subject.grapheme_width() + 1 + entry_strings.tags.grapheme_width(),
-            ); /* subject */
+            min_width.4 = min_width.4.max(subject_colwidth); /* subject */
             self.rows.insert_thread(
                 thread,
                 (thread, root_env_hash),
@@ -523,15 +587,35 @@ impl MailListingTrait for CompactListing {
             .set_even_odd_theme(self.color_cache.even, self.color_cache.odd);
 
         /* index column */
-        _ = self.data_columns.columns[0].resize_with_context(min_width.0, self.rows.len(), context);
+        _ = self.data_columns.columns[0].resize_with_context(
+            min_width.0,
+            self.rows.len() * row_height,
+            context,
+        );
         /* date column */
-        _ = self.data_columns.columns[1].resize_with_context(min_width.1, self.rows.len(), context);
+        _ = self.data_columns.columns[1].resize_with_context(
+            min_width.1,
+            self.rows.len() * row_height,
+            context,
+        );
         /* from column */
-        _ = self.data_columns.columns[2].resize_with_context(min_width.2, self.rows.len(), context);
+        _ = self.data_columns.columns[2].resize_with_context(
+            min_width.2,
+            self.rows.len() * row_height,
+            context,
+        );
         // flags column
-        _ = self.data_columns.columns[3].resize_with_context(min_width.3, self.rows.len(), context);
+        _ = self.data_columns.columns[3].resize_with_context(
+            min_width.3,
+            self.rows.len() * row_height,
+            context,
+        );
         // subject column
-        _ = self.data_columns.columns[4].resize_with_context(min_width.4, self.rows.len(), context);
+        _ = self.data_columns.columns[4].resize_with_context(
+            min_width.4,
+            self.rows.len() * row_height,
+            context,
+        );
         self.data_columns.segment_tree[0] = row_widths.0.into();
         self.data_columns.segment_tree[1] = row_widths.1.into();
         self.data_columns.segment_tree[2] = row_widths.2.into();
@@ -568,6 +652,7 @@ impl ListingTrait for CompactListing {
     fn set_coordinates(&mut self, coordinates: (AccountHash, MailboxHash)) {
         if self.coordinates() != (coordinates.0, coordinates.1) {
             self.new_cursor_pos = (coordinates.0, coordinates.1, 0);
+            self.limit = None;
         }
         self.focus = Focus::None;
         self.filtered_selection.clear();
@@ -640,7 +725,9 @@ impl ListingTrait for CompactListing {
         grid.copy_area(
             self.data_columns.columns[3].grid(),
             area.skip_cols(x),
-            self.data_columns.columns[3].area().nth_row(idx),
+            self.data_columns.columns[3]
+                .area()
+                .nth_row(idx * self.data_columns.row_height),
         );
         for c in grid.row_iter(area, x..area.width(), 0) {
             grid[c].set_bg(row_attr.bg).set_attrs(row_attr.attrs);
@@ -661,10 +748,20 @@ impl ListingTrait for CompactListing {
                 area,
                 self.data_columns.columns[0].area(),
             );
+            if self.filter_term.is_empty() {
+                draw_skeleton_rows(
+                    grid,
+                    area.skip_rows(1),
+                    pending_envelopes(&context.accounts[&self.cursor_pos.0], self.cursor_pos.1),
+                    1,
+                    &self.color_cache,
+                );
+            }
             context.dirty_areas.push_back(area);
             return;
         }
-        let rows = area.height();
+        let row_height = self.data_columns.row_height;
+        let rows = area.height() / row_height;
         if rows == 0 {
             return;
         }
@@ -674,6 +771,8 @@ impl ListingTrait for CompactListing {
         if self.force_draw {
             grid.clear_area(area, self.color_cache.theme_default);
         }
+        /* Leave any rows below the last whole entry blank. */
+        let area = area.take_rows(rows * row_height);
 
         let prev_page_no = (self.cursor_pos.2).wrapping_div(rows);
         let page_no = (self.new_cursor_pos.2).wrapping_div(rows);
@@ -691,12 +790,13 @@ impl ListingTrait for CompactListing {
                 if idx >= self.length {
                     continue; //bounds check
                 }
-                let new_area = area.nth_row(idx % rows);
+                let new_area = self.entry_area(area, idx % rows);
                 self.data_columns
                     .draw(grid, idx, self.cursor_pos.2, grid.bounds_iter(new_area));
                 if highlight {
                     let row_attr = row_attr!(self.color_cache, even: idx % 2 == 0, unseen: false, highlighted: true, selected: false);
                     grid.change_theme(new_area, row_attr);
+                    context.set_focus(new_area.upper_left());
                 } else if let Some(row_attr) = self.rows.row_attr_cache.get(&idx) {
                     grid.change_theme(new_area, *row_attr);
                 }
@@ -729,9 +829,9 @@ impl ListingTrait for CompactListing {
             self.draw_relative_numbers(grid, area, top_idx, context);
         }
         /* apply each row colors separately */
-        for i in top_idx..(top_idx + area.height()) {
+        for i in top_idx..(top_idx + rows) {
             if let Some(row_attr) = self.rows.row_attr_cache.get(&i) {
-                grid.change_theme(area.nth_row(i % rows), *row_attr);
+                grid.change_theme(self.entry_area(area, i % rows), *row_attr);
             }
         }
 
@@ -743,14 +843,25 @@ impl ListingTrait for CompactListing {
             highlighted: true,
             selected: false
         );
-        grid.change_theme(area.nth_row(self.cursor_pos.2 % rows), row_attr);
+        let cursor_area = self.entry_area(area, self.cursor_pos.2 % rows);
+        grid.change_theme(cursor_area, row_attr);
+        context.set_focus(cursor_area.upper_left());
 
         /* clear gap if available height is more than count of entries */
         if top_idx + rows > self.length {
             grid.change_theme(
-                area.skip_rows(self.length - top_idx),
+                area.skip_rows((self.length - top_idx) * row_height),
                 self.color_cache.theme_default,
             );
+            if self.filter_term.is_empty() {
+                draw_skeleton_rows(
+                    grid,
+                    area.skip_rows((self.length - top_idx) * row_height),
+                    pending_envelopes(&context.accounts[&self.cursor_pos.0], self.cursor_pos.1),
+                    row_height,
+                    &self.color_cache,
+                );
+            }
         }
 
         self.force_draw = false;
@@ -794,7 +905,7 @@ impl ListingTrait for CompactListing {
         if !self.filtered_selection.is_empty() {
             threads.group_inner_sort_by(
                 &mut self.filtered_selection,
-                self.sort,
+                &self.sort,
                 &context.accounts[&self.cursor_pos.0].collection.envelopes,
             );
             self.new_cursor_pos.2 = self.cursor_pos.2.min(self.filtered_selection.len() - 1);
@@ -814,6 +925,14 @@ impl ListingTrait for CompactListing {
         self.view_area
     }
 
+    fn limit(&self) -> Option<&ListingLimit> {
+        self.limit.as_ref()
+    }
+
+    fn set_limit(&mut self, limit: Option<ListingLimit>) {
+        self.limit = limit;
+    }
+
     fn unfocused(&self) -> bool {
         !matches!(self.focus, Focus::None)
     }
@@ -839,6 +958,42 @@ impl ListingTrait for CompactListing {
         self.set_dirty(true);
     }
 
+    fn cursor_row(&self) -> usize {
+        self.new_cursor_pos.2
+    }
+
+    fn cursor_envelope(&self) -> Option<EnvelopeHash> {
+        let thread_hash = self.get_thread_under_cursor(self.new_cursor_pos.2)?;
+        self.rows.thread_to_env.get(&thread_hash)?.first().copied()
+    }
+
+    fn envelope_row(&self, env_hash: EnvelopeHash) -> Option<usize> {
+        if !self.filter_term.is_empty() {
+            return None;
+        }
+        self.rows.env_order.get(&env_hash).copied()
+    }
+
+    fn entry_dates(&self, context: &Context) -> Vec<UnixTimestamp> {
+        let threads = context.accounts[&self.cursor_pos.0]
+            .collection
+            .get_threads(self.cursor_pos.1);
+        if !self.filter_term.is_empty() {
+            return self
+                .filtered_selection
+                .iter()
+                .map(|h| threads.thread_ref(*h).date())
+                .collect();
+        }
+        let mut ret = vec![0; self.length];
+        for (h, row) in self.rows.thread_order.iter() {
+            if let Some(date) = ret.get_mut(*row) {
+                *date = threads.thread_ref(*h).date();
+            }
+        }
+        ret
+    }
+
     fn set_focus(&mut self, new_value: Focus, context: &mut Context) {
         match new_value {
             Focus::None => {
@@ -901,13 +1056,12 @@ impl CompactListing {
         context: &Context,
     ) -> Box<Self> {
         let color_cache = ColorCache::new(context, IndexStyle::Compact);
-        let sort = *mailbox_settings!(context[coordinates.0][&coordinates.1].listing.sort);
+        let sort = mailbox_settings!(context[coordinates.0][&coordinates.1].listing.sort).clone();
         Box::new(Self {
             cursor_pos: (AccountHash::default(), MailboxHash::default(), 0),
             new_cursor_pos: (coordinates.0, coordinates.1, 0),
             length: 0,
             sort,
-            subsort: (SortField::Date, SortOrder::Desc),
             search_job: None,
             select_job: None,
             filter_term: String::new(),
@@ -917,6 +1071,7 @@ impl CompactListing {
             data_columns: DataColumns::new(color_cache.theme_default),
             rows_drawn: SegmentTree::default(),
             rows: RowsState::default(),
+            limit: None,
             dirty: true,
             force_draw: true,
             color_cache,
@@ -1000,8 +1155,21 @@ impl CompactListing {
         } else {
             root_envelope.subject().trim().to_string()
         };
+        let subject = match entry_mailbox(
+            context,
+            (self.cursor_pos.0, self.cursor_pos.1),
+            root_envelope.hash(),
+        ) {
+            Some(mailbox) => format!("[{mailbox}] {subject}"),
+            None => subject,
+        };
         EntryStrings {
-            date: DateString(self.format_date(context, thread.date())),
+            date: DateString::new(
+                self.format_date(context, thread.date()),
+                || thread_size(account, threads, hash),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            ),
             subject: if thread.len() > 1 {
                 SubjectString(format!("{} ({})", subject, thread.len()))
             } else {
@@ -1016,16 +1184,24 @@ impl CompactListing {
                 thread.snoozed(),
                 thread.unseen() > 0,
                 thread.has_attachments(),
+                root_envelope.score(),
                 context,
                 (self.cursor_pos.0, self.cursor_pos.1),
             ),
             from: FromString(Address::display_name_slice(from, None)),
+            initials: InitialsString::new(from, context, (self.cursor_pos.0, self.cursor_pos.1)),
             tags: TagString(tags_string, colors),
             unseen: thread.unseen() > 0,
             highlight_self,
         }
     }
 
+    /// Area of the entry `offset` entries below the top of `area`.
+    fn entry_area(&self, area: Area, offset: usize) -> Area {
+        let row_height = self.data_columns.row_height;
+        area.skip_rows(offset * row_height).take_rows(row_height)
+    }
+
     fn get_thread_under_cursor(&self, cursor: usize) -> Option<ThreadHash> {
         if self.filter_term.is_empty() {
             self.rows
@@ -1039,6 +1215,52 @@ impl CompactListing {
         }
     }
 
+    /// Repaint only the rows in `updated` that are visible in `area`, after
+    /// they have been re-rendered by [`CompactListing::update_line`].
+    ///
+    /// Returns `false` without drawing anything if the column widths of the
+    /// current page changed, in which case the entire page must be redrawn.
+    fn draw_row_updates(
+        &mut self,
+        grid: &mut CellBuffer,
+        area: Area,
+        updated: &[usize],
+        context: &mut Context,
+    ) -> bool {
+        let rows = area.height() / self.data_columns.row_height;
+        if rows == 0 || self.length == 0 {
+            return true;
+        }
+        let top_idx = (self.cursor_pos.2).wrapping_div(rows) * rows;
+        let widths = self.data_columns.widths;
+        _ = self.data_columns.recalc_widths(area.size(), top_idx);
+        if self.data_columns.widths != widths {
+            return false;
+        }
+        for &idx in updated {
+            if idx < top_idx || idx >= top_idx + rows || idx >= self.length {
+                continue;
+            }
+            let row_area = self.entry_area(area, idx % rows);
+            self.data_columns
+                .draw(grid, idx, self.cursor_pos.2, grid.bounds_iter(row_area));
+            if idx == self.cursor_pos.2 {
+                let row_attr = row_attr!(self.color_cache, even: idx % 2 == 0, unseen: false, highlighted: true, selected: false);
+                grid.change_theme(row_area, row_attr);
+            } else if let Some(row_attr) = self.rows.row_attr_cache.get(&idx) {
+                grid.change_theme(row_area, *row_attr);
+            }
+            context.dirty_areas.push_back(row_area);
+        }
+        if *account_settings!(context[self.cursor_pos.0].listing.relative_list_indices) {
+            self.draw_relative_numbers(grid, area, top_idx, context);
+            context
+                .dirty_areas
+                .push_back(area.take_cols(self.data_columns.widths[0]));
+        }
+        true
+    }
+
     fn update_line(&mut self, context: &Context, env_hash: EnvelopeHash) {
         let account = &context.accounts[&self.cursor_pos.0];
 
@@ -1060,6 +1282,21 @@ impl CompactListing {
             highlighted: false,
             selected: self.rows.is_thread_selected(thread_hash)
         );
+        let row_attr = if self.rows.is_thread_selected(thread_hash) {
+            row_attr
+        } else {
+            self.color_cache.score_attr(
+                self.color_cache.age_attr(
+                    row_attr,
+                    thread.date(),
+                    context,
+                    (self.cursor_pos.0, self.cursor_pos.1),
+                ),
+                envelope.score(),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            )
+        };
         self.rows.row_attr_cache.insert(idx, row_attr);
 
         let mut other_subjects = IndexSet::new();
@@ -1132,9 +1369,13 @@ impl CompactListing {
             envelope.recipient_any(&my_address) || envelope.sender_any(&my_address)
         };
         drop(envelope);
+        let row_height = self.data_columns.row_height;
         let columns = &mut self.data_columns.columns;
         for n in 0..=4 {
-            let area = columns[n].area().nth_row(idx);
+            let area = columns[n]
+                .area()
+                .skip_rows(idx * row_height)
+                .take_rows(row_height);
             columns[n].grid_mut().clear_area(area, row_attr);
         }
         self.rows_drawn.update(idx, 1);
@@ -1161,6 +1402,7 @@ impl CompactListing {
             self.data_columns.columns[4].area().width(),
         );
 
+        let row_height = self.data_columns.row_height;
         let columns = &mut self.data_columns.columns;
         let mut itoa_buffer = itoa::Buffer::new();
         for (idx, ((_thread_hash, root_env_hash), strings)) in self
@@ -1183,8 +1425,9 @@ impl CompactListing {
                 continue;
             }
             let row_attr = self.rows.row_attr_cache[&idx];
+            let row = idx * row_height;
             let (x, _) = {
-                let area = columns[0].area().nth_row(idx);
+                let area = columns[0].area().nth_row(row);
                 columns[0].grid_mut().write_string(
                     itoa_buffer.format(idx),
                     row_attr.fg,
@@ -1197,14 +1440,14 @@ impl CompactListing {
             };
             for c in {
                 let area = columns[0].area();
-                columns[0].grid_mut().row_iter(area, x..min_width.0, idx)
+                columns[0].grid_mut().row_iter(area, x..min_width.0, row)
             } {
                 columns[0].grid_mut()[c]
                     .set_bg(row_attr.bg)
                     .set_attrs(row_attr.attrs);
             }
             let (x, _) = {
-                let area = columns[1].area().nth_row(idx);
+                let area = columns[1].area().nth_row(row);
                 columns[1].grid_mut().write_string(
                     &strings.date,
                     row_attr.fg,
@@ -1217,15 +1460,24 @@ impl CompactListing {
             };
             for c in {
                 let area = columns[1].area();
-                columns[1].grid_mut().row_iter(area, x..min_width.1, idx)
+                columns[1].grid_mut().row_iter(area, x..min_width.1, row)
             } {
                 columns[1].grid_mut()[c]
                     .set_bg(row_attr.bg)
                     .set_attrs(row_attr.attrs);
             }
+            let initials_width = {
+                let area = columns[2].area().nth_row(row);
+                super::draw_initials(
+                    columns[2].grid_mut(),
+                    area,
+                    &strings.initials,
+                    &self.color_cache,
+                )
+            };
             let (x, _) = {
-                let area = columns[2].area().nth_row(idx);
-                columns[2].grid_mut().write_string(
+                let area = columns[2].area().nth_row(row).skip_cols(initials_width);
+                let (x, y) = columns[2].grid_mut().write_string(
                     &strings.from,
                     row_attr.fg,
                     row_attr.bg,
@@ -1233,11 +1485,12 @@ impl CompactListing {
                     area,
                     None,
                     None,
-                )
+                );
+                (initials_width + x, y)
             };
             for c in {
                 let area = columns[2].area();
-                columns[2].grid_mut().row_iter(area, x..min_width.2, idx)
+                columns[2].grid_mut().row_iter(area, x..min_width.2, row)
             } {
                 columns[2].grid_mut()[c]
                     .set_bg(row_attr.bg)
@@ -1248,12 +1501,16 @@ impl CompactListing {
                 for text_formatter in crate::conf::text_format_regexps(context, "listing.from") {
                     let t = columns[2].grid_mut().insert_tag(text_formatter.tag);
                     for (start, end) in text_formatter.regexp.find_iter(strings.from.as_str()) {
-                        columns[2].grid_mut().set_tag(t, (start, idx), (end, idx));
+                        columns[2].grid_mut().set_tag(
+                            t,
+                            (start + initials_width, row),
+                            (end + initials_width, row),
+                        );
                     }
                 }
             }
             {
-                let mut area_col_3 = columns[3].area().nth_row(idx);
+                let mut area_col_3 = columns[3].area().nth_row(row);
                 area_col_3 = area_col_3.skip_cols(columns[3].grid_mut().write_string(
                     &strings.flag,
                     row_attr.fg,
@@ -1292,7 +1549,7 @@ impl CompactListing {
                 }
             }
             {
-                let mut area_col_4 = columns[4].area().nth_row(idx);
+                let mut area_col_4 = columns[4].area().nth_row(row);
                 area_col_4 = area_col_4.skip_cols(columns[4].grid_mut().write_string(
                     &strings.subject,
                     row_attr.fg,
@@ -1302,6 +1559,11 @@ impl CompactListing {
                     None,
                     None,
                 ));
+                if context.draw_hyperlinks() {
+                    columns[4]
+                        .grid_mut()
+                        .set_uris_from_text(&strings.subject, columns[4].area().nth_row(row));
+                }
                 {
                     for text_formatter in
                         crate::conf::text_format_regexps(context, "listing.subject")
@@ -1310,11 +1572,22 @@ impl CompactListing {
                         for (start, end) in
                             text_formatter.regexp.find_iter(strings.subject.as_str())
                         {
-                            columns[4].grid_mut().set_tag(t, (start, idx), (end, idx));
+                            columns[4].grid_mut().set_tag(t, (start, row), (end, row));
                         }
                     }
                 }
-                area_col_4 = area_col_4.skip_cols(1);
+                if row_height > 1 {
+                    /* Tags and preview go on the entry's second line. */
+                    for c in columns[4].grid().row_iter(area_col_4, 0..min_width.4, 0) {
+                        columns[4].grid_mut()[c]
+                            .set_ch(' ')
+                            .set_bg(row_attr.bg)
+                            .set_attrs(row_attr.attrs);
+                    }
+                    area_col_4 = columns[4].area().nth_row(row + 1);
+                } else {
+                    area_col_4 = area_col_4.skip_cols(1);
+                }
                 for (t, &color) in strings.tags.split_whitespace().zip(strings.tags.1.iter()) {
                     let color = color.unwrap_or(self.color_cache.tag_default.bg);
                     let (x, _) = columns[4].grid_mut().write_string(
@@ -1335,6 +1608,56 @@ impl CompactListing {
                     }
                     area_col_4 = area_col_4.skip_cols(x + 1);
                 }
+                if let Some(value) = context.plugins.listing_columns(
+                    &context.accounts[&self.cursor_pos.0],
+                    self.cursor_pos.1,
+                    *root_env_hash,
+                ) {
+                    let (x, _) = columns[4].grid_mut().write_string(
+                        &value,
+                        row_attr.fg,
+                        row_attr.bg,
+                        row_attr.attrs,
+                        area_col_4.skip_cols(1),
+                        None,
+                        None,
+                    );
+                    area_col_4 = area_col_4.skip_cols(x + 1);
+                }
+                if let Some(summary) = super::entry_forge_summary(
+                    context,
+                    (self.cursor_pos.0, self.cursor_pos.1),
+                    &context.accounts[&self.cursor_pos.0]
+                        .collection
+                        .get_env(*root_env_hash),
+                ) {
+                    let (x, _) = columns[4].grid_mut().write_string(
+                        &summary,
+                        row_attr.fg,
+                        row_attr.bg,
+                        row_attr.attrs,
+                        area_col_4.skip_cols(1),
+                        None,
+                        None,
+                    );
+                    area_col_4 = area_col_4.skip_cols(x + 1);
+                }
+                if let Some(preview) = super::entry_preview(
+                    context,
+                    (self.cursor_pos.0, self.cursor_pos.1),
+                    *root_env_hash,
+                ) {
+                    let (x, _) = columns[4].grid_mut().write_string(
+                        &preview,
+                        self.color_cache.preview.fg,
+                        row_attr.bg,
+                        self.color_cache.preview.attrs,
+                        area_col_4.skip_cols(1),
+                        None,
+                        None,
+                    );
+                    area_col_4 = area_col_4.skip_cols(x + 1);
+                }
                 for c in columns[4].grid().row_iter(area_col_4, 0..min_width.4, 0) {
                     columns[4].grid_mut()[c]
                         .set_ch(' ')
@@ -1415,7 +1738,7 @@ impl CompactListing {
         let area = area.take_cols(width);
         let account = &context.accounts[&self.cursor_pos.0];
         let threads = account.collection.get_threads(self.cursor_pos.1);
-        for i in 0..area.height() {
+        for i in 0..area.height() / self.data_columns.row_height {
             let idx = top_idx + i;
             if idx >= self.length {
                 break;
@@ -1433,7 +1756,8 @@ impl CompactListing {
                 row_attr!(self.color_cache, even: (top_idx + i) % 2 == 0, unseen: false, highlighted: true, selected: false)
             };
 
-            grid.clear_area(area.nth_row(i), row_attr);
+            let entry_area = self.entry_area(area, i);
+            grid.clear_area(entry_area, row_attr);
             grid.write_string(
                 &if self.new_cursor_pos.2.saturating_sub(top_idx) == i {
                     self.new_cursor_pos.2.to_string()
@@ -1445,7 +1769,7 @@ impl CompactListing {
                 row_attr.fg,
                 row_attr.bg,
                 row_attr.attrs,
-                area.nth_row(i),
+                entry_area.nth_row(0),
                 None,
                 None,
             );
@@ -1537,7 +1861,7 @@ impl Component for CompactListing {
                 area = area.skip_rows(y + 1);
             }
 
-            let rows = area.height();
+            let rows = area.height() / self.data_columns.row_height;
 
             if let Some(modifier) = self.modifier_command.take() {
                 if let Some(mvm) = self.movement.as_ref() {
@@ -1709,24 +2033,28 @@ impl Component for CompactListing {
             }
 
             if !self.rows.row_updates.is_empty() {
+                let mut updated: SmallVec<[usize; 8]> = SmallVec::new();
                 while let Some(env_hash) = self.rows.row_updates.pop() {
                     if !self.rows.env_to_thread.contains_key(&env_hash) {
                         self.refresh_mailbox(context, true);
                         self.set_dirty(true);
+                        self.force_draw = true;
                         break;
                     }
                     self.update_line(context, env_hash);
-                    let row: usize = self.rows.env_order[&env_hash];
-                    let page_no = (self.new_cursor_pos.2).wrapping_div(rows);
-
-                    let top_idx = page_no * rows;
-                    self.force_draw |= row >= top_idx && row < top_idx + rows;
+                    updated.push(self.rows.env_order[&env_hash]);
                 }
-                if self.force_draw {
+                /* Only repaint the updated rows, unless the cursor moved or
+                 * something else requires the entire page to be redrawn. */
+                if self.force_draw
+                    || self.movement.is_some()
+                    || self.cursor_pos != self.new_cursor_pos
+                    || !self.draw_row_updates(grid, area, &updated, context)
+                {
                     /* Draw the entire list */
                     self.draw_list(grid, area, context);
-                    self.force_draw = false;
                 }
+                self.force_draw = false;
             } else {
                 /* Draw the entire list */
                 self.draw_list(grid, area, context);
@@ -1835,30 +2163,18 @@ impl Component for CompactListing {
                 }
                 UIEvent::Action(ref action) => {
                     match action {
-                        Action::Sort(field, order) if !self.unfocused() => {
-                            let new_order = (*field, *order);
-                            if new_order != self.sort {
+                        Action::Sort(_) | Action::ReverseSort | Action::SubSort(_, _)
+                            if !self.unfocused() =>
+                        {
+                            if let Some(new_sort) = apply_sort_action(&self.sort, action) {
                                 // "Keep it coming"
-                                self.sort = (*field, *order);
+                                self.sort = new_sort;
                                 self.refresh_mailbox(context, true);
                                 self.set_dirty(true);
                                 self.force_draw = true;
                             }
                             return true;
                         }
-                        Action::Sort(field, order) if !self.unfocused() => {
-                            self.sort = (*field, *order);
-                            if !self.filtered_selection.is_empty() {
-                                self.set_dirty(true);
-                            }
-                            self.refresh_mailbox(context, false);
-                            return true;
-                        }
-                        Action::SubSort(field, order) if !self.unfocused() => {
-                            self.subsort = (*field, *order);
-                            // [ref:FIXME]: perform subsort.
-                            return true;
-                        }
                         Action::Listing(ToggleThreadSnooze) if !self.unfocused() => {
                             // [ref:FIXME]: Re-implement toggle thread snooze
                             /*
@@ -1961,7 +2277,7 @@ impl Component for CompactListing {
             UIEvent::Action(Action::Listing(Search(ref filter_term))) if !self.unfocused() => {
                 match context.accounts[&self.cursor_pos.0].search(
                     filter_term,
-                    self.sort,
+                    &self.sort,
                     self.cursor_pos.1,
                 ) {
                     Ok(job) => {
@@ -1990,7 +2306,7 @@ impl Component for CompactListing {
             UIEvent::Action(Action::Listing(Select(ref search_term))) if !self.unfocused() => {
                 match context.accounts[&self.cursor_pos.0].search(
                     search_term,
-                    self.sort,
+                    &self.sort,
                     self.cursor_pos.1,
                 ) {
                     Ok(job) => {
@@ -22,7 +22,7 @@
 use std::{collections::BTreeMap, iter::FromIterator};
 
 use indexmap::IndexSet;
-use melib::{Address, SortField, SortOrder, TagHash, Threads};
+use melib::{Address, SortChain, TagHash, Threads};
 
 use super::*;
 use crate::{components::PageMovement, jobs::JoinHandle};
@@ -112,9 +112,10 @@ pub struct ConversationsListing {
     cursor_pos: (AccountHash, MailboxHash, usize),
     new_cursor_pos: (AccountHash, MailboxHash, usize),
     length: usize,
-    sort: (SortField, SortOrder),
-    subsort: (SortField, SortOrder),
+    sort: SortChain,
     rows: RowsState<(ThreadHash, EnvelopeHash)>,
+    /// Active `limit` query.
+    limit: Option<ListingLimit>,
     error: std::result::Result<(), String>,
 
     #[allow(clippy::type_complexity)]
@@ -215,10 +216,8 @@ impl MailListingTrait for ConversationsListing {
         let threads = context.accounts[&self.cursor_pos.0]
             .collection
             .get_threads(self.cursor_pos.1);
-        let mut roots = threads.roots();
-        threads.group_inner_sort_by(
-            &mut roots,
-            self.sort,
+        let roots = threads.sorted_roots(
+            &self.sort,
             &context.accounts[&self.cursor_pos.0].collection.envelopes,
         );
         drop(threads);
@@ -271,6 +270,7 @@ impl MailListingTrait for ConversationsListing {
         let mut from_address_list = Vec::new();
         let mut from_address_set: std::collections::HashSet<Vec<u8>> =
             std::collections::HashSet::new();
+        let mut prev_date = None;
         'items_for_loop: for thread in items {
             let thread_node = &threads.thread_nodes()[&threads.thread_ref(thread).root()];
             let root_env_hash = if let Some(h) = thread_node.message().or_else(|| {
@@ -316,6 +316,11 @@ impl MailListingTrait for ConversationsListing {
                     continue;
                 }
             }
+            if let Some(ref limit) = self.limit {
+                if !limit.thread_matches(account, &threads, thread) {
+                    continue;
+                }
+            }
             other_subjects.clear();
             tags.clear();
             from_address_list.clear();
@@ -355,7 +360,7 @@ impl MailListingTrait for ConversationsListing {
                 }
             }
 
-            let strings = self.make_entry_string(
+            let mut strings = self.make_entry_string(
                 root_envelope,
                 context,
                 &tags_lck,
@@ -365,6 +370,16 @@ impl MailListingTrait for ConversationsListing {
                 &tags,
                 thread,
             );
+            if let Some(label) = date_separator(
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+                &self.sort,
+                prev_date,
+                threads.thread_ref(thread).date(),
+            ) {
+                strings.date = DateString(label.to_string());
+            }
+            prev_date = Some(threads.thread_ref(thread).date());
             max_entry_columns = std::cmp::max(
                 max_entry_columns,
                 strings.flag.len()
@@ -375,7 +390,7 @@ impl MailListingTrait for ConversationsListing {
             );
             max_entry_columns = std::cmp::max(
                 max_entry_columns,
-                strings.date.len() + 1 + strings.from.grapheme_width(),
+                strings.date.len() + 1 + strings.from_width(),
             );
             self.rows.insert_thread(
                 thread,
@@ -406,6 +421,7 @@ impl ListingTrait for ConversationsListing {
     fn set_coordinates(&mut self, coordinates: (AccountHash, MailboxHash)) {
         if self.coordinates() != (coordinates.0, coordinates.1) {
             self.new_cursor_pos = (coordinates.0, coordinates.1, 0);
+            self.limit = None;
         }
         self.focus = Focus::None;
         self.filtered_selection.clear();
@@ -469,6 +485,15 @@ impl ListingTrait for ConversationsListing {
                 None,
                 None,
             );
+            if self.filter_term.is_empty() {
+                draw_skeleton_rows(
+                    grid,
+                    area.skip_rows(1),
+                    pending_envelopes(&context.accounts[&self.cursor_pos.0], self.cursor_pos.1),
+                    3,
+                    &self.color_cache,
+                );
+            }
             context.dirty_areas.push_back(area);
             return;
         }
@@ -498,6 +523,7 @@ impl ListingTrait for ConversationsListing {
                 self.highlight_line(grid, new_area, *idx, context);
                 context.dirty_areas.push_back(new_area);
             }
+            context.set_focus(area.skip_rows(3 * (self.cursor_pos.2 % rows)).upper_left());
             if !self.force_draw {
                 return;
             }
@@ -512,6 +538,15 @@ impl ListingTrait for ConversationsListing {
         grid.clear_area(area, self.color_cache.theme_default);
         // Page_no has changed, so draw new page
         self.draw_rows(grid, area, context, top_idx);
+        if top_idx + rows > self.length && self.filter_term.is_empty() {
+            draw_skeleton_rows(
+                grid,
+                area.skip_rows(3 * (self.length - top_idx)),
+                pending_envelopes(&context.accounts[&self.cursor_pos.0], self.cursor_pos.1),
+                3,
+                &self.color_cache,
+            );
+        }
 
         self.highlight_line(
             grid,
@@ -519,6 +554,7 @@ impl ListingTrait for ConversationsListing {
             self.cursor_pos.2,
             context,
         );
+        context.set_focus(area.skip_rows(3 * (self.cursor_pos.2 % rows)).upper_left());
 
         self.force_draw = false;
         context.dirty_areas.push_back(area);
@@ -557,7 +593,7 @@ impl ListingTrait for ConversationsListing {
         if !self.filtered_selection.is_empty() {
             threads.group_inner_sort_by(
                 &mut self.filtered_selection,
-                self.sort,
+                &self.sort,
                 &context.accounts[&self.cursor_pos.0].collection.envelopes,
             );
             self.new_cursor_pos.2 = std::cmp::min(
@@ -578,6 +614,14 @@ impl ListingTrait for ConversationsListing {
         self.view_area
     }
 
+    fn limit(&self) -> Option<&ListingLimit> {
+        self.limit.as_ref()
+    }
+
+    fn set_limit(&mut self, limit: Option<ListingLimit>) {
+        self.limit = limit;
+    }
+
     fn unfocused(&self) -> bool {
         !matches!(self.focus, Focus::None)
     }
@@ -603,6 +647,42 @@ impl ListingTrait for ConversationsListing {
         self.set_dirty(true);
     }
 
+    fn cursor_row(&self) -> usize {
+        self.new_cursor_pos.2
+    }
+
+    fn cursor_envelope(&self) -> Option<EnvelopeHash> {
+        let thread_hash = self.get_thread_under_cursor(self.new_cursor_pos.2)?;
+        self.rows.thread_to_env.get(&thread_hash)?.first().copied()
+    }
+
+    fn envelope_row(&self, env_hash: EnvelopeHash) -> Option<usize> {
+        if !self.filter_term.is_empty() {
+            return None;
+        }
+        self.rows.env_order.get(&env_hash).copied()
+    }
+
+    fn entry_dates(&self, context: &Context) -> Vec<UnixTimestamp> {
+        let threads = context.accounts[&self.cursor_pos.0]
+            .collection
+            .get_threads(self.cursor_pos.1);
+        if !self.filter_term.is_empty() {
+            return self
+                .filtered_selection
+                .iter()
+                .map(|h| threads.thread_ref(*h).date())
+                .collect();
+        }
+        let mut ret = vec![0; self.length];
+        for (h, row) in self.rows.thread_order.iter() {
+            if let Some(date) = ret.get_mut(*row) {
+                *date = threads.thread_ref(*h).date();
+            }
+        }
+        ret
+    }
+
     fn set_focus(&mut self, new_value: Focus, context: &mut Context) {
         match new_value {
             Focus::None => {
@@ -666,14 +746,14 @@ impl ConversationsListing {
         coordinates: (AccountHash, MailboxHash),
         context: &Context,
     ) -> Box<Self> {
-        let sort = *mailbox_settings!(context[coordinates.0][&coordinates.1].listing.sort);
+        let sort = mailbox_settings!(context[coordinates.0][&coordinates.1].listing.sort).clone();
         Box::new(Self {
             cursor_pos: (coordinates.0, MailboxHash::default(), 0),
             new_cursor_pos: (coordinates.0, coordinates.1, 0),
             length: 0,
             sort,
-            subsort: (SortField::Date, SortOrder::Desc),
             rows: RowsState::default(),
+            limit: None,
             error: Ok(()),
             search_job: None,
             filter_term: String::new(),
@@ -761,8 +841,21 @@ impl ConversationsListing {
         } else {
             root_envelope.subject().trim().to_string()
         };
+        let subject = match entry_mailbox(
+            context,
+            (self.cursor_pos.0, self.cursor_pos.1),
+            root_envelope.hash(),
+        ) {
+            Some(mailbox) => format!("[{mailbox}] {subject}"),
+            None => subject,
+        };
         EntryStrings {
-            date: DateString(self.format_date(context, thread.date())),
+            date: DateString::new(
+                self.format_date(context, thread.date()),
+                || thread_size(account, threads, hash),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            ),
             subject: SubjectString(if thread.len() > 1 {
                 format!("{} ({})", subject, thread.len())
             } else {
@@ -778,10 +871,12 @@ impl ConversationsListing {
                 thread.snoozed(),
                 thread.unseen() > 0,
                 thread.has_attachments(),
+                root_envelope.score(),
                 context,
                 (self.cursor_pos.0, self.cursor_pos.1),
             ),
             from: FromString(Address::display_name_slice(from, None)),
+            initials: InitialsString::new(from, context, (self.cursor_pos.0, self.cursor_pos.1)),
             tags: TagString(tags_string, colors),
             unseen: thread.unseen() > 0,
             highlight_self: false,
@@ -903,6 +998,22 @@ impl ConversationsListing {
                 highlighted: self.cursor_pos.2 == idx,
                 selected: self.rows.is_thread_selected(*thread_hash)
             );
+            let subject_attr =
+                if self.cursor_pos.2 == idx || self.rows.is_thread_selected(*thread_hash) {
+                    subject_attr
+                } else {
+                    self.color_cache.score_attr(
+                        self.color_cache.age_attr(
+                            subject_attr,
+                            thread.date(),
+                            context,
+                            (self.cursor_pos.0, self.cursor_pos.1),
+                        ),
+                        account.collection.get_env(*root_env_hash).score(),
+                        context,
+                        (self.cursor_pos.0, self.cursor_pos.1),
+                    )
+                };
             // draw subject
             let (x_, subject_overflowed) = grid.write_string(
                 &strings.subject,
@@ -913,6 +1024,9 @@ impl ConversationsListing {
                 None,
                 None,
             );
+            if context.draw_hyperlinks() {
+                grid.set_uris_from_text(&strings.subject, area.nth_row(0).skip_cols(x));
+            }
             x += x_;
             let mut subject_overflowed = subject_overflowed > 0;
             for (t, &color) in strings.tags.split_whitespace().zip(strings.tags.1.iter()) {
@@ -979,6 +1093,7 @@ impl ConversationsListing {
                 highlighted: self.cursor_pos.2 == idx,
                 selected: self.rows.is_thread_selected(*thread_hash)
             );
+            x += super::draw_initials(grid, area.skip(x, 1), &strings.initials, &self.color_cache);
             // draw from
             x += grid
                 .write_string(
@@ -991,6 +1106,30 @@ impl ConversationsListing {
                     None,
                 )
                 .0;
+            // draw preview, only for entries that are visible
+            if area.height() > 1 {
+                if let Some(preview) = super::entry_preview(
+                    context,
+                    (self.cursor_pos.0, self.cursor_pos.1),
+                    *root_env_hash,
+                ) {
+                    for c in grid.row_iter(area, x..(x + 4), 1) {
+                        grid[c].set_ch('▁').set_fg(row_attr.fg).set_bg(row_attr.bg);
+                    }
+                    x += 4;
+                    x += grid
+                        .write_string(
+                            &preview,
+                            self.color_cache.preview.fg,
+                            row_attr.bg,
+                            self.color_cache.preview.attrs,
+                            area.skip(x, 1),
+                            None,
+                            None,
+                        )
+                        .0;
+                }
+            }
 
             for c in grid.row_iter(area, x..area.width(), 1) {
                 grid[c].set_ch('▁').set_fg(row_attr.fg).set_bg(row_attr.bg);
@@ -1438,20 +1577,17 @@ impl Component for ConversationsListing {
                     self.set_dirty(true);
                 }
                 UIEvent::Action(ref action) => match action {
-                    Action::Sort(field, order) if !self.unfocused() => {
-                        let new_order = (*field, *order);
-                        if new_order != self.sort {
+                    Action::Sort(_) | Action::ReverseSort | Action::SubSort(_, _)
+                        if !self.unfocused() =>
+                    {
+                        if let Some(new_sort) = apply_sort_action(&self.sort, action) {
                             // "Keep it coming"
-                            self.sort = (*field, *order);
+                            self.sort = new_sort;
                             self.refresh_mailbox(context, false);
                             self.set_dirty(true);
                         }
                         return true;
                     }
-                    Action::SubSort(field, order) if !self.unfocused() => {
-                        self.subsort = (*field, *order);
-                        return true;
-                    }
                     Action::Listing(ToggleThreadSnooze) if !self.unfocused() => {
                         //if let Some(thread) = self.get_thread_under_cursor(self.cursor_pos.2) {
                         //    let account = &mut context.accounts[&self.cursor_pos.0];
@@ -1501,7 +1637,7 @@ impl Component for ConversationsListing {
                 Action::Listing(Search(ref filter_term)) if !self.unfocused() => {
                     match context.accounts[&self.cursor_pos.0].search(
                         filter_term,
-                        self.sort,
+                        &self.sort,
                         self.cursor_pos.1,
                     ) {
                         Ok(job) => {
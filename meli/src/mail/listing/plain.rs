@@ -21,7 +21,7 @@
 
 use std::iter::FromIterator;
 
-use melib::{Address, SortField, SortOrder, ThreadNode};
+use melib::{Address, SortChain, SortField, ThreadNode};
 
 use super::{EntryStrings, *};
 use crate::{components::PageMovement, jobs::JoinHandle};
@@ -133,9 +133,10 @@ pub struct PlainListing {
     cursor_pos: (AccountHash, MailboxHash, usize),
     new_cursor_pos: (AccountHash, MailboxHash, usize),
     length: usize,
-    sort: (SortField, SortOrder),
-    subsort: (SortField, SortOrder),
+    sort: SortChain,
     rows: RowsState<(ThreadHash, EnvelopeHash)>,
+    /// Active `limit` query.
+    limit: Option<ListingLimit>,
     /// Cache current view.
     data_columns: DataColumns<5>,
 
@@ -247,29 +248,25 @@ impl MailListingTrait for PlainListing {
             .envelopes
             .read()
             .unwrap();
-        let sort = self.sort;
-        self.local_collection.sort_by(|a, b| match sort {
-            (SortField::Date, SortOrder::Desc) => {
-                let ma = &env_lck[a];
-                let mb = &env_lck[b];
-                mb.date().cmp(&ma.date())
-            }
-            (SortField::Date, SortOrder::Asc) => {
-                let ma = &env_lck[a];
-                let mb = &env_lck[b];
-                ma.date().cmp(&mb.date())
-            }
-            (SortField::Subject, SortOrder::Desc) => {
-                let ma = &env_lck[a];
-                let mb = &env_lck[b];
-                ma.subject().cmp(&mb.subject())
-            }
-            (SortField::Subject, SortOrder::Asc) => {
-                let ma = &env_lck[a];
-                let mb = &env_lck[b];
-                mb.subject().cmp(&ma.subject())
-            }
+        let threads = context.accounts[&self.cursor_pos.0]
+            .collection
+            .get_threads(self.cursor_pos.1);
+        let thread_len = |h: &EnvelopeHash| {
+            threads
+                .envelope_to_thread
+                .get(h)
+                .map(|t| threads.thread_ref(*t).len())
+                .unwrap_or(1)
+        };
+        let sort = &self.sort;
+        self.local_collection.sort_by(|a, b| {
+            sort.compare(|field| match field {
+                SortField::ThreadLength => thread_len(a).cmp(&thread_len(b)),
+                _ => env_lck[a].cmp_by_field(&env_lck[b], field),
+            })
+            .then_with(|| a.cmp(b))
         });
+        drop(threads);
         let items = Box::new(self.local_collection.clone().into_iter())
             as Box<dyn Iterator<Item = EnvelopeHash>>;
 
@@ -319,6 +316,7 @@ impl ListingTrait for PlainListing {
     fn set_coordinates(&mut self, coordinates: (AccountHash, MailboxHash)) {
         if self.coordinates() != (coordinates.0, coordinates.1) {
             self.new_cursor_pos = (coordinates.0, coordinates.1, 0);
+            self.limit = None;
         }
         self.focus = Focus::None;
         self.filtered_selection.clear();
@@ -412,6 +410,15 @@ impl ListingTrait for PlainListing {
                 area,
                 self.data_columns.columns[0].area(),
             );
+            if self.filter_term.is_empty() {
+                draw_skeleton_rows(
+                    grid,
+                    area.skip_rows(1),
+                    pending_envelopes(&context.accounts[&self.cursor_pos.0], self.cursor_pos.1),
+                    1,
+                    &self.color_cache,
+                );
+            }
             context.dirty_areas.push_back(area);
             return;
         }
@@ -446,6 +453,7 @@ impl ListingTrait for PlainListing {
                 if highlight {
                     let row_attr = row_attr!(self.color_cache, even: idx % 2 == 0, unseen: false, highlighted: true, selected: false);
                     grid.change_theme(new_area, row_attr);
+                    context.set_focus(new_area.upper_left());
                 } else if let Some(row_attr) = self.rows.row_attr_cache.get(&idx) {
                     grid.change_theme(new_area, *row_attr);
                 }
@@ -493,6 +501,7 @@ impl ListingTrait for PlainListing {
             selected: false
         );
         grid.change_theme(area.nth_row(self.cursor_pos.2 % rows), row_attr);
+        context.set_focus(area.nth_row(self.cursor_pos.2 % rows).upper_left());
 
         /* clear gap if available height is more than count of entries */
         if top_idx + rows > self.length {
@@ -500,6 +509,15 @@ impl ListingTrait for PlainListing {
                 area.skip_rows(self.length - top_idx),
                 self.color_cache.theme_default,
             );
+            if self.filter_term.is_empty() {
+                draw_skeleton_rows(
+                    grid,
+                    area.skip_rows(self.length - top_idx),
+                    pending_envelopes(&context.accounts[&self.cursor_pos.0], self.cursor_pos.1),
+                    1,
+                    &self.color_cache,
+                );
+            }
         }
 
         self.force_draw = false;
@@ -552,6 +570,14 @@ impl ListingTrait for PlainListing {
         self.view_area
     }
 
+    fn limit(&self) -> Option<&ListingLimit> {
+        self.limit.as_ref()
+    }
+
+    fn set_limit(&mut self, limit: Option<ListingLimit>) {
+        self.limit = limit;
+    }
+
     fn unfocused(&self) -> bool {
         !matches!(self.focus, Focus::None)
     }
@@ -577,6 +603,29 @@ impl ListingTrait for PlainListing {
         self.set_dirty(true);
     }
 
+    fn cursor_row(&self) -> usize {
+        self.new_cursor_pos.2
+    }
+
+    fn cursor_envelope(&self) -> Option<EnvelopeHash> {
+        self.get_env_under_cursor(self.new_cursor_pos.2)
+    }
+
+    fn envelope_row(&self, env_hash: EnvelopeHash) -> Option<usize> {
+        if !self.filter_term.is_empty() {
+            return None;
+        }
+        self.rows.env_order.get(&env_hash).copied()
+    }
+
+    fn entry_dates(&self, context: &Context) -> Vec<UnixTimestamp> {
+        let account = &context.accounts[&self.cursor_pos.0];
+        (0..self.length)
+            .filter_map(|i| self.get_env_under_cursor(i))
+            .map(|env_hash| account.collection.get_env(env_hash).date())
+            .collect()
+    }
+
     fn set_focus(&mut self, new_value: Focus, context: &mut Context) {
         match new_value {
             Focus::None => {
@@ -638,14 +687,14 @@ impl PlainListing {
         context: &Context,
     ) -> Box<Self> {
         let color_cache = ColorCache::new(context, IndexStyle::Plain);
-        let sort = *mailbox_settings!(context[coordinates.0][&coordinates.1].listing.sort);
+        let sort = mailbox_settings!(context[coordinates.0][&coordinates.1].listing.sort).clone();
         Box::new(Self {
             cursor_pos: (AccountHash::default(), MailboxHash::default(), 0),
             new_cursor_pos: (coordinates.0, coordinates.1, 0),
             length: 0,
             sort,
-            subsort: (SortField::Date, SortOrder::Desc),
             rows: RowsState::default(),
+            limit: None,
             local_collection: Vec::new(),
             filter_term: String::new(),
             search_job: None,
@@ -704,9 +753,19 @@ impl PlainListing {
                 tags.pop();
             }
         }
-        let subject = e.subject().trim().to_string();
+        let mut subject = e.subject().trim().to_string();
+        if let Some(mailbox) =
+            entry_mailbox(context, (self.cursor_pos.0, self.cursor_pos.1), e.hash())
+        {
+            subject = format!("[{mailbox}] {subject}");
+        }
         EntryStrings {
-            date: DateString(self.format_date(context, e.date())),
+            date: DateString::new(
+                self.format_date(context, e.date()),
+                || e.size(),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            ),
             subject: SubjectString(subject),
             flag: FlagString::new(
                 e.flags(),
@@ -714,10 +773,16 @@ impl PlainListing {
                 /* snoozed */ false,
                 !e.is_seen(),
                 e.has_attachments(),
+                e.score(),
                 context,
                 (self.cursor_pos.0, self.cursor_pos.1),
             ),
             from: FromString(Address::display_name_slice(e.from(), None)),
+            initials: InitialsString::new(
+                e.from(),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            ),
             tags: TagString(tags, colors),
             unseen: !e.is_seen(),
             highlight_self: false,
@@ -764,6 +829,7 @@ impl PlainListing {
         .unwrap_or(super::DEFAULT_HIGHLIGHT_SELF_FLAG)
         .grapheme_width();
         let mut itoa_buffer = itoa::Buffer::new();
+        let mut prev_date = None;
         for i in iter {
             if !context.accounts[&self.cursor_pos.0].contains_key(i)
                 || !threads.envelope_to_thread.contains_key(&i)
@@ -792,6 +858,11 @@ impl PlainListing {
                     continue;
                 }
             }
+            if let Some(ref limit) = self.limit {
+                if !limit.is_match(&envelope) {
+                    continue;
+                }
+            }
             let row_attr = row_attr!(
                 self.color_cache,
                 even: self.length % 2 == 0,
@@ -799,9 +870,31 @@ impl PlainListing {
                 highlighted: false,
                 selected: false
             );
+            let row_attr = self.color_cache.age_attr(
+                row_attr,
+                envelope.date(),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            );
+            let row_attr = self.color_cache.score_attr(
+                row_attr,
+                envelope.score(),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            );
             self.rows.row_attr_cache.insert(self.length, row_attr);
 
             let mut entry_strings = self.make_entry_string(&envelope, context);
+            if let Some(label) = date_separator(
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+                &self.sort,
+                prev_date,
+                envelope.date(),
+            ) {
+                entry_strings.date = DateString(label.to_string());
+            }
+            prev_date = Some(envelope.date());
             entry_strings.highlight_self = should_highlight_self
                 && (envelope.recipient_any(&my_address) || envelope.sender_any(&my_address));
             row_widths.0.push(
@@ -818,13 +911,9 @@ impl PlainListing {
                     .try_into()
                     .unwrap_or(255),
             );
-            row_widths.2.push(
-                entry_strings
-                    .from
-                    .grapheme_width()
-                    .try_into()
-                    .unwrap_or(255),
-            );
+            row_widths
+                .2
+                .push(entry_strings.from_width().try_into().unwrap_or(255));
             row_widths.3.push(
                 (entry_strings.flag.grapheme_width()
                     + usize::from(entry_strings.highlight_self) * highlight_self_colwidth)
@@ -837,7 +926,7 @@ impl PlainListing {
                     .unwrap_or(255),
             );
             min_width.1 = min_width.1.max(entry_strings.date.grapheme_width()); /* date */
-            min_width.2 = min_width.2.max(entry_strings.from.grapheme_width()); /* from */
+            min_width.2 = min_width.2.max(entry_strings.from_width()); /* from */
             min_width.3 = min_width.3.max(
                 entry_strings.flag.grapheme_width()
                     + usize::from(entry_strings.highlight_self) * highlight_self_colwidth,
@@ -953,15 +1042,22 @@ impl PlainListing {
             }
             {
                 let area_col_2 = columns[2].area().nth_row(idx);
+                let initials_width = super::draw_initials(
+                    columns[2].grid_mut(),
+                    area_col_2,
+                    &strings.initials,
+                    &self.color_cache,
+                );
                 let (skip_cols, _) = columns[2].grid_mut().write_string(
                     &strings.from,
                     row_attr.fg,
                     row_attr.bg,
                     row_attr.attrs,
-                    area_col_2,
+                    area_col_2.skip_cols(initials_width),
                     None,
                     None,
                 );
+                let skip_cols = initials_width + skip_cols;
                 {
                     for text_formatter in crate::conf::text_format_regexps(context, "listing.from")
                     {
@@ -969,8 +1065,8 @@ impl PlainListing {
                         for (start, end) in text_formatter.regexp.find_iter(strings.from.as_str()) {
                             columns[2].grid_mut().set_tag(
                                 t,
-                                (start + skip_cols, idx),
-                                (end + skip_cols, idx),
+                                (start + initials_width, idx),
+                                (end + initials_width, idx),
                             );
                         }
                     }
@@ -1034,6 +1130,11 @@ impl PlainListing {
                     None,
                     None,
                 ));
+                if context.draw_hyperlinks() {
+                    columns[4]
+                        .grid_mut()
+                        .set_uris_from_text(&strings.subject, columns[4].area().nth_row(idx));
+                }
                 {
                     for text_formatter in
                         crate::conf::text_format_regexps(context, "listing.subject")
@@ -1100,6 +1201,52 @@ impl PlainListing {
         }
     }
 
+    /// Repaint only the rows in `updated` that are visible in `area`, after
+    /// they have been re-rendered by [`PlainListing::update_line`].
+    ///
+    /// Returns `false` without drawing anything if the column widths of the
+    /// current page changed, in which case the entire page must be redrawn.
+    fn draw_row_updates(
+        &mut self,
+        grid: &mut CellBuffer,
+        area: Area,
+        updated: &[usize],
+        context: &mut Context,
+    ) -> bool {
+        let rows = area.height();
+        if rows == 0 || self.length == 0 {
+            return true;
+        }
+        let top_idx = (self.cursor_pos.2).wrapping_div(rows) * rows;
+        let widths = self.data_columns.widths;
+        _ = self.data_columns.recalc_widths(area.size(), top_idx);
+        if self.data_columns.widths != widths {
+            return false;
+        }
+        for &idx in updated {
+            if idx < top_idx || idx >= top_idx + rows || idx >= self.length {
+                continue;
+            }
+            let row_area = area.nth_row(idx % rows);
+            self.data_columns
+                .draw(grid, idx, self.cursor_pos.2, grid.bounds_iter(row_area));
+            if idx == self.cursor_pos.2 {
+                let row_attr = row_attr!(self.color_cache, even: idx % 2 == 0, unseen: false, highlighted: true, selected: false);
+                grid.change_theme(row_area, row_attr);
+            } else if let Some(row_attr) = self.rows.row_attr_cache.get(&idx) {
+                grid.change_theme(row_area, *row_attr);
+            }
+            context.dirty_areas.push_back(row_area);
+        }
+        if *account_settings!(context[self.cursor_pos.0].listing.relative_list_indices) {
+            self.draw_relative_numbers(grid, area, top_idx);
+            context
+                .dirty_areas
+                .push_back(area.take_cols(self.data_columns.widths[0]));
+        }
+        true
+    }
+
     fn update_line(&mut self, context: &Context, env_hash: EnvelopeHash) {
         let account = &context.accounts[&self.cursor_pos.0];
 
@@ -1118,6 +1265,21 @@ impl PlainListing {
             highlighted: false,
             selected: self.rows.selection.get(&env_hash).copied().unwrap_or(false)
         );
+        let row_attr = if self.rows.selection.get(&env_hash).copied().unwrap_or(false) {
+            row_attr
+        } else {
+            self.color_cache.score_attr(
+                self.color_cache.age_attr(
+                    row_attr,
+                    envelope.date(),
+                    context,
+                    (self.cursor_pos.0, self.cursor_pos.1),
+                ),
+                envelope.score(),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            )
+        };
         self.rows.row_attr_cache.insert(idx, row_attr);
 
         let strings = self.make_entry_string(&envelope, context);
@@ -1171,15 +1333,22 @@ impl PlainListing {
         }
         let (x, _) = {
             let area = columns[2].area().nth_row(idx);
-            columns[2].grid_mut().write_string(
+            let initials_width = super::draw_initials(
+                columns[2].grid_mut(),
+                area,
+                &strings.initials,
+                &self.color_cache,
+            );
+            let (x, y) = columns[2].grid_mut().write_string(
                 &strings.from,
                 row_attr.fg,
                 row_attr.bg,
                 row_attr.attrs,
-                area,
+                area.skip_cols(initials_width),
                 None,
                 None,
-            )
+            );
+            (initials_width + x, y)
         };
         for c in {
             let area = columns[2].area().nth_row(idx);
@@ -1242,6 +1411,11 @@ impl PlainListing {
                 None,
                 None,
             ));
+            if context.draw_hyperlinks() {
+                columns[4]
+                    .grid_mut()
+                    .set_uris_from_text(&strings.subject, columns[4].area().nth_row(idx));
+            }
             {
                 for text_formatter in crate::conf::text_format_regexps(context, "listing.subject") {
                     let t = columns[4].grid_mut().insert_tag(text_formatter.tag);
@@ -1627,35 +1801,28 @@ impl Component for PlainListing {
             }
 
             if !self.rows.row_updates.is_empty() {
+                let mut updated: SmallVec<[usize; 8]> = SmallVec::new();
                 while let Some(env_hash) = self.rows.row_updates.pop() {
                     if !self.rows.env_to_thread.contains_key(&env_hash) {
                         self.refresh_mailbox(context, true);
                         self.set_dirty(true);
+                        self.force_draw = true;
                         break;
                     }
                     self.update_line(context, env_hash);
-                    let row: usize = self.rows.env_order[&env_hash];
-                    let envelope: EnvelopeRef = context.accounts[&self.cursor_pos.0]
-                        .collection
-                        .get_env(env_hash);
-                    let row_attr = row_attr!(
-                        self.color_cache,
-                        even: row % 2 == 0,
-                        unseen: !envelope.is_seen(),
-                        highlighted: false,
-                        selected: self.rows.selection.get(&env_hash).copied().unwrap_or(false)
-                    );
-                    self.rows.row_attr_cache.insert(row, row_attr);
-                    let page_no = (self.new_cursor_pos.2).wrapping_div(rows);
-
-                    let top_idx = page_no * rows;
-                    self.force_draw |= row >= top_idx && row < top_idx + rows;
+                    updated.push(self.rows.env_order[&env_hash]);
                 }
-                if self.force_draw {
+                /* Only repaint the updated rows, unless the cursor moved or
+                 * something else requires the entire page to be redrawn. */
+                if self.force_draw
+                    || self.movement.is_some()
+                    || self.cursor_pos != self.new_cursor_pos
+                    || !self.draw_row_updates(grid, area, &updated, context)
+                {
                     /* Draw the entire list */
                     self.draw_list(grid, area, context);
-                    self.force_draw = false;
                 }
+                self.force_draw = false;
             } else {
                 /* Draw the entire list */
                 self.draw_list(grid, area, context);
@@ -1754,21 +1921,17 @@ impl Component for PlainListing {
                     return true;
                 }
                 UIEvent::Action(ref action) => match action {
-                    Action::Sort(field, order) if !self.unfocused() => {
-                        let new_order = (*field, *order);
-                        if new_order != self.sort {
+                    Action::Sort(_) | Action::ReverseSort | Action::SubSort(_, _)
+                        if !self.unfocused() =>
+                    {
+                        if let Some(new_sort) = apply_sort_action(&self.sort, action) {
                             // "Keep it coming"
-                            self.sort = (*field, *order);
+                            self.sort = new_sort;
                             self.refresh_mailbox(context, false);
                             self.set_dirty(true);
                         }
                         return true;
                     }
-                    Action::SubSort(field, order) if !self.unfocused() => {
-                        // Plain listing has no subsort semantics.
-                        self.subsort = (*field, *order);
-                        return true;
-                    }
 
                     _ => {}
                 },
@@ -1848,7 +2011,7 @@ impl Component for PlainListing {
             UIEvent::Action(Action::Listing(Search(ref filter_term))) if !self.unfocused() => {
                 match context.accounts[&self.cursor_pos.0].search(
                     filter_term,
-                    self.sort,
+                    &self.sort,
                     self.cursor_pos.1,
                 ) {
                     Ok(job) => {
@@ -1877,7 +2040,7 @@ impl Component for PlainListing {
             UIEvent::Action(Action::Listing(Select(ref search_term))) if !self.unfocused() => {
                 match context.accounts[&self.cursor_pos.0].search(
                     search_term,
-                    self.sort,
+                    &self.sort,
                     self.cursor_pos.1,
                 ) {
                     Ok(job) => {
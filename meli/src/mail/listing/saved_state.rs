@@ -0,0 +1,133 @@
+/*
+ * meli
+ *
+ * Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Listing state that is kept across sessions.
+//!
+//! Each account has a `listing_state` file in its data directory, which
+//! records the mailbox that was open last, the selected e-mail of each
+//! mailbox, which mailboxes are collapsed in the sidebar and which threads
+//! are collapsed in the thread view. Mailboxes are identified by their path
+//! and e-mail by their Message-ID, since neither row numbers nor envelope
+//! hashes are stable across sessions.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use melib::error::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::version_migrations::artifacts::{from_unversioned, StateFile};
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ListingState {
+    /// Path of the mailbox that was open last.
+    pub mailbox: Option<String>,
+    /// Message-ID of the selected e-mail of each mailbox.
+    pub selected: BTreeMap<String, String>,
+    /// Mailboxes collapsed in the sidebar, if the account was ever shown.
+    #[serde(alias = "collapsed")]
+    pub collapsed_mailboxes: Option<BTreeSet<String>>,
+    /// Message-IDs of e-mail whose replies are collapsed in the thread view.
+    pub collapsed_threads: BTreeSet<String>,
+    /// Whether the last open mailbox has been reopened in this session.
+    #[serde(skip)]
+    pub restored: bool,
+}
+
+impl ListingState {
+    /// The `listing_state` file of an account.
+    pub const FILE: StateFile = StateFile {
+        description: "listing state",
+        name: "listing_state",
+        steps: &[from_unversioned],
+    };
+
+    /// Load the saved state of account `account_name`, or an empty state if
+    /// there is none.
+    pub fn load(account_name: &str) -> Self {
+        match Self::FILE.load(account_name) {
+            Ok(state) => state.unwrap_or_default(),
+            Err(err) => {
+                log::warn!(
+                    "Could not read listing state of account {}: {}",
+                    account_name,
+                    err
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Whether mailbox `path` was collapsed in the sidebar, if known.
+    pub fn is_collapsed(&self, path: &str) -> Option<bool> {
+        self.collapsed_mailboxes.as_ref().map(|c| c.contains(path))
+    }
+
+    pub fn save(&self, account_name: &str) -> Result<()> {
+        Self::FILE.save(account_name, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listing_saved_state_serde() {
+        let state: ListingState = serde_json::from_str("{}").unwrap();
+        assert_eq!(state.mailbox, None);
+        assert!(state.selected.is_empty());
+        assert_eq!(state.collapsed_mailboxes, None);
+        assert!(state.collapsed_threads.is_empty());
+
+        let mut state = ListingState {
+            mailbox: Some("INBOX/lists".to_string()),
+            restored: true,
+            ..ListingState::default()
+        };
+        state
+            .selected
+            .insert("INBOX".to_string(), "1234@example.com".to_string());
+        state.collapsed_mailboxes = Some(["Archive".to_string()].into_iter().collect());
+        state
+            .collapsed_threads
+            .insert("5678@example.com".to_string());
+        let state: ListingState =
+            serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        assert_eq!(state.mailbox.as_deref(), Some("INBOX/lists"));
+        assert_eq!(
+            state.selected.get("INBOX").map(String::as_str),
+            Some("1234@example.com")
+        );
+        assert!(state.collapsed_mailboxes.unwrap().contains("Archive"));
+        assert!(state.collapsed_threads.contains("5678@example.com"));
+        assert!(!state.restored);
+
+        // Files written before threads were tracked name the sidebar state
+        // `collapsed` and have row numbers instead of Message-IDs, which are
+        // ignored.
+        let state: ListingState =
+            serde_json::from_str(r#"{"cursors": {"INBOX": 12}, "collapsed": ["Archive"]}"#)
+                .unwrap();
+        assert!(state.selected.is_empty());
+        assert_eq!(state.is_collapsed("Archive"), Some(true));
+    }
+}
@@ -21,10 +21,13 @@
 
 use std::{convert::TryInto, iter::FromIterator};
 
-use melib::{Address, SortField, SortOrder, ThreadNode, Threads};
+use melib::{Address, SortChain, ThreadNode, Threads};
 
 use super::*;
-use crate::{components::PageMovement, jobs::JoinHandle, segment_tree::SegmentTree};
+use crate::{
+    components::PageMovement, conf::data_types::ThreadTreeGlyphs, jobs::JoinHandle,
+    segment_tree::SegmentTree,
+};
 
 macro_rules! row_attr {
     ($color_cache:expr, even: $even:expr, unseen: $unseen:expr, highlighted: $highlighted:expr, selected: $selected:expr  $(,)*) => {{
@@ -133,8 +136,7 @@ pub struct ThreadListing {
     cursor_pos: (AccountHash, MailboxHash, usize),
     new_cursor_pos: (AccountHash, MailboxHash, usize),
     length: usize,
-    sort: (SortField, SortOrder),
-    subsort: (SortField, SortOrder),
+    sort: SortChain,
     /// Cache current view.
     color_cache: ColorCache,
 
@@ -148,6 +150,13 @@ pub struct ThreadListing {
     data_columns: DataColumns<5>,
     rows_drawn: SegmentTree,
     rows: RowsState<(ThreadHash, EnvelopeHash)>,
+    /// Active `limit` query.
+    limit: Option<ListingLimit>,
+    /// Entries hidden by `listing.filter` or the limit that are shown as the
+    /// context of the matching entries of their thread.
+    context_rows: HashSet<EnvelopeHash>,
+    /// Threads whose hidden entries are shown, see `listing.filter_context`.
+    expanded_context: HashSet<ThreadHash>,
     seen_cache: IndexMap<EnvelopeHash, bool>,
     /// If we must redraw on next redraw event
     dirty: bool,
@@ -232,10 +241,8 @@ impl MailListingTrait for ThreadListing {
         let threads = context.accounts[&self.cursor_pos.0]
             .collection
             .get_threads(self.cursor_pos.1);
-        let mut roots = threads.roots();
-        threads.group_inner_sort_by(
-            &mut roots,
-            self.sort,
+        let roots = threads.sorted_roots(
+            &self.sort,
             &context.accounts[&self.cursor_pos.0].collection.envelopes,
         );
 
@@ -290,8 +297,71 @@ impl MailListingTrait for ThreadListing {
             .filter_map(|r| threads.groups[&r].root().map(|r| r.root))
             .collect::<SmallVec<[ThreadNodeHash; 1024]>>();
         let mut indentations: Vec<bool> = Vec::with_capacity(6);
-        let mut iter = threads.threads_iter(roots).peekable();
         let thread_nodes: &HashMap<ThreadNodeHash, ThreadNode> = threads.thread_nodes();
+        let filter_query = mailbox_settings!(
+            context[self.new_cursor_pos.0][&self.new_cursor_pos.1]
+                .listing
+                .filter
+        )
+        .as_ref();
+        let filter_context = *mailbox_settings!(
+            context[self.new_cursor_pos.0][&self.new_cursor_pos.1]
+                .listing
+                .filter_context
+        );
+        let rows = threads.threads_iter(roots).collect::<Vec<_>>();
+        self.context_rows.clear();
+        // Number of the entries of each thread that are hidden and not shown as
+        // context.
+        let mut hidden_count: HashMap<ThreadHash, usize> = HashMap::default();
+        let rows = if filter_query.is_some() || self.limit.is_some() {
+            use melib::search::QueryTrait;
+            let mut matching: HashSet<ThreadNodeHash> = HashSet::default();
+            for (_, thread_node_hash, _) in &rows {
+                let thread_node = &thread_nodes[thread_node_hash];
+                let Some(env_hash) = thread_node.message() else {
+                    continue;
+                };
+                let envelope = account.collection.get_env(env_hash);
+                if filter_query.map_or(true, |q| envelope.is_match(q))
+                    && self.limit.as_ref().map_or(true, |l| l.is_match(&envelope))
+                {
+                    matching.insert(*thread_node_hash);
+                } else if filter_context {
+                    let group = threads.find_group(thread_node.group);
+                    if self.expanded_context.contains(&group) {
+                        self.context_rows.insert(env_hash);
+                    } else {
+                        *hidden_count.entry(group).or_default() += 1;
+                    }
+                }
+            }
+            visible_thread_rows(rows, |thread_node_hash| {
+                matching.contains(thread_node_hash)
+                    || thread_nodes[thread_node_hash]
+                        .message()
+                        .is_some_and(|env_hash| self.context_rows.contains(&env_hash))
+            })
+        } else {
+            rows
+        };
+        let mut iter = rows.into_iter().peekable();
+        let glyphs = mailbox_settings!(
+            context[self.new_cursor_pos.0][&self.new_cursor_pos.1]
+                .listing
+                .thread_tree_glyphs
+        )
+        .unwrap_or_else(|| {
+            if *mailbox_settings!(
+                context[self.new_cursor_pos.0][&self.new_cursor_pos.1]
+                    .listing
+                    .thread_tree_ascii
+            ) {
+                ThreadTreeGlyphs::ASCII
+            } else {
+                ThreadTreeGlyphs::UNICODE
+            }
+        });
         /* This is just a desugared for loop so that we can use .peek() */
         let mut idx: usize = 0;
         let mut prev_group = ThreadHash::null();
@@ -325,18 +395,6 @@ impl MailListingTrait for ThreadListing {
 
             if let Some(env_hash) = thread_node.message() {
                 let envelope: EnvelopeRef = account.collection.get_env(env_hash);
-                use melib::search::QueryTrait;
-                if let Some(filter_query) = mailbox_settings!(
-                    context[self.new_cursor_pos.0][&self.new_cursor_pos.1]
-                        .listing
-                        .filter
-                )
-                .as_ref()
-                {
-                    if !envelope.is_match(filter_query) {
-                        continue;
-                    }
-                }
                 let is_root = threads.find_group(thread_node.group) != prev_group;
                 prev_group = threads.find_group(thread_node.group);
 
@@ -351,9 +409,16 @@ impl MailListingTrait for ThreadListing {
                     &indentations,
                     has_sibling,
                     is_root,
+                    &glyphs,
                 ));
+                if let Some(hidden) = hidden_count.get(&prev_group).filter(|_| is_root) {
+                    entry_strings
+                        .subject
+                        .push_str(&format!(" ({hidden} hidden)"));
+                }
                 if hide_from {
                     entry_strings.from.clear();
+                    entry_strings.initials.clear();
                 }
                 hide_from = !threaded_repeat_identical_from_values
                     && matches!(
@@ -368,13 +433,9 @@ impl MailListingTrait for ThreadListing {
                         .try_into()
                         .unwrap_or(255),
                 ); /* date */
-                row_widths.2.push(
-                    entry_strings
-                        .from
-                        .grapheme_width()
-                        .try_into()
-                        .unwrap_or(255),
-                ); /* from */
+                row_widths
+                    .2
+                    .push(entry_strings.from_width().try_into().unwrap_or(255)); /* from */
                 row_widths.3.push(
                     (entry_strings.flag.grapheme_width()
                         + usize::from(entry_strings.highlight_self) * highlight_self_colwidth)
@@ -389,7 +450,7 @@ impl MailListingTrait for ThreadListing {
                     .unwrap_or(255),
                 );
                 min_width.1 = min_width.1.max(entry_strings.date.grapheme_width()); /* date */
-                min_width.2 = min_width.2.max(entry_strings.from.grapheme_width()); /* from */
+                min_width.2 = min_width.2.max(entry_strings.from_width()); /* from */
                 min_width.3 = min_width.3.max(
                     entry_strings.flag.grapheme_width()
                         + usize::from(entry_strings.highlight_self) * highlight_self_colwidth,
@@ -510,6 +571,8 @@ impl ListingTrait for ThreadListing {
     fn set_coordinates(&mut self, coordinates: (AccountHash, MailboxHash)) {
         if self.coordinates() != (coordinates.0, coordinates.1) {
             self.new_cursor_pos = (coordinates.0, coordinates.1, 0);
+            self.limit = None;
+            self.expanded_context.clear();
         }
         self.focus = Focus::None;
         self.initialized = false;
@@ -534,6 +597,15 @@ impl ListingTrait for ThreadListing {
                 area,
                 self.data_columns.columns[0].area(),
             );
+            if self.filter_term.is_empty() {
+                draw_skeleton_rows(
+                    grid,
+                    area.skip_rows(1),
+                    pending_envelopes(&context.accounts[&self.cursor_pos.0], self.cursor_pos.1),
+                    1,
+                    &self.color_cache,
+                );
+            }
             context.dirty_areas.push_back(area);
             return;
         }
@@ -570,6 +642,7 @@ impl ListingTrait for ThreadListing {
                 if highlight {
                     let row_attr = row_attr!(self.color_cache, even: idx % 2 == 0, unseen: false, highlighted: true, selected: false);
                     grid.change_theme(new_area, row_attr);
+                    context.set_focus(new_area.upper_left());
                 } else if let Some(row_attr) = self.rows.row_attr_cache.get(&idx) {
                     grid.change_theme(new_area, *row_attr);
                 }
@@ -619,6 +692,7 @@ impl ListingTrait for ThreadListing {
             selected: false
         );
         grid.change_theme(area.nth_row(self.cursor_pos.2 % rows), row_attr);
+        context.set_focus(area.nth_row(self.cursor_pos.2 % rows).upper_left());
 
         // clear gap if available height is more than count of entries
         if top_idx + rows > self.length {
@@ -626,6 +700,15 @@ impl ListingTrait for ThreadListing {
                 area.skip_rows(self.length - top_idx),
                 self.color_cache.theme_default,
             );
+            if self.filter_term.is_empty() {
+                draw_skeleton_rows(
+                    grid,
+                    area.skip_rows(self.length - top_idx),
+                    pending_envelopes(&context.accounts[&self.cursor_pos.0], self.cursor_pos.1),
+                    1,
+                    &self.color_cache,
+                );
+            }
         }
 
         self.force_draw = false;
@@ -708,7 +791,7 @@ impl ListingTrait for ThreadListing {
         if !self.filtered_selection.is_empty() {
             threads.group_inner_sort_by(
                 &mut self.filtered_selection,
-                self.sort,
+                &self.sort,
                 &context.accounts[&self.cursor_pos.0].collection.envelopes,
             );
             self.new_cursor_pos.2 = self.cursor_pos.2.min(self.filtered_selection.len() - 1);
@@ -728,6 +811,14 @@ impl ListingTrait for ThreadListing {
         self.view_area
     }
 
+    fn limit(&self) -> Option<&ListingLimit> {
+        self.limit.as_ref()
+    }
+
+    fn set_limit(&mut self, limit: Option<ListingLimit>) {
+        self.limit = limit;
+    }
+
     fn unfocused(&self) -> bool {
         !matches!(self.focus, Focus::None)
     }
@@ -753,6 +844,30 @@ impl ListingTrait for ThreadListing {
         self.set_dirty(true);
     }
 
+    fn cursor_row(&self) -> usize {
+        self.new_cursor_pos.2
+    }
+
+    fn cursor_envelope(&self) -> Option<EnvelopeHash> {
+        self.get_env_under_cursor(self.new_cursor_pos.2)
+    }
+
+    fn envelope_row(&self, env_hash: EnvelopeHash) -> Option<usize> {
+        if !self.filter_term.is_empty() {
+            return None;
+        }
+        self.rows.env_order.get(&env_hash).copied()
+    }
+
+    fn entry_dates(&self, context: &Context) -> Vec<UnixTimestamp> {
+        let account = &context.accounts[&self.cursor_pos.0];
+        self.rows
+            .entries
+            .iter()
+            .map(|((_, env_hash), _)| account.collection.get_env(*env_hash).date())
+            .collect()
+    }
+
     fn set_focus(&mut self, new_value: Focus, context: &mut Context) {
         match new_value {
             Focus::None => {
@@ -814,17 +929,19 @@ impl ThreadListing {
         context: &Context,
     ) -> Box<Self> {
         let color_cache = ColorCache::new(context, IndexStyle::Threaded);
-        let sort = *mailbox_settings!(context[coordinates.0][&coordinates.1].listing.sort);
+        let sort = mailbox_settings!(context[coordinates.0][&coordinates.1].listing.sort).clone();
         Box::new(Self {
             cursor_pos: (coordinates.0, MailboxHash::default(), 0),
             new_cursor_pos: (coordinates.0, coordinates.1, 0),
             length: 0,
             sort,
-            subsort: (Default::default(), Default::default()),
             data_columns: DataColumns::new(color_cache.theme_default),
             color_cache,
             rows_drawn: SegmentTree::default(),
             rows: RowsState::default(),
+            limit: None,
+            context_rows: HashSet::default(),
+            expanded_context: HashSet::default(),
             seen_cache: IndexMap::default(),
             filter_term: String::new(),
             search_job: None,
@@ -852,6 +969,7 @@ impl ThreadListing {
         indentations: &[bool],
         has_sibling: bool,
         is_root: bool,
+        glyphs: &ThreadTreeGlyphs,
     ) -> String {
         let thread_node = &threads[&node_idx];
         let has_parent = thread_node.has_parent();
@@ -867,7 +985,7 @@ impl ThreadListing {
         if !(is_root && has_parent && !has_sibling) {
             for i in 0..indent {
                 if indentations.len() > i && indentations[i] {
-                    subject.push('│');
+                    subject.push(glyphs.vertical);
                 } else if indentations.len() > i {
                     subject.push(' ');
                 }
@@ -879,16 +997,16 @@ impl ThreadListing {
 
         if indent > 0 && ((has_sibling || has_visible_parent) || is_root) {
             if has_sibling && has_visible_parent {
-                subject.push('├');
+                subject.push(glyphs.branch);
             } else if has_sibling {
-                subject.push('┬');
+                subject.push(glyphs.first_branch);
             } else if has_parent && is_root {
-                subject.push('─');
+                subject.push(glyphs.horizontal);
             } else {
-                subject.push('└');
+                subject.push(glyphs.last_branch);
             }
-            subject.push('─');
-            subject.push('>');
+            subject.push(glyphs.horizontal);
+            subject.push(glyphs.arrow);
         }
 
         if show_subject {
@@ -941,7 +1059,12 @@ impl ThreadListing {
         }
         let subject = e.subject().trim().to_string();
         EntryStrings {
-            date: DateString(self.format_date(context, e.date())),
+            date: DateString::new(
+                self.format_date(context, e.date()),
+                || e.size(),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            ),
             subject: SubjectString(subject),
             flag: FlagString::new(
                 e.flags(),
@@ -949,10 +1072,16 @@ impl ThreadListing {
                 /* snoozed */ false,
                 !e.is_seen(),
                 e.has_attachments(),
+                e.score(),
                 context,
                 (self.cursor_pos.0, self.cursor_pos.1),
             ),
             from: FromString(Address::display_name_slice(e.from(), None)),
+            initials: InitialsString::new(
+                e.from(),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            ),
             tags: TagString(tags, colors),
             unseen: !e.is_seen(),
             highlight_self: false,
@@ -997,6 +1126,29 @@ impl ThreadListing {
                 highlighted: false,
                 selected: false,
             );
+            let (date, score) = {
+                let envelope = context.accounts[&self.cursor_pos.0]
+                    .collection
+                    .get_env(*env_hash);
+                (envelope.date(), envelope.score())
+            };
+            let row_attr = self.color_cache.age_attr(
+                row_attr,
+                date,
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            );
+            let row_attr = self.color_cache.score_attr(
+                row_attr,
+                score,
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            );
+            let row_attr = if self.context_rows.contains(env_hash) {
+                self.color_cache.context_attr(row_attr)
+            } else {
+                row_attr
+            };
             self.rows.row_attr_cache.insert(idx, row_attr);
             {
                 let mut area_col_0 = columns[0].area().nth_row(idx);
@@ -1039,15 +1191,22 @@ impl ThreadListing {
             {
                 let area_col_2 = columns[2].area().nth_row(idx);
                 columns[2].grid_mut().clear_area(area_col_2, row_attr);
+                let initials_width = super::draw_initials(
+                    columns[2].grid_mut(),
+                    area_col_2,
+                    &strings.initials,
+                    &self.color_cache,
+                );
                 let (skip_cols, _) = columns[2].grid_mut().write_string(
                     &strings.from,
                     row_attr.fg,
                     row_attr.bg,
                     row_attr.attrs,
-                    area_col_2,
+                    area_col_2.skip_cols(initials_width),
                     None,
                     None,
                 );
+                let skip_cols = initials_width + skip_cols;
                 {
                     for text_formatter in crate::conf::text_format_regexps(context, "listing.from")
                     {
@@ -1055,8 +1214,8 @@ impl ThreadListing {
                         for (start, end) in text_formatter.regexp.find_iter(strings.from.as_str()) {
                             columns[2].grid_mut().set_tag(
                                 t,
-                                (start + skip_cols, idx),
-                                (end + skip_cols, idx),
+                                (start + initials_width, idx),
+                                (end + initials_width, idx),
                             );
                         }
                     }
@@ -1122,6 +1281,11 @@ impl ThreadListing {
                     None,
                     None,
                 ));
+                if context.draw_hyperlinks() {
+                    columns[4]
+                        .grid_mut()
+                        .set_uris_from_text(&strings.subject, columns[4].area().nth_row(idx));
+                }
                 {
                     for text_formatter in
                         crate::conf::text_format_regexps(context, "listing.subject")
@@ -1165,6 +1329,52 @@ impl ThreadListing {
         }
     }
 
+    /// Repaint only the rows in `updated` that are visible in `area`, after
+    /// they have been re-rendered by [`ThreadListing::update_line`].
+    ///
+    /// Returns `false` without drawing anything if the column widths of the
+    /// current page changed, in which case the entire page must be redrawn.
+    fn draw_row_updates(
+        &mut self,
+        grid: &mut CellBuffer,
+        area: Area,
+        updated: &[usize],
+        context: &mut Context,
+    ) -> bool {
+        let rows = area.height();
+        if rows == 0 || self.length == 0 {
+            return true;
+        }
+        let top_idx = (self.cursor_pos.2).wrapping_div(rows) * rows;
+        let widths = self.data_columns.widths;
+        _ = self.data_columns.recalc_widths(area.size(), top_idx);
+        if self.data_columns.widths != widths {
+            return false;
+        }
+        for &idx in updated {
+            if idx < top_idx || idx >= top_idx + rows || idx >= self.length {
+                continue;
+            }
+            let row_area = area.nth_row(idx % rows);
+            self.data_columns
+                .draw(grid, idx, self.cursor_pos.2, grid.bounds_iter(row_area));
+            if idx == self.cursor_pos.2 {
+                let row_attr = row_attr!(self.color_cache, even: idx % 2 == 0, unseen: false, highlighted: true, selected: false);
+                grid.change_theme(row_area, row_attr);
+            } else if let Some(row_attr) = self.rows.row_attr_cache.get(&idx) {
+                grid.change_theme(row_area, *row_attr);
+            }
+            context.dirty_areas.push_back(row_area);
+        }
+        if *account_settings!(context[self.cursor_pos.0].listing.relative_list_indices) {
+            self.draw_relative_numbers(grid, area, top_idx);
+            context
+                .dirty_areas
+                .push_back(area.take_cols(self.data_columns.widths[0]));
+        }
+        true
+    }
+
     fn update_line(&mut self, context: &Context, env_hash: EnvelopeHash) {
         let account = &context.accounts[&self.cursor_pos.0];
 
@@ -1183,6 +1393,21 @@ impl ThreadListing {
             highlighted: false,
             selected: self.selection().get(&env_hash).copied().unwrap_or(false),
         );
+        let row_attr = if self.selection().get(&env_hash).copied().unwrap_or(false) {
+            row_attr
+        } else {
+            self.color_cache.score_attr(
+                self.color_cache.age_attr(
+                    row_attr,
+                    envelope.date(),
+                    context,
+                    (self.cursor_pos.0, self.cursor_pos.1),
+                ),
+                envelope.score(),
+                context,
+                (self.cursor_pos.0, self.cursor_pos.1),
+            )
+        };
         self.rows.row_attr_cache.insert(idx, row_attr);
         self.seen_cache.insert(env_hash, envelope.is_seen());
 
@@ -1557,31 +1782,24 @@ impl Component for ThreadListing {
             }
 
             if !self.rows.row_updates.is_empty() {
-                let page_no = (self.new_cursor_pos.2).wrapping_div(rows);
-                let top_idx = page_no * rows;
-
+                let mut updated: SmallVec<[usize; 8]> = SmallVec::new();
                 while let Some(env_hash) = self.rows.row_updates.pop() {
                     if !self.rows.env_to_thread.contains_key(&env_hash) {
                         self.refresh_mailbox(context, true);
                         self.set_dirty(true);
+                        self.force_draw = true;
                         break;
                     }
                     self.update_line(context, env_hash);
-                    let row: usize = self.rows.env_order[&env_hash];
-                    let envelope: EnvelopeRef = context.accounts[&self.new_cursor_pos.0]
-                        .collection
-                        .get_env(env_hash);
-                    let row_attr = row_attr!(
-                        self.color_cache,
-                        even: row % 2 == 0,
-                        unseen: !envelope.is_seen(),
-                        highlighted: false,
-                        selected: self.selection()[&env_hash]
-                    );
-                    self.rows.row_attr_cache.insert(row, row_attr);
-                    self.force_draw |= row >= top_idx && row < top_idx + rows;
+                    updated.push(self.rows.env_order[&env_hash]);
                 }
-                if self.force_draw {
+                // Only repaint the updated rows, unless the cursor moved or
+                // something else requires the entire page to be redrawn.
+                if self.force_draw
+                    || self.movement.is_some()
+                    || self.cursor_pos != self.new_cursor_pos
+                    || !self.draw_row_updates(grid, area, &updated, context)
+                {
                     /* Draw the entire list */
                     self.draw_list(grid, area, context);
                 }
@@ -1736,6 +1954,30 @@ impl Component for ThreadListing {
                 }
                 return true;
             }
+            UIEvent::Input(ref key)
+                if !self.unfocused()
+                    && shortcut!(key == shortcuts[Shortcuts::LISTING]["toggle_thread_context"]) =>
+            {
+                let Some(env_hash) = self.get_env_under_cursor(self.new_cursor_pos.2) else {
+                    return true;
+                };
+                let group = {
+                    let account = &context.accounts[&self.cursor_pos.0];
+                    let threads = account.collection.get_threads(self.cursor_pos.1);
+                    let thread_node_hash = account.collection.get_env(env_hash).thread();
+                    threads
+                        .thread_nodes
+                        .get(&thread_node_hash)
+                        .map(|thread_node| threads.find_group(thread_node.group))
+                };
+                if let Some(group) = group {
+                    if !self.expanded_context.remove(&group) {
+                        self.expanded_context.insert(group);
+                    }
+                    self.refresh_mailbox(context, false);
+                }
+                return true;
+            }
             UIEvent::Input(ref key)
                 if !self.unfocused()
                     && shortcut!(key == shortcuts[Shortcuts::LISTING]["select_motion"]) =>
@@ -1746,21 +1988,12 @@ impl Component for ThreadListing {
                 return true;
             }
             UIEvent::Action(ref action) => match action {
-                Action::Sort(field, order) if !self.unfocused() => {
-                    let new_order = (*field, *order);
-                    if new_order != self.sort {
-                        // "Keep it coming"
-                        self.sort = (*field, *order);
-                        self.refresh_mailbox(context, false);
-                        self.set_dirty(true);
-                    }
-                    return true;
-                }
-                Action::SubSort(field, order) => {
-                    let new_order = (*field, *order);
-                    if new_order != self.subsort {
+                Action::Sort(_) | Action::ReverseSort | Action::SubSort(_, _)
+                    if !self.unfocused() =>
+                {
+                    if let Some(new_sort) = apply_sort_action(&self.sort, action) {
                         // "Keep it coming"
-                        self.subsort = (*field, *order);
+                        self.sort = new_sort;
                         self.refresh_mailbox(context, false);
                         self.set_dirty(true);
                     }
@@ -1769,7 +2002,7 @@ impl Component for ThreadListing {
                 Action::Listing(Search(ref filter_term)) if !self.unfocused() => {
                     match context.accounts[&self.new_cursor_pos.0].search(
                         filter_term,
-                        self.sort,
+                        &self.sort,
                         self.new_cursor_pos.1,
                     ) {
                         Ok(job) => {
@@ -1798,7 +2031,7 @@ impl Component for ThreadListing {
                 Action::Listing(Select(ref search_term)) if !self.unfocused() => {
                     match context.accounts[&self.cursor_pos.0].search(
                         search_term,
-                        self.sort,
+                        &self.sort,
                         self.cursor_pos.1,
                     ) {
                         Ok(job) => {
@@ -1901,3 +2134,36 @@ impl Component for ThreadListing {
         self.id
     }
 }
+
+/// Rows of thread trees, as returned by [`Threads::threads_iter`], without the
+/// ones that are not visible, e.g. because a filter query hides them.
+///
+/// Each visible row is attached to its closest visible ancestor, so that its
+/// indentation and whether it has siblings match the rows that are drawn and
+/// the tree has no broken branches.
+pub fn visible_thread_rows<T>(
+    rows: Vec<(usize, T, bool)>,
+    is_visible: impl Fn(&T) -> bool,
+) -> Vec<(usize, T, bool)> {
+    // Indentations of the visible ancestors of the current row.
+    let mut ancestors: Vec<usize> = vec![];
+    let mut ret = vec![];
+    for (indentation, item, _) in rows {
+        while ancestors.last().is_some_and(|a| *a >= indentation) {
+            ancestors.pop();
+        }
+        if is_visible(&item) {
+            ret.push((ancestors.len(), item, false));
+            ancestors.push(indentation);
+        }
+    }
+    // A row has a sibling if a later row has the same indentation before any
+    // row with a smaller one.
+    let mut seen: Vec<bool> = vec![];
+    for (indentation, _, has_sibling) in ret.iter_mut().rev() {
+        seen.resize(*indentation + 1, false);
+        *has_sibling = *indentation > 0 && seen[*indentation];
+        seen[*indentation] = true;
+    }
+    ret
+}
@@ -29,7 +29,7 @@ use std::{
 use melib::{
     email::{
         attachment_types::{ContentDisposition, ContentType, MultipartType},
-        pgp as melib_pgp, Attachment, AttachmentBuilder,
+        pgp as melib_pgp, Attachment, AttachmentBuilder, HeaderMap,
     },
     error::*,
     gpgme::*,
@@ -136,6 +136,7 @@ pub fn encrypt_filter(
     mut sign_keys: Option<Vec<Key>>,
     default_encrypt_key: Option<String>,
     mut encrypt_keys: Vec<Key>,
+    protected_headers: Option<HeaderMap>,
 ) -> Result<impl FnOnce(AttachmentBuilder) -> AttachmentBoxFuture + Send> {
     Ok(move |a: AttachmentBuilder| -> AttachmentBoxFuture {
         Box::pin(async move {
@@ -194,7 +195,7 @@ pub fn encrypt_filter(
                     }
                 }
             }
-            let a: Attachment = if let Some(sign_keys) = sign_keys {
+            let mut a: Attachment = if let Some(sign_keys) = sign_keys {
                 let a: Attachment = a.into();
                 let mut ctx = Context::new()?;
                 let data = ctx.new_data_mem(&melib_pgp::convert_attachment_to_rfc_spec(
@@ -221,8 +222,22 @@ pub fn encrypt_filter(
             } else {
                 a.into()
             };
+            let mut raw = String::new();
+            if let Some(headers) = protected_headers {
+                if let ContentType::Text {
+                    ref mut parameters, ..
+                }
+                | ContentType::Multipart {
+                    ref mut parameters, ..
+                } = a.content_type
+                {
+                    parameters.push((b"protected-headers".to_vec(), b"v1".to_vec()));
+                    raw.push_str(&melib_pgp::protected_headers_block(&headers));
+                }
+            }
+            raw.push_str(&a.into_raw());
             let mut ctx = Context::new()?;
-            let data = ctx.new_data_mem(a.into_raw().as_bytes())?;
+            let data = ctx.new_data_mem(raw.as_bytes())?;
 
             let enc_attachment = {
                 let mut a = Attachment::new(
@@ -535,4 +535,8 @@ impl Component for AccountStatus {
     fn id(&self) -> ComponentId {
         self.id
     }
+
+    fn view_name(&self) -> Option<&'static str> {
+        Some("account status")
+    }
 }
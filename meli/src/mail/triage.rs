@@ -0,0 +1,464 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Unread e-mail triage, opened with the `triage` command.
+//!
+//! Shows the unseen e-mail of a mailbox one at a time, newest first. Each
+//! e-mail is archived, sent to the trash, kept or replied to with a single
+//! key, after which the next unseen e-mail is shown right away.
+
+use std::collections::{HashSet, VecDeque};
+
+use melib::{Flag, FlagOp, SpecialUsageMailbox, UnixTimestamp};
+
+use super::*;
+
+/// The unseen e-mail left to triage, newest first.
+#[derive(Debug, Default)]
+struct TriageQueue {
+    /// The first entry is the e-mail that is shown.
+    pending: VecDeque<(UnixTimestamp, EnvelopeHash)>,
+    /// E-mail that were already triaged, so that they are not queued again
+    /// before their flags are updated.
+    done: HashSet<EnvelopeHash>,
+}
+
+impl TriageQueue {
+    fn new(entries: impl IntoIterator<Item = (EnvelopeHash, UnixTimestamp)>) -> Self {
+        let mut pending = entries
+            .into_iter()
+            .map(|(env_hash, date)| (date, env_hash))
+            .collect::<Vec<_>>();
+        pending.sort_by(|a, b| b.cmp(a));
+        Self {
+            pending: pending.into(),
+            done: HashSet::default(),
+        }
+    }
+
+    fn current(&self) -> Option<EnvelopeHash> {
+        self.pending.front().map(|(_, h)| *h)
+    }
+
+    /// Mark the current e-mail as triaged and return the next one.
+    fn advance(&mut self) -> Option<EnvelopeHash> {
+        if let Some((_, env_hash)) = self.pending.pop_front() {
+            self.done.insert(env_hash);
+        }
+        self.current()
+    }
+
+    /// Queue an e-mail that arrived after the triage started. The shown
+    /// e-mail stays the same, so new e-mail are placed after it.
+    fn insert(&mut self, env_hash: EnvelopeHash, date: UnixTimestamp) -> bool {
+        if self.done.contains(&env_hash) || self.pending.iter().any(|(_, h)| *h == env_hash) {
+            return false;
+        }
+        let pos = self
+            .pending
+            .iter()
+            .skip(1)
+            .position(|(d, _)| *d < date)
+            .map(|i| i + 1)
+            .unwrap_or(self.pending.len());
+        self.pending.insert(pos, (date, env_hash));
+        true
+    }
+
+    /// Remove an e-mail that is no longer in the mailbox. Returns whether it
+    /// was the shown e-mail.
+    fn remove(&mut self, env_hash: EnvelopeHash) -> bool {
+        let was_current = self.current() == Some(env_hash);
+        self.pending.retain(|(_, h)| *h != env_hash);
+        was_current
+    }
+
+    fn rename(&mut self, old_hash: EnvelopeHash, new_hash: EnvelopeHash) {
+        for (_, h) in self.pending.iter_mut() {
+            if *h == old_hash {
+                *h = new_hash;
+            }
+        }
+        if self.done.remove(&old_hash) {
+            self.done.insert(new_hash);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum TriageAction {
+    Archive,
+    Delete,
+    Keep,
+    Reply,
+}
+
+#[derive(Debug)]
+pub struct TriageView {
+    account_hash: AccountHash,
+    mailbox_hash: MailboxHash,
+    mailbox_name: String,
+    queue: TriageQueue,
+    view: Option<Box<MailView>>,
+    theme_default: ThemeAttribute,
+    header_theme: ThemeAttribute,
+    dirty: bool,
+    id: ComponentId,
+}
+
+impl std::fmt::Display for TriageView {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "triage of {}", self.mailbox_name)
+    }
+}
+
+impl TriageView {
+    pub fn new(
+        context: &mut Context,
+        account_hash: AccountHash,
+        mailbox_hash: MailboxHash,
+    ) -> Result<Self> {
+        let account = &context.accounts[&account_hash];
+        let Some(mailbox_name) = account
+            .mailbox_entries
+            .get(&mailbox_hash)
+            .map(|entry| entry.name().to_string())
+        else {
+            return Err(Error::new("No mailbox is open.").set_kind(ErrorKind::NotFound));
+        };
+        let queue = TriageQueue::new(Self::unseen(context, account_hash, mailbox_hash));
+        let mut ret = Self {
+            account_hash,
+            mailbox_hash,
+            mailbox_name,
+            queue,
+            view: None,
+            theme_default: crate::conf::value(context, "theme_default"),
+            header_theme: crate::conf::value(context, "widgets.list.header"),
+            dirty: true,
+            id: ComponentId::default(),
+        };
+        ret.open_current(context);
+        Ok(ret)
+    }
+
+    /// Unseen e-mail of the mailbox with their dates.
+    fn unseen(
+        context: &Context,
+        account_hash: AccountHash,
+        mailbox_hash: MailboxHash,
+    ) -> Vec<(EnvelopeHash, UnixTimestamp)> {
+        let account = &context.accounts[&account_hash];
+        if !account.mailbox_entries.contains_key(&mailbox_hash) {
+            return vec![];
+        }
+        let envelopes = account.collection.envelopes.read().unwrap();
+        account
+            .collection
+            .get_mailbox(mailbox_hash)
+            .iter()
+            .filter_map(|h| envelopes.get(h))
+            .filter(|env| !env.is_seen())
+            .map(|env| (env.hash(), env.date()))
+            .collect()
+    }
+
+    fn open_current(&mut self, context: &mut Context) {
+        self.view = self.queue.current().map(|env_hash| {
+            Box::new(MailView::new(
+                Some((self.account_hash, self.mailbox_hash, env_hash)),
+                true,
+                context,
+            ))
+        });
+        self.set_dirty(true);
+    }
+
+    fn perform(&mut self, action: TriageAction, context: &mut Context) {
+        let Some(env_hash) = self.queue.current() else {
+            return;
+        };
+        let account = &mut context.accounts[&self.account_hash];
+        let (title, result) = match action {
+            TriageAction::Archive => (
+                "Could not archive e-mail",
+                account
+                    .special_use_mailbox(SpecialUsageMailbox::Archive)
+                    .ok_or_else(|| {
+                        Error::new("No Archive mailbox is configured.")
+                            .set_kind(ErrorKind::NotFound)
+                    })
+                    .and_then(|archive| {
+                        account.move_messages(env_hash.into(), self.mailbox_hash, archive)
+                    })
+                    .map(|_| ()),
+            ),
+            TriageAction::Delete => (
+                "Could not send e-mail to trash",
+                account
+                    .special_use_mailbox(SpecialUsageMailbox::Trash)
+                    .ok_or_else(|| {
+                        Error::new("No Trash mailbox is configured.").set_kind(ErrorKind::NotFound)
+                    })
+                    .and_then(|trash| {
+                        account.move_messages(env_hash.into(), self.mailbox_hash, trash)
+                    })
+                    .map(|_| ()),
+            ),
+            TriageAction::Keep => (
+                "Could not set message as seen",
+                account.set_flags(
+                    env_hash.into(),
+                    self.mailbox_hash,
+                    vec![FlagOp::Set(Flag::SEEN)],
+                ),
+            ),
+            TriageAction::Reply => {
+                if !self.view.as_mut().is_some_and(|view| view.reply(context)) {
+                    context
+                        .replies
+                        .push_back(UIEvent::StatusEvent(StatusEvent::DisplayMessage(
+                            "E-mail is still loading.".to_string(),
+                        )));
+                    return;
+                }
+                ("", Ok(()))
+            }
+        };
+        if let Err(err) = result {
+            context.replies.push_back(UIEvent::Notification {
+                title: Some(title.into()),
+                source: None,
+                body: err.to_string().into(),
+                kind: Some(NotificationType::Error(err.kind)),
+            });
+            return;
+        }
+        self.next(context);
+    }
+
+    fn next(&mut self, context: &mut Context) {
+        self.queue.advance();
+        self.open_current(context);
+    }
+
+    fn update(&mut self, context: &mut Context) {
+        let was_empty = self.queue.current().is_none();
+        for (env_hash, date) in Self::unseen(context, self.account_hash, self.mailbox_hash) {
+            self.queue.insert(env_hash, date);
+        }
+        if was_empty && self.queue.current().is_some() {
+            self.open_current(context);
+        }
+        self.set_dirty(true);
+    }
+}
+
+impl Component for TriageView {
+    fn draw(&mut self, grid: &mut CellBuffer, area: Area, context: &mut Context) {
+        if self.dirty {
+            let header = area.nth_row(0);
+            grid.clear_area(header, self.header_theme);
+            let text = if self.queue.current().is_none() {
+                format!("{}: no unread e-mail left.", self.mailbox_name)
+            } else {
+                let shortcuts = &context.settings.shortcuts.triage;
+                format!(
+                    "{}: {} unread e-mail left, newest first. {}: archive, {}: delete, {}: keep, \
+                     {}: reply",
+                    self.mailbox_name,
+                    self.queue.len(),
+                    shortcuts.archive,
+                    shortcuts.delete,
+                    shortcuts.keep,
+                    shortcuts.reply
+                )
+            };
+            grid.write_string(
+                &text,
+                self.header_theme.fg,
+                self.header_theme.bg,
+                self.header_theme.attrs | Attr::BOLD,
+                header.skip_cols(1),
+                None,
+                None,
+            );
+            context.dirty_areas.push_back(header);
+            if self.view.is_none() {
+                let body = area.skip_rows(1);
+                grid.clear_area(body, self.theme_default);
+                context.dirty_areas.push_back(body);
+            }
+            self.dirty = false;
+        }
+        if let Some(view) = self.view.as_mut() {
+            view.draw(grid, area.skip_rows(1), context);
+        }
+    }
+
+    fn process_event(&mut self, event: &mut UIEvent, context: &mut Context) -> bool {
+        let shortcuts = self.shortcuts(context);
+        let action = match *event {
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::TRIAGE]["archive"]) =>
+            {
+                Some(TriageAction::Archive)
+            }
+            UIEvent::Input(ref key) if shortcut!(key == shortcuts[Shortcuts::TRIAGE]["delete"]) => {
+                Some(TriageAction::Delete)
+            }
+            UIEvent::Input(ref key) if shortcut!(key == shortcuts[Shortcuts::TRIAGE]["keep"]) => {
+                Some(TriageAction::Keep)
+            }
+            UIEvent::Input(ref key) if shortcut!(key == shortcuts[Shortcuts::TRIAGE]["reply"]) => {
+                Some(TriageAction::Reply)
+            }
+            _ => None,
+        };
+        if let Some(action) = action {
+            self.perform(action, context);
+            return true;
+        }
+        if let Some(view) = self.view.as_mut() {
+            if view.process_event(event, context) {
+                return true;
+            }
+        }
+        match *event {
+            UIEvent::ConfigReload { old_settings: _ } => {
+                self.theme_default = crate::conf::value(context, "theme_default");
+                self.header_theme = crate::conf::value(context, "widgets.list.header");
+                self.set_dirty(true);
+            }
+            UIEvent::Resize => {
+                self.set_dirty(true);
+            }
+            UIEvent::MailboxUpdate((account_hash, mailbox_hash))
+                if account_hash == self.account_hash && mailbox_hash == self.mailbox_hash =>
+            {
+                self.update(context);
+            }
+            UIEvent::EnvelopeRename(old_hash, new_hash) => {
+                self.queue.rename(old_hash, new_hash);
+            }
+            UIEvent::EnvelopeRemove(env_hash, _) => {
+                if self.queue.remove(env_hash) {
+                    self.open_current(context);
+                } else {
+                    self.set_dirty(true);
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn shortcuts(&self, context: &Context) -> ShortcutMaps {
+        let mut map = self
+            .view
+            .as_ref()
+            .map(|view| view.shortcuts(context))
+            .unwrap_or_default();
+        map.insert(
+            Shortcuts::TRIAGE,
+            context.settings.shortcuts.triage.key_values(),
+        );
+        map
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty || self.view.as_ref().is_some_and(|view| view.is_dirty())
+    }
+
+    fn set_dirty(&mut self, value: bool) {
+        self.dirty = value;
+        if let Some(view) = self.view.as_mut() {
+            view.set_dirty(value);
+        }
+    }
+
+    fn kill(&mut self, uuid: ComponentId, context: &mut Context) {
+        debug_assert!(uuid == self.id);
+        context
+            .replies
+            .push_back(UIEvent::Action(crate::command::Action::Tab(
+                crate::command::TabAction::Kill(uuid),
+            )));
+    }
+
+    fn id(&self) -> ComponentId {
+        self.id
+    }
+
+    fn view_name(&self) -> Option<&'static str> {
+        Some("triage")
+    }
+
+    fn status(&self, _context: &Context) -> String {
+        format!(
+            "Triage of {}: {} unread e-mail left",
+            self.mailbox_name,
+            self.queue.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triage_queue() {
+        let [a, b, c, d, e] = [1, 2, 3, 4, 5].map(EnvelopeHash);
+        let mut queue = TriageQueue::new([(a, 10), (b, 30), (c, 20)]);
+        assert_eq!(queue.current(), Some(b));
+        assert_eq!(queue.len(), 3);
+
+        // New e-mail are shown after the current one, newest first.
+        assert!(queue.insert(d, 40));
+        assert!(queue.insert(e, 15));
+        assert!(!queue.insert(d, 40));
+        assert_eq!(queue.current(), Some(b));
+        assert_eq!(
+            queue.pending.iter().map(|(_, h)| *h).collect::<Vec<_>>(),
+            vec![b, d, c, e, a]
+        );
+
+        assert_eq!(queue.advance(), Some(d));
+        // Triaged e-mail are not queued again.
+        assert!(!queue.insert(b, 30));
+
+        assert!(!queue.remove(c));
+        assert!(queue.remove(d));
+        assert_eq!(queue.current(), Some(e));
+        queue.rename(e, c);
+        assert_eq!(queue.current(), Some(c));
+        assert_eq!(queue.advance(), Some(a));
+        assert_eq!(queue.advance(), None);
+        assert_eq!(queue.advance(), None);
+        assert_eq!(queue.len(), 0);
+    }
+}
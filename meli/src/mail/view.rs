@@ -28,8 +28,11 @@ use std::{
 
 use indexmap::IndexSet;
 use melib::{
-    email::attachment_types::ContentType, list_management, mailto::Mailto, parser::BytesExt, Card,
-    Draft, FlagOp, HeaderName, SpecialUsageMailbox,
+    email::{attachment_types::ContentType, forge::ForgeNotification},
+    list_management,
+    mailto::Mailto,
+    parser::BytesExt,
+    Card, Draft, FlagOp, HeaderName, SpecialUsageMailbox,
 };
 use smallvec::SmallVec;
 
@@ -51,6 +54,10 @@ pub use envelope::EnvelopeView;
 
 pub mod filters;
 pub use filters::*;
+pub mod links;
+pub mod preview;
+pub mod remote_content;
+pub mod scan;
 
 #[cfg(test)]
 mod tests;
@@ -120,6 +127,20 @@ impl MailView {
         let Some(coordinates) = self.coordinates else {
             return;
         };
+        if context.screen_reader() && context.accounts[&coordinates.0].contains_key(coordinates.2) {
+            let description = {
+                let envelope = context.accounts[&coordinates.0]
+                    .collection
+                    .get_env(coordinates.2);
+                format!(
+                    "{} from {}, {}",
+                    envelope.subject(),
+                    envelope.field_from_to_string(),
+                    envelope.date_as_str()
+                )
+            };
+            context.announce("Message", description);
+        }
         let account = &mut context.accounts[&coordinates.0];
         if account.contains_key(coordinates.2) {
             {
@@ -177,6 +198,17 @@ impl MailView {
         self.initialized = true;
     }
 
+    /// Open a reply to the viewed e-mail, unless its body is not loaded yet.
+    ///
+    /// Returns whether the composer was opened.
+    pub fn reply(&mut self, context: &mut Context) -> bool {
+        if !matches!(self.state, MailViewState::Loaded { .. }) {
+            return false;
+        }
+        self.perform_action(PendingReplyAction::Reply, context);
+        true
+    }
+
     fn perform_action(&mut self, action: PendingReplyAction, context: &mut Context) {
         let Some(coordinates) = self.coordinates else {
             return;
@@ -196,29 +228,41 @@ impl MailView {
             MailViewState::Loaded {
                 ref bytes,
                 ref env,
-                ref env_view,
+                ref mut env_view,
                 ..
-            } => (bytes, env_view.body_text(), env),
+            } => {
+                // Quote only the lines selected in the pager's visual mode, if any.
+                let selection = if matches!(
+                    action,
+                    PendingReplyAction::Reply
+                        | PendingReplyAction::ReplyToAuthor
+                        | PendingReplyAction::ReplyToAll
+                ) {
+                    env_view.pager.take_selection()
+                } else {
+                    None
+                };
+                let reply_body = selection.unwrap_or_else(|| {
+                    env_view.reply_text(*account_settings!(
+                        context[coordinates.0].composing.quote_attachments
+                    ))
+                });
+                (bytes, reply_body, env)
+            }
             MailViewState::Error { .. } => {
                 return;
             }
         };
         let composer = match action {
-            PendingReplyAction::Reply => Box::new(Composer::reply_to_select(
-                coordinates,
-                reply_body.to_string(),
-                context,
-            )),
-            PendingReplyAction::ReplyToAuthor => Box::new(Composer::reply_to_author(
-                coordinates,
-                reply_body.to_string(),
-                context,
-            )),
-            PendingReplyAction::ReplyToAll => Box::new(Composer::reply_to_all(
-                coordinates,
-                reply_body.to_string(),
-                context,
-            )),
+            PendingReplyAction::Reply => {
+                Box::new(Composer::reply_to_select(coordinates, reply_body, context))
+            }
+            PendingReplyAction::ReplyToAuthor => {
+                Box::new(Composer::reply_to_author(coordinates, reply_body, context))
+            }
+            PendingReplyAction::ReplyToAll => {
+                Box::new(Composer::reply_to_all(coordinates, reply_body, context))
+            }
             PendingReplyAction::ForwardAttachment => {
                 Box::new(Composer::forward(coordinates, bytes, env, true, context))
             }
@@ -334,9 +378,11 @@ impl Component for MailView {
             ref mut env_view, ..
         } = self.state
         {
-            {
+            if *mailbox_settings!(context[coordinates.0][&coordinates.1].pager.auto_mark_read) {
                 let account = &mut context.accounts[&coordinates.0];
-                if !account.collection.get_env(coordinates.2).is_seen() {
+                if !account.collection.get_env(coordinates.2).is_seen()
+                    && !account.is_read_only(coordinates.1)
+                {
                     if let Err(err) = account.set_flags(
                         coordinates.2.into(),
                         coordinates.1,
@@ -738,8 +784,9 @@ impl Component for MailView {
                                             .arg(&url_arg)
                                             .stdin(Stdio::piped())
                                             .stdout(Stdio::piped())
-                                            .spawn()
-                                        {
+                                            .spawn_checked(
+                                                &context.command_policy(Some(coordinates.0)),
+                                            ) {
                                             Ok(child) => {
                                                 context
                                                     .children
@@ -791,7 +838,7 @@ impl Component for MailView {
                                 .arg(url_arg)
                                 .stdin(Stdio::piped())
                                 .stdout(Stdio::piped())
-                                .spawn()
+                                .spawn_checked(&context.command_policy(Some(coordinates.0)))
                             {
                                 Ok(child) => context
                                     .children
@@ -819,6 +866,103 @@ impl Component for MailView {
                     }
                 };
             }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::ENVELOPE_VIEW]["forge_open"]) =>
+            {
+                context
+                    .replies
+                    .push_back(UIEvent::Command("forge-open".to_string()));
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::ENVELOPE_VIEW]["forge_mute"]) =>
+            {
+                context
+                    .replies
+                    .push_back(UIEvent::Command("forge-mute".to_string()));
+                return true;
+            }
+            UIEvent::Action(Forge(ref action)) => {
+                let account = &context.accounts[&coordinates.0];
+                if !account.contains_key(coordinates.2) {
+                    return true;
+                }
+                let envelope: EnvelopeRef = account.collection.get_env(coordinates.2);
+                let notification = ForgeNotification::detect(&envelope);
+                let has_unsubscribe = list_management::ListActions::detect(&envelope)
+                    .is_some_and(|actions| actions.unsubscribe.is_some());
+                drop(envelope);
+                let Some(notification) = notification else {
+                    context
+                        .replies
+                        .push_back(UIEvent::StatusEvent(StatusEvent::DisplayMessage(
+                            "Viewed e-mail is not a GitHub or GitLab notification.".to_string(),
+                        )));
+                    return true;
+                };
+                match action {
+                    ForgeAction::Open => {
+                        let Some(url_arg) = notification.url else {
+                            context.replies.push_back(UIEvent::StatusEvent(
+                                StatusEvent::DisplayMessage(format!(
+                                    "No web page is known for {notification}."
+                                )),
+                            ));
+                            return true;
+                        };
+                        let url_launcher = mailbox_settings!(
+                            context[coordinates.0][&coordinates.1].pager.url_launcher
+                        )
+                        .as_ref()
+                        .map(|s| s.as_str())
+                        .unwrap_or(if cfg!(target_os = "macos") {
+                            "open"
+                        } else {
+                            "xdg-open"
+                        });
+                        match Command::new(url_launcher)
+                            .arg(&url_arg)
+                            .stdin(Stdio::piped())
+                            .stdout(Stdio::piped())
+                            .spawn_checked(&context.command_policy(Some(coordinates.0)))
+                        {
+                            Ok(child) => context
+                                .children
+                                .entry(url_launcher.to_string().into())
+                                .or_default()
+                                .push(ForkedProcess::Generic {
+                                    id: url_launcher.to_string().into(),
+                                    command: Some(format!("{url_launcher} {url_arg}").into()),
+                                    child,
+                                }),
+                            Err(err) => {
+                                context.replies.push_back(UIEvent::Notification {
+                                    title: Some(format!("Couldn't launch {url_launcher}").into()),
+                                    source: None,
+                                    body: err.to_string().into(),
+                                    kind: Some(NotificationType::Error(err.kind().into())),
+                                });
+                            }
+                        }
+                    }
+                    // Forges send a List-Unsubscribe header that unsubscribes from
+                    // the thread of the issue or pull request only.
+                    ForgeAction::Mute if has_unsubscribe => {
+                        context.replies.push_back(UIEvent::Action(MailingListAction(
+                            MailingListAction::ListUnsubscribe,
+                        )));
+                    }
+                    ForgeAction::Mute => {
+                        context.replies.push_back(UIEvent::StatusEvent(
+                            StatusEvent::DisplayMessage(format!(
+                                "{notification} has no List-Unsubscribe header to mute its \
+                                 thread with."
+                            )),
+                        ));
+                    }
+                }
+                return true;
+            }
             UIEvent::Action(Listing(OpenInNewTab)) => {
                 let mut new_tab = Self::new(self.coordinates, true, context);
                 new_tab.set_dirty(true);
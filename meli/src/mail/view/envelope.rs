@@ -20,11 +20,16 @@
  */
 
 use std::{
-    collections::VecDeque,
+    borrow::Cow,
+    collections::{BTreeMap, VecDeque},
     process::{Command, Stdio},
 };
 
-use melib::utils::{shellexpand::ShellExpandTrait, xdg::query_default_app};
+use melib::{
+    email::{attachment_types::Text, dsn::DeliveryStatusNotification},
+    utils::{shellexpand::ShellExpandTrait, xdg::query_default_app},
+    MimePart, MimeStructure,
+};
 
 use super::*;
 #[cfg(feature = "gpgme")]
@@ -46,9 +51,13 @@ pub struct EnvelopeView {
     pub force_draw_headers: bool,
     pub options: ViewOptions,
     pub mail: Mail,
-    pub body: Box<Attachment>,
     pub display: Vec<AttachmentDisplay>,
+    /// The delivery status notification in the e-mail, if it is one.
+    pub delivery_report: Option<DeliveryStatusNotification>,
     pub body_text: String,
+    /// Length of the text of the first displayed part in `body_text`, before
+    /// the text of any attachments displayed inline.
+    pub main_body_text_len: Option<usize>,
     pub html_filter: Option<Result<ViewFilter>>,
     pub filters: Vec<ViewFilter>,
     pub links: Vec<Link<'static>>,
@@ -59,6 +68,10 @@ pub struct EnvelopeView {
     pub force_charset: Option<Box<UIDialog<Option<Charset>>>>,
     pub view_settings: ViewSettings,
     pub active_jobs: HashSet<JobId>,
+    /// Reports of attachments refused by the attachment scanner, by index.
+    pub scan_warnings: BTreeMap<usize, String>,
+    /// Digests of flagged attachments the user has allowed to be opened.
+    pub scan_overrides: HashSet<String>,
     pub main_loop_handler: MainLoopHandler,
     pub id: ComponentId,
 }
@@ -90,7 +103,6 @@ impl EnvelopeView {
         main_loop_handler: MainLoopHandler,
     ) -> Self {
         let view_settings = view_settings.unwrap_or_default();
-        let body = Box::new(AttachmentBuilder::new(&mail.bytes).build());
         let mut ret = Self {
             pager: pager.unwrap_or_default(),
             subview,
@@ -101,10 +113,11 @@ impl EnvelopeView {
             force_charset: None,
             attachment_tree: String::new(),
             attachment_paths: vec![],
-            body,
             display: vec![],
+            delivery_report: None,
             links: vec![],
             body_text: String::new(),
+            main_body_text_len: None,
             html_filter: None,
             filters: vec![],
             view_settings,
@@ -113,17 +126,31 @@ impl EnvelopeView {
             mail,
             main_loop_handler,
             active_jobs: HashSet::default(),
+            scan_warnings: BTreeMap::default(),
+            scan_overrides: HashSet::default(),
             id: ComponentId::default(),
         };
 
         let mut display = vec![];
-        Self::attachment_to_display_helper(
-            &ret.body,
+        let structure = MimeStructure::new(ret.mail.bytes.as_slice());
+        Self::part_to_display_helper(
+            &structure,
+            structure.root(),
             &ret.main_loop_handler,
             &mut ret.active_jobs,
             &mut display,
             &ret.view_settings,
         );
+        // Only parse the whole e-mail if it is a delivery status notification.
+        if structure.parts().iter().any(|(_, part)| {
+            part.mime_type()
+                .to_ascii_lowercase()
+                .ends_with("delivery-status")
+        }) {
+            ret.delivery_report = DeliveryStatusNotification::from_attachment(
+                &structure.attachment(structure.root()),
+            );
+        }
         let (attachment_paths, attachment_tree) = ret.attachment_displays_to_tree(&display);
         ret.display = display;
         ret.attachment_tree = attachment_tree;
@@ -132,6 +159,67 @@ impl EnvelopeView {
         ret
     }
 
+    /// Like [`Self::attachment_to_display_helper`], for `part` of the e-mail
+    /// `structure`.
+    ///
+    /// Parts that are not displayed inline, and the multipart parts that
+    /// contain them, are only outlined and are not copied out of the e-mail
+    /// until they are opened, see [`Self::find_attachment`].
+    fn part_to_display_helper(
+        structure: &MimeStructure<&[u8]>,
+        part: &MimePart,
+        main_loop_handler: &MainLoopHandler,
+        active_jobs: &mut HashSet<JobId>,
+        acc: &mut Vec<AttachmentDisplay>,
+        view_settings: &ViewSettings,
+    ) {
+        let outline = || AttachmentDisplay::Attachment {
+            inner: Box::new(part.outline()),
+            part: Some(part.clone()),
+        };
+        if part.content_disposition.kind.is_attachment() {
+            acc.push(outline());
+            return;
+        }
+        match part.content_type {
+            ContentType::Text { .. } | ContentType::MessageRfc822 => {}
+            ContentType::Multipart {
+                kind: MultipartType::Alternative | MultipartType::Signed | MultipartType::Encrypted,
+                ..
+            } => {}
+            ContentType::Multipart { .. } => {
+                let mut display = vec![];
+                for child in &part.children {
+                    Self::part_to_display_helper(
+                        structure,
+                        child,
+                        main_loop_handler,
+                        active_jobs,
+                        &mut display,
+                        view_settings,
+                    );
+                }
+                acc.push(AttachmentDisplay::Mixed {
+                    inner: Box::new(part.outline()),
+                    display,
+                    part: Some(part.clone()),
+                });
+                return;
+            }
+            _ => {
+                acc.push(outline());
+                return;
+            }
+        }
+        Self::attachment_to_display_helper(
+            &structure.attachment(part),
+            main_loop_handler,
+            active_jobs,
+            acc,
+            view_settings,
+        );
+    }
+
     fn attachment_to_display_helper(
         a: &Attachment,
         main_loop_handler: &MainLoopHandler,
@@ -142,6 +230,7 @@ impl EnvelopeView {
         if a.content_disposition.kind.is_attachment() {
             acc.push(AttachmentDisplay::Attachment {
                 inner: Box::new(a.clone()),
+                part: None,
             });
         } else if a.content_type().is_text_html() {
             let bytes = a.decode(view_settings.charset.into());
@@ -153,7 +242,7 @@ impl EnvelopeView {
                 .args(["-c", filter_invocation])
                 .stdin(Stdio::piped())
                 .stdout(Stdio::piped())
-                .spawn()
+                .spawn_checked(&view_settings.command_policy)
                 .and_then(|mut cmd| {
                     cmd.stdin.as_mut().unwrap().write_all(&bytes)?;
                     Ok(String::from_utf8_lossy(&cmd.wait_with_output()?.stdout).to_string())
@@ -227,6 +316,7 @@ impl EnvelopeView {
             } else {
                 acc.push(AttachmentDisplay::Attachment {
                     inner: Box::new(a.clone()),
+                    part: None,
                 });
             }
         } else if let ContentType::Multipart {
@@ -401,12 +491,14 @@ impl EnvelopeView {
                     acc.push(AttachmentDisplay::Mixed {
                         inner: Box::new(a.clone()),
                         display,
+                        part: None,
                     });
                 }
             }
         } else {
             acc.push(AttachmentDisplay::Attachment {
                 inner: Box::new(a.clone()),
+                part: None,
             });
         }
     }
@@ -450,7 +542,11 @@ impl EnvelopeView {
                 s.push(' ');
             }
 
-            s.push_str(&att_display.attachment().to_string());
+            if let Some(part) = att_display.part() {
+                s.push_str(&part.to_string());
+            } else {
+                s.push_str(&att_display.attachment().to_string());
+            }
             paths.push(cur_path.clone());
             if let Some(sub_att_display_vec) = att_display.as_multipart() {
                 let mut iter = (0..sub_att_display_vec.len()).peekable();
@@ -499,7 +595,22 @@ impl EnvelopeView {
         &'_ self,
         lidx: usize,
         context: &mut Context,
-    ) -> Option<&'_ melib::Attachment> {
+    ) -> Option<Cow<'_, melib::Attachment>> {
+        let ret = self.find_attachment(lidx);
+        if ret.is_none() {
+            context.replies.push_back(UIEvent::Notification {
+                title: None,
+                source: None,
+                body: format!("Attachment `{lidx}` not found.").into(),
+                kind: None,
+            });
+        }
+        ret
+    }
+
+    /// Find attachment `lidx`, copying it out of the e-mail if it has only
+    /// been outlined.
+    fn find_attachment(&'_ self, lidx: usize) -> Option<Cow<'_, melib::Attachment>> {
         if let Some(path) = self
             .attachment_paths
             .get(lidx)
@@ -510,9 +621,9 @@ impl EnvelopeView {
             fn find_attachment<'a>(
                 a: &'a AttachmentDisplay,
                 path: &[usize],
-            ) -> Option<&'a melib::Attachment> {
+            ) -> Option<&'a AttachmentDisplay> {
                 if path.is_empty() {
-                    return Some(a.attachment());
+                    return Some(a);
                 }
                 if let Some(parts) = a.as_multipart() {
                     let first = path[0];
@@ -523,28 +634,230 @@ impl EnvelopeView {
                 None
             }
 
-            let ret = find_attachment(root_attachment, &path[1..]);
-            if lidx == 0 {
-                return ret.filter(|a| {
-                    a.content_disposition.kind.is_attachment() || a.content_type == "message/rfc822"
-                });
-            } else {
-                return ret;
+            let display = find_attachment(root_attachment, &path[1..])?;
+            let ret = display.attachment();
+            if lidx == 0
+                && !(ret.content_disposition.kind.is_attachment()
+                    || ret.content_type == "message/rfc822")
+            {
+                return None;
             }
+            return Some(match display.part() {
+                Some(part) => Cow::Owned(part.attachment(&self.mail.bytes)),
+                None => Cow::Borrowed(ret),
+            });
+        }
+        None
+    }
+
+    /// Scan attachment `lidx` with the `attachment_scan_command` setting, if
+    /// any, and return whether it may be opened or saved. Attachments that
+    /// are flagged or could not be scanned are refused unless they have been
+    /// allowed with the `allow_flagged_attachment` shortcut.
+    fn scan_attachment(&mut self, lidx: usize, context: &mut Context) -> bool {
+        let Some(command) = self.view_settings.attachment_scan_command.clone() else {
+            return true;
+        };
+        let Some((bytes, filename)) = self
+            .find_attachment(lidx)
+            .map(|a| (a.decode(self.view_settings.charset.into()), a.filename()))
+        else {
+            return true;
+        };
+        let report = match scan::scan(
+            &command,
+            &bytes,
+            filename.as_deref(),
+            &self.view_settings.command_policy,
+            context,
+        ) {
+            Ok((_, scan::ScanResult::Clean)) => {
+                self.scan_warnings.remove(&lidx);
+                return true;
+            }
+            Ok((_, scan::ScanResult::Flagged(report))) => report,
+            Err(err) => format!("Could not be scanned: {err}"),
+        };
+        if self
+            .scan_overrides
+            .contains(&preview::digest(&command, &bytes))
+        {
+            return true;
         }
+        let allow_shortcut = &self.view_settings.env_view_shortcuts["allow_flagged_attachment"];
         context.replies.push_back(UIEvent::Notification {
-            title: None,
+            title: Some(format!("Attachment {lidx} was not opened").into()),
             source: None,
-            body: format!("Attachment `{lidx}` not found.").into(),
-            kind: None,
+            body: format!("{report}\nPress `{lidx}{allow_shortcut}` to allow opening it anyway.")
+                .into(),
+            kind: Some(NotificationType::Error(ErrorKind::External)),
         });
-        None
+        self.scan_warnings.insert(lidx, report);
+        self.force_draw_headers = true;
+        self.set_dirty(true);
+        false
+    }
+
+    /// Pipe attachment `lidx`, or the first attachment with an action if
+    /// `None`, to its command in the `attachment_actions` setting.
+    fn attachment_action(&mut self, lidx: Option<usize>, context: &mut Context) {
+        let actions = &context.settings.pager.attachment_actions;
+        let action = |a: &melib::Attachment| {
+            if matches!(a.content_type(), ContentType::Multipart { .. }) {
+                return None;
+            }
+            preview::previewer(actions, &a.mime_type()).map(str::to_string)
+        };
+        let found = match lidx {
+            Some(lidx) => self
+                .find_attachment(lidx)
+                .map(|a| (lidx, action(&a), a.mime_type())),
+            None => (0..self.attachment_paths.len()).find_map(|lidx| {
+                let a = self.find_attachment(lidx)?;
+                Some((lidx, Some(action(&a)?), a.mime_type()))
+            }),
+        };
+        let (lidx, command) = match found {
+            Some((lidx, Some(command), _)) => (lidx, command),
+            Some((_, None, mime_type)) => {
+                context.replies.push_back(UIEvent::Notification {
+                    title: None,
+                    source: None,
+                    body: format!("No attachment action configured for {mime_type}.").into(),
+                    kind: None,
+                });
+                return;
+            }
+            None => {
+                context.replies.push_back(UIEvent::Notification {
+                    title: None,
+                    source: None,
+                    body: match lidx {
+                        Some(lidx) => format!("Attachment `{lidx}` not found."),
+                        None => "No attachment has an attachment action configured.".to_string(),
+                    }
+                    .into(),
+                    kind: None,
+                });
+                return;
+            }
+        };
+        if !self.scan_attachment(lidx, context) {
+            return;
+        }
+        let Some((bytes, filename)) = self
+            .find_attachment(lidx)
+            .map(|a| (a.decode(self.view_settings.charset.into()), a.filename()))
+        else {
+            return;
+        };
+        let result = preview::run(
+            &command,
+            &bytes,
+            filename.as_deref(),
+            &self.view_settings.command_policy,
+        )
+        .and_then(|output| {
+            if output.status.success() {
+                Ok(output)
+            } else {
+                Err(
+                    Error::new(format!("`{command}` failed with {}", output.status))
+                        .set_details(String::from_utf8_lossy(&output.stderr).trim().to_string())
+                        .set_kind(ErrorKind::External),
+                )
+            }
+        });
+        match result {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                context
+                    .replies
+                    .push_back(UIEvent::StatusEvent(StatusEvent::DisplayMessage(
+                        if stdout.trim().is_empty() {
+                            format!("Attachment {lidx} was piped to `{command}`.")
+                        } else {
+                            format!(
+                                "Attachment {lidx} was piped to `{command}`: {}",
+                                stdout.trim()
+                            )
+                        },
+                    )));
+            }
+            Err(err) => {
+                context.replies.push_back(UIEvent::Notification {
+                    title: Some(format!("Attachment action of attachment {lidx} failed").into()),
+                    source: None,
+                    body: err.to_string().into(),
+                    kind: Some(NotificationType::Error(err.kind)),
+                });
+            }
+        }
     }
 
     pub fn body_text(&self) -> &str {
         &self.body_text
     }
 
+    /// The body text without the text of attachments displayed inline.
+    pub fn main_body_text(&self) -> &str {
+        &self.body_text[..self.main_body_text_len.unwrap_or(self.body_text.len())]
+    }
+
+    /// The text to quote in replies. If the e-mail has no plain text but
+    /// HTML, its main text is rendered with [`html_to_text`], which leaves out
+    /// images and link targets, instead of being taken from the HTML filter
+    /// or from the HTML source.
+    ///
+    /// [`html_to_text`]: crate::mail::export::html_to_text
+    pub fn reply_text(&self, quote_attachments: bool) -> String {
+        let body = self.mail.body();
+        let Some(html) = Self::html_only_part(&body) else {
+            return if quote_attachments {
+                self.body_text()
+            } else {
+                self.main_body_text()
+            }
+            .to_string();
+        };
+        let mut text = crate::mail::export::html_to_text(&String::from_utf8_lossy(
+            &html.decode(self.view_settings.charset.into()),
+        ));
+        if quote_attachments {
+            text.push_str(&self.body_text[self.main_body_text().len()..]);
+        }
+        text
+    }
+
+    /// The HTML part that holds the main text of `a`, if it has no plain text
+    /// alternative.
+    fn html_only_part(a: &Attachment) -> Option<&Attachment> {
+        match a.content_type {
+            ContentType::Text {
+                kind: Text::Html, ..
+            } => Some(a),
+            ContentType::Multipart {
+                kind: MultipartType::Alternative | MultipartType::Related,
+                ref parts,
+                ..
+            } => {
+                if parts
+                    .iter()
+                    .any(|p| p.is_text() && !p.is_html() && !p.body().trim().is_empty())
+                {
+                    return None;
+                }
+                parts.iter().find_map(Self::html_only_part)
+            }
+            ContentType::Multipart {
+                kind: MultipartType::Mixed | MultipartType::Signed,
+                ref parts,
+                ..
+            } => parts.first().and_then(Self::html_only_part),
+            _ => None,
+        }
+    }
+
     fn save_attachment(&self, a_i: usize, path: &str, context: &mut Context) {
         let mut path = std::path::Path::new(path).to_path_buf().expand();
 
@@ -617,6 +930,29 @@ impl EnvelopeView {
             });
         }
     }
+
+    /// Replace the subject of the envelope with the protected subject found in
+    /// its decrypted body, both here and in the account's collection so that
+    /// listings show it too.
+    fn set_protected_subject(&mut self, subject: String, context: &mut Context) {
+        let env_hash = self.mail.hash();
+        if self.mail.subject() == subject {
+            return;
+        }
+        self.mail.envelope.set_subject(subject.clone().into_bytes());
+        if let Some(account) = context
+            .accounts
+            .values()
+            .find(|a| a.collection.contains_key(&env_hash))
+        {
+            account
+                .collection
+                .get_env_mut(env_hash)
+                .set_subject(subject.into_bytes());
+            context.replies.push_back(UIEvent::EnvelopeUpdate(env_hash));
+        }
+        self.force_draw_headers = true;
+    }
 }
 
 impl Component for EnvelopeView {
@@ -628,7 +964,10 @@ impl Component for EnvelopeView {
         let hdr_area_theme = crate::conf::value(context, "mail.view.headers_area");
 
         let y: usize = {
-            if self.options.contains(ViewOptions::SOURCE) {
+            if self
+                .options
+                .intersects(ViewOptions::SOURCE | ViewOptions::DELIVERY_PATH)
+            {
                 grid.clear_area(area, self.view_settings.theme_default);
                 context.dirty_areas.push_back(area);
                 0
@@ -665,9 +1004,10 @@ impl Component for EnvelopeView {
                                             None,
                                             Some(0)
                                         );
+                                    let value = $string;
                                     let (__x, mut __y) =
                                         grid.write_string(
-                                            &$string,
+                                            &value,
                                             hdr_theme.fg,
                                             hdr_theme.bg,
                                             hdr_theme.attrs,
@@ -675,6 +1015,26 @@ impl Component for EnvelopeView {
                                             Some(_x + 1),
                                             Some(2)
                                         );
+                                    {
+                                        // Only the first line of wrapped values is formatted.
+                                        let (x, row) = area.skip_rows(y + _y).upper_left();
+                                        let x = x + _x + 1;
+                                        let max_x = area.bottom_right().0 + 1;
+                                        for text_formatter in
+                                            crate::conf::text_format_regexps(context, "mail.view.headers")
+                                        {
+                                            let t = grid.insert_tag(text_formatter.tag);
+                                            for (start, end) in text_formatter.regexp.find_iter(&value) {
+                                                if x + start < max_x {
+                                                    grid.set_tag(
+                                                        t,
+                                                        (x + start, row),
+                                                        ((x + end).min(max_x), row),
+                                                    );
+                                                }
+                                            }
+                                        }
+                                    }
                                     grid.clear_area(
                                         area.skip_rows(y + _y + __y).skip_cols(_x + 1 + __x).take_rows(1),
                                         hdr_area_theme,
@@ -870,6 +1230,63 @@ impl Component for EnvelopeView {
                     }
                 }
 
+                if let Some(notification) = melib::email::forge::ForgeNotification::detect(envelope)
+                {
+                    let forge = notification.forge;
+                    print_header!((
+                        forge,
+                        format!("{notification} Available actions: [ forge-open, forge-mute ]")
+                    ));
+                }
+
+                if let Some(ref report) = self.delivery_report {
+                    let error_theme = crate::conf::value(context, "error_message");
+                    for recipient in &report.recipients {
+                        if sticky || skip_header_ctr == 0 {
+                            let theme = if recipient.is_failure() {
+                                error_theme
+                            } else {
+                                hdr_theme
+                            };
+                            grid.clear_area(area.nth_row(y), hdr_area_theme);
+                            let (_, _y) = grid.write_string(
+                                &format!("Delivery report: {recipient}"),
+                                theme.fg,
+                                theme.bg,
+                                theme.attrs,
+                                area.nth_row(y),
+                                None,
+                                Some(0),
+                            );
+                            y += _y + 1;
+                        } else {
+                            skip_header_ctr = skip_header_ctr.saturating_sub(1);
+                        }
+                        self.headers_no += 1;
+                    }
+                }
+
+                if !self.scan_warnings.is_empty() {
+                    let warning_theme = crate::conf::value(context, "error_message");
+                    for (idx, report) in &self.scan_warnings {
+                        if sticky || skip_header_ctr == 0 {
+                            grid.clear_area(area.nth_row(y), hdr_area_theme);
+                            let (_, _y) = grid.write_string(
+                                &format!("Warning: attachment {idx} was not opened: {report}"),
+                                warning_theme.fg,
+                                warning_theme.bg,
+                                warning_theme.attrs,
+                                area.nth_row(y),
+                                None,
+                                Some(0),
+                            );
+                            y += _y + 1;
+                        } else {
+                            skip_header_ctr = skip_header_ctr.saturating_sub(1);
+                        }
+                        self.headers_no += 1;
+                    }
+                }
                 self.force_draw_headers = false;
                 grid.clear_area(area.skip_rows(y), self.view_settings.theme_default);
                 context.dirty_areas.push_back(area.take_rows(y + 3));
@@ -947,9 +1364,12 @@ impl Component for EnvelopeView {
         }
         if !self.initialised {
             self.initialised = true;
-            let mut text = if !self.filters.is_empty() {
+            let mut text = if self.options.contains(ViewOptions::DELIVERY_PATH) {
+                delivery_path(&self.mail.bytes, Some(self.mail.date()).filter(|d| *d != 0))
+            } else if !self.filters.is_empty() {
                 let mut text = String::new();
                 self.body_text.clear();
+                self.main_body_text_len = None;
                 if let Some(last) = self.filters.last() {
                     let mut scan_stack = VecDeque::from([last]);
                     let mut render_stack = VecDeque::new();
@@ -1020,9 +1440,11 @@ impl Component for EnvelopeView {
                         match body_text {
                             ViewFilterContent::Filtered { inner } => {
                                 let payload =
-                                    self.options.convert(&mut self.links, &self.body, inner);
+                                    self.options
+                                        .convert(&mut self.links, &self.mail.bytes, inner);
                                 text.push_str(&payload);
                                 self.body_text.push_str(&payload);
+                                self.main_body_text_len.get_or_insert(self.body_text.len());
                             }
                             ViewFilterContent::Error { inner } => text.push_str(&inner.to_string()),
                             ViewFilterContent::Running { .. } => {
@@ -1035,12 +1457,14 @@ impl Component for EnvelopeView {
                 text
             } else {
                 self.options
-                    .convert(&mut self.links, &self.body, &self.body_text)
+                    .convert(&mut self.links, &self.mail.bytes, &self.body_text)
             };
-            if !text.trim().is_empty() {
-                text.push_str("\n\n");
+            if !self.options.contains(ViewOptions::DELIVERY_PATH) {
+                if !text.trim().is_empty() {
+                    text.push_str("\n\n");
+                }
+                text.push_str(&self.attachment_tree);
             }
-            text.push_str(&self.attachment_tree);
             while text.ends_with('\n') {
                 text.pop();
             }
@@ -1053,6 +1477,11 @@ impl Component for EnvelopeView {
                 None,
                 self.view_settings.body_theme,
             );
+            self.pager.set_highlight_body(
+                !self
+                    .options
+                    .intersects(ViewOptions::SOURCE | ViewOptions::DELIVERY_PATH),
+            );
             if let Some(ref filter) = self.view_settings.pager_filter {
                 self.pager.filter(filter, context);
             }
@@ -1098,6 +1527,7 @@ impl Component for EnvelopeView {
                     if self.active_jobs.contains(job_id) =>
                 {
                     let mut caught = false;
+                    let mut protected_subject = None;
                     for d in self.display.iter_mut() {
                         let succeeded: bool;
                         match d {
@@ -1177,6 +1607,13 @@ impl Component for EnvelopeView {
                                         let plaintext = Box::new(
                                             AttachmentBuilder::new(&decrypted_bytes).build(),
                                         );
+                                        protected_subject =
+                                            melib::email::pgp::protected_headers(&plaintext)
+                                                .and_then(|headers| {
+                                                    headers
+                                                        .get(HeaderName::SUBJECT)
+                                                        .map(str::to_string)
+                                                });
                                         let mut plaintext_display = vec![];
                                         Self::attachment_to_display_helper(
                                             &plaintext,
@@ -1218,6 +1655,9 @@ impl Component for EnvelopeView {
                             .job_executor
                             .set_job_success(*job_id, succeeded);
                     }
+                    if let Some(subject) = protected_subject {
+                        self.set_protected_subject(subject, context);
+                    }
                     if caught {
                         self.links.clear();
                         self.initialised = false;
@@ -1354,6 +1794,17 @@ impl Component for EnvelopeView {
                 } else {
                     self.options.toggle(ViewOptions::SOURCE);
                 }
+                self.options.remove(ViewOptions::DELIVERY_PATH);
+                self.set_dirty(true);
+                self.initialised = false;
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::ENVELOPE_VIEW]["view_delivery_path"]) =>
+            {
+                self.options.toggle(ViewOptions::DELIVERY_PATH);
+                self.options
+                    .remove(ViewOptions::SOURCE | ViewOptions::SOURCE_RAW);
                 self.set_dirty(true);
                 self.initialised = false;
                 return true;
@@ -1364,7 +1815,8 @@ impl Component for EnvelopeView {
                         key == shortcuts[Shortcuts::ENVELOPE_VIEW]["return_to_normal_view"]
                     ) =>
             {
-                self.options.remove(ViewOptions::SOURCE | ViewOptions::URL);
+                self.options
+                    .remove(ViewOptions::SOURCE | ViewOptions::URL | ViewOptions::DELIVERY_PATH);
                 self.set_dirty(true);
                 self.initialised = false;
                 return true;
@@ -1392,8 +1844,11 @@ impl Component for EnvelopeView {
                 let Some(lidx) = context.cmd_buf_clear() else {
                     return true;
                 };
+                if !self.scan_attachment(lidx, context) {
+                    return true;
+                }
                 if let Some(attachment) = self.open_attachment(lidx, context) {
-                    if crate::mailcap::MailcapEntry::execute(attachment, context).is_ok() {
+                    if crate::mailcap::MailcapEntry::execute(&attachment, context).is_ok() {
                         self.set_dirty(true);
                     } else {
                         context.replies.push_back(UIEvent::Notification {
@@ -1410,6 +1865,88 @@ impl Component for EnvelopeView {
                 }
                 return true;
             }
+            UIEvent::Input(ref key)
+                if context.cmd_buf().is_some()
+                    && shortcut!(
+                        key == shortcuts[Shortcuts::ENVELOPE_VIEW]["preview_attachment"]
+                    ) =>
+            {
+                let Some(lidx) = context.cmd_buf_clear() else {
+                    return true;
+                };
+                if !self.scan_attachment(lidx, context) {
+                    return true;
+                }
+                if let Some(attachment) = self.open_attachment(lidx, context) {
+                    let mime_type = attachment.mime_type();
+                    match preview::previewer(
+                        &context.settings.pager.attachment_previewers,
+                        &mime_type,
+                    )
+                    .map(|command| {
+                        ViewFilter::new_preview(&attachment, command, &self.view_settings, context)
+                    }) {
+                        Some(Ok(filter)) => {
+                            self.filters.push(filter);
+                            self.initialised = false;
+                            self.set_dirty(true);
+                        }
+                        Some(Err(err)) => {
+                            context.replies.push_back(UIEvent::Notification {
+                                title: Some("Could not preview attachment".into()),
+                                source: None,
+                                body: err.to_string().into(),
+                                kind: Some(NotificationType::Error(err.kind)),
+                            });
+                        }
+                        None => {
+                            context.replies.push_back(UIEvent::Notification {
+                                title: None,
+                                source: None,
+                                body: format!("No previewer configured for {mime_type}.").into(),
+                                kind: None,
+                            });
+                        }
+                    }
+                }
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::ENVELOPE_VIEW]["attachment_action"]) =>
+            {
+                let lidx = context.cmd_buf_clear();
+                self.attachment_action(lidx, context);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if context.cmd_buf().is_some()
+                    && shortcut!(
+                        key == shortcuts[Shortcuts::ENVELOPE_VIEW]["allow_flagged_attachment"]
+                    ) =>
+            {
+                let Some(lidx) = context.cmd_buf_clear() else {
+                    return true;
+                };
+                let Some(command) = self.view_settings.attachment_scan_command.as_ref() else {
+                    return true;
+                };
+                if let Some(attachment) = self.open_attachment(lidx, context) {
+                    let digest = preview::digest(
+                        command,
+                        &attachment.decode(self.view_settings.charset.into()),
+                    );
+                    self.scan_overrides.insert(digest);
+                    self.scan_warnings.remove(&lidx);
+                    self.force_draw_headers = true;
+                    self.set_dirty(true);
+                    context
+                        .replies
+                        .push_back(UIEvent::StatusEvent(StatusEvent::DisplayMessage(format!(
+                            "Attachment {lidx} will be opened despite the scan result."
+                        ))));
+                }
+                return true;
+            }
             UIEvent::Action(View(ViewAction::ExportMail(ref path))) => {
                 // Save entire message as eml
                 let mut path = std::path::Path::new(path).to_path_buf().expand();
@@ -1445,14 +1982,113 @@ impl Component for EnvelopeView {
 
                 return true;
             }
+            UIEvent::Action(View(ViewAction::PrintMail(ref command))) => {
+                let Some(command) = command
+                    .as_ref()
+                    .or(self.view_settings.print_command.as_ref())
+                    .cloned()
+                else {
+                    context.replies.push_back(UIEvent::Notification {
+                        title: None,
+                        source: None,
+                        body: "You did not specify a print command, and you haven't defined any \
+                               command in [pager.print_command] as a fallback. Try \
+                               re-executing the command with a print command appended."
+                            .into(),
+                        kind: Some(NotificationType::Error(melib::ErrorKind::None)),
+                    });
+                    return true;
+                };
+                let mut headers = vec![
+                    (
+                        "Date",
+                        self.view_settings
+                            .format_date_value(self.mail.date(), self.mail.date_as_str())
+                            .to_string(),
+                    ),
+                    ("From", self.mail.field_from_to_string()),
+                    ("To", self.mail.field_to_to_string()),
+                ];
+                let cc = self.mail.field_cc_to_string();
+                if !cc.is_empty() {
+                    headers.push(("Cc", cc));
+                }
+                headers.push(("Subject", self.mail.subject().to_string()));
+                let output = print_layout(
+                    &headers,
+                    &self.body_text,
+                    &self.attachment_tree,
+                    self.view_settings.print_width,
+                    self.view_settings.print_page_lines,
+                );
+                // Kill input thread so that spawned command can be sole receiver of stdin
+                {
+                    context.input_kill();
+                }
+                log::trace!("Executing: sh -c \"{}\"", command.replace('"', "\\\""));
+                match Command::new("sh")
+                    .args(["-c", &command])
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::inherit())
+                    .stderr(Stdio::inherit())
+                    .spawn_checked(&self.view_settings.command_policy)
+                    .map_err(Error::from)
+                    .and_then(|mut child| {
+                        let Some(mut stdin) = child.stdin.take() else {
+                            let _ = child.wait();
+                            return Err(Error::new(format!(
+                                "Could not open standard input of {command}"
+                            ))
+                            .set_kind(ErrorKind::External));
+                        };
+                        stdin.write_all(output.as_bytes()).chain_err_summary(|| {
+                            format!("Could not write to standard input of {command}")
+                        })?;
+                        drop(stdin);
+                        Ok(child.wait()?)
+                    }) {
+                    Ok(status) if status.success() => {
+                        context.replies.push_back(UIEvent::Notification {
+                            title: None,
+                            source: None,
+                            body: format!("Printed with `{command}`.").into(),
+                            kind: Some(NotificationType::Info),
+                        });
+                    }
+                    Ok(status) => {
+                        context.replies.push_back(UIEvent::Notification {
+                            title: Some(format!("Failed to execute {command}").into()),
+                            source: None,
+                            body: format!("{command} exited with {status}").into(),
+                            kind: Some(NotificationType::Error(melib::error::ErrorKind::External)),
+                        });
+                    }
+                    Err(err) => {
+                        context.replies.push_back(UIEvent::Notification {
+                            title: Some(format!("Failed to execute {command}: {err}").into()),
+                            source: None,
+                            body: err.to_string().into(),
+                            kind: Some(NotificationType::Error(melib::error::ErrorKind::External)),
+                        });
+                    }
+                }
+                context.replies.push_back(UIEvent::RestoreStandardIO);
+                self.set_dirty(true);
+                return true;
+            }
             UIEvent::Action(View(ViewAction::SaveAttachment(a_i, FileAction::Path(ref path)))) => {
-                self.save_attachment(a_i, path, context);
+                if self.scan_attachment(a_i, context) {
+                    self.save_attachment(a_i, path, context);
+                }
                 return true;
             }
             UIEvent::Action(View(ViewAction::SaveAttachment(
                 a_i,
                 FileAction::FilePicker(ref command),
             ))) => {
+                if !self.scan_attachment(a_i, context) {
+                    return true;
+                }
                 let command = if let Some(cmd) =
                     command
                         .as_ref()
@@ -1484,7 +2120,7 @@ impl Component for EnvelopeView {
                     .stdin(Stdio::inherit())
                     .stdout(Stdio::piped())
                     .stderr(Stdio::piped())
-                    .spawn()
+                    .spawn_checked(&self.view_settings.command_policy)
                     .and_then(|child| Ok(child.wait_with_output()?.stdout))
                 {
                     Ok(stdout) => {
@@ -1536,8 +2172,9 @@ impl Component for EnvelopeView {
                 return true;
             }
             UIEvent::Action(View(ViewAction::PipeAttachment(a_i, ref bin, ref args))) => {
-                use std::borrow::Cow;
-
+                if !self.scan_attachment(a_i, context) {
+                    return true;
+                }
                 let bytes = if let Some(u) = self.open_attachment(a_i, context) {
                     Cow::Owned(u.decode(self.view_settings.charset.into()))
                 } else if a_i == 0 {
@@ -1562,7 +2199,7 @@ impl Component for EnvelopeView {
                     .stdin(Stdio::piped())
                     .stdout(Stdio::inherit())
                     .stderr(Stdio::inherit())
-                    .spawn()
+                    .spawn_checked(&self.view_settings.command_policy)
                     .map_err(Error::from)
                     .and_then(|mut child| {
                         let Some(mut stdin) = child.stdin.take() else {
@@ -1604,6 +2241,9 @@ impl Component for EnvelopeView {
                 let Some(lidx) = context.cmd_buf_clear() else {
                     return true;
                 };
+                if !self.scan_attachment(lidx, context) {
+                    return true;
+                }
                 if let Some(attachment) = self.open_attachment(lidx, context) {
                     match attachment.content_type() {
                         ContentType::MessageRfc822 => {
@@ -1632,9 +2272,11 @@ impl Component for EnvelopeView {
                         | ContentType::Text { .. }
                         | ContentType::PGPSignature
                         | ContentType::CMSSignature => {
-                            if let Ok(filter) =
-                                ViewFilter::new_attachment(attachment, &self.view_settings, context)
-                            {
+                            if let Ok(filter) = ViewFilter::new_attachment(
+                                &attachment,
+                                &self.view_settings,
+                                context,
+                            ) {
                                 self.filters.push(filter);
                             }
                             self.initialised = false;
@@ -1661,7 +2303,7 @@ impl Component for EnvelopeView {
                                         .args(["-c", &exec_cmd])
                                         .stdin(Stdio::piped())
                                         .stdout(Stdio::piped())
-                                        .spawn()?;
+                                        .spawn_checked(&self.view_settings.command_policy)?;
                                     Ok((p, exec_cmd, child))
                                 });
                                 match res {
@@ -1686,9 +2328,11 @@ impl Component for EnvelopeView {
                                         });
                                     }
                                 }
-                            } else if let Ok(filter) =
-                                ViewFilter::new_attachment(attachment, &self.view_settings, context)
-                            {
+                            } else if let Ok(filter) = ViewFilter::new_attachment(
+                                &attachment,
+                                &self.view_settings,
+                                context,
+                            ) {
                                 self.filters.push(filter);
                                 self.initialised = false;
                                 self.set_dirty(true);
@@ -1752,7 +2396,7 @@ impl Component for EnvelopeView {
                     return true;
                 };
                 let links = &self.links;
-                let (_kind, url) = {
+                let (kind, url) = {
                     if let Some(l) = links.get(lidx).map(|l| (l.kind, l.value.as_ref())) {
                         l
                     } else {
@@ -1766,6 +2410,33 @@ impl Component for EnvelopeView {
                     }
                 };
 
+                if matches!(kind, LinkKind::Email) || url.starts_with("mailto:") {
+                    // Open mailto: links in the composer with their RFC 6068 fields
+                    // (subject, body, cc etc.) prefilled instead of handing them to the
+                    // URL launcher.
+                    let target = if matches!(kind, LinkKind::Email) {
+                        format!("mailto:{url}")
+                    } else {
+                        url.to_string()
+                    };
+                    match Mailto::try_from(target.as_str()) {
+                        Ok(mailto) => {
+                            context.replies.push_back(UIEvent::Action(Action::Compose(
+                                ComposeAction::Mailto(mailto),
+                            )));
+                        }
+                        Err(err) => {
+                            context.replies.push_back(UIEvent::Notification {
+                                title: Some(format!("Could not parse {target}").into()),
+                                source: None,
+                                body: err.into(),
+                                kind: Some(NotificationType::Error(melib::ErrorKind::ValueError)),
+                            });
+                        }
+                    }
+                    return true;
+                }
+
                 let url_launcher = self.view_settings.url_launcher.as_deref().unwrap_or(
                     if cfg!(target_os = "macos") {
                         "open"
@@ -1777,7 +2448,7 @@ impl Component for EnvelopeView {
                     .arg(url)
                     .stdin(Stdio::piped())
                     .stdout(Stdio::piped())
-                    .spawn()
+                    .spawn_checked(&self.view_settings.command_policy)
                 {
                     Ok(child) => {
                         context
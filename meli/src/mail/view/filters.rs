@@ -44,10 +44,15 @@ use crate::{
     components::*,
     desktop_exec_to_command,
     jobs::{IsAsync, JobId, JoinHandle},
-    mail::view::ViewSettings,
+    mail::view::{
+        links::reveal_link_targets,
+        preview,
+        remote_content::{block_remote_content, RemoteContentReport},
+        ViewSettings,
+    },
     terminal::{Area, CellBuffer},
     try_recv_timeout,
-    types::{ForkedProcess, NotificationType},
+    types::{CommandPolicy, CommandPolicyExt, ForkedProcess, NotificationType},
     Context, ErrorKind, File, StatusEvent, UIEvent,
 };
 
@@ -143,12 +148,12 @@ impl ViewFilter {
         view_settings: &ViewSettings,
         context: &Context,
     ) -> Result<Self> {
-        fn run(cmd: &str, args: &[&str], bytes: &[u8]) -> Result<String> {
+        fn run(cmd: &str, args: &[&str], bytes: &[u8], policy: &CommandPolicy) -> Result<String> {
             let mut html_filter = Command::new(cmd)
                 .args(args)
                 .stdin(Stdio::piped())
                 .stdout(Stdio::piped())
-                .spawn()?;
+                .spawn_checked(policy)?;
             html_filter
                 .stdin
                 .as_mut()
@@ -242,7 +247,12 @@ impl ViewFilter {
             Cow<'static, str>,
             &'static str,
             SmallVec<[Cow<'static, str>; 8]>,
-        ) = if let Some(filter_invocation) = settings.pager.html_filter.as_ref() {
+        ) = if let Some(filter_invocation) = view_settings
+            .html_remote_content_filter
+            .as_ref()
+            .filter(|_| view_settings.allow_remote_content)
+            .or(settings.pager.html_filter.as_ref())
+        {
             (
                 filter_invocation.to_string().into(),
                 "sh",
@@ -256,9 +266,19 @@ impl ViewFilter {
             )
         };
         let bytes: Vec<u8> = att.decode(view_settings.charset.into());
+        let html = reveal_link_targets(
+            &String::from_utf8_lossy(&bytes),
+            view_settings.html_reveal_link_targets,
+        );
+        let (bytes2, remote_content) = if view_settings.allow_remote_content {
+            (html.into_bytes(), RemoteContentReport::default())
+        } else {
+            let (html, report) = block_remote_content(&html);
+            (html.into_bytes(), report)
+        };
 
         let filter_invocation2 = filter_invocation.to_string();
-        let bytes2 = bytes.clone();
+        let policy = view_settings.command_policy.clone();
         let job = async move {
             let filter_invocation = filter_invocation2;
             let bytes = bytes2;
@@ -266,7 +286,7 @@ impl ViewFilter {
                 .iter()
                 .map(|a| a.as_ref())
                 .collect::<SmallVec<[&str; 8]>>();
-            match run(cmd, &borrowed_args, &bytes) {
+            match run(cmd, &borrowed_args, &bytes, &policy) {
                 Err(err) => Err((
                     Error::new(format!(
                         "Failed to start html filter process `{filter_invocation}`",
@@ -284,10 +304,15 @@ impl ViewFilter {
         };
         let filter_invocation2 = filter_invocation.to_string();
         let open_html_shortcut = settings.shortcuts.envelope_view.open_html.clone();
+        let remote_content_notice = if remote_content.is_empty() {
+            String::new()
+        } else {
+            format!("{remote_content} ")
+        };
         let on_success_notice_cb = Arc::new(move || {
             format!(
-                "Text piped through `{filter_invocation2}` Press `{open_html_shortcut}` to open \
-                 in web browser."
+                "Text piped through `{filter_invocation2}` {remote_content_notice}Press \
+                 `{open_html_shortcut}` to open in web browser."
             )
             .into()
         });
@@ -336,6 +361,85 @@ impl ViewFilter {
         })
     }
 
+    /// Preview an attachment as text converted by the external `command`,
+    /// reusing cached output of previous conversions.
+    pub fn new_preview(
+        att: &Attachment,
+        command: &str,
+        view_settings: &ViewSettings,
+        context: &Context,
+    ) -> Result<Self> {
+        let bytes = att.decode(Default::default());
+        let filter_invocation = command.to_string();
+        let return_shortcut = context
+            .settings
+            .shortcuts
+            .envelope_view
+            .return_to_normal_view
+            .clone();
+        let retval = Self {
+            filter_invocation: filter_invocation.clone(),
+            content_type: att.content_type.clone(),
+            size: att.size(),
+            notice: None,
+            headers: vec![],
+            unfiltered: vec![],
+            body_text: ViewFilterContent::Filtered {
+                inner: String::new(),
+            },
+            event_handler: None,
+            id: ComponentId::default(),
+        };
+        if let Some(text) = preview::cached(&preview::digest(command, &bytes)) {
+            return Ok(Self {
+                notice: Some(
+                    format!(
+                        "Cached preview made with `{filter_invocation}`. Press \
+                         `{return_shortcut}` to return."
+                    )
+                    .into(),
+                ),
+                unfiltered: text.as_bytes().to_vec(),
+                body_text: ViewFilterContent::Filtered { inner: text },
+                ..retval
+            });
+        }
+        let filename = att.filename();
+        let command = command.to_string();
+        let policy = view_settings.command_policy.clone();
+        let job = async move {
+            match preview::convert(&command, &bytes, filename.as_deref(), &policy) {
+                Err(err) => Err((err, bytes)),
+                Ok(text) => {
+                    let mut att = AttachmentBuilder::default();
+                    att.set_raw(text.into_bytes()).set_body_to_raw();
+                    Ok((att.build(), bytes))
+                }
+            }
+        };
+        let filter_invocation2 = filter_invocation.clone();
+        let on_success_notice_cb = Arc::new(move || {
+            format!(
+                "Preview made with `{filter_invocation2}`. Press `{return_shortcut}` to return."
+            )
+            .into()
+        });
+        let job_handle = context.main_loop_handler.job_executor.spawn(
+            filter_invocation.into(),
+            job,
+            IsAsync::Blocking,
+        );
+        Ok(Self {
+            body_text: ViewFilterContent::Running {
+                job_id: job_handle.job_id,
+                on_success_notice_cb,
+                job_handle,
+                view_settings: view_settings.clone(),
+            },
+            ..retval
+        })
+    }
+
     pub fn new_attachment(
         att: &Attachment,
         view_settings: &ViewSettings,
@@ -862,7 +966,7 @@ impl ViewFilter {
                                 .args(["-c", &exec_cmd])
                                 .stdin(Stdio::piped())
                                 .stdout(Stdio::piped())
-                                .spawn()?,
+                                .spawn_checked(&context.command_policy(None))?,
                         ))
                     });
                 match res {
@@ -0,0 +1,133 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Revealing of link targets in HTML e-mail.
+//!
+//! HTML filters only render the anchor text of links, which can hide where a
+//! link actually leads. Targets of links whose text looks like a different
+//! address or whose host is deceptive are written out after the link as
+//! `[⚠ target]`, so that the pager shows and highlights them.
+
+use super::remote_content::{attributes, tag_end, tag_name};
+use crate::types::{url_host, url_is_suspicious, SUSPICIOUS_LINK_MARKER};
+
+/// Write out the targets of suspicious links of `html` after their anchor
+/// text. If `reveal_all` is set, targets that differ from their anchor text
+/// are written out as well.
+pub fn reveal_link_targets(html: &str, reveal_all: bool) -> String {
+    let mut ret = String::with_capacity(html.len());
+    let mut rest = html;
+    // Target and text of the anchor being read.
+    let mut anchor: Option<(String, String)> = None;
+    while let Some(pos) = rest.find('<') {
+        if let Some((_, text)) = anchor.as_mut() {
+            text.push_str(&rest[..pos]);
+        }
+        ret.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        let end = tag_end(rest);
+        let tag = &rest[..end];
+        let name = tag_name(tag);
+        if name.eq_ignore_ascii_case("a") {
+            anchor = attributes(tag).into_iter().find_map(|attr| {
+                let (_, value) = attr.value?;
+                tag[attr.name]
+                    .eq_ignore_ascii_case("href")
+                    .then(|| (decode_entities(value.trim()), String::new()))
+            });
+            ret.push_str(tag);
+        } else if name.eq_ignore_ascii_case("/a") {
+            ret.push_str(tag);
+            if let Some((href, text)) = anchor.take() {
+                let text = decode_entities(text.trim());
+                if url_is_suspicious(&href) || text_is_misleading(&text, &href) {
+                    ret.push_str(&format!(" [{} {}]", SUSPICIOUS_LINK_MARKER, escape(&href)));
+                } else if reveal_all
+                    && !href.is_empty()
+                    && !href.starts_with('#')
+                    && text != href
+                    && href.strip_prefix("mailto:") != Some(text.as_str())
+                {
+                    ret.push_str(&format!(" [{}]", escape(&href)));
+                }
+            }
+        } else {
+            ret.push_str(tag);
+        }
+        rest = &rest[end..];
+    }
+    ret.push_str(rest);
+    ret
+}
+
+/// Whether the anchor `text` looks like a web address with a host other than
+/// the one of `href`.
+fn text_is_misleading(text: &str, href: &str) -> bool {
+    let Some(href_host) = url_host(href) else {
+        return false;
+    };
+    let Some(text_host) = text_host(text) else {
+        return false;
+    };
+    let href_host = href_host.to_ascii_lowercase();
+    let href_host = href_host.strip_prefix("www.").unwrap_or(&href_host);
+    let text_host = text_host.to_ascii_lowercase();
+    let text_host = text_host.strip_prefix("www.").unwrap_or(&text_host);
+    !(href_host == text_host
+        || href_host
+            .strip_suffix(text_host)
+            .is_some_and(|prefix| prefix.ends_with('.')))
+}
+
+/// The host of an anchor text that looks like a web address, such as
+/// `https://example.com/login` or `www.example.com`.
+fn text_host(text: &str) -> Option<&str> {
+    if text.contains("://") {
+        return url_host(text);
+    }
+    if text.is_empty() || text.contains(char::is_whitespace) {
+        return None;
+    }
+    let host = text.split(['/', '?', '#', ':']).next().unwrap_or_default();
+    let tld = host.rsplit_once('.')?.1;
+    (tld.len() >= 2
+        && tld.chars().all(|c| c.is_ascii_alphabetic())
+        && host
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '.' || c == '-'))
+    .then_some(host)
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
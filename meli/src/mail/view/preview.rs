@@ -0,0 +1,151 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Text previews of attachments made by external converters.
+//!
+//! Converters are looked up by MIME type in the `attachment_previewers`
+//! setting. Their output is cached under `$XDG_CACHE_HOME/meli/previews`,
+//! keyed by a digest of the attachment content and the converter command, so
+//! that previewing the same attachment again does not run the converter.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+use indexmap::IndexMap;
+use melib::{error::*, log, utils::fnmatch::Fnmatch};
+
+use crate::{CommandPolicy, CommandPolicyExt, File};
+
+/// The converter command for attachments of type `mime_type`, i.e. the value
+/// of the first pattern of `previewers` that matches it.
+pub fn previewer<'a>(previewers: &'a IndexMap<String, String>, mime_type: &str) -> Option<&'a str> {
+    let mime_type = mime_type.to_ascii_lowercase();
+    previewers
+        .iter()
+        .find(|(pattern, _)| mime_type.fnmatches(&pattern.to_ascii_lowercase()))
+        .map(|(_, command)| command.as_str())
+}
+
+/// Digest of an attachment's content and the command that converts it.
+pub fn digest(command: &str, bytes: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    command.hash(&mut hasher);
+    bytes.hash(&mut hasher);
+    format!("{:016x}{:08x}", hasher.finish(), bytes.len())
+}
+
+fn cache_path(digest: &str) -> Result<PathBuf> {
+    Ok(xdg::BaseDirectories::with_prefix("meli")?
+        .place_cache_file(Path::new("previews").join(digest))?)
+}
+
+/// Previously converted output for `digest`, if any.
+pub fn cached(digest: &str) -> Option<String> {
+    std::fs::read_to_string(cache_path(digest).ok()?).ok()
+}
+
+/// Replace `%s` in `command` with the quoted `path`.
+pub fn expand_command(command: &str, path: &Path) -> String {
+    let quoted = format!("'{}'", path.display().to_string().replace('\'', r"'\''"));
+    command.replace("%s", &quoted)
+}
+
+/// Run the shell `command` on an attachment and wait for its output.
+///
+/// If `command` contains `%s`, the attachment is written to a temporary file
+/// whose path replaces it, keeping the extension of `filename` for commands
+/// that detect the format from it. Otherwise the attachment is piped to its
+/// standard input.
+pub fn run(
+    command: &str,
+    bytes: &[u8],
+    filename: Option<&str>,
+    policy: &CommandPolicy,
+) -> Result<Output> {
+    let (file, command_line) = if command.contains("%s") {
+        let extension = filename
+            .and_then(|f| Path::new(f).extension())
+            .and_then(|e| e.to_str())
+            .filter(|e| e.chars().all(|c| c.is_ascii_alphanumeric()));
+        let file = File::create_temp_file(bytes, None, None, extension, true)?;
+        let command_line = expand_command(command, file.path());
+        (Some(file), command_line)
+    } else {
+        (None, command.to_string())
+    };
+    log::trace!("Executing: sh -c \"{}\"", command_line.replace('"', "\\\""));
+    let mut child = Command::new("sh")
+        .args(["-c", &command_line])
+        .stdin(if file.is_some() {
+            Stdio::null()
+        } else {
+            Stdio::piped()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_checked(policy)
+        .chain_err_summary(|| format!("Failed to start `{command}`"))
+        .chain_err_kind(ErrorKind::External)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The command may exit without reading all of its input.
+        if let Err(err) = stdin.write_all(bytes) {
+            log::trace!("Could not write attachment to `{command}`: {err}");
+        }
+    }
+    let output = child
+        .wait_with_output()
+        .chain_err_summary(|| format!("Could not wait for `{command}`"))?;
+    drop(file);
+    Ok(output)
+}
+
+/// Convert `bytes` to text with `command` and cache the result. See [`run`]
+/// for how the attachment is passed to the command.
+pub fn convert(
+    command: &str,
+    bytes: &[u8],
+    filename: Option<&str>,
+    policy: &CommandPolicy,
+) -> Result<String> {
+    let digest = digest(command, bytes);
+    if let Some(text) = cached(&digest) {
+        return Ok(text);
+    }
+    let output = run(command, bytes, filename, policy)?;
+    if !output.status.success() {
+        return Err(
+            Error::new(format!("`{command}` failed with {}", output.status))
+                .set_details(String::from_utf8_lossy(&output.stderr).trim().to_string())
+                .set_kind(ErrorKind::External),
+        );
+    }
+    let text = String::from_utf8_lossy(&output.stdout).into_owned();
+    if let Err(err) = cache_path(&digest).and_then(|p| Ok(std::fs::write(p, &text)?)) {
+        log::warn!("Could not cache preview of attachment: {err}");
+    }
+    Ok(text)
+}
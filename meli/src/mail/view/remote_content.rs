@@ -0,0 +1,398 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Blocking of remote content in HTML e-mail.
+//!
+//! Remote resources are neutralised before HTML is piped to a filter, so that
+//! no filter can fetch them: the attributes that refer to them are renamed
+//! (e.g. `src` to `data-blocked-src`) and CSS `url()` values get a `blocked:`
+//! scheme prefix.
+
+use indexmap::IndexSet;
+use melib::Address;
+
+/// Domains of e-mail marketing and analytics services that embed tracking
+/// pixels. Subdomains match as well.
+const TRACKER_DOMAINS: &[&str] = &[
+    "list-manage.com",
+    "mailchimp.com",
+    "mcusercontent.com",
+    "sendgrid.net",
+    "mandrillapp.com",
+    "mailgun.org",
+    "sparkpostmail.com",
+    "hubspot.com",
+    "hs-analytics.net",
+    "hubspotemail.net",
+    "pardot.com",
+    "exacttarget.com",
+    "sailthru.com",
+    "returnpath.net",
+    "mixpanel.com",
+    "google-analytics.com",
+    "doubleclick.net",
+    "createsend.com",
+    "constantcontact.com",
+    "klaviyo.com",
+    "customeriomail.com",
+    "mailtrack.io",
+];
+
+/// Attributes whose values are URLs of resources that are loaded when the
+/// document is displayed.
+const RESOURCE_ATTRIBUTES: &[&str] = &["src", "srcset", "background", "poster", "data"];
+
+/// Summary of the remote content blocked in an HTML document.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RemoteContentReport {
+    /// Blocked remote images, tracking pixels included.
+    pub images: usize,
+    /// Blocked images that are likely tracking pixels: 1x1 images and images
+    /// hosted by known tracking services.
+    pub tracking_pixels: usize,
+    /// Other blocked remote resources, such as stylesheets and background
+    /// images.
+    pub other: usize,
+    /// Hosts of the blocked resources.
+    pub hosts: IndexSet<String>,
+}
+
+impl RemoteContentReport {
+    pub fn is_empty(&self) -> bool {
+        self.images == 0 && self.other == 0
+    }
+}
+
+impl std::fmt::Display for RemoteContentReport {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn plural(n: usize) -> &'static str {
+            if n == 1 {
+                ""
+            } else {
+                "s"
+            }
+        }
+
+        write!(fmt, "Blocked")?;
+        if self.images > 0 {
+            write!(fmt, " {} remote image{}", self.images, plural(self.images))?;
+            if self.tracking_pixels > 0 {
+                write!(
+                    fmt,
+                    " ({} tracking pixel{})",
+                    self.tracking_pixels,
+                    plural(self.tracking_pixels)
+                )?;
+            }
+            if self.other > 0 {
+                write!(fmt, " and")?;
+            }
+        }
+        if self.other > 0 {
+            write!(
+                fmt,
+                " {} other remote resource{}",
+                self.other,
+                plural(self.other)
+            )?;
+        }
+        if !self.hosts.is_empty() {
+            write!(fmt, " from ")?;
+            for (i, host) in self.hosts.iter().enumerate() {
+                if i > 0 {
+                    write!(fmt, ", ")?;
+                }
+                write!(fmt, "{host}")?;
+            }
+        }
+        write!(fmt, ".")
+    }
+}
+
+/// Whether any address of `from` is in `allowlist`, which holds e-mail
+/// addresses and domains prefixed with `@`.
+pub fn sender_is_allowed(from: &[Address], allowlist: &[String]) -> bool {
+    from.iter().any(|addr| {
+        let email = addr.get_email();
+        allowlist.iter().any(|entry| match entry.strip_prefix('@') {
+            Some(domain) => email
+                .rsplit_once('@')
+                .is_some_and(|(_, d)| d.eq_ignore_ascii_case(domain)),
+            None => email.eq_ignore_ascii_case(entry),
+        })
+    })
+}
+
+/// Neutralise the remote resources of `html` and return the result along with
+/// a report of what was blocked.
+pub fn block_remote_content(html: &str) -> (String, RemoteContentReport) {
+    let mut report = RemoteContentReport::default();
+    let mut ret = String::with_capacity(html.len());
+    let mut rest = html;
+    let mut in_style = false;
+    while let Some(pos) = rest.find('<') {
+        if in_style {
+            ret.push_str(&block_css(&rest[..pos], &mut report));
+        } else {
+            ret.push_str(&rest[..pos]);
+        }
+        rest = &rest[pos..];
+        let end = tag_end(rest);
+        let tag = &rest[..end];
+        let name = tag_name(tag);
+        if name.eq_ignore_ascii_case("style") {
+            in_style = true;
+        } else if name.eq_ignore_ascii_case("/style") {
+            in_style = false;
+        }
+        ret.push_str(&block_tag(tag, &name, &mut report));
+        rest = &rest[end..];
+    }
+    ret.push_str(rest);
+    (ret, report)
+}
+
+/// Length of the tag or comment at the start of `s`, which starts with `<`.
+pub(super) fn tag_end(s: &str) -> usize {
+    if s.starts_with("<!--") {
+        return s.find("-->").map_or(s.len(), |i| i + "-->".len());
+    }
+    let mut quote = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return i + 1,
+            None => {}
+        }
+    }
+    s.len()
+}
+
+pub(super) fn tag_name(tag: &str) -> String {
+    tag[1..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '/')
+        .collect()
+}
+
+/// The host of `url` if it is a remote URL.
+fn remote_host(url: &str) -> Option<String> {
+    let url = url.trim();
+    let rest = ["http://", "https://", "//"].iter().find_map(|scheme| {
+        url.get(..scheme.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(scheme))
+            .map(|_| &url[scheme.len()..])
+    })?;
+    let host = rest
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default()
+        .rsplit('@')
+        .next()
+        .unwrap_or_default()
+        .split(':')
+        .next()
+        .unwrap_or_default();
+    Some(host.to_ascii_lowercase())
+}
+
+fn is_tracker(host: &str) -> bool {
+    TRACKER_DOMAINS.iter().any(|d| {
+        host == *d
+            || host
+                .strip_suffix(d)
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+/// A tag attribute: the byte range of its name in the tag and its value.
+pub(super) struct Attribute<'a> {
+    pub name: std::ops::Range<usize>,
+    pub value: Option<(std::ops::Range<usize>, &'a str)>,
+}
+
+pub(super) fn attributes(tag: &str) -> Vec<Attribute<'_>> {
+    let bytes = tag.as_bytes();
+    let mut ret = vec![];
+    let mut i = 1;
+    while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+        i += 1;
+    }
+    loop {
+        while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'/') {
+            i += 1;
+        }
+        if i >= bytes.len() || bytes[i] == b'>' {
+            break;
+        }
+        let name_start = i;
+        while i < bytes.len()
+            && !bytes[i].is_ascii_whitespace()
+            && !matches!(bytes[i], b'=' | b'>' | b'/')
+        {
+            i += 1;
+        }
+        let name = name_start..i;
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i >= bytes.len() || bytes[i] != b'=' {
+            ret.push(Attribute { name, value: None });
+            continue;
+        }
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let value = if i < bytes.len() && matches!(bytes[i], b'"' | b'\'') {
+            let quote = bytes[i];
+            let start = i + 1;
+            i = start;
+            while i < bytes.len() && bytes[i] != quote {
+                i += 1;
+            }
+            let range = start..i;
+            i = (i + 1).min(bytes.len());
+            range
+        } else {
+            let start = i;
+            while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                i += 1;
+            }
+            start..i
+        };
+        ret.push(Attribute {
+            name,
+            value: Some((value.clone(), &tag[value])),
+        });
+    }
+    ret
+}
+
+fn dimension_is_pixel(value: Option<&str>) -> bool {
+    value.is_some_and(|v| {
+        v.trim()
+            .trim_end_matches("px")
+            .parse::<u32>()
+            .is_ok_and(|n| n <= 1)
+    })
+}
+
+fn block_tag<'a>(
+    tag: &'a str,
+    name: &str,
+    report: &mut RemoteContentReport,
+) -> std::borrow::Cow<'a, str> {
+    if tag.starts_with("<!") || name.starts_with('/') {
+        return tag.into();
+    }
+    let attributes = attributes(tag);
+    let value_of = |attr: &str| {
+        attributes.iter().find_map(|a| {
+            tag[a.name.clone()]
+                .eq_ignore_ascii_case(attr)
+                .then_some(a.value.as_ref().map(|(_, v)| *v))
+                .flatten()
+        })
+    };
+    let is_image = name.eq_ignore_ascii_case("img");
+    let is_pixel =
+        is_image && dimension_is_pixel(value_of("width")) && dimension_is_pixel(value_of("height"));
+    let is_stylesheet = name.eq_ignore_ascii_case("link")
+        && value_of("rel").is_some_and(|rel| {
+            rel.split_whitespace()
+                .any(|r| r.eq_ignore_ascii_case("stylesheet") || r.eq_ignore_ascii_case("icon"))
+        });
+
+    // Replacements of byte ranges of the tag, in order.
+    let mut replacements: Vec<(std::ops::Range<usize>, String)> = vec![];
+    let mut blocked_image = false;
+    for attr in &attributes {
+        let attr_name = &tag[attr.name.clone()];
+        let Some((ref value_range, value)) = attr.value else {
+            continue;
+        };
+        if attr_name.eq_ignore_ascii_case("style") {
+            let css = block_css(value, report);
+            if css != value {
+                replacements.push((value_range.clone(), css));
+            }
+            continue;
+        }
+        let is_resource = RESOURCE_ATTRIBUTES
+            .iter()
+            .any(|a| attr_name.eq_ignore_ascii_case(a))
+            || (is_stylesheet && attr_name.eq_ignore_ascii_case("href"));
+        if !is_resource {
+            continue;
+        }
+        let Some(host) = remote_host(value.split([',', ' ']).next().unwrap_or_default()) else {
+            continue;
+        };
+        replacements.push((attr.name.clone(), format!("data-blocked-{attr_name}")));
+        if is_image && !blocked_image {
+            blocked_image = true;
+            report.images += 1;
+            if is_pixel || is_tracker(&host) {
+                report.tracking_pixels += 1;
+            }
+        } else if !is_image {
+            report.other += 1;
+        }
+        report.hosts.insert(host);
+    }
+    if replacements.is_empty() {
+        return tag.into();
+    }
+    let mut ret = String::with_capacity(tag.len() + 16 * replacements.len());
+    let mut prev = 0;
+    for (range, replacement) in replacements {
+        ret.push_str(&tag[prev..range.start]);
+        ret.push_str(&replacement);
+        prev = range.end;
+    }
+    ret.push_str(&tag[prev..]);
+    ret.into()
+}
+
+/// Prefix remote CSS `url()` values of `css` with a `blocked:` scheme.
+fn block_css(css: &str, report: &mut RemoteContentReport) -> String {
+    let mut ret = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(pos) = rest.to_ascii_lowercase().find("url(") {
+        let (before, after) = rest.split_at(pos + "url(".len());
+        ret.push_str(before);
+        let quote_len = after.len() - after.trim_start_matches(['"', '\'', ' ']).len();
+        let (quote, url) = after.split_at(quote_len);
+        ret.push_str(quote);
+        if let Some(host) = remote_host(url.split([')', '"', '\'']).next().unwrap_or_default()) {
+            ret.push_str("blocked:");
+            report.other += 1;
+            report.hosts.insert(host);
+        }
+        rest = url;
+    }
+    ret.push_str(rest);
+    ret
+}
@@ -0,0 +1,96 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Scanning of attachments for malware before they are opened or saved.
+//!
+//! The `attachment_scan_command` setting is run on the attachment like
+//! [`clamdscan(1)`](https://docs.clamav.net/), whose exit status is `0` for
+//! clean files and `1` for infected ones. Results are cached in
+//! [`Context::attachment_scans`] by content digest for the rest of the
+//! session.
+
+use melib::error::*;
+
+use super::preview;
+use crate::{CommandPolicy, Context};
+
+/// Outcome of scanning an attachment.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ScanResult {
+    Clean,
+    /// The scanner reported the attachment, with its report.
+    Flagged(String),
+}
+
+impl ScanResult {
+    /// Interpret the exit status and standard output of a scanner.
+    pub fn from_output(command: &str, code: Option<i32>, stdout: &[u8]) -> Result<Self> {
+        match code {
+            Some(0) => Ok(Self::Clean),
+            Some(1) => {
+                let report = String::from_utf8_lossy(stdout)
+                    .lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                Ok(Self::Flagged(if report.is_empty() {
+                    format!("flagged by `{command}`")
+                } else {
+                    report
+                }))
+            }
+            Some(code) => Err(Error::new(format!(
+                "`{command}` could not scan the attachment (exit status {code})"
+            ))
+            .set_kind(ErrorKind::External)),
+            None => Err(
+                Error::new(format!("`{command}` was terminated by a signal"))
+                    .set_kind(ErrorKind::External),
+            ),
+        }
+    }
+}
+
+/// Scan `bytes` with `command`, reusing the result of a previous scan of the
+/// same content. Returns the result along with the digest of the content.
+pub fn scan(
+    command: &str,
+    bytes: &[u8],
+    filename: Option<&str>,
+    policy: &CommandPolicy,
+    context: &mut Context,
+) -> Result<(String, ScanResult)> {
+    let digest = preview::digest(command, bytes);
+    if let Some(result) = context.attachment_scans.get(&digest) {
+        return Ok((digest, result.clone()));
+    }
+    let output = preview::run(command, bytes, filename, policy)?;
+    let result =
+        ScanResult::from_output(command, output.status.code(), &output.stdout).map_err(|err| {
+            err.set_details(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        })?;
+    context
+        .attachment_scans
+        .insert(digest.clone(), result.clone());
+    Ok((digest, result))
+}
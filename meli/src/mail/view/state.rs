@@ -127,10 +127,47 @@ impl MailViewState {
                     context[coordinates.0][&coordinates.1].pager.html_filter
                 )
                 .clone(),
+                allow_remote_content: super::remote_content::sender_is_allowed(
+                    env.from(),
+                    mailbox_settings!(
+                        context[coordinates.0][&coordinates.1]
+                            .pager
+                            .html_remote_content_allowlist
+                    ),
+                ),
+                html_remote_content_filter: mailbox_settings!(
+                    context[coordinates.0][&coordinates.1]
+                        .pager
+                        .html_remote_content_filter
+                )
+                .clone(),
+                html_reveal_link_targets: *mailbox_settings!(
+                    context[coordinates.0][&coordinates.1]
+                        .pager
+                        .html_reveal_link_targets
+                ),
                 url_launcher: mailbox_settings!(
                     context[coordinates.0][&coordinates.1].pager.url_launcher
                 )
                 .clone(),
+                print_command: mailbox_settings!(
+                    context[coordinates.0][&coordinates.1].pager.print_command
+                )
+                .clone(),
+                attachment_scan_command: mailbox_settings!(
+                    context[coordinates.0][&coordinates.1]
+                        .pager
+                        .attachment_scan_command
+                )
+                .clone(),
+                print_page_lines: *mailbox_settings!(
+                    context[coordinates.0][&coordinates.1]
+                        .pager
+                        .print_page_lines
+                ),
+                print_width: *mailbox_settings!(
+                    context[coordinates.0][&coordinates.1].pager.minimum_width
+                ),
                 auto_choose_multipart_alternative: mailbox_settings!(
                     context[coordinates.0][&coordinates.1]
                         .pager
@@ -162,6 +199,7 @@ impl MailViewState {
                     context[coordinates.0][&coordinates.1].pgp.auto_decrypt
                 ),
                 charset: None,
+                command_policy: context.command_policy(Some(coordinates.0)),
             }),
             context.main_loop_handler.clone(),
         ));
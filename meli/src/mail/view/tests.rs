@@ -98,3 +98,195 @@ fn test_view_filter_multipart_related() {
     //let value = ViewFilter::new_attachment(&att, &mut ctx).unwrap();
     //assert_eq!(&value.content_type.to_string(), "text/related");
 }
+
+#[test]
+fn test_view_print_layout() {
+    use super::print_layout;
+
+    let headers = [
+        ("From", "Jane Doe <jane@example.com>".to_string()),
+        ("Subject", "Minutes".to_string()),
+    ];
+
+    // Long lines are wrapped at whitespace, or at the width if a word does not
+    // fit.
+    assert_eq!(
+        print_layout(&headers, "one two three four", "", 9, 0),
+        "From:\nJane Doe\n<jane@exa\nmple.com>\nSubject:\nMinutes\n\none two\nthree\nfour\n"
+    );
+
+    // The attachment list follows the body.
+    assert_eq!(
+        print_layout(&headers[1..], "body", "1 text/plain", 80, 0),
+        "Subject: Minutes\n\nbody\n\n1 text/plain\n"
+    );
+
+    // Pages are separated with form feeds and do not start with blank lines.
+    let body = "a\nb\n\nc\nd\ne\nf\ng";
+    assert_eq!(
+        print_layout(&headers[1..], body, "", 80, 4),
+        "Subject: Minutes\n\na\nb\n\x0cc\nd\ne\nf\n\x0cg\n"
+    );
+    assert_eq!(
+        print_layout(&headers[1..], body, "", 80, 8),
+        "Subject: Minutes\n\na\nb\n\nc\nd\ne\n\x0cf\ng\n"
+    );
+    // A page that would end inside a paragraph ends at a blank line in its
+    // final quarter instead.
+    assert_eq!(
+        print_layout(&headers[1..], body, "", 80, 6),
+        "Subject: Minutes\n\na\nb\n\n\x0cc\nd\ne\nf\ng\n"
+    );
+}
+
+#[test]
+fn test_view_block_remote_content() {
+    use melib::Address;
+
+    use super::remote_content::{block_remote_content, sender_is_allowed};
+
+    let html = r#"<html><head><style>body { background: url("https://cdn.example.com/bg.png"); }</style>
+<link rel="stylesheet" href="https://cdn.example.com/s.css"></head>
+<body><!-- <img src="http://example.org/a.png"> -->
+<img src="https://cdn.example.com/logo.png" alt='logo >'>
+<img width=1 height="1px" src=http://t.example.org/p.gif>
+<IMG SRC="https://foo.list-manage.com/track/open.php?u=1">
+<img src="cid:part1@example.com"><a href="https://example.com">link</a>
+</body></html>"#;
+    let (blocked, report) = block_remote_content(html);
+    assert_eq!(
+        blocked,
+        r#"<html><head><style>body { background: url("blocked:https://cdn.example.com/bg.png"); }</style>
+<link rel="stylesheet" data-blocked-href="https://cdn.example.com/s.css"></head>
+<body><!-- <img src="http://example.org/a.png"> -->
+<img data-blocked-src="https://cdn.example.com/logo.png" alt='logo >'>
+<img width=1 height="1px" data-blocked-src=http://t.example.org/p.gif>
+<IMG data-blocked-SRC="https://foo.list-manage.com/track/open.php?u=1">
+<img src="cid:part1@example.com"><a href="https://example.com">link</a>
+</body></html>"#
+    );
+    assert_eq!(
+        (report.images, report.tracking_pixels, report.other),
+        (3, 2, 2)
+    );
+    assert_eq!(
+        report.to_string(),
+        "Blocked 3 remote images (2 tracking pixels) and 2 other remote resources from \
+         cdn.example.com, t.example.org, foo.list-manage.com."
+    );
+
+    let from = [Address::new(None, "news@Example.com".to_string())];
+    assert!(sender_is_allowed(&from, &["@example.com".to_string()]));
+    assert!(sender_is_allowed(&from, &["news@example.com".to_string()]));
+    assert!(!sender_is_allowed(&from, &["@example.org".to_string()]));
+}
+
+#[test]
+fn test_view_reveal_link_targets() {
+    use super::links::reveal_link_targets;
+
+    let html = r#"<p><a href="https://evil.example.org/login">https://www.bank.com</a>
+<a href="https://mail.example.com/x">example.com</a>
+<a href="https://xn--bnk-sna.com">bank</a>
+<a href="https://example.com@evil.example.org">site</a>
+<a href="https://example.com/a?b=1&amp;c=2">read <b>more</b></a>
+<a href="mailto:a@example.com">a@example.com</a></p>"#;
+    assert_eq!(
+        reveal_link_targets(html, false),
+        r#"<p><a href="https://evil.example.org/login">https://www.bank.com</a> [⚠ https://evil.example.org/login]
+<a href="https://mail.example.com/x">example.com</a>
+<a href="https://xn--bnk-sna.com">bank</a> [⚠ https://xn--bnk-sna.com]
+<a href="https://example.com@evil.example.org">site</a> [⚠ https://example.com@evil.example.org]
+<a href="https://example.com/a?b=1&amp;c=2">read <b>more</b></a>
+<a href="mailto:a@example.com">a@example.com</a></p>"#
+    );
+    assert_eq!(
+        reveal_link_targets(
+            r#"<a href="https://example.com/a?b=1&amp;c=2">read <b>more</b></a>"#,
+            true
+        ),
+        r#"<a href="https://example.com/a?b=1&amp;c=2">read <b>more</b></a> [https://example.com/a?b=1&amp;c=2]"#
+    );
+}
+
+#[test]
+fn test_view_delivery_path() {
+    use super::delivery_path;
+
+    let raw = b"Received: from relay.example.org by mx.example.net; Tue, 1 Oct 2024 10:02:13 \
+                +0000\r\nReceived: from client.example.com ([192.0.2.1]) by relay.example.org \
+                with ESMTPSA; Tue, 1 Oct 2024 10:00:03 +0000\r\nDate: Tue, 1 Oct 2024 \
+                10:00:00 +0000\r\n\r\nbody";
+    let text = delivery_path(raw, Some(1727776800));
+    assert!(
+        text.starts_with("Delivery path, 2 hops, 2m 13s in total\n"),
+        "{text}"
+    );
+    assert!(text.contains(" (+3s)\n"), "{text}");
+    assert!(text.contains(" (+2m 10s) slowest hop\n"), "{text}");
+    assert!(
+        text.contains("  from   client.example.com ([192.0.2.1])\n"),
+        "{text}"
+    );
+    assert!(text.contains("  with   ESMTPSA\n"), "{text}");
+
+    assert_eq!(
+        delivery_path(b"Subject: hi\r\n\r\nbody", None),
+        "This message has no Received headers."
+    );
+}
+
+#[test]
+fn test_view_attachment_previewer() {
+    use std::path::Path;
+
+    use indexmap::IndexMap;
+
+    use super::preview::{digest, expand_command, previewer};
+
+    let previewers: IndexMap<String, String> = [
+        ("application/pdf", "pdftotext %s -"),
+        ("image/*", "exiftool %s"),
+        ("*", "file -"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+    assert_eq!(
+        previewer(&previewers, "application/PDF"),
+        Some("pdftotext %s -")
+    );
+    assert_eq!(previewer(&previewers, "image/png"), Some("exiftool %s"));
+    assert_eq!(previewer(&previewers, "text/csv"), Some("file -"));
+    assert_eq!(previewer(&IndexMap::default(), "image/png"), None);
+
+    assert_eq!(
+        expand_command("pdftotext %s -", Path::new("/tmp/it's.pdf")),
+        r"pdftotext '/tmp/it'\''s.pdf' -"
+    );
+
+    assert_eq!(digest("cat", b"abc"), digest("cat", b"abc"));
+    assert_ne!(digest("cat", b"abc"), digest("cat", b"abd"));
+    assert_ne!(digest("cat", b"abc"), digest("less", b"abc"));
+}
+
+#[test]
+fn test_view_attachment_scan_result() {
+    use super::scan::ScanResult;
+
+    assert_eq!(
+        ScanResult::from_output("clamdscan -", Some(0), b"stream: OK\n").unwrap(),
+        ScanResult::Clean
+    );
+    assert_eq!(
+        ScanResult::from_output("clamdscan -", Some(1), b"stream: Eicar-Signature FOUND\n\n")
+            .unwrap(),
+        ScanResult::Flagged("stream: Eicar-Signature FOUND".to_string())
+    );
+    assert_eq!(
+        ScanResult::from_output("scan", Some(1), b"").unwrap(),
+        ScanResult::Flagged("flagged by `scan`".to_string())
+    );
+    ScanResult::from_output("clamdscan -", Some(2), b"").unwrap_err();
+    ScanResult::from_output("clamdscan -", None, b"").unwrap_err();
+}
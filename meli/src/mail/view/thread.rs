@@ -19,7 +19,7 @@
  * along with meli. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use std::cmp;
+use std::{cmp, collections::BTreeSet};
 
 use melib::{
     utils::datetime::{timestamp_to_string, UnixTimestamp},
@@ -762,6 +762,71 @@ impl ThreadView {
         }
     }
 
+    pub const fn account_hash(&self) -> AccountHash {
+        self.coordinates.0
+    }
+
+    /// Message-ID of each e-mail of the thread and whether its replies are
+    /// collapsed.
+    pub fn collapsed_entries(&self, context: &Context) -> Vec<(String, bool)> {
+        let account = &context.accounts[&self.coordinates.0];
+        self.entries
+            .iter()
+            .filter(|e| account.contains_key(e.msg_hash))
+            .map(|e| {
+                (
+                    account
+                        .collection
+                        .get_env(e.msg_hash)
+                        .message_id()
+                        .to_string(),
+                    e.hidden,
+                )
+            })
+            .collect()
+    }
+
+    /// Collapse the replies of the e-mail whose Message-ID is in `collapsed`,
+    /// unless the open e-mail is one of them.
+    pub fn collapse_entries(&mut self, collapsed: &BTreeSet<String>, context: &Context) {
+        if collapsed.is_empty() || self.entries.is_empty() {
+            return;
+        }
+        let account = &context.accounts[&self.coordinates.0];
+        for i in 0..self.entries.len() {
+            let entry = &self.entries[i];
+            if !account.contains_key(entry.msg_hash)
+                || !collapsed.contains(
+                    &account
+                        .collection
+                        .get_env(entry.msg_hash)
+                        .message_id()
+                        .to_string(),
+                )
+            {
+                continue;
+            }
+            // Replies are the entries that follow with a deeper indentation.
+            let replies_end = self.entries[i + 1..]
+                .iter()
+                .position(|e| e.indentation <= entry.indentation)
+                .map_or(self.entries.len(), |p| i + 1 + p);
+            if (i + 1..replies_end).contains(&self.new_expanded_pos) {
+                continue;
+            }
+            self.entries[i].hidden = true;
+            self.entries[i].dirty = true;
+        }
+        self.recalc_visible_entries();
+        self.new_cursor_pos = self
+            .visible_entries
+            .iter()
+            .flat_map(|v| v.iter())
+            .position(|idx| *idx == self.new_expanded_pos)
+            .unwrap_or(0);
+        self.set_dirty(true);
+    }
+
     /// Current position in self.entries (not in drawn entries which might
     /// exclude nonvisible ones)
     fn current_pos(&self) -> Option<usize> {
@@ -798,7 +863,9 @@ impl Component for ThreadView {
             self.expanded_pos = self.new_expanded_pos;
         }
 
-        if self.entries.len() == 1 {
+        if self.entries.len() == 1
+            || (context.zoomed && matches!(self.focus, ThreadViewFocus::MailView))
+        {
             self.entries[self.new_expanded_pos]
                 .mailview
                 .draw(grid, area, context);
@@ -23,13 +23,13 @@ use std::{borrow::Cow, fmt::Write as IoWrite};
 
 use melib::{
     attachment_types::Charset, conf::ActionFlag, email::headers::HeaderName, error::*,
-    pgp::DecryptionMetadata, Attachment, Result,
+    pgp::DecryptionMetadata, Attachment, MimePart, Result,
 };
 
 use crate::{
     conf::shortcuts::EnvelopeViewShortcuts,
     jobs::{JobId, JoinHandle},
-    types::{Link, LinkKind},
+    types::{CommandPolicy, Link, LinkKind},
     ShortcutMap, ThemeAttribute,
 };
 
@@ -37,7 +37,16 @@ use crate::{
 pub struct ViewSettings {
     pub pager_filter: Option<String>,
     pub html_filter: Option<String>,
+    /// Whether the sender is in `pager.html_remote_content_allowlist`.
+    pub allow_remote_content: bool,
+    pub html_remote_content_filter: Option<String>,
+    pub html_reveal_link_targets: bool,
     pub url_launcher: Option<String>,
+    pub print_command: Option<String>,
+    /// `pager.attachment_scan_command`
+    pub attachment_scan_command: Option<String>,
+    pub print_page_lines: usize,
+    pub print_width: usize,
     pub expand_headers: bool,
     pub theme_default: ThemeAttribute,
     pub env_view_shortcuts: ShortcutMap,
@@ -50,6 +59,8 @@ pub struct ViewSettings {
     pub auto_verify_signatures: ActionFlag,
     pub auto_decrypt: ActionFlag,
     pub charset: Option<Charset>,
+    /// Policy of the account for the filters and viewers the view runs.
+    pub command_policy: CommandPolicy,
 }
 
 impl Default for ViewSettings {
@@ -59,7 +70,14 @@ impl Default for ViewSettings {
             body_theme: Default::default(),
             pager_filter: None,
             html_filter: None,
+            allow_remote_content: false,
+            html_remote_content_filter: None,
+            html_reveal_link_targets: false,
             url_launcher: None,
+            print_command: None,
+            attachment_scan_command: None,
+            print_page_lines: 66,
+            print_width: 80,
             env_view_shortcuts: EnvelopeViewShortcuts::default().key_values(),
             auto_choose_multipart_alternative: true,
             expand_headers: false,
@@ -69,6 +87,7 @@ impl Default for ViewSettings {
             auto_verify_signatures: ActionFlag::InternalVal(true),
             auto_decrypt: ActionFlag::InternalVal(true),
             charset: None,
+            command_policy: CommandPolicy::default(),
         }
     }
 }
@@ -81,39 +100,32 @@ impl ViewSettings {
         timestamp: melib::UnixTimestamp,
         val: &'hdr str,
     ) -> Cow<'hdr, str> {
-        let find_offset = |s: &str| -> (bool, (i64, i64)) {
-            let mut diff = (true, (0, 0));
-            if let Some(pos) = s.as_bytes().iter().position(|b| *b == b'+' || *b == b'-') {
-                let offset = &s[pos..];
-                diff.0 = offset.starts_with('+');
-                if let (Some(hr_offset), Some(min_offset)) = (
-                    offset.get(1..3).and_then(|slice| slice.parse::<i64>().ok()),
-                    offset.get(3..5).and_then(|slice| slice.parse::<i64>().ok()),
-                ) {
-                    diff.1 .0 = hr_offset;
-                    diff.1 .1 = min_offset;
-                }
-            }
-            diff
-        };
         if self.show_date_in_my_timezone {
-            use melib::utils::datetime;
+            use melib::{email::parser::dates::rfc5322_date_with_zone, utils::datetime};
             let local_date = datetime::timestamp_to_string(
                 timestamp,
                 Some(datetime::formats::RFC822_DATE),
                 false,
             );
-            let orig_offset = find_offset(val);
-            let local_offset = find_offset(&local_date);
-            if orig_offset == local_offset {
+            // The zone of the original value, if it could be parsed and is known.
+            let Some(orig_offset) = rfc5322_date_with_zone(val.as_bytes())
+                .ok()
+                .and_then(|(_, zone)| zone)
+            else {
+                return local_date.into();
+            };
+            let local_offset = rfc5322_date_with_zone(local_date.as_bytes())
+                .ok()
+                .and_then(|(_, zone)| zone);
+            if Some(orig_offset) == local_offset {
                 val.into()
             } else {
                 format!(
                     "{} [actual timezone: {}{:02}{:02}]",
                     local_date,
-                    if orig_offset.0 { '+' } else { '-' },
-                    orig_offset.1 .0,
-                    orig_offset.1 .1
+                    if orig_offset < 0 { '-' } else { '+' },
+                    orig_offset.abs() / 3600,
+                    orig_offset.abs() % 3600 / 60
                 )
                 .into()
             }
@@ -235,6 +247,7 @@ bitflags::bitflags! {
         const URL               = 1;
         const SOURCE            = Self::URL.bits() << 1;
         const SOURCE_RAW        = Self::SOURCE.bits() << 1;
+        const DELIVERY_PATH     = Self::SOURCE_RAW.bits() << 1;
     }
 }
 
@@ -245,19 +258,14 @@ impl Default for ViewOptions {
 }
 
 impl ViewOptions {
-    pub fn convert(
-        &self,
-        links: &mut Vec<Link<'static>>,
-        attachment: &melib::Attachment,
-        text: &str,
-    ) -> String {
+    pub fn convert(&self, links: &mut Vec<Link<'static>>, raw: &[u8], text: &str) -> String {
         let mut text = if self.contains(Self::SOURCE) {
             if self.contains(Self::SOURCE_RAW) {
-                String::from_utf8_lossy(attachment.raw()).into_owned()
+                String::from_utf8_lossy(raw).into_owned()
             } else {
                 /* Decode each header value */
                 let mut ret = String::new();
-                match melib::email::parser::headers::headers(attachment.raw()).map(|(_, v)| v) {
+                match melib::email::parser::headers::headers(raw).map(|(_, v)| v) {
                     Ok(headers) => {
                         for (h, v) in headers {
                             _ = match melib::email::parser::encodings::phrase(v, true) {
@@ -329,9 +337,14 @@ pub enum AttachmentDisplay {
         shown_display: usize,
         display: Vec<Self>,
     },
+    /// Multipart part other than `multipart/alternative`, signed or encrypted.
+    ///
+    /// If `part` is set, `inner` is only an outline of the part in the e-mail,
+    /// see [`MimePart::outline`].
     Mixed {
         inner: Box<Attachment>,
         display: Vec<Self>,
+        part: Option<MimePart>,
     },
     InlineText {
         inner: Box<Attachment>,
@@ -348,8 +361,13 @@ pub enum AttachmentDisplay {
     InlineOther {
         inner: Box<Attachment>,
     },
+    /// Part that is not displayed inline.
+    ///
+    /// If `part` is set, `inner` is only an outline of the part in the e-mail,
+    /// see [`MimePart::outline`].
     Attachment {
         inner: Box<Attachment>,
+        part: Option<MimePart>,
     },
     SignedPending {
         inner: Box<Attachment>,
@@ -396,7 +414,11 @@ impl AttachmentDisplay {
                 shown_display: _,
                 display: _,
             }
-            | Self::Mixed { inner, display: _ }
+            | Self::Mixed {
+                inner,
+                display: _,
+                part: _,
+            }
             | Self::InlineText {
                 inner,
                 text: _,
@@ -409,7 +431,7 @@ impl AttachmentDisplay {
                 text: _,
             }
             | Self::InlineOther { inner }
-            | Self::Attachment { inner }
+            | Self::Attachment { inner, part: _ }
             | Self::SignedPending {
                 inner,
                 display: _,
@@ -438,6 +460,21 @@ impl AttachmentDisplay {
         }
     }
 
+    /// The location of the part in the e-mail, if
+    /// [`attachment`](Self::attachment) is only its outline.
+    #[inline]
+    pub const fn part(&self) -> Option<&MimePart> {
+        match self {
+            Self::Mixed {
+                part: Some(part), ..
+            }
+            | Self::Attachment {
+                part: Some(part), ..
+            } => Some(part),
+            _ => None,
+        }
+    }
+
     #[inline]
     pub fn as_multipart(&self) -> Option<&[Self]> {
         match self {
@@ -446,7 +483,11 @@ impl AttachmentDisplay {
                 shown_display: _,
                 display,
             }
-            | Self::Mixed { inner: _, display }
+            | Self::Mixed {
+                inner: _,
+                display,
+                part: _,
+            }
             | Self::InlineRfc822 {
                 raw: _,
                 inner: _,
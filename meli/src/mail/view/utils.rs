@@ -105,3 +105,185 @@ mod tests {
         );
     }
 }
+
+/// Lay out a message for printing: `headers`, then `body`, then the
+/// `attachments` list, with lines wider than `width` columns wrapped and
+/// pages of `page_lines` lines separated by form feed characters.
+///
+/// If a page would end inside a paragraph, it is ended at the last blank line
+/// of its final quarter instead, if there is one.
+pub fn print_layout(
+    headers: &[(&str, String)],
+    body: &str,
+    attachments: &str,
+    width: usize,
+    page_lines: usize,
+) -> String {
+    use melib::text::TextProcessing;
+
+    fn wrap(lines: &mut Vec<String>, line: &str, width: usize) {
+        let mut current = String::new();
+        let mut current_width = 0;
+        let mut last_space: Option<usize> = None;
+        for g in line.split_graphemes() {
+            let g_width = g.grapheme_width();
+            if current_width + g_width > width && !current.is_empty() {
+                let rest = match last_space {
+                    Some(idx) if idx > 0 => {
+                        let rest = current.split_off(idx + 1);
+                        current.truncate(idx);
+                        rest
+                    }
+                    _ => String::new(),
+                };
+                lines.push(std::mem::replace(&mut current, rest));
+                current_width = current.grapheme_width();
+                last_space = None;
+            }
+            if g.trim().is_empty() {
+                last_space = Some(current.len());
+            }
+            current.push_str(g);
+            current_width += g_width;
+        }
+        lines.push(current);
+    }
+
+    let width = width.max(1);
+    let mut lines: Vec<String> = vec![];
+    for (name, value) in headers {
+        wrap(&mut lines, &format!("{name}: {value}"), width);
+    }
+    lines.push(String::new());
+    for line in body.trim_end().lines() {
+        wrap(&mut lines, line.trim_end(), width);
+    }
+    if !attachments.trim().is_empty() {
+        lines.push(String::new());
+        for line in attachments.trim_end().lines() {
+            wrap(&mut lines, line.trim_end(), width);
+        }
+    }
+
+    let page_lines = if page_lines == 0 {
+        lines.len()
+    } else {
+        page_lines
+    };
+    let mut ret = String::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let mut end = (start + page_lines).min(lines.len());
+        if end < lines.len() && !lines[end].trim().is_empty() {
+            if let Some(blank) = (start + page_lines * 3 / 4..end)
+                .rev()
+                .find(|i| lines[*i].trim().is_empty())
+            {
+                end = blank + 1;
+            }
+        }
+        if start > 0 {
+            ret.push('\x0c');
+        }
+        for line in &lines[start..end] {
+            ret.push_str(line);
+            ret.push('\n');
+        }
+        start = end;
+        // Do not start a new page with blank lines.
+        while start < lines.len() && lines[start].trim().is_empty() {
+            start += 1;
+        }
+    }
+    ret
+}
+
+/// Format a duration in seconds, e.g. `1h 2m`, `3m 4s` or `5s`.
+fn format_latency(seconds: i64) -> String {
+    let sign = if seconds < 0 { "-" } else { "" };
+    let seconds = seconds.unsigned_abs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if hours > 0 {
+        format!("{sign}{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{sign}{minutes}m {seconds}s")
+    } else {
+        format!("{sign}{seconds}s")
+    }
+}
+
+/// Describe the delivery path of message `raw` from its `Received` headers,
+/// with the time each hop took. The first hop is measured from `sent`, the
+/// value of the `Date` header, if it is known.
+pub fn delivery_path(raw: &[u8], sent: Option<melib::UnixTimestamp>) -> String {
+    use std::fmt::Write;
+
+    use melib::{email::received::Hop, utils::datetime::timestamp_to_string};
+
+    let hops = melib::email::received::hops(raw);
+    if hops.is_empty() {
+        return "This message has no Received headers.".to_string();
+    }
+    let sent = Hop {
+        timestamp: sent,
+        ..Hop::default()
+    };
+    let latencies = hops
+        .iter()
+        .enumerate()
+        .map(|(i, hop)| hop.latency(if i == 0 { &sent } else { &hops[i - 1] }))
+        .collect::<Vec<_>>();
+    let slowest = latencies
+        .iter()
+        .enumerate()
+        .filter_map(|(i, l)| Some((i, (*l)?)))
+        .max_by_key(|(_, l)| *l)
+        .filter(|(_, l)| *l > 0)
+        .map(|(i, _)| i);
+
+    let mut ret = format!(
+        "Delivery path, {} hop{}",
+        hops.len(),
+        if hops.len() == 1 { "" } else { "s" }
+    );
+    if let Some(total) = hops.last().and_then(|last| last.latency(&sent)) {
+        _ = write!(ret, ", {} in total", format_latency(total));
+    }
+    ret.push_str("\n\n");
+    if let Some(sent) = sent.timestamp {
+        _ = writeln!(ret, "sent     {}\n", timestamp_to_string(sent, None, false));
+    }
+    for (i, (hop, latency)) in hops.iter().zip(latencies).enumerate() {
+        _ = write!(
+            ret,
+            "#{:<7} {}",
+            i + 1,
+            hop.timestamp
+                .map(|t| timestamp_to_string(t, None, false))
+                .unwrap_or_else(|| "unknown date".to_string())
+        );
+        match latency {
+            Some(l) if l < 0 => _ = write!(ret, " ({}, clocks disagree)", format_latency(l)),
+            Some(l) => _ = write!(ret, " (+{})", format_latency(l)),
+            None => {}
+        }
+        if slowest == Some(i) {
+            ret.push_str(" slowest hop");
+        }
+        ret.push('\n');
+        for (name, value) in [
+            ("from", &hop.from),
+            ("by", &hop.by),
+            ("via", &hop.via),
+            ("with", &hop.with),
+            ("id", &hop.id),
+            ("for", &hop.recipient),
+        ] {
+            if let Some(value) = value {
+                _ = writeln!(ret, "  {name:<6} {value}");
+            }
+        }
+        ret.push('\n');
+    }
+    ret
+}
@@ -34,7 +34,7 @@ use melib::{email::Attachment, log, utils::fnmatch::Fnmatch, Error, Result};
 
 use crate::{
     state::Context,
-    types::{File, UIEvent},
+    types::{CommandPolicyExt, File, UIEvent},
 };
 
 macro_rules! split_command {
@@ -196,13 +196,14 @@ impl MailcapEntry {
                     .collect::<Result<Vec<String>>>()?;
                 let cmd_string = format!("{} {}", cmd, args.join(" "));
                 log::trace!("Executing: sh -c \"{}\"", cmd_string.replace('"', "\\\""));
+                let policy = context.command_policy(None);
                 if copiousoutput {
                     let out = if needs_stdin {
                         let mut child = Command::new("sh")
                             .args(["-c", &cmd_string])
                             .stdin(Stdio::piped())
                             .stdout(Stdio::piped())
-                            .spawn()?;
+                            .spawn_checked(&policy)?;
 
                         child
                             .stdin
@@ -215,7 +216,7 @@ impl MailcapEntry {
                             .args(["-c", &cmd_string])
                             .stdin(Stdio::piped())
                             .stdout(Stdio::piped())
-                            .spawn()?;
+                            .spawn_checked(&policy)?;
 
                         child.wait_with_output()?.stdout
                     };
@@ -229,7 +230,7 @@ impl MailcapEntry {
                         .args(["-c", pager_cmd.as_ref()])
                         .stdin(Stdio::piped())
                         .stdout(Stdio::inherit())
-                        .spawn()?;
+                        .spawn_checked(&policy)?;
                     pager.stdin.as_mut().unwrap().write_all(&out)?;
                     let _output = pager.wait_with_output()?;
                     log::trace!("stdout = {}", String::from_utf8_lossy(&_output.stdout));
@@ -238,7 +239,7 @@ impl MailcapEntry {
                         .args(["-c", &cmd_string])
                         .stdin(Stdio::piped())
                         .stdout(Stdio::inherit())
-                        .spawn()?;
+                        .spawn_checked(&policy)?;
 
                     child
                         .stdin
@@ -252,7 +253,7 @@ impl MailcapEntry {
                         .args(["-c", &cmd_string])
                         .stdin(Stdio::inherit())
                         .stdout(Stdio::inherit())
-                        .spawn()?;
+                        .spawn_checked(&policy)?;
 
                     let _output = child.wait_with_output()?;
                     log::trace!("stdout = {}", String::from_utf8_lossy(&_output.stdout));
@@ -53,6 +53,9 @@ fn run_app(mut opt: Opt) -> Result<()> {
     } else {
         None
     };
+    let print_memory_report = opt.memory_report;
+    let print_startup_profile = opt.profile_startup;
+    let take_over_lock = opt.take_over_lock;
     if let Some(result) = opt.execute() {
         return result;
     }
@@ -71,6 +74,12 @@ fn run_app(mut opt: Opt) -> Result<()> {
 
     let signal_recvr = signal_handlers::notify(signals, sender.clone())?;
 
+    /* Keep other instances from using the same caches while running. */
+    let _instance_lock = if view_subcmd.is_none() {
+        Some(instance_lock::InstanceLock::acquire(take_over_lock)?)
+    } else {
+        None
+    };
     /* Create the application State. */
     let mut state;
 
@@ -101,6 +110,9 @@ fn run_app(mut opt: Opt) -> Result<()> {
         .enter_command_mode
         .clone();
     let quit_key: Key = state.context.settings.shortcuts.general.quit.clone();
+    let mut memory_report = None;
+    let mut startup_profile = None;
+    let mut first_draw = true;
 
     /* Keep track of the input mode. See UIMode for details */
     'main: loop {
@@ -113,6 +125,10 @@ fn run_app(mut opt: Opt) -> Result<()> {
                 state.rcv_event(e);
             }
             state.redraw();
+            if first_draw {
+                first_draw = false;
+                state.startup_profile.phase("first draw");
+            }
 
             /* Poll on all channels. Currently we have the input channel for stdin,
              * watching events and the signal watcher. */
@@ -152,6 +168,12 @@ fn run_app(mut opt: Opt) -> Result<()> {
                                     match k {
                                         _ if k == quit_key => {
                                             if state.can_quit_cleanly() {
+                                                if print_memory_report {
+                                                    memory_report = Some(state.memory_report());
+                                                }
+                                                if print_startup_profile {
+                                                    startup_profile = Some(state.startup_profile.report());
+                                                }
                                                 drop(state);
                                                 break 'main;
                                             } else {
@@ -229,6 +251,7 @@ fn run_app(mut opt: Opt) -> Result<()> {
                                 }
                             }
                             state.rcv_event(UIEvent::StatusEvent(StatusEvent::JobFinished(id)));
+                            state.check_startup_finished();
                         },
                     }
                 },
@@ -275,5 +298,11 @@ fn run_app(mut opt: Opt) -> Result<()> {
             }
         }
     }
+    if let Some(report) = memory_report {
+        eprintln!("{report}");
+    }
+    if let Some(report) = startup_profile {
+        eprintln!("{report}");
+    }
     Ok(())
 }
@@ -463,6 +463,20 @@ impl Component for JobManager {
                 }
                 x_offset += w + 2;
             }
+            // Show how many of the executor's threads are running a job.
+            let (busy, total) = context.main_loop_handler.job_executor.utilization();
+            let utilization = format!("{busy}/{total} threads busy");
+            if x_offset + utilization.len() < area.width() {
+                grid.write_string(
+                    &utilization,
+                    self.theme_default.fg,
+                    self.theme_default.bg,
+                    self.theme_default.attrs,
+                    area.skip_cols(area.width() - utilization.len()),
+                    None,
+                    None,
+                );
+            }
             context.dirty_areas.push_back(area);
         }
 
@@ -640,6 +654,10 @@ impl Component for JobManager {
         self.id
     }
 
+    fn view_name(&self) -> Option<&'static str> {
+        Some("jobs")
+    }
+
     fn can_quit_cleanly(&mut self, _context: &Context) -> bool {
         true
     }
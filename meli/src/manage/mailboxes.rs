@@ -736,6 +736,10 @@ impl Component for MailboxManager {
         self.id
     }
 
+    fn view_name(&self) -> Option<&'static str> {
+        Some("mailboxes")
+    }
+
     fn can_quit_cleanly(&mut self, _context: &Context) -> bool {
         true
     }
@@ -25,3 +25,6 @@ pub use mailboxes::*;
 
 pub mod jobs;
 pub use jobs::*;
+
+pub mod settings;
+pub use settings::*;
@@ -0,0 +1,600 @@
+/*
+ * meli
+ *
+ * Copyright 2024 Manos Pitsidianakis
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Settings editor tab, opened with the `manage-settings` command.
+//!
+//! Changes are applied to the running configuration immediately, the same
+//! way `reload-config` applies a changed configuration file, and can be
+//! written back to the configuration file.
+
+use melib::{error::*, log, text::Reflow};
+
+use crate::{
+    components::{prelude::*, ShortcutMap},
+    conf::edit::{self, SettingDoc},
+    terminal::UText,
+    types::NotificationType,
+    utilities::{ScrollBar, TextField},
+};
+
+#[derive(Debug)]
+struct SettingRow {
+    /// Key path, e.g. `["pager", "filter"]`, or the section name for
+    /// headings.
+    path: Vec<String>,
+    /// `path` as a dotted key, e.g. `pager.filter`.
+    key: String,
+    /// The current value, or `None` for headings and unset settings.
+    value: Option<toml::Value>,
+    heading: bool,
+}
+
+impl SettingRow {
+    fn doc(&self) -> Option<&'static SettingDoc> {
+        edit::settings_docs().get(&self.key)
+    }
+
+    fn kind(&self) -> Cow<'static, str> {
+        if let Some(kind) = self.doc().and_then(|d| d.kind.as_deref()) {
+            return kind.into();
+        }
+        match self.value {
+            Some(toml::Value::Boolean(_)) => "boolean",
+            Some(toml::Value::Integer(_)) => "integer",
+            Some(toml::Value::Float(_)) => "float",
+            Some(toml::Value::String(_)) => "String",
+            Some(toml::Value::Array(_)) => "array",
+            Some(toml::Value::Table(_)) => "table",
+            Some(toml::Value::Datetime(_)) => "datetime",
+            None => "unset",
+        }
+        .into()
+    }
+}
+
+#[derive(Debug)]
+pub struct SettingsEditor {
+    rows: Vec<SettingRow>,
+    cursor: usize,
+    /// The value being edited, if any.
+    editing: Option<TextField>,
+    /// Settings changed since the editor was opened or last written, by key
+    /// path, with their new values.
+    changes: IndexMap<Vec<String>, toml::Value>,
+    theme_default: ThemeAttribute,
+    heading_theme: ThemeAttribute,
+    highlight_theme: ThemeAttribute,
+    initialized: bool,
+    dirty: bool,
+    id: ComponentId,
+}
+
+impl std::fmt::Display for SettingsEditor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "settings")
+    }
+}
+
+impl SettingsEditor {
+    const DESCRIPTION: &'static str = "settings editor";
+
+    pub fn new(context: &Context) -> Self {
+        let mut ret = Self {
+            rows: vec![],
+            cursor: 0,
+            editing: None,
+            changes: IndexMap::default(),
+            theme_default: ThemeAttribute::default(),
+            heading_theme: ThemeAttribute::default(),
+            highlight_theme: ThemeAttribute::default(),
+            initialized: false,
+            dirty: true,
+            id: ComponentId::default(),
+        };
+        ret.set_theme(context);
+        ret
+    }
+
+    fn set_theme(&mut self, context: &Context) {
+        self.theme_default = crate::conf::value(context, "theme_default");
+        self.heading_theme = crate::conf::value(context, "widgets.list.header");
+        self.highlight_theme = if context.settings.terminal.use_color() {
+            crate::conf::value(context, "highlight")
+        } else {
+            ThemeAttribute {
+                attrs: Attr::REVERSE,
+                ..ThemeAttribute::default()
+            }
+        };
+    }
+
+    fn update_rows(&mut self, context: &Context) {
+        self.initialized = true;
+        self.rows.clear();
+        let docs = edit::settings_docs();
+        for section in edit::SECTIONS {
+            self.rows.push(SettingRow {
+                path: vec![section.to_string()],
+                key: section.to_string(),
+                value: None,
+                heading: true,
+            });
+            let mut values = match context.settings.section_value(section) {
+                Ok(value) => edit::flatten(&value)
+                    .into_iter()
+                    .map(|(mut path, v)| {
+                        path.insert(0, section.to_string());
+                        (path, Some(v))
+                    })
+                    .collect::<IndexMap<Vec<String>, Option<toml::Value>>>(),
+                Err(err) => {
+                    log::error!("Could not list {section} settings: {err}");
+                    IndexMap::default()
+                }
+            };
+            // Unset optional settings are not serialized, but are documented.
+            for path in docs.keys().filter_map(|k| edit::split_key(k).ok()) {
+                if path.first().map(String::as_str) == Some(section)
+                    && !values.keys().any(|k| k.starts_with(&path))
+                {
+                    values.insert(path, None);
+                }
+            }
+            values.sort_keys();
+            self.rows
+                .extend(values.into_iter().map(|(path, value)| SettingRow {
+                    key: edit::join_key(&path),
+                    path,
+                    value,
+                    heading: false,
+                }));
+        }
+        self.cursor = self.cursor.min(self.rows.len().saturating_sub(1));
+    }
+
+    /// Apply `value` to the running configuration and notify all components
+    /// like a configuration reload does.
+    fn apply(&mut self, path: &[String], value: toml::Value, context: &mut Context) -> Result<()> {
+        let mut new_settings = context.settings.clone();
+        new_settings.set_key(path, value.clone())?;
+        let old_settings = std::mem::replace(&mut context.settings, new_settings);
+        context
+            .replies
+            .push_back(UIEvent::ConfigReload { old_settings });
+        context.replies.push_back(UIEvent::Resize);
+        self.changes.insert(path.to_vec(), value);
+        self.initialized = false;
+        Ok(())
+    }
+
+    /// Write the changed settings to the configuration file.
+    ///
+    /// The new text is written to a temporary file next to the configuration
+    /// file, which then replaces it, so that the configuration file is never
+    /// left partially written.
+    fn write(&mut self) -> Result<std::path::PathBuf> {
+        use std::{io::Write, os::unix::fs::OpenOptionsExt};
+
+        let path = crate::conf::get_config_file()?;
+        // Replace the file a symbolic link points to, not the link.
+        let path = std::fs::canonicalize(&path).unwrap_or(path);
+        let mut text = std::fs::read_to_string(&path)
+            .chain_err_summary(|| format!("Could not read {}", path.display()))?;
+        for (key_path, value) in &self.changes {
+            text = edit::update_toml_text(&text, key_path, value)?;
+        }
+        let permissions = std::fs::metadata(&path)
+            .chain_err_summary(|| format!("Could not read {}", path.display()))?
+            .permissions();
+        let mut tmp_name = std::ffi::OsString::from(".");
+        tmp_name.push(path.file_name().unwrap_or_default());
+        tmp_name.push(format!(".{}.tmp", std::process::id()));
+        let tmp_path = path.with_file_name(tmp_name);
+        let write_file = || -> std::io::Result<()> {
+            // The file may contain passwords, so it is only readable by the user
+            // until its permissions are copied from the configuration file.
+            let mut file = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(0o600)
+                .open(&tmp_path)?;
+            file.write_all(text.as_bytes())?;
+            file.set_permissions(permissions)?;
+            file.sync_all()?;
+            std::fs::rename(&tmp_path, &path)
+        };
+        if let Err(err) = write_file() {
+            _ = std::fs::remove_file(&tmp_path);
+            return Err(err).chain_err_summary(|| format!("Could not write {}", path.display()));
+        }
+        self.changes.clear();
+        Ok(path)
+    }
+
+    fn notify_error(err: Error, context: &mut Context) {
+        context.replies.push_back(UIEvent::Notification {
+            title: None,
+            source: None,
+            body: err.to_string().into(),
+            kind: Some(NotificationType::Error(err.kind)),
+        });
+    }
+
+    fn open_entry(&mut self, context: &mut Context) {
+        let Some(row) = self.rows.get(self.cursor).filter(|r| !r.heading) else {
+            return;
+        };
+        if let Some(toml::Value::Boolean(value)) = row.value {
+            let path = row.path.clone();
+            if let Err(err) = self.apply(&path, toml::Value::Boolean(!value), context) {
+                Self::notify_error(err, context);
+            }
+        } else {
+            let mut text = UText::new(
+                row.value
+                    .as_ref()
+                    .map(|v| v.to_string())
+                    .unwrap_or_default(),
+            );
+            text.set_cursor(text.as_str().len());
+            self.editing = Some(TextField::new(text, None));
+            context
+                .replies
+                .push_back(UIEvent::ChangeMode(UIMode::Insert));
+        }
+        self.set_dirty(true);
+    }
+
+    fn draw_doc(&self, grid: &mut CellBuffer, area: Area) {
+        grid.clear_area(area, self.theme_default);
+        let Some(row) = self.rows.get(self.cursor).filter(|r| !r.heading) else {
+            return;
+        };
+        grid.write_string(
+            &format!("{} ({})", row.key, row.kind()),
+            self.theme_default.fg,
+            self.theme_default.bg,
+            self.theme_default.attrs | Attr::BOLD,
+            area.nth_row(0).skip_cols(1),
+            None,
+            None,
+        );
+        let text = row
+            .doc()
+            .map(|d| d.text.as_str())
+            .unwrap_or("Not documented in meli.conf(5).");
+        let lines = text.split_lines_reflow(Reflow::All, Some(area.width().saturating_sub(2)));
+        for (i, line) in lines
+            .iter()
+            .take(area.height().saturating_sub(1))
+            .enumerate()
+        {
+            grid.write_string(
+                line,
+                self.theme_default.fg,
+                self.theme_default.bg,
+                self.theme_default.attrs,
+                area.nth_row(i + 1).skip_cols(1),
+                None,
+                None,
+            );
+        }
+    }
+}
+
+impl Component for SettingsEditor {
+    fn draw(&mut self, grid: &mut CellBuffer, area: Area, context: &mut Context) {
+        if !self.is_dirty() {
+            return;
+        }
+        if !self.initialized {
+            self.update_rows(context);
+        }
+        self.dirty = false;
+        grid.clear_area(area, self.theme_default);
+        let doc_height = (area.height() / 3).clamp(1, 10);
+        let rows = area.height().saturating_sub(doc_height + 1);
+        if rows == 0 {
+            return;
+        }
+        let list_area = area.take_rows(rows);
+        let key_width = self
+            .rows
+            .iter()
+            .filter(|r| !r.heading)
+            .map(|r| r.key.len())
+            .max()
+            .unwrap_or(0);
+        let top_idx = (self.cursor / rows) * rows;
+        for (i, row) in self.rows.iter().enumerate().skip(top_idx).take(rows) {
+            let row_area = list_area.nth_row(i - top_idx);
+            let mut theme = if i == self.cursor && self.editing.is_none() {
+                self.highlight_theme
+            } else if row.heading {
+                self.heading_theme
+            } else {
+                self.theme_default
+            };
+            if row.heading || self.changes.contains_key(&row.path) {
+                theme.attrs |= Attr::BOLD;
+            }
+            grid.clear_area(row_area, theme);
+            if row.heading {
+                grid.write_string(
+                    &format!("[{}]", row.key),
+                    theme.fg,
+                    theme.bg,
+                    theme.attrs,
+                    row_area.skip_cols(1),
+                    None,
+                    None,
+                );
+                continue;
+            }
+            grid.write_string(
+                &format!("{:key_width$} = ", row.key),
+                theme.fg,
+                theme.bg,
+                theme.attrs,
+                row_area.skip_cols(1),
+                None,
+                None,
+            );
+            let value_area = row_area.skip_cols(key_width + 4);
+            match (i == self.cursor, self.editing.as_mut(), row.value.as_ref()) {
+                (true, Some(field), _) => {
+                    field.draw(grid, value_area, context);
+                    field.draw_cursor(grid, value_area, value_area, context);
+                }
+                (_, _, Some(value)) => {
+                    grid.write_string(
+                        &value.to_string(),
+                        theme.fg,
+                        theme.bg,
+                        theme.attrs,
+                        value_area,
+                        None,
+                        None,
+                    );
+                }
+                (_, _, None) => {
+                    grid.write_string(
+                        "(unset)",
+                        theme.fg,
+                        theme.bg,
+                        theme.attrs | Attr::DIM,
+                        value_area,
+                        None,
+                        None,
+                    );
+                }
+            }
+        }
+        if self.rows.len() > rows {
+            ScrollBar::default().set_show_arrows(true).draw(
+                grid,
+                list_area.nth_col(list_area.width().saturating_sub(1)),
+                context,
+                self.cursor,
+                rows,
+                self.rows.len(),
+            );
+        }
+        let divider = if grid.ascii_drawing { "-" } else { "─" };
+        grid.write_string(
+            &divider.repeat(area.width()),
+            self.theme_default.fg,
+            self.theme_default.bg,
+            self.theme_default.attrs,
+            area.nth_row(rows),
+            None,
+            None,
+        );
+        self.draw_doc(grid, area.skip_rows(rows + 1));
+        context.dirty_areas.push_back(area);
+    }
+
+    fn process_event(&mut self, event: &mut UIEvent, context: &mut Context) -> bool {
+        if let Some(field) = self.editing.as_mut() {
+            match event {
+                UIEvent::InsertInput(Key::Char('\n')) => {
+                    let value = edit::parse_value(field.as_str().trim());
+                    self.editing = None;
+                    let path = self.rows[self.cursor].path.clone();
+                    if let Err(err) = self.apply(&path, value, context) {
+                        Self::notify_error(err, context);
+                    }
+                    context
+                        .replies
+                        .push_back(UIEvent::ChangeMode(UIMode::Normal));
+                    self.set_dirty(true);
+                    return true;
+                }
+                UIEvent::InsertInput(_) => {
+                    field.process_event(event, context);
+                    self.set_dirty(true);
+                    return true;
+                }
+                UIEvent::ChangeMode(UIMode::Normal) => {
+                    self.editing = None;
+                    self.set_dirty(true);
+                    return false;
+                }
+                _ => {}
+            }
+        }
+        let shortcuts = self.shortcuts(context);
+        match event {
+            UIEvent::ConfigReload { old_settings: _ } => {
+                self.set_theme(context);
+                self.initialized = false;
+                self.set_dirty(true);
+            }
+            UIEvent::Resize => {
+                self.set_dirty(true);
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["scroll_up"]) =>
+            {
+                self.cursor = self.cursor.saturating_sub(1);
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["scroll_down"]) =>
+            {
+                if self.cursor + 1 < self.rows.len() {
+                    self.cursor += 1;
+                }
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["next_page"]) =>
+            {
+                // Move to the next section.
+                if let Some(pos) = self
+                    .rows
+                    .iter()
+                    .skip(self.cursor + 1)
+                    .position(|r| r.heading)
+                {
+                    self.cursor += pos + 1;
+                }
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["prev_page"]) =>
+            {
+                // Move to the previous section.
+                self.cursor = self.rows[..self.cursor]
+                    .iter()
+                    .rposition(|r| r.heading)
+                    .unwrap_or(0);
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["home_page"]) =>
+            {
+                self.cursor = 0;
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["end_page"]) =>
+            {
+                self.cursor = self.rows.len().saturating_sub(1);
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["open_entry"]) =>
+            {
+                self.open_entry(context);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Self::DESCRIPTION]["write changes"]) =>
+            {
+                if self.changes.is_empty() {
+                    context
+                        .replies
+                        .push_back(UIEvent::StatusEvent(StatusEvent::DisplayMessage(
+                            "No changes to write.".to_string(),
+                        )));
+                    return true;
+                }
+                match self.write() {
+                    Ok(path) => {
+                        context.replies.push_back(UIEvent::StatusEvent(
+                            StatusEvent::DisplayMessage(format!(
+                                "Wrote changes to {}",
+                                path.display()
+                            )),
+                        ));
+                    }
+                    Err(err) => Self::notify_error(err, context),
+                }
+                self.set_dirty(true);
+                return true;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, value: bool) {
+        self.dirty = value;
+    }
+
+    fn kill(&mut self, uuid: ComponentId, context: &mut Context) {
+        debug_assert!(uuid == self.id);
+        context
+            .replies
+            .push_back(UIEvent::Action(crate::command::Action::Tab(
+                crate::command::TabAction::Kill(uuid),
+            )));
+    }
+
+    fn shortcuts(&self, context: &Context) -> ShortcutMaps {
+        let mut map = ShortcutMaps::default();
+
+        map.insert(
+            Shortcuts::GENERAL,
+            context.settings.shortcuts.general.key_values(),
+        );
+        let mut our_map = ShortcutMap::default();
+        our_map.insert("write changes", Key::Char('w'));
+        map.insert(Self::DESCRIPTION, our_map);
+
+        map
+    }
+
+    fn id(&self) -> ComponentId {
+        self.id
+    }
+
+    fn view_name(&self) -> Option<&'static str> {
+        Some("settings")
+    }
+
+    fn can_quit_cleanly(&mut self, _context: &Context) -> bool {
+        true
+    }
+
+    fn status(&self, _context: &Context) -> String {
+        if self.changes.is_empty() {
+            "Press Enter to edit a setting".to_string()
+        } else {
+            format!(
+                "{} unsaved change{}, press `w` to write them to the configuration file",
+                self.changes.len(),
+                if self.changes.len() == 1 { "" } else { "s" }
+            )
+        }
+    }
+}
@@ -21,13 +21,14 @@
 // SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
 
 use std::{
+    borrow::Cow,
     env, fs,
-    io::Read,
+    io::{Read, Write},
     path::{Path, PathBuf},
     sync::Arc,
 };
 
-use flate2::bufread::GzDecoder;
+use flate2::{bufread::GzDecoder, Compression, GzBuilder};
 use melib::{log, ShellExpandTrait};
 
 use crate::{Error, Result};
@@ -45,6 +46,9 @@ pub const POSSIBLE_VALUES: &[&str] = &[
     "meli-themes.5",
     "guide",
     "meli.7",
+    "commands",
+    "meli-commands",
+    "meli-commands.1",
 ];
 
 pub fn parse_manpage(src: &str) -> Result<ManPages> {
@@ -54,6 +58,7 @@ pub fn parse_manpage(src: &str) -> Result<ManPages> {
         "meli.conf" | "meli.conf.5" | "conf" | "config" | "configuration" => Ok(ManPages::Conf),
         "meli-themes" | "meli-themes.5" | "themes" | "theming" | "theme" => Ok(ManPages::Themes),
         "meli.conf.examples" | "meli.conf.examples.5" => Ok(ManPages::ConfExamples),
+        "meli-commands" | "meli-commands.1" | "commands" => Ok(ManPages::Commands),
         _ => Err(Error::new(format!("Invalid documentation page: {src}",))),
     }
 }
@@ -71,6 +76,9 @@ pub enum ManPages {
     Guide = 3,
     /// meli.conf.examples(5)
     ConfExamples = 4,
+    /// meli-commands(1), generated from the command line arguments and
+    /// command definitions of this binary.
+    Commands = 5,
 }
 
 impl std::fmt::Display for ManPages {
@@ -84,6 +92,7 @@ impl std::fmt::Display for ManPages {
                 Self::ConfExamples => "meli.conf.examples.5",
                 Self::Themes => "meli-themes.5",
                 Self::Guide => "meli.7",
+                Self::Commands => "meli-commands.1",
             }
         )
     }
@@ -142,6 +151,7 @@ impl ManPages {
 
         for (p, dir) in [
             (Self::Main, "man1"),
+            (Self::Commands, "man1"),
             (Self::Conf, "man5"),
             (Self::ConfExamples, "man5"),
             (Self::Themes, "man5"),
@@ -167,19 +177,35 @@ impl ManPages {
         Ok(path)
     }
 
-    pub fn mdoc_gz(self) -> &'static [u8] {
-        Self::MANPAGES_MDOC[self as usize]
+    pub fn mdoc_gz(self) -> Result<Cow<'static, [u8]>> {
+        if self == Self::Commands {
+            return Ok(Cow::Owned(Self::gzip(self.read(true)?.as_bytes())?));
+        }
+        Ok(Cow::Borrowed(Self::MANPAGES_MDOC[self as usize]))
     }
 
-    pub fn text_gz(self) -> &'static [u8] {
-        Self::MANPAGES[self as usize]
+    pub fn text_gz(self) -> Result<Cow<'static, [u8]>> {
+        if self == Self::Commands {
+            return Ok(Cow::Owned(Self::gzip(self.read(false)?.as_bytes())?));
+        }
+        Ok(Cow::Borrowed(Self::MANPAGES[self as usize]))
     }
 
     pub fn read(self, source: bool) -> Result<String> {
+        if self == Self::Commands {
+            let mdoc = Self::commands_mdoc();
+            if source {
+                return Ok(mdoc);
+            }
+            return Ok(Self::render(&mdoc).unwrap_or_else(|err| {
+                log::warn!("Could not render {self} with mandoc(1) or man(1): {err}");
+                mdoc
+            }));
+        }
         let mut gz = GzDecoder::new(if source {
-            self.mdoc_gz()
+            Self::MANPAGES_MDOC[self as usize]
         } else {
-            self.text_gz()
+            Self::MANPAGES[self as usize]
         });
         let mut v = String::with_capacity(
             str::parse::<usize>(unsafe {
@@ -192,6 +218,94 @@ impl ManPages {
         Ok(v)
     }
 
+    /// Compresses a page the same way the build script compresses the
+    /// embedded ones, with its size in the comment header.
+    fn gzip(bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut gz = GzBuilder::new()
+            .comment(bytes.len().to_string().into_bytes())
+            .write(Vec::with_capacity(bytes.len()), Compression::default());
+        gz.write_all(bytes)?;
+        Ok(gz.finish()?)
+    }
+
+    /// Renders mdoc source to text with `mandoc(1)`, or `man(1)` if it isn't
+    /// available.
+    fn render(source: &str) -> Result<String> {
+        use std::process::{Command, Stdio};
+
+        let mut child = Command::new("mandoc")
+            .args(["-T", "utf8"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .or_else(|_| {
+                Command::new("man")
+                    .args(["-l", "-"])
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .spawn()
+            })?;
+        child.stdin.take().unwrap().write_all(source.as_bytes())?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(Error::new(format!(
+                "Renderer exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Generates the mdoc source of meli-commands(1) from the command line
+    /// argument parser and the command mode definitions, so that it always
+    /// documents exactly what this binary accepts.
+    pub fn commands_mdoc() -> String {
+        use std::{collections::HashSet, fmt::Write};
+
+        use structopt::StructOpt;
+
+        /// Escapes text so that it is not interpreted as roff requests or
+        /// escape sequences.
+        fn escape(text: &str) -> String {
+            let text = text.replace('\\', "\\e");
+            if text.starts_with(['.', '\'']) {
+                format!("\\&{text}")
+            } else {
+                text
+            }
+        }
+
+        let mut ret = String::new();
+        let _ = writeln!(
+            ret,
+            ".Dd $Mdocdate$\n.Dt MELI-COMMANDS 1\n.Os meli {}\n.Sh NAME\n.Nm meli-commands\n.Nd              command line and command mode synopsis of meli\n.Sh DESCRIPTION\nThis page is              generated from the definitions\n.Xr meli 1\nuses to parse its arguments and              commands.\nSee\n.Xr meli 1\nfor a detailed description of each.",
+            env!("CARGO_PKG_VERSION")
+        );
+
+        ret.push_str(".Sh COMMAND LINE\n.Bd -literal\n");
+        let mut help = vec![];
+        let _ = crate::args::Opt::clap().write_long_help(&mut help);
+        for line in String::from_utf8_lossy(&help).lines() {
+            let _ = writeln!(ret, "{}", escape(line));
+        }
+        ret.push_str(".Ed\n");
+
+        ret.push_str(
+            ".Sh COMMANDS\nThe following commands can be issued in command mode.\n.Bl -tag -width              4n\n",
+        );
+        let mut seen = HashSet::new();
+        for (_, desc, tokens, _) in crate::command::COMMAND_COMPLETION {
+            let synopsis = tokens.to_string();
+            if synopsis.is_empty() || !seen.insert((synopsis.clone(), *desc)) {
+                continue;
+            }
+            let _ = writeln!(ret, ".It Xo\n{}\n.Xc\n{}", escape(&synopsis), escape(desc));
+        }
+        ret.push_str(".El\n");
+        ret
+    }
+
     /// Helper function to remove backspace markup from mandoc output.
     pub fn remove_markup(input: &str) -> Result<String> {
         use std::{
@@ -264,7 +264,7 @@ impl NotificationRouter {
                 .stdin(Stdio::piped())
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
-                .spawn()
+                .spawn_checked(&context.command_policy(None))
                 .chain_err_details(|| "Could not run notification script")?;
             context
                 .children
@@ -0,0 +1,742 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Plugins: external processes that extend `meli`.
+//!
+//! Plugins are declared in the `plugins` section of the configuration and
+//! started along with `meli`. `meli` writes JSON-RPC 2.0 messages to the
+//! standard input of each plugin and reads its replies from its standard
+//! output, one message per line. Lines a plugin writes to its standard error
+//! are logged.
+//!
+//! The methods a plugin is called with depend on its
+//! [`hooks`](crate::conf::PluginConf::hooks):
+//!
+//! - `message_received` is a notification sent for each new e-mail, with the
+//!   `account`, the `mailbox` path and the [`EnvelopeInfo`] of the e-mail as
+//!   params.
+//! - `pre_send` is a request sent before a draft is sent, with the `account`
+//!   and the `draft` text as params. The result is either `null` or an object
+//!   whose `draft` string replaces the draft. An error is shown to the user
+//!   like the errors of other compose hooks. The requests are sent from a job,
+//!   and the composer waits for them before asking to send the draft.
+//! - `listing_column` is a request sent for each e-mail the compact listing
+//!   shows, with the same params as `message_received`. The result is a
+//!   string shown after the subject.
+//!
+//! The `command` request is sent when the user enters one of the plugin's
+//! [`commands`](crate::conf::PluginConf::commands) in command mode, with the
+//! `name` of the command and the rest of the line as `args`. A string result
+//! is shown to the user.
+//!
+//! Messages are written to a plugin by a dedicated thread, so that a plugin
+//! that does not read its standard input never blocks `meli`.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    io::{BufRead, BufReader, Write},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use crossbeam::channel::Sender;
+use futures::channel::oneshot;
+use indexmap::IndexMap;
+use melib::{
+    error::{Error, ErrorKind, Result},
+    log,
+    text::{TextProcessing, Truncate},
+    utils::futures::timeout,
+    Envelope, EnvelopeHash, MailboxHash, RefreshEventKind, UnixTimestamp,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    conf::{PluginConf, PluginHook},
+    types::{CommandPolicy, CommandPolicyExt, StatusEvent, ThreadEvent, UIEvent},
+    Account,
+};
+
+/// Version of the JSON-RPC protocol.
+const JSONRPC_VERSION: &str = "2.0";
+
+/// The fields of an e-mail sent to plugins.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EnvelopeInfo {
+    pub hash: String,
+    pub message_id: String,
+    pub subject: String,
+    pub from: Vec<String>,
+    pub to: Vec<String>,
+    pub date: UnixTimestamp,
+    pub seen: bool,
+}
+
+impl EnvelopeInfo {
+    pub fn new(envelope: &Envelope) -> Self {
+        Self {
+            hash: envelope.hash().to_string(),
+            message_id: envelope.message_id().to_string(),
+            subject: envelope.subject().to_string(),
+            from: envelope.from().iter().map(|a| a.to_string()).collect(),
+            to: envelope.to().iter().map(|a| a.to_string()).collect(),
+            date: envelope.date(),
+            seen: envelope.is_seen(),
+        }
+    }
+}
+
+/// Params of `message_received` and `listing_column`.
+#[derive(Debug, Serialize)]
+struct EnvelopeParams<'a> {
+    account: &'a str,
+    mailbox: &'a str,
+    envelope: EnvelopeInfo,
+}
+
+#[derive(Debug, Serialize)]
+struct PreSendParams<'a> {
+    account: &'a str,
+    draft: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct PreSendResult {
+    #[serde(default)]
+    draft: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CommandParams<'a> {
+    name: &'a str,
+    args: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct Request<'a, P> {
+    jsonrpc: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<u64>,
+    method: &'a str,
+    params: P,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    #[serde(default)]
+    id: Option<u64>,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+/// A JSON-RPC error object.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+type Reply = std::result::Result<Value, RpcError>;
+
+/// What to do with the reply to a request.
+enum Pending {
+    /// Send it to a future that is waiting for it.
+    Wait(oneshot::Sender<Reply>),
+    /// Handle it in the thread that reads the plugin's output.
+    Callback(Box<dyn FnOnce(Reply) + Send>),
+}
+
+/// The means to send messages to a plugin, which can be shared with jobs.
+#[derive(Clone)]
+struct Connection {
+    name: Arc<str>,
+    /// Lines for the thread that writes to the plugin's standard input.
+    stdin: mpsc::Sender<String>,
+    next_id: Arc<AtomicU64>,
+    pending: Arc<Mutex<HashMap<u64, Pending>>>,
+}
+
+impl Connection {
+    fn write<P: Serialize>(&self, id: Option<u64>, method: &str, params: P) -> Result<()> {
+        let mut line = serde_json::to_string(&Request {
+            jsonrpc: JSONRPC_VERSION,
+            id,
+            method,
+            params,
+        })?;
+        line.push('\n');
+        self.stdin.send(line).map_err(|_| {
+            Error::new(format!(
+                "Could not write to plugin {}: it stopped reading its input.",
+                self.name
+            ))
+        })
+    }
+
+    fn notify<P: Serialize>(&self, method: &str, params: P) -> Result<()> {
+        self.write(None, method, params)
+    }
+
+    fn request<P: Serialize>(&self, method: &str, params: P, pending: Pending) -> Result<()> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.pending.lock().unwrap().insert(id, pending);
+        if let Err(err) = self.write(Some(id), method, params) {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Send a request and return a future that waits for its result for at
+    /// most `duration`.
+    fn call<P: Serialize>(
+        &self,
+        method: &str,
+        params: P,
+        duration: Duration,
+    ) -> impl Future<Output = Result<Value>> + Send + 'static {
+        let (tx, rx) = oneshot::channel();
+        let sent = self.request(method, params, Pending::Wait(tx));
+        let name = self.name.clone();
+        let method = method.to_string();
+        async move {
+            sent?;
+            match timeout(Some(duration), rx).await {
+                Ok(Ok(Ok(result))) => Ok(result),
+                Ok(Ok(Err(err))) => Err(Error::new(format!(
+                    "Plugin {name} returned an error for {method}: {}",
+                    err.message
+                ))),
+                Err(_) => Err(Error::new(format!(
+                    "Plugin {name} did not reply to {method} in time."
+                ))
+                .set_kind(ErrorKind::TimedOut)),
+                Ok(Err(oneshot::Canceled)) => Err(Error::new(format!(
+                    "Plugin {name} exited without replying to {method}."
+                ))),
+            }
+        }
+    }
+}
+
+/// A running plugin process.
+struct Plugin {
+    name: String,
+    conf: PluginConf,
+    child: Child,
+    connection: Connection,
+    /// Listing column values, `None` while the request is pending.
+    columns: Arc<Mutex<HashMap<EnvelopeHash, Option<String>>>>,
+    sender: Sender<ThreadEvent>,
+}
+
+impl Plugin {
+    fn spawn(
+        name: &str,
+        conf: &PluginConf,
+        sender: Sender<ThreadEvent>,
+        policy: &CommandPolicy,
+    ) -> Result<Self> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&conf.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn_checked(policy)
+            .map_err(|err| {
+                Error::new(format!(
+                    "Could not start plugin {name}: `{}` failed to execute.",
+                    conf.command
+                ))
+                .set_source(Some(Arc::new(err)))
+            })?;
+        let mut stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let stderr = child.stderr.take().unwrap();
+        let pending: Arc<Mutex<HashMap<u64, Pending>>> = Arc::default();
+        {
+            let name = name.to_string();
+            let pending = pending.clone();
+            thread::Builder::new()
+                .name(format!("plugin {name}"))
+                .spawn(move || {
+                    for line in BufReader::new(stdout).lines() {
+                        let Ok(line) = line else {
+                            break;
+                        };
+                        if line.trim().is_empty() {
+                            continue;
+                        }
+                        let response: Response = match serde_json::from_str(&line) {
+                            Ok(response) => response,
+                            Err(err) => {
+                                log::warn!("Plugin {name} sent an invalid message: {err}");
+                                continue;
+                            }
+                        };
+                        let Some(pending) = response
+                            .id
+                            .and_then(|id| pending.lock().unwrap().remove(&id))
+                        else {
+                            log::debug!("Plugin {name} replied to an unknown request: {line}");
+                            continue;
+                        };
+                        let reply = match response.error {
+                            Some(err) => Err(err),
+                            None => Ok(response.result.unwrap_or(Value::Null)),
+                        };
+                        match pending {
+                            Pending::Wait(tx) => {
+                                _ = tx.send(reply);
+                            }
+                            Pending::Callback(f) => f(reply),
+                        }
+                    }
+                    log::warn!("Plugin {name} closed its standard output.");
+                    pending.lock().unwrap().clear();
+                })?;
+        }
+        let (stdin_tx, stdin_rx) = mpsc::channel::<String>();
+        {
+            let name = name.to_string();
+            let pending = pending.clone();
+            thread::Builder::new()
+                .name(format!("plugin {name} stdin"))
+                .spawn(move || {
+                    for line in stdin_rx {
+                        if let Err(err) = stdin
+                            .write_all(line.as_bytes())
+                            .and_then(|()| stdin.flush())
+                        {
+                            log::warn!("Could not write to plugin {name}: {err}");
+                            break;
+                        }
+                    }
+                    // Requests that were not written are never replied to.
+                    pending.lock().unwrap().clear();
+                })?;
+        }
+        {
+            let name = name.to_string();
+            thread::Builder::new()
+                .name(format!("plugin {name} stderr"))
+                .spawn(move || {
+                    for line in BufReader::new(stderr).lines().map_while(|l| l.ok()) {
+                        log::info!("Plugin {name}: {line}");
+                    }
+                })?;
+        }
+        Ok(Self {
+            name: name.to_string(),
+            conf: conf.clone(),
+            child,
+            connection: Connection {
+                name: name.into(),
+                stdin: stdin_tx,
+                next_id: Arc::new(AtomicU64::new(0)),
+                pending,
+            },
+            columns: Arc::default(),
+            sender,
+        })
+    }
+
+    fn has_hook(&self, hook: PluginHook) -> bool {
+        self.conf.hooks.contains(&hook)
+    }
+}
+
+impl Drop for Plugin {
+    fn drop(&mut self) {
+        _ = self.child.kill();
+        _ = self.child.wait();
+    }
+}
+
+/// The plugins declared in the configuration.
+#[derive(Default)]
+pub struct PluginManager {
+    plugins: IndexMap<String, Plugin>,
+}
+
+impl std::fmt::Debug for PluginManager {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_list().entries(self.plugins.keys()).finish()
+    }
+}
+
+impl PluginManager {
+    /// Start the plugins of `settings`. Plugins that can't be started are
+    /// reported with a notification and skipped.
+    pub fn new(
+        settings: &IndexMap<String, PluginConf>,
+        sender: Sender<ThreadEvent>,
+        policy: &CommandPolicy,
+    ) -> Self {
+        let mut plugins = IndexMap::default();
+        for (name, conf) in settings {
+            match Plugin::spawn(name, conf, sender.clone(), policy) {
+                Ok(plugin) => {
+                    plugins.insert(name.clone(), plugin);
+                }
+                Err(err) => {
+                    log::error!("{err}");
+                    _ = sender.send(ThreadEvent::UIEvent(UIEvent::Notification {
+                        title: Some("Could not start plugin".into()),
+                        body: err.to_string().into(),
+                        source: Some(err),
+                        kind: Some(crate::types::NotificationType::Error(ErrorKind::External)),
+                    }));
+                }
+            }
+        }
+        Self { plugins }
+    }
+
+    /// Names of the plugins that have `hook`.
+    pub fn with_hook(&self, hook: PluginHook) -> impl Iterator<Item = &str> + '_ {
+        self.plugins
+            .values()
+            .filter(move |p| p.has_hook(hook))
+            .map(|p| p.name.as_str())
+    }
+
+    /// Send `message_received` for the e-mails `events` add to
+    /// `mailbox_hash`. Must be called before `account` processes the events,
+    /// so that known e-mails can be told apart.
+    pub fn message_received(
+        &self,
+        account: &Account,
+        mailbox_hash: MailboxHash,
+        events: &[RefreshEventKind],
+    ) {
+        if self.with_hook(PluginHook::MessageReceived).next().is_none() {
+            return;
+        }
+        let Some(mailbox) = account.mailbox_entries.get(&mailbox_hash) else {
+            return;
+        };
+        for event in events {
+            let RefreshEventKind::Create(envelope) = event else {
+                continue;
+            };
+            if account.contains_key(envelope.hash()) {
+                continue;
+            }
+            for plugin in self
+                .plugins
+                .values()
+                .filter(|p| p.has_hook(PluginHook::MessageReceived))
+            {
+                if let Err(err) = plugin.connection.notify(
+                    "message_received",
+                    EnvelopeParams {
+                        account: account.name(),
+                        mailbox: mailbox.ref_mailbox.path(),
+                        envelope: EnvelopeInfo::new(envelope),
+                    },
+                ) {
+                    log::error!("{err}");
+                }
+            }
+        }
+    }
+
+    /// Let the plugins `names` check or change the `draft` text before it is
+    /// sent, one after the other.
+    ///
+    /// Returns a future to run as a job, which resolves to the new text of the
+    /// draft if a plugin changed it.
+    pub fn pre_send(
+        &self,
+        names: &[String],
+        account: &str,
+        draft: String,
+    ) -> impl Future<Output = Result<Option<String>>> + Send + 'static {
+        let plugins = names
+            .iter()
+            .filter_map(|name| self.plugins.get(name))
+            .map(|p| (p.connection.clone(), Duration::from_secs(p.conf.timeout)))
+            .collect::<Vec<_>>();
+        let account = account.to_string();
+        async move {
+            let mut new_draft = None;
+            for (connection, duration) in plugins {
+                let result = connection
+                    .call(
+                        "pre_send",
+                        PreSendParams {
+                            account: &account,
+                            draft: new_draft.as_ref().unwrap_or(&draft),
+                        },
+                        duration,
+                    )
+                    .await?;
+                let result: Option<PreSendResult> =
+                    serde_json::from_value(result).map_err(|err| {
+                        Error::new(format!(
+                            "Plugin {} returned an invalid result for pre_send: {err}",
+                            connection.name
+                        ))
+                    })?;
+                if let Some(text) = result.and_then(|r| r.draft) {
+                    new_draft = Some(text);
+                }
+            }
+            Ok(new_draft)
+        }
+    }
+
+    /// Width to reserve for the listing columns of plugins, including the
+    /// space before each one.
+    pub fn listing_columns_width(&self) -> usize {
+        self.plugins
+            .values()
+            .filter(|p| p.has_hook(PluginHook::ListingColumn))
+            .map(|p| p.conf.column_width + 1)
+            .sum()
+    }
+
+    /// Return the listing column values of `env_hash`, separated by spaces.
+    ///
+    /// Values are requested in the background the first time they are needed;
+    /// an [`UIEvent::EnvelopeUpdate`] is sent when one arrives.
+    pub fn listing_columns(
+        &self,
+        account: &Account,
+        mailbox_hash: MailboxHash,
+        env_hash: EnvelopeHash,
+    ) -> Option<String> {
+        let mut ret = String::new();
+        for plugin in self
+            .plugins
+            .values()
+            .filter(|p| p.has_hook(PluginHook::ListingColumn))
+        {
+            let mut columns = plugin.columns.lock().unwrap();
+            match columns.get(&env_hash) {
+                Some(Some(value)) => {
+                    if !value.is_empty() {
+                        if !ret.is_empty() {
+                            ret.push(' ');
+                        }
+                        ret.push_str(value);
+                    }
+                    continue;
+                }
+                Some(None) => continue,
+                None => {}
+            }
+            if !account.contains_key(env_hash) {
+                continue;
+            }
+            let mailbox = account
+                .mailbox_entries
+                .get(&mailbox_hash)
+                .map(|m| m.ref_mailbox.path())
+                .unwrap_or_default();
+            let envelope = EnvelopeInfo::new(&account.collection.get_env(env_hash));
+            columns.insert(env_hash, None);
+            drop(columns);
+            let callback = {
+                let name = plugin.name.clone();
+                let width = plugin.conf.column_width;
+                let columns = plugin.columns.clone();
+                let sender = plugin.sender.clone();
+                move |reply: Reply| {
+                    let mut value = match reply {
+                        Ok(Value::String(value)) => value,
+                        Ok(Value::Null) => String::new(),
+                        Ok(other) => other.to_string(),
+                        Err(err) => {
+                            log::debug!(
+                                "Plugin {name} returned an error for listing_column: {}",
+                                err.message
+                            );
+                            String::new()
+                        }
+                    };
+                    value = value.replace(['\n', '\r'], " ");
+                    if value.grapheme_len() > width {
+                        value.truncate_at_boundary(width);
+                    }
+                    columns.lock().unwrap().insert(env_hash, Some(value));
+                    _ = sender.send(ThreadEvent::UIEvent(UIEvent::EnvelopeUpdate(env_hash)));
+                }
+            };
+            if let Err(err) = plugin.connection.request(
+                "listing_column",
+                EnvelopeParams {
+                    account: account.name(),
+                    mailbox,
+                    envelope,
+                },
+                Pending::Callback(Box::new(callback)),
+            ) {
+                log::error!("{err}");
+                plugin
+                    .columns
+                    .lock()
+                    .unwrap()
+                    .insert(env_hash, Some(String::new()));
+            }
+        }
+        Some(ret).filter(|r| !r.is_empty())
+    }
+
+    /// Send the command mode command `input` to the plugin that handles it.
+    ///
+    /// Returns `None` if no plugin handles it.
+    pub fn command(&self, input: &str) -> Option<Result<()>> {
+        let input = input.trim();
+        let (name, args) = input
+            .split_once(char::is_whitespace)
+            .map(|(n, a)| (n, a.trim()))
+            .unwrap_or((input, ""));
+        let plugin = self
+            .plugins
+            .values()
+            .find(|p| p.conf.commands.iter().any(|c| c == name))?;
+        let callback = {
+            let plugin_name = plugin.name.clone();
+            let sender = plugin.sender.clone();
+            move |reply: Reply| {
+                let event = match reply {
+                    Ok(Value::Null) => return,
+                    Ok(Value::String(message)) => {
+                        UIEvent::StatusEvent(StatusEvent::DisplayMessage(message))
+                    }
+                    Ok(other) => {
+                        UIEvent::StatusEvent(StatusEvent::DisplayMessage(other.to_string()))
+                    }
+                    Err(err) => UIEvent::Notification {
+                        title: Some(format!("Plugin {plugin_name}").into()),
+                        body: err.message.into(),
+                        source: None,
+                        kind: Some(crate::types::NotificationType::Error(ErrorKind::External)),
+                    },
+                };
+                _ = sender.send(ThreadEvent::UIEvent(event));
+            }
+        };
+        Some(plugin.connection.request(
+            "command",
+            CommandParams { name, args },
+            Pending::Callback(Box::new(callback)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn(command: &str) -> Plugin {
+        let (sender, _) = crossbeam::channel::unbounded();
+        Plugin::spawn(
+            "test",
+            &PluginConf {
+                command: command.to_string(),
+                hooks: vec![],
+                commands: vec![],
+                column_width: 12,
+                timeout: 1,
+            },
+            sender,
+            &CommandPolicy::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_plugin_call() {
+        let plugin =
+            spawn(r#"sed -u 's/.*"id":\([0-9]*\).*/{"jsonrpc":"2.0","id":\1,"result":"pong"}/'"#);
+        let call = |plugin: &Plugin, duration| {
+            futures::executor::block_on(plugin.connection.call("ping", (), duration))
+        };
+        for _ in 0..2 {
+            assert_eq!(
+                call(&plugin, Duration::from_secs(5)).unwrap(),
+                Value::String("pong".to_string())
+            );
+        }
+
+        let plugin = spawn(
+            r#"sed -u 's/.*"id":\([0-9]*\).*/{"jsonrpc":"2.0","id":\1,"error":{"code":1,"message":"refused"}}/'"#,
+        );
+        let err = call(&plugin, Duration::from_secs(5)).unwrap_err();
+        assert!(err.to_string().contains("refused"), "{err}");
+
+        let plugin = spawn("cat > /dev/null");
+        let err = call(&plugin, Duration::from_millis(100)).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::TimedOut);
+
+        // A plugin that does not read its input does not block writers.
+        let plugin = spawn("sleep 5");
+        let started = std::time::Instant::now();
+        for _ in 0..64 {
+            plugin
+                .connection
+                .notify("message_received", "x".repeat(16 * 1024))
+                .unwrap();
+        }
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_plugin_request_format() {
+        assert_eq!(
+            serde_json::to_string(&Request {
+                jsonrpc: JSONRPC_VERSION,
+                id: Some(3),
+                method: "command",
+                params: CommandParams {
+                    name: "spam-report",
+                    args: "now"
+                },
+            })
+            .unwrap(),
+            r#"{"jsonrpc":"2.0","id":3,"method":"command","params":{"name":"spam-report","args":"now"}}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Request {
+                jsonrpc: JSONRPC_VERSION,
+                id: None,
+                method: "message_received",
+                params: (),
+            })
+            .unwrap(),
+            r#"{"jsonrpc":"2.0","method":"message_received","params":null}"#
+        );
+    }
+}
@@ -25,9 +25,10 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+use indexmap::IndexMap;
 use melib::{
     backends::MailBackend,
-    email::{attachment_types::Text, Envelope, EnvelopeHash},
+    email::{attachment_types::Text, Envelope, EnvelopeHash, Flag},
     log,
     search::{
         escape_double_quote,
@@ -35,9 +36,12 @@ use melib::{
     },
     smol,
     utils::sqlite3::{rusqlite::params, DatabaseDescription},
-    Error, Result, ResultIntoError, SortField, SortOrder,
+    Error, Result, ResultIntoError, SortChain, SortField, SortOrder,
 };
 
+use crate::CommandPolicy;
+
+pub mod extract;
 #[cfg(test)]
 mod tests;
 
@@ -63,6 +67,7 @@ const DB: DatabaseDescription = DatabaseDescription {
                     flags            INTEGER NOT NULL,
                     has_attachments  BOOLEAN NOT NULL,
                     body_text        TEXT NOT NULL,
+                    attachment_text  TEXT NOT NULL,
                     timestamp        BLOB NOT NULL
                    );
         CREATE TABLE IF NOT EXISTS folders (
@@ -95,26 +100,42 @@ CREATE INDEX IF NOT EXISTS envelope_cc_index ON envelopes (cc);
 CREATE INDEX IF NOT EXISTS envelope_bcc_index ON envelopes (bcc);
 CREATE INDEX IF NOT EXISTS envelope_message_id_index ON envelopes (message_id);
 
-        CREATE VIRTUAL TABLE IF NOT EXISTS fts USING fts5(subject, body_text, content=envelopes, \
-         content_rowid=id);
+        CREATE VIRTUAL TABLE IF NOT EXISTS fts USING fts5(subject, body_text, attachment_text, \
+         content=envelopes, content_rowid=id);
 
 -- Triggers to keep the FTS index up to date.
 CREATE TRIGGER IF NOT EXISTS envelopes_ai AFTER INSERT ON envelopes BEGIN
-  INSERT INTO fts(rowid, subject, body_text) VALUES (new.id, new.subject, new.body_text);
+  INSERT INTO fts(rowid, subject, body_text, attachment_text) VALUES (new.id, new.subject, \
+         new.body_text, new.attachment_text);
 END;
 
 CREATE TRIGGER IF NOT EXISTS envelopes_ad AFTER DELETE ON envelopes BEGIN
-  INSERT INTO fts(fts, rowid, subject, body_text) VALUES('delete', old.id, old.subject, \
-         old.body_text);
+  INSERT INTO fts(fts, rowid, subject, body_text, attachment_text) VALUES('delete', old.id, \
+         old.subject, old.body_text, old.attachment_text);
 END;
 
 CREATE TRIGGER IF NOT EXISTS envelopes_au AFTER UPDATE ON envelopes BEGIN
-  INSERT INTO fts(fts, rowid, subject, body_text) VALUES('delete', old.id, old.subject, \
-         old.body_text);
-  INSERT INTO fts(rowid, subject, body_text) VALUES (new.id, new.subject, new.body_text);
+  INSERT INTO fts(fts, rowid, subject, body_text, attachment_text) VALUES('delete', old.id, \
+         old.subject, old.body_text, old.attachment_text);
+  INSERT INTO fts(rowid, subject, body_text, attachment_text) VALUES (new.id, new.subject, \
+         new.body_text, new.attachment_text);
 END; ",
     ),
-    version: 1,
+    version: 2,
+    migrations: &[(
+        // Version 2 indexes the text of attachments. The triggers are recreated by the
+        // init script, and attachments of already indexed e-mail stay unindexed until the
+        // index is rebuilt.
+        1,
+        "ALTER TABLE envelopes ADD COLUMN attachment_text TEXT NOT NULL DEFAULT '';
+DROP TRIGGER IF EXISTS envelopes_ai;
+DROP TRIGGER IF EXISTS envelopes_ad;
+DROP TRIGGER IF EXISTS envelopes_au;
+DROP TABLE IF EXISTS fts;
+CREATE VIRTUAL TABLE fts USING fts5(subject, body_text, attachment_text, content=envelopes, \
+         content_rowid=id);
+INSERT INTO fts(fts) VALUES('rebuild');",
+    )],
 };
 
 //#[inline(always)]
@@ -153,6 +174,8 @@ impl AccountCache {
         envelope: Envelope,
         backend: Arc<Mutex<Box<dyn MailBackend>>>,
         acc_name: Arc<str>,
+        attachment_extractors: IndexMap<String, String>,
+        command_policy: CommandPolicy,
     ) -> Result<()> {
         let db_desc = DatabaseDescription {
             identifier: Some(acc_name.to_string().into()),
@@ -170,14 +193,20 @@ impl AccountCache {
             .unwrap()
             .envelope_bytes_by_hash(envelope.hash())?;
 
-        let body = match op.await.map(|bytes| envelope.body_bytes(&bytes)) {
-            Ok(body) => body.text(Text::Plain),
+        let body_attachment = match op.await.map(|bytes| envelope.body_bytes(&bytes)) {
+            Ok(body) => body,
             Err(err) => {
                 log::error!("Failed to open envelope {}: {err}", envelope.message_id());
                 return Err(err);
             }
         };
         smol::unblock(move || {
+            let body = body_attachment.text(Text::Plain);
+            let attachment_text = extract::attachments_text(
+                &body_attachment,
+                &attachment_extractors,
+                &command_policy,
+            );
             let mut conn = db_desc.open_or_create_db()?;
 
             let tx =
@@ -208,8 +237,8 @@ impl AccountCache {
                 .execute(
                     "INSERT OR REPLACE INTO envelopes (account_id, hash, date, _from, _to, cc, \
                      bcc, subject, message_id, in_reply_to, _references, flags, has_attachments, \
-                     body_text, timestamp)
-              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                     body_text, attachment_text, timestamp)
+              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
                     params![
                         account_id,
                         envelope.hash().to_be_bytes().to_vec(),
@@ -228,6 +257,7 @@ impl AccountCache {
                         i64::from(envelope.flags().bits()),
                         i32::from(envelope.has_attachments()),
                         body,
+                        attachment_text,
                         envelope.date().to_be_bytes().to_vec()
                     ],
                 )
@@ -281,7 +311,11 @@ impl AccountCache {
         acc_name: Arc<str>,
         collection: melib::Collection,
         backend_mutex: Arc<Mutex<Box<dyn MailBackend>>>,
+        attachment_extractors: IndexMap<String, String>,
+        command_policy: CommandPolicy,
     ) -> Result<()> {
+        let attachment_extractors = Arc::new(attachment_extractors);
+        let command_policy = Arc::new(command_policy);
         let acc_mutex = collection.envelopes.clone();
         let db_desc = Arc::new(DatabaseDescription {
             identifier: Some(acc_name.to_string().into()),
@@ -347,6 +381,8 @@ impl AccountCache {
             {
                 let acc_mutex = acc_mutex.clone();
                 let db_desc = Arc::clone(&db_desc);
+                let attachment_extractors = Arc::clone(&attachment_extractors);
+                let command_policy = Arc::clone(&command_policy);
                 smol::unblock(move || {
                     let mut conn = db_desc.open_or_create_db()?;
                     let tx = conn.transaction_with_behavior(
@@ -355,12 +391,18 @@ impl AccountCache {
                     let envelopes_lck = acc_mutex.read().unwrap();
                     for (env_hash, bytes) in chunk_bytes {
                         if let Some(e) = envelopes_lck.get(&env_hash) {
-                            let body = e.body_bytes(&bytes).text(Text::Plain).replace('\0', "");
+                            let body_attachment = e.body_bytes(&bytes);
+                            let body = body_attachment.text(Text::Plain).replace('\0', "");
+                            let attachment_text = extract::attachments_text(
+                                &body_attachment,
+                                &attachment_extractors,
+                                &command_policy,
+                            );
                             tx.execute(
                                 "INSERT OR REPLACE INTO envelopes (account_id, hash, date, _from, \
                                  _to, cc, bcc, subject, message_id, in_reply_to, _references, \
-                                 flags, has_attachments, body_text, timestamp)
-              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                                 flags, has_attachments, body_text, attachment_text, timestamp)
+              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
                                 params![
                                     account_id,
                                     e.hash().to_be_bytes().to_vec(),
@@ -381,6 +423,7 @@ impl AccountCache {
                                     i64::from(e.flags().bits()),
                                     i32::from(e.has_attachments()),
                                     body,
+                                    attachment_text,
                                     e.date().to_be_bytes().to_vec()
                                 ],
                             )
@@ -403,7 +446,7 @@ impl AccountCache {
     pub async fn search(
         acc_name: Arc<str>,
         query: Query,
-        (sort_field, sort_order): (SortField, SortOrder),
+        sort: SortChain,
     ) -> Result<Vec<EnvelopeHash>> {
         let db_desc = DatabaseDescription {
             identifier: Some(acc_name.to_string().into()),
@@ -421,20 +464,40 @@ impl AccountCache {
         smol::unblock(move || {
             let mut conn = db_desc.open_or_create_db()?;
 
-            let sort_field = match sort_field {
-                SortField::Subject => "subject",
-                SortField::Date => "timestamp",
-            };
-
-            let sort_order = match sort_order {
-                SortOrder::Asc => "ASC",
-                SortOrder::Desc => "DESC",
-            };
+            let mut order_by = sort
+                .keys()
+                .iter()
+                .filter_map(|&(field, order)| {
+                    let column = match field {
+                        SortField::Subject => "subject".to_string(),
+                        SortField::Date => "timestamp".to_string(),
+                        SortField::Attachments => "has_attachments".to_string(),
+                        SortField::From => "_from".to_string(),
+                        SortField::Unseen => format!("(flags & {}) = 0", Flag::SEEN.bits()),
+                        // The index does not store these; search results are sorted again by
+                        // the listing when they are displayed.
+                        SortField::Size
+                        | SortField::Domain
+                        | SortField::Recipients
+                        | SortField::ThreadLength
+                        | SortField::Score => return None,
+                    };
+                    let order = match order {
+                        SortOrder::Asc => "ASC",
+                        SortOrder::Desc => "DESC",
+                    };
+                    Some(format!("{column} {order}"))
+                })
+                .collect::<Vec<String>>();
+            if order_by.is_empty() {
+                order_by.push("timestamp DESC".to_string());
+            }
+            let order_by = order_by.join(", ");
 
             let tx = conn.transaction()?;
             let mut stmt = tx
                 .prepare(&format!(
-                    "SELECT hash FROM envelopes WHERE {query} ORDER BY {sort_field} {sort_order};"
+                    "SELECT hash FROM envelopes WHERE {query} ORDER BY {order_by};"
                 ))
                 .map_err(|e| Error::new(e.to_string()))?;
 
@@ -450,6 +513,14 @@ impl AccountCache {
         .await
     }
 
+    /// Description of the index database of account `acc_name`.
+    pub fn db_description(acc_name: &str) -> DatabaseDescription {
+        DatabaseDescription {
+            identifier: Some(acc_name.to_string().into()),
+            ..DB.clone()
+        }
+    }
+
     pub fn db_path(acc_name: &Arc<str>) -> Result<Option<PathBuf>> {
         let db_desc = DatabaseDescription {
             identifier: Some(acc_name.to_string().into()),
@@ -562,6 +633,11 @@ pub fn query_to_sql(q: &Query) -> String {
             HasAttachment => {
                 s.push_str("has_attachments == 1 ");
             }
+            Attachment(t) => {
+                s.push_str("attachment_text LIKE \"%");
+                s.extend(escape_double_quote(t).chars());
+                s.push_str("%\" ");
+            }
             _ => {}
         }
     }
@@ -0,0 +1,252 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Extraction of the text of attachments for the search index.
+//!
+//! Extractors are looked up by MIME type in the `attachment_extractors`
+//! account setting, like the converters of `pager.attachment_previewers`.
+//! Word documents (`.docx`) without a configured extractor are read without
+//! an external command, since they are zip archives of XML files.
+
+use std::io::Read;
+
+use indexmap::IndexMap;
+use melib::{
+    email::{attachment_types::ContentType, Attachment},
+    error::*,
+    log,
+};
+
+use crate::{mail::view::preview, CommandPolicy};
+
+/// MIME type of Word documents.
+pub const DOCX_MIME_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+
+/// Maximum bytes of text indexed per attachment.
+const MAX_TEXT_LEN: usize = 1024 * 1024;
+
+/// The filenames and text of the attachments of `body`, one per line.
+pub fn attachments_text(
+    body: &Attachment,
+    extractors: &IndexMap<String, String>,
+    policy: &CommandPolicy,
+) -> String {
+    let mut ret = String::new();
+    for attachment in body.attachments() {
+        if matches!(attachment.content_type(), ContentType::Multipart { .. }) {
+            continue;
+        }
+        let Some(filename) = attachment.filename() else {
+            continue;
+        };
+        ret.push_str(&filename);
+        ret.push('\n');
+        let bytes = attachment.decode(Default::default());
+        match extract(
+            &attachment.mime_type(),
+            &bytes,
+            &filename,
+            extractors,
+            policy,
+        ) {
+            Ok(Some(mut text)) => {
+                if text.len() > MAX_TEXT_LEN {
+                    let mut end = MAX_TEXT_LEN;
+                    while !text.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    text.truncate(end);
+                }
+                ret.push_str(text.trim());
+                ret.push('\n');
+            }
+            Ok(None) => {}
+            Err(err) => log::warn!("Could not extract text of attachment {filename}: {err}"),
+        }
+    }
+    ret.replace('\0', "")
+}
+
+/// Extract the text of an attachment of type `mime_type`, if there is an
+/// extractor for it.
+pub fn extract(
+    mime_type: &str,
+    bytes: &[u8],
+    filename: &str,
+    extractors: &IndexMap<String, String>,
+    policy: &CommandPolicy,
+) -> Result<Option<String>> {
+    match preview::previewer(extractors, mime_type) {
+        // An empty command disables extraction.
+        Some(command) if command.trim().is_empty() => Ok(None),
+        Some(command) => preview::convert(command, bytes, Some(filename), policy).map(Some),
+        None if mime_type.eq_ignore_ascii_case(DOCX_MIME_TYPE) => docx_text(bytes).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// The text of the paragraphs of a Word document.
+pub fn docx_text(bytes: &[u8]) -> Result<String> {
+    let xml = zip_entry(bytes, "word/document.xml")?;
+    Ok(xml_text(&String::from_utf8_lossy(&xml)))
+}
+
+fn read_u16(bytes: &[u8], pos: usize) -> Option<usize> {
+    Some(u16::from_le_bytes(bytes.get(pos..pos + 2)?.try_into().ok()?).into())
+}
+
+fn read_u32(bytes: &[u8], pos: usize) -> Option<usize> {
+    u32::from_le_bytes(bytes.get(pos..pos + 4)?.try_into().ok()?)
+        .try_into()
+        .ok()
+}
+
+/// The uncompressed content of the file `name` in the zip archive `bytes`.
+pub fn zip_entry(bytes: &[u8], name: &str) -> Result<Vec<u8>> {
+    const END_OF_CENTRAL_DIRECTORY: &[u8] = b"PK\x05\x06";
+    const CENTRAL_DIRECTORY_ENTRY: &[u8] = b"PK\x01\x02";
+    const LOCAL_HEADER: &[u8] = b"PK\x03\x04";
+
+    let invalid = || Error::new("Invalid zip archive").set_kind(ErrorKind::ValueError);
+    let end = bytes
+        .windows(4)
+        .rposition(|w| w == END_OF_CENTRAL_DIRECTORY)
+        .ok_or_else(invalid)?;
+    let entries = read_u16(bytes, end + 10).ok_or_else(invalid)?;
+    let mut pos = read_u32(bytes, end + 16).ok_or_else(invalid)?;
+    for _ in 0..entries {
+        if bytes.get(pos..pos + 4) != Some(CENTRAL_DIRECTORY_ENTRY) {
+            return Err(invalid());
+        }
+        let field = |offset: usize, len: usize| {
+            if len == 2 {
+                read_u16(bytes, pos + offset)
+            } else {
+                read_u32(bytes, pos + offset)
+            }
+            .ok_or_else(invalid)
+        };
+        let method = field(10, 2)?;
+        let compressed_size = field(20, 4)?;
+        let size = field(24, 4)?;
+        let name_len = field(28, 2)?;
+        let extra_len = field(30, 2)?;
+        let comment_len = field(32, 2)?;
+        let header = field(42, 4)?;
+        let entry_name = bytes
+            .get(pos + 46..pos + 46 + name_len)
+            .ok_or_else(invalid)?;
+        pos += 46 + name_len + extra_len + comment_len;
+        if entry_name != name.as_bytes() {
+            continue;
+        }
+        if bytes.get(header..header + 4) != Some(LOCAL_HEADER) {
+            return Err(invalid());
+        }
+        let start = header
+            + 30
+            + read_u16(bytes, header + 26).ok_or_else(invalid)?
+            + read_u16(bytes, header + 28).ok_or_else(invalid)?;
+        let data = bytes
+            .get(start..start + compressed_size)
+            .ok_or_else(invalid)?;
+        let limit = size.min(16 * MAX_TEXT_LEN);
+        return match method {
+            0 => Ok(data[..limit.min(data.len())].to_vec()),
+            8 => {
+                let mut ret = Vec::with_capacity(limit);
+                flate2::read::DeflateDecoder::new(data)
+                    .take(limit as u64)
+                    .read_to_end(&mut ret)
+                    .chain_err_summary(|| format!("Could not decompress {name}"))?;
+                Ok(ret)
+            }
+            other => Err(
+                Error::new(format!("Unsupported compression method {other} of {name}"))
+                    .set_kind(ErrorKind::NotSupported),
+            ),
+        };
+    }
+    Err(Error::new(format!("{name} not found in zip archive")).set_kind(ErrorKind::NotFound))
+}
+
+/// The text content of an `OOXML` document, with paragraphs and line breaks
+/// as new lines.
+pub fn xml_text(xml: &str) -> String {
+    let mut ret = String::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        ret.push_str(&unescape_xml(&rest[..start]));
+        let Some(end) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim_end_matches('/');
+        match tag.split_whitespace().next().unwrap_or_default() {
+            "/w:p" | "w:br" | "w:cr" => ret.push('\n'),
+            "w:tab" => ret.push('\t'),
+            _ => {}
+        }
+        rest = &rest[start + end + 1..];
+    }
+    ret.push_str(&unescape_xml(rest));
+    ret
+}
+
+fn unescape_xml(text: &str) -> String {
+    let mut ret = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        ret.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|n| u32::from_str_radix(n, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse::<u32>))
+                .and_then(|n| n.ok())
+                .and_then(char::from_u32),
+        };
+        match c {
+            Some(c) => {
+                ret.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                ret.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    ret.push_str(rest);
+    ret
+}
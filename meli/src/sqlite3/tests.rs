@@ -51,6 +51,103 @@ fn test_sqlite3_query_to_sql() {
                 .1
         )
     );
+    assert_eq!(
+        "(attachment_text LIKE \"%quarterly report%\" ) AND (has_attachments == 1 ) ",
+        &query_to_sql(
+            &query()
+                .parse_complete("attachment:\"quarterly report\" and has:attachment")
+                .unwrap()
+                .1
+        )
+    );
+}
+
+/// Build a zip archive with a single file `name`, optionally deflated.
+fn zip_archive(name: &str, content: &[u8], deflate: bool) -> Vec<u8> {
+    use std::io::Write;
+
+    let data = if deflate {
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap()
+    } else {
+        content.to_vec()
+    };
+    let method: u16 = if deflate { 8 } else { 0 };
+    let mut ret = vec![];
+    // Local file header.
+    ret.extend_from_slice(b"PK\x03\x04");
+    ret.extend_from_slice(&[20, 0, 0, 0]);
+    ret.extend_from_slice(&method.to_le_bytes());
+    ret.extend_from_slice(&[0; 8]);
+    ret.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    ret.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    ret.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    ret.extend_from_slice(&[0, 0]);
+    ret.extend_from_slice(name.as_bytes());
+    ret.extend_from_slice(&data);
+    // Central directory.
+    let central_directory = ret.len();
+    ret.extend_from_slice(b"PK\x01\x02");
+    ret.extend_from_slice(&[20, 0, 20, 0, 0, 0]);
+    ret.extend_from_slice(&method.to_le_bytes());
+    ret.extend_from_slice(&[0; 8]);
+    ret.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    ret.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    ret.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    ret.extend_from_slice(&[0; 12]);
+    ret.extend_from_slice(&0_u32.to_le_bytes());
+    ret.extend_from_slice(name.as_bytes());
+    let central_directory_len = ret.len() - central_directory;
+    // End of central directory.
+    ret.extend_from_slice(b"PK\x05\x06");
+    ret.extend_from_slice(&[0, 0, 0, 0, 1, 0, 1, 0]);
+    ret.extend_from_slice(&(central_directory_len as u32).to_le_bytes());
+    ret.extend_from_slice(&(central_directory as u32).to_le_bytes());
+    ret.extend_from_slice(&[0, 0]);
+    ret
+}
+
+#[test]
+fn test_sqlite3_extract_docx_text() {
+    const DOCUMENT: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:p><w:r><w:t>Quarterly report</w:t></w:r></w:p><w:p><w:r><w:t xml:space="preserve">Revenue &amp; costs</w:t><w:tab/><w:t>&#8364;10</w:t><w:br/><w:t>&lt;draft&gt;</w:t></w:r></w:p></w:body></w:document>"#;
+    const TEXT: &str = "Quarterly report\nRevenue & costs\t€10\n<draft>\n";
+
+    for deflate in [false, true] {
+        let docx = zip_archive("word/document.xml", DOCUMENT.as_bytes(), deflate);
+        assert_eq!(&extract::docx_text(&docx).unwrap(), TEXT);
+        assert_eq!(
+            extract::extract(
+                extract::DOCX_MIME_TYPE,
+                &docx,
+                "report.docx",
+                &IndexMap::default(),
+                &CommandPolicy::default()
+            )
+            .unwrap()
+            .as_deref(),
+            Some(TEXT)
+        );
+    }
+    let other = zip_archive("content.xml", DOCUMENT.as_bytes(), false);
+    assert!(extract::docx_text(&other).unwrap_err().kind.is_not_found());
+    extract::docx_text(b"not a zip archive").unwrap_err();
+    // An empty command disables extraction.
+    let extractors = [(extract::DOCX_MIME_TYPE.to_string(), String::new())]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        extract::extract(
+            extract::DOCX_MIME_TYPE,
+            &other,
+            "report.docx",
+            &extractors,
+            &CommandPolicy::default()
+        )
+        .unwrap(),
+        None
+    );
 }
 
 fn new_maildir_backend(
@@ -88,6 +185,7 @@ fn new_maildir_backend(
         .into_iter()
         .collect(),
         manual_refresh: true,
+        retained_headers: vec![],
         extra: indexmap::indexmap! {
             "root_mailbox".into() => root_mailbox.display().to_string(),
         },
@@ -171,18 +269,20 @@ fn test_sqlite3_reindex() {
             collection: melib::Collection,
             backend_mutex: Arc<Mutex<Box<dyn MailBackend>>>,
         ) {
-            let reindex_fut = AccountCache::index(acc_name, collection, Arc::clone(&backend_mutex));
+            let reindex_fut = AccountCache::index(
+                acc_name,
+                collection,
+                Arc::clone(&backend_mutex),
+                IndexMap::default(),
+                CommandPolicy::default(),
+            );
             smol::block_on(reindex_fut).unwrap();
         }
 
         /// Helper function to perform a search operation, which is async, by
         /// blocking on it to completion.
         pub(super) fn perform_search(acc_name: &Arc<str>, query: Query) -> Vec<EnvelopeHash> {
-            let search_fut = AccountCache::search(
-                Arc::clone(acc_name),
-                query,
-                (SortField::Date, SortOrder::Desc),
-            );
+            let search_fut = AccountCache::search(Arc::clone(acc_name), query, SortChain::default());
             smol::block_on(search_fut).unwrap()
         }
     }
@@ -0,0 +1,113 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Timing of the phases of startup.
+//!
+//! The report is printed on exit with the `--profile-startup` command line
+//! flag and shown by the `startup-profile` command.
+
+use std::{
+    borrow::Cow,
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+/// Durations of the startup phases, in the order they ended.
+#[derive(Clone, Debug)]
+pub struct StartupProfile {
+    start: Instant,
+    last: Instant,
+    phases: Vec<(Cow<'static, str>, Duration)>,
+    /// Durations of parts of a phase, e.g. the initialization of each account,
+    /// by phase index.
+    details: Vec<(usize, String, Duration)>,
+    finished: bool,
+}
+
+impl Default for StartupProfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StartupProfile {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            last: now,
+            phases: vec![],
+            details: vec![],
+            finished: false,
+        }
+    }
+
+    /// Record that phase `name` ended now.
+    pub fn phase(&mut self, name: impl Into<Cow<'static, str>>) {
+        if self.finished {
+            return;
+        }
+        let now = Instant::now();
+        self.phases.push((name.into(), now - self.last));
+        self.last = now;
+    }
+
+    /// Record the duration of a part of the next phase to end.
+    pub fn detail(&mut self, name: String, duration: Duration) {
+        if self.finished {
+            return;
+        }
+        self.details.push((self.phases.len(), name, duration));
+    }
+
+    /// Record that the last phase, `name`, ended now. Later phases are
+    /// ignored.
+    pub fn finish(&mut self, name: impl Into<Cow<'static, str>>) {
+        self.phase(name);
+        self.finished = true;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// A table of the phases and their durations.
+    pub fn report(&self) -> String {
+        let mut ret = String::new();
+        for (i, (name, duration)) in self.phases.iter().enumerate() {
+            let _ = writeln!(ret, "{name:<32}{:>10.1} ms", as_millis(*duration));
+            for (_, detail, duration) in self.details.iter().filter(|(p, _, _)| *p == i) {
+                let _ = writeln!(ret, "  {detail:<30}{:>10.1} ms", as_millis(*duration));
+            }
+        }
+        let total = self.last - self.start;
+        let _ = write!(ret, "{:<32}{:>10.1} ms", "total", as_millis(total));
+        if !self.finished {
+            ret.push_str(" (startup has not finished yet)");
+        }
+        ret
+    }
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
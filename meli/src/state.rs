@@ -36,11 +36,12 @@
 
 use std::{
     borrow::Cow,
-    collections::BTreeSet,
+    collections::{BTreeSet, HashMap},
     os::fd::OwnedFd,
     path::{Path, PathBuf},
     sync::Arc,
     thread,
+    time::Duration,
 };
 
 use crossbeam::channel::{unbounded, Receiver, Sender};
@@ -49,16 +50,19 @@ use melib::{
     backends::{
         AccountHash, BackendEvent, BackendEventConsumer, Backends, RefreshEvent, RefreshEventKind,
     },
-    utils::datetime,
+    utils::{datetime, shellexpand::ShellExpandTrait},
 };
 use smallvec::SmallVec;
 
 use super::*;
 use crate::{
+    accounts::MailboxStatus,
     conf::data_types::SearchBackend,
-    jobs::JobExecutor,
+    jobs::{IsAsync, JobExecutor, JobId, JoinHandle},
     notifications::DisplayMessageBox,
+    startup_profile::StartupProfile,
     terminal::{get_events, Screen, Tty},
+    usage_stats::UsageStats,
 };
 
 struct InputHandler {
@@ -159,9 +163,115 @@ pub struct Context {
     /// Children processes
     pub children: IndexMap<Cow<'static, str>, Vec<ForkedProcess>>,
     pub temp_files: Vec<File>,
+    /// Results of attachment scans in this session, by content digest.
+    pub attachment_scans: IndexMap<String, crate::mail::view::scan::ScanResult>,
+    /// What the terminal can display, detected at startup.
+    pub terminal_capabilities: TerminalCapabilities,
+    /// Position of the focused line, where the terminal cursor is shown in
+    /// screen reader mode.
+    pub focus: Option<Pos>,
+    /// Whether the focused pane is expanded to the whole window, toggled with
+    /// the `toggle_zoom` shortcut.
+    pub zoomed: bool,
+    /// Running plugins, see [`crate::plugins`].
+    pub plugins: crate::plugins::PluginManager,
 }
 
 impl Context {
+    /// Whether to draw with ASCII characters only, either because the user
+    /// asked for it or because the terminal cannot display Unicode.
+    pub fn ascii_drawing(&self) -> bool {
+        self.settings.terminal.ascii_drawing
+            || self.settings.terminal.screen_reader
+            || !self.terminal_capabilities.unicode
+    }
+
+    /// Whether to draw `OSC 8` hyperlinks: if the user didn't set
+    /// `terminal.draw_hyperlinks`, whether the terminal supports them.
+    pub fn draw_hyperlinks(&self) -> bool {
+        if self.settings.terminal.draw_hyperlinks.is_internal() {
+            return self.terminal_capabilities.hyperlinks;
+        }
+        self.settings.terminal.draw_hyperlinks.is_true()
+    }
+
+    #[inline]
+    pub fn screen_reader(&self) -> bool {
+        self.settings.terminal.screen_reader
+    }
+
+    /// Announce a change of state in the status line, in screen reader mode.
+    ///
+    /// Announcements have the form `{kind}: {description}` so that they can be
+    /// told apart from other status messages.
+    pub fn announce(&mut self, kind: &str, description: impl std::fmt::Display) {
+        if !self.screen_reader() {
+            return;
+        }
+        self.replies
+            .push_back(UIEvent::StatusEvent(StatusEvent::UpdateSubStatus(format!(
+                "{kind}: {description}"
+            ))));
+    }
+
+    /// Score the envelopes of a mailbox with its `listing.score_rules`, see
+    /// [`Account::apply_score_rules`].
+    pub fn apply_score_rules(
+        &self,
+        account_hash: AccountHash,
+        mailbox_hash: MailboxHash,
+        rescore: bool,
+    ) -> bool {
+        let Some(account) = self.accounts.get(&account_hash) else {
+            return false;
+        };
+        if !account.mailbox_entries.contains_key(&mailbox_hash) {
+            return false;
+        }
+        let rules = mailbox_settings!(self[account_hash][&mailbox_hash].listing.score_rules);
+        if rules.is_empty() && !rescore {
+            return false;
+        }
+        account.apply_score_rules(mailbox_hash, rules, rescore)
+    }
+
+    /// Mark the line at `pos` as focused.
+    #[inline]
+    pub fn set_focus(&mut self, pos: Pos) {
+        self.focus = Some(pos);
+    }
+
+    /// Whether to draw colors, unless the user or the terminal does not want
+    /// them.
+    /// The [`CommandPolicy`] of external commands run on behalf of
+    /// `account_hash`.
+    ///
+    /// Commands that do not belong to an account must be allowed by every
+    /// account with an `allowed_commands` list, and are logged if any account
+    /// has `log_commands` set.
+    pub fn command_policy(&self, account_hash: Option<AccountHash>) -> CommandPolicy {
+        if let Some(account) = account_hash.and_then(|h| self.accounts.get(&h)) {
+            return account.settings.conf.command_policy();
+        }
+        let mut ret = CommandPolicy::default();
+        for account in self.accounts.values() {
+            let conf = &account.settings.conf;
+            ret.log |= conf.log_commands;
+            if let Some(ref allowed) = conf.allowed_commands {
+                ret.allowed = Some(match ret.allowed.take() {
+                    None => allowed.clone(),
+                    Some(prev) => prev.into_iter().filter(|p| allowed.contains(p)).collect(),
+                });
+            }
+        }
+        ret
+    }
+
+    pub fn use_color(&self) -> bool {
+        self.settings.terminal.use_color()
+            && self.terminal_capabilities.colors != ColorSupport::None
+    }
+
     pub fn replies(&mut self) -> smallvec::SmallVec<[UIEvent; 8]> {
         self.replies.drain(0..).collect()
     }
@@ -266,9 +376,14 @@ impl Context {
             realized: IndexMap::default(),
             unrealized: IndexSet::default(),
             temp_files: Vec::new(),
+            attachment_scans: IndexMap::default(),
             current_dir: std::env::current_dir().unwrap(),
             children: IndexMap::default(),
+            terminal_capabilities: TerminalCapabilities::default(),
+            focus: None,
+            zoomed: false,
             cmd_buf: None,
+            plugins: crate::plugins::PluginManager::default(),
 
             input_thread: InputHandler {
                 pipe: input_thread_pipe,
@@ -363,10 +478,21 @@ pub struct State {
     pub context: Box<Context>,
     timer: thread::JoinHandle<()>,
     message_box: DisplayMessageBox,
+    pub startup_profile: StartupProfile,
+    /// Usage statistics, if they are collected.
+    usage_stats: Option<UsageStats>,
+    /// Running `pager.clipboard_command` jobs and their commands.
+    clipboard_jobs: HashMap<JobId, (String, JoinHandle<Result<()>>)>,
+    /// Accounts being initialized after startup, in configuration order.
+    #[allow(clippy::type_complexity)]
+    pending_accounts: VecDeque<(String, JoinHandle<Result<(Account, Duration)>>)>,
 }
 
 impl Drop for State {
     fn drop(&mut self) {
+        if let Some(Err(err)) = self.usage_stats.as_ref().map(UsageStats::save) {
+            log::error!("Could not save usage statistics: {err}");
+        }
         if let Some(Err(err)) = self.kill_main_child() {
             log::debug!("Failed to kill subprocess: {}", err);
         }
@@ -419,53 +545,63 @@ impl State {
     ) -> Result<Self> {
         // Create async channel to block the input-thread if we need to fork and stop it
         // from reading stdin, see get_events() for details
+        let mut startup_profile = StartupProfile::new();
         let input_thread = unbounded();
         let input_thread_pipe = crate::types::pipe()?;
-        let backends = Backends::new();
         let settings = Box::new(if let Some(settings) = settings {
             settings
         } else {
             Settings::new()?
         });
+        startup_profile.phase("configuration");
 
         let (cols, rows) = termion::terminal_size().chain_err_summary(|| {
             "Could not determine terminal size. Are you running this on a tty? If yes, do you need \
              permissions for tty ioctls?"
         })?;
         let (cols, rows) = (cols as usize, rows as usize);
-
-        let job_executor = Arc::new(JobExecutor::new(sender.clone()));
-        let accounts = {
-            settings
-                .accounts
-                .iter()
-                .map(|(n, a_s)| {
-                    let sender = sender.clone();
-                    let account_hash = AccountHash::from_bytes(n.as_bytes());
-                    Account::new(
-                        account_hash,
-                        n.to_string(),
-                        a_s.clone(),
-                        &backends,
-                        MainLoopHandler {
-                            job_executor: job_executor.clone(),
-                            sender: sender.clone(),
-                        },
-                        BackendEventConsumer::new(Arc::new(
-                            move |account_hash: AccountHash, ev: BackendEvent| {
-                                sender
-                                    .send(ThreadEvent::UIEvent(UIEvent::BackendEvent(
-                                        account_hash,
-                                        ev,
-                                    )))
-                                    .unwrap();
-                            },
-                        )),
-                    )
-                })
-                .collect::<Result<Vec<Account>>>()?
+        let terminal_capabilities = TerminalCapabilities::detect();
+        log::debug!("Terminal capabilities: {:?}", terminal_capabilities);
+
+        let job_executor = Arc::new(JobExecutor::with_settings(sender.clone(), &settings.jobs));
+        // The first account is set up before the user interface, which needs at
+        // least one account to show. The others are set up in jobs and added by
+        // `add_initialized_accounts` as they finish, so that an account whose
+        // backend is slow to initialize does not hold back the first draw.
+        // Mailboxes are then fetched in the background and each account's
+        // readiness is signalled with `StartupCheck` events as its mailboxes
+        // load.
+        let main_loop_handler = MainLoopHandler {
+            job_executor: job_executor.clone(),
+            sender: sender.clone(),
         };
-        let accounts = accounts.into_iter().map(|acc| (acc.hash(), acc)).collect();
+        let mut account_confs = settings.accounts.iter();
+        let Some((name, account_conf)) = account_confs.next() else {
+            return Err(
+                Error::new("No accounts are configured.").set_kind(ErrorKind::Configuration)
+            );
+        };
+        let (account, duration) = Self::init_account(
+            name.to_string(),
+            account_conf.clone(),
+            main_loop_handler.clone(),
+        )?;
+        startup_profile.detail(account.name().to_string(), duration);
+        let accounts = IndexMap::from([(account.hash(), account)]);
+        let pending_accounts = account_confs
+            .map(|(name, account_conf)| {
+                let fut = {
+                    let name = name.to_string();
+                    let account_conf = account_conf.clone();
+                    let main_loop_handler = main_loop_handler.clone();
+                    async move { Self::init_account(name, account_conf, main_loop_handler) }
+                };
+                let handle =
+                    job_executor.spawn(format!("{}-init", name).into(), fut, IsAsync::Blocking);
+                (name.to_string(), handle)
+            })
+            .collect();
+        startup_profile.phase("account initialization");
 
         let timer = {
             let sender = sender.clone();
@@ -489,11 +625,15 @@ impl State {
         screen
             .tty_mut()
             .set_mouse(settings.terminal.use_mouse.is_true())
-            .set_draw_fn(if settings.terminal.use_color() {
-                Screen::draw_horizontal_segment
-            } else {
-                Screen::draw_horizontal_segment_no_color
-            });
+            .set_draw_fn(
+                if settings.terminal.use_color()
+                    && terminal_capabilities.colors != ColorSupport::None
+                {
+                    Screen::draw_horizontal_segment
+                } else {
+                    Screen::draw_horizontal_segment_no_color
+                },
+            );
         let message_box = DisplayMessageBox::new(&screen);
         let mut s = Self {
             screen,
@@ -505,6 +645,10 @@ impl State {
             timer,
             draw_rate_limit: RateLimit::new(1, 3, job_executor.clone()),
             message_box,
+            startup_profile,
+            usage_stats: None,
+            clipboard_jobs: HashMap::default(),
+            pending_accounts,
             context: Box::new(Context {
                 accounts,
                 settings,
@@ -513,9 +657,14 @@ impl State {
                 realized: IndexMap::default(),
                 unrealized: IndexSet::default(),
                 temp_files: Vec::new(),
+                attachment_scans: IndexMap::default(),
                 current_dir: std::env::current_dir()?,
                 children: IndexMap::default(),
+                terminal_capabilities,
+                focus: None,
+                zoomed: false,
                 cmd_buf: None,
+                plugins: crate::plugins::PluginManager::default(),
                 input_thread: InputHandler {
                     pipe: input_thread_pipe,
                     rx: input_thread.1,
@@ -530,7 +679,7 @@ impl State {
                 receiver,
             }),
         };
-        if s.context.settings.terminal.ascii_drawing {
+        if s.context.ascii_drawing() {
             s.screen.grid_mut().set_ascii_drawing(true);
             s.screen.overlay_grid_mut().set_ascii_drawing(true);
         }
@@ -540,10 +689,16 @@ impl State {
                 .overlay_grid_mut()
                 .set_force_text_presentation(true);
         }
-        if s.context.settings.terminal.draw_hyperlinks() {
+        if s.context.draw_hyperlinks() {
             s.screen.grid_mut().set_draw_hyperlinks(true);
             s.screen.overlay_grid_mut().set_draw_hyperlinks(true);
         }
+        s.screen
+            .grid_mut()
+            .set_color_support(s.context.terminal_capabilities.colors);
+        s.screen
+            .overlay_grid_mut()
+            .set_color_support(s.context.terminal_capabilities.colors);
 
         s.screen.switch_to_alternate_screen(&s.context);
         s.screen.do_background_query();
@@ -558,10 +713,90 @@ impl State {
                 //)));
             }
         }
+        s.context.plugins = crate::plugins::PluginManager::new(
+            &s.context.settings.plugins,
+            s.context.main_loop_handler.sender.clone(),
+            &s.context.command_policy(None),
+        );
+        s.update_usage_stats();
         s.context.restore_input();
+        s.startup_profile.phase("terminal setup");
         Ok(s)
     }
 
+    fn init_account(
+        name: String,
+        account_conf: crate::conf::AccountConf,
+        main_loop_handler: MainLoopHandler,
+    ) -> Result<(Account, Duration)> {
+        let instant = std::time::Instant::now();
+        let account_hash = AccountHash::from_bytes(name.as_bytes());
+        let sender = main_loop_handler.sender.clone();
+        let ret = Account::new(
+            account_hash,
+            name,
+            account_conf,
+            &Backends::new(),
+            main_loop_handler,
+            BackendEventConsumer::new(Arc::new(
+                move |account_hash: AccountHash, ev: BackendEvent| {
+                    sender
+                        .send(ThreadEvent::UIEvent(UIEvent::BackendEvent(
+                            account_hash,
+                            ev,
+                        )))
+                        .unwrap();
+                },
+            )),
+        )?;
+        log::trace!(
+            "Account {} initialized in {:?}.",
+            ret.name(),
+            instant.elapsed()
+        );
+        Ok((ret, instant.elapsed()))
+    }
+
+    /// Add the accounts whose initialization jobs have finished.
+    ///
+    /// Accounts are added in configuration order, so that the indices of
+    /// accounts already shown do not change: an account that is ready waits
+    /// for the ones configured before it.
+    fn add_initialized_accounts(&mut self) {
+        while let Some((_, handle)) = self.pending_accounts.front_mut() {
+            let result = match handle.chan.try_recv() {
+                Ok(None) => return,
+                Ok(Some(result)) => result,
+                Err(_) => Err(Error::new("Initialization job was canceled.")),
+            };
+            let Some((name, _)) = self.pending_accounts.pop_front() else {
+                return;
+            };
+            match result {
+                Ok((mut account, duration)) => {
+                    self.startup_profile
+                        .detail(account.name().to_string(), duration);
+                    if !account.backend_capabilities.is_remote {
+                        account.watch(None);
+                    }
+                    let account_hash = account.hash();
+                    self.context.accounts.insert(account_hash, account);
+                    self.rcv_event(UIEvent::AccountAdded(account_hash));
+                }
+                Err(err) => {
+                    log::error!("Could not initialize account {}: {}", name, err);
+                    self.context.replies.push_back(UIEvent::Notification {
+                        title: Some(format!("Could not initialize account {name}").into()),
+                        body: err.to_string().into(),
+                        kind: Some(NotificationType::Error(err.kind)),
+                        source: Some(err),
+                    });
+                }
+            }
+        }
+        self.check_startup_finished();
+    }
+
     /*
      * When we receive a mailbox hash from a watcher thread,
      * we match the hash to the index of the mailbox, request a reload
@@ -590,9 +825,12 @@ impl State {
                 return;
             }
             let Context {
-                ref mut accounts, ..
+                ref mut accounts,
+                ref plugins,
+                ..
             } = &mut *self.context;
 
+            plugins.message_received(&accounts[&account_hash], mailbox_hash, &events);
             if let Some(notifications) = accounts[&account_hash].reload(events, mailbox_hash) {
                 for n in notifications {
                     if matches!(n, UIEvent::Notification { .. }) {
@@ -762,6 +1000,11 @@ impl State {
                 self.screen.draw_overlay(row.cols(), row.row_index());
             }
         }
+        if self.context.screen_reader() && can_draw_above_screen {
+            if let Some(pos) = self.context.focus {
+                self.screen.show_cursor_at(pos);
+            }
+        }
         self.flush();
     }
 
@@ -783,6 +1026,17 @@ impl State {
     }
 
     pub fn can_quit_cleanly(&mut self) -> bool {
+        let mut accounts_done = true;
+        for account in self.context.accounts.values_mut() {
+            accounts_done &= account.can_quit_cleanly();
+        }
+        if !accounts_done {
+            self.show_display_message(
+                "Changes to e-mail are still being saved, quit again when they are done."
+                    .to_string(),
+            );
+            return false;
+        }
         let Self {
             ref mut components,
             ref context,
@@ -791,6 +1045,42 @@ impl State {
         components.values_mut().all(|c| c.can_quit_cleanly(context))
     }
 
+    /// Memory usage estimate of each account's collection, for the
+    /// `--memory-report` command line flag.
+    pub fn memory_report(&self) -> String {
+        use std::fmt::Write;
+
+        let mut ret = String::new();
+        for account in self.context.accounts.values() {
+            let _ = writeln!(
+                ret,
+                "{}:\n{}\n",
+                account.name(),
+                account.collection.memory_report()
+            );
+        }
+        ret
+    }
+
+    /// Finish the startup profile once the mailboxes opened at startup have
+    /// loaded.
+    pub fn check_startup_finished(&mut self) {
+        if self.startup_profile.is_finished() || !self.pending_accounts.is_empty() {
+            return;
+        }
+        let mut statuses = self
+            .context
+            .accounts
+            .values()
+            .flat_map(|a| a.mailbox_entries.values().map(|e| &e.status));
+        if statuses.clone().any(MailboxStatus::is_parsing)
+            || !statuses.any(MailboxStatus::is_available)
+        {
+            return;
+        }
+        self.startup_profile.finish("mailbox loading");
+    }
+
     pub fn register_component(&mut self, component: Box<dyn Component>) {
         component.realize(None, &mut self.context);
         self.components.insert(component.id(), component);
@@ -881,6 +1171,8 @@ impl State {
                     acc_name,
                     account.collection.clone(),
                     backend_mutex,
+                    account.settings.conf.attachment_extractors.clone(),
+                    account.settings.conf.command_policy(),
                 );
                 let handle = self.context.main_loop_handler.job_executor.spawn(
                     "sqlite3::index".into(),
@@ -913,6 +1205,188 @@ impl State {
                     kind: Some(NotificationType::Error(ErrorKind::None)),
                 });
             }
+            AccountAction(ref account_name, ContactDuplicates) => {
+                if let Some(account) = self
+                    .context
+                    .accounts
+                    .values()
+                    .find(|a| a.name() == account_name)
+                {
+                    let report =
+                        crate::contacts::duplicates_report(account.name(), &account.contacts);
+                    self.rcv_event(UIEvent::Action(Tab(New(Some(Box::new(
+                        Pager::from_string(
+                            report,
+                            &self.context,
+                            None,
+                            None,
+                            crate::conf::value(&self.context, "theme_default"),
+                        ),
+                    ))))));
+                } else {
+                    self.context.replies.push_back(UIEvent::Notification {
+                        title: None,
+                        source: None,
+                        body: format!("Account {account_name} was not found.").into(),
+                        kind: Some(NotificationType::Error(ErrorKind::None)),
+                    });
+                }
+            }
+            AccountAction(ref account_name, RetentionPreview) => {
+                if let Some(account) = self
+                    .context
+                    .accounts
+                    .values()
+                    .find(|a| a.name() == account_name)
+                {
+                    let report = account.retention_report();
+                    self.rcv_event(UIEvent::Action(Tab(New(Some(Box::new(
+                        Pager::from_string(
+                            report,
+                            &self.context,
+                            None,
+                            None,
+                            crate::conf::value(&self.context, "theme_default"),
+                        ),
+                    ))))));
+                } else {
+                    self.context.replies.push_back(UIEvent::Notification {
+                        title: None,
+                        source: None,
+                        body: format!("Account {account_name} was not found.").into(),
+                        kind: Some(NotificationType::Error(ErrorKind::None)),
+                    });
+                }
+            }
+            AccountAction(ref account_name, Maintenance) => {
+                let Some(account) = self
+                    .context
+                    .accounts
+                    .values_mut()
+                    .find(|a| a.name() == account_name)
+                else {
+                    self.context.replies.push_back(UIEvent::Notification {
+                        title: None,
+                        source: None,
+                        body: format!("Account {account_name} was not found.").into(),
+                        kind: Some(NotificationType::Error(ErrorKind::None)),
+                    });
+                    return;
+                };
+                match account.start_maintenance() {
+                    Ok(()) => {
+                        self.context.replies.push_back(UIEvent::Notification {
+                            title: None,
+                            source: None,
+                            body: "Cache maintenance started.".into(),
+                            kind: Some(NotificationType::Info),
+                        });
+                    }
+                    Err(err) => {
+                        self.context.replies.push_back(UIEvent::Notification {
+                            title: None,
+                            source: None,
+                            body: err.to_string().into(),
+                            kind: Some(NotificationType::Error(err.kind)),
+                        });
+                    }
+                }
+            }
+            AccountAction(ref account_name, SpecialMailboxes) => {
+                if let Some(account_hash) = self
+                    .context
+                    .accounts
+                    .values()
+                    .find(|a| a.name() == account_name)
+                    .map(|a| a.hash())
+                {
+                    crate::accounts::special_mailboxes_dialog(
+                        &mut self.context,
+                        account_hash,
+                        &crate::conf::SpecialMailboxes::USAGES,
+                    );
+                } else {
+                    self.context.replies.push_back(UIEvent::Notification {
+                        title: None,
+                        source: None,
+                        body: format!("Account {account_name} was not found.").into(),
+                        kind: Some(NotificationType::Error(ErrorKind::None)),
+                    });
+                }
+            }
+            AccountAction(ref account_name, SetSpecialMailbox(usage, ref path)) => {
+                let Some(account) = self
+                    .context
+                    .accounts
+                    .values_mut()
+                    .find(|a| a.name() == account_name)
+                else {
+                    self.context.replies.push_back(UIEvent::Notification {
+                        title: None,
+                        source: None,
+                        body: format!("Account {account_name} was not found.").into(),
+                        kind: Some(NotificationType::Error(ErrorKind::None)),
+                    });
+                    return;
+                };
+                match account.set_special_mailbox(usage, path) {
+                    Ok(()) => {
+                        self.context.replies.push_back(UIEvent::StatusEvent(
+                            StatusEvent::DisplayMessage(format!(
+                                "{account_name}: using `{path}` as the {} mailbox.",
+                                usage.to_string().to_lowercase()
+                            )),
+                        ));
+                    }
+                    Err(err) => {
+                        self.context.replies.push_back(UIEvent::Notification {
+                            title: Some("Could not set special mailbox.".into()),
+                            body: err.to_string().into(),
+                            kind: Some(NotificationType::Error(err.kind)),
+                            source: Some(err),
+                        });
+                    }
+                }
+            }
+            AccountAction(ref account_name, ImportContacts(format, ref path)) => {
+                let Some(account) = self
+                    .context
+                    .accounts
+                    .values_mut()
+                    .find(|a| a.name() == account_name)
+                else {
+                    self.context.replies.push_back(UIEvent::Notification {
+                        title: None,
+                        source: None,
+                        body: format!("Account {account_name} was not found.").into(),
+                        kind: Some(NotificationType::Error(ErrorKind::None)),
+                    });
+                    return;
+                };
+                match account.contacts.import(format, &path.expand()) {
+                    Ok(count) => {
+                        self.context.replies.push_back(UIEvent::Notification {
+                            title: None,
+                            source: None,
+                            body: format!(
+                                "Imported {count} contact{} from {format} {}.",
+                                if count == 1 { "" } else { "s" },
+                                path.display()
+                            )
+                            .into(),
+                            kind: Some(NotificationType::Info),
+                        });
+                    }
+                    Err(err) => {
+                        self.context.replies.push_back(UIEvent::Notification {
+                            title: Some("Could not import contacts.".into()),
+                            body: err.to_string().into(),
+                            kind: Some(NotificationType::Error(err.kind)),
+                            source: Some(err),
+                        });
+                    }
+                }
+            }
             AccountAction(ref account_name, PrintAccountSetting(ref setting)) => {
                 let path = setting.split('.').collect::<SmallVec<[&str; 16]>>();
                 if let Some(pos) = self
@@ -964,6 +1438,50 @@ impl State {
                     )))
                     .unwrap();
             }
+            Tab(ViewUsageStats) => {
+                let report = self.usage_stats.as_ref().map_or_else(
+                    || {
+                        "Usage statistics are not collected. Set `usage_stats.enabled` to \
+                         collect them."
+                            .to_string()
+                    },
+                    UsageStats::report,
+                );
+                self.rcv_event(UIEvent::Action(Tab(New(Some(Box::new(
+                    Pager::from_string(
+                        report,
+                        &self.context,
+                        None,
+                        None,
+                        crate::conf::value(&self.context, "theme_default"),
+                    ),
+                ))))));
+            }
+            PurgeUsageStats => match UsageStats::purge() {
+                Ok(()) => {
+                    if self.usage_stats.is_some() {
+                        self.usage_stats = Some(UsageStats::new());
+                    }
+                    self.show_display_message("Deleted the usage statistics.".to_string());
+                }
+                Err(err) => self.context.replies.push_back(UIEvent::Notification {
+                    title: Some("Could not delete the usage statistics".into()),
+                    source: None,
+                    body: err.to_string().into(),
+                    kind: Some(NotificationType::Error(err.kind)),
+                }),
+            },
+            Tab(ViewStartupProfile) => {
+                self.rcv_event(UIEvent::Action(Tab(New(Some(Box::new(
+                    Pager::from_string(
+                        self.startup_profile.report(),
+                        &self.context,
+                        None,
+                        None,
+                        crate::conf::value(&self.context, "theme_default"),
+                    ),
+                ))))));
+            }
             #[cfg(feature = "cli-docs")]
             Tab(Man(manpage)) => match manpage
                 .read(false)
@@ -991,6 +1509,26 @@ impl State {
         }
     }
 
+    /// Start or stop collecting usage statistics if `usage_stats.enabled`
+    /// has changed.
+    fn update_usage_stats(&mut self) {
+        match (
+            self.context.settings.usage_stats.enabled,
+            self.usage_stats.is_some(),
+        ) {
+            (true, false) => match UsageStats::load() {
+                Ok(stats) => self.usage_stats = Some(stats),
+                Err(err) => log::warn!("Could not load usage statistics: {err}"),
+            },
+            (false, true) => {
+                if let Some(Err(err)) = self.usage_stats.take().as_ref().map(UsageStats::save) {
+                    log::error!("Could not save usage statistics: {err}");
+                }
+            }
+            _ => {}
+        }
+    }
+
     /// The application's main loop sends `UIEvents` to state via this method.
     pub fn rcv_event(&mut self, mut event: UIEvent) {
         if let UIEvent::Input(_) = event {
@@ -998,12 +1536,28 @@ impl State {
                 self.message_box.expiration_start = Some(datetime::now());
             }
         }
+        if matches!(
+            event,
+            UIEvent::Input(_)
+                | UIEvent::CmdInput(_)
+                | UIEvent::InsertInput(_)
+                | UIEvent::EmbeddedInput(_)
+        ) && self.usage_stats.is_some()
+        {
+            let view = self.components.values().find_map(|c| c.view_name());
+            if let Some(stats) = self.usage_stats.as_mut() {
+                stats.input(view);
+            }
+        }
 
         match event {
             // Command type is handled only by State.
             UIEvent::Command(cmd) => {
                 match parse_command(cmd.as_bytes()) {
                     Ok(action) => {
+                        if let Some(stats) = self.usage_stats.as_mut() {
+                            stats.command(&cmd);
+                        }
                         if action.needs_confirmation() {
                             let new = Box::new(UIConfirmationDialog::new(
                                 "Are you sure?",
@@ -1077,6 +1631,24 @@ impl State {
                         }
                     }
                     Err(err) => {
+                        match self.context.plugins.command(&cmd) {
+                            Some(Ok(())) => {
+                                if let Some(stats) = self.usage_stats.as_mut() {
+                                    stats.command(&cmd);
+                                }
+                                return;
+                            }
+                            Some(Err(err)) => {
+                                self.context.replies.push_back(UIEvent::Notification {
+                                    title: Some(format!("Could not run `{cmd}`").into()),
+                                    source: None,
+                                    body: err.to_string().into(),
+                                    kind: Some(NotificationType::Error(err.kind)),
+                                });
+                                return;
+                            }
+                            None => {}
+                        }
                         self.context.replies.push_back(UIEvent::Notification {
                             title: Some(format!("Invalid command `{cmd}`").into()),
                             source: None,
@@ -1100,6 +1672,85 @@ impl State {
                 self.context.restore_input();
                 return;
             }
+            UIEvent::CopyToClipboard(ref text) => {
+                let Some(ref cmd) = self.context.settings.pager.clipboard_command else {
+                    self.screen.set_clipboard(text);
+                    if !self.context.terminal_capabilities.clipboard {
+                        self.context.replies.push_back(UIEvent::StatusEvent(
+                            StatusEvent::DisplayMessage(
+                                "Sent text to the terminal's clipboard with OSC 52, which your \
+                                 terminal might not support. Set `pager.clipboard_command` to \
+                                 copy with a command instead."
+                                    .to_string(),
+                            ),
+                        ));
+                    }
+                    return;
+                };
+                use std::{
+                    io::Write,
+                    process::{Command, Stdio},
+                };
+                // The command runs in a job: clipboard utilities such as xclip
+                // and wl-copy keep running in the background to serve the
+                // selection, so only wait for the shell to exit and do not
+                // capture any of their output.
+                let cmd = cmd.clone();
+                let text = text.clone();
+                let policy = self.context.command_policy(None);
+                let job = {
+                    let cmd = cmd.clone();
+                    async move {
+                        let mut child = Command::new("sh")
+                            .args(["-c", &cmd])
+                            .stdin(Stdio::piped())
+                            .stdout(Stdio::null())
+                            .stderr(Stdio::null())
+                            .spawn_checked(&policy)?;
+                        if let Some(mut stdin) = child.stdin.take() {
+                            stdin.write_all(text.as_bytes())?;
+                        }
+                        let status = child.wait()?;
+                        if !status.success() {
+                            return Err(Error::new(format!("`{cmd}` exited with {status}."))
+                                .set_kind(ErrorKind::External));
+                        }
+                        Ok(())
+                    }
+                };
+                let handle = self.context.main_loop_handler.job_executor.spawn(
+                    "clipboard".into(),
+                    job,
+                    IsAsync::Blocking,
+                );
+                self.clipboard_jobs.insert(handle.job_id, (cmd, handle));
+                return;
+            }
+            UIEvent::StatusEvent(StatusEvent::JobFinished(ref job_id))
+                if self
+                    .pending_accounts
+                    .iter()
+                    .any(|(_, h)| h.job_id == *job_id) =>
+            {
+                self.add_initialized_accounts();
+                return;
+            }
+            UIEvent::StatusEvent(StatusEvent::JobFinished(ref job_id))
+                if self.clipboard_jobs.contains_key(job_id) =>
+            {
+                let Some((cmd, mut handle)) = self.clipboard_jobs.remove(job_id) else {
+                    return;
+                };
+                if let Ok(Some(Err(err))) = handle.chan.try_recv() {
+                    self.context.replies.push_back(UIEvent::Notification {
+                        title: Some(format!("Could not copy with `{cmd}`").into()),
+                        source: None,
+                        body: err.to_string().into(),
+                        kind: Some(NotificationType::Error(err.kind)),
+                    });
+                }
+                return;
+            }
             UIEvent::Fork(
                 child @ ForkedProcess::Generic {
                     id: _,
@@ -1143,6 +1794,18 @@ impl State {
                 self.rcv_event(UIEvent::AccountStatusChange(account_hash, Some(message)));
                 return;
             }
+            UIEvent::BackendEvent(
+                _,
+                BackendEvent::Refresh(RefreshEvent {
+                    account_hash,
+                    mailbox_hash,
+                    kind: RefreshEventKind::MailboxCountsUpdate,
+                }),
+            ) => {
+                // Only the counts changed; don't load the mailbox.
+                self.rcv_event(UIEvent::MailboxUpdate((account_hash, mailbox_hash)));
+                return;
+            }
             UIEvent::BackendEvent(
                 _,
                 BackendEvent::Refresh(RefreshEvent {
@@ -1166,6 +1829,15 @@ impl State {
                 self.redraw();
                 return;
             }
+            UIEvent::Timer(id)
+                if self
+                    .context
+                    .accounts
+                    .values_mut()
+                    .any(|acc| acc.process_timer(id)) =>
+            {
+                return;
+            }
             UIEvent::Input(ref key)
                 if *key
                     == self
@@ -1184,6 +1856,10 @@ impl State {
                 self.message_box.show_next();
                 return;
             }
+            UIEvent::StatusEvent(StatusEvent::DisplayMessage(ref mut msg)) => {
+                self.show_display_message(std::mem::take(msg));
+                return;
+            }
             UIEvent::Notification {
                 ref title,
                 source: _,
@@ -1214,6 +1890,20 @@ impl State {
                 self.process_realizations();
                 return;
             }
+            UIEvent::MailboxUpdate((account_hash, mailbox_hash)) => {
+                self.context
+                    .apply_score_rules(account_hash, mailbox_hash, false);
+            }
+            UIEvent::ConfigReload { .. } => {
+                self.update_usage_stats();
+                // Score rules might have changed.
+                for (account_hash, account) in self.context.accounts.iter() {
+                    for mailbox_hash in account.mailbox_entries.keys() {
+                        self.context
+                            .apply_score_rules(*account_hash, *mailbox_hash, true);
+                    }
+                }
+            }
             _ => {}
         }
 
@@ -53,6 +53,40 @@ pub fn create_config(path: Option<PathOrStdio>) -> Result<()> {
     Ok(())
 }
 
+pub fn import_mutt(path: Option<PathOrStdio>, account_name: &str) -> Result<()> {
+    let config = match path {
+        Some(PathOrStdio::Stdio) => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            conf::muttrc::convert(&input, account_name)
+        }
+        Some(PathOrStdio::Path(path)) => conf::muttrc::import_muttrc(&path, account_name)?,
+        None => {
+            let xdg_config = std::env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("~/.config"));
+            let path = [
+                PathBuf::from("~/.muttrc"),
+                PathBuf::from("~/.mutt/muttrc"),
+                xdg_config.join("mutt/muttrc"),
+                PathBuf::from("~/.neomuttrc"),
+                xdg_config.join("neomutt/neomuttrc"),
+            ]
+            .into_iter()
+            .map(|p| p.expand())
+            .find(|p| p.is_file())
+            .ok_or_else(|| {
+                Error::new("Could not find a mutt configuration file.")
+                    .set_details("Specify its path as an argument.")
+                    .set_kind(ErrorKind::NotFound)
+            })?;
+            conf::muttrc::import_muttrc(&path, account_name)?
+        }
+    };
+    std::io::stdout().write_all(config.as_bytes())?;
+    Ok(())
+}
+
 pub fn edit_config() -> Result<()> {
     let editor = std::env::var("EDITOR")
         .or_else(|_| std::env::var("VISUAL"))
@@ -142,6 +176,29 @@ pub fn test_config(path: Option<PathOrStdio>) -> Result<()> {
     Ok(())
 }
 
+pub fn migrate(dry_run: bool, take_over_lock: bool) -> Result<()> {
+    // Files can't be migrated while another instance is using them.
+    let _instance_lock = if dry_run {
+        None
+    } else {
+        Some(instance_lock::InstanceLock::acquire(take_over_lock)?)
+    };
+    let mut stdout = std::io::stdout();
+    let settings = if dry_run {
+        let config_path = conf::get_config_file()?;
+        version_migrations::print_pending_migrations(&config_path, &mut stdout)?;
+        conf::FileSettings::validate(config_path, false)?
+    } else {
+        // Performs pending configuration migrations, asking first.
+        conf::FileSettings::new()?
+    };
+    version_migrations::artifacts::migrate_artifacts(
+        settings.accounts.keys().map(String::as_str),
+        dry_run,
+        &mut stdout,
+    )
+}
+
 pub fn view(
     path: PathBuf,
     sender: Sender<ThreadEvent>,
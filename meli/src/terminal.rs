@@ -24,8 +24,10 @@
 use serde::{de, de::Visitor, Deserialize, Deserializer};
 
 mod braille;
+mod capabilities;
 mod color;
 mod screen;
+pub use capabilities::*;
 pub use color::*;
 #[macro_use]
 pub mod cells;
@@ -195,6 +197,108 @@ impl<
     }
 }
 
+/// Longest link target that is made into an `OSC 8` hyperlink.
+pub const HYPERLINK_MAX_LEN: usize = 2048;
+
+/// Make `url` safe to use as the target of an `OSC 8` hyperlink.
+///
+/// Link targets may only contain printable ASCII characters; any other byte,
+/// including control characters that could terminate the escape sequence
+/// early, is percent-encoded. Returns `None` if `url` is empty or longer than
+/// [`HYPERLINK_MAX_LEN`].
+pub fn sanitize_hyperlink_target(url: &str) -> Option<Cow<'_, str>> {
+    if url.is_empty() || url.len() > HYPERLINK_MAX_LEN {
+        return None;
+    }
+    if url.bytes().all(|b| (0x21..=0x7e).contains(&b)) {
+        return Some(Cow::Borrowed(url));
+    }
+    let mut ret = String::with_capacity(url.len() + 16);
+    for b in url.bytes() {
+        if (0x21..=0x7e).contains(&b) {
+            ret.push(b as char);
+        } else {
+            use std::fmt::Write;
+
+            _ = write!(ret, "%{b:02X}");
+        }
+    }
+    Some(Cow::Owned(ret))
+}
+
+/// Whether the terminal is known to support `OSC 8` hyperlinks, judging from
+/// the environment variables `var` returns, see
+/// [`TerminalCapabilities::hyperlinks`].
+fn supports_hyperlinks_with(var: impl Fn(&str) -> Option<String>) -> bool {
+    let term = var("TERM").unwrap_or_default();
+    // Multiplexers need to be configured to pass hyperlinks through.
+    if term.starts_with("screen") || term.starts_with("tmux") {
+        return false;
+    }
+    if [
+        "xterm-kitty",
+        "foot",
+        "wezterm",
+        "alacritty",
+        "xterm-ghostty",
+    ]
+    .iter()
+    .any(|t| term.starts_with(t))
+    {
+        return true;
+    }
+    if matches!(
+        var("TERM_PROGRAM").as_deref(),
+        Some("WezTerm" | "iTerm.app" | "vscode" | "ghostty")
+    ) {
+        return true;
+    }
+    if [
+        "KITTY_WINDOW_ID",
+        "WEZTERM_EXECUTABLE",
+        "WT_SESSION",
+        "KONSOLE_VERSION",
+    ]
+    .iter()
+    .any(|v| var(v).is_some())
+    {
+        return true;
+    }
+    // VTE based terminals support hyperlinks since version 0.50.
+    var("VTE_VERSION")
+        .and_then(|v| v.parse::<u32>().ok())
+        .is_some_and(|v| v >= 5000)
+}
+
+/// `OSC 52` sequence that sets the terminal's clipboard selection to the
+/// given text.
+#[derive(Clone, Copy, Debug)]
+pub struct SetClipboard<'a>(pub &'a str);
+
+impl std::fmt::Display for SetClipboard<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        write!(f, "\x1b]52;c;")?;
+        for chunk in self.0.as_bytes().chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0_u32, |acc, (i, b)| acc | (u32::from(*b) << (16 - 8 * i)));
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    let idx = (n >> (18 - 6 * i)) & 0x3f;
+                    write!(f, "{}", ALPHABET[idx as usize] as char)?;
+                } else {
+                    write!(f, "=")?;
+                }
+            }
+        }
+        write!(f, "\x07")
+    }
+}
+
 /// Create an OSC-introduced sequence.
 macro_rules! osc {
     ($( $l:expr ),*) => { concat!("\x1b]", $( $l ),*) };
@@ -0,0 +1,120 @@
+/*
+ * meli
+ *
+ * Copyright 2024 Manos Pitsidianakis
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Detection of what the terminal can display.
+//!
+//! Capabilities are guessed at startup from the terminal type (`TERM`),
+//! `COLORTERM`, the locale and variables that terminal emulators set, and
+//! stored in [`Context`](crate::Context) so that components can degrade
+//! gracefully instead of assuming a modern terminal.
+
+/// Colors a terminal can display.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ColorSupport {
+    /// No colors, e.g. the `dumb` terminal type.
+    None,
+    /// The 16 ANSI colors.
+    Ansi16,
+    /// The 256 color palette.
+    Ansi256,
+    /// 24-bit RGB colors.
+    #[default]
+    TrueColor,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TerminalCapabilities {
+    pub colors: ColorSupport,
+    /// Whether the locale uses UTF-8, so that box drawing and other non-ASCII
+    /// characters can be displayed.
+    pub unicode: bool,
+    /// Support for `OSC 8` hyperlinks.
+    pub hyperlinks: bool,
+    /// Support for setting the clipboard with `OSC 52`.
+    pub clipboard: bool,
+}
+
+impl Default for TerminalCapabilities {
+    fn default() -> Self {
+        Self {
+            colors: ColorSupport::TrueColor,
+            unicode: true,
+            hyperlinks: false,
+            clipboard: false,
+        }
+    }
+}
+
+impl TerminalCapabilities {
+    /// Guess the capabilities of the terminal `meli` runs in.
+    pub fn detect() -> Self {
+        Self::detect_with(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+    }
+
+    pub(super) fn detect_with(var: impl Fn(&str) -> Option<String>) -> Self {
+        let term = var("TERM").unwrap_or_default();
+        let term_program = var("TERM_PROGRAM").unwrap_or_default();
+        let multiplexer = term.starts_with("screen") || term.starts_with("tmux");
+        let kitty = term == "xterm-kitty" || (!multiplexer && var("KITTY_WINDOW_ID").is_some());
+        let modern = kitty
+            || ["foot", "wezterm", "alacritty", "xterm-ghostty", "contour"]
+                .iter()
+                .any(|t| term.starts_with(t))
+            || matches!(
+                term_program.as_str(),
+                "WezTerm" | "iTerm.app" | "vscode" | "ghostty"
+            );
+        let vte_version = var("VTE_VERSION").and_then(|v| v.parse::<u32>().ok());
+
+        let colors = if term.is_empty() || term == "dumb" {
+            ColorSupport::None
+        } else if matches!(var("COLORTERM").as_deref(), Some("truecolor" | "24bit"))
+            || term.ends_with("-direct")
+            || term.contains("truecolor")
+            || modern
+            || vte_version.is_some_and(|v| v >= 3600)
+            || var("KONSOLE_VERSION").is_some()
+        {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") || term.starts_with("xterm") || multiplexer {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Ansi16
+        };
+
+        // The first of these that is set decides the character encoding.
+        let unicode = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|v| var(v))
+            .is_none_or(|locale| {
+                let locale = locale.to_ascii_lowercase();
+                locale.contains("utf-8") || locale.contains("utf8")
+            });
+
+        Self {
+            colors,
+            unicode,
+            hyperlinks: super::supports_hyperlinks_with(&var),
+            // tmux forwards OSC 52 to the outer terminal by default.
+            clipboard: modern || term.starts_with("tmux"),
+        }
+    }
+}
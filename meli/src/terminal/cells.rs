@@ -23,6 +23,7 @@
 //! foreground/background colors and attributes.
 
 use std::{
+    borrow::Cow,
     collections::BTreeMap,
     convert::From,
     ops::{Deref, DerefMut, Index, IndexMut},
@@ -30,12 +31,12 @@ use std::{
 
 use melib::{
     log,
-    text::{search::KMP, wcwidth, TextPresentation},
+    text::{search::KMP, wcwidth, TextPresentation, TextProcessing},
 };
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use smallvec::SmallVec;
 
-use super::{Area, Color, Pos, ScreenGeneration};
+use super::{Area, Color, ColorSupport, Pos, ScreenGeneration};
 use crate::{state::Context, ThemeAttribute};
 
 /// In a scroll region up and down cursor movements shift the region vertically.
@@ -71,6 +72,9 @@ pub struct CellBuffer {
     pub force_text_presentation: bool,
     /// Use color.
     pub use_color: bool,
+    /// Colors the terminal can display, others are replaced with the closest
+    /// one when drawn.
+    pub color_support: ColorSupport,
     /// Draw `OSC8` hyperlinks.
     pub draw_hyperlinks: bool,
     pub tab_width: u8,
@@ -93,6 +97,7 @@ impl std::fmt::Debug for CellBuffer {
             .field("ascii_drawing", &self.ascii_drawing)
             .field("force_text_presentation", &self.force_text_presentation)
             .field("use_color", &self.use_color)
+            .field("color_support", &self.color_support)
             .field("tab_width", &self.tab_width)
             .field("growable", &self.growable)
             .field("tag_table", &self.tag_table)
@@ -119,6 +124,7 @@ impl CellBuffer {
             force_text_presentation: false,
             draw_hyperlinks: false,
             use_color: false,
+            color_support: ColorSupport::TrueColor,
             tab_width: 4,
             tag_table: Default::default(),
             tag_associations: SmallVec::new(),
@@ -147,6 +153,7 @@ impl CellBuffer {
             draw_hyperlinks: false,
             force_text_presentation: false,
             use_color: true,
+            color_support: ColorSupport::TrueColor,
             tab_width: 4,
             tag_table: Default::default(),
             tag_associations: SmallVec::new(),
@@ -166,10 +173,11 @@ impl CellBuffer {
             ret
         });
         Self {
-            ascii_drawing: context.settings.terminal.ascii_drawing,
+            ascii_drawing: context.ascii_drawing(),
             force_text_presentation: context.settings.terminal.use_text_presentation(),
-            use_color: context.settings.terminal.use_color(),
-            draw_hyperlinks: context.settings.terminal.draw_hyperlinks(),
+            use_color: context.use_color(),
+            color_support: context.terminal_capabilities.colors,
+            draw_hyperlinks: context.draw_hyperlinks(),
             ..Self::new(default_cell, area)
         }
     }
@@ -187,6 +195,10 @@ impl CellBuffer {
         self.use_color = new_val;
     }
 
+    pub fn set_color_support(&mut self, new_val: ColorSupport) {
+        self.color_support = new_val;
+    }
+
     pub fn set_tab_width(&mut self, new_val: u8) {
         self.tab_width = new_val;
     }
@@ -216,8 +228,9 @@ impl CellBuffer {
                 .set_attrs(theme_default.attrs);
             ret
         };
-        self.ascii_drawing = context.settings.terminal.ascii_drawing;
-        self.use_color = context.settings.terminal.use_color();
+        self.ascii_drawing = context.ascii_drawing();
+        self.use_color = context.use_color();
+        self.color_support = context.terminal_capabilities.colors;
         self.force_text_presentation = context.settings.terminal.use_text_presentation();
 
         let newlen = newcols * newrows;
@@ -531,17 +544,21 @@ impl CellBuffer {
         }
     }
 
-    pub fn insert_uri(&mut self, uri: &str) -> u64 {
+    /// Add `uri` to the hyperlinks table and return its key, or `None` if it
+    /// cannot be used as a link target (see
+    /// [`sanitize_hyperlink_target`](crate::terminal::sanitize_hyperlink_target)).
+    pub fn insert_uri(&mut self, uri: &str) -> Option<u64> {
         use std::{
             collections::hash_map::DefaultHasher,
             hash::{Hash, Hasher},
         };
 
+        let uri = crate::terminal::sanitize_hyperlink_target(uri)?;
         let mut hasher = DefaultHasher::new();
         uri.hash(&mut hasher);
         let hash = hasher.finish();
         self.hyperlinks_table.insert(hash, uri.into());
-        hash
+        Some(hash)
     }
 
     pub fn set_uri(&mut self, uri: u64, start: Pos, end: Pos) {
@@ -550,6 +567,39 @@ impl CellBuffer {
         }
     }
 
+    /// Make the URLs and e-mail addresses in `text`, which has been written at
+    /// the start of the single row `area`, into hyperlinks.
+    ///
+    /// Hyperlinks previously set in `area` are removed.
+    pub fn set_uris_from_text(&mut self, text: &str, area: Area) {
+        for c in self.bounds_iter(area).flatten() {
+            self.hyperlinks_associations.remove(&c);
+        }
+        if area.is_empty() {
+            return;
+        }
+        let width = area.width();
+        for link in linkify::LinkFinder::new().links(text) {
+            let target: Cow<'_, str> = match link.kind() {
+                linkify::LinkKind::Url => link.as_str().into(),
+                linkify::LinkKind::Email => format!("mailto:{}", link.as_str()).into(),
+                _ => continue,
+            };
+            let start = text[..link.start()].grapheme_width();
+            if start >= width {
+                break;
+            }
+            let end = (start + link.as_str().grapheme_width()).min(width);
+            if let Some(uri) = self.insert_uri(&target) {
+                self.set_uri(
+                    uri,
+                    area.skip_cols(start).upper_left(),
+                    area.skip_cols(end).upper_left(),
+                );
+            }
+        }
+    }
+
     #[inline(always)]
     pub fn generation(&self) -> ScreenGeneration {
         self.area.generation()
@@ -629,7 +679,11 @@ impl CellBuffer {
             let diff_y = get_y(dest.upper_left()) as isize - get_y(src.upper_left()) as isize;
             self.hyperlinks_table
                 .extend(grid_src.hyperlinks_table.clone());
-            for (start, (uri, end)) in &grid_src.hyperlinks_associations {
+            let (src_start, src_end) = (src.upper_left(), src.bottom_right());
+            for (start, (uri, end)) in grid_src.hyperlinks_associations.iter().filter(|(p, _)| {
+                (get_x(src_start)..=get_x(src_end)).contains(&get_x(**p))
+                    && (get_y(src_start)..=get_y(src_end)).contains(&get_y(**p))
+            }) {
                 let new_start = (
                     (get_x(*start) as isize + diff_x) as usize,
                     (get_y(*start) as isize + diff_y) as usize,
@@ -22,6 +22,8 @@
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use termion::color::{AnsiValue, Rgb as TermionRgb};
 
+use super::ColorSupport;
+
 /// The color of a `Cell`.
 ///
 /// `Color::Default` represents the default color of the underlying terminal.
@@ -1069,6 +1071,125 @@ impl Color {
             Self::Default => (0, 0, 0),
         }
     }
+
+    /// The closest color that a terminal with `support` can display.
+    pub fn downgrade(self, support: ColorSupport) -> Self {
+        match (self, support) {
+            (Self::Rgb(r, g, b), ColorSupport::Ansi256) => Self::Byte(rgb_to_256((r, g, b))),
+            (Self::Rgb(r, g, b), ColorSupport::Ansi16) => Self::Byte(rgb_to_16((r, g, b))),
+            (Self::Byte(n), ColorSupport::Ansi16) if n >= 16 => {
+                Self::Byte(rgb_to_16(byte_to_rgb(n)))
+            }
+            _ => self,
+        }
+    }
+}
+
+/// Levels of the components of the 6x6x6 color cube of the 256 color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The 16 ANSI colors, as xterm displays them by default.
+const ANSI16_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+fn byte_to_rgb(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => ANSI16_RGB[usize::from(n)],
+        16..=231 => {
+            let n = usize::from(n - 16);
+            (
+                CUBE_LEVELS[n / 36],
+                CUBE_LEVELS[(n / 6) % 6],
+                CUBE_LEVELS[n % 6],
+            )
+        }
+        _ => {
+            let v = 8 + 10 * (n - 232);
+            (v, v, v)
+        }
+    }
+}
+
+fn rgb_distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).unsigned_abs().pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// The closest color of the 256 color palette, either from its color cube or
+/// its grayscale ramp.
+fn rgb_to_256(rgb: (u8, u8, u8)) -> u8 {
+    let cube_index = |v: u8| {
+        (0..6_u8)
+            .min_by_key(|i| (i32::from(CUBE_LEVELS[usize::from(*i)]) - i32::from(v)).abs())
+            .unwrap_or_default()
+    };
+    let cube = 16 + 36 * cube_index(rgb.0) + 6 * cube_index(rgb.1) + cube_index(rgb.2);
+    let average = ((u16::from(rgb.0) + u16::from(rgb.1) + u16::from(rgb.2)) / 3) as u8;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23);
+    if rgb_distance(byte_to_rgb(gray), rgb) < rgb_distance(byte_to_rgb(cube), rgb) {
+        gray
+    } else {
+        cube
+    }
+}
+
+fn rgb_to_16(rgb: (u8, u8, u8)) -> u8 {
+    (0..16_u8)
+        .min_by_key(|i| rgb_distance(ANSI16_RGB[usize::from(*i)], rgb))
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_color_downgrade() {
+    assert_eq!(
+        Color::Rgb(1, 2, 3).downgrade(ColorSupport::TrueColor),
+        Color::Rgb(1, 2, 3)
+    );
+    assert_eq!(
+        Color::Rgb(255, 255, 255).downgrade(ColorSupport::Ansi256),
+        Color::Byte(231)
+    );
+    assert_eq!(
+        Color::Rgb(255, 102, 0).downgrade(ColorSupport::Ansi256),
+        Color::Byte(202)
+    );
+    // Grays are taken from the grayscale ramp.
+    assert_eq!(
+        Color::Rgb(46, 52, 64).downgrade(ColorSupport::Ansi256),
+        Color::Byte(237)
+    );
+    assert_eq!(
+        Color::Rgb(250, 10, 10).downgrade(ColorSupport::Ansi16),
+        Color::Byte(9)
+    );
+    assert_eq!(
+        Color::Byte(196).downgrade(ColorSupport::Ansi16),
+        Color::Byte(9)
+    );
+    assert_eq!(
+        Color::Byte(3).downgrade(ColorSupport::Ansi16),
+        Color::Byte(3)
+    );
+    assert_eq!(
+        Color::Default.downgrade(ColorSupport::Ansi16),
+        Color::Default
+    );
 }
 
 // Implementation of determining the perceived lightness
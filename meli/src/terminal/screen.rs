@@ -31,7 +31,7 @@ use crate::{
         DisableAlternateScrollMode, DisableMouse, DisableSGRMouse, DisableWraparoundMode,
         EnableAlternateScrollMode, EnableMouse, EnableSGRMouse, Pos, QueryBackground,
         QueryForeground, QuerySynchronizedOutputSupport, RestoreWindowTitleIconFromStack,
-        RestoreWraparoundMode, SaveWindowTitleIconToStack, SaveWraparoundMode,
+        RestoreWraparoundMode, SaveWindowTitleIconToStack, SaveWraparoundMode, SetClipboard,
     },
     Attr, Context, ThemeAttribute,
 };
@@ -425,6 +425,21 @@ impl Screen<Tty> {
         }
     }
 
+    /// Show the terminal cursor at `pos`, so that screen readers and braille
+    /// displays, which follow the cursor, read the focused line.
+    pub fn show_cursor_at(&mut self, (x, y): Pos) {
+        let Some(stdout) = self.display.stdout.as_mut() else {
+            return;
+        };
+        write!(
+            stdout,
+            "{}{}",
+            cursor::Goto(x as u16 + 1, y as u16 + 1),
+            cursor::Show
+        )
+        .unwrap();
+    }
+
     /// Draw only a specific `area` on the screen.
     pub fn draw_horizontal_segment(
         grid: &mut CellBuffer,
@@ -443,6 +458,7 @@ impl Screen<Tty> {
         let mut current_attrs = Attr::DEFAULT;
         let mut current_uri = None;
         let draw_hyperlinks = grid.draw_hyperlinks;
+        let color_support = grid.color_support;
         write!(stdout, "\x1B[m").unwrap();
         for x in xs {
             let c = &grid[(x, y)];
@@ -464,13 +480,17 @@ impl Screen<Tty> {
                 c.attrs().write(current_attrs, stdout).unwrap();
                 current_attrs = c.attrs();
             }
-            if c.bg() != current_bg {
-                c.bg().write_bg(stdout).unwrap();
-                current_bg = c.bg();
+            let (fg, bg) = (
+                c.fg().downgrade(color_support),
+                c.bg().downgrade(color_support),
+            );
+            if bg != current_bg {
+                bg.write_bg(stdout).unwrap();
+                current_bg = bg;
             }
-            if c.fg() != current_fg {
-                c.fg().write_fg(stdout).unwrap();
-                current_fg = c.fg();
+            if fg != current_fg {
+                fg.write_fg(stdout).unwrap();
+                current_fg = fg;
             }
             if !c.empty() {
                 write!(stdout, "{}", c.ch()).unwrap();
@@ -524,6 +544,15 @@ impl Screen<Tty> {
             .expect("Could not write to stdout");
         _ = stdout.flush();
     }
+
+    /// Ask the terminal to set its clipboard selection to `text`.
+    pub fn set_clipboard(&mut self, text: &str) {
+        let Some(stdout) = self.display.stdout.as_mut() else {
+            return;
+        };
+        write!(stdout, "{}", SetClipboard(text)).expect("Could not write to stdout");
+        _ = stdout.flush();
+    }
 }
 
 impl Screen<Virtual> {
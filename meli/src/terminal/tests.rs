@@ -52,3 +52,125 @@ fn test_terminal_osc8_print() {
         assert_eq!(&input.to_string(), output);
     }
 }
+
+#[test]
+fn test_terminal_osc8_sanitize_target() {
+    use crate::terminal::{sanitize_hyperlink_target, HYPERLINK_MAX_LEN};
+
+    assert_eq!(
+        sanitize_hyperlink_target("https://meli-email.org/").as_deref(),
+        Some("https://meli-email.org/")
+    );
+    // Control characters must not be able to end the escape sequence.
+    assert_eq!(
+        sanitize_hyperlink_target("https://example.com/\x07\x1b]8;;evil").as_deref(),
+        Some("https://example.com/%07%1B]8;;evil")
+    );
+    assert_eq!(
+        sanitize_hyperlink_target("https://example.com/a b/ü").as_deref(),
+        Some("https://example.com/a%20b/%C3%BC")
+    );
+    assert_eq!(sanitize_hyperlink_target(""), None);
+    assert_eq!(
+        sanitize_hyperlink_target(&"a".repeat(HYPERLINK_MAX_LEN + 1)),
+        None
+    );
+}
+
+#[test]
+fn test_terminal_supports_hyperlinks() {
+    use super::supports_hyperlinks_with;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |var| {
+            vars.iter()
+                .find(|(k, _)| *k == var)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    assert!(supports_hyperlinks_with(env(&[("TERM", "xterm-kitty")])));
+    assert!(supports_hyperlinks_with(env(&[("TERM", "foot-extra")])));
+    assert!(supports_hyperlinks_with(env(&[
+        ("TERM", "xterm-256color"),
+        ("TERM_PROGRAM", "WezTerm")
+    ])));
+    assert!(supports_hyperlinks_with(env(&[
+        ("TERM", "xterm-256color"),
+        ("VTE_VERSION", "7600")
+    ])));
+    assert!(!supports_hyperlinks_with(env(&[
+        ("TERM", "xterm-256color"),
+        ("VTE_VERSION", "4800")
+    ])));
+    assert!(!supports_hyperlinks_with(env(&[(
+        "TERM",
+        "xterm-256color"
+    )])));
+    assert!(!supports_hyperlinks_with(env(&[
+        ("TERM", "tmux-256color"),
+        ("KITTY_WINDOW_ID", "1")
+    ])));
+    assert!(!supports_hyperlinks_with(env(&[])));
+}
+
+#[test]
+fn test_terminal_osc52_set_clipboard() {
+    use crate::terminal::SetClipboard;
+
+    assert_eq!(SetClipboard("").to_string(), "\x1b]52;c;\x07");
+    assert_eq!(SetClipboard("f").to_string(), "\x1b]52;c;Zg==\x07");
+    assert_eq!(SetClipboard("fo").to_string(), "\x1b]52;c;Zm8=\x07");
+    assert_eq!(SetClipboard("foo").to_string(), "\x1b]52;c;Zm9v\x07");
+    assert_eq!(
+        SetClipboard("> quoted line\n").to_string(),
+        "\x1b]52;c;PiBxdW90ZWQgbGluZQo=\x07"
+    );
+    assert_eq!(SetClipboard("ü").to_string(), "\x1b]52;c;w7w=\x07");
+}
+
+#[test]
+fn test_terminal_capabilities_detect() {
+    use crate::terminal::{ColorSupport, TerminalCapabilities};
+
+    fn detect(vars: &[(&str, &str)]) -> TerminalCapabilities {
+        TerminalCapabilities::detect_with(|var| {
+            vars.iter()
+                .find(|(k, _)| *k == var)
+                .map(|(_, v)| v.to_string())
+        })
+    }
+
+    let caps = detect(&[("TERM", "xterm-kitty"), ("LANG", "en_US.UTF-8")]);
+    assert_eq!(caps.colors, ColorSupport::TrueColor);
+    assert!(caps.unicode);
+    assert!(caps.hyperlinks);
+    assert!(caps.clipboard);
+
+    let caps = detect(&[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")]);
+    assert_eq!(caps.colors, ColorSupport::TrueColor);
+    assert!(!caps.clipboard);
+    assert!(!caps.hyperlinks);
+
+    let caps = detect(&[("TERM", "tmux-256color"), ("KITTY_WINDOW_ID", "1")]);
+    assert_eq!(caps.colors, ColorSupport::Ansi256);
+    assert!(caps.clipboard);
+    assert!(!caps.hyperlinks);
+
+    let caps = detect(&[("TERM", "linux"), ("LANG", "C")]);
+    assert_eq!(caps.colors, ColorSupport::Ansi16);
+    assert!(!caps.unicode);
+
+    // LC_ALL overrides LANG.
+    let caps = detect(&[
+        ("TERM", "foot"),
+        ("LC_ALL", "POSIX"),
+        ("LANG", "el_GR.utf8"),
+    ]);
+    assert!(!caps.unicode);
+    assert!(caps.hyperlinks);
+
+    assert_eq!(detect(&[("TERM", "dumb")]).colors, ColorSupport::None);
+    assert_eq!(detect(&[]).colors, ColorSupport::None);
+    assert!(detect(&[]).unicode);
+}
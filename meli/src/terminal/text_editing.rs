@@ -188,9 +188,18 @@ impl UText {
         true
     }
 
-    pub fn cut_left(&mut self) {
+    /// Insert string `s` in position `self.cursor_pos` and move the cursor
+    /// after it.
+    pub fn insert_str(&mut self, s: &str) {
+        for c in s.chars() {
+            self.insert_char(c);
+        }
+    }
+
+    /// Remove everything before the cursor and return it.
+    pub fn cut_left(&mut self) -> String {
         if self.content.is_empty() {
-            return;
+            return String::new();
         }
         let offset = {
             let (left, _) = self.content.split_at(self.cursor_pos);
@@ -200,7 +209,37 @@ impl UText {
         };
         self.cursor_pos = 0;
         self.grapheme_cursor_pos = 0;
-        self.content.drain(..offset).count();
+        self.content.drain(..offset).collect()
+    }
+
+    /// Remove everything after the cursor and return it.
+    pub fn cut_right(&mut self) -> String {
+        self.content.split_off(self.cursor_pos)
+    }
+
+    /// Remove the word before the cursor along with any whitespace between
+    /// them, and return it.
+    pub fn cut_previous_word(&mut self) -> String {
+        let end = self.cursor_pos;
+        let left = &self.content[..end];
+        let start = left
+            .trim_end()
+            .trim_end_matches(|c: char| !c.is_whitespace())
+            .len();
+        self.set_cursor(start);
+        self.content.drain(start..end).collect()
+    }
+
+    /// Remove the word after the cursor along with any whitespace between
+    /// them, and return it.
+    pub fn cut_next_word(&mut self) -> String {
+        let start = self.cursor_pos;
+        let right = &self.content[start..];
+        let word = right.trim_start();
+        let end = start
+            + (right.len() - word.len())
+            + word.find(char::is_whitespace).unwrap_or(word.len());
+        self.content.drain(start..end).collect()
     }
 
     /// Transpose the two graphemes around the cursor.
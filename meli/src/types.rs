@@ -53,6 +53,8 @@ use crate::components::{Component, ComponentId, ScrollUpdate};
 #[macro_use]
 mod helpers;
 pub use helpers::*;
+mod command_policy;
+pub use command_policy::*;
 
 pub type UIMessage = Box<dyn 'static + std::any::Any + Send + Sync>;
 
@@ -67,6 +69,8 @@ pub enum StatusEvent {
     JobCanceled(JobId),
     SetMouse(bool),
     ScrollUpdate(ScrollUpdate),
+    /// Show a message in the on-screen message box.
+    DisplayMessage(String),
 }
 
 /// [`ThreadEvent`] encapsulates all of the possible values we need to transfer
@@ -345,6 +349,9 @@ pub enum UIEvent {
     MailboxDelete((AccountHash, MailboxHash)),
     MailboxCreate((AccountHash, MailboxHash)),
     AccountStatusChange(AccountHash, Option<Cow<'static, str>>),
+    /// An account finished initializing after startup and was appended to
+    /// the context's accounts.
+    AccountAdded(AccountHash),
     ComponentUnrealize(ComponentId),
     BackendEvent(AccountHash, BackendEvent),
     StartupCheck(MailboxHash),
@@ -370,6 +377,9 @@ pub enum UIEvent {
         old_settings: Box<crate::conf::Settings>,
     },
     VisibilityChange(bool),
+    /// Copy text to the clipboard, either with the `pager.clipboard_command`
+    /// setting or the terminal's `OSC 52` support.
+    CopyToClipboard(String),
 }
 
 pub struct CallbackFn(pub Box<dyn FnOnce(&mut crate::Context) + Send + 'static>);
@@ -601,6 +611,125 @@ pub struct Link<'a> {
     pub kind: LinkKind,
 }
 
+impl Link<'_> {
+    /// The link's target, i.e. its value with a `mailto:` scheme prepended if
+    /// it is an e-mail address.
+    pub fn target(&self) -> Cow<'_, str> {
+        match self.kind {
+            LinkKind::Url => Cow::Borrowed(&self.value),
+            LinkKind::Email => Cow::Owned(format!("mailto:{}", self.value)),
+        }
+    }
+
+    /// Whether the link is a URL whose host may be deceptive, see
+    /// [`url_is_suspicious`].
+    pub fn is_suspicious(&self) -> bool {
+        self.kind == LinkKind::Url && url_is_suspicious(&self.value)
+    }
+}
+
+/// Marker placed before link targets that are revealed because they are
+/// suspicious, e.g. `[⚠ https://example.com]`.
+pub const SUSPICIOUS_LINK_MARKER: &str = "⚠";
+
+/// The host of a URL with a `scheme://` prefix, without user information or
+/// port.
+pub fn url_host(url: &str) -> Option<&str> {
+    let (_, rest) = url.trim().split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default();
+    (!host.is_empty()).then_some(host)
+}
+
+/// Whether the host of `url` may imitate another: it is an internationalized
+/// domain name, either in punycode (`xn--`) or in Unicode form, which can
+/// contain look-alike characters, or it is preceded by user information that
+/// can pose as a host, as in `https://bank.example.com@example.org`.
+pub fn url_is_suspicious(url: &str) -> bool {
+    let Some(host) = url_host(url) else {
+        return false;
+    };
+    let has_userinfo = url
+        .split_once("://")
+        .map(|(_, rest)| rest.split(['/', '?', '#']).next().unwrap_or_default())
+        .is_some_and(|authority| authority.contains('@'));
+    has_userinfo
+        || !host.is_ascii()
+        || host.split('.').any(|label| {
+            label
+                .get(..4)
+                .is_some_and(|p| p.eq_ignore_ascii_case("xn--"))
+        })
+}
+
+/// Remove the query parameters of the URLs in `text` whose names match one of
+/// `rules`, e.g. tracking parameters.
+///
+/// A rule is a glob pattern of parameter names, e.g. `utm_*`. A rule preceded
+/// by a domain and `:`, e.g. `example.com:ref`, only applies to URLs of that
+/// domain and its subdomains.
+pub fn strip_url_parameters(text: &str, rules: &[String]) -> String {
+    use melib::utils::fnmatch::Fnmatch;
+
+    if rules.is_empty() {
+        return text.to_string();
+    }
+    let matches = |host: &str, name: &str| {
+        let name = name.to_ascii_lowercase();
+        rules.iter().any(|rule| {
+            let (domain, pattern) = match rule.split_once(':') {
+                Some((domain, pattern)) => (Some(domain), pattern),
+                None => (None, rule.as_str()),
+            };
+            domain.map_or(true, |domain| {
+                host.eq_ignore_ascii_case(domain)
+                    || host
+                        .to_ascii_lowercase()
+                        .ends_with(&format!(".{}", domain.to_ascii_lowercase()))
+            }) && name.fnmatches(&pattern.to_ascii_lowercase())
+        })
+    };
+    let mut ret = String::with_capacity(text.len());
+    let mut last = 0;
+    for link in linkify::LinkFinder::new()
+        .kinds(&[linkify::LinkKind::Url])
+        .links(text)
+    {
+        let url = link.as_str();
+        let (url_base, fragment) = match url.split_once('#') {
+            Some((url_base, fragment)) => (url_base, Some(fragment)),
+            None => (url, None),
+        };
+        let Some((path, query)) = url_base.split_once('?') else {
+            continue;
+        };
+        let host = url_host(url).unwrap_or_default();
+        let params = query.split('&').collect::<Vec<_>>();
+        let kept = params
+            .iter()
+            .filter(|p| !matches(host, p.split('=').next().unwrap_or_default()))
+            .copied()
+            .collect::<Vec<_>>();
+        if kept.len() == params.len() {
+            continue;
+        }
+        ret.push_str(&text[last..link.start()]);
+        ret.push_str(path);
+        if !kept.is_empty() {
+            ret.push('?');
+            ret.push_str(&kept.join("&"));
+        }
+        if let Some(fragment) = fragment {
+            ret.push('#');
+            ret.push_str(fragment);
+        }
+        last = link.end();
+    }
+    ret.push_str(&text[last..]);
+    ret
+}
+
 #[cfg(test)]
 mod tests {
     //use super::*;
@@ -678,4 +807,45 @@ mod tests {
         }
         */
     }
+
+    #[test]
+    fn test_types_url_is_suspicious() {
+        use super::{url_host, url_is_suspicious};
+
+        assert_eq!(url_host("https://example.com/path"), Some("example.com"));
+        assert_eq!(
+            url_host("http://user@mail.example.com:8080?q"),
+            Some("mail.example.com")
+        );
+        assert_eq!(url_host("mailto:user@example.com"), None);
+
+        assert!(!url_is_suspicious("https://www.example.com/login"));
+        assert!(url_is_suspicious("https://xn--exmple-cua.com/login"));
+        assert!(url_is_suspicious("https://еxample.com/login"));
+        assert!(url_is_suspicious("https://bank.example.com@example.org/"));
+        assert!(!url_is_suspicious(
+            "https://example.com/?from=a@example.org"
+        ));
+    }
+
+    #[test]
+    fn test_types_strip_url_parameters() {
+        use super::strip_url_parameters;
+
+        let rules = ["utm_*".to_string(), "example.org:ref".to_string()];
+        assert_eq!(
+            strip_url_parameters(
+                "See https://example.com/a?UTM_Source=x&id=3#top and\n\
+                 https://shop.example.org/p?ref=mail&utm_medium=y.",
+                &rules
+            ),
+            "See https://example.com/a?id=3#top and\nhttps://shop.example.org/p."
+        );
+        let text = "https://example.com/?ref=1 https://notexample.org/?ref=1";
+        assert_eq!(strip_url_parameters(text, &rules), text);
+        assert_eq!(
+            strip_url_parameters("https://example.com/?utm_source=x", &[]),
+            "https://example.com/?utm_source=x"
+        );
+    }
 }
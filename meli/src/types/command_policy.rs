@@ -0,0 +1,178 @@
+/*
+ * meli
+ *
+ * Copyright 2024 Manos Pitsidianakis
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Restrictions on the external commands meli spawns, see the
+//! `allowed_commands` and `log_commands` account settings.
+
+use std::{
+    ffi::OsStr,
+    io,
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Output},
+};
+
+use melib::log;
+
+/// Characters that make a `sh -c` command line run more than a single simple
+/// command, which cannot be checked against an allowlist.
+const SHELL_CONTROL_CHARACTERS: &[char] = &['|', '&', ';', '<', '>', '(', ')', '$', '`', '\n'];
+
+/// Which external commands may be spawned and whether spawning them is logged.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CommandPolicy {
+    /// Absolute paths of the executables that may be spawned. `None` allows
+    /// every executable.
+    pub allowed: Option<Vec<PathBuf>>,
+    /// Log every spawned command along with its arguments.
+    pub log: bool,
+}
+
+impl CommandPolicy {
+    /// Check whether `command` may be spawned, logging it if
+    /// [`CommandPolicy::log`] is set.
+    ///
+    /// Command lines passed to the shell with `sh -c` are checked by their
+    /// first word, and refused if they contain shell control characters.
+    pub fn check(&self, command: &Command) -> io::Result<()> {
+        let program = command.get_program();
+        let args = command.get_args().collect::<Vec<&OsStr>>();
+        if self.log {
+            log::info!("Spawning {:?} with arguments {:?}", program, args);
+        }
+        let Some(ref allowed) = self.allowed else {
+            return Ok(());
+        };
+        let executable = if program == "sh" && args.first() == Some(&OsStr::new("-c")) {
+            let line = args.get(1).and_then(|a| a.to_str()).unwrap_or_default();
+            if line.contains(SHELL_CONTROL_CHARACTERS) {
+                return Err(denied(format!(
+                    "command line `{line}` contains shell control characters and cannot be \
+                     checked against allowed_commands"
+                )));
+            }
+            line.split_whitespace()
+                .find(|w| !w.contains('='))
+                .unwrap_or_default()
+                .trim_matches(|c| c == '"' || c == '\'')
+        } else {
+            program.to_str().unwrap_or_default()
+        };
+        let Some(path) = resolve(executable) else {
+            return Err(denied(format!("command `{executable}` was not found")));
+        };
+        let canonical = path.canonicalize().ok();
+        if allowed
+            .iter()
+            .any(|a| a == &path || (canonical.is_some() && a.canonicalize().ok() == canonical))
+        {
+            Ok(())
+        } else {
+            Err(denied(format!(
+                "`{}` is not in allowed_commands",
+                path.display()
+            )))
+        }
+    }
+}
+
+fn denied(message: String) -> io::Error {
+    log::warn!("Refused to spawn external command: {message}");
+    io::Error::new(io::ErrorKind::PermissionDenied, message)
+}
+
+/// Find the path of `executable` like the shell does, by looking it up in
+/// `PATH` unless it contains a slash.
+fn resolve(executable: &str) -> Option<PathBuf> {
+    if executable.is_empty() {
+        return None;
+    }
+    if executable.contains('/') {
+        let path = Path::new(executable);
+        return if path.is_absolute() {
+            Some(path.to_path_buf())
+        } else {
+            std::env::current_dir().ok().map(|cwd| cwd.join(path))
+        };
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(executable))
+        .find(|path| path.is_file())
+}
+
+/// Spawn commands after checking them against a [`CommandPolicy`].
+///
+/// Refused commands fail with [`io::ErrorKind::PermissionDenied`], so callers
+/// can report them like any other spawn error.
+pub trait CommandPolicyExt {
+    fn spawn_checked(&mut self, policy: &CommandPolicy) -> io::Result<Child>;
+    fn output_checked(&mut self, policy: &CommandPolicy) -> io::Result<Output>;
+    fn status_checked(&mut self, policy: &CommandPolicy) -> io::Result<ExitStatus>;
+}
+
+impl CommandPolicyExt for Command {
+    fn spawn_checked(&mut self, policy: &CommandPolicy) -> io::Result<Child> {
+        policy.check(self)?;
+        self.spawn()
+    }
+
+    fn output_checked(&mut self, policy: &CommandPolicy) -> io::Result<Output> {
+        policy.check(self)?;
+        self.output()
+    }
+
+    fn status_checked(&mut self, policy: &CommandPolicy) -> io::Result<ExitStatus> {
+        policy.check(self)?;
+        self.status()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_policy() {
+        let sh = resolve("sh").unwrap();
+        let policy = CommandPolicy {
+            allowed: Some(vec![sh]),
+            log: false,
+        };
+        assert!(CommandPolicy::default()
+            .check(&Command::new("/nonexistent/binary"))
+            .is_ok());
+        assert!(policy.check(&Command::new("sh")).is_ok());
+        assert!(policy
+            .check(Command::new("sh").args(["-c", "sh -x"]))
+            .is_ok());
+        assert!(policy
+            .check(Command::new("sh").args(["-c", "LC_ALL=C \"sh\" file"]))
+            .is_ok());
+        let err = policy
+            .check(Command::new("sh").args(["-c", "sh | cat"]))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        let err = policy
+            .check(Command::new("sh").args(["-c", "/nonexistent/binary"]))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(policy.check(&Command::new("/nonexistent/binary")).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Local statistics of how `meli` is used.
+//!
+//! If `usage_stats.enabled` is set, the commands that are executed and the
+//! time spent in each kind of view are counted, so that users can look at
+//! their own workflows with the `usage-stats` command. Only the name of each
+//! command is recorded, not its arguments, and views are recorded by their
+//! [`Component::view_name`](crate::Component::view_name), not by what they
+//! show. The statistics are kept in the data directory and are never sent
+//! anywhere.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+use melib::{
+    error::*,
+    utils::datetime::{self, UnixTimestamp},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::version_migrations::artifacts::{from_unversioned, StateFile};
+
+/// The file the statistics are stored in, in the data directory of `meli`.
+pub const USAGE_STATS_FILE: StateFile = StateFile {
+    description: "usage statistics",
+    name: "usage_stats.json",
+    steps: &[from_unversioned],
+};
+
+/// Time without input after which the user is considered away. Longer gaps
+/// count as this long.
+const IDLE_LIMIT: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct UsageStats {
+    /// When collection started.
+    since: UnixTimestamp,
+    /// Number of times each command was executed, by command name.
+    commands: BTreeMap<String, u64>,
+    /// Milliseconds spent in each view, by view name.
+    views: BTreeMap<String, u64>,
+    /// The view of the last input and when it happened.
+    #[serde(skip)]
+    last_input: Option<(&'static str, Instant)>,
+}
+
+impl UsageStats {
+    pub fn new() -> Self {
+        Self {
+            since: datetime::now(),
+            ..Self::default()
+        }
+    }
+
+    /// Load the stored statistics, or start new ones if there are none.
+    pub fn load() -> Result<Self> {
+        Ok(USAGE_STATS_FILE.load("")?.unwrap_or_else(Self::new))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        USAGE_STATS_FILE.save("", self)
+    }
+
+    /// Delete the stored statistics.
+    pub fn purge() -> Result<()> {
+        let path = USAGE_STATS_FILE.path("")?;
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .chain_err_summary(|| format!("Could not delete {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Record that command `command` was executed. Only its first word, the
+    /// command name, is kept.
+    pub fn command(&mut self, command: &str) {
+        if let Some(name) = command.split_whitespace().next() {
+            *self.commands.entry(name.to_string()).or_default() += 1;
+        }
+    }
+
+    /// Record user input while view `view` is shown. The time since the
+    /// previous input is counted towards the view that was shown then.
+    pub fn input(&mut self, view: Option<&'static str>) {
+        self.input_at(view, Instant::now());
+    }
+
+    fn input_at(&mut self, view: Option<&'static str>, now: Instant) {
+        if let Some((previous, at)) = self.last_input {
+            let elapsed = now.saturating_duration_since(at).min(IDLE_LIMIT);
+            *self.views.entry(previous.to_string()).or_default() += elapsed.as_millis() as u64;
+        }
+        self.last_input = view.map(|view| (view, now));
+    }
+
+    /// A table of the time spent in each view and of the commands executed,
+    /// most used first.
+    pub fn report(&self) -> String {
+        let mut ret = format!(
+            "Usage statistics since {}. They are stored only on this computer.\n\nTime per \
+             view\n",
+            datetime::timestamp_to_string(self.since, Some("%Y-%m-%d"), false)
+        );
+        let mut views = self.views.iter().collect::<Vec<_>>();
+        views.sort_by(|a, b| b.1.cmp(a.1));
+        if views.is_empty() {
+            ret.push_str("  none yet\n");
+        }
+        for (view, millis) in views {
+            let _ = writeln!(ret, "  {view:<30}{:>12}", format_duration(*millis / 1000));
+        }
+        ret.push_str("\nCommands\n");
+        let mut commands = self.commands.iter().collect::<Vec<_>>();
+        commands.sort_by(|a, b| b.1.cmp(a.1));
+        if commands.is_empty() {
+            ret.push_str("  none yet\n");
+        }
+        for (command, count) in commands {
+            let _ = writeln!(ret, "  {command:<30}{count:>12}");
+        }
+        ret
+    }
+}
+
+fn format_duration(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m {seconds:02}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds:02}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_stats_collection() {
+        let mut stats = UsageStats::new();
+        stats.command("sort subject asc");
+        stats.command("sort date desc");
+        stats.command("  compose");
+        stats.command("");
+        assert_eq!(stats.commands.len(), 2);
+        assert_eq!(stats.commands["sort"], 2);
+        assert_eq!(stats.commands["compose"], 1);
+
+        let start = Instant::now();
+        stats.input_at(Some("listing"), start);
+        stats.input_at(Some("mail view"), start + Duration::from_secs(10));
+        // Gaps longer than the idle limit are capped.
+        stats.input_at(None, start + Duration::from_secs(10 * 60));
+        stats.input_at(Some("listing"), start + Duration::from_secs(11 * 60));
+        assert_eq!(stats.views.len(), 2);
+        assert_eq!(stats.views["listing"], 10_000);
+        assert_eq!(stats.views["mail view"], IDLE_LIMIT.as_millis() as u64);
+
+        let report = stats.report();
+        assert!(report.contains("  mail view                           5m 00s\n"));
+        assert!(report.contains("  listing                                10s\n"));
+        assert!(report.contains("  sort                                     2\n"));
+        assert!(report.find("sort").unwrap() < report.find("compose").unwrap());
+    }
+
+    #[test]
+    fn test_usage_stats_format_duration() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(59), "59s");
+        assert_eq!(format_duration(61), "1m 01s");
+        assert_eq!(format_duration(3 * 3600 + 62), "3h 01m 02s");
+    }
+}
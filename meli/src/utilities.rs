@@ -258,6 +258,14 @@ impl Component for StatusBar {
             return;
         }
 
+        if context.zoomed && self.mode == UIMode::Normal {
+            // The status bar is hidden while zoomed, unless it is needed for
+            // command mode.
+            self.container.draw(grid, area, context);
+            self.dirty = false;
+            return;
+        }
+
         self.container.draw(
             grid,
             area.take_rows(total_rows.saturating_sub(self.height)),
@@ -605,12 +613,9 @@ impl Component for StatusBar {
                         None
                     }
                 }) {
-                    let mut utext = UText::new(suggestion);
-                    let len = utext.as_str().len();
-                    utext.set_cursor(len);
                     self.container.set_dirty(true);
                     self.set_dirty(true);
-                    self.ex_buffer = TextField::new(utext, None);
+                    self.ex_buffer.set_content(suggestion);
                 }
             }
             UIEvent::CmdInput(Key::Char(c)) => {
@@ -627,7 +632,8 @@ impl Component for StatusBar {
             }
             UIEvent::CmdInput(Key::Ctrl('u')) => {
                 self.dirty = true;
-                self.ex_buffer.clear();
+                self.ex_buffer
+                    .process_event(&mut UIEvent::InsertInput(Key::Ctrl('u')), context);
                 self.ex_buffer_cmd_history_pos.take();
                 return true;
             }
@@ -654,14 +660,11 @@ impl Component for StatusBar {
                 let pos = self.ex_buffer_cmd_history_pos.map(|p| p + 1).unwrap_or(0);
                 let pos = std::cmp::min(pos, self.cmd_history.len().saturating_sub(1));
                 if Some(pos) != self.ex_buffer_cmd_history_pos {
-                    let mut utext = UText::new(
-                        self.cmd_history[self.cmd_history.len().saturating_sub(1) - pos].clone(),
-                    );
-                    let len = utext.as_str().len();
-                    utext.set_cursor(len);
                     self.container.set_dirty(true);
                     self.set_dirty(true);
-                    self.ex_buffer = TextField::new(utext, None);
+                    self.ex_buffer.set_content(
+                        self.cmd_history[self.cmd_history.len().saturating_sub(1) - pos].clone(),
+                    );
                     self.ex_buffer_cmd_history_pos = Some(pos);
                     self.dirty = true;
                 }
@@ -677,21 +680,25 @@ impl Component for StatusBar {
                     self.ex_buffer.clear();
                     self.dirty = true;
                 } else if let Some(pos) = self.ex_buffer_cmd_history_pos.map(|p| p - 1) {
-                    let mut utext = UText::new(
-                        self.cmd_history[self.cmd_history.len().saturating_sub(1) - pos].clone(),
-                    );
-                    let len = utext.as_str().len();
-                    utext.set_cursor(len);
                     self.container.set_dirty(true);
                     self.set_dirty(true);
-                    self.ex_buffer = TextField::new(utext, None);
+                    self.ex_buffer.set_content(
+                        self.cmd_history[self.cmd_history.len().saturating_sub(1) - pos].clone(),
+                    );
                     self.ex_buffer_cmd_history_pos = Some(pos);
                     self.dirty = true;
                 }
 
                 return true;
             }
-            UIEvent::CmdInput(k @ Key::Backspace) | UIEvent::CmdInput(k @ Key::Ctrl(_)) => {
+            UIEvent::CmdInput(
+                k @ (Key::Backspace
+                | Key::Delete
+                | Key::Home
+                | Key::End
+                | Key::Ctrl(_)
+                | Key::Alt(_)),
+            ) => {
                 self.dirty = true;
                 self.ex_buffer
                     .process_event(&mut UIEvent::InsertInput(k.clone()), context);
@@ -798,6 +805,10 @@ impl Component for StatusBar {
         self.id
     }
 
+    fn view_name(&self) -> Option<&'static str> {
+        self.container.view_name()
+    }
+
     fn can_quit_cleanly(&mut self, context: &Context) -> bool {
         self.container.can_quit_cleanly(context)
     }
@@ -934,6 +945,19 @@ impl Tabbed {
         }
         self.help_view.curr_views = children_maps;
     }
+
+    /// Announce the tab that was switched to, in screen reader mode.
+    fn announce_tab(&self, context: &mut Context) {
+        context.announce(
+            "Tab",
+            format!(
+                "{} of {}, {}",
+                self.cursor_pos + 1,
+                self.children.len(),
+                self.children[self.cursor_pos]
+            ),
+        );
+    }
 }
 
 impl std::fmt::Display for Tabbed {
@@ -957,7 +981,9 @@ impl Component for Tabbed {
         /* children should be drawn after the shortcuts/help panel lest they
          * overwrite the panel on the grid. the drawing order is determined
          * by the dirty_areas queue which is LIFO */
-        if self.children.len() > 1 {
+        if context.zoomed {
+            self.children[self.cursor_pos].draw(grid, area, context);
+        } else if self.children.len() > 1 {
             self.draw_tabs(grid, area.nth_row(0), context);
             self.children[self.cursor_pos].draw(grid, area.skip_rows(1), context);
         } else {
@@ -1356,6 +1382,7 @@ impl Component for Tabbed {
                         .push_back(UIEvent::StatusEvent(StatusEvent::UpdateStatus(
                             self.children[self.cursor_pos].status(context),
                         )));
+                    self.announce_tab(context);
                     self.set_dirty(true);
                 }
                 return true;
@@ -1372,6 +1399,27 @@ impl Component for Tabbed {
                     .push_back(UIEvent::StatusEvent(StatusEvent::UpdateStatus(
                         self.children[self.cursor_pos].status(context),
                     )));
+                self.announce_tab(context);
+                self.set_dirty(true);
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::GENERAL]["toggle_zoom"]) =>
+            {
+                context.zoomed = !context.zoomed;
+                // Every container has to lay out its children again.
+                context.replies.push_back(UIEvent::Resize);
+                context
+                    .replies
+                    .push_back(UIEvent::StatusEvent(StatusEvent::DisplayMessage(
+                        if context.zoomed {
+                            "Zoomed in, press the toggle_zoom shortcut again to restore the \
+                             layout."
+                                .to_string()
+                        } else {
+                            "Zoomed out.".to_string()
+                        },
+                    )));
                 self.set_dirty(true);
                 return true;
             }
@@ -1399,6 +1447,7 @@ impl Component for Tabbed {
                 self.cursor_pos = self.children.len() - 1;
                 self.children[self.cursor_pos].set_dirty(true);
                 self.update_help_curr_views(context);
+                self.announce_tab(context);
                 return true;
             }
             UIEvent::Action(Tab(Close)) => {
@@ -1423,6 +1472,7 @@ impl Component for Tabbed {
                     self.cursor_pos = 0;
                     self.set_dirty(true);
                     self.update_help_curr_views(context);
+                    self.announce_tab(context);
                     return true;
                 } else {
                     log::debug!(
@@ -1547,6 +1597,10 @@ impl Component for Tabbed {
         self.id
     }
 
+    fn view_name(&self) -> Option<&'static str> {
+        self.children[self.cursor_pos].view_name()
+    }
+
     fn shortcuts(&self, context: &Context) -> ShortcutMaps {
         let mut map = ShortcutMaps::default();
         map.insert(
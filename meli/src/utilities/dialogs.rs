@@ -552,6 +552,19 @@ impl<T: PartialEq + std::fmt::Debug + Clone + Sync + Send, F: 'static + Sync + S
         self.done
     }
 
+    /// Mark the entries for which `selected` returns `true` as selected, if
+    /// more than one entry can be chosen.
+    pub fn select(&mut self, selected: impl Fn(&T) -> bool) {
+        if self.single_only {
+            return;
+        }
+        for (id, is_selected) in self.entries.iter_mut() {
+            *is_selected = selected(id);
+        }
+        self.initialized = false;
+        self.dirty = true;
+    }
+
     pub fn collect(self) -> Vec<T> {
         self.entries
             .into_iter()
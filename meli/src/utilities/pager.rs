@@ -27,6 +27,118 @@ use crate::{
     terminal::embedded::EmbeddedGrid,
 };
 
+/// Kind of a line of e-mail body text, used by [`Pager`] to highlight quotes,
+/// patches and signatures.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BodyLineKind {
+    #[default]
+    Text,
+    /// Quoted text and its quote depth.
+    Quote(usize),
+    /// `diff`, `index`, `---` and `+++` lines of a patch.
+    DiffHeader,
+    /// `@@ -a,b +c,d @@` hunk header of a patch.
+    DiffHunk,
+    DiffAdded,
+    DiffRemoved,
+    /// The `-- ` signature delimiter and the lines following it.
+    Signature,
+}
+
+impl BodyLineKind {
+    /// How many times `line` is quoted, counting `>` characters at its start
+    /// that may be separated by spaces.
+    pub fn quote_depth(line: &str) -> usize {
+        let mut depth = 0;
+        for c in line.chars() {
+            match c {
+                '>' => depth += 1,
+                ' ' if depth > 0 => {}
+                _ => break,
+            }
+        }
+        depth
+    }
+
+    /// Classify the lines of `text`.
+    ///
+    /// Returns the byte offset where each run of lines of the same kind
+    /// starts, along with the kind.
+    pub fn classify(text: &str) -> Vec<(usize, Self)> {
+        let mut ret: Vec<(usize, Self)> = vec![];
+        let mut offset = 0;
+        let mut in_diff = false;
+        let mut in_signature = false;
+        let mut lines = text.split_inclusive('\n').peekable();
+        while let Some(line) = lines.next() {
+            let start = offset;
+            offset += line.len();
+            let line = line.trim_end_matches(['\n', '\r']);
+            let depth = Self::quote_depth(line);
+            let kind = if depth > 0 {
+                in_diff = false;
+                in_signature = false;
+                Self::Quote(depth)
+            } else if line == "-- " {
+                in_diff = false;
+                in_signature = true;
+                Self::Signature
+            } else if line.starts_with("diff ")
+                || (line.starts_with("--- ")
+                    && lines.peek().is_some_and(|next| next.starts_with("+++ ")))
+            {
+                in_diff = true;
+                Self::DiffHeader
+            } else if line.starts_with("@@ -") && line[4..].contains(" @@") {
+                in_diff = true;
+                Self::DiffHunk
+            } else if in_diff
+                && (line.starts_with("--- ")
+                    || line.starts_with("+++ ")
+                    || line.starts_with("index "))
+            {
+                Self::DiffHeader
+            } else if in_diff {
+                match line.as_bytes().first() {
+                    Some(b'+') => Self::DiffAdded,
+                    Some(b'-') => Self::DiffRemoved,
+                    None | Some(b' ' | b'\\') => Self::Text,
+                    Some(_) => {
+                        in_diff = false;
+                        Self::Text
+                    }
+                }
+            } else if in_signature {
+                Self::Signature
+            } else {
+                Self::Text
+            };
+            if ret.last().map(|(_, k)| *k != kind).unwrap_or(true) {
+                ret.push((start, kind));
+            }
+        }
+        ret
+    }
+
+    /// Theme key used to draw lines of this kind, if any.
+    pub const fn theme_key(self) -> Option<&'static str> {
+        match self {
+            Self::Text => None,
+            Self::Quote(depth) => Some(match depth % 4 {
+                1 => "pager.quote.a",
+                2 => "pager.quote.b",
+                3 => "pager.quote.c",
+                _ => "pager.quote.d",
+            }),
+            Self::DiffHeader => Some("pager.diff.header"),
+            Self::DiffHunk => Some("pager.diff.hunk"),
+            Self::DiffAdded => Some("pager.diff.added"),
+            Self::DiffRemoved => Some("pager.diff.removed"),
+            Self::Signature => Some("pager.signature"),
+        }
+    }
+}
+
 /// A pager for text.
 /// `Pager` holds its own content in its own `CellBuffer` and when `draw` is
 /// called, it draws the current view of the text. It is responsible for
@@ -40,6 +152,9 @@ pub struct Pager {
     width: usize,
     minimum_width: usize,
     search: Option<SearchPattern>,
+    /// Lines selected in visual mode, as `(anchor, cursor)` indices of
+    /// `text_lines`.
+    visual: Option<(usize, usize)>,
     dirty: bool,
 
     colors: ThemeAttribute,
@@ -58,6 +173,9 @@ pub struct Pager {
     text_lines: Vec<Line>,
     line_breaker: LineBreakText,
     movement: Option<PageMovement>,
+    /// Line kinds of `text` if it is highlighted as e-mail body text, see
+    /// [`BodyLineKind::classify`].
+    body_lines: Option<Vec<(usize, BodyLineKind)>>,
     id: ComponentId,
 }
 
@@ -72,6 +190,7 @@ impl Clone for Pager {
             width: self.width,
             minimum_width: self.minimum_width,
             search: self.search.clone(),
+            visual: self.visual,
             dirty: true,
             colors: self.colors,
             initialised: false,
@@ -82,6 +201,7 @@ impl Clone for Pager {
             text_lines: self.text_lines.clone(),
             line_breaker: self.line_breaker.clone(),
             movement: self.movement,
+            body_lines: self.body_lines.clone(),
             id: ComponentId::default(),
         }
     }
@@ -134,6 +254,13 @@ impl Pager {
         self.reflow
     }
 
+    /// Highlight quotes, patches and signatures in the text.
+    pub fn set_highlight_body(&mut self, new_val: bool) -> &mut Self {
+        self.body_lines = new_val.then(|| BodyLineKind::classify(&self.text));
+        self.dirty = true;
+        self
+    }
+
     pub fn update_from_str(&mut self, text: &str, mut width: Option<usize>) {
         if let Some(ref mut width) = width.as_mut() {
             if **width < self.minimum_width {
@@ -147,11 +274,32 @@ impl Pager {
         self.height = 0;
         self.width = 0;
         self.search = None;
+        self.visual = None;
+        if self.body_lines.is_some() {
+            self.body_lines = Some(BodyLineKind::classify(&self.text));
+        }
         self.set_dirty(true);
         self.initialised = false;
         self.cursor = (0, 0);
     }
 
+    /// The text of the lines selected in visual mode, if any.
+    pub fn selection(&self) -> Option<String> {
+        let (anchor, cursor) = self.visual?;
+        let first = self.text_lines.get(anchor.min(cursor))?;
+        let last = self.text_lines.get(anchor.max(cursor))?;
+        self.text.get(first.start..last.end).map(str::to_string)
+    }
+
+    /// Return the selected text, if any, and leave visual mode.
+    pub fn take_selection(&mut self) -> Option<String> {
+        let ret = self.selection();
+        if self.visual.take().is_some() {
+            self.dirty = true;
+        }
+        ret
+    }
+
     pub fn from_string(
         text: String,
         context: &Context,
@@ -199,7 +347,12 @@ impl Pager {
     }
 
     pub fn filter(&mut self, cmd: &str, context: &Context) {
-        async fn filter_fut(bin: String, text: String, tab_width: u8) -> Result<EmbeddedGrid> {
+        async fn filter_fut(
+            bin: String,
+            text: String,
+            tab_width: u8,
+            policy: CommandPolicy,
+        ) -> Result<EmbeddedGrid> {
             use std::{
                 io::Write,
                 process::{Command, Stdio},
@@ -209,7 +362,7 @@ impl Pager {
                 .stdin(Stdio::piped())
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
-                .spawn()
+                .spawn_checked(&policy)
                 .chain_err_summary(|| "Failed to start pager filter process")?;
             let stdin = filter_child.stdin.as_mut().ok_or("failed to open stdin")?;
             stdin
@@ -237,7 +390,12 @@ impl Pager {
             Ok(embedded)
         }
         let tab_width = context.settings.terminal.tab_width;
-        let fut = Box::pin(filter_fut(cmd.to_string(), self.text.clone(), tab_width));
+        let fut = Box::pin(filter_fut(
+            cmd.to_string(),
+            self.text.clone(),
+            tab_width,
+            context.command_policy(None),
+        ));
         let handle = context.main_loop_handler.job_executor.spawn(
             format!("Running pager filter {cmd}").into(),
             fut,
@@ -250,6 +408,12 @@ impl Pager {
         self.cursor.1
     }
 
+    /// Move the view by `movement` on the next draw.
+    pub fn scroll(&mut self, movement: PageMovement) {
+        self.movement = Some(movement);
+        self.dirty = true;
+    }
+
     pub fn size(&self) -> (usize, usize) {
         (self.width, self.height)
     }
@@ -338,6 +502,7 @@ impl Pager {
     }
 
     fn draw_page(&mut self, grid: &mut CellBuffer, area: Area, context: &mut Context) {
+        context.set_focus(area.upper_left());
         if let Some((ref cmd, ref content)) = self.filtered_content {
             grid.copy_area(
                 content.buffer(),
@@ -376,6 +541,13 @@ impl Pager {
                     })
                 })
                 .collect::<Vec<Link<'_>>>();
+            let is_suspicious = |link: &Link<'_>| {
+                link.is_suspicious()
+                    || self.text[..link.start]
+                        .trim_end_matches(' ')
+                        .ends_with(SUSPICIOUS_LINK_MARKER)
+            };
+            let mut suspicious_spans = vec![];
             let mut cur_link_idx = 0;
             for l in self
                 .text_lines
@@ -386,6 +558,7 @@ impl Pager {
                 if area2.is_empty() {
                     break;
                 }
+                suspicious_spans.clear();
                 // Perform a simple scan pass over `links`, by keeping current link index to
                 // consider in `cur_link_idx`.
                 //
@@ -441,8 +614,12 @@ impl Pager {
                                 let skip_x = skip_x + link.value.len();
                                 area2.skip_cols(skip_x).upper_left()
                             };
-                            let uri = grid.insert_uri(&link.value);
-                            grid.set_uri(uri, start, end);
+                            if let Some(uri) = grid.insert_uri(&link.target()) {
+                                grid.set_uri(uri, start, end);
+                            }
+                            if is_suspicious(link) {
+                                suspicious_spans.push((start, end));
+                            }
                         }
                         if link.end < l.end {
                             // In this case, there is more than one link in this line, so continue
@@ -462,16 +639,28 @@ impl Pager {
                     {
                         let start = area2.upper_left();
                         let end = area2.skip_cols(l.content.len()).upper_left();
-                        let uri = grid.insert_uri(&link.value);
-                        grid.set_uri(uri, start, end);
+                        if let Some(uri) = grid.insert_uri(&link.target()) {
+                            grid.set_uri(uri, start, end);
+                        }
+                        if is_suspicious(link) {
+                            suspicious_spans.push((start, end));
+                        }
                     }
                     break;
                 }
+                let attr = self
+                    .body_lines
+                    .as_ref()
+                    .and_then(|body_lines| {
+                        let idx = body_lines.partition_point(|(start, _)| *start <= l.start);
+                        body_lines[idx.checked_sub(1)?].1.theme_key()
+                    })
+                    .map(|key| crate::conf::value(context, key));
                 grid.write_string(
                     &l.content,
-                    self.colors.fg,
-                    self.colors.bg,
-                    Attr::DEFAULT,
+                    attr.map_or(self.colors.fg, |a| a.fg),
+                    attr.map_or(self.colors.bg, |a| a.bg),
+                    attr.map_or(Attr::DEFAULT, |a| a.attrs),
                     area2,
                     None,
                     None,
@@ -481,6 +670,16 @@ impl Pager {
                         .set_fg(crate::conf::value(context, "highlight").fg)
                         .set_attrs(crate::conf::value(context, "highlight").attrs);
                 }
+                if !suspicious_spans.is_empty() {
+                    let attr = crate::conf::value(context, "pager.suspicious_link");
+                    for (start, end) in suspicious_spans.drain(..) {
+                        for x in start.0..end.0 {
+                            if let Some(c) = grid.get_mut(x, start.1) {
+                                c.set_fg(attr.fg).set_attrs(attr.attrs);
+                            }
+                        }
+                    }
+                }
                 area2 = area2.skip_rows(1);
             }
 
@@ -548,6 +747,22 @@ impl Pager {
                     }
                 }
             }
+            if let Some((anchor, cursor)) = self.visual {
+                let attr = crate::conf::value(context, "pager.highlight_selection");
+                let cursor_line = self.cursor.1;
+                let first = anchor.min(cursor).max(cursor_line);
+                let last = anchor
+                    .max(cursor)
+                    .min(cursor_line + area.height().saturating_sub(1));
+                for y in first..=last {
+                    for c in grid.row_iter(area.nth_row(y - cursor_line), 0..area.width(), 0) {
+                        grid[c]
+                            .set_fg(attr.fg)
+                            .set_bg(attr.bg)
+                            .set_attrs(attr.attrs);
+                    }
+                }
+            }
         }
     }
 }
@@ -649,6 +864,23 @@ impl Component for Pager {
             }
         }
 
+        if let Some((anchor, cursor)) = self.visual {
+            // Keep the selection cursor within the rendered lines and in view.
+            self.draw_lines_up_to(
+                grid,
+                area,
+                context,
+                cursor + Self::PAGES_AHEAD_TO_RENDER_NO * rows,
+            );
+            let cursor = cursor.min(self.text_lines.len().saturating_sub(1));
+            self.visual = Some((anchor, cursor));
+            if cursor < self.cursor.1 {
+                self.cursor.1 = cursor;
+            } else if cursor >= self.cursor.1 + rows {
+                self.cursor.1 = cursor + 1 - rows;
+            }
+        }
+
         if let Some(ref mut search) = self.search {
             if !search.positions.is_empty() {
                 if let Some(mvm) = search.movement.take() {
@@ -777,6 +1009,79 @@ impl Component for Pager {
                 self.set_colors(crate::conf::value(context, "theme_default"));
                 self.set_dirty(true);
             }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::PAGER]["visual_mode"])
+                    && self.filtered_content.is_none()
+                    && !self.text_lines.is_empty() =>
+            {
+                self.visual = if self.visual.is_some() {
+                    None
+                } else {
+                    let line = self.cursor.1.min(self.text_lines.len() - 1);
+                    Some((line, line))
+                };
+                context
+                    .replies
+                    .push_back(UIEvent::StatusEvent(StatusEvent::UpdateSubStatus(
+                        if self.visual.is_some() {
+                            "-- VISUAL --".to_string()
+                        } else {
+                            String::new()
+                        },
+                    )));
+                self.dirty = true;
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::PAGER]["scroll_up"])
+                    && self.visual.is_some() =>
+            {
+                if let Some((_, ref mut cursor)) = self.visual {
+                    *cursor = cursor.saturating_sub(1);
+                }
+                self.dirty = true;
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::PAGER]["scroll_down"])
+                    && self.visual.is_some() =>
+            {
+                if let Some((_, ref mut cursor)) = self.visual {
+                    *cursor += 1;
+                }
+                self.dirty = true;
+                return true;
+            }
+            UIEvent::Input(ref key)
+                if shortcut!(key == shortcuts[Shortcuts::PAGER]["copy_selection"])
+                    && self.visual.is_some() =>
+            {
+                if let Some(text) = self.take_selection() {
+                    context.replies.push_back(UIEvent::Notification {
+                        title: None,
+                        source: None,
+                        body: format!("Copied {} lines.", text.lines().count()).into(),
+                        kind: Some(NotificationType::Info),
+                    });
+                    context.replies.push_back(UIEvent::CopyToClipboard(text));
+                }
+                context
+                    .replies
+                    .push_back(UIEvent::StatusEvent(StatusEvent::UpdateSubStatus(
+                        String::new(),
+                    )));
+                return true;
+            }
+            UIEvent::Input(Key::Esc) if self.visual.is_some() => {
+                self.visual = None;
+                self.dirty = true;
+                context
+                    .replies
+                    .push_back(UIEvent::StatusEvent(StatusEvent::UpdateSubStatus(
+                        String::new(),
+                    )));
+                return true;
+            }
             UIEvent::Input(ref key)
                 if shortcut!(key == shortcuts[Shortcuts::PAGER]["scroll_up"])
                     && self.cursor.1 > 0 =>
@@ -847,7 +1152,7 @@ impl Component for Pager {
                     .args(args.as_slice())
                     .stdin(Stdio::piped())
                     .stdout(Stdio::piped())
-                    .spawn()
+                    .spawn_checked(&context.command_policy(None))
                 {
                     Ok(o) => o,
                     Err(err) => {
@@ -1063,6 +1368,10 @@ impl Component for Pager {
         self.id
     }
 
+    fn view_name(&self) -> Option<&'static str> {
+        Some("pager")
+    }
+
     fn kill(&mut self, uuid: ComponentId, context: &mut Context) {
         if self.id != uuid {
             return;
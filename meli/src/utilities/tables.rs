@@ -139,6 +139,9 @@ pub struct DataColumns<const N: usize> {
     pub x_offset: usize,
     pub width_accum: usize,
     pub segment_tree: Box<[SegmentTree; N]>,
+    /// Number of rows each entry spans in the column grids. Entry `i`
+    /// occupies rows `i * row_height..(i + 1) * row_height`.
+    pub row_height: usize,
 }
 
 // Workaround because Default derive doesn't work for const generic array
@@ -164,6 +167,7 @@ impl<const N: usize> DataColumns<N> {
             x_offset: 0,
             width_accum: 0,
             segment_tree: Box::new(init_array(SegmentTree::default)),
+            row_height: 1,
         }
     }
 }
@@ -186,13 +190,13 @@ impl<const N: usize> DataColumns<N> {
         let mut width_accum = 0;
         let mut growees = 0;
         let mut growees_max = 0;
+        let entries = screen_height.div_ceil(self.row_height.max(1));
         for i in 0..N {
             if screen_height == 0 {
                 self.widths[i] = 0;
                 continue;
             }
-            self.widths[i] =
-                self.segment_tree[i].get_max(top_idx, top_idx + screen_height - 1) as usize;
+            self.widths[i] = self.segment_tree[i].get_max(top_idx, top_idx + entries - 1) as usize;
             if self.widths[i] == 0 {
                 self.widths[i] = self.columns[i].cols();
             }
@@ -256,7 +260,8 @@ impl<const N: usize> DataColumns<N> {
     ) {
         let mut start_col = 0;
         let total_area = bounds.area();
-        let height = total_area.height();
+        let row_height = self.row_height.max(1);
+        let entries = total_area.height().div_ceil(row_height);
         if self.width_accum > 0 && self.x_offset + total_area.width() > self.width_accum {
             self.x_offset = self.width_accum.saturating_sub(total_area.width());
         }
@@ -285,7 +290,7 @@ impl<const N: usize> DataColumns<N> {
                 bounds.area(),
                 self.columns[col]
                     .area()
-                    .skip_rows(top_idx)
+                    .skip_rows(top_idx * row_height)
                     .skip_cols(x_offset)
                     .take_cols(column_width - x_offset),
             );
@@ -299,23 +304,23 @@ impl<const N: usize> DataColumns<N> {
             }
             TableTheme::EvenOdd { even, odd } => {
                 grid.change_theme(total_area, even);
-                for (top_idx, row) in (top_idx..).zip(0..total_area.height()) {
-                    if top_idx % 2 != 0 {
+                for row in 0..total_area.height() {
+                    if (top_idx + row / row_height) % 2 != 0 {
                         grid.change_theme(total_area.nth_row(row), odd);
                     }
                 }
             }
         }
 
-        if self.cursor_config.handle && (top_idx..(top_idx + height)).contains(&cursor_pos) {
-            let offset = cursor_pos - top_idx;
+        if self.cursor_config.handle && (top_idx..(top_idx + entries)).contains(&cursor_pos) {
+            let offset = (cursor_pos - top_idx) * row_height;
             let row_attr = match self.cursor_config.theme {
                 TableTheme::Single(attr) => attr,
                 TableTheme::EvenOdd { even, odd: _ } if cursor_pos % 2 == 0 => even,
                 TableTheme::EvenOdd { even: _, odd } => odd,
             };
 
-            grid.change_theme(total_area.skip_rows(offset).take_rows(1), row_attr);
+            grid.change_theme(total_area.skip_rows(offset).take_rows(row_height), row_attr);
         }
     }
 
@@ -177,6 +177,157 @@ fn test_utilities_text_input_field() {
     _ = tmpdir.close();
 }
 
+#[test]
+fn test_utilities_text_input_field_kill_ring_undo() {
+    use super::TextField;
+    use crate::{Component, Key, UIEvent};
+
+    let tmpdir = tempfile::TempDir::new().unwrap();
+    let mut context = crate::state::Context::new_mock(&tmpdir);
+    let mut key = |field: &mut TextField, k: Key| {
+        assert!(field.process_event(&mut UIEvent::InsertInput(k), &mut context));
+    };
+
+    let mut field = TextField::default();
+    field.set_content("search from:alice subject:report".to_string());
+
+    // Kill to end of line and word-wise kills.
+    key(&mut field, Key::Ctrl('w'));
+    assert_eq!(field.as_str(), "search from:alice ");
+    key(&mut field, Key::Ctrl('a'));
+    key(&mut field, Key::Alt('d'));
+    assert_eq!(field.as_str(), " from:alice ");
+    key(&mut field, Key::Ctrl('k'));
+    assert_eq!(field.as_str(), "");
+    assert_eq!(
+        field.kill_ring(),
+        &["subject:report", "search", " from:alice "]
+    );
+
+    // Yank the last kill, then rotate to the ones before it.
+    key(&mut field, Key::Ctrl('y'));
+    assert_eq!(field.as_str(), " from:alice ");
+    key(&mut field, Key::Alt('y'));
+    assert_eq!(field.as_str(), "search");
+    key(&mut field, Key::Alt('y'));
+    assert_eq!(field.as_str(), "subject:report");
+    key(&mut field, Key::Ctrl('u'));
+    assert_eq!(field.as_str(), "");
+    assert_eq!(field.kill_ring().last().unwrap(), "subject:report");
+
+    // Undo restores the text before each edit; typed characters are undone
+    // together.
+    key(&mut field, Key::Ctrl('7'));
+    assert_eq!(field.as_str(), "subject:report");
+    key(&mut field, Key::Ctrl('7'));
+    assert_eq!(field.as_str(), "");
+    key(&mut field, Key::Char('a'));
+    key(&mut field, Key::Char('b'));
+    key(&mut field, Key::Char('c'));
+    key(&mut field, Key::Backspace);
+    assert_eq!(field.as_str(), "ab");
+    key(&mut field, Key::Ctrl('7'));
+    assert_eq!(field.as_str(), "abc");
+    key(&mut field, Key::Ctrl('7'));
+    assert_eq!(field.as_str(), "");
+
+    // Clearing the field keeps the kill ring.
+    field.clear();
+    key(&mut field, Key::Ctrl('y'));
+    assert_eq!(field.as_str(), "subject:report");
+    _ = tmpdir.close();
+}
+
+#[test]
+fn test_utilities_form_widget_tab_navigation() {
+    use super::FormWidget;
+    use crate::{Component, Key, UIEvent};
+
+    let tmpdir = tempfile::TempDir::new().unwrap();
+    let mut context = crate::state::Context::new_mock(&tmpdir);
+
+    let mut form: FormWidget<bool> = FormWidget::default();
+    form.push(("To".into(), String::new()));
+    form.push(("Cc".into(), String::new()));
+    form.push(("Subject".into(), String::new()));
+    assert_eq!(form.cursor(), 0);
+
+    // Tab moves between fields in normal mode.
+    assert!(form.process_event(&mut UIEvent::Input(Key::Char('\t')), &mut context));
+    assert_eq!(form.cursor(), 1);
+
+    // Tab moves to the next field while editing, without inserting anything.
+    assert!(form.process_event(&mut UIEvent::Input(Key::Char('\n')), &mut context));
+    assert!(form.is_editing());
+    assert!(form.process_event(&mut UIEvent::InsertInput(Key::Char('a')), &mut context));
+    assert!(form.process_event(&mut UIEvent::InsertInput(Key::Char('\t')), &mut context));
+    assert_eq!(form.cursor(), 2);
+    assert!(form.is_editing());
+    assert_eq!(form.values()["Cc"].as_str(), "a");
+
+    // In the last field there is no next field, so a space is inserted.
+    assert!(form.process_event(&mut UIEvent::InsertInput(Key::Char('\t')), &mut context));
+    assert_eq!(form.cursor(), 2);
+    assert_eq!(form.values()["Subject"].as_str(), " ");
+
+    form.set_error("Cc", Some("invalid address".to_string()));
+    assert_eq!(form.error("Cc"), Some("invalid address"));
+    form.set_error("Cc", None);
+    assert_eq!(form.error("Cc"), None);
+    _ = tmpdir.close();
+}
+
+#[test]
+fn test_utilities_pager_body_line_kinds() {
+    use super::BodyLineKind::{self, *};
+
+    let text = "Hello,\n\
+                > quoted\n\
+                > > nested\n\
+                >> nested\n\
+                \n\
+                ---\n \
+                file | 2 +-\n\
+                diff --git a/file b/file\n\
+                index 1234..5678 100644\n\
+                --- a/file\n\
+                +++ b/file\n\
+                @@ -1,3 +1,3 @@ fn main()\n \
+                context\n\
+                -old\n\
+                +new\n\
+                \n\
+                Thanks,\n\
+                - a list item\n\
+                -- \n\
+                Signature\n";
+    let kinds = BodyLineKind::classify(text);
+    let kind_of = |needle: &str| {
+        let offset = text.find(needle).unwrap();
+        let idx = kinds.partition_point(|(start, _)| *start <= offset);
+        kinds[idx - 1].1
+    };
+    assert_eq!(kind_of("Hello"), Text);
+    assert_eq!(kind_of("> quoted"), Quote(1));
+    assert_eq!(kind_of("> > nested"), Quote(2));
+    assert_eq!(kind_of(">> nested"), Quote(2));
+    assert_eq!(kind_of(" file | 2"), Text);
+    assert_eq!(kind_of("diff --git"), DiffHeader);
+    assert_eq!(kind_of("index 1234"), DiffHeader);
+    assert_eq!(kind_of("--- a/file"), DiffHeader);
+    assert_eq!(kind_of("+++ b/file"), DiffHeader);
+    assert_eq!(kind_of("@@ -1,3"), DiffHunk);
+    assert_eq!(kind_of(" context"), Text);
+    assert_eq!(kind_of("-old"), DiffRemoved);
+    assert_eq!(kind_of("+new"), DiffAdded);
+    assert_eq!(kind_of("Thanks"), Text);
+    assert_eq!(kind_of("- a list item"), Text);
+    assert_eq!(kind_of("-- \n"), Signature);
+    assert_eq!(kind_of("Signature"), Signature);
+    assert_eq!(Quote(5).theme_key(), Some("pager.quote.a"));
+    assert_eq!(Text.theme_key(), None);
+}
+
 /// Returns a closure that prints the string " OK\n" to `stderr`.
 ///
 /// If `stderr` is a TTY, the output will contain escape code sequences to
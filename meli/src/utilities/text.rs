@@ -26,11 +26,30 @@ use std::sync::Arc;
 use super::*;
 use crate::melib::text::Truncate;
 
+/// How many kills are kept in the kill ring of a [`TextField`].
+const KILL_RING_LIMIT: usize = 16;
+/// How many edits of a [`TextField`] can be undone.
+const UNDO_LIMIT: usize = 64;
+
 /// Text input widget.
+///
+/// Supports readline-like editing: cursor and word movements, a kill ring
+/// (`Ctrl+K`, `Ctrl+U`, `Ctrl+W`, `Meta+D`, yanked with `Ctrl+Y` and rotated
+/// with `Meta+Y`) and undo (`Ctrl+_`).
 #[derive(Clone)]
 pub struct TextField {
     inner: UText,
     autocomplete: Option<(Arc<AutoCompleteFn>, Box<AutoComplete>)>,
+    /// Killed text, most recent last.
+    kill_ring: Vec<String>,
+    /// Index in `kill_ring` of the text inserted by the previous event, if it
+    /// was a yank.
+    yank: Option<usize>,
+    /// Previous states of the text, most recent last.
+    undo: Vec<UText>,
+    /// Whether the previous event inserted a character; consecutive
+    /// insertions are undone together.
+    typing: bool,
     theme_attr: Option<ThemeAttribute>,
     highlight: Option<ThemeAttribute>,
     id: ComponentId,
@@ -42,6 +61,7 @@ impl std::fmt::Debug for TextField {
             .field("id", &self.id)
             .field("inner", &self.inner)
             .field("has AutoComplete", &self.autocomplete.is_some())
+            .field("kill_ring", &self.kill_ring)
             .finish_non_exhaustive()
     }
 }
@@ -51,6 +71,10 @@ impl Default for TextField {
         Self {
             inner: UText::new(String::with_capacity(256)),
             autocomplete: None,
+            kill_ring: vec![],
+            yank: None,
+            undo: vec![],
+            typing: false,
             theme_attr: None,
             highlight: None,
             id: ComponentId::default(),
@@ -63,6 +87,10 @@ impl TextField {
         Self {
             inner,
             autocomplete: autocomplete.map(|(a, b)| (Arc::new(a), b)),
+            kill_ring: vec![],
+            yank: None,
+            undo: vec![],
+            typing: false,
             theme_attr: None,
             highlight: None,
             id: ComponentId::default(),
@@ -75,8 +103,11 @@ impl TextField {
     }
 
     /// Reset with new content.
+    ///
+    /// Edits made before can no longer be undone, but the kill ring is kept.
     pub fn set_content(&mut self, new_val: String) {
         self.inner.set_content(new_val);
+        self.undo.clear();
     }
 
     /// Cursor position as seen by the user: the cursor is seen moving by
@@ -111,9 +142,34 @@ impl TextField {
         self.inner.into_string()
     }
 
+    /// Whether an autocomplete suggestion is selected.
+    pub fn has_suggestion(&self) -> bool {
+        self.autocomplete
+            .as_ref()
+            .is_some_and(|a| a.1.has_suggestion())
+    }
+
     /// Clear inner string content and set cursor to zero.
+    ///
+    /// Edits made before can no longer be undone, but the kill ring is kept.
     pub fn clear(&mut self) {
-        self.inner.clear()
+        self.inner.clear();
+        self.undo.clear();
+    }
+
+    /// Killed text, most recent last.
+    pub fn kill_ring(&self) -> &[String] {
+        &self.kill_ring
+    }
+
+    fn kill(&mut self, text: String) {
+        if text.is_empty() {
+            return;
+        }
+        if self.kill_ring.len() == KILL_RING_LIMIT {
+            self.kill_ring.remove(0);
+        }
+        self.kill_ring.push(text);
     }
 
     /// Draw cursor at given area along with autocomplete suggestions, if any.
@@ -235,6 +291,10 @@ impl Component for TextField {
     }
 
     fn process_event(&mut self, event: &mut UIEvent, context: &mut Context) -> bool {
+        let before = self.inner.clone();
+        let prev_yank = self.yank.take();
+        let was_typing = std::mem::replace(&mut self.typing, false);
+        let mut skip_undo = false;
         match *event {
             UIEvent::InsertInput(Key::Char('\t')) => {
                 if let Some(suggestion) = self
@@ -285,11 +345,10 @@ impl Component for TextField {
             }
             UIEvent::InsertInput(Key::Char(k)) => {
                 self.inner.insert_char(k);
+                self.typing = true;
             }
             UIEvent::InsertInput(Key::Paste(ref p)) => {
-                for c in p.chars() {
-                    self.inner.insert_char(c);
-                }
+                self.inner.insert_str(p);
             }
             UIEvent::InsertInput(Key::Alt('b')) => {
                 // Meta+B Backward one alphanumeric word
@@ -383,26 +442,48 @@ impl Component for TextField {
             }
             UIEvent::InsertInput(Key::Ctrl('w')) => {
                 // Cut previous word
-                while self.inner.as_str()[..self.inner.cursor_pos()]
-                    .last_grapheme()
-                    .map(|(_, graph)| !graph.is_empty() && graph.trim().is_empty())
-                    .unwrap_or(false)
-                {
-                    if !self.inner.backspace() {
-                        break;
-                    }
+                let killed = self.inner.cut_previous_word();
+                self.kill(killed);
+            }
+            UIEvent::InsertInput(Key::Alt('d')) => {
+                // Meta+D Cut next word
+                let killed = self.inner.cut_next_word();
+                self.kill(killed);
+            }
+            UIEvent::InsertInput(Key::Ctrl('u')) => {
+                // Ctrl+U Cut to beginning of line
+                let killed = self.inner.cut_left();
+                self.kill(killed);
+            }
+            UIEvent::InsertInput(Key::Ctrl('k')) => {
+                // Ctrl+K Cut to end of line
+                let killed = self.inner.cut_right();
+                self.kill(killed);
+            }
+            UIEvent::InsertInput(Key::Ctrl('y')) => {
+                // Ctrl+Y Paste most recently cut text
+                if let Some(text) = self.kill_ring.last() {
+                    self.inner.insert_str(text);
+                    self.yank = Some(self.kill_ring.len() - 1);
                 }
-                while self.inner.as_str()[..self.inner.cursor_pos()]
-                    .last_grapheme()
-                    .map(|(_, graph)| !graph.is_empty() && !graph.trim().is_empty())
-                    .unwrap_or(false)
-                {
-                    if !self.inner.backspace() {
-                        break;
-                    }
+            }
+            UIEvent::InsertInput(Key::Alt('y')) => {
+                // Meta+Y Replace text pasted by the previous event with the text cut before it
+                if let (Some(i), Some(prev)) = (prev_yank, self.undo.last()) {
+                    let i = i.checked_sub(1).unwrap_or(self.kill_ring.len() - 1);
+                    self.inner = prev.clone();
+                    self.inner.insert_str(&self.kill_ring[i]);
+                    self.yank = Some(i);
+                    skip_undo = true;
                 }
             }
-            UIEvent::InsertInput(Key::Ctrl('u')) => self.inner.cut_left(),
+            UIEvent::InsertInput(Key::Ctrl('7')) => {
+                // Ctrl+_ Undo (0x1F is reported as Ctrl+7)
+                if let Some(prev) = self.undo.pop() {
+                    self.inner = prev;
+                }
+                skip_undo = true;
+            }
             UIEvent::InsertInput(Key::Ctrl('e')) | UIEvent::InsertInput(Key::End) => {
                 // Ctrl+E End of line
                 self.inner.set_cursor(self.inner.as_str().len());
@@ -411,6 +492,12 @@ impl Component for TextField {
                 return false;
             }
         }
+        if !skip_undo && before.as_str() != self.inner.as_str() && !(was_typing && self.typing) {
+            if self.undo.len() == UNDO_LIMIT {
+                self.undo.remove(0);
+            }
+            self.undo.push(before);
+        }
         self.set_dirty(true);
         true
     }
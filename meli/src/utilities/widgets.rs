@@ -80,6 +80,14 @@ impl Field {
         self.as_str().is_empty()
     }
 
+    /// Whether an autocomplete suggestion is selected.
+    pub fn has_suggestion(&self) -> bool {
+        match self {
+            Self::Text(ref s) => s.has_suggestion(),
+            Self::Choice(_, _, _) => false,
+        }
+    }
+
     pub fn into_string(self) -> String {
         match self {
             Self::Text(s) => s.into_string(),
@@ -213,6 +221,8 @@ where
     fields: IndexMap<Cow<'static, str>, Field>,
     layout: Vec<Cow<'static, str>>,
     buttons: ButtonWidget<T>,
+    /// Validation errors shown next to their fields.
+    errors: IndexMap<Cow<'static, str>, String>,
 
     field_name_max_length: usize,
     cursor: usize,
@@ -232,6 +242,7 @@ impl<T: 'static + std::fmt::Debug + Copy + Default + Clone + Send + Sync> Clone
             fields: self.fields.clone(),
             layout: self.layout.clone(),
             buttons: self.buttons.clone(),
+            errors: self.errors.clone(),
             focus: self.focus,
             hide_buttons: self.hide_buttons,
             field_name_max_length: self.field_name_max_length,
@@ -252,6 +263,7 @@ impl<T: 'static + std::fmt::Debug + Copy + Default + Send + Sync> Default for Fo
             fields: Default::default(),
             layout: Default::default(),
             buttons: Default::default(),
+            errors: Default::default(),
             focus: FormFocus::Fields,
             hide_buttons: false,
             field_name_max_length: 10,
@@ -373,6 +385,31 @@ impl<T: 'static + std::fmt::Debug + Copy + Default + Send + Sync> FormWidget<T>
     pub fn buttons_result(&mut self) -> Option<T> {
         self.buttons.result.take()
     }
+
+    /// Set or clear the validation error shown next to field `field`.
+    pub fn set_error(&mut self, field: &str, error: Option<String>) {
+        if self.errors.get(field) == error.as_ref() {
+            return;
+        }
+        match error {
+            Some(error) => {
+                self.errors.insert(field.to_string().into(), error);
+            }
+            None => {
+                self.errors.shift_remove(field);
+            }
+        }
+        self.set_dirty(true);
+    }
+
+    pub fn error(&self, field: &str) -> Option<&str> {
+        self.errors.get(field).map(String::as_str)
+    }
+
+    /// Whether the cursor is in a field that is being edited.
+    pub fn is_editing(&self) -> bool {
+        self.focus == FormFocus::TextInput
+    }
 }
 
 impl<T: 'static + std::fmt::Debug + Copy + Default + Send + Sync> Component for FormWidget<T> {
@@ -382,6 +419,7 @@ impl<T: 'static + std::fmt::Debug + Copy + Default + Send + Sync> Component for
 
             grid.clear_area(area, theme_default);
             let label_attrs = crate::conf::value(context, "widgets.form.label");
+            let error_attrs = crate::conf::value(context, "error_message");
             let mut highlighted = crate::conf::value(context, "highlight");
             if !context.settings.terminal.use_color() {
                 highlighted.attrs |= Attr::REVERSE;
@@ -413,6 +451,21 @@ impl<T: 'static + std::fmt::Debug + Copy + Default + Send + Sync> Component for
                 );
                 grid.change_theme(area.nth_row(i), theme_attr);
 
+                /* Write validation error after the field's value */
+                if let Some(error) = self.errors.get(k) {
+                    grid.write_string(
+                        &format!("⚠ {error}"),
+                        error_attrs.fg,
+                        error_attrs.bg,
+                        error_attrs.attrs,
+                        area.nth_row(i).skip_cols(
+                            self.field_name_max_length + 2 + v.as_str().grapheme_width() + 2,
+                        ),
+                        None,
+                        None,
+                    );
+                }
+
                 /* Highlight if necessary */
                 if i == self.cursor && self.focus == FormFocus::TextInput {
                     v.draw_cursor(
@@ -491,12 +544,29 @@ impl<T: 'static + std::fmt::Debug + Copy + Default + Send + Sync> Component for
                 }
                 return true;
             }
+            UIEvent::InsertInput(Key::Char('\t'))
+                if self.focus == FormFocus::TextInput
+                    && !self.fields[&self.layout[self.cursor]].has_suggestion()
+                    && self.cursor + 1 < self.layout.len() =>
+            {
+                /* Move to the next field and keep editing */
+                self.cursor += 1;
+                self.set_dirty(true);
+                return true;
+            }
             UIEvent::InsertInput(Key::Char('\t')) if self.focus == FormFocus::TextInput => {
                 let field = self.fields.get_mut(&self.layout[self.cursor]).unwrap();
                 field.process_event(event, context);
                 self.set_dirty(true);
                 return true;
             }
+            UIEvent::Input(Key::Char('\t'))
+                if self.focus == FormFocus::Fields && self.cursor + 1 < self.layout.len() =>
+            {
+                self.cursor += 1;
+                self.set_dirty(true);
+                return true;
+            }
             UIEvent::Input(Key::Char('\n')) if self.focus == FormFocus::Fields => {
                 self.focus = FormFocus::TextInput;
                 context
@@ -562,6 +632,7 @@ impl<T: 'static + std::fmt::Debug + Copy + Default + Send + Sync> Component for
         our_map.insert("up", self.cursor_up_shortcut.clone());
         our_map.insert("down", self.cursor_down_shortcut.clone());
         our_map.insert("toggle field editing", Key::Char('\n'));
+        our_map.insert("next field", Key::Char('\t'));
         map.insert("fields input", our_map);
 
         map
@@ -964,6 +1035,12 @@ impl AutoComplete {
         self.cursor = val;
     }
 
+    /// Whether a suggestion is selected, i.e. if
+    /// [`AutoComplete::get_suggestion`] would return one.
+    pub fn has_suggestion(&self) -> bool {
+        !self.entries.is_empty() && self.cursor > 0
+    }
+
     pub fn get_suggestion(&mut self) -> Option<String> {
         if self.entries.is_empty() || self.cursor == 0 {
             return None;
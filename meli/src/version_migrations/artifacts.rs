@@ -0,0 +1,328 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Versioning of the files `meli` keeps in its data directory and in the data
+//! directory of each account.
+//!
+//! Databases, such as the message index and the offline caches of backends,
+//! record their schema version in their `user_version` `PRAGMA`. Their
+//! descriptions carry migration scripts that bring older schemas up to date
+//! when the database is opened. Databases whose version can't be migrated
+//! are recreated, since their contents can be fetched or indexed again.
+//!
+//! JSON state files, such as the listing state, are described by a
+//! [`StateFile`] and stored as `{"version": N, "data": ...}`. Files without a
+//! version were written before state files were versioned and are version
+//! `0`. Older files are brought up to date when loaded and are written in the
+//! current version when saved. Files of newer versions are not read, so that
+//! running an older `meli` doesn't discard them.
+//!
+//! The `meli migrate` command brings all files of all accounts up to date at
+//! once with [`migrate_artifacts`].
+
+use std::{cmp::Ordering, os::unix::fs::PermissionsExt, path::PathBuf};
+
+use melib::error::*;
+#[cfg(feature = "sqlite3")]
+use melib::utils::sqlite3::DatabaseDescription;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// Converts the data of a [`StateFile`] from one version to the next.
+pub type MigrationStep = fn(Value) -> Result<Value>;
+
+/// The step from a file written before state files were versioned to
+/// version `1`, which holds the same data.
+pub fn from_unversioned(data: Value) -> Result<Value> {
+    Ok(data)
+}
+
+/// A versioned JSON file in the data directory of an account, or in the data
+/// directory of `meli` itself if the account name is empty.
+#[derive(Clone, Copy, Debug)]
+pub struct StateFile {
+    /// What the file holds, for messages.
+    pub description: &'static str,
+    /// File name in the account's data directory.
+    pub name: &'static str,
+    /// `steps[n]` converts data of version `n` to version `n + 1`. The current
+    /// version is the number of steps.
+    pub steps: &'static [MigrationStep],
+}
+
+#[derive(Serialize)]
+struct Versioned<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+impl StateFile {
+    /// The version files are written in.
+    pub const fn version(&self) -> u32 {
+        self.steps.len() as u32
+    }
+
+    /// Path of the file of account `account_name`.
+    pub fn path(&self, account_name: &str) -> Result<PathBuf> {
+        Ok(data_dir(account_name)?.get_data_file(self.name))
+    }
+
+    /// Load the file of account `account_name`, or `None` if there is none.
+    pub fn load<T: DeserializeOwned>(&self, account_name: &str) -> Result<Option<T>> {
+        let path = self.path(account_name)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let (version, data) = self.read(&path)?;
+        let data = self.upgrade(version, data)?;
+        Ok(Some(serde_json::from_value(data).chain_err_summary(
+            || format!("Could not read {}", path.display()),
+        )?))
+    }
+
+    /// Save `data` as the file of account `account_name`.
+    pub fn save<T: Serialize>(&self, account_name: &str, data: &T) -> Result<()> {
+        let f = std::fs::File::create(data_dir(account_name)?.place_data_file(self.name)?)?;
+        let mut permissions = f.metadata()?.permissions();
+        permissions.set_mode(0o600); // Read/write for owner only.
+        f.set_permissions(permissions)?;
+        serde_json::to_writer(
+            std::io::BufWriter::new(f),
+            &Versioned {
+                version: self.version(),
+                data,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Rewrite the file of account `account_name` in the current version. If
+    /// `dry_run` is set, only report what would be done.
+    pub fn migrate(&self, account_name: &str, dry_run: bool) -> Result<ArtifactStatus> {
+        let path = self.path(account_name)?;
+        if !path.exists() {
+            return Ok(ArtifactStatus::Missing);
+        }
+        let (version, data) = self.read(&path)?;
+        let to = self.version();
+        match version.cmp(&to) {
+            Ordering::Equal => Ok(ArtifactStatus::UpToDate { version }),
+            Ordering::Greater => Ok(ArtifactStatus::Newer { version }),
+            Ordering::Less => {
+                let data = self.upgrade(version, data)?;
+                if !dry_run {
+                    self.save(account_name, &data)?;
+                }
+                Ok(ArtifactStatus::Migrated { from: version, to })
+            }
+        }
+    }
+
+    fn read(&self, path: &std::path::Path) -> Result<(u32, Value)> {
+        let value: Value = std::fs::File::open(path)
+            .map_err(Error::from)
+            .and_then(|f| Ok(serde_json::from_reader(std::io::BufReader::new(f))?))
+            .chain_err_summary(|| format!("Could not read {}", path.display()))?;
+        Ok(split_version(value))
+    }
+
+    fn upgrade(&self, version: u32, mut data: Value) -> Result<Value> {
+        if version > self.version() {
+            return Err(Error::new(format!(
+                "The {} file was written by a newer version of meli (file version {}, supported \
+                 version {}).",
+                self.description,
+                version,
+                self.version()
+            ))
+            .set_kind(ErrorKind::NotSupported));
+        }
+        for step in &self.steps[version as usize..] {
+            data = step(data)?;
+        }
+        Ok(data)
+    }
+}
+
+fn data_dir(account_name: &str) -> Result<xdg::BaseDirectories> {
+    Ok(if account_name.is_empty() {
+        xdg::BaseDirectories::with_prefix("meli")?
+    } else {
+        xdg::BaseDirectories::with_profile("meli", account_name)?
+    })
+}
+
+/// Split the contents of a state file into its version and data.
+pub fn split_version(value: Value) -> (u32, Value) {
+    match value {
+        Value::Object(mut map)
+            if map.len() == 2
+                && map.contains_key("data")
+                && map.get("version").is_some_and(Value::is_u64) =>
+        {
+            let version = map["version"].as_u64().unwrap_or_default() as u32;
+            (version, map.remove("data").unwrap_or_default())
+        }
+        value => (0, value),
+    }
+}
+
+/// What [`migrate_artifacts`] did to a file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ArtifactStatus {
+    /// The file doesn't exist.
+    Missing,
+    UpToDate {
+        version: u32,
+    },
+    Migrated {
+        from: u32,
+        to: u32,
+    },
+    /// The file was recreated, since its version can't be migrated.
+    Reset {
+        from: u32,
+        to: u32,
+    },
+    /// The file was left alone, since it was written by a newer version of
+    /// `meli`.
+    Newer {
+        version: u32,
+    },
+}
+
+impl std::fmt::Display for ArtifactStatus {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Missing => write!(fmt, "missing"),
+            Self::UpToDate { version } => write!(fmt, "up to date (version {version})"),
+            Self::Migrated { from, to } => write!(fmt, "migrated from version {from} to {to}"),
+            Self::Reset { from, to } => write!(
+                fmt,
+                "recreated, version {from} can't be migrated to version {to}"
+            ),
+            Self::Newer { version } => write!(
+                fmt,
+                "left unchanged, version {version} was written by a newer version of meli"
+            ),
+        }
+    }
+}
+
+/// Migrate database `db` to its current version. If `dry_run` is set, only
+/// report what would be done.
+#[cfg(feature = "sqlite3")]
+pub fn migrate_database(db: &DatabaseDescription, dry_run: bool) -> Result<ArtifactStatus> {
+    let Some(from) = db.stored_version()? else {
+        return Ok(ArtifactStatus::Missing);
+    };
+    let to = db.version;
+    // Version `0` databases have no schema yet and are set up when opened.
+    if from == 0 || from == to {
+        return Ok(ArtifactStatus::UpToDate { version: to });
+    }
+    let status = if db.migration_scripts(from).is_some() {
+        ArtifactStatus::Migrated { from, to }
+    } else {
+        ArtifactStatus::Reset { from, to }
+    };
+    if !dry_run {
+        // Opening the database migrates or recreates it.
+        db.open_or_create_db()?;
+    }
+    Ok(status)
+}
+
+/// Bring the files of accounts `account_names` and the files of `meli` itself
+/// up to date and print what was done with each existing file to `writer`. If
+/// `dry_run` is set, only report what would be done.
+pub fn migrate_artifacts<'a>(
+    account_names: impl IntoIterator<Item = &'a str>,
+    dry_run: bool,
+    writer: &mut impl std::io::Write,
+) -> Result<()> {
+    let mut failures = 0;
+    for account_name in account_names {
+        let mut statuses: Vec<(&str, Result<ArtifactStatus>)> = vec![];
+        #[cfg(feature = "sqlite3")]
+        for (description, db) in [
+            (
+                "message index",
+                crate::sqlite3::AccountCache::db_description(account_name),
+            ),
+            (
+                "IMAP offline cache",
+                melib::imap::sync::sqlite3_cache::Sqlite3Cache::db_description(account_name, None),
+            ),
+            (
+                "NNTP flag store",
+                melib::nntp::Store::db_description(account_name),
+            ),
+            ("state database", crate::accounts::state_db(account_name)),
+        ] {
+            statuses.push((description, migrate_database(&db, dry_run)));
+        }
+        for file in [
+            crate::mail::listing::ListingState::FILE,
+            crate::accounts::SPECIAL_MAILBOXES_FILE,
+            crate::accounts::CONTACTS_FILE,
+        ] {
+            statuses.push((file.description, file.migrate(account_name, dry_run)));
+        }
+        failures += report(account_name, statuses, writer)?;
+    }
+    let file = crate::usage_stats::USAGE_STATS_FILE;
+    failures += report(
+        "meli",
+        vec![(file.description, file.migrate("", dry_run))],
+        writer,
+    )?;
+    writer.flush()?;
+    if failures > 0 {
+        return Err(Error::new(format!(
+            "{failures} file{} could not be migrated.",
+            if failures == 1 { "" } else { "s" }
+        )));
+    }
+    Ok(())
+}
+
+/// Print the existing files of `owner` in `statuses` to `writer` and return
+/// how many failed.
+fn report(
+    owner: &str,
+    statuses: Vec<(&str, Result<ArtifactStatus>)>,
+    writer: &mut impl std::io::Write,
+) -> Result<usize> {
+    let mut failures = 0;
+    for (description, status) in statuses {
+        match status {
+            Ok(ArtifactStatus::Missing) => {}
+            Ok(status) => writeln!(writer, "{owner}: {description}: {status}")?,
+            Err(err) => {
+                failures += 1;
+                writeln!(writer, "{owner}: {description}: {err}")?;
+            }
+        }
+    }
+    Ok(failures)
+}
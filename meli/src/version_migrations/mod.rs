@@ -77,7 +77,14 @@
 //! [`Migration::revert`] which follows the same logic as [`Migration::perform`]
 //! but in reverse. It is not always possible a migration can be reverted, since
 //! migrations are not necessarily lossless.
+//!
+//! # Data files
+//!
+//! Caches, indexes and state files of accounts carry their own version and
+//! are migrated when they are opened, independently of the version file. See
+//! the [`artifacts`] module.
 
+pub mod artifacts;
 #[cfg(test)]
 mod tests;
 
@@ -449,19 +456,10 @@ pub fn version_setup(
             return Ok(());
         }
     };
-    let stored_version = if !version_file.try_exists().unwrap_or(false) {
-        None
-    } else {
-        let mut stored_version =
-            std::fs::read_to_string(&version_file).chain_err_related_path(&version_file)?;
-        while stored_version.ends_with(['\r', '\n', ' ', '\t']) {
-            stored_version.pop();
-        }
-        if LATEST.as_str() == stored_version {
-            return Ok(());
-        }
-        Some(stored_version)
-    };
+    let stored_version = read_version_file(&version_file)?;
+    if stored_version.as_deref() == Some(LATEST.as_str()) {
+        return Ok(());
+    }
     let version_map = versions();
     let migrations = calculate_migrations(stored_version.as_deref(), version_map);
     if !migrations.is_empty() {
@@ -609,6 +607,38 @@ pub fn version_setup(
     Ok(())
 }
 
+/// Return the contents of version file `version_file`, or `None` if it doesn't
+/// exist.
+fn read_version_file(version_file: &Path) -> Result<Option<String>> {
+    if !version_file.try_exists().unwrap_or(false) {
+        return Ok(None);
+    }
+    let mut stored_version =
+        std::fs::read_to_string(version_file).chain_err_related_path(version_file)?;
+    while stored_version.ends_with(['\r', '\n', ' ', '\t']) {
+        stored_version.pop();
+    }
+    Ok(Some(stored_version))
+}
+
+/// Print the migrations [`version_setup`] would offer to perform for
+/// configuration file `config`, without performing them.
+pub fn print_pending_migrations(config: &Path, writer: &mut impl std::io::Write) -> Result<()> {
+    let stored_version = read_version_file(&version_file()?)?;
+    if stored_version.as_deref() == Some(LATEST.as_str()) {
+        return Ok(());
+    }
+    for (vers, migrs) in calculate_migrations(stored_version.as_deref(), versions()) {
+        for m in migrs {
+            if m.is_applicable(config) != Some(false) {
+                writeln!(writer, "v{}/{}: {}", vers, m.id(), m.description())?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
 /// Return any migrations between current version and latest version, if any.
 pub fn calculate_migrations<'v>(
     current_version: Option<&str>,
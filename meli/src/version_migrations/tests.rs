@@ -142,3 +142,174 @@ server_password_command = "false"
     }
 }
 }
+
+#[test]
+fn test_version_migrations_split_version() {
+    use serde_json::json;
+
+    use super::artifacts::split_version;
+
+    assert_eq!(
+        split_version(json!({"version": 3, "data": {"a": 1}})),
+        (3, json!({"a": 1}))
+    );
+    // Unversioned files are version 0.
+    assert_eq!(
+        split_version(json!({"mailbox": "INBOX"})),
+        (0, json!({"mailbox": "INBOX"}))
+    );
+    assert_eq!(
+        split_version(json!({"version": 3})),
+        (0, json!({"version": 3}))
+    );
+    assert_eq!(
+        split_version(json!({"version": "3", "data": {}})),
+        (0, json!({"version": "3", "data": {}}))
+    );
+}
+
+rusty_fork_test! {
+#[test]
+fn test_version_migrations_state_files() {
+    use serde_json::{json, Value};
+
+    use super::artifacts::{from_unversioned, ArtifactStatus, StateFile};
+
+    fn rename_count(mut data: Value) -> Result<Value> {
+        if let Some(n) = data.as_object_mut().and_then(|m| m.remove("n")) {
+            data["count"] = n;
+        }
+        Ok(data)
+    }
+
+    const FILE: StateFile = StateFile {
+        description: "test state",
+        name: "test_state",
+        steps: &[from_unversioned, rename_count],
+    };
+
+    #[derive(Debug, serde::Deserialize, serde::Serialize)]
+    struct State {
+        count: u32,
+    }
+
+    let tempdir = tempfile::tempdir().unwrap();
+    std::env::set_var("XDG_DATA_HOME", tempdir.path());
+    let path = FILE.path("test").unwrap();
+    assert_eq!(FILE.migrate("test", false).unwrap(), ArtifactStatus::Missing);
+    assert!(FILE.load::<State>("test").unwrap().is_none());
+
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, br#"{"n": 5}"#).unwrap();
+    assert_eq!(FILE.load::<State>("test").unwrap().unwrap().count, 5);
+    assert_eq!(
+        FILE.migrate("test", true).unwrap(),
+        ArtifactStatus::Migrated { from: 0, to: 2 }
+    );
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), r#"{"n": 5}"#);
+    assert_eq!(
+        FILE.migrate("test", false).unwrap(),
+        ArtifactStatus::Migrated { from: 0, to: 2 }
+    );
+    let value: Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(value, json!({"version": 2, "data": {"count": 5}}));
+    assert_eq!(
+        FILE.migrate("test", false).unwrap(),
+        ArtifactStatus::UpToDate { version: 2 }
+    );
+
+    std::fs::write(&path, br#"{"version": 1, "data": {"n": 7}}"#).unwrap();
+    assert_eq!(FILE.load::<State>("test").unwrap().unwrap().count, 7);
+
+    std::fs::write(&path, br#"{"version": 3, "data": {"count": 7}}"#).unwrap();
+    FILE.load::<State>("test").unwrap_err();
+    assert_eq!(
+        FILE.migrate("test", false).unwrap(),
+        ArtifactStatus::Newer { version: 3 }
+    );
+
+    FILE.save("test", &State { count: 1 }).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        r#"{"version":2,"data":{"count":1}}"#
+    );
+}
+}
+
+#[cfg(feature = "sqlite3")]
+#[test]
+fn test_version_migrations_database() {
+    use melib::utils::sqlite3::DatabaseDescription;
+
+    use super::artifacts::{migrate_database, ArtifactStatus};
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let v1 = DatabaseDescription {
+        name: "test.db",
+        identifier: None,
+        application_prefix: "meli",
+        directory: Some(tempdir.path().to_path_buf().into()),
+        init_script: Some("CREATE TABLE IF NOT EXISTS t (a INTEGER NOT NULL);"),
+        version: 1,
+        migrations: &[],
+    };
+    let v2 = DatabaseDescription {
+        init_script: Some(
+            "CREATE TABLE IF NOT EXISTS t (a INTEGER NOT NULL, b INTEGER NOT NULL DEFAULT 0);",
+        ),
+        version: 2,
+        migrations: &[(1, "ALTER TABLE t ADD COLUMN b INTEGER NOT NULL DEFAULT 0;")],
+        ..v1.clone()
+    };
+    let v3 = DatabaseDescription {
+        version: 3,
+        ..v2.clone()
+    };
+    assert_eq!(
+        migrate_database(&v2, false).unwrap(),
+        ArtifactStatus::Missing
+    );
+
+    v1.open_or_create_db()
+        .unwrap()
+        .execute("INSERT INTO t (a) VALUES (42);", [])
+        .unwrap();
+    assert_eq!(v2.stored_version().unwrap(), Some(1));
+    assert_eq!(v2.migration_scripts(1).unwrap().len(), 1);
+    assert_eq!(v3.migration_scripts(1), None);
+    assert_eq!(
+        migrate_database(&v2, true).unwrap(),
+        ArtifactStatus::Migrated { from: 1, to: 2 }
+    );
+    assert_eq!(v2.stored_version().unwrap(), Some(1));
+    assert_eq!(
+        migrate_database(&v2, false).unwrap(),
+        ArtifactStatus::Migrated { from: 1, to: 2 }
+    );
+    assert_eq!(v2.stored_version().unwrap(), Some(2));
+    let (a, b): (i64, i64) = v2
+        .open_or_create_db()
+        .unwrap()
+        .query_row("SELECT a, b FROM t;", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap();
+    assert_eq!((a, b), (42, 0));
+    assert_eq!(
+        migrate_database(&v2, false).unwrap(),
+        ArtifactStatus::UpToDate { version: 2 }
+    );
+
+    // There is no migration from version 2 to 3, so the database is recreated.
+    assert_eq!(
+        migrate_database(&v3, false).unwrap(),
+        ArtifactStatus::Reset { from: 2, to: 3 }
+    );
+    assert_eq!(v3.stored_version().unwrap(), Some(3));
+    let count: i64 = v3
+        .open_or_create_db()
+        .unwrap()
+        .query_row("SELECT COUNT(*) FROM t;", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 0);
+}
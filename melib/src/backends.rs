@@ -340,6 +340,9 @@ pub enum RefreshEventKind {
     },
     MailboxSubscribe(MailboxHash),
     MailboxUnsubscribe(MailboxHash),
+    /// The message counts of the mailbox changed, without its envelopes being
+    /// loaded.
+    MailboxCountsUpdate,
 }
 
 #[derive(Clone, Debug)]
@@ -37,12 +37,80 @@ pub type EnvelopeRefMut<'g> = RwRefMut<'g, EnvelopeHash, Envelope>;
 pub struct Collection {
     pub envelopes: Arc<RwLock<HashMap<EnvelopeHash, Envelope>>>,
     pub message_id_index: Arc<RwLock<HashMap<Vec<u8>, EnvelopeHash>>>,
-    pub threads: Arc<RwLock<HashMap<MailboxHash, Threads>>>,
+    threads: Arc<RwLock<HashMap<MailboxHash, ThreadsSlot>>>,
     pub sent_mailbox: Arc<RwLock<Option<MailboxHash>>>,
     pub mailboxes: Arc<RwLock<HashMap<MailboxHash, HashSet<EnvelopeHash>>>>,
     pub tag_index: Arc<RwLock<BTreeMap<TagHash, String>>>,
 }
 
+/// The threads of a mailbox, see [`Collection::update_threads`].
+#[derive(Debug, Default)]
+struct ThreadsSlot {
+    threads: Threads,
+    /// Incremented on every change of `threads`.
+    generation: u64,
+    /// A copy of `threads` that does not share state with it, kept by
+    /// [`Collection::update_threads`] to build the next version of `threads`
+    /// on without copying them.
+    spare: Option<Threads>,
+}
+
+impl ThreadsSlot {
+    /// Get `threads` for changing them in place.
+    fn get_mut(&mut self) -> &mut Threads {
+        self.generation += 1;
+        self.spare = None;
+        &mut self.threads
+    }
+}
+
+/// Approximate memory usage of a [`Collection`], see
+/// [`Collection::memory_report`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MemoryReport {
+    pub envelopes: usize,
+    pub envelope_bytes: usize,
+    pub index_bytes: usize,
+    pub thread_nodes: usize,
+    pub thread_bytes: usize,
+    /// Subjects and addresses interned process-wide, shared between all
+    /// collections.
+    pub interned: crate::utils::intern::InternStats,
+}
+
+impl MemoryReport {
+    /// Total bytes, excluding interned values.
+    pub fn total(&self) -> usize {
+        self.envelope_bytes + self.index_bytes + self.thread_bytes
+    }
+}
+
+impl std::fmt::Display for MemoryReport {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            fmt,
+            "envelopes: {} ({})",
+            self.envelopes,
+            BytesDisplay(self.envelope_bytes)
+        )?;
+        writeln!(fmt, "message-id index: {}", BytesDisplay(self.index_bytes))?;
+        writeln!(
+            fmt,
+            "thread nodes: {} ({})",
+            self.thread_nodes,
+            BytesDisplay(self.thread_bytes)
+        )?;
+        writeln!(
+            fmt,
+            "interned values: {} ({}, {} references)",
+            self.interned.entries,
+            BytesDisplay(self.interned.bytes),
+            self.interned.references
+        )?;
+        write!(fmt, "total: {}", BytesDisplay(self.total()))
+    }
+}
+
 impl Default for Collection {
     fn default() -> Self {
         Self::new()
@@ -74,6 +142,88 @@ impl Collection {
         }
     }
 
+    /// Lock the threads of all mailboxes for writing.
+    ///
+    /// Threads must only be changed through [`ThreadsSlot::get_mut`], so that
+    /// [`Self::update_threads`], which builds new threads without holding the
+    /// lock, can tell whether they were changed in the meantime.
+    fn threads_mut(&self) -> RwLockWriteGuard<'_, HashMap<MailboxHash, ThreadsSlot>> {
+        self.threads.write().unwrap()
+    }
+
+    /// Apply `f` to the threads of `mailbox_hash`.
+    ///
+    /// `f` is applied to a separate copy of the threads, which replaces them
+    /// once `f` returns, so that readers of the threads are not blocked while
+    /// it runs. `f` must not change the envelopes of the collection, and
+    /// instead pushes the thread nodes it assigns to envelopes to its second
+    /// argument, which are set once the threads are replaced.
+    ///
+    /// The replaced threads are then brought up to date by applying `f` to
+    /// them as well, and are kept as the copy of the next update, so that
+    /// threads built in batches are not copied for each batch. If the threads
+    /// of the mailbox were changed meanwhile, this is retried, and after a few
+    /// tries `f` is applied while holding the lock instead.
+    fn update_threads<R>(
+        &self,
+        mailbox_hash: MailboxHash,
+        f: impl Fn(&mut Threads, &mut Vec<(EnvelopeHash, ThreadNodeHash)>) -> R,
+    ) -> R {
+        const TRIES: usize = 3;
+
+        for _ in 0..TRIES {
+            let spare = {
+                let mut threads_lck = self.threads_mut();
+                let slot = threads_lck.entry(mailbox_hash).or_default();
+                slot.spare.take().map(|spare| (slot.generation, spare))
+            };
+            let (generation, mut threads) = match spare {
+                Some(spare) => spare,
+                None => {
+                    let threads_lck = self.threads.read().unwrap();
+                    let Some(slot) = threads_lck.get(&mailbox_hash) else {
+                        continue;
+                    };
+                    (slot.generation, slot.threads.snapshot())
+                }
+            };
+            let mut assigned = vec![];
+            let ret = f(&mut threads, &mut assigned);
+            let mut old_threads = {
+                let mut threads_lck = self.threads_mut();
+                let Some(slot) = threads_lck
+                    .get_mut(&mailbox_hash)
+                    .filter(|slot| slot.generation == generation)
+                else {
+                    continue;
+                };
+                threads.take_view(&mut slot.threads);
+                slot.generation += 1;
+                Threads::assign_threads(&self.envelopes, assigned);
+                std::mem::replace(&mut slot.threads, threads)
+            };
+            f(&mut old_threads, &mut vec![]);
+            if let Some(slot) = self
+                .threads_mut()
+                .get_mut(&mailbox_hash)
+                .filter(|slot| slot.generation == generation + 1)
+            {
+                slot.spare = Some(old_threads);
+            }
+            return ret;
+        }
+        let mut assigned = vec![];
+        let ret = f(
+            self.threads_mut()
+                .entry(mailbox_hash)
+                .or_default()
+                .get_mut(),
+            &mut assigned,
+        );
+        Threads::assign_threads(&self.envelopes, assigned);
+        ret
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.envelopes.read().unwrap().len()
@@ -84,6 +234,50 @@ impl Collection {
         self.envelopes.read().unwrap().is_empty()
     }
 
+    /// Estimate the memory used by this collection.
+    pub fn memory_report(&self) -> MemoryReport {
+        let mut ret = MemoryReport::default();
+        {
+            let envelopes_lck = self.envelopes.read().unwrap();
+            ret.envelopes = envelopes_lck.len();
+            ret.envelope_bytes = envelopes_lck.capacity()
+                * (std::mem::size_of::<EnvelopeHash>() + std::mem::size_of::<Envelope>())
+                + envelopes_lck
+                    .values()
+                    .map(Envelope::heap_size)
+                    .sum::<usize>();
+        }
+        {
+            let message_id_index_lck = self.message_id_index.read().unwrap();
+            ret.index_bytes = message_id_index_lck.capacity()
+                * (std::mem::size_of::<Vec<u8>>() + std::mem::size_of::<EnvelopeHash>())
+                + message_id_index_lck
+                    .keys()
+                    .map(Vec::capacity)
+                    .sum::<usize>();
+        }
+        for threads in self
+            .threads
+            .read()
+            .unwrap()
+            .values()
+            .flat_map(|slot| std::iter::once(&slot.threads).chain(slot.spare.as_ref()))
+        {
+            ret.thread_nodes += threads.thread_nodes.len();
+            ret.thread_bytes += threads.thread_nodes.capacity()
+                * (std::mem::size_of::<ThreadNodeHash>() + std::mem::size_of::<ThreadNode>())
+                + threads
+                    .thread_nodes
+                    .values()
+                    .map(|n| n.children.capacity() * std::mem::size_of::<ThreadNodeHash>())
+                    .sum::<usize>()
+                + threads.groups.capacity()
+                    * (std::mem::size_of::<ThreadHash>() + std::mem::size_of::<ThreadGroup>());
+        }
+        ret.interned = crate::utils::intern::stats();
+        ret
+    }
+
     pub fn remove(&self, envelope_hash: EnvelopeHash, mailbox_hash: MailboxHash) {
         debug!("DEBUG: Removing {}", envelope_hash);
         self.envelopes.write().unwrap().remove(&envelope_hash);
@@ -94,16 +288,17 @@ impl Collection {
             .and_modify(|m| {
                 m.remove(&envelope_hash);
             });
-        let mut threads_lck = self.threads.write().unwrap();
+        let mut threads_lck = self.threads_mut();
         threads_lck
             .entry(mailbox_hash)
             .or_default()
+            .get_mut()
             .remove(envelope_hash);
         for (h, t) in threads_lck.iter_mut() {
             if *h == mailbox_hash {
                 continue;
             }
-            t.remove(envelope_hash);
+            t.get_mut().remove(envelope_hash);
         }
     }
 
@@ -127,11 +322,12 @@ impl Collection {
             });
         envelope.set_hash(new_hash);
         self.envelopes.write().unwrap().insert(new_hash, envelope);
-        let mut threads_lck = self.threads.write().unwrap();
+        let mut threads_lck = self.threads_mut();
         {
             if threads_lck
                 .entry(mailbox_hash)
                 .or_default()
+                .get_mut()
                 .update_envelope(&self.envelopes, old_hash, new_hash)
                 .is_ok()
             {
@@ -142,131 +338,102 @@ impl Collection {
         threads_lck
             .entry(mailbox_hash)
             .or_default()
+            .get_mut()
             .insert(&self.envelopes, new_hash);
         for (h, t) in threads_lck.iter_mut() {
             if *h == mailbox_hash {
                 continue;
             }
-            _ = t.update_envelope(&self.envelopes, old_hash, new_hash);
+            _ = t
+                .get_mut()
+                .update_envelope(&self.envelopes, old_hash, new_hash);
         }
         true
     }
 
     /// Merge new mailbox to collection and update threads.
     /// Returns a list of already existing mailboxs whose threads were updated
+    ///
+    /// The threads are built without blocking readers of the collection, see
+    /// [`Self::threads_mut`], so that this can run in a background thread.
     pub fn merge(
         &self,
-        mut new_envelopes: HashMap<EnvelopeHash, Envelope>,
+        new_envelopes: HashMap<EnvelopeHash, Envelope>,
         mailbox_hash: MailboxHash,
         sent_mailbox: Option<MailboxHash>,
     ) -> Option<SmallVec<[MailboxHash; 8]>> {
         *self.sent_mailbox.write().unwrap() = sent_mailbox;
 
-        let Self {
-            ref threads,
-            ref envelopes,
-            ref mailboxes,
-            ref sent_mailbox,
-            ..
-        } = self;
-
-        let mut threads_lck = threads.write().unwrap();
-        let mut mailboxes_lck = mailboxes.write().unwrap();
-        if let std::collections::hash_map::Entry::Vacant(e) = threads_lck.entry(mailbox_hash) {
-            e.insert(Threads::new(new_envelopes.len()));
-            mailboxes_lck.insert(mailbox_hash, new_envelopes.keys().cloned().collect());
-            for (h, e) in new_envelopes {
-                envelopes.write().unwrap().insert(h, e);
+        let mut ordered_hash_set = new_envelopes.keys().cloned().collect::<Vec<EnvelopeHash>>();
+        ordered_hash_set.sort_by(|a, b| {
+            new_envelopes[a]
+                .date()
+                .partial_cmp(&new_envelopes[b].date())
+                .unwrap()
+        });
+        self.mailboxes
+            .write()
+            .unwrap()
+            .entry(mailbox_hash)
+            .or_default()
+            .extend(ordered_hash_set.iter().cloned());
+        self.envelopes.write().unwrap().extend(new_envelopes);
+        self.update_threads(mailbox_hash, |threads, assigned| {
+            for h in &ordered_hash_set {
+                threads.insert_unassigned(&self.envelopes, *h, assigned);
             }
-        } else {
-            mailboxes_lck.entry(mailbox_hash).and_modify(|m| {
-                m.extend(new_envelopes.keys().cloned());
-            });
-            threads_lck.entry(mailbox_hash).and_modify(|t| {
-                let mut ordered_hash_set =
-                    new_envelopes.keys().cloned().collect::<Vec<EnvelopeHash>>();
-                ordered_hash_set.sort_by(|a, b| {
-                    new_envelopes[a]
-                        .date()
-                        .partial_cmp(&new_envelopes[b].date())
-                        .unwrap()
-                });
-                for h in ordered_hash_set {
-                    envelopes
-                        .write()
-                        .unwrap()
-                        .insert(h, new_envelopes.remove(&h).unwrap());
-                    t.insert(envelopes, h);
-                }
-            });
-        }
+        });
 
         let mut ret = SmallVec::new();
-        let keys = threads_lck.keys().cloned().collect::<Vec<MailboxHash>>();
+        let keys = self
+            .threads
+            .read()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<MailboxHash>>();
         for t_fh in keys {
             if t_fh == mailbox_hash {
                 continue;
             }
-            if sent_mailbox
-                .read()
-                .unwrap()
-                .map(|f| f == mailbox_hash)
-                .unwrap_or(false)
-            {
-                let envelopes_lck = envelopes.read().unwrap();
-                let mut ordered_hash_set = threads_lck[&mailbox_hash]
-                    .hash_set
-                    .iter()
-                    .cloned()
-                    .collect::<Vec<EnvelopeHash>>();
-                ordered_hash_set.sort_by(|a, b| {
-                    envelopes_lck[a]
-                        .date()
-                        .partial_cmp(&envelopes_lck[b].date())
-                        .unwrap()
-                });
-                drop(envelopes_lck);
-                let mut updated = false;
-                for h in ordered_hash_set {
-                    updated |= threads_lck
-                        .entry(t_fh)
-                        .or_default()
-                        .insert_reply(envelopes, h);
-                }
-                if updated {
-                    ret.push(t_fh);
-                }
+            // Thread the e-mail of the sent mailbox into the other mailboxes as
+            // replies, and vice versa.
+            let (from, into) = if sent_mailbox == Some(mailbox_hash) {
+                (mailbox_hash, t_fh)
+            } else if sent_mailbox == Some(t_fh) {
+                (t_fh, mailbox_hash)
+            } else {
                 continue;
-            }
-            if sent_mailbox
-                .read()
-                .unwrap()
-                .map(|f| f == t_fh)
-                .unwrap_or(false)
-            {
-                let envelopes_lck = envelopes.read().unwrap();
-                let mut ordered_hash_set = threads_lck[&t_fh]
+            };
+            let mut ordered_hash_set = {
+                let threads_lck = self.threads.read().unwrap();
+                let Some(slot) = threads_lck.get(&from) else {
+                    continue;
+                };
+                slot.threads
                     .hash_set
                     .iter()
                     .cloned()
-                    .collect::<Vec<EnvelopeHash>>();
+                    .collect::<Vec<EnvelopeHash>>()
+            };
+            {
+                let envelopes_lck = self.envelopes.read().unwrap();
                 ordered_hash_set.sort_by(|a, b| {
                     envelopes_lck[a]
                         .date()
                         .partial_cmp(&envelopes_lck[b].date())
                         .unwrap()
                 });
-                drop(envelopes_lck);
+            }
+            let updated = self.update_threads(into, |threads, assigned| {
                 let mut updated = false;
-                for h in ordered_hash_set {
-                    updated |= threads_lck
-                        .entry(mailbox_hash)
-                        .or_default()
-                        .insert_reply(envelopes, h);
-                }
-                if updated {
-                    ret.push(mailbox_hash);
+                for h in &ordered_hash_set {
+                    updated |= threads.insert_reply_unassigned(&self.envelopes, *h, assigned);
                 }
+                updated
+            });
+            if updated {
+                ret.push(into);
             }
         }
         if ret.is_empty() {
@@ -294,7 +461,7 @@ impl Collection {
                 m.insert(new_hash);
             });
         self.envelopes.write().unwrap().insert(new_hash, envelope);
-        let mut threads_lck = self.threads.write().unwrap();
+        let mut threads_lck = self.threads_mut();
         if self
             .sent_mailbox
             .read()
@@ -303,13 +470,16 @@ impl Collection {
             .unwrap_or(false)
         {
             for t in threads_lck.values_mut() {
-                _ = t.update_envelope(&self.envelopes, old_hash, new_hash);
+                _ = t
+                    .get_mut()
+                    .update_envelope(&self.envelopes, old_hash, new_hash);
             }
         }
         {
             if threads_lck
                 .entry(mailbox_hash)
                 .or_default()
+                .get_mut()
                 .update_envelope(&self.envelopes, old_hash, new_hash)
                 .is_ok()
             {
@@ -320,17 +490,20 @@ impl Collection {
         threads_lck
             .entry(mailbox_hash)
             .or_default()
+            .get_mut()
             .insert(&self.envelopes, new_hash);
         for (h, t) in threads_lck.iter_mut() {
             if *h == mailbox_hash {
                 continue;
             }
-            _ = t.update_envelope(&self.envelopes, old_hash, new_hash);
+            _ = t
+                .get_mut()
+                .update_envelope(&self.envelopes, old_hash, new_hash);
         }
     }
 
     pub fn update_flags(&self, env_hash: EnvelopeHash, mailbox_hash: MailboxHash) {
-        let mut threads_lck = self.threads.write().unwrap();
+        let mut threads_lck = self.threads_mut();
         if self
             .sent_mailbox
             .read()
@@ -339,13 +512,16 @@ impl Collection {
             .unwrap_or(false)
         {
             for t in threads_lck.values_mut() {
-                _ = t.update_envelope(&self.envelopes, env_hash, env_hash);
+                _ = t
+                    .get_mut()
+                    .update_envelope(&self.envelopes, env_hash, env_hash);
             }
         }
         {
             if threads_lck
                 .entry(mailbox_hash)
                 .or_default()
+                .get_mut()
                 .update_envelope(&self.envelopes, env_hash, env_hash)
                 .is_ok()
             {
@@ -356,13 +532,52 @@ impl Collection {
         threads_lck
             .entry(mailbox_hash)
             .or_default()
+            .get_mut()
             .insert(&self.envelopes, env_hash);
         for (h, t) in threads_lck.iter_mut() {
             if *h == mailbox_hash {
                 continue;
             }
-            _ = t.update_envelope(&self.envelopes, env_hash, env_hash);
+            _ = t
+                .get_mut()
+                .update_envelope(&self.envelopes, env_hash, env_hash);
+        }
+    }
+
+    /// Add or remove envelopes of the collection to a mailbox that is not
+    /// managed by the mail backend, such as a virtual mailbox made up of
+    /// envelopes from other mailboxes. Each item of `membership` states
+    /// whether the envelope should be in `mailbox_hash`.
+    ///
+    /// Returns `true` if the mailbox's contents changed.
+    pub fn set_mailbox_membership(
+        &self,
+        mailbox_hash: MailboxHash,
+        membership: impl IntoIterator<Item = (EnvelopeHash, bool)>,
+    ) -> bool {
+        self.new_mailbox(mailbox_hash);
+        let mut changed = false;
+        let mut threads_lck = self.threads_mut();
+        let mut mailboxes_lck = self.mailboxes.write().unwrap();
+        let (Some(slot), Some(mailbox)) = (
+            threads_lck.get_mut(&mailbox_hash),
+            mailboxes_lck.get_mut(&mailbox_hash),
+        ) else {
+            return false;
+        };
+        let threads = slot.get_mut();
+        for (env_hash, is_member) in membership {
+            if is_member && self.contains_key(&env_hash) {
+                if mailbox.insert(env_hash) {
+                    threads.insert(&self.envelopes, env_hash);
+                    changed = true;
+                }
+            } else if mailbox.remove(&env_hash) {
+                threads.remove(env_hash);
+                changed = true;
+            }
         }
+        changed
     }
 
     pub fn insert(&self, envelope: Envelope, mailbox_hash: MailboxHash) -> bool {
@@ -379,11 +594,10 @@ impl Collection {
                 m.insert(hash);
             });
         self.envelopes.write().unwrap().insert(hash, envelope);
-        self.threads
-            .write()
-            .unwrap()
+        self.threads_mut()
             .entry(mailbox_hash)
             .or_default()
+            .get_mut()
             .insert(&self.envelopes, hash);
         if self
             .sent_mailbox
@@ -399,9 +613,9 @@ impl Collection {
 
     pub fn insert_reply(&self, env_hash: EnvelopeHash) {
         debug_assert!(self.envelopes.read().unwrap().contains_key(&env_hash));
-        let mut iter = self.threads.write().unwrap();
+        let mut iter = self.threads_mut();
         for t in iter.values_mut() {
-            t.insert_reply(&self.envelopes, env_hash);
+            t.get_mut().insert_reply(&self.envelopes, env_hash);
         }
     }
 
@@ -418,9 +632,14 @@ impl Collection {
     }
 
     #[inline]
-    pub fn get_threads(&'_ self, hash: MailboxHash) -> RwRef<'_, MailboxHash, Threads> {
+    pub fn get_threads(&'_ self, hash: MailboxHash) -> ThreadsRef<'_> {
         let guard = self.threads.read().unwrap();
-        RwRef { guard, hash }
+        ThreadsRef { guard, hash }
+    }
+
+    #[inline]
+    pub fn has_threads(&self, hash: MailboxHash) -> bool {
+        self.threads.read().unwrap().contains_key(&hash)
     }
 
     #[inline]
@@ -432,6 +651,19 @@ impl Collection {
         RwRef { guard, hash }
     }
 
+    /// Date of the newest envelope in `mailbox_hash`, if it has any.
+    pub fn newest_date(&self, mailbox_hash: MailboxHash) -> Option<crate::UnixTimestamp> {
+        let envelopes = self.envelopes.read().unwrap();
+        self.mailboxes
+            .read()
+            .unwrap()
+            .get(&mailbox_hash)?
+            .iter()
+            .filter_map(|h| envelopes.get(h))
+            .map(|env| env.date())
+            .max()
+    }
+
     #[inline]
     pub fn contains_key(&self, env_hash: &EnvelopeHash) -> bool {
         self.envelopes.read().unwrap().contains_key(env_hash)
@@ -441,14 +673,50 @@ impl Collection {
         let mut mailboxes_lck = self.mailboxes.write().unwrap();
         if let std::collections::hash_map::Entry::Vacant(e) = mailboxes_lck.entry(mailbox_hash) {
             e.insert(Default::default());
-            self.threads
-                .write()
-                .unwrap()
-                .insert(mailbox_hash, Threads::default());
+            self.threads_mut()
+                .insert(mailbox_hash, ThreadsSlot::default());
+        }
+    }
+
+    /// Remove a mailbox and its threads from the collection.
+    pub fn remove_mailbox(&self, mailbox_hash: MailboxHash) {
+        self.threads_mut().remove(&mailbox_hash);
+        self.mailboxes.write().unwrap().remove(&mailbox_hash);
+    }
+
+    /// Move the threads and envelopes of a mailbox to a new hash, after it
+    /// was renamed.
+    pub fn rename_mailbox(&self, old_hash: MailboxHash, new_hash: MailboxHash) {
+        {
+            let mut threads_lck = self.threads_mut();
+            if let Some(slot) = threads_lck.remove(&old_hash) {
+                threads_lck.insert(new_hash, slot);
+            }
+        }
+        let mut mailboxes_lck = self.mailboxes.write().unwrap();
+        if let Some(entry) = mailboxes_lck.remove(&old_hash) {
+            mailboxes_lck.insert(new_hash, entry);
         }
     }
 }
 
+pub struct ThreadsRef<'g> {
+    guard: RwLockReadGuard<'g, HashMap<MailboxHash, ThreadsSlot>>,
+    hash: MailboxHash,
+}
+
+impl Deref for ThreadsRef<'_> {
+    type Target = Threads;
+
+    fn deref(&self) -> &Threads {
+        &self
+            .guard
+            .get(&self.hash)
+            .expect("Hash was not found")
+            .threads
+    }
+}
+
 pub struct RwRef<'g, K: std::cmp::Eq + std::hash::Hash, V> {
     guard: RwLockReadGuard<'g, HashMap<K, V>>,
     hash: K,
@@ -55,6 +55,10 @@ pub struct AccountSettings {
     pub mailboxes: IndexMap<String, MailboxConf>,
     #[serde(default)]
     pub manual_refresh: bool,
+    /// Names of additional headers to retain in envelopes, for backends that
+    /// only fetch a subset of each message's headers (e.g. IMAP).
+    #[serde(default)]
+    pub retained_headers: Vec<String>,
     #[serde(flatten)]
     pub extra: IndexMap<String, String>,
 }
@@ -99,6 +103,9 @@ impl AccountSettings {
     /// Get the server password, either directly from the `server_password`
     /// settings value, or by running the `server_password_command` and reading
     /// the output.
+    ///
+    /// The command is run with `sh -c` without any restriction; callers that
+    /// limit which commands may run must check it beforehand.
     pub fn server_password(&self) -> Result<String> {
         if let Some(cmd) = self.extra.get("server_password_command") {
             let output = std::process::Command::new("sh")
@@ -175,6 +182,44 @@ impl AccountSettings {
                 }
             }
         }
+        {
+            if let Some(abook_file) = self.extra.swap_remove("abook_file") {
+                let path = Path::new(&abook_file).expand();
+
+                if !matches!(path.try_exists(), Ok(true)) {
+                    return Err(Error::new(format!(
+                        "`abook_file` path {} does not exist",
+                        path.display()
+                    ))
+                    .set_details("`abook_file` must be an existing path of an abook database")
+                    .set_kind(ErrorKind::Configuration));
+                }
+                if !path.is_file() {
+                    return Err(Error::new(format!(
+                        "`abook_file` path {} is not a file",
+                        path.display()
+                    ))
+                    .set_details("`abook_file` must be a path of an abook database")
+                    .set_kind(ErrorKind::Configuration));
+                }
+            }
+        }
+        for name in &self.retained_headers {
+            if name.is_empty()
+                || !name
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+            {
+                return Err(Error::new(format!(
+                    "`retained_headers` value {:?} is not a valid header name",
+                    name
+                ))
+                .set_details(
+                    "Header names may only contain ASCII letters, digits, dashes and underscores.",
+                )
+                .set_kind(ErrorKind::Configuration));
+            }
+        }
 
         Ok(())
     }
@@ -191,6 +236,8 @@ pub struct MailboxConf {
     pub subscribe: ToggleFlag,
     #[serde(default)]
     pub ignore: ToggleFlag,
+    #[serde(default = "false_val")]
+    pub read_only: bool,
     #[serde(default = "none")]
     pub usage: Option<SpecialUsageMailbox>,
     #[serde(default = "none")]
@@ -208,6 +255,7 @@ impl Default for MailboxConf {
             autoload: false,
             subscribe: ToggleFlag::Unset,
             ignore: ToggleFlag::Unset,
+            read_only: false,
             usage: None,
             sort_order: None,
             encoding: None,
@@ -0,0 +1,137 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! # `abook` address book format
+//!
+//! An `abook` database is an INI-like file with one numbered section per
+//! contact:
+//!
+//! ```text
+//! # abook addressbook file
+//!
+//! [format]
+//! program=abook
+//! version=0.6.1
+//!
+//! [0]
+//! name=John Doe
+//! email=john@example.com,jdoe@example.org
+//! nick=johnny
+//! ```
+
+use crate::contacts::Card;
+
+/// Parse the contacts of an `abook` database.
+///
+/// The first e-mail address of a contact is its e-mail, and the rest are kept
+/// as the extra properties `E-MAIL 2`, `E-MAIL 3` and so on. The `nick` field
+/// is the contact's nickname. Fields without a [`Card`] counterpart are kept
+/// as extra properties with uppercase names.
+pub fn parse_abook(contents: &str) -> Vec<Card> {
+    let mut ret = vec![];
+    let mut current: Option<Card> = None;
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            ret.extend(current.take());
+            if section.parse::<usize>().is_ok() {
+                current = Some(Card::new());
+            }
+            continue;
+        }
+        let Some(card) = current.as_mut() else {
+            continue;
+        };
+        let Some((key, value)) = line.split_once('=') else {
+            log::debug!("Skipping abook line {:?}", line);
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match key.trim() {
+            "name" => {
+                card.set_name(value.to_string());
+            }
+            "email" => {
+                let mut emails = value.split(',').map(str::trim).filter(|e| !e.is_empty());
+                if let Some(email) = emails.next() {
+                    card.set_email(email.to_string());
+                }
+                for (n, email) in emails.enumerate() {
+                    card.set_extra_property(&format!("E-MAIL {}", n + 2), email.to_string());
+                }
+            }
+            "nick" => {
+                card.set_nickname(value.to_string());
+            }
+            "url" => {
+                card.set_url(value.to_string());
+            }
+            other => {
+                card.set_extra_property(&other.to_uppercase(), value.to_string());
+            }
+        }
+    }
+    ret.extend(current);
+    ret
+}
+
+#[test]
+fn test_abook_contacts() {
+    let cards = parse_abook(
+        r#"# abook addressbook file
+
+[format]
+program=abook
+version=0.6.1
+
+
+[0]
+name=John Doe
+email=john@example.com,jdoe@example.org
+nick=johnny
+mobile=555-1234
+
+[1]
+name=Jane Doe
+email=jane@example.com
+url=https://example.com
+"#,
+    );
+    assert_eq!(cards.len(), 2);
+    assert_eq!(cards[0].name(), "John Doe");
+    assert_eq!(cards[0].email(), "john@example.com");
+    assert_eq!(cards[0].nickname(), "johnny");
+    assert_eq!(
+        cards[0].extra_property("E-MAIL 2"),
+        Some("jdoe@example.org")
+    );
+    assert_eq!(cards[0].extra_property("MOBILE"), Some("555-1234"));
+    assert_eq!(cards[1].name(), "Jane Doe");
+    assert_eq!(cards[1].email(), "jane@example.com");
+    assert_eq!(cards[1].url(), "https://example.com");
+    assert!(cards[1].extra_properties().is_empty());
+}
@@ -32,7 +32,7 @@ use uuid::Uuid;
 
 use crate::{
     contacts::CardId,
-    utils::datetime::{now, timestamp_to_string, UnixTimestamp},
+    utils::datetime::{now, timestamp_to_string, timestamp_to_string_utc, UnixTimestamp},
 };
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -43,6 +43,9 @@ pub struct Card {
     pub additionalname: String,
     pub name_prefix: String,
     pub name_suffix: String,
+    /// Short name used to refer to the contact, e.g. a `mutt` alias.
+    #[serde(default)]
+    pub nickname: String,
     pub birthday: Option<UnixTimestamp>,
     pub email: String,
     pub url: String,
@@ -108,6 +111,7 @@ impl Card {
             additionalname: String::new(),
             name_prefix: String::new(),
             name_suffix: String::new(),
+            nickname: String::new(),
             //address
             birthday: None,
             email: String::new(),
@@ -130,6 +134,7 @@ impl Card {
     get_fn! { additionalname str }
     get_fn! { name_prefix str }
     get_fn! { name_suffix str }
+    get_fn! { nickname str }
     get_fn! { email str }
     get_fn! { url str }
     get_fn! { key str }
@@ -160,6 +165,7 @@ impl Card {
     set_fn! { set_additionalname, additionalname }
     set_fn! { set_name_prefix, name_prefix }
     set_fn! { set_name_suffix, name_suffix }
+    set_fn! { set_nickname, nickname }
     set_fn! { set_email, email }
     set_fn! { set_url, url }
     set_fn! { set_key, key }
@@ -174,6 +180,80 @@ impl Card {
         self
     }
 
+    /// Whether the birthday of the card falls on the day of `timestamp`, in
+    /// local time, in any year.
+    ///
+    /// Birthdays are dates without a time zone, stored as midnight UTC.
+    pub fn has_birthday_on(&self, timestamp: UnixTimestamp) -> bool {
+        self.birthday.is_some_and(|birthday| {
+            timestamp_to_string_utc(birthday, Some("%m-%d"), false)
+                == timestamp_to_string(timestamp, Some("%m-%d"), false)
+        })
+    }
+
+    /// Normalize a name for comparison: its lowercase alphanumeric words,
+    /// sorted and separated by spaces.
+    pub fn normalized_name(name: &str) -> String {
+        let mut words = name
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect::<Vec<String>>();
+        words.sort();
+        words.join(" ")
+    }
+
+    /// Merge the fields of `other` into this card.
+    ///
+    /// Empty fields are filled in with the values of `other`. Values of
+    /// `other` that conflict with non-empty fields are kept as extra
+    /// properties, under the field name (e.g. `E-MAIL`) followed by a
+    /// number, so that no information is lost.
+    pub fn merge(&mut self, other: &Self) {
+        macro_rules! merge_field {
+            ($key:literal, $field:tt) => {
+                if self.$field.is_empty() {
+                    self.$field = other.$field.clone();
+                } else if !other.$field.is_empty() && self.$field != other.$field {
+                    self.add_merged_property($key, other.$field.clone());
+                }
+            };
+        }
+        merge_field! { "TITLE", title };
+        merge_field! { "NAME", name };
+        merge_field! { "ADDITIONAL NAME", additionalname };
+        merge_field! { "NAME PREFIX", name_prefix };
+        merge_field! { "NAME SUFFIX", name_suffix };
+        merge_field! { "NICKNAME", nickname };
+        merge_field! { "E-MAIL", email };
+        merge_field! { "URL", url };
+        merge_field! { "KEY", key };
+        if self.birthday.is_none() {
+            self.birthday = other.birthday;
+        }
+        for (key, value) in other.extra_properties.iter() {
+            match self.extra_properties.get(key) {
+                None => {
+                    self.extra_properties.insert(key.clone(), value.clone());
+                }
+                Some(v) if v == value => {}
+                Some(_) => self.add_merged_property(key, value.clone()),
+            }
+        }
+        self.last_edited = now();
+    }
+
+    fn add_merged_property(&mut self, key: &str, value: String) {
+        if self.extra_properties.values().any(|v| *v == value) {
+            return;
+        }
+        let key = (2..)
+            .map(|n| format!("{key} {n}"))
+            .find(|k| !self.extra_properties.contains_key(k))
+            .unwrap();
+        self.extra_properties.insert(key, value);
+    }
+
     pub fn to_vcard_string(&self) -> String {
         use crate::utils::vobject::{vcard::VcardBuilder, *};
         write_component(
@@ -209,6 +289,7 @@ impl From<IndexMap<String, String>> for Card {
         get! { "ADDITIONAL NAME", additionalname };
         get! { "NAME PREFIX", name_prefix };
         get! { "NAME SUFFIX", name_suffix };
+        get! { "NICKNAME", nickname };
         get! { "E-MAIL", email };
         get! { "URL", url };
         get! { "KEY", key };
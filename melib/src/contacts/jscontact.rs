@@ -356,6 +356,7 @@ pub mod json_types {
                 additionalname: "".into(),
                 name_prefix: "".into(),
                 name_suffix: "".into(),
+                nickname: "".into(),
                 birthday: None,
                 email: "user@example.com".into(),
                 url: "".into(),
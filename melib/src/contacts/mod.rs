@@ -19,6 +19,7 @@
  * along with meli. If not, see <http://www.gnu.org/licenses/>.
  */
 
+pub mod abook;
 pub mod jscontact;
 pub mod mutt;
 pub mod notmuchcontact;
@@ -26,6 +27,7 @@ pub mod vcard;
 
 mod card;
 use std::{
+    collections::HashMap,
     hash::{Hash, Hasher},
     ops::Deref,
     path::Path,
@@ -35,7 +37,10 @@ pub use card::*;
 use indexmap::IndexMap;
 use uuid::Uuid;
 
-use crate::utils::{parsec::Parser, shellexpand::ShellExpandTrait};
+use crate::{
+    error::{Error, ErrorKind, Result},
+    utils::{datetime::UnixTimestamp, shellexpand::ShellExpandTrait},
+};
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 #[serde(from = "String")]
@@ -92,27 +97,22 @@ impl Contacts {
 
     pub fn with_account(s: &crate::conf::AccountSettings) -> Self {
         let mut ret = Self::new(s.name.clone());
-        if let Some(mutt_alias_file) = s.extra.get("mutt_alias_file") {
-            match std::fs::read_to_string(Path::new(mutt_alias_file).expand())
-                .map_err(|err| err.to_string())
-                .and_then(|contents| {
-                    contents
-                        .lines()
-                        .map(|line| mutt::parse_mutt_contact().parse(line).map(|(_, c)| c))
-                        .collect::<Result<Vec<Card>, &str>>()
-                        .map_err(|err| err.to_string())
-                }) {
+        for (key, format) in [
+            ("mutt_alias_file", ImportFormat::MuttAlias),
+            ("abook_file", ImportFormat::Abook),
+        ] {
+            let Some(path) = s.extra.get(key) else {
+                continue;
+            };
+            match format.parse_file(&Path::new(path).expand()) {
                 Ok(cards) => {
-                    for c in cards {
+                    for mut c in cards {
+                        c.set_external_resource(true);
                         ret.add_card(c);
                     }
                 }
                 Err(err) => {
-                    log::warn!(
-                        "Could not load mutt alias file {:?}: {}",
-                        mutt_alias_file,
-                        err
-                    );
+                    log::warn!("Could not load {} {:?}: {}", format, path, err);
                 }
             }
         }
@@ -203,10 +203,112 @@ impl Contacts {
         self.cards.contains_key(&card_id)
     }
 
+    /// Find groups of cards that are likely duplicates of each other, that is
+    /// cards that share an e-mail address or have similar names.
+    ///
+    /// Names are similar if they have the same words regardless of case,
+    /// punctuation and order, e.g. `John Doe` and `doe, john`. Each group is
+    /// returned in address book order, and groups are ordered by their first
+    /// card.
+    pub fn duplicates(&self) -> Vec<Vec<CardId>> {
+        fn find(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+
+        let cards = self.cards.values().collect::<Vec<&Card>>();
+        let mut parents = (0..cards.len()).collect::<Vec<usize>>();
+        let mut seen: HashMap<String, usize> = HashMap::default();
+        for (i, card) in cards.iter().enumerate() {
+            let emails = crate::email::Address::list_try_from(card.email.as_str())
+                .map(|list| {
+                    list.iter()
+                        .flat_map(crate::email::Address::mailboxes)
+                        .map(|a| a.get_email().to_lowercase())
+                        .collect::<Vec<String>>()
+                })
+                .unwrap_or_else(|_| vec![card.email.trim().to_lowercase()]);
+            let name = Card::normalized_name(&card.name);
+            for key in emails
+                .into_iter()
+                .filter(|e| !e.is_empty())
+                .map(|e| format!("email:{e}"))
+                .chain((!name.is_empty()).then(|| format!("name:{name}")))
+            {
+                if let Some(&j) = seen.get(&key) {
+                    let (root_i, root_j) = (find(&mut parents, i), find(&mut parents, j));
+                    parents[root_i.max(root_j)] = root_i.min(root_j);
+                } else {
+                    seen.insert(key, i);
+                }
+            }
+        }
+        let mut groups: IndexMap<usize, Vec<CardId>> = IndexMap::default();
+        for (i, card) in cards.iter().enumerate() {
+            groups
+                .entry(find(&mut parents, i))
+                .or_default()
+                .push(card.id);
+        }
+        groups.into_values().filter(|g| g.len() > 1).collect()
+    }
+
+    /// Merge the cards in `from` into the card `into` with [`Card::merge`],
+    /// and remove them from the address book.
+    pub fn merge_cards(&mut self, into: CardId, from: &[CardId]) -> Result<()> {
+        if !self.cards.contains_key(&into) {
+            return Err(
+                Error::new(format!("Contact {into} was not found.")).set_kind(ErrorKind::NotFound)
+            );
+        }
+        for id in from.iter().filter(|id| **id != into) {
+            let Some(other) = self.cards.shift_remove(id) else {
+                continue;
+            };
+            self.cards[&into].merge(&other);
+        }
+        Ok(())
+    }
+
+    /// Import the contacts of the file at `path` in the given `format`, and
+    /// return how many were imported.
+    ///
+    /// Contacts that already exist with the same name and e-mail are skipped.
+    pub fn import(&mut self, format: ImportFormat, path: &Path) -> Result<usize> {
+        let mut count = 0;
+        for card in format.parse_file(path)? {
+            if self
+                .cards
+                .values()
+                .any(|c| c.name == card.name && c.email == card.email)
+            {
+                continue;
+            }
+            self.add_card(card);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Cards whose birthday falls on the day of `timestamp`, see
+    /// [`Card::has_birthday_on`].
+    pub fn birthdays_on(&self, timestamp: UnixTimestamp) -> impl Iterator<Item = &Card> + '_ {
+        self.cards
+            .values()
+            .filter(move |c| c.has_birthday_on(timestamp))
+    }
+
     pub fn search(&self, term: &str) -> Vec<String> {
         self.cards
             .values()
-            .filter(|c| c.email.contains(term) || c.name.contains(term))
+            .filter(|c| {
+                c.email.contains(term)
+                    || c.name.contains(term)
+                    || c.nickname.eq_ignore_ascii_case(term)
+            })
             .map(|c| {
                 crate::email::Address::new(
                     if c.name.is_empty() {
@@ -222,6 +324,56 @@ impl Contacts {
     }
 }
 
+/// Address book file formats of other programs that contacts can be imported
+/// from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImportFormat {
+    /// A `mutt` alias file.
+    MuttAlias,
+    /// An `abook` database.
+    Abook,
+}
+
+impl ImportFormat {
+    /// Read and parse the file at `path`.
+    pub fn parse_file(self, path: &Path) -> Result<Vec<Card>> {
+        let contents = std::fs::read_to_string(path).map_err(|err| {
+            let kind: ErrorKind = err.kind().into();
+            Error::new(format!("Could not read {} {}", self, path.display()))
+                .set_kind(kind)
+                .set_source(Some(std::sync::Arc::new(err)))
+        })?;
+        Ok(match self {
+            Self::MuttAlias => mutt::parse_mutt_alias_file(&contents),
+            Self::Abook => abook::parse_abook(&contents),
+        })
+    }
+}
+
+impl std::fmt::Display for ImportFormat {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::MuttAlias => write!(fmt, "mutt alias file"),
+            Self::Abook => write!(fmt, "abook database"),
+        }
+    }
+}
+
+impl std::str::FromStr for ImportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            s if s.eq_ignore_ascii_case("mutt") => Ok(Self::MuttAlias),
+            s if s.eq_ignore_ascii_case("abook") => Ok(Self::Abook),
+            other => Err(Error::new(format!(
+                "Unknown contacts format `{other}`, expected `mutt` or `abook`."
+            ))
+            .set_kind(ErrorKind::ValueError)),
+        }
+    }
+}
+
 impl Deref for Contacts {
     type Target = IndexMap<CardId, Card>;
 
@@ -229,3 +381,6 @@ impl Deref for Contacts {
         &self.cards
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -57,7 +57,7 @@ pub fn parse_mutt_contact<'a>() -> impl Parser<'a, Card> {
                 let mut tokens = l.split_whitespace().collect::<VecDeque<&str>>();
 
                 let mut ret = Card::new();
-                let title = tokens.pop_front().ok_or(l)?.to_string();
+                let nickname = tokens.pop_front().ok_or(l)?.to_string();
                 let mut email = tokens.pop_back().ok_or(l)?.to_string();
                 if email.starts_with('<') && email.ends_with('>') {
                     email.pop();
@@ -70,9 +70,9 @@ pub fn parse_mutt_contact<'a>() -> impl Parser<'a, Card> {
                 });
                 name.pop();
                 if name.trim().is_empty() {
-                    name.clone_from(&title);
+                    name.clone_from(&nickname);
                 }
-                ret.set_title(title).set_email(email).set_name(name);
+                ret.set_nickname(nickname).set_email(email).set_name(name);
                 Ok::<Card, &'a str>(ret)
             },
         )
@@ -80,6 +80,23 @@ pub fn parse_mutt_contact<'a>() -> impl Parser<'a, Card> {
     }
 }
 
+/// Parse the `alias` commands of a `mutt` alias file, skipping empty lines,
+/// comments and other commands.
+pub fn parse_mutt_alias_file(contents: &str) -> Vec<Card> {
+    contents
+        .lines()
+        .map(str::trim_start)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| match parse_mutt_contact().parse(line) {
+            Ok((_, card)) => Some(card),
+            Err(err) => {
+                log::debug!("Skipping mutt alias file line {:?}: {}", line, err);
+                None
+            }
+        })
+        .collect()
+}
+
 #[test]
 fn test_mutt_contacts() {
     let a = "alias mumon      My dear pupil Mumon foobar@example.com";
@@ -89,18 +106,29 @@ fn test_mutt_contacts() {
     let (other, a_card) = parse_mutt_contact().parse(a).unwrap();
     assert!(other.is_empty());
     assert_eq!(a_card.name(), "My dear pupil Mumon");
-    assert_eq!(a_card.title(), "mumon");
+    assert_eq!(a_card.nickname(), "mumon");
     assert_eq!(a_card.email(), "foobar@example.com");
 
     let (other, b_card) = parse_mutt_contact().parse(b).unwrap();
     assert!(other.is_empty());
     assert_eq!(b_card.name(), "mumon");
-    assert_eq!(b_card.title(), "mumon");
+    assert_eq!(b_card.nickname(), "mumon");
     assert_eq!(b_card.email(), "foobar@example.com");
 
     let (other, c_card) = parse_mutt_contact().parse(c).unwrap();
     assert!(other.is_empty());
     assert_eq!(c_card.name(), "<long name>");
-    assert_eq!(c_card.title(), "<nickname>");
+    assert_eq!(c_card.nickname(), "<nickname>");
     assert_eq!(c_card.email(), "address");
+
+    let cards = parse_mutt_alias_file(
+        "# aliases\n\nalias mumon Mumon <foobar@example.com>\nset sort=date\n  alias joe \
+         joe@example.com\n",
+    );
+    assert_eq!(cards.len(), 2);
+    assert_eq!(cards[0].nickname(), "mumon");
+    assert_eq!(cards[0].name(), "Mumon");
+    assert_eq!(cards[0].email(), "foobar@example.com");
+    assert_eq!(cards[1].nickname(), "joe");
+    assert_eq!(cards[1].email(), "joe@example.com");
 }
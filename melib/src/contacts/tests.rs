@@ -0,0 +1,101 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+use super::*;
+
+fn card(name: &str, email: &str) -> Card {
+    let mut card = Card::new();
+    card.set_name(name.to_string()).set_email(email.to_string());
+    card
+}
+
+#[test]
+fn test_contacts_duplicates() {
+    let mut contacts = Contacts::new("test".to_string());
+    let a = card("John Doe", "john@example.com");
+    let b = card("Johnny", "JOHN@example.com");
+    let c = card("doe, john", "jdoe@example.org");
+    let d = card("Jane Doe", "jane@example.com");
+    let e = card("", "");
+    let ids = [a.id, b.id, c.id, d.id, e.id];
+    for card in [a, b, c, d, e] {
+        contacts.add_card(card);
+    }
+    assert_eq!(contacts.duplicates(), vec![vec![ids[0], ids[1], ids[2]]]);
+}
+
+#[test]
+fn test_contacts_merge_cards() {
+    let mut contacts = Contacts::new("test".to_string());
+    let mut a = card("John Doe", "john@example.com");
+    a.set_extra_property("NOTE", "friend".to_string());
+    let mut b = card("", "jdoe@example.org");
+    b.set_url("https://example.com".to_string())
+        .set_extra_property("NOTE", "colleague".to_string())
+        .set_extra_property("PHONE", "555".to_string());
+    let (a_id, b_id) = (a.id, b.id);
+    contacts.add_card(a);
+    contacts.add_card(b);
+    contacts.merge_cards(a_id, &[b_id]).unwrap();
+    assert!(!contacts.card_exists(b_id));
+    let merged = &contacts[&a_id];
+    assert_eq!(merged.name(), "John Doe");
+    assert_eq!(merged.email(), "john@example.com");
+    assert_eq!(merged.url(), "https://example.com");
+    assert_eq!(merged.extra_property("E-MAIL 2"), Some("jdoe@example.org"));
+    assert_eq!(merged.extra_property("NOTE"), Some("friend"));
+    assert_eq!(merged.extra_property("NOTE 2"), Some("colleague"));
+    assert_eq!(merged.extra_property("PHONE"), Some("555"));
+    assert!(contacts.merge_cards(b_id, &[a_id]).is_err());
+}
+
+#[test]
+fn test_contacts_birthdays_on() {
+    use crate::utils::datetime::{now, timestamp_from_string, timestamp_to_string};
+
+    let now = now();
+    // A leap year, so that the test also passes on the 29th of February.
+    let birthday = |date: String| timestamp_from_string(date, "%Y%m%d").unwrap();
+    let mut contacts = Contacts::new("test".to_string());
+    let mut a = card("John Doe", "john@example.com");
+    a.birthday = birthday(format!(
+        "1984{}",
+        timestamp_to_string(now, Some("%m%d"), false)
+    ));
+    let mut b = card("Jane Doe", "jane@example.com");
+    b.birthday = birthday(format!(
+        "1984{}",
+        timestamp_to_string(now + 2 * 24 * 60 * 60, Some("%m%d"), false)
+    ));
+    let c = card("No Birthday", "nobody@example.com");
+    let a_id = a.id;
+    assert!(a.has_birthday_on(now));
+    assert!(!b.has_birthday_on(now));
+    assert!(!c.has_birthday_on(now));
+    for card in [a, b, c] {
+        contacts.add_card(card);
+    }
+    assert_eq!(
+        contacts.birthdays_on(now).map(|c| c.id).collect::<Vec<_>>(),
+        vec![a_id]
+    );
+}
@@ -95,16 +95,22 @@ pub mod address;
 pub mod attachment_types;
 pub mod attachments;
 pub mod compose;
+pub mod dsn;
+pub mod forge;
 pub mod headers;
 pub mod list_management;
 pub mod mailto;
 pub mod parser;
 pub mod pgp;
+pub mod received;
 
-use std::{borrow::Cow, ops::Deref};
+use std::{borrow::Cow, ops::Deref, sync::Arc};
 
 pub use address::{Address, MessageID, References, StrBuild, StrBuilder};
-pub use attachments::{Attachment, AttachmentBuilder};
+pub use attachments::{
+    lazy::{MimePart, MimeStructure},
+    Attachment, AttachmentBuilder,
+};
 pub use compose::{attachment_from_file, Draft};
 pub use headers::*;
 use indexmap::IndexSet;
@@ -113,7 +119,9 @@ use smallvec::SmallVec;
 use crate::{
     error::{Error, Result},
     parser::BytesExt,
+    text::grapheme_clusters::TextProcessing,
     thread::ThreadNodeHash,
+    utils::{intern::intern, SortField},
     TagHash, UnixTimestamp,
 };
 
@@ -251,7 +259,8 @@ crate::declare_u64_hash!(EnvelopeHash);
 #[derive(Clone, Deserialize, Serialize)]
 pub struct Envelope {
     pub date: String,
-    pub subject: Option<String>,
+    #[serde(deserialize_with = "crate::utils::intern::deserialize_opt_str")]
+    pub subject: Option<Arc<str>>,
     pub from: SmallVec<[Address; 1]>,
     pub to: SmallVec<[Address; 1]>,
     pub cc: SmallVec<[Address; 1]>,
@@ -267,6 +276,13 @@ pub struct Envelope {
     pub flags: Flag,
     pub has_attachments: bool,
     pub tags: IndexSet<TagHash>,
+    /// Size of the message in bytes, if known.
+    #[serde(default)]
+    pub size: usize,
+    /// Score assigned by the user's scoring rules, `None` if the envelope has
+    /// not been scored yet.
+    #[serde(skip)]
+    pub score: Option<i64>,
 }
 
 impl std::fmt::Debug for Envelope {
@@ -310,6 +326,8 @@ impl Envelope {
             has_attachments: false,
             flags: Flag::default(),
             tags: IndexSet::new(),
+            size: 0,
+            score: None,
         }
     }
 
@@ -335,6 +353,7 @@ impl Envelope {
     }
 
     pub fn populate_headers(&mut self, mut bytes: &[u8]) -> Result<()> {
+        self.size = bytes.len();
         if bytes.starts_with(b"From ") {
             /* Attempt to recover if message includes the mbox From label as first line */
             if let Some(offset) = bytes.find(b"\n") {
@@ -349,6 +368,10 @@ impl Envelope {
                 return Err(Error::new(error_msg));
             }
         };
+        // Dates to use if the `Date` header is missing or invalid: the time the
+        // message was delivered or received by the last server.
+        let mut fallback_dates = vec![];
+        let mut delivery_report = false;
         for (name, value) in headers {
             match name {
                 HeaderName::TO => {
@@ -398,6 +421,17 @@ impl Envelope {
                         self.set_date(value);
                     }
                 }
+                HeaderName::DELIVERY_DATE => {
+                    if let Ok(d) = parser::dates::rfc5322_date(value) {
+                        fallback_dates.insert(0, d);
+                    }
+                }
+                HeaderName::RECEIVED if fallback_dates.is_empty() => {
+                    if let Some(d) = received::Hop::parse(&String::from_utf8_lossy(value)).timestamp
+                    {
+                        fallback_dates.push(d);
+                    }
+                }
                 HeaderName::CONTENT_TYPE => match parser::attachments::content_type(value) {
                     Ok((_, (ct, cst, ref params)))
                         if ct.eq_ignore_ascii_case(b"multipart")
@@ -423,6 +457,16 @@ impl Envelope {
                             );
                         }
                     }
+                    Ok((_, (ct, cst, ref params)))
+                        if ct.eq_ignore_ascii_case(b"multipart")
+                            && cst.eq_ignore_ascii_case(b"report") =>
+                    {
+                        delivery_report = params.iter().any(|(n, v)| {
+                            n.eq_ignore_ascii_case(b"report-type")
+                                && (v.eq_ignore_ascii_case(b"delivery-status")
+                                    || v.eq_ignore_ascii_case(b"global-delivery-status"))
+                        });
+                    }
                     _ => {}
                 },
                 _ => {}
@@ -437,6 +481,15 @@ impl Envelope {
                     .unwrap_or_else(|_| String::from_utf8_lossy(value).into()),
             );
         }
+        if delivery_report && self.in_reply_to.is_none() && self.references.is_none() {
+            // Thread bounces with the message they report on.
+            if let Some(original_message_id) =
+                dsn::DeliveryStatusNotification::from_attachment(&self.body_bytes(bytes))
+                    .and_then(|report| report.original_message_id)
+            {
+                self.push_in_reply_to(original_message_id);
+            }
+        }
         /*
          * https://tools.ietf.org/html/rfc5322#section-3.6.4
          *
@@ -454,6 +507,8 @@ impl Envelope {
         }
         if let Ok(d) = parser::dates::rfc5322_date(self.date.as_bytes()) {
             self.set_datetime(d);
+        } else if let Some(d) = fallback_dates.first() {
+            self.set_datetime(*d);
         }
         if self.message_id.raw().is_empty() {
             let hash = self.hash;
@@ -478,6 +533,14 @@ impl Envelope {
         &self.date
     }
 
+    /// Offset from UTC in seconds of the time zone the `Date` header was
+    /// written in, if it is known.
+    pub fn date_zone_offset(&self) -> Option<i64> {
+        parser::dates::rfc5322_date_with_zone(self.date.as_bytes())
+            .ok()
+            .and_then(|(_, zone)| zone)
+    }
+
     pub fn from(&self) -> &[Address] {
         self.from.as_slice()
     }
@@ -563,8 +626,8 @@ impl Envelope {
 
     pub fn subject(&self) -> Cow<'_, str> {
         match self.subject {
-            Some(ref s) => Cow::from(s),
-            _ => Cow::from(String::new()),
+            Some(ref s) => Cow::Borrowed(s),
+            _ => Cow::Borrowed(""),
         }
     }
 
@@ -649,7 +712,7 @@ impl Envelope {
             new_val.pop();
         }
 
-        self.subject = Some(new_val);
+        self.subject = Some(intern(&new_val));
         self
     }
 
@@ -761,6 +824,74 @@ impl Envelope {
         self.has_attachments
     }
 
+    pub fn set_size(&mut self, new_val: usize) -> &mut Self {
+        self.size = new_val;
+        self
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn set_score(&mut self, new_val: Option<i64>) -> &mut Self {
+        self.score = new_val;
+        self
+    }
+
+    pub fn score(&self) -> i64 {
+        self.score.unwrap_or(0)
+    }
+
+    pub fn is_scored(&self) -> bool {
+        self.score.is_some()
+    }
+
+    /// Number of recipients in `To`, `Cc` and `Bcc`.
+    pub fn recipient_count(&self) -> usize {
+        self.to.len() + self.cc.len() + self.bcc.len()
+    }
+
+    /// Lowercase address spec of the first `From` address, used for sorting.
+    fn sender_sort_key(&self) -> String {
+        self.from
+            .first()
+            .map(|a| a.get_email().to_lowercase())
+            .unwrap_or_default()
+    }
+
+    /// Lowercase domain of the first `From` address, used for sorting.
+    fn sender_domain_sort_key(&self) -> String {
+        self.from
+            .first()
+            .and_then(Address::get_fqdn)
+            .map(|d| d.to_lowercase())
+            .unwrap_or_default()
+    }
+
+    /// Compare with `other` by `field`, in ascending order.
+    ///
+    /// [`SortField::ThreadLength`] is not a property of a single envelope, so
+    /// all envelopes compare equal by it.
+    pub fn cmp_by_field(&self, other: &Self, field: SortField) -> std::cmp::Ordering {
+        match field {
+            SortField::Date => self.timestamp.cmp(&other.timestamp),
+            SortField::ThreadLength => std::cmp::Ordering::Equal,
+            SortField::Unseen => (!self.is_seen()).cmp(&!other.is_seen()),
+            SortField::Subject => self
+                .subject()
+                .split_graphemes()
+                .cmp(&other.subject().split_graphemes()),
+            SortField::Size => self.size.cmp(&other.size),
+            SortField::Attachments => self.has_attachments.cmp(&other.has_attachments),
+            SortField::From => self.sender_sort_key().cmp(&other.sender_sort_key()),
+            SortField::Domain => self
+                .sender_domain_sort_key()
+                .cmp(&other.sender_domain_sort_key()),
+            SortField::Recipients => self.recipient_count().cmp(&other.recipient_count()),
+            SortField::Score => self.score().cmp(&other.score()),
+        }
+    }
+
     pub fn tags(&self) -> &IndexSet<TagHash> {
         &self.tags
     }
@@ -769,6 +900,58 @@ impl Envelope {
         &mut self.tags
     }
 
+    /// Approximate number of heap bytes owned by this envelope.
+    ///
+    /// Interned values such as the subject and the mailbox addresses are
+    /// shared between envelopes and are not included; see
+    /// [`crate::utils::intern::stats`].
+    pub fn heap_size(&self) -> usize {
+        fn address_size(addr: &Address) -> usize {
+            match addr {
+                Address::Mailbox(_) => 0,
+                Address::Group(g) => {
+                    g.raw.capacity()
+                        + g.mailbox_list.capacity() * std::mem::size_of::<Address>()
+                        + g.mailbox_list.iter().map(address_size).sum::<usize>()
+                }
+            }
+        }
+
+        fn addresses_size(addrs: &[Address], spilled: bool) -> usize {
+            (if spilled {
+                addrs.len() * std::mem::size_of::<Address>()
+            } else {
+                0
+            }) + addrs.iter().map(address_size).sum::<usize>()
+        }
+
+        fn refs_size(refs: Option<&References>) -> usize {
+            refs.map(|r| {
+                r.refs().len() * std::mem::size_of::<MessageID>()
+                    + r.refs().iter().map(|m| m.0.capacity()).sum::<usize>()
+            })
+            .unwrap_or(0)
+        }
+
+        self.date.capacity()
+            + addresses_size(&self.from, self.from.spilled())
+            + addresses_size(&self.to, self.to.spilled())
+            + addresses_size(&self.cc, self.cc.spilled())
+            + self.bcc.capacity() * std::mem::size_of::<Address>()
+            + addresses_size(&self.bcc, false)
+            + refs_size(self.in_reply_to.as_ref())
+            + refs_size(self.references.as_ref())
+            + self.message_id.0.capacity()
+            + self.other_headers.capacity()
+                * (std::mem::size_of::<HeaderName>() + std::mem::size_of::<String>())
+            + self
+                .other_headers
+                .values()
+                .map(String::capacity)
+                .sum::<usize>()
+            + self.tags.capacity() * std::mem::size_of::<TagHash>()
+    }
+
     /// Returns `true` if `is_recipient` address is included in To:, Cc: or Bcc:
     /// headers.
     pub fn recipient_any(&self, is_recipient: &Address) -> bool {
@@ -25,9 +25,11 @@ use std::{
     collections::HashSet,
     convert::TryFrom,
     hash::{Hash, Hasher},
+    sync::Arc,
 };
 
 use super::*;
+use crate::utils::intern::intern_bytes;
 #[cfg(test)]
 mod tests;
 
@@ -43,7 +45,7 @@ pub struct GroupAddress {
  * Container for an address.
  *
  * ```text
- * >           raw: Vec<u8>
+ * >          raw: Arc<[u8]>
  * > ┌──────────┴────────────┐
  * > Name <address@domain.tld>
  * > └─┬┘  └──────────┬─────┘
@@ -52,7 +54,7 @@ pub struct GroupAddress {
  * >            address_spec
  *
  *
- * >           raw: Vec<u8>
+ * >          raw: Arc<[u8]>
  * > ┌──────────┴────────────────────┐
  * > "Name Name2" <address@domain.tld>
  * >  └─────┬──┘   └──────────┬─────┘
@@ -60,9 +62,13 @@ pub struct GroupAddress {
  * >                          │
  * >                    address_spec
  * ```
+ *
+ * `raw` is interned, so that the addresses of the same sender are stored
+ * once; see [`crate::utils::intern`].
  */
 pub struct MailboxAddress {
-    pub raw: Vec<u8>,
+    #[serde(deserialize_with = "crate::utils::intern::deserialize_bytes")]
+    pub raw: Arc<[u8]>,
     pub display_name: StrBuilder,
     pub address_spec: StrBuilder,
 }
@@ -112,7 +118,7 @@ impl Address {
     pub fn new(display_name: Option<String>, address: String) -> Self {
         Self::Mailbox(if let Some(d) = display_name {
             MailboxAddress {
-                raw: format!("{d} <{address}>").into_bytes(),
+                raw: intern_bytes(format!("{d} <{address}>").as_bytes()),
                 display_name: StrBuilder {
                     offset: 0,
                     length: d.len(),
@@ -124,7 +130,7 @@ impl Address {
             }
         } else {
             MailboxAddress {
-                raw: address.to_string().into_bytes(),
+                raw: intern_bytes(address.as_bytes()),
                 display_name: StrBuilder {
                     offset: 0,
                     length: 0,
@@ -159,11 +165,20 @@ impl Address {
 
     pub fn raw(&self) -> &[u8] {
         match self {
-            Self::Mailbox(m) => m.raw.as_slice(),
+            Self::Mailbox(m) => &m.raw,
             Self::Group(g) => g.raw.as_slice(),
         }
     }
 
+    /// The mailboxes of this address: the address itself if it is a mailbox,
+    /// or the members of a group, which may be empty.
+    pub fn mailboxes(&self) -> &[Self] {
+        match self {
+            Self::Mailbox(_) => std::slice::from_ref(self),
+            Self::Group(g) => &g.mailbox_list,
+        }
+    }
+
     /// Get the display name of this address.
     ///
     /// If it's a group, it's the name of the group. Otherwise it's the
@@ -681,7 +696,7 @@ macro_rules! make_address {
         let address = { $a };
         Address::Mailbox(if display_name.is_empty() {
             MailboxAddress {
-                raw: format!("{}", address).into_bytes(),
+                raw: $crate::utils::intern::intern_bytes(format!("{}", address).as_bytes()),
                 display_name: StrBuilder {
                     offset: 0,
                     length: 0,
@@ -693,7 +708,9 @@ macro_rules! make_address {
             }
         } else {
             MailboxAddress {
-                raw: format!("{} <{}>", display_name, address).into_bytes(),
+                raw: $crate::utils::intern::intern_bytes(
+                    format!("{} <{}>", display_name, address).as_bytes(),
+                ),
                 display_name: StrBuilder {
                     offset: 0,
                     length: display_name.len(),
@@ -46,3 +46,22 @@ fn test_email_address_message_id_comparisons() {
     assert_eq!(val, M_ID);
     assert_eq!(val, M_ID[1..][..M_LEN - 2]);
 }
+
+#[test]
+fn test_email_address_group_mailboxes() {
+    let (_, addrs) = parser::address::rfc2822address_list(
+        b"a@example.com, team: b@example.com, c@example.com;, undisclosed-recipients:;",
+    )
+    .unwrap();
+    assert_eq!(addrs.len(), 3);
+    assert_eq!(addrs[0].mailboxes(), &addrs[..1]);
+    assert_eq!(
+        addrs[1]
+            .mailboxes()
+            .iter()
+            .map(|a| a.get_email())
+            .collect::<Vec<_>>(),
+        vec!["b@example.com".to_string(), "c@example.com".to_string()]
+    );
+    assert!(addrs[2].mailboxes().is_empty());
+}
@@ -36,6 +36,10 @@ use crate::{
     BytesDisplay,
 };
 
+pub mod lazy;
+#[cfg(test)]
+mod tests;
+
 /// Type alias for function that takes an [`Attachment`] and appends a bytes
 /// representation in its second argument.
 pub type Filter<'a> = Box<dyn FnMut(&Attachment, &mut Vec<u8>) + 'a>;
@@ -0,0 +1,294 @@
+/*
+ * meli - attachments module
+ *
+ * Copyright 2024 Manos Pitsidianakis
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Lazy parsing of MIME structure.
+//!
+//! [`AttachmentBuilder::new`] copies every part of a message into its own
+//! buffer while parsing it, which is slow for messages with very large
+//! attachments. [`MimeStructure`] only parses the headers of each part and
+//! records where each part is located in the message; part bodies are not
+//! touched until they are requested.
+
+use super::{Attachment, AttachmentBuilder, DecodeOptions};
+use crate::{
+    email::{
+        address::StrBuilder,
+        attachment_types::{
+            ContentDisposition, ContentTransferEncoding, ContentType, MultipartType,
+        },
+        parser::{self, BytesExt},
+        HeaderName,
+    },
+    BytesDisplay,
+};
+
+/// Nesting depth after which multipart parts are not descended into.
+const MAX_DEPTH: usize = 64;
+
+/// A part of a [`MimeStructure`].
+///
+/// Multipart content types always have an empty `parts` field; their
+/// sub-parts are in [`MimePart::children`] instead.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MimePart {
+    pub content_type: ContentType,
+    pub content_transfer_encoding: ContentTransferEncoding,
+    pub content_disposition: ContentDisposition,
+    /// Location of the entire part, including its headers, in the message.
+    pub raw: StrBuilder,
+    /// Location of the part's body in the message.
+    pub body: StrBuilder,
+    pub children: Vec<MimePart>,
+}
+
+impl MimePart {
+    pub fn is_multipart(&self) -> bool {
+        matches!(self.content_type, ContentType::Multipart { .. })
+    }
+
+    /// Size of the encoded body in bytes.
+    pub fn size(&self) -> usize {
+        self.body.length
+    }
+
+    pub fn mime_type(&self) -> String {
+        self.content_type.to_string()
+    }
+
+    pub fn filename(&self) -> Option<String> {
+        Attachment {
+            content_type: self.content_type.clone(),
+            content_transfer_encoding: self.content_transfer_encoding.clone(),
+            content_disposition: self.content_disposition.clone(),
+            raw: vec![],
+            body: StrBuilder::default(),
+        }
+        .filename()
+    }
+
+    /// An [`Attachment`] with the headers of this part and the outlines of
+    /// its sub-parts, but without any contents.
+    ///
+    /// It can describe the part, e.g. its content type and filename, but not
+    /// decode it; use [`MimePart::attachment`] for that.
+    pub fn outline(&self) -> Attachment {
+        let mut content_type = self.content_type.clone();
+        if let ContentType::Multipart { ref mut parts, .. } = content_type {
+            *parts = self.children.iter().map(Self::outline).collect();
+        }
+        Attachment {
+            content_type,
+            content_transfer_encoding: self.content_transfer_encoding.clone(),
+            content_disposition: self.content_disposition.clone(),
+            raw: vec![],
+            body: StrBuilder::default(),
+        }
+    }
+
+    /// Parse this part of `bytes`, the message it was parsed from, fully
+    /// into an [`Attachment`], copying its contents.
+    pub fn attachment(&self, bytes: &[u8]) -> Attachment {
+        AttachmentBuilder::new(self.raw.display_bytes(bytes)).build()
+    }
+
+    fn parse(bytes: &[u8], raw: StrBuilder, depth: usize) -> Self {
+        let input = &bytes[raw.offset..raw.offset + raw.length];
+        let mut ret = Self {
+            raw,
+            body: raw,
+            content_transfer_encoding: ContentTransferEncoding::_7Bit,
+            ..Self::default()
+        };
+        let (headers, body) = match parser::attachments::attachment(input) {
+            Ok((_, v)) => v,
+            Err(err) => {
+                if !input.trim().is_empty() {
+                    log::debug!("error in parsing attachment: {}", err);
+                }
+                return ret;
+            }
+        };
+        ret.body = StrBuilder {
+            offset: raw.offset + input.len() - body.len(),
+            length: body.len(),
+        };
+        let mut boundary = None;
+        for (name, value) in headers {
+            if name == HeaderName::CONTENT_TYPE {
+                boundary = ret.set_content_type_from_bytes(value);
+            } else if name == HeaderName::CONTENT_TRANSFER_ENCODING {
+                ret.content_transfer_encoding = ContentTransferEncoding::from(value);
+            } else if name == HeaderName::CONTENT_DISPOSITION {
+                ret.content_disposition = ContentDisposition::from(value);
+            }
+        }
+        if let Some(boundary) = boundary {
+            if depth < MAX_DEPTH && !body.is_empty() {
+                if let Ok((_, parts)) = parser::attachments::parts(body, &boundary) {
+                    // `parts` returns subslices of `body`, so their offsets can be
+                    // recovered from their addresses.
+                    ret.children = parts
+                        .into_iter()
+                        .map(|p| StrBuilder {
+                            offset: ret.body.offset
+                                + (p.as_ptr() as usize - body.as_ptr() as usize),
+                            length: p.len(),
+                        })
+                        .map(|p| Self::parse(bytes, p, depth + 1))
+                        .collect();
+                }
+            }
+        }
+        ret
+    }
+
+    /// Set content type, returning the boundary if it's a multipart type.
+    ///
+    /// Unlike [`AttachmentBuilder::set_content_type_from_bytes`], multipart
+    /// bodies are not parsed.
+    fn set_content_type_from_bytes(&mut self, value: &[u8]) -> Option<Vec<u8>> {
+        if let Ok((_, (ct, cst, params))) = parser::attachments::content_type(value) {
+            if ct.eq_ignore_ascii_case(b"multipart") {
+                let boundary = params
+                    .iter()
+                    .find(|(n, _)| n.eq_ignore_ascii_case(b"boundary"))
+                    .map(|(_, v)| v.to_vec())?;
+                self.content_type = ContentType::Multipart {
+                    boundary: boundary.clone(),
+                    kind: MultipartType::from(cst),
+                    parameters: params
+                        .into_iter()
+                        .map(|(kb, vb)| (kb.to_vec(), vb.to_vec()))
+                        .collect::<Vec<(Vec<u8>, Vec<u8>)>>(),
+                    parts: vec![],
+                };
+                return Some(boundary);
+            }
+        }
+        let mut builder = AttachmentBuilder::default();
+        builder.set_content_type_from_bytes(value);
+        self.content_type = builder.content_type;
+        None
+    }
+}
+
+/// Like the `Display` implementation of [`Attachment`], except that
+/// `message/rfc822` parts are not parsed to show their subject.
+impl std::fmt::Display for MimePart {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let size = BytesDisplay(self.raw.length);
+        match self.content_type {
+            ContentType::Multipart { .. } => write!(
+                f,
+                "{} attachment with {} parts",
+                self.mime_type(),
+                self.children.len()
+            ),
+            ContentType::MessageRfc822 => write!(f, "[message/rfc822] {size}"),
+            ContentType::PGPSignature => write!(f, "pgp signature [{}]", self.mime_type()),
+            ContentType::CMSSignature => write!(f, "S/MIME signature [{}]", self.mime_type()),
+            _ => {
+                if let Some(name) = self.filename() {
+                    write!(f, "\"{}\", [{}] {}", name, self.mime_type(), size)
+                } else if matches!(self.content_type, ContentType::Text { .. }) {
+                    write!(f, "Text attachment [{}] {}", self.mime_type(), size)
+                } else {
+                    write!(f, "Data attachment [{}] {}", self.mime_type(), size)
+                }
+            }
+        }
+    }
+}
+
+/// The MIME part tree of a message, parsed without copying any part bodies.
+#[derive(Clone, Debug)]
+pub struct MimeStructure<B: AsRef<[u8]>> {
+    bytes: B,
+    root: MimePart,
+}
+
+impl<B: AsRef<[u8]>> MimeStructure<B> {
+    pub fn new(bytes: B) -> Self {
+        let root = MimePart::parse(
+            bytes.as_ref(),
+            StrBuilder {
+                offset: 0,
+                length: bytes.as_ref().len(),
+            },
+            0,
+        );
+        Self { bytes, root }
+    }
+
+    pub fn root(&self) -> &MimePart {
+        &self.root
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        self.bytes.as_ref()
+    }
+
+    /// Return the part found by following `path` from the root, where each
+    /// element is an index into [`MimePart::children`].
+    pub fn get(&self, path: &[usize]) -> Option<&MimePart> {
+        path.iter()
+            .try_fold(&self.root, |part, &i| part.children.get(i))
+    }
+
+    /// All parts in depth-first order, along with their path from the root.
+    pub fn parts(&self) -> Vec<(Vec<usize>, &MimePart)> {
+        fn rec<'a>(
+            part: &'a MimePart,
+            path: &mut Vec<usize>,
+            ret: &mut Vec<(Vec<usize>, &'a MimePart)>,
+        ) {
+            ret.push((path.clone(), part));
+            for (i, child) in part.children.iter().enumerate() {
+                path.push(i);
+                rec(child, path, ret);
+                path.pop();
+            }
+        }
+        let mut ret = vec![];
+        rec(&self.root, &mut vec![], &mut ret);
+        ret
+    }
+
+    /// The entire `part`, including its headers.
+    pub fn raw(&self, part: &MimePart) -> &[u8] {
+        part.raw.display_bytes(self.bytes())
+    }
+
+    /// The encoded body of `part`.
+    pub fn body(&self, part: &MimePart) -> &[u8] {
+        part.body.display_bytes(self.bytes())
+    }
+
+    /// Parse `part` fully into an [`Attachment`], copying its contents.
+    pub fn attachment(&self, part: &MimePart) -> Attachment {
+        part.attachment(self.bytes())
+    }
+
+    /// Decode the body of `part`.
+    pub fn decode(&self, part: &MimePart, options: DecodeOptions<'_>) -> Vec<u8> {
+        self.attachment(part).decode(options)
+    }
+}
@@ -0,0 +1,105 @@
+//
+// melib
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+use super::{lazy::MimeStructure, *};
+
+const MIXED: &str = "Subject: test\r
+MIME-Version: 1.0\r
+Content-Type: multipart/mixed; boundary=\"outer\"\r
+\r
+--outer\r
+Content-Type: multipart/alternative; boundary=\"inner\"\r
+\r
+--inner\r
+Content-Type: text/plain; charset=utf-8\r
+\r
+plain text\r
+--inner\r
+Content-Type: text/html; charset=utf-8\r
+\r
+<p>html text</p>\r
+--inner--\r
+\r
+--outer\r
+Content-Type: application/octet-stream; name=\"data.bin\"\r
+Content-Disposition: attachment; filename=\"data.bin\"\r
+Content-Transfer-Encoding: base64\r
+\r
+aGVsbG8gd29ybGQ=\r
+--outer--\r
+";
+
+#[test]
+fn test_attachments_lazy_mime_structure() {
+    let structure = MimeStructure::new(MIXED.as_bytes());
+    let root = structure.root();
+    assert!(root.is_multipart());
+    assert_eq!(root.children.len(), 2);
+    let paths = structure
+        .parts()
+        .into_iter()
+        .map(|(path, part)| (path, part.mime_type()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        vec![
+            (vec![], "multipart/mixed".to_string()),
+            (vec![0], "multipart/alternative".to_string()),
+            (vec![0, 0], "text/plain".to_string()),
+            (vec![0, 1], "text/html".to_string()),
+            (vec![1], "application/octet-stream".to_string()),
+        ]
+    );
+
+    let plain = structure.get(&[0, 0]).unwrap();
+    assert_eq!(structure.body(plain), b"plain text");
+    assert_eq!(
+        String::from_utf8(structure.decode(plain, Default::default())).unwrap(),
+        "plain text"
+    );
+
+    let data = structure.get(&[1]).unwrap();
+    assert_eq!(data.filename().as_deref(), Some("data.bin"));
+    assert_eq!(structure.body(data), b"aGVsbG8gd29ybGQ=");
+    assert_eq!(structure.decode(data, Default::default()), b"hello world");
+    assert!(structure.get(&[1, 0]).is_none());
+
+    // Parts parsed lazily agree with eagerly parsed ones.
+    let eager = AttachmentBuilder::new(MIXED.as_bytes()).build();
+    let ContentType::Multipart { ref parts, .. } = eager.content_type else {
+        panic!("expected multipart, got {:?}", eager.content_type);
+    };
+    assert_eq!(structure.attachment(data), parts[1]);
+    assert_eq!(structure.raw(data), parts[1].raw());
+
+    // Outlines describe parts without their contents.
+    let outline = root.outline();
+    assert!(outline.raw().is_empty());
+    assert_eq!(outline.to_string(), eager.to_string());
+    assert_eq!(
+        outline.content_type.parts().unwrap()[1]
+            .filename()
+            .as_deref(),
+        Some("data.bin")
+    );
+    assert_eq!(data.to_string(), parts[1].to_string());
+}
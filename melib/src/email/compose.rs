@@ -40,6 +40,7 @@ use crate::{
     utils::{datetime, shellexpand::ShellExpandTrait, xdg::query_mime_info},
 };
 
+pub mod lint;
 pub mod mime;
 pub mod random;
 #[cfg(test)]
@@ -47,6 +48,68 @@ mod tests;
 
 use super::parser;
 
+/// Maximum length of a line in bytes, excluding the `CRLF`, allowed by RFC
+/// 5322.
+pub const MAX_LINE_LENGTH: usize = 998;
+
+/// How a text part is encoded when a [`Draft`] is finalised.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextEncoding {
+    pub content_transfer_encoding: ContentTransferEncoding,
+    pub charset: Charset,
+    /// How many lines are longer than [`MAX_LINE_LENGTH`].
+    pub long_lines: usize,
+    /// Length in bytes of the longest line.
+    pub longest_line: usize,
+}
+
+impl TextEncoding {
+    /// Choose the encoding of `text`: `7bit` for ASCII text, `8bit` for UTF-8
+    /// text and `quoted-printable` if any line is too long to be sent as is.
+    pub fn new(text: &str) -> Self {
+        let (long_lines, longest_line) =
+            text.lines()
+                .map(str::len)
+                .fold((0, 0), |(long_lines, longest_line), len| {
+                    (
+                        long_lines + usize::from(len > MAX_LINE_LENGTH),
+                        longest_line.max(len),
+                    )
+                });
+        let charset = if text.is_ascii() {
+            Charset::Ascii
+        } else {
+            Charset::UTF8
+        };
+        let content_transfer_encoding = if long_lines > 0 {
+            ContentTransferEncoding::QuotedPrintable
+        } else if charset == Charset::Ascii {
+            ContentTransferEncoding::_7Bit
+        } else {
+            ContentTransferEncoding::_8Bit
+        };
+        Self {
+            content_transfer_encoding,
+            charset,
+            long_lines,
+            longest_line,
+        }
+    }
+
+    /// Append `text` encoded with this encoding to `ret`, with `CRLF` line
+    /// endings.
+    fn push_encoded(&self, ret: &mut String, text: &str) {
+        if self.content_transfer_encoding == ContentTransferEncoding::QuotedPrintable {
+            ret.push_str(&mime::encode_quoted_printable(text));
+            return;
+        }
+        for line in text.lines() {
+            ret.push_str(line);
+            ret.push_str("\r\n");
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Draft {
     pub headers: HeaderMap,
@@ -285,6 +348,41 @@ impl Draft {
         self
     }
 
+    /// The encoding the body will be sent with.
+    pub fn body_encoding(&self) -> TextEncoding {
+        TextEncoding::new(&self.body)
+    }
+
+    /// Estimate the size in bytes of the finalised message, including the
+    /// `base64` encoding of binary attachments.
+    pub fn estimated_size(&self) -> usize {
+        // Content headers and boundary of each MIME part.
+        const PART_OVERHEAD: usize = 200;
+
+        let headers = self
+            .headers
+            .iter()
+            .map(|(k, v)| k.as_str().len() + v.len() + 4)
+            .sum::<usize>();
+        let body = self.body.len() + self.body.lines().count();
+        let attachments = self
+            .attachments
+            .iter()
+            .map(|a| {
+                let raw = a.raw().len();
+                PART_OVERHEAD
+                    + if a.raw().is_ascii() {
+                        raw
+                    } else {
+                        // 4 characters per 3 bytes, in lines of 76 characters.
+                        let encoded = raw.div_ceil(3) * 4;
+                        encoded + encoded.div_ceil(76) * 2
+                    }
+            })
+            .sum::<usize>();
+        headers + PART_OVERHEAD + body + attachments
+    }
+
     pub fn to_edit_string(&self) -> String {
         let mut ret = String::new();
 
@@ -354,23 +452,29 @@ impl Draft {
         }
 
         if self.attachments.is_empty() {
+            let encoding = self.body_encoding();
             if !has_ctype {
                 let content_type: ContentType = Default::default();
-                let content_transfer_encoding: ContentTransferEncoding =
-                    ContentTransferEncoding::_8Bit;
                 ret.push_str(&format!(
-                    "Content-Type: {content_type}; charset=\"utf-8\"\r\n"
+                    "Content-Type: {content_type}; charset=\"{}\"\r\n",
+                    encoding.charset
+                ));
+            }
+            if !has_cte {
+                ret.push_str(&format!(
+                    "Content-Transfer-Encoding: {}\r\n",
+                    encoding.content_transfer_encoding
                 ));
-                if !has_cte {
-                    ret.push_str(&format!(
-                        "Content-Transfer-Encoding: {content_transfer_encoding}\r\n"
-                    ));
-                }
             }
             ret.push_str("\r\n");
-            for line in self.body.lines() {
-                ret.push_str(line);
-                ret.push_str("\r\n");
+            if has_cte {
+                // The user chose the encoding, so send the body as is.
+                for line in self.body.lines() {
+                    ret.push_str(line);
+                    ret.push_str("\r\n");
+                }
+            } else {
+                encoding.push_encoded(&mut ret, &self.body);
             }
         } else if self.body.is_empty() && self.attachments.len() == 1 {
             let attachment = std::mem::take(&mut self.attachments).remove(0);
@@ -446,11 +550,20 @@ fn print_attachment(ret: &mut String, a: AttachmentBuilder) {
             charset: Charset::UTF8,
             parameters: ref v,
         } if v.is_empty() => {
-            ret.push_str("Content-Type: text/plain; charset=\"utf-8\"\r\n\r\n");
-            for line in String::from_utf8_lossy(a.raw()).lines() {
-                ret.push_str(line);
-                ret.push_str("\r\n");
+            let text = String::from_utf8_lossy(a.raw());
+            let encoding = TextEncoding::new(&text);
+            ret.push_str(&format!(
+                "Content-Type: text/plain; charset=\"{}\"\r\n",
+                encoding.charset
+            ));
+            if encoding.content_transfer_encoding != ContentTransferEncoding::_7Bit {
+                ret.push_str(&format!(
+                    "Content-Transfer-Encoding: {}\r\n",
+                    encoding.content_transfer_encoding
+                ));
             }
+            ret.push_str("\r\n");
+            encoding.push_encoded(ret, &text);
         }
         ContentType::Text { .. } => {
             let mut pop_crlf = false;
@@ -0,0 +1,300 @@
+/*
+ * meli - melib crate.
+ *
+ * Copyright 2024 Manos Pitsidianakis
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Validation of built messages before they are sent.
+//!
+//! [`lint`] walks the MIME tree of a finalised message and reports problems
+//! that other clients or servers might choke on. Some of them can be fixed on
+//! the [`Draft`] with [`Draft::fix_lints`].
+
+use std::fmt;
+
+use super::*;
+use crate::email::parser::{self, BytesExt};
+
+/// Multipart nesting deeper than this is reported.
+pub const MAX_NESTING: usize = 6;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LintKind {
+    /// A `multipart/alternative` part has no `text/plain` alternative.
+    MissingTextAlternative,
+    /// A boundary delimiter can be confused with the boundary of an enclosing
+    /// multipart.
+    BoundaryCollision,
+    /// Multipart parts are nested more than [`MAX_NESTING`] levels deep.
+    DeepNesting,
+    /// A header contains control characters or is not a valid header line.
+    InvalidHeaderCharacters,
+    /// A part contains 8-bit data or lines longer than [`MAX_LINE_LENGTH`]
+    /// that its `Content-Transfer-Encoding` does not allow.
+    UnencodedEightBit,
+}
+
+impl LintKind {
+    /// Whether [`Draft::fix_lints`] can fix this kind of problem.
+    pub const fn is_fixable(self) -> bool {
+        matches!(
+            self,
+            Self::InvalidHeaderCharacters | Self::UnencodedEightBit
+        )
+    }
+}
+
+/// A problem found in a built message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lint {
+    pub kind: LintKind,
+    /// Position of the MIME part, e.g. `1.2` for the second part of the first
+    /// part, or empty for the message itself.
+    pub part: String,
+    pub description: String,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.part.is_empty() {
+            write!(f, "{}", self.description)?;
+        } else {
+            write!(f, "part {}: {}", self.part, self.description)?;
+        }
+        if self.kind.is_fixable() {
+            write!(f, " (fixable)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Check the MIME tree of the message `raw`.
+pub fn lint(raw: &[u8]) -> Vec<Lint> {
+    let mut ret = vec![];
+    lint_part(raw, "", &mut vec![], &mut ret);
+    ret
+}
+
+fn lint_part(raw: &[u8], part: &str, boundaries: &mut Vec<Vec<u8>>, ret: &mut Vec<Lint>) {
+    let mut push = |kind: LintKind, description: String| {
+        ret.push(Lint {
+            kind,
+            part: part.to_string(),
+            description,
+        })
+    };
+    // Parts without headers start with the empty line.
+    let header_end = if raw.starts_with(b"\n") || raw.starts_with(b"\r\n") {
+        Some(0)
+    } else {
+        raw.find(b"\r\n\r\n")
+            .map(|i| i + 2)
+            .or_else(|| raw.find(b"\n\n").map(|i| i + 1))
+    }
+    .unwrap_or(raw.len());
+    for line in raw[..header_end].split(|b| *b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        let is_continuation = matches!(line[0], b' ' | b'\t');
+        let valid_name = is_continuation
+            || line.find(b":").is_some_and(|colon| {
+                colon > 0 && line[..colon].iter().all(|b| (33..=126).contains(b))
+            });
+        if !valid_name || line.iter().any(|b| b.is_ascii_control() && *b != b'\t') {
+            push(
+                LintKind::InvalidHeaderCharacters,
+                format!(
+                    "invalid header line `{}`",
+                    String::from_utf8_lossy(line).escape_debug()
+                ),
+            );
+        }
+    }
+
+    let Ok((_, (headers, body))) = parser::attachments::attachment(raw) else {
+        push(
+            LintKind::InvalidHeaderCharacters,
+            "headers could not be parsed".to_string(),
+        );
+        return;
+    };
+    let header = |name: HeaderName| {
+        headers
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.trim())
+    };
+    let content_transfer_encoding = header(HeaderName::CONTENT_TRANSFER_ENCODING)
+        .map(ContentTransferEncoding::from)
+        .unwrap_or(ContentTransferEncoding::_7Bit);
+    let content_type = header(HeaderName::CONTENT_TYPE)
+        .and_then(|v| parser::attachments::content_type(v).ok())
+        .map(|(_, (kind, subtype, params))| {
+            let boundary = params
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(b"boundary"))
+                .map(|(_, v)| v.to_vec());
+            (
+                kind.to_ascii_lowercase(),
+                subtype.to_ascii_lowercase(),
+                boundary,
+            )
+        });
+
+    match content_type {
+        Some((kind, subtype, Some(boundary))) if kind == b"multipart" => {
+            if boundaries.len() + 1 > MAX_NESTING {
+                push(
+                    LintKind::DeepNesting,
+                    format!("multipart parts are nested more than {MAX_NESTING} levels deep"),
+                );
+            }
+            // A boundary must not be a prefix of an enclosing one or the other way
+            // around (RFC 2046 section 5.1.2).
+            if boundaries
+                .iter()
+                .any(|b| b.starts_with(&boundary) || boundary.starts_with(b))
+            {
+                push(
+                    LintKind::BoundaryCollision,
+                    format!(
+                        "boundary `{}` collides with the boundary of an enclosing part",
+                        String::from_utf8_lossy(&boundary)
+                    ),
+                );
+            }
+            let parts = parser::attachments::parts(body, &boundary)
+                .map(|(_, parts)| parts)
+                .unwrap_or_default();
+            if subtype == b"alternative"
+                && !parts.iter().any(|p| {
+                    parser::attachments::attachment(p)
+                        .ok()
+                        .and_then(|(_, (headers, _))| {
+                            headers
+                                .into_iter()
+                                .find(|(n, _)| *n == HeaderName::CONTENT_TYPE)
+                        })
+                        // Parts are text/plain by default.
+                        .is_none_or(|(_, v)| {
+                            v.trim().to_ascii_lowercase().starts_with(b"text/plain")
+                        })
+                })
+            {
+                push(
+                    LintKind::MissingTextAlternative,
+                    "multipart/alternative has no text/plain alternative".to_string(),
+                );
+            }
+            boundaries.push(boundary);
+            for (i, p) in parts.iter().enumerate() {
+                let sub_part = if part.is_empty() {
+                    (i + 1).to_string()
+                } else {
+                    format!("{part}.{}", i + 1)
+                };
+                lint_part(p, &sub_part, boundaries, ret);
+            }
+            boundaries.pop();
+        }
+        _ => {
+            if let Some(b) = boundaries.iter().find(|b| {
+                let mut delimiter = b"--".to_vec();
+                delimiter.extend_from_slice(b);
+                body.find(&delimiter).is_some()
+            }) {
+                push(
+                    LintKind::BoundaryCollision,
+                    format!(
+                        "content contains the boundary `{}` of an enclosing part",
+                        String::from_utf8_lossy(b)
+                    ),
+                );
+            }
+            let is_7bit = content_transfer_encoding == ContentTransferEncoding::_7Bit;
+            let is_8bit = content_transfer_encoding == ContentTransferEncoding::_8Bit;
+            if (is_7bit && !body.is_ascii()) || ((is_7bit || is_8bit) && body.contains(&0)) {
+                push(
+                    LintKind::UnencodedEightBit,
+                    format!(
+                        "8-bit data with Content-Transfer-Encoding {content_transfer_encoding}"
+                    ),
+                );
+            } else if (is_7bit || is_8bit)
+                && body
+                    .split(|b| *b == b'\n')
+                    .any(|l| l.len() > MAX_LINE_LENGTH + 1)
+            {
+                push(
+                    LintKind::UnencodedEightBit,
+                    format!(
+                        "lines longer than {MAX_LINE_LENGTH} bytes with Content-Transfer-Encoding \
+                         {content_transfer_encoding}"
+                    ),
+                );
+            }
+        }
+    }
+}
+
+impl Draft {
+    /// Build the message and check its MIME tree.
+    pub fn lint(&self) -> Result<Vec<Lint>> {
+        Ok(lint(self.clone().finalise()?.as_bytes()))
+    }
+
+    /// Fix the problems that [`LintKind::is_fixable`]: remove control
+    /// characters from header values and label text attachments that contain
+    /// 8-bit data as `8bit`.
+    pub fn fix_lints(&mut self) {
+        let invalid_names = self
+            .headers
+            .keys()
+            .filter(|k| {
+                k.as_str().is_empty()
+                    || !k
+                        .as_str()
+                        .bytes()
+                        .all(|b| (33..=126).contains(&b) && b != b':')
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for name in invalid_names {
+            self.headers.remove(name);
+        }
+        for value in self.headers.values_mut() {
+            if value.chars().any(|c| c.is_ascii_control() && c != '\t') {
+                *value = value
+                    .chars()
+                    .map(|c| if matches!(c, '\r' | '\n') { ' ' } else { c })
+                    .filter(|c| !c.is_ascii_control() || *c == '\t')
+                    .collect();
+            }
+        }
+        for a in self.attachments.iter_mut() {
+            if matches!(a.content_type, ContentType::Text { .. })
+                && a.content_transfer_encoding == ContentTransferEncoding::_7Bit
+                && !a.raw().is_ascii()
+            {
+                a.set_content_transfer_encoding(ContentTransferEncoding::_8Bit);
+            }
+        }
+    }
+}
@@ -88,3 +88,38 @@ pub fn encode_header(value: &str) -> String {
     }
     ret
 }
+
+/// Encode `text` as `quoted-printable` (RFC 2045 section 6.7).
+///
+/// Every line of the result, including the last one, ends with `CRLF` and
+/// long lines are split with soft line breaks so that no encoded line is
+/// longer than 76 characters.
+pub fn encode_quoted_printable(text: &str) -> String {
+    const MAX_ENCODED_LINE: usize = 76;
+
+    let mut ret = String::with_capacity(text.len() + text.len() / 8);
+    for line in text.lines() {
+        let bytes = line.as_bytes();
+        let mut line_len = 0;
+        for (i, &b) in bytes.iter().enumerate() {
+            // Trailing whitespace is removed by some transports, so encode it.
+            let is_trailing_whitespace = matches!(b, b' ' | b'\t') && i + 1 == bytes.len();
+            let literal = !is_trailing_whitespace
+                && (matches!(b, b' ' | b'\t') || (b.is_ascii_graphic() && b != b'='));
+            let width = if literal { 1 } else { 3 };
+            // Leave room for the `=` of a soft line break.
+            if line_len + width > MAX_ENCODED_LINE - 1 {
+                ret.push_str("=\r\n");
+                line_len = 0;
+            }
+            if literal {
+                ret.push(b as char);
+            } else {
+                ret.push_str(&format!("={b:02X}"));
+            }
+            line_len += width;
+        }
+        ret.push_str("\r\n");
+    }
+    ret
+}
@@ -172,6 +172,7 @@ This is a MIME formatted message with attachments. Use a MIME-compliant client t
          properly.\x0d
 --bzz_bzz__bzz__xxxxxxxxxxxxxxxxxxxxxxxxxxxxx\x0d
 Content-Type: text/plain; charset=\"utf-8\"\x0d
+Content-Transfer-Encoding: 8bit\x0d
 \x0d
 αδφαφσαφασ\x0d
 --bzz_bzz__bzz__xxxxxxxxxxxxxxxxxxxxxxxxxxxxx\x0d
@@ -350,3 +351,156 @@ fn test_email_compose_draft_from_str() {
     );
     assert_eq!(err.kind, ErrorKind::ValueError);
 }
+
+#[test]
+fn test_email_compose_body_encoding() {
+    let encoding = TextEncoding::new("ascii only\nsecond line\n");
+    assert_eq!(
+        encoding.content_transfer_encoding,
+        ContentTransferEncoding::_7Bit
+    );
+    assert_eq!(encoding.charset, Charset::Ascii);
+    assert_eq!(encoding.long_lines, 0);
+    assert_eq!(encoding.longest_line, "second line".len());
+
+    let encoding = TextEncoding::new(GREEK_FOOBAR);
+    assert_eq!(
+        encoding.content_transfer_encoding,
+        ContentTransferEncoding::_8Bit
+    );
+    assert_eq!(encoding.charset, Charset::UTF8);
+
+    let long_line = "a".repeat(MAX_LINE_LENGTH + 1);
+    let encoding = TextEncoding::new(&format!("short\n{long_line}\n"));
+    assert_eq!(
+        encoding.content_transfer_encoding,
+        ContentTransferEncoding::QuotedPrintable
+    );
+    assert_eq!(encoding.long_lines, 1);
+    assert_eq!(encoding.longest_line, MAX_LINE_LENGTH + 1);
+
+    let mut draft = Draft::default();
+    draft.set_body(format!("short\n{long_line}\n"));
+    let bytes = draft.finalise().unwrap();
+    assert!(bytes.contains("Content-Type: text/plain; charset=\"us-ascii\"\r\n"));
+    assert!(bytes.contains("Content-Transfer-Encoding: quoted-printable\r\n"));
+    let (_, body) = bytes.split_once("\r\n\r\n").unwrap();
+    assert!(body.lines().all(|l| l.len() <= 76));
+    assert_eq!(
+        body.replace("=\r\n", ""),
+        format!("short\r\n{long_line}\r\n")
+    );
+}
+
+#[test]
+fn test_email_compose_quoted_printable() {
+    assert_eq!(
+        mime::encode_quoted_printable("a=b \ntrailing \nκ"),
+        "a=3Db=20\r\ntrailing=20\r\n=CE=BA\r\n"
+    );
+}
+
+#[test]
+fn test_email_compose_estimated_size() {
+    let mut draft = Draft::default();
+    draft.set_body("hello".to_string());
+    let without_attachment = draft.estimated_size();
+    let mut attachment = AttachmentBuilder::new(b"");
+    attachment.set_raw(vec![0xff; 3000]);
+    draft.attachments_mut().push(attachment);
+    // 3000 bytes are 4000 base64 characters.
+    assert!(draft.estimated_size() >= without_attachment + 4000);
+    assert!(draft.estimated_size() < without_attachment + 4500);
+}
+
+#[test]
+fn test_email_compose_lint() {
+    use lint::{lint, LintKind, MAX_NESTING};
+
+    let kinds = |raw: &str| {
+        lint(raw.as_bytes())
+            .into_iter()
+            .map(|l| l.kind)
+            .collect::<Vec<_>>()
+    };
+
+    let mut draft = Draft::default();
+    draft.set_body(GREEK_FOOBAR.to_string());
+    assert_eq!(draft.lint().unwrap(), vec![]);
+
+    assert_eq!(
+        kinds(
+            "Content-Type: multipart/alternative; boundary=\"b\"\r\n\r\n--b\r\nContent-Type: \
+             text/html\r\n\r\n<p>hi</p>\r\n--b--\r\n"
+        ),
+        vec![LintKind::MissingTextAlternative]
+    );
+    assert_eq!(
+        kinds(
+            "Content-Type: multipart/alternative; boundary=\"b\"\r\n\r\n--b\r\n\r\nhi\r\n--b\r\nContent-Type: \
+             text/html\r\n\r\n<p>hi</p>\r\n--b--\r\n"
+        ),
+        vec![]
+    );
+    assert_eq!(
+        kinds(
+            "Content-Type: multipart/mixed; boundary=\"outer\"\r\n\r\n--outer\r\nContent-Type: \
+             multipart/mixed; boundary=\"out\"\r\n\r\n--out\r\n\r\nhi\r\n--out--\r\n--outer--\r\n"
+        ),
+        vec![LintKind::BoundaryCollision]
+    );
+    let invalid = kinds("Subject: a\x07b\r\nNo colon\r\n\r\nhi\r\n");
+    assert!(invalid.len() >= 2, "{:?}", invalid);
+    assert!(
+        invalid
+            .iter()
+            .all(|k| *k == LintKind::InvalidHeaderCharacters),
+        "{:?}",
+        invalid
+    );
+    assert_eq!(
+        kinds(&format!("Subject: hi\r\n\r\n{GREEK_FOOBAR}\r\n")),
+        vec![LintKind::UnencodedEightBit]
+    );
+    assert_eq!(
+        kinds(&format!(
+            "Content-Transfer-Encoding: 8bit\r\n\r\n{GREEK_FOOBAR}\r\n{}\r\n",
+            "a".repeat(MAX_LINE_LENGTH + 1)
+        )),
+        vec![LintKind::UnencodedEightBit]
+    );
+
+    let mut nested = "\r\nhi\r\n".to_string();
+    for i in 0..=MAX_NESTING {
+        nested = format!(
+            "Content-Type: multipart/mixed; boundary=\"b{i}\"\r\n\r\n--b{i}\r\n{nested}\r\n--b{i}--\r\n"
+        );
+    }
+    assert_eq!(kinds(&nested), vec![LintKind::DeepNesting]);
+}
+
+#[test]
+fn test_email_compose_fix_lints() {
+    let mut draft = Draft::default();
+    draft.set_body("hello".to_string());
+    draft
+        .set_header(HeaderName::SUBJECT, "a\r\nBcc: b\x07".to_string())
+        .set_header(HeaderName::TO, "c@example.com".to_string());
+    let mut attachment = AttachmentBuilder::new(b"");
+    attachment
+        .set_raw(GREEK_FOOBAR.as_bytes().to_vec())
+        .set_content_type(ContentType::Text {
+            kind: Text::Html,
+            parameters: vec![],
+            charset: Charset::UTF8,
+        })
+        .set_content_transfer_encoding(ContentTransferEncoding::_7Bit);
+    draft.attachments_mut().push(attachment);
+
+    let lints = draft.lint().unwrap();
+    assert!(!lints.is_empty());
+    assert!(lints.iter().all(|l| l.kind.is_fixable()), "{:?}", lints);
+    draft.fix_lints();
+    assert_eq!(draft.lint().unwrap(), vec![]);
+    assert_eq!(&draft.headers[HeaderName::SUBJECT], "a  Bcc: b");
+}
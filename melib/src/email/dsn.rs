@@ -0,0 +1,343 @@
+/*
+ * meli - melib crate.
+ *
+ * Copyright 2024 Manos Pitsidianakis
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Parsing of delivery status notifications (`RFC3464`), the reports that
+//! mail servers send back when a message could not be delivered.
+//!
+//! A report is a `multipart/report` message with a `message/delivery-status`
+//! part, which holds a group of fields about the message followed by a group
+//! of fields for each recipient, and usually the returned message or its
+//! headers.
+
+use std::fmt;
+
+use super::{
+    address::MessageID,
+    attachment_types::{ContentType, Text},
+    attachments::{Attachment, DecodeOptions},
+    parser, HeaderName,
+};
+
+/// The delivery status of a recipient of the reported message.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RecipientStatus {
+    /// The address of the `Final-Recipient`, or of the `Original-Recipient`
+    /// if the former is missing.
+    pub recipient: String,
+    /// What the reporting server did, e.g. `failed` or `delayed`.
+    pub action: String,
+    /// The status code, e.g. `5.1.1`.
+    pub status: Option<String>,
+    /// The reply of the remote server, if any.
+    pub diagnostic: Option<String>,
+}
+
+impl RecipientStatus {
+    /// Whether the message could not be delivered to this recipient.
+    pub fn is_failure(&self) -> bool {
+        self.action.eq_ignore_ascii_case("failed")
+            || self.status.as_deref().is_some_and(|s| s.starts_with('5'))
+    }
+}
+
+/// Displays the status, e.g. `user@example.com could not be delivered (5.1.1
+/// Bad destination mailbox address): smtp; 550 User unknown`.
+impl fmt::Display for RecipientStatus {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.recipient)?;
+        match self.action.to_ascii_lowercase().as_str() {
+            "failed" => write!(fmt, " could not be delivered")?,
+            "delayed" => write!(fmt, " has not been delivered yet")?,
+            "delivered" => write!(fmt, " was delivered")?,
+            "relayed" => write!(fmt, " was relayed")?,
+            "expanded" => write!(fmt, " was delivered and forwarded")?,
+            "" => {}
+            other => write!(fmt, " {other}")?,
+        }
+        match self.status.as_deref() {
+            Some(status) => match status_description(status) {
+                Some(description) => write!(fmt, " ({status} {description})")?,
+                None => write!(fmt, " ({status})")?,
+            },
+            None => {}
+        }
+        if let Some(ref diagnostic) = self.diagnostic {
+            write!(fmt, ": {diagnostic}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A parsed delivery status notification.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DeliveryStatusNotification {
+    /// The server that generated the report.
+    pub reporting_mta: Option<String>,
+    /// The `Message-ID` of the reported message, if it was returned with the
+    /// report.
+    pub original_message_id: Option<MessageID>,
+    pub recipients: Vec<RecipientStatus>,
+}
+
+impl DeliveryStatusNotification {
+    /// Parse the report in the body of a message, if it has a
+    /// `message/delivery-status` part.
+    pub fn from_attachment(body: &Attachment) -> Option<Self> {
+        let mut status = None;
+        let mut original_message_id = None;
+        for part in body.attachments() {
+            match part.content_type() {
+                ContentType::Other { tag, .. }
+                    if tag.eq_ignore_ascii_case(b"message/delivery-status")
+                        || tag.eq_ignore_ascii_case(b"message/global-delivery-status") =>
+                {
+                    if status.is_none() {
+                        status = Some(part.decode(DecodeOptions::default()));
+                    }
+                }
+                ContentType::MessageRfc822
+                | ContentType::Text {
+                    kind: Text::Rfc822, ..
+                } => {
+                    original_message_id = original_message_id
+                        .or_else(|| message_id(&part.decode(DecodeOptions::default())));
+                }
+                ContentType::Text {
+                    kind: Text::Other { tag },
+                    ..
+                } if tag.eq_ignore_ascii_case(b"rfc822-headers") => {
+                    original_message_id = original_message_id
+                        .or_else(|| message_id(&part.decode(DecodeOptions::default())));
+                }
+                _ => {}
+            }
+        }
+        let status = String::from_utf8_lossy(&status?).into_owned();
+        let mut groups = field_groups(&status).into_iter();
+        let reporting_mta = groups
+            .next()?
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Reporting-MTA"))
+            .map(|(_, value)| strip_type(&value).to_string());
+        let recipients = groups
+            .filter_map(|group| {
+                let field = |name: &str| {
+                    group
+                        .iter()
+                        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                        .map(|(_, value)| value.as_str())
+                };
+                let recipient = field("Final-Recipient").or_else(|| field("Original-Recipient"))?;
+                Some(RecipientStatus {
+                    recipient: strip_type(recipient).to_string(),
+                    action: field("Action").unwrap_or_default().to_string(),
+                    status: field("Status").map(str::to_string),
+                    diagnostic: field("Diagnostic-Code").map(|d| strip_type(d).to_string()),
+                })
+            })
+            .collect::<Vec<RecipientStatus>>();
+        Some(Self {
+            reporting_mta,
+            original_message_id,
+            recipients,
+        })
+    }
+
+    /// The recipients the message could not be delivered to.
+    pub fn failed(&self) -> impl Iterator<Item = &RecipientStatus> {
+        self.recipients.iter().filter(|r| r.is_failure())
+    }
+}
+
+/// A description of an enhanced status code (`RFC3463`), e.g. `Mailbox full`
+/// for `4.2.2`.
+pub fn status_description(status: &str) -> Option<&'static str> {
+    let (class, detail) = status.split_once('.')?;
+    Some(match detail {
+        "1.1" => "Bad destination mailbox address",
+        "1.2" => "Bad destination system address",
+        "1.3" => "Bad destination mailbox address syntax",
+        "1.6" => "Destination mailbox has moved",
+        "1.10" => "Recipient address has null MX",
+        "2.1" => "Mailbox disabled",
+        "2.2" => "Mailbox full",
+        "2.3" => "Message length exceeds administrative limit",
+        "3.4" => "Message too big for system",
+        "4.4" => "Unable to route",
+        "4.7" => "Delivery time expired",
+        "7.1" => "Delivery not authorized, message refused",
+        _ => match class {
+            "2" => "Success",
+            "4" => "Persistent transient failure",
+            "5" => "Permanent failure",
+            _ => return None,
+        },
+    })
+}
+
+/// Split the body of a `message/delivery-status` part into its groups of
+/// fields, unfolding continuation lines.
+fn field_groups(body: &str) -> Vec<Vec<(String, String)>> {
+    let mut groups = vec![];
+    let mut group: Vec<(String, String)> = vec![];
+    for line in body.lines() {
+        if line.trim().is_empty() {
+            if !group.is_empty() {
+                groups.push(std::mem::take(&mut group));
+            }
+        } else if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = group.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            group.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    if !group.is_empty() {
+        groups.push(group);
+    }
+    groups
+}
+
+/// Strip the type of a typed field value, e.g. `rfc822; user@example.com`.
+fn strip_type(value: &str) -> &str {
+    value
+        .split_once(';')
+        .map(|(_, value)| value)
+        .unwrap_or(value)
+        .trim()
+}
+
+/// The `Message-ID` of a message or a header block.
+fn message_id(bytes: &[u8]) -> Option<MessageID> {
+    // The last header of a header block may lack its line terminator.
+    let mut bytes = bytes.to_vec();
+    if !bytes.ends_with(b"\n") {
+        bytes.extend_from_slice(b"\r\n");
+    }
+    let (_, headers) = parser::headers::headers(&bytes).ok()?;
+    let (_, value) = headers
+        .into_iter()
+        .find(|(name, _)| *name == HeaderName::MESSAGE_ID)?;
+    parser::address::msg_id(value.trim_ascii())
+        .ok()
+        .map(|(_, id)| id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::Envelope;
+
+    const BOUNCE: &[u8] = b"From: Mail Delivery System <MAILER-DAEMON@mx.example.com>\r\n\
+To: sender@example.org\r\n\
+Subject: Undelivered Mail Returned to Sender\r\n\
+Message-ID: <bounce-1@mx.example.com>\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/report; report-type=delivery-status;\r\n\
+\tboundary=\"BOUNDARY\"\r\n\
+\r\n\
+--BOUNDARY\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+I'm sorry to have to inform you that your message could not be delivered.\r\n\
+\r\n\
+--BOUNDARY\r\n\
+Content-Type: message/delivery-status\r\n\
+\r\n\
+Reporting-MTA: dns; mx.example.com\r\n\
+Arrival-Date: Mon, 15 Jan 2024 10:00:00 +0000\r\n\
+\r\n\
+Final-Recipient: rfc822; nobody@example.com\r\n\
+Original-Recipient: rfc822; nobody@example.com\r\n\
+Action: failed\r\n\
+Status: 5.1.1\r\n\
+Diagnostic-Code: smtp; 550 5.1.1 <nobody@example.com>:\r\n\
+\tRecipient address rejected: User unknown\r\n\
+\r\n\
+Final-Recipient: rfc822; busy@example.com\r\n\
+Action: delayed\r\n\
+Status: 4.2.2\r\n\
+\r\n\
+--BOUNDARY\r\n\
+Content-Type: text/rfc822-headers\r\n\
+\r\n\
+From: sender@example.org\r\n\
+To: nobody@example.com, busy@example.com\r\n\
+Subject: Hello\r\n\
+Message-ID: <original-1@example.org>\r\n\
+\r\n\
+--BOUNDARY--\r\n";
+
+    #[test]
+    fn test_dsn_parse() {
+        let envelope = Envelope::from_bytes(BOUNCE, None).unwrap();
+        let report =
+            DeliveryStatusNotification::from_attachment(&envelope.body_bytes(BOUNCE)).unwrap();
+        assert_eq!(report.reporting_mta.as_deref(), Some("mx.example.com"));
+        assert_eq!(
+            report.original_message_id.as_ref().map(|id| id.to_string()),
+            Some("original-1@example.org".to_string())
+        );
+        assert_eq!(report.recipients.len(), 2);
+        let failed = report.failed().collect::<Vec<_>>();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].recipient, "nobody@example.com");
+        assert_eq!(failed[0].status.as_deref(), Some("5.1.1"));
+        assert_eq!(
+            failed[0].to_string(),
+            "nobody@example.com could not be delivered (5.1.1 Bad destination mailbox address): \
+             550 5.1.1 <nobody@example.com>: Recipient address rejected: User unknown"
+        );
+        assert!(!report.recipients[1].is_failure());
+        assert_eq!(
+            report.recipients[1].to_string(),
+            "busy@example.com has not been delivered yet (4.2.2 Mailbox full)"
+        );
+
+        // The bounce is threaded as a reply to the returned message.
+        assert_eq!(
+            envelope.in_reply_to().map(|r| r.refs()[0].to_string()),
+            Some("original-1@example.org".to_string())
+        );
+
+        let plain = b"Subject: Hello\r\nMessage-ID: <a@example.org>\r\n\r\nbody";
+        let envelope = Envelope::from_bytes(plain, None).unwrap();
+        assert_eq!(
+            DeliveryStatusNotification::from_attachment(&envelope.body_bytes(plain)),
+            None
+        );
+        assert_eq!(envelope.in_reply_to(), None);
+    }
+
+    #[test]
+    fn test_dsn_status_description() {
+        assert_eq!(status_description("5.2.2"), Some("Mailbox full"));
+        assert_eq!(status_description("5.9.9"), Some("Permanent failure"));
+        assert_eq!(
+            status_description("4.9.9"),
+            Some("Persistent transient failure")
+        );
+        assert_eq!(status_description("9.9.9"), None);
+        assert_eq!(status_description("garbage"), None);
+    }
+}
@@ -0,0 +1,235 @@
+/*
+ * meli - melib crate.
+ *
+ * Copyright 2024 Manos Pitsidianakis
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Detection of notification e-mail sent by software forges.
+//!
+//! GitHub marks its notifications with an `X-GitHub-Reason` header and GitLab
+//! with `X-GitLab-*` headers. The repository and the issue or pull request
+//! number are read from these headers and from the `Message-ID`, whose domain
+//! is the host of the forge.
+
+use std::fmt;
+
+use super::Envelope;
+
+/// A software forge that sends notification e-mail.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Forge {
+    GitHub,
+    GitLab,
+}
+
+impl fmt::Display for Forge {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::GitHub => write!(fmt, "GitHub"),
+            Self::GitLab => write!(fmt, "GitLab"),
+        }
+    }
+}
+
+/// The kind of item a notification is about.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ForgeItem {
+    Issue,
+    /// A pull request, or a merge request in GitLab.
+    PullRequest,
+}
+
+/// The structured summary of a forge notification.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ForgeNotification {
+    pub forge: Forge,
+    /// `owner/repository` in GitHub, the project path in GitLab.
+    pub repository: String,
+    /// The issue or pull request and its number, if the notification is about
+    /// one.
+    pub item: Option<(ForgeItem, u64)>,
+    /// Why the notification was sent, e.g. `mention` or `review_requested`.
+    pub reason: Option<String>,
+    /// The web page of the item, or of the repository if the notification is
+    /// not about an item.
+    pub url: Option<String>,
+}
+
+impl ForgeNotification {
+    /// Detect whether `envelope` is a forge notification.
+    pub fn detect(envelope: &Envelope) -> Option<Self> {
+        let headers = envelope.other_headers();
+        let message_id = envelope
+            .message_id()
+            .as_str()
+            .trim_matches(|c| c == '<' || c == '>');
+        let (local_part, host) = message_id.rsplit_once('@').unwrap_or((message_id, ""));
+        let reason = |name: &str| {
+            headers
+                .get(name)
+                .map(str::trim)
+                .filter(|r| !r.is_empty())
+                .map(str::to_string)
+        };
+        if headers.get("X-GitHub-Reason").is_some() {
+            // Message-IDs look like `owner/repo/pull/123/c456@github.com`.
+            let mut segments = local_part.split('/');
+            let (repository, item) = match (segments.next(), segments.next()) {
+                (Some(owner), Some(repo)) if !owner.is_empty() && !repo.is_empty() => {
+                    let item = match (segments.next(), segments.next().map(str::parse::<u64>)) {
+                        (Some("pull"), Some(Ok(n))) => Some((ForgeItem::PullRequest, n)),
+                        (Some("issues"), Some(Ok(n))) => Some((ForgeItem::Issue, n)),
+                        _ => None,
+                    };
+                    (format!("{owner}/{repo}"), item)
+                }
+                _ => (subject_repository(&envelope.subject())?, None),
+            };
+            let host = if host.is_empty() { "github.com" } else { host };
+            let url = match item {
+                Some((ForgeItem::PullRequest, n)) => {
+                    format!("https://{host}/{repository}/pull/{n}")
+                }
+                Some((ForgeItem::Issue, n)) => format!("https://{host}/{repository}/issues/{n}"),
+                None => format!("https://{host}/{repository}"),
+            };
+            return Some(Self {
+                forge: Forge::GitHub,
+                repository,
+                item,
+                reason: reason("X-GitHub-Reason"),
+                url: Some(url),
+            });
+        }
+        let repository = headers.get("X-GitLab-Project-Path")?.trim().to_string();
+        if repository.is_empty() {
+            return None;
+        }
+        let iid = |name: &str| headers.get(name).and_then(|n| n.trim().parse::<u64>().ok());
+        let item = iid("X-GitLab-MergeRequest-IID")
+            .map(|n| (ForgeItem::PullRequest, n))
+            .or_else(|| iid("X-GitLab-Issue-IID").map(|n| (ForgeItem::Issue, n)));
+        let url = (!host.is_empty()).then(|| match item {
+            Some((ForgeItem::PullRequest, n)) => {
+                format!("https://{host}/{repository}/-/merge_requests/{n}")
+            }
+            Some((ForgeItem::Issue, n)) => format!("https://{host}/{repository}/-/issues/{n}"),
+            None => format!("https://{host}/{repository}"),
+        });
+        Some(Self {
+            forge: Forge::GitLab,
+            repository,
+            item,
+            reason: reason("X-GitLab-NotificationReason"),
+            url,
+        })
+    }
+}
+
+/// Displays the summary, e.g. `owner/repo#123 (mention)`. GitLab merge
+/// requests are written as `group/project!45`.
+impl fmt::Display for ForgeNotification {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.repository)?;
+        match (self.forge, self.item) {
+            (Forge::GitLab, Some((ForgeItem::PullRequest, n))) => write!(fmt, "!{n}")?,
+            (_, Some((_, n))) => write!(fmt, "#{n}")?,
+            (_, None) => {}
+        }
+        if let Some(ref reason) = self.reason {
+            write!(fmt, " ({reason})")?;
+        }
+        Ok(())
+    }
+}
+
+/// The `owner/repo` of subjects such as `Re: [owner/repo] Title (PR #1)`.
+fn subject_repository(subject: &str) -> Option<String> {
+    let start = subject.find('[')?;
+    let end = start + subject[start..].find(']')?;
+    let repository = &subject[start + 1..end];
+    (repository.contains('/') && !repository.contains(char::is_whitespace))
+        .then(|| repository.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::EnvelopeHash;
+
+    #[test]
+    fn test_forge_github() {
+        let raw = b"From: Some One <notifications@github.com>\r\nSubject: Re: [meli/meli] \
+                    Fix sorting (PR #123)\r\nMessage-ID: \
+                    <meli/meli/pull/123/c456789@github.com>\r\nX-GitHub-Reason: \
+                    review_requested\r\n\r\nbody";
+        let envelope = Envelope::from_bytes(raw, None).unwrap();
+        let notification = ForgeNotification::detect(&envelope).unwrap();
+        assert_eq!(notification.forge, Forge::GitHub);
+        assert_eq!(notification.repository, "meli/meli");
+        assert_eq!(notification.item, Some((ForgeItem::PullRequest, 123)));
+        assert_eq!(notification.reason.as_deref(), Some("review_requested"));
+        assert_eq!(
+            notification.url.as_deref(),
+            Some("https://github.com/meli/meli/pull/123")
+        );
+        assert_eq!(notification.to_string(), "meli/meli#123 (review_requested)");
+
+        let raw = b"Subject: [meli/meli] Run failed: CI - master\r\nMessage-ID: \
+                    <0123abcd@github.com>\r\nX-GitHub-Reason: ci_activity\r\n\r\nbody";
+        let envelope = Envelope::from_bytes(raw, None).unwrap();
+        let notification = ForgeNotification::detect(&envelope).unwrap();
+        assert_eq!(notification.repository, "meli/meli");
+        assert_eq!(notification.item, None);
+        assert_eq!(
+            notification.url.as_deref(),
+            Some("https://github.com/meli/meli")
+        );
+        assert_eq!(notification.to_string(), "meli/meli (ci_activity)");
+
+        let mut envelope = Envelope::new(EnvelopeHash::default());
+        envelope.set_message_id(b"<meli/meli/issues/7@github.com>");
+        assert_eq!(ForgeNotification::detect(&envelope), None);
+    }
+
+    #[test]
+    fn test_forge_gitlab() {
+        let raw = b"Subject: Re: project | Fix sorting (!45)\r\nMessage-ID: \
+                    <note_1234@gitlab.example.com>\r\nX-GitLab-Project-Path: \
+                    group/project\r\nX-GitLab-MergeRequest-IID: 45\r\n\
+                    X-GitLab-NotificationReason: mentioned\r\n\r\nbody";
+        let envelope = Envelope::from_bytes(raw, None).unwrap();
+        let notification = ForgeNotification::detect(&envelope).unwrap();
+        assert_eq!(notification.forge, Forge::GitLab);
+        assert_eq!(notification.item, Some((ForgeItem::PullRequest, 45)));
+        assert_eq!(
+            notification.url.as_deref(),
+            Some("https://gitlab.example.com/group/project/-/merge_requests/45")
+        );
+        assert_eq!(notification.to_string(), "group/project!45 (mentioned)");
+
+        let raw = b"Subject: project | Crash (#3)\r\nMessage-ID: \
+                    <issue_99@gitlab.example.com>\r\nX-GitLab-Project-Path: \
+                    group/project\r\nX-GitLab-Issue-IID: 3\r\n\r\nbody";
+        let envelope = Envelope::from_bytes(raw, None).unwrap();
+        let notification = ForgeNotification::detect(&envelope).unwrap();
+        assert_eq!(notification.item, Some((ForgeItem::Issue, 3)));
+        assert_eq!(notification.reason, None);
+        assert_eq!(notification.to_string(), "group/project#3");
+    }
+}
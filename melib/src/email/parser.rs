@@ -414,17 +414,24 @@ pub fn mail(input: &[u8]) -> Result<(Vec<(HeaderName, &[u8])>, &[u8])> {
 
 pub mod dates {
     //! Date values in headers.
+    use nom::bytes::complete::{tag_no_case, take_while_m_n};
+
     use super::{generic::*, *};
     use crate::utils::datetime::UnixTimestamp;
 
-    fn take_n_digits(n: usize) -> impl Fn(&[u8]) -> IResult<&[u8], &[u8]> {
+    /// Offset of a time zone from UTC in seconds.
+    ///
+    /// `None` means the zone of the date is unknown: `-0000` and unknown
+    /// obsolete zones.
+    pub type ZoneOffset = Option<i64>;
+
+    /// Between `min` and `max` digits, as a number.
+    fn number(min: usize, max: usize) -> impl Fn(&[u8]) -> IResult<&[u8], i64> {
         move |input: &[u8]| {
-            let (input, ret) = take(n)(input)?;
-            if !ret.iter().all(|c| is_digit(*c)) {
-                return Err(nom::Err::Error(
-                    (input, "take_n_digits(): not digits").into(),
-                ));
-            }
+            let (input, digits) = take_while_m_n(min, max, is_digit)(input)?;
+            let ret = digits
+                .iter()
+                .fold(0_i64, |acc, d| acc * 10 + i64::from(d - b'0'));
             Ok((input, ret))
         }
     }
@@ -463,212 +470,223 @@ pub mod dates {
     /// have been used in Internet messages.  Any such time zone whose
     /// meaning is not known SHOULD be considered equivalent to "-0000"
     /// unless there is out-of-band information confirming their meaning.
-    fn obs_zone(input: &[u8]) -> IResult<&[u8], (&[u8], &[u8])> {
-        alt((
-            map(tag("UT"), |_| (&b"+"[..], &b"0000"[..])),
-            map(tag("GMT"), |_| (&b"+"[..], &b"0000"[..])),
-            map(tag("EDT"), |_| (&b"-"[..], &b"0400"[..])),
-            map(tag("EST"), |_| (&b"-"[..], &b"0500"[..])),
-            map(tag("CDT"), |_| (&b"-"[..], &b"0500"[..])),
-            map(tag("CST"), |_| (&b"-"[..], &b"0600"[..])),
-            map(tag("MDT"), |_| (&b"-"[..], &b"0600"[..])),
-            map(tag("MST"), |_| (&b"-"[..], &b"0700"[..])),
-            map(tag("PDT"), |_| (&b"-"[..], &b"0700"[..])),
-            map(tag("PST"), |_| (&b"-"[..], &b"0800"[..])),
-            map(take_while1(is_alphabetic), |_| (&b"-"[..], &b"0000"[..])),
-        ))(input)
+    fn obs_zone(input: &[u8]) -> IResult<&[u8], ZoneOffset> {
+        let (input, name) = take_while1(is_alphabetic)(input)?;
+        let hours = match name.to_ascii_uppercase().as_slice() {
+            b"UT" | b"UTC" | b"GMT" => 0,
+            b"EDT" => -4,
+            b"EST" | b"CDT" => -5,
+            b"CST" | b"MDT" => -6,
+            b"MST" | b"PDT" => -7,
+            b"PST" => -8,
+            _ => return Ok((input, None)),
+        };
+        Ok((input, Some(hours * 60 * 60)))
     }
 
     /// ```text
     /// zone            =   (FWS ( "+" / "-" ) 4DIGIT) / obs-zone
     /// ```
-    fn zone(input: &[u8]) -> IResult<&[u8], (&[u8], &[u8])> {
+    ///
+    /// `-0000` means the zone is unknown.
+    fn zone(input: &[u8]) -> IResult<&[u8], ZoneOffset> {
         alt((
             |input| {
                 let (input, sign) = alt((tag("+"), tag("-")))(input)?;
-                let (input, zone) = take_n_digits(4)(input)?;
-                Ok((input, (sign, zone)))
+                let (input, hours) = number(2, 2)(input)?;
+                let (input, minutes) = number(2, 2)(input)?;
+                if sign == b"-" && hours == 0 && minutes == 0 {
+                    return Ok((input, None));
+                }
+                let offset = hours * 60 * 60 + minutes * 60;
+                Ok((input, Some(if sign == b"-" { -offset } else { offset })))
             },
             obs_zone,
         ))(input)
     }
 
     /// ```text
-    /// date-time       =   [ day-of-week "," ] date time [CFWS]
-    /// date            =   day month year
-    /// time            =   time-of-day zone
+    /// day-of-week     =   ([FWS] day-name) / obs-day-of-week
+    /// day-name        =   "Mon" / "Tue" / "Wed" / "Thu" /
+    ///                    "Fri" / "Sat" / "Sun"
+    /// ```
+    ///
+    /// Names are matched case insensitively and full names, e.g.
+    /// `Thursday`, are accepted as well.
+    fn day_of_week(input: &[u8]) -> IResult<&[u8], ()> {
+        let (input, _) = alt((
+            tag_no_case("Mon"),
+            tag_no_case("Tue"),
+            tag_no_case("Wed"),
+            tag_no_case("Thu"),
+            tag_no_case("Fri"),
+            tag_no_case("Sat"),
+            tag_no_case("Sun"),
+        ))(input)?;
+        let (input, _) = take_while(is_alphabetic)(input)?;
+        Ok((input, ()))
+    }
+
+    /// ```text
+    /// month           =   "Jan" / "Feb" / "Mar" / "Apr" /
+    ///                    "May" / "Jun" / "Jul" / "Aug" /
+    ///                    "Sep" / "Oct" / "Nov" / "Dec"
+    /// ```
+    ///
+    /// Returns the number of the month, starting from 1. Names are matched
+    /// case insensitively and full names, e.g. `February`, are accepted as
+    /// well.
+    fn month(input: &[u8]) -> IResult<&[u8], i64> {
+        const MONTHS: [&str; 12] = [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ];
+        let (rest, name) = take_while1(is_alphabetic)(input)?;
+        let Some(month) = MONTHS
+            .iter()
+            .position(|m| name.len() >= 3 && name[..3].eq_ignore_ascii_case(m.as_bytes()))
+        else {
+            return Err(nom::Err::Error((input, "month(): invalid month").into()));
+        };
+        Ok((rest, month as i64 + 1))
+    }
+
+    /// ```text
+    /// year            =   (FWS 4*DIGIT FWS) / obs-year
+    /// obs-year        =   [CFWS] 2*DIGIT [CFWS]
+    /// ```
+    ///
+    /// Two digit years are mapped to 2000-2049 if less than 50 and to
+    /// 1950-1999 otherwise, and three digit years are added to 1900, as
+    /// section 4.3 of RFC5322 specifies.
+    fn year(input: &[u8]) -> IResult<&[u8], i64> {
+        let (rest, digits) = take_while_m_n(2, 4, is_digit)(input)?;
+        let (_, year) = number(2, 4)(digits)?;
+        let year = match digits.len() {
+            2 if year < 50 => 2000 + year,
+            2 | 3 => 1900 + year,
+            _ => year,
+        };
+        Ok((rest, year))
+    }
+
+    /// ```text
     /// time-of-day     =   hour ":" minute [ ":" second ]
     /// hour            =   2DIGIT / obs-hour
     /// minute          =   2DIGIT / obs-minute
     /// second          =   2DIGIT / obs-second
+    /// obs-hour        =   [CFWS] 2DIGIT [CFWS]
     /// ```
-    fn date_time(input: &[u8]) -> IResult<&[u8], UnixTimestamp> {
-        let orig_input = input;
-        let mut accum: SmallVec<[u8; 32]> = SmallVec::new();
-        let (input, day_of_week) = opt(terminated(day_of_week, tag(",")))(input)?;
-        let (input, day) = day(input)?;
-        let (input, month) = month(input)?;
-        let (input, year) = year(input)?;
-        let (input, hour) = take_n_digits(2)(input)?;
-        let (input, _) = tag(":")(input)?;
-        let (input, minute) = take_n_digits(2)(input)?;
-        let (input, second) = opt(preceded(tag(":"), take_n_digits(2)))(input)?;
-        let (input, _) = fws(input)?;
-        let (input, (sign, zone)) = zone(input)?;
-        let (input, _) = opt(cfws)(input)?;
-        if let Some(day_of_week) = day_of_week {
-            accum.extend_from_slice(&day_of_week);
-            accum.extend_from_slice(b", ");
-        }
-        accum.extend_from_slice(day);
-        accum.extend_from_slice(b" ");
-        accum.extend_from_slice(month);
-        accum.extend_from_slice(b" ");
-        accum.extend_from_slice(year);
-        accum.extend_from_slice(b" ");
-        accum.extend_from_slice(hour);
-        accum.extend_from_slice(b":");
-        accum.extend_from_slice(minute);
-        if let Some(second) = second {
-            accum.extend_from_slice(b":");
-            accum.extend_from_slice(second);
-        }
-        accum.extend_from_slice(b" ");
-        accum.extend_from_slice(sign);
-        accum.extend_from_slice(zone);
-        match crate::utils::datetime::rfc822_to_timestamp(accum.to_vec()) {
-            Ok(t) => Ok((input, t)),
-            Err(_err) => Err(nom::Err::Error(
-                (
-                    orig_input,
-                    "date_time(): could not convert date from rfc822",
-                )
-                    .into(),
-            )),
+    fn time_of_day(input: &[u8]) -> IResult<&[u8], (i64, i64, i64)> {
+        fn colon(input: &[u8]) -> IResult<&[u8], ()> {
+            let (input, _) = opt(cfws)(input)?;
+            let (input, _) = tag(":")(input)?;
+            let (input, _) = opt(cfws)(input)?;
+            Ok((input, ()))
+        }
+        let (input, hour) = number(1, 2)(input)?;
+        let (input, _) = colon(input)?;
+        let (input, minute) = number(2, 2)(input)?;
+        let (input, second) = opt(preceded(colon, number(2, 2)))(input)?;
+        Ok((input, (hour, minute, second.unwrap_or(0))))
+    }
+
+    /// Number of days since 1970-01-01 of the given date of the proleptic
+    /// Gregorian calendar.
+    fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = (if year >= 0 { year } else { year - 399 }) / 400;
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    /// Convert a date to a Unix timestamp. Unknown zones are treated as UTC
+    /// and dates before the Unix epoch are clamped to it.
+    fn to_timestamp<'a>(
+        input: &'a [u8],
+        (year, month, day): (i64, i64, i64),
+        (hour, minute, second): (i64, i64, i64),
+        zone: ZoneOffset,
+    ) -> IResult<&'a [u8], UnixTimestamp> {
+        if !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+            return Err(nom::Err::Error(
+                (input, "to_timestamp(): date out of range").into(),
+            ));
         }
+        let timestamp = days_from_civil(year, month, day) * 24 * 60 * 60
+            + hour * 60 * 60
+            + minute * 60
+            + second
+            - zone.unwrap_or(0);
+        Ok((input, UnixTimestamp::try_from(timestamp).unwrap_or(0)))
     }
 
-    /// e.g Wed Sep  9 00:27:54 2020
-    ///
     /// ```text
-    /// day-of-week month day time year
     /// date-time       =   [ day-of-week "," ] date time [CFWS]
     /// date            =   day month year
+    /// day             =   ([FWS] 1*2DIGIT FWS) / obs-day
     /// time            =   time-of-day zone
-    /// time-of-day     =   hour ":" minute [ ":" second ]
-    /// hour            =   2DIGIT / obs-hour
-    /// minute          =   2DIGIT / obs-minute
-    /// second          =   2DIGIT / obs-second
     /// ```
-    pub fn mbox_date_time(input: &[u8]) -> IResult<&[u8], UnixTimestamp> {
-        let orig_input = input;
-        let mut accum: SmallVec<[u8; 32]> = SmallVec::new();
+    ///
+    /// The obsolete syntax is accepted as well: comments and folding white
+    /// space between all tokens, a missing comma after the day of the week,
+    /// two and three digit years and a missing zone, which is treated as
+    /// unknown.
+    fn date_time(input: &[u8]) -> IResult<&[u8], (UnixTimestamp, ZoneOffset)> {
+        let (input, _) = opt(cfws)(input)?;
+        let (input, _) = opt(terminated(day_of_week, pair(opt(cfws), opt(tag(",")))))(input)?;
         let (input, _) = opt(cfws)(input)?;
-        let (input, day_of_week) = day_of_week(input)?;
+        let (input, day) = number(1, 2)(input)?;
         let (input, _) = opt(cfws)(input)?;
         let (input, month) = month(input)?;
         let (input, _) = opt(cfws)(input)?;
-        let (input, day) = day(input)?;
+        let (input, year) = year(input)?;
+        let (input, _) = opt(cfws)(input)?;
+        let (input, time) = time_of_day(input)?;
         let (input, _) = opt(cfws)(input)?;
-        let (input, hour) = take_n_digits(2)(input)?;
-        let (input, _) = tag(":")(input)?;
-        let (input, minute) = take_n_digits(2)(input)?;
-        let (input, second) = opt(preceded(tag(":"), take_n_digits(2)))(input)?;
-        let (input, _) = fws(input)?;
         let (input, zone) = opt(zone)(input)?;
         let (input, _) = opt(cfws)(input)?;
-        let (input, year) = year(input)?;
-        accum.extend_from_slice(&day_of_week);
-        accum.extend_from_slice(b", ");
-        accum.extend_from_slice(day);
-        accum.extend_from_slice(b" ");
-        accum.extend_from_slice(month);
-        accum.extend_from_slice(b" ");
-        accum.extend_from_slice(year);
-        accum.extend_from_slice(b" ");
-        accum.extend_from_slice(hour);
-        accum.extend_from_slice(b":");
-        accum.extend_from_slice(minute);
-        if let Some(second) = second {
-            accum.extend_from_slice(b":");
-            accum.extend_from_slice(second);
-        }
-        if let Some((sign, zone)) = zone {
-            accum.extend_from_slice(b" ");
-            accum.extend_from_slice(sign);
-            accum.extend_from_slice(zone);
-        }
-        match crate::utils::datetime::rfc822_to_timestamp(accum.to_vec()) {
-            Ok(t) => Ok((input, t)),
-            Err(_err) => Err(nom::Err::Error(
-                (
-                    orig_input,
-                    "mbox_date_time(): could not convert date from rfc822",
-                )
-                    .into(),
-            )),
-        }
-    }
-
-    /// ```text
-    /// day-of-week     =   ([FWS] day-name) / obs-day-of-week
-    /// day-name        =   "Mon" / "Tue" / "Wed" / "Thu" /
-    ///                    "Fri" / "Sat" / "Sun"
-    /// ```
-    fn day_of_week(input: &[u8]) -> IResult<&[u8], Cow<'_, [u8]>> {
-        let (input, day_name) = alt((
-            tag("Mon"),
-            tag("Tue"),
-            tag("Wed"),
-            tag("Thu"),
-            tag("Fri"),
-            tag("Sat"),
-            tag("Sun"),
-        ))(input)?;
-        Ok((input, day_name.into()))
-    }
-
-    /// `day             =   ([FWS] 1*2DIGIT FWS) / obs-day`
-    fn day(input: &[u8]) -> IResult<&[u8], &[u8]> {
-        let (input, _) = opt(fws)(input)?;
-        let (input, ret) = alt((take_n_digits(2), take_n_digits(1)))(input)?;
-        let (input, _) = fws(input)?;
-
-        Ok((input, ret))
+        let zone = zone.flatten();
+        let (input, timestamp) = to_timestamp(input, (year, month, day), time, zone)?;
+        Ok((input, (timestamp, zone)))
     }
 
+    /// e.g Wed Sep  9 00:27:54 2020
+    ///
     /// ```text
-    /// month           =   "Jan" / "Feb" / "Mar" / "Apr" /
-    ///                    "May" / "Jun" / "Jul" / "Aug" /
-    ///                    "Sep" / "Oct" / "Nov" / "Dec"
+    /// day-of-week month day time [zone] year
     /// ```
-    fn month(input: &[u8]) -> IResult<&[u8], &[u8]> {
-        alt((
-            tag("Jan"),
-            tag("Feb"),
-            tag("Mar"),
-            tag("Apr"),
-            tag("May"),
-            tag("Jun"),
-            tag("Jul"),
-            tag("Aug"),
-            tag("Sep"),
-            tag("Oct"),
-            tag("Nov"),
-            tag("Dec"),
-        ))(input)
+    pub fn mbox_date_time(input: &[u8]) -> IResult<&[u8], UnixTimestamp> {
+        mbox_date_time_with_zone(input).map(|(rest, (timestamp, _))| (rest, timestamp))
     }
 
-    ///year            =   (FWS 4*DIGIT FWS) / obs-year
-    fn year(input: &[u8]) -> IResult<&[u8], &[u8]> {
-        let (input, _) = opt(fws)(input)?;
-        let (input, ret) = take_n_digits(4)(input)?;
-        let (input, _) = opt(fws)(input)?;
-        Ok((input, ret))
+    fn mbox_date_time_with_zone(input: &[u8]) -> IResult<&[u8], (UnixTimestamp, ZoneOffset)> {
+        let (input, _) = opt(cfws)(input)?;
+        let (input, _) = day_of_week(input)?;
+        let (input, _) = opt(cfws)(input)?;
+        let (input, month) = month(input)?;
+        let (input, _) = opt(cfws)(input)?;
+        let (input, day) = number(1, 2)(input)?;
+        let (input, _) = opt(cfws)(input)?;
+        let (input, time) = time_of_day(input)?;
+        let (input, _) = opt(cfws)(input)?;
+        let (input, zone) = opt(zone)(input)?;
+        let (input, _) = opt(cfws)(input)?;
+        let (input, year) = year(input)?;
+        let zone = zone.flatten();
+        let (input, timestamp) = to_timestamp(input, (year, month, day), time, zone)?;
+        Ok((input, (timestamp, zone)))
     }
 
     /// Parses an `RFC822`/`RFC5322` `Date` value as a Unix timestamp.
     pub fn rfc5322_date(input: &[u8]) -> Result<crate::UnixTimestamp> {
+        rfc5322_date_with_zone(input).map(|(timestamp, _)| timestamp)
+    }
+
+    /// Parses an `RFC822`/`RFC5322` `Date` value as a Unix timestamp and the
+    /// offset of the zone it was written in.
+    pub fn rfc5322_date_with_zone(input: &[u8]) -> Result<(UnixTimestamp, ZoneOffset)> {
         date_time(input)
             .or_else(|_| {
                 let (rest, parsed_result) = encodings::phrase(input, false)?;
@@ -676,7 +694,7 @@ pub mod dates {
                     Ok(v) => v,
                     Err(_) => {
                         if let Ok(v) = crate::utils::datetime::rfc822_to_timestamp(parsed_result) {
-                            (&[][..], v)
+                            (&[][..], (v, None))
                         } else {
                             return Err(nom::Err::Error(
                                 (rest, "rfc5322_date(): invalid input").into(),
@@ -687,7 +705,7 @@ pub mod dates {
                 Ok((rest, ret))
             })
             .or_else(|_| {
-                let (rest, ret) = match mbox_date_time(input) {
+                let (rest, ret) = match mbox_date_time_with_zone(input) {
                     Ok(v) => v,
                     Err(_) => {
                         return Err(nom::Err::Error(
@@ -2328,7 +2346,9 @@ pub mod address {
     use super::*;
     use crate::email::{
         address::*,
-        parser::generic::{atom, cfws, dot_atom, dot_atom_text, dtext, phrase2, quoted_string},
+        parser::generic::{
+            atom, cfws, dot_atom, dot_atom_text, dtext, phrase2, quoted_string, word,
+        },
     };
     pub fn display_addr(input: &[u8]) -> IResult<&[u8], Address> {
         if input.is_empty() || input.len() < 3 {
@@ -2415,7 +2435,7 @@ pub mod address {
                 Ok((
                     input.get(rest_start..).unwrap_or_default(),
                     Address::Mailbox(MailboxAddress {
-                        raw,
+                        raw: crate::utils::intern::intern_bytes(&raw),
                         display_name,
                         address_spec,
                     }),
@@ -2432,16 +2452,40 @@ pub mod address {
         }
     }
 
-    ///`angle-addr      =   [CFWS] "<" addr-spec ">" [CFWS] / obs-angle-addr`
+    ///```text
+    /// angle-addr      =   [CFWS] "<" addr-spec ">" [CFWS] / obs-angle-addr
+    /// obs-angle-addr  =   [CFWS] "<" obs-route addr-spec ">" [CFWS]
+    /// ```
     pub fn angle_addr(input: &[u8]) -> IResult<&[u8], Address> {
         let (input, _) = opt(cfws)(input)?;
         let (input, _) = tag("<")(input)?;
+        let (input, _) = opt(obs_route)(input)?;
         let (input, addr_spec) = addr_spec(input)?;
         let (input, _) = tag(">")(input)?;
         let (input, _) = opt(cfws)(input)?;
         Ok((input, addr_spec))
     }
 
+    ///Returns the domains of the route, which are otherwise ignored.
+    ///
+    ///```text
+    /// obs-route       =   obs-domain-list ":"
+    /// obs-domain-list =   *(CFWS / ",") "@" domain
+    ///                     *("," [CFWS] ["@" domain])
+    /// ```
+    pub fn obs_route(input: &[u8]) -> IResult<&[u8], Vec<Cow<'_, [u8]>>> {
+        let (input, _) = many0(alt((map(cfws, |_| ()), map(tag(","), |_| ()))))(input)?;
+        let (input, first) = preceded(tag("@"), domain)(input)?;
+        let (input, rest) = many0(preceded(
+            pair(tag(","), opt(cfws)),
+            opt(preceded(tag("@"), domain)),
+        ))(input)?;
+        let (input, _) = tag(":")(input)?;
+        let mut ret = vec![first];
+        ret.extend(rest.into_iter().flatten());
+        Ok((input, ret))
+    }
+
     ///`obs-domain      =   atom *("." atom)`
     pub fn obs_domain(input: &[u8]) -> IResult<&[u8], Cow<'_, [u8]>> {
         let (mut input, atom_) = context("obs_domain", atom)(input)?;
@@ -2466,7 +2510,26 @@ pub mod address {
 
     ///`local-part      =   dot-atom / quoted-string / obs-local-part`
     pub fn local_part(input: &[u8]) -> IResult<&[u8], Cow<'_, [u8]>> {
-        alt((dot_atom, quoted_string))(input)
+        alt((
+            terminated(dot_atom, peek(tag("@"))),
+            terminated(quoted_string, peek(tag("@"))),
+            obs_local_part,
+        ))(input)
+    }
+
+    ///Returns the words joined with dots and without the CFWS around them,
+    ///e.g. `"john".doe` and `john . doe` are both returned as `john.doe`.
+    ///
+    ///`obs-local-part  =   word *("." word)`
+    pub fn obs_local_part(input: &[u8]) -> IResult<&[u8], Cow<'_, [u8]>> {
+        let (input, first) = word(input)?;
+        let (input, rest) = many0(preceded(tag("."), word))(input)?;
+        let mut ret = first.trim().to_vec();
+        for word in rest {
+            ret.push(b'.');
+            ret.extend_from_slice(word.trim());
+        }
+        Ok((input, ret.into()))
     }
 
     ///`domain          =   dot-atom / domain-literal / obs-domain`
@@ -2528,7 +2591,10 @@ pub mod address {
 
     ///`display-name    =   phrase`
     pub fn display_name(input: &[u8]) -> IResult<&[u8], Vec<u8>> {
-        let (rest, ret) = phrase2(input)?;
+        let (rest, mut ret) = phrase2(input)?;
+        // Comments between words, e.g. `John (the (real) one) Doe`, leave the
+        // white space around them behind.
+        ret.dedup_by(|b, prev| b.is_ascii_whitespace() && prev.is_ascii_whitespace());
         if let Ok((_, ret)) = crate::email::parser::encodings::phrase(&ret, true) {
             Ok((rest, ret))
         } else {
@@ -2566,8 +2632,13 @@ pub mod address {
                 if !input.starts_with(b",") {
                     break;
                 }
-                input = &input[1..];
-                let (input_, next_m) = mailbox(input)?;
+                // Skip the empty elements of `obs-mbox-list`, e.g. `a@b.test,,c@d.test`.
+                while let Some(rest) = input.strip_prefix(b",") {
+                    input = rest.ltrim();
+                }
+                let Ok((input_, next_m)) = mailbox(input) else {
+                    break;
+                };
                 ret.push(next_m);
                 input = input_;
             }
@@ -2599,12 +2670,27 @@ pub mod address {
         ))
     }
 
+    /// A local part without a domain, e.g. `root`, as written by some local
+    /// mailers. It is only accepted as a whole list element.
+    fn obs_bare_local_part(input: &[u8]) -> IResult<&[u8], Address> {
+        let (rest, local_part) = alt((dot_atom, quoted_string))(input)?;
+        if !(rest.is_empty() || rest.starts_with(b",")) {
+            return Err(nom::Err::Error(
+                (input, "obs_bare_local_part(): expected end of address").into(),
+            ));
+        }
+        Ok((rest, Address::new(None, to_str!(&local_part).to_string())))
+    }
+
     /// `address         =   mailbox / group`
     pub fn address(input: &[u8]) -> IResult<&[u8], Address> {
         let err = match alt((mailbox, group))(input.ltrim()) {
             ok @ Ok(_) => return ok,
             Err(err) => err,
         };
+        if let ok @ Ok(_) = obs_bare_local_part(input.ltrim()) {
+            return ok;
+        }
         // Try to detect common errors
         static RE: std::sync::OnceLock<regex::bytes::Regex> = std::sync::OnceLock::new();
 
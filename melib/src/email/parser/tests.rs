@@ -25,7 +25,7 @@ use crate::{
         address::*,
         parser::{
             address::*,
-            dates::rfc5322_date,
+            dates::{rfc5322_date, rfc5322_date_with_zone},
             encodings::*,
             generic::{comment, phrase2, unstructured},
             headers,
@@ -589,6 +589,59 @@ fn test_email_parser_addresses() {
     );
 }
 
+#[test]
+fn test_email_parser_addresses_obsolete_syntax() {
+    // Address headers seen in the wild that use the obsolete syntax of RFC5322
+    // section 4.4.
+    let corpus: &[(&str, &[(&str, &str)])] = &[
+        (
+            "<@a.example,@b.example:user@example.com>",
+            &[("", "user@example.com")],
+        ),
+        (
+            "Joe <@relay.example:joe@example.com>",
+            &[("Joe", "joe@example.com")],
+        ),
+        (
+            r#""Doe, John" <@mx.example:john@example.com>, jane@example.com (Jane)"#,
+            &[("Doe, John", "john@example.com"), ("", "jane@example.com")],
+        ),
+        (r#""john".doe@example.com"#, &[("", "john.doe@example.com")]),
+        (
+            r#"Pete(A nice \) chap) <pete(his account)@silly.test(his host)>"#,
+            &[("Pete", "pete@silly.test")],
+        ),
+        (
+            "John (the (real) one) Doe <j@example.com>",
+            &[("John Doe", "j@example.com")],
+        ),
+        ("root", &[("", "root")]),
+        (
+            "root, jdoe@example.org",
+            &[("", "root"), ("", "jdoe@example.org")],
+        ),
+        (
+            "A Group:c@a.test,,joe@where.test;",
+            &[("", "c@a.test"), ("", "joe@where.test")],
+        ),
+    ];
+    for (raw, expected) in corpus {
+        let (rest, list) = rfc2822address_list(raw.as_bytes()).unwrap();
+        assert!(rest.is_empty(), "{raw}: {:?}", to_str!(rest));
+        assert_eq!(
+            list.iter()
+                .flat_map(Address::mailboxes)
+                .map(|a| (a.get_display_name().unwrap_or_default(), a.get_email()))
+                .collect::<Vec<_>>(),
+            expected
+                .iter()
+                .map(|(d, a)| (d.to_string(), a.to_string()))
+                .collect::<Vec<_>>(),
+            "{raw}"
+        );
+    }
+}
+
 #[test]
 fn test_email_parser_quoted_printable() {
     let input = r#"<=21-- SEPARATOR  -->
@@ -657,6 +710,80 @@ fn test_email_parser_dates_date_new() {
     assert_eq!(rfc5322_date(val).unwrap(), 1599611274);
 }
 
+#[test]
+fn test_email_parser_dates_obsolete_syntax() {
+    let date = rfc5322_date(b"Sat, 13 Feb 1999 23:32:00 -0500").unwrap();
+    assert_eq!(date, 918966720);
+    // Two digit year, no day of week and obsolete zone.
+    assert_eq!(rfc5322_date(b"13 Feb 99 23:32 EST").unwrap(), date);
+    // Comments and folding white space between tokens.
+    assert_eq!(
+        rfc5322_date(b"Sat (day) , 13 Feb\r\n 1999 (year) 23 : 32 : 00 -0500 (EST)").unwrap(),
+        date
+    );
+    // Full, lowercase names and no comma.
+    assert_eq!(
+        rfc5322_date(b"saturday 13 february 1999 23:32:00 -0500").unwrap(),
+        date
+    );
+    assert_eq!(
+        rfc5322_date(b"1 Jan 49 00:00:00 +0000").unwrap(),
+        rfc5322_date(b"1 Jan 2049 00:00:00 +0000").unwrap()
+    );
+    assert_eq!(rfc5322_date(b"1 Jan 50 00:00:00 +0000").unwrap(), 0);
+    assert_eq!(
+        rfc5322_date(b"1 Jan 103 00:00:00 +0000").unwrap(),
+        rfc5322_date(b"1 Jan 2003 00:00:00 +0000").unwrap()
+    );
+    // Missing zones are treated as UTC.
+    assert_eq!(rfc5322_date(b"Sun, 14 Feb 1999 04:32:00").unwrap(), date);
+    assert!(rfc5322_date(b"Sat, 32 Feb 1999 23:32:00 -0500").is_err());
+    assert!(rfc5322_date(b"Sat, 13 Feb 1999 24:32:00 -0500").is_err());
+}
+
+#[test]
+fn test_email_parser_dates_zone() {
+    assert_eq!(
+        rfc5322_date_with_zone(b"Thu, 13 Feb 1999 23:32 -0330").unwrap(),
+        (918961320, Some(-(3 * 3600 + 30 * 60)))
+    );
+    assert_eq!(
+        rfc5322_date_with_zone(b"Thu, 13 Feb 1999 23:32 PDT")
+            .unwrap()
+            .1,
+        Some(-7 * 3600)
+    );
+    assert_eq!(
+        rfc5322_date_with_zone(b"Thu, 13 Feb 1999 23:32 GMT")
+            .unwrap()
+            .1,
+        Some(0)
+    );
+    // Unknown zones.
+    for val in [
+        &b"Thu, 13 Feb 1999 23:32 -0000"[..],
+        b"Thu, 13 Feb 1999 23:32 X",
+        b"Thu, 13 Feb 1999 23:32 CEST",
+    ] {
+        assert_eq!(rfc5322_date_with_zone(val).unwrap().1, None);
+    }
+}
+
+#[test]
+fn test_email_parser_dates_fallback() {
+    let raw = b"Received: from relay.example.org by mx.example.net; Sat, 13 Feb 1999 23:32:00 \
+                -0500\r\nReceived: from [192.0.2.1] by relay.example.org; Sat, 13 Feb 1999 \
+                23:30:00 -0500\r\nDate: yesterday\r\nSubject: hi\r\n\r\nbody";
+    let envelope = crate::Envelope::from_bytes(raw, None).unwrap();
+    assert_eq!(envelope.date(), 918966720);
+    let raw = b"Received: from relay.example.org by mx.example.net; Sat, 13 Feb 1999 23:32:00 \
+                -0500\r\nDelivery-Date: Sat, 13 Feb 1999 23:33:00 -0500\r\nSubject: \
+                hi\r\n\r\nbody";
+    let envelope = crate::Envelope::from_bytes(raw, None).unwrap();
+    assert_eq!(envelope.date(), 918966780);
+    assert_eq!(envelope.date_zone_offset(), None);
+}
+
 #[test]
 fn test_email_parser_comment() {
     let s = b"(recursive (comment) block)";
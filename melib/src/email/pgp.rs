@@ -19,15 +19,31 @@
  * along with meli. If not, see <http://www.gnu.org/licenses/>.
  */
 
-//! Verification of `OpenPGP` signatures.
+//! Verification of `OpenPGP` signatures and protected headers.
 use crate::{
     email::{
         attachment_types::{ContentType, MultipartType},
         attachments::Attachment,
+        compose::mime,
+        headers::{HeaderMap, HeaderName},
+        parser::{self, BytesExt},
     },
     Error, Result,
 };
 
+/// Subject placed in the unprotected outer headers of an encrypted message
+/// whose real subject is protected.
+pub const PROTECTED_SUBJECT_PLACEHOLDER: &str = "...";
+
+/// Headers copied into the cryptographic payload of encrypted messages.
+pub const PROTECTED_HEADERS: &[HeaderName] = &[
+    HeaderName::SUBJECT,
+    HeaderName::FROM,
+    HeaderName::TO,
+    HeaderName::CC,
+    HeaderName::REPLY_TO,
+];
+
 /// Convert raw attachment to the form needed for signature verification ([RFC3156](https://tools.ietf.org/html/rfc3156))
 ///
 /// ## RFC3156
@@ -140,6 +156,65 @@ pub fn verify_signature(a: &Attachment) -> Result<(Vec<u8>, &Attachment)> {
     }
 }
 
+/// Return the protected headers (`protected-headers="v1"`, also known as
+/// Memory Hole) of a decrypted message part, if it has any.
+///
+/// If the part is a `multipart/signed` message, the headers are looked up in
+/// its signed part instead.
+pub fn protected_headers(a: &Attachment) -> Option<HeaderMap> {
+    if let ContentType::Multipart {
+        kind: MultipartType::Signed,
+        ref parts,
+        ..
+    } = a.content_type
+    {
+        if let Some(signed_part) = parts.iter().find(|p| {
+            p.content_type != ContentType::PGPSignature
+                && p.content_type != ContentType::CMSSignature
+        }) {
+            if let Some(ret) = protected_headers(signed_part) {
+                return Some(ret);
+            }
+        }
+    }
+    if !a.parameters().iter().any(|(n, v)| {
+        n.eq_ignore_ascii_case(b"protected-headers") && matches!(v.trim(), b"v1" | b"\"v1\"")
+    }) {
+        return None;
+    }
+    let (_, headers) = parser::headers::headers(a.raw()).ok()?;
+    let mut ret = HeaderMap::empty();
+    for (name, value) in headers {
+        if name.as_str().to_ascii_lowercase().starts_with("content-")
+            || name == HeaderName::MIME_VERSION
+        {
+            continue;
+        }
+        let value = parser::encodings::phrase(value.trim(), false)
+            .map(|(_, v)| String::from_utf8_lossy(&v).into_owned())
+            .unwrap_or_else(|_| String::from_utf8_lossy(value.trim()).into_owned());
+        ret.insert(name, value);
+    }
+    (!ret.is_empty()).then_some(ret)
+}
+
+/// Serialize `headers` as a header block to be placed in front of the
+/// cryptographic payload of an encrypted message.
+///
+/// The payload's `Content-Type` must also carry the `protected-headers="v1"`
+/// parameter, see [`protected_headers`].
+pub fn protected_headers_block(headers: &HeaderMap) -> String {
+    let mut ret = String::new();
+    for (k, v) in headers.iter() {
+        if v.is_ascii() {
+            ret.push_str(&format!("{k}: {v}\r\n"));
+        } else {
+            ret.push_str(&format!("{}: {}\r\n", k, mime::encode_header(v)));
+        }
+    }
+    ret
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct DecryptionMetadata {
     pub recipients: Vec<Recipient>,
@@ -160,3 +235,37 @@ pub struct SignatureMetadata {
     pub file_name: Option<String>,
     pub is_mime: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::attachments::AttachmentBuilder;
+
+    #[test]
+    fn test_pgp_protected_headers() {
+        let plaintext = AttachmentBuilder::new(
+            b"Content-Type: text/plain; charset=utf-8; protected-headers=\"v1\"\r\nSubject: \
+              =?UTF-8?Q?Caf=C3=A9_plans?=\r\nFrom: Alice <alice@example.com>\r\n\r\nHello.\r\n",
+        )
+        .build();
+        let headers = protected_headers(&plaintext).unwrap();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(&headers[HeaderName::SUBJECT], "Café plans");
+        assert_eq!(&headers[HeaderName::FROM], "Alice <alice@example.com>");
+
+        let block = protected_headers_block(&headers);
+        assert!(block.is_ascii());
+        let plaintext = AttachmentBuilder::new(
+            format!("Content-Type: text/plain; protected-headers=v1\r\n{block}\r\nHello.\r\n")
+                .as_bytes(),
+        )
+        .build();
+        assert_eq!(protected_headers(&plaintext), Some(headers));
+
+        let plaintext = AttachmentBuilder::new(
+            b"Content-Type: text/plain; charset=utf-8\r\nSubject: Not protected\r\n\r\nHello.\r\n",
+        )
+        .build();
+        assert!(protected_headers(&plaintext).is_none());
+    }
+}
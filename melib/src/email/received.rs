@@ -0,0 +1,198 @@
+/*
+ * meli - melib crate.
+ *
+ * Copyright 2024 Manos Pitsidianakis
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Parsing of the `Received` trace headers of `RFC5321` into the delivery
+//! path of a message.
+//!
+//! Each server that relays a message prepends a `Received` header, so the
+//! topmost header is the last hop. [`hops`] returns them in delivery order.
+
+use super::{parser, HeaderName};
+use crate::utils::datetime::UnixTimestamp;
+
+/// A server the message passed through, as recorded in a `Received` header.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Hop {
+    /// The sending host, including any comment with its `HELO` name and
+    /// address.
+    pub from: Option<String>,
+    /// The address literal of the sending host, if recorded.
+    pub from_address: Option<String>,
+    /// The receiving host.
+    pub by: Option<String>,
+    pub via: Option<String>,
+    /// The protocol, e.g. `ESMTPS`.
+    pub with: Option<String>,
+    pub id: Option<String>,
+    /// The recipient the message was received for.
+    pub recipient: Option<String>,
+    /// When the message was received.
+    pub timestamp: Option<UnixTimestamp>,
+}
+
+impl Hop {
+    /// Parse the value of a `Received` header.
+    pub fn parse(value: &str) -> Self {
+        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+        let (clauses, date) = match value.rfind(';') {
+            Some(pos) => (&value[..pos], Some(value[pos + 1..].trim())),
+            None => (value.as_str(), None),
+        };
+        let mut ret = Self {
+            timestamp: date.and_then(parse_date),
+            ..Self::default()
+        };
+        // Each clause starts with a keyword and lasts until the next one.
+        let mut fields: Vec<(&str, String)> = vec![];
+        for token in tokens(clauses) {
+            let keyword = ["from", "by", "via", "with", "id", "for"]
+                .into_iter()
+                .find(|k| token.eq_ignore_ascii_case(k));
+            match keyword {
+                Some(keyword) if !fields.iter().any(|(k, _)| *k == keyword) => {
+                    fields.push((keyword, String::new()));
+                }
+                _ => {
+                    if let Some((_, value)) = fields.last_mut() {
+                        if !value.is_empty() {
+                            value.push(' ');
+                        }
+                        value.push_str(token);
+                    }
+                }
+            }
+        }
+        for (keyword, value) in fields {
+            let field = match keyword {
+                "from" => &mut ret.from,
+                "by" => &mut ret.by,
+                "via" => &mut ret.via,
+                "with" => &mut ret.with,
+                "id" => &mut ret.id,
+                _ => &mut ret.recipient,
+            };
+            *field = Some(value).filter(|v| !v.is_empty());
+        }
+        ret.from_address = ret.from.as_deref().and_then(|from| {
+            let start = from.find('[')?;
+            let end = start + from[start..].find(']')?;
+            Some(from[start + 1..end].trim_start_matches("IPv6:").to_string())
+        });
+        ret
+    }
+
+    /// Seconds between the previous hop and this one. It is negative if the
+    /// clocks of the servers disagree.
+    pub fn latency(&self, previous: &Self) -> Option<i64> {
+        Some(self.timestamp? as i64 - previous.timestamp? as i64)
+    }
+}
+
+/// The `Received` headers of message `raw` in delivery order, i.e. the hop
+/// closest to the sender first.
+pub fn hops(raw: &[u8]) -> Vec<Hop> {
+    let Ok((_, headers)) = parser::headers::headers(raw) else {
+        return vec![];
+    };
+    let mut ret = headers
+        .into_iter()
+        .filter(|(name, _)| *name == HeaderName::RECEIVED)
+        .map(|(_, value)| Hop::parse(&String::from_utf8_lossy(value)))
+        .collect::<Vec<_>>();
+    ret.reverse();
+    ret
+}
+
+/// Split clauses into words, keeping parenthesized comments in one token.
+fn tokens(s: &str) -> Vec<&str> {
+    let mut ret = vec![];
+    let mut depth = 0_usize;
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ' ' if depth == 0 => {
+                if let Some(start) = start.take() {
+                    ret.push(&s[start..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(start) = start {
+        ret.push(&s[start..]);
+    }
+    ret
+}
+
+fn parse_date(date: &str) -> Option<UnixTimestamp> {
+    parser::dates::rfc5322_date(date.as_bytes()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_received_parse() {
+        let hop = Hop::parse(
+            "from mail.example.com (mail.example.com [192.0.2.1])\r\n\tby mx.example.org \
+             (Postfix) with ESMTPS id 4ABC123\r\n\tfor <user@example.org>; Tue, 1 Oct 2024 \
+             10:00:05 +0000 (UTC)",
+        );
+        assert_eq!(
+            hop.from.as_deref(),
+            Some("mail.example.com (mail.example.com [192.0.2.1])")
+        );
+        assert_eq!(hop.from_address.as_deref(), Some("192.0.2.1"));
+        assert_eq!(hop.by.as_deref(), Some("mx.example.org (Postfix)"));
+        assert_eq!(hop.with.as_deref(), Some("ESMTPS"));
+        assert_eq!(hop.id.as_deref(), Some("4ABC123"));
+        assert_eq!(hop.recipient.as_deref(), Some("<user@example.org>"));
+        assert_eq!(hop.timestamp, Some(1727776805));
+
+        let hop = Hop::parse("by localhost (Postfix, from userid 1000) id 1234");
+        assert_eq!(hop.from, None);
+        assert_eq!(
+            hop.by.as_deref(),
+            Some("localhost (Postfix, from userid 1000)")
+        );
+        assert_eq!(hop.id.as_deref(), Some("1234"));
+        assert_eq!(hop.timestamp, None);
+    }
+
+    #[test]
+    fn test_received_hops() {
+        let raw = b"Received: from relay.example.org by mx.example.net; Tue, 1 Oct 2024 10:00:10 \
+                    +0000\r\nReceived: from [IPv6:2001:db8::1] by relay.example.org; Tue, 1 Oct \
+                    2024 10:00:03 +0000\r\nSubject: hi\r\n\r\nbody";
+        let hops = hops(raw);
+        assert_eq!(hops.len(), 2);
+        assert_eq!(hops[0].by.as_deref(), Some("relay.example.org"));
+        assert_eq!(hops[0].from_address.as_deref(), Some("2001:db8::1"));
+        assert_eq!(hops[1].by.as_deref(), Some("mx.example.net"));
+        assert_eq!(hops[1].latency(&hops[0]), Some(7));
+        assert_eq!(hops[0].latency(&hops[1]), Some(-7));
+    }
+}
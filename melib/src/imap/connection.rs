@@ -25,7 +25,7 @@ use std::{
     future::Future,
     pin::Pin,
     str::FromStr,
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::{Duration, Instant, SystemTime},
 };
 
@@ -59,7 +59,7 @@ pub use smol::Async as AsyncWrapper;
 use crate::{
     backends::{
         prelude::{EnvelopeHash, Flag, Query},
-        BackendEvent, BackendMailbox, MailboxHash, RefreshEvent,
+        BackendEvent, BackendMailbox, MailboxHash, MailboxPermissions, RefreshEvent,
     },
     email::parser::BytesExt,
     error::*,
@@ -69,7 +69,8 @@ use crate::{
             ResponseCode, SelectResponse,
         },
         search::ToImapSearch,
-        Capabilities, ImapServerConf, UIDStore, UID,
+        sync::cache::ModSequence,
+        Capabilities, ImapServerConf, ServerQuirks, UIDStore, UID, UIDVALIDITY,
     },
     text::Truncate,
     utils::{
@@ -139,10 +140,23 @@ impl ImapProtocol {
 pub struct ImapExtensionUse {
     pub auth_anonymous: bool,
     pub condstore: bool,
+    /// Use the `QRESYNC` extension ([RFC7162]) to resynchronise mailboxes;
+    /// requires `condstore`.
+    ///
+    /// [RFC7162]: https://www.rfc-editor.org/rfc/rfc7162
+    pub qresync: bool,
     pub idle: bool,
     pub deflate: bool,
     pub oauth2: bool,
     pub id: bool,
+    /// Use the Gmail IMAP extensions (`X-GM-EXT-1`).
+    pub gmail: bool,
+    /// Use the `SORT` and `THREAD` extensions ([RFC5256]) for a
+    /// server-ordered fetch: every message is still fetched, newest threads
+    /// first. Off by default, since it does not make fetching any shorter.
+    ///
+    /// [RFC5256]: https://www.rfc-editor.org/rfc/rfc5256
+    pub sort: bool,
 }
 
 impl Default for ImapExtensionUse {
@@ -150,10 +164,13 @@ impl Default for ImapExtensionUse {
         Self {
             auth_anonymous: false,
             condstore: true,
+            qresync: true,
             idle: true,
             deflate: true,
             oauth2: false,
             id: false,
+            gmail: true,
+            sort: false,
         }
     }
 }
@@ -379,6 +396,7 @@ impl ImapStream {
         send_state_changes: bool,
     ) -> Result<(Capabilities, Self)> {
         let path = &server_conf.server_hostname;
+        let mut quirks = ServerQuirks::empty();
 
         let stream = if server_conf.use_tls {
             if send_state_changes {
@@ -446,6 +464,7 @@ impl ImapStream {
                     match server_conf.protocol {
                         ImapProtocol::IMAP { .. } => {
                             if response.starts_with(b"* OK ") && response.find(b"\r\n").is_some() {
+                                quirks |= ServerQuirks::from_greeting(&response);
                                 if let Some(pos) = response.find(b"\r\n") {
                                     response.drain(0..pos + 2);
                                 }
@@ -566,6 +585,7 @@ impl ImapStream {
         }
         ret.send_command(CommandBody::Capability).await?;
         ret.read_response(&mut res).await?;
+        quirks |= ServerQuirks::from_greeting(&res);
 
         fn parse_capabilities(bytes: &[u8], hostname: &str) -> Result<IndexSet<Box<[u8]>>> {
             protocol_parser::capabilities(bytes)
@@ -798,10 +818,41 @@ impl ImapStream {
                 }
                 Ok((_, None)) => {}
                 Ok((_, res @ Some(_))) => {
+                    quirks |= res.as_ref().map(ServerQuirks::from_id).unwrap_or_default();
                     *uid_store.server_id.lock().unwrap() = res;
                 }
             }
         }
+        if uid_store.namespace.lock().unwrap().is_none()
+            && capabilities
+                .iter()
+                .any(|cap| cap.eq_ignore_ascii_case(b"NAMESPACE"))
+        {
+            // [ref:TODO]: (#222) imap-codec does not support "NAMESPACE" currently.
+            ret.send_command_raw(b"NAMESPACE").await?;
+            ret.read_response(&mut res).await?;
+            match res
+                .split_rn()
+                .find(|l| l.starts_with(b"* NAMESPACE"))
+                .map(protocol_parser::namespace_response)
+            {
+                Some(Ok((_, namespace))) => {
+                    log::trace!("{}: {:?}", uid_store.account_name, namespace);
+                    *uid_store.namespace.lock().unwrap() = Some(namespace);
+                }
+                _ => {
+                    log::warn!(
+                        "{}: Could not parse NAMESPACE response from server: {}",
+                        uid_store.account_name,
+                        String::from_utf8_lossy(&res).as_ref().trim_at_boundary(40)
+                    );
+                }
+            }
+        }
+        if !quirks.is_empty() {
+            log::trace!("{}: server quirks: {:?}", uid_store.account_name, quirks);
+            *uid_store.quirks.lock().unwrap() |= quirks;
+        }
         Ok((capabilities, ret))
     }
 
@@ -1053,11 +1104,14 @@ impl ImapConnection {
                     extension_use:
                         ImapExtensionUse {
                             condstore,
+                            qresync,
                             deflate,
                             idle: _,
                             oauth2: _,
                             auth_anonymous: _,
                             id: _,
+                            gmail: _,
+                            sort: _,
                         },
                 } => {
                     if capabilities.contains(&b"CONDSTORE"[..]) && condstore {
@@ -1088,6 +1142,32 @@ impl ImapConnection {
                                 self.read_response(&mut ret, RequiredResponses::empty())
                                     .await?;
                                 self.sync_policy = SyncPolicy::Condstore;
+                                if qresync
+                                    && capabilities.contains(&b"QRESYNC"[..])
+                                    && capabilities.contains(&b"ENABLE"[..])
+                                {
+                                    /* Upgrade to Qresync, which also replaces EXPUNGE responses
+                                     * with VANISHED responses. */
+                                    // [ref:TODO]: (#222) imap-codec does not support
+                                    // "CONDSTORE/QRESYNC" currently.
+                                    self.send_command_raw(b"ENABLE QRESYNC").await?;
+                                    match self
+                                        .read_response(&mut ret, RequiredResponses::empty())
+                                        .await
+                                    {
+                                        Ok(()) => {
+                                            self.sync_policy = SyncPolicy::CondstoreQresync;
+                                        }
+                                        Err(err) if err.kind.is_network() => return Err(err),
+                                        Err(err) => {
+                                            log::warn!(
+                                                "Could not enable QRESYNC in account `{}`: {}",
+                                                self.uid_store.account_name,
+                                                err
+                                            );
+                                        }
+                                    }
+                                }
                             }
                         }
                     }
@@ -1360,6 +1440,76 @@ impl ImapConnection {
         self.send_command(CommandBody::select(imap_path.as_str())?)
             .await?;
         self.read_response(ret, RequiredResponses::SELECT).await?;
+        self.process_select_response(mailbox_hash, &imap_path, permissions, ret)
+            .await
+    }
+
+    /// `SELECT` a mailbox with `QRESYNC` parameters ([RFC7162] Section
+    /// 3.2.5), so that the server reports the UIDs expunged and the flags
+    /// changed since `modseq` along with the `SELECT` response.
+    ///
+    /// The `VANISHED (EARLIER)` and `FETCH` responses are kept in `ret`.
+    ///
+    /// [RFC7162]: https://www.rfc-editor.org/rfc/rfc7162
+    pub async fn select_mailbox_qresync(
+        &mut self,
+        mailbox_hash: MailboxHash,
+        ret: &mut Vec<u8>,
+        uidvalidity: UIDVALIDITY,
+        modseq: ModSequence,
+        max_uid: UID,
+    ) -> Result<SelectResponse> {
+        let (imap_path, no_select, permissions) = {
+            let m = &self.uid_store.mailboxes.lock().await[&mailbox_hash];
+            (
+                m.imap_path().to_string(),
+                m.no_select,
+                m.permissions.clone(),
+            )
+        };
+        if no_select {
+            return Err(Error::new(format!(
+                "Trying to select a \\NoSelect mailbox: {}",
+                imap_path
+            ))
+            .set_kind(ErrorKind::Bug));
+        }
+        let known_uids = if max_uid == 0 {
+            String::new()
+        } else {
+            format!(" 1:{max_uid}")
+        };
+        // [ref:TODO]: (#222) imap-codec does not support "CONDSTORE/QRESYNC" currently.
+        self.send_command_raw(
+            format!("SELECT \"{imap_path}\" (QRESYNC ({uidvalidity} {modseq}{known_uids}))")
+                .as_bytes(),
+        )
+        .await?;
+        self.read_response(
+            ret,
+            RequiredResponses::SELECT
+                | RequiredResponses::VANISHED
+                | RequiredResponses::FETCH_UID
+                | RequiredResponses::FETCH_FLAGS,
+        )
+        .await?;
+        // Message sequence numbers of the previous session are not valid anymore.
+        self.uid_store
+            .msn_index
+            .lock()
+            .unwrap()
+            .remove(&mailbox_hash);
+        self.process_select_response(mailbox_hash, &imap_path, permissions, ret)
+            .await
+    }
+
+    async fn process_select_response(
+        &mut self,
+        mailbox_hash: MailboxHash,
+        imap_path: &str,
+        permissions: Arc<Mutex<MailboxPermissions>>,
+        ret: &[u8],
+    ) -> Result<SelectResponse> {
         imap_log!(
             trace,
             self,
@@ -1703,6 +1853,91 @@ impl ImapConnection {
         Ok(())
     }
 
+    /// Whether the server has the Gmail IMAP extensions (`X-GM-EXT-1`) and
+    /// their use is enabled.
+    pub fn has_gmail_extensions(&self) -> bool {
+        matches!(
+            self.server_conf.protocol,
+            ImapProtocol::IMAP {
+                extension_use: ImapExtensionUse { gmail: true, .. }
+            }
+        ) && self
+            .uid_store
+            .capabilities
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|cap| cap.eq_ignore_ascii_case(b"X-GM-EXT-1"))
+    }
+
+    /// Whether the server has the `SORT` extension and its use is enabled.
+    pub fn has_sort_extension(&self) -> bool {
+        matches!(
+            self.server_conf.protocol,
+            ImapProtocol::IMAP {
+                extension_use: ImapExtensionUse { sort: true, .. }
+            }
+        ) && self
+            .uid_store
+            .capabilities
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|cap| cap.eq_ignore_ascii_case(b"SORT"))
+    }
+
+    /// Whether the server has the `THREAD=REFERENCES` extension and the use
+    /// of `SORT` and `THREAD` is enabled.
+    pub fn has_thread_references(&self) -> bool {
+        self.has_sort_extension()
+            && self
+                .uid_store
+                .capabilities
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|cap| cap.eq_ignore_ascii_case(b"THREAD=REFERENCES"))
+    }
+
+    /// The UIDs of the messages of mailbox `mailbox_hash`, newest first, as
+    /// sorted by the server with `UID SORT`.
+    pub async fn sorted_uids(&mut self, mailbox_hash: MailboxHash) -> Result<Vec<UID>> {
+        let mut response = Vec::with_capacity(8 * 1024);
+        self.examine_mailbox(mailbox_hash, &mut response, false)
+            .await?;
+        self.send_command_raw(b"UID SORT (REVERSE DATE) UTF-8 ALL")
+            .await?;
+        self.read_response(&mut response, RequiredResponses::SORT)
+            .await?;
+        for l in response.split_rn() {
+            if l.starts_with(b"* SORT") {
+                return Ok(protocol_parser::sort_results(l)?.1);
+            }
+        }
+        Err(Error::new(String::from_utf8_lossy(&response).to_string()))
+    }
+
+    /// The threads of the messages of mailbox `mailbox_hash` by UID, as
+    /// computed by the server with `UID THREAD REFERENCES`.
+    pub async fn thread_uids(
+        &mut self,
+        mailbox_hash: MailboxHash,
+    ) -> Result<Vec<protocol_parser::ThreadList>> {
+        let mut response = Vec::with_capacity(8 * 1024);
+        self.examine_mailbox(mailbox_hash, &mut response, false)
+            .await?;
+        self.send_command_raw(b"UID THREAD REFERENCES UTF-8 ALL")
+            .await?;
+        self.read_response(&mut response, RequiredResponses::THREAD)
+            .await?;
+        for l in response.split_rn() {
+            if l.starts_with(b"* THREAD") {
+                return Ok(protocol_parser::thread_results(l)?.1);
+            }
+        }
+        Err(Error::new(String::from_utf8_lossy(&response).to_string()))
+    }
+
     pub async fn search(
         &mut self,
         query: Query,
@@ -1713,7 +1948,10 @@ impl ImapConnection {
                 "Cannot search without specifying mailbox on IMAP",
             ));
         };
-        let query_str = query.to_imap_search();
+        let query_str = match query.to_gmail_raw_search() {
+            Some(raw) if self.has_gmail_extensions() => raw,
+            _ => query.to_imap_search(),
+        };
 
         let mut response = Vec::with_capacity(8 * 1024);
         self.examine_mailbox(mailbox_hash, &mut response, false)
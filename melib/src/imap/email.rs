@@ -29,30 +29,65 @@ use imap_codec::imap_types::{
     flag::Flag,
 };
 
-use crate::imap::protocol_parser::RequiredResponses;
+use crate::imap::{protocol_parser::RequiredResponses, ServerQuirks};
 
-// [ref:TODO]: (#222) Make this `const` as soon as it is possible.
-pub fn common_attributes() -> (RequiredResponses, MacroOrMessageDataItemNames<'static>) {
+/// The `FETCH` items requested for new envelopes, and the responses required
+/// to parse them, minus those that `quirks` prevent from being requested.
+///
+/// The `References` header is always requested along with any
+/// `retained_headers`.
+pub fn common_attributes(
+    quirks: ServerQuirks,
+    retained_headers: &[String],
+) -> (RequiredResponses, MacroOrMessageDataItemNames<'static>) {
+    let header_fields = std::iter::once(AString::from(Atom::unvalidated("REFERENCES")))
+        .chain(
+            retained_headers
+                .iter()
+                .map(|h| AString::from(Atom::unvalidated(h.to_ascii_uppercase()))),
+        )
+        .collect::<Vec<_>>();
+    let mut items = vec![
+        MessageDataItemName::Uid,
+        MessageDataItemName::Flags,
+        MessageDataItemName::Envelope,
+        MessageDataItemName::BodyExt {
+            section: Some(Section::HeaderFields(
+                None,
+                Vec1::try_from(header_fields).expect("REFERENCES is always present"),
+            )),
+            partial: None,
+            peek: true,
+        },
+        MessageDataItemName::Rfc822Size,
+    ];
+    if !quirks.contains(ServerQuirks::BROKEN_BODYSTRUCTURE) {
+        items.insert(4, MessageDataItemName::BodyStructure);
+    }
     (
-        RequiredResponses::FETCH_UID
-            | RequiredResponses::FETCH_FLAGS
-            | RequiredResponses::FETCH_ENVELOPE
-            | RequiredResponses::FETCH_REFERENCES
-            | RequiredResponses::FETCH_BODYSTRUCTURE,
-        MacroOrMessageDataItemNames::MessageDataItemNames(vec![
-            MessageDataItemName::Uid,
-            MessageDataItemName::Flags,
-            MessageDataItemName::Envelope,
-            MessageDataItemName::BodyExt {
-                section: Some(Section::HeaderFields(
-                    None,
-                    Vec1::from(AString::from(Atom::unvalidated("REFERENCES"))),
-                )),
-                partial: None,
-                peek: true,
-            },
-            MessageDataItemName::BodyStructure,
-        ]),
+        quirks.required_responses(
+            RequiredResponses::FETCH_UID
+                | RequiredResponses::FETCH_FLAGS
+                | RequiredResponses::FETCH_ENVELOPE
+                | RequiredResponses::FETCH_REFERENCES
+                | RequiredResponses::FETCH_BODYSTRUCTURE,
+        ),
+        MacroOrMessageDataItemNames::MessageDataItemNames(items),
+    )
+}
+
+/// The items of [`common_attributes`] with the Gmail `X-GM-LABELS` and
+/// `X-GM-THRID` items, for servers with the `X-GM-EXT-1` capability.
+// [ref:TODO]: (#222) imap-codec does not support the Gmail extensions.
+pub fn gmail_common_attributes(retained_headers: &[String]) -> String {
+    let mut header_fields = "REFERENCES".to_string();
+    for h in retained_headers {
+        header_fields.push(' ');
+        header_fields.push_str(&h.to_ascii_uppercase());
+    }
+    format!(
+        "(UID FLAGS ENVELOPE BODY.PEEK[HEADER.FIELDS ({header_fields})] BODYSTRUCTURE \
+         RFC822.SIZE X-GM-LABELS X-GM-THRID)"
     )
 }
 
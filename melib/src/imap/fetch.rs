@@ -20,6 +20,8 @@
 //
 // SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
 
+use std::collections::VecDeque;
+
 use super::*;
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
@@ -30,9 +32,21 @@ pub enum FetchStage {
         max_uid: Option<UID>,
     },
     ResyncCache,
+    /// Load the envelopes of the offline cache from `max_uid` downwards,
+    /// without contacting the server.
+    OfflineCache {
+        max_uid: Option<UID>,
+    },
+    /// Sync the changes made on the server since the envelopes shown from
+    /// the offline cache were cached.
+    BackgroundResync,
     FreshFetch {
         max_uid: UID,
     },
+    /// Fetch every UID of [`FetchState::ordered`] in batches, in the order
+    /// the server sorted and threaded them. This only changes the order
+    /// envelopes arrive in, the whole mailbox is still fetched.
+    ServerOrderedFetch,
     Finished,
 }
 
@@ -44,6 +58,19 @@ pub struct FetchState {
     pub uid_store: Arc<UIDStore>,
     pub batch_size: usize,
     pub cache_batch_size: usize,
+    /// UIDs left to fetch in the order the server sorted and threaded them,
+    /// if it supports the `SORT` extension.
+    pub ordered: VecDeque<UID>,
+    /// The UID of the message each message replies to, according to the
+    /// server's `THREAD` response.
+    pub thread_parents: HashMap<UID, UID>,
+    /// The UIDs that are values of `thread_parents`.
+    pub parent_uids: HashSet<UID>,
+    /// The Message-IDs of the fetched messages of `parent_uids`.
+    pub parent_message_ids: HashMap<UID, MessageID>,
+    /// The envelopes shown from the offline cache before syncing with the
+    /// server.
+    pub offline_envelopes: BTreeSet<EnvelopeHash>,
 }
 
 impl FetchState {
@@ -52,12 +79,8 @@ impl FetchState {
         loop {
             match self.stage {
                 FetchStage::InitialFresh => {
-                    let select_response = self
-                        .connection
-                        .lock()
-                        .await?
-                        .init_mailbox(self.mailbox_hash)
-                        .await?;
+                    let mut conn = self.connection.lock().await?;
+                    let select_response = conn.init_mailbox(self.mailbox_hash).await?;
                     _ = self
                         .uid_store
                         .update_mailbox(self.mailbox_hash, &select_response);
@@ -66,6 +89,28 @@ impl FetchState {
                         self.stage = FetchStage::Finished;
                         return Ok(Vec::new());
                     }
+                    if conn.has_sort_extension() {
+                        match Self::server_order(&mut conn, self.mailbox_hash).await {
+                            Ok((ordered, thread_parents)) if !ordered.is_empty() => {
+                                self.parent_uids = thread_parents.values().copied().collect();
+                                self.thread_parents = thread_parents;
+                                self.ordered = ordered.into();
+                                self.stage = FetchStage::ServerOrderedFetch;
+                                continue;
+                            }
+                            Ok(_) => {}
+                            Err(err) => {
+                                imap_log!(
+                                    debug,
+                                    conn,
+                                    "Could not sort mailbox {} on the server, fetching by UID \
+                                     instead: {}",
+                                    self.mailbox_hash,
+                                    err
+                                );
+                            }
+                        }
+                    }
                     self.stage = FetchStage::FreshFetch {
                         max_uid: select_response.uidnext,
                     };
@@ -216,178 +261,176 @@ impl FetchState {
                     self.stage = FetchStage::InitialFresh;
                     continue;
                 }
-                FetchStage::FreshFetch { max_uid } => {
-                    let Self {
-                        ref mut stage,
-                        ref connection,
-                        mailbox_hash,
-                        ref uid_store,
-                        batch_size,
-                        cache_batch_size: _,
-                    } = self;
-                    let mailbox_hash = *mailbox_hash;
-                    let mut our_unseen: BTreeSet<EnvelopeHash> = BTreeSet::default();
-                    let (mailbox_path, mailbox_exists, no_select, unseen) = {
-                        let f = &uid_store.mailboxes.lock().await[&mailbox_hash];
-                        (
-                            f.imap_path().to_string(),
-                            Arc::clone(&f.exists),
-                            f.no_select,
-                            Arc::clone(&f.unseen),
-                        )
+                FetchStage::OfflineCache { max_uid: None } => {
+                    let max_uid = match self.uid_store.mailbox_state(self.mailbox_hash) {
+                        Ok(Some(_)) => self.max_uid(),
+                        other => other.map(|_| None),
                     };
-                    if no_select {
-                        self.stage = FetchStage::Finished;
-                        return Ok(Vec::new());
+                    self.stage = match max_uid {
+                        Ok(Some(max_uid)) if max_uid > 0 => FetchStage::OfflineCache {
+                            max_uid: Some(max_uid),
+                        },
+                        Ok(_) => FetchStage::ResyncCache,
+                        Err(err) => {
+                            log::error!(
+                                "IMAP cache error: could not fetch cache for {}. Reason: {}",
+                                self.uid_store.account_name,
+                                err
+                            );
+                            FetchStage::ResyncCache
+                        }
+                    };
+                    continue;
+                }
+                FetchStage::OfflineCache {
+                    max_uid: Some(max_uid),
+                } => {
+                    let env_hashes = match self.uid_store.envelopes(
+                        self.mailbox_hash,
+                        max_uid,
+                        self.cache_batch_size,
+                    ) {
+                        Ok(Some(env_hashes)) => env_hashes,
+                        Ok(None) => {
+                            self.forget_offline_envelopes().await;
+                            self.stage = FetchStage::ResyncCache;
+                            continue;
+                        }
+                        Err(err) => {
+                            log::error!(
+                                "IMAP cache error: could not fetch cache for {}. Reason: {}",
+                                self.uid_store.account_name,
+                                err
+                            );
+                            self.forget_offline_envelopes().await;
+                            self.stage = FetchStage::ResyncCache;
+                            continue;
+                        }
+                    };
+                    // The batch was the UIDs from `max_uid - cache_batch_size` to
+                    // `max_uid`.
+                    self.stage = match max_uid.saturating_sub(self.cache_batch_size + 1) {
+                        0 => FetchStage::BackgroundResync,
+                        other => FetchStage::OfflineCache {
+                            max_uid: Some(other),
+                        },
+                    };
+                    let cached_payload = {
+                        let env_lck = self.uid_store.envelopes.lock().unwrap();
+                        env_hashes
+                            .into_iter()
+                            .filter_map(|env_hash| {
+                                env_lck.get(&env_hash).map(|c_env| c_env.inner.clone())
+                            })
+                            .collect::<Vec<Envelope>>()
+                    };
+                    let (mailbox_exists, unseen) = {
+                        let f = &self.uid_store.mailboxes.lock().await[&self.mailbox_hash];
+                        (Arc::clone(&f.exists), Arc::clone(&f.unseen))
+                    };
+                    unseen.lock().unwrap().insert_existing_set(
+                        cached_payload
+                            .iter()
+                            .filter(|env| !env.is_seen())
+                            .map(|env| env.hash())
+                            .collect(),
+                    );
+                    let env_hashes = cached_payload
+                        .iter()
+                        .map(|env| env.hash())
+                        .collect::<BTreeSet<_>>();
+                    self.offline_envelopes.extend(env_hashes.iter().copied());
+                    mailbox_exists
+                        .lock()
+                        .unwrap()
+                        .insert_existing_set(env_hashes);
+                    return Ok(cached_payload);
+                }
+                FetchStage::BackgroundResync => {
+                    let res = self
+                        .connection
+                        .lock()
+                        .await?
+                        .resync(self.mailbox_hash)
+                        .await?;
+                    if let Some(payload) = self.resynced(res).await {
+                        return Ok(payload);
                     }
-                    let mut conn = connection.lock().await?;
-                    let mut response = Vec::with_capacity(8 * 1024);
+                    continue;
+                }
+                FetchStage::FreshFetch { max_uid } => {
                     let max_uid_left = max_uid;
-
-                    let mut envelopes = Vec::with_capacity(*batch_size);
-                    conn.examine_mailbox(mailbox_hash, &mut response, false)
-                        .await?;
+                    let mut envelopes = vec![];
                     if max_uid_left > 0 {
+                        let min = max_uid_left.saturating_sub(self.batch_size).max(1);
+                        let max = max_uid_left;
                         let sequence_set = if max_uid_left == 1 {
                             SequenceSet::from(ONE)
                         } else {
-                            let min = max_uid_left.saturating_sub(*batch_size).max(1);
-                            let max = max_uid_left;
-
                             SequenceSet::try_from(min..=max)?
                         };
-                        let (required_responses, macro_or_item_names) =
-                            crate::imap::email::common_attributes();
-                        conn.send_command(CommandBody::Fetch {
-                            sequence_set,
-                            macro_or_item_names,
-                            uid: true,
-                        })
-                        .await?;
-                        conn.read_response(&mut response, required_responses)
-                            .await
-                            .chain_err_summary(|| {
-                                format!("Could not parse fetch response for mailbox {mailbox_path}")
-                            })?;
-                        let (_, mut v, _) = protocol_parser::fetch_responses(&response)?;
-                        for FetchResponse {
-                            ref uid,
-                            ref mut envelope,
-                            ref mut flags,
-                            raw_fetch_value,
-                            ref references,
-                            ..
-                        } in v.iter_mut()
+                        match self
+                            .fetch_batch(sequence_set, format!("{min}:{max}"))
+                            .await?
                         {
-                            if uid.is_none() || envelope.is_none() || flags.is_none() {
-                                imap_log!(
-                                    trace,
-                                    conn,
-                                    "BUG? something in fetch is none. UID: {:?}, envelope: {:?} \
-                                     flags: {:?}",
-                                    uid,
-                                    envelope,
-                                    flags
-                                );
-                                imap_log!(
-                                    trace,
-                                    conn,
-                                    "response was: {}",
-                                    String::from_utf8_lossy(&response)
-                                );
-                                if let Ok(Some(untagged_response)) =
-                                    super::protocol_parser::untagged_responses(raw_fetch_value)
-                                        .map(|(_, v, _)| v)
-                                {
-                                    if let Some(ev) =
-                                        conn.process_untagged(untagged_response).await?
-                                    {
-                                        conn.add_backend_event(ev);
-                                    }
-                                }
-                                continue;
-                            }
-                            let uid = uid.unwrap();
-                            let env = envelope.as_mut().unwrap();
-                            env.set_hash(generate_envelope_hash(&mailbox_path, &uid));
-                            if let Some(value) = references {
-                                env.set_references(value);
-                            }
-                            let mut tag_lck = uid_store.collection.tag_index.write().unwrap();
-                            if let Some((flags, keywords)) = flags {
-                                env.set_flags(*flags);
-                                if !env.is_seen() {
-                                    our_unseen.insert(env.hash());
-                                }
-                                for f in keywords {
-                                    let hash = TagHash::from_bytes(f.as_bytes());
-                                    tag_lck.entry(hash).or_insert_with(|| f.to_string());
-                                    env.tags_mut().insert(hash);
-                                }
+                            Some(batch) => envelopes = batch,
+                            None => {
+                                self.stage = FetchStage::Finished;
+                                return Ok(Vec::new());
                             }
                         }
-                        {
-                            let mut uid_store = Arc::clone(&self.uid_store);
-
-                            if let Err(err) = uid_store
-                                .insert_envelopes(mailbox_hash, &v)
-                                .chain_err_summary(|| {
-                                    format!(
-                                        "Could not save envelopes in cache for mailbox \
-                                         {mailbox_path}"
-                                    )
-                                })
-                            {
-                                (uid_store.event_consumer)(uid_store.account_hash, err.into());
-                            }
-                        }
-
-                        for f in v {
-                            let FetchResponse {
-                                uid: Some(uid),
-                                message_sequence_number,
-                                envelope: Some(env),
-                                ..
-                            } = f
-                            else {
-                                continue;
-                            };
-                            uid_store
-                                .msn_index
-                                .lock()
-                                .unwrap()
-                                .entry(mailbox_hash)
-                                .or_default()
-                                .insert(message_sequence_number - 1, uid);
-                            uid_store
-                                .hash_index
-                                .lock()
-                                .unwrap()
-                                .insert(env.hash(), (uid, mailbox_hash));
-                            uid_store
-                                .uid_index
-                                .lock()
-                                .unwrap()
-                                .insert((mailbox_hash, uid), env.hash());
-                            envelopes.push(env);
-                        }
-                        unseen.lock().unwrap().insert_existing_set(our_unseen);
-                        mailbox_exists.lock().unwrap().insert_existing_set(
-                            envelopes.iter().map(|env| env.hash()).collect::<_>(),
-                        );
-                        drop(conn);
                     }
                     if max_uid_left <= 1 {
-                        unseen.lock().unwrap().set_not_yet_seen(0);
-                        mailbox_exists.lock().unwrap().set_not_yet_seen(0);
-                        *stage = FetchStage::Finished;
+                        self.set_all_seen().await;
+                        self.stage = FetchStage::Finished;
                     } else {
-                        *stage = FetchStage::FreshFetch {
-                            max_uid: std::cmp::max(max_uid_left.saturating_sub(*batch_size + 1), 1),
+                        self.stage = FetchStage::FreshFetch {
+                            max_uid: std::cmp::max(
+                                max_uid_left.saturating_sub(self.batch_size + 1),
+                                1,
+                            ),
                         };
                     }
                     return Ok(envelopes);
                 }
+                FetchStage::ServerOrderedFetch => {
+                    let batch_len = self.batch_size.min(self.ordered.len());
+                    let uids = self.ordered.drain(..batch_len).collect::<Vec<UID>>();
+                    let mut envelopes = vec![];
+                    if !uids.is_empty() {
+                        let raw_sequence = uids
+                            .iter()
+                            .map(UID::to_string)
+                            .collect::<Vec<String>>()
+                            .join(",");
+                        let sequence_set = SequenceSet::try_from(
+                            uids.into_iter()
+                                .map(|uid| {
+                                    u32::try_from(uid)
+                                        .ok()
+                                        .and_then(|uid| NonZeroU32::try_from(uid).ok())
+                                        .ok_or_else(|| {
+                                            Error::new(format!(
+                                                "Application error: invalid UID {uid}"
+                                            ))
+                                        })
+                                })
+                                .collect::<Result<Vec<NonZeroU32>>>()?,
+                        )?;
+                        match self.fetch_batch(sequence_set, raw_sequence).await? {
+                            Some(batch) => envelopes = batch,
+                            None => {
+                                self.stage = FetchStage::Finished;
+                                return Ok(Vec::new());
+                            }
+                        }
+                    }
+                    if self.ordered.is_empty() {
+                        self.set_all_seen().await;
+                        self.stage = FetchStage::Finished;
+                    }
+                    return Ok(envelopes);
+                }
                 FetchStage::Finished => {
                     return Ok(vec![]);
                 }
@@ -395,6 +438,234 @@ impl FetchState {
         }
     }
 
+    /// Fetch the envelopes of the UIDs of `sequence_set`, also written as
+    /// `raw_sequence` for commands sent verbatim. Returns `None` if the
+    /// mailbox can't be selected.
+    async fn fetch_batch(
+        &mut self,
+        sequence_set: SequenceSet,
+        raw_sequence: String,
+    ) -> Result<Option<Vec<Envelope>>> {
+        let mailbox_hash = self.mailbox_hash;
+        let uid_store = &self.uid_store;
+        let mut our_unseen: BTreeSet<EnvelopeHash> = BTreeSet::default();
+        let (mailbox_path, mailbox_exists, no_select, unseen) = {
+            let f = &uid_store.mailboxes.lock().await[&mailbox_hash];
+            (
+                f.imap_path().to_string(),
+                Arc::clone(&f.exists),
+                f.no_select,
+                Arc::clone(&f.unseen),
+            )
+        };
+        if no_select {
+            return Ok(None);
+        }
+        let mut conn = self.connection.lock().await?;
+        let mut response = Vec::with_capacity(8 * 1024);
+        let mut envelopes = vec![];
+        conn.examine_mailbox(mailbox_hash, &mut response, false)
+            .await?;
+        let (required_responses, macro_or_item_names) = crate::imap::email::common_attributes(
+            *uid_store.quirks.lock().unwrap(),
+            &uid_store.retained_headers,
+        );
+        if conn.has_gmail_extensions() {
+            conn.send_command_raw(
+                format!(
+                    "UID FETCH {raw_sequence} {}",
+                    crate::imap::email::gmail_common_attributes(&uid_store.retained_headers)
+                )
+                .as_bytes(),
+            )
+            .await?;
+        } else {
+            conn.send_command(CommandBody::Fetch {
+                sequence_set,
+                macro_or_item_names,
+                uid: true,
+            })
+            .await?;
+        }
+        conn.read_response(&mut response, required_responses)
+            .await
+            .chain_err_summary(|| {
+                format!("Could not parse fetch response for mailbox {mailbox_path}")
+            })?;
+        let (_, mut v, _) = protocol_parser::fetch_responses(&response)?;
+        for FetchResponse {
+            ref uid,
+            ref mut envelope,
+            ref mut flags,
+            raw_fetch_value,
+            ref references,
+            ref gmail_labels,
+            gmail_thread_id,
+            ..
+        } in v.iter_mut()
+        {
+            if uid.is_none() || envelope.is_none() || flags.is_none() {
+                imap_log!(
+                    trace,
+                    conn,
+                    "BUG? something in fetch is none. UID: {:?}, envelope: {:?} flags: {:?}",
+                    uid,
+                    envelope,
+                    flags
+                );
+                imap_log!(
+                    trace,
+                    conn,
+                    "response was: {}",
+                    String::from_utf8_lossy(&response)
+                );
+                if let Ok(Some(untagged_response)) =
+                    super::protocol_parser::untagged_responses(raw_fetch_value).map(|(_, v, _)| v)
+                {
+                    if let Some(ev) = conn.process_untagged(untagged_response).await? {
+                        conn.add_backend_event(ev);
+                    }
+                }
+                continue;
+            }
+            let uid = uid.unwrap();
+            let env = envelope.as_mut().unwrap();
+            env.set_hash(generate_envelope_hash(&mailbox_path, &uid));
+            if let Some(value) = references {
+                env.set_references(value);
+            }
+            let mut tag_lck = uid_store.collection.tag_index.write().unwrap();
+            if let Some((flags, keywords)) = flags {
+                env.set_flags(*flags);
+                if !env.is_seen() {
+                    our_unseen.insert(env.hash());
+                }
+                for f in keywords {
+                    let hash = TagHash::from_bytes(f.as_bytes());
+                    tag_lck.entry(hash).or_insert_with(|| f.to_string());
+                    env.tags_mut().insert(hash);
+                }
+            }
+            for label in gmail_labels.iter().flatten() {
+                let hash = TagHash::from_bytes(label.as_bytes());
+                tag_lck.entry(hash).or_insert_with(|| label.to_string());
+                env.tags_mut().insert(hash);
+            }
+            if let Some(thread_id) = *gmail_thread_id {
+                uid_store.set_gmail_thread(env, thread_id);
+            }
+        }
+        if !self.thread_parents.is_empty() {
+            // Thread messages without In-Reply-To or References headers as the
+            // server did.
+            for f in v.iter() {
+                if let (Some(uid), Some(env)) = (f.uid, f.envelope.as_ref()) {
+                    if self.parent_uids.contains(&uid) {
+                        self.parent_message_ids
+                            .insert(uid, env.message_id().clone());
+                    }
+                }
+            }
+            for f in v.iter_mut() {
+                if let (Some(uid), Some(env)) = (f.uid, f.envelope.as_mut()) {
+                    if env.in_reply_to().is_some() || !env.references().is_empty() {
+                        continue;
+                    }
+                    if let Some(parent) = self
+                        .thread_parents
+                        .get(&uid)
+                        .and_then(|parent| self.parent_message_ids.get(parent))
+                    {
+                        env.push_in_reply_to(parent.clone());
+                    }
+                }
+            }
+        }
+        {
+            let mut uid_store = Arc::clone(uid_store);
+
+            if let Err(err) = uid_store
+                .insert_envelopes(mailbox_hash, &v)
+                .chain_err_summary(|| {
+                    format!("Could not save envelopes in cache for mailbox {mailbox_path}")
+                })
+            {
+                (uid_store.event_consumer)(uid_store.account_hash, err.into());
+            }
+        }
+
+        for f in v {
+            let FetchResponse {
+                uid: Some(uid),
+                message_sequence_number,
+                envelope: Some(env),
+                ..
+            } = f
+            else {
+                continue;
+            };
+            uid_store
+                .msn_index
+                .lock()
+                .unwrap()
+                .entry(mailbox_hash)
+                .or_default()
+                .insert(message_sequence_number - 1, uid);
+            uid_store
+                .hash_index
+                .lock()
+                .unwrap()
+                .insert(env.hash(), (uid, mailbox_hash));
+            uid_store
+                .uid_index
+                .lock()
+                .unwrap()
+                .insert((mailbox_hash, uid), env.hash());
+            envelopes.push(env);
+        }
+        unseen.lock().unwrap().insert_existing_set(our_unseen);
+        mailbox_exists
+            .lock()
+            .unwrap()
+            .insert_existing_set(envelopes.iter().map(|env| env.hash()).collect::<_>());
+        Ok(Some(envelopes))
+    }
+
+    /// Mark the counts of the mailbox as complete, once all its envelopes
+    /// have been fetched.
+    async fn set_all_seen(&self) {
+        let f = &self.uid_store.mailboxes.lock().await[&self.mailbox_hash];
+        f.unseen.lock().unwrap().set_not_yet_seen(0);
+        f.exists.lock().unwrap().set_not_yet_seen(0);
+    }
+
+    /// The UIDs of mailbox `mailbox_hash` in the order the server sorts and
+    /// threads them, see [`thread_order`], and the UID of the message each
+    /// message replies to. If the server can't thread, the UIDs are only
+    /// sorted.
+    async fn server_order(
+        conn: &mut ImapConnection,
+        mailbox_hash: MailboxHash,
+    ) -> Result<(Vec<UID>, HashMap<UID, UID>)> {
+        let sorted = conn.sorted_uids(mailbox_hash).await?;
+        if !conn.has_thread_references() {
+            return Ok((sorted, HashMap::default()));
+        }
+        match conn.thread_uids(mailbox_hash).await {
+            Ok(threads) => Ok(thread_order(&sorted, &threads)),
+            Err(err) => {
+                imap_log!(
+                    debug,
+                    conn,
+                    "Could not thread mailbox {} on the server: {}",
+                    mailbox_hash,
+                    err
+                );
+                Ok((sorted, HashMap::default()))
+            }
+        }
+    }
+
     fn load_cache(
         conn: &ImapConnection,
         mailbox_hash: MailboxHash,
@@ -425,12 +696,11 @@ impl FetchState {
 
     async fn cached_envs(&mut self, max_uid: UID) -> Result<Option<Vec<Envelope>>> {
         let Self {
-            stage: _,
             ref mut connection,
             mailbox_hash,
             ref uid_store,
-            batch_size: _,
             cache_batch_size,
+            ..
         } = self;
         let mailbox_hash = *mailbox_hash;
         if !uid_store.keep_offline_cache.load(Ordering::SeqCst) {
@@ -463,6 +733,52 @@ impl FetchState {
         }
     }
 
+    /// Move on from [`FetchStage::BackgroundResync`] with the result of
+    /// [`ImapConnection::resync`], returning the envelopes to show if the
+    /// cache was synced.
+    ///
+    /// If it could not be synced, e.g. because the mailbox's UIDVALIDITY
+    /// changed, the envelopes shown from the offline cache are removed and
+    /// the mailbox is fetched from scratch.
+    async fn resynced(&mut self, payload: Option<Vec<Envelope>>) -> Option<Vec<Envelope>> {
+        if payload.is_some() {
+            self.stage = FetchStage::Finished;
+            return payload;
+        }
+        self.forget_offline_envelopes().await;
+        self.stage = FetchStage::InitialFresh;
+        None
+    }
+
+    /// Remove the envelopes shown from the offline cache, since they turned
+    /// out to be stale.
+    async fn forget_offline_envelopes(&mut self) {
+        let offline_envelopes = std::mem::take(&mut self.offline_envelopes);
+        if offline_envelopes.is_empty() {
+            return;
+        }
+        {
+            let f = &self.uid_store.mailboxes.lock().await[&self.mailbox_hash];
+            let mut exists = f.exists.lock().unwrap();
+            let mut unseen = f.unseen.lock().unwrap();
+            for env_hash in &offline_envelopes {
+                exists.remove(*env_hash);
+                unseen.remove(*env_hash);
+            }
+        }
+        for env_hash in offline_envelopes {
+            (self.uid_store.event_consumer)(
+                self.uid_store.account_hash,
+                RefreshEvent {
+                    account_hash: self.uid_store.account_hash,
+                    mailbox_hash: self.mailbox_hash,
+                    kind: RefreshEventKind::Remove(env_hash),
+                }
+                .into(),
+            );
+        }
+    }
+
     fn max_uid(&mut self) -> Result<Option<UID>> {
         let mailbox_hash = self.mailbox_hash;
         match self.uid_store.max_uid(mailbox_hash)? {
@@ -471,3 +787,236 @@ impl FetchState {
         }
     }
 }
+
+/// Order the UIDs of `sorted`, which are sorted newest first, so that the
+/// messages of each of `threads` are next to each other, with the thread of
+/// the newest message first and parents before their replies. UIDs that are
+/// in no thread keep their place after the threaded ones. Returns the order
+/// and the UID of the message each message replies to.
+pub fn thread_order(
+    sorted: &[UID],
+    threads: &[protocol_parser::ThreadList],
+) -> (Vec<UID>, HashMap<UID, UID>) {
+    let position = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, uid)| (*uid, i))
+        .collect::<HashMap<UID, usize>>();
+    let mut parents = HashMap::default();
+    let mut threads = threads
+        .iter()
+        .filter_map(|thread| {
+            let messages = thread
+                .messages()
+                .into_iter()
+                .filter(|(uid, _)| position.contains_key(uid))
+                .collect::<Vec<(UID, Option<UID>)>>();
+            let newest = messages.iter().map(|(uid, _)| position[uid]).min()?;
+            Some((newest, messages))
+        })
+        .collect::<Vec<_>>();
+    threads.sort_by_key(|(newest, _)| *newest);
+    let mut ordered = Vec::with_capacity(sorted.len());
+    let mut seen = HashSet::with_capacity(sorted.len());
+    for (_, messages) in threads {
+        for (uid, parent) in messages {
+            if !seen.insert(uid) {
+                continue;
+            }
+            ordered.push(uid);
+            if let Some(parent) = parent {
+                parents.insert(uid, parent);
+            }
+        }
+    }
+    ordered.extend(sorted.iter().filter(|uid| !seen.contains(*uid)));
+    (ordered, parents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imap_thread_order() {
+        let (_, threads) =
+            protocol_parser::thread_results(b"* THREAD (2)(3 6 (4 23)(44 7 96))(99)\r\n").unwrap();
+        let sorted = [96, 44, 23, 7, 6, 4, 3, 2, 50];
+        let (ordered, parents) = thread_order(&sorted, &threads);
+        assert_eq!(ordered, vec![3, 6, 4, 23, 44, 7, 96, 2, 50]);
+        assert_eq!(
+            parents,
+            HashMap::from([(6, 3), (4, 6), (23, 4), (44, 6), (7, 44), (96, 7)])
+        );
+        let (ordered, parents) = thread_order(&sorted, &[]);
+        assert_eq!(ordered, sorted.to_vec());
+        assert!(parents.is_empty());
+    }
+
+    #[cfg(feature = "sqlite3")]
+    #[test]
+    fn test_imap_fetch_offline_cache() {
+        use futures::executor::block_on;
+
+        use crate::{
+            backends::{BackendEvent, IsSubscribedFn},
+            imap::sync::sqlite3_cache::Sqlite3Cache,
+        };
+
+        let tempdir = tempfile::tempdir().unwrap();
+        let removed = Arc::new(Mutex::new(vec![]));
+        let event_consumer = {
+            let removed = Arc::clone(&removed);
+            BackendEventConsumer::new(Arc::new(move |_, event| {
+                if let BackendEvent::Refresh(RefreshEvent {
+                    kind: RefreshEventKind::Remove(env_hash),
+                    ..
+                }) = event
+                {
+                    removed.lock().unwrap().push(env_hash);
+                }
+            }))
+        };
+        let uid_store = Arc::new(UIDStore::new(
+            IsSubscribedFn::default(),
+            AccountHash::from_bytes(b"test"),
+            "test".to_string().into(),
+            event_consumer,
+            None,
+            true,
+        ));
+        *uid_store.offline_cache.lock().unwrap() =
+            Some(Sqlite3Cache::get(Arc::clone(&uid_store), Some(tempdir.path())).unwrap());
+        let mailbox_hash = MailboxHash::from(b"INBOX".as_slice());
+        block_on(uid_store.mailboxes.lock()).insert(
+            mailbox_hash,
+            ImapMailbox {
+                hash: mailbox_hash,
+                imap_path: "INBOX".to_string(),
+                ..ImapMailbox::default()
+            },
+        );
+
+        // A mailbox with five cached envelopes.
+        let mut cache = Arc::clone(&uid_store);
+        cache
+            .init_mailbox(
+                mailbox_hash,
+                &SelectResponse {
+                    uidvalidity: 1,
+                    ..SelectResponse::default()
+                },
+            )
+            .unwrap();
+        let envelopes = (1..=5)
+            .map(|i| Envelope::new(EnvelopeHash(i)))
+            .collect::<Vec<Envelope>>();
+        let fetches = envelopes
+            .iter()
+            .zip(1..)
+            .map(|(env, uid)| FetchResponse {
+                uid: Some(uid),
+                message_sequence_number: uid,
+                modseq: None,
+                flags: None,
+                body: None,
+                references: None,
+                envelope: Some(env.clone()),
+                bodystructure: false,
+                gmail_labels: None,
+                gmail_thread_id: None,
+                raw_fetch_value: &[],
+            })
+            .collect::<Vec<_>>();
+        cache.insert_envelopes(mailbox_hash, &fetches).unwrap();
+        let cached = envelopes
+            .iter()
+            .map(Envelope::hash)
+            .collect::<BTreeSet<EnvelopeHash>>();
+
+        // The server is never contacted while the cache is shown.
+        let server_conf = ImapServerConf {
+            server_hostname: "localhost".to_string(),
+            server_username: "user".to_string(),
+            server_password: "password".to_string(),
+            server_port: 143,
+            use_starttls: false,
+            use_tls: false,
+            danger_accept_invalid_certs: false,
+            protocol: ImapProtocol::default(),
+            timeout: None,
+        };
+        let connection = Arc::new(ConnectionMutex::new(
+            ImapConnection::new_connection(
+                &server_conf,
+                "test".into(),
+                Arc::clone(&uid_store),
+                false,
+            ),
+            server_conf,
+            Arc::clone(&uid_store),
+            false,
+        ));
+        let mut state = FetchState {
+            stage: FetchStage::OfflineCache { max_uid: None },
+            connection,
+            mailbox_hash,
+            uid_store: Arc::clone(&uid_store),
+            batch_size: 2,
+            cache_batch_size: 2,
+            ordered: Default::default(),
+            thread_parents: Default::default(),
+            parent_uids: Default::default(),
+            parent_message_ids: Default::default(),
+            offline_envelopes: Default::default(),
+        };
+        let mut shown = BTreeSet::new();
+        let chunk = block_on(state.chunk()).unwrap();
+        assert_eq!(chunk.len(), 3);
+        assert_eq!(state.stage, FetchStage::OfflineCache { max_uid: Some(2) });
+        shown.extend(chunk.iter().map(Envelope::hash));
+        let chunk = block_on(state.chunk()).unwrap();
+        assert_eq!(chunk.len(), 2);
+        assert_eq!(state.stage, FetchStage::BackgroundResync);
+        shown.extend(chunk.iter().map(Envelope::hash));
+        assert_eq!(shown, cached);
+        assert_eq!(state.offline_envelopes, cached);
+        let exists = Arc::clone(&block_on(uid_store.mailboxes.lock())[&mailbox_hash].exists);
+        assert_eq!(exists.lock().unwrap().len(), 5);
+
+        // The cache was synced: the envelopes shown stay.
+        assert!(block_on(state.resynced(Some(vec![]))).is_some_and(|p| p.is_empty()));
+        assert_eq!(state.stage, FetchStage::Finished);
+        assert!(removed.lock().unwrap().is_empty());
+        assert_eq!(exists.lock().unwrap().len(), 5);
+
+        // The UIDVALIDITY of the mailbox changed instead, so resync reset its
+        // cache and returned nothing: the envelopes shown are removed and the
+        // mailbox is fetched from scratch.
+        state.stage = FetchStage::BackgroundResync;
+        cache
+            .init_mailbox(
+                mailbox_hash,
+                &SelectResponse {
+                    uidvalidity: 2,
+                    ..SelectResponse::default()
+                },
+            )
+            .unwrap();
+        assert!(block_on(state.resynced(None)).is_none());
+        assert_eq!(state.stage, FetchStage::InitialFresh);
+        assert!(state.offline_envelopes.is_empty());
+        assert_eq!(exists.lock().unwrap().len(), 0);
+        assert_eq!(
+            removed
+                .lock()
+                .unwrap()
+                .iter()
+                .copied()
+                .collect::<BTreeSet<EnvelopeHash>>(),
+            cached
+        );
+        assert_eq!(cache.max_uid(mailbox_hash).unwrap(), None);
+        _ = tempdir.close();
+    }
+}
@@ -19,14 +19,17 @@
  * along with meli. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use std::sync::{Arc, Mutex, RwLock};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+};
 
 use crate::{
     backends::{
         BackendMailbox, LazyCountSet, Mailbox, MailboxHash, MailboxPermissions, SpecialUsageMailbox,
     },
     error::*,
-    imap::protocol_parser::SelectResponse,
+    imap::protocol_parser::{Namespace, SelectResponse},
 };
 
 #[derive(Clone, Debug, Default)]
@@ -131,3 +134,53 @@ impl BackendMailbox for ImapMailbox {
         self
     }
 }
+
+/// Make the children of `parent` top-level mailboxes.
+pub fn promote_children(mailboxes: &mut HashMap<MailboxHash, ImapMailbox>, parent: MailboxHash) {
+    let Some(parent) = mailboxes.get_mut(&parent) else {
+        return;
+    };
+    for child in std::mem::take(&mut parent.children) {
+        if let Some(m) = mailboxes.get_mut(&child) {
+            m.parent = None;
+        }
+    }
+}
+
+impl Namespace {
+    /// If the namespace's prefix is a mailbox, such as `INBOX` for the prefix
+    /// `INBOX.`, show the mailboxes under it as top-level mailboxes.
+    pub fn apply_to_mailboxes(&self, mailboxes: &mut HashMap<MailboxHash, ImapMailbox>) {
+        let Some(delimiter) = self.delimiter else {
+            return;
+        };
+        let Some(parent_path) = self.prefix.strip_suffix(delimiter as char) else {
+            return;
+        };
+        if let Some(parent) = mailboxes
+            .values()
+            .find(|m| {
+                m.imap_path == parent_path
+                    || (m.imap_path.eq_ignore_ascii_case("INBOX")
+                        && parent_path.eq_ignore_ascii_case("INBOX"))
+            })
+            .map(|m| m.hash)
+        {
+            promote_children(mailboxes, parent);
+        }
+    }
+
+    /// Convert a mailbox path with `/` as a hierarchy delimiter to the path of
+    /// the mailbox in this namespace, e.g. `Lists/meli` to `INBOX.Lists.meli`
+    /// for the namespace prefix `INBOX.` with delimiter `.`.
+    pub fn imap_path(&self, path: &str) -> String {
+        let delimiter = self.delimiter.map(char::from).unwrap_or('/');
+        let prefix_path = self.prefix.replace(delimiter, "/");
+        let path = path.strip_prefix(prefix_path.as_str()).unwrap_or(path);
+        format!(
+            "{}{}",
+            self.prefix,
+            path.replace('/', &delimiter.to_string())
+        )
+    }
+}
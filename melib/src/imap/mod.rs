@@ -37,6 +37,8 @@ mod watch;
 pub use watch::*;
 mod search;
 pub use search::*;
+mod quirks;
+pub use quirks::*;
 pub mod email;
 pub mod error;
 pub mod fetch;
@@ -101,10 +103,14 @@ pub static SUPPORTED_CAPABILITIES: &[&str] = &[
     "LOGIN",
     "LOGINDISABLED",
     "MOVE",
+    "NAMESPACE",
     "SASL-IR",
+    "SORT",
     "SPECIAL-USE",
+    "THREAD=REFERENCES",
     "UIDPLUS",
     "UNSELECT",
+    "X-GM-EXT-1",
 ];
 
 #[derive(Debug, Default)]
@@ -183,6 +189,16 @@ pub struct UIDStore {
     pub is_online: Arc<Mutex<(SystemTime, Result<()>)>>,
     pub event_consumer: BackendEventConsumer,
     pub timeout: Option<Duration>,
+    /// Root Message-ID of each Gmail thread (`X-GM-THRID`) seen so far, see
+    /// [`UIDStore::set_gmail_thread`].
+    pub gmail_threads: Arc<Mutex<HashMap<u64, MessageID>>>,
+    /// Workarounds for the server, detected when connecting.
+    pub quirks: Arc<Mutex<ServerQuirks>>,
+    /// Response of the `NAMESPACE` command, if the server supports it.
+    pub namespace: Arc<Mutex<Option<NamespaceResponse>>>,
+    /// Additional headers fetched along with each envelope, see
+    /// [`AccountSettings::retained_headers`].
+    pub retained_headers: Vec<String>,
 }
 
 impl UIDStore {
@@ -219,6 +235,36 @@ impl UIDStore {
             ))),
             event_consumer,
             timeout,
+            gmail_threads: Default::default(),
+            quirks: Default::default(),
+            namespace: Default::default(),
+            retained_headers: vec![],
+        }
+    }
+
+    /// The first personal namespace of the server, if known.
+    pub fn personal_namespace(&self) -> Option<Namespace> {
+        self.namespace
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|n| n.personal.first().cloned())
+    }
+
+    /// Thread `env` under the root of its Gmail thread `thread_id` if it has
+    /// no `In-Reply-To` or `References` headers, so that messages Gmail
+    /// threads together are threaded together even if their senders' clients
+    /// omitted those headers.
+    pub fn set_gmail_thread(&self, env: &mut Envelope, thread_id: u64) {
+        let mut gmail_threads = self.gmail_threads.lock().unwrap();
+        let root = gmail_threads.entry(thread_id).or_insert_with(|| {
+            env.references()
+                .first()
+                .unwrap_or_else(|| env.message_id())
+                .clone()
+        });
+        if env.in_reply_to().is_none() && root != env.message_id() {
+            env.push_in_reply_to(root.clone());
         }
     }
 
@@ -253,6 +299,12 @@ pub struct ImapType {
     pub connection: Arc<ConnectionMutex>,
     pub server_conf: ImapServerConf,
     pub uid_store: Arc<UIDStore>,
+    /// Interval of polling the message counts of mailboxes that are not
+    /// loaded, see [`poll_status`].
+    pub status_poll_interval: Option<Duration>,
+    /// Interval of `NOOP` commands that watch for changes when `IDLE` is not
+    /// available, see [`poll_with_noop`].
+    pub noop_interval: Option<Duration>,
 }
 
 impl MailBackend for ImapType {
@@ -276,9 +328,12 @@ impl MailBackend for ImapType {
                     idle,
                     deflate,
                     condstore,
+                    qresync,
                     oauth2,
                     auth_anonymous,
                     id,
+                    gmail,
+                    sort,
                 },
         } = self.server_conf.protocol
         {
@@ -311,6 +366,15 @@ impl MailBackend for ImapType {
                             };
                         }
                     }
+                    "QRESYNC" => {
+                        if condstore && qresync {
+                            *status = MailBackendExtensionStatus::Enabled { comment: None };
+                        } else {
+                            *status = MailBackendExtensionStatus::Supported {
+                                comment: Some("Disabled by user configuration"),
+                            };
+                        }
+                    }
                     "AUTH=OAUTH2" => {
                         if oauth2 {
                             *status = MailBackendExtensionStatus::Enabled { comment: None };
@@ -346,6 +410,24 @@ impl MailBackend for ImapType {
                             };
                         }
                     }
+                    "X-GM-EXT-1" => {
+                        if gmail {
+                            *status = MailBackendExtensionStatus::Enabled { comment: None };
+                        } else {
+                            *status = MailBackendExtensionStatus::Supported {
+                                comment: Some("Disabled by user configuration"),
+                            };
+                        }
+                    }
+                    "SORT" | "THREAD=REFERENCES" => {
+                        if sort {
+                            *status = MailBackendExtensionStatus::Enabled { comment: None };
+                        } else {
+                            *status = MailBackendExtensionStatus::Supported {
+                                comment: Some("Disabled by user configuration"),
+                            };
+                        }
+                    }
                     "ID" => {
                         if id {
                             *status = MailBackendExtensionStatus::Enabled { comment: None };
@@ -388,12 +470,17 @@ impl MailBackend for ImapType {
 
     fn fetch(&mut self, mailbox_hash: MailboxHash) -> ResultStream<Vec<Envelope>> {
         let mut state = FetchState {
-            stage: FetchStage::ResyncCache,
+            stage: FetchStage::OfflineCache { max_uid: None },
             connection: self.connection.clone(),
             mailbox_hash,
             uid_store: self.uid_store.clone(),
             batch_size: 1500,
             cache_batch_size: 25000,
+            ordered: Default::default(),
+            thread_parents: Default::default(),
+            parent_uids: Default::default(),
+            parent_message_ids: Default::default(),
+            offline_envelopes: Default::default(),
         };
 
         Ok(Box::pin(try_fn_stream(|emitter| async move {
@@ -511,6 +598,8 @@ impl MailBackend for ImapType {
         let server_conf = self.server_conf.clone();
         let main_conn = self.connection.clone();
         let uid_store = self.uid_store.clone();
+        let status_poll_interval = self.status_poll_interval;
+        let noop_interval = self.noop_interval;
         Ok(Box::pin(try_fn_stream(|emitter| async move {
             use futures::stream::StreamExt;
 
@@ -546,6 +635,24 @@ impl MailBackend for ImapType {
                     main_conn: main_conn.clone(),
                     uid_store: uid_store.clone(),
                 })))
+            } else if let Some(interval) = noop_interval {
+                WatchKit::Poll(Box::pin(poll_with_noop(
+                    ImapWatchKit {
+                        conn: ImapConnection::new_connection(
+                            &server_conf,
+                            format!(
+                                "{}-watch-poll_with_NOOP",
+                                uid_store.account_name.as_ref().trim_at_boundary(25)
+                            )
+                            .into(),
+                            uid_store.clone(),
+                            false,
+                        ),
+                        main_conn: main_conn.clone(),
+                        uid_store: uid_store.clone(),
+                    },
+                    interval,
+                )))
             } else {
                 WatchKit::Poll(Box::pin(poll_with_examine(ImapWatchKit {
                     conn: ImapConnection::new_connection(
@@ -562,10 +669,20 @@ impl MailBackend for ImapType {
                     uid_store: uid_store.clone(),
                 })))
             };
+            let mut status_poll: BoxStream<'static, Result<BackendEvent>> =
+                if let Some(interval) = status_poll_interval {
+                    Box::pin(poll_status(main_conn.clone(), uid_store.clone(), interval))
+                } else {
+                    Box::pin(futures::stream::pending())
+                };
             while let Some(ev) = {
                 match watch_kit {
-                    WatchKit::Idle(ref mut idle) => idle.next().await,
-                    WatchKit::Poll(ref mut poll) => poll.next().await,
+                    WatchKit::Idle(ref mut idle) => {
+                        futures::stream::select(idle, &mut status_poll).next().await
+                    }
+                    WatchKit::Poll(ref mut poll) => {
+                        futures::stream::select(poll, &mut status_poll).next().await
+                    }
                 }
             } {
                 match ev {
@@ -1027,6 +1144,9 @@ impl MailBackend for ImapType {
              *
              * The default delimiter for us is '/' just like UNIX paths. I apologise if
              * this decision is unpleasant for you.
+             *
+             * If the server advertises a personal namespace with the NAMESPACE command,
+             * its prefix and delimiter are used instead.
              */
 
             {
@@ -1037,18 +1157,25 @@ impl MailBackend for ImapType {
                         "Mailbox named `{path}` already exists.",
                     )));
                 }
-                for root_mailbox in mailboxes.values().filter(|f| f.parent.is_none()) {
-                    if path.starts_with(&root_mailbox.name) {
-                        log::trace!(
-                            "{} path starts with {:?}",
-                            uid_store.account_name,
-                            &root_mailbox
-                        );
-                        path = path.replace(
-                            '/',
-                            (root_mailbox.separator as char).encode_utf8(&mut [0; 4]),
-                        );
-                        break;
+                let namespace = uid_store
+                    .personal_namespace()
+                    .filter(|n| !n.prefix.is_empty() || n.delimiter.is_some());
+                if let Some(namespace) = namespace {
+                    path = namespace.imap_path(&path);
+                } else {
+                    for root_mailbox in mailboxes.values().filter(|f| f.parent.is_none()) {
+                        if path.starts_with(&root_mailbox.name) {
+                            log::trace!(
+                                "{} path starts with {:?}",
+                                uid_store.account_name,
+                                &root_mailbox
+                            );
+                            path = path.replace(
+                                '/',
+                                (root_mailbox.separator as char).encode_utf8(&mut [0; 4]),
+                            );
+                            break;
+                        }
                     }
                 }
 
@@ -1349,6 +1476,18 @@ impl ImapType {
             Some(Duration::from_secs(timeout))
         };
         let use_connection_pool = get_conf_val!(s["use_connection_pool"], true)?;
+        let status_poll_interval = get_conf_val!(s["status_poll_interval"], 5 * 60_u64)?;
+        let status_poll_interval = if status_poll_interval == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(status_poll_interval))
+        };
+        let noop_interval = get_conf_val!(s["noop_interval"], 60_u64)?;
+        let noop_interval = if noop_interval == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(noop_interval))
+        };
         let server_conf = ImapServerConf {
             server_hostname: server_hostname.to_string(),
             server_username: server_username.to_string(),
@@ -1361,10 +1500,13 @@ impl ImapType {
                 extension_use: ImapExtensionUse {
                     idle: get_conf_val!(s["use_idle"], true)?,
                     condstore: get_conf_val!(s["use_condstore"], true)?,
+                    qresync: get_conf_val!(s["use_qresync"], true)?,
                     deflate: get_conf_val!(s["use_deflate"], true)?,
                     oauth2: use_oauth2,
                     auth_anonymous: get_conf_val!(s["use_auth_anonymous"], false)?,
                     id: get_conf_val!(s["use_id"], false)?,
+                    gmail: get_conf_val!(s["use_gmail_extensions"], true)?,
+                    sort: get_conf_val!(s["use_sort"], false)?,
                 },
             },
             timeout,
@@ -1373,6 +1515,7 @@ impl ImapType {
         let account_name = s.name.to_string().into();
         let uid_store: Arc<UIDStore> = Arc::new(UIDStore {
             offline_cache: Arc::new(Mutex::new(None)),
+            retained_headers: s.retained_headers.clone(),
             ..UIDStore::new(
                 is_subscribed,
                 account_hash,
@@ -1402,6 +1545,8 @@ impl ImapType {
             )),
             server_conf,
             uid_store,
+            status_poll_interval,
+            noop_interval,
         }))
     }
 
@@ -1566,6 +1711,11 @@ impl ImapType {
                 imap_log!(trace, conn, "parse error for {:?}", l);
             }
         }
+        let quirks = *conn.uid_store.quirks.lock().unwrap();
+        quirks.apply_to_mailboxes(&mut mailboxes);
+        if let Some(namespace) = conn.uid_store.personal_namespace() {
+            namespace.apply_to_mailboxes(&mut mailboxes);
+        }
         Ok(mailboxes)
     }
 
@@ -1652,11 +1802,16 @@ impl ImapType {
         }
         get_conf_val!(s["use_idle"], true)?;
         get_conf_val!(s["use_condstore"], true)?;
+        get_conf_val!(s["use_qresync"], true)?;
         get_conf_val!(s["use_deflate"], true)?;
         get_conf_val!(s["use_auth_anonymous"], false)?;
         get_conf_val!(s["use_id"], false)?;
+        get_conf_val!(s["use_gmail_extensions"], true)?;
+        get_conf_val!(s["use_sort"], false)?;
         let _timeout = get_conf_val!(s["timeout"], 16_u64)?;
         get_conf_val!(s["use_connection_pool"], true)?;
+        let _status_poll_interval = get_conf_val!(s["status_poll_interval"], 5 * 60_u64)?;
+        let _noop_interval = get_conf_val!(s["noop_interval"], 60_u64)?;
         let extra_keys = s
             .extra
             .keys()
@@ -34,7 +34,7 @@ use nom::{
     character::{complete::digit1, is_digit},
     combinator::{map, map_res, opt},
     multi::{fold_many1, length_data, many0, many1, separated_list1},
-    sequence::{delimited, preceded},
+    sequence::{delimited, preceded, terminated},
 };
 
 use super::*;
@@ -100,6 +100,13 @@ bitflags! {
         const FETCH_BODYSTRUCTURE = 0b0100_0000_0000_0000_0000;
         /// Require an *untagged* `FETCH` response with a `ENVELOPE` item included.
         const FETCH_ENVELOPE      = 0b1000_0000_0000_0000_0000;
+        /// Require an *untagged* `SORT` response as part of a `SORT` response.
+        const SORT                = 0b0001_0000_0000_0000_0000_0000;
+        /// Require an *untagged* `THREAD` response as part of a `THREAD` response.
+        const THREAD              = 0b0010_0000_0000_0000_0000_0000;
+        /// Require an *untagged* `VANISHED` response as part of a `SELECT` response with `QRESYNC`
+        /// parameters.
+        const VANISHED            = 0b0100_0000_0000_0000_0000_0000;
         /// Require any `SELECT` related reponse.
         const SELECT              = Self::FLAGS.bits() | Self::EXISTS.bits() | Self::RECENT.bits() | Self::UNSEEN.bits() | Self::PERMANENTFLAGS.bits() | Self::UIDNEXT.bits() | Self::UIDVALIDITY.bits();
         /// Require any `EXAMINE` related reponse.
@@ -138,6 +145,9 @@ impl RequiredResponses {
             Self::LSUB => stripped.starts_with(b"LSUB"),
             Self::STATUS => stripped.starts_with(b"STATUS"),
             Self::SEARCH => stripped.starts_with(b"SEARCH"),
+            Self::SORT => stripped.starts_with(b"SORT"),
+            Self::THREAD => stripped.starts_with(b"THREAD"),
+            Self::VANISHED => stripped.starts_with(b"VANISHED "),
         };
         if !self.intersects(
             Self::FETCH_UID
@@ -546,6 +556,10 @@ pub struct FetchResponse<'a> {
     pub references: Option<&'a [u8]>,
     pub envelope: Option<Envelope>,
     pub bodystructure: bool,
+    /// User labels of the message on Gmail servers (`X-GM-LABELS`).
+    pub gmail_labels: Option<Vec<String>>,
+    /// Thread id of the message on Gmail servers (`X-GM-THRID`).
+    pub gmail_thread_id: Option<u64>,
     pub raw_fetch_value: &'a [u8],
 }
 
@@ -623,6 +637,8 @@ pub fn fetch_response(input: &[u8]) -> ImapParseResult<'_, FetchResponse<'_>> {
         references: None,
         envelope: None,
         bodystructure: false,
+        gmail_labels: None,
+        gmail_thread_id: None,
         raw_fetch_value: &[],
     };
 
@@ -638,6 +654,8 @@ pub fn fetch_response(input: &[u8]) -> ImapParseResult<'_, FetchResponse<'_>> {
     should_start_with!(&input[i..], b"FETCH (");
     i += b"FETCH (".len();
     let mut has_attachments = false;
+    let mut size = None;
+    let mut header_fields: Vec<(HeaderName, &[u8])> = vec![];
     while i < input.len() {
         eat_whitespace!(break);
         bounds!(break);
@@ -726,6 +744,28 @@ pub fn fetch_response(input: &[u8]) -> ImapParseResult<'_, FetchResponse<'_>> {
                         .trim_at_boundary(40)
                 )));
             }
+        } else if input[i..].starts_with(b"RFC822.SIZE ") {
+            i += b"RFC822.SIZE ".len();
+            if let Ok((rest, val)) = map_res(digit1, |s| {
+                usize::from_str(unsafe { std::str::from_utf8_unchecked(s) })
+            })(&input[i..])
+            {
+                size = Some(val);
+                i += input.len() - i - rest.len();
+            } else {
+                log::debug!(
+                    "Unexpected input while parsing UID FETCH response. Could not parse \
+                     RFC822.SIZE: {}",
+                    String::from_utf8_lossy(&input[i..])
+                );
+                return Err(Error::new(format!(
+                    "Unexpected input while parsing UID FETCH response. Could not parse \
+                     RFC822.SIZE: {}",
+                    String::from_utf8_lossy(&input[i..])
+                        .as_ref()
+                        .trim_at_boundary(40)
+                )));
+            }
         } else if input[i..].starts_with(b"ENVELOPE (") {
             i += b"ENVELOPE ".len();
             if let Ok((rest, envelope)) = envelope(&input[i..]) {
@@ -802,6 +842,85 @@ pub fn fetch_response(input: &[u8]) -> ImapParseResult<'_, FetchResponse<'_>> {
                         .trim_at_boundary(40)
                 )));
             }
+        } else if input[i..].starts_with(b"BODY[HEADER.FIELDS (") {
+            // References along with the account's `retained_headers`.
+            let Some(pos) = input[i..].windows(b")] ".len()).position(|w| w == b")] ") else {
+                return Err(Error::new(format!(
+                    "Unexpected input while parsing UID FETCH response. Could not parse \
+                     BODY[HEADER.FIELDS: {}",
+                    String::from_utf8_lossy(&input[i..])
+                        .as_ref()
+                        .trim_at_boundary(40)
+                )));
+            };
+            i += pos + b")] ".len();
+            if let Ok((rest, block)) = astring_token(&input[i..]) {
+                ret.references = Some(&[]);
+                if let Ok((_, headers)) = crate::email::parser::headers::headers(block) {
+                    for (name, value) in headers {
+                        if name == HeaderName::REFERENCES {
+                            ret.references = Some(value);
+                        } else {
+                            header_fields.push((name, value));
+                        }
+                    }
+                }
+                i += input.len() - i - rest.len();
+            } else {
+                log::debug!(
+                    "Unexpected input while parsing UID FETCH response. Could not parse \
+                     BODY[HEADER.FIELDS: {}",
+                    String::from_utf8_lossy(&input[i..])
+                );
+                return Err(Error::new(format!(
+                    "Unexpected input while parsing UID FETCH response. Could not parse \
+                     BODY[HEADER.FIELDS: {}",
+                    String::from_utf8_lossy(&input[i..])
+                        .as_ref()
+                        .trim_at_boundary(40)
+                )));
+            }
+        } else if input[i..].starts_with(b"X-GM-LABELS (") {
+            i += b"X-GM-LABELS (".len();
+            if let Ok((rest, labels)) = gmail_labels(&input[i..]) {
+                ret.gmail_labels = Some(labels);
+                i += input.len() - i - rest.len();
+            } else {
+                log::debug!(
+                    "Unexpected input while parsing UID FETCH response. Could not parse \
+                     X-GM-LABELS: {}",
+                    String::from_utf8_lossy(&input[i..])
+                );
+                return Err(Error::new(format!(
+                    "Unexpected input while parsing UID FETCH response. Could not parse \
+                     X-GM-LABELS: {}",
+                    String::from_utf8_lossy(&input[i..])
+                        .as_ref()
+                        .trim_at_boundary(40)
+                )));
+            }
+        } else if input[i..].starts_with(b"X-GM-THRID ") {
+            i += b"X-GM-THRID ".len();
+            if let Ok((rest, thread_id)) = map_res(digit1, |s| {
+                u64::from_str(unsafe { std::str::from_utf8_unchecked(s) })
+            })(&input[i..])
+            {
+                ret.gmail_thread_id = Some(thread_id);
+                i += input.len() - i - rest.len();
+            } else {
+                log::debug!(
+                    "Unexpected input while parsing UID FETCH response. Could not parse \
+                     X-GM-THRID: {}",
+                    String::from_utf8_lossy(&input[i..])
+                );
+                return Err(Error::new(format!(
+                    "Unexpected input while parsing UID FETCH response. Could not parse \
+                     X-GM-THRID: {}",
+                    String::from_utf8_lossy(&input[i..])
+                        .as_ref()
+                        .trim_at_boundary(40)
+                )));
+            }
         } else if input[i..].starts_with(b")\r\n") {
             i += b")\r\n".len();
             break;
@@ -822,6 +941,18 @@ pub fn fetch_response(input: &[u8]) -> ImapParseResult<'_, FetchResponse<'_>> {
 
     if let Some(env) = ret.envelope.as_mut() {
         env.set_has_attachments(has_attachments);
+        if let Some(size) = size {
+            env.set_size(size);
+        }
+        for (name, value) in header_fields {
+            let value = crate::email::parser::encodings::phrase(value, false)
+                .map(|(_, value)| {
+                    String::from_utf8(value)
+                        .unwrap_or_else(|err| String::from_utf8_lossy(&err.into_bytes()).into())
+                })
+                .unwrap_or_else(|_| String::from_utf8_lossy(value).into());
+            env.other_headers_mut().insert(name, value);
+        }
     }
 
     Ok((&input[i..], ret, None))
@@ -977,6 +1108,9 @@ pub enum UntaggedResponse<'s> {
     /// ```
     Recent(ImapNum),
     Fetch(Box<FetchResponse<'s>>),
+    /// `VANISHED` response, sent instead of `EXPUNGE` once `QRESYNC` is
+    /// enabled. See [`vanished_response`].
+    Vanished(VanishedResponse),
     Bye {
         reason: &'s str,
     },
@@ -984,6 +1118,10 @@ pub enum UntaggedResponse<'s> {
 
 pub fn untagged_responses(input: &[u8]) -> ImapParseResult<'_, Option<UntaggedResponse<'_>>> {
     let orig_input = input;
+    if input.starts_with(b"* VANISHED ") {
+        let (input, vanished) = vanished_response(input)?;
+        return Ok((input, Some(UntaggedResponse::Vanished(vanished)), None));
+    }
     let (input, _) = tag::<_, &[u8], (&[u8], nom::error::ErrorKind)>(UNTAGGED_PREFIX)(input)?;
     let (input, num) = map_res::<_, _, _, (&[u8], nom::error::ErrorKind), _, _, _>(digit1, |s| {
         ImapNum::from_str(unsafe { std::str::from_utf8_unchecked(s) })
@@ -1056,6 +1194,82 @@ pub fn search_results_raw<'a>(input: &'a [u8]) -> IResult<&'a [u8], &'a [u8]> {
     ))(input)
 }
 
+/// Parse the untagged response of a `SORT` command ([RFC5256]), the numbers
+/// of the messages in the requested order.
+///
+/// [RFC5256]: https://www.rfc-editor.org/rfc/rfc5256
+pub fn sort_results<'a>(input: &'a [u8]) -> IResult<&'a [u8], Vec<ImapNum>> {
+    alt((
+        |input: &'a [u8]| -> IResult<&'a [u8], Vec<ImapNum>> {
+            let (input, _) = tag("* SORT ")(input)?;
+            let (input, list) = separated_list1(
+                tag(b" "),
+                map_res(is_not(" \r\n"), |s: &[u8]| {
+                    ImapNum::from_str(unsafe { std::str::from_utf8_unchecked(s) })
+                }),
+            )(input)?;
+            let (input, _) = tag(CRLF)(input)?;
+            Ok((input, list))
+        },
+        |input: &'a [u8]| -> IResult<&'a [u8], Vec<ImapNum>> {
+            let (input, _) = tag("* SORT\r\n")(input)?;
+            Ok((input, vec![]))
+        },
+    ))(input)
+}
+
+/// A thread of a `THREAD` response, see [`thread_results`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ThreadList {
+    /// Messages that each reply to the previous one. Empty if the first
+    /// message of the thread is missing, in which case the `children` are
+    /// siblings.
+    pub members: Vec<ImapNum>,
+    /// The threads of the replies to the last member.
+    pub children: Vec<ThreadList>,
+}
+
+impl ThreadList {
+    /// The messages of the thread in depth-first order, each with the
+    /// message it replies to, if any.
+    pub fn messages(&self) -> Vec<(ImapNum, Option<ImapNum>)> {
+        let mut ret = vec![];
+        let mut stack = vec![(self, None)];
+        while let Some((list, mut parent)) = stack.pop() {
+            for &member in &list.members {
+                ret.push((member, parent));
+                parent = Some(member);
+            }
+            stack.extend(list.children.iter().rev().map(|child| (child, parent)));
+        }
+        ret
+    }
+}
+
+fn thread_list(input: &[u8]) -> IResult<&[u8], ThreadList> {
+    let (input, _) = tag("(")(input)?;
+    let (input, members) = many0(terminated(
+        map_res(digit1, |s: &[u8]| {
+            ImapNum::from_str(unsafe { std::str::from_utf8_unchecked(s) })
+        }),
+        opt(tag(" ")),
+    ))(input)?;
+    let (input, children) = many0(terminated(thread_list, opt(tag(" "))))(input)?;
+    let (input, _) = tag(")")(input)?;
+    Ok((input, ThreadList { members, children }))
+}
+
+/// Parse the untagged response of a `THREAD` command ([RFC5256]).
+///
+/// [RFC5256]: https://www.rfc-editor.org/rfc/rfc5256
+pub fn thread_results(input: &[u8]) -> IResult<&[u8], Vec<ThreadList>> {
+    let (input, _) = tag("* THREAD")(input)?;
+    let (input, _) = opt(tag(" "))(input)?;
+    let (input, list) = many0(terminated(thread_list, opt(tag(" "))))(input)?;
+    let (input, _) = tag(CRLF)(input)?;
+    Ok((input, list))
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct SelectResponse {
     pub exists: ImapNum,
@@ -1071,6 +1285,55 @@ pub struct SelectResponse {
     pub highestmodseq: Option<std::result::Result<ModSequence, ()>>,
 }
 
+/// A `VANISHED` response ([RFC7162] Section 3.2.10).
+///
+/// [RFC7162]: https://www.rfc-editor.org/rfc/rfc7162
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VanishedResponse {
+    /// Whether the response is tagged `(EARLIER)`, i.e. it reports messages
+    /// that were expunged before the mailbox was selected.
+    pub earlier: bool,
+    /// Inclusive ranges of the expunged UIDs.
+    pub uids: Vec<(UID, UID)>,
+}
+
+impl VanishedResponse {
+    pub fn contains(&self, uid: UID) -> bool {
+        self.uids
+            .iter()
+            .any(|&(low, high)| (low..=high).contains(&uid))
+    }
+}
+
+/// Parse a `VANISHED` response, e.g. `* VANISHED (EARLIER) 41,43:116\r\n`.
+pub fn vanished_response(input: &[u8]) -> IResult<&[u8], VanishedResponse> {
+    let (input, _) = tag("* VANISHED ")(input)?;
+    let (input, earlier) = opt(tag("(EARLIER) "))(input)?;
+    let (input, uids) = separated_list1(tag(","), uid_range)(input)?;
+    let (input, _) = tag(CRLF)(input)?;
+    Ok((
+        input,
+        VanishedResponse {
+            earlier: earlier.is_some(),
+            uids,
+        },
+    ))
+}
+
+fn uid_range(input: &[u8]) -> IResult<&[u8], (UID, UID)> {
+    let (input, low) = map_res(digit1, |s: &[u8]| {
+        UID::from_str(unsafe { std::str::from_utf8_unchecked(s) })
+    })(input)?;
+    let (input, high) = opt(preceded(
+        tag(":"),
+        map_res(digit1, |s: &[u8]| {
+            UID::from_str(unsafe { std::str::from_utf8_unchecked(s) })
+        }),
+    ))(input)?;
+    let high = high.unwrap_or(low);
+    Ok((input, (low.min(high), low.max(high))))
+}
+
 /*
  *  Example: C: A142 SELECT INBOX
  *           S: * 172 EXISTS
@@ -1199,6 +1462,44 @@ pub fn flags(input: &[u8]) -> IResult<&[u8], (Flag, Vec<String>)> {
     Ok((input, (ret, keywords)))
 }
 
+/// Parse the list of a Gmail `X-GM-LABELS` FETCH item, after its opening
+/// parenthesis.
+///
+/// System labels such as `\Inbox` or `\Important` are skipped since they
+/// correspond to mailboxes and flags. User labels are decoded from modified
+/// UTF-7.
+pub fn gmail_labels(input: &[u8]) -> IResult<&[u8], Vec<String>> {
+    let mut labels = Vec::new();
+    let mut input = input.trim_start();
+    loop {
+        if let Some(rest) = input.strip_prefix(b")") {
+            return Ok((rest, labels));
+        }
+        if input.is_empty() {
+            return Err(nom::Err::Error((input, "gmail_labels(): EOF").into()));
+        }
+        let (rest, label) = if input.starts_with(b"\\") {
+            let end = input
+                .iter()
+                .position(|&b| b == b' ' || b == b')')
+                .unwrap_or(input.len());
+            (&input[end..], None)
+        } else if input.starts_with(b"\"") || input.starts_with(b"{") {
+            let (rest, label) = quoted(input)?;
+            (rest, Some(label))
+        } else {
+            let (rest, label) = astring_char(input)?;
+            (rest, Some(label.to_vec()))
+        };
+        if let Some(label) = label.filter(|l| !l.starts_with(b"\\")) {
+            labels.push(crate::backends::utf7::decode_utf7_imap(
+                &String::from_utf8_lossy(&label),
+            ));
+        }
+        input = rest.trim_start();
+    }
+}
+
 pub fn byte_flags(input: &[u8]) -> IResult<&[u8], (Flag, Vec<String>)> {
     match flags(input) {
         Ok((rest, ret)) => Ok((rest, ret)),
@@ -1367,24 +1668,25 @@ pub fn envelope_address(input: &[u8]) -> IResult<&[u8], Address> {
     Ok((
         input,
         Address::Mailbox(MailboxAddress {
-            raw: if let Some(host_name) = host_name.as_ref() {
-                format!(
-                    "{}{}<{}@{}>",
-                    to_str!(&name),
-                    if name.is_empty() { "" } else { " " },
-                    to_str!(&mailbox_name),
-                    to_str!(host_name)
-                )
-                .into_bytes()
-            } else {
-                format!(
-                    "{}{}{}",
-                    to_str!(&name),
-                    if name.is_empty() { "" } else { " " },
-                    to_str!(&mailbox_name),
-                )
-                .into_bytes()
-            },
+            raw: crate::utils::intern::intern_bytes(
+                if let Some(host_name) = host_name.as_ref() {
+                    format!(
+                        "{}{}<{}@{}>",
+                        to_str!(&name),
+                        if name.is_empty() { "" } else { " " },
+                        to_str!(&mailbox_name),
+                        to_str!(host_name)
+                    )
+                } else {
+                    format!(
+                        "{}{}{}",
+                        to_str!(&name),
+                        if name.is_empty() { "" } else { " " },
+                        to_str!(&mailbox_name),
+                    )
+                }
+                .as_bytes(),
+            ),
             display_name: str_builder!(0, name.len()),
             address_spec: if let Some(host_name) = host_name.as_ref() {
                 str_builder!(
@@ -1547,6 +1849,98 @@ fn eat_whitespace(mut input: &[u8]) -> IResult<&[u8], ()> {
     Ok((input, ()))
 }
 
+/// A namespace of a `NAMESPACE` response.
+///
+/// See [RFC2342](https://datatracker.ietf.org/doc/rfc2342/).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Namespace {
+    /// Prefix of the mailbox paths in the namespace, e.g. `INBOX.`.
+    pub prefix: String,
+    /// Hierarchy delimiter of the namespace, if it has a hierarchy.
+    pub delimiter: Option<u8>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NamespaceResponse {
+    pub personal: Vec<Namespace>,
+    pub other_users: Vec<Namespace>,
+    pub shared: Vec<Namespace>,
+}
+
+// namespace-response = "*" SP "NAMESPACE" SP namespace SP namespace SP namespace
+// namespace = nil / "(" 1*namespace-descr ")"
+// namespace-descr = "(" string SP (DQUOTE QUOTED-CHAR DQUOTE / nil)
+//                   *(namespace-response-extension) ")"
+//* NAMESPACE (("" "/")) (("~" "/")) NIL
+pub fn namespace_response(input: &[u8]) -> IResult<&[u8], NamespaceResponse> {
+    fn namespaces(input: &[u8]) -> IResult<&[u8], Vec<Namespace>> {
+        if let Some(rest) = input.strip_prefix(b"NIL") {
+            return Ok((rest, vec![]));
+        }
+        let (mut input, _) = tag("(")(input)?;
+        let mut ret = vec![];
+        while !input.starts_with(b")") {
+            let (rest, _) = tag("(")(input)?;
+            let (rest, prefix) = string_token(rest)?;
+            let (rest, _) = tag(" ")(rest)?;
+            let (rest, delimiter) = if let Some(rest) = rest.strip_prefix(b"NIL") {
+                (rest, None)
+            } else if let Some(rest) = rest.strip_prefix(b"\"\\\\\"") {
+                (rest, Some(b'\\'))
+            } else {
+                let (rest, delimiter) = delimited(tag("\""), take(1_u32), tag("\""))(rest)?;
+                (rest, Some(delimiter[0]))
+            };
+            // Skip namespace response extensions.
+            let mut depth = 0;
+            let mut in_quotes = false;
+            let mut escape_next = false;
+            let mut end = None;
+            for (i, &b) in rest.iter().enumerate() {
+                match (b, in_quotes, escape_next) {
+                    (_, true, true) => escape_next = false,
+                    (b'\\', true, false) => escape_next = true,
+                    (b'"', _, _) => in_quotes = !in_quotes,
+                    (b'(', false, _) => depth += 1,
+                    (b')', false, _) if depth == 0 => {
+                        end = Some(i);
+                        break;
+                    }
+                    (b')', false, _) => depth -= 1,
+                    _ => {}
+                }
+            }
+            let Some(end) = end else {
+                return Err(nom::Err::Error(
+                    (rest, "namespace_response(): unterminated namespace").into(),
+                ));
+            };
+            ret.push(Namespace {
+                prefix: String::from_utf8_lossy(prefix).to_string(),
+                delimiter,
+            });
+            input = &rest[end + 1..];
+        }
+        Ok((&input[1..], ret))
+    }
+
+    let (input, _) = tag("* NAMESPACE ")(input.ltrim())?;
+    let (input, personal) = namespaces(input)?;
+    let (input, _) = tag(" ")(input)?;
+    let (input, other_users) = namespaces(input)?;
+    let (input, _) = tag(" ")(input)?;
+    let (input, shared) = namespaces(input)?;
+    let (input, _) = tag(CRLF)(input)?;
+    Ok((
+        input,
+        NamespaceResponse {
+            personal,
+            other_users,
+            shared,
+        },
+    ))
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct StatusResponse {
     pub mailbox: Option<MailboxHash>,
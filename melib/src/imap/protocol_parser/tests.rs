@@ -252,6 +252,8 @@ fn test_imap_untagged_responses() {
             references: None,
             envelope: None,
             bodystructure: false,
+            gmail_labels: None,
+            gmail_thread_id: None,
             raw_fetch_value: &b"* 1079 FETCH (UID 1103 MODSEQ (1365) FLAGS (\\Seen))\r\n"[..],
         }))
     );
@@ -269,11 +271,63 @@ fn test_imap_untagged_responses() {
             references: None,
             envelope: None,
             bodystructure: false,
+            gmail_labels: None,
+            gmail_thread_id: None,
             raw_fetch_value: &b"* 1 FETCH (FLAGS (\\Seen))\r\n"[..],
         }))
     );
 }
 
+#[test]
+fn test_imap_vanished_response() {
+    // Example from RFC7162 Section 3.2.5.2
+    let response = &b"* VANISHED (EARLIER) 41,43:116,118,120:211,214:540\r\n"[..];
+    let (rest, vanished) = vanished_response(response).unwrap();
+    assert!(rest.is_empty());
+    assert!(vanished.earlier);
+    assert_eq!(
+        vanished.uids,
+        vec![(41, 41), (43, 116), (118, 118), (120, 211), (214, 540)]
+    );
+    assert!(vanished.contains(41));
+    assert!(!vanished.contains(42));
+    assert!(vanished.contains(100));
+    assert!(!vanished.contains(119));
+    assert!(vanished.contains(540));
+    assert!(!vanished.contains(541));
+    assert!(RequiredResponses::VANISHED.check(response));
+    assert!(!RequiredResponses::SELECT.check(response));
+
+    assert_eq!(
+        untagged_responses(b"* VANISHED 405,407:406\r\n")
+            .map(|(_, v, _)| v)
+            .unwrap()
+            .unwrap(),
+        UntaggedResponse::Vanished(VanishedResponse {
+            earlier: false,
+            uids: vec![(405, 405), (406, 407)],
+        })
+    );
+    assert!(vanished_response(b"* VANISHED (EARLIER)\r\n").is_err());
+
+    let response = b"* OK [CLOSED]\r\n* 100 EXISTS\r\n* 11 RECENT\r\n* OK [UIDVALIDITY \
+                     67890007] UIDVALIDITY\r\n* OK [UIDNEXT 600] Predicted next UID\r\n* OK \
+                     [HIGHESTMODSEQ 90060115205545359] Highest mailbox mod-sequence\r\n* \
+                     VANISHED (EARLIER) 41,43:116\r\n* 49 FETCH (UID 117 FLAGS (\\Seen \
+                     \\Answered) MODSEQ (90060115194045001))\r\nA02 OK [READ-WRITE] Sorry, \
+                     UIDVALIDITY mismatch\r\n";
+    let select_response = select_response(response).unwrap();
+    assert_eq!(select_response.exists, 100);
+    assert_eq!(select_response.uidvalidity, 67890007);
+    assert_eq!(select_response.uidnext, 600);
+    assert_eq!(
+        select_response.highestmodseq,
+        Some(Ok(ModSequence(
+            std::num::NonZeroU64::new(90060115205545359).unwrap()
+        )))
+    );
+}
+
 #[test]
 fn test_imap_fetch_response() {
     #[rustfmt::skip]
@@ -299,6 +353,8 @@ fn test_imap_fetch_response() {
                 references: Some(b""),
                 envelope: Some(env),
                 bodystructure: true,
+                gmail_labels: None,
+                gmail_thread_id: None,
                 raw_fetch_value: input,
             },
             None
@@ -306,6 +362,55 @@ fn test_imap_fetch_response() {
     );
 }
 
+#[test]
+fn test_imap_fetch_response_size() {
+    #[rustfmt::skip]
+    let input: &[u8] = b"* 23 FETCH (UID 23 RFC822.SIZE 44827 ENVELOPE (\"Fri, 24 Jun 2011 10:09:10 +0000\" \"xxxx/xxxx\" ((\"xx@xx.com\" NIL \"xx\" \"xx.com\")) NIL NIL NIL NIL NIL NIL \"<xx@xx.com>\"))\r\n";
+    let (rest, response, _) = fetch_response(input).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(response.uid, Some(23));
+    assert_eq!(response.envelope.unwrap().size(), 44827);
+}
+
+#[test]
+fn test_imap_fetch_response_retained_headers() {
+    #[rustfmt::skip]
+    let input: &[u8] = b"* 23 FETCH (UID 23 ENVELOPE (\"Fri, 24 Jun 2011 10:09:10 +0000\" \"xxxx/xxxx\" ((\"xx@xx.com\" NIL \"xx\" \"xx.com\")) NIL NIL NIL NIL NIL NIL \"<xx@xx.com>\") BODY[HEADER.FIELDS (REFERENCES X-SPAM-STATUS DELIVERED-TO)] {52}\r\nReferences: <a@b>\r\nX-Spam-Status: No, score=-1.0\r\n\r\n)\r\n";
+    let (rest, response, _) = fetch_response(input).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(
+        response.references.map(<[u8]>::trim_ascii),
+        Some(&b"<a@b>"[..])
+    );
+    let env = response.envelope.unwrap();
+    assert_eq!(
+        env.other_headers().get("X-Spam-Status"),
+        Some("No, score=-1.0")
+    );
+    assert_eq!(env.other_headers().get("Delivered-To"), None);
+}
+
+#[test]
+fn test_imap_fetch_response_gmail() {
+    #[rustfmt::skip]
+    let input: &[u8] = b"* 12 FETCH (X-GM-THRID 1278455344230334865 UID 9 X-GM-LABELS (\\Inbox \\Sent \"\\\\Important\" work \"Muy Importante\" &BB4EQgQ,BEAEMAQyBDsENQQ9BD0ESwQ1-))\r\n";
+    let (rest, response, _) = fetch_response(input).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(response.uid, Some(9));
+    assert_eq!(response.gmail_thread_id, Some(1278455344230334865));
+    assert_eq!(
+        response.gmail_labels.unwrap(),
+        vec![
+            "work".to_string(),
+            "Muy Importante".to_string(),
+            "Отправленные".to_string()
+        ]
+    );
+    let (rest, response, _) = fetch_response(b"* 1 FETCH (UID 1 X-GM-LABELS ())\r\n").unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(response.gmail_labels, Some(vec![]));
+}
+
 #[test]
 fn test_imap_search() {
     assert_eq!(search_results(b"* SEARCH\r\n").map(|(_, v)| v), Ok(vec![]));
@@ -323,6 +428,36 @@ fn test_imap_search() {
     );
 }
 
+#[test]
+fn test_imap_sort_thread() {
+    assert_eq!(sort_results(b"* SORT\r\n").map(|(_, v)| v), Ok(vec![]));
+    assert_eq!(
+        sort_results(b"* SORT 2 84 882\r\n").map(|(_, v)| v),
+        Ok(vec![2, 84, 882])
+    );
+    assert_eq!(thread_results(b"* THREAD\r\n").map(|(_, v)| v), Ok(vec![]));
+    // Examples from RFC5256.
+    let (rest, threads) = thread_results(b"* THREAD (2)(3 6 (4 23)(44 7 96))\r\n").unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(threads.len(), 2);
+    assert_eq!(threads[0].messages(), vec![(2, None)]);
+    assert_eq!(
+        threads[1].messages(),
+        vec![
+            (3, None),
+            (6, Some(3)),
+            (4, Some(6)),
+            (23, Some(4)),
+            (44, Some(6)),
+            (7, Some(44)),
+            (96, Some(7)),
+        ]
+    );
+    let (_, threads) = thread_results(b"* THREAD ((3)(5))\r\n").unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0].messages(), vec![(3, None), (5, None)]);
+}
+
 #[test]
 fn test_imap_select_response() {
     let r = b"* FLAGS (\\Answered \\Flagged \\Deleted \\Seen \\Draft)\r\n* OK [PERMANENTFLAGS (\\Answered \\Flagged \\Deleted \\Seen \\Draft \\*)] Flags permitted.\r\n* 45 EXISTS\r\n* 0 RECENT\r\n* OK [UNSEEN 16] First unseen.\r\n* OK [UIDVALIDITY 1554422056] UIDs valid\r\n* OK [UIDNEXT 50] Predicted next UID\r\n";
@@ -397,3 +532,68 @@ fn test_imap_envelope() {
     let input: &[u8] = b"(\"Fri, 24 Jun 2011 10:09:10 +0000\" \"xxxx/xxxx\" ((\"xx@xx.com\" NIL \"xx\" \"xx.com\")) NIL NIL ((\"xx@xx\" NIL \"xx\" \"xx.com\")) ((\"'xx, xx'\" NIL \"xx.xx\" \"xx.com\") (\"xx.xx@xx.com\" NIL \"xx.xx\" \"xx.com\") (\"'xx'\" NIL \"xx.xx\" \"xx.com\") (\"'xx xx'\" NIL \"xx.xx\" \"xx.com\") (\"xx.xx@xx.com\" NIL \"xx.xx\" \"xx.com\")) NIL NIL \"<xx@xx.com>\")";
     _ = envelope(input).unwrap();
 }
+
+#[test]
+fn test_imap_namespace_response() {
+    #[rustfmt::skip]
+    let input: &[u8] = b"* NAMESPACE ((\"INBOX.\" \".\")) NIL ((\"#shared/\" \"/\" \"X-PARAM\" (\"FLAG1\" \"FLAG2\"))(\"#flat\" NIL))\r\n";
+    let (rest, response) = namespace_response(input).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(
+        response,
+        NamespaceResponse {
+            personal: vec![Namespace {
+                prefix: "INBOX.".to_string(),
+                delimiter: Some(b'.'),
+            }],
+            other_users: vec![],
+            shared: vec![
+                Namespace {
+                    prefix: "#shared/".to_string(),
+                    delimiter: Some(b'/'),
+                },
+                Namespace {
+                    prefix: "#flat".to_string(),
+                    delimiter: None,
+                },
+            ],
+        }
+    );
+    let (_, response) = namespace_response(b"* NAMESPACE ((\"\" \"/\")) NIL NIL\r\n").unwrap();
+    assert_eq!(
+        response.personal,
+        vec![Namespace {
+            prefix: String::new(),
+            delimiter: Some(b'/'),
+        }]
+    );
+
+    let namespace = |prefix: &str, delimiter: u8| Namespace {
+        prefix: prefix.to_string(),
+        delimiter: Some(delimiter),
+    };
+    let inbox_prefix = namespace("INBOX.", b'.');
+    assert_eq!(inbox_prefix.imap_path("Lists/meli"), "INBOX.Lists.meli");
+    assert_eq!(inbox_prefix.imap_path("INBOX/Lists"), "INBOX.Lists");
+    let no_prefix = namespace("", b'.');
+    assert_eq!(no_prefix.imap_path("Lists/meli"), "Lists.meli");
+    let slash = namespace("", b'/');
+    assert_eq!(slash.imap_path("Lists/meli"), "Lists/meli");
+
+    let mut mailboxes = std::collections::HashMap::default();
+    for l in [
+        &b"* LIST (\\HasChildren) \".\" INBOX\r\n"[..],
+        b"* LIST (\\HasNoChildren) \".\" INBOX.Sent\r\n",
+    ] {
+        let (_, mailbox) = list_mailbox_result(l).unwrap();
+        mailboxes.insert(mailbox.hash, mailbox);
+    }
+    let inbox = MailboxHash::from_bytes(b"INBOX");
+    let sent = MailboxHash::from_bytes(b"INBOX.Sent");
+    mailboxes.get_mut(&inbox).unwrap().children.push(sent);
+    no_prefix.apply_to_mailboxes(&mut mailboxes);
+    assert_eq!(mailboxes[&sent].parent, Some(inbox));
+    inbox_prefix.apply_to_mailboxes(&mut mailboxes);
+    assert_eq!(mailboxes[&sent].parent, None);
+    assert!(mailboxes[&inbox].children.is_empty());
+}
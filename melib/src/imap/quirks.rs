@@ -0,0 +1,220 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Workarounds for non-conforming behavior of specific IMAP servers.
+//!
+//! Servers are identified by their greeting and, if the `ID` extension is
+//! used, by their `ID` response. The detected [`ServerQuirks`] are stored in
+//! [`UIDStore::quirks`](super::UIDStore::quirks) and are applied when building
+//! `FETCH` commands and the mailbox tree.
+
+use std::collections::HashMap;
+
+use super::{
+    promote_children, protocol_parser::id_ext::IDResponse, ImapMailbox, RequiredResponses,
+};
+use crate::backends::MailboxHash;
+
+bitflags! {
+    #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ServerQuirks: u8 {
+        /// `BODYSTRUCTURE` responses do not conform to RFC 3501 and may fail
+        /// to parse, so they are not requested. Envelopes are not marked as
+        /// having attachments.
+        const BROKEN_BODYSTRUCTURE   = 0b0000_0001;
+        /// Personal mailboxes are children of `INBOX` because of an `INBOX.`
+        /// namespace prefix. If every mailbox is under `INBOX`, they are shown
+        /// as top-level mailboxes.
+        const INBOX_NAMESPACE_PREFIX = 0b0000_0010;
+    }
+}
+
+/// Known servers, identified by a case-insensitive substring of their
+/// greeting or of the name or vendor of their `ID` response.
+pub const SERVER_QUIRKS: &[(&str, ServerQuirks)] = &[
+    ("Microsoft Exchange", ServerQuirks::BROKEN_BODYSTRUCTURE),
+    ("Dovecot", ServerQuirks::INBOX_NAMESPACE_PREFIX),
+    ("Courier", ServerQuirks::INBOX_NAMESPACE_PREFIX),
+];
+
+impl ServerQuirks {
+    fn lookup(text: &str) -> Self {
+        let text = text.to_ascii_lowercase();
+        SERVER_QUIRKS
+            .iter()
+            .filter(|(name, _)| text.contains(&name.to_ascii_lowercase()))
+            .fold(Self::empty(), |acc, (_, quirks)| acc | *quirks)
+    }
+
+    /// Quirks of the server that sent the untagged `OK` greeting in
+    /// `response`, if any.
+    pub fn from_greeting(response: &[u8]) -> Self {
+        response
+            .split(|&b| b == b'\n')
+            .find(|l| l.starts_with(b"* OK"))
+            .map(|l| Self::lookup(&String::from_utf8_lossy(l)))
+            .unwrap_or_default()
+    }
+
+    /// Quirks of the server that sent the `ID` response `id`.
+    pub fn from_id(id: &IDResponse) -> Self {
+        [id.name.as_deref(), id.vendor.as_deref()]
+            .into_iter()
+            .flatten()
+            .fold(Self::empty(), |acc, s| acc | Self::lookup(s))
+    }
+
+    /// The ` BODYSTRUCTURE` item of a raw `FETCH` command, if it should be
+    /// requested.
+    pub fn bodystructure_item(self) -> &'static str {
+        if self.contains(Self::BROKEN_BODYSTRUCTURE) {
+            ""
+        } else {
+            " BODYSTRUCTURE"
+        }
+    }
+
+    /// Remove the responses these quirks prevent from being requested.
+    pub fn required_responses(self, required: RequiredResponses) -> RequiredResponses {
+        if self.contains(Self::BROKEN_BODYSTRUCTURE) {
+            required - RequiredResponses::FETCH_BODYSTRUCTURE
+        } else {
+            required
+        }
+    }
+
+    /// Apply [`ServerQuirks::INBOX_NAMESPACE_PREFIX`] to the mailbox tree:
+    /// if every mailbox is a descendant of `INBOX`, make the children of
+    /// `INBOX` top-level mailboxes.
+    pub fn apply_to_mailboxes(self, mailboxes: &mut HashMap<MailboxHash, ImapMailbox>) {
+        if !self.contains(Self::INBOX_NAMESPACE_PREFIX) {
+            return;
+        }
+        let Some((inbox_hash, prefix)) = mailboxes
+            .values()
+            .find(|m| m.imap_path.eq_ignore_ascii_case("INBOX"))
+            .map(|m| (m.hash, format!("{}{}", m.imap_path, m.separator as char)))
+        else {
+            return;
+        };
+        if mailboxes.len() < 2
+            || !mailboxes
+                .values()
+                .all(|m| m.hash == inbox_hash || m.imap_path.starts_with(&prefix))
+        {
+            return;
+        }
+        promote_children(mailboxes, inbox_hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::imap::protocol_parser::list_mailbox_result;
+
+    #[test]
+    fn test_imap_server_quirks() {
+        assert_eq!(
+            ServerQuirks::from_greeting(
+                b"* OK The Microsoft Exchange IMAP4 service is ready.\r\n* CAPABILITY IMAP4rev1\r\n"
+            ),
+            ServerQuirks::BROKEN_BODYSTRUCTURE
+        );
+        assert_eq!(
+            ServerQuirks::from_greeting(b"* OK [CAPABILITY IMAP4rev1] Dovecot ready.\r\n"),
+            ServerQuirks::INBOX_NAMESPACE_PREFIX
+        );
+        assert_eq!(
+            ServerQuirks::from_greeting(b"* OK Gimap ready for requests\r\n"),
+            ServerQuirks::empty()
+        );
+        assert_eq!(
+            ServerQuirks::from_id(&IDResponse {
+                name: Some("Courier-IMAP".to_string()),
+                ..IDResponse::default()
+            }),
+            ServerQuirks::INBOX_NAMESPACE_PREFIX
+        );
+        assert_eq!(
+            ServerQuirks::BROKEN_BODYSTRUCTURE.required_responses(
+                RequiredResponses::FETCH_UID | RequiredResponses::FETCH_BODYSTRUCTURE
+            ),
+            RequiredResponses::FETCH_UID
+        );
+    }
+
+    #[test]
+    fn test_imap_server_quirks_inbox_namespace_prefix() {
+        let list = |lines: &[&[u8]]| {
+            let mut mailboxes = HashMap::default();
+            for l in lines {
+                let (_, mailbox) = list_mailbox_result(l).unwrap();
+                if let Some(parent) = mailbox.parent {
+                    mailboxes
+                        .entry(parent)
+                        .or_insert_with(ImapMailbox::default)
+                        .children
+                        .push(mailbox.hash);
+                }
+                let entry = mailboxes.entry(mailbox.hash).or_default();
+                let children = std::mem::take(&mut entry.children);
+                *entry = ImapMailbox {
+                    children,
+                    ..mailbox
+                };
+            }
+            mailboxes
+        };
+        let lines: &[&[u8]] = &[
+            b"* LIST (\\HasChildren) \".\" INBOX\r\n",
+            b"* LIST (\\HasNoChildren) \".\" INBOX.Sent\r\n",
+            b"* LIST (\\HasNoChildren) \".\" INBOX.Lists.meli\r\n",
+            b"* LIST (\\HasChildren) \".\" INBOX.Lists\r\n",
+        ];
+        let sent = MailboxHash::from_bytes(b"INBOX.Sent");
+        let lists = MailboxHash::from_bytes(b"INBOX.Lists");
+        let meli = MailboxHash::from_bytes(b"INBOX.Lists.meli");
+
+        let mut mailboxes = list(lines);
+        ServerQuirks::empty().apply_to_mailboxes(&mut mailboxes);
+        assert!(mailboxes[&sent].parent.is_some());
+
+        ServerQuirks::INBOX_NAMESPACE_PREFIX.apply_to_mailboxes(&mut mailboxes);
+        assert_eq!(mailboxes[&sent].parent, None);
+        assert_eq!(mailboxes[&lists].parent, None);
+        assert_eq!(mailboxes[&meli].parent, Some(lists));
+        assert!(mailboxes[&MailboxHash::from_bytes(b"INBOX")]
+            .children
+            .is_empty());
+
+        // Mailboxes outside the INBOX namespace keep the tree as it is.
+        let mut lines = lines.to_vec();
+        lines.push(b"* LIST () \"/\" Archive\r\n");
+        let mut mailboxes = list(&lines);
+        ServerQuirks::INBOX_NAMESPACE_PREFIX.apply_to_mailboxes(&mut mailboxes);
+        assert_eq!(
+            mailboxes[&sent].parent,
+            Some(MailboxHash::from_bytes(b"INBOX"))
+        );
+    }
+}
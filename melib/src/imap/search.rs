@@ -35,6 +35,14 @@ mod private {
 pub trait ToImapSearch: private::Sealed {
     /// Convert [`crate::search::Query`] into IMAP search criteria.
     fn to_imap_search(&self) -> String;
+
+    /// Convert [`crate::search::Query`] into an `X-GM-RAW` search criterion
+    /// that uses Gmail's own search syntax, for servers with the `X-GM-EXT-1`
+    /// capability.
+    ///
+    /// Returns `None` if the query has terms that Gmail's search syntax cannot
+    /// express, such as arbitrary headers.
+    fn to_gmail_raw_search(&self) -> Option<String>;
 }
 
 impl private::Sealed for Query {}
@@ -232,6 +240,13 @@ impl ToImapSearch for Query {
                 Q(HasAttachment) => {
                     log::warn!("HasAttachment in IMAP is unimplemented.");
                 }
+                Q(Attachment(t)) => {
+                    // Servers with full text search may index attachments as part of the body.
+                    space_pad!(s);
+                    s.push_str(r#"BODY ""#);
+                    s.extend(escape_double_quote(t).chars());
+                    s.push('"');
+                }
                 Q(Answered) => {
                     space_pad!(s);
                     s.push_str(r#"ANSWERED ""#);
@@ -259,6 +274,116 @@ impl ToImapSearch for Query {
         }
         s
     }
+
+    fn to_gmail_raw_search(&self) -> Option<String> {
+        let mut raw = String::new();
+        gmail_raw(self, &mut raw, true)?;
+        let mut s = String::from("X-GM-RAW \"");
+        for c in raw.chars() {
+            if matches!(c, '"' | '\\') {
+                s.push('\\');
+            }
+            s.push(c);
+        }
+        s.push('"');
+        Some(s)
+    }
+}
+
+/// Append `q` to `s` in Gmail's search syntax.
+///
+/// Conjunctions are juxtaposed terms, and need parentheses unless `in_and` is
+/// set, since juxtaposed terms inside braces are a disjunction.
+fn gmail_raw(q: &Query, s: &mut String, in_and: bool) -> Option<()> {
+    use Query::*;
+
+    /// Gmail phrases cannot contain double quotes.
+    fn phrase(s: &mut String, field: &str, t: &str) {
+        s.push_str(field);
+        s.push('"');
+        s.extend(t.chars().filter(|c| *c != '"'));
+        s.push('"');
+    }
+
+    match q {
+        Subject(t) => phrase(s, "subject:", t),
+        From(t) => phrase(s, "from:", t),
+        To(t) => phrase(s, "to:", t),
+        Cc(t) => phrase(s, "cc:", t),
+        Bcc(t) => phrase(s, "bcc:", t),
+        AllAddresses(t) => {
+            phrase(s, "{from:", t);
+            phrase(s, " to:", t);
+            phrase(s, " cc:", t);
+            phrase(s, " bcc:", t);
+            s.push('}');
+        }
+        AllText(t) | Body(t) => phrase(s, "", t),
+        Attachment(t) => phrase(s, "filename:", t),
+        HasAttachment => s.push_str("has:attachment"),
+        Header(h, v) if h.as_str().eq_ignore_ascii_case("message-id") => {
+            s.push_str("rfc822msgid:");
+            s.extend(v.chars().filter(|c| !matches!(c, '"' | '<' | '>')));
+        }
+        Header(_, _) | InReplyTo(_) | References(_) | Answered | AnsweredBy { .. } => {
+            return None;
+        }
+        Flags(v) => {
+            for (i, f) in v.iter().enumerate() {
+                if i > 0 {
+                    s.push(' ');
+                }
+                match f.as_str() {
+                    "draft" => s.push_str("in:drafts"),
+                    "deleted" => s.push_str("in:trash"),
+                    "flagged" => s.push_str("is:starred"),
+                    "seen" | "read" => s.push_str("is:read"),
+                    "unseen" | "unread" => s.push_str("is:unread"),
+                    "recent" | "answered" | "unanswered" => return None,
+                    keyword => {
+                        s.push_str("label:");
+                        s.extend(keyword.chars().filter(|c| *c != '"').map(|c| {
+                            if c.is_whitespace() {
+                                '-'
+                            } else {
+                                c
+                            }
+                        }));
+                    }
+                }
+            }
+        }
+        Before(t) => s.push_str(&format!("before:{t}")),
+        After(t) => s.push_str(&format!("after:{t}")),
+        Between(t1, t2) => s.push_str(&format!("after:{t1} before:{t2}")),
+        On(t) => s.push_str(&format!("after:{t} before:{}", t + 24 * 60 * 60)),
+        Larger { than } => s.push_str(&format!("larger:{than}")),
+        Smaller { than } => s.push_str(&format!("smaller:{than}")),
+        And(q1, q2) => {
+            if !in_and {
+                s.push('(');
+            }
+            gmail_raw(q1, s, true)?;
+            s.push(' ');
+            gmail_raw(q2, s, true)?;
+            if !in_and {
+                s.push(')');
+            }
+        }
+        Or(q1, q2) => {
+            s.push('{');
+            gmail_raw(q1, s, false)?;
+            s.push(' ');
+            gmail_raw(q2, s, false)?;
+            s.push('}');
+        }
+        Not(q) => {
+            s.push_str("-(");
+            gmail_raw(q, s, true)?;
+            s.push(')');
+        }
+    }
+    Some(())
 }
 
 #[cfg(test)]
@@ -316,4 +441,42 @@ mod tests {
             r#"OR SUBJECT "wo" (OR FROM "Manos" (OR TO "Manos" (OR CC "Manos" BCC "Manos")))"#
         );
     }
+
+    #[test]
+    fn test_imap_query_gmail_raw_search() {
+        let (_, q) = query().parse_complete("subject: test and i").unwrap();
+        assert_eq!(
+            q.to_gmail_raw_search().unwrap(),
+            r#"X-GM-RAW "subject:\"test\" \"i\"""#
+        );
+
+        let (_, q) = query()
+            .parse_complete(r#"subject:"wah ah ah" or (from:Manos and from:Sia)"#)
+            .unwrap();
+        assert_eq!(
+            q.to_gmail_raw_search().unwrap(),
+            r#"X-GM-RAW "{subject:\"wah ah ah\" (from:\"Manos\" from:\"Sia\")}""#
+        );
+
+        let q = Query::Not(Box::new(Query::Flags(vec!["unseen".to_string()])));
+        assert_eq!(
+            q.to_gmail_raw_search().unwrap(),
+            r#"X-GM-RAW "-(is:unread)""#
+        );
+
+        let q = Query::And(
+            Box::new(Query::Before(1685836800)),
+            Box::new(Query::Flags(vec!["flagged".to_string()])),
+        );
+        assert_eq!(
+            q.to_gmail_raw_search().unwrap(),
+            r#"X-GM-RAW "before:1685836800 is:starred""#
+        );
+
+        let (_, q) = query().parse_complete("tags:work").unwrap();
+        assert_eq!(q.to_gmail_raw_search().unwrap(), r#"X-GM-RAW "label:work""#);
+
+        let (_, q) = query().parse_complete("in-reply-to:foo").unwrap();
+        assert_eq!(q.to_gmail_raw_search(), None);
+    }
 }
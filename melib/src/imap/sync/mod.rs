@@ -61,7 +61,6 @@ impl ImapConnection {
         &mut self,
         mailbox_hash: MailboxHash,
     ) -> Result<Option<Vec<Envelope>>> {
-        let mut payload = vec![];
         let mut response = Vec::with_capacity(8 * 1024);
         let cached_uidvalidity = self
             .uid_store
@@ -84,15 +83,9 @@ impl ImapConnection {
 
         let current_uidvalidity: UID = cached_uidvalidity.unwrap_or(1);
         let max_uid: UID = cached_max_uid.unwrap_or(1);
-        let (mailbox_path, mailbox_exists, unseen) = {
-            let f = &self.uid_store.mailboxes.lock().await[&mailbox_hash];
-            (
-                f.imap_path().to_string(),
-                f.exists.clone(),
-                f.unseen.clone(),
-            )
-        };
-        let mut new_unseen = BTreeSet::default();
+        let mailbox_path = self.uid_store.mailboxes.lock().await[&mailbox_hash]
+            .imap_path()
+            .to_string();
         let select_response = self
             .select_mailbox(mailbox_hash, &mut response, true)
             .await?;
@@ -106,92 +99,9 @@ impl ImapConnection {
             .update_mailbox(mailbox_hash, &select_response)?;
 
         // 2. tag1 UID FETCH <lastseenuid+1>:* <descriptors>
-        let (required_responses, attributes) = crate::imap::email::common_attributes();
-        self.send_command(CommandBody::fetch(max_uid + 1.., attributes, true)?)
+        let payload = self
+            .fetch_new_envelopes(mailbox_hash, &mailbox_path, max_uid, &mut response)
             .await?;
-        self.read_response(&mut response, required_responses)
-            .await?;
-        let (_, mut v, _) = protocol_parser::fetch_responses(&response)?;
-        for FetchResponse {
-            ref uid,
-            ref mut envelope,
-            ref mut flags,
-            ref references,
-            ..
-        } in v.iter_mut()
-        {
-            let uid = uid.unwrap();
-            let env = envelope.as_mut().unwrap();
-            env.set_hash(generate_envelope_hash(&mailbox_path, &uid));
-            if let Some(value) = references {
-                env.set_references(value);
-            }
-            let mut tag_lck = self.uid_store.collection.tag_index.write().unwrap();
-            if let Some((flags, keywords)) = flags {
-                env.set_flags(*flags);
-                if !env.is_seen() {
-                    new_unseen.insert(env.hash());
-                }
-                for f in keywords {
-                    let hash = TagHash::from_bytes(f.as_bytes());
-                    tag_lck.entry(hash).or_insert_with(|| f.to_string());
-                    env.tags_mut().insert(hash);
-                }
-            }
-        }
-        {
-            self.uid_store
-                .insert_envelopes(mailbox_hash, &v)
-                .chain_err_summary(|| {
-                    format!("Could not save envelopes in cache for mailbox {mailbox_path}")
-                })?;
-        }
-
-        for FetchResponse {
-            uid,
-            message_sequence_number: _,
-            envelope,
-            ..
-        } in v
-        {
-            let uid = uid.unwrap();
-            let env = envelope.unwrap();
-            self.uid_store
-                .hash_index
-                .lock()
-                .unwrap()
-                .insert(env.hash(), (uid, mailbox_hash));
-            self.uid_store
-                .uid_index
-                .lock()
-                .unwrap()
-                .insert((mailbox_hash, uid), env.hash());
-            payload.push((uid, env));
-        }
-        let payload_hash_set: BTreeSet<_> =
-            payload.iter().map(|(_, env)| env.hash()).collect::<_>();
-        {
-            let mut unseen_lck = unseen.lock().unwrap();
-            if unseen_lck.set.is_empty() {
-                let new_total = unseen_lck.len() + new_unseen.len();
-                unseen_lck.set_not_yet_seen(new_total);
-            } else {
-                for &seen_env_hash in payload_hash_set.difference(&new_unseen) {
-                    unseen_lck.remove(seen_env_hash);
-                }
-
-                unseen_lck.insert_set(new_unseen);
-            }
-        }
-        {
-            let mut exists_lck = mailbox_exists.lock().unwrap();
-            if exists_lck.set.is_empty() {
-                let new_total = exists_lck.len() + payload_hash_set.len();
-                exists_lck.set_not_yet_seen(new_total);
-            } else {
-                exists_lck.insert_set(payload_hash_set);
-            }
-        }
         // 3. tag2 UID FETCH 1:<lastseenuid> FLAGS
         let sequence_set = if max_uid == 0 {
             SequenceSet::from(..)
@@ -388,11 +298,13 @@ impl ImapConnection {
 
             // 2. tag1 UID FETCH <lastseenuid+1>:* <descriptors>
             // [ref:TODO]: (#222) imap-codec does not support "CONDSTORE/QRESYNC" currently.
+            let quirks = *self.uid_store.quirks.lock().unwrap();
             self.send_command_raw(
                 format!(
-                    "UID FETCH {}:* (UID FLAGS ENVELOPE BODY.PEEK[HEADER.FIELDS (REFERENCES)] \
-                     BODYSTRUCTURE) (CHANGEDSINCE {})",
+                    "UID FETCH {}:* (UID FLAGS ENVELOPE BODY.PEEK[HEADER.FIELDS \
+                     (REFERENCES)]{}) (CHANGEDSINCE {})",
                     cached_max_uid + 1,
+                    quirks.bodystructure_item(),
                     cached_highestmodseq,
                 )
                 .as_bytes(),
@@ -400,12 +312,14 @@ impl ImapConnection {
             .await?;
             self.read_response(
                 &mut response,
-                RequiredResponses::FETCH_UID
-                    | RequiredResponses::FETCH_FLAGS
-                    | RequiredResponses::FETCH_ENVELOPE
-                    | RequiredResponses::FETCH_REFERENCES
-                    | RequiredResponses::FETCH_BODYSTRUCTURE
-                    | RequiredResponses::FETCH_MODSEQ,
+                quirks.required_responses(
+                    RequiredResponses::FETCH_UID
+                        | RequiredResponses::FETCH_FLAGS
+                        | RequiredResponses::FETCH_ENVELOPE
+                        | RequiredResponses::FETCH_REFERENCES
+                        | RequiredResponses::FETCH_BODYSTRUCTURE
+                        | RequiredResponses::FETCH_MODSEQ,
+                ),
             )
             .await?;
             debug!(
@@ -610,16 +524,268 @@ impl ImapConnection {
     ///
     /// Re-sync IMAP state by following the strategy described in
     /// [RFC7162](https://datatracker.ietf.org/doc/rfc7162/) "Quick Flag Changes Resynchronization (CONDSTORE) and Quick
-    /// Mailbox Resynchronization (QRESYNC)"
+    /// Mailbox Resynchronization (QRESYNC)", Section 3.2.5.2: the `SELECT`
+    /// response itself reports the expunged UIDs and the changed flags, so only
+    /// new messages need to be fetched.
     pub async fn resync_condstoreqresync(
         &mut self,
         mailbox_hash: MailboxHash,
     ) -> Result<Option<Vec<Envelope>>> {
-        log::trace!(
-            "resync_condstoreqresync: mailbox_hash: {:?}, function unimplemented",
-            mailbox_hash
+        let mut response = Vec::with_capacity(8 * 1024);
+        let cached_uidvalidity = self
+            .uid_store
+            .uidvalidity
+            .lock()
+            .unwrap()
+            .get(&mailbox_hash)
+            .cloned();
+        let cached_max_uid = self
+            .uid_store
+            .max_uids
+            .lock()
+            .unwrap()
+            .get(&mailbox_hash)
+            .cloned();
+        let cached_highestmodseq = self
+            .uid_store
+            .highestmodseqs
+            .lock()
+            .unwrap()
+            .get(&mailbox_hash)
+            .cloned();
+        let (Some(cached_uidvalidity), Some(cached_max_uid), Some(cached_highestmodseq)) =
+            (cached_uidvalidity, cached_max_uid, cached_highestmodseq)
+        else {
+            // This means the mailbox is not cached.
+            return Ok(None);
+        };
+        let Ok(cached_highestmodseq) = cached_highestmodseq else {
+            // No MODSEQ is available for __this__ mailbox, fallback to basic sync
+            return self.resync_basic(mailbox_hash).await;
+        };
+        let mailbox_path = self.uid_store.mailboxes.lock().await[&mailbox_hash]
+            .imap_path()
+            .to_string();
+        // 1. SELECT with the cached UIDVALIDITY and HIGHESTMODSEQ.
+        let select_response = self
+            .select_mailbox_qresync(
+                mailbox_hash,
+                &mut response,
+                cached_uidvalidity,
+                cached_highestmodseq,
+                cached_max_uid,
+            )
+            .await?;
+        if select_response.uidvalidity != cached_uidvalidity {
+            // The server ignores the QRESYNC parameters, discard cache and rebuild.
+            self.uid_store
+                .init_mailbox(mailbox_hash, &select_response)?;
+            return Ok(None);
+        }
+        let new_highestmodseq = match select_response.highestmodseq {
+            Some(Ok(new_highestmodseq)) => new_highestmodseq,
+            Some(Err(())) => {
+                self.uid_store
+                    .highestmodseqs
+                    .lock()
+                    .unwrap()
+                    .insert(mailbox_hash, Err(()));
+                return self.resync_basic(mailbox_hash).await;
+            }
+            None => return self.resync_basic(mailbox_hash).await,
+        };
+        self.uid_store
+            .update_mailbox(mailbox_hash, &select_response)?;
+        let mut refresh_events = vec![];
+        {
+            // 2. Apply the VANISHED (EARLIER) and FETCH responses.
+            let mut env_lck = self.uid_store.envelopes.lock().unwrap();
+            for l in response.split_rn() {
+                if l.starts_with(b"* VANISHED ") {
+                    let (_, vanished) = protocol_parser::vanished_response(l)?;
+                    let expunged = env_lck
+                        .iter()
+                        .filter(|(_, cenv)| {
+                            cenv.mailbox_hash == mailbox_hash && vanished.contains(cenv.uid)
+                        })
+                        .map(|(h, cenv)| (*h, cenv.uid))
+                        .collect::<Vec<(EnvelopeHash, UID)>>();
+                    for (env_hash, uid) in expunged {
+                        refresh_events.push((
+                            uid,
+                            RefreshEvent {
+                                mailbox_hash,
+                                account_hash: self.uid_store.account_hash,
+                                kind: RefreshEventKind::Remove(env_hash),
+                            },
+                        ));
+                        env_lck.remove(&env_hash);
+                    }
+                    continue;
+                }
+                let Ok((
+                    _,
+                    FetchResponse {
+                        uid: Some(uid),
+                        flags: Some((flags, tags)),
+                        ..
+                    },
+                    _,
+                )) = protocol_parser::fetch_response(l)
+                else {
+                    continue;
+                };
+                let env_hash = generate_envelope_hash(&mailbox_path, &uid);
+                let Some(cenv) = env_lck.get_mut(&env_hash) else {
+                    continue;
+                };
+                if cenv.inner.flags() != flags
+                    || cenv.inner.tags()
+                        != &tags
+                            .iter()
+                            .map(|t| TagHash::from_bytes(t.as_bytes()))
+                            .collect::<IndexSet<TagHash>>()
+                {
+                    cenv.inner.set_flags(flags);
+                    cenv.inner.tags_mut().clear();
+                    cenv.inner
+                        .tags_mut()
+                        .extend(tags.iter().map(|t| TagHash::from_bytes(t.as_bytes())));
+                    refresh_events.push((
+                        uid,
+                        RefreshEvent {
+                            mailbox_hash,
+                            account_hash: self.uid_store.account_hash,
+                            kind: RefreshEventKind::NewFlags(env_hash, (flags, tags)),
+                        },
+                    ));
+                }
+            }
+        }
+        // 3. Fetch the messages that arrived since the last sync.
+        let payload =
+            if select_response.uidnext == 0 || select_response.uidnext > cached_max_uid + 1 {
+                self.fetch_new_envelopes(mailbox_hash, &mailbox_path, cached_max_uid, &mut response)
+                    .await?
+            } else {
+                vec![]
+            };
+        self.uid_store
+            .highestmodseqs
+            .lock()
+            .unwrap()
+            .insert(mailbox_hash, Ok(new_highestmodseq));
+        self.uid_store.update(mailbox_hash, &refresh_events)?;
+        for (_uid, ev) in refresh_events {
+            self.add_refresh_event(ev);
+        }
+        Ok(Some(payload.into_iter().map(|(_, env)| env).collect()))
+    }
+
+    /// Fetch the messages with UIDs greater than `max_uid`, and add them to
+    /// the cache and the mailbox counts.
+    async fn fetch_new_envelopes(
+        &mut self,
+        mailbox_hash: MailboxHash,
+        mailbox_path: &str,
+        max_uid: UID,
+        response: &mut Vec<u8>,
+    ) -> Result<Vec<(UID, Envelope)>> {
+        let mut payload = vec![];
+        let (mailbox_exists, unseen) = {
+            let f = &self.uid_store.mailboxes.lock().await[&mailbox_hash];
+            (f.exists.clone(), f.unseen.clone())
+        };
+        let mut new_unseen = BTreeSet::default();
+        let (required_responses, attributes) = crate::imap::email::common_attributes(
+            *self.uid_store.quirks.lock().unwrap(),
+            &self.uid_store.retained_headers,
         );
-        self.resync_condstore(mailbox_hash).await
+        self.send_command(CommandBody::fetch(max_uid + 1.., attributes, true)?)
+            .await?;
+        self.read_response(response, required_responses).await?;
+        let (_, mut v, _) = protocol_parser::fetch_responses(response)?;
+        for FetchResponse {
+            ref uid,
+            ref mut envelope,
+            ref mut flags,
+            ref references,
+            ..
+        } in v.iter_mut()
+        {
+            let uid = uid.unwrap();
+            let env = envelope.as_mut().unwrap();
+            env.set_hash(generate_envelope_hash(mailbox_path, &uid));
+            if let Some(value) = references {
+                env.set_references(value);
+            }
+            let mut tag_lck = self.uid_store.collection.tag_index.write().unwrap();
+            if let Some((flags, keywords)) = flags {
+                env.set_flags(*flags);
+                if !env.is_seen() {
+                    new_unseen.insert(env.hash());
+                }
+                for f in keywords {
+                    let hash = TagHash::from_bytes(f.as_bytes());
+                    tag_lck.entry(hash).or_insert_with(|| f.to_string());
+                    env.tags_mut().insert(hash);
+                }
+            }
+        }
+        {
+            self.uid_store
+                .insert_envelopes(mailbox_hash, &v)
+                .chain_err_summary(|| {
+                    format!("Could not save envelopes in cache for mailbox {mailbox_path}")
+                })?;
+        }
+
+        for FetchResponse {
+            uid,
+            message_sequence_number: _,
+            envelope,
+            ..
+        } in v
+        {
+            let uid = uid.unwrap();
+            let env = envelope.unwrap();
+            self.uid_store
+                .hash_index
+                .lock()
+                .unwrap()
+                .insert(env.hash(), (uid, mailbox_hash));
+            self.uid_store
+                .uid_index
+                .lock()
+                .unwrap()
+                .insert((mailbox_hash, uid), env.hash());
+            payload.push((uid, env));
+        }
+        let payload_hash_set: BTreeSet<_> =
+            payload.iter().map(|(_, env)| env.hash()).collect::<_>();
+        {
+            let mut unseen_lck = unseen.lock().unwrap();
+            if unseen_lck.set.is_empty() {
+                let new_total = unseen_lck.len() + new_unseen.len();
+                unseen_lck.set_not_yet_seen(new_total);
+            } else {
+                for &seen_env_hash in payload_hash_set.difference(&new_unseen) {
+                    unseen_lck.remove(seen_env_hash);
+                }
+
+                unseen_lck.insert_set(new_unseen);
+            }
+        }
+        {
+            let mut exists_lck = mailbox_exists.lock().unwrap();
+            if exists_lck.set.is_empty() {
+                let new_total = exists_lck.len() + payload_hash_set.len();
+                exists_lck.set_not_yet_seen(new_total);
+            } else {
+                exists_lck.insert_set(payload_hash_set);
+            }
+        }
+        Ok(payload)
     }
 
     pub async fn init_mailbox(&mut self, mailbox_hash: MailboxHash) -> Result<SelectResponse> {
@@ -83,6 +83,7 @@ const DB_DESCRIPTION: DatabaseDescription = DatabaseDescription {
     CREATE INDEX IF NOT EXISTS mailbox_idx ON mailbox(mailbox_hash);",
     ),
     version: 4,
+    migrations: &[],
 };
 
 impl From<EnvelopeHash> for Value {
@@ -136,6 +137,55 @@ impl Sqlite3Cache {
     }
 }
 
+impl Sqlite3Cache {
+    /// Description of the cache database of the IMAP account `account_name`.
+    pub fn db_description(account_name: &str, data_dir: Option<&Path>) -> DatabaseDescription {
+        DatabaseDescription {
+            identifier: Some(account_name.to_string().into()),
+            directory: data_dir.map(|p| p.to_path_buf().into()),
+            ..DB_DESCRIPTION.clone()
+        }
+    }
+
+    /// Removes cached message bodies, least recently cached first, until the
+    /// remaining ones take at most `budget` bytes. Envelopes stay cached, and
+    /// removed bodies are fetched from the server again when needed.
+    ///
+    /// Returns how many bodies were removed.
+    pub fn expire_bodies(db_desc: &DatabaseDescription, budget: u64) -> Result<usize> {
+        if !db_desc.exists()? {
+            return Ok(0);
+        }
+        let mut conn = db_desc.open_or_create_db()?;
+        let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+        let expired = {
+            let mut stmt = tx.prepare(
+                "SELECT rowid, length(rfc822) FROM envelopes WHERE rfc822 IS NOT NULL ORDER BY \
+                 rowid DESC;",
+            )?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))?
+                .collect::<std::result::Result<Vec<(i64, i64)>, _>>()?;
+            let mut total: u64 = 0;
+            rows.into_iter()
+                .filter(|(_, len)| {
+                    total = total.saturating_add(*len as u64);
+                    total > budget
+                })
+                .map(|(rowid, _)| rowid)
+                .collect::<Vec<i64>>()
+        };
+        for rowid in &expired {
+            tx.execute(
+                "UPDATE envelopes SET rfc822 = NULL WHERE rowid = ?1;",
+                sqlite3::params![rowid],
+            )?;
+        }
+        tx.commit()?;
+        Ok(expired.len())
+    }
+}
+
 impl ImapCacheReset for Sqlite3Cache {
     fn reset_db(uid_store: &UIDStore, data_dir: Option<&Path>) -> Result<()> {
         let db_desc = DatabaseDescription {
@@ -456,6 +506,8 @@ impl ImapCache for Sqlite3Cache {
                 envelope: Some(envelope),
                 raw_fetch_value: _,
                 bodystructure: _,
+                gmail_labels: _,
+                gmail_thread_id: _,
             } = item
             {
                 max_uid = max_uid.max(*uid);
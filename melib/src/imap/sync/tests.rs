@@ -54,6 +54,8 @@ fn test_imap_sync_sqlite() {
         references: None,
         envelope: Some(Envelope::default()),
         bodystructure: false,
+        gmail_labels: None,
+        gmail_thread_id: None,
         raw_fetch_value: &[],
     };
     let fetches = &[fetch];
@@ -74,3 +76,75 @@ fn test_imap_sync_sqlite() {
     value.insert_envelopes(mailbox_hash, fetches).unwrap();
     _ = tempdir.close();
 }
+
+#[cfg(feature = "sqlite3")]
+#[test]
+fn test_imap_sync_sqlite_maintenance() {
+    use crate::{imap::sync::sqlite3_cache::Sqlite3Cache, utils::sqlite3::params};
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let db_desc = Sqlite3Cache::db_description("test", Some(tempdir.path()));
+    assert_eq!(Sqlite3Cache::expire_bodies(&db_desc, 0).unwrap(), 0);
+    {
+        let conn = db_desc.open_or_create_db().unwrap();
+        conn.execute(
+            "INSERT INTO mailbox (mailbox_hash, uidvalidity, max_uid, flags) VALUES (1, 1, 4, \
+             x'00');",
+            [],
+        )
+        .unwrap();
+        for uid in 1..=4_i64 {
+            conn.execute(
+                "INSERT INTO envelopes (hash, mailbox_hash, uid, rfc822, envelope) VALUES (?1, 1, \
+                 ?1, ?2, x'00');",
+                params![uid, vec![b'a'; 100]],
+            )
+            .unwrap();
+        }
+    }
+    assert!(db_desc.check_integrity().unwrap().is_empty());
+
+    // The two most recently cached bodies fit in the budget.
+    assert_eq!(Sqlite3Cache::expire_bodies(&db_desc, 250).unwrap(), 2);
+    let conn = db_desc.open_or_create_db().unwrap();
+    let mut stmt = conn
+        .prepare("SELECT uid FROM envelopes WHERE rfc822 IS NOT NULL ORDER BY uid;")
+        .unwrap();
+    let cached = stmt
+        .query_map([], |row| row.get::<_, i64>(0))
+        .unwrap()
+        .collect::<Result<Vec<i64>, _>>()
+        .unwrap();
+    assert_eq!(cached, vec![3, 4]);
+    drop(stmt);
+    drop(conn);
+
+    let (before, after) = db_desc.vacuum().unwrap();
+    assert!(before > 0 && after > 0);
+
+    // Marking for reset leaves open connections working; the database is
+    // reset when it is opened next.
+    let count = |conn: &crate::utils::sqlite3::Connection| {
+        conn.query_row("SELECT COUNT(*) FROM envelopes;", [], |row| {
+            row.get::<_, i64>(0)
+        })
+        .unwrap()
+    };
+    let conn = db_desc.open_or_create_db().unwrap();
+    db_desc.mark_for_reset().unwrap();
+    assert!(db_desc.is_marked_for_reset().unwrap());
+    assert_eq!(count(&conn), 4);
+    drop(conn);
+    let conn = db_desc.open_or_create_db().unwrap();
+    assert!(!db_desc.is_marked_for_reset().unwrap());
+    assert_eq!(count(&conn), 0);
+    drop(conn);
+
+    std::fs::write(
+        db_desc.db_path().unwrap(),
+        b"not a database, just garbage bytes",
+    )
+    .unwrap();
+    assert!(!db_desc.check_integrity().unwrap().is_empty());
+    _ = tempdir.close();
+}
@@ -226,9 +226,91 @@ impl ImapConnection {
                 let [(_, event)] = pair;
                 Ok(Some(event.into()))
             }
+            UntaggedResponse::Vanished(vanished) => {
+                imap_log!(trace, self, "vanished {:?}", vanished);
+                let deleted_uids_hashes = self
+                    .uid_store
+                    .uid_index
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|((mbx, u), _)| *mbx == mailbox_hash && vanished.contains(*u))
+                    .map(|((_, uid), hash)| (*uid, *hash))
+                    .collect::<Vec<(UID, crate::email::EnvelopeHash)>>();
+                if deleted_uids_hashes.is_empty() {
+                    return Ok(None);
+                }
+                {
+                    // Message sequence numbers after each expunged message are decremented.
+                    let mut lck = self.uid_store.msn_index.lock().unwrap();
+                    let msn_index = lck.entry(mailbox_hash).or_default();
+                    let mut removed = 0;
+                    *msn_index = std::mem::take(msn_index)
+                        .into_iter()
+                        .filter_map(|(msn, uid)| {
+                            if vanished.contains(uid) {
+                                removed += 1;
+                                None
+                            } else {
+                                Some((msn - removed, uid))
+                            }
+                        })
+                        .collect();
+                }
+                let mut uid_events = vec![];
+                {
+                    let mut mboxes = self.uid_store.mailboxes.lock().await;
+                    for (deleted_uid, deleted_hash) in deleted_uids_hashes {
+                        self.uid_store
+                            .uid_index
+                            .lock()
+                            .unwrap()
+                            .remove(&(mailbox_hash, deleted_uid));
+                        if let Some(mbx) = mboxes.get_mut(&mailbox_hash) {
+                            mbx.exists.lock().unwrap().remove(deleted_hash);
+                            mbx.unseen.lock().unwrap().remove(deleted_hash);
+                        }
+                        self.uid_store
+                            .hash_index
+                            .lock()
+                            .unwrap()
+                            .remove(&deleted_hash);
+                        uid_events.push((
+                            deleted_uid,
+                            RefreshEvent {
+                                account_hash: self.uid_store.account_hash,
+                                mailbox_hash,
+                                kind: Remove(deleted_hash),
+                            },
+                        ));
+                    }
+                }
+                if let Err(err) = self
+                    .uid_store
+                    .update(mailbox_hash, &uid_events)
+                    .or_else(ignore_not_found)
+                {
+                    log::error!(
+                        "Could not update cache for mailbox_hash = {:?} uid, events = {:?}: err \
+                         = {}",
+                        mailbox_hash,
+                        uid_events,
+                        err
+                    );
+                }
+                Ok(uid_events
+                    .into_iter()
+                    .map(|(_, ev)| ev)
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .ok())
+            }
             UntaggedResponse::Exists(n) => {
                 imap_log!(trace, self, "exists {}", n);
-                let (required_responses, attributes) = common_attributes();
+                let (required_responses, attributes) = common_attributes(
+                    *self.uid_store.quirks.lock().unwrap(),
+                    &self.uid_store.retained_headers,
+                );
                 try_fail!(
                     mailbox_hash,
                     self.send_command(CommandBody::fetch(n, attributes, false)?).await
@@ -359,6 +441,7 @@ impl ImapConnection {
                     }
                     Ok(v) => {
                         // [ref:FIXME]: use imap_codec types instead of a raw command
+                        let quirks = *self.uid_store.quirks.lock().unwrap();
                         let command = {
                             let mut iter = v.split(u8::is_ascii_whitespace);
                             let first = iter.next().unwrap_or(v);
@@ -369,7 +452,8 @@ impl ImapConnection {
                             }
                             format!(
                                 "UID FETCH {accum} (UID FLAGS ENVELOPE BODY.PEEK[HEADER.FIELDS \
-                                 (REFERENCES)] BODYSTRUCTURE)"
+                                 (REFERENCES)]{})",
+                                quirks.bodystructure_item()
                             )
                         };
                         try_fail!(
@@ -377,11 +461,13 @@ impl ImapConnection {
                             self.send_command_raw(command.as_bytes()).await,
                             self.read_response(
                                 &mut response,
-                                RequiredResponses::FETCH_UID
-                                    | RequiredResponses::FETCH_FLAGS
-                                    | RequiredResponses::FETCH_ENVELOPE
-                                    | RequiredResponses::FETCH_REFERENCES
-                                    | RequiredResponses::FETCH_BODYSTRUCTURE,
+                                quirks.required_responses(
+                                    RequiredResponses::FETCH_UID
+                                        | RequiredResponses::FETCH_FLAGS
+                                        | RequiredResponses::FETCH_ENVELOPE
+                                        | RequiredResponses::FETCH_REFERENCES
+                                        | RequiredResponses::FETCH_BODYSTRUCTURE,
+                                ),
                             )
                             .await,
                         );
@@ -513,6 +599,8 @@ impl ImapConnection {
                     references: _,
                     envelope: _,
                     bodystructure: _,
+                    gmail_labels: _,
+                    gmail_thread_id: _,
                     raw_fetch_value: _,
                 } = *fetch;
                 if let Some(flags) = flags {
@@ -66,6 +66,238 @@ pub fn poll_with_examine(
     })
 }
 
+/// Poll the message counts of subscribed mailboxes that have not been loaded
+/// yet every `interval`, so that their counts are up to date without
+/// selecting them.
+///
+/// Counts are requested with a single `LIST-STATUS` command if the server
+/// supports it, otherwise with a `STATUS` command for each mailbox.
+pub fn poll_status(
+    main_conn: Arc<ConnectionMutex>,
+    uid_store: Arc<UIDStore>,
+    interval: Duration,
+) -> impl futures::stream::Stream<Item = Result<BackendEvent>> {
+    try_fn_stream(move |emitter| async move {
+        log::trace!("poll with STATUS");
+        let mut response = Vec::with_capacity(8 * 1024);
+        loop {
+            smol::Timer::after(interval).await;
+            let mailboxes: HashMap<MailboxHash, ImapMailbox> = {
+                let mailboxes_lck = timeout(uid_store.timeout, uid_store.mailboxes.lock()).await?;
+                mailboxes_lck
+                    .iter()
+                    .filter(|(_, m)| m.is_subscribed && !m.no_select && m.is_cold())
+                    .map(|(h, m)| (*h, m.clone()))
+                    .collect()
+            };
+            if mailboxes.is_empty() {
+                continue;
+            }
+            let mut statuses = vec![];
+            {
+                let mut conn = main_conn.lock().await?;
+                let has_list_status: bool = conn
+                    .uid_store
+                    .capabilities
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .any(|cap| cap.eq_ignore_ascii_case(b"LIST-STATUS"));
+                if has_list_status {
+                    // [ref:TODO]: (#222) imap-codec does not support "LIST Command Extensions"
+                    // currently.
+                    conn.send_command_raw(b"LIST \"\" \"*\" RETURN (STATUS (MESSAGES UNSEEN))")
+                        .await?;
+                    conn.read_response(
+                        &mut response,
+                        RequiredResponses::LIST | RequiredResponses::STATUS,
+                    )
+                    .await?;
+                    statuses.extend(
+                        response
+                            .split_rn()
+                            .filter_map(|l| protocol_parser::status_response(l).ok())
+                            .map(|(_, v)| v),
+                    );
+                } else {
+                    for mailbox in mailboxes.values() {
+                        conn.send_command_raw(
+                            format!("STATUS \"{}\" (MESSAGES UNSEEN)", mailbox.imap_path())
+                                .as_bytes(),
+                        )
+                        .await?;
+                        if let Err(err) = conn
+                            .read_response(&mut response, RequiredResponses::STATUS)
+                            .await
+                        {
+                            if err.kind.is_network() {
+                                return Err(err);
+                            }
+                            log::trace!("STATUS of {} failed: {err}", mailbox.imap_path());
+                            continue;
+                        }
+                        statuses.extend(
+                            response
+                                .split_rn()
+                                .filter_map(|l| protocol_parser::status_response(l).ok())
+                                .map(|(_, v)| v),
+                        );
+                    }
+                }
+            }
+            for status in statuses {
+                let Some(mailbox) = status.mailbox.and_then(|h| mailboxes.get(&h)) else {
+                    continue;
+                };
+                // The mailbox might have been loaded in the meantime, in which case its
+                // counts are already accurate.
+                if !mailbox.is_cold() {
+                    continue;
+                }
+                let mut changed = false;
+                if let Some(total) = status.messages {
+                    if let Ok(mut exists_lck) = mailbox.exists.lock() {
+                        if exists_lck.len() != total {
+                            exists_lck.clear();
+                            exists_lck.set_not_yet_seen(total);
+                            changed = true;
+                        }
+                    }
+                }
+                if let Some(total) = status.unseen {
+                    if let Ok(mut unseen_lck) = mailbox.unseen.lock() {
+                        if unseen_lck.len() != total {
+                            unseen_lck.clear();
+                            unseen_lck.set_not_yet_seen(total);
+                            changed = true;
+                        }
+                    }
+                }
+                if changed {
+                    emitter
+                        .emit(
+                            RefreshEvent {
+                                account_hash: uid_store.account_hash,
+                                mailbox_hash: mailbox.hash,
+                                kind: RefreshEventKind::MailboxCountsUpdate,
+                            }
+                            .into(),
+                        )
+                        .await;
+                }
+            }
+        }
+    })
+}
+
+/// Watch INBOX for changes by sending a `NOOP` command every `interval`, for
+/// servers that don't support `IDLE`.
+///
+/// Like [`idle`], the other mailboxes are examined every five minutes.
+pub fn poll_with_noop(
+    kit: ImapWatchKit,
+    interval: Duration,
+) -> impl futures::stream::Stream<Item = Result<BackendEvent>> {
+    // duration interval to check other mailboxes for changes
+    const _5_MINS: Duration = Duration::from_secs(5 * 60);
+    try_fn_stream(move |emitter| async move {
+        log::trace!("poll with NOOP");
+        let ImapWatchKit {
+            mut conn,
+            main_conn,
+            uid_store,
+        } = kit;
+        conn.connect().await?;
+        let mailbox: ImapMailbox = find_inbox(&uid_store).await?;
+        let mailbox_hash = mailbox.hash();
+        let mut response = Vec::with_capacity(8 * 1024);
+        if let Some(ev) = examine_watched(&mut conn, mailbox_hash, &mut response).await? {
+            emitter.emit(ev).await;
+        }
+        let mailboxes: HashMap<MailboxHash, ImapMailbox> = {
+            let mailboxes_lck = timeout(uid_store.timeout, uid_store.mailboxes.lock()).await?;
+            mailboxes_lck.clone()
+        };
+        let mut watch = Instant::now();
+        loop {
+            smol::Timer::after(interval).await;
+            // Untagged responses to NOOP (EXISTS, EXPUNGE, FETCH) are processed and
+            // turned into events by read_response().
+            conn.send_command(CommandBody::Noop).await?;
+            conn.read_response(&mut response, RequiredResponses::empty())
+                .await?;
+            let now = Instant::now();
+            if now.duration_since(watch) >= _5_MINS {
+                /* Time to poll the other mailboxes */
+                let mut main_conn_lck = main_conn.lock().await?;
+                for (h, mailbox) in mailboxes.clone() {
+                    if h == mailbox_hash {
+                        continue;
+                    }
+                    if let Some(ev) = examine_updates(mailbox, &mut main_conn_lck).await? {
+                        emitter.emit(ev).await;
+                    }
+                }
+                watch = now;
+            }
+        }
+    })
+}
+
+/// Find the INBOX of the account, waiting for the mailbox list to be
+/// fetched if needed.
+async fn find_inbox(uid_store: &UIDStore) -> Result<ImapMailbox> {
+    let mut retries = 0;
+    loop {
+        let inbox = uid_store
+            .mailboxes
+            .lock()
+            .await
+            .values()
+            .find(|f| f.parent.is_none() && (f.special_usage() == SpecialUsageMailbox::Inbox))
+            .cloned();
+        match inbox {
+            Some(mailbox) => return Ok(mailbox),
+            None if retries >= 10 => {
+                return Err(Error::new(
+                    "INBOX mailbox not found in local mailbox index. the connection might have \
+                     not parsed the IMAP mailboxes correctly",
+                )
+                .set_kind(ErrorKind::TimedOut));
+            }
+            None => {
+                smol::Timer::after(Duration::from_millis(
+                    retries * (4 * crate::utils::random::random_u8() as u64),
+                ))
+                .await;
+                retries += 1;
+            }
+        }
+    }
+}
+
+/// `EXAMINE` the mailbox a watch connection stays on, returning a
+/// [`RefreshEventKind::Rescan`] event if its `UIDVALIDITY` changed.
+async fn examine_watched(
+    conn: &mut ImapConnection,
+    mailbox_hash: MailboxHash,
+    response: &mut Vec<u8>,
+) -> Result<Option<BackendEvent>> {
+    let select_response = conn.examine_mailbox(mailbox_hash, response, true).await?;
+    let mut uidvalidities = conn.uid_store.uidvalidity.lock().unwrap();
+    let mismatch = uidvalidities
+        .insert(mailbox_hash, select_response.uidvalidity)
+        .is_some_and(|v| v != select_response.uidvalidity);
+    Ok(mismatch.then(|| {
+        RefreshEvent {
+            account_hash: conn.uid_store.account_hash,
+            mailbox_hash,
+            kind: RefreshEventKind::Rescan,
+        }
+        .into()
+    }))
+}
+
 pub fn idle(kit: ImapWatchKit) -> impl futures::stream::Stream<Item = Result<BackendEvent>> {
     // duration interval to send heartbeat
     const _10_MINS: Duration = Duration::from_secs(10 * 60);
@@ -81,64 +313,11 @@ pub fn idle(kit: ImapWatchKit) -> impl futures::stream::Stream<Item = Result<Bac
             uid_store,
         } = kit;
         conn.connect().await?;
-        let mailbox: ImapMailbox = {
-            let mut retries = 0;
-            loop {
-                let inbox = uid_store
-                    .mailboxes
-                    .lock()
-                    .await
-                    .values()
-                    .find(|f| {
-                        f.parent.is_none() && (f.special_usage() == SpecialUsageMailbox::Inbox)
-                    })
-                    .cloned();
-                match inbox {
-                    Some(mailbox) => break mailbox,
-                    None if retries >= 10 => {
-                        return Err(Error::new(
-                            "INBOX mailbox not found in local mailbox index. the connection might \
-                             have not parsed the IMAP mailboxes correctly",
-                        )
-                        .set_kind(ErrorKind::TimedOut));
-                    }
-                    None => {
-                        smol::Timer::after(Duration::from_millis(
-                            retries * (4 * crate::utils::random::random_u8() as u64),
-                        ))
-                        .await;
-                        retries += 1;
-                    }
-                }
-            }
-        };
+        let mailbox: ImapMailbox = find_inbox(&uid_store).await?;
         let mailbox_hash = mailbox.hash();
         let mut response = Vec::with_capacity(8 * 1024);
-        let select_response = conn
-            .examine_mailbox(mailbox_hash, &mut response, true)
-            .await?;
-        {
-            let mut mismatch = false;
-            {
-                let mut uidvalidities = uid_store.uidvalidity.lock().unwrap();
-
-                if let Some(v) = uidvalidities.get(&mailbox_hash) {
-                    mismatch = *v != select_response.uidvalidity;
-                }
-                uidvalidities.insert(mailbox_hash, select_response.uidvalidity);
-            }
-            if mismatch {
-                emitter
-                    .emit(
-                        RefreshEvent {
-                            account_hash: uid_store.account_hash,
-                            mailbox_hash,
-                            kind: RefreshEventKind::Rescan,
-                        }
-                        .into(),
-                    )
-                    .await;
-            }
+        if let Some(ev) = examine_watched(&mut conn, mailbox_hash, &mut response).await? {
+            emitter.emit(ev).await;
         }
         let mailboxes: HashMap<MailboxHash, ImapMailbox> = {
             let mailboxes_lck = timeout(uid_store.timeout, uid_store.mailboxes.lock()).await?;
@@ -350,7 +529,10 @@ pub async fn examine_updates(
                 );
                 return Ok(None);
             }
-            let (required_responses, attributes) = common_attributes();
+            let (required_responses, attributes) = common_attributes(
+                *conn.uid_store.quirks.lock().unwrap(),
+                &conn.uid_store.retained_headers,
+            );
             conn.send_command(CommandBody::fetch(v.as_slice(), attributes, true)?)
                 .await?;
             conn.read_response(&mut response, required_responses)
@@ -358,7 +540,10 @@ pub async fn examine_updates(
         } else if select_response.exists > current_exists {
             let min = current_exists.max(1);
 
-            let (required_responses, attributes) = common_attributes();
+            let (required_responses, attributes) = common_attributes(
+                *conn.uid_store.quirks.lock().unwrap(),
+                &conn.uid_store.retained_headers,
+            );
             conn.send_command(CommandBody::fetch(min.., attributes, false)?)
                 .await?;
             conn.read_response(&mut response, required_responses)
@@ -48,11 +48,12 @@ use crate::{
         identity::{Identity, IdentityGet, IdentitySet},
         mailbox::MailboxObject,
         methods::{
-            AddedItem, Changes, ChangesResponse, Get, GetResponse, MethodResponse, QueryChanges,
-            QueryChangesResponse, ResultField, Set,
+            event_source_request_format, AddedItem, Changes, ChangesResponse, Get, GetResponse,
+            MethodResponse, QueryChanges, QueryChangesResponse, ResultField, Set,
         },
         objects::{Id, State},
         protocol::{self, JmapMailCapability, Request},
+        push::{EVENT_SOURCE_PING_SECS, EVENT_SOURCE_TYPES},
         session::Session,
         JmapServerConf, Store,
     },
@@ -447,6 +448,25 @@ impl JmapConnection {
         (self.store.event_consumer)(self.store.account_hash, ev);
     }
 
+    /// Changes of every known mailbox since the last known `Email` state.
+    pub async fn all_email_changes(&self) -> Result<Vec<BackendEvent>> {
+        let mailbox_hashes = self
+            .store
+            .mailboxes
+            .read()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<SmallVec<[MailboxHash; 16]>>();
+        let mut ret = vec![];
+        for mailbox_hash in mailbox_hashes {
+            if let Some(ev) = self.email_changes(mailbox_hash).await? {
+                ret.push(ev);
+            }
+        }
+        Ok(ret)
+    }
+
     pub async fn email_changes(&self, mailbox_hash: MailboxHash) -> Result<Option<BackendEvent>> {
         let mut current_state: State<EmailObject> =
             if let Some(s) = self.store.email_state.lock().await.clone() {
@@ -726,6 +746,35 @@ impl JmapConnection {
         Ok(res_text)
     }
 
+    /// Open a push connection to the session's event source (RFC 8620
+    /// Section 7.3), whose body is a stream of state change events.
+    pub async fn event_source(&self) -> Result<isahc::Response<isahc::AsyncBody>> {
+        let url = event_source_request_format(
+            &self.session_guard().await?.event_source_url,
+            EVENT_SOURCE_TYPES,
+            EVENT_SOURCE_PING_SECS,
+        )?;
+        // The connection stays open indefinitely, so instead of the client's
+        // timeout give up only if even the server's pings stop arriving.
+        let request = http::Request::get(url.as_str())
+            .header(http::header::ACCEPT, "text/event-stream")
+            .timeout(Duration::from_secs(24 * 60 * 60))
+            .low_speed_timeout(1, Duration::from_secs(3 * EVENT_SOURCE_PING_SECS))
+            .body(())
+            .map_err(isahc::Error::from)?;
+        let mut resp = self.client.send_async(request).await?;
+        if !resp.status().is_success() {
+            let kind: crate::error::NetworkErrorKind = resp.status().into();
+            let res_text = resp.text().await.unwrap_or_default();
+            return Err(Error::new(format!(
+                "Could not connect to JMAP event source for {}. Reply from server: {res_text}",
+                self.server_conf.server_url
+            ))
+            .set_kind(kind.into()));
+        }
+        Ok(resp)
+    }
+
     pub async fn get_async(&self, url: &Url) -> Result<isahc::Response<isahc::AsyncBody>> {
         let mut resp = if cfg!(feature = "jmap-trace") {
             let res = self.client.get_async(url.as_str()).await;
@@ -357,6 +357,7 @@ impl From<EmailObject> for crate::Envelope {
             env.set_datetime(d);
         }
         env.set_has_attachments(t.has_attachment);
+        env.set_size(t.size.try_into().unwrap_or(usize::MAX));
         if let Some(subject) = t.subject.take() {
             env.set_subject(subject.into_bytes());
         }
@@ -709,6 +710,10 @@ impl From<crate::search::Query> for Filter<EmailFilterCondition, EmailObject> {
                 Body(t) => {
                     *f = Filter::Condition(EmailFilterCondition::new().body(t.clone()));
                 }
+                Attachment(t) => {
+                    // Servers may search the text of attachments as part of the body.
+                    *f = Filter::Condition(EmailFilterCondition::new().body(t.clone()));
+                }
                 Before(t) => {
                     *f = Filter::Condition(
                         EmailFilterCondition::new().before(timestamp_to_string_utc(
@@ -1095,6 +1095,58 @@ pub fn upload_request_format(
     })
 }
 
+#[allow(clippy::literal_string_with_formatting_args)]
+const TYPES_URL_FORMAT_ARG: &str = "{types}";
+#[allow(clippy::literal_string_with_formatting_args)]
+const CLOSEAFTER_URL_FORMAT_ARG: &str = "{closeafter}";
+#[allow(clippy::literal_string_with_formatting_args)]
+const PING_URL_FORMAT_ARG: &str = "{ping}";
+
+/// Instantiate the `eventSourceUrl` template of the session object (RFC 8620
+/// Section 7.3) for a connection that stays open and receives changes of the
+/// object types in `types`, with a keep-alive ping every `ping` seconds.
+pub fn event_source_request_format(
+    event_source_url: &RequestUrlTemplate,
+    types: &[&str],
+    ping: u64,
+) -> Result<Url> {
+    let mut ret = String::with_capacity(event_source_url.text.len() + 32);
+    let mut prev_pos = 0;
+
+    while let Some(pos) = event_source_url.text.as_bytes()[prev_pos..].find(b"{") {
+        ret.push_str(&event_source_url.text[prev_pos..prev_pos + pos]);
+        prev_pos += pos;
+        if event_source_url.text[prev_pos..].starts_with(TYPES_URL_FORMAT_ARG) {
+            ret.push_str(&types.join(","));
+            prev_pos += TYPES_URL_FORMAT_ARG.len();
+        } else if event_source_url.text[prev_pos..].starts_with(CLOSEAFTER_URL_FORMAT_ARG) {
+            ret.push_str("no");
+            prev_pos += CLOSEAFTER_URL_FORMAT_ARG.len();
+        } else if event_source_url.text[prev_pos..].starts_with(PING_URL_FORMAT_ARG) {
+            ret.push_str(&ping.to_string());
+            prev_pos += PING_URL_FORMAT_ARG.len();
+        } else {
+            ret.push('{');
+            prev_pos += 1;
+        }
+    }
+    if prev_pos != event_source_url.text.len() {
+        ret.push_str(&event_source_url.text[prev_pos..]);
+    }
+    Url::parse(&ret).map_err(|err| {
+        Error::new("Could not instantiate URL from JMAP server's URL template value")
+            .set_details(format!(
+                "`eventSourceUrl` template returned by server in session object could not be \
+                 instantiated:\neventSourceUrl: {}\nresult: {ret}\n\nIf you believe this value \
+                 is correct and should have been accepted, please report it as a bug! Otherwise \
+                 inform the server administrator for this protocol violation.",
+                event_source_url.text
+            ))
+            .set_kind(ErrorKind::ProtocolError)
+            .set_source(Some(Arc::new(err)))
+    })
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UploadResponse {
@@ -81,6 +81,9 @@ use protocol::*;
 pub mod session;
 use session::*;
 
+pub mod push;
+use push::{EventSourceParser, StateChange, EVENT_SOURCE_PING_SECS, EVENT_SOURCE_TYPES};
+
 pub mod objects;
 use objects::{BlobObject, Id, State};
 
@@ -492,32 +495,66 @@ impl MailBackend for JmapType {
     }
 
     fn watch(&mut self) -> ResultStream<BackendEvent> {
+        use futures::{
+            io::{AsyncBufReadExt, BufReader},
+            stream::StreamExt,
+        };
+
         let connection = self.connection.clone();
-        let store = self.store.clone();
         Ok(Box::pin(try_fn_stream(|emitter| async move {
             {
                 let mut conn = connection.lock().await;
                 conn.connect().await?;
             }
             loop {
-                {
-                    let mailbox_hashes = {
-                        store
-                            .mailboxes
-                            .read()
-                            .unwrap()
-                            .keys()
-                            .cloned()
-                            .collect::<SmallVec<[MailboxHash; 16]>>()
-                    };
+                let (push, account_id) = {
                     let conn = connection.lock().await;
-                    for mailbox_hash in mailbox_hashes {
-                        if let Some(ev) = conn.email_changes(mailbox_hash).await? {
+                    let account_id = conn.session_guard().await?.mail_account_id();
+                    (conn.event_source().await, account_id)
+                };
+                let resp = match push {
+                    Ok(resp) => resp,
+                    Err(err) => {
+                        log::debug!(
+                            "JMAP event source unavailable, polling for changes instead: {err}"
+                        );
+                        for ev in connection.lock().await.all_email_changes().await? {
                             emitter.emit(ev).await;
                         }
+                        sleep(Duration::from_secs(EVENT_SOURCE_PING_SECS)).await;
+                        continue;
                     }
+                };
+                let opened = Instant::now();
+                let mut lines = BufReader::new(resp.into_body()).lines();
+                let mut parser = EventSourceParser::new();
+                while let Some(Ok(line)) = lines.next().await {
+                    let Some(event) = parser.feed(&line) else {
+                        continue;
+                    };
+                    if event.event != "state" {
+                        continue;
+                    }
+                    match deserialize_from_str::<StateChange>(&event.data) {
+                        Ok(change) if change.has_changes(&account_id, EVENT_SOURCE_TYPES) => {
+                            for ev in connection.lock().await.all_email_changes().await? {
+                                emitter.emit(ev).await;
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(err) => {
+                            log::warn!("Could not parse JMAP push notification: {err}");
+                        }
+                    }
+                }
+                // The server closed the connection; catch up on anything missed
+                // and reconnect, backing off if it did not stay open for long.
+                for ev in connection.lock().await.all_email_changes().await? {
+                    emitter.emit(ev).await;
+                }
+                if opened.elapsed() < Duration::from_secs(EVENT_SOURCE_PING_SECS) {
+                    sleep(Duration::from_secs(EVENT_SOURCE_PING_SECS)).await;
                 }
-                sleep(Duration::from_secs(60)).await;
             }
         })))
     }
@@ -0,0 +1,108 @@
+/*
+ * meli - jmap module.
+ *
+ * Copyright 2019-2022 Manos Pitsidianakis
+ *
+ * This file is part of meli.
+ *
+ * meli is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * meli is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with meli. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Push notifications over an event source connection, as described in
+//! RFC 8620 Section 7.
+
+use indexmap::IndexMap;
+
+use crate::jmap::objects::{Account, Id};
+
+/// Interval in seconds between keep-alive pings we ask the server to send on
+/// the event source connection.
+pub const EVENT_SOURCE_PING_SECS: u64 = 60;
+
+/// Object types whose changes we subscribe to.
+pub const EVENT_SOURCE_TYPES: &[&str] = &["Email", "Mailbox"];
+
+/// A `StateChange` push notification (RFC 8620 Section 7.1).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "@type", rename = "StateChange")]
+pub struct StateChange {
+    /// New state strings of each changed object type, by account.
+    pub changed: IndexMap<Id<Account>, IndexMap<String, String>>,
+}
+
+impl StateChange {
+    /// Whether any of `types` changed in `account_id`.
+    pub fn has_changes(&self, account_id: &Id<Account>, types: &[&str]) -> bool {
+        self.changed
+            .get(account_id)
+            .is_some_and(|changed| types.iter().any(|t| changed.contains_key(*t)))
+    }
+}
+
+/// A complete event received from an event source stream.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ServerSentEvent {
+    /// Value of the `event` field, `message` if the server sent none.
+    pub event: String,
+    /// Value of the `data` fields, joined with newlines.
+    pub data: String,
+}
+
+/// Incremental parser for the `text/event-stream` format, fed one line at a
+/// time.
+#[derive(Debug, Default)]
+pub struct EventSourceParser {
+    event: Option<String>,
+    data: Option<String>,
+}
+
+impl EventSourceParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a line without its terminator. Returns an event when `line` is
+    /// the blank line dispatching it.
+    pub fn feed(&mut self, line: &str) -> Option<ServerSentEvent> {
+        if line.is_empty() {
+            let data = self.data.take();
+            let event = self.event.take();
+            return data.map(|data| ServerSentEvent {
+                event: event.unwrap_or_else(|| "message".to_string()),
+                data,
+            });
+        }
+        if line.starts_with(':') {
+            // Comment, used by servers as keep-alive.
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => {
+                self.event = Some(value.to_string());
+            }
+            "data" => {
+                if let Some(ref mut data) = self.data {
+                    data.push('\n');
+                    data.push_str(value);
+                } else {
+                    self.data = Some(value.to_string());
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
@@ -1162,3 +1162,64 @@ fn test_jmap_server_get_method_and_response() {
         );
     }
 }
+
+#[test]
+fn test_jmap_push_event_source() {
+    use serde_json::json;
+    use url::Url;
+
+    use crate::jmap::{
+        methods::{event_source_request_format, RequestUrlTemplate},
+        objects::{Account, Id},
+        push::{EventSourceParser, ServerSentEvent, StateChange},
+    };
+
+    let event_source_url: RequestUrlTemplate = serde_json::from_value(json!(
+        "https://jmap.example.com/eventsource/?types={types}&closeafter={closeafter}&ping={ping}"
+    ))
+    .unwrap();
+    assert_eq!(
+        event_source_request_format(&event_source_url, &["Email", "Mailbox"], 60).unwrap(),
+        Url::parse(
+            "https://jmap.example.com/eventsource/?types=Email,Mailbox&closeafter=no&ping=60"
+        )
+        .unwrap()
+    );
+
+    let mut parser = EventSourceParser::new();
+    let mut events = vec![];
+    for line in [
+        ": keep-alive",
+        "event: ping",
+        "data: {\"interval\":60}",
+        "",
+        "",
+        "event: state",
+        "data: {\"@type\":\"StateChange\",",
+        "data:\"changed\":{\"a3123\":{\"Email\":\"d35ecb040aab\"}}}",
+        "",
+    ] {
+        events.extend(parser.feed(line));
+    }
+    assert_eq!(
+        events,
+        vec![
+            ServerSentEvent {
+                event: "ping".into(),
+                data: r#"{"interval":60}"#.into(),
+            },
+            ServerSentEvent {
+                event: "state".into(),
+                data: "{\"@type\":\"StateChange\",\n\"changed\":{\"a3123\":{\"Email\":\"d35ecb040aab\"}}}"
+                    .into(),
+            },
+        ]
+    );
+
+    let change: StateChange = serde_json::from_str(&events[1].data).unwrap();
+    let account_id: Id<Account> = "a3123".into();
+    assert_eq!(change.changed[&account_id]["Email"], "d35ecb040aab");
+    assert!(change.has_changes(&account_id, &["Email", "Mailbox"]));
+    assert!(!change.has_changes(&account_id, &["Mailbox"]));
+    assert!(!change.has_changes(&"other".into(), &["Email"]));
+}
@@ -143,7 +143,7 @@ pub mod text;
 pub use utils::{
     datetime::UnixTimestamp,
     logging::{LogLevel, StderrLogger},
-    SortField, SortOrder,
+    SortChain, SortField, SortOrder,
 };
 
 pub mod contacts;
@@ -0,0 +1,147 @@
+//
+// melib
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of melib.
+//
+// melib is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// melib is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with melib. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Memory mapped maildir entries.
+//!
+//! Maildir entries are never modified in place: flag changes rename the file
+//! and new content is written to a new file. This makes it possible to map
+//! them read-only and parse envelopes directly from the mapping instead of
+//! copying the file contents into a buffer first.
+//!
+//! If another program truncates a mapped file anyway, reading past its new end
+//! raises `SIGBUS`, which is not handled. Mapping is therefore only done if
+//! the account's `use_mmap` setting is enabled, mappings are only kept while
+//! an entry is parsed, and message bodies are always read into buffers.
+//! Files are read with `read(2)` otherwise.
+
+use std::{
+    fs,
+    io::Read,
+    ops::Deref,
+    os::fd::AsRawFd,
+    path::{Path, PathBuf},
+};
+
+use crate::error::Result;
+
+#[derive(Debug)]
+enum Contents {
+    /// A read-only, private memory mapping of `len` bytes.
+    Mapped {
+        ptr: *mut libc::c_void,
+        len: usize,
+    },
+    Read(Vec<u8>),
+}
+
+/// The contents of a whole file, either memory mapped or read into a buffer.
+#[derive(Debug)]
+pub struct MappedFile {
+    contents: Contents,
+    path: PathBuf,
+}
+
+// SAFETY: The mapping is read-only and never mutated after creation, so it can
+// be shared and sent across threads like a `Box<[u8]>`.
+unsafe impl Send for MappedFile {}
+// SAFETY: See `Send` implementation above.
+unsafe impl Sync for MappedFile {}
+
+impl MappedFile {
+    /// Map the file at `path` if `mmap` is set, or read it otherwise.
+    pub fn open(path: &Path, mmap: bool) -> Result<Self> {
+        let mut file = fs::File::open(path)?;
+        let len = usize::try_from(file.metadata()?.len()).unwrap_or(usize::MAX);
+        if !mmap || len == 0 {
+            // Zero-length mappings are invalid.
+            let mut buf = Vec::with_capacity(len);
+            file.read_to_end(&mut buf)?;
+            return Ok(Self {
+                contents: Contents::Read(buf),
+                path: path.to_path_buf(),
+            });
+        }
+        // SAFETY: `file` is an open file descriptor and `len` is its size. The
+        // descriptor can be closed afterwards since the mapping keeps its own
+        // reference to the file.
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(Self {
+            contents: Contents::Mapped { ptr, len },
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Path of the file at the time it was opened.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file is memory mapped.
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.contents, Contents::Mapped { .. })
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self.contents {
+            // SAFETY: `ptr` points to a live mapping of `len` readable bytes
+            // that outlives the returned borrow.
+            Contents::Mapped { ptr, len } => unsafe {
+                std::slice::from_raw_parts(ptr as *const u8, len)
+            },
+            Contents::Read(ref buf) => buf,
+        }
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        if let Contents::Mapped { ptr, len } = self.contents {
+            // SAFETY: `ptr` and `len` describe a mapping created in `open`
+            // that has not been unmapped yet.
+            unsafe {
+                libc::munmap(ptr, len);
+            }
+        }
+    }
+}
@@ -27,7 +27,7 @@
 use std::{
     collections::{HashMap, HashSet, VecDeque},
     fs,
-    io::{self, Read, Write},
+    io::{self, Write},
     os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
@@ -38,12 +38,14 @@ use futures::{channel::mpsc, SinkExt};
 use notify::Watcher;
 use regex::Regex;
 
+pub mod mmap;
 pub mod utilities;
 pub mod watch;
 
 #[cfg(test)]
 mod tests;
 
+use mmap::MappedFile;
 use utilities::{
     HashIndex, HashIndexes, MaildirFilePathExt, MaildirMailbox, MaildirMailboxPathExt, MaildirOp,
     PathMod,
@@ -63,12 +65,20 @@ pub struct Configuration {
     /// Is `root_mailbox` a valid maildir folder or just a folder containing
     /// valid maildir folders?
     pub is_root_a_mailbox: bool,
+    /// Number of threads that parse the messages of a mailbox when it is
+    /// fetched.
+    pub parsing_threads: usize,
+    /// Parse messages from memory mappings of their files instead of reading
+    /// them, see [`mmap`].
+    pub use_mmap: bool,
     pub settings: AccountSettings,
 }
 
 impl Configuration {
     pub fn new(settings: &AccountSettings) -> Result<Self> {
         const RENAME_REGEX_FIELDNAME: &str = "rename_regex";
+        const PARSING_THREADS_FIELDNAME: &str = "parsing_threads";
+        const USE_MMAP_FIELDNAME: &str = "use_mmap";
 
         let rename_regex = if let Some(v) = settings.extra.get(RENAME_REGEX_FIELDNAME).map(|v| {
             Regex::new(v).map_err(|e| {
@@ -86,8 +96,41 @@ impl Configuration {
             None
         };
 
+        let parsing_threads = if let Some(v) = settings.extra.get(PARSING_THREADS_FIELDNAME) {
+            match v.parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => {
+                    return Err(Error::new(format!(
+                        "Configuration error ({}): Invalid value for field \
+                         `{PARSING_THREADS_FIELDNAME}`: {v}, expected a positive integer",
+                        settings.name.as_str(),
+                    ))
+                    .set_kind(ErrorKind::ValueError));
+                }
+            }
+        } else {
+            std::thread::available_parallelism()
+                .map(Into::into)
+                .unwrap_or(1)
+        };
+
+        let use_mmap = if let Some(v) = settings.extra.get(USE_MMAP_FIELDNAME) {
+            v.parse::<bool>().map_err(|_| {
+                Error::new(format!(
+                    "Configuration error ({}): Invalid value for field `{USE_MMAP_FIELDNAME}`: \
+                     {v}, expected true or false",
+                    settings.name.as_str(),
+                ))
+                .set_kind(ErrorKind::ValueError)
+            })?
+        } else {
+            false
+        };
+
         Ok(Self {
             rename_regex,
+            parsing_threads,
+            use_mmap,
             settings: settings.clone(),
             ..Self::default()
         })
@@ -146,6 +189,8 @@ impl MailBackend for MaildirType {
         let hash_indexes = self.hash_indexes.clone();
         let mailbox_index = self.mailbox_index.clone();
         let chunk_size = 2048;
+        let parsing_threads = self.config.parsing_threads;
+        let use_mmap = self.config.use_mmap;
         path.push("new");
         for p in path.read_dir()?.flatten() {
             _ = utilities::move_to_cur(&self.config, &p.path());
@@ -157,17 +202,17 @@ impl MailBackend for MaildirType {
             .flatten()
             .map(|e| e.path())
             .collect::<Vec<_>>();
-        async fn fetch(
+        fn fetch(
             chunk: Vec<std::path::PathBuf>,
             mailbox_hash: MailboxHash,
             unseen: Arc<Mutex<usize>>,
             total: Arc<Mutex<usize>>,
             hash_indexes: HashIndexes,
             mailbox_index: Arc<Mutex<HashMap<EnvelopeHash, MailboxHash>>>,
+            use_mmap: bool,
         ) -> Result<Option<Vec<Envelope>>> {
             let mut local_r: Vec<Envelope> = Vec::with_capacity(chunk.len());
             let mut unseen_total: usize = 0;
-            let mut buf = Vec::with_capacity(4096);
             for file in chunk {
                 let env_hash = file.to_envelope_hash();
                 {
@@ -176,10 +221,8 @@ impl MailBackend for MaildirType {
                     hi.index.insert(env_hash, file.to_path_buf().into());
                     hi.reverse_index.insert(file.to_path_buf(), env_hash);
                 }
-                let mut reader = io::BufReader::new(fs::File::open(&file)?);
-                buf.clear();
-                reader.read_to_end(&mut buf)?;
-                match Envelope::from_bytes(buf.as_slice(), Some(file.flags())) {
+                let mapped = MappedFile::open(&file, use_mmap)?;
+                match Envelope::from_bytes(&mapped, Some(file.flags())) {
                     Ok(mut env) => {
                         env.set_hash(env_hash);
                         mailbox_index.lock().unwrap().insert(env_hash, mailbox_hash);
@@ -207,20 +250,47 @@ impl MailBackend for MaildirType {
         }
         Ok(Box::pin(try_fn_stream(|emitter| async move {
             for chunk in files.chunks(chunk_size) {
-                if let Some(res) = fetch(
-                    chunk.to_vec(),
-                    mailbox_hash,
-                    unseen.clone(),
-                    total.clone(),
-                    hash_indexes.clone(),
-                    mailbox_index.clone(),
-                )
-                .await
-                .map_err(|err| {
-                    log::debug!("fetch err {err:?}");
-                    err
-                })? {
-                    emitter.emit(res).await;
+                // Parse the chunk in `parsing_threads` parts concurrently.
+                let part_size = chunk.len().div_ceil(parsing_threads).max(1);
+                let results = std::thread::scope(|scope| {
+                    chunk
+                        .chunks(part_size)
+                        .map(|part| {
+                            let (unseen, total) = (unseen.clone(), total.clone());
+                            let hash_indexes = hash_indexes.clone();
+                            let mailbox_index = mailbox_index.clone();
+                            scope.spawn(move || {
+                                fetch(
+                                    part.to_vec(),
+                                    mailbox_hash,
+                                    unseen,
+                                    total,
+                                    hash_indexes,
+                                    mailbox_index,
+                                    use_mmap,
+                                )
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                        .map(|handle| {
+                            handle.join().unwrap_or_else(|_| {
+                                Err(Error::new("Maildir parsing thread panicked."))
+                            })
+                        })
+                        .collect::<Vec<Result<Option<Vec<Envelope>>>>>()
+                });
+                let mut envelopes = vec![];
+                for res in results {
+                    if let Some(res) = res.map_err(|err| {
+                        log::debug!("fetch err {err:?}");
+                        err
+                    })? {
+                        envelopes.extend(res);
+                    }
+                }
+                if !envelopes.is_empty() {
+                    emitter.emit(envelopes).await;
                 }
             }
             Ok(())
@@ -241,7 +311,6 @@ impl MailBackend for MaildirType {
         Ok(Box::pin(async move {
             let thunk = move |sender: &BackendEventConsumer| {
                 log::trace!("refreshing {mailbox_hash:?}");
-                let mut buf = Vec::with_capacity(4096);
                 let files = Self::list_mail_in_maildir_fs(&config, path.clone(), false)?;
                 let mut removed_hashes = {
                     let mut map = hash_indexes.lock().unwrap();
@@ -260,10 +329,8 @@ impl MailBackend for MaildirType {
                         map.index.insert(env_hash, file.to_path_buf().into());
                         map.reverse_index.insert(file.to_path_buf(), env_hash);
                     }
-                    let mut reader = io::BufReader::new(fs::File::open(&file)?);
-                    buf.clear();
-                    reader.read_to_end(&mut buf)?;
-                    if let Ok(mut env) = Envelope::from_bytes(buf.as_slice(), Some(file.flags())) {
+                    let mapped = MappedFile::open(&file, config.use_mmap)?;
+                    if let Ok(mut env) = Envelope::from_bytes(&mapped, Some(file.flags())) {
                         env.set_hash(env_hash);
                         mailbox_index
                             .lock()
@@ -958,6 +1025,8 @@ impl MaildirType {
         }
         _ = Configuration::new(s)?;
         _ = s.extra.swap_remove("rename_regex");
+        _ = s.extra.swap_remove("parsing_threads");
+        _ = s.extra.swap_remove("use_mmap");
 
         Ok(())
     }
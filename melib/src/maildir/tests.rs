@@ -33,6 +33,7 @@ use crate::{
     email::Flag,
     error::Result,
     maildir::{
+        mmap::MappedFile,
         utilities::{move_to_cur, MaildirFilePathExt, MaildirMailbox},
         Configuration, MaildirType,
     },
@@ -295,6 +296,7 @@ fn new_maildir_backend(
         subscribed_mailboxes,
         mailboxes,
         manual_refresh: true,
+        retained_headers: vec![],
         extra,
     };
 
@@ -547,3 +549,29 @@ fn test_maildir_mailbox_paths() {
         );
     }
 }
+
+#[test]
+fn test_maildir_mapped_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let entry = temp_dir.path().join("1423819205.29514_1:2,S");
+    let empty = temp_dir.path().join("1423819205.29516_1:2,S");
+    std::fs::write(&entry, b"Subject: first\n\nbody\n").unwrap();
+    std::fs::write(&empty, b"").unwrap();
+
+    assert_eq!(&*MappedFile::open(&empty, true).unwrap(), b"");
+    assert_eq!(&*MappedFile::open(&empty, false).unwrap(), b"");
+
+    let read = MappedFile::open(&entry, false).unwrap();
+    assert!(!read.is_mapped());
+    assert_eq!(&read[..], b"Subject: first\n\nbody\n");
+    let mapped = MappedFile::open(&entry, true).unwrap();
+    assert!(mapped.is_mapped());
+    assert_eq!(&mapped[..], b"Subject: first\n\nbody\n");
+    assert_eq!(mapped.path(), entry.as_path());
+    // Mappings outlive renames of their file.
+    let renamed = temp_dir.path().join("1423819205.29514_1:2,RS");
+    std::fs::rename(&entry, &renamed).unwrap();
+    assert_eq!(&mapped[..], b"Subject: first\n\nbody\n");
+    let env = Envelope::from_bytes(&mapped, Some(renamed.flags())).unwrap();
+    assert_eq!(env.subject(), "first");
+}
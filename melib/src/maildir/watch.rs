@@ -22,8 +22,6 @@
 
 use std::{
     collections::HashMap,
-    fs,
-    io::{self, Read},
     path::Path,
     sync::{Arc, Mutex},
 };
@@ -35,6 +33,7 @@ use crate::{
     backends::{prelude::*, RefreshEventKind::*},
     error::Result,
     maildir::{
+        mmap::MappedFile,
         utilities::{
             move_to_cur, HashIndex, HashIndexes, MaildirFilePathExt, MaildirMailboxPathExt, PathMod,
         },
@@ -94,7 +93,6 @@ impl MaildirWatch {
         try_fn_stream(|emitter| async move {
             // Move watcher to prevent it being Dropped.
             let _watcher = watcher;
-            let mut buf = Vec::with_capacity(4096);
             let mut events = vec![];
             'watch_loop: loop {
                 #[allow(clippy::iter_with_drain)]
@@ -145,10 +143,10 @@ impl MaildirWatch {
                                         continue;
                                     };
                                     if let Ok(env) = add_path_to_index(
+                                        &config,
                                         &hash_indexes,
                                         mailbox_hash,
                                         pathbuf.as_path(),
-                                        &mut buf,
                                     ) {
                                         mailbox_index
                                             .lock()
@@ -213,10 +211,10 @@ impl MaildirWatch {
                                             // Did we just miss a Create event? In any case, create
                                             // envelope.
                                             if let Ok(env) = add_path_to_index(
+                                                &config,
                                                 &hash_indexes,
                                                 mailbox_hash,
                                                 pathbuf.as_path(),
-                                                &mut buf,
                                             ) {
                                                 mailbox_index
                                                     .lock()
@@ -254,10 +252,10 @@ impl MaildirWatch {
                                     };
                                     drop(hash_indexes_lock);
                                     if let Ok(env) = add_path_to_index(
+                                        &config,
                                         &hash_indexes,
                                         mailbox_hash,
                                         pathbuf.as_path(),
-                                        &mut buf,
                                     ) {
                                         {
                                             let mut mi = mailbox_index.lock().unwrap();
@@ -414,10 +412,10 @@ impl MaildirWatch {
                                             kind: Remove(old_hash),
                                         }));
                                         if let Ok(env) = add_path_to_index(
+                                            &config,
                                             &hash_indexes,
                                             dest_mailbox,
                                             dest.as_path(),
-                                            &mut buf,
                                         ) {
                                             mailbox_index
                                                 .lock()
@@ -512,10 +510,10 @@ impl MaildirWatch {
                                     }
                                     drop(hash_indexes_lock);
                                     if let Ok(env) = add_path_to_index(
+                                        &config,
                                         &hash_indexes,
                                         dest_mailbox.unwrap_or(mailbox_hash),
                                         dest.as_path(),
-                                        &mut buf,
                                     ) {
                                         mailbox_index.lock().unwrap().insert(
                                             env.hash(),
@@ -553,10 +551,10 @@ impl MaildirWatch {
                                 } else if let Some(dest_mailbox) = dest_mailbox {
                                     drop(hash_indexes_lock);
                                     if let Ok(env) = add_path_to_index(
+                                        &config,
                                         &hash_indexes,
                                         dest_mailbox,
                                         dest.as_path(),
-                                        &mut buf,
                                     ) {
                                         mailbox_index
                                             .lock()
@@ -635,10 +633,10 @@ impl MaildirWatch {
 }
 
 fn add_path_to_index(
+    config: &Configuration,
     hash_index: &HashIndexes,
     mailbox_hash: MailboxHash,
     path: &Path,
-    buf: &mut Vec<u8>,
 ) -> Result<Envelope> {
     log::trace!(
         "add_path_to_index path {:?} filename{:?}",
@@ -646,10 +644,8 @@ fn add_path_to_index(
         path.file_name()
     );
     let env_hash = path.to_envelope_hash();
-    let mut reader = io::BufReader::new(fs::File::open(path)?);
-    buf.clear();
-    reader.read_to_end(buf)?;
-    let mut env = Envelope::from_bytes(buf.as_slice(), Some(path.flags()))?;
+    let mapped = MappedFile::open(path, config.use_mmap)?;
+    let mut env = Envelope::from_bytes(&mapped, Some(path.flags()))?;
     env.set_hash(env_hash);
     {
         let mut lck = hash_index.lock().unwrap();
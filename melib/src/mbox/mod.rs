@@ -398,6 +398,36 @@ macro_rules! find_From__line {
 }
 
 impl MboxFormat {
+    /// Reverse the `>From ` quoting this format applies to message bodies.
+    ///
+    /// `mboxrd` removes one `>` from every line matching `^>+From `, which
+    /// restores the original message exactly. `mboxo` and `mboxcl` can only
+    /// turn `>From ` back into `From `, since they don't distinguish quoted
+    /// lines from lines that started with `>From ` to begin with. `mboxcl2`
+    /// does not quote at all.
+    pub fn unescape<'a>(&self, input: &'a [u8]) -> Cow<'a, [u8]> {
+        let is_escaped = |line: &[u8]| match self {
+            Self::MboxRd => line
+                .iter()
+                .position(|b| *b != b'>')
+                .is_some_and(|pos| pos > 0 && line[pos..].starts_with(b"From ")),
+            Self::MboxO | Self::MboxCl => line.starts_with(b">From "),
+            Self::MboxCl2 => false,
+        };
+        if !input.split(|b| *b == b'\n').any(is_escaped) {
+            return Cow::Borrowed(input);
+        }
+        let mut ret = Vec::with_capacity(input.len());
+        for line in input.split_inclusive(|b| *b == b'\n') {
+            if is_escaped(line) {
+                ret.extend_from_slice(&line[1..]);
+            } else {
+                ret.extend_from_slice(line);
+            }
+        }
+        Cow::Owned(ret)
+    }
+
     pub fn parse<'i>(&self, input: &'i [u8], is_crlf: bool) -> ParsingResult<'i, Envelope> {
         let mut input = input;
         match self {
@@ -748,6 +778,64 @@ pub fn mbox_parse(
     Ok((&[], envelopes))
 }
 
+/// Offset of the From_ line of the message whose headers start at `offset`.
+fn from_line_offset(input: &[u8], offset: Offset) -> Offset {
+    input[..offset.saturating_sub(1).min(input.len())]
+        .rfind(b"\n")
+        .map(|pos| pos + 1)
+        .unwrap_or(0)
+}
+
+/// Remove the messages in `remove` from the contents `input` of an `mbox`
+/// file, and return the new contents.
+///
+/// Each message is cut from its From_ line up to the From_ line of the
+/// message that follows it. The offsets of the remaining messages in `index`
+/// are updated to point into the returned contents and removed messages are
+/// dropped from it. Anything before the first message is kept as is.
+pub fn mbox_compact(
+    index: &mut HashMap<EnvelopeHash, (Offset, Length)>,
+    input: &[u8],
+    remove: &[EnvelopeHash],
+    is_crlf: bool,
+) -> Vec<u8> {
+    let mut messages: Vec<(Offset, EnvelopeHash)> = index
+        .iter()
+        .map(|(hash, (offset, _))| (from_line_offset(input, *offset), *hash))
+        .collect();
+    messages.sort_unstable();
+    let mut ret = Vec::with_capacity(input.len());
+    ret.extend_from_slice(&input[..messages.first().map(|(o, _)| *o).unwrap_or(input.len())]);
+    let mut removed = 0;
+    let mut last_removed = false;
+    for (i, (start, hash)) in messages.iter().enumerate() {
+        let end = messages.get(i + 1).map(|(o, _)| *o).unwrap_or(input.len());
+        last_removed = remove.contains(hash);
+        if last_removed {
+            index.remove(hash);
+            removed += end - start;
+        } else {
+            if let Some((offset, _)) = index.get_mut(hash) {
+                *offset -= removed;
+            }
+            ret.extend_from_slice(&input[*start..end]);
+        }
+    }
+    if last_removed {
+        // Drop the blank line that separated the new last message from the
+        // removed ones.
+        let line_ending: &[u8] = if is_crlf { b"\r\n" } else { b"\n" };
+        if ret.ends_with(line_ending) && ret[..ret.len() - line_ending.len()].ends_with(line_ending)
+        {
+            ret.truncate(ret.len() - line_ending.len());
+        }
+        for (offset, length) in index.values_mut() {
+            *length = (*length).min(ret.len().saturating_sub(*offset));
+        }
+    }
+    ret
+}
+
 pub struct MessageIterator<'a> {
     pub is_crlf: bool,
     pub index: Arc<Mutex<HashMap<EnvelopeHash, (Offset, Length)>>>,
@@ -1194,9 +1282,16 @@ impl MailBackend for MboxType {
             index[&hash]
         };
         let mailbox_path = mailboxes_lck[&mailbox_hash].fs_path.clone();
+        let format = mailboxes_lck[&mailbox_hash].format;
         let op = MboxOp::new(hash, mailbox_path.as_path(), offset, length);
 
-        Ok(Box::pin(async move { op.as_bytes().await }))
+        Ok(Box::pin(async move {
+            let bytes = op.as_bytes().await?;
+            Ok(match format.unescape(&bytes) {
+                Cow::Borrowed(_) => bytes,
+                Cow::Owned(unescaped) => unescaped,
+            })
+        }))
     }
 
     fn copy_messages(
@@ -1226,25 +1321,186 @@ impl MailBackend for MboxType {
 
     fn delete_messages(
         &mut self,
-        _env_hashes: EnvelopeHashBatch,
-        _mailbox_hash: MailboxHash,
+        env_hashes: EnvelopeHashBatch,
+        mailbox_hash: MailboxHash,
     ) -> ResultFuture<()> {
-        Err(
-            Error::new("Deleting messages is currently unimplemented for mbox backend")
-                .set_kind(ErrorKind::NotImplemented),
-        )
+        if !self.mailboxes.lock().unwrap().contains_key(&mailbox_hash) {
+            return Err(Error::new("Invalid mailbox hash").set_kind(ErrorKind::ValueError));
+        }
+        let account_hash = AccountHash::from_bytes(self.account_name.as_bytes());
+        let mailboxes = self.mailboxes.clone();
+        let mailbox_index = self.mailbox_index.clone();
+        let event_consumer = self.event_consumer.clone();
+        Ok(Box::pin(async move {
+            let removed = smol::unblock(move || -> Result<Vec<EnvelopeHash>> {
+                use std::io::{Seek, Write};
+
+                let mut mailboxes_lck = mailboxes.lock().unwrap();
+                let mailbox = mailboxes_lck.get_mut(&mailbox_hash).ok_or_else(|| {
+                    Error::new("Invalid mailbox hash").set_kind(ErrorKind::ValueError)
+                })?;
+                let file = std::fs::OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(&mailbox.fs_path)?;
+                let mut file = file.lock(FileLockOptions::try_thrice(), &mailbox.fs_path)?;
+                let mut contents = Vec::new();
+                file.read_to_end(&mut contents)?;
+                if !contents.starts_with(&mailbox.content) {
+                    return Err(Error::new(format!(
+                        "mbox file {} was rewritten by another program, refusing to overwrite it. \
+                         Reload the mailbox and try again.",
+                        mailbox.fs_path.display()
+                    )));
+                }
+                let is_crlf: bool = contents.find(b"\r\n").is_some();
+                let mut index = mailbox.index.lock().unwrap();
+                let removed: Vec<EnvelopeHash> = env_hashes
+                    .iter()
+                    .filter(|env_hash| index.contains_key(env_hash))
+                    .collect();
+                if removed.is_empty() {
+                    return Ok(removed);
+                }
+                let unseen = removed
+                    .iter()
+                    .filter(|env_hash| {
+                        let start = from_line_offset(&mailbox.content, index[*env_hash].0);
+                        mailbox
+                            .format
+                            .parse(&mailbox.content[start..], is_crlf)
+                            .is_ok_and(|(_, env)| !env.is_seen())
+                    })
+                    .count();
+                let new_content = mbox_compact(&mut index, &mailbox.content, &removed, is_crlf);
+                drop(index);
+                file.set_len(0)?;
+                file.rewind()?;
+                file.write_all(&new_content)?;
+                // Messages appended since the mailbox was last read are not in the index
+                // yet; keep them after the compacted part for the watcher to pick up.
+                file.write_all(&contents[mailbox.content.len()..])?;
+                file.flush()?;
+                mailbox.content = new_content;
+                {
+                    let mut total = mailbox.total.lock().unwrap();
+                    *total = total.saturating_sub(removed.len());
+                    let mut unseen_lck = mailbox.unseen.lock().unwrap();
+                    *unseen_lck = unseen_lck.saturating_sub(unseen);
+                }
+                let mut mailbox_index_lck = mailbox_index.lock().unwrap();
+                for env_hash in &removed {
+                    mailbox_index_lck.remove(env_hash);
+                }
+                Ok(removed)
+            })
+            .await?;
+            for env_hash in removed {
+                (event_consumer)(
+                    account_hash,
+                    BackendEvent::Refresh(RefreshEvent {
+                        account_hash,
+                        mailbox_hash,
+                        kind: RefreshEventKind::Remove(env_hash),
+                    }),
+                );
+            }
+            Ok(())
+        }))
     }
 
     fn save(
         &mut self,
-        _bytes: Vec<u8>,
-        _mailbox_hash: MailboxHash,
-        _flags: Option<Flag>,
+        bytes: Vec<u8>,
+        mailbox_hash: MailboxHash,
+        flags: Option<Flag>,
     ) -> ResultFuture<()> {
-        Err(
-            Error::new("Saving messages is currently unimplemented for mbox backend")
-                .set_kind(ErrorKind::NotImplemented),
-        )
+        if !self.mailboxes.lock().unwrap().contains_key(&mailbox_hash) {
+            return Err(Error::new("Invalid mailbox hash").set_kind(ErrorKind::ValueError));
+        }
+        let account_hash = AccountHash::from_bytes(self.account_name.as_bytes());
+        let mailboxes = self.mailboxes.clone();
+        let mailbox_index = self.mailbox_index.clone();
+        let event_consumer = self.event_consumer.clone();
+        Ok(Box::pin(async move {
+            let env = smol::unblock(move || -> Result<Option<Envelope>> {
+                use std::io::Write;
+
+                let mut mailboxes_lck = mailboxes.lock().unwrap();
+                let mailbox = mailboxes_lck.get_mut(&mailbox_hash).ok_or_else(|| {
+                    Error::new("Invalid mailbox hash").set_kind(ErrorKind::ValueError)
+                })?;
+                let file = std::fs::OpenOptions::new()
+                    .read(true)
+                    .append(true)
+                    .open(&mailbox.fs_path)?;
+                let mut file = file.lock(FileLockOptions::try_thrice(), &mailbox.fs_path)?;
+                let mut contents = Vec::new();
+                file.read_to_end(&mut contents)?;
+                let is_crlf: bool = if contents.is_empty() {
+                    bytes.find(b"\r\n").is_some()
+                } else {
+                    contents.find(b"\r\n").is_some()
+                };
+                let envelope_from = Envelope::from_bytes(&bytes, None)
+                    .ok()
+                    .and_then(|env| env.from().first().cloned());
+                let offset = contents.len();
+                mailbox.format.append(
+                    &mut contents,
+                    &bytes,
+                    envelope_from.as_ref(),
+                    Some(crate::utils::datetime::now()),
+                    (flags.unwrap_or_default(), vec![]),
+                    MboxMetadata::CClient,
+                    offset == 0,
+                    is_crlf,
+                )?;
+                file.write_all(&contents[offset..])?;
+                file.flush()?;
+                // Only index what was just written if the mailbox is up to date, otherwise
+                // let the watcher pick up all new messages.
+                if mailbox.content.len() != offset {
+                    return Ok(None);
+                }
+                let line_ending: &[u8] = if is_crlf { b"\r\n" } else { b"\n" };
+                let start = offset + contents[offset..].find(b"From ").unwrap_or(0);
+                let (_, env) = mailbox
+                    .format
+                    .parse(&contents[start..], is_crlf)
+                    .map_err(|(_, err)| *err)?;
+                let headers_offset = contents[start..]
+                    .find(line_ending)
+                    .map(|pos| start + pos + line_ending.len())
+                    .unwrap_or(contents.len());
+                mailbox.index.lock().unwrap().insert(
+                    env.hash(),
+                    (headers_offset, contents.len() - headers_offset),
+                );
+                if !env.is_seen() {
+                    *mailbox.unseen.lock().unwrap() += 1;
+                }
+                *mailbox.total.lock().unwrap() += 1;
+                mailbox_index
+                    .lock()
+                    .unwrap()
+                    .insert(env.hash(), mailbox_hash);
+                mailbox.content = contents;
+                Ok(Some(env))
+            })
+            .await?;
+            if let Some(env) = env {
+                (event_consumer)(
+                    account_hash,
+                    BackendEvent::Refresh(RefreshEvent {
+                        account_hash,
+                        mailbox_hash,
+                        kind: RefreshEventKind::Create(Box::new(env)),
+                    }),
+                );
+            }
+            Ok(())
+        }))
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
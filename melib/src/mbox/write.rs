@@ -25,6 +25,79 @@ use crate::utils::{
     parsec::{CRLF, LF},
 };
 
+impl MboxMetadata {
+    /// Headers that store `flags` and `tags` in this metadata format.
+    pub fn headers(self, flags: Flag, tags: &[&str]) -> Vec<(HeaderName, String)> {
+        match self {
+            Self::CClient => {
+                let mut ret = vec![];
+                if flags.is_seen() {
+                    ret.push((HeaderName::STATUS, "R".into()));
+                }
+                if flags.is_flagged()
+                    || flags.is_replied()
+                    || flags.is_draft()
+                    || flags.is_trashed()
+                {
+                    ret.push((
+                        HeaderName::X_STATUS,
+                        format!(
+                            "{flagged}{replied}{draft}{trashed}",
+                            flagged = if flags.is_flagged() { "F" } else { "" },
+                            replied = if flags.is_replied() { "A" } else { "" },
+                            draft = if flags.is_draft() { "T" } else { "" },
+                            trashed = if flags.is_trashed() { "D" } else { "" }
+                        ),
+                    ));
+                }
+                if !tags.is_empty() {
+                    ret.push((HeaderName::X_KEYWORDS, tags.join(" ")));
+                }
+                ret
+            }
+            Self::None => vec![],
+        }
+    }
+
+    /// Write `input` as a standalone message, e.g. an `.eml` file, with any
+    /// metadata headers it already has replaced by ones for `flags` and
+    /// `tags`.
+    pub fn write_message(
+        self,
+        writer: &mut dyn std::io::Write,
+        input: &[u8],
+        (flags, tags): (Flag, Vec<&str>),
+    ) -> Result<()> {
+        if tags.iter().any(|t| t.contains(' ')) {
+            return Err(Error::new("mbox tags/keywords can't contain spaces"));
+        }
+        let (mut headers, body) = parser::mail(input)?;
+        headers.retain(|(header_name, _)| {
+            header_name != HeaderName::STATUS
+                && header_name != HeaderName::X_STATUS
+                && header_name != HeaderName::X_KEYWORDS
+        });
+        let line_ending: &'static [u8] = if input.windows(CRLF.len()).any(|w| w == CRLF) {
+            CRLF
+        } else {
+            LF
+        };
+        for (h, v) in headers {
+            writer.write_fmt(format_args!("{h}: "))?;
+            writer.write_all(v)?;
+            writer.write_all(line_ending)?;
+        }
+        for (h, v) in self.headers(flags, &tags) {
+            writer.write_fmt(format_args!("{h}: "))?;
+            writer.write_all(v.as_bytes())?;
+            writer.write_all(line_ending)?;
+        }
+        writer.write_all(line_ending)?;
+        writer.write_all(body)?;
+        Ok(())
+    }
+}
+
 impl MboxFormat {
     #[allow(clippy::too_many_arguments)]
     pub fn append(
@@ -98,48 +171,13 @@ impl MboxFormat {
             }
             Ok::<(), Error>(())
         };
-        let write_metadata_fn = |writer: &mut dyn std::io::Write| match metadata_format {
-            MboxMetadata::CClient => {
-                for (h, v) in {
-                    if flags.is_seen() {
-                        Some((HeaderName::STATUS, "R".into()))
-                    } else {
-                        None
-                    }
-                    .into_iter()
-                    .chain(
-                        if !flags.is_flagged()
-                            && !flags.is_replied()
-                            && !flags.is_draft()
-                            && !flags.is_trashed()
-                        {
-                            None
-                        } else {
-                            Some((
-                                HeaderName::X_STATUS,
-                                format!(
-                                    "{flagged}{replied}{draft}{trashed}",
-                                    flagged = if flags.is_flagged() { "F" } else { "" },
-                                    replied = if flags.is_replied() { "A" } else { "" },
-                                    draft = if flags.is_draft() { "T" } else { "" },
-                                    trashed = if flags.is_trashed() { "D" } else { "" }
-                                ),
-                            ))
-                        },
-                    )
-                    .chain(if tags.is_empty() {
-                        None
-                    } else {
-                        Some((HeaderName::X_KEYWORDS, tags.as_slice().join(" ")))
-                    })
-                } {
-                    writer.write_fmt(format_args!("{h}: "))?;
-                    writer.write_all(v.as_bytes())?;
-                    writer.write_all(line_ending)?;
-                }
-                Ok::<(), Error>(())
+        let write_metadata_fn = |writer: &mut dyn std::io::Write| {
+            for (h, v) in metadata_format.headers(flags, &tags) {
+                writer.write_fmt(format_args!("{h}: "))?;
+                writer.write_all(v.as_bytes())?;
+                writer.write_all(line_ending)?;
             }
-            MboxMetadata::None => Ok(()),
+            Ok::<(), Error>(())
         };
 
         let body_len = {
@@ -157,7 +195,25 @@ impl MboxFormat {
 
         match self {
             Self::MboxO | Self::MboxRd => {
-                Err(Error::new("Not implemented.").set_kind(ErrorKind::NotImplemented))
+                for (h, v) in headers {
+                    writer.write_fmt(format_args!("{h}: "))?;
+                    write_header_val_fn(writer, v)?;
+                    writer.write_all(line_ending)?;
+                }
+                write_metadata_fn(writer)?;
+                writer.write_all(line_ending)?;
+                for line in body.split_inclusive(|b| *b == b'\n') {
+                    if self.is_quoted_line(line) {
+                        writer.write_all(b">")?;
+                    }
+                    if let Some(line) = line.strip_suffix(b"\n") {
+                        writer.write_all(line.strip_suffix(b"\r").unwrap_or(line))?;
+                        writer.write_all(line_ending)?;
+                    } else {
+                        writer.write_all(line)?;
+                    }
+                }
+                Ok(())
             }
             Self::MboxCl => {
                 let len = (body_len
@@ -261,4 +317,20 @@ impl MboxFormat {
             }
         }
     }
+
+    /// Whether a body `line` must be prefixed with `>` when written in this
+    /// format, so that it is not mistaken for a From_ line.
+    ///
+    /// `mboxrd` also quotes lines that are already quoted, so that the quoting
+    /// can be reversed with [`MboxFormat::unescape`].
+    fn is_quoted_line(&self, line: &[u8]) -> bool {
+        match self {
+            Self::MboxRd => line
+                .iter()
+                .position(|b| *b != b'>')
+                .is_some_and(|pos| line[pos..].starts_with(b"From ")),
+            Self::MboxO | Self::MboxCl => line.starts_with(b"From "),
+            Self::MboxCl2 => false,
+        }
+    }
 }
@@ -109,7 +109,7 @@ pub fn over_article(input: &str) -> IResult<&str, (UID, Envelope)> {
     let (input, _) = tag("\t")(input)?;
     let (input, references) = opt(is_not("\t"))(input)?;
     let (input, _) = tag("\t")(input)?;
-    let (input, _bytes) = opt(is_not("\t"))(input)?;
+    let (input, bytes) = opt(is_not("\t"))(input)?;
     let (input, _) = tag("\t")(input)?;
     let (input, _lines) = opt(is_not("\t\r\n"))(input)?;
     let (input, _other_headers) = opt(is_not("\r\n"))(input)?;
@@ -152,6 +152,10 @@ pub fn over_article(input: &str) -> IResult<&str, (UID, Envelope)> {
             if let Some(message_id) = message_id {
                 env.set_message_id(message_id.as_bytes());
             }
+
+            if let Some(bytes) = bytes.and_then(|b| usize::from_str(b.trim()).ok()) {
+                env.set_size(bytes);
+            }
             (usize::from_str(num).unwrap(), env)
         }),
     ))
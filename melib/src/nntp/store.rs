@@ -54,6 +54,7 @@ CREATE TABLE IF NOT EXISTS article (
     CREATE INDEX IF NOT EXISTS article_idx ON article(hash);",
         ),
         version: 1,
+        migrations: &[],
     };
 
     #[derive(Debug)]
@@ -63,15 +64,19 @@ CREATE TABLE IF NOT EXISTS article (
 
     impl Store {
         pub fn new(id: &str) -> Result<Self> {
-            let db_desc = DatabaseDescription {
-                identifier: Some(id.to_string().into()),
-                ..DB_DESCRIPTION
-            };
             Ok(Self {
-                connection: db_desc.open_or_create_db()?,
+                connection: Self::db_description(id).open_or_create_db()?,
             })
         }
 
+        /// Description of the store database of account `id`.
+        pub fn db_description(id: &str) -> DatabaseDescription {
+            DatabaseDescription {
+                identifier: Some(id.to_string().into()),
+                ..DB_DESCRIPTION
+            }
+        }
+
         pub fn set_flags(
             &self,
             envelope_hash: EnvelopeHash,
@@ -182,6 +182,9 @@ impl<'m> Message<'m> {
             .set_in_reply_to(self.header(c"In-Reply-To").unwrap_or_default())
             .set_datetime(self.date())
             .set_flags(flags);
+        if let Ok(metadata) = std::fs::metadata(self.get_filename()) {
+            env.set_size(metadata.len().try_into().unwrap_or(usize::MAX));
+        }
         env
     }
 
@@ -273,6 +273,17 @@ impl MelibQueryToNotmuchQuery for crate::search::Query {
             HasAttachment => {
                 ret.push_str("tag:attachment");
             }
+            Attachment(s) => {
+                ret.push_str("attachment:\"");
+                for c in s.chars() {
+                    if c == '"' {
+                        ret.push_str("\\\"");
+                    } else {
+                        ret.push(c);
+                    }
+                }
+                ret.push('"');
+            }
             And(q1, q2) => {
                 ret.push('(');
                 q1.query_to_string(ret)?;
@@ -51,6 +51,8 @@ pub enum Query {
     AllText(String),
     Flags(Vec<String>),
     HasAttachment,
+    /// Attachments whose filename or text contains the string.
+    Attachment(String),
     And(Box<Self>, Box<Self>),
     Or(Box<Self>, Box<Self>),
     Not(Box<Self>),
@@ -122,6 +124,10 @@ impl QueryTrait for crate::Envelope {
                 log::warn!("Filtering with Body is unimplemented.");
                 false
             }
+            Attachment(_) => {
+                log::warn!("Filtering with Attachment is unimplemented.");
+                false
+            }
             Answered => {
                 log::warn!("Filtering with Answered is unimplemented.");
                 false
@@ -375,6 +381,14 @@ pub mod query_parser {
         }
     }
 
+    fn attachment<'a>() -> impl Parser<'a, Query> {
+        prefix(
+            whitespace_wrap(match_literal("attachment:")),
+            whitespace_wrap(literal()),
+        )
+        .map(Query::Attachment)
+    }
+
     fn literal<'a>() -> impl Parser<'a, String> {
         move |input| either(quoted_string(), string()).parse(input)
     }
@@ -468,6 +482,7 @@ pub mod query_parser {
                 .or_else(|_| larger().parse(input))
                 .or_else(|_| smaller().parse(input))
                 .or_else(|_| has_attachment().parse(input))
+                .or_else(|_| attachment().parse(input))
             {
                 Ok(q)
             } else if let Ok((rest, query_a)) = not().parse(input) {
@@ -660,5 +675,15 @@ mod tests {
             Ok(("", Flags(vec!["f".to_string()]))),
             query().parse_complete("tags:f")
         );
+        assert_eq!(
+            Ok((
+                "",
+                And(
+                    Box::new(Attachment("quarterly report".to_string())),
+                    Box::new(HasAttachment)
+                )
+            )),
+            query().parse_complete("attachment:\"quarterly report\" and has:attachment")
+        );
     }
 }
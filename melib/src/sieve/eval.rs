@@ -0,0 +1,392 @@
+//
+// melib - sieve module
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Evaluation of parsed Sieve scripts against e-mail.
+//!
+//! A [`Script`] decides what to do with a message, it does not do anything
+//! itself: [`Script::evaluate`] returns the [`ActionCommand`]s that apply to
+//! it, and it is up to the caller to keep, file, redirect or discard it.
+//!
+//! Supported are the actions of RFC 5228, its tests except for `envelope`,
+//! and the `:count` and `:value` match types of RFC 5231. The `date` test of
+//! RFC 5260 is not supported.
+
+use std::cmp::Ordering;
+
+use super::{parser::parse_sieve, *};
+use crate::{
+    email::{
+        parser::{self, BytesExt},
+        HeaderName,
+    },
+    error::{Error, ErrorKind, Result},
+};
+
+/// Extensions that may be named in a script's `require` commands.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "fileinto",
+    "relational",
+    "comparator-i;octet",
+    "comparator-i;ascii-casemap",
+];
+
+/// A parsed Sieve script.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Script {
+    rules: Vec<Rule>,
+}
+
+impl Script {
+    /// Parse `source` and check that it only requires supported extensions.
+    pub fn new(source: &str) -> Result<Self> {
+        let rest = match parse_sieve().parse(source) {
+            Ok((rest, rules)) if rest.trim().is_empty() => {
+                return Self::from_rules(rules);
+            }
+            Ok((rest, _)) | Err(rest) => rest,
+        };
+        let line = source[..source.len() - rest.len()].lines().count().max(1);
+        Err(
+            Error::new(format!("Could not parse sieve script at line {line}."))
+                .set_kind(ErrorKind::ValueError),
+        )
+    }
+
+    /// Check that `rules` only require supported extensions.
+    pub fn from_rules(rules: Vec<Rule>) -> Result<Self> {
+        for rule in rules.iter() {
+            if let Rule::Control(ControlCommand::Require(extensions)) = rule {
+                if let Some(ext) = extensions
+                    .iter()
+                    .find(|ext| !SUPPORTED_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+                {
+                    return Err(
+                        Error::new(format!("Sieve extension `{ext}` is not supported."))
+                            .set_kind(ErrorKind::NotSupported),
+                    );
+                }
+            }
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Return what to do with the e-mail `message`, given as its raw bytes.
+    ///
+    /// The result is never empty and has no duplicates. Unless an action
+    /// cancels it, the implicit keep of RFC 5228 applies and the result is
+    /// `[Keep]`. If no action but `discard` applies, the result is
+    /// `[Discard]`.
+    pub fn evaluate(&self, message: &[u8]) -> Result<Vec<ActionCommand>> {
+        let headers = parser::headers::headers(message)
+            .map(|(_, headers)| headers)
+            .unwrap_or_default();
+        let context = Context {
+            headers: &headers,
+            size: message.len() as u64,
+        };
+        let mut state = State {
+            actions: vec![],
+            implicit_keep: true,
+            stopped: false,
+        };
+        context.run(&self.rules, &mut state)?;
+        if state.actions.is_empty() {
+            return Ok(vec![if state.implicit_keep {
+                ActionCommand::Keep
+            } else {
+                ActionCommand::Discard
+            }]);
+        }
+        Ok(state.actions)
+    }
+}
+
+impl std::str::FromStr for Script {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        Self::new(source)
+    }
+}
+
+struct State {
+    actions: Vec<ActionCommand>,
+    implicit_keep: bool,
+    stopped: bool,
+}
+
+impl State {
+    fn push(&mut self, action: ActionCommand) {
+        if !self.actions.contains(&action) {
+            self.actions.push(action);
+        }
+    }
+}
+
+struct Context<'a> {
+    headers: &'a [(HeaderName, &'a [u8])],
+    size: u64,
+}
+
+impl<'a> Context<'a> {
+    fn run(&self, rules: &[Rule], state: &mut State) -> Result<()> {
+        for rule in rules {
+            if state.stopped {
+                break;
+            }
+            match rule {
+                Rule::Block(RuleBlock(block)) => self.run(block, state)?,
+                Rule::Action(ActionCommand::Discard) => state.implicit_keep = false,
+                Rule::Action(action @ ActionCommand::Keep) => state.push(action.clone()),
+                Rule::Action(
+                    action @ (ActionCommand::Fileinto { .. } | ActionCommand::Redirect { .. }),
+                ) => {
+                    state.implicit_keep = false;
+                    state.push(action.clone());
+                }
+                Rule::Control(ControlCommand::Stop) => state.stopped = true,
+                Rule::Control(ControlCommand::Require(_)) => {}
+                Rule::Control(ControlCommand::If {
+                    condition: (condition, RuleBlock(block)),
+                    elsif,
+                    else_,
+                }) => {
+                    if self.test(condition)? {
+                        self.run(block, state)?;
+                    } else if let Some((condition, RuleBlock(block))) = elsif.as_deref() {
+                        if self.test(condition)? {
+                            self.run(block, state)?;
+                        } else if let Some(RuleBlock(block)) = else_ {
+                            self.run(block, state)?;
+                        }
+                    } else if let Some(RuleBlock(block)) = else_ {
+                        self.run(block, state)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Values of the headers named `name`.
+    fn raw_values<'s>(&'s self, name: &'s str) -> impl Iterator<Item = &'a [u8]> + 's {
+        self.headers
+            .iter()
+            .filter(move |(n, _)| n == name)
+            .map(|(_, value)| value.trim())
+    }
+
+    /// Decoded values of the headers named `name`.
+    fn values<'s>(&'s self, name: &'s str) -> impl Iterator<Item = String> + 's {
+        self.raw_values(name).map(|value| {
+            parser::encodings::phrase(value, false)
+                .map(|(_, v)| String::from_utf8_lossy(&v).into_owned())
+                .unwrap_or_else(|_| String::from_utf8_lossy(value).into_owned())
+        })
+    }
+
+    fn test(&self, condition: &ConditionRule) -> Result<bool> {
+        Ok(match condition {
+            ConditionRule::AnyOf(tests) => {
+                for test in tests {
+                    if self.test(test)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+            ConditionRule::AllOf(tests) => {
+                for test in tests {
+                    if !self.test(test)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
+            ConditionRule::Not(test) => !self.test(test)?,
+            ConditionRule::Literal(value) => *value,
+            ConditionRule::Exists(names) => names
+                .iter()
+                .all(|name| self.headers.iter().any(|(n, _)| n == name.as_str())),
+            ConditionRule::Size { operator, limit } => match operator {
+                IntegerOperator::Over => self.size > *limit,
+                IntegerOperator::Under => self.size < *limit,
+            },
+            ConditionRule::Header {
+                comparator,
+                match_operator,
+                header_names,
+                key_list,
+            } => {
+                let values = header_names
+                    .iter()
+                    .flat_map(|name| self.values(name))
+                    .collect::<Vec<String>>();
+                matches(
+                    comparator.unwrap_or(CharacterOperator::AsciiCasemap),
+                    match_operator.unwrap_or(MatchOperator::Is),
+                    &values,
+                    key_list,
+                )
+            }
+            ConditionRule::Address {
+                comparator,
+                address_part,
+                match_type,
+                header_list,
+                key_list,
+            } => {
+                let address_part = address_part.unwrap_or(AddressOperator::All);
+                let values = header_list
+                    .iter()
+                    .flat_map(|name| self.raw_values(name))
+                    .flat_map(|value| {
+                        parser::address::rfc2822address_list(value)
+                            .map(|(_, list)| list.into_vec())
+                            .unwrap_or_default()
+                    })
+                    .map(|address| address.get_email())
+                    .filter(|email| !email.is_empty())
+                    .map(|email| {
+                        let (localpart, domain) =
+                            email.rsplit_once('@').unwrap_or((email.as_str(), ""));
+                        match address_part {
+                            AddressOperator::All => email.clone(),
+                            AddressOperator::Localpart => localpart.to_string(),
+                            AddressOperator::Domain => domain.to_string(),
+                        }
+                    })
+                    .collect::<Vec<String>>();
+                matches(
+                    comparator.unwrap_or(CharacterOperator::AsciiCasemap),
+                    match_type.unwrap_or(MatchOperator::Is),
+                    &values,
+                    key_list,
+                )
+            }
+            ConditionRule::Date { .. } => {
+                return Err(Error::new("Sieve `date` test is not supported.")
+                    .set_kind(ErrorKind::NotSupported));
+            }
+        })
+    }
+}
+
+/// Whether any of `values` matches any of `keys`.
+fn matches(
+    comparator: CharacterOperator,
+    match_operator: MatchOperator,
+    values: &[String],
+    keys: &[String],
+) -> bool {
+    let fold = |s: &str| match comparator {
+        CharacterOperator::Octet => s.to_string(),
+        CharacterOperator::AsciiCasemap => s.to_ascii_lowercase(),
+    };
+    if let MatchOperator::Count(relation) = match_operator {
+        return keys.iter().any(|key| {
+            key.trim()
+                .parse::<usize>()
+                .map(|key| relation_holds(relation, values.len().cmp(&key)))
+                .unwrap_or(false)
+        });
+    }
+    values.iter().map(|v| fold(v.as_str())).any(|value| {
+        keys.iter()
+            .map(|k| fold(k.as_str()))
+            .any(|key| match match_operator {
+                MatchOperator::Is => value == key,
+                MatchOperator::Contains => value.contains(&key),
+                MatchOperator::Matches => wildcard_match(&key, &value),
+                MatchOperator::Value(relation) => relation_holds(relation, value.cmp(&key)),
+                MatchOperator::Count(_) => unreachable!(),
+            })
+    })
+}
+
+fn relation_holds(relation: RelationalMatch, ordering: Ordering) -> bool {
+    match relation {
+        RelationalMatch::Gt => ordering == Ordering::Greater,
+        RelationalMatch::Ge => ordering != Ordering::Less,
+        RelationalMatch::Lt => ordering == Ordering::Less,
+        RelationalMatch::Le => ordering != Ordering::Greater,
+        RelationalMatch::Eq => ordering == Ordering::Equal,
+        RelationalMatch::Ne => ordering != Ordering::Equal,
+    }
+}
+
+/// Match `value` against a `:matches` pattern, where `*` matches any
+/// sequence of characters, `?` matches one character and `\` escapes the
+/// next character.
+pub fn wildcard_match(pattern: &str, value: &str) -> bool {
+    enum Token {
+        Any,
+        One,
+        Char(char),
+    }
+
+    let mut tokens = vec![];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '*' => Token::Any,
+            '?' => Token::One,
+            '\\' => Token::Char(chars.next().unwrap_or('\\')),
+            c => Token::Char(c),
+        });
+    }
+    let value = value.chars().collect::<Vec<char>>();
+    let (mut t, mut v) = (0, 0);
+    // Position of the last `*` and of the value character it was tried at.
+    let mut backtrack: Option<(usize, usize)> = None;
+    while v < value.len() {
+        match tokens.get(t) {
+            Some(Token::Any) => {
+                backtrack = Some((t, v));
+                t += 1;
+                continue;
+            }
+            Some(Token::One) => {
+                t += 1;
+                v += 1;
+                continue;
+            }
+            Some(Token::Char(c)) if *c == value[v] => {
+                t += 1;
+                v += 1;
+                continue;
+            }
+            _ => {}
+        }
+        let Some((bt, bv)) = backtrack else {
+            return false;
+        };
+        backtrack = Some((bt, bv + 1));
+        t = bt + 1;
+        v = bv + 1;
+    }
+    tokens[t..].iter().all(|t| matches!(t, Token::Any))
+}
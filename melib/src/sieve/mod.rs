@@ -19,12 +19,15 @@
  * along with meli. If not, see <http://www.gnu.org/licenses/>.
  */
 
+pub mod eval;
 pub mod parser;
 #[cfg(test)]
 mod tests;
 
 use crate::utils::parsec::*;
 
+pub use self::eval::Script;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct RuleBlock(pub Vec<Rule>);
 
@@ -188,17 +188,20 @@ pub fn number<'a>() -> impl Parser<'a, u64> {
     map_res(
         pair(
             is_a(b"0123456789"),
-            pred(any_char, |c| {
+            opt(pred(any_char, |c| {
                 ['k', 'm', 'g'].contains(&c.to_ascii_lowercase())
-            }),
+            })),
         ),
         |(num_s, quant)| {
-            Ok(match (num_s.parse::<u64>(), quant.to_ascii_lowercase()) {
-                (Ok(num), 'k') => num * 1_000,
-                (Ok(num), 'm') => num * 1_000_000,
-                (Ok(num), 'g') => num * 1_000_000_000,
-                _ => return Err(num_s),
-            })
+            Ok(
+                match (num_s.parse::<u64>(), quant.map(|q| q.to_ascii_lowercase())) {
+                    (Ok(num), None) => num,
+                    (Ok(num), Some('k')) => num * 1_000,
+                    (Ok(num), Some('m')) => num * 1_000_000,
+                    (Ok(num), Some('g')) => num * 1_000_000_000,
+                    _ => return Err(num_s),
+                },
+            )
         },
     )
 }
@@ -234,14 +237,44 @@ pub fn parse_sieve_comparator<'a>() -> impl Parser<'a, CharacterOperator> {
     }
 }
 
+// relational-match = DQUOTE
+//         ("gt" / "ge" / "lt" / "le" / "eq" / "ne") DQUOTE
+pub fn parse_sieve_relational_match<'a>() -> impl Parser<'a, RelationalMatch> {
+    move |input| {
+        map_res(string(), |s| match s.to_ascii_lowercase().as_str() {
+            "gt" => Ok(RelationalMatch::Gt),
+            "ge" => Ok(RelationalMatch::Ge),
+            "lt" => Ok(RelationalMatch::Lt),
+            "le" => Ok(RelationalMatch::Le),
+            "eq" => Ok(RelationalMatch::Eq),
+            "ne" => Ok(RelationalMatch::Ne),
+            _ => Err("invalid relational match"),
+        })
+        .parse(input)
+    }
+}
+
 // MATCH-TYPE   = ":is" / ":contains" / ":matches"
+// MATCH-TYPE   =/ ":count" relational-match / ":value" relational-match
 pub fn parse_sieve_match_type<'a>() -> impl Parser<'a, MatchOperator> {
     move |input| {
         either(
-            map(parse_token(":is"), |_| MatchOperator::Is),
             either(
-                map(parse_token(":contains"), |_| MatchOperator::Contains),
-                map(parse_token(":matches"), |_| MatchOperator::Matches),
+                map(parse_token(":is"), |_| MatchOperator::Is),
+                either(
+                    map(parse_token(":contains"), |_| MatchOperator::Contains),
+                    map(parse_token(":matches"), |_| MatchOperator::Matches),
+                ),
+            ),
+            either(
+                map(
+                    right(parse_token(":count"), ws(parse_sieve_relational_match())),
+                    MatchOperator::Count,
+                ),
+                map(
+                    right(parse_token(":value"), ws(parse_sieve_relational_match())),
+                    MatchOperator::Value,
+                ),
             ),
         )
         .parse(input)
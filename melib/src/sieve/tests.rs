@@ -345,3 +345,98 @@ if size :under 1k
         )
     );
 }
+
+#[test]
+fn test_sieve_evaluate() {
+    use super::{eval::wildcard_match, Script};
+
+    const MESSAGE: &[u8] = b"From: \"Wile E. Coyote\" <coyote@desert.example.org>\r
+To: roadrunner@acme.example.com, \"Acme Sales\" <sales@acme.example.com>\r
+Subject: =?UTF-8?Q?I_have_a_present_for_you?=\r
+X-Spam-Score: 7\r
+\r
+Look, I'm sorry about the whole anvil thing.\r
+";
+
+    fn evaluate(source: &str) -> Vec<super::ActionCommand> {
+        Script::new(source).unwrap().evaluate(MESSAGE).unwrap()
+    }
+
+    assert!(wildcard_match("*coyote*", "wile e. coyote"));
+    assert!(wildcard_match(
+        "i have a ?resent*",
+        "i have a present for you"
+    ));
+    assert!(wildcard_match(r"\*", "*"));
+    assert!(!wildcard_match(r"\*", "a"));
+    assert!(!wildcard_match("*coyote", "coyote!"));
+
+    // Implicit keep.
+    assert_eq!(evaluate(""), vec![Keep]);
+    assert_eq!(evaluate("if false { discard; }"), vec![Keep]);
+    assert_eq!(evaluate("discard;"), vec![Discard]);
+
+    assert_eq!(
+        evaluate(
+            r#"require "fileinto";
+            if header :contains "subject" "PRESENT" {
+                fileinto "Gifts";
+            }"#
+        ),
+        vec![Fileinto {
+            mailbox: "Gifts".to_string()
+        }]
+    );
+    assert_eq!(
+        evaluate(r#"if header :comparator "i;octet" :contains "subject" "PRESENT" { discard; }"#),
+        vec![Keep]
+    );
+    assert_eq!(
+        evaluate(
+            r#"if address :domain :is "from" "desert.example.org" {
+                redirect "wile@example.com";
+                keep;
+            }"#
+        ),
+        vec![
+            Redirect {
+                address: "wile@example.com".to_string()
+            },
+            Keep
+        ]
+    );
+    assert_eq!(
+        evaluate(
+            r#"if address :localpart :matches "to" "sal*" { discard; stop; }
+            fileinto "Unreachable";"#
+        ),
+        vec![Discard]
+    );
+    assert_eq!(
+        evaluate(
+            r#"require ["fileinto", "relational"];
+            if address :count "eq" "to" "2" { fileinto "Two"; }
+            elsif exists "x-spam-score" { fileinto "Spam"; }
+            else { fileinto "Other"; }
+            if header :value "ge" "x-spam-score" "5" { fileinto "Spam"; }
+            if allof (exists ["from", "to"], not exists "cc", size :under 1K) {
+                fileinto "Two";
+            }"#
+        ),
+        vec![
+            Fileinto {
+                mailbox: "Two".to_string()
+            },
+            Fileinto {
+                mailbox: "Spam".to_string()
+            }
+        ]
+    );
+    assert_eq!(
+        evaluate(r#"if anyof (size :over 1M, header :is "x-spam-score" "8") { discard; }"#),
+        vec![Keep]
+    );
+
+    Script::new(r#"require "vacation";"#).unwrap_err();
+    Script::new("if true { discard; ").unwrap_err();
+}
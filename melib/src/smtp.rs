@@ -129,6 +129,12 @@ pub enum Password {
 }
 
 impl Password {
+    /// Get the password, running the command of [`Password::CommandEval`]
+    /// with `sh -c`.
+    ///
+    /// Commands are run without any restriction, like the `token_command` of
+    /// [`SmtpAuth::XOAuth2`]; callers that limit which commands may run must
+    /// check them beforehand.
     pub async fn evaluate(&self) -> Result<Vec<u8>> {
         match self {
             Self::Raw(p) => Ok(p.as_bytes().to_vec()),
@@ -184,13 +190,14 @@ pub enum SmtpAuth {
         #[serde(default = "crate::conf::true_val")]
         require_auth: bool,
     },
-    // md5, sasl, etc
+    // sasl, etc
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct SmtpAuthType {
     plain: bool,
     login: bool,
+    cram_md5: bool,
 }
 
 impl SmtpAuth {
@@ -493,6 +500,8 @@ impl SmtpConnection {
                             auth_type.plain = true;
                         } else if _type == "LOGIN" {
                             auth_type.login = true;
+                        } else if _type == "CRAM-MD5" {
+                            auth_type.cram_md5 = true;
                         }
                     }
                 }
@@ -508,7 +517,32 @@ impl SmtpConnection {
                     ..
                 } => {
                     let password = password.evaluate().await?;
-                    if auth_type.login {
+                    // CRAM-MD5 doesn't send the password itself, so prefer it over an
+                    // unencrypted connection.
+                    if auth_type.cram_md5
+                        && (ret.server_conf.security == SmtpSecurity::None
+                            || !(auth_type.login || auth_type.plain))
+                    {
+                        // # RFC 2195 IMAP/POP AUTHorize Extension for Simple
+                        // # Challenge/Response
+                        // # https://www.rfc-editor.org/rfc/rfc2195
+                        let username = username.to_string();
+                        ret.send_command(&[b"AUTH CRAM-MD5"]).await?;
+                        let challenge = {
+                            let reply = ret
+                                .read_lines(&mut res, Some((ReplyCode::_334, &[])))
+                                .await
+                                .chain_err_kind(ErrorKind::Authentication)?;
+                            #[allow(deprecated)]
+                            base64::decode(reply.lines.first().copied().unwrap_or_default().trim())
+                                .chain_err_summary(|| "Invalid CRAM-MD5 challenge from SMTP server")
+                                .chain_err_kind(ErrorKind::Authentication)?
+                        };
+                        #[allow(deprecated)]
+                        let buf =
+                            base64::encode(cram_md5_response(&username, &password, &challenge));
+                        ret.send_command(&[buf.as_bytes()]).await?;
+                    } else if auth_type.login {
                         let username = username.to_string();
                         ret.send_command(&[b"AUTH LOGIN"]).await?;
                         ret.read_lines(&mut res, Some((ReplyCode::_334, &[])))
@@ -654,7 +688,12 @@ impl SmtpConnection {
         let envelope = Envelope::from_bytes(mail.as_bytes(), None)
             .chain_err_summary(|| "SMTP submission was aborted")?;
         let tos = tos.unwrap_or_else(|| envelope.to());
-        if tos.is_empty() && envelope.cc().is_empty() && envelope.bcc().is_empty() {
+        if tos
+            .iter()
+            .chain(envelope.cc().iter())
+            .chain(envelope.bcc().iter())
+            .all(|addr| addr.mailboxes().is_empty())
+        {
             return Err(Error::new(
                 "SMTP submission was aborted because there was no e-mail address found in the To: \
                  header field. Consider adding recipients.",
@@ -710,6 +749,7 @@ impl SmtpConnection {
             .iter()
             .chain(envelope.cc().iter())
             .chain(envelope.bcc().iter())
+            .flat_map(|addr| addr.mailboxes())
         {
             current_command.clear();
             current_command.push(b"RCPT TO:<");
@@ -1054,6 +1094,21 @@ impl TryFrom<&'_ str> for ReplyCode {
     }
 }
 
+/// Response to a `CRAM-MD5` authentication challenge, as described in [RFC
+/// 2195](https://www.rfc-editor.org/rfc/rfc2195): the username, a space and
+/// the HMAC-MD5 digest of the (base64 decoded) `challenge` keyed with the
+/// password, in lowercase hex.
+///
+/// The response must be base64 encoded before it is sent to the server.
+pub fn cram_md5_response(username: &str, password: &[u8], challenge: &[u8]) -> String {
+    crate::utils::md5::hmac_md5(password, challenge)
+        .iter()
+        .fold(format!("{username} "), |mut acc, b| {
+            acc.push_str(&format!("{b:02x}"));
+            acc
+        })
+}
+
 /// A single line or multi-line server reply, along with its reply code
 #[derive(Clone, Debug)]
 pub struct Reply<'s> {
@@ -35,10 +35,12 @@
 
 use crate::{
     email::{address::StrBuild, parser::BytesExt, *},
-    SortField, SortOrder, UnixTimestamp,
+    SortChain, SortField, SortOrder, UnixTimestamp,
 };
 
 mod iterators;
+#[cfg(test)]
+mod tests;
 use std::{
     cmp::Ordering,
     collections::{HashMap, HashSet, VecDeque},
@@ -58,7 +60,9 @@ type Envelopes = Arc<RwLock<HashMap<EnvelopeHash, Envelope>>>;
 
 macro_rules! uuid_hash_type {
     ($n:ident) => {
-        #[derive(PartialEq, Hash, Eq, Copy, Clone, Serialize, Deserialize, Default)]
+        #[derive(
+            PartialEq, Hash, Eq, PartialOrd, Ord, Copy, Clone, Serialize, Deserialize, Default,
+        )]
         pub struct $n(Uuid);
 
         impl std::fmt::Debug for $n {
@@ -612,8 +616,22 @@ pub struct Threads {
     pub hash_set: HashSet<EnvelopeHash>,
     pub thread_to_envelope: HashMap<ThreadHash, Vec<EnvelopeHash>>,
     pub envelope_to_thread: HashMap<EnvelopeHash, ThreadHash>,
-    sort: Arc<RwLock<(SortField, SortOrder)>>,
+    sort: Arc<RwLock<SortChain>>,
     subsort: Arc<RwLock<(SortField, SortOrder)>>,
+    #[serde(skip)]
+    root_cache: Arc<RwLock<RootCache>>,
+}
+
+/// Root thread groups in sorted order, as last returned by
+/// [`Threads::sorted_roots`].
+///
+/// Groups touched by insertions and removals are marked as dirty and are
+/// re-positioned on the next call instead of sorting all roots again.
+#[derive(Clone, Debug, Default)]
+struct RootCache {
+    sort: Option<SortChain>,
+    roots: Vec<ThreadHash>,
+    dirty: HashSet<ThreadHash>,
 }
 
 impl PartialEq for ThreadNode {
@@ -692,13 +710,63 @@ impl Threads {
             hash_set,
             thread_to_envelope,
             envelope_to_thread,
-            sort: Arc::new(RwLock::new((SortField::Date, SortOrder::Desc))),
+            sort: Arc::new(RwLock::new(SortChain::default())),
             subsort: Arc::new(RwLock::new((SortField::Subject, SortOrder::Desc))),
 
             ..Default::default()
         }
     }
 
+    /// A copy of `self` that shares no state with it, unlike
+    /// [`Clone::clone`] which shares the sort state, root caches and thread
+    /// group links.
+    pub fn snapshot(&self) -> Self {
+        fn copy<T: Clone>(value: &Arc<RwLock<T>>) -> Arc<RwLock<T>> {
+            Arc::new(RwLock::new(value.read().unwrap().clone()))
+        }
+
+        Self {
+            thread_nodes: self.thread_nodes.clone(),
+            root_set: copy(&self.root_set),
+            tree_index: copy(&self.tree_index),
+            groups: self
+                .groups
+                .iter()
+                .map(|(h, g)| {
+                    let g = match g {
+                        ThreadGroup::Root(root) => ThreadGroup::Root(root.clone()),
+                        ThreadGroup::Node { parent } => ThreadGroup::Node {
+                            parent: copy(parent),
+                        },
+                    };
+                    (*h, g)
+                })
+                .collect(),
+            message_ids: self.message_ids.clone(),
+            message_ids_set: self.message_ids_set.clone(),
+            missing_message_ids: self.missing_message_ids.clone(),
+            hash_set: self.hash_set.clone(),
+            thread_to_envelope: self.thread_to_envelope.clone(),
+            envelope_to_thread: self.envelope_to_thread.clone(),
+            sort: copy(&self.sort),
+            subsort: copy(&self.subsort),
+            root_cache: copy(&self.root_cache),
+        }
+    }
+
+    /// Exchange the state that readers keep up to date, such as the sort
+    /// order and the sorted roots cache, with `other`, which `self` is about
+    /// to replace. Groups that `self` marked as dirty stay dirty.
+    pub fn take_view(&mut self, other: &mut Self) {
+        let dirty = std::mem::take(&mut self.root_cache.write().unwrap().dirty);
+        std::mem::swap(&mut self.root_set, &mut other.root_set);
+        std::mem::swap(&mut self.tree_index, &mut other.tree_index);
+        std::mem::swap(&mut self.sort, &mut other.sort);
+        std::mem::swap(&mut self.subsort, &mut other.subsort);
+        std::mem::swap(&mut self.root_cache, &mut other.root_cache);
+        self.root_cache.write().unwrap().dirty.extend(dirty);
+    }
+
     pub fn threads_iter(&self, root_tree: SmallVec<[ThreadNodeHash; 1024]>) -> ThreadsIterator<'_> {
         ThreadsIterator {
             root_tree,
@@ -728,13 +796,12 @@ impl Threads {
          * - hash_set
          * - message fields in thread_nodes
          */
-        let thread_node_hash = if let Some((key, _)) = self
-            .thread_nodes
-            .iter()
-            .find(|(_, n)| n.message.map(|n| n == old_hash).unwrap_or(false))
-        {
-            *key
-        } else {
+        let hint = envelopes
+            .read()
+            .unwrap()
+            .get(&new_hash)
+            .map(Envelope::thread);
+        let Some(thread_node_hash) = self.find_envelope_node(old_hash, hint) else {
             return Err(());
         };
 
@@ -754,6 +821,7 @@ impl Threads {
             }
         }
         self.thread_nodes.get_mut(&thread_node_hash).unwrap().unseen = is_unseen;
+        self.mark_dirty(self.thread_nodes[&thread_node_hash].group);
         self.hash_set.remove(&old_hash);
         self.hash_set.insert(new_hash);
         let thread_hash = self.envelope_to_thread.remove(&old_hash).unwrap();
@@ -769,20 +837,90 @@ impl Threads {
         Ok(())
     }
 
+    /// Find the node holding `env_hash`.
+    ///
+    /// `hint` is checked first, then the nodes of the envelope's thread group,
+    /// and only if both fail all nodes.
+    fn find_envelope_node(
+        &self,
+        env_hash: EnvelopeHash,
+        hint: Option<ThreadNodeHash>,
+    ) -> Option<ThreadNodeHash> {
+        if let Some(hint) = hint {
+            if self.thread_nodes.get(&hint).and_then(ThreadNode::message) == Some(env_hash) {
+                return Some(hint);
+            }
+        }
+        if let Some(group) = self
+            .envelope_to_thread
+            .get(&env_hash)
+            .filter(|g| self.groups.contains_key(g))
+        {
+            let group = self.find_group(*group);
+            if let Some((_, node)) = self
+                .thread_iter(group)
+                .find(|(_, n)| self.thread_nodes[n].message == Some(env_hash))
+            {
+                return Some(node);
+            }
+        }
+        self.thread_nodes
+            .iter()
+            .find(|(_, n)| n.message == Some(env_hash))
+            .map(|(h, _)| *h)
+    }
+
+    /// Mark the thread group of node `h` as changed, for when a field it is
+    /// sorted by was modified outside of [`Threads`].
+    pub fn mark_node_dirty(&self, h: ThreadNodeHash) {
+        if let Some(node) = self.thread_nodes.get(&h) {
+            self.mark_dirty(node.group);
+        }
+    }
+
+    /// Mark the thread group `h` as changed, so that its position in
+    /// [`Threads::sorted_roots`] is recalculated.
+    fn mark_dirty(&self, h: ThreadHash) {
+        let h = if self.groups.contains_key(&h) {
+            self.find_group(h)
+        } else {
+            h
+        };
+        self.root_cache.write().unwrap().dirty.insert(h);
+    }
+
     #[inline]
     pub fn remove(&mut self, envelope_hash: EnvelopeHash) {
-        self.hash_set.remove(&envelope_hash);
+        if !self.hash_set.remove(&envelope_hash) {
+            return;
+        }
 
-        let t_id: ThreadNodeHash = if let Some((pos, n)) = self
-            .thread_nodes
-            .iter_mut()
-            .find(|(_, n)| n.message.map(|n| n == envelope_hash).unwrap_or(false))
-        {
-            n.message = None;
-            *pos
-        } else {
+        let Some(t_id) = self.find_envelope_node(envelope_hash, None) else {
             return;
         };
+        let was_unseen = {
+            let node = self.thread_nodes.get_mut(&t_id).unwrap();
+            node.message = None;
+            std::mem::replace(&mut node.unseen, false)
+        };
+        let group = self.thread_nodes[&t_id].group;
+        if self.groups.contains_key(&group) {
+            let Thread {
+                ref mut len,
+                ref mut unseen,
+                ..
+            } = self.thread_ref_mut(group);
+            *len = len.saturating_sub(1);
+            if was_unseen {
+                *unseen = unseen.saturating_sub(1);
+            }
+            self.mark_dirty(group);
+        }
+        if let Some(thread_hash) = self.envelope_to_thread.remove(&envelope_hash) {
+            if let Some(env_hashes) = self.thread_to_envelope.get_mut(&thread_hash) {
+                env_hashes.retain(|h| *h != envelope_hash);
+            }
+        }
 
         if self.thread_nodes[&t_id].parent.is_none() {
             let mut tree_index = self.tree_index.write().unwrap();
@@ -883,7 +1021,35 @@ impl Threads {
     }
 
     pub fn insert(&mut self, envelopes: &Envelopes, env_hash: EnvelopeHash) {
-        self.insert_internal(envelopes, env_hash, false);
+        let mut assigned = vec![];
+        self.insert_internal(envelopes, env_hash, false, &mut assigned);
+        Self::assign_threads(envelopes, assigned);
+    }
+
+    /// Like [`Threads::insert`], but instead of setting the thread node of
+    /// the envelope in `envelopes`, push it to `assigned`, so that `envelopes`
+    /// is left unchanged. See [`Threads::assign_threads`].
+    pub fn insert_unassigned(
+        &mut self,
+        envelopes: &Envelopes,
+        env_hash: EnvelopeHash,
+        assigned: &mut Vec<(EnvelopeHash, ThreadNodeHash)>,
+    ) {
+        self.insert_internal(envelopes, env_hash, false, assigned);
+    }
+
+    /// Set the thread node of each envelope of `assigned` that is still in
+    /// `envelopes`.
+    pub fn assign_threads(
+        envelopes: &Envelopes,
+        assigned: impl IntoIterator<Item = (EnvelopeHash, ThreadNodeHash)>,
+    ) {
+        let mut envelopes_lck = envelopes.write().unwrap();
+        for (env_hash, thread_hash) in assigned {
+            if let Some(env) = envelopes_lck.get_mut(&env_hash) {
+                env.set_thread(thread_hash);
+            }
+        }
     }
 
     fn insert_internal(
@@ -891,6 +1057,7 @@ impl Threads {
         envelopes: &Envelopes,
         env_hash: EnvelopeHash,
         other_mailbox: bool,
+        assigned: &mut Vec<(EnvelopeHash, ThreadNodeHash)>,
     ) -> bool {
         if self.hash_set.contains(&env_hash) {
             return true;
@@ -908,12 +1075,7 @@ impl Threads {
                 let thread_hash = self.message_ids[message_id];
                 let node = self.thread_nodes.entry(thread_hash).or_default();
                 drop(envelopes_lck);
-                envelopes
-                    .write()
-                    .unwrap()
-                    .get_mut(&env_hash)
-                    .unwrap()
-                    .set_thread(thread_hash);
+                assigned.push((env_hash, thread_hash));
 
                 /* If thread node currently has a message from a foreign mailbox and env_hash
                  * is from current mailbox we want to update it, otherwise
@@ -1110,13 +1272,9 @@ impl Threads {
             }
         }
         drop(envelopes_lck);
+        self.mark_dirty(self.thread_nodes[&new_id].group);
         self.update_show_subject(new_id, env_hash, envelopes);
-        envelopes
-            .write()
-            .unwrap()
-            .get_mut(&env_hash)
-            .unwrap()
-            .set_thread(new_id);
+        assigned.push((env_hash, new_id));
 
         /*
         save_graph(
@@ -1135,7 +1293,20 @@ impl Threads {
 
     /* Insert or update */
     pub fn insert_reply(&mut self, envelopes: &Envelopes, env_hash: EnvelopeHash) -> bool {
-        self.insert_internal(envelopes, env_hash, true)
+        let mut assigned = vec![];
+        let ret = self.insert_internal(envelopes, env_hash, true, &mut assigned);
+        Self::assign_threads(envelopes, assigned);
+        ret
+    }
+
+    /// Like [`Threads::insert_reply`], see [`Threads::insert_unassigned`].
+    pub fn insert_reply_unassigned(
+        &mut self,
+        envelopes: &Envelopes,
+        env_hash: EnvelopeHash,
+        assigned: &mut Vec<(EnvelopeHash, ThreadNodeHash)>,
+    ) -> bool {
+        self.insert_internal(envelopes, env_hash, true, assigned)
     }
 
     fn inner_subsort_by(&self, _subsort: (SortField, SortOrder), _envelopes: &Envelopes) {
@@ -1204,219 +1375,127 @@ impl Threads {
         */
     }
 
+    /// Compare thread nodes `a` and `b` according to `sort`.
+    ///
+    /// Date, thread length and unseen state are properties of the nodes'
+    /// thread groups; every other field compares the nodes' envelopes, with
+    /// empty nodes ordered before the rest. Nodes that are equal by every key
+    /// are ordered by their hashes, so that the result does not depend on
+    /// the order of the input.
+    fn node_cmp(
+        &self,
+        a: ThreadNodeHash,
+        b: ThreadNodeHash,
+        sort: &SortChain,
+        envelopes: &HashMap<EnvelopeHash, Envelope>,
+    ) -> Ordering {
+        let group = |h: ThreadNodeHash| self.thread_ref(self.thread_nodes[&h].group);
+        sort.compare(|field| match field {
+            SortField::Date => group(a).date().cmp(&group(b).date()),
+            SortField::ThreadLength => group(a).len().cmp(&group(b).len()),
+            SortField::Unseen => (group(a).unseen() > 0).cmp(&(group(b).unseen() > 0)),
+            _ => match (
+                self.thread_nodes[&a].message(),
+                self.thread_nodes[&b].message(),
+            ) {
+                (Some(a), Some(b)) => envelopes[&a].cmp_by_field(&envelopes[&b], field),
+                (Some(_), None) => Ordering::Greater,
+                (None, Some(_)) => Ordering::Less,
+                (None, None) => Ordering::Equal,
+            },
+        })
+        .then_with(|| a.cmp(&b))
+    }
+
+    /// Compare root thread groups `a` and `b` according to `sort`.
+    fn group_cmp(
+        &self,
+        a: ThreadHash,
+        b: ThreadHash,
+        sort: &SortChain,
+        envelopes: &HashMap<EnvelopeHash, Envelope>,
+    ) -> Ordering {
+        self.node_cmp(
+            self.thread_ref(a).root(),
+            self.thread_ref(b).root(),
+            sort,
+            envelopes,
+        )
+    }
+
     pub fn group_inner_sort_by(
         &self,
         vec: &mut [ThreadHash],
-        sort: (SortField, SortOrder),
+        sort: &SortChain,
         envelopes: &Envelopes,
     ) {
         let envelopes = envelopes.read().unwrap();
-        vec.sort_by(|a, b| match sort {
-            (SortField::Date, SortOrder::Desc) => {
-                let a = self.thread_ref(*a).date();
-                let b = self.thread_ref(*b).date();
-                b.cmp(&a)
-            }
-            (SortField::Date, SortOrder::Asc) => {
-                let a = self.thread_ref(*a).date();
-                let b = self.thread_ref(*b).date();
-                a.cmp(&b)
-            }
-            (SortField::Subject, SortOrder::Desc) => {
-                let a = &self.thread_nodes[&self.thread_ref(*a).root()].message();
-                let b = &self.thread_nodes[&self.thread_ref(*b).root()].message();
-
-                match (a, b) {
-                    (Some(_), Some(_)) => {}
-                    (Some(_), None) => {
-                        return Ordering::Greater;
-                    }
-                    (None, Some(_)) => {
-                        return Ordering::Less;
-                    }
-                    (None, None) => {
-                        return Ordering::Equal;
-                    }
-                }
-                let ma = &envelopes[&a.unwrap()];
-                let mb = &envelopes[&b.unwrap()];
-                {
-                    ma.subject()
-                        .split_graphemes()
-                        .cmp(&mb.subject().split_graphemes())
-                }
-            }
-            (SortField::Subject, SortOrder::Asc) => {
-                let a = &self.thread_nodes[&self.thread_ref(*a).root()].message();
-                let b = &self.thread_nodes[&self.thread_ref(*b).root()].message();
-
-                match (a, b) {
-                    (Some(_), Some(_)) => {}
-                    (Some(_), None) => {
-                        return Ordering::Less;
-                    }
-                    (None, Some(_)) => {
-                        return Ordering::Greater;
-                    }
-                    (None, None) => {
-                        return Ordering::Equal;
-                    }
-                }
-                let ma = &envelopes[&a.unwrap()];
-                let mb = &envelopes[&b.unwrap()];
-                {
-                    mb.subject()
-                        .as_ref()
-                        .split_graphemes()
-                        .cmp(&ma.subject().split_graphemes())
+        vec.sort_by(|a, b| self.group_cmp(*a, *b, sort, &envelopes));
+    }
+
+    /// Return root thread groups sorted by `sort`.
+    ///
+    /// The result is cached; if only a few thread groups have changed since
+    /// the last call with the same `sort`, they are moved to their new
+    /// positions instead of sorting all groups again.
+    pub fn sorted_roots(
+        &self,
+        sort: &SortChain,
+        envelopes: &Envelopes,
+    ) -> SmallVec<[ThreadHash; 1024]> {
+        let envelopes = envelopes.read().unwrap();
+        let mut cache = self.root_cache.write().unwrap();
+        let RootCache {
+            sort: ref mut cached_sort,
+            ref mut roots,
+            ref mut dirty,
+        } = *cache;
+        if cached_sort.as_ref() != Some(sort) || dirty.len() > roots.len() / 4 {
+            *roots = self.roots().into_vec();
+            roots.sort_by(|a, b| self.group_cmp(*a, *b, sort, &envelopes));
+            *cached_sort = Some(sort.clone());
+            dirty.clear();
+        } else if !dirty.is_empty() {
+            let is_root = |h: &ThreadHash| matches!(self.groups.get(h), Some(ThreadGroup::Root(_)));
+            roots.retain(|h| !dirty.contains(h) && is_root(h));
+            for h in dirty.drain() {
+                if !is_root(&h) {
+                    continue;
                 }
+                let pos = roots
+                    .binary_search_by(|probe| self.group_cmp(*probe, h, sort, &envelopes))
+                    .unwrap_or_else(|pos| pos);
+                roots.insert(pos, h);
             }
-        });
+        }
+        roots.iter().cloned().collect()
     }
+
     pub fn node_inner_sort_by(
         &self,
         vec: &mut [ThreadNodeHash],
-        sort: (SortField, SortOrder),
+        sort: &SortChain,
         envelopes: &Envelopes,
     ) {
         let envelopes = envelopes.read().unwrap();
-        vec.sort_by(|a, b| match sort {
-            (SortField::Date, SortOrder::Desc) => {
-                let a = self.thread_ref(self.thread_nodes[a].group).date();
-                let b = self.thread_ref(self.thread_nodes[b].group).date();
-                b.cmp(&a)
-            }
-            (SortField::Date, SortOrder::Asc) => {
-                let a = self.thread_ref(self.thread_nodes[a].group).date();
-                let b = self.thread_ref(self.thread_nodes[b].group).date();
-                a.cmp(&b)
-            }
-            (SortField::Subject, SortOrder::Desc) => {
-                let a = &self.thread_nodes[a].message();
-                let b = &self.thread_nodes[b].message();
-
-                match (a, b) {
-                    (Some(_), Some(_)) => {}
-                    (Some(_), None) => {
-                        return Ordering::Greater;
-                    }
-                    (None, Some(_)) => {
-                        return Ordering::Less;
-                    }
-                    (None, None) => {
-                        return Ordering::Equal;
-                    }
-                }
-                let ma = &envelopes[&a.unwrap()];
-                let mb = &envelopes[&b.unwrap()];
-                {
-                    ma.subject()
-                        .split_graphemes()
-                        .cmp(&mb.subject().split_graphemes())
-                }
-            }
-            (SortField::Subject, SortOrder::Asc) => {
-                let a = &self.thread_nodes[a].message();
-                let b = &self.thread_nodes[b].message();
-
-                match (a, b) {
-                    (Some(_), Some(_)) => {}
-                    (Some(_), None) => {
-                        return Ordering::Less;
-                    }
-                    (None, Some(_)) => {
-                        return Ordering::Greater;
-                    }
-                    (None, None) => {
-                        return Ordering::Equal;
-                    }
-                }
-                let ma = &envelopes[&a.unwrap()];
-                let mb = &envelopes[&b.unwrap()];
-                {
-                    mb.subject()
-                        .as_ref()
-                        .split_graphemes()
-                        .cmp(&ma.subject().split_graphemes())
-                }
-            }
-        });
+        vec.sort_by(|a, b| self.node_cmp(*a, *b, sort, &envelopes));
     }
-    fn inner_sort_by(&self, sort: (SortField, SortOrder), envelopes: &Envelopes) {
+
+    fn inner_sort_by(&self, sort: &SortChain, envelopes: &Envelopes) {
         let tree = &mut self.tree_index.write().unwrap();
         let envelopes = envelopes.read().unwrap();
-        tree.sort_by(|a, b| match sort {
-            (SortField::Date, SortOrder::Desc) => {
-                let a = self.thread_ref(self.thread_nodes[a].group).date();
-                let b = self.thread_ref(self.thread_nodes[b].group).date();
-                b.cmp(&a)
-            }
-            (SortField::Date, SortOrder::Asc) => {
-                let a = self.thread_ref(self.thread_nodes[a].group).date();
-                let b = self.thread_ref(self.thread_nodes[b].group).date();
-                a.cmp(&b)
-            }
-            (SortField::Subject, SortOrder::Desc) => {
-                let a = &self.thread_nodes[a].message();
-                let b = &self.thread_nodes[b].message();
-
-                match (a, b) {
-                    (Some(_), Some(_)) => {}
-                    (Some(_), None) => {
-                        return Ordering::Greater;
-                    }
-                    (None, Some(_)) => {
-                        return Ordering::Less;
-                    }
-                    (None, None) => {
-                        return Ordering::Equal;
-                    }
-                }
-                let ma = &envelopes[&a.unwrap()];
-                let mb = &envelopes[&b.unwrap()];
-                {
-                    ma.subject()
-                        .split_graphemes()
-                        .cmp(&mb.subject().split_graphemes())
-                }
-            }
-            (SortField::Subject, SortOrder::Asc) => {
-                let a = &self.thread_nodes[a].message();
-                let b = &self.thread_nodes[b].message();
-
-                match (a, b) {
-                    (Some(_), Some(_)) => {}
-                    (Some(_), None) => {
-                        return Ordering::Less;
-                    }
-                    (None, Some(_)) => {
-                        return Ordering::Greater;
-                    }
-                    (None, None) => {
-                        return Ordering::Equal;
-                    }
-                }
-                let ma = &envelopes[&a.unwrap()];
-                let mb = &envelopes[&b.unwrap()];
-                {
-                    mb.subject()
-                        .as_ref()
-                        .split_graphemes()
-                        .cmp(&ma.subject().split_graphemes())
-                }
-            }
-        });
+        tree.sort_by(|a, b| self.node_cmp(*a, *b, sort, &envelopes));
     }
 
     pub fn sort_by(
         &self,
-        sort: (SortField, SortOrder),
+        sort: &SortChain,
         subsort: (SortField, SortOrder),
         envelopes: &Envelopes,
     ) {
-        if *self.sort.read().unwrap() != sort {
+        if *self.sort.read().unwrap() != *sort {
             self.inner_sort_by(sort, envelopes);
-            *self.sort.write().unwrap() = sort;
+            *self.sort.write().unwrap() = sort.clone();
         }
         if *self.subsort.read().unwrap() != subsort {
             self.inner_subsort_by(subsort, envelopes);
@@ -0,0 +1,181 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+use crate::{
+    backends::MailboxHash,
+    thread::{ThreadHash, Threads},
+    Collection, Envelope, EnvelopeHash, SortChain, SortField, SortOrder,
+};
+
+fn envelope(message_id: &str, in_reply_to: Option<&str>, minute: u8) -> Envelope {
+    let mut raw = format!(
+        "From: <user@example.com>\nSubject: message {message_id}\nMessage-ID: \
+         <{message_id}@example.com>\nDate: Mon, 01 Jan 2024 00:{minute:02}:00 +0000\n"
+    );
+    if let Some(irt) = in_reply_to {
+        raw.push_str(&format!(
+            "In-Reply-To: <{irt}@example.com>\nReferences: <{irt}@example.com>\n"
+        ));
+    }
+    raw.push_str("\nbody\n");
+    Envelope::from_bytes(raw.as_bytes(), None).unwrap()
+}
+
+fn group_of(collection: &Collection, threads: &Threads, env_hash: EnvelopeHash) -> ThreadHash {
+    let node = collection.get_env(env_hash).thread();
+    threads.find_group(threads.thread_nodes()[&node].group)
+}
+
+#[test]
+fn test_thread_incremental_sorted_roots() {
+    let mailbox_hash = MailboxHash(0);
+    let collection = Collection::default();
+    let mut hashes = vec![];
+    for i in 0..8 {
+        let env = envelope(&format!("root{i}"), None, i * 2);
+        hashes.push(env.hash());
+        collection.insert(env, mailbox_hash);
+    }
+    let sort = SortChain::from((SortField::Date, SortOrder::Desc));
+    let full_sort = |threads: &Threads| {
+        let mut roots = threads.roots();
+        threads.group_inner_sort_by(&mut roots, &sort, &collection.envelopes);
+        roots
+    };
+
+    {
+        let threads = collection.get_threads(mailbox_hash);
+        let roots = threads.sorted_roots(&sort, &collection.envelopes);
+        assert_eq!(roots.len(), 8);
+        assert_eq!(roots, full_sort(&threads));
+        assert_eq!(roots[0], group_of(&collection, &threads, hashes[7]));
+    }
+
+    // A reply to the oldest thread moves it to the top.
+    let reply = envelope("reply0", Some("root0"), 30);
+    let reply_hash = reply.hash();
+    collection.insert(reply, mailbox_hash);
+    {
+        let threads = collection.get_threads(mailbox_hash);
+        let roots = threads.sorted_roots(&sort, &collection.envelopes);
+        assert_eq!(roots.len(), 8);
+        assert_eq!(roots, full_sort(&threads));
+        let group = group_of(&collection, &threads, hashes[0]);
+        assert_eq!(roots[0], group);
+        assert_eq!(group, group_of(&collection, &threads, reply_hash));
+        assert_eq!(threads.thread_ref(group).len(), 2);
+    }
+
+    // Removing the reply only updates its thread group.
+    collection.remove(reply_hash, mailbox_hash);
+    {
+        let threads = collection.get_threads(mailbox_hash);
+        let group = group_of(&collection, &threads, hashes[0]);
+        assert_eq!(threads.thread_ref(group).len(), 1);
+        assert!(!threads.envelope_to_thread.contains_key(&reply_hash));
+        assert_eq!(
+            threads.sorted_roots(&sort, &collection.envelopes),
+            full_sort(&threads)
+        );
+    }
+
+    // Changing the sort order re-sorts everything.
+    let threads = collection.get_threads(mailbox_hash);
+    let asc = threads.sorted_roots(
+        &(SortField::Date, SortOrder::Asc).into(),
+        &collection.envelopes,
+    );
+    let mut desc = threads.sorted_roots(&sort, &collection.envelopes);
+    desc.reverse();
+    assert_eq!(asc, desc);
+}
+
+#[test]
+fn test_thread_sort_fields() {
+    let mailbox_hash = MailboxHash(0);
+    let collection = Collection::default();
+    let mut hashes = vec![];
+    for i in 0..3 {
+        let mut env = envelope(&format!("root{i}"), None, i);
+        env.set_size(100 * (3 - i as usize));
+        hashes.push(env.hash());
+        collection.insert(env, mailbox_hash);
+    }
+    for i in 0..2 {
+        collection.insert(
+            envelope(&format!("reply{i}"), Some("root1"), 10 + i),
+            mailbox_hash,
+        );
+    }
+
+    let threads = collection.get_threads(mailbox_hash);
+    let root = |i: usize| group_of(&collection, &threads, hashes[i]);
+
+    let by_size = threads.sorted_roots(
+        &(SortField::Size, SortOrder::Desc).into(),
+        &collection.envelopes,
+    );
+    assert_eq!(by_size.as_slice(), &[root(0), root(1), root(2)]);
+    let by_size = threads.sorted_roots(
+        &(SortField::Size, SortOrder::Asc).into(),
+        &collection.envelopes,
+    );
+    assert_eq!(by_size.as_slice(), &[root(2), root(1), root(0)]);
+
+    let by_len = threads.sorted_roots(
+        &(SortField::ThreadLength, SortOrder::Desc).into(),
+        &collection.envelopes,
+    );
+    assert_eq!(by_len[0], root(1));
+    assert_eq!(threads.thread_ref(by_len[0]).len(), 3);
+}
+
+#[test]
+fn test_thread_sort_chain() {
+    let mailbox_hash = MailboxHash(0);
+    let collection = Collection::default();
+    let mut hashes = vec![];
+    for i in 0..4 {
+        let mut env = envelope(&format!("root{i}"), None, i);
+        if i % 2 == 0 {
+            env.set_seen();
+        }
+        hashes.push(env.hash());
+        collection.insert(env, mailbox_hash);
+    }
+
+    let threads = collection.get_threads(mailbox_hash);
+    let root = |i: usize| group_of(&collection, &threads, hashes[i]);
+    let chain: SortChain = "unseen desc, date desc".parse().unwrap();
+    assert_eq!(
+        chain.keys(),
+        &[
+            (SortField::Unseen, SortOrder::Desc),
+            (SortField::Date, SortOrder::Desc)
+        ]
+    );
+    assert_eq!(chain.to_string(), "unseen desc, date desc");
+    let roots = threads.sorted_roots(&chain, &collection.envelopes);
+    assert_eq!(roots.as_slice(), &[root(3), root(1), root(2), root(0)]);
+    let roots = threads.sorted_roots(&chain.reversed(), &collection.envelopes);
+    assert_eq!(roots.as_slice(), &[root(0), root(2), root(1), root(3)]);
+}
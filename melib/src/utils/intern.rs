@@ -0,0 +1,164 @@
+//
+// meli
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of meli.
+//
+// meli is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// meli is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with meli. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+//! Process-wide string interning.
+//!
+//! Envelope subjects, which repeat across the messages of a thread and of
+//! mailing lists, are stored once and shared as [`Arc<str>`] values. The
+//! same goes for the raw bytes of mailbox addresses, such as senders and
+//! mailing list addresses, which are shared as [`Arc<[u8]>`] values.
+//!
+//! The interner is a pair of sets behind a global lock, shared by all
+//! accounts and by the threads that parse envelopes in parallel. Interning
+//! is one hash lookup, which is small next to parsing the envelope it is
+//! done for. Values no envelope refers to anymore are dropped when the sets
+//! have doubled in size since the last collection, see [`collect_garbage`].
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use serde::{Deserialize, Deserializer};
+
+/// Size of the sets below which unused values are not collected.
+const MIN_COLLECTION_SIZE: usize = 1024;
+
+#[derive(Default)]
+struct Interner {
+    strings: HashSet<Arc<str>>,
+    bytes: HashSet<Arc<[u8]>>,
+    /// Size of the sets at which to collect unused values next.
+    collect_at: usize,
+}
+
+impl Interner {
+    fn len(&self) -> usize {
+        self.strings.len() + self.bytes.len()
+    }
+
+    fn collect_garbage(&mut self) -> usize {
+        let prev_len = self.len();
+        self.strings.retain(|s| Arc::strong_count(s) > 1);
+        self.bytes.retain(|b| Arc::strong_count(b) > 1);
+        self.collect_at = (2 * self.len()).max(MIN_COLLECTION_SIZE);
+        prev_len - self.len()
+    }
+
+    fn maybe_collect_garbage(&mut self) {
+        if self.len() >= self.collect_at {
+            self.collect_garbage();
+        }
+    }
+}
+
+fn interner() -> &'static Mutex<Interner> {
+    static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+/// Return a shared copy of `s`, allocating it only if it hasn't been
+/// interned before.
+pub fn intern(s: &str) -> Arc<str> {
+    let mut interner = interner().lock().unwrap();
+    if let Some(v) = interner.strings.get(s) {
+        return v.clone();
+    }
+    interner.maybe_collect_garbage();
+    let v: Arc<str> = Arc::from(s);
+    interner.strings.insert(v.clone());
+    v
+}
+
+/// Return a shared copy of `b`, allocating it only if it hasn't been
+/// interned before.
+pub fn intern_bytes(b: &[u8]) -> Arc<[u8]> {
+    let mut interner = interner().lock().unwrap();
+    if let Some(v) = interner.bytes.get(b) {
+        return v.clone();
+    }
+    interner.maybe_collect_garbage();
+    let v: Arc<[u8]> = Arc::from(b);
+    interner.bytes.insert(v.clone());
+    v
+}
+
+/// Statistics of the interner's current state.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct InternStats {
+    /// Number of distinct interned values.
+    pub entries: usize,
+    /// Total bytes of distinct interned values.
+    pub bytes: usize,
+    /// Number of references to interned values held outside of the
+    /// interner.
+    pub references: usize,
+}
+
+pub fn stats() -> InternStats {
+    let interner = interner().lock().unwrap();
+    let acc = interner
+        .strings
+        .iter()
+        .fold(InternStats::default(), |mut acc, s| {
+            acc.entries += 1;
+            acc.bytes += s.len();
+            acc.references += Arc::strong_count(s) - 1;
+            acc
+        });
+    interner.bytes.iter().fold(acc, |mut acc, b| {
+        acc.entries += 1;
+        acc.bytes += b.len();
+        acc.references += Arc::strong_count(b) - 1;
+        acc
+    })
+}
+
+/// Drop interned values that are no longer referenced anywhere else.
+///
+/// This is also done by [`intern`] and [`intern_bytes`] as the sets grow,
+/// and by account maintenance.
+///
+/// Returns the number of values removed.
+pub fn collect_garbage() -> usize {
+    interner().lock().unwrap().collect_garbage()
+}
+
+/// Deserialize an optional string and intern it, for use with
+/// `#[serde(deserialize_with)]`.
+pub fn deserialize_opt_str<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<Arc<str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<String>::deserialize(deserializer)?.map(|s| intern(&s)))
+}
+
+/// Deserialize bytes and intern them, for use with
+/// `#[serde(deserialize_with)]`.
+pub fn deserialize_bytes<'de, D>(deserializer: D) -> std::result::Result<Arc<[u8]>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(intern_bytes(&Vec::<u8>::deserialize(deserializer)?))
+}
@@ -25,11 +25,13 @@ pub mod connections;
 pub mod datetime;
 pub mod fnmatch;
 pub mod futures;
+pub mod intern;
 pub mod random;
 pub mod vobject;
 #[macro_use]
 pub mod logging;
 pub mod lock;
+pub mod md5;
 pub mod parsec;
 pub mod patch_retrieve;
 pub mod percent_encoding;
@@ -222,6 +224,30 @@ pub enum SortField {
     #[default]
     #[serde(alias = "date")]
     Date,
+    /// Size of the message in bytes.
+    #[serde(alias = "size")]
+    Size,
+    /// Whether the message has attachments.
+    #[serde(alias = "attachments", alias = "has-attachments")]
+    Attachments,
+    /// Address of the sender.
+    #[serde(alias = "from", alias = "sender")]
+    From,
+    /// Domain of the sender's address.
+    #[serde(alias = "domain", alias = "sender-domain")]
+    Domain,
+    /// Number of recipients (`To`, `Cc` and `Bcc`).
+    #[serde(alias = "recipients")]
+    Recipients,
+    /// Number of messages in the thread.
+    #[serde(alias = "thread-length", alias = "thread_length")]
+    ThreadLength,
+    /// Whether the message (or any message in the thread) is unseen.
+    #[serde(alias = "unseen")]
+    Unseen,
+    /// Score assigned by scoring rules.
+    #[serde(alias = "score")]
+    Score,
 }
 
 impl FromStr for SortField {
@@ -230,6 +256,14 @@ impl FromStr for SortField {
         match s.trim().to_ascii_lowercase().as_str() {
             "subject" | "s" | "sub" | "sbj" | "subj" => Ok(Self::Subject),
             "date" | "d" => Ok(Self::Date),
+            "size" => Ok(Self::Size),
+            "attachments" | "has-attachments" | "att" => Ok(Self::Attachments),
+            "from" | "sender" => Ok(Self::From),
+            "domain" | "sender-domain" => Ok(Self::Domain),
+            "recipients" | "rcpt" => Ok(Self::Recipients),
+            "thread-length" | "thread_length" | "length" => Ok(Self::ThreadLength),
+            "unseen" | "unread" => Ok(Self::Unseen),
+            "score" => Ok(Self::Score),
             _ => Err(()),
         }
     }
@@ -257,6 +291,180 @@ impl std::ops::Not for SortOrder {
     }
 }
 
+impl SortField {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Subject => "subject",
+            Self::Date => "date",
+            Self::Size => "size",
+            Self::Attachments => "attachments",
+            Self::From => "from",
+            Self::Domain => "domain",
+            Self::Recipients => "recipients",
+            Self::ThreadLength => "thread-length",
+            Self::Unseen => "unseen",
+            Self::Score => "score",
+        }
+    }
+}
+
+impl SortOrder {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Asc => "asc",
+            Self::Desc => "desc",
+        }
+    }
+
+    /// Apply this order to `ord`, the ascending comparison of two items by
+    /// `field`.
+    ///
+    /// Subjects are listed alphabetically when sorting in descending order.
+    pub fn apply(self, field: SortField, ord: std::cmp::Ordering) -> std::cmp::Ordering {
+        match (field, self) {
+            (SortField::Subject, Self::Desc) => ord,
+            (SortField::Subject, Self::Asc) => ord.reverse(),
+            (_, Self::Asc) => ord,
+            (_, Self::Desc) => ord.reverse(),
+        }
+    }
+}
+
+/// A non-empty list of sort keys, e.g. unseen messages first and then by date.
+///
+/// Items are compared by each key in turn, and by the next key only if they
+/// are equal according to the previous ones.
+///
+/// In configuration files it can be given as a single `[field, order]` pair, a
+/// list of pairs or a string such as `"unseen desc, date desc"`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "SortChainRepr", into = "Vec<(SortField, SortOrder)>")]
+pub struct SortChain(smallvec::SmallVec<[(SortField, SortOrder); 2]>);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SortChainRepr {
+    Key((SortField, SortOrder)),
+    Keys(Vec<(SortField, SortOrder)>),
+    String(String),
+}
+
+impl TryFrom<SortChainRepr> for SortChain {
+    type Error = String;
+
+    fn try_from(val: SortChainRepr) -> std::result::Result<Self, Self::Error> {
+        match val {
+            SortChainRepr::Key(key) => Ok(key.into()),
+            SortChainRepr::Keys(keys) if keys.is_empty() => {
+                Err("sort chain must have at least one sort key".to_string())
+            }
+            SortChainRepr::Keys(keys) => Ok(Self(keys.into_iter().collect())),
+            SortChainRepr::String(s) => {
+                Self::from_str(&s).map_err(|()| format!("`{s}` is not a valid sort chain"))
+            }
+        }
+    }
+}
+
+impl From<SortChain> for Vec<(SortField, SortOrder)> {
+    fn from(val: SortChain) -> Self {
+        val.0.into_vec()
+    }
+}
+
+impl From<(SortField, SortOrder)> for SortChain {
+    fn from(key: (SortField, SortOrder)) -> Self {
+        Self(smallvec::smallvec![key])
+    }
+}
+
+impl Default for SortChain {
+    fn default() -> Self {
+        (SortField::default(), SortOrder::default()).into()
+    }
+}
+
+impl SortChain {
+    pub fn keys(&self) -> &[(SortField, SortOrder)] {
+        &self.0
+    }
+
+    /// The primary sort key.
+    pub fn first(&self) -> (SortField, SortOrder) {
+        self.0[0]
+    }
+
+    /// Return a chain with the same primary key, followed by `key`.
+    pub fn with_secondary(&self, key: (SortField, SortOrder)) -> Self {
+        let mut ret = Self::from(self.first());
+        if ret.0[0].0 != key.0 {
+            ret.0.push(key);
+        }
+        ret
+    }
+
+    /// Return a chain with the order of every key reversed.
+    pub fn reversed(&self) -> Self {
+        Self(
+            self.0
+                .iter()
+                .map(|&(field, order)| (field, !order))
+                .collect(),
+        )
+    }
+
+    /// Compare two items with `cmp`, which returns the ascending comparison of
+    /// the items by the given field.
+    pub fn compare<F>(&self, mut cmp: F) -> std::cmp::Ordering
+    where
+        F: FnMut(SortField) -> std::cmp::Ordering,
+    {
+        for &(field, order) in self.0.iter() {
+            let ord = order.apply(field, cmp(field));
+            if ord.is_ne() {
+                return ord;
+            }
+        }
+        std::cmp::Ordering::Equal
+    }
+}
+
+impl std::fmt::Display for SortChain {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, (field, order)) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(fmt, ", ")?;
+            }
+            write!(fmt, "{} {}", field.as_str(), order.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SortChain {
+    type Err = ();
+
+    /// Parse a comma separated list of `field [order]` keys. The order
+    /// defaults to descending.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut ret = smallvec::SmallVec::new();
+        for key in s.split(',') {
+            let mut tokens = key.split_whitespace();
+            let field = SortField::from_str(tokens.next().ok_or(())?)?;
+            let order = tokens
+                .next()
+                .map(SortOrder::from_str)
+                .transpose()?
+                .unwrap_or_default();
+            if tokens.next().is_some() {
+                return Err(());
+            }
+            ret.push((field, order));
+        }
+        Ok(Self(ret))
+    }
+}
+
 pub mod hostname {
     //! Get local hostname.
     use std::io::Read;
@@ -89,6 +89,7 @@ mod nttp {
                 subscribed_mailboxes: vec![groupname.clone()],
                 mailboxes: vec![(groupname, Default::default())].into_iter().collect(),
                 manual_refresh: true,
+                retained_headers: vec![],
                 extra,
             };
 
@@ -53,6 +53,12 @@ pub struct DatabaseDescription {
     /// The current value of the `user_version` `PRAGMA` of the `sqlite3`
     /// database, used for schema versioning.
     pub version: u32,
+    /// Scripts that migrate the schema from an older version, as
+    /// `(from_version, script)` pairs. Each script brings the schema from
+    /// `from_version` to `from_version + 1`, and
+    /// [`DatabaseDescription::init_script`] runs after them. Databases of
+    /// versions that can't be migrated are recreated.
+    pub migrations: &'static [(u32, &'static str)],
 }
 
 impl DatabaseDescription {
@@ -131,8 +137,14 @@ impl DatabaseDescription {
     }
 
     /// Returns an [`rusqlite::Connection`] for this description.
+    ///
+    /// If the database was marked with
+    /// [`DatabaseDescription::mark_for_reset`], it is reset first.
     pub fn open_or_create_db(&self) -> Result<Connection> {
         let mut second_try: bool = false;
+        if self.is_marked_for_reset()? {
+            self.reset_db()?;
+        }
         let db_path = self.db_path()?;
         let set_mode = !db_path.exists();
         if set_mode {
@@ -164,22 +176,50 @@ impl DatabaseDescription {
                 let version: i32 =
                     conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
                 if version != 0_i32 && version as u32 != self.version {
-                    log::info!(
-                        "Database version mismatch, is {} but expected {}. Attempting to recreate \
-                         database.",
-                        version,
-                        self.version
-                    );
-                    if second_try {
-                        return Err(Error::new(format!(
-                            "Database version mismatch, is {} but expected {}. Could not recreate \
-                             database.",
-                            version, self.version
-                        )));
+                    let migrated = self
+                        .migration_scripts(version as u32)
+                        .is_some_and(|scripts| match self.migrate_schema(&conn, &scripts) {
+                            Ok(()) => {
+                                log::info!(
+                                    "Migrated {} database in {} from version {} to {}.",
+                                    self.name,
+                                    db_path.display(),
+                                    version,
+                                    self.version
+                                );
+                                true
+                            }
+                            Err(err) => {
+                                log::warn!(
+                                    "Could not migrate {} database in {} from version {} to {}: \
+                                     {}",
+                                    self.name,
+                                    db_path.display(),
+                                    version,
+                                    self.version,
+                                    err
+                                );
+                                false
+                            }
+                        });
+                    if !migrated {
+                        log::info!(
+                            "Database version mismatch, is {} but expected {}. Attempting to \
+                             recreate database.",
+                            version,
+                            self.version
+                        );
+                        if second_try {
+                            return Err(Error::new(format!(
+                                "Database version mismatch, is {} but expected {}. Could not \
+                                 recreate database.",
+                                version, self.version
+                            )));
+                        }
+                        self.reset_db()?;
+                        second_try = true;
+                        return Ok(None);
                     }
-                    self.reset_db()?;
-                    second_try = true;
-                    return Ok(None);
                 }
 
                 if version == 0 {
@@ -192,7 +232,6 @@ impl DatabaseDescription {
 
                 Ok(Some(conn))
             };
-            inner_fn().unwrap();
             match inner_fn() {
                 Ok(None) => continue,
                 Ok(Some(conn)) => return Ok(conn),
@@ -207,25 +246,167 @@ impl DatabaseDescription {
         }
     }
 
-    /// Reset database to a clean slate.
-    pub fn reset_db(&self) -> Result<()> {
+    /// Returns the `user_version` of the database file, or `None` if it
+    /// doesn't exist. A version of `0` means the schema was never initialized.
+    pub fn stored_version(&self) -> Result<Option<u32>> {
         let db_path = self.db_path()?;
         if !db_path.exists() {
-            return Ok(());
+            return Ok(None);
+        }
+        let conn = Connection::open(&db_path)?;
+        conn.busy_timeout(std::time::Duration::new(10, 0))?;
+        let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        Ok(Some(version as u32))
+    }
+
+    /// Returns the scripts of [`DatabaseDescription::migrations`] that bring
+    /// the schema from `version` to [`DatabaseDescription::version`], or
+    /// `None` if there is no such sequence of migrations.
+    pub fn migration_scripts(&self, version: u32) -> Option<Vec<&'static str>> {
+        if version == 0 || version >= self.version {
+            return None;
         }
-        log::info!("Resetting {} database in {}", self.name, db_path.display());
-        std::fs::remove_file(&db_path).map_err(|err| {
-            Error::new(format!("{}: could not remove file", db_path.display()))
+        (version..self.version)
+            .map(|from| {
+                self.migrations
+                    .iter()
+                    .find(|(v, _)| *v == from)
+                    .map(|(_, script)| *script)
+            })
+            .collect()
+    }
+
+    fn migrate_schema(&self, conn: &Connection, scripts: &[&str]) -> Result<()> {
+        let tx = conn.unchecked_transaction()?;
+        for script in scripts {
+            tx.execute_batch(script)?;
+        }
+        tx.pragma_update(None, "user_version", self.version)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns the size of the database file in bytes, or `0` if it doesn't
+    /// exist.
+    pub fn size(&self) -> Result<u64> {
+        let db_path = self.db_path()?;
+        Ok(std::fs::metadata(db_path).map(|m| m.len()).unwrap_or(0))
+    }
+
+    /// Checks the database for corruption with the `quick_check` `PRAGMA`.
+    ///
+    /// Returns the problems found, which are empty if the database is intact
+    /// or doesn't exist.
+    pub fn check_integrity(&self) -> Result<Vec<String>> {
+        let db_path = self.db_path()?;
+        if !db_path.exists() {
+            return Ok(vec![]);
+        }
+        let conn = Connection::open(&db_path)?;
+        conn.busy_timeout(std::time::Duration::new(10, 0))?;
+        let check = || -> rusqlite::Result<Vec<String>> {
+            let mut stmt = conn.prepare("PRAGMA quick_check;")?;
+            let rows = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()?;
+            Ok(rows.into_iter().filter(|r| r != "ok").collect())
+        };
+        match check() {
+            Ok(problems) => Ok(problems),
+            Err(err)
+                if matches!(
+                    err.sqlite_error_code(),
+                    Some(rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase)
+                ) =>
+            {
+                Ok(vec![err.to_string()])
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Rebuilds the database file to reclaim unused space, returning its size
+    /// in bytes before and after.
+    pub fn vacuum(&self) -> Result<(u64, u64)> {
+        let db_path = self.db_path()?;
+        if !db_path.exists() {
+            return Ok((0, 0));
+        }
+        let before = self.size()?;
+        let conn = Connection::open(&db_path)?;
+        conn.busy_timeout(std::time::Duration::new(10, 0))?;
+        conn.execute_batch("VACUUM;")?;
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE);", [], |_| Ok(()))?;
+        let after = self.size()?;
+        log::info!(
+            "Vacuumed {} database in {}: {} bytes to {} bytes",
+            self.name,
+            db_path.display(),
+            before,
+            after
+        );
+        Ok((before, after))
+    }
+
+    fn reset_marker_path(&self) -> Result<PathBuf> {
+        let mut path = self.db_path()?.into_os_string();
+        path.push(".reset");
+        Ok(path.into())
+    }
+
+    /// Marks the database to be reset the next time it is opened with
+    /// [`DatabaseDescription::open_or_create_db`].
+    ///
+    /// Unlike [`DatabaseDescription::reset_db`], this is safe while the
+    /// database is open elsewhere: removing the file would leave those
+    /// connections working on an unlinked copy.
+    pub fn mark_for_reset(&self) -> Result<()> {
+        let marker_path = self.reset_marker_path()?;
+        std::fs::File::create(&marker_path).map_err(|err| {
+            Error::new(format!("{}: could not create file", marker_path.display()))
                 .set_kind(ErrorKind::from(err.kind()))
                 .set_source(Some(Arc::new(err)))
         })?;
         log::info!(
-            "{} {} database reset successful",
+            "Marked {} database in {} to be reset",
             self.name,
-            db_path.display()
+            self.db_path()?.display()
         );
         Ok(())
     }
+
+    /// Returns whether [`DatabaseDescription::mark_for_reset`] was called
+    /// and the database hasn't been reset since.
+    pub fn is_marked_for_reset(&self) -> Result<bool> {
+        Ok(self.reset_marker_path()?.exists())
+    }
+
+    /// Reset database to a clean slate.
+    pub fn reset_db(&self) -> Result<()> {
+        let db_path = self.db_path()?;
+        if db_path.exists() {
+            log::info!("Resetting {} database in {}", self.name, db_path.display());
+            std::fs::remove_file(&db_path).map_err(|err| {
+                Error::new(format!("{}: could not remove file", db_path.display()))
+                    .set_kind(ErrorKind::from(err.kind()))
+                    .set_source(Some(Arc::new(err)))
+            })?;
+            log::info!(
+                "{} {} database reset successful",
+                self.name,
+                db_path.display()
+            );
+        }
+        let marker_path = self.reset_marker_path()?;
+        if marker_path.exists() {
+            std::fs::remove_file(&marker_path).map_err(|err| {
+                Error::new(format!("{}: could not remove file", marker_path.display()))
+                    .set_kind(ErrorKind::from(err.kind()))
+                    .set_source(Some(Arc::new(err)))
+            })?;
+        }
+        Ok(())
+    }
 }
 
 impl ToSql for Envelope {
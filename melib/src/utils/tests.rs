@@ -464,3 +464,153 @@ fn test_utils_base36() {
         );
     }
 }
+
+#[test]
+fn test_intern() {
+    use std::sync::Arc;
+
+    use crate::utils::intern::{collect_garbage, intern, stats};
+
+    let a = intern("[meli-devel] test_intern subject");
+    let b = intern(&String::from("[meli-devel] test_intern subject"));
+    assert!(Arc::ptr_eq(&a, &b));
+    assert_eq!(a.as_ref(), "[meli-devel] test_intern subject");
+    let c = intern("test_intern other subject");
+    assert!(!Arc::ptr_eq(&a, &c));
+    assert!(stats().entries >= 2);
+    drop(c);
+    collect_garbage();
+    let d = intern("[meli-devel] test_intern subject");
+    assert!(Arc::ptr_eq(&a, &d));
+
+    // Unused strings are dropped as the set grows, without calling
+    // collect_garbage().
+    for i in 0..4096 {
+        drop(intern(&format!("test_intern unused subject {i}")));
+    }
+    assert!(stats().entries < 4096);
+    let e = intern("[meli-devel] test_intern subject");
+    assert!(Arc::ptr_eq(&a, &e));
+}
+
+#[test]
+fn test_intern_addresses() {
+    use std::sync::Arc;
+
+    use crate::email::{parser::address::address, Address};
+
+    let raw = |addr: &Address| match addr {
+        Address::Mailbox(m) => m.raw.clone(),
+        Address::Group(_) => unreachable!(),
+    };
+    let (_, a) = address(b"Test Intern <test_intern@example.com>").unwrap();
+    let (_, b) = address(b"Test Intern <test_intern@example.com>").unwrap();
+    let c = Address::new(
+        Some("Test Intern".to_string()),
+        "test_intern@example.com".to_string(),
+    );
+    assert!(Arc::ptr_eq(&raw(&a), &raw(&b)));
+    assert!(Arc::ptr_eq(&raw(&a), &raw(&c)));
+    assert_eq!(a.get_display_name().as_deref(), Some("Test Intern"));
+    assert_eq!(a.get_email(), "test_intern@example.com");
+    let (_, d) = address(b"Other <test_intern@example.com>").unwrap();
+    assert!(!Arc::ptr_eq(&raw(&a), &raw(&d)));
+    assert_eq!(a, d);
+}
+
+#[test]
+fn test_utils_sort_chain_deserialize() {
+    use crate::{SortChain, SortField, SortOrder};
+
+    #[derive(serde::Deserialize)]
+    struct Conf {
+        sort: SortChain,
+    }
+
+    for (input, keys) in [
+        (
+            r#"sort = ["date", "asc"]"#,
+            &[(SortField::Date, SortOrder::Asc)][..],
+        ),
+        (
+            r#"sort = [["unseen", "desc"], ["size", "asc"]]"#,
+            &[
+                (SortField::Unseen, SortOrder::Desc),
+                (SortField::Size, SortOrder::Asc),
+            ][..],
+        ),
+        (
+            r#"sort = "unseen, subject asc""#,
+            &[
+                (SortField::Unseen, SortOrder::Desc),
+                (SortField::Subject, SortOrder::Asc),
+            ][..],
+        ),
+    ] {
+        let conf: Conf = toml::from_str(input).unwrap();
+        assert_eq!(conf.sort.keys(), keys, "{input}");
+    }
+    assert!(toml::from_str::<Conf>("sort = []").is_err());
+    assert!(toml::from_str::<Conf>(r#"sort = "unseen desc desc""#).is_err());
+}
+
+#[test]
+fn test_utils_md5() {
+    use crate::utils::md5::{hmac_md5, md5};
+
+    let hex = |digest: [u8; 16]| {
+        digest
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>()
+    };
+
+    // RFC 1321, section A.5
+    for (input, digest) in [
+        ("", "d41d8cd98f00b204e9800998ecf8427e"),
+        ("a", "0cc175b9c0f1b6a831c399e269772661"),
+        ("abc", "900150983cd24fb0d6963f7d28e17f72"),
+        ("message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+        (
+            "abcdefghijklmnopqrstuvwxyz",
+            "c3fcd3d76192e4007dfb496cca67e13b",
+        ),
+        (
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+            "d174ab98d277d9f5a5611c2c9f419d9f",
+        ),
+        (
+            "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+            "57edf4a22be3c955ac49da2e2107b67a",
+        ),
+    ] {
+        assert_eq!(hex(md5(input.as_bytes())), digest, "{input:?}");
+    }
+
+    // RFC 2104, section 2, and RFC 2202, section 2, for a key longer than the
+    // block size.
+    for (key, data, digest) in [
+        (
+            vec![0x0b; 16],
+            b"Hi There".to_vec(),
+            "9294727a3638bb1c13f48ef8158bfc9d",
+        ),
+        (
+            b"Jefe".to_vec(),
+            b"what do ya want for nothing?".to_vec(),
+            "750c783e6ab0b503eaa86e310a5db738",
+        ),
+        (
+            vec![0xaa; 16],
+            vec![0xdd; 50],
+            "56be34521d144c88dbb8c733f0e8b3f6",
+        ),
+        (
+            vec![0xaa; 80],
+            b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+            "6b1ab7fe4bd7bf8f0b62e6ce61b9d0cd",
+        ),
+    ] {
+        assert_eq!(hex(hmac_md5(&key, &data)), digest, "{key:?}");
+    }
+}
@@ -761,6 +761,7 @@ mod tests {
             subscribed_mailboxes: vec![],
             mailboxes: indexmap::indexmap! {},
             manual_refresh: false,
+            retained_headers: vec![],
             extra: indexmap::indexmap! {
                 "server_hostname".to_string() => local_addr.ip().to_string(),
                 "server_username".to_string() => "user".to_string(),
@@ -0,0 +1,211 @@
+//
+// melib
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of melib.
+//
+// melib is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// melib is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with melib. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+use melib::email::{parser::address::rfc2822address_list, Address};
+
+/// A `xorshift64` generator, so that failures are reproducible without
+/// depending on a random number crate.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn word(&mut self, alphabet: &[u8]) -> String {
+        (0..1 + self.below(8))
+            .map(|_| alphabet[self.below(alphabet.len())] as char)
+            .collect()
+    }
+
+    fn name(&mut self) -> String {
+        const LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+        (0..1 + self.below(3))
+            .map(|_| self.word(LETTERS))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn local_part(&mut self) -> String {
+        const ATEXT: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+        (0..1 + self.below(2))
+            .map(|_| self.word(ATEXT))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    fn domain(&mut self) -> String {
+        const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+        format!(
+            "{}.{}",
+            self.word(LETTERS),
+            ["example", "test", "example.com"][self.below(3)]
+        )
+    }
+
+    fn route(&mut self) -> String {
+        let hops = (0..1 + self.below(3))
+            .map(|_| format!("@{}", self.domain()))
+            .collect::<Vec<_>>();
+        format!("{}:", hops.join(","))
+    }
+
+    /// A mailbox in one of the forms of RFC 5322, including its obsolete
+    /// syntax, and the display name and address it should be parsed as.
+    fn mailbox(&mut self) -> (String, (String, String)) {
+        let local_part = self.local_part();
+        let domain = self.domain();
+        let addr = format!("{local_part}@{domain}");
+        match self.below(8) {
+            0 => (addr.clone(), (String::new(), addr)),
+            1 => {
+                let comment = self.name();
+                (format!("{addr} ({comment})"), (String::new(), addr))
+            }
+            2 => {
+                let name = self.name();
+                (format!("{name} <{addr}>"), (name, addr))
+            }
+            3 => {
+                let name = self.name();
+                let route = self.route();
+                (format!("{name} <{route}{addr}>"), (name, addr))
+            }
+            4 => {
+                let route = self.route();
+                (format!("<{route}{addr}>"), (String::new(), addr))
+            }
+            5 => {
+                let (last, first) = (self.name(), self.name());
+                let name = format!("{last}, {first}");
+                (format!("\"{name}\" <{addr}>"), (name, addr))
+            }
+            6 => {
+                let (first, last) = (self.name(), self.name());
+                let (outer, inner) = (self.name(), self.name());
+                (
+                    format!("{first} ({outer} ({inner}) {outer}) {last} <{addr}>"),
+                    (format!("{first} {last}"), addr),
+                )
+            }
+            _ => {
+                let name = self.name();
+                let (c1, c2, c3) = (self.name(), self.name(), self.name());
+                (
+                    format!("{name}({c1}) <{local_part}({c2})@{domain}({c3})>"),
+                    (name, addr),
+                )
+            }
+        }
+    }
+
+    /// A list element: a mailbox, a bare local part or a group of mailboxes
+    /// with empty elements.
+    fn element(&mut self) -> (String, Vec<(String, String)>) {
+        match self.below(10) {
+            0 => {
+                let local_part = self.local_part();
+                (local_part.clone(), vec![(String::new(), local_part)])
+            }
+            1 => {
+                let name = self.name();
+                let (mut members, mut expected) = (vec![], vec![]);
+                for _ in 0..1 + self.below(3) {
+                    let local_part = self.local_part();
+                    let addr = format!("{local_part}@{}", self.domain());
+                    members.push(addr.clone());
+                    expected.push((String::new(), addr));
+                }
+                let separator = [",", ",,", ", "][self.below(3)];
+                (format!("{name}:{};", members.join(separator)), expected)
+            }
+            _ => {
+                let (raw, expected) = self.mailbox();
+                (raw, vec![expected])
+            }
+        }
+    }
+
+    /// An address list and the mailboxes it should be parsed as.
+    fn address_list(&mut self) -> (String, Vec<(String, String)>) {
+        let (mut raw, mut expected) = (String::new(), vec![]);
+        for i in 0..1 + self.below(4) {
+            if i > 0 {
+                raw.push_str([",", ", ", ",,", ", ,"][self.below(4)]);
+            }
+            let (element, mailboxes) = self.element();
+            raw.push_str(&element);
+            expected.extend(mailboxes);
+        }
+        (raw, expected)
+    }
+}
+
+fn mailboxes(list: &[Address]) -> Vec<(String, String)> {
+    list.iter()
+        .flat_map(Address::mailboxes)
+        .map(|a| (a.get_display_name().unwrap_or_default(), a.get_email()))
+        .collect()
+}
+
+#[test]
+fn test_address_parse_generated_obsolete_syntax() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..1000 {
+        let (raw, expected) = rng.address_list();
+        let (rest, list) =
+            rfc2822address_list(raw.as_bytes()).unwrap_or_else(|err| panic!("{raw}: {err:?}"));
+        assert!(
+            rest.is_empty(),
+            "{raw}: {:?}",
+            String::from_utf8_lossy(rest)
+        );
+        let parsed = mailboxes(&list);
+        assert_eq!(parsed, expected, "{raw}");
+
+        // The parsed mailboxes are written back in the current syntax, which
+        // parses to the same mailboxes.
+        let written = list
+            .iter()
+            .flat_map(Address::mailboxes)
+            .map(Address::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let (rest, list) = rfc2822address_list(written.as_bytes())
+            .unwrap_or_else(|err| panic!("{raw}: {written}: {err:?}"));
+        assert!(rest.is_empty(), "{raw}: {written}");
+        assert_eq!(mailboxes(&list), parsed, "{raw}: {written}");
+
+        // Truncated input, as left by broken mailers, is rejected or parsed
+        // partially, but does not panic.
+        for end in 0..raw.len() {
+            _ = rfc2822address_list(&raw.as_bytes()[..end]);
+        }
+    }
+}
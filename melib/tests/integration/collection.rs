@@ -0,0 +1,103 @@
+//
+// melib
+//
+// Copyright 2024 Emmanouil Pitsidianakis <manos@pitsidianak.is>
+//
+// This file is part of melib.
+//
+// melib is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// melib is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with melib. If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: EUPL-1.2 OR GPL-3.0-or-later
+
+use std::{collections::HashMap, thread};
+
+use melib::{Collection, Envelope, EnvelopeHash, MailboxHash};
+
+/// `count` envelopes, each replying to the previous one.
+fn envelopes(prefix: &str, count: usize) -> HashMap<EnvelopeHash, Envelope> {
+    (0..count)
+        .map(|i| {
+            let in_reply_to = if i > 0 {
+                format!("In-Reply-To: <{prefix}-{}@example.com>\r\n", i - 1)
+            } else {
+                String::new()
+            };
+            let bytes = format!(
+                "From: user@example.com\r\nMessage-ID: <{prefix}-{i}@example.com>\r\n{in_reply_to}\
+                 Subject: {prefix}\r\n\r\nbody\r\n"
+            );
+            let env = Envelope::from_bytes(bytes.as_bytes(), None).unwrap();
+            (env.hash(), env)
+        })
+        .collect()
+}
+
+#[test]
+fn test_collection_concurrent_merge() {
+    let collection = Collection::new();
+    let mailboxes = [
+        MailboxHash::from(b"INBOX".as_slice()),
+        MailboxHash::from(b"Archive".as_slice()),
+    ];
+    for mailbox_hash in mailboxes {
+        collection.new_mailbox(mailbox_hash);
+    }
+    let writers = mailboxes
+        .into_iter()
+        .enumerate()
+        .map(|(i, mailbox_hash)| {
+            let collection = collection.clone();
+            thread::spawn(move || {
+                for batch in 0..4 {
+                    collection.merge(envelopes(&format!("{i}-{batch}"), 50), mailbox_hash, None);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    // Threads can be read while they are being built.
+    for _ in 0..100 {
+        for mailbox_hash in mailboxes {
+            let threads = collection.get_threads(mailbox_hash);
+            assert!(threads.hash_set.len() <= 200);
+        }
+    }
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    for mailbox_hash in mailboxes {
+        assert_eq!(collection.get_threads(mailbox_hash).hash_set.len(), 200);
+        assert_eq!(collection.get_mailbox(mailbox_hash).len(), 200);
+    }
+    assert_eq!(collection.len(), 400);
+}
+
+#[test]
+fn test_collection_merge_batches() {
+    let collection = Collection::new();
+    let mailbox_hash = MailboxHash::from(b"INBOX".as_slice());
+    collection.new_mailbox(mailbox_hash);
+    let mut envelopes = envelopes("batch", 30).into_iter().collect::<Vec<_>>();
+    envelopes.sort_by_key(|(_, env)| env.message_id().raw().to_vec());
+    // Later batches are built on the threads replaced by earlier ones.
+    for batch in envelopes.chunks(10) {
+        collection.merge(batch.iter().cloned().collect(), mailbox_hash, None);
+    }
+    let threads = collection.get_threads(mailbox_hash);
+    assert_eq!(threads.hash_set.len(), 30);
+    assert_eq!(threads.roots().len(), 1);
+    for (env_hash, _) in &envelopes {
+        let node = collection.get_env(*env_hash).thread();
+        assert_eq!(threads.thread_nodes()[&node].message(), Some(*env_hash));
+    }
+}
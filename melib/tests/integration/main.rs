@@ -19,6 +19,8 @@
 // along with meli. If not, see <http://www.gnu.org/licenses/>.
 //
 
+mod address_parse;
+mod collection;
 mod configs;
 mod mbox_parse;
 
@@ -26,7 +26,7 @@ use std::{
 };
 
 use flate2::bufread::GzDecoder;
-use melib::{mbox::*, Envelope, Result};
+use melib::{mbox::*, Envelope, EnvelopeHash, Flag, Result};
 
 #[test]
 fn test_mbox_parse() {
@@ -106,3 +106,137 @@ fn test_mbox_parse() {
         assert_eq!(&original_post, &envelopes[0]);
     }
 }
+
+#[test]
+fn test_mbox_write_message() {
+    const MESSAGE: &[u8] = b"From: <user@example.com>\nSubject: hello\nStatus: O\nX-Keywords: \
+                             old\n\nFrom the body\n";
+
+    let mut eml = vec![];
+    MboxMetadata::CClient
+        .write_message(
+            &mut eml,
+            MESSAGE,
+            (Flag::SEEN | Flag::FLAGGED | Flag::REPLIED, vec!["work"]),
+        )
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&eml),
+        "From: <user@example.com>\nSubject: hello\nStatus: R\nX-Status: FA\nX-Keywords: \
+         work\n\nFrom the body\n"
+    );
+
+    let mut eml = vec![];
+    MboxMetadata::CClient
+        .write_message(&mut eml, MESSAGE, (Flag::empty(), vec![]))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&eml),
+        "From: <user@example.com>\nSubject: hello\n\nFrom the body\n"
+    );
+    assert!(MboxMetadata::CClient
+        .write_message(&mut vec![], MESSAGE, (Flag::empty(), vec!["two words"]))
+        .is_err());
+}
+
+#[test]
+fn test_mbox_append_quoting() {
+    const MESSAGE: &[u8] = b"From: <user@example.com>\nSubject: hello\n\nFrom me\n>From you\nhi\n";
+
+    let append = |format: MboxFormat| -> String {
+        let mut mbox = vec![];
+        format
+            .append(
+                &mut mbox,
+                MESSAGE,
+                None,
+                Some(0),
+                (Flag::empty(), vec![]),
+                MboxMetadata::None,
+                true,
+                false,
+            )
+            .unwrap();
+        let mbox = String::from_utf8(mbox).unwrap();
+        let (from_line, message) = mbox.split_once('\n').unwrap();
+        assert!(from_line.starts_with("From "));
+        message.to_string()
+    };
+
+    let mboxrd = append(MboxFormat::MboxRd);
+    assert_eq!(
+        mboxrd,
+        "From: <user@example.com>\nSubject: hello\n\n>From me\n>>From you\nhi\n"
+    );
+    assert_eq!(
+        MboxFormat::MboxRd.unescape(mboxrd.as_bytes()).as_ref(),
+        MESSAGE
+    );
+
+    let mboxo = append(MboxFormat::MboxO);
+    assert_eq!(
+        mboxo,
+        "From: <user@example.com>\nSubject: hello\n\n>From me\n>From you\nhi\n"
+    );
+    assert_eq!(
+        MboxFormat::MboxO.unescape(mboxo.as_bytes()).as_ref(),
+        b"From: <user@example.com>\nSubject: hello\n\nFrom me\nFrom you\nhi\n"
+    );
+    assert_eq!(
+        MboxFormat::MboxCl2.unescape(mboxo.as_bytes()).as_ref(),
+        mboxo.as_bytes()
+    );
+}
+
+#[test]
+fn test_mbox_compact() {
+    const FROM_LINE: &str = "From user@example.com Thu Jan  1 00:00:00 1970\n";
+    let messages = ["one", "two", "three"]
+        .map(|subject| format!("{FROM_LINE}Subject: {subject}\n\n{subject}\n"));
+    let input = messages.join("\n");
+    let hashes = [1, 2, 3].map(EnvelopeHash);
+    let index = || -> HashMap<EnvelopeHash, (Offset, Length)> {
+        let mut offset = 0;
+        hashes
+            .iter()
+            .zip(messages.iter())
+            .map(|(hash, message)| {
+                let entry = (
+                    *hash,
+                    (offset + FROM_LINE.len(), message.len() - FROM_LINE.len()),
+                );
+                offset += message.len() + 1;
+                entry
+            })
+            .collect()
+    };
+
+    // Remove a message in the middle.
+    let mut idx = index();
+    let output = mbox_compact(&mut idx, input.as_bytes(), &[hashes[1]], false);
+    let expected = format!("{}\n{}", messages[0], messages[2]);
+    assert_eq!(String::from_utf8_lossy(&output), expected);
+    assert_eq!(idx.len(), 2);
+    assert_eq!(idx[&hashes[0]], index()[&hashes[0]]);
+    let (offset, length) = idx[&hashes[2]];
+    assert_eq!(&output[offset..][..length], b"Subject: three\n\nthree\n");
+
+    // Remove the last message, the separating blank line goes with it.
+    let mut idx = index();
+    let output = mbox_compact(&mut idx, input.as_bytes(), &[hashes[2]], false);
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        format!("{}\n{}", messages[0], messages[1])
+    );
+    let (offset, length) = idx[&hashes[1]];
+    assert_eq!(&output[offset..][..length], b"Subject: two\n\ntwo\n");
+
+    // Remove the first message.
+    let mut idx = index();
+    let output = mbox_compact(&mut idx, input.as_bytes(), &[hashes[0]], false);
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        format!("{}\n{}", messages[1], messages[2])
+    );
+    assert_eq!(idx[&hashes[1]].0, FROM_LINE.len());
+}
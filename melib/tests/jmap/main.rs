@@ -784,6 +784,7 @@ mod tests {
             subscribed_mailboxes: vec![],
             mailboxes: indexmap::indexmap! {},
             manual_refresh: false,
+            retained_headers: vec![],
             extra: indexmap::indexmap! {
                 "server_url".to_string() => format!("http://{}:{}", local_addr.ip(), local_addr.port()),
                 "server_username".to_string() => "user".to_string(),
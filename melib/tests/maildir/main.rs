@@ -99,6 +99,7 @@ fn new_maildir_backend(
         subscribed_mailboxes,
         mailboxes,
         manual_refresh: true,
+        retained_headers: vec![],
         extra,
     };
 
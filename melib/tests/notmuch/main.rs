@@ -167,6 +167,7 @@ other_email=test2@example.com;test3@example.com
             subscribed_mailboxes,
             mailboxes,
             manual_refresh: true,
+            retained_headers: vec![],
             extra,
         };
 
@@ -444,3 +444,17 @@ hello world.
             .unwrap();
     }
 }
+
+#[cfg(feature = "smtp")]
+#[test]
+fn test_smtp_cram_md5_response() {
+    // Example exchange from RFC 2195 Section 2.
+    assert_eq!(
+        melib::smtp::cram_md5_response(
+            "tim",
+            b"tanstaaftanstaaf",
+            b"<1896.697170952@postoffice.reston.mci.net>"
+        ),
+        "tim b913a602c7eda7a495b4e6e7334d3890"
+    );
+}